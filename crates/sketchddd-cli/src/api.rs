@@ -0,0 +1,443 @@
+//! The headless API of `serve --headless`: SketchDDD as a service.
+//!
+//! Other tools send the source of a model with each request, so the server
+//! holds no model of its own:
+//!
+//! | Endpoint | Body | Response |
+//! |----------|------|----------|
+//! | `POST /parse` | `{ source }` | The model, in the export format |
+//! | `POST /validate` | `{ source, config? }` | The validation report, as `check --format json` |
+//! | `POST /codegen` | `{ source, target }` | The generated code of each context |
+//! | `POST /viz` | `{ source, format?, context? }` | The diagram of each context |
+//! | `POST /diff` | `{ old, new }` | The changes, as `diff --format json` |
+//! | `GET /openapi.json` | | The OpenAPI description of the API |
+//! | `GET /docs` | | The API's documentation, from its description |
+//! | `GET /health` | | `{ status, version }` |
+//!
+//! When `SKETCHDDD_API_KEY` is set, the `POST` endpoints answer 401 unless
+//! the request carries the key, as `Authorization: Bearer <key>` or in an
+//! `X-API-Key` header. Errors are JSON objects with an `error` message, as
+//! in the visual builder's API.
+//!
+//! A configuration sent to `/validate` may not name rule scripts, since
+//! the server would read and run them from its own disk. Requests are
+//! handled on the blocking pool, keeping parsing and validation of large
+//! models off the threads serving connections.
+
+use crate::config;
+use crate::report;
+use crate::serve::{parse_model, ApiError};
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{Html, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use colored::Colorize;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sketchddd_codegen::Target;
+use sketchddd_core::{diff_models, LintConfig, ModelDocument, Registry};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Environment variable holding the key requests must carry.
+pub const API_KEY_ENV: &str = "SKETCHDDD_API_KEY";
+
+/// Page showing the API's documentation from its OpenAPI description.
+const DOCS_PAGE: &str = r##"<!DOCTYPE html>
+<html>
+<head>
+  <meta charset="utf-8">
+  <title>SketchDDD API</title>
+  <link rel="stylesheet" href="https://cdn.jsdelivr.net/npm/swagger-ui-dist@5/swagger-ui.css">
+</head>
+<body>
+  <div id="docs"></div>
+  <script src="https://cdn.jsdelivr.net/npm/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+  <script>
+    SwaggerUIBundle({ url: "/openapi.json", dom_id: "#docs" });
+  </script>
+</body>
+</html>
+"##;
+
+/// The key requests must carry, if any.
+type ApiKey = Arc<Option<String>>;
+
+/// Build the application serving the API.
+fn router(key: ApiKey) -> Router {
+    let engine = Router::new()
+        .route("/parse", post(parse))
+        .route("/validate", post(validate))
+        .route("/codegen", post(codegen))
+        .route("/viz", post(viz))
+        .route("/diff", post(diff))
+        .route_layer(middleware::from_fn_with_state(key, authorize));
+
+    Router::new()
+        .route("/openapi.json", get(|| async { Json(openapi()) }))
+        .route("/docs", get(|| async { Html(DOCS_PAGE) }))
+        .route("/health", get(health))
+        .merge(engine)
+        .fallback(|| async {
+            ApiError(StatusCode::NOT_FOUND, "No such endpoint; see /docs".into())
+        })
+}
+
+/// Answer requests without the API key with 401.
+async fn authorize(
+    State(key): State<ApiKey>,
+    headers: HeaderMap,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let Some(key) = key.as_deref() else {
+        return Ok(next.run(request).await);
+    };
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    let given = bearer.or_else(|| headers.get("x-api-key").and_then(|v| v.to_str().ok()));
+    match given {
+        Some(given) if keys_match(given.as_bytes(), key.as_bytes()) => Ok(next.run(request).await),
+        Some(_) => Err(ApiError(StatusCode::UNAUTHORIZED, "Invalid API key".into())),
+        None => Err(ApiError(StatusCode::UNAUTHORIZED, "Missing API key".into())),
+    }
+}
+
+/// Compare keys without stopping at the first difference, so that how long
+/// a comparison takes does not tell how much of a key is right.
+fn keys_match(given: &[u8], key: &[u8]) -> bool {
+    given.len() == key.len() && given.iter().zip(key).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Take the JSON body of a request, answering a malformed one with 400.
+fn body<T>(body: Result<Json<T>, JsonRejection>) -> Result<T, ApiError> {
+    body.map(|Json(body)| body)
+        .map_err(|e| ApiError(StatusCode::BAD_REQUEST, e.body_text()))
+}
+
+/// Parse and transform the source of a model, answering 422 if it does not
+/// describe one.
+fn model(source: &str) -> Result<sketchddd_core::Workspace, ApiError> {
+    parse_model(source).map_err(|e| ApiError(StatusCode::UNPROCESSABLE_ENTITY, e))
+}
+
+/// Run the work of a request on the blocking pool.
+async fn blocking<T: Send + 'static>(
+    work: impl FnOnce() -> Result<T, ApiError> + Send + 'static,
+) -> Result<T, ApiError> {
+    tokio::task::spawn_blocking(work).await.map_err(|e| {
+        ApiError(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Request failed: {}", e),
+        )
+    })?
+}
+
+async fn health() -> Json<Value> {
+    Json(json!({ "status": "ok", "version": env!("CARGO_PKG_VERSION") }))
+}
+
+#[derive(Deserialize)]
+struct ParseRequest {
+    source: String,
+}
+
+async fn parse(
+    request: Result<Json<ParseRequest>, JsonRejection>,
+) -> Result<Json<Value>, ApiError> {
+    let request = body(request)?;
+    blocking(move || Ok(Json(ModelDocument::new(model(&request.source)?).to_value()))).await
+}
+
+#[derive(Deserialize)]
+struct ValidateRequest {
+    source: String,
+
+    /// A configuration file, for its lint settings
+    config: Option<String>,
+}
+
+async fn validate(
+    request: Result<Json<ValidateRequest>, JsonRejection>,
+) -> Result<Json<Value>, ApiError> {
+    let request = body(request)?;
+    blocking(move || {
+        let (registry, lint_config) = match &request.config {
+            Some(config) => config::parse_lint_config(config, None)
+                .map_err(|e| ApiError(StatusCode::UNPROCESSABLE_ENTITY, e))?,
+            None => (Registry::builtin(), LintConfig::new()),
+        };
+        let result = model(&request.source)?.lint(&registry, &lint_config);
+        Ok(Json(json!(result.to_json_report(None))))
+    })
+    .await
+}
+
+#[derive(Deserialize)]
+struct CodegenRequest {
+    source: String,
+    target: String,
+}
+
+async fn codegen(
+    request: Result<Json<CodegenRequest>, JsonRejection>,
+) -> Result<Json<BTreeMap<String, String>>, ApiError> {
+    let request = body(request)?;
+    let target: Target = request.target.parse().map_err(|_| {
+        ApiError(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("Unknown target language: {}", request.target),
+        )
+    })?;
+    blocking(move || {
+        sketchddd_codegen::generate_workspace(&model(&request.source)?, target)
+            .map(|files| Json(files.into_iter().collect()))
+            .map_err(|e| {
+                ApiError(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    format!("Code generation error: {}", e),
+                )
+            })
+    })
+    .await
+}
+
+#[derive(Deserialize)]
+struct VizRequest {
+    source: String,
+    format: Option<String>,
+    context: Option<String>,
+}
+
+async fn viz(
+    request: Result<Json<VizRequest>, JsonRejection>,
+) -> Result<Json<BTreeMap<String, String>>, ApiError> {
+    let request = body(request)?;
+    let format = request.format.as_deref().unwrap_or("mermaid");
+    let format: sketchddd_viz::Format = format.parse().map_err(|_| {
+        ApiError(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("Unknown visualization format: {}", format),
+        )
+    })?;
+    blocking(move || draw(&request, format)).await
+}
+
+/// Draw the diagrams a request to `/viz` asks for.
+fn draw(
+    request: &VizRequest,
+    format: sketchddd_viz::Format,
+) -> Result<Json<BTreeMap<String, String>>, ApiError> {
+    let workspace = model(&request.source)?;
+    let contexts: Vec<_> = workspace
+        .contexts
+        .iter()
+        .filter(|c| {
+            request
+                .context
+                .as_deref()
+                .is_none_or(|name| c.name() == name)
+        })
+        .collect();
+    if let (Some(name), true) = (&request.context, contexts.is_empty()) {
+        return Err(ApiError(
+            StatusCode::NOT_FOUND,
+            format!("Context '{}' not found", name),
+        ));
+    }
+    contexts
+        .into_iter()
+        .map(|context| {
+            sketchddd_viz::generate(context, format)
+                .map(|diagram| (context.name().to_string(), diagram))
+        })
+        .collect::<Result<_, _>>()
+        .map(Json)
+        .map_err(|e| {
+            ApiError(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("Visualization error: {}", e),
+            )
+        })
+}
+
+#[derive(Deserialize)]
+struct DiffRequest {
+    old: String,
+    new: String,
+}
+
+async fn diff(request: Result<Json<DiffRequest>, JsonRejection>) -> Result<Json<Value>, ApiError> {
+    let request = body(request)?;
+    blocking(move || {
+        let old = model(&request.old)?;
+        let new = model(&request.new)?;
+        let diff = diff_models(&old.contexts, &new.contexts);
+        Ok(Json(json!({
+            "changes": diff.changes,
+            "version_warnings": diff.version_warnings,
+            "summary": {
+                "changes": diff.changes.len(),
+                "breaking": diff.breaking_changes().count(),
+                "max_severity": diff.max_severity(),
+            },
+        })))
+    })
+    .await
+}
+
+/// Describe an endpoint taking a JSON body.
+fn operation(summary: &str, required: &[&str], properties: Value, response: &str) -> Value {
+    json!({
+        "post": {
+            "summary": summary,
+            "security": [{ "bearer": [] }, { "apiKey": [] }],
+            "requestBody": {
+                "required": true,
+                "content": {
+                    "application/json": {
+                        "schema": {
+                            "type": "object",
+                            "required": required,
+                            "properties": properties,
+                        }
+                    }
+                }
+            },
+            "responses": {
+                "200": {
+                    "description": response,
+                    "content": { "application/json": { "schema": { "type": "object" } } }
+                },
+                "400": { "$ref": "#/components/responses/Error" },
+                "401": { "$ref": "#/components/responses/Error" },
+                "422": { "$ref": "#/components/responses/Error" },
+            }
+        }
+    })
+}
+
+/// Describe the API in OpenAPI 3.
+pub fn openapi() -> Value {
+    let source = json!({ "type": "string", "description": "Source of a model" });
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "SketchDDD",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Parse, validate, generate code from, draw and compare SketchDDD models.",
+        },
+        "paths": {
+            "/parse": operation(
+                "Parse a model",
+                &["source"],
+                json!({ "source": source }),
+                "The model, in the export format",
+            ),
+            "/validate": operation(
+                "Validate a model",
+                &["source"],
+                json!({
+                    "source": source,
+                    "config": {
+                        "type": "string",
+                        "description": "A sketchddd.toml, for its lint settings; it may not name rule scripts",
+                    },
+                }),
+                "The validation report, as `check --format json`",
+            ),
+            "/codegen": operation(
+                "Generate code from a model",
+                &["source", "target"],
+                json!({
+                    "source": source,
+                    "target": {
+                        "type": "string",
+                        "enum": ["rust", "typescript", "kotlin", "python", "java", "clojure", "haskell"],
+                    },
+                }),
+                "The generated code, by context",
+            ),
+            "/viz": operation(
+                "Draw the diagrams of a model",
+                &["source"],
+                json!({
+                    "source": source,
+                    "format": {
+                        "type": "string",
+                        "enum": ["mermaid", "graphviz", "html", "excalidraw", "drawio"],
+                        "default": "mermaid",
+                    },
+                    "context": { "type": "string", "description": "The one context to draw" },
+                }),
+                "The diagram, by context",
+            ),
+            "/diff": operation(
+                "Compare two models",
+                &["old", "new"],
+                json!({ "old": source, "new": source }),
+                "The changes, as `diff --format json`",
+            ),
+            "/health": {
+                "get": {
+                    "summary": "Check the server is up",
+                    "responses": { "200": { "description": "The status and version of the server" } }
+                }
+            },
+        },
+        "components": {
+            "securitySchemes": {
+                "bearer": { "type": "http", "scheme": "bearer" },
+                "apiKey": { "type": "apiKey", "in": "header", "name": "X-API-Key" },
+            },
+            "responses": {
+                "Error": {
+                    "description": "An error",
+                    "content": {
+                        "application/json": {
+                            "schema": {
+                                "type": "object",
+                                "properties": { "error": { "type": "string" } },
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Serve the API until the process is stopped.
+pub fn serve(host: &str, port: u16) -> Result<(), String> {
+    let key = std::env::var(API_KEY_ENV)
+        .ok()
+        .filter(|key| !key.is_empty());
+    if key.is_none() {
+        report::warning(format_args!(
+            "{} is not set, so anyone who can reach the server can use it",
+            API_KEY_ENV
+        ));
+    }
+
+    let runtime =
+        tokio::runtime::Runtime::new().map_err(|e| format!("Failed to start the server: {}", e))?;
+    runtime.block_on(async {
+        let listener = tokio::net::TcpListener::bind((host, port))
+            .await
+            .map_err(|e| format!("Failed to listen on {}:{}: {}", host, port, e))?;
+        report::status(format!(
+            "{} http://{}:{}/docs",
+            "Serving".green().bold(),
+            host,
+            port
+        ));
+        axum::serve(listener, router(Arc::new(key)))
+            .await
+            .map_err(|e| format!("Server error: {}", e))
+    })
+}
@@ -0,0 +1,86 @@
+//! The issue baseline in `sketchddd-baseline.json`.
+//!
+//! The baseline is looked up like `sketchddd.toml`, next to the model or in
+//! a parent directory, and records the accepted issues of every model below
+//! it. Models are identified by their path relative to the baseline, so the
+//! file can be committed and shared.
+
+use crate::config;
+use crate::report::{self, CliError};
+use sketchddd_core::{Baseline, ValidationError, ValidationResult};
+use std::path::{Path, PathBuf};
+
+/// Name of the baseline file.
+pub const BASELINE_FILE: &str = "sketchddd-baseline.json";
+
+/// A model's baseline, with where it is stored and the model's key in it.
+pub struct ModelBaseline {
+    path: PathBuf,
+    key: String,
+    baseline: Baseline,
+}
+
+impl ModelBaseline {
+    /// Load the baseline governing a model file.
+    ///
+    /// Without a baseline file, an empty baseline is returned that would be
+    /// stored next to the model.
+    pub fn load(model: &Path) -> Result<Self, CliError> {
+        let (path, baseline) = match config::find_project_file(model, BASELINE_FILE) {
+            Some(path) => {
+                let source = std::fs::read_to_string(&path).map_err(|e| {
+                    report::io_error(format!("Failed to read {}: {}", path.display(), e))
+                })?;
+                let baseline = serde_json::from_str(&source)
+                    .map_err(|e| format!("{}: {}", path.display(), e))?;
+                (path, baseline)
+            }
+            None => {
+                let dir = config::model_dir(model)
+                    .ok_or_else(|| "Failed to find the model's directory".to_string())?;
+                (dir.join(BASELINE_FILE), Baseline::new())
+            }
+        };
+        let key = model_key(&path, model);
+        Ok(Self {
+            path,
+            key,
+            baseline,
+        })
+    }
+
+    /// Get where the baseline is stored.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Remove the issues the baseline records, keeping the new ones.
+    pub fn remove_known(&self, result: &mut ValidationResult) {
+        self.baseline.remove_known(Some(&self.key), result);
+    }
+
+    /// Record the model's current issues and write the baseline.
+    pub fn update(&mut self, issues: &[ValidationError]) -> Result<(), CliError> {
+        self.baseline.record(Some(&self.key), issues);
+        let json = serde_json::to_string_pretty(&self.baseline)
+            .map_err(|e| report::internal_error(format!("JSON serialization error: {}", e)))?;
+        std::fs::write(&self.path, json + "\n").map_err(|e| {
+            report::io_error(format!("Failed to write {}: {}", self.path.display(), e))
+        })
+    }
+}
+
+/// Get the path of a model relative to the baseline, with `/` separators.
+fn model_key(baseline: &Path, model: &Path) -> String {
+    let model = model.canonicalize().unwrap_or_else(|_| model.to_path_buf());
+    let base = baseline
+        .parent()
+        .and_then(|dir| dir.canonicalize().ok())
+        .unwrap_or_default();
+    let relative = model.strip_prefix(&base).unwrap_or(&model);
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
@@ -0,0 +1,77 @@
+//! A cache of loaded models, in `~/.cache/sketchddd/models`.
+//!
+//! Parsing and transforming a large model takes longer than reading it back
+//! in the binary format, so commands keep the models they load there and
+//! read them back while the source is unchanged. An entry is keyed by the
+//! source of the model and by the `sketchddd` executable, so editing the
+//! model or installing another build misses the cache. Small models are
+//! quicker to parse than to read, and are not cached.
+//!
+//! `$XDG_CACHE_HOME` moves the cache, and `SKETCHDDD_CACHE` names another
+//! directory, or turns the cache off when set to `off`. Entries can be
+//! removed at any time.
+
+use sketchddd_core::Workspace;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+
+/// Environment variable naming the cache directory, or `off`.
+pub const CACHE_ENV: &str = "SKETCHDDD_CACHE";
+
+/// Length of the source of the smallest model cached, in bytes.
+const MIN_SOURCE_LEN: usize = 16 * 1024;
+
+/// Get the directory of the cache, unless it is turned off.
+fn dir() -> Option<PathBuf> {
+    match std::env::var_os(CACHE_ENV).filter(|dir| !dir.is_empty()) {
+        Some(dir) if dir == "off" => None,
+        Some(dir) => Some(PathBuf::from(dir)),
+        None => std::env::var_os("XDG_CACHE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(dirs::cache_dir)
+            .map(|dir| dir.join("sketchddd").join("models")),
+    }
+}
+
+/// Get the path of the entry for a model's source.
+fn entry(source: &str) -> Option<PathBuf> {
+    if source.len() < MIN_SOURCE_LEN {
+        return None;
+    }
+    let mut hasher = DefaultHasher::new();
+    source.hash(&mut hasher);
+    env!("CARGO_PKG_VERSION").hash(&mut hasher);
+    // A rebuilt executable may load models differently
+    let built = std::env::current_exe()
+        .and_then(|exe| exe.metadata())
+        .and_then(|metadata| metadata.modified())
+        .ok()?;
+    built.hash(&mut hasher);
+    Some(dir()?.join(format!("{:016x}.bin", hasher.finish())))
+}
+
+/// Get the cached model of a source, if there is one.
+pub fn load(source: &str) -> Option<Workspace> {
+    let bytes = std::fs::read(entry(source)?).ok()?;
+    Workspace::from_bytes(&bytes).ok()
+}
+
+/// Cache the model of a source. Failing to is not an error: the model is
+/// loaded from its source next time.
+pub fn store(source: &str, workspace: &Workspace) {
+    let Some(path) = entry(source) else {
+        return;
+    };
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    // Write a whole entry or none, should another command read it meanwhile
+    let partial = path.with_extension(format!("{}.tmp", std::process::id()));
+    let written = std::fs::write(&partial, workspace.to_bytes())
+        .and_then(|()| std::fs::rename(&partial, &path));
+    if written.is_err() {
+        let _ = std::fs::remove_file(&partial);
+    }
+}
@@ -0,0 +1,295 @@
+//! Quality gates of `sketchddd ci`.
+//!
+//! A CI run passes when the model passes every gate. The `[ci]` section of
+//! `sketchddd.toml` sets them; the defaults let no error and no breaking
+//! change through:
+//!
+//! ```toml
+//! [ci]
+//! max-errors = 0        # errors allowed
+//! max-warnings = 10     # warnings allowed; any number by default
+//! fail-on = "breaking"  # least severe change from the base failing the run, or "none"
+//! min-coverage = 80     # context map coverage required, in percent
+//! ```
+//!
+//! The base a model is compared with is a model file or a git revision, at
+//! which the model's files are read with `git show`.
+
+use crate::config;
+use crate::project::{ModelSource, Sources};
+use crate::report::{self, CliError};
+use serde::{Deserialize, Serialize};
+use sketchddd_core::{Compatibility, ModelDiff, ValidationResult, Workspace};
+use sketchddd_parser::transform;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
+
+/// The gates of a CI run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gates {
+    /// Errors allowed
+    pub max_errors: usize,
+
+    /// Warnings allowed, or any number
+    pub max_warnings: Option<usize>,
+
+    /// Least severe change from the base failing the run, or none
+    pub fail_on: Option<Compatibility>,
+
+    /// Context map coverage required, in percent
+    pub min_coverage: Option<f64>,
+}
+
+impl Default for Gates {
+    fn default() -> Self {
+        Self {
+            max_errors: 0,
+            max_warnings: None,
+            fail_on: Some(Compatibility::Breaking),
+            min_coverage: None,
+        }
+    }
+}
+
+/// Parse the severity a run fails on, or `none`.
+pub fn parse_fail_on(value: &str) -> Result<Option<Compatibility>, String> {
+    match value.trim().to_lowercase().as_str() {
+        "none" => Ok(None),
+        other => Compatibility::from_str(other).map(Some),
+    }
+}
+
+impl Gates {
+    /// Load the gates governing a model file from its `[ci]` section.
+    pub fn load(model: &Path) -> Result<Self, CliError> {
+        let Some(path) = config::find_config(model) else {
+            return Ok(Self::default());
+        };
+        let source = std::fs::read_to_string(&path)
+            .map_err(|e| report::io_error(format!("Failed to read {}: {}", path.display(), e)))?;
+        Self::parse(&source).map_err(|e| format!("{}: {}", path.display(), e).into())
+    }
+
+    /// Parse the `[ci]` section of a configuration file.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        #[derive(Deserialize)]
+        struct Config {
+            #[serde(default)]
+            ci: Option<Ci>,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "kebab-case", deny_unknown_fields)]
+        struct Ci {
+            max_errors: Option<usize>,
+            max_warnings: Option<usize>,
+            fail_on: Option<String>,
+            min_coverage: Option<f64>,
+        }
+
+        let config: Config = toml::from_str(source).map_err(|e| e.message().to_string())?;
+        let mut gates = Self::default();
+        let Some(ci) = config.ci else {
+            return Ok(gates);
+        };
+        if let Some(max_errors) = ci.max_errors {
+            gates.max_errors = max_errors;
+        }
+        gates.max_warnings = ci.max_warnings;
+        if let Some(fail_on) = ci.fail_on {
+            gates.fail_on = parse_fail_on(&fail_on).map_err(|e| format!("[ci] fail-on: {}", e))?;
+        }
+        gates.min_coverage = ci.min_coverage;
+        Ok(gates)
+    }
+
+    /// Check a model's issues, coverage and changes from the base against
+    /// the gates.
+    pub fn check(
+        &self,
+        result: &ValidationResult,
+        coverage: f64,
+        diff: Option<&ModelDiff>,
+    ) -> Vec<Gate> {
+        let errors = result.error_count();
+        let warnings = result.warning_count();
+        let mut gates = vec![
+            Gate {
+                name: "errors",
+                passed: errors <= self.max_errors,
+                detail: format!("{} (max {})", errors, self.max_errors),
+            },
+            Gate {
+                name: "warnings",
+                passed: self.max_warnings.is_none_or(|max| warnings <= max),
+                detail: match self.max_warnings {
+                    Some(max) => format!("{} (max {})", warnings, max),
+                    None => format!("{} (no limit)", warnings),
+                },
+            },
+        ];
+        if let Some(diff) = diff {
+            let severity = diff.max_severity();
+            let threshold = match self.fail_on {
+                Some(threshold) => format!("fails on {}", threshold),
+                None => "never fails".to_string(),
+            };
+            gates.push(Gate {
+                name: "changes",
+                passed: !failing(self.fail_on, severity),
+                detail: match severity {
+                    Some(severity) => format!(
+                        "{} change(s), most {} ({})",
+                        diff.changes.len(),
+                        severity,
+                        threshold
+                    ),
+                    None => format!("no changes ({})", threshold),
+                },
+            });
+        }
+        if let Some(min) = self.min_coverage {
+            gates.push(Gate {
+                name: "coverage",
+                passed: coverage >= min,
+                detail: format!("{:.0}% (min {:.0}%)", coverage, min),
+            });
+        }
+        gates
+    }
+}
+
+/// Check whether a change of a severity fails a run.
+pub fn failing(fail_on: Option<Compatibility>, severity: Option<Compatibility>) -> bool {
+    matches!((fail_on, severity), (Some(threshold), Some(severity)) if severity >= threshold)
+}
+
+/// The outcome of a gate.
+#[derive(Debug, Clone, Serialize)]
+pub struct Gate {
+    /// What the gate checks
+    pub name: &'static str,
+
+    /// Whether the model passed it
+    pub passed: bool,
+
+    /// The value checked and the gate's limit
+    pub detail: String,
+}
+
+/// Load the model at the base: a model file, or the model's files at a git
+/// revision.
+///
+/// Files the revision does not have are left out, so a model added since
+/// is compared with an empty one.
+pub fn load_base(base: &str, model: &ModelSource) -> Result<Workspace, CliError> {
+    let sources = if Path::new(base).is_file() {
+        Sources::read(&[PathBuf::from(base)])?
+    } else {
+        let paths = match model {
+            ModelSource::File(path) => vec![path.clone()],
+            ModelSource::Project(project) => project.files()?,
+        };
+        let mut sources = Vec::new();
+        for path in paths {
+            if let Some(source) = git_show(base, &path)? {
+                sources.push((path, source));
+            }
+        }
+        if sources.is_empty() {
+            return Ok(Workspace::new());
+        }
+        Sources::join(sources)
+    };
+    let ast = sources.parse()?;
+    let result = transform(&ast).map_err(|e| format!("Transform error in base {}: {}", base, e))?;
+    Ok(result.into_workspace())
+}
+
+/// Read a file at a git revision, or `None` if the revision does not have
+/// it.
+fn git_show(revision: &str, path: &Path) -> Result<Option<String>, CliError> {
+    let dir = config::model_dir(path).unwrap_or_else(|| PathBuf::from("."));
+    let git = |args: &[&str]| {
+        tracing::debug!(dir = %dir.display(), ?args, "git");
+        Command::new("git")
+            .current_dir(&dir)
+            .args(args)
+            .output()
+            .map_err(|e| format!("Failed to run git: {}", e))
+    };
+
+    let commit = format!("{}^{{commit}}", revision);
+    if !git(&["rev-parse", "--verify", "--quiet", &commit])?
+        .status
+        .success()
+    {
+        return Err(format!("Base '{}' is neither a file nor a git revision", revision).into());
+    }
+    // Paths at a revision are relative to the top of the repository
+    let top = git(&["rev-parse", "--show-toplevel"])?;
+    let top = PathBuf::from(String::from_utf8_lossy(&top.stdout).trim());
+    let full = path
+        .canonicalize()
+        .map_err(|e| report::io_error(format!("Failed to read {}: {}", path.display(), e)))?;
+    let top = top.canonicalize().unwrap_or(top);
+    let relative = full.strip_prefix(&top).map_err(|_| {
+        format!(
+            "{} is not in the git repository at {}",
+            path.display(),
+            top.display()
+        )
+    })?;
+    let relative: Vec<_> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect();
+
+    let shown = git(&["show", &format!("{}:{}", revision, relative.join("/"))])?;
+    Ok(shown
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&shown.stdout).into_owned()))
+}
+
+/// Format a GitHub Actions workflow command annotating the run, or a
+/// location in a file.
+pub fn annotation(
+    level: &str,
+    file: Option<&str>,
+    line: Option<u32>,
+    column: Option<u32>,
+    title: &str,
+    message: &str,
+) -> String {
+    let mut properties = Vec::new();
+    if let Some(file) = file {
+        properties.push(format!("file={}", escape_property(file)));
+    }
+    if let Some(line) = line {
+        properties.push(format!("line={}", line));
+    }
+    if let Some(column) = column {
+        properties.push(format!("col={}", column));
+    }
+    properties.push(format!("title={}", escape_property(title)));
+    format!(
+        "::{} {}::{}",
+        level,
+        properties.join(","),
+        escape_data(message)
+    )
+}
+
+/// Escape the message of a workflow command.
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a property of a workflow command.
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
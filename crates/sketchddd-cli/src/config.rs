@@ -0,0 +1,167 @@
+//! Project configuration from `sketchddd.toml`.
+//!
+//! The file is looked up next to the model being processed, then in each
+//! parent directory, so every model of a project shares one configuration.
+//! The `[model]`, `[codegen]` and `[viz]` sections describe the project, see
+//! [`crate::project`], and `[ci]` the quality gates, see [`crate::ci`]; the
+//! `[lint]`, `[naming]`, `[limits]` and `[rules]` sections are read here:
+//!
+//! ```toml
+//! [lint]
+//! W0001 = "off"
+//! W0003 = "error"
+//!
+//! [naming]
+//! aggregate-suffix = "Aggregate"
+//!
+//! [limits]
+//! object-morphisms = 12
+//! context-objects = 40
+//!
+//! [rules.X0001]
+//! description = "Aggregate without an owner"
+//! script = "rules/owner.rhai"
+//! ```
+
+use crate::report::{self, CliError};
+use sketchddd_core::{
+    AggregateSuffix, GodObject, LargeContext, LintConfig, Registry, RuleLevel, ScriptRule,
+};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Name of the project configuration file.
+pub const CONFIG_FILE: &str = "sketchddd.toml";
+
+/// Find the configuration file governing a model file.
+pub fn find_config(model: &Path) -> Option<PathBuf> {
+    find_project_file(model, CONFIG_FILE)
+}
+
+/// Find a file named `name` next to a model file or in a parent directory.
+pub fn find_project_file(model: &Path, name: &str) -> Option<PathBuf> {
+    model_dir(model)?
+        .ancestors()
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Get the directory of a model file.
+pub fn model_dir(model: &Path) -> Option<PathBuf> {
+    match model.parent().filter(|p| !p.as_os_str().is_empty()) {
+        Some(dir) => Some(dir.to_path_buf()),
+        None => std::env::current_dir().ok(),
+    }
+}
+
+/// Load the lint rules and configuration governing a model file.
+///
+/// Without a configuration file, or without a `[lint]` section, every
+/// built-in rule runs with its default severity.
+pub fn load_lint_config(model: &Path) -> Result<(Registry, LintConfig), CliError> {
+    let Some(path) = find_config(model) else {
+        return Ok((Registry::builtin(), LintConfig::new()));
+    };
+    let source = std::fs::read_to_string(&path)
+        .map_err(|e| report::io_error(format!("Failed to read {}: {}", path.display(), e)))?;
+    tracing::debug!(config = %path.display(), "lint configuration");
+    let dir = path.parent().unwrap_or(Path::new("."));
+    parse_lint_config(&source, Some(dir)).map_err(|e| format!("{}: {}", path.display(), e).into())
+}
+
+/// Parse the `[lint]`, `[naming]`, `[limits]` and `[rules]` sections of a
+/// configuration file in `dir`, which rule scripts are relative to. Without
+/// a directory, as for a configuration sent by a client, rule scripts are
+/// refused rather than read.
+pub fn parse_lint_config(
+    source: &str,
+    dir: Option<&Path>,
+) -> Result<(Registry, LintConfig), String> {
+    #[derive(serde::Deserialize)]
+    struct Config {
+        #[serde(default)]
+        lint: BTreeMap<String, String>,
+        #[serde(default)]
+        naming: Naming,
+        #[serde(default)]
+        limits: Limits,
+        #[serde(default)]
+        rules: BTreeMap<String, ScriptConfig>,
+    }
+
+    #[derive(Default, serde::Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    struct Naming {
+        aggregate_suffix: Option<String>,
+    }
+
+    #[derive(Default, serde::Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    struct Limits {
+        object_morphisms: Option<usize>,
+        context_objects: Option<usize>,
+    }
+
+    #[derive(serde::Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct ScriptConfig {
+        script: PathBuf,
+        description: Option<String>,
+        severity: Option<String>,
+    }
+
+    let config: Config = toml::from_str(source).map_err(|e| e.message().to_string())?;
+    let mut registry = Registry::builtin();
+    if let Some(suffix) = config.naming.aggregate_suffix {
+        registry.register(AggregateSuffix::new(suffix));
+    }
+    if let Some(max) = config.limits.object_morphisms {
+        registry.register(GodObject::new(max));
+    }
+    if let Some(max) = config.limits.context_objects {
+        registry.register(LargeContext::new(max));
+    }
+    for (id, rule) in config.rules {
+        let Some(dir) = dir else {
+            return Err(format!(
+                "[rules] {}: rule scripts are not accepted here",
+                id
+            ));
+        };
+        if registry.get(&id).is_some() {
+            return Err(format!(
+                "[rules] {}: the id is taken by a built-in rule",
+                id
+            ));
+        }
+        let path = dir.join(&rule.script);
+        let source = std::fs::read_to_string(&path)
+            .map_err(|e| format!("[rules] {}: failed to read {}: {}", id, path.display(), e))?;
+        let description = rule
+            .description
+            .unwrap_or_else(|| format!("Rule script {}", id));
+        let mut script = ScriptRule::new(&id, description, &source)
+            .map_err(|e| format!("[rules] {}: {}", id, e.message))?;
+        if let Some(severity) = rule.severity {
+            let level = severity
+                .parse()
+                .map_err(|e| format!("[rules] {}: {}", id, e))?;
+            match level {
+                RuleLevel::On(severity) => script = script.with_severity(severity),
+                RuleLevel::Off => {
+                    return Err(format!(
+                        "[rules] {}: severity cannot be \"off\"; set {} = \"off\" under [lint]",
+                        id, id
+                    ))
+                }
+            }
+        }
+        registry.register(script);
+    }
+    let mut lint = LintConfig::new();
+    for (id, level) in config.lint {
+        let level: RuleLevel = level.parse().map_err(|e| format!("[lint] {}: {}", id, e))?;
+        lint.set(id, level);
+    }
+    Ok((registry, lint))
+}
@@ -3,32 +3,65 @@
 //! Command-line interface for validating, generating, and visualizing
 //! SketchDDD domain models.
 
-use clap::{Parser, Subcommand, ValueEnum};
+mod api;
+mod baseline;
+mod cache;
+mod ci;
+mod config;
+mod output;
+mod project;
+mod query;
+mod render;
+mod report;
+mod scaffold;
+mod serve;
+mod settings;
+mod site;
+
+use baseline::ModelBaseline;
+use clap::{Parser, Subcommand};
 use colored::Colorize;
+use output::{Artifact, Output, Status};
+use project::{ModelSource, Project, Sources};
+use query::Statement;
+use render::{Engine, ImageFormat};
+use report::{CliError, Verbosity};
+use serde::Serialize;
+use settings::{Key, Settings};
 use sketchddd_codegen::Target;
-use sketchddd_core::{validate_model, Severity, ValidationError};
-use sketchddd_parser::{parse_file, transform};
-use std::path::PathBuf;
-
-/// Verbosity level for output
-#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Default)]
-pub enum Verbosity {
-    /// Suppress all non-essential output
-    Quiet,
-    /// Normal output (default)
-    #[default]
-    Normal,
-    /// Verbose output with additional details
-    Verbose,
-}
+use sketchddd_core::{
+    apply_fixes, architecture, did_you_mean, diff_models, naming, BoundedContext, ChangeKind,
+    Compatibility, Counts, Fix, MapCoverage, ModelDiff, ModelDocument, Registry, Rule, Severity,
+    SeverityPolicy, SourceLocation, ValidationError, ValidationResult, Workspace,
+    MODEL_FORMAT_VERSION,
+};
+use sketchddd_parser::add::parse_field;
+use sketchddd_parser::import::rust::RustFile;
+use sketchddd_parser::import::{self, Imported};
+use sketchddd_parser::merge;
+use sketchddd_parser::{
+    attach_fixes, migrate_source, parse_file, parse_type, transform, unparse, AggregateDecl,
+    Declaration, EntityDecl, EnumDecl, MorphismDecl, ParseError, PrettyPrint, SourceChange,
+    ValueObjectDecl, VariantDecl,
+};
+use std::path::{Path, PathBuf};
 
 #[derive(Parser)]
 #[command(name = "sketchddd")]
 #[command(author, version, about = "Build Domain Models Visually or with Code", long_about = None)]
 struct Cli {
-    /// Verbosity level
-    #[arg(short, long, value_enum, default_value_t = Verbosity::Normal, global = true)]
-    verbosity: Verbosity,
+    /// Only show errors
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Show more details; -vv and -vvv also log what commands do on stderr
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+
+    /// Output format; json prints the result of any command as a JSON
+    /// object for scripts and tools (see also the formats of each command)
+    #[arg(short, long, global = true)]
+    format: Option<String>,
 
     #[command(subcommand)]
     command: Option<Commands>,
@@ -40,64 +73,260 @@ struct Cli {
 
 #[derive(Subcommand)]
 enum Commands {
-    /// Validate a SketchDDD model file
+    /// Validate a SketchDDD model file (formats: pretty, json, sarif)
     Check {
-        /// Path to the .sddd or .sketch file (optional if .sddd file in current dir)
+        /// Path to the .sddd or .sketch file (defaults to the project's model,
+        /// or the .sddd file in the current dir)
+        file: Option<PathBuf>,
+
+        /// Apply the fixes of issues that have one, rewriting the file
+        #[arg(long)]
+        fix: bool,
+
+        /// Record the current warnings in sketchddd-baseline.json, so that
+        /// only new ones are reported
+        #[arg(long)]
+        update_baseline: bool,
+
+        /// Report the issues of a code as errors, or every warning with
+        /// `--deny warnings`
+        #[arg(long, value_name = "CODE")]
+        deny: Vec<String>,
+
+        /// Report the issues of a code as warnings, including errors
+        #[arg(long, value_name = "CODE")]
+        warn: Vec<String>,
+
+        /// Do not report the issues of a warning code
+        #[arg(long, value_name = "CODE")]
+        allow: Vec<String>,
+    },
+
+    /// Run the lint rules over a model, or list and explain the rules
+    /// (formats: pretty, json, sarif; pretty or json for --list and
+    /// --explain)
+    Lint {
+        /// Path to the .sddd or .sketch file (defaults to the project's model,
+        /// or the .sddd file in the current dir)
+        file: Option<PathBuf>,
+
+        /// Apply the fixes of issues that have one, rewriting the files
+        #[arg(long)]
+        fix: bool,
+
+        /// List the rules with their severity in the project
+        #[arg(long, conflicts_with_all = ["explain", "fix"])]
+        list: bool,
+
+        /// Print the documentation of a rule
+        #[arg(long, value_name = "CODE", conflicts_with = "fix")]
+        explain: Option<String>,
+
+        /// Report the issues of a code as errors, or every warning with
+        /// `--deny warnings`
+        #[arg(long, value_name = "CODE")]
+        deny: Vec<String>,
+
+        /// Report the issues of a code as warnings, including errors
+        #[arg(long, value_name = "CODE")]
+        warn: Vec<String>,
+
+        /// Do not report the issues of a warning code
+        #[arg(long, value_name = "CODE")]
+        allow: Vec<String>,
+    },
+
+    /// Run every check of a CI pipeline over a model, failing on the
+    /// project's quality gates (formats: pretty, github, json; github in
+    /// GitHub Actions)
+    Ci {
+        /// Path to the .sddd or .sketch file (defaults to the project's model,
+        /// or the .sddd file in the current dir)
         file: Option<PathBuf>,
 
-        /// Output format for errors
-        #[arg(short, long, default_value = "pretty")]
-        format: String,
+        /// Model file or git revision to compare the model with, to find
+        /// breaking changes
+        #[arg(long, value_name = "REF_OR_FILE")]
+        base: Option<String>,
+
+        /// Warnings allowed, instead of the project's max-warnings
+        #[arg(long, value_name = "N")]
+        max_warnings: Option<usize>,
+
+        /// Least severe change from the base failing the run (breaking,
+        /// compatible, cosmetic, none), instead of the project's fail-on
+        #[arg(long, value_name = "SEVERITY")]
+        fail_on: Option<String>,
     },
 
     /// Generate code from a SketchDDD model
     Codegen {
-        /// Path to the .sddd or .sketch file (optional if .sddd file in current dir)
+        /// Path to the .sddd or .sketch file (defaults to the project's model,
+        /// or the .sddd file in the current dir)
         file: Option<PathBuf>,
 
-        /// Target language (rust, typescript, kotlin, python, java, clojure, haskell)
-        #[arg(short, long, default_value = "rust")]
-        target: String,
+        /// Target language (rust, typescript, kotlin, python, java, clojure,
+        /// haskell); defaults to the project's targets, or rust
+        #[arg(short, long)]
+        target: Option<String>,
 
-        /// Output directory or file
+        /// Output directory or file; defaults to the project's output
         #[arg(short, long)]
         output: Option<PathBuf>,
     },
 
-    /// Generate visualizations from a SketchDDD model
+    /// Generate visualizations from a SketchDDD model (formats: mermaid,
+    /// graphviz, html, excalidraw, drawio, json; defaults to the project's
+    /// format, or mermaid)
     Viz {
-        /// Path to the .sddd or .sketch file (optional if .sddd file in current dir)
+        /// Path to the .sddd or .sketch file (defaults to the project's model,
+        /// or the .sddd file in the current dir)
         file: Option<PathBuf>,
 
-        /// Output format (graphviz, mermaid)
-        #[arg(short, long, default_value = "mermaid")]
-        format: String,
+        /// Output file, or directory for one file per context; defaults to
+        /// the project's output
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Only render this context
+        #[arg(long, conflicts_with = "context_map")]
+        context: Option<String>,
 
-        /// Output file
+        /// Render the context map of the whole model, grouped by subdomain
+        #[arg(long)]
+        context_map: bool,
+
+        /// Kind of diagram of each context: class, er, state (the
+        /// lifecycles of its enums) or board (its event storming board); er
+        /// and state are Mermaid only, board Mermaid or HTML
+        #[arg(long, default_value = "class", conflicts_with = "context_map")]
+        diagram: String,
+
+        /// Add a legend and a banner naming the model, its version and when
+        /// the diagram was generated; class diagrams and context maps in
+        /// Graphviz or Mermaid only
+        #[arg(long)]
+        legend: bool,
+    },
+
+    /// Render diagrams of a model as SVG or PNG images (formats: svg,
+    /// png, json; defaults to the output file's extension, or svg)
+    Render {
+        /// Path to the .sddd or .sketch file (defaults to the project's model,
+        /// or the .sddd file in the current dir)
+        file: Option<PathBuf>,
+
+        /// Output file, or directory for one image per context; defaults to
+        /// the project's viz output, or the current dir
         #[arg(short, long)]
         output: Option<PathBuf>,
+
+        /// Only render this context
+        #[arg(long, conflicts_with = "context_map")]
+        context: Option<String>,
+
+        /// Render the context map of the whole model
+        #[arg(long)]
+        context_map: bool,
+
+        /// What lays out the diagrams
+        #[arg(long, value_enum, default_value_t = Engine::Auto)]
+        engine: Engine,
     },
 
     /// Initialize a new SketchDDD project
     Init {
         /// Project name
-        name: String,
+        #[arg(required_unless_present = "list_templates")]
+        name: Option<String>,
 
-        /// Template to use (minimal, ecommerce, microservices)
+        /// Template to use (minimal, commerce, banking, healthcare,
+        /// event-sourced, microservices)
         #[arg(short, long, default_value = "minimal")]
         template: String,
+
+        /// List the templates instead of creating a project
+        #[arg(long)]
+        list_templates: bool,
     },
 
     /// Start the visual builder server
     Serve {
+        /// Path to the .sddd or .sketch file (defaults to the project's model,
+        /// or the .sddd file in the current dir)
+        file: Option<PathBuf>,
+
         /// Port to listen on
         #[arg(short, long, default_value = "3000")]
         port: u16,
+
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+
+        /// Directory of the built visual builder (web/dist)
+        #[arg(long)]
+        assets: Option<PathBuf>,
+
+        /// Open the visual builder in a browser
+        #[arg(long)]
+        open: bool,
+
+        /// Serve only the API, taking models in requests, for other tools
+        #[arg(long, conflicts_with_all = ["file", "assets", "open"])]
+        headless: bool,
+    },
+
+    /// Report size and coupling metrics of a model (formats: pretty, json)
+    Stats {
+        /// Path to the .sddd or .sketch file (defaults to the project's model,
+        /// or the .sddd file in the current dir)
+        file: Option<PathBuf>,
+
+        /// Number of largest aggregates to show
+        #[arg(long, default_value_t = 5)]
+        top: usize,
+    },
+
+    /// Query a model, e.g. "usages of Money" or "path Order -> Customer"
+    /// (formats: pretty, json)
+    Query {
+        /// The query: morphisms from OBJECT, morphisms to OBJECT, usages of
+        /// OBJECT, path OBJECT -> OBJECT, aggregates using OBJECT, or
+        /// consumers of CONTEXT; objects may be qualified, as in Sales.Order
+        query: String,
+
+        /// Path to the .sddd or .sketch file (defaults to the project's model,
+        /// or the .sddd file in the current dir)
+        file: Option<PathBuf>,
+
+        /// Only look objects up in this context
+        #[arg(long)]
+        context: Option<String>,
+    },
+
+    /// Generate a static documentation site for a model
+    Docs {
+        /// Path to the .sddd or .sketch file (defaults to the project's model,
+        /// or the .sddd file in the current dir)
+        file: Option<PathBuf>,
+
+        /// Directory the site is written to
+        #[arg(short, long, default_value = "site")]
+        output: PathBuf,
+
+        /// Title of the site (defaults to the name of the model file, or of
+        /// the project's directory)
+        #[arg(long)]
+        title: Option<String>,
     },
 
-    /// Export model to JSON format
+    /// Export model to JSON or YAML format, or to architecture portals
+    /// (formats: json, yaml, structurizr, backstage; defaults to the output
+    /// file's extension, or json)
     Export {
-        /// Path to the .sddd or .sketch file (optional if .sddd file in current dir)
+        /// Path to the .sddd or .sketch file (defaults to the project's model,
+        /// or the .sddd file in the current dir)
         file: Option<PathBuf>,
 
         /// Output file
@@ -105,29 +334,85 @@ enum Commands {
         output: Option<PathBuf>,
     },
 
-    /// Import model from JSON format
+    /// Import model from JSON or YAML format, or draft a context from an
+    /// SQL schema, JSON Schema or OpenAPI document, or Rust crate
     Import {
-        /// Path to the JSON file
+        /// Path to the JSON, YAML or SQL file, or a Rust crate or file
         file: PathBuf,
 
         /// Output .sddd file
         #[arg(short, long)]
         output: Option<PathBuf>,
+
+        /// Format of the file (json, yaml, sql, schema for a JSON Schema or
+        /// OpenAPI document, or rust; defaults to the one its extension
+        /// names, rust for a directory, or json)
+        #[arg(long, value_name = "FORMAT")]
+        from: Option<String>,
+
+        /// Name of the context drafted from a schema or source (defaults
+        /// to the file's name)
+        #[arg(long)]
+        context: Option<String>,
+
+        /// Module of a Rust crate whose public structs and enums are
+        /// imported without `#[domain(...)]`, such as `domain`; repeatable
+        #[arg(long = "module", value_name = "PATH")]
+        modules: Vec<String>,
+    },
+
+    /// Add an element to a context of a model, keeping the file's comments
+    /// and formatting it
+    #[command(subcommand)]
+    Add(AddCommands),
+
+    /// Rewrite model files and exports written for older versions of
+    /// SketchDDD in the current syntax and format (formats: pretty, json)
+    Migrate {
+        /// A .sddd or .sketch file, or a JSON or YAML export (defaults to
+        /// the project's model files, or the .sddd file in the current dir)
+        file: Option<PathBuf>,
+
+        /// Only report the changes, failing if a file needs any
+        #[arg(long)]
+        check: bool,
     },
 
-    /// Compare two model versions
+    /// Compare two model versions (formats: pretty, json)
     Diff {
         /// First .sddd or .sketch file
         old: PathBuf,
 
         /// Second .sddd or .sketch file
         new: PathBuf,
+
+        /// Exit with an error if a change is at least this severe
+        /// (breaking, compatible, cosmetic)
+        #[arg(long, value_name = "SEVERITY")]
+        fail_on: Option<Compatibility>,
+    },
+
+    /// Merge the changes two branches made to a model, as a git merge
+    /// driver: `sketchddd merge-driver %O %A %B`
+    MergeDriver {
+        /// The common ancestor of both versions
+        base: PathBuf,
+
+        /// Our version, overwritten with the merged model
+        ours: PathBuf,
+
+        /// Their version
+        theirs: PathBuf,
     },
 
     /// Manage templates
     #[command(subcommand)]
     Template(TemplateCommands),
 
+    /// Read and write the user's settings (formats: pretty, json)
+    #[command(subcommand)]
+    Config(ConfigCommands),
+
     /// Check for updates
     Update {
         /// Only check, don't install
@@ -136,6 +421,95 @@ enum Commands {
     },
 }
 
+/// Elements `add` declares
+#[derive(Subcommand)]
+enum AddCommands {
+    /// Add an entity
+    Entity {
+        /// Name of the entity
+        name: String,
+
+        #[command(flatten)]
+        target: AddTarget,
+
+        /// A field, as name:Type; may be repeated
+        #[arg(long = "field", value_name = "NAME:TYPE")]
+        fields: Vec<String>,
+    },
+
+    /// Add a value object
+    Value {
+        /// Name of the value object
+        name: String,
+
+        #[command(flatten)]
+        target: AddTarget,
+
+        /// A field, as name:Type; may be repeated
+        #[arg(long = "field", value_name = "NAME:TYPE")]
+        fields: Vec<String>,
+    },
+
+    /// Add an enum
+    Enum {
+        /// Name of the enum
+        name: String,
+
+        #[command(flatten)]
+        target: AddTarget,
+
+        /// Variants of the enum, separated by commas
+        #[arg(long, value_delimiter = ',', required = true)]
+        variants: Vec<String>,
+    },
+
+    /// Add an aggregate
+    Aggregate {
+        /// Name of the aggregate
+        name: String,
+
+        #[command(flatten)]
+        target: AddTarget,
+
+        /// Root entity (defaults to the aggregate's name)
+        #[arg(long)]
+        root: Option<String>,
+
+        /// Entities the aggregate contains, separated by commas
+        #[arg(long, value_delimiter = ',')]
+        contains: Vec<String>,
+    },
+
+    /// Add a morphism
+    Morphism {
+        /// Name of the morphism
+        name: String,
+
+        #[command(flatten)]
+        target: AddTarget,
+
+        /// Source type
+        #[arg(long)]
+        from: String,
+
+        /// Target type, such as Customer, List<LineItem> or Address?
+        #[arg(long)]
+        to: String,
+    },
+}
+
+/// Where `add` declares an element
+#[derive(clap::Args)]
+struct AddTarget {
+    /// Path to the .sddd or .sketch file (defaults to the project's model,
+    /// or the .sddd file in the current dir)
+    file: Option<PathBuf>,
+
+    /// Context to add the element to (optional if the model has one)
+    #[arg(long)]
+    context: Option<String>,
+}
+
 /// Template subcommands
 #[derive(Subcommand)]
 enum TemplateCommands {
@@ -200,13 +574,114 @@ enum TemplateCommands {
     },
 }
 
+/// Config subcommands
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Print the value of a setting, from the project's sketchddd.toml, the
+    /// user's settings or its default
+    Get {
+        /// Setting, such as codegen.targets
+        key: String,
+    },
+
+    /// Set a setting in the user's settings file
+    Set {
+        /// Setting, such as codegen.targets
+        key: String,
+
+        /// Value; lists are comma-separated, as in rust,typescript
+        value: String,
+    },
+
+    /// List every setting with its value
+    List,
+}
+
 fn main() {
     let cli = Cli::parse();
+    let command = command_name(cli.command.as_ref());
+    let format = cli.format.as_deref();
+    let json = format == Some("json");
+    let settings = Settings::load();
+    if let Ok(settings) = &settings {
+        settings.color.apply();
+    }
+    // Reports for tools are all a run prints on stdout
+    let verbosity = if json || format == Some("sarif") || cli.quiet {
+        Verbosity::Quiet
+    } else if cli.verbose > 0 {
+        Verbosity::Verbose
+    } else {
+        Verbosity::Normal
+    };
+    report::init(verbosity, cli.verbose);
+    if let Err(e) = settings {
+        report::warning(e);
+    }
+    std::panic::set_hook(Box::new(|info| {
+        report::error(format_args!(
+            "internal error, please report it at {}/issues: {}",
+            env!("CARGO_PKG_REPOSITORY"),
+            info
+        ));
+    }));
 
     let result = match cli.command {
-        Some(Commands::Check { file, format }) => {
-            match resolve_sddd_file(file) {
-                Ok(file) => cmd_check(&file, &format, cli.verbosity),
+        Some(Commands::Check {
+            file,
+            fix,
+            update_baseline,
+            deny,
+            warn,
+            allow,
+        }) => {
+            match (resolve_model(file), severity_policy(deny, warn, allow)) {
+                (Ok(model), Ok(policy)) => cmd_check(
+                    &model,
+                    command,
+                    format.unwrap_or("pretty"),
+                    fix,
+                    update_baseline,
+                    &policy,
+                ),
+                (Err(e), _) | (_, Err(e)) => Err(e),
+            }
+        }
+        Some(Commands::Lint {
+            file,
+            fix,
+            list,
+            explain,
+            deny,
+            warn,
+            allow,
+        }) => {
+            let format = format.unwrap_or("pretty");
+            if list || explain.is_some() {
+                cmd_lint_rules(file.as_deref(), explain.as_deref(), format)
+            } else {
+                match (resolve_model(file), severity_policy(deny, warn, allow)) {
+                    (Ok(model), Ok(policy)) => {
+                        cmd_check(&model, command, format, fix, false, &policy)
+                    }
+                    (Err(e), _) | (_, Err(e)) => Err(e),
+                }
+            }
+        }
+        Some(Commands::Ci {
+            file,
+            base,
+            max_warnings,
+            fail_on,
+        }) => {
+            match resolve_model(file) {
+                Ok(model) => cmd_ci(
+                    &model,
+                    base.as_deref(),
+                    format,
+                    max_warnings,
+                    fail_on.as_deref(),
+                ),
                 Err(e) => Err(e),
             }
         }
@@ -215,64 +690,244 @@ fn main() {
             target,
             output,
         }) => {
-            match resolve_sddd_file(file) {
-                Ok(file) => cmd_codegen(&file, &target, output, cli.verbosity),
-                Err(e) => Err(e),
+            match (resolve_model(file), json_only(command, format)) {
+                (Ok(model), Ok(json)) => {
+                    cmd_codegen(&model, target.as_deref(), output, json)
+                }
+                (Err(e), _) | (_, Err(e)) => Err(e),
             }
         }
         Some(Commands::Viz {
             file,
-            format,
             output,
+            context,
+            context_map,
+            diagram,
+            legend,
         }) => {
-            match resolve_sddd_file(file) {
-                Ok(file) => cmd_viz(&file, &format, output, cli.verbosity),
+            match resolve_model(file) {
+                Ok(model) => cmd_viz(
+                    &model,
+                    format,
+                    output,
+                    context.as_deref(),
+                    context_map,
+                    &diagram,
+                    legend,
+                ),
+                Err(e) => Err(e),
+            }
+        }
+        Some(Commands::Render {
+            file,
+            output,
+            context,
+            context_map,
+            engine,
+        }) => {
+            match resolve_model(file) {
+                Ok(model) => cmd_render(
+                    &model,
+                    format,
+                    output,
+                    context.as_deref(),
+                    context_map,
+                    engine,
+                ),
+                Err(e) => Err(e),
+            }
+        }
+        Some(Commands::Init {
+            name,
+            template,
+            list_templates,
+        }) => match (name, json_only(command, format)) {
+            (_, Err(e)) => Err(e),
+            (Some(name), Ok(json)) if !list_templates => cmd_init(&name, &template, json),
+            (_, Ok(json)) => cmd_init_list_templates(json),
+        },
+        Some(Commands::Serve {
+            file,
+            port,
+            host,
+            assets,
+            open,
+            headless,
+        }) => match no_json(command, format) {
+            Err(e) => Err(e),
+            Ok(()) if headless => cmd_serve_headless(&host, port),
+            Ok(()) => match resolve_model(file) {
+                Ok(model) => cmd_serve(model, &host, port, assets.as_deref(), open),
+                Err(e) => Err(e),
+            },
+        },
+        Some(Commands::Stats { file, top }) => {
+            match resolve_model(file) {
+                Ok(model) => cmd_stats(&model, format.unwrap_or("pretty"), top),
                 Err(e) => Err(e),
             }
         }
-        Some(Commands::Init { name, template }) => cmd_init(&name, &template, cli.verbosity),
-        Some(Commands::Serve { port }) => cmd_serve(port, cli.verbosity),
+        Some(Commands::Query { query, file, context }) => {
+            match (resolve_model(file), json_only(command, format)) {
+                (Ok(model), Ok(json)) => {
+                    cmd_query(&model, &query, context.as_deref(), json)
+                }
+                (Err(e), _) | (_, Err(e)) => Err(e),
+            }
+        }
+        Some(Commands::Docs { file, output, title }) => {
+            match (resolve_model(file), json_only(command, format)) {
+                (Ok(model), Ok(json)) => cmd_docs(&model, &output, title, json),
+                (Err(e), _) | (_, Err(e)) => Err(e),
+            }
+        }
         Some(Commands::Export { file, output }) => {
-            match resolve_sddd_file(file) {
-                Ok(file) => cmd_export(&file, output, cli.verbosity),
+            match resolve_model(file) {
+                Ok(model) => cmd_export(&model, format, output),
+                Err(e) => Err(e),
+            }
+        }
+        Some(Commands::Import {
+            file,
+            output,
+            from,
+            context,
+            modules,
+        }) => no_json(command, format)
+            .and_then(|()| cmd_import(&file, output, from.as_deref(), context, modules)),
+        Some(Commands::Add(element)) => {
+            match json_only(command, format) {
+                Ok(json) => cmd_add(element, json),
+                Err(e) => Err(e),
+            }
+        }
+        Some(Commands::Migrate { file, check }) => {
+            match json_only(command, format) {
+                Ok(json) => cmd_migrate(file, check, json),
+                Err(e) => Err(e),
+            }
+        }
+        Some(Commands::Diff { old, new, fail_on }) => {
+            cmd_diff(&old, &new, format.unwrap_or("pretty"), fail_on)
+        }
+        Some(Commands::MergeDriver { base, ours, theirs }) => {
+            no_json(command, format).and_then(|()| cmd_merge_driver(&base, &ours, &theirs))
+        }
+        Some(Commands::Template(subcmd)) => {
+            no_json(command, format).and_then(|()| cmd_template(subcmd))
+        }
+        Some(Commands::Config(subcmd)) => {
+            match json_only(command, format) {
+                Ok(json) => cmd_config(subcmd, json),
                 Err(e) => Err(e),
             }
         }
-        Some(Commands::Import { file, output }) => cmd_import(&file, output, cli.verbosity),
-        Some(Commands::Diff { old, new }) => cmd_diff(&old, &new, cli.verbosity),
-        Some(Commands::Template(subcmd)) => cmd_template(subcmd, cli.verbosity),
-        Some(Commands::Update { check }) => cmd_update(check, cli.verbosity),
+        Some(Commands::Update { check }) => {
+            no_json(command, format).and_then(|()| cmd_update(check))
+        }
         None => {
-            // Auto-detect .sddd file and run check
-            match resolve_sddd_file(cli.file) {
-                Ok(file) => cmd_check(&file, "pretty", cli.verbosity),
+            // Auto-detect the model and run check
+            match resolve_model(cli.file) {
+                Ok(model) => cmd_check(
+                    &model,
+                    command,
+                    format.unwrap_or("pretty"),
+                    false,
+                    false,
+                    &SeverityPolicy::new(),
+                ),
                 Err(e) => Err(e),
             }
         }
     };
 
     if let Err(e) = result {
-        eprintln!("{}: {}", "error".red().bold(), e);
-        std::process::exit(1);
+        if json && !output::printed() {
+            // Tools read why the command could not run from the object too
+            let _ = Output::error(command, &e.to_string()).print();
+        }
+        report::error(&e);
+        std::process::exit(e.code().code());
+    }
+}
+
+/// Get the name a command is run with, as given in its JSON output
+fn command_name(command: Option<&Commands>) -> &'static str {
+    match command {
+        Some(Commands::Check { .. }) | None => "check",
+        Some(Commands::Lint { .. }) => "lint",
+        Some(Commands::Ci { .. }) => "ci",
+        Some(Commands::Codegen { .. }) => "codegen",
+        Some(Commands::Viz { .. }) => "viz",
+        Some(Commands::Render { .. }) => "render",
+        Some(Commands::Init { .. }) => "init",
+        Some(Commands::Serve { .. }) => "serve",
+        Some(Commands::Stats { .. }) => "stats",
+        Some(Commands::Query { .. }) => "query",
+        Some(Commands::Docs { .. }) => "docs",
+        Some(Commands::Export { .. }) => "export",
+        Some(Commands::Import { .. }) => "import",
+        Some(Commands::Add(_)) => "add",
+        Some(Commands::Migrate { .. }) => "migrate",
+        Some(Commands::Diff { .. }) => "diff",
+        Some(Commands::MergeDriver { .. }) => "merge-driver",
+        Some(Commands::Template(_)) => "template",
+        Some(Commands::Config(_)) => "config",
+        Some(Commands::Update { .. }) => "update",
+    }
+}
+
+/// Check the format of a command whose only format is json, returning
+/// whether it was asked for
+fn json_only(command: &str, format: Option<&str>) -> Result<bool, CliError> {
+    match format {
+        None | Some("pretty") => Ok(false),
+        Some("json") => Ok(true),
+        Some(other) => Err(report::usage_error(format!(
+            "Unknown {} format '{}'; expected pretty or json",
+            command, other
+        ))),
     }
 }
 
-/// Resolve .sddd file path, auto-detecting if not provided
-fn resolve_sddd_file(file: Option<PathBuf>) -> Result<PathBuf, String> {
-    match file {
-        Some(f) => Ok(f),
-        None => auto_detect_sddd_file(),
+/// Check that a command with no formats was not given one
+fn no_json(command: &str, format: Option<&str>) -> Result<(), CliError> {
+    match format {
+        None | Some("pretty") => Ok(()),
+        Some(_) => Err(report::usage_error(format!(
+            "{} has no JSON output",
+            command
+        ))),
+    }
+}
+
+/// Resolve the model a command works on: the given file, else the model
+/// of the project the current directory is in, else the .sddd file in it
+fn resolve_model(file: Option<PathBuf>) -> Result<ModelSource, CliError> {
+    let model = match file {
+        Some(f) => ModelSource::File(f),
+        None => match Project::find()? {
+            Some(project) => ModelSource::Project(Box::new(project)),
+            None => ModelSource::File(auto_detect_sddd_file()?),
+        },
+    };
+    match &model {
+        ModelSource::File(path) => tracing::info!(file = %path.display(), "model file"),
+        ModelSource::Project(project) => {
+            tracing::info!(manifest = %project.manifest.display(), "project")
+        }
     }
+    Ok(model)
 }
 
 /// Auto-detect .sddd file in current directory
-fn auto_detect_sddd_file() -> Result<PathBuf, String> {
+fn auto_detect_sddd_file() -> Result<PathBuf, CliError> {
     let current_dir = std::env::current_dir()
-        .map_err(|e| format!("Failed to get current directory: {}", e))?;
+        .map_err(|e| report::io_error(format!("Failed to get current directory: {}", e)))?;
 
     // Look for .sddd files in current directory
     let entries = std::fs::read_dir(&current_dir)
-        .map_err(|e| format!("Failed to read directory: {}", e))?;
+        .map_err(|e| report::io_error(format!("Failed to read directory: {}", e)))?;
 
     let sddd_files: Vec<PathBuf> = entries
         .filter_map(|e| e.ok())
@@ -285,7 +940,7 @@ fn auto_detect_sddd_file() -> Result<PathBuf, String> {
         .collect();
 
     match sddd_files.len() {
-        0 => Err("No .sddd file found in current directory. Specify a file path or run 'sketchddd init' to create one.".to_string()),
+        0 => Err("No .sddd file found in current directory. Specify a file path or run 'sketchddd init' to create one.".into()),
         1 => Ok(sddd_files.into_iter().next().unwrap()),
         _ => {
             // Multiple files found - prefer one matching directory name
@@ -312,7 +967,7 @@ fn auto_detect_sddd_file() -> Result<PathBuf, String> {
                     Err(format!(
                         "Multiple .sddd files found: {}. Please specify which file to use.",
                         names.join(", ")
-                    ))
+                    ).into())
                 }
             }
         }
@@ -320,73 +975,209 @@ fn auto_detect_sddd_file() -> Result<PathBuf, String> {
 }
 
 /// Check/validate a SketchDDD model file
-fn cmd_check(file: &PathBuf, format: &str, verbosity: Verbosity) -> Result<(), String> {
-    if verbosity != Verbosity::Quiet {
-        println!("{} {}", "Checking".cyan().bold(), file.display());
+/// Build the severity policy of `check` from its `--deny`, `--warn` and
+/// `--allow` options.
+fn severity_policy(
+    deny: Vec<String>,
+    warn: Vec<String>,
+    allow: Vec<String>,
+) -> Result<SeverityPolicy, CliError> {
+    if let Some(code) = allow.iter().find(|code| code.starts_with('E')) {
+        return Err(report::usage_error(format!(
+            "Errors cannot be allowed; use --warn {} to report it as a warning",
+            code
+        )));
+    }
+    let mut policy = SeverityPolicy::new();
+    for code in deny {
+        if code == "warnings" {
+            policy = policy.deny_warnings();
+        } else {
+            policy = policy.deny(code);
+        }
     }
+    policy.warn = warn;
+    policy.allow = allow;
+    Ok(policy)
+}
 
-    // Read file
-    let source =
-        std::fs::read_to_string(file).map_err(|e| format!("Failed to read file: {}", e))?;
+/// A model that was parsed, transformed and linted.
+struct LintedModel {
+    workspace: Workspace,
 
-    // Parse to AST
-    let ast = parse_file(&source).map_err(|e| format!("Parse error: {}", e))?;
+    /// Issues found, with the locations of a project's relative to its files
+    result: ValidationResult,
 
-    if verbosity == Verbosity::Verbose {
-        println!(
-            "  {} {} context(s), {} context map(s)",
-            "Parsed".blue(),
-            ast.contexts.len(),
-            ast.context_maps.len()
-        );
-    }
+    /// The rules that ran
+    registry: Registry,
+
+    /// Warnings from building the model, such as implicitly declared objects
+    transform_issues: Vec<ValidationError>,
+
+    /// Coverage of shared concepts, when the opt-in rule asks for it
+    coverage: Option<MapCoverage>,
+}
+
+/// Parse, transform and lint a model, with its project's lint
+/// configuration, printing the transform's warnings on stderr.
+fn lint_model(model: &ModelSource) -> Result<LintedModel, CliError> {
+    let file = model.path();
+
+    // Read and parse the model's files
+    let sources = model.read()?;
+    let ast = sources.parse()?;
 
     // Transform AST to semantic model
     let transform_result = transform(&ast).map_err(|e| format!("Transform error: {}", e))?;
 
     // Show transform warnings
     for warning in &transform_result.warnings {
-        let location = match (warning.line, warning.column) {
-            (Some(l), Some(c)) => format!("{}:{}:{}", file.display(), l, c),
-            (Some(l), None) => format!("{}:{}", file.display(), l),
-            _ => file.display().to_string(),
-        };
-        eprintln!(
-            "{}: {} {}",
-            location,
-            "warning".yellow().bold(),
-            warning.message
-        );
+        let location = sources.location(warning.line, warning.column);
+        report::warning(format_args!("{}: {}", location, warning.message));
+    }
+    // Keep them as issues for the machine-readable reports
+    let mut transform_issues: Vec<ValidationError> = transform_result
+        .warnings
+        .iter()
+        .map(|warning| {
+            let mut issue = ValidationError::warning("TRANSFORM_WARNING", &warning.message)
+                .with_location(SourceLocation {
+                    file: None,
+                    line: warning.line,
+                    column: warning.column,
+                });
+            issue.fix = warning.fix.clone();
+            issue
+        })
+        .collect();
+
+    // Validate the model, with the project's lint configuration
+    let (registry, lint_config) = config::load_lint_config(file)?;
+    for id in registry.unknown_rules(&lint_config) {
+        report::warning(format_args!(
+            "{}: unknown lint rule '{}'",
+            config::CONFIG_FILE,
+            id
+        ));
+    }
+    let workspace = transform_result.into_workspace();
+    let mut result = workspace.lint(&registry, &lint_config);
+    attach_fixes(&sources.text, &ast, &mut result.issues);
+    if model.project().is_some() {
+        sources.relocate(&mut result.issues);
+        sources.relocate(&mut transform_issues);
+    }
+
+    let coverage = registry
+        .get("W0090")
+        .and_then(|rule| lint_config.severity(rule))
+        .map(|_| workspace.context_map_coverage());
+    Ok(LintedModel {
+        workspace,
+        result,
+        registry,
+        transform_issues,
+        coverage,
+    })
+}
+
+fn cmd_check(
+    model: &ModelSource,
+    command: &'static str,
+    format: &str,
+    fix: bool,
+    update_baseline: bool,
+    policy: &SeverityPolicy,
+) -> Result<(), CliError> {
+    let file = model.path();
+
+    if fix {
+        let applied = fix_model(model)?;
+        if applied > 0 {
+            report::success(format!("Applied {} fix(es) to {}", applied, file.display()));
+        }
+    }
+
+    report::heading("Checking", file.display());
+
+    let LintedModel {
+        workspace,
+        result: mut validation_result,
+        registry,
+        transform_issues,
+        coverage,
+    } = lint_model(model)?;
+
+    report::detail(format!(
+        "  {} {} context(s), {} context map(s)",
+        "Parsed".blue(),
+        workspace.contexts.len(),
+        workspace.context_maps.len()
+    ));
+
+    // Leave out the warnings accepted in the baseline
+    let mut baseline = ModelBaseline::load(file)?;
+    if update_baseline {
+        baseline.update(&validation_result.issues)?;
+        report::success(format!(
+            "Recorded {} warning(s) in {}",
+            validation_result.warning_count(),
+            baseline.path().display()
+        ));
     }
+    baseline.remove_known(&mut validation_result);
+    let validation_result = validation_result.with_policy(policy);
 
-    // Validate the model
-    let validation_result =
-        validate_model(&transform_result.contexts, &transform_result.context_maps);
+    // Machine-readable reports carry the transform warnings too, which
+    // otherwise only go to stderr
+    let mut reported = validation_result.clone();
+    reported.issues.splice(0..0, transform_issues);
 
     // Report results based on format
     match format {
         "json" => {
-            let json = serde_json::to_string_pretty(&validation_result.issues)
-                .map_err(|e| format!("JSON serialization error: {}", e))?;
-            println!("{}", json);
+            let report = reported.to_json_report(Some(&file.display().to_string()));
+            let mut output = Output::new(command)
+                .with_field("file", report.file)
+                .with_field("summary", report.summary);
+            if let Some(coverage) = &coverage {
+                output = output.with_field("context_map_coverage", coverage.percent());
+            }
+            if !validation_result.is_ok() {
+                output.status = Status::Failure;
+            }
+            output.diagnostics = report.issues;
+            output.print()?;
+        }
+        "sarif" => {
+            let sarif = reported.to_sarif(&file.display().to_string(), &registry);
+            let json = serde_json::to_string_pretty(&sarif)
+                .map_err(|e| report::internal_error(format!("JSON serialization error: {}", e)))?;
+            report::output(json);
         }
         _ => {
             // Pretty format (default)
-            print_validation_issues(file, &validation_result.issues, verbosity);
+            print_validation_issues(file, &validation_result.issues);
         }
     }
 
+    if let Some(coverage) = coverage {
+        report::status(format!(
+            "  {} {:.0}% ({} of {} shared concept(s) mapped)",
+            "Context map coverage:".blue(),
+            coverage.percent(),
+            coverage.mapped(),
+            coverage.concepts()
+        ));
+    }
+
     // Summary
     let error_count = validation_result.error_count();
     let warning_count = validation_result.warning_count();
 
-    if verbosity != Verbosity::Quiet {
+    if report::verbosity() != Verbosity::Quiet {
         if error_count == 0 && warning_count == 0 {
-            println!(
-                "{} {} No issues found!",
-                "✓".green().bold(),
-                file.display()
-            );
+            report::success(format!("{} No issues found!", file.display()));
         } else {
             let errors_str = if error_count == 1 {
                 "1 error".red().bold().to_string()
@@ -414,627 +1205,1789 @@ fn cmd_check(file: &PathBuf, format: &str, verbosity: Verbosity) -> Result<(), S
                 (false, false) => "no issues".to_string(),
             };
 
-            println!("  {} generated {} {}", file.display(), summary, "");
+            report::status(format!("  {} generated {}", file.display(), summary));
+        }
+    }
+
+    if validation_result.is_ok() {
+        Ok(())
+    } else {
+        Err(format!("Validation failed with {} error(s)", error_count).into())
+    }
+}
+
+/// Apply the fixes of the issues found in a model, returning how many were
+/// applied.
+///
+/// Fixes touching the same text as an earlier fix are left for the next run.
+fn fix_model(model: &ModelSource) -> Result<usize, CliError> {
+    let sources = model.read()?;
+    let ast = sources.parse()?;
+    let transform_result = transform(&ast).map_err(|e| format!("Transform error: {}", e))?;
+    let mut fixes: Vec<Fix> = transform_result
+        .warnings
+        .iter()
+        .filter_map(|w| w.fix.clone())
+        .collect();
+
+    let (registry, lint_config) = config::load_lint_config(model.path())?;
+    let mut issues = transform_result
+        .into_workspace()
+        .lint(&registry, &lint_config)
+        .issues;
+    attach_fixes(&sources.text, &ast, &mut issues);
+    fixes.extend(issues.into_iter().filter_map(|i| i.fix));
+
+    let mut total = 0;
+    for (file, source, fixes) in sources.fixes_by_file(&fixes) {
+        let (fixed, applied) = apply_fixes(source, &fixes);
+        if applied > 0 {
+            std::fs::write(file, fixed)
+                .map_err(|e| report::io_error(format!("Failed to write file: {}", e)))?;
+        }
+        total += applied;
+    }
+    Ok(total)
+}
+
+/// Get the name of the level a rule runs at.
+fn level_name(severity: Option<Severity>) -> &'static str {
+    match severity {
+        Some(Severity::Error) => "error",
+        Some(Severity::Warning) => "warning",
+        Some(Severity::Hint) => "hint",
+        None => "off",
+    }
+}
+
+/// List the lint rules with the severity the project gives them, or
+/// explain one.
+fn cmd_lint_rules(
+    file: Option<&Path>,
+    explain: Option<&str>,
+    format: &str,
+) -> Result<(), CliError> {
+    if !matches!(format, "pretty" | "json") {
+        return Err(report::usage_error(format!(
+            "Unknown rule list format '{}'; expected pretty or json",
+            format
+        )));
+    }
+
+    // Without a model, the configuration of the current directory applies
+    let (registry, lint_config) =
+        config::load_lint_config(file.unwrap_or(Path::new(config::CONFIG_FILE)))?;
+    let rules: Vec<&dyn Rule> = match explain {
+        Some(code) => {
+            let Some(rule) = registry.get(code) else {
+                let ids: Vec<&str> = registry.rules().map(|r| r.id()).collect();
+                let mut message = format!("Unknown lint rule '{}'", code);
+                if let Some(suggestion) = did_you_mean(code, &ids) {
+                    message.push_str(&format!("; {}", suggestion));
+                }
+                return Err(report::usage_error(message));
+            };
+            vec![rule]
+        }
+        None => registry.rules().collect(),
+    };
+
+    if format == "json" {
+        let described: Vec<serde_json::Value> = rules
+            .iter()
+            .map(|rule| {
+                serde_json::json!({
+                    "id": rule.id(),
+                    "description": rule.description(),
+                    "severity": level_name(lint_config.severity(*rule)),
+                    "default_severity": level_name(
+                        rule.enabled_by_default().then(|| rule.default_severity())
+                    ),
+                    "explanation": rule.explanation(),
+                })
+            })
+            .collect();
+        let output = match explain {
+            Some(_) => Output::new("lint").with_field("rule", &described[0]),
+            None => Output::new("lint").with_field("rules", described),
+        };
+        return output.print();
+    }
+
+    let colored_level = |severity: Option<Severity>| {
+        let name = format!("{:<7}", level_name(severity));
+        match severity {
+            Some(Severity::Error) => name.red(),
+            Some(Severity::Warning) => name.yellow(),
+            Some(Severity::Hint) => name.blue(),
+            None => name.dimmed(),
+        }
+    };
+
+    if let Some(rule) = rules.first().filter(|_| explain.is_some()) {
+        let severity = lint_config.severity(*rule);
+        let default = rule.enabled_by_default().then(|| rule.default_severity());
+        report::output(format!("{}: {}", rule.id().bold(), rule.description()));
+        if severity == default {
+            report::output(format!("Severity: {}", level_name(severity)));
+        } else {
+            report::output(format!(
+                "Severity: {} ({} by default)",
+                level_name(severity),
+                level_name(default)
+            ));
+        }
+        report::output("");
+        report::output(
+            rule.explanation()
+                .unwrap_or("No further documentation.")
+                .trim_end(),
+        );
+        return Ok(());
+    }
+
+    report::status("Lint rules".cyan().bold());
+    report::status("");
+    for rule in rules {
+        report::output(format!(
+            "  {}  {}  {}",
+            rule.id().green(),
+            colored_level(lint_config.severity(rule)),
+            rule.description()
+        ));
+    }
+    report::status("");
+    report::status("Run `sketchddd lint --explain <CODE>` to learn more about a rule.");
+    Ok(())
+}
+
+/// Print validation issues in a pretty format
+fn print_validation_issues(file: &Path, issues: &[ValidationError]) {
+    for issue in issues {
+        let severity_str = match issue.severity {
+            Severity::Error => "error".red().bold(),
+            Severity::Warning => "warning".yellow().bold(),
+            Severity::Hint => "hint".blue().bold(),
+        };
+
+        // Issues of a project name their file
+        let file = issue
+            .location
+            .file
+            .clone()
+            .unwrap_or_else(|| file.display().to_string());
+        let location = match (&issue.location.line, &issue.location.column) {
+            (Some(l), Some(c)) => format!("{}:{}:{}", file, l, c),
+            (Some(l), None) => format!("{}:{}", file, l),
+            _ => file,
+        };
+
+        report::output(format!(
+            "{}: {}[{}]: {}",
+            location, severity_str, issue.code, issue.message
+        ));
+
+        if let Some(ref suggestion) = issue.suggestion {
+            report::detail(format!("  {} {}", "suggestion:".cyan(), suggestion));
+        }
+    }
+}
+
+fn cmd_codegen(
+    model: &ModelSource,
+    target: Option<&str>,
+    output: Option<PathBuf>,
+    json: bool,
+) -> Result<(), CliError> {
+    let defaults = model.project().map(|p| &p.codegen);
+    let targets = match target {
+        Some(target) => vec![target.to_string()],
+        None => match defaults.map(|d| d.targets.clone()) {
+            Some(targets) if !targets.is_empty() => targets,
+            // The user's targets apply when the project has none
+            _ => Some(Settings::load()?.codegen.targets)
+                .filter(|targets| !targets.is_empty())
+                .unwrap_or_else(|| vec!["rust".to_string()]),
+        },
+    };
+
+    let targets = targets
+        .into_iter()
+        .map(|target| match target.parse::<Target>() {
+            Ok(target_enum) => Ok((target, target_enum)),
+            Err(_) => Err(report::usage_error(format!("Unknown target language: {}. Supported: rust, typescript, kotlin, python, java, clojure, haskell", target))),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let workspace = load_workspace(model)?;
+    let mut artifacts = Vec::new();
+    for (target, target_enum) in &targets {
+        // The project's output directory has a directory per target
+        let output = match (&output, defaults.and_then(|d| d.output.as_ref())) {
+            (Some(path), _) => Some(path.clone()),
+            (None, Some(dir)) => {
+                let dir = dir.join(target);
+                std::fs::create_dir_all(&dir).map_err(|e| {
+                    report::io_error(format!("Failed to create {}: {}", dir.display(), e))
+                })?;
+                Some(dir)
+            }
+            (None, None) => None,
+        };
+        artifacts.extend(generate_code(
+            &workspace,
+            model.path(),
+            target,
+            *target_enum,
+            output,
+            json,
+        )?);
+    }
+
+    if json {
+        let mut output = Output::new("codegen");
+        output.artifacts = artifacts;
+        output.print()?;
+    }
+    Ok(())
+}
+
+/// Generate the code of a model in a target language, printing it unless
+/// it is written to files or `json` is set.
+fn generate_code(
+    workspace: &Workspace,
+    file: &Path,
+    target: &str,
+    target_enum: Target,
+    output: Option<PathBuf>,
+    json: bool,
+) -> Result<Vec<Artifact>, CliError> {
+    report::heading("Generating", format!("{} -> {}", file.display(), target));
+
+    // Generate code for each context
+    let generated = sketchddd_codegen::generate_workspace(workspace, target_enum)
+        .map_err(|e| format!("Code generation error: {}", e))?;
+    let mut artifacts = Vec::new();
+    for (name, code) in generated {
+        // Determine output path
+        let output_path = match &output {
+            Some(dir) if dir.is_dir() => dir.join(format!(
+                "{}.{}",
+                to_snake_case(&name),
+                target_enum.extension()
+            )),
+            Some(path) => path.clone(),
+            None => {
+                // Output to stdout
+                if !json {
+                    report::output(&code);
+                }
+                artifacts.push(
+                    Artifact::content("code", code)
+                        .with_format(target)
+                        .with_context(name),
+                );
+                continue;
+            }
+        };
+
+        std::fs::write(&output_path, &code)
+            .map_err(|e| report::io_error(format!("Failed to write output: {}", e)))?;
+        artifacts.push(
+            Artifact::written("code", &output_path)
+                .with_format(target)
+                .with_context(name),
+        );
+
+        report::status(format!(
+            "  {} Generated {}",
+            "✓".green().bold(),
+            output_path.display()
+        ));
+    }
+
+    Ok(artifacts)
+}
+
+fn cmd_viz(
+    model: &ModelSource,
+    format: Option<&str>,
+    output: Option<PathBuf>,
+    context: Option<&str>,
+    context_map: bool,
+    diagram: &str,
+    legend: bool,
+) -> Result<(), CliError> {
+    let file = model.path();
+    let defaults = model.viz()?;
+    // JSON output describes the diagrams in the default format
+    let json = format == Some("json");
+    let format = format
+        .filter(|_| !json)
+        .or(defaults.format.as_deref())
+        .unwrap_or("mermaid");
+    // The configured output is a directory
+    let output = match (output, defaults.output.as_ref()) {
+        (None, Some(dir)) => {
+            std::fs::create_dir_all(dir).map_err(|e| {
+                report::io_error(format!("Failed to create {}: {}", dir.display(), e))
+            })?;
+            Some(dir.clone())
+        }
+        (output, _) => output,
+    };
+
+    report::heading("Visualizing", format!("{} -> {}", file.display(), format));
+
+    let viz_format: sketchddd_viz::Format = format.parse().map_err(|_| {
+        report::usage_error(format!(
+            "Unknown visualization format: {}. Supported: graphviz, mermaid, html, excalidraw, drawio, json",
+            format
+        ))
+    })?;
+    let diagram: sketchddd_viz::Diagram = diagram.parse().map_err(|_| {
+        report::usage_error(format!(
+            "Unknown diagram: {}. Supported: class, er, state, board",
+            diagram
+        ))
+    })?;
+    let workspace = load_workspace(model)?;
+
+    let theme = defaults.theme();
+    let legend = legend || defaults.legend;
+    let banner = legend.then(|| sketchddd_viz::legend::Banner {
+        model: Some(model.name()),
+        version: workspace.version.as_ref().map(|v| v.to_string()),
+        generated: Some(generated_at()),
+    });
+    // Other diagrams and formats are drawn without a legend
+    let banner = banner.filter(|_| {
+        let drawn = matches!(
+            viz_format,
+            sketchddd_viz::Format::Graphviz | sketchddd_viz::Format::Mermaid
+        ) && (context_map || diagram == sketchddd_viz::Diagram::Class);
+        if !drawn {
+            report::warning(
+                "Legends are only drawn on class diagrams and context maps in Graphviz or Mermaid",
+            );
+        }
+        drawn
+    });
+
+    let mut artifacts = Vec::new();
+    if context_map {
+        let mut viz =
+            sketchddd_viz::generate_context_map_with_theme(&workspace, viz_format, &theme)
+                .map_err(|e| format!("Visualization error: {}", e))?;
+        if let Some(banner) = &banner {
+            viz = sketchddd_viz::legend::context_map(&viz, viz_format, &workspace, &theme, banner)
+                .map_err(|e| format!("Visualization error: {}", e))?;
+        }
+        let output = match output {
+            Some(dir) if dir.is_dir() => {
+                Some(dir.join(format!("context_map.{}", diagram_extension(viz_format))))
+            }
+            output => output,
+        };
+        artifacts.push(write_viz(viz, output.as_ref(), json)?);
+        return print_diagrams(artifacts, format, json);
+    }
+
+    // Generate visualization for each context
+    let mut diagrams = Vec::new();
+    for context in select_contexts(&workspace, context, file)? {
+        let mut viz = sketchddd_viz::generate_diagram(context, viz_format, diagram, &theme)
+            .map_err(|e| format!("Visualization error: {}", e))?;
+        if let Some(banner) = &banner {
+            viz = sketchddd_viz::legend::class_diagram(&viz, viz_format, context, &theme, banner)
+                .map_err(|e| format!("Visualization error: {}", e))?;
+        }
+        // Contexts without lifecycles or event storming elements have no
+        // state diagram or board
+        if viz.is_empty() {
+            continue;
+        }
+        match &output {
+            Some(dir) if dir.is_dir() => {
+                let ext = diagram_extension(viz_format);
+                let path = dir.join(format!("{}.{}", to_snake_case(context.name()), ext));
+                artifacts.push(write_viz(viz, Some(&path), json)?.with_context(context.name()));
+            }
+            None if json => {
+                artifacts.push(Artifact::content("diagram", viz).with_context(context.name()))
+            }
+            _ => diagrams.push(viz),
+        }
+    }
+
+    // Several contexts written to one file or stdout follow each other,
+    // except for documents such as HTML pages, which hold one context each
+    if viz_format.is_document() && diagrams.len() > 1 {
+        return Err(format!(
+            "{} contexts need an output directory; pass --context, or a directory as --output",
+            diagrams.len()
+        )
+        .into());
+    }
+    if !diagrams.is_empty() {
+        let viz = diagrams.join("\n");
+        artifacts.push(write_viz(viz, output.as_ref(), json)?);
+    }
+
+    print_diagrams(artifacts, format, json)
+}
+
+/// Print the diagrams a `viz` run produced, when `json` is set.
+fn print_diagrams(artifacts: Vec<Artifact>, format: &str, json: bool) -> Result<(), CliError> {
+    if json {
+        let mut output = Output::new("viz");
+        output.artifacts = artifacts
+            .into_iter()
+            .map(|artifact| artifact.with_format(format))
+            .collect();
+        output.print()?;
+    }
+    Ok(())
+}
+
+/// Get the context a command is limited to, or every context.
+fn select_contexts<'a>(
+    workspace: &'a Workspace,
+    context: Option<&str>,
+    file: &Path,
+) -> Result<Vec<&'a BoundedContext>, CliError> {
+    let Some(name) = context else {
+        return Ok(workspace.contexts.iter().collect());
+    };
+    match workspace.contexts.iter().find(|c| c.name() == name) {
+        Some(found) => Ok(vec![found]),
+        None => {
+            let names: Vec<&str> = workspace.contexts.iter().map(|c| c.name()).collect();
+            let mut message = format!("Context '{}' not found in {}", name, file.display());
+            if let Some(suggestion) = did_you_mean(name, &names) {
+                message.push_str(&format!("; {}", suggestion));
+            }
+            Err(message.into())
+        }
+    }
+}
+
+fn cmd_render(
+    model: &ModelSource,
+    format: Option<&str>,
+    output: Option<PathBuf>,
+    context: Option<&str>,
+    context_map: bool,
+    engine: Engine,
+) -> Result<(), CliError> {
+    let file = model.path();
+    let json = format == Some("json");
+    let image = match format.filter(|_| !json) {
+        Some(name) => ImageFormat::from_name(name).ok_or_else(|| {
+            report::usage_error(format!(
+                "Unknown image format '{}'; expected svg, png or json",
+                name
+            ))
+        })?,
+        None => output
+            .as_deref()
+            .and_then(|path| path.extension())
+            .and_then(|ext| ext.to_str())
+            .and_then(ImageFormat::from_name)
+            .unwrap_or(ImageFormat::Svg),
+    };
+    // Images go to the configured diagrams, or the current directory
+    let defaults = model.viz()?;
+    let output = match (output, defaults.output.as_ref()) {
+        (Some(output), _) => output,
+        (None, Some(dir)) => {
+            std::fs::create_dir_all(dir).map_err(|e| {
+                report::io_error(format!("Failed to create {}: {}", dir.display(), e))
+            })?;
+            dir.clone()
+        }
+        (None, None) => PathBuf::from("."),
+    };
+    let engine = engine.resolve();
+
+    report::heading(
+        "Rendering",
+        format!(
+            "{} -> {} ({})",
+            file.display(),
+            image.extension(),
+            engine.name()
+        ),
+    );
+
+    let workspace = load_workspace(model)?;
+    let diagram_format = engine.diagram_format();
+    let theme = defaults.theme();
+    let mut diagrams = Vec::new();
+    if context_map {
+        let diagram =
+            sketchddd_viz::generate_context_map_with_theme(&workspace, diagram_format, &theme)
+                .map_err(|e| format!("Visualization error: {}", e))?;
+        diagrams.push((None, diagram));
+    } else {
+        for context in select_contexts(&workspace, context, file)? {
+            let diagram = sketchddd_viz::generate_with_theme(context, diagram_format, &theme)
+                .map_err(|e| format!("Visualization error: {}", e))?;
+            diagrams.push((Some(context.name()), diagram));
+        }
+    }
+    if !output.is_dir() && diagrams.len() > 1 {
+        return Err(format!(
+            "{} contexts need an output directory; pass --context, or a directory as --output",
+            diagrams.len()
+        )
+        .into());
+    }
+
+    let mut artifacts = Vec::new();
+    for (context, diagram) in diagrams {
+        let path = if output.is_dir() {
+            let name = context.map_or_else(|| "context_map".to_string(), to_snake_case);
+            output.join(format!("{}.{}", name, image.extension()))
+        } else {
+            output.clone()
+        };
+        let bytes = render::render(&diagram, engine, image)?;
+        std::fs::write(&path, bytes)
+            .map_err(|e| report::io_error(format!("Failed to write {}: {}", path.display(), e)))?;
+        report::status(format!(
+            "  {} Rendered {}",
+            "✓".green().bold(),
+            path.display()
+        ));
+        let artifact = Artifact::written("image", &path).with_format(image.extension());
+        artifacts.push(match context {
+            Some(name) => artifact.with_context(name),
+            None => artifact,
+        });
+    }
+
+    if json {
+        let mut output = Output::new("render");
+        output.artifacts = artifacts;
+        output.print()?;
+    }
+    Ok(())
+}
+
+/// Get when diagrams are generated, as `2024-05-01 12:00 UTC`: the time
+/// given by `SOURCE_DATE_EPOCH`, for reproducible builds, or now.
+fn generated_at() -> String {
+    let seconds = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.trim().parse::<u64>().ok())
+        .unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |since| since.as_secs())
+        });
+    let (days, time) = (seconds / 86_400, seconds % 86_400);
+
+    // Civil date of a day since 1970-01-01, after Howard Hinnant's algorithm
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60
+    )
+}
+
+/// Get the extension of the files diagrams of a format are written to.
+fn diagram_extension(format: sketchddd_viz::Format) -> &'static str {
+    match format {
+        sketchddd_viz::Format::Graphviz => "dot",
+        sketchddd_viz::Format::Mermaid => "md",
+        sketchddd_viz::Format::Html => "html",
+        sketchddd_viz::Format::Excalidraw => "excalidraw",
+        sketchddd_viz::Format::Drawio => "drawio",
+    }
+}
+
+/// Write a diagram to the output file, or to stdout unless `json` is set.
+fn write_viz(viz: String, output: Option<&PathBuf>, json: bool) -> Result<Artifact, CliError> {
+    match output {
+        Some(path) => {
+            std::fs::write(path, viz)
+                .map_err(|e| report::io_error(format!("Failed to write output: {}", e)))?;
+            report::status(format!(
+                "  {} Generated {}",
+                "✓".green().bold(),
+                path.display()
+            ));
+            Ok(Artifact::written("diagram", path))
+        }
+        None => {
+            if !json {
+                report::output(&viz);
+            }
+            Ok(Artifact::content("diagram", viz))
+        }
+    }
+}
+
+fn cmd_init(name: &str, template: &str, json: bool) -> Result<(), CliError> {
+    let Some(scaffold) = scaffold::find(template) else {
+        let names: Vec<&str> = scaffold::TEMPLATES.iter().map(|t| t.name).collect();
+        let mut message = format!("Unknown template '{}'", template);
+        if let Some(suggestion) = did_you_mean(template, &names) {
+            message.push_str(&format!("; {}", suggestion));
+        }
+        message.push_str("\nRun `sketchddd init --list-templates` to see them all");
+        return Err(report::usage_error(message));
+    };
+
+    report::heading(
+        "Initializing",
+        format!("{} (template: {})", name, scaffold.name),
+    );
+
+    // Create directory
+    std::fs::create_dir_all(name)
+        .map_err(|e| report::io_error(format!("Failed to create directory: {}", e)))?;
+    let (written, skipped) = scaffold.write(name, Path::new(name))?;
+
+    if json {
+        let mut output = Output::new("init")
+            .with_field("name", name)
+            .with_field("template", scaffold.name)
+            .with_field("skipped", &skipped);
+        for path in &written {
+            let path = Path::new(name).join(path);
+            output.artifacts.push(Artifact::written("file", &path));
+        }
+        return output.print();
+    }
+
+    report::success(format!("Created {}/", name));
+    report::status(format!(
+        "  {} {} ({} template)",
+        "→".blue(),
+        written[0],
+        scaffold.description
+    ));
+    for path in &written[1..] {
+        report::status(format!("  {} {}", "→".blue(), path));
+    }
+    for path in &skipped {
+        report::status(format!("  {} {} (exists, kept)", "-".dimmed(), path));
+    }
+    report::status("");
+    report::status("Next steps:");
+    report::status(format!("  {} {}", "cd".cyan(), name));
+    report::status(format!("  {} check {}", "sketchddd".cyan(), written[0]));
+
+    Ok(())
+}
+
+/// List the built-in project templates.
+fn cmd_init_list_templates(json: bool) -> Result<(), CliError> {
+    if json {
+        let templates: Vec<_> = scaffold::TEMPLATES
+            .iter()
+            .map(|template| {
+                serde_json::json!({
+                    "name": template.name,
+                    "description": template.description,
+                })
+            })
+            .collect();
+        return Output::new("init")
+            .with_field("templates", templates)
+            .print();
+    }
+
+    report::status("Project templates".cyan().bold());
+    report::status("");
+    for template in scaffold::TEMPLATES {
+        report::output(format!(
+            "  {:<15} {}",
+            template.name.green(),
+            template.description
+        ));
+    }
+    report::status("");
+    report::status("Usage: sketchddd init <project-name> --template <template>");
+    Ok(())
+}
+
+fn cmd_serve(
+    model: ModelSource,
+    host: &str,
+    port: u16,
+    assets: Option<&Path>,
+    open: bool,
+) -> Result<(), CliError> {
+    report::heading(
+        "Starting",
+        format!("visual builder for {}", model.path().display()),
+    );
+    Ok(serve::serve(model, host, port, assets, open)?)
+}
+
+fn cmd_serve_headless(host: &str, port: u16) -> Result<(), CliError> {
+    report::heading("Starting", "the SketchDDD API");
+    Ok(api::serve(host, port)?)
+}
+
+/// Check if a path names a YAML file.
+fn is_yaml(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("yaml" | "yml")
+    )
+}
+
+/// Report size and coupling metrics of a model.
+fn cmd_stats(model: &ModelSource, format: &str, top: usize) -> Result<(), CliError> {
+    if !matches!(format, "pretty" | "json") {
+        return Err(report::usage_error(format!(
+            "Unknown stats format '{}'; expected pretty or json",
+            format
+        )));
+    }
+
+    let stats = load_workspace(model)?.stats();
+    if format == "json" {
+        return Output::new("stats")
+            .with_fields(&stats)
+            .with_field("aggregates", stats.largest_aggregates(top))
+            .print();
+    }
+
+    report::heading("Statistics for", model.path().display());
+    report::status("");
+
+    let print_counts = |counts: &Counts| {
+        report::output(format!(
+            "    entities: {}, value objects: {}, enums: {}",
+            counts.entities, counts.value_objects, counts.enums
+        ));
+        report::output(format!(
+            "    aggregates: {}, average size: {:.1}",
+            counts.aggregates, counts.average_aggregate_size
+        ));
+        report::output(format!(
+            "    morphisms: {}, equations: {}",
+            counts.morphisms, counts.equations
+        ));
+    };
+
+    report::output(format!(
+        "{} ({} contexts, {} context maps)",
+        "Model".bold(),
+        stats.contexts.len(),
+        stats.context_maps
+    ));
+    print_counts(&stats.totals);
+
+    for context in &stats.contexts {
+        report::status("");
+        report::output(context.name.green().bold());
+        print_counts(&context.counts);
+        if !context.coupled_with.is_empty() {
+            report::output(format!(
+                "    coupled with {}",
+                context.coupled_with.join(", ")
+            ));
+        }
+    }
+
+    if !stats.couplings.is_empty() {
+        report::status("");
+        report::status("Coupling".bold());
+        for coupling in &stats.couplings {
+            report::output(format!(
+                "    {} -> {}: {} map(s), {} mapped object(s)",
+                coupling.source,
+                coupling.target,
+                coupling.maps.len(),
+                coupling.mapped_objects
+            ));
+        }
+    }
+
+    let largest = stats.largest_aggregates(top);
+    if !largest.is_empty() {
+        report::status("");
+        report::status("Largest aggregates".bold());
+        for aggregate in largest {
+            report::output(format!(
+                "    {}.{}: {} objects",
+                aggregate.context, aggregate.root, aggregate.size
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Generate a static documentation site for a model.
+fn cmd_docs(
+    model: &ModelSource,
+    output: &Path,
+    title: Option<String>,
+    json: bool,
+) -> Result<(), CliError> {
+    report::heading("Documenting", model.path().display());
+
+    let workspace = load_workspace(model)?;
+    let title = title.unwrap_or_else(|| model.name());
+    let files = site::render(&workspace, &title);
+    let written = site::write(&files, output)?;
+
+    if json {
+        let mut printed = Output::new("docs");
+        printed.artifacts = files
+            .iter()
+            .map(|(name, _)| {
+                let kind = if name.ends_with(".html") {
+                    "page"
+                } else {
+                    "file"
+                };
+                Artifact::written(kind, &output.join(name))
+            })
+            .collect();
+        printed.print()?;
+    }
+
+    report::success(format!("Wrote {} files to {}", written, output.display()));
+    report::status(format!("  Open {}", output.join("index.html").display()));
+    Ok(())
+}
+
+fn cmd_export(
+    model: &ModelSource,
+    format: Option<&str>,
+    output: Option<PathBuf>,
+) -> Result<(), CliError> {
+    let format = match format {
+        Some(format @ ("json" | "structurizr" | "backstage")) => format,
+        Some("yaml" | "yml") => "yaml",
+        Some(other) => {
+            return Err(report::usage_error(format!(
+                "Unknown export format '{}'; expected json, yaml, structurizr or backstage",
+                other
+            )))
+        }
+        None if output.as_deref().is_some_and(is_yaml) => "yaml",
+        None => "json",
+    };
+
+    report::heading("Exporting", model.path().display());
+
+    let workspace = load_workspace(model)?;
+    let to_yaml = |value: &serde_json::Value| {
+        serde_yaml::to_string(value)
+            .map_err(|e| report::internal_error(format!("YAML serialization error: {}", e)))
+    };
+    let exported = match format {
+        "yaml" => to_yaml(&ModelDocument::new(workspace).to_value())?,
+        // A catalog-info.yaml holds one entity per document
+        "backstage" => architecture::backstage(&workspace)
+            .iter()
+            .map(|entity| to_yaml(entity).map(|yaml| format!("---\n{}", yaml)))
+            .collect::<Result<String, CliError>>()?,
+        _ => {
+            let value = match format {
+                "structurizr" => architecture::structurizr(&workspace, &model.name()),
+                _ => ModelDocument::new(workspace).to_value(),
+            };
+            serde_json::to_string_pretty(&value)
+                .map_err(|e| report::internal_error(format!("JSON serialization error: {}", e)))?
+        }
+    };
+
+    // Write to output file or stdout
+    match output {
+        Some(path) => {
+            std::fs::write(&path, &exported)
+                .map_err(|e| report::io_error(format!("Failed to write output: {}", e)))?;
+            report::success(format!("Exported to {}", path.display()));
+        }
+        None => {
+            report::output(exported.trim_end());
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_import(
+    file: &Path,
+    output: Option<PathBuf>,
+    from: Option<&str>,
+    context: Option<String>,
+    modules: Vec<String>,
+) -> Result<(), CliError> {
+    let from = match from {
+        Some(from @ ("json" | "yaml" | "sql" | "schema" | "rust")) => from,
+        Some(other) => {
+            return Err(report::usage_error(format!(
+                "Unknown import format '{}'; expected json, yaml, sql, schema or rust",
+                other
+            )))
+        }
+        None if file.is_dir() || file.extension().is_some_and(|e| e == "rs") => "rust",
+        None if file.extension().is_some_and(|e| e == "sql") => "sql",
+        None if is_yaml(file) => "yaml",
+        None => "json",
+    };
+    if from != "rust" && !modules.is_empty() {
+        return Err(report::usage_error("--module only applies to Rust sources"));
+    }
+    report::heading("Importing", file.display());
+
+    // Contexts drafted from schemas and sources are named after the file or
+    // directory by default
+    let context = context.unwrap_or_else(|| {
+        let path = file.canonicalize().unwrap_or_else(|_| file.to_path_buf());
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        naming::to_pascal_case(&stem)
+    });
+    let source = if from == "rust" {
+        let files = read_crate(file)?;
+        draft_source(file, import::rust::import(&files, &context, &modules))?
+    } else if from == "sql" {
+        let content = read_import(file)?;
+        draft_source(file, import::sql::import(&content, &context))?
+    } else {
+        let content = read_import(file)?;
+        let yaml = from == "yaml" || (from == "schema" && is_yaml(file));
+        let value = read_document(file, &content, yaml)?;
+        if from == "schema" || import::schema::is_schema(&value) {
+            draft_source(file, import::schema::import(&value, &context))?
+        } else {
+            let document = ModelDocument::from_value(value)
+                .map_err(|e| format!("Cannot import {}: {}", file.display(), e))?;
+            unparse(&document.model).pretty_print()
+        }
+    };
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, &source)
+                .map_err(|e| report::io_error(format!("Failed to write output: {}", e)))?;
+            report::success(format!("Imported to {}", path.display()));
         }
+        None => report::output(source.trim_end()),
     }
 
-    if validation_result.is_ok() {
-        Ok(())
-    } else {
-        Err(format!(
-            "Validation failed with {} error(s)",
-            error_count
-        ))
+    Ok(())
+}
+
+/// Report the warnings of a context drafted from a schema, returning its
+/// source.
+fn draft_source(file: &Path, imported: Result<Imported, ParseError>) -> Result<String, CliError> {
+    let imported = imported
+        .map_err(|e| report::parse_error(format!("Cannot import {}: {}", file.display(), e)))?;
+    for warning in &imported.warnings {
+        report::warning(warning);
     }
+    Ok(imported.into_file().pretty_print())
 }
 
-/// Print validation issues in a pretty format
-fn print_validation_issues(file: &PathBuf, issues: &[ValidationError], verbosity: Verbosity) {
-    for issue in issues {
-        let severity_str = match issue.severity {
-            Severity::Error => "error".red().bold(),
-            Severity::Warning => "warning".yellow().bold(),
-            Severity::Hint => "hint".blue().bold(),
-        };
+/// Read a file to import.
+fn read_import(file: &Path) -> Result<String, CliError> {
+    std::fs::read_to_string(file)
+        .map_err(|e| report::io_error(format!("Failed to read {}: {}", file.display(), e)))
+}
 
-        let location = match (&issue.location.line, &issue.location.column) {
-            (Some(l), Some(c)) => format!("{}:{}:{}", file.display(), l, c),
-            (Some(l), None) => format!("{}:{}", file.display(), l),
-            _ => file.display().to_string(),
+/// Read the Rust files of a crate, from its directory, or from a directory
+/// of modules such as its `src` directory, or a single Rust file.
+fn read_crate(path: &Path) -> Result<Vec<RustFile>, CliError> {
+    let (root, mut paths) = if path.is_dir() {
+        let src = path.join("src");
+        let root = if src.is_dir() {
+            src
+        } else {
+            path.to_path_buf()
         };
+        let pattern = root.join("**").join("*.rs");
+        let paths = glob::glob(&pattern.to_string_lossy())
+            .map_err(|e| {
+                format!(
+                    "Cannot list the Rust files of {}: {}",
+                    path.display(),
+                    e.msg
+                )
+            })?
+            .filter_map(Result::ok)
+            .collect();
+        (root, paths)
+    } else {
+        (PathBuf::new(), vec![path.to_path_buf()])
+    };
+    paths.sort();
+
+    paths
+        .into_iter()
+        .map(|file| {
+            let module = match file.strip_prefix(&root) {
+                Ok(relative) if path.is_dir() => import::rust::module_of(relative),
+                _ => String::new(),
+            };
+            Ok(RustFile {
+                module,
+                path: file.display().to_string(),
+                source: read_import(&file)?,
+            })
+        })
+        .collect()
+}
 
-        println!(
-            "{}: {}[{}]: {}",
-            location, severity_str, issue.code, issue.message
-        );
+/// Read a JSON or YAML document.
+fn read_document(file: &Path, content: &str, yaml: bool) -> Result<serde_json::Value, CliError> {
+    let value = if yaml {
+        let yaml: serde_yaml::Value = serde_yaml::from_str(content)
+            .map_err(|e| report::parse_error(format!("YAML error in {}: {}", file.display(), e)))?;
+        serde_json::to_value(yaml)
+            .map_err(|e| report::parse_error(format!("YAML error in {}: {}", file.display(), e)))?
+    } else {
+        serde_json::from_str(content)
+            .map_err(|e| report::parse_error(format!("JSON error in {}: {}", file.display(), e)))?
+    };
+    Ok(value)
+}
 
-        if verbosity == Verbosity::Verbose {
-            if let Some(ref suggestion) = issue.suggestion {
-                println!("  {} {}", "suggestion:".cyan(), suggestion);
-            }
-        }
-    }
+/// What `migrate` changes in a file
+#[derive(Serialize)]
+struct FileMigration {
+    /// The file
+    path: String,
+
+    /// Lines of source rewritten
+    changes: Vec<SourceChange>,
+
+    /// Version of the export format the file was in, for exports
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format_version: Option<u64>,
 }
 
-fn cmd_codegen(
-    file: &PathBuf,
-    target: &str,
-    output: Option<PathBuf>,
-    verbosity: Verbosity,
-) -> Result<(), String> {
-    if verbosity != Verbosity::Quiet {
-        println!(
-            "{} {} -> {}",
-            "Generating".cyan().bold(),
-            file.display(),
-            target
-        );
+impl FileMigration {
+    /// Check whether the file needs migrating.
+    fn is_pending(&self) -> bool {
+        !self.changes.is_empty()
+            || self
+                .format_version
+                .is_some_and(|version| version != u64::from(MODEL_FORMAT_VERSION))
     }
+}
 
-    // Read and parse file
-    let source =
-        std::fs::read_to_string(file).map_err(|e| format!("Failed to read file: {}", e))?;
-    let ast = parse_file(&source).map_err(|e| format!("Parse error: {}", e))?;
-    let transform_result = transform(&ast).map_err(|e| format!("Transform error: {}", e))?;
+/// Migrate a file, returning what changes and its migrated content, if
+/// any changes.
+fn migrate_file(path: &Path) -> Result<(FileMigration, Option<String>), CliError> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| report::io_error(format!("Failed to read {}: {}", path.display(), e)))?;
+    let mut migration = FileMigration {
+        path: path.display().to_string(),
+        changes: Vec::new(),
+        format_version: None,
+    };
 
-    // Parse target language
-    let target_enum: Target = target
-        .parse()
-        .map_err(|_| format!("Unknown target language: {}. Supported: rust, typescript, kotlin, python, java, clojure, haskell", target))?;
+    let yaml = is_yaml(path);
+    if yaml || path.extension().is_some_and(|ext| ext == "json") {
+        let value: serde_json::Value = if yaml {
+            serde_yaml::from_str(&content).map_err(|e| {
+                report::parse_error(format!("YAML error in {}: {}", path.display(), e))
+            })?
+        } else {
+            serde_json::from_str(&content).map_err(|e| {
+                report::parse_error(format!("JSON error in {}: {}", path.display(), e))
+            })?
+        };
+        migration.format_version = value.get("version").and_then(|v| v.as_u64());
+        let document = ModelDocument::from_value(value)
+            .map_err(|e| format!("Cannot migrate {}: {}", path.display(), e))?;
+        if !migration.is_pending() {
+            return Ok((migration, None));
+        }
+        let migrated = if yaml {
+            serde_yaml::to_string(&document.to_value())
+                .map_err(|e| report::internal_error(format!("YAML serialization error: {}", e)))?
+        } else {
+            serde_json::to_string_pretty(&document.to_value())
+                .map_err(|e| report::internal_error(format!("JSON serialization error: {}", e)))?
+        };
+        return Ok((migration, Some(migrated)));
+    }
 
-    // Generate code for each context
-    for context in &transform_result.contexts {
-        let code = sketchddd_codegen::generate(context, target_enum)
-            .map_err(|e| format!("Code generation error: {}", e))?;
+    let migrated = migrate_source(&content);
+    if migrated.is_unchanged() {
+        return Ok((migration, None));
+    }
+    // Only write a file that parses now
+    parse_file(&migrated.source)
+        .map_err(|e| format!("{} does not parse once migrated: {}", path.display(), e))?;
+    migration.changes = migrated.changes;
+    Ok((migration, Some(migrated.source)))
+}
 
-        // Determine output path
-        let output_path = match &output {
-            Some(dir) if dir.is_dir() => {
-                let ext = match target_enum {
-                    Target::Rust => "rs",
-                    Target::TypeScript => "ts",
-                    Target::Kotlin => "kt",
-                    Target::Python => "py",
-                    Target::Java => "java",
-                    Target::Clojure => "clj",
-                    Target::Haskell => "hs",
-                };
-                dir.join(format!("{}.{}", to_snake_case(context.name()), ext))
+fn cmd_migrate(file: Option<PathBuf>, check: bool, json: bool) -> Result<(), CliError> {
+    let paths = match resolve_model(file)? {
+        ModelSource::File(path) => vec![path],
+        ModelSource::Project(project) => project.files()?,
+    };
+
+    let mut migrations = Vec::new();
+    let mut artifacts = Vec::new();
+    for path in &paths {
+        let (migration, migrated) = migrate_file(path)?;
+        if let (Some(migrated), false) = (migrated, check) {
+            std::fs::write(path, migrated).map_err(|e| {
+                report::io_error(format!("Failed to write {}: {}", path.display(), e))
+            })?;
+            artifacts.push(Artifact::written("file", path));
+        }
+        migrations.push(migration);
+    }
+    let pending = migrations.iter().filter(|m| m.is_pending()).count();
+    let failure = check && pending > 0;
+
+    if json {
+        let mut output = Output::new("migrate").with_field("files", &migrations);
+        output.artifacts = artifacts;
+        if failure {
+            output.status = Status::Failure;
+        }
+        output.print()?;
+    } else {
+        for migration in &migrations {
+            report::heading("Migrating", &migration.path);
+            if let Some(version) = migration.format_version {
+                if version != u64::from(MODEL_FORMAT_VERSION) {
+                    report::status(format!(
+                        "  format version {} -> {}",
+                        version, MODEL_FORMAT_VERSION
+                    ));
+                }
             }
-            Some(path) => path.clone(),
-            None => {
-                // Output to stdout
-                println!("{}", code);
-                continue;
+            for change in &migration.changes {
+                report::status(format!("  line {}: {}", change.line, change.description));
+                report::status(format!("    {} {}", "-".red(), change.before));
+                report::status(format!("    {} {}", "+".green(), change.after));
             }
-        };
+            if !migration.is_pending() {
+                report::status(format!("  {} Up to date", "✓".green().bold()));
+            } else if !check {
+                report::status(format!("  {} Migrated", "✓".green().bold()));
+            }
+        }
+    }
 
-        std::fs::write(&output_path, &code)
-            .map_err(|e| format!("Failed to write output: {}", e))?;
+    if failure {
+        Err(format!("{} file(s) need migrating; run sketchddd migrate", pending).into())
+    } else {
+        Ok(())
+    }
+}
 
-        if verbosity != Verbosity::Quiet {
-            println!(
-                "  {} Generated {}",
-                "✓".green().bold(),
-                output_path.display()
-            );
+/// Build the declaration of an element to add, with where to add it.
+fn add_declaration(element: AddCommands) -> Result<(Declaration, AddTarget), CliError> {
+    let fields = |fields: Vec<String>| -> Result<_, CliError> {
+        fields
+            .iter()
+            .map(|field| parse_field(field).map_err(|e| report::usage_error(e.to_string())))
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let declaration = match element {
+        AddCommands::Entity {
+            name,
+            target,
+            fields: list,
+        } => {
+            let mut entity = EntityDecl::new(name);
+            entity.fields = fields(list)?;
+            (Declaration::Entity(entity), target)
         }
-    }
+        AddCommands::Value {
+            name,
+            target,
+            fields: list,
+        } => {
+            let mut value = ValueObjectDecl::new(name);
+            value.fields = fields(list)?;
+            (Declaration::Value(value), target)
+        }
+        AddCommands::Enum {
+            name,
+            target,
+            variants,
+        } => {
+            let mut enum_decl = EnumDecl::new(name);
+            enum_decl.variants = variants
+                .iter()
+                .map(|v| VariantDecl::new(v.trim()))
+                .collect();
+            (Declaration::Enum(enum_decl), target)
+        }
+        AddCommands::Aggregate {
+            name,
+            target,
+            root,
+            contains,
+        } => {
+            let mut aggregate = AggregateDecl::new(&name);
+            aggregate.root = Some(root.unwrap_or(name));
+            aggregate.contains = contains.iter().map(|c| c.trim().to_string()).collect();
+            (Declaration::Aggregate(aggregate), target)
+        }
+        AddCommands::Morphism {
+            name,
+            target,
+            from,
+            to,
+        } => {
+            let source = parse_type(&from).map_err(|e| e.to_string())?;
+            let target_type = parse_type(&to).map_err(|e| e.to_string())?;
+            (
+                Declaration::Morphism(MorphismDecl::new(name, source, target_type)),
+                target,
+            )
+        }
+    };
+    Ok(declaration)
+}
+
+fn cmd_add(element: AddCommands, json: bool) -> Result<(), CliError> {
+    let (declaration, target) = add_declaration(element)?;
+    let model = resolve_model(target.file)?;
+    let sources = model.read()?;
+    let ast = sources.parse()?;
+
+    let names: Vec<&str> = ast.contexts.iter().map(|c| c.name.as_str()).collect();
+    let context = match (target.context, names.as_slice()) {
+        (Some(name), _) if names.contains(&name.as_str()) => name,
+        (Some(name), _) => {
+            let mut message = format!("Context '{}' not found in {}", name, model.path().display());
+            if let Some(suggestion) = did_you_mean(&name, &names) {
+                message.push_str(&format!("; {}", suggestion));
+            }
+            return Err(message.into());
+        }
+        (None, [only]) => only.to_string(),
+        (None, []) => return Err(format!("{} has no context", model.path().display()).into()),
+        (None, _) => {
+            return Err(format!(
+                "{} has {} contexts; choose one with --context",
+                model.path().display(),
+                names.len()
+            )
+            .into())
+        }
+    };
 
+    // Edit the file declaring the context
+    let mut files: Vec<(PathBuf, String)> = sources
+        .files()
+        .map(|(path, source)| (path.to_path_buf(), source.to_string()))
+        .collect();
+    let declares = |source: &str| {
+        parse_file(source).is_ok_and(|file| file.contexts.iter().any(|c| c.name == context))
+    };
+    let index = files
+        .iter()
+        .position(|(_, source)| declares(source))
+        .ok_or_else(|| {
+            format!(
+                "No file of {} declares context '{}'",
+                model.path().display(),
+                context
+            )
+        })?;
+    let path = files[index].0.clone();
+    let mut file = parse_file(&files[index].1)
+        .map_err(|e| report::parse_error(format!("Parse error in {}: {}", path.display(), e)))?;
+    let kind = declaration.kind();
+    let name = declaration.name().to_string();
+    file.contexts
+        .iter_mut()
+        .find(|c| c.name == context)
+        .expect("the file declares the context")
+        .add_declaration(declaration)
+        .map_err(|e| e.to_string())?;
+    let source = file.pretty_print();
+
+    // Only write a model that still loads
+    files[index].1 = source.clone();
+    transform(&Sources::join(files).parse()?)
+        .map_err(|e| format!("Cannot add {} '{}': {}", kind, name, e))?;
+    std::fs::write(&path, &source)
+        .map_err(|e| report::io_error(format!("Failed to write {}: {}", path.display(), e)))?;
+
+    if json {
+        let mut output = Output::new("add")
+            .with_field("context", &context)
+            .with_field("kind", kind)
+            .with_field("name", &name);
+        output.artifacts.push(Artifact::written("file", &path));
+        return output.print();
+    }
+    report::success(format!(
+        "Added {} {} to {} in {}",
+        kind,
+        name,
+        context,
+        path.display()
+    ));
     Ok(())
 }
 
-fn cmd_viz(
-    file: &PathBuf,
+fn cmd_diff(
+    old: &Path,
+    new: &Path,
     format: &str,
-    output: Option<PathBuf>,
-    verbosity: Verbosity,
-) -> Result<(), String> {
-    if verbosity != Verbosity::Quiet {
-        println!(
-            "{} {} -> {}",
-            "Visualizing".cyan().bold(),
-            file.display(),
+    fail_on: Option<Compatibility>,
+) -> Result<(), CliError> {
+    if !matches!(format, "pretty" | "json") {
+        return Err(report::usage_error(format!(
+            "Unknown diff format '{}'; expected pretty or json",
             format
-        );
+        )));
     }
+    report::heading(
+        "Comparing",
+        format!("{} vs {}", old.display(), new.display()),
+    );
 
-    // Read and parse file
-    let source =
-        std::fs::read_to_string(file).map_err(|e| format!("Failed to read file: {}", e))?;
-    let ast = parse_file(&source).map_err(|e| format!("Parse error: {}", e))?;
-    let transform_result = transform(&ast).map_err(|e| format!("Transform error: {}", e))?;
-
-    // Generate visualization for each context
-    for context in &transform_result.contexts {
-        let viz = match format {
-            "graphviz" | "dot" => sketchddd_viz::graphviz::generate(context)
-                .map_err(|e| format!("Visualization error: {}", e))?,
-            "mermaid" | "md" => sketchddd_viz::mermaid::generate(context)
-                .map_err(|e| format!("Visualization error: {}", e))?,
-            _ => return Err(format!("Unknown visualization format: {}. Supported: graphviz, mermaid", format)),
-        };
+    let old_model = load_workspace(&ModelSource::File(old.to_path_buf()))?;
+    let new_model = load_workspace(&ModelSource::File(new.to_path_buf()))?;
+    let diff = diff_models(&old_model.contexts, &new_model.contexts);
+    let failure = match (fail_on, diff.max_severity()) {
+        (Some(threshold), Some(severity)) if severity >= threshold => Some(format!(
+            "Found {} changes (--fail-on {})",
+            severity, threshold
+        )),
+        _ => None,
+    };
 
-        match &output {
-            Some(path) => {
-                std::fs::write(path, &viz)
-                    .map_err(|e| format!("Failed to write output: {}", e))?;
-                if verbosity != Verbosity::Quiet {
-                    println!("  {} Generated {}", "✓".green().bold(), path.display());
-                }
-            }
-            None => {
-                println!("{}", viz);
-            }
+    if format == "json" {
+        let mut output = Output::new("diff")
+            .with_field("old", old.display().to_string())
+            .with_field("new", new.display().to_string())
+            .with_field("changes", &diff.changes)
+            .with_field("version_warnings", &diff.version_warnings)
+            .with_field(
+                "summary",
+                serde_json::json!({
+                    "changes": diff.changes.len(),
+                    "breaking": diff.breaking_changes().count(),
+                    "max_severity": diff.max_severity(),
+                }),
+            );
+        if failure.is_some() {
+            output.status = Status::Failure;
         }
+        output.print()?;
+    } else {
+        print_changes(&diff);
     }
 
-    Ok(())
-}
+    if diff.is_empty() {
+        report::success("No changes");
+    } else {
+        let breaking = diff.breaking_changes().count();
+        let summary = format!("{} change(s), {} breaking", diff.changes.len(), breaking);
+        if breaking == 0 {
+            report::success(summary);
+        } else {
+            report::warning(summary);
+        }
+    }
 
-fn cmd_init(name: &str, template: &str, verbosity: Verbosity) -> Result<(), String> {
-    if verbosity != Verbosity::Quiet {
-        println!(
-            "{} {} (template: {})",
-            "Initializing".cyan().bold(),
-            name,
-            template
-        );
+    match failure {
+        Some(message) => Err(message.into()),
+        None => Ok(()),
     }
+}
 
-    // Create directory
-    std::fs::create_dir_all(name).map_err(|e| format!("Failed to create directory: {}", e))?;
+fn cmd_merge_driver(base: &Path, ours: &Path, theirs: &Path) -> Result<(), CliError> {
+    report::heading("Merging", ours.display());
 
-    // Get template content
-    let (content, description) = match template {
-        "ecommerce" => (get_ecommerce_template(name), "e-commerce domain"),
-        "microservices" => (get_microservices_template(name), "microservices architecture"),
-        _ => (get_minimal_template(name), "minimal project"),
+    let parse = |path: &Path| {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| report::io_error(format!("Failed to read {}: {}", path.display(), e)))?;
+        parse_file(&source)
+            .map_err(|e| report::parse_error(format!("Parse error in {}: {}", path.display(), e)))
     };
+    let merged = merge::merge(&parse(base)?, &parse(ours)?, &parse(theirs)?);
 
-    // Create the main .sddd file
-    let filename = format!("{}/{}.sddd", name, name.to_lowercase());
-    std::fs::write(&filename, content).map_err(|e| format!("Failed to write file: {}", e))?;
-
-    // Create a .gitignore
-    let gitignore = r#"# Generated files
-/generated/
-*.gen.*
-
-# Editor files
-.vscode/
-.idea/
-*.swp
-*.swo
-
-# OS files
-.DS_Store
-Thumbs.db
-"#;
-    std::fs::write(format!("{}/.gitignore", name), gitignore)
-        .map_err(|e| format!("Failed to write .gitignore: {}", e))?;
-
-    if verbosity != Verbosity::Quiet {
-        println!("{} Created {}/", "✓".green().bold(), name);
-        println!("  {} {}.sddd ({} template)", "→".blue(), name.to_lowercase(), description);
-        println!("  {} .gitignore", "→".blue());
-        println!();
-        println!("Next steps:");
-        println!(
-            "  {} {}",
-            "cd".cyan(),
-            name
-        );
-        println!(
-            "  {} check {}.sddd",
-            "sketchddd".cyan(),
-            name.to_lowercase()
-        );
+    std::fs::write(ours, &merged.source)
+        .map_err(|e| report::io_error(format!("Failed to write {}: {}", ours.display(), e)))?;
+    if merged.conflicts.is_empty() {
+        report::success("Merged without conflicts");
+        return Ok(());
     }
-
-    Ok(())
-}
-
-/// Minimal template for new projects
-fn get_minimal_template(name: &str) -> String {
-    format!(
-        r#"// {name} Domain Model
-// Created with SketchDDD
-// Documentation: https://sketchddd.dev
-
-context {name} {{
-    // Define your domain objects
-    objects {{
-        // Add objects here, e.g.: Customer, Order, Product
-    }}
-
-    // Define relationships between objects
-    morphisms {{
-        // Add morphisms here, e.g.: placedBy: Order -> Customer
-    }}
-
-    // Define entities with identity
-    // entity Customer {{
-    //     id: UUID
-    //     name: String
-    // }}
-
-    // Define value objects
-    // value Money {{
-    //     amount: Decimal
-    //     currency: Currency
-    // }}
-
-    // Define aggregates
-    // aggregate OrderAggregate {{
-    //     root: Order
-    //     contains: [LineItem]
-    // }}
-
-    // Define enumerations
-    // enum Status = Active | Inactive | Pending
-}}
-"#,
-        name = name
+    for conflict in &merged.conflicts {
+        report::warning(format!("Conflict in {}", conflict));
+    }
+    Err(format!(
+        "{} conflict(s) left to resolve in {}",
+        merged.conflicts.len(),
+        ours.display()
     )
+    .into())
 }
 
-/// E-commerce template
-fn get_ecommerce_template(name: &str) -> String {
-    format!(
-        r#"// {name} - E-Commerce Domain Model
-// Created with SketchDDD
-
-context {name} {{
-    // Core domain objects
-    objects {{
-        Product,
-        Category,
-        Inventory
-    }}
-
-    // Entities with identity
-    entity Customer {{
-        id: UUID
-        email: Email
-        name: String
-    }}
-
-    entity Order {{
-        id: UUID
-        orderNumber: String
-        placedAt: DateTime
-    }}
-
-    entity LineItem {{
-        id: UUID
-        quantity: Integer
-    }}
-
-    // Value objects (immutable)
-    value Money {{
-        amount: Decimal
-        currency: Currency
-    }}
-
-    value Address {{
-        street: String
-        city: String
-        country: String
-        postalCode: String
-    }}
-
-    // Relationships
-    morphisms {{
-        placedBy: Order -> Customer
-        items: Order -> List<LineItem>
-        product: LineItem -> Product
-        unitPrice: LineItem -> Money
-        shippingAddress: Order -> Address
-        billingAddress: Order -> Address?
-        belongsTo: Product -> Category
-    }}
-
-    // Aggregates (consistency boundaries)
-    aggregate OrderAggregate {{
-        root: Order
-        contains: [LineItem]
-        invariant: totalItems = sum(items.quantity)
-    }}
-
-    // Enumerations
-    enum OrderStatus = Draft | Pending | Confirmed | Shipped | Delivered | Cancelled
-
-    enum PaymentStatus = Pending | Authorized | Captured | Refunded | Failed
-}}
-"#,
-        name = name
-    )
-}
+/// Print the changes between two models by context, and warnings about
+/// their versions on stderr.
+fn print_changes(diff: &ModelDiff) {
+    for (context, changes) in diff.by_context() {
+        report::output(context.bold());
+        for change in changes {
+            let (marker, element) = match change.kind {
+                ChangeKind::Added => ("+".green().bold(), change.element.green()),
+                ChangeKind::Removed => ("-".red().bold(), change.element.red()),
+                ChangeKind::Changed => ("~".yellow().bold(), change.element.yellow()),
+            };
+            let label = match change.compatibility {
+                Compatibility::Breaking => "breaking".red().bold(),
+                Compatibility::Compatible => "compatible".green(),
+                Compatibility::Cosmetic => "cosmetic".dimmed(),
+            };
+            let detail = change
+                .detail
+                .as_ref()
+                .map(|d| format!(" ({})", d))
+                .unwrap_or_default();
+            report::output(format!(
+                "  {} {} {}{} [{}]",
+                marker, element, change.kind, detail, label
+            ));
+        }
+    }
 
-/// Microservices template with multiple contexts
-fn get_microservices_template(name: &str) -> String {
-    format!(
-        r#"// {name} - Microservices Domain Model
-// Created with SketchDDD
-// This template demonstrates multiple bounded contexts and context maps
-
-// ============================================
-// Orders Context
-// ============================================
-context Orders {{
-    entity Order {{
-        id: UUID
-        customerId: UUID
-        status: OrderStatus
-    }}
-
-    entity LineItem {{
-        id: UUID
-        productId: UUID
-        quantity: Integer
-    }}
-
-    value Money {{
-        amount: Decimal
-        currency: Currency
-    }}
-
-    morphisms {{
-        items: Order -> List<LineItem>
-        total: Order -> Money
-    }}
-
-    aggregate OrderAggregate {{
-        root: Order
-        contains: [LineItem]
-    }}
-
-    enum OrderStatus = Created | Confirmed | Fulfilled | Cancelled
-}}
-
-// ============================================
-// Inventory Context
-// ============================================
-context Inventory {{
-    entity StockItem {{
-        id: UUID
-        productId: UUID
-        quantity: Integer
-        warehouseId: UUID
-    }}
-
-    entity Warehouse {{
-        id: UUID
-        name: String
-        location: String
-    }}
-
-    morphisms {{
-        storedIn: StockItem -> Warehouse
-    }}
-
-    aggregate WarehouseAggregate {{
-        root: Warehouse
-        contains: [StockItem]
-    }}
-}}
-
-// ============================================
-// Shipping Context
-// ============================================
-context Shipping {{
-    entity Shipment {{
-        id: UUID
-        orderId: UUID
-        trackingNumber: String
-    }}
-
-    entity Carrier {{
-        id: UUID
-        name: String
-    }}
-
-    value Address {{
-        street: String
-        city: String
-        country: String
-    }}
-
-    morphisms {{
-        destination: Shipment -> Address
-        carrier: Shipment -> Carrier
-    }}
-
-    enum ShipmentStatus = Pending | InTransit | Delivered | Returned
-}}
-
-// ============================================
-// Context Maps (Integration Patterns)
-// ============================================
-
-// Orders publishes events that Inventory consumes
-map OrdersToInventory: Orders -> Inventory {{
-    pattern: CustomerSupplier
-    mappings {{
-        Order -> StockItem
-    }}
-}}
-
-// Orders publishes events that Shipping consumes
-map OrdersToShipping: Orders -> Shipping {{
-    pattern: CustomerSupplier
-    mappings {{
-        Order -> Shipment
-    }}
-}}
-"#,
-        name = name
-    )
+    for warning in &diff.version_warnings {
+        report::warning(warning);
+    }
 }
 
-fn cmd_serve(port: u16, verbosity: Verbosity) -> Result<(), String> {
-    if verbosity != Verbosity::Quiet {
-        println!(
-            "{} Visual builder at http://localhost:{}",
-            "Starting".cyan().bold(),
-            port
-        );
+fn cmd_ci(
+    model: &ModelSource,
+    base: Option<&str>,
+    format: Option<&str>,
+    max_warnings: Option<usize>,
+    fail_on: Option<&str>,
+) -> Result<(), CliError> {
+    let format = match format {
+        Some(format) => format,
+        None if std::env::var("GITHUB_ACTIONS").is_ok_and(|v| v == "true") => "github",
+        None => "pretty",
+    };
+    if !matches!(format, "pretty" | "github" | "json") {
+        return Err(report::usage_error(format!(
+            "Unknown ci format '{}'; expected pretty, github or json",
+            format
+        )));
     }
-    println!("{} Server not yet implemented", "⚠".yellow().bold());
-    Ok(())
-}
+    let github = format == "github";
+    let file = model.path();
 
-fn cmd_export(file: &PathBuf, output: Option<PathBuf>, verbosity: Verbosity) -> Result<(), String> {
-    if verbosity != Verbosity::Quiet {
-        println!("{} {}", "Exporting".cyan().bold(), file.display());
+    let mut gates = ci::Gates::load(file)?;
+    if let Some(max_warnings) = max_warnings {
+        gates.max_warnings = Some(max_warnings);
+    }
+    if let Some(fail_on) = fail_on {
+        gates.fail_on = ci::parse_fail_on(fail_on).map_err(report::usage_error)?;
     }
 
-    // Read and parse the source file
-    let source =
-        std::fs::read_to_string(file).map_err(|e| format!("Failed to read file: {}", e))?;
-    let ast = parse_file(&source).map_err(|e| format!("Parse error: {}", e))?;
-    let transform_result = transform(&ast).map_err(|e| format!("Transform error: {}", e))?;
+    if format == "pretty" {
+        report::heading("Running CI on", file.display());
+    }
 
-    // Serialize to JSON
-    let json_output = serde_json::json!({
-        "contexts": transform_result.contexts.iter().map(|ctx| {
-            serde_json::json!({
-                "name": ctx.name(),
-                "entities": ctx.entities().len(),
-                "valueObjects": ctx.value_objects().len(),
-                "aggregates": ctx.aggregate_roots().len(),
-            })
-        }).collect::<Vec<_>>(),
-        "contextMaps": transform_result.context_maps.iter().map(|map| {
-            serde_json::json!({
-                "name": map.name(),
-                "source": map.source_context(),
-                "target": map.target_context(),
-                "pattern": format!("{:?}", map.pattern()),
-            })
-        }).collect::<Vec<_>>(),
+    let loaded = lint_model(model).and_then(|linted| {
+        let diff = match base {
+            Some(base) => {
+                let old = ci::load_base(base, model)?;
+                Some(diff_models(&old.contexts, &linted.workspace.contexts))
+            }
+            None => None,
+        };
+        Ok((linted, diff))
     });
+    let (linted, diff) = match loaded {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            if github {
+                report::output(ci::annotation(
+                    "error",
+                    None,
+                    None,
+                    None,
+                    "SketchDDD",
+                    &e.to_string(),
+                ));
+            }
+            return Err(e);
+        }
+    };
+    let mut result = linted.result;
+    ModelBaseline::load(file)?.remove_known(&mut result);
+    // The gates count the transform warnings printed while loading too
+    let mut reported = result.clone();
+    reported.issues.splice(0..0, linted.transform_issues);
+    let coverage = linted.workspace.context_map_coverage().percent();
+    let outcomes = gates.check(&reported, coverage, diff.as_ref());
+    let failed = outcomes.iter().filter(|gate| !gate.passed).count();
 
-    let json_str = serde_json::to_string_pretty(&json_output)
-        .map_err(|e| format!("JSON serialization error: {}", e))?;
-
-    // Write to output file or stdout
-    match output {
-        Some(path) => {
-            std::fs::write(&path, &json_str)
-                .map_err(|e| format!("Failed to write output: {}", e))?;
-            if verbosity != Verbosity::Quiet {
-                println!("{} Exported to {}", "✓".green().bold(), path.display());
+    match format {
+        "json" => {
+            let report = reported.to_json_report(Some(&file.display().to_string()));
+            let mut output = Output::new("ci")
+                .with_field("file", report.file)
+                .with_field("summary", report.summary)
+                .with_field("gates", &outcomes);
+            if let Some(diff) = &diff {
+                output = output
+                    .with_field("changes", &diff.changes)
+                    .with_field("version_warnings", &diff.version_warnings);
             }
+            if failed > 0 {
+                output.status = Status::Failure;
+            }
+            output.diagnostics = report.issues;
+            output.print()?;
         }
-        None => {
-            println!("{}", json_str);
+        "github" => {
+            for issue in &reported.issues {
+                let level = match issue.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                    Severity::Hint => "notice",
+                };
+                let issue_file = issue
+                    .location
+                    .file
+                    .clone()
+                    .unwrap_or_else(|| file.display().to_string());
+                report::output(ci::annotation(
+                    level,
+                    Some(&issue_file),
+                    issue.location.line,
+                    issue.location.column,
+                    &issue.code,
+                    &issue.message,
+                ));
+            }
+            for change in diff.iter().flat_map(|diff| &diff.changes) {
+                let level = if ci::failing(gates.fail_on, Some(change.compatibility)) {
+                    "error"
+                } else {
+                    "notice"
+                };
+                let title = format!("{} change", change.compatibility);
+                report::output(ci::annotation(
+                    level,
+                    None,
+                    None,
+                    None,
+                    &title,
+                    &change.to_string(),
+                ));
+            }
+            for warning in diff.iter().flat_map(|diff| &diff.version_warnings) {
+                report::output(ci::annotation(
+                    "warning", None, None, None, "Version", warning,
+                ));
+            }
+            for gate in outcomes.iter().filter(|gate| !gate.passed) {
+                let message = format!("{}: {}", gate.name, gate.detail);
+                report::output(ci::annotation(
+                    "error",
+                    None,
+                    None,
+                    None,
+                    "Quality gate",
+                    &message,
+                ));
+            }
+        }
+        _ => {
+            print_validation_issues(file, &result.issues);
+            if let Some(diff) = &diff {
+                print_changes(diff);
+            }
         }
     }
 
-    Ok(())
+    report::status("Quality gates".cyan().bold());
+    for gate in &outcomes {
+        let mark = if gate.passed {
+            "✓".green().bold()
+        } else {
+            "✗".red().bold()
+        };
+        report::status(format!("  {} {}: {}", mark, gate.name, gate.detail));
+    }
+
+    if failed > 0 {
+        Err(format!("{} of {} quality gate(s) failed", failed, outcomes.len()).into())
+    } else {
+        Ok(())
+    }
 }
 
-fn cmd_import(file: &PathBuf, output: Option<PathBuf>, verbosity: Verbosity) -> Result<(), String> {
-    if verbosity != Verbosity::Quiet {
-        println!("{} {}", "Importing".cyan().bold(), file.display());
+fn cmd_query(
+    model: &ModelSource,
+    query: &str,
+    context: Option<&str>,
+    json: bool,
+) -> Result<(), CliError> {
+    let statement: Statement = query.parse()?;
+    let workspace = load_workspace(model)?;
+    let contexts = select_contexts(&workspace, context, model.path())?;
+    let table = query::run(&statement, &workspace, &contexts)?;
+
+    if json {
+        return Output::new("query")
+            .with_field("query", query.trim())
+            .with_field("columns", &table.columns)
+            .with_field("rows", table.records())
+            .print();
     }
-    let _ = output;
-    println!("{} Import not yet implemented", "⚠".yellow().bold());
+
+    if table.rows.is_empty() {
+        report::status(format!("No results for '{}'", query.trim()));
+        return Ok(());
+    }
+    table.print();
+    report::detail("");
+    report::detail(format!("{} row(s)", table.rows.len()));
     Ok(())
 }
 
-fn cmd_diff(old: &PathBuf, new: &PathBuf, verbosity: Verbosity) -> Result<(), String> {
-    if verbosity != Verbosity::Quiet {
-        println!(
-            "{} {} vs {}",
-            "Comparing".cyan().bold(),
-            old.display(),
-            new.display()
-        );
+/// Parse and transform a model into a workspace.
+fn load_workspace(model: &ModelSource) -> Result<Workspace, CliError> {
+    let sources = model.read()?;
+    if let Some(workspace) = cache::load(&sources.text) {
+        tracing::debug!(contexts = workspace.contexts.len(), "loaded from the cache");
+        return Ok(workspace);
     }
-    println!("{} Diff not yet implemented", "⚠".yellow().bold());
-    Ok(())
+    let ast = sources.parse()?;
+    tracing::debug!(contexts = ast.contexts.len(), "parsed");
+    let result = transform(&ast)
+        .map_err(|e| format!("Transform error in {}: {}", model.path().display(), e))?;
+    tracing::debug!(warnings = result.warnings.len(), "transformed");
+    let workspace = result.into_workspace();
+    cache::store(&sources.text, &workspace);
+    Ok(workspace)
 }
 
 /// Handle template subcommands
-fn cmd_template(cmd: TemplateCommands, verbosity: Verbosity) -> Result<(), String> {
+fn cmd_template(cmd: TemplateCommands) -> Result<(), CliError> {
     match cmd {
-        TemplateCommands::List { remote } => cmd_template_list(remote, verbosity),
-        TemplateCommands::Info { name } => cmd_template_info(&name, verbosity),
-        TemplateCommands::Validate { path } => cmd_template_validate(&path, verbosity),
-        TemplateCommands::Install { source, force } => cmd_template_install(&source, force, verbosity),
-        TemplateCommands::UpdateTemplate { name } => cmd_template_update(&name, verbosity),
-        TemplateCommands::Remove { name, force } => cmd_template_remove(&name, force, verbosity),
-        TemplateCommands::Create { name, source, output } => cmd_template_create(&name, source, output, verbosity),
+        TemplateCommands::List { remote } => cmd_template_list(remote),
+        TemplateCommands::Info { name } => cmd_template_info(&name),
+        TemplateCommands::Validate { path } => cmd_template_validate(&path),
+        TemplateCommands::Install { source, force } => cmd_template_install(&source, force),
+        TemplateCommands::UpdateTemplate { name } => cmd_template_update(&name),
+        TemplateCommands::Remove { name, force } => cmd_template_remove(&name, force),
+        TemplateCommands::Create {
+            name,
+            source,
+            output,
+        } => cmd_template_create(&name, source, output),
     }
 }
 
 /// Get templates directory
-fn get_templates_dir() -> Result<PathBuf, String> {
+fn get_templates_dir() -> Result<PathBuf, CliError> {
     let home = dirs::home_dir().ok_or("Could not find home directory")?;
     let templates_dir = home.join(".sketchddd").join("templates");
     Ok(templates_dir)
 }
 
 /// List available templates
-fn cmd_template_list(remote: bool, verbosity: Verbosity) -> Result<(), String> {
-    if verbosity != Verbosity::Quiet {
-        println!("{}", "Available Templates".cyan().bold());
-        println!();
-    }
+fn cmd_template_list(remote: bool) -> Result<(), CliError> {
+    report::status("Available Templates".cyan().bold());
+    report::status("");
 
     // Built-in templates
-    println!("{}", "Built-in:".blue().bold());
-    println!("  {} - Empty project with example comments", "minimal".green());
-    println!("  {} - E-commerce domain with orders, products, customers", "ecommerce".green());
-    println!("  {} - Multi-context architecture with context maps", "microservices".green());
-    println!();
+    report::status("Built-in:".blue().bold());
+    for template in scaffold::TEMPLATES {
+        match template.aliases {
+            [] => report::output(format!(
+                "  {} - {}",
+                template.name.green(),
+                template.contents[0]
+            )),
+            aliases => report::output(format!(
+                "  {} ({}) - {}",
+                template.name.green(),
+                aliases.join(", "),
+                template.contents[0]
+            )),
+        }
+    }
+    report::status("");
 
     // Installed templates
     let templates_dir = get_templates_dir()?;
     if templates_dir.exists() {
         let entries: Vec<_> = std::fs::read_dir(&templates_dir)
-            .map_err(|e| format!("Failed to read templates directory: {}", e))?
+            .map_err(|e| report::io_error(format!("Failed to read templates directory: {}", e)))?
             .filter_map(|e| e.ok())
             .filter(|e| e.path().is_dir())
             .collect();
 
         if !entries.is_empty() {
-            println!("{}", "Installed:".blue().bold());
+            report::status("Installed:".blue().bold());
             for entry in entries {
                 let name = entry.file_name().to_string_lossy().to_string();
                 let desc = read_template_description(&entry.path()).unwrap_or_default();
                 if desc.is_empty() {
-                    println!("  {}", name.green());
+                    report::output(format!("  {}", name.green()));
                 } else {
-                    println!("  {} - {}", name.green(), desc);
+                    report::output(format!("  {} - {}", name.green(), desc));
                 }
             }
-            println!();
+            report::status("");
         }
     }
 
     // Remote templates (if --remote flag is set)
     if remote {
-        println!("{}", "Remote Registry:".blue().bold());
-        println!("  {} Fetching from registry...", "→".blue());
+        report::status("Remote Registry:".blue().bold());
+        report::status(format!("  {} Fetching from registry...", "→".blue()));
         // TODO: Implement actual registry fetch
-        println!("  {} Registry not yet available", "⚠".yellow());
-        println!();
+        report::status(format!("  {} Registry not yet available", "⚠".yellow()));
+        report::status("");
     }
 
     Ok(())
 }
 
 /// Read template description from manifest
-fn read_template_description(path: &PathBuf) -> Option<String> {
+fn read_template_description(path: &Path) -> Option<String> {
     let manifest = path.join("template.json");
     if manifest.exists() {
         if let Ok(content) = std::fs::read_to_string(&manifest) {
             if let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) {
-                return json.get("description").and_then(|d| d.as_str()).map(|s| s.to_string());
+                return json
+                    .get("description")
+                    .and_then(|d| d.as_str())
+                    .map(|s| s.to_string());
             }
         }
     }
@@ -1042,47 +2995,29 @@ fn read_template_description(path: &PathBuf) -> Option<String> {
 }
 
 /// Show template info
-fn cmd_template_info(name: &str, verbosity: Verbosity) -> Result<(), String> {
-    let _ = verbosity;
-
+fn cmd_template_info(name: &str) -> Result<(), CliError> {
     // Check built-in templates
-    match name {
-        "minimal" => {
-            println!("{}", "Template: minimal".cyan().bold());
-            println!();
-            println!("{}: Built-in", "Source".blue());
-            println!("{}: Empty project with commented examples", "Description".blue());
-            println!("{}: Single bounded context structure", "Contents".blue());
-            println!();
-            println!("Usage: sketchddd init <project-name> --template minimal");
-        }
-        "ecommerce" => {
-            println!("{}", "Template: ecommerce".cyan().bold());
-            println!();
-            println!("{}: Built-in", "Source".blue());
-            println!("{}: E-commerce domain model", "Description".blue());
-            println!("{}", "Contents:".blue());
-            println!("  - Customer, Order, Product entities");
-            println!("  - Money, Address value objects");
-            println!("  - OrderAggregate with LineItems");
-            println!("  - OrderStatus, PaymentStatus enums");
-            println!();
-            println!("Usage: sketchddd init <project-name> --template ecommerce");
-        }
-        "microservices" => {
-            println!("{}", "Template: microservices".cyan().bold());
-            println!();
-            println!("{}: Built-in", "Source".blue());
-            println!("{}: Multi-context microservices architecture", "Description".blue());
-            println!("{}", "Contents:".blue());
-            println!("  - Orders context");
-            println!("  - Inventory context");
-            println!("  - Shipping context");
-            println!("  - Context maps with CustomerSupplier pattern");
-            println!();
-            println!("Usage: sketchddd init <project-name> --template microservices");
+    match scaffold::find(name) {
+        Some(template) => {
+            report::output(format!("Template: {}", template.name).cyan().bold());
+            report::status("");
+            report::output(format!("{}: Built-in", "Source".blue()));
+            report::output(format!(
+                "{}: {}",
+                "Description".blue(),
+                template.description
+            ));
+            report::output("Contents:".blue());
+            for item in template.contents {
+                report::output(format!("  - {}", item));
+            }
+            report::status("");
+            report::status(format!(
+                "Usage: sketchddd init <project-name> --template {}",
+                template.name
+            ));
         }
-        _ => {
+        None => {
             // Check installed templates
             let templates_dir = get_templates_dir()?;
             let template_path = templates_dir.join(name);
@@ -1091,28 +3026,28 @@ fn cmd_template_info(name: &str, verbosity: Verbosity) -> Result<(), String> {
                 let manifest_path = template_path.join("template.json");
                 if manifest_path.exists() {
                     let content = std::fs::read_to_string(&manifest_path)
-                        .map_err(|e| format!("Failed to read manifest: {}", e))?;
+                        .map_err(|e| report::io_error(format!("Failed to read manifest: {}", e)))?;
                     let json: serde_json::Value = serde_json::from_str(&content)
                         .map_err(|e| format!("Invalid manifest JSON: {}", e))?;
 
-                    println!("{}", format!("Template: {}", name).cyan().bold());
-                    println!();
+                    report::output(format!("Template: {}", name).cyan().bold());
+                    report::status("");
 
                     if let Some(desc) = json.get("description").and_then(|d| d.as_str()) {
-                        println!("{}: {}", "Description".blue(), desc);
+                        report::output(format!("{}: {}", "Description".blue(), desc));
                     }
                     if let Some(author) = json.get("author").and_then(|a| a.as_str()) {
-                        println!("{}: {}", "Author".blue(), author);
+                        report::output(format!("{}: {}", "Author".blue(), author));
                     }
                     if let Some(version) = json.get("version").and_then(|v| v.as_str()) {
-                        println!("{}: {}", "Version".blue(), version);
+                        report::output(format!("{}: {}", "Version".blue(), version));
                     }
                 } else {
-                    println!("{}", format!("Template: {}", name).cyan().bold());
-                    println!("{}: Installed (no manifest)", "Source".blue());
+                    report::output(format!("Template: {}", name).cyan().bold());
+                    report::output(format!("{}: Installed (no manifest)", "Source".blue()));
                 }
             } else {
-                return Err(format!("Template '{}' not found", name));
+                return Err(format!("Template '{}' not found", name).into());
             }
         }
     }
@@ -1121,17 +3056,15 @@ fn cmd_template_info(name: &str, verbosity: Verbosity) -> Result<(), String> {
 }
 
 /// Validate a template
-fn cmd_template_validate(path: &PathBuf, verbosity: Verbosity) -> Result<(), String> {
-    if verbosity != Verbosity::Quiet {
-        println!("{} {}", "Validating template".cyan().bold(), path.display());
-    }
+fn cmd_template_validate(path: &PathBuf) -> Result<(), CliError> {
+    report::heading("Validating template", path.display());
 
     let mut errors = Vec::new();
     let mut warnings = Vec::new();
 
     // Check if path exists
     if !path.exists() {
-        return Err(format!("Path does not exist: {}", path.display()));
+        return Err(format!("Path does not exist: {}", path.display()).into());
     }
 
     // Check for template.json manifest
@@ -1146,7 +3079,7 @@ fn cmd_template_validate(path: &PathBuf, verbosity: Verbosity) -> Result<(), Str
     } else {
         // Validate manifest
         let content = std::fs::read_to_string(&manifest_path)
-            .map_err(|e| format!("Failed to read manifest: {}", e))?;
+            .map_err(|e| report::io_error(format!("Failed to read manifest: {}", e)))?;
 
         match serde_json::from_str::<serde_json::Value>(&content) {
             Ok(json) => {
@@ -1166,7 +3099,7 @@ fn cmd_template_validate(path: &PathBuf, verbosity: Verbosity) -> Result<(), Str
     // Check for .sddd template file
     let sddd_files: Vec<_> = if path.is_dir() {
         std::fs::read_dir(path)
-            .map_err(|e| format!("Failed to read directory: {}", e))?
+            .map_err(|e| report::io_error(format!("Failed to read directory: {}", e)))?
             .filter_map(|e| e.ok())
             .filter(|e| {
                 e.path()
@@ -1175,9 +3108,8 @@ fn cmd_template_validate(path: &PathBuf, verbosity: Verbosity) -> Result<(), Str
                     .unwrap_or(false)
             })
             .collect()
-    } else if path.extension().map(|e| e == "sddd").unwrap_or(false) {
-        vec![]  // Single file is the template
     } else {
+        // A single .sddd file is the template itself
         vec![]
     };
 
@@ -1201,46 +3133,40 @@ fn cmd_template_validate(path: &PathBuf, verbosity: Verbosity) -> Result<(), Str
     }
 
     // Report results
-    if verbosity != Verbosity::Quiet {
-        for warning in &warnings {
-            println!("  {} {}", "warning:".yellow().bold(), warning);
-        }
-        for error in &errors {
-            println!("  {} {}", "error:".red().bold(), error);
-        }
+    for warning in &warnings {
+        report::status(format!("  {} {}", "warning:".yellow().bold(), warning));
+    }
+    for error in &errors {
+        report::status(format!("  {} {}", "error:".red().bold(), error));
     }
 
     if errors.is_empty() {
-        if verbosity != Verbosity::Quiet {
-            println!("{} Template is valid", "✓".green().bold());
-        }
+        report::success("Template is valid");
         Ok(())
     } else {
-        Err(format!("Template validation failed with {} error(s)", errors.len()))
+        Err(format!("Template validation failed with {} error(s)", errors.len()).into())
     }
 }
 
 /// Install a template
-fn cmd_template_install(source: &str, force: bool, verbosity: Verbosity) -> Result<(), String> {
-    if verbosity != Verbosity::Quiet {
-        println!("{} {}", "Installing template".cyan().bold(), source);
-    }
+fn cmd_template_install(source: &str, force: bool) -> Result<(), CliError> {
+    report::heading("Installing template", source);
 
     let templates_dir = get_templates_dir()?;
     std::fs::create_dir_all(&templates_dir)
-        .map_err(|e| format!("Failed to create templates directory: {}", e))?;
+        .map_err(|e| report::io_error(format!("Failed to create templates directory: {}", e)))?;
 
     // Determine if source is URL or local path
     if source.starts_with("http://") || source.starts_with("https://") {
         // TODO: Implement URL download
-        println!("{} URL installation not yet implemented", "⚠".yellow().bold());
+        report::warning("URL installation not yet implemented");
         return Ok(());
     }
 
     // Local path
     let source_path = PathBuf::from(source);
     if !source_path.exists() {
-        return Err(format!("Source path does not exist: {}", source));
+        return Err(format!("Source path does not exist: {}", source).into());
     }
 
     // Get template name
@@ -1255,7 +3181,8 @@ fn cmd_template_install(source: &str, force: bool, verbosity: Verbosity) -> Resu
         return Err(format!(
             "Template '{}' already exists. Use --force to overwrite.",
             name
-        ));
+        )
+        .into());
     }
 
     // Copy template
@@ -1263,24 +3190,28 @@ fn cmd_template_install(source: &str, force: bool, verbosity: Verbosity) -> Resu
         copy_dir_recursive(&source_path, &dest_path)?;
     } else {
         std::fs::create_dir_all(&dest_path)
-            .map_err(|e| format!("Failed to create template directory: {}", e))?;
-        std::fs::copy(&source_path, dest_path.join(source_path.file_name().unwrap()))
-            .map_err(|e| format!("Failed to copy template: {}", e))?;
+            .map_err(|e| report::io_error(format!("Failed to create template directory: {}", e)))?;
+        std::fs::copy(
+            &source_path,
+            dest_path.join(source_path.file_name().unwrap()),
+        )
+        .map_err(|e| report::io_error(format!("Failed to copy template: {}", e)))?;
     }
 
-    if verbosity != Verbosity::Quiet {
-        println!("{} Installed template '{}'", "✓".green().bold(), name);
-    }
+    report::success(format!("Installed template '{}'", name));
 
     Ok(())
 }
 
 /// Recursively copy a directory
-fn copy_dir_recursive(src: &PathBuf, dst: &PathBuf) -> Result<(), String> {
-    std::fs::create_dir_all(dst).map_err(|e| format!("Failed to create directory: {}", e))?;
-
-    for entry in std::fs::read_dir(src).map_err(|e| format!("Failed to read directory: {}", e))? {
-        let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
+fn copy_dir_recursive(src: &PathBuf, dst: &PathBuf) -> Result<(), CliError> {
+    std::fs::create_dir_all(dst)
+        .map_err(|e| report::io_error(format!("Failed to create directory: {}", e)))?;
+
+    for entry in std::fs::read_dir(src)
+        .map_err(|e| report::io_error(format!("Failed to read directory: {}", e)))?
+    {
+        let entry = entry.map_err(|e| report::io_error(format!("Failed to read entry: {}", e)))?;
         let src_path = entry.path();
         let dst_path = dst.join(entry.file_name());
 
@@ -1288,7 +3219,7 @@ fn copy_dir_recursive(src: &PathBuf, dst: &PathBuf) -> Result<(), String> {
             copy_dir_recursive(&src_path, &dst_path)?;
         } else {
             std::fs::copy(&src_path, &dst_path)
-                .map_err(|e| format!("Failed to copy file: {}", e))?;
+                .map_err(|e| report::io_error(format!("Failed to copy file: {}", e)))?;
         }
     }
 
@@ -1296,68 +3227,64 @@ fn copy_dir_recursive(src: &PathBuf, dst: &PathBuf) -> Result<(), String> {
 }
 
 /// Update a template
-fn cmd_template_update(name: &str, verbosity: Verbosity) -> Result<(), String> {
-    if verbosity != Verbosity::Quiet {
-        println!("{} {}", "Updating template".cyan().bold(), name);
-    }
+fn cmd_template_update(name: &str) -> Result<(), CliError> {
+    report::heading("Updating template", name);
 
     if name == "all" {
-        println!("{} Updating all templates...", "→".blue());
+        report::status(format!("{} Updating all templates...", "→".blue()));
         // TODO: Implement update all
-        println!("{} Template updates not yet implemented", "⚠".yellow().bold());
+        report::warning("Template updates not yet implemented");
     } else {
         let templates_dir = get_templates_dir()?;
         let template_path = templates_dir.join(name);
 
         if !template_path.exists() {
-            return Err(format!("Template '{}' is not installed", name));
+            return Err(format!("Template '{}' is not installed", name).into());
         }
 
         // TODO: Check registry for updates and download
-        println!("{} Template updates not yet implemented", "⚠".yellow().bold());
+        report::warning("Template updates not yet implemented");
     }
 
     Ok(())
 }
 
 /// Remove a template
-fn cmd_template_remove(name: &str, force: bool, verbosity: Verbosity) -> Result<(), String> {
+fn cmd_template_remove(name: &str, force: bool) -> Result<(), CliError> {
     // Check for built-in templates
-    if matches!(name, "minimal" | "ecommerce" | "microservices") {
-        return Err(format!("Cannot remove built-in template '{}'", name));
+    if scaffold::find(name).is_some() {
+        return Err(format!("Cannot remove built-in template '{}'", name).into());
     }
 
     let templates_dir = get_templates_dir()?;
     let template_path = templates_dir.join(name);
 
     if !template_path.exists() {
-        return Err(format!("Template '{}' is not installed", name));
+        return Err(format!("Template '{}' is not installed", name).into());
     }
 
     if !force {
-        println!(
+        report::output(format!(
             "{} Remove template '{}'? This cannot be undone. [y/N]",
             "?".yellow().bold(),
             name
-        );
+        ));
 
         let mut input = String::new();
         std::io::stdin()
             .read_line(&mut input)
-            .map_err(|e| format!("Failed to read input: {}", e))?;
+            .map_err(|e| report::io_error(format!("Failed to read input: {}", e)))?;
 
         if !input.trim().eq_ignore_ascii_case("y") {
-            println!("Cancelled.");
+            report::status("Cancelled.");
             return Ok(());
         }
     }
 
     std::fs::remove_dir_all(&template_path)
-        .map_err(|e| format!("Failed to remove template: {}", e))?;
+        .map_err(|e| report::io_error(format!("Failed to remove template: {}", e)))?;
 
-    if verbosity != Verbosity::Quiet {
-        println!("{} Removed template '{}'", "✓".green().bold(), name);
-    }
+    report::success(format!("Removed template '{}'", name));
 
     Ok(())
 }
@@ -1367,16 +3294,13 @@ fn cmd_template_create(
     name: &str,
     source: Option<PathBuf>,
     output: Option<PathBuf>,
-    verbosity: Verbosity,
-) -> Result<(), String> {
-    if verbosity != Verbosity::Quiet {
-        println!("{} {}", "Creating template".cyan().bold(), name);
-    }
+) -> Result<(), CliError> {
+    report::heading("Creating template", name);
 
     // Determine output directory
     let output_dir = output.unwrap_or_else(|| PathBuf::from(name));
     std::fs::create_dir_all(&output_dir)
-        .map_err(|e| format!("Failed to create directory: {}", e))?;
+        .map_err(|e| report::io_error(format!("Failed to create directory: {}", e)))?;
 
     // Create template manifest
     let manifest = serde_json::json!({
@@ -1392,18 +3316,23 @@ fn cmd_template_create(
         output_dir.join("template.json"),
         serde_json::to_string_pretty(&manifest).unwrap(),
     )
-    .map_err(|e| format!("Failed to write manifest: {}", e))?;
+    .map_err(|e| report::io_error(format!("Failed to write manifest: {}", e)))?;
 
     // Copy or create template .sddd file
     let sddd_content = if let Some(source_path) = source {
         std::fs::read_to_string(&source_path)
-            .map_err(|e| format!("Failed to read source file: {}", e))?
+            .map_err(|e| report::io_error(format!("Failed to read source file: {}", e)))?
     } else {
-        get_minimal_template(name)
+        scaffold::find("minimal")
+            .expect("the minimal template is built in")
+            .model(name)
     };
 
-    std::fs::write(output_dir.join(format!("{}.sddd", name.to_lowercase())), sddd_content)
-        .map_err(|e| format!("Failed to write template file: {}", e))?;
+    std::fs::write(
+        output_dir.join(format!("{}.sddd", name.to_lowercase())),
+        sddd_content,
+    )
+    .map_err(|e| report::io_error(format!("Failed to write template file: {}", e)))?;
 
     // Create README
     let readme = format!(
@@ -1426,50 +3355,130 @@ sketchddd init my-project --template {}
 
 MIT
 "#,
-        name, name, name.to_lowercase()
+        name,
+        name,
+        name.to_lowercase()
     );
 
     std::fs::write(output_dir.join("README.md"), readme)
-        .map_err(|e| format!("Failed to write README: {}", e))?;
+        .map_err(|e| report::io_error(format!("Failed to write README: {}", e)))?;
 
-    if verbosity != Verbosity::Quiet {
-        println!("{} Created template in {}/", "✓".green().bold(), output_dir.display());
-        println!("  {} template.json", "→".blue());
-        println!("  {} {}.sddd", "→".blue(), name.to_lowercase());
-        println!("  {} README.md", "→".blue());
-    }
+    report::success(format!("Created template in {}/", output_dir.display()));
+    report::status(format!("  {} template.json", "→".blue()));
+    report::status(format!("  {} {}.sddd", "→".blue(), name.to_lowercase()));
+    report::status(format!("  {} README.md", "→".blue()));
+
+    Ok(())
+}
 
+/// Read or write the user's settings
+fn cmd_config(cmd: ConfigCommands, json: bool) -> Result<(), CliError> {
+    match cmd {
+        ConfigCommands::Get { key } => {
+            let key: Key = key.parse().map_err(report::usage_error)?;
+            let project = Project::find()?;
+            let (value, origin) = settings::get(key, project.as_ref())?;
+            if json {
+                Output::new("config")
+                    .with_field("key", key.name())
+                    .with_field("value", &value)
+                    .with_field("origin", origin.describe())
+                    .print()?;
+            } else {
+                report::output(settings::display(&value));
+                report::detail(format!("{} {}", "from".dimmed(), origin.describe()));
+            }
+        }
+        ConfigCommands::Set { key, value } => {
+            let key: Key = key.parse().map_err(report::usage_error)?;
+            let value = key.parse_value(&value).map_err(report::usage_error)?;
+            let path = settings::set(key, value.clone())?;
+            if json {
+                let mut output = Output::new("config")
+                    .with_field("key", key.name())
+                    .with_field("value", &value);
+                output.artifacts.push(Artifact::written("file", &path));
+                output.print()?;
+            } else {
+                report::success(format!(
+                    "Set {} = {} in {}",
+                    key.name(),
+                    settings::display(&value),
+                    path.display()
+                ));
+            }
+        }
+        ConfigCommands::List => {
+            let project = Project::find()?;
+            let mut entries = Vec::new();
+            for key in Key::ALL {
+                let (value, origin) = settings::get(key, project.as_ref())?;
+                entries.push((key, value, origin));
+            }
+            if json {
+                let settings: Vec<serde_json::Value> = entries
+                    .iter()
+                    .map(|(key, value, origin)| {
+                        serde_json::json!({
+                            "key": key.name(),
+                            "value": value,
+                            "origin": origin.describe(),
+                        })
+                    })
+                    .collect();
+                Output::new("config")
+                    .with_field("settings", settings)
+                    .print()?;
+            } else {
+                let width = Key::ALL.iter().map(|k| k.name().len()).max().unwrap_or(0);
+                for (key, value, origin) in &entries {
+                    report::output(format!(
+                        "{:<width$}  {}  {}",
+                        key.name(),
+                        settings::display(value),
+                        format!("({})", origin.describe()).dimmed(),
+                        width = width
+                    ));
+                }
+            }
+        }
+    }
     Ok(())
 }
 
 /// Check for updates
-fn cmd_update(check_only: bool, verbosity: Verbosity) -> Result<(), String> {
+fn cmd_update(check_only: bool) -> Result<(), CliError> {
     let current_version = env!("CARGO_PKG_VERSION");
 
-    if verbosity != Verbosity::Quiet {
-        println!("{} version {}", "SketchDDD".cyan().bold(), current_version);
+    report::heading("SketchDDD", format!("version {}", current_version));
+
+    if !Settings::load()?.update.check {
+        report::status(format!(
+            "{} Update checks are disabled by update.check in {}",
+            "→".blue(),
+            settings::path().unwrap_or_default().display()
+        ));
+        return Ok(());
     }
 
     // TODO: Implement actual version check from registry/GitHub
-    println!("{} Checking for updates...", "→".blue());
+    report::status(format!("{} Checking for updates...", "→".blue()));
 
     // Simulated check - in real implementation, fetch from GitHub releases API
     let latest_version = current_version; // Would be fetched from remote
 
     if latest_version == current_version {
-        println!("{} You are running the latest version!", "✓".green().bold());
+        report::success("You are running the latest version!");
     } else {
-        println!(
-            "{} New version {} available (current: {})",
-            "⚠".yellow().bold(),
-            latest_version,
-            current_version
-        );
+        report::warning(format!(
+            "New version {} available (current: {})",
+            latest_version, current_version
+        ));
 
         if !check_only {
-            println!();
-            println!("To update, run:");
-            println!("  {} install sketchddd", "cargo".cyan());
+            report::status("");
+            report::status("To update, run:");
+            report::status(format!("  {} install sketchddd", "cargo".cyan()));
         }
     }
 
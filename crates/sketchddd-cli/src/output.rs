@@ -0,0 +1,189 @@
+//! Machine-readable output of commands, printed with `--format json`.
+//!
+//! Every command prints the same object, so that scripts, editors and CI
+//! bots can drive the CLI without scraping the text meant for people:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "command": "check",
+//!   "status": "failure",
+//!   "diagnostics": [{ "code": "E0020", "severity": "error", "message": "..." }],
+//!   "artifacts": [],
+//!   "summary": { "errors": 1, "warnings": 0, "hints": 0 }
+//! }
+//! ```
+//!
+//! `status` is `success`, `failure` when the command ran but its check
+//! failed, such as a model with errors, or `error` when it could not run,
+//! with the reason in `error`. `diagnostics` are issues found in the model
+//! and `artifacts` the files or contents the command produced. Fields
+//! specific to a command follow. The shape only changes with
+//! [`OUTPUT_VERSION`].
+
+use crate::report::{self, CliError};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use sketchddd_core::JsonIssue;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Version of the [`Output`] format.
+pub const OUTPUT_VERSION: u32 = 1;
+
+/// Whether a command printed its output, so that an error it returns
+/// afterwards is not printed again.
+static PRINTED: AtomicBool = AtomicBool::new(false);
+
+/// The outcome of a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    /// The command succeeded
+    Success,
+
+    /// The command ran, but what it checks failed
+    Failure,
+
+    /// The command could not run
+    Error,
+}
+
+/// Something a command produced.
+#[derive(Debug, Clone, Serialize)]
+pub struct Artifact {
+    /// What the artifact is: `code`, `diagram`, `image`, `page` or `file`
+    pub kind: &'static str,
+
+    /// Target language or diagram format, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+
+    /// Bounded context the artifact is for, if one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+
+    /// File the artifact was written to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+
+    /// Content of the artifact, when it was not written to a file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+}
+
+impl Artifact {
+    /// Describe a file that was written.
+    pub fn written(kind: &'static str, path: &Path) -> Self {
+        Self {
+            kind,
+            format: None,
+            context: None,
+            path: Some(path.display().to_string()),
+            content: None,
+        }
+    }
+
+    /// Describe content that was not written to a file.
+    pub fn content(kind: &'static str, content: String) -> Self {
+        Self {
+            kind,
+            format: None,
+            context: None,
+            path: None,
+            content: Some(content),
+        }
+    }
+
+    /// Set the target language or diagram format.
+    pub fn with_format(mut self, format: impl Into<String>) -> Self {
+        self.format = Some(format.into());
+        self
+    }
+
+    /// Set the bounded context.
+    pub fn with_context(mut self, context: impl Into<String>) -> Self {
+        self.context = Some(context.into());
+        self
+    }
+}
+
+/// The output of a command.
+#[derive(Debug, Clone, Serialize)]
+pub struct Output {
+    /// Version of the format, see [`OUTPUT_VERSION`]
+    pub version: u32,
+
+    /// Name of the command
+    pub command: &'static str,
+
+    /// Outcome of the command
+    pub status: Status,
+
+    /// Why the command could not run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    /// Issues found in the model
+    pub diagnostics: Vec<JsonIssue>,
+
+    /// What the command produced
+    pub artifacts: Vec<Artifact>,
+
+    /// Fields specific to the command
+    #[serde(flatten)]
+    pub fields: Map<String, Value>,
+}
+
+impl Output {
+    /// Start the output of a successful command.
+    pub fn new(command: &'static str) -> Self {
+        Self {
+            version: OUTPUT_VERSION,
+            command,
+            status: Status::Success,
+            error: None,
+            diagnostics: Vec::new(),
+            artifacts: Vec::new(),
+            fields: Map::new(),
+        }
+    }
+
+    /// Describe a command that could not run.
+    pub fn error(command: &'static str, message: &str) -> Self {
+        Self {
+            status: Status::Error,
+            error: Some(message.to_string()),
+            ..Self::new(command)
+        }
+    }
+
+    /// Add a field specific to the command.
+    pub fn with_field(mut self, name: &str, value: impl Serialize) -> Self {
+        let value = serde_json::to_value(value).expect("command output serializes to JSON");
+        self.fields.insert(name.to_string(), value);
+        self
+    }
+
+    /// Add the fields of an object, such as a report, to the command's.
+    pub fn with_fields(mut self, value: impl Serialize) -> Self {
+        if let Ok(Value::Object(fields)) = serde_json::to_value(value) {
+            self.fields.extend(fields);
+        }
+        self
+    }
+
+    /// Print the output on stdout.
+    pub fn print(&self) -> Result<(), CliError> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| report::internal_error(format!("JSON serialization error: {}", e)))?;
+        report::output(json);
+        PRINTED.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+/// Check whether a command printed its output.
+pub fn printed() -> bool {
+    PRINTED.load(Ordering::Relaxed)
+}
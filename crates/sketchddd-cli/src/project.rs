@@ -0,0 +1,469 @@
+//! Projects whose model spans several files.
+//!
+//! A `sketchddd.toml` with a `[model]` section makes its directory a
+//! project. The model is made of every file matching one of the `sources`
+//! globs, which are relative to the directory. The `[codegen]` and `[viz]`
+//! sections give the defaults of `codegen` and `viz` in the project:
+//!
+//! ```toml
+//! [model]
+//! sources = ["model/**/*.sddd"]
+//!
+//! [codegen]
+//! targets = ["rust", "typescript"]
+//! output = "generated"
+//!
+//! [viz]
+//! format = "mermaid"
+//! output = "docs/diagrams"
+//! direction = "TB"
+//!
+//! [viz.colors]
+//! aggregate-root = "#ffe6cc"
+//! ```
+//!
+//! The other keys of `[viz]` make the theme of the diagrams, as described
+//! in [`sketchddd_viz::theme`]. They apply to any model governed by the
+//! file, even when it has no `[model]` section.
+//!
+//! Commands given no model file work on the project the current directory
+//! is in. The project's files are joined into one source, so declarations
+//! in one file may refer to those in another; the locations of issues are
+//! mapped back to the files.
+
+use crate::config;
+use crate::report::{self, CliError};
+use serde::Deserialize;
+use sketchddd_core::{Fix, ValidationError};
+use sketchddd_parser::{parse_file, File};
+use sketchddd_viz::theme::{Direction, EdgeStyles, ElementColors, Theme};
+use std::path::{Path, PathBuf};
+
+/// A project, described by a `sketchddd.toml` with a `[model]` section.
+pub struct Project {
+    /// The project's configuration file
+    pub manifest: PathBuf,
+
+    /// Globs of the model files, relative to the project's directory
+    sources: Vec<String>,
+
+    /// Defaults of `codegen`
+    pub codegen: CodegenDefaults,
+
+    /// Defaults of `viz`
+    pub viz: VizDefaults,
+}
+
+/// The `[codegen]` section of a project.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CodegenDefaults {
+    /// Targets generated when `--target` is not given
+    #[serde(default)]
+    pub targets: Vec<String>,
+
+    /// Directory the code is written to, in a directory per target
+    pub output: Option<PathBuf>,
+}
+
+/// The `[viz]` section of a project.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VizDefaults {
+    /// Format used when `--format` is not given
+    pub format: Option<String>,
+
+    /// Directory the diagrams are written to, in a file per context
+    pub output: Option<PathBuf>,
+
+    /// Direction diagrams are laid out in
+    pub direction: Option<Direction>,
+
+    /// Font of labels
+    pub font: Option<String>,
+
+    /// Fill colours by kind of object
+    #[serde(default)]
+    pub colors: ElementColors,
+
+    /// Line styles by kind of edge
+    #[serde(default)]
+    pub edges: EdgeStyles,
+
+    /// Whether class diagrams write multiplicities on the ends of morphisms
+    #[serde(default)]
+    pub multiplicities: bool,
+
+    /// Whether diagrams get a legend and a banner, as with `--legend`
+    #[serde(default)]
+    pub legend: bool,
+}
+
+impl VizDefaults {
+    /// Load the `[viz]` section of the configuration file governing a model
+    /// file, or the defaults without one.
+    pub fn load(model: &Path) -> Result<Self, CliError> {
+        match config::find_config(model) {
+            Some(manifest) => Ok(Manifest::read(&manifest)?.viz),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Get the theme of the project's diagrams.
+    pub fn theme(&self) -> Theme {
+        Theme {
+            direction: self.direction,
+            font: self.font.clone(),
+            colors: self.colors.clone(),
+            edges: self.edges.clone(),
+            multiplicities: self.multiplicities,
+        }
+    }
+}
+
+/// The sections of a `sketchddd.toml` describing a project.
+#[derive(Deserialize)]
+struct Manifest {
+    model: Option<Model>,
+    #[serde(default)]
+    codegen: CodegenDefaults,
+    #[serde(default)]
+    viz: VizDefaults,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Model {
+    sources: Vec<String>,
+}
+
+impl Manifest {
+    /// Read a configuration file, making its output directories relative
+    /// to the file's directory.
+    fn read(manifest: &Path) -> Result<Self, CliError> {
+        let source = std::fs::read_to_string(manifest).map_err(|e| {
+            report::io_error(format!("Failed to read {}: {}", manifest.display(), e))
+        })?;
+        let mut parsed: Self = toml::from_str(&source)
+            .map_err(|e| format!("{}: {}", manifest.display(), e.message()))?;
+        let root = manifest.parent().unwrap_or(Path::new(""));
+        parsed.codegen.output = parsed.codegen.output.map(|dir| root.join(dir));
+        parsed.viz.output = parsed.viz.output.map(|dir| root.join(dir));
+        Ok(parsed)
+    }
+}
+
+impl Project {
+    /// Find the project the current directory is in.
+    ///
+    /// Like lint settings, the project is given by the nearest
+    /// `sketchddd.toml`; if it has no `[model]` section, there is none.
+    pub fn find() -> Result<Option<Self>, CliError> {
+        let current_dir = std::env::current_dir()
+            .map_err(|e| report::io_error(format!("Failed to get current directory: {}", e)))?;
+        // Keep the path relative, so that files are shown as the user wrote them
+        let mut root = PathBuf::new();
+        for dir in current_dir.ancestors() {
+            if dir.join(config::CONFIG_FILE).is_file() {
+                return Self::load(&root.join(config::CONFIG_FILE));
+            }
+            root.push("..");
+        }
+        Ok(None)
+    }
+
+    /// Load a project from its configuration file, or `None` if the file
+    /// has no `[model]` section.
+    pub fn load(manifest: &Path) -> Result<Option<Self>, CliError> {
+        let parsed = Manifest::read(manifest)?;
+        let Some(model) = parsed.model else {
+            return Ok(None);
+        };
+        Ok(Some(Self {
+            manifest: manifest.to_path_buf(),
+            sources: model.sources,
+            codegen: parsed.codegen,
+            viz: parsed.viz,
+        }))
+    }
+
+    /// Get the project's directory.
+    pub fn root(&self) -> &Path {
+        self.manifest.parent().unwrap_or(Path::new(""))
+    }
+
+    /// Find the model files, in the order of the globs matching them and
+    /// then by path.
+    pub fn files(&self) -> Result<Vec<PathBuf>, CliError> {
+        let mut files = Vec::new();
+        for pattern in &self.sources {
+            let full = self.root().join(pattern);
+            let paths = glob::glob(&full.to_string_lossy()).map_err(|e| {
+                format!(
+                    "{}: [model] sources: invalid glob '{}': {}",
+                    self.manifest.display(),
+                    pattern,
+                    e.msg
+                )
+            })?;
+            let mut matched: Vec<PathBuf> = paths
+                .filter_map(Result::ok)
+                .filter(|path| path.is_file())
+                .collect();
+            matched.sort();
+            for path in matched {
+                if !files.contains(&path) {
+                    files.push(path);
+                }
+            }
+        }
+        if files.is_empty() {
+            return Err(format!(
+                "{}: no model file matches [model] sources",
+                self.manifest.display()
+            )
+            .into());
+        }
+        Ok(files)
+    }
+}
+
+/// What a command works on: a model file, or the model files of a project.
+pub enum ModelSource {
+    /// A single model file
+    File(PathBuf),
+
+    /// The model files of a project
+    Project(Box<Project>),
+}
+
+impl ModelSource {
+    /// Get the path naming the model: the file, or the project's
+    /// configuration file.
+    pub fn path(&self) -> &Path {
+        match self {
+            ModelSource::File(path) => path,
+            ModelSource::Project(project) => &project.manifest,
+        }
+    }
+
+    /// Get the project, if the model is a project's.
+    pub fn project(&self) -> Option<&Project> {
+        match self {
+            ModelSource::File(_) => None,
+            ModelSource::Project(project) => Some(project),
+        }
+    }
+
+    /// Get the name of the model: the file's stem, or the name of the
+    /// project's directory.
+    pub fn name(&self) -> String {
+        // A project is named after its directory, which may be the current one
+        let named = match self {
+            ModelSource::File(path) => path.file_stem().map(PathBuf::from),
+            ModelSource::Project(project) => std::fs::canonicalize(project.root())
+                .ok()
+                .and_then(|root| root.file_name().map(PathBuf::from)),
+        };
+        named.map_or_else(
+            || "Domain Model".to_string(),
+            |name| name.display().to_string(),
+        )
+    }
+
+    /// Get the defaults of `viz` for the model: the project's, or those of
+    /// the configuration file governing the model file.
+    pub fn viz(&self) -> Result<VizDefaults, CliError> {
+        match self {
+            ModelSource::File(path) => VizDefaults::load(path),
+            ModelSource::Project(project) => Ok(project.viz.clone()),
+        }
+    }
+
+    /// Read the source of the model.
+    pub fn read(&self) -> Result<Sources, CliError> {
+        match self {
+            ModelSource::File(path) => Sources::read(std::slice::from_ref(path)),
+            ModelSource::Project(project) => Sources::read(&project.files()?),
+        }
+    }
+
+    /// Check whether a change to a file may change the model.
+    pub fn depends_on(&self, path: &Path) -> bool {
+        match self {
+            ModelSource::File(file) => path.file_name() == file.file_name(),
+            ModelSource::Project(_) => {
+                path.file_name()
+                    .is_some_and(|name| name == config::CONFIG_FILE)
+                    || path
+                        .extension()
+                        .is_some_and(|ext| ext == "sddd" || ext == "sketch")
+            }
+        }
+    }
+}
+
+/// The source of a model, joined from its files.
+pub struct Sources {
+    /// The joined source
+    pub text: String,
+
+    /// The files, in the order they were joined; never empty
+    files: Vec<SourceFile>,
+}
+
+/// Where a file's source is in the joined source.
+struct SourceFile {
+    path: PathBuf,
+    start: usize,
+    end: usize,
+    first_line: u32,
+}
+
+impl SourceFile {
+    /// Make a fix to the joined source a fix to this file, unless it edits
+    /// text outside the file.
+    fn shift(&self, fix: &Fix) -> Option<Fix> {
+        let inside = fix
+            .edits
+            .iter()
+            .all(|e| e.start >= self.start && e.end <= self.end);
+        inside.then(|| {
+            let mut fix = fix.clone();
+            for edit in &mut fix.edits {
+                edit.start -= self.start;
+                edit.end -= self.start;
+            }
+            fix
+        })
+    }
+}
+
+impl Sources {
+    /// Read model files and join their sources.
+    pub fn read(paths: &[PathBuf]) -> Result<Self, CliError> {
+        let mut sources = Vec::new();
+        for path in paths {
+            let source = std::fs::read_to_string(path).map_err(|e| {
+                report::io_error(format!("Failed to read file {}: {}", path.display(), e))
+            })?;
+            tracing::debug!(file = %path.display(), bytes = source.len(), "read");
+            sources.push((path.clone(), source));
+        }
+        Ok(Self::join(sources))
+    }
+
+    /// Join the sources of model files, given with their paths.
+    pub fn join(sources: Vec<(PathBuf, String)>) -> Self {
+        let mut text = String::new();
+        let mut files = Vec::new();
+        let mut lines = 0;
+        for (path, source) in sources {
+            if !text.is_empty() && !text.ends_with('\n') {
+                text.push('\n');
+                lines += 1;
+            }
+            files.push(SourceFile {
+                path,
+                start: text.len(),
+                end: text.len() + source.len(),
+                first_line: lines + 1,
+            });
+            lines += source.matches('\n').count() as u32;
+            text.push_str(&source);
+        }
+        Self { text, files }
+    }
+
+    /// Get the path and source of each file.
+    pub fn files(&self) -> impl Iterator<Item = (&Path, &str)> {
+        self.files
+            .iter()
+            .map(|file| (file.path.as_path(), &self.text[file.start..file.end]))
+    }
+
+    /// Parse the joined source, reporting a syntax error in the file it is
+    /// in.
+    pub fn parse(&self) -> Result<File, CliError> {
+        parse_file(&self.text).map_err(|joined| {
+            self.files
+                .iter()
+                .find_map(|file| {
+                    parse_file(&self.text[file.start..file.end]).err().map(|e| {
+                        let at = match (e.line, e.column) {
+                            (Some(line), Some(column)) => {
+                                format!("{}:{}:{}", file.path.display(), line, column)
+                            }
+                            _ => file.path.display().to_string(),
+                        };
+                        report::parse_error(format!("Parse error in {}: {}", at, e))
+                    })
+                })
+                .unwrap_or_else(|| report::parse_error(format!("Parse error: {}", joined)))
+        })
+    }
+
+    /// Get the file a line of the joined source is in.
+    fn file_at(&self, line: u32) -> &SourceFile {
+        self.files
+            .iter()
+            .rev()
+            .find(|file| file.first_line <= line)
+            .unwrap_or(&self.files[0])
+    }
+
+    /// Format a location in the joined source as `file:line:column`.
+    pub fn location(&self, line: Option<u32>, column: Option<u32>) -> String {
+        let Some(line) = line else {
+            return self.files[0].path.display().to_string();
+        };
+        let file = self.file_at(line);
+        let line = line - file.first_line + 1;
+        match column {
+            Some(column) => format!("{}:{}:{}", file.path.display(), line, column),
+            None => format!("{}:{}", file.path.display(), line),
+        }
+    }
+
+    /// Point issues at the file they are in, with their lines and fixes
+    /// relative to it. Issues without a line are put in the first file, as
+    /// their locations are.
+    ///
+    /// Fixes editing other files than their issue's are dropped.
+    pub fn relocate(&self, issues: &mut [ValidationError]) {
+        for issue in issues {
+            let Some(line) = issue.location.line else {
+                issue.location.file = Some(self.files[0].path.display().to_string());
+                continue;
+            };
+            let file = self.file_at(line);
+            issue.location.file = Some(file.path.display().to_string());
+            issue.location.line = Some(line - file.first_line + 1);
+            issue.fix = issue.fix.as_ref().and_then(|fix| file.shift(fix));
+        }
+    }
+
+    /// Split fixes to the joined source by the file they edit, returning
+    /// each file with its source and fixes.
+    ///
+    /// Fixes editing several files are left out.
+    pub fn fixes_by_file(&self, fixes: &[Fix]) -> Vec<(&Path, &str, Vec<Fix>)> {
+        let mut split: Vec<Vec<Fix>> = self.files.iter().map(|_| Vec::new()).collect();
+        for fix in fixes {
+            // An insertion between two files goes to the first one
+            let shifted = self
+                .files
+                .iter()
+                .enumerate()
+                .find_map(|(i, file)| file.shift(fix).map(|fix| (i, fix)));
+            if let Some((i, fix)) = shifted {
+                split[i].push(fix);
+            }
+        }
+        self.files
+            .iter()
+            .zip(split)
+            .map(|(file, fixes)| (file.path.as_path(), &self.text[file.start..file.end], fixes))
+            .collect()
+    }
+}
@@ -0,0 +1,334 @@
+//! The query language of `sketchddd query`.
+//!
+//! A query asks one question of a model:
+//!
+//! ```text
+//! morphisms from Order      the morphisms leaving an object
+//! morphisms to Customer     the morphisms arriving at an object
+//! usages of Money           where an object is referenced
+//! path Order -> Address     a shortest chain of morphisms between two objects
+//! aggregates using Money    the aggregates depending on a value object
+//! consumers of Sales        the contexts downstream of a context
+//! ```
+//!
+//! Keywords are case-insensitive. An object is looked up in every context,
+//! unless it is qualified by its context, as in `Sales.Order`. Answers are
+//! tables, computed by the core [`Query`](sketchddd_core::Query) API.
+
+use crate::report;
+use colored::Colorize;
+use serde_json::{Map, Value};
+use sketchddd_core::sketch::Morphism;
+use sketchddd_core::{did_you_mean, BoundedContext, Usage, Workspace};
+use std::str::FromStr;
+
+/// The forms a query can take, for error messages.
+const FORMS: &str = "morphisms from|to OBJECT, usages of OBJECT, path OBJECT -> OBJECT, \
+                     aggregates using OBJECT or consumers of CONTEXT";
+
+/// An object named in a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectName {
+    /// Context the name is qualified by, if any
+    pub context: Option<String>,
+
+    /// Name of the object
+    pub name: String,
+}
+
+impl FromStr for ObjectName {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let (context, name) = match s.split_once('.') {
+            Some((context, name)) => (Some(context.to_string()), name),
+            None => (None, s),
+        };
+        if name.is_empty() || context.as_deref() == Some("") {
+            return Err(format!("Invalid object name '{}'", s));
+        }
+        Ok(Self {
+            context,
+            name: name.to_string(),
+        })
+    }
+}
+
+/// A parsed query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Statement {
+    /// `morphisms from OBJECT`
+    MorphismsFrom(ObjectName),
+
+    /// `morphisms to OBJECT`
+    MorphismsTo(ObjectName),
+
+    /// `usages of OBJECT`
+    UsagesOf(ObjectName),
+
+    /// `path OBJECT -> OBJECT`
+    Path(ObjectName, ObjectName),
+
+    /// `aggregates using OBJECT`
+    AggregatesUsing(ObjectName),
+
+    /// `consumers of CONTEXT`
+    ConsumersOf(String),
+}
+
+impl FromStr for Statement {
+    type Err = String;
+
+    fn from_str(query: &str) -> Result<Self, String> {
+        // `Order->Customer` is `Order -> Customer`
+        let spaced = query.replace("->", " -> ");
+        let words: Vec<&str> = spaced.split_whitespace().collect();
+        let keywords: Vec<String> = words.iter().map(|w| w.to_lowercase()).collect();
+        let keywords: Vec<&str> = keywords.iter().map(String::as_str).collect();
+        let object = |index: usize| words[index].parse::<ObjectName>();
+
+        match keywords.as_slice() {
+            ["morphisms", "from", _] => Ok(Statement::MorphismsFrom(object(2)?)),
+            ["morphisms", "to", _] => Ok(Statement::MorphismsTo(object(2)?)),
+            ["usages", "of", _] => Ok(Statement::UsagesOf(object(2)?)),
+            ["path", _, "->", _] => Ok(Statement::Path(object(1)?, object(3)?)),
+            ["aggregates", "using", _] => Ok(Statement::AggregatesUsing(object(2)?)),
+            ["consumers", "of", _] => Ok(Statement::ConsumersOf(words[2].to_string())),
+            _ => Err(format!(
+                "Cannot understand the query '{}'; expected {}",
+                query.trim(),
+                FORMS
+            )),
+        }
+    }
+}
+
+/// The answer to a query.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Table {
+    /// Names of the columns
+    pub columns: Vec<&'static str>,
+
+    /// Rows, with a value per column
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    fn new(columns: &[&'static str]) -> Self {
+        Self {
+            columns: columns.to_vec(),
+            rows: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+
+    /// Get the rows as objects keyed by column, for JSON.
+    pub fn records(&self) -> Vec<Map<String, Value>> {
+        self.rows
+            .iter()
+            .map(|row| {
+                self.columns
+                    .iter()
+                    .zip(row)
+                    .map(|(column, value)| (column.to_string(), Value::from(value.as_str())))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Print the table with aligned columns.
+    pub fn print(&self) {
+        let mut widths: Vec<usize> = self.columns.iter().map(|c| c.chars().count()).collect();
+        for row in &self.rows {
+            for (width, value) in widths.iter_mut().zip(row) {
+                *width = (*width).max(value.chars().count());
+            }
+        }
+        let line = |values: Vec<&str>| -> String {
+            let cells: Vec<String> = values
+                .iter()
+                .zip(&widths)
+                .map(|(value, width)| format!("{:<width$}", value, width = width))
+                .collect();
+            cells.join("  ").trim_end().to_string()
+        };
+
+        report::output(line(self.columns.clone()).bold());
+        for row in &self.rows {
+            report::output(line(row.iter().map(String::as_str).collect()));
+        }
+    }
+}
+
+/// Answer a query about the objects of some contexts of a workspace.
+///
+/// Fails if an object or context the query names does not exist.
+pub fn run(
+    statement: &Statement,
+    workspace: &Workspace,
+    contexts: &[&BoundedContext],
+) -> Result<Table, String> {
+    let query = workspace.query();
+    match statement {
+        Statement::MorphismsFrom(object) | Statement::MorphismsTo(object) => {
+            let mut table = Table::new(&["context", "morphism", "source", "target"]);
+            for ctx in containing(workspace, contexts, object)? {
+                let morphisms = match statement {
+                    Statement::MorphismsFrom(_) => query.morphisms_from(ctx.name(), &object.name),
+                    _ => query.morphisms_to(ctx.name(), &object.name),
+                };
+                for morphism in morphisms {
+                    let (source, target) = ends(ctx, morphism);
+                    table.push(vec![
+                        ctx.name().to_string(),
+                        morphism.name.clone(),
+                        source,
+                        target,
+                    ]);
+                }
+            }
+            Ok(table)
+        }
+        Statement::UsagesOf(object) => {
+            let mut table = Table::new(&["context", "kind", "name", "detail"]);
+            for ctx in containing(workspace, contexts, object)? {
+                for usage in query.usages(ctx.name(), &object.name) {
+                    let (kind, name, detail) = match usage {
+                        Usage::Morphism { morphism, name } => {
+                            let detail = match ctx.graph().get_morphism(morphism) {
+                                Some(morphism) => {
+                                    let (source, target) = ends(ctx, morphism);
+                                    format!("{} -> {}", source, target)
+                                }
+                                None => String::new(),
+                            };
+                            ("morphism", name, detail)
+                        }
+                        Usage::AggregateRoot { aggregate } => {
+                            ("aggregate root", aggregate, String::new())
+                        }
+                        Usage::AggregateMember { aggregate } => {
+                            ("aggregate member", aggregate, String::new())
+                        }
+                        Usage::ContextMap { map, counterpart } => {
+                            ("context map", map, format!("maps to {}", counterpart))
+                        }
+                    };
+                    table.push(vec![ctx.name().to_string(), kind.to_string(), name, detail]);
+                }
+            }
+            Ok(table)
+        }
+        Statement::Path(source, target) => {
+            let mut table = Table::new(&["context", "step", "morphism", "source", "target"]);
+            let sources = containing(workspace, contexts, source)?;
+            let targets = containing(workspace, contexts, target)?;
+            for ctx in sources
+                .into_iter()
+                .filter(|c| targets.iter().any(|t| std::ptr::eq(*t, *c)))
+            {
+                let Some(path) = query.path(ctx.name(), &source.name, &target.name) else {
+                    continue;
+                };
+                if path.is_empty() {
+                    // An object reaches itself by its identity
+                    let identity = ctx
+                        .graph()
+                        .find_object_by_name(&source.name)
+                        .and_then(|o| ctx.graph().get_identity_morphism(o.id))
+                        .map_or_else(|| format!("id_{}", source.name), |m| m.name.clone());
+                    table.push(vec![
+                        ctx.name().to_string(),
+                        "1".to_string(),
+                        identity,
+                        source.name.clone(),
+                        source.name.clone(),
+                    ]);
+                }
+                for (step, morphism) in path.into_iter().enumerate() {
+                    let (from, to) = ends(ctx, morphism);
+                    table.push(vec![
+                        ctx.name().to_string(),
+                        (step + 1).to_string(),
+                        morphism.name.clone(),
+                        from,
+                        to,
+                    ]);
+                }
+            }
+            Ok(table)
+        }
+        Statement::AggregatesUsing(object) => {
+            let mut table = Table::new(&["context", "aggregate"]);
+            for ctx in containing(workspace, contexts, object)? {
+                for aggregate in query.aggregates_using(ctx.name(), &object.name) {
+                    table.push(vec![ctx.name().to_string(), aggregate]);
+                }
+            }
+            Ok(table)
+        }
+        Statement::ConsumersOf(context) => {
+            if workspace.context(context).is_none() {
+                let names: Vec<&str> = workspace.contexts.iter().map(|c| c.name()).collect();
+                return Err(not_found("Context", context, &names));
+            }
+            let mut table = Table::new(&["context", "consumer"]);
+            for consumer in query.consumers_of(context) {
+                table.push(vec![context.clone(), consumer.to_string()]);
+            }
+            Ok(table)
+        }
+    }
+}
+
+/// Get the contexts, among those queried, declaring an object.
+fn containing<'a>(
+    workspace: &Workspace,
+    contexts: &[&'a BoundedContext],
+    object: &ObjectName,
+) -> Result<Vec<&'a BoundedContext>, String> {
+    if let Some(context) = &object.context {
+        if workspace.context(context).is_none() {
+            let names: Vec<&str> = workspace.contexts.iter().map(|c| c.name()).collect();
+            return Err(not_found("Context", context, &names));
+        }
+    }
+
+    let found: Vec<&BoundedContext> = contexts
+        .iter()
+        .copied()
+        .filter(|ctx| object.context.as_deref().is_none_or(|c| c == ctx.name()))
+        .filter(|ctx| ctx.graph().find_object_by_name(&object.name).is_some())
+        .collect();
+    if found.is_empty() {
+        let names: Vec<&str> = contexts
+            .iter()
+            .flat_map(|ctx| ctx.graph().objects().map(|o| o.name.as_str()))
+            .collect();
+        return Err(not_found("Object", &object.name, &names));
+    }
+    Ok(found)
+}
+
+/// Get the names of the objects a morphism goes between.
+fn ends(ctx: &BoundedContext, morphism: &Morphism) -> (String, String) {
+    let name = |id| {
+        ctx.graph()
+            .get_object(id)
+            .map(|o| o.name.clone())
+            .unwrap_or_default()
+    };
+    (name(morphism.source), name(morphism.target))
+}
+
+/// Describe a name that does not exist, suggesting a similar one.
+fn not_found(what: &str, name: &str, candidates: &[&str]) -> String {
+    let mut message = format!("{} '{}' not found", what, name);
+    if let Some(suggestion) = did_you_mean(name, candidates) {
+        message.push_str(&format!("; {}", suggestion));
+    }
+    message
+}
@@ -0,0 +1,247 @@
+//! Images of diagrams for `sketchddd render`.
+//!
+//! Diagrams are laid out by Graphviz's `dot` when it is installed, or by
+//! the layout built into SketchDDD, so that images can be made without
+//! installing anything. The built-in layout draws SVG, which is rasterized
+//! for PNG; it ignores the clusters grouping contexts by subdomain. The
+//! Mermaid CLI, `mmdc`, can be used instead to draw the Mermaid diagrams.
+
+use crate::report::{self, CliError};
+use clap::ValueEnum;
+use layout::backends::svg::SVGWriter;
+use layout::gv::{DotParser, GraphBuilder};
+use resvg::tiny_skia::{Color, Pixmap, Transform};
+use resvg::usvg::fontdb::{Family, Query};
+use resvg::usvg::{Options, Tree};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Format of an image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// Scalable vector graphics
+    Svg,
+
+    /// Portable network graphics
+    Png,
+}
+
+impl ImageFormat {
+    /// Get the format of a name or file extension.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "svg" => Some(ImageFormat::Svg),
+            "png" => Some(ImageFormat::Png),
+            _ => None,
+        }
+    }
+
+    /// Get the extension of files in the format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Svg => "svg",
+            ImageFormat::Png => "png",
+        }
+    }
+}
+
+/// What lays out and draws diagrams.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Default)]
+pub enum Engine {
+    /// Graphviz's dot when it is installed, else the built-in layout
+    #[default]
+    Auto,
+
+    /// The layout built into SketchDDD
+    Builtin,
+
+    /// Graphviz's dot
+    Dot,
+
+    /// The Mermaid CLI
+    Mmdc,
+}
+
+impl Engine {
+    /// Choose the engine `Auto` stands for.
+    pub fn resolve(self) -> Self {
+        match self {
+            Engine::Auto if installed("dot", "-V") => Engine::Dot,
+            Engine::Auto => Engine::Builtin,
+            engine => engine,
+        }
+    }
+
+    /// Get the name of the engine, as given to `--engine`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Engine::Auto => "auto",
+            Engine::Builtin => "builtin",
+            Engine::Dot => "dot",
+            Engine::Mmdc => "mmdc",
+        }
+    }
+
+    /// Get the format of the diagrams the engine draws.
+    pub fn diagram_format(self) -> sketchddd_viz::Format {
+        match self {
+            Engine::Mmdc => sketchddd_viz::Format::Mermaid,
+            _ => sketchddd_viz::Format::Graphviz,
+        }
+    }
+}
+
+/// Check whether a program can be run.
+fn installed(program: &str, version_flag: &str) -> bool {
+    Command::new(program)
+        .arg(version_flag)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok()
+}
+
+/// Draw a diagram, in the format of the engine, as an image.
+///
+/// `Auto` must have been resolved.
+pub fn render(diagram: &str, engine: Engine, format: ImageFormat) -> Result<Vec<u8>, CliError> {
+    tracing::debug!(?engine, ?format, "render");
+    match engine {
+        Engine::Auto | Engine::Builtin => {
+            let svg = layout_svg(diagram)?;
+            match format {
+                ImageFormat::Svg => Ok(svg.into_bytes()),
+                ImageFormat::Png => Ok(rasterize(&svg)?),
+            }
+        }
+        Engine::Dot => run_dot(diagram, format),
+        Engine::Mmdc => run_mmdc(diagram, format),
+    }
+}
+
+/// Lay out a Graphviz diagram with the built-in layout.
+fn layout_svg(dot: &str) -> Result<String, String> {
+    let graph = DotParser::new(dot)
+        .process()
+        .map_err(|e| format!("Cannot lay out the diagram: {}", e))?;
+    let mut builder = GraphBuilder::new();
+    builder.visit_graph(&graph);
+    let mut svg = SVGWriter::new();
+    builder.get().do_it(false, false, false, &mut svg);
+    Ok(svg.finalize())
+}
+
+/// Draw an SVG image as PNG, on a white background.
+fn rasterize(svg: &str) -> Result<Vec<u8>, String> {
+    let mut options = Options::default();
+    let fonts = options.fontdb_mut();
+    fonts.load_system_fonts();
+    // Labels ask for Times; use another font of the system without it
+    let serif = Query {
+        families: &[Family::Serif],
+        ..Query::default()
+    };
+    if fonts.query(&serif).is_none() {
+        let families: Vec<String> = fonts
+            .faces()
+            .filter_map(|face| face.families.first())
+            .map(|(name, _)| name.clone())
+            .collect();
+        let fallback = families
+            .iter()
+            .find(|name| name.ends_with("Serif"))
+            .or(families.first());
+        if let Some(family) = fallback {
+            fonts.set_serif_family(family.clone());
+        }
+    }
+
+    let tree = Tree::from_str(svg, &options).map_err(|e| format!("Invalid SVG: {}", e))?;
+    let size = tree.size().to_int_size();
+    let mut pixmap = Pixmap::new(size.width(), size.height())
+        .ok_or_else(|| "The diagram is too large for an image".to_string())?;
+    pixmap.fill(Color::WHITE);
+    resvg::render(&tree, Transform::default(), &mut pixmap.as_mut());
+    pixmap
+        .encode_png()
+        .map_err(|e| format!("Failed to encode PNG: {}", e))
+}
+
+/// Draw a Graphviz diagram with `dot`.
+fn run_dot(dot: &str, format: ImageFormat) -> Result<Vec<u8>, CliError> {
+    let mut child = Command::new("dot")
+        .arg(format!("-T{}", format.extension()))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            format!(
+                "Failed to run dot: {}; install Graphviz, or use --engine builtin",
+                e
+            )
+        })?;
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(dot.as_bytes())
+        .map_err(|e| format!("Failed to run dot: {}", e))?;
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to run dot: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "dot failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    Ok(output.stdout)
+}
+
+/// Draw a Mermaid diagram with `mmdc`, which reads and writes files.
+fn run_mmdc(diagram: &str, format: ImageFormat) -> Result<Vec<u8>, CliError> {
+    // The Mermaid generators wrap diagrams in Markdown fences
+    let source: String = diagram
+        .lines()
+        .filter(|line| !line.starts_with("```"))
+        .map(|line| format!("{}\n", line))
+        .collect();
+
+    let dir = std::env::temp_dir().join(format!("sketchddd-render-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| report::io_error(format!("Failed to create {}: {}", dir.display(), e)))?;
+    let input = dir.join("diagram.mmd");
+    let output = dir.join(format!("diagram.{}", format.extension()));
+    let result = std::fs::write(&input, source)
+        .map_err(|e| report::io_error(format!("Failed to write {}: {}", input.display(), e)))
+        .and_then(|()| {
+            Command::new("mmdc")
+                .arg("-i")
+                .arg(&input)
+                .arg("-o")
+                .arg(&output)
+                .arg("--quiet")
+                .output()
+                .map_err(|e| {
+                    format!(
+                        "Failed to run mmdc: {}; install @mermaid-js/mermaid-cli, or use --engine builtin",
+                        e
+                    )
+                    .into()
+                })
+        })
+        .and_then(|run| {
+            if run.status.success() {
+                std::fs::read(&output).map_err(|e| report::io_error(format!("Failed to read mmdc's image: {}", e)))
+            } else {
+                Err(format!(
+                    "mmdc failed: {}",
+                    String::from_utf8_lossy(&run.stderr).trim()
+                ).into())
+            }
+        });
+    let _ = std::fs::remove_dir_all(&dir);
+    result
+}
@@ -0,0 +1,212 @@
+//! Reporting of what commands do, and how a run ends.
+//!
+//! Commands tell people what they did through this module rather than by
+//! printing: [`status`] lines, such as "✓ Generated src/model.rs", are left
+//! out with `-q`, and [`detail`] lines are only shown with `-v`. What a
+//! command produces, such as generated code printed on stdout, is printed
+//! with [`output`], which `-q` leaves in.
+//!
+//! Logs of the command's internals go through `tracing` to stderr, as
+//! `key=value` fields: warnings by default, `info` with `-v`, `debug` with
+//! `-vv` and `trace` with `-vvv`. `SKETCHDDD_LOG` sets the filter instead,
+//! as in `SKETCHDDD_LOG=sketchddd=debug`.
+//!
+//! A run stopped by an error ends with the [`ExitCode`] of its [`CliError`]:
+//! one made by [`parse_error`], [`usage_error`], [`io_error`] or
+//! [`internal_error`], or `1` for any other error, such as a model with
+//! errors.
+
+use colored::Colorize;
+use std::fmt::{self, Display};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU8, Ordering};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+/// Environment variable setting the log filter.
+pub const LOG_ENV: &str = "SKETCHDDD_LOG";
+
+/// Verbosity level for output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Verbosity {
+    /// Suppress all non-essential output
+    Quiet,
+    /// Normal output (default)
+    #[default]
+    Normal,
+    /// Verbose output with additional details
+    Verbose,
+}
+
+/// The verbosity of the run, as a [`Verbosity`].
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+/// How a run stopped by an error ended, as the process's exit code; a run
+/// that succeeds exits with 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ExitCode {
+    /// The model has errors, a check failed, or the command could not run
+    Failure = 1,
+
+    /// A model or document could not be parsed, or the command line was
+    /// invalid
+    Parse = 2,
+
+    /// A file could not be read or written
+    Io = 3,
+
+    /// SketchDDD failed, which is a bug
+    Internal = 101,
+}
+
+impl ExitCode {
+    /// Get the code the process exits with.
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+}
+
+/// Set the verbosity of the run and start logging.
+///
+/// `verbose` is the number of `-v` flags, which raise the log level.
+pub fn init(verbosity: Verbosity, verbose: u8) {
+    VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+
+    let level = match (verbosity, verbose) {
+        (Verbosity::Quiet, _) => "error",
+        (_, 0) => "warn",
+        (_, 1) => "warn,sketchddd=info",
+        (_, 2) => "warn,sketchddd=debug",
+        _ => "warn,sketchddd=trace",
+    };
+    let filter = tracing_subscriber::EnvFilter::try_from_env(LOG_ENV)
+        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(level));
+    let ansi = colored::control::SHOULD_COLORIZE.should_colorize();
+    let _ = tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(std::io::stderr)
+                .with_ansi(ansi)
+                .without_time(),
+        )
+        .with(filter)
+        .try_init();
+}
+
+/// Get the verbosity of the run.
+pub fn verbosity() -> Verbosity {
+    match VERBOSITY.load(Ordering::Relaxed) {
+        0 => Verbosity::Quiet,
+        1 => Verbosity::Normal,
+        _ => Verbosity::Verbose,
+    }
+}
+
+/// Print a line of what a command produces on stdout, even if the run is
+/// quiet.
+///
+/// A reader closing stdout early, as `head` does, ends the run: there is
+/// no one left to print for.
+pub fn output(line: impl Display) {
+    if let Err(e) = writeln!(io::stdout().lock(), "{}", line) {
+        if e.kind() == io::ErrorKind::BrokenPipe {
+            std::process::exit(0);
+        }
+        error(format_args!("failed to write to stdout: {}", e));
+        std::process::exit(ExitCode::Io.code());
+    }
+}
+
+/// Print a line telling what a command does, unless the run is quiet.
+pub fn status(line: impl Display) {
+    if verbosity() != Verbosity::Quiet {
+        output(line);
+    }
+}
+
+/// Print a line of detail, if the run is verbose.
+pub fn detail(line: impl Display) {
+    if verbosity() == Verbosity::Verbose {
+        output(line);
+    }
+}
+
+/// Print the action a command starts, as in "Checking model.sddd".
+pub fn heading(action: &str, subject: impl Display) {
+    status(format_args!("{} {}", action.cyan().bold(), subject));
+}
+
+/// Print what a command achieved.
+pub fn success(message: impl Display) {
+    status(format_args!("{} {}", "✓".green().bold(), message));
+}
+
+/// Print a warning about what a command did.
+pub fn warning(message: impl Display) {
+    status(format_args!("{} {}", "⚠".yellow().bold(), message));
+}
+
+/// Print the error stopping the run on stderr, even if the run is quiet.
+pub fn error(message: impl Display) {
+    eprintln!("{}: {}", "error".red().bold(), message);
+}
+
+/// An error stopping the run, with the code the run exits with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CliError {
+    code: ExitCode,
+    message: String,
+}
+
+impl CliError {
+    /// Create an error ending the run with the given code.
+    pub fn new(code: ExitCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// Get the code the run exits with.
+    pub fn code(&self) -> ExitCode {
+        self.code
+    }
+}
+
+impl Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<String> for CliError {
+    fn from(message: String) -> Self {
+        Self::new(ExitCode::Failure, message)
+    }
+}
+
+impl From<&str> for CliError {
+    fn from(message: &str) -> Self {
+        Self::new(ExitCode::Failure, message)
+    }
+}
+
+/// Describe a model or document that could not be parsed.
+pub fn parse_error(message: impl Into<String>) -> CliError {
+    CliError::new(ExitCode::Parse, message)
+}
+
+/// Describe an argument with an invalid value, such as an unknown format.
+pub fn usage_error(message: impl Into<String>) -> CliError {
+    CliError::new(ExitCode::Parse, message)
+}
+
+/// Describe a file that could not be read or written.
+pub fn io_error(message: impl Into<String>) -> CliError {
+    CliError::new(ExitCode::Io, message)
+}
+
+/// Describe a failure of SketchDDD itself.
+pub fn internal_error(message: impl Into<String>) -> CliError {
+    CliError::new(ExitCode::Internal, message)
+}
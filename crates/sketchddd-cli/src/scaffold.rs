@@ -0,0 +1,195 @@
+//! Built-in project templates for `sketchddd init`.
+//!
+//! A template is a set of files, kept under `templates/` in this crate and
+//! compiled into the binary. In their contents, `{name}` is replaced with
+//! the project name, `{model}` with the name of the model file without its
+//! extension, and `{template}` with the template's name. Every project also
+//! gets a `.gitignore` and a `README.md`, and a `sketchddd.toml` unless the
+//! template has its own.
+
+use crate::report::{self, CliError};
+use std::path::Path;
+
+/// A built-in project template.
+pub struct Template {
+    /// Name given to `--template`
+    pub name: &'static str,
+
+    /// Other names accepted for the template
+    pub aliases: &'static [&'static str],
+
+    /// What the template models
+    pub description: &'static str,
+
+    /// What the model contains, one item per line of `template info`
+    pub contents: &'static [&'static str],
+
+    /// Files besides the model, by path relative to the project
+    files: &'static [(&'static str, &'static str)],
+
+    /// Source of the model
+    model: &'static str,
+}
+
+const GITIGNORE: &str = include_str!("../templates/gitignore");
+const README: &str = include_str!("../templates/README.md");
+const CONFIG: &str = include_str!("../templates/sketchddd.toml");
+
+/// The built-in templates.
+pub const TEMPLATES: &[Template] = &[
+    Template {
+        name: "minimal",
+        aliases: &[],
+        description: "minimal project",
+        contents: &["Single bounded context with commented examples"],
+        files: &[],
+        model: include_str!("../templates/minimal/model.sddd"),
+    },
+    Template {
+        name: "commerce",
+        aliases: &["ecommerce"],
+        description: "e-commerce domain",
+        contents: &[
+            "Catalog, Ordering and Billing contexts in a Commerce domain",
+            "Customer, Order, Invoice entities and Money, Address value objects",
+            "Order lifecycle from Draft to Delivered",
+            "Context maps between the three contexts",
+        ],
+        files: &[],
+        model: include_str!("../templates/commerce/model.sddd"),
+    },
+    Template {
+        name: "banking",
+        aliases: &[],
+        description: "banking domain",
+        contents: &[
+            "Customers, Accounts and Payments contexts in a Banking domain",
+            "Account and Transfer aggregates with lifecycles",
+            "Owning teams recorded with @meta",
+            "Project lint rule, written as a script, requiring an owner per aggregate",
+        ],
+        files: &[
+            (
+                "sketchddd.toml",
+                include_str!("../templates/banking/sketchddd.toml"),
+            ),
+            (
+                "rules/owner.rhai",
+                include_str!("../templates/banking/rules/owner.rhai"),
+            ),
+        ],
+        model: include_str!("../templates/banking/model.sddd"),
+    },
+    Template {
+        name: "healthcare",
+        aliases: &[],
+        description: "healthcare domain",
+        contents: &[
+            "Patients, Scheduling and Clinical contexts in a Healthcare domain",
+            "Personal data tagged as pii",
+            "Appointment lifecycle from Requested to Completed",
+            "Anti-corruption layer from appointments to encounters",
+        ],
+        files: &[(
+            "sketchddd.toml",
+            include_str!("../templates/healthcare/sketchddd.toml"),
+        )],
+        model: include_str!("../templates/healthcare/model.sddd"),
+    },
+    Template {
+        name: "event-sourced",
+        aliases: &[],
+        description: "event-sourced domain",
+        contents: &[
+            "Ordering and Fulfillment contexts with commands and events",
+            "Actor, policy and hotspot from an event storming session",
+            "Fulfillment saga reacting to published events",
+        ],
+        files: &[(
+            "sketchddd.toml",
+            include_str!("../templates/event-sourced/sketchddd.toml"),
+        )],
+        model: include_str!("../templates/event-sourced/model.sddd"),
+    },
+    Template {
+        name: "microservices",
+        aliases: &[],
+        description: "microservices architecture",
+        contents: &[
+            "Orders, Inventory and Shipping contexts",
+            "Context maps with the CustomerSupplier pattern",
+        ],
+        files: &[],
+        model: include_str!("../templates/microservices/model.sddd"),
+    },
+];
+
+/// Find a built-in template by name or alias.
+pub fn find(name: &str) -> Option<&'static Template> {
+    TEMPLATES
+        .iter()
+        .find(|t| t.name == name || t.aliases.contains(&name))
+}
+
+impl Template {
+    /// Get the files of a project, by path relative to the project
+    /// directory; the model comes first.
+    pub fn files(&self, name: &str) -> Vec<(String, String)> {
+        let mut files = vec![(format!("{}.sddd", name.to_lowercase()), self.model(name))];
+        files.extend(
+            self.files
+                .iter()
+                .map(|(path, content)| (path.to_string(), self.fill(content, name))),
+        );
+        if !self.files.iter().any(|(path, _)| *path == "sketchddd.toml") {
+            files.push(("sketchddd.toml".to_string(), CONFIG.to_string()));
+        }
+        files.push((".gitignore".to_string(), GITIGNORE.to_string()));
+        files.push(("README.md".to_string(), self.fill(README, name)));
+        files
+    }
+
+    /// Get the source of the model of a project.
+    pub fn model(&self, name: &str) -> String {
+        self.fill(self.model, name)
+    }
+
+    /// Replace the placeholders of a file's contents.
+    fn fill(&self, content: &str, name: &str) -> String {
+        content
+            .replace("{name}", name)
+            .replace("{model}", &name.to_lowercase())
+            .replace("{template}", self.name)
+    }
+
+    /// Write the files of a project into its directory, returning the paths
+    /// written and the paths skipped.
+    ///
+    /// Files that already exist, such as the `.gitignore` of a repository
+    /// the project is created in, are kept; an existing model is an error.
+    pub fn write(&self, name: &str, dir: &Path) -> Result<(Vec<String>, Vec<String>), CliError> {
+        let files = self.files(name);
+        let model = dir.join(&files[0].0);
+        if model.exists() {
+            return Err(format!("{} already exists", model.display()).into());
+        }
+
+        let (mut written, mut skipped) = (Vec::new(), Vec::new());
+        for (path, content) in files {
+            let full = dir.join(&path);
+            if full.exists() {
+                skipped.push(path);
+                continue;
+            }
+            if let Some(parent) = full.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| report::io_error(format!("Failed to create directory: {}", e)))?;
+            }
+            std::fs::write(&full, content).map_err(|e| {
+                report::io_error(format!("Failed to write {}: {}", full.display(), e))
+            })?;
+            written.push(path);
+        }
+        Ok((written, skipped))
+    }
+}
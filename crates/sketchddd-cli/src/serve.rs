@@ -0,0 +1,648 @@
+//! The `serve` command: the visual builder and an API over a model.
+//!
+//! The server loads a model file, or the model files of a project, and keeps
+//! the model loaded, reloading it when a file changes on disk. Its API works
+//! on that model:
+//!
+//! | Endpoint | Response |
+//! |----------|----------|
+//! | `GET /api/model` | The source and the model, in the export format |
+//! | `GET /api/model/binary` | The model in the binary format, for large models |
+//! | `POST /api/parse` | The model of the source in the request body |
+//! | `GET /api/validate` | The validation report, as `check --format json` |
+//! | `GET /api/codegen/{target}` | The generated code of each context |
+//! | `GET /api/viz/{format}` | The diagram of every context, or of `?context=` |
+//! | `GET /api/events` | A `reload` server-sent event after each reload |
+//! | `GET /api/collab` | A WebSocket for editing the model with others |
+//!
+//! Errors are JSON objects with an `error` message. Every other path is
+//! served from the visual builder's assets, the `web/dist` directory built
+//! by `npm run build`, falling back to its `index.html` so the builder's
+//! routes work. Without assets, a small page showing the diagrams of the
+//! model is served instead.
+//!
+//! Collaborators editing the model in the builder each open the WebSocket,
+//! and are sent a `snapshot` of the model with its revision. They send the
+//! [`EditCommand`](sketchddd_core::EditCommand)s they make as
+//! `{ "base": revision, "context": name, "command": command }`, the base
+//! being the revision they last saw. The server puts the edits in order:
+//! each one it applies is sent to every collaborator as an `edit`, with
+//! the revision it makes, and one conflicting with an edit that arrived
+//! first is sent back to its author only, as `rejected`. The API's model,
+//! issues, code and diagrams are those of the edited model.
+//!
+//! Edits are kept in the server's memory only. The files stay the source
+//! of the model: when they change, the model is loaded from them again and
+//! the edits made since the last load are discarded. Everyone is sent a
+//! new snapshot, counting the edits discarded, and the server warns of
+//! them.
+//!
+//! The issues, generated code and diagrams of each context are kept
+//! between requests with the fingerprint of the context, so after a reload
+//! only the contexts that changed are validated, generated and drawn again.
+
+use crate::config;
+use crate::project::{ModelSource, Sources};
+use crate::report;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path as UrlPath, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use colored::Colorize;
+use notify::{RecursiveMode, Watcher};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sketchddd_codegen::Target;
+use sketchddd_core::{
+    Collaboration, Fingerprint, Fingerprints, Memo, ModelDocument, Operation, ValidationCache,
+    Workspace,
+};
+use sketchddd_parser::{parse_file, transform, File};
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+use tower_http::services::{ServeDir, ServeFile};
+
+/// Page served when the visual builder's assets are not available.
+const FALLBACK_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+  <meta charset="utf-8">
+  <title>SketchDDD</title>
+  <script src="https://cdn.jsdelivr.net/npm/mermaid@10/dist/mermaid.min.js"></script>
+</head>
+<body style="font-family: sans-serif">
+  <p>The visual builder is not built; run <code>npm run build</code> in
+  <code>web/</code> or pass <code>--assets</code>. Showing the model's diagrams.</p>
+  <pre id="error" style="color: crimson"></pre>
+  <div id="diagrams"></div>
+  <script>
+    mermaid.initialize({ startOnLoad: false });
+    async function render() {
+      const response = await fetch("/api/viz/mermaid");
+      const text = await response.text();
+      document.getElementById("error").textContent = response.ok ? "" : text;
+      if (!response.ok) return;
+      const { svg } = await mermaid.render("model", text);
+      document.getElementById("diagrams").innerHTML = svg;
+    }
+    render();
+    new EventSource("/api/events").addEventListener("reload", render);
+  </script>
+</body>
+</html>
+"#;
+
+/// The loaded model.
+struct Loaded {
+    /// Source of the model, unless its files could not be read
+    sources: Option<Sources>,
+
+    /// The model, or why the files do not describe one
+    workspace: Result<Workspace, String>,
+
+    /// Fingerprints of the model's contexts and maps
+    fingerprints: Fingerprints,
+}
+
+/// What the handlers derive from the model, kept across reloads.
+#[derive(Default)]
+struct Derived {
+    /// Issues of each context and context map
+    validation: ValidationCache,
+
+    /// Generated code of each context, by target
+    code: Memo<(String, Target), Result<String, String>>,
+
+    /// Diagram of each context, by format
+    diagrams: Memo<(String, sketchddd_viz::Format), Result<String, String>>,
+}
+
+/// The model as collaborators edit it.
+struct Shared {
+    /// The edited model, or why the files do not describe one
+    collaboration: Result<Collaboration, String>,
+
+    /// Fingerprints of the edited model's contexts and maps
+    fingerprints: Fingerprints,
+
+    /// The current revision, counting reloads
+    revision: u64,
+
+    /// Number of edits applied since the model was loaded
+    edits: u64,
+}
+
+impl Shared {
+    /// Start editing a loaded model.
+    fn new(loaded: &Loaded, revision: u64) -> Self {
+        Self {
+            collaboration: loaded
+                .workspace
+                .clone()
+                .map(|workspace| Collaboration::new(workspace, revision)),
+            fingerprints: loaded.fingerprints.clone(),
+            revision,
+            edits: 0,
+        }
+    }
+
+    /// Describe the model for a collaborator starting from it.
+    fn snapshot(&self) -> Value {
+        let (model, error) = match &self.collaboration {
+            Ok(collaboration) => (
+                ModelDocument::new(collaboration.workspace().clone()).to_value(),
+                None,
+            ),
+            Err(e) => (Value::Null, Some(e.clone())),
+        };
+        json!({
+            "type": "snapshot",
+            "revision": self.revision,
+            "model": model,
+            "error": error,
+        })
+    }
+}
+
+/// An edit a collaborator sends.
+#[derive(Deserialize)]
+struct EditRequest {
+    /// The revision the edit was made on
+    base: u64,
+
+    #[serde(flatten)]
+    operation: Operation,
+}
+
+/// State shared by the handlers.
+struct AppState {
+    /// The model being served
+    model: ModelSource,
+
+    /// The model as last loaded
+    loaded: RwLock<Loaded>,
+
+    /// What was derived from the model as loaded before
+    derived: Mutex<Derived>,
+
+    /// Notifies event streams of reloads
+    reloads: broadcast::Sender<()>,
+
+    /// The model as collaborators edit it
+    shared: Mutex<Shared>,
+
+    /// Sends collaborators each edit, and a snapshot after each reload
+    edits: broadcast::Sender<String>,
+
+    /// Number of collaborators who joined, numbering the next one
+    collaborators: AtomicU64,
+}
+
+type SharedState = Arc<AppState>;
+
+impl AppState {
+    /// Load the model again and notify the event streams, returning the
+    /// number of edits discarded.
+    fn reload(&self) -> u64 {
+        let loaded = load(&self.model);
+        let discarded = {
+            // Edits made on the model replaced are stale
+            let mut shared = self.shared.lock().expect("edit lock poisoned");
+            let discarded = shared.edits;
+            *shared = Shared::new(&loaded, shared.revision + 1);
+            let mut snapshot = shared.snapshot();
+            snapshot["discarded"] = json!(discarded);
+            let _ = self.edits.send(snapshot.to_string());
+            discarded
+        };
+        if let Ok(workspace) = &loaded.workspace {
+            // Outputs of removed contexts are never asked for again
+            let mut derived = self.derived.lock().expect("derived lock poisoned");
+            let present = |name: &String| workspace.context(name).is_some();
+            derived.code.retain(|(name, _)| present(name));
+            derived.diagrams.retain(|(name, _)| present(name));
+        }
+        *self.loaded.write().expect("model lock poisoned") = loaded;
+        // Nobody listening is fine
+        let _ = self.reloads.send(());
+        discarded
+    }
+
+    /// Take a collaborator's edit, and describe it as rejected unless it
+    /// applies.
+    fn edit(&self, client: u64, request: &str) -> Option<String> {
+        let request: EditRequest = match serde_json::from_str(request) {
+            Ok(request) => request,
+            Err(e) => {
+                let error = format!("Invalid edit: {}", e);
+                return Some(json!({ "type": "rejected", "error": error }).to_string());
+            }
+        };
+        let mut shared = self.shared.lock().expect("edit lock poisoned");
+        let collaboration = match &mut shared.collaboration {
+            Ok(collaboration) => collaboration,
+            Err(e) => {
+                let rejected = json!({ "type": "rejected", "base": request.base, "error": e });
+                return Some(rejected.to_string());
+            }
+        };
+        match collaboration.apply(request.base, request.operation) {
+            Ok(applied) => {
+                shared.fingerprints = Fingerprints::of(collaboration.workspace());
+                shared.revision = applied.revision;
+                shared.edits += 1;
+                let edit = json!({
+                    "type": "edit",
+                    "client": client,
+                    "revision": applied.revision,
+                    "context": applied.operation.context,
+                    "command": applied.operation.command,
+                    "edited": applied.edited,
+                });
+                // Sent while locked, so collaborators get edits in order
+                let _ = self.edits.send(edit.to_string());
+                None
+            }
+            Err(e) => {
+                let rejected = json!({
+                    "type": "rejected",
+                    "base": request.base,
+                    "error": e.to_string(),
+                });
+                Some(rejected.to_string())
+            }
+        }
+    }
+
+    /// Run `f` on the edited model and its fingerprints, or fail if the
+    /// files do not describe one.
+    fn with_workspace<T>(
+        &self,
+        f: impl FnOnce(&Workspace, &Fingerprints) -> Result<T, ApiError>,
+    ) -> Result<T, ApiError> {
+        let shared = self.shared.lock().expect("edit lock poisoned");
+        match &shared.collaboration {
+            Ok(collaboration) => f(collaboration.workspace(), &shared.fingerprints),
+            Err(e) => Err(ApiError(StatusCode::UNPROCESSABLE_ENTITY, e.clone())),
+        }
+    }
+}
+
+/// An error answered as `{ "error": message }`.
+pub(crate) struct ApiError(pub(crate) StatusCode, pub(crate) String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+/// Parse and transform the source of a model.
+pub(crate) fn parse_model(source: &str) -> Result<Workspace, String> {
+    let ast = parse_file(source).map_err(|e| format!("Parse error: {}", e))?;
+    transform_model(&ast)
+}
+
+/// Transform the syntax of a model.
+fn transform_model(ast: &File) -> Result<Workspace, String> {
+    let result = transform(ast).map_err(|e| format!("Transform error: {}", e))?;
+    Ok(result.into_workspace())
+}
+
+/// Load a model, keeping the error if it cannot be loaded.
+fn load(model: &ModelSource) -> Loaded {
+    match model.read() {
+        Ok(sources) => {
+            let workspace = sources
+                .parse()
+                .map_err(|e| e.to_string())
+                .and_then(|ast| transform_model(&ast));
+            Loaded {
+                fingerprints: workspace.as_ref().map(Fingerprints::of).unwrap_or_default(),
+                workspace,
+                sources: Some(sources),
+            }
+        }
+        Err(e) => Loaded {
+            sources: None,
+            workspace: Err(e.to_string()),
+            fingerprints: Fingerprints::default(),
+        },
+    }
+}
+
+/// Build the application serving a model.
+fn router(state: SharedState, assets: Option<&Path>) -> Router {
+    let api = Router::new()
+        .route("/api/model", get(model))
+        .route("/api/model/binary", get(model_binary))
+        .route("/api/parse", post(parse))
+        .route("/api/validate", get(validate))
+        .route("/api/codegen/:target", get(codegen))
+        .route("/api/viz/:format", get(viz))
+        .route("/api/events", get(events))
+        .route("/api/collab", get(collab))
+        .with_state(state);
+
+    match assets {
+        Some(dir) => api
+            .fallback_service(ServeDir::new(dir).fallback(ServeFile::new(dir.join("index.html")))),
+        None => api.fallback(|| async { Html(FALLBACK_PAGE) }),
+    }
+}
+
+async fn model(State(state): State<SharedState>) -> Json<Value> {
+    let shared = state.shared.lock().expect("edit lock poisoned");
+    let (model, error) = match &shared.collaboration {
+        Ok(collaboration) => (
+            ModelDocument::new(collaboration.workspace().clone()).to_value(),
+            None,
+        ),
+        Err(e) => (Value::Null, Some(e.clone())),
+    };
+    // The source is that of the files, without the edits
+    let loaded = state.loaded.read().expect("model lock poisoned");
+    Json(json!({
+        "file": state.model.path().display().to_string(),
+        "source": loaded.sources.as_ref().map_or("", |s| s.text.as_str()),
+        "revision": shared.revision,
+        "model": model,
+        "error": error,
+    }))
+}
+
+async fn model_binary(State(state): State<SharedState>) -> Result<Response, ApiError> {
+    let bytes = state.with_workspace(|workspace, _| Ok(workspace.to_bytes()))?;
+    Ok(([(header::CONTENT_TYPE, "application/octet-stream")], bytes).into_response())
+}
+
+async fn parse(source: String) -> Result<Json<Value>, ApiError> {
+    parse_model(&source)
+        .map(|workspace| Json(ModelDocument::new(workspace).to_value()))
+        .map_err(|e| ApiError(StatusCode::UNPROCESSABLE_ENTITY, e))
+}
+
+async fn validate(State(state): State<SharedState>) -> Result<Json<Value>, ApiError> {
+    let file = state.model.path();
+    let (registry, lint_config) = config::load_lint_config(file)
+        .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let mut result = state.with_workspace(|workspace, fingerprints| {
+        let mut derived = state.derived.lock().expect("derived lock poisoned");
+        derived.validation.mark_changed(workspace, fingerprints);
+        Ok(derived.validation.lint(workspace, &registry, &lint_config))
+    })?;
+    // Issues of a project name their file
+    let loaded = state.loaded.read().expect("model lock poisoned");
+    if let (Some(sources), Some(_)) = (&loaded.sources, state.model.project()) {
+        sources.relocate(&mut result.issues);
+    }
+    let report = result.to_json_report(Some(&file.display().to_string()));
+    Ok(Json(json!(report)))
+}
+
+async fn codegen(
+    State(state): State<SharedState>,
+    UrlPath(target): UrlPath<String>,
+) -> Result<Json<BTreeMap<String, String>>, ApiError> {
+    let target: Target = target.parse().map_err(|_| {
+        ApiError(
+            StatusCode::NOT_FOUND,
+            format!("Unknown target language: {}", target),
+        )
+    })?;
+    state.with_workspace(|workspace, fingerprints| {
+        let mut derived = state.derived.lock().expect("derived lock poisoned");
+        workspace
+            .contexts
+            .iter()
+            .zip(&fingerprints.contexts)
+            .map(|(context, &fingerprint)| {
+                let key = (context.name().to_string(), target);
+                let code = derived.code.get_or_derive(key, fingerprint, || {
+                    sketchddd_codegen::generate(context, target).map_err(|e| e.to_string())
+                });
+                code.clone().map(|code| (context.name().to_string(), code))
+            })
+            .collect::<Result<BTreeMap<_, _>, _>>()
+            .map(Json)
+            .map_err(|e| {
+                ApiError(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    format!("Code generation error: {}", e),
+                )
+            })
+    })
+}
+
+#[derive(Deserialize)]
+struct VizQuery {
+    context: Option<String>,
+}
+
+async fn viz(
+    State(state): State<SharedState>,
+    UrlPath(format): UrlPath<String>,
+    Query(query): Query<VizQuery>,
+) -> Result<String, ApiError> {
+    let format: sketchddd_viz::Format = format.parse().map_err(|_| {
+        ApiError(
+            StatusCode::NOT_FOUND,
+            format!("Unknown visualization format: {}", format),
+        )
+    })?;
+    let theme = state
+        .model
+        .viz()
+        .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .theme();
+    state.with_workspace(|workspace, fingerprints| {
+        let contexts: Vec<_> = workspace
+            .contexts
+            .iter()
+            .zip(&fingerprints.contexts)
+            .filter(|(c, _)| query.context.as_deref().is_none_or(|name| c.name() == name))
+            .collect();
+        if let (Some(name), true) = (&query.context, contexts.is_empty()) {
+            return Err(ApiError(
+                StatusCode::NOT_FOUND,
+                format!("Context '{}' not found", name),
+            ));
+        }
+        let mut derived = state.derived.lock().expect("derived lock poisoned");
+        let diagrams = contexts
+            .into_iter()
+            .map(|(context, &fingerprint)| {
+                // The theme is read again on each request, and may change
+                let input = Fingerprint::of(&(fingerprint, &theme));
+                let key = (context.name().to_string(), format);
+                derived
+                    .diagrams
+                    .get_or_derive(key, input, || {
+                        sketchddd_viz::generate_with_theme(context, format, &theme)
+                            .map_err(|e| e.to_string())
+                    })
+                    .clone()
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| {
+                ApiError(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    format!("Visualization error: {}", e),
+                )
+            })?;
+        Ok(diagrams.join("\n"))
+    })
+}
+
+async fn events(
+    State(state): State<SharedState>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let reloads = BroadcastStream::new(state.reloads.subscribe())
+        .map(|_| Ok(Event::default().event("reload").data("")));
+    Sse::new(reloads).keep_alive(KeepAlive::default())
+}
+
+async fn collab(State(state): State<SharedState>, upgrade: WebSocketUpgrade) -> Response {
+    upgrade.on_upgrade(move |socket| collaborate(state, socket))
+}
+
+/// Relay the edits of a collaborator and of the others, until either side
+/// closes the socket.
+async fn collaborate(state: SharedState, mut socket: WebSocket) {
+    let client = state.collaborators.fetch_add(1, Ordering::Relaxed) + 1;
+    // Subscribe while locked, so that no edit falls between the snapshot
+    // and the first edit sent
+    let (mut snapshot, mut edits) = {
+        let shared = state.shared.lock().expect("edit lock poisoned");
+        (shared.snapshot(), state.edits.subscribe())
+    };
+    snapshot["client"] = json!(client);
+    if socket
+        .send(Message::Text(snapshot.to_string()))
+        .await
+        .is_err()
+    {
+        return;
+    }
+
+    loop {
+        let reply = tokio::select! {
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(request))) => state.edit(client, &request),
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => None,
+            },
+            edit = edits.recv() => match edit {
+                Ok(edit) => Some(edit),
+                // Too slow to follow the edits, so start again from the model
+                Err(RecvError::Lagged(_)) => {
+                    let shared = state.shared.lock().expect("edit lock poisoned");
+                    edits = edits.resubscribe();
+                    Some(shared.snapshot().to_string())
+                }
+                Err(RecvError::Closed) => return,
+            },
+        };
+        if let Some(reply) = reply {
+            if socket.send(Message::Text(reply)).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Serve a model until the process is stopped.
+pub fn serve(
+    model: ModelSource,
+    host: &str,
+    port: u16,
+    assets: Option<&Path>,
+    open: bool,
+) -> Result<(), String> {
+    if let Some(dir) = assets.filter(|dir| !dir.join("index.html").is_file()) {
+        return Err(format!(
+            "No index.html in {}; build the visual builder with `npm run build`",
+            dir.display()
+        ));
+    }
+
+    let loaded = load(&model);
+    if let Err(e) = &loaded.workspace {
+        report::warning(e);
+    }
+    // Editors often replace a file rather than write it, so watch the
+    // directory of a model file, and every directory of a project
+    let (dir, mode) = match model.project() {
+        Some(project) => (
+            config::model_dir(&project.manifest),
+            RecursiveMode::Recursive,
+        ),
+        None => (config::model_dir(model.path()), RecursiveMode::NonRecursive),
+    };
+    let dir = dir.ok_or_else(|| "Failed to find the model's directory".to_string())?;
+    let state = Arc::new(AppState {
+        model,
+        shared: Mutex::new(Shared::new(&loaded, 0)),
+        loaded: RwLock::new(loaded),
+        derived: Mutex::default(),
+        reloads: broadcast::channel(16).0,
+        edits: broadcast::channel(256).0,
+        collaborators: AtomicU64::new(0),
+    });
+
+    let watched = state.clone();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let Ok(event) = event else { return };
+        let touched = event.kind.is_modify() || event.kind.is_create();
+        if touched && event.paths.iter().any(|p| watched.model.depends_on(p)) {
+            let discarded = watched.reload();
+            if discarded > 0 {
+                report::warning(format_args!(
+                    "Discarded {} edit(s) made since the model was loaded",
+                    discarded
+                ));
+            }
+            match &watched
+                .loaded
+                .read()
+                .expect("model lock poisoned")
+                .workspace
+            {
+                Ok(_) => report::heading("Reloaded", watched.model.path().display()),
+                Err(e) => report::warning(e),
+            }
+        }
+    })
+    .map_err(|e| format!("Failed to watch {}: {}", dir.display(), e))?;
+    watcher
+        .watch(&dir, mode)
+        .map_err(|e| format!("Failed to watch {}: {}", dir.display(), e))?;
+
+    let runtime =
+        tokio::runtime::Runtime::new().map_err(|e| format!("Failed to start the server: {}", e))?;
+    runtime.block_on(async {
+        let listener = tokio::net::TcpListener::bind((host, port))
+            .await
+            .map_err(|e| format!("Failed to listen on {}:{}: {}", host, port, e))?;
+        let url = format!("http://{}:{}", host, port);
+        report::status(format!("{} {}", "Serving".green().bold(), url));
+        if open {
+            if let Err(e) = open::that(&url) {
+                report::warning(format_args!("Failed to open a browser: {}", e));
+            }
+        }
+        axum::serve(listener, router(state, assets))
+            .await
+            .map_err(|e| format!("Server error: {}", e))
+    })
+}
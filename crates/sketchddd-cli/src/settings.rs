@@ -0,0 +1,345 @@
+//! Per-user settings from `~/.config/sketchddd/config.toml`.
+//!
+//! The settings are the user's defaults across projects. A project's
+//! `sketchddd.toml` wins over them where both set a value, as its
+//! `[codegen]` targets do:
+//!
+//! ```toml
+//! color = "auto"          # auto, always or never
+//!
+//! [codegen]
+//! targets = ["rust"]      # generated when neither --target nor the project gives any
+//!
+//! [telemetry]
+//! enabled = false         # opt out of usage reports
+//!
+//! [update]
+//! check = true            # whether `sketchddd update` looks for releases
+//! ```
+//!
+//! `$XDG_CONFIG_HOME` moves the file, and `SKETCHDDD_CONFIG` names another
+//! one. `sketchddd config get` and `sketchddd config set` read and write it.
+
+use crate::project::Project;
+use crate::report::{self, CliError};
+use serde::Deserialize;
+use sketchddd_codegen::Target;
+use sketchddd_core::did_you_mean;
+use std::path::PathBuf;
+use std::str::FromStr;
+use toml::{Table, Value};
+
+/// Environment variable naming the settings file.
+pub const CONFIG_ENV: &str = "SKETCHDDD_CONFIG";
+
+/// When output is colored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorChoice {
+    /// When writing to a terminal, unless `NO_COLOR` is set
+    #[default]
+    Auto,
+
+    /// Always
+    Always,
+
+    /// Never
+    Never,
+}
+
+impl ColorChoice {
+    /// Color the output of the process as chosen.
+    pub fn apply(self) {
+        match self {
+            ColorChoice::Auto => {}
+            ColorChoice::Always => colored::control::set_override(true),
+            ColorChoice::Never => colored::control::set_override(false),
+        }
+    }
+}
+
+/// The user's settings.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    /// When output is colored
+    pub color: ColorChoice,
+
+    /// Defaults of `codegen`
+    pub codegen: CodegenSettings,
+
+    /// Usage reports
+    pub telemetry: TelemetrySettings,
+
+    /// Checks for new releases
+    pub update: UpdateSettings,
+}
+
+/// The `[codegen]` section of the settings.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CodegenSettings {
+    /// Targets generated when neither `--target` nor the project gives any
+    pub targets: Vec<String>,
+}
+
+/// The `[telemetry]` section of the settings.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TelemetrySettings {
+    /// Whether usage reports may be sent
+    pub enabled: bool,
+}
+
+impl Default for TelemetrySettings {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+/// The `[update]` section of the settings.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UpdateSettings {
+    /// Whether `sketchddd update` looks for releases
+    pub check: bool,
+}
+
+impl Default for UpdateSettings {
+    fn default() -> Self {
+        Self { check: true }
+    }
+}
+
+/// Get the path of the settings file, whether or not it exists.
+pub fn path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os(CONFIG_ENV).filter(|p| !p.is_empty()) {
+        return Some(PathBuf::from(path));
+    }
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".config")))?;
+    Some(config_dir.join("sketchddd").join("config.toml"))
+}
+
+impl Settings {
+    /// Load the user's settings, or the defaults if there is no file.
+    pub fn load() -> Result<Self, String> {
+        let table = read_table()?;
+        Self::from_table(table).map_err(|e| match path() {
+            Some(path) => format!("{}: {}", path.display(), e),
+            None => e,
+        })
+    }
+
+    /// Read settings from a parsed settings file.
+    pub fn from_table(table: Table) -> Result<Self, String> {
+        Settings::deserialize(Value::Table(table)).map_err(|e| e.message().to_string())
+    }
+}
+
+/// Read the settings file as a table, empty if there is no file.
+fn read_table() -> Result<Table, String> {
+    let Some(path) = path().filter(|p| p.is_file()) else {
+        return Ok(Table::new());
+    };
+    tracing::debug!(settings = %path.display(), "user settings");
+    let source = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    source
+        .parse()
+        .map_err(|e: toml::de::Error| format!("{}: {}", path.display(), e.message()))
+}
+
+/// A setting, named by its path in the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    /// `color`
+    Color,
+
+    /// `codegen.targets`
+    CodegenTargets,
+
+    /// `telemetry.enabled`
+    TelemetryEnabled,
+
+    /// `update.check`
+    UpdateCheck,
+}
+
+impl Key {
+    /// Every setting.
+    pub const ALL: [Key; 4] = [
+        Key::Color,
+        Key::CodegenTargets,
+        Key::TelemetryEnabled,
+        Key::UpdateCheck,
+    ];
+
+    /// Get the path of the setting in the file.
+    pub fn name(self) -> &'static str {
+        match self {
+            Key::Color => "color",
+            Key::CodegenTargets => "codegen.targets",
+            Key::TelemetryEnabled => "telemetry.enabled",
+            Key::UpdateCheck => "update.check",
+        }
+    }
+
+    /// Parse a value of the setting as given on the command line: a list
+    /// is comma-separated.
+    pub fn parse_value(self, value: &str) -> Result<Value, String> {
+        let invalid = |expected: &str| {
+            format!(
+                "Invalid value '{}' for {}; expected {}",
+                value,
+                self.name(),
+                expected
+            )
+        };
+        match self {
+            Key::Color => match value {
+                "auto" | "always" | "never" => Ok(Value::from(value)),
+                _ => Err(invalid("auto, always or never")),
+            },
+            Key::CodegenTargets => {
+                let mut targets = Vec::new();
+                for target in value.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+                    target.parse::<Target>().map_err(|_| {
+                        invalid("rust, typescript, kotlin, python, java, clojure or haskell")
+                    })?;
+                    targets.push(Value::from(target));
+                }
+                Ok(Value::Array(targets))
+            }
+            Key::TelemetryEnabled | Key::UpdateCheck => value
+                .parse::<bool>()
+                .map(Value::from)
+                .map_err(|_| invalid("true or false")),
+        }
+    }
+}
+
+impl FromStr for Key {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        if let Some(key) = Key::ALL.into_iter().find(|k| k.name() == s) {
+            return Ok(key);
+        }
+        let names: Vec<&str> = Key::ALL.iter().map(|k| k.name()).collect();
+        let mut message = format!("Unknown setting '{}'", s);
+        match did_you_mean(s, &names) {
+            Some(suggestion) => message.push_str(&format!("; {}", suggestion)),
+            None => message.push_str(&format!("; expected one of {}", names.join(", "))),
+        }
+        Err(message)
+    }
+}
+
+/// Where the value of a setting comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin {
+    /// The project's `sketchddd.toml`
+    Project(PathBuf),
+
+    /// The user's settings file
+    User(PathBuf),
+
+    /// Nothing sets it
+    Default,
+}
+
+impl Origin {
+    /// Describe where the value comes from.
+    pub fn describe(&self) -> String {
+        match self {
+            Origin::Project(path) | Origin::User(path) => path.display().to_string(),
+            Origin::Default => "default".to_string(),
+        }
+    }
+}
+
+/// Get the value a setting takes, with the project's configuration
+/// winning over the user's settings, and where it comes from.
+pub fn get(key: Key, project: Option<&Project>) -> Result<(Value, Origin), CliError> {
+    if let (Key::CodegenTargets, Some(project)) = (key, project) {
+        if !project.codegen.targets.is_empty() {
+            let targets = project.codegen.targets.clone().into();
+            return Ok((targets, Origin::Project(project.manifest.clone())));
+        }
+    }
+
+    let table = read_table()?;
+    if let Some(value) = lookup(&table, key.name()) {
+        let path = path().unwrap_or_default();
+        return Ok((value.clone(), Origin::User(path)));
+    }
+
+    let defaults = Settings::default();
+    let value = match key {
+        Key::Color => Value::from("auto"),
+        Key::CodegenTargets => Value::Array(Vec::new()),
+        Key::TelemetryEnabled => Value::from(defaults.telemetry.enabled),
+        Key::UpdateCheck => Value::from(defaults.update.check),
+    };
+    Ok((value, Origin::Default))
+}
+
+/// Set a setting in the user's settings file, returning the file.
+///
+/// The file, and its directory, are created if needed. A value that would
+/// make the file invalid leaves it untouched.
+pub fn set(key: Key, value: Value) -> Result<PathBuf, CliError> {
+    let path = path().ok_or("Could not find the configuration directory")?;
+    let mut table = read_table()?;
+    insert(&mut table, key.name(), value)?;
+    Settings::from_table(table.clone()).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| report::io_error(format!("Failed to create {}: {}", dir.display(), e)))?;
+    }
+    let source = toml::to_string_pretty(&table)
+        .map_err(|e| report::io_error(format!("Failed to write {}: {}", path.display(), e)))?;
+    std::fs::write(&path, source)
+        .map_err(|e| report::io_error(format!("Failed to write {}: {}", path.display(), e)))?;
+    Ok(path)
+}
+
+/// Get the value at a dotted path of a table.
+fn lookup<'a>(table: &'a Table, key: &str) -> Option<&'a Value> {
+    let (section, name) = match key.split_once('.') {
+        Some((section, name)) => (Some(section), name),
+        None => (None, key),
+    };
+    match section {
+        Some(section) => table.get(section)?.as_table()?.get(name),
+        None => table.get(name),
+    }
+}
+
+/// Set the value at a dotted path of a table.
+fn insert(table: &mut Table, key: &str, value: Value) -> Result<(), String> {
+    let Some((section, name)) = key.split_once('.') else {
+        table.insert(key.to_string(), value);
+        return Ok(());
+    };
+    let section = table
+        .entry(section)
+        .or_insert_with(|| Value::Table(Table::new()))
+        .as_table_mut()
+        .ok_or_else(|| format!("'{}' is not a section", section))?;
+    section.insert(name.to_string(), value);
+    Ok(())
+}
+
+/// Format a value as in the settings file, with strings unquoted.
+pub fn display(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items.iter().map(display).collect::<Vec<_>>().join(","),
+        other => other.to_string(),
+    }
+}
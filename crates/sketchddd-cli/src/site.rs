@@ -0,0 +1,586 @@
+//! Static documentation site for `sketchddd docs`.
+//!
+//! The site has an overview page with the context map, a page per bounded
+//! context and per context map, and a glossary of the ubiquitous language.
+//! Diagrams are embedded as Mermaid, which the pages load from a CDN to
+//! render them in the browser. Links are relative, so the site can be
+//! served from any directory, such as a GitHub Pages site.
+
+use crate::report::{self, CliError};
+use sketchddd_core::sketch::{ObjectId, Path as SketchPath};
+use sketchddd_core::{BoundedContext, Enforcement, Metadata, NamedContextMap, Workspace};
+use sketchddd_viz::{context_map, event_storming, lifecycle, mermaid, Format};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+use std::path::Path;
+
+const STYLE: &str = include_str!("../templates/site/style.css");
+
+const MERMAID_SCRIPT: &str = r#"<script type="module">
+import mermaid from "https://cdn.jsdelivr.net/npm/mermaid@10/dist/mermaid.esm.min.mjs";
+mermaid.initialize({ startOnLoad: true });
+</script>
+"#;
+
+/// Render the site of a model, returning its files by path relative to the
+/// site's directory.
+pub fn render(workspace: &Workspace, title: &str) -> Vec<(String, String)> {
+    let mut files = vec![
+        ("index.html".to_string(), index(workspace, title)),
+        ("glossary.html".to_string(), glossary(workspace, title)),
+    ];
+    for context in &workspace.contexts {
+        files.push((
+            format!("contexts/{}.html", context.name()),
+            context_page(workspace, context, title),
+        ));
+    }
+    for map in &workspace.context_maps {
+        files.push((format!("maps/{}.html", map.name()), map_page(map, title)));
+    }
+    files.push(("style.css".to_string(), STYLE.to_string()));
+    // Keep GitHub Pages from running the site through Jekyll
+    files.push((".nojekyll".to_string(), String::new()));
+    files
+}
+
+/// Write the files of a site into a directory, returning the number of
+/// files written.
+pub fn write(files: &[(String, String)], dir: &Path) -> Result<usize, CliError> {
+    for (path, content) in files {
+        let full = dir.join(path);
+        if let Some(parent) = full.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| report::io_error(format!("Failed to create directory: {}", e)))?;
+        }
+        std::fs::write(&full, content)
+            .map_err(|e| report::io_error(format!("Failed to write {}: {}", full.display(), e)))?;
+    }
+    Ok(files.len())
+}
+
+/// Escape text for HTML.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Turn the fenced Mermaid blocks a diagram generator returns into
+/// elements Mermaid renders.
+fn diagrams(fenced: &str) -> String {
+    let mut html = String::new();
+    for block in fenced.split("```mermaid\n").skip(1) {
+        let source = block.split("```").next().unwrap_or_default();
+        let _ = writeln!(html, "<pre class=\"mermaid\">\n{}</pre>", escape(source));
+    }
+    html
+}
+
+/// Wrap the body of a page, `depth` directories below the site's root.
+fn page(site: &str, title: &str, depth: usize, body: &str) -> String {
+    let root = "../".repeat(depth);
+    let heading = if title == site {
+        escape(site)
+    } else {
+        format!("{} - {}", escape(title), escape(site))
+    };
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{heading}</title>\n<link rel=\"stylesheet\" href=\"{root}style.css\">\n\
+         </head>\n<body>\n<nav><a href=\"{root}index.html\">{site}</a> \
+         <a href=\"{root}glossary.html\">Glossary</a></nav>\n<main>\n{body}</main>\n",
+        site = escape(site),
+    );
+    if body.contains("class=\"mermaid\"") {
+        html.push_str(MERMAID_SCRIPT);
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Link to the page of a context from a page `depth` directories deep.
+fn context_link(name: &str, depth: usize) -> String {
+    format!(
+        "<a href=\"{}contexts/{}.html\">{}</a>",
+        "../".repeat(depth),
+        name,
+        escape(name)
+    )
+}
+
+/// Link to the page of a context map from a page `depth` directories deep.
+fn map_link(name: &str, depth: usize) -> String {
+    format!(
+        "<a href=\"{}maps/{}.html\">{}</a>",
+        "../".repeat(depth),
+        name,
+        escape(name)
+    )
+}
+
+/// Describe metadata as a line of badges, or nothing if there is none.
+fn badges(metadata: Option<&Metadata>) -> String {
+    let Some(metadata) = metadata.filter(|m| !m.is_empty()) else {
+        return String::new();
+    };
+    let mut html = String::from(" ");
+    if let Some(classification) = metadata.classification {
+        let _ = write!(html, "<span class=\"badge {0}\">{0}</span>", classification);
+    }
+    for tag in &metadata.tags {
+        let _ = write!(html, "<span class=\"badge\">{}</span>", escape(tag));
+    }
+    for (key, value) in &metadata.values {
+        let _ = write!(
+            html,
+            "<span class=\"badge\">{}: {}</span>",
+            escape(key),
+            escape(&value.to_string())
+        );
+    }
+    html
+}
+
+/// The overview page: the contexts, grouped by domain, and the context map.
+fn index(workspace: &Workspace, site: &str) -> String {
+    let stats = workspace.stats();
+    let mut body = format!("<h1>{}</h1>\n", escape(site));
+    if let Some(version) = &workspace.version {
+        let _ = writeln!(body, "<p class=\"version\">Version {}</p>", version);
+    }
+    let _ = writeln!(
+        body,
+        "<p>{} bounded contexts, {} context maps, {} aggregates.</p>",
+        stats.contexts.len(),
+        stats.context_maps,
+        stats.totals.aggregates
+    );
+
+    for domain in &workspace.domains {
+        let _ = writeln!(body, "<h2>Domain {}</h2>\n<ul>", escape(&domain.name));
+        for subdomain in &domain.subdomains {
+            let contexts: Vec<String> = subdomain
+                .contexts
+                .iter()
+                .map(|c| context_link(c, 0))
+                .collect();
+            let _ = writeln!(
+                body,
+                "<li>{}{}: {}</li>",
+                escape(&subdomain.name),
+                badges(Some(&subdomain.metadata)),
+                contexts.join(", ")
+            );
+        }
+        body.push_str("</ul>\n");
+    }
+
+    body.push_str("<h2>Bounded Contexts</h2>\n<table>\n");
+    body.push_str(
+        "<tr><th>Context</th><th>Entities</th><th>Value objects</th><th>Aggregates</th></tr>\n",
+    );
+    for context in &stats.contexts {
+        let _ = writeln!(
+            body,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            context_link(&context.name, 0),
+            context.counts.entities,
+            context.counts.value_objects,
+            context.counts.aggregates
+        );
+    }
+    body.push_str("</table>\n");
+
+    if !workspace.context_maps.is_empty() {
+        body.push_str("<h2>Context Map</h2>\n");
+        if let Ok(diagram) = context_map::generate(workspace, Format::Mermaid) {
+            body.push_str(&diagrams(&diagram));
+        }
+        body.push_str("<ul>\n");
+        for map in &workspace.context_maps {
+            let _ = writeln!(
+                body,
+                "<li>{}: {} to {} ({:?})</li>",
+                map_link(map.name(), 0),
+                context_link(map.source_context(), 0),
+                context_link(map.target_context(), 0),
+                map.pattern()
+            );
+        }
+        body.push_str("</ul>\n");
+    }
+
+    page(site, site, 0, &body)
+}
+
+/// The glossary: the terms of the ubiquitous language, then every concept
+/// with the contexts declaring it.
+fn glossary(workspace: &Workspace, site: &str) -> String {
+    let mut body = String::from("<h1>Glossary</h1>\n");
+    if !workspace.glossary.is_empty() {
+        body.push_str("<dl>\n");
+        for (term, definition) in &workspace.glossary {
+            let _ = writeln!(
+                body,
+                "<dt id=\"{0}\">{0}</dt><dd>{1}</dd>",
+                escape(term),
+                escape(definition)
+            );
+        }
+        body.push_str("</dl>\n");
+    }
+
+    let mut concepts: BTreeMap<&str, Vec<(&str, &str)>> = BTreeMap::new();
+    for context in &workspace.contexts {
+        for (object, kind) in concepts_of(context) {
+            let name = context.graph().get_object(object).map(|o| o.name.as_str());
+            if let Some(name) = name {
+                concepts
+                    .entry(name)
+                    .or_default()
+                    .push((context.name(), kind));
+            }
+        }
+    }
+    body.push_str("<h2>Concepts</h2>\n<table>\n<tr><th>Concept</th><th>Declared in</th></tr>\n");
+    for (name, places) in concepts {
+        let places: Vec<String> = places
+            .iter()
+            .map(|(context, kind)| {
+                format!(
+                    "<a href=\"contexts/{0}.html#{1}\">{0}</a> ({2})",
+                    escape(context),
+                    escape(name),
+                    kind
+                )
+            })
+            .collect();
+        let _ = writeln!(
+            body,
+            "<tr><td>{}</td><td>{}</td></tr>",
+            escape(name),
+            places.join(", ")
+        );
+    }
+    body.push_str("</table>\n");
+
+    page(site, "Glossary", 0, &body)
+}
+
+/// Get the objects of a context worth documenting, with their kind:
+/// aggregate roots, entities, value objects, enums and then other objects,
+/// each in declaration order. Equalizers of invariants are left out.
+fn concepts_of(context: &BoundedContext) -> Vec<(ObjectId, &'static str)> {
+    const KINDS: [&str; 5] = ["aggregate root", "entity", "value object", "enum", "object"];
+    let equalizers: HashSet<ObjectId> = context.invariants().iter().map(|i| i.equalizer).collect();
+    let mut concepts: Vec<_> = context
+        .graph()
+        .objects()
+        .filter(|o| !equalizers.contains(&o.id))
+        .map(|o| {
+            let kind = if context.is_aggregate_root(o.id) {
+                0
+            } else if context.is_entity(o.id) {
+                1
+            } else if context.is_value_object(o.id) {
+                2
+            } else if context.get_enum_colimit(o.id).is_some() {
+                3
+            } else {
+                4
+            };
+            (kind, o.id)
+        })
+        .collect();
+    concepts.sort();
+    concepts
+        .into_iter()
+        .map(|(kind, id)| (id, KINDS[kind]))
+        .collect()
+}
+
+/// Format a path as its source followed by its morphisms.
+fn path_text(context: &BoundedContext, path: &SketchPath) -> String {
+    let graph = context.graph();
+    std::iter::once(graph.get_object(path.source).map(|o| o.name.as_str()))
+        .chain(
+            path.morphisms
+                .iter()
+                .map(|&m| graph.get_morphism(m).map(|m| m.name.as_str())),
+        )
+        .map(|name| name.unwrap_or("?"))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// The page of a bounded context.
+fn context_page(workspace: &Workspace, context: &BoundedContext, site: &str) -> String {
+    let graph = context.graph();
+    let name = |id: ObjectId| {
+        graph
+            .get_object(id)
+            .map(|o| escape(&o.name))
+            .unwrap_or_default()
+    };
+
+    let mut body = format!(
+        "<h1>{}{}</h1>\n",
+        escape(context.name()),
+        badges(Some(context.metadata()))
+    );
+    if let Some(subdomain) = workspace.subdomain_of(context.name()) {
+        let _ = writeln!(body, "<p>Subdomain {}</p>", escape(&subdomain.name));
+    }
+    if let Ok(diagram) = mermaid::generate(context) {
+        body.push_str(&diagrams(&diagram));
+    }
+
+    // Fields are the morphisms out of an object, besides identities and
+    // the injections into enums; a value object's fields are its projections
+    let injections: HashSet<_> = context
+        .sketch()
+        .colimits
+        .iter()
+        .flat_map(|colimit| colimit.injections.iter().filter_map(|i| i.morphism))
+        .collect();
+    let aggregate_projections: HashSet<_> = context
+        .aggregate_roots()
+        .iter()
+        .filter_map(|&root| context.get_aggregate(root))
+        .flat_map(|limit| limit.projections.iter().map(|p| p.morphism))
+        .collect();
+
+    body.push_str("<h2>Concepts</h2>\n");
+    for (object, kind) in concepts_of(context) {
+        let _ = writeln!(
+            body,
+            "<h3 id=\"{0}\">{0} <small>{1}</small>{2}</h3>",
+            name(object),
+            kind,
+            badges(context.object_metadata(object))
+        );
+        if let Some(colimit) = context.get_enum_colimit(object) {
+            let variants: Vec<String> =
+                colimit.injections.iter().map(|i| escape(&i.name)).collect();
+            let _ = writeln!(body, "<p>Variants: {}</p>", variants.join(", "));
+            continue;
+        }
+        let fields: Vec<_> = graph
+            .morphisms()
+            .filter(|m| m.source == object && !m.is_identity)
+            .filter(|m| !injections.contains(&m.id) && !aggregate_projections.contains(&m.id))
+            .collect();
+        if fields.is_empty() {
+            continue;
+        }
+        body.push_str("<table>\n<tr><th>Field</th><th>Type</th></tr>\n");
+        for field in fields {
+            let mut target = name(field.target);
+            if context.is_optional(field.id) {
+                target.push('?');
+            }
+            if let Some(expression) = context.derivation_of(field.id) {
+                let _ = write!(target, " = {}", escape(&expression.to_string()));
+            }
+            let _ = writeln!(
+                body,
+                "<tr><td>{}</td><td><a href=\"#{}\">{}</a></td></tr>",
+                escape(&field.name),
+                name(field.target),
+                target
+            );
+        }
+        body.push_str("</table>\n");
+    }
+
+    if !context.aggregate_roots().is_empty() {
+        body.push_str("<h2>Aggregates</h2>\n");
+        for &root in context.aggregate_roots() {
+            let Some(limit) = context.get_aggregate(root) else {
+                continue;
+            };
+            let _ = writeln!(
+                body,
+                "<h3>{}{}</h3>\n<p>Root: <a href=\"#{2}\">{2}</a></p>",
+                escape(&limit.name),
+                badges(context.aggregate_metadata(root)),
+                name(root)
+            );
+            let members: Vec<String> = limit
+                .component_objects()
+                .map(|member| format!("<a href=\"#{0}\">{0}</a>", name(member)))
+                .collect();
+            if !members.is_empty() {
+                let _ = writeln!(body, "<p>Contains: {}</p>", members.join(", "));
+            }
+            let invariants = context.invariants_of(root);
+            if !invariants.is_empty() {
+                body.push_str("<ul>\n");
+                for invariant in invariants {
+                    let mode = match invariant.enforcement {
+                        Enforcement::Strict => "",
+                        Enforcement::Eventual => " <small>eventual</small>",
+                    };
+                    let _ = writeln!(
+                        body,
+                        "<li><code>{}</code>{}</li>",
+                        escape(&invariant.expression.to_string()),
+                        mode
+                    );
+                }
+                body.push_str("</ul>\n");
+            }
+        }
+    }
+
+    if !context.sketch().equations.is_empty() {
+        body.push_str("<h2>Invariants</h2>\n<ul>\n");
+        for equation in &context.sketch().equations {
+            let name = match equation.name.as_str() {
+                "" | "anonymous" => String::new(),
+                name => format!("{}: ", escape(name)),
+            };
+            let mode = if equation.eventual {
+                " <small>eventual</small>"
+            } else {
+                ""
+            };
+            let _ = writeln!(
+                body,
+                "<li>{}<code>{} = {}</code>{}</li>",
+                name,
+                escape(&path_text(context, &equation.lhs)),
+                escape(&path_text(context, &equation.rhs)),
+                mode
+            );
+        }
+        body.push_str("</ul>\n");
+    }
+
+    if !context.lifecycles().is_empty() {
+        body.push_str("<h2>Lifecycles</h2>\n");
+        if let Ok(diagram) = lifecycle::generate(context) {
+            body.push_str(&diagrams(&diagram));
+        }
+    }
+
+    if !context.processes().is_empty() {
+        body.push_str("<h2>Processes</h2>\n");
+        for process in context.processes() {
+            let _ = writeln!(body, "<h3>{}</h3>\n<ol>", escape(&process.name));
+            for step in &process.steps {
+                let _ = writeln!(
+                    body,
+                    "<li>on <a href=\"#{0}\">{0}</a>, <a href=\"#{1}\">{1}</a></li>",
+                    name(step.event),
+                    name(step.command)
+                );
+            }
+            body.push_str("</ol>\n");
+        }
+    }
+
+    let storming = !context.actors().is_empty()
+        || !context.policies().is_empty()
+        || !context.hotspots().is_empty();
+    if storming {
+        body.push_str("<h2>Event Storming</h2>\n");
+        if let Ok(diagram) = event_storming::generate(context) {
+            body.push_str(&diagrams(&diagram));
+        }
+    }
+
+    let maps: Vec<&NamedContextMap> = workspace
+        .context_maps
+        .iter()
+        .filter(|m| m.source_context() == context.name() || m.target_context() == context.name())
+        .collect();
+    if !maps.is_empty() {
+        body.push_str("<h2>Context Maps</h2>\n<ul>\n");
+        for map in maps {
+            let _ = writeln!(
+                body,
+                "<li>{}: {} to {} ({:?})</li>",
+                map_link(map.name(), 1),
+                context_link(map.source_context(), 1),
+                context_link(map.target_context(), 1),
+                map.pattern()
+            );
+        }
+        body.push_str("</ul>\n");
+    }
+
+    page(site, context.name(), 1, &body)
+}
+
+/// The page of a context map.
+fn map_page(map: &NamedContextMap, site: &str) -> String {
+    let mut body = format!(
+        "<h1>{}</h1>\n<p>{} to {}, {:?}</p>\n",
+        escape(map.name()),
+        context_link(map.source_context(), 1),
+        context_link(map.target_context(), 1),
+        map.pattern()
+    );
+
+    let mut mappings = |title: &str, rows: Vec<(&str, &str, Option<&str>)>| {
+        if rows.is_empty() {
+            return;
+        }
+        let _ = writeln!(
+            body,
+            "<h2>{}</h2>\n<table>\n<tr><th>{}</th><th>{}</th><th>Description</th></tr>",
+            title,
+            escape(map.source_context()),
+            escape(map.target_context())
+        );
+        for (source, target, description) in rows {
+            let _ = writeln!(
+                body,
+                "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape(source),
+                escape(target),
+                escape(description.unwrap_or_default())
+            );
+        }
+        body.push_str("</table>\n");
+    };
+    mappings(
+        "Objects",
+        map.object_mappings()
+            .iter()
+            .map(|m| {
+                (
+                    m.source.as_str(),
+                    m.target.as_str(),
+                    m.description.as_deref(),
+                )
+            })
+            .collect(),
+    );
+    mappings(
+        "Morphisms",
+        map.morphism_mappings()
+            .iter()
+            .map(|m| {
+                (
+                    m.source.as_str(),
+                    m.target.as_str(),
+                    m.description.as_deref(),
+                )
+            })
+            .collect(),
+    );
+
+    page(site, map.name(), 1, &body)
+}
@@ -7,7 +7,7 @@ use std::fs;
 
 /// Get the sketchddd binary command
 fn sketchddd() -> Command {
-    Command::cargo_bin("sketchddd").unwrap()
+    Command::new(env!("CARGO_BIN_EXE_sketchddd"))
 }
 
 // =============================================================
@@ -61,7 +61,15 @@ fn test_check_valid_file() {
 
     fs::write(&file_path, r#"
         context Commerce {
-            objects { Customer, Order }
+            objects { Customer, Order, LineItem }
+            morphisms {
+                placedBy: Order -> Customer
+                items: Order -> LineItem
+            }
+            aggregate Order {
+                root: Order
+                contains: [LineItem]
+            }
         }
     "#).unwrap();
 
@@ -114,6 +122,88 @@ fn test_check_invalid_syntax() {
         .stderr(predicate::str::contains("Parse error"));
 }
 
+#[test]
+fn test_exit_codes() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let invalid = temp_dir.path().join("invalid.sddd");
+    fs::write(&invalid, "this is not valid syntax {{{").unwrap();
+    let failing = temp_dir.path().join("failing.sddd");
+    fs::write(&failing, r#"
+        context Sales {
+            objects { Order }
+            morphisms {
+                placedBy: Order -> Customer
+            }
+            aggregate Order { root: Missing }
+        }
+    "#).unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args(["check", failing.to_str().unwrap()]);
+    cmd.assert().code(1);
+
+    let mut cmd = sketchddd();
+    cmd.args(["check", invalid.to_str().unwrap()]);
+    cmd.assert().code(2);
+
+    let mut cmd = sketchddd();
+    cmd.args(["check", "nonexistent.sddd"]);
+    cmd.assert().code(3);
+
+    // -q and -v contradict each other
+    let mut cmd = sketchddd();
+    cmd.args(["-q", "-v", "check", failing.to_str().unwrap()]);
+    cmd.assert().code(2);
+
+    // So do values an option does not take
+    let mut cmd = sketchddd();
+    cmd.args(["viz", "-f", "bogus", failing.to_str().unwrap()]);
+    cmd.assert().code(2);
+
+    let mut cmd = sketchddd();
+    cmd.args(["codegen", "-t", "cobol", failing.to_str().unwrap()]);
+    cmd.assert().code(2);
+
+    let mut cmd = sketchddd();
+    cmd.args(["--format", "json", "template", "list"]);
+    cmd.assert().code(2);
+}
+
+#[test]
+fn test_closed_stdout() {
+    // A reader closing stdout early, as `head` does, ends the run quietly
+    let mut child = sketchddd()
+        .args(["template", "list"])
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    drop(child.stdout.take());
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("internal error"));
+}
+
+#[test]
+fn test_verbose_logs() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("shop.sddd");
+    fs::write(&file_path, "context Shop {\n  entity Order\n}\n").unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.env_remove("SKETCHDDD_LOG")
+        .args(["-vv", "check", file_path.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("read"))
+        .stderr(predicate::str::contains("bytes="));
+
+    let mut cmd = sketchddd();
+    cmd.env_remove("SKETCHDDD_LOG")
+        .args(["check", file_path.to_str().unwrap()]);
+    cmd.assert().success().stderr(predicate::str::contains("bytes=").not());
+}
+
 #[test]
 fn test_check_with_entities() {
     let temp_dir = tempfile::tempdir().unwrap();
@@ -173,7 +263,7 @@ fn test_check_quiet_mode() {
     "#).unwrap();
 
     let mut cmd = sketchddd();
-    cmd.args(["-v", "quiet", "check", file_path.to_str().unwrap()]);
+    cmd.args(["-q", "check", file_path.to_str().unwrap()]);
     cmd.assert()
         .success()
         .stdout(predicate::str::is_empty());
@@ -191,7 +281,7 @@ fn test_check_verbose_mode() {
     "#).unwrap();
 
     let mut cmd = sketchddd();
-    cmd.args(["-v", "verbose", "check", file_path.to_str().unwrap()]);
+    cmd.args(["-v", "check", file_path.to_str().unwrap()]);
     cmd.assert()
         .success()
         .stdout(predicate::str::contains("Parsed"));
@@ -209,9 +299,69 @@ fn test_check_json_format() {
 
     let mut cmd = sketchddd();
     cmd.args(["check", "--format", "json", file_path.to_str().unwrap()]);
-    cmd.assert()
-        .success()
-        .stdout(predicate::str::contains("[]")); // Empty issues array
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(report["version"], 1);
+    assert_eq!(report["command"], "check");
+    assert_eq!(report["status"], "success");
+    assert_eq!(report["summary"]["errors"], 0);
+    assert_eq!(report["diagnostics"], serde_json::json!([])); // Empty issues array
+}
+
+#[test]
+fn test_check_sarif_format() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("sarif.sddd");
+
+    fs::write(&file_path, r#"
+        context Test {
+            objects { Order, Order }
+        }
+    "#).unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args(["check", "--format", "sarif", file_path.to_str().unwrap()]);
+    let output = cmd.assert().failure().get_output().stdout.clone();
+    let sarif: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(sarif["version"], "2.1.0");
+    let results = sarif["runs"][0]["results"].as_array().unwrap();
+    let duplicate = results.iter().find(|r| r["ruleId"] == "E0020").unwrap();
+    assert_eq!(duplicate["level"], "error");
+    assert_eq!(duplicate["locations"][0]["physicalLocation"]["region"]["startLine"], 3);
+    assert!(duplicate["fixes"].is_array());
+}
+
+#[test]
+fn test_check_reports_transform_warnings() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("implicit.sddd");
+
+    fs::write(&file_path, r#"
+        context Test {
+            value Money {
+                amount: Decimal
+            }
+        }
+    "#).unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args(["check", "--format", "json", file_path.to_str().unwrap()]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let diagnostics = report["diagnostics"].as_array().unwrap();
+    let implicit = diagnostics.iter().find(|d| d["code"] == "TRANSFORM_WARNING").unwrap();
+    assert!(implicit["message"].as_str().unwrap().contains("Decimal"));
+    assert_eq!(implicit["line"], 4);
+    assert!(report["summary"]["warnings"].as_u64().unwrap() >= 1);
+
+    let mut cmd = sketchddd();
+    cmd.args(["check", "--format", "sarif", file_path.to_str().unwrap()]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let sarif: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let results = sarif["runs"][0]["results"].as_array().unwrap();
+    let implicit = results.iter().find(|r| r["ruleId"] == "TRANSFORM_WARNING").unwrap();
+    assert_eq!(implicit["level"], "warning");
+    assert_eq!(implicit["locations"][0]["physicalLocation"]["region"]["startLine"], 4);
 }
 
 // =============================================================
@@ -279,6 +429,102 @@ fn test_init_microservices_template() {
     assert!(content.contains("map OrdersToInventory"));
 }
 
+#[test]
+fn test_init_templates_check() {
+    let temp_dir = tempfile::tempdir().unwrap();
+
+    for template in ["minimal", "commerce", "banking", "healthcare", "event-sourced", "microservices"] {
+        let mut cmd = sketchddd();
+        cmd.current_dir(temp_dir.path());
+        cmd.args(["-q", "init", template, "--template", template]);
+        cmd.assert().success();
+
+        let project = temp_dir.path().join(template);
+        assert!(project.join("sketchddd.toml").exists(), "{}", template);
+        assert!(project.join(".gitignore").exists(), "{}", template);
+        assert!(fs::read_to_string(project.join("README.md")).unwrap().contains(template));
+
+        let mut cmd = sketchddd();
+        cmd.args(["check", project.join(format!("{}.sddd", template)).to_str().unwrap()]);
+        cmd.assert().success();
+        // The project's configuration names its model
+        let mut cmd = sketchddd();
+        cmd.current_dir(&project);
+        cmd.arg("check");
+        cmd.assert().success();
+    }
+    assert!(temp_dir.path().join("banking/rules/owner.rhai").exists());
+}
+
+#[test]
+fn test_init_keeps_existing_files() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let project = temp_dir.path().join("shop");
+    fs::create_dir(&project).unwrap();
+    fs::write(project.join(".gitignore"), "target/\n").unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.current_dir(temp_dir.path());
+    cmd.args(["init", "shop", "--template", "commerce"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(".gitignore (exists, kept)"));
+    assert_eq!(fs::read_to_string(project.join(".gitignore")).unwrap(), "target/\n");
+
+    // The model is never overwritten
+    let mut cmd = sketchddd();
+    cmd.current_dir(temp_dir.path());
+    cmd.args(["init", "shop"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("shop.sddd already exists"));
+}
+
+#[test]
+fn test_init_list_templates() {
+    let mut cmd = sketchddd();
+    cmd.args(["init", "--list-templates"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("banking"))
+        .stdout(predicate::str::contains("event-sourced"));
+
+    let mut cmd = sketchddd();
+    cmd.args(["init", "bank", "--template", "bankign"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown template 'bankign'; did you mean `banking`?"));
+}
+
+#[test]
+fn test_init_json() {
+    let temp_dir = tempfile::tempdir().unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.current_dir(temp_dir.path());
+    cmd.args(["--format", "json", "init", "shop", "--template", "ecommerce"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(report["command"], "init");
+    assert_eq!(report["template"], "commerce");
+    let artifacts = report["artifacts"].as_array().unwrap();
+    assert!(!artifacts.is_empty());
+    for artifact in artifacts {
+        assert_eq!(artifact["kind"], "file");
+        let path = artifact["path"].as_str().unwrap();
+        assert!(path.starts_with("shop"));
+        assert!(temp_dir.path().join(path).exists());
+    }
+    assert!(artifacts.iter().any(|a| a["path"].as_str().unwrap().ends_with(".sddd")));
+
+    let mut cmd = sketchddd();
+    cmd.args(["--format", "json", "init", "--list-templates"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let templates = report["templates"].as_array().unwrap();
+    assert!(templates.iter().any(|t| t["name"] == "banking"));
+}
+
 #[test]
 fn test_init_quiet_mode() {
     let temp_dir = tempfile::tempdir().unwrap();
@@ -286,7 +532,7 @@ fn test_init_quiet_mode() {
 
     let mut cmd = sketchddd();
     cmd.current_dir(temp_dir.path());
-    cmd.args(["-v", "quiet", "init", project_name]);
+    cmd.args(["-q", "init", project_name]);
     cmd.assert()
         .success()
         .stdout(predicate::str::is_empty());
@@ -349,6 +595,339 @@ fn test_export_to_file() {
     assert!(content.contains("Test"));
 }
 
+#[test]
+fn test_export_architecture() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("commerce.sddd");
+
+    fs::write(
+        &file_path,
+        r#"
+        context Sales { }
+
+        @meta(owner: "billing-team")
+        context Billing { }
+
+        map SalesToBilling: Sales -> Billing {
+            pattern: CustomerSupplier
+        }
+
+        domain Commerce {
+            @core
+            subdomain OrderManagement {
+                contexts { Sales, Billing }
+            }
+        }
+    "#,
+    )
+    .unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args([
+        "-q",
+        "--format",
+        "structurizr",
+        "export",
+        file_path.to_str().unwrap(),
+    ]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let workspace: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(workspace["name"], "commerce");
+    let system = &workspace["model"]["softwareSystems"][0];
+    assert_eq!(system["name"], "OrderManagement");
+    assert_eq!(system["containers"][1]["name"], "Billing");
+    assert_eq!(
+        system["containers"][1]["relationships"][0]["description"],
+        "SalesToBilling"
+    );
+
+    let mut cmd = sketchddd();
+    cmd.args([
+        "-q",
+        "--format",
+        "backstage",
+        "export",
+        file_path.to_str().unwrap(),
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("kind: System"))
+        .stdout(predicate::str::contains("name: order-management"))
+        .stdout(predicate::str::contains("owner: billing-team"))
+        .stdout(predicate::str::contains("- component:sales"));
+}
+
+#[test]
+fn test_model_cache() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("large.sddd");
+    let cache_dir = temp_dir.path().join("cache");
+
+    // Only models slower to parse than to read back are cached
+    let source: String = (0..500)
+        .map(|i| format!("context Context{} {{ entity Order{} }}\n", i, i))
+        .collect();
+    fs::write(&file_path, &source).unwrap();
+
+    for _ in 0..2 {
+        let mut cmd = sketchddd();
+        cmd.env("SKETCHDDD_CACHE", &cache_dir)
+            .args(["-q", "stats", file_path.to_str().unwrap()]);
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("500 contexts"));
+    }
+    let entries: Vec<_> = fs::read_dir(&cache_dir).unwrap().collect();
+    assert_eq!(entries.len(), 1);
+
+    // A broken entry is loaded past
+    let entry = entries[0].as_ref().unwrap().path();
+    fs::write(&entry, b"SDDM").unwrap();
+    let mut cmd = sketchddd();
+    cmd.env("SKETCHDDD_CACHE", &cache_dir)
+        .args(["-q", "stats", file_path.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("500 contexts"));
+
+    let mut cmd = sketchddd();
+    cmd.env("SKETCHDDD_CACHE", "off")
+        .args(["-q", "stats", file_path.to_str().unwrap()]);
+    cmd.assert().success();
+}
+
+#[test]
+fn test_add_elements() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("shop.sddd");
+    fs::write(&file_path, r#"// The shop
+context Sales {
+  objects { Customer }
+
+  // Placed by customers
+  entity Order
+}
+
+context Billing {
+  objects { Invoice }
+}
+"#).unwrap();
+    let file = file_path.to_str().unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args(["add", "entity", "LineItem", file, "--context", "Sales"])
+        .args(["--field", "quantity: Int"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Added entity LineItem to Sales"));
+
+    let mut cmd = sketchddd();
+    cmd.args(["add", "morphism", "items", file, "--context", "Sales"])
+        .args(["--from", "Order", "--to", "List<LineItem>"]);
+    cmd.assert().success();
+
+    let mut cmd = sketchddd();
+    cmd.args(["--format", "json", "add", "enum", "InvoiceStatus", file])
+        .args(["--context", "Billing", "--variants", "Open,Paid"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let added: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(added["kind"], "enum");
+    assert_eq!(added["context"], "Billing");
+
+    let source = fs::read_to_string(&file_path).unwrap();
+    assert!(source.starts_with("// The shop\n"));
+    assert!(source.contains("  // Placed by customers\n  entity Order\n"));
+    assert!(source.contains("  entity LineItem {\n    quantity: Int\n  }"));
+    assert!(source.contains("items: Order -> List<LineItem>"));
+    assert!(source.contains("enum InvoiceStatus = Open | Paid"));
+
+    // The model has two contexts
+    let mut cmd = sketchddd();
+    cmd.args(["add", "value", "Money", file]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("choose one with --context"));
+
+    let mut cmd = sketchddd();
+    cmd.args(["add", "entity", "Order", file, "--context", "Sales"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Context 'Sales' already declares 'Order'"));
+    assert_eq!(fs::read_to_string(&file_path).unwrap(), source);
+
+    let mut cmd = sketchddd();
+    cmd.args(["check", file]);
+    cmd.assert().success();
+}
+
+#[test]
+fn test_migrate() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("blog.sddd");
+    fs::write(&file_path, r#"context Blog {
+  objects { Post, Author, Comment }
+  morphisms {
+    author: Post -> Author @one
+    comments: Post -> Comment @many // newest first
+  }
+}
+"#).unwrap();
+    let file = file_path.to_str().unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args(["check", file]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("blog.sddd:4:28: Cardinality '@one'"))
+        .stderr(predicate::str::contains("run `sketchddd migrate <file>`"));
+
+    let mut cmd = sketchddd();
+    cmd.args(["migrate", "--check", file]);
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("line 5: replaced @many by the target type List<Comment>"))
+        .stderr(predicate::str::contains("1 file(s) need migrating"));
+
+    let mut cmd = sketchddd();
+    cmd.args(["--format", "json", "migrate", file]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let migrated: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(migrated["files"][0]["changes"].as_array().unwrap().len(), 2);
+    assert_eq!(migrated["artifacts"][0]["kind"], "file");
+
+    let source = fs::read_to_string(&file_path).unwrap();
+    assert!(source.contains("    author: Post -> Author\n"));
+    assert!(source.contains("    comments: Post -> List<Comment> // newest first\n"));
+
+    let mut cmd = sketchddd();
+    cmd.args(["migrate", "--check", file]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Up to date"));
+
+    // The migrated model keeps its relationships
+    let mut cmd = sketchddd();
+    cmd.args(["check", file]);
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("Object 'List'").not());
+    let mut cmd = sketchddd();
+    cmd.args(["query", "usages of Comment", file]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Post -> Comment"));
+}
+
+#[test]
+fn test_config() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join("sketchddd").join("config.toml");
+    let model_path = temp_dir.path().join("shop.sddd");
+    fs::write(&model_path, "context Shop {\n  entity Order\n}\n").unwrap();
+    let config = |args: &[&str]| {
+        let mut cmd = sketchddd();
+        cmd.env("SKETCHDDD_CONFIG", &config_path).args(args);
+        cmd
+    };
+
+    config(&["config", "get", "codegen.targets"])
+        .assert()
+        .success()
+        .stdout("\n");
+
+    config(&["config", "set", "codegen.targets", "typescript,python"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Set codegen.targets = typescript,python"));
+    config(&["config", "set", "telemetry.enabled", "false"])
+        .assert()
+        .success();
+    config(&["config", "set", "update.check", "maybe"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("expected true or false"));
+    config(&["config", "get", "colour"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("did you mean `color`"));
+    assert!(fs::read_to_string(&config_path).unwrap().contains("[codegen]"));
+
+    let output = config(&["--format", "json", "config", "get", "codegen.targets"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let setting: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(setting["value"], serde_json::json!(["typescript", "python"]));
+    assert_eq!(setting["origin"], config_path.to_str().unwrap());
+
+    config(&["config", "get", "telemetry.enabled"])
+        .assert()
+        .success()
+        .stdout("false\n");
+
+    // Codegen reads the settings, telemetry opt-out included, and generates
+    // the user's targets
+    config(&["codegen", model_path.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("-> typescript"))
+        .stdout(predicate::str::contains("-> python"));
+}
+
+#[test]
+fn test_export_import_round_trip() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let input_path = temp_dir.path().join("input.sddd");
+    let yaml_path = temp_dir.path().join("model.yaml");
+    let output_path = temp_dir.path().join("output.sddd");
+
+    fs::write(&input_path, r#"
+        context Sales {
+            entity Order
+            entity Customer
+            morphisms {
+                placedBy: Order -> Customer
+            }
+            aggregate Order {
+                contains: [Customer]
+            }
+            enum OrderStatus = Pending | Shipped
+        }
+    "#).unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args([
+        "export",
+        input_path.to_str().unwrap(),
+        "--output",
+        yaml_path.to_str().unwrap(),
+    ]);
+    cmd.assert().success();
+    let yaml = fs::read_to_string(&yaml_path).unwrap();
+    assert!(yaml.contains("version: 1"));
+
+    let mut cmd = sketchddd();
+    cmd.args([
+        "import",
+        yaml_path.to_str().unwrap(),
+        "--output",
+        output_path.to_str().unwrap(),
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Imported to"));
+
+    let source = fs::read_to_string(&output_path).unwrap();
+    assert!(source.contains("placedBy: Order -> Customer"));
+    assert!(source.contains("enum OrderStatus = Pending | Shipped"));
+
+    let mut cmd = sketchddd();
+    cmd.args(["check", output_path.to_str().unwrap()]);
+    cmd.assert().success();
+}
+
 // =============================================================
 // Context Map Tests
 // =============================================================
@@ -429,6 +1008,45 @@ fn test_codegen_typescript() {
         .stdout(predicate::str::contains("Generated from"));
 }
 
+#[test]
+fn test_codegen_to_many_morphisms() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("codegen.sddd");
+
+    fs::write(&file_path, r#"
+        context Sales {
+            entity Order {}
+            entity LineItem {}
+            entity Customer {}
+            morphisms {
+                items: Order -> List<LineItem>
+                watchers: Order -> Set<Customer>
+            }
+            aggregate Order {
+                invariant: count(items) > 0
+            }
+        }
+    "#).unwrap();
+
+    let expected = [
+        ("rust", ["pub items: Vec<LineItemId>,", "pub watchers: std::collections::HashSet<CustomerId>,"]),
+        ("typescript", ["readonly items: LineItemId[];", "watchers: z.set(CustomerIdSchema),"]),
+        ("python", ["items: List[LineItemId]", "watchers: Set[CustomerId]"]),
+        ("java", ["List<LineItemId> items", "Set<CustomerId> watchers"]),
+        ("kotlin", ["val items: List<LineItemId>", "val watchers: Set<CustomerId>"]),
+    ];
+    for (target, fields) in expected {
+        let mut cmd = sketchddd();
+        cmd.args(["codegen", "-q", file_path.to_str().unwrap(), "--target", target]);
+        let output = cmd.output().unwrap();
+        let code = String::from_utf8(output.stdout).unwrap();
+        assert!(output.status.success(), "{}", target);
+        for field in fields {
+            assert!(code.contains(field), "{} lacks {}:\n{}", target, field, code);
+        }
+    }
+}
+
 #[test]
 fn test_viz_mermaid() {
     let temp_dir = tempfile::tempdir().unwrap();
@@ -468,30 +1086,1805 @@ fn test_viz_graphviz() {
 }
 
 #[test]
-fn test_serve_stub() {
-    let mut cmd = sketchddd();
-    cmd.args(["serve"]);
-    cmd.assert()
-        .success()
-        .stdout(predicate::str::contains("not yet implemented"));
-}
+fn test_codegen_and_viz_json_format() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("model.sddd");
+
+    fs::write(&file_path, r#"
+        context Sales {
+            entity Order
+        }
+        context Billing {
+            entity Invoice
+        }
+    "#).unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args(["codegen", "--format", "json", "--target", "typescript", file_path.to_str().unwrap()]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(report["command"], "codegen");
+    assert_eq!(report["status"], "success");
+    let artifacts = report["artifacts"].as_array().unwrap();
+    assert_eq!(artifacts.len(), 2);
+    assert_eq!(artifacts[0]["kind"], "code");
+    assert_eq!(artifacts[0]["format"], "typescript");
+    assert_eq!(artifacts[0]["context"], "Sales");
+    assert!(artifacts[0]["content"].as_str().unwrap().contains("Order"));
+
+    let mut cmd = sketchddd();
+    cmd.current_dir(temp_dir.path());
+    cmd.args(["viz", "--format", "json", "model.sddd", "--context", "Billing", "-o", "billing.md"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(report["command"], "viz");
+    assert_eq!(report["artifacts"][0]["kind"], "diagram");
+    assert_eq!(report["artifacts"][0]["format"], "mermaid");
+    assert_eq!(report["artifacts"][0]["path"], "billing.md");
+    assert!(temp_dir.path().join("billing.md").exists());
+}
+
+#[test]
+fn test_json_format_error() {
+    let mut cmd = sketchddd();
+    cmd.args(["viz", "--format", "json", "missing.sddd"]);
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["command"], "viz");
+    assert_eq!(report["status"], "error");
+    assert!(report["error"].as_str().unwrap().contains("missing.sddd"));
+
+    let mut cmd = sketchddd();
+    cmd.args(["serve", "--format", "json", "missing.sddd"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("serve has no JSON output"));
+}
+
+#[test]
+fn test_viz_context_selection() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("viz.sddd");
+
+    fs::write(&file_path, r#"
+        context Sales {
+            objects { Order }
+        }
+        context Shipping {
+            objects { Parcel }
+        }
+    "#).unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args(["viz", file_path.to_str().unwrap(), "--context", "Shipping", "-q"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::starts_with("```mermaid"))
+        .stdout(predicate::str::contains("Parcel"))
+        .stdout(predicate::str::contains("Order").not());
+
+    let mut cmd = sketchddd();
+    cmd.args(["viz", file_path.to_str().unwrap(), "--context", "Shiping"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Context 'Shiping' not found"))
+        .stderr(predicate::str::contains("did you mean `Shipping`?"));
+
+    // Both contexts go to one file rather than the last overwriting the first
+    let output = temp_dir.path().join("model.dot");
+    let mut cmd = sketchddd();
+    cmd.args(["viz", file_path.to_str().unwrap(), "-f", "dot", "-o", output.to_str().unwrap()]);
+    cmd.assert().success();
+    let dot = fs::read_to_string(&output).unwrap();
+    assert!(dot.contains("Order") && dot.contains("Parcel"));
+
+    let dir = temp_dir.path().join("diagrams");
+    fs::create_dir(&dir).unwrap();
+    let mut cmd = sketchddd();
+    cmd.args(["viz", file_path.to_str().unwrap(), "-o", dir.to_str().unwrap()]);
+    cmd.assert().success();
+    assert!(fs::read_to_string(dir.join("sales.md")).unwrap().contains("Order"));
+    assert!(fs::read_to_string(dir.join("shipping.md")).unwrap().contains("Parcel"));
+}
+
+#[test]
+fn test_viz_context_map() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("viz.sddd");
+
+    fs::write(&file_path, r#"
+        context Sales { }
+        context Shipping { }
+
+        map SalesToShipping: Sales -> Shipping {
+            pattern: CustomerSupplier
+        }
+
+        domain Commerce {
+            @core
+            subdomain Ordering {
+                contexts { Sales }
+            }
+        }
+    "#).unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args(["viz", file_path.to_str().unwrap(), "--context-map"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("subgraph subdomain_Ordering[\"Ordering (core)\"]"))
+        .stdout(predicate::str::contains("Sales -->|\"CS (U → D)\"| Shipping"));
+}
+
+#[test]
+fn test_viz_html() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("viz.sddd");
+    let out_dir = temp_dir.path().join("diagrams");
+    fs::create_dir(&out_dir).unwrap();
+
+    fs::write(&file_path, r#"
+        context Sales {
+            @meta(description: "A customer's purchase")
+            entity Order
+            entity Customer
+            morphisms {
+                placedBy: Order -> Customer
+            }
+        }
+        context Shipping {
+            objects { Parcel }
+        }
+    "#).unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args(["viz", file_path.to_str().unwrap(), "--format", "html", "-o"]).arg(&out_dir);
+    cmd.assert().success();
+
+    let page = fs::read_to_string(out_dir.join("sales.html")).unwrap();
+    assert!(page.starts_with("<!DOCTYPE html>"));
+    assert!(page.contains("<a href=\"contexts/Sales.html#Order\" data-name=\"Order\" data-kind=\"entity\" data-description=\"A customer's purchase\">"));
+    assert!(out_dir.join("shipping.html").is_file());
+
+    let mut cmd = sketchddd();
+    cmd.args(["viz", file_path.to_str().unwrap(), "--format", "html"]);
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "2 contexts need an output directory",
+    ));
+}
+
+#[test]
+fn test_viz_excalidraw_and_drawio() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("viz.sddd");
+    let out_dir = temp_dir.path().join("diagrams");
+    fs::create_dir(&out_dir).unwrap();
+
+    fs::write(
+        &file_path,
+        r#"
+        context Sales {
+            objects { Order, Customer }
+            morphisms {
+                placedBy: Order -> Customer
+            }
+        }
+        context Shipping { }
+
+        map SalesToShipping: Sales -> Shipping {
+            pattern: CustomerSupplier
+        }
+    "#,
+    )
+    .unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args(["viz", file_path.to_str().unwrap(), "-f", "excalidraw", "-o"])
+        .arg(&out_dir);
+    cmd.assert().success();
+
+    let drawing: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(out_dir.join("sales.excalidraw")).unwrap())
+            .unwrap();
+    assert_eq!(drawing["type"], "excalidraw");
+    let texts: Vec<&str> = drawing["elements"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|e| e["text"].as_str())
+        .collect();
+    assert!(texts.contains(&"Order") && texts.contains(&"placedBy"));
+
+    let mut cmd = sketchddd();
+    cmd.args([
+        "viz",
+        file_path.to_str().unwrap(),
+        "--context-map",
+        "-f",
+        "drawio",
+        "-o",
+    ])
+    .arg(&out_dir);
+    cmd.assert().success();
+
+    let diagram = fs::read_to_string(out_dir.join("context_map.drawio")).unwrap();
+    assert!(diagram.starts_with("<mxfile host=\"SketchDDD\">"));
+    assert!(diagram.contains("value=\"CS (U → D)\""));
+
+    let mut cmd = sketchddd();
+    cmd.args(["viz", file_path.to_str().unwrap(), "-f", "drawio"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("2 contexts need an output directory"));
+}
+
+#[test]
+fn test_viz_legend() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("commerce.sddd");
+
+    fs::write(
+        &file_path,
+        r#"
+        context Sales {
+            objects { Order, LineItem }
+            aggregate Order {
+                root: Order
+                contains: [LineItem]
+            }
+        }
+    "#,
+    )
+    .unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.env("SOURCE_DATE_EPOCH", "1714564800").args([
+        "viz",
+        file_path.to_str().unwrap(),
+        "-f",
+        "dot",
+        "--legend",
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "  label=\"commerce · generated 2024-05-01 12:00 UTC\";\n  labelloc=t;\n",
+        ))
+        .stdout(predicate::str::contains("subgraph cluster_legend {"))
+        .stdout(predicate::str::contains(
+            "_legend_0 [label=\"aggregate root\" penwidth=2];",
+        ));
+
+    // Other diagrams are drawn without a legend
+    let mut cmd = sketchddd();
+    cmd.args(["viz", file_path.to_str().unwrap(), "-f", "html", "--legend"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Legends are only drawn"))
+        .stdout(predicate::str::contains("generated").not());
+}
+
+#[test]
+fn test_viz_event_storming_board() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("viz.sddd");
+
+    fs::write(&file_path, r#"
+        context Fulfillment {
+            objects { Order, PlaceOrder, OrderPlaced, ReserveStock }
+            aggregate Order {
+                root: Order
+            }
+            morphisms {
+                order: PlaceOrder -> Order
+                placed: OrderPlaced -> Order
+            }
+            actor Customer { PlaceOrder }
+            policy ReserveOnPlacement { on OrderPlaced -> ReserveStock }
+            hotspot ReserveStock "What if stock runs out?"
+        }
+        context Empty {
+            objects { A }
+        }
+    "#).unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args(["viz", file_path.to_str().unwrap(), "--diagram", "board"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("    aggregate_Order[\"Order\"]:::aggregate\n"))
+        .stdout(predicate::str::contains("    PlaceOrder --> aggregate_Order\n"))
+        .stdout(predicate::str::contains("    aggregate_Order --> OrderPlaced\n"));
+
+    let out_path = temp_dir.path().join("board.html");
+    let mut cmd = sketchddd();
+    cmd.args(["viz", file_path.to_str().unwrap(), "--diagram", "board", "-f", "html", "-o"])
+        .arg(&out_path);
+    cmd.assert().success();
+    let page = fs::read_to_string(&out_path).unwrap();
+    assert!(page.contains("<title>Fulfillment event storming</title>"));
+    assert!(page.contains(">Aggregates</text>"));
+}
+
+#[test]
+fn test_viz_er_and_state_diagrams() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("viz.sddd");
+
+    fs::write(&file_path, r#"
+        context Sales {
+            objects { Order, Customer, LineItem }
+            enum OrderStatus = Pending | Shipped
+            morphisms {
+                placedBy: Order -> Customer
+                items: Order -> List<LineItem>
+                referredBy: Customer -> Customer?
+                status: Order -> OrderStatus
+            }
+            lifecycle Order: OrderStatus {
+                Pending -> Shipped
+            }
+        }
+    "#).unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args(["viz", file_path.to_str().unwrap(), "--diagram", "er"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("erDiagram"))
+        .stdout(predicate::str::contains("        OrderStatus status\n"))
+        .stdout(predicate::str::contains("    Order }o--|| Customer : placedBy\n"))
+        .stdout(predicate::str::contains("    Order ||--o{ LineItem : items\n"))
+        .stdout(predicate::str::contains("    Customer }o--o| Customer : referredBy\n"));
+
+    let mut cmd = sketchddd();
+    cmd.args(["viz", file_path.to_str().unwrap(), "--diagram", "state"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("stateDiagram-v2"))
+        .stdout(predicate::str::contains("    Pending --> Shipped\n"));
+
+    let mut cmd = sketchddd();
+    cmd.args(["viz", file_path.to_str().unwrap(), "--diagram", "er", "--format", "graphviz"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("ER diagrams are only drawn in Mermaid"));
+}
+
+#[test]
+fn test_render_builtin() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("model.sddd");
+
+    fs::write(&file_path, r#"
+        context Sales {
+            objects { Order, Customer }
+            morphisms {
+                placedBy: Order -> Customer
+            }
+        }
+        context Billing {
+            objects { Invoice }
+        }
+    "#).unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.current_dir(temp_dir.path());
+    cmd.args(["render", "--engine", "builtin"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Rendered ./sales.svg"));
+    let svg = fs::read_to_string(temp_dir.path().join("sales.svg")).unwrap();
+    assert!(svg.starts_with("<?xml"));
+    assert!(svg.contains("placedBy"));
+    assert!(temp_dir.path().join("billing.svg").exists());
+
+    let mut cmd = sketchddd();
+    cmd.current_dir(temp_dir.path());
+    cmd.args(["render", "--engine", "builtin", "--context", "Sales", "-o", "sales.png"]);
+    cmd.assert().success();
+    let png = fs::read(temp_dir.path().join("sales.png")).unwrap();
+    assert!(png.starts_with(b"\x89PNG"));
+
+    let mut cmd = sketchddd();
+    cmd.current_dir(temp_dir.path());
+    cmd.args(["render", "--engine", "builtin", "-o", "model.png"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("2 contexts need an output directory"));
+}
+
+#[test]
+fn test_check_subdomain_membership() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("domain.sddd");
+
+    fs::write(&file_path, r#"
+        context Sales { }
+
+        domain Commerce {
+            subdomain Ordering {
+                contexts { Sales }
+            }
+            subdomain Billing {
+                contexts { Sales }
+            }
+        }
+    "#).unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args(["check", file_path.to_str().unwrap()]);
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("E0073"));
+}
+
+#[test]
+fn test_check_lint_config() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let models = temp_dir.path().join("models");
+    fs::create_dir(&models).unwrap();
+    let file_path = models.join("fulfillment.sddd");
+    let config_path = temp_dir.path().join("sketchddd.toml");
+
+    fs::write(&file_path, r#"
+        context Fulfillment {
+            process EmptySaga { }
+        }
+    "#).unwrap();
+
+    // The configuration in a parent directory applies
+    fs::write(&config_path, "[lint]\nW0020 = \"error\"\n").unwrap();
+    let mut cmd = sketchddd();
+    cmd.args(["check", file_path.to_str().unwrap()]);
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("error[W0020]"));
+
+    fs::write(&config_path, "[lint]\nW0020 = \"off\"\nW9999 = \"off\"\n").unwrap();
+    let mut cmd = sketchddd();
+    cmd.args(["check", file_path.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("No issues found"))
+        .stdout(predicate::str::contains("sketchddd.toml: unknown lint rule 'W9999'"));
+    let mut cmd = sketchddd();
+    cmd.args(["-q", "check", file_path.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("W9999").not())
+        .stderr(predicate::str::contains("W9999").not());
+
+    fs::write(&config_path, "[lint]\nW0020 = \"loud\"\n").unwrap();
+    let mut cmd = sketchddd();
+    cmd.args(["check", file_path.to_str().unwrap()]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid lint level 'loud'"));
+}
+
+#[test]
+fn test_check_naming_rules() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("orders.sddd");
+    let config_path = temp_dir.path().join("sketchddd.toml");
+
+    fs::write(&file_path, r#"
+        context Sales {
+            objects { Orders, line_item }
+            morphisms {
+                Items: Orders -> line_item
+            }
+            aggregate Order {
+                root: Orders
+            }
+        }
+    "#).unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args(["-v", "check", file_path.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("warning[W0030]"))
+        .stdout(predicate::str::contains("did you mean `LineItem`?"))
+        .stdout(predicate::str::contains("warning[W0031]"))
+        .stdout(predicate::str::contains("did you mean `items`?"))
+        .stdout(predicate::str::contains("W0033").not());
+
+    fs::write(&config_path, "[naming]\naggregate-suffix = \"Aggregate\"\n").unwrap();
+    let mut cmd = sketchddd();
+    cmd.args(["-v", "check", file_path.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("warning[W0033]"))
+        .stdout(predicate::str::contains("did you mean `OrderAggregate`?"));
+}
+
+#[test]
+fn test_check_references_into_aggregates() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("model.sddd");
+    let model = |target: &str| {
+        format!(
+            r#"
+        context Sales {{
+            entity Order
+            entity LineItem
+            entity Shipment
+            entity Review
+            morphisms {{
+                order: Shipment -> Order
+                reviewed: Review -> {}
+            }}
+            aggregate OrderAggregate {{
+                root: Order
+                contains: [LineItem]
+            }}
+            aggregate ShipmentAggregate {{
+                root: Shipment
+            }}
+        }}
+    "#,
+            target
+        )
+    };
+
+    fs::write(&file_path, model("Order")).unwrap();
+    let mut cmd = sketchddd();
+    cmd.args(["check", file_path.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("W0003").not());
+
+    fs::write(&file_path, model("LineItem")).unwrap();
+    let mut cmd = sketchddd();
+    cmd.args(["check", file_path.to_str().unwrap()]);
+    cmd.assert().success().stdout(predicate::str::contains(
+        "warning[W0003]: 'Review', outside any aggregate, references 'LineItem' inside aggregate 'OrderAggregate' through 'reviewed'",
+    ));
+}
+
+#[test]
+fn test_check_script_rule() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("sales.sddd");
+    fs::create_dir(temp_dir.path().join("rules")).unwrap();
+
+    fs::write(&file_path, r#"
+        context Sales {
+            entity Order
+            entity Quote
+
+            @meta(owner: "sales-team")
+            aggregate Order {
+                root: Order
+            }
+
+            aggregate Quote {
+                root: Quote
+            }
+        }
+    "#).unwrap();
+    fs::write(temp_dir.path().join("rules/owner.rhai"), r#"
+        fn check(model) {
+            print("checking owners");
+            let issues = [];
+            for context in model.contexts {
+                for aggregate in context.aggregates {
+                    if !("owner" in aggregate.meta) {
+                        issues.push(`Aggregate '${aggregate.name}' has no owner`);
+                    }
+                }
+            }
+            issues
+        }
+    "#).unwrap();
+    fs::write(
+        temp_dir.path().join("sketchddd.toml"),
+        "[rules.X0001]\ndescription = \"Aggregate without an owner\"\nscript = \"rules/owner.rhai\"\nseverity = \"error\"\n",
+    ).unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args(["check", file_path.to_str().unwrap()]);
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("error[X0001]"))
+        .stdout(predicate::str::contains("Aggregate 'Quote' has no owner"))
+        .stdout(predicate::str::contains("Aggregate 'Order' has no owner").not())
+        .stdout(predicate::str::contains("checking owners").not());
+
+    // What the script prints stays out of machine-readable output
+    let mut cmd = sketchddd();
+    cmd.args(["--format", "json", "check", file_path.to_str().unwrap()]);
+    let output = cmd.assert().failure().get_output().stdout.clone();
+    let result: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert!(result.to_string().contains("Aggregate 'Quote' has no owner"));
+
+    // The script's rule is configured like any other
+    fs::write(
+        temp_dir.path().join("sketchddd.toml"),
+        "[lint]\nX0001 = \"off\"\n\n[rules.X0001]\nscript = \"rules/owner.rhai\"\n",
+    ).unwrap();
+    let mut cmd = sketchddd();
+    cmd.args(["check", file_path.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("X0001").not());
+
+    fs::write(
+        temp_dir.path().join("sketchddd.toml"),
+        "[rules.W0001]\nscript = \"rules/owner.rhai\"\n",
+    ).unwrap();
+    let mut cmd = sketchddd();
+    cmd.args(["check", file_path.to_str().unwrap()]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("W0001: the id is taken by a built-in rule"));
+}
+
+#[test]
+fn test_check_context_map_coverage() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("commerce.sddd");
+    let config_path = temp_dir.path().join("sketchddd.toml");
+
+    fs::write(&file_path, r#"
+        context Sales {
+            entity Customer
+            entity Order
+        }
+
+        context Billing {
+            entity Customer
+            entity Order
+        }
+
+        map SalesToBilling: Sales -> Billing {
+            pattern: CustomerSupplier
+            mappings {
+                Order -> Order
+            }
+        }
+    "#).unwrap();
+
+    // The rule is opt-in
+    let mut cmd = sketchddd();
+    cmd.args(["check", file_path.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("W0090").not())
+        .stdout(predicate::str::contains("Context map coverage").not());
+
+    fs::write(&config_path, "[lint]\nW0090 = \"warning\"\n").unwrap();
+    let mut cmd = sketchddd();
+    cmd.args(["check", file_path.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("warning[W0090]"))
+        .stdout(predicate::str::contains("does not map 'Customer'"))
+        .stdout(predicate::str::contains("Context map coverage: 50% (1 of 2 shared concept(s) mapped)"));
+
+    let mut cmd = sketchddd();
+    cmd.args(["check", "--format", "json", file_path.to_str().unwrap()]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(report["context_map_coverage"], 50.0);
+}
+
+#[test]
+fn test_check_severity_policy() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("fulfillment.sddd");
+
+    fs::write(&file_path, r#"
+        context Fulfillment {
+            process EmptySaga { }
+        }
+    "#).unwrap();
+    let check = |args: &[&str]| {
+        let mut cmd = sketchddd();
+        cmd.arg("check").args(args).arg(file_path.to_str().unwrap());
+        cmd.assert()
+    };
+
+    check(&[]).success().stdout(predicate::str::contains("warning[W0020]"));
+    check(&["--deny", "warnings"])
+        .failure()
+        .stdout(predicate::str::contains("error[W0020]"));
+    check(&["--deny", "warnings", "--warn", "W0020"]).success();
+    check(&["--deny", "W0020"]).failure();
+    check(&["--allow", "W0020"])
+        .success()
+        .stdout(predicate::str::contains("No issues found"));
+    check(&["--allow", "E0020"])
+        .failure()
+        .stderr(predicate::str::contains("Errors cannot be allowed"));
+
+    fs::write(&file_path, r#"
+        context Fulfillment {
+            objects { Order, Order }
+        }
+    "#).unwrap();
+    check(&[]).failure();
+    check(&["--warn", "E0020"])
+        .success()
+        .stdout(predicate::str::contains("warning[E0020]"));
+}
+
+#[test]
+fn test_check_fix() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("orders.sddd");
+
+    fs::write(&file_path, r#"
+        context Sales {
+            objects { Order, line_item, Order }
+            morphisms {
+                Items: Order -> line_item
+                placedBy: Order -> Customer
+            }
+            aggregate Order {
+                root: Order
+            }
+        }
+    "#).unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args(["check", "--fix", file_path.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Applied 4 fix(es)"));
+
+    let fixed = fs::read_to_string(&file_path).unwrap();
+    assert!(fixed.contains("objects { Order, LineItem, Customer }"));
+    assert!(fixed.contains("items: Order -> LineItem"));
+
+    let mut cmd = sketchddd();
+    cmd.args(["check", file_path.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("adding implicitly").not())
+        .stdout(predicate::str::contains("W0030").not())
+        .stdout(predicate::str::contains("E0020").not());
+}
+
+#[test]
+fn test_check_baseline() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("sales.sddd");
+    let baseline_path = temp_dir.path().join("sketchddd-baseline.json");
+    let model = |extra: &str| {
+        format!(r#"
+            context Sales {{
+                objects {{ Customer, Order, LineItem, Note {} }}
+                morphisms {{
+                    placedBy: Order -> Customer
+                }}
+                aggregate Order {{
+                    root: Order
+                    contains: [LineItem]
+                }}
+            }}
+        "#, extra)
+    };
+
+    fs::write(&file_path, model("")).unwrap();
+    let mut cmd = sketchddd();
+    cmd.args(["check", "--update-baseline", file_path.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Recorded 1 warning(s)"))
+        .stdout(predicate::str::contains("No issues found!"));
+
+    let baseline = fs::read_to_string(&baseline_path).unwrap();
+    assert!(baseline.contains("\"file\": \"sales.sddd\""));
+    assert!(baseline.contains("\"code\": \"W0080\""));
+
+    fs::write(&file_path, model(", Memo")).unwrap();
+    let mut cmd = sketchddd();
+    cmd.args(["check", file_path.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Object 'Memo' is not used"))
+        .stdout(predicate::str::contains("Object 'Note'").not());
+}
+
+#[test]
+fn test_check_design_limits() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("sales.sddd");
+    let config_path = temp_dir.path().join("sketchddd.toml");
+
+    fs::write(&file_path, r#"
+        context Sales {
+            objects { Order, Customer, Product }
+            morphisms {
+                placedBy: Order -> Customer
+                product: Order -> Product
+            }
+            aggregate Order {
+                root: Order
+                contains: [Customer, Product]
+            }
+        }
+    "#).unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args(["check", file_path.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("No issues found"));
+
+    fs::write(&config_path, "[limits]\nobject-morphisms = 1\ncontext-objects = 2\n").unwrap();
+    let mut cmd = sketchddd();
+    cmd.args(["check", file_path.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "warning[W0041]: Object 'Order' has 2 morphisms, more than 1",
+        ))
+        .stdout(predicate::str::contains(
+            "warning[W0042]: Context 'Sales' has 3 objects, more than 2",
+        ));
+}
+
+#[test]
+fn test_lint_list_rules() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    fs::write(
+        temp_dir.path().join("sketchddd.toml"),
+        "[lint]\nW0001 = \"error\"\nW0030 = \"off\"\n",
+    ).unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.current_dir(temp_dir.path());
+    cmd.args(["lint", "--list"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let stdout = String::from_utf8(output).unwrap();
+    assert!(stdout.contains("W0001  error    Aggregate with more than five members"));
+    assert!(stdout.contains("W0030  off"));
+    assert!(stdout.contains("W0090  off"));
+
+    let mut cmd = sketchddd();
+    cmd.current_dir(temp_dir.path());
+    cmd.args(["lint", "--list", "--format", "json"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let rules: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let w0001 = rules["rules"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|rule| rule["id"] == "W0001")
+        .unwrap();
+    assert_eq!(w0001["severity"], "error");
+    assert_eq!(w0001["default_severity"], "warning");
+}
+
+#[test]
+fn test_lint_explain() {
+    let mut cmd = sketchddd();
+    cmd.args(["lint", "--explain", "W0001"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("W0001: Aggregate with more than five members"))
+        .stdout(predicate::str::contains("Severity: warning"));
+
+    let mut cmd = sketchddd();
+    cmd.args(["lint", "--explain", "W0090", "--format", "json"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let output: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let rule = &output["rule"];
+    assert_eq!(rule["id"], "W0090");
+    assert_eq!(rule["default_severity"], "off");
+    assert!(rule["explanation"].as_str().unwrap().contains("context map"));
+
+    let mut cmd = sketchddd();
+    cmd.args(["lint", "--explain", "W001"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown lint rule 'W001'"));
+}
+
+#[test]
+fn test_lint_fix() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("sales.sddd");
+
+    fs::write(&file_path, r#"
+        context Sales {
+            objects { Order, line_item }
+            morphisms {
+                items: Order -> line_item
+            }
+        }
+    "#).unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args(["lint", "--format", "json", file_path.to_str().unwrap()]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert!(report["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|issue| issue["code"] == "W0030"));
+
+    let mut cmd = sketchddd();
+    cmd.args(["lint", "--fix", file_path.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Applied 1 fix(es)"));
+    let fixed = fs::read_to_string(&file_path).unwrap();
+    assert!(fixed.contains("items: Order -> LineItem"));
+}
+
+/// Send a request to a local server, retrying while it starts, and return
+/// the response, with any bytes that are not UTF-8 replaced.
+fn http_request(port: u16, method: &str, path: &str, body: &str) -> String {
+    http_request_with(port, method, path, &[], body)
+}
+
+/// Send a request with headers to a local server, as [`http_request`].
+fn http_request_with(
+    port: u16,
+    method: &str,
+    path: &str,
+    headers: &[(&str, &str)],
+    body: &str,
+) -> String {
+    use std::io::{Read, Write};
+    let headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}: {}\r\n", name, value))
+        .collect();
+    for _ in 0..100 {
+        if let Ok(mut stream) = std::net::TcpStream::connect(("127.0.0.1", port)) {
+            write!(
+                stream,
+                "{} {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n{}Content-Length: {}\r\n\r\n{}",
+                method,
+                path,
+                headers,
+                body.len(),
+                body
+            )
+            .unwrap();
+            let mut response = Vec::new();
+            stream.read_to_end(&mut response).unwrap();
+            return String::from_utf8_lossy(&response).into_owned();
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    panic!("server on port {} did not start", port);
+}
+
+#[test]
+fn test_serve_api() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file = temp_dir.path().join("model.sddd");
+    fs::write(&file, "context Sales { entity Order }").unwrap();
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut server = sketchddd()
+        .args(["serve", file.to_str().unwrap(), "--port", &port.to_string()])
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+
+    let model = http_request(port, "GET", "/api/model", "");
+    let binary = http_request(port, "GET", "/api/model/binary", "");
+    let validate = http_request(port, "GET", "/api/validate", "");
+    let viz = http_request(port, "GET", "/api/viz/mermaid", "");
+    let codegen = http_request(port, "GET", "/api/codegen/cobol", "");
+    let parse = http_request(port, "POST", "/api/parse", "context Billing {");
+    let page = http_request(port, "GET", "/", "");
+
+    // Editing the file reloads the model, and what was derived from it
+    fs::write(
+        &file,
+        "context Sales { entity Order entity Customer entity Customer }",
+    )
+    .unwrap();
+    let mut reloaded = String::new();
+    for _ in 0..50 {
+        reloaded = http_request(port, "GET", "/api/viz/mermaid", "");
+        if reloaded.contains("Customer") {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    let revalidated = http_request(port, "GET", "/api/validate", "");
+    let generated = http_request(port, "GET", "/api/codegen/rust", "");
+    server.kill().unwrap();
+    server.wait().unwrap();
+
+    assert!(model.starts_with("HTTP/1.1 200"));
+    assert!(model.contains(r#""version":1"#));
+    assert!(model.contains(r#""source":"context Sales { entity Order }""#));
+    assert!(binary.contains("content-type: application/octet-stream"));
+    assert!(binary.contains("\r\n\r\nSDDM"));
+    assert!(validate.contains(r#""errors":0"#));
+    assert!(viz.contains("Order"));
+    assert!(codegen.starts_with("HTTP/1.1 404"));
+    assert!(codegen.contains("Unknown target language: cobol"));
+    assert!(parse.starts_with("HTTP/1.1 422"));
+    assert!(parse.contains("Parse error"));
+    assert!(page.contains("The visual builder is not built"));
+    assert!(reloaded.contains("Customer"));
+    assert!(revalidated.contains(r#""errors":1"#));
+    assert!(generated.contains("pub struct Customer"));
+}
+
+#[test]
+fn test_serve_collaborative_editing() {
+    use tungstenite::stream::MaybeTlsStream;
+    use tungstenite::{Message, WebSocket};
+
+    type Socket = WebSocket<MaybeTlsStream<std::net::TcpStream>>;
+    let receive = |socket: &mut Socket| -> serde_json::Value {
+        let message = socket.read().unwrap();
+        serde_json::from_str(message.to_text().unwrap()).unwrap()
+    };
+    let join = |port: u16| -> Socket {
+        let (mut socket, _) =
+            tungstenite::connect(format!("ws://127.0.0.1:{}/api/collab", port)).unwrap();
+        if let MaybeTlsStream::Plain(stream) = socket.get_mut() {
+            stream
+                .set_read_timeout(Some(std::time::Duration::from_secs(10)))
+                .unwrap();
+        }
+        socket
+    };
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file = temp_dir.path().join("model.sddd");
+    fs::write(&file, "context Sales { entity Order entity Customer }").unwrap();
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    // Stop the server even if an assertion fails
+    struct Server(std::process::Child);
+    impl Drop for Server {
+        fn drop(&mut self) {
+            let _ = self.0.kill();
+            let _ = self.0.wait();
+        }
+    }
+    let _server = Server(
+        sketchddd()
+            .args(["serve", file.to_str().unwrap(), "--port", &port.to_string()])
+            .stdout(std::process::Stdio::null())
+            .spawn()
+            .unwrap(),
+    );
+    http_request(port, "GET", "/api/model", "");
+
+    let mut alice = join(port);
+    let mut bob = join(port);
+    let snapshot = receive(&mut alice);
+    assert_eq!(snapshot["type"], "snapshot");
+    assert_eq!(snapshot["revision"], 0);
+    assert_eq!(
+        snapshot["model"]["model"]["contexts"][0]["sketch"]["name"],
+        "Sales"
+    );
+    let alice_id = snapshot["client"].clone();
+    assert_eq!(receive(&mut bob)["revision"], 0);
+
+    // Both add an Invoice to revision 0; Alice's arrives first
+    alice
+        .send(Message::text(
+            r#"{"base":0,"context":"Sales","command":{"AddEntity":{"name":"Invoice"}}}"#,
+        ))
+        .unwrap();
+    let edit = receive(&mut alice);
+    assert_eq!(edit["type"], "edit");
+    assert_eq!(edit["revision"], 1);
+    assert_eq!(edit["client"], alice_id);
+    assert_eq!(edit["edited"]["Object"], 2);
+    assert_eq!(receive(&mut bob), edit);
+
+    bob.send(Message::text(
+        r#"{"base":0,"context":"Sales","command":{"AddValueObject":{"name":"Invoice"}}}"#,
+    ))
+    .unwrap();
+    let rejected = receive(&mut bob);
+    assert_eq!(rejected["type"], "rejected");
+    assert_eq!(rejected["error"], "'Invoice' already exists");
+
+    bob.send(Message::text(
+        r#"{"base":1,"context":"Sales","command":{"RenameObject":{"object":0,"name":"Purchase"}}}"#,
+    ))
+    .unwrap();
+    let edit = receive(&mut alice);
+    assert_eq!(edit["revision"], 2);
+    assert_eq!(edit["command"]["RenameObject"]["name"], "Purchase");
+    assert_eq!(receive(&mut bob), edit);
+
+    // The API serves the edited model
+    let model = http_request(port, "GET", "/api/model", "");
+    assert!(model.contains("\"revision\":2"));
+    assert!(model.contains("Invoice"));
+    assert!(model.contains("Purchase"));
+    let code = http_request(port, "GET", "/api/codegen/rust", "");
+    assert!(code.contains("Invoice"));
+
+    // Reloading the file starts everyone again from it, discarding the edits
+    fs::write(&file, "context Sales { entity Order }").unwrap();
+    let snapshot = receive(&mut alice);
+    assert_eq!(snapshot["type"], "snapshot");
+    assert_eq!(snapshot["revision"], 3);
+    assert_eq!(snapshot["discarded"], 2);
+    let model = http_request(port, "GET", "/api/model", "");
+    assert!(!model.contains("Invoice"));
+}
+
+#[test]
+fn test_serve_headless() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut server = sketchddd()
+        .args(["serve", "--headless", "--port", &port.to_string()])
+        .env("SKETCHDDD_API_KEY", "s3cret")
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+
+    let json = [("Content-Type", "application/json")];
+    let authorized = [
+        ("Content-Type", "application/json"),
+        ("Authorization", "Bearer s3cret"),
+    ];
+    let source = r#""context Sales { entity Order entity Customer morphisms { placedBy: Order -> Customer } }""#;
+
+    let health = http_request(port, "GET", "/health", "");
+    let openapi = http_request(port, "GET", "/openapi.json", "");
+    let missing = http_request_with(
+        port,
+        "POST",
+        "/parse",
+        &json,
+        &format!(r#"{{"source":{}}}"#, source),
+    );
+    let wrong = http_request_with(
+        port,
+        "POST",
+        "/parse",
+        &[("Content-Type", "application/json"), ("X-API-Key", "guess")],
+        &format!(r#"{{"source":{}}}"#, source),
+    );
+    let parse = http_request_with(
+        port,
+        "POST",
+        "/parse",
+        &authorized,
+        &format!(r#"{{"source":{}}}"#, source),
+    );
+    let validate = http_request_with(
+        port,
+        "POST",
+        "/validate",
+        &authorized,
+        &format!(r#"{{"source":{}}}"#, source),
+    );
+    let scripted = http_request_with(
+        port,
+        "POST",
+        "/validate",
+        &authorized,
+        &format!(
+            r#"{{"source":{},"config":"[rules.leak]\nscript = \"/etc/hostname\""}}"#,
+            source
+        ),
+    );
+    let codegen = http_request_with(
+        port,
+        "POST",
+        "/codegen",
+        &authorized,
+        &format!(r#"{{"source":{},"target":"typescript"}}"#, source),
+    );
+    let viz = http_request_with(
+        port,
+        "POST",
+        "/viz",
+        &authorized,
+        &format!(r#"{{"source":{}}}"#, source),
+    );
+    let diff = http_request_with(
+        port,
+        "POST",
+        "/diff",
+        &authorized,
+        &format!(
+            r#"{{"old":{},"new":"context Sales {{ entity Order }}"}}"#,
+            source
+        ),
+    );
+    let invalid = http_request_with(
+        port,
+        "POST",
+        "/parse",
+        &authorized,
+        r#"{"source":"context Sales {"}"#,
+    );
+    let malformed = http_request_with(
+        port,
+        "POST",
+        "/parse",
+        &authorized,
+        r#"{"text":"context Sales {}"}"#,
+    );
+    server.kill().unwrap();
+    server.wait().unwrap();
+
+    assert!(health.contains(r#""status":"ok""#));
+    assert!(openapi.contains(r#""openapi":"3.0.3""#));
+    assert!(openapi.contains(r#""/codegen""#));
+    assert!(missing.starts_with("HTTP/1.1 401"));
+    assert!(missing.contains("Missing API key"));
+    assert!(wrong.starts_with("HTTP/1.1 401"));
+    assert!(wrong.contains("Invalid API key"));
+    assert!(parse.starts_with("HTTP/1.1 200"));
+    assert!(parse.contains(r#""version":1"#));
+    assert!(validate.contains(r#""errors":0"#));
+    assert!(scripted.starts_with("HTTP/1.1 422"));
+    assert!(scripted.contains("rule scripts are not accepted"));
+    assert!(!scripted.contains("hostname"));
+    assert!(codegen.contains(r#""Sales":"#));
+    assert!(codegen.contains("interface Order"));
+    assert!(viz.contains("placedBy"));
+    assert!(diff.contains(r#""breaking":2"#));
+    assert!(invalid.starts_with("HTTP/1.1 422"));
+    assert!(invalid.contains("Parse error"));
+    assert!(malformed.starts_with("HTTP/1.1 400"));
+    assert!(malformed.contains("missing field `source`"));
+}
+
+#[test]
+fn test_serve_requires_built_assets() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file = temp_dir.path().join("model.sddd");
+    fs::write(&file, "context Sales { entity Order }").unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args(["serve", file.to_str().unwrap(), "--assets", temp_dir.path().to_str().unwrap()]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No index.html in"));
+}
+
+#[test]
+fn test_import_requires_versioned_model() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("import.json");
+
+    fs::write(&file_path, "{}").unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args(["import", file_path.to_str().unwrap()]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("has no format version"));
+
+    fs::write(&file_path, r#"{ "version": 99, "model": {} }"#).unwrap();
+    let mut cmd = sketchddd();
+    cmd.args(["import", file_path.to_str().unwrap()]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("model format version 99 is newer"));
+}
+
+#[test]
+fn test_import_sql_schema() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let schema_path = temp_dir.path().join("shop_db.sql");
+    let output_path = temp_dir.path().join("shop.sddd");
+
+    fs::write(
+        &schema_path,
+        r#"
+        CREATE TYPE order_status AS ENUM ('pending', 'shipped');
+        CREATE TABLE customers (
+            id UUID PRIMARY KEY,
+            email TEXT NOT NULL
+        );
+        CREATE TABLE orders (
+            id UUID PRIMARY KEY,
+            customer_id UUID NOT NULL REFERENCES customers (id),
+            status order_status NOT NULL,
+            total_amount DECIMAL NOT NULL,
+            total_currency CHAR(3) NOT NULL
+        );
+    "#,
+    )
+    .unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args([
+        "import",
+        schema_path.to_str().unwrap(),
+        "-o",
+        output_path.to_str().unwrap(),
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("became the value object 'Total'"));
+
+    let source = fs::read_to_string(&output_path).unwrap();
+    assert!(source.contains("context ShopDb {"));
+    assert!(source.contains("entity Order {"));
+    assert!(source.contains("customer: Order -> Customer"));
+    assert!(source.contains("enum OrderStatus = Pending | Shipped"));
+
+    // The draft is a valid model
+    let mut cmd = sketchddd();
+    cmd.args(["check", output_path.to_str().unwrap()]);
+    cmd.assert().success();
+
+    let mut cmd = sketchddd();
+    cmd.args([
+        "import",
+        schema_path.to_str().unwrap(),
+        "--from",
+        "sql",
+        "--context",
+        "Sales",
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("context Sales {"));
+}
+
+#[test]
+fn test_import_openapi_document() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let spec_path = temp_dir.path().join("billing-api.yaml");
+    let output_path = temp_dir.path().join("billing.sddd");
+
+    fs::write(
+        &spec_path,
+        r#"
+openapi: 3.0.3
+info:
+  title: Billing
+  version: 1.0.0
+paths: {}
+components:
+  schemas:
+    Invoice:
+      type: object
+      required: [id, customer]
+      properties:
+        id:
+          type: string
+          format: uuid
+        customer:
+          $ref: '#/components/schemas/Customer'
+        total:
+          $ref: '#/components/schemas/Money'
+        status:
+          type: string
+          enum: [draft, paid]
+    Customer:
+      type: object
+      properties:
+        id:
+          type: string
+    Money:
+      type: object
+      properties:
+        amount:
+          type: number
+        currency:
+          type: string
+"#,
+    )
+    .unwrap();
+
+    // OpenAPI documents are told from exports of models
+    let mut cmd = sketchddd();
+    cmd.args([
+        "import",
+        spec_path.to_str().unwrap(),
+        "-o",
+        output_path.to_str().unwrap(),
+    ]);
+    cmd.assert().success().stdout(predicate::str::contains(
+        "Schema 'Money' has no id property",
+    ));
+
+    let source = fs::read_to_string(&output_path).unwrap();
+    assert!(source.contains("context BillingApi {"));
+    assert!(source.contains("customer: Invoice -> Customer"));
+    assert!(source.contains("total: Money?"));
+    assert!(source.contains("enum InvoiceStatus = Draft | Paid"));
+
+    let mut cmd = sketchddd();
+    cmd.args(["check", output_path.to_str().unwrap()]);
+    cmd.assert().success();
+}
+
+#[test]
+fn test_import_json_schema() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let schema_path = temp_dir.path().join("payment.json");
+
+    fs::write(
+        &schema_path,
+        r##"{
+        "title": "Payment",
+        "type": "object",
+        "properties": {
+            "id": { "type": "string" },
+            "method": {
+                "oneOf": [{ "$ref": "#/$defs/Card" }, { "$ref": "#/$defs/Wire" }]
+            }
+        },
+        "$defs": {
+            "Card": { "type": "object", "properties": { "last4": { "type": "string" } } },
+            "Wire": { "type": "object", "properties": { "iban": { "type": "string" } } }
+        }
+    }"##,
+    )
+    .unwrap();
+
+    // A JSON Schema without `$schema` is only read as one when asked
+    let mut cmd = sketchddd();
+    cmd.args(["import", schema_path.to_str().unwrap()]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("has no format version"));
+
+    let mut cmd = sketchddd();
+    cmd.args([
+        "import",
+        schema_path.to_str().unwrap(),
+        "--from",
+        "schema",
+        "--context",
+        "Payments",
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("context Payments {"))
+        .stdout(predicate::str::contains(
+            "enum PaymentMethod = Card(Card) | Wire(Wire)",
+        ));
+}
+
+#[test]
+fn test_import_rust_crate() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let crate_dir = temp_dir.path().join("shop-core");
+    let output_path = temp_dir.path().join("shop.sddd");
+    fs::create_dir_all(crate_dir.join("src/domain")).unwrap();
+
+    fs::write(
+        crate_dir.join("Cargo.toml"),
+        "[package]\nname = \"shop-core\"\n",
+    )
+    .unwrap();
+    fs::write(
+        crate_dir.join("src/lib.rs"),
+        r#"
+        pub mod domain;
+
+        #[domain(value)]
+        pub struct Money {
+            amount: i64,
+            currency: String,
+        }
+    "#,
+    )
+    .unwrap();
+    fs::write(crate_dir.join("src/domain/mod.rs"), "pub mod order;").unwrap();
+    fs::write(
+        crate_dir.join("src/domain/order.rs"),
+        r#"
+        pub struct CustomerId(uuid::Uuid);
+
+        pub struct Customer {
+            pub id: CustomerId,
+        }
+
+        pub struct Order {
+            pub id: u64,
+            pub customer_id: CustomerId,
+            pub total: crate::Money,
+            pub status: Status,
+        }
+
+        pub enum Status { Open, Closed }
+    "#,
+    )
+    .unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args([
+        "import",
+        crate_dir.to_str().unwrap(),
+        "--module",
+        "crate::domain",
+        "-o",
+        output_path.to_str().unwrap(),
+    ]);
+    cmd.assert().success();
+
+    let source = fs::read_to_string(&output_path).unwrap();
+    assert!(source.contains("context ShopCore {"));
+    assert!(source.contains("entity Customer {\n    id: UUID\n  }"));
+    assert!(source.contains("customer: Order -> Customer"));
+    assert!(source.contains("total: Money"));
+    assert!(source.contains("enum Status = Open | Closed"));
+
+    let mut cmd = sketchddd();
+    cmd.args(["check", output_path.to_str().unwrap()]);
+    cmd.assert().success();
+
+    // Without modules, only marked items are imported
+    let mut cmd = sketchddd();
+    cmd.args(["import", crate_dir.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("value Money"))
+        .stdout(predicate::str::contains("entity Order").not());
+}
+
+#[test]
+fn test_import_module_requires_rust() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("schema.sql");
+    fs::write(&file_path, "CREATE TABLE t (id INT);").unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args(["import", file_path.to_str().unwrap(), "--module", "domain"]);
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "--module only applies to Rust sources",
+    ));
+}
+
+#[test]
+fn test_import_rejects_unknown_format() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("schema.txt");
+    fs::write(&file_path, "").unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args(["import", file_path.to_str().unwrap(), "--from", "csv"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown import format 'csv'"));
+}
+
+#[test]
+fn test_stats() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("shop.sddd");
+
+    fs::write(&file_path, r#"
+        context Sales {
+            objects { Order, LineItem, Customer }
+            morphisms {
+                items: Order -> LineItem
+                placedBy: Order -> Customer
+            }
+            aggregate Order {
+                root: Order
+                contains: [LineItem]
+            }
+        }
+
+        context Billing {
+            objects { Invoice }
+        }
+
+        map SalesToBilling: Sales -> Billing {
+            pattern: CustomerSupplier
+            mappings {
+                Order -> Invoice
+            }
+        }
+    "#).unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args(["stats", file_path.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Model (2 contexts, 1 context maps)"))
+        .stdout(predicate::str::contains("morphisms: 2, equations: 0"))
+        .stdout(predicate::str::contains("Sales -> Billing: 1 map(s), 1 mapped object(s)"))
+        .stdout(predicate::str::contains("Sales.Order: 2 objects"));
+
+    let mut cmd = sketchddd();
+    cmd.args(["stats", "--format", "json", "--top", "0", file_path.to_str().unwrap()]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let stats: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(stats["totals"]["aggregates"], 1);
+    assert_eq!(stats["contexts"][0]["name"], "Sales");
+    assert_eq!(stats["contexts"][0]["average_aggregate_size"], 2.0);
+    assert_eq!(stats["contexts"][1]["coupled_with"][0], "Sales");
+    assert_eq!(stats["aggregates"].as_array().unwrap().len(), 0);
+}
+
+#[test]
+fn test_query() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("shop.sddd");
+
+    fs::write(&file_path, r#"
+        context Sales {
+            objects { Order, LineItem, Customer, Address }
+            value Money { amount: Decimal }
+            morphisms {
+                items: Order -> LineItem
+                placedBy: Order -> Customer
+                price: LineItem -> Money
+                address: Customer -> Address
+            }
+            aggregate Order {
+                root: Order
+                contains: [LineItem]
+            }
+        }
+
+        context Billing {
+            objects { Invoice }
+        }
+
+        map SalesToBilling: Sales -> Billing {
+            pattern: CustomerSupplier
+            mappings {
+                Order -> Invoice
+            }
+        }
+    "#).unwrap();
+    let file = file_path.to_str().unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args(["query", "morphisms from Order", file]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Sales    items     Order   LineItem"))
+        .stdout(predicate::str::contains("Sales    placedBy  Order   Customer"));
+
+    let mut cmd = sketchddd();
+    cmd.args(["query", "usages of Order", file]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("aggregate root"))
+        .stdout(predicate::str::contains("SalesToBilling  maps to Invoice"));
+
+    let mut cmd = sketchddd();
+    cmd.args(["query", "PATH Sales.Order -> Address", file]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("1     placedBy  Order     Customer"))
+        .stdout(predicate::str::contains("2     address   Customer  Address"));
+
+    let mut cmd = sketchddd();
+    cmd.args(["query", "path Customer -> Order", file]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("No results"));
+
+    let mut cmd = sketchddd();
+    cmd.args(["--format", "json", "query", "path Order -> Order", file]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let answer: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(answer["rows"].as_array().unwrap().len(), 1);
+    assert_eq!(answer["rows"][0]["morphism"], "id_Order");
+    assert_eq!(answer["rows"][0]["source"], "Order");
+    assert_eq!(answer["rows"][0]["target"], "Order");
+
+    let mut cmd = sketchddd();
+    cmd.args(["query", "usages of Ordr", file]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Object 'Ordr' not found; did you mean `Order`?"));
+
+    let mut cmd = sketchddd();
+    cmd.args(["query", "find Order", file]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Cannot understand the query 'find Order'"));
+
+    let mut cmd = sketchddd();
+    cmd.args(["--format", "json", "query", "path Order->Money", file]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let answer: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(answer["command"], "query");
+    assert_eq!(answer["columns"][2], "morphism");
+    assert_eq!(answer["rows"][0]["morphism"], "items");
+    assert_eq!(answer["rows"][1]["morphism"], "price");
+    assert_eq!(answer["rows"][1]["target"], "Money");
+
+    let mut cmd = sketchddd();
+    cmd.args(["--format", "json", "query", "consumers of Sales", file]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let answer: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(answer["rows"][0]["consumer"], "Billing");
+}
 
 #[test]
-fn test_import_stub() {
+fn test_docs_site() {
     let temp_dir = tempfile::tempdir().unwrap();
-    let file_path = temp_dir.path().join("import.json");
+    let file_path = temp_dir.path().join("shop.sddd");
+    let site_dir = temp_dir.path().join("site");
 
-    fs::write(&file_path, "{}").unwrap();
+    fs::write(&file_path, r#"
+        context Sales {
+            objects { Order, LineItem, Customer }
+            morphisms {
+                items: Order -> LineItem
+                placedBy: Order -> Customer
+            }
+            aggregate Order {
+                root: Order
+                contains: [LineItem]
+            }
+        }
+
+        context Billing {
+            objects { Invoice }
+        }
+
+        map SalesToBilling: Sales -> Billing {
+            pattern: CustomerSupplier
+            mappings {
+                Order -> Invoice
+            }
+        }
+    "#).unwrap();
 
     let mut cmd = sketchddd();
-    cmd.args(["import", file_path.to_str().unwrap()]);
+    cmd.args([
+        "docs",
+        file_path.to_str().unwrap(),
+        "--output",
+        site_dir.to_str().unwrap(),
+        "--title",
+        "Shop <Model>",
+    ]);
     cmd.assert()
         .success()
-        .stdout(predicate::str::contains("not yet implemented"));
+        .stdout(predicate::str::contains("Wrote 7 files"));
+
+    let index = fs::read_to_string(site_dir.join("index.html")).unwrap();
+    assert!(index.contains("<title>Shop &lt;Model&gt;</title>"));
+    assert!(index.contains("<a href=\"contexts/Sales.html\">Sales</a>"));
+    assert!(index.contains("<pre class=\"mermaid\">\nflowchart LR"));
+
+    let sales = fs::read_to_string(site_dir.join("contexts/Sales.html")).unwrap();
+    assert!(sales.contains("<pre class=\"mermaid\">\nclassDiagram"));
+    assert!(sales.contains("<h3 id=\"Order\">Order <small>aggregate root</small></h3>"));
+    assert!(sales.contains("<tr><td>placedBy</td><td><a href=\"#Customer\">Customer</a></td></tr>"));
+    assert!(sales.contains("Contains: <a href=\"#LineItem\">LineItem</a>"));
+    assert!(sales.contains("href=\"../maps/SalesToBilling.html\""));
+
+    let map = fs::read_to_string(site_dir.join("maps/SalesToBilling.html")).unwrap();
+    assert!(map.contains("<tr><td>Order</td><td>Invoice</td><td></td></tr>"));
+    let glossary = fs::read_to_string(site_dir.join("glossary.html")).unwrap();
+    assert!(glossary.contains("<a href=\"contexts/Billing.html#Invoice\">Billing</a>"));
+    assert!(site_dir.join("style.css").exists());
+    assert!(site_dir.join(".nojekyll").exists());
 }
 
 #[test]
-fn test_diff_stub() {
+fn test_diff_contexts() {
     let temp_dir = tempfile::tempdir().unwrap();
     let file1 = temp_dir.path().join("old.sddd");
     let file2 = temp_dir.path().join("new.sddd");
@@ -503,7 +2896,373 @@ fn test_diff_stub() {
     cmd.args(["diff", file1.to_str().unwrap(), file2.to_str().unwrap()]);
     cmd.assert()
         .success()
-        .stdout(predicate::str::contains("not yet implemented"));
+        .stdout(predicate::str::contains("context 'A' removed"))
+        .stdout(predicate::str::contains("context 'B' added"))
+        .stdout(predicate::str::contains("2 change(s), 1 breaking"));
+}
+
+#[test]
+fn test_diff_breaking_change_without_major_bump() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file1 = temp_dir.path().join("old.sddd");
+    let file2 = temp_dir.path().join("new.sddd");
+
+    fs::write(
+        &file1,
+        r#"
+@version(1)
+context Billing {
+    @removed_in("2.0")
+    entity LegacyInvoice
+    entity Invoice
+}
+"#,
+    )
+    .unwrap();
+    fs::write(
+        &file2,
+        r#"
+@version("1.1")
+context Billing {
+    entity Invoice
+    entity Payment
+}
+"#,
+    )
+    .unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args(["diff", file1.to_str().unwrap(), file2.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "entity 'LegacyInvoice' removed (scheduled for removal in 2.0)",
+        ))
+        .stdout(predicate::str::contains("entity 'Payment' added"))
+        .stdout(predicate::str::contains("expected a major version bump"));
+}
+
+#[test]
+fn test_diff_fail_on_breaking() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file1 = temp_dir.path().join("old.sddd");
+    let file2 = temp_dir.path().join("new.sddd");
+    let file3 = temp_dir.path().join("optional.sddd");
+
+    fs::write(&file1, "context Sales { entity Order entity Customer }").unwrap();
+    fs::write(
+        &file2,
+        "context Sales { entity Order entity Customer morphisms { placedBy: Order -> Customer } }",
+    )
+    .unwrap();
+    fs::write(
+        &file3,
+        "context Sales { entity Order entity Customer morphisms { placedBy: Order -> Customer? } }",
+    )
+    .unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args(["diff", file1.to_str().unwrap(), file2.to_str().unwrap(), "--fail-on", "breaking"]);
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("morphism 'Order.placedBy' added (required) [breaking]"))
+        .stderr(predicate::str::contains("Found breaking changes (--fail-on breaking)"));
+
+    let mut cmd = sketchddd();
+    cmd.args(["diff", file1.to_str().unwrap(), file3.to_str().unwrap(), "--fail-on", "breaking"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("morphism 'Order.placedBy' added (optional) [compatible]"));
+}
+
+#[test]
+fn test_diff_groups_changes_by_context() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file1 = temp_dir.path().join("old.sddd");
+    let file2 = temp_dir.path().join("new.sddd");
+
+    fs::write(&file1, "context Sales { entity Order entity Quote } context Billing { entity Invoice }").unwrap();
+    fs::write(&file2, "context Sales { entity Order entity Customer } context Billing { entity Invoice entity Payment }").unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args(["diff", file1.to_str().unwrap(), file2.to_str().unwrap()]);
+    cmd.assert().success().stdout(predicate::str::contains(
+        "Sales\n  - entity 'Quote' removed [breaking]\n  + entity 'Customer' added [compatible]\nBilling\n  + entity 'Payment' added [compatible]",
+    ));
+
+    // The JSON output lists the changes in the same order
+    let mut cmd = sketchddd();
+    cmd.args(["diff", file1.to_str().unwrap(), file2.to_str().unwrap(), "--format", "json"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let elements: Vec<&str> = report["changes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["element"].as_str().unwrap())
+        .collect();
+    assert_eq!(elements, ["entity 'Quote'", "entity 'Customer'", "entity 'Payment'"]);
+}
+
+#[test]
+fn test_diff_json_format() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file1 = temp_dir.path().join("old.sddd");
+    let file2 = temp_dir.path().join("new.sddd");
+
+    fs::write(&file1, "context Sales { entity Order entity Quote }").unwrap();
+    fs::write(&file2, "context Sales { entity Order }").unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args(["diff", file1.to_str().unwrap(), file2.to_str().unwrap(), "--format", "json", "--fail-on", "breaking"]);
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["command"], "diff");
+    assert_eq!(report["status"], "failure");
+    assert_eq!(report["changes"][0]["context"], "Sales");
+    assert_eq!(report["changes"][0]["kind"], "Removed");
+    assert_eq!(report["changes"][0]["element"], "entity 'Quote'");
+    assert_eq!(report["summary"]["breaking"], 1);
+    assert_eq!(report["summary"]["max_severity"], "Breaking");
+}
+
+#[test]
+fn test_ci_gates() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let old = temp_dir.path().join("old.sddd");
+    let new = temp_dir.path().join("new.sddd");
+
+    fs::write(&old, "context Sales { entity Order entity Quote }").unwrap();
+    fs::write(&new, "context Sales { entity Order }").unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.current_dir(temp_dir.path());
+    cmd.env_remove("GITHUB_ACTIONS");
+    cmd.args(["ci", "new.sddd"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("✓ errors: 0 (max 0)"));
+
+    let mut cmd = sketchddd();
+    cmd.current_dir(temp_dir.path());
+    cmd.env_remove("GITHUB_ACTIONS");
+    cmd.args(["ci", "new.sddd", "--base", "old.sddd"]);
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("- entity 'Quote' removed [breaking]"))
+        .stdout(predicate::str::contains("✗ changes: 1 change(s), most breaking (fails on breaking)"))
+        .stderr(predicate::str::contains("1 of 3 quality gate(s) failed"));
+
+    fs::write(temp_dir.path().join("sketchddd.toml"), "[ci]\nfail-on = \"none\"\nmax-warnings = 1\n").unwrap();
+    let mut cmd = sketchddd();
+    cmd.current_dir(temp_dir.path());
+    cmd.env("GITHUB_ACTIONS", "true");
+    cmd.args(["ci", "new.sddd", "--base", "old.sddd"]);
+    let output = cmd.output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{}", stdout);
+    assert!(stdout.contains("::warning file=new.sddd,title=W0080::"));
+    assert!(stdout.contains("::notice title=breaking change::Sales: entity 'Quote' removed"));
+
+    let mut cmd = sketchddd();
+    cmd.current_dir(temp_dir.path());
+    cmd.args(["ci", "--format", "json", "new.sddd", "--fail-on", "breaking", "--base", "old.sddd"]);
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["command"], "ci");
+    assert_eq!(report["status"], "failure");
+    assert_eq!(report["gates"][2]["name"], "changes");
+    assert_eq!(report["gates"][2]["passed"], false);
+}
+
+#[test]
+fn test_ci_project_warnings() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(temp_dir.path().join("model")).unwrap();
+    fs::write(
+        temp_dir.path().join("sketchddd.toml"),
+        "[model]\nsources = [\"model/*.sddd\"]\n\n[ci]\nmax-warnings = 1\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("model/sales.sddd"),
+        "context Sales {\n  entity Order {}\n  value Money {\n    amount: Decimal\n  }\n}\n",
+    )
+    .unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.current_dir(temp_dir.path());
+    cmd.env("GITHUB_ACTIONS", "true");
+    cmd.arg("ci");
+    let output = cmd.output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(!output.status.success(), "{}", stdout);
+    // Transform warnings count against the gate like lint warnings
+    assert!(stdout.contains("::warning file=model/sales.sddd,line=4,col=5,title=TRANSFORM_WARNING::"));
+    // Issues without a location are in the model file, not the configuration
+    assert!(stdout.contains("::warning file=model/sales.sddd,title=W0080::"));
+    assert!(!stdout.contains("file=sketchddd.toml"));
+    assert!(stdout.contains("::error title=Quality gate::warnings: 3 (max 1)"));
+}
+
+#[test]
+fn test_ci_git_base() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let dir = temp_dir.path();
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .current_dir(dir)
+            .args(["-c", "user.name=CI", "-c", "user.email=ci@example.com"])
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
+
+    fs::write(dir.join("model.sddd"), "context Sales { entity Order entity Quote }").unwrap();
+    git(&["init", "-q"]);
+    git(&["add", "model.sddd"]);
+    git(&["commit", "-q", "-m", "Add model"]);
+    fs::write(dir.join("model.sddd"), "context Sales { entity Order }").unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.current_dir(dir);
+    cmd.env("GITHUB_ACTIONS", "true");
+    cmd.args(["ci", "model.sddd", "--base", "HEAD"]);
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("::error title=breaking change::Sales: entity 'Quote' removed"))
+        .stdout(predicate::str::contains("::error title=Quality gate::changes"));
+
+    let mut cmd = sketchddd();
+    cmd.current_dir(dir);
+    cmd.args(["ci", "model.sddd", "--base", "no-such-branch"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Base 'no-such-branch' is neither a file nor a git revision"));
+}
+
+#[test]
+fn test_diff_identical_files() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file = temp_dir.path().join("model.sddd");
+    fs::write(&file, "context A { entity Order }").unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args(["diff", file.to_str().unwrap(), file.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("No changes"));
+}
+
+#[test]
+fn test_merge_driver() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let dir = temp_dir.path();
+    let base = dir.join("base.sddd");
+    let ours = dir.join("ours.sddd");
+    let theirs = dir.join("theirs.sddd");
+
+    fs::write(
+        &base,
+        "context Sales {\n  entity Order\n  enum Status = Open | Closed\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        &ours,
+        "context Sales {\n  entity Order\n  entity Quote\n  enum Status = Open | Closed\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        &theirs,
+        "context Sales {\n  entity Order\n  enum Status = Open | Archived\n}\n",
+    )
+    .unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.args([
+        "merge-driver",
+        base.to_str().unwrap(),
+        ours.to_str().unwrap(),
+        theirs.to_str().unwrap(),
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Merged without conflicts"));
+    let merged = fs::read_to_string(&ours).unwrap();
+    assert!(merged.contains("entity Quote"));
+    assert!(merged.contains("enum Status = Open | Archived"));
+
+    fs::write(
+        &ours,
+        "context Sales {\n  entity Order\n  enum Status = Open | Shipped\n}\n",
+    )
+    .unwrap();
+    let mut cmd = sketchddd();
+    cmd.args([
+        "merge-driver",
+        base.to_str().unwrap(),
+        ours.to_str().unwrap(),
+        theirs.to_str().unwrap(),
+    ]);
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("Conflict in enum Status in Sales"))
+        .stderr(predicate::str::contains("1 conflict(s) left to resolve"));
+    let merged = fs::read_to_string(&ours).unwrap();
+    assert!(merged.contains("<<<<<<< ours\n  enum Status = Open | Shipped\n=======\n  enum Status = Open | Archived\n>>>>>>> theirs\n"));
+    assert!(merged.contains("entity Order"));
+}
+
+#[test]
+fn test_merge_driver_in_git() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let dir = temp_dir.path();
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .current_dir(dir)
+            .args(["-c", "user.name=CI", "-c", "user.email=ci@example.com"])
+            .args(args)
+            .status()
+            .unwrap()
+    };
+    let driver = format!("{} merge-driver %O %A %B", env!("CARGO_BIN_EXE_sketchddd"));
+
+    fs::write(
+        dir.join("model.sddd"),
+        "context Sales {\n  entity Order\n}\n",
+    )
+    .unwrap();
+    fs::write(dir.join(".gitattributes"), "*.sddd merge=sketchddd\n").unwrap();
+    assert!(git(&["init", "-q", "-b", "main"]).success());
+    assert!(git(&["config", "merge.sketchddd.driver", &driver]).success());
+    assert!(git(&["add", "."]).success());
+    assert!(git(&["commit", "-q", "-m", "Add model"]).success());
+
+    assert!(git(&["checkout", "-q", "-b", "quotes"]).success());
+    fs::write(
+        dir.join("model.sddd"),
+        "context Sales {\n  entity Order\n  entity Quote\n}\n",
+    )
+    .unwrap();
+    assert!(git(&["commit", "-q", "-am", "Add quotes"]).success());
+
+    assert!(git(&["checkout", "-q", "main"]).success());
+    fs::write(
+        dir.join("model.sddd"),
+        "context Sales {\n  entity Order\n  entity Invoice\n}\n",
+    )
+    .unwrap();
+    assert!(git(&["commit", "-q", "-am", "Add invoices"]).success());
+
+    assert!(git(&["merge", "-q", "--no-edit", "quotes"]).success());
+    assert_eq!(
+        fs::read_to_string(dir.join("model.sddd")).unwrap(),
+        "context Sales {\n  entity Order\n  entity Invoice\n  entity Quote\n}\n"
+    );
 }
 
 // =============================================================
@@ -520,6 +3279,14 @@ fn test_template_list() {
         .stdout(predicate::str::contains("minimal"))
         .stdout(predicate::str::contains("ecommerce"))
         .stdout(predicate::str::contains("microservices"));
+
+    // -q leaves the headings out, and the templates in
+    let mut cmd = sketchddd();
+    cmd.args(["-q", "template", "list"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Built-in").not())
+        .stdout(predicate::str::contains("minimal"));
 }
 
 #[test]
@@ -538,7 +3305,7 @@ fn test_template_info_ecommerce() {
     cmd.args(["template", "info", "ecommerce"]);
     cmd.assert()
         .success()
-        .stdout(predicate::str::contains("Template: ecommerce"))
+        .stdout(predicate::str::contains("Template: commerce"))
         .stdout(predicate::str::contains("Customer"));
 }
 
@@ -621,6 +3388,10 @@ fn test_auto_detect_sddd() {
     fs::write(&file_path, r#"
         context Project {
             objects { A, B }
+            aggregate A {
+                root: A
+                contains: [B]
+            }
         }
     "#).unwrap();
 
@@ -643,3 +3414,210 @@ fn test_auto_detect_no_sddd() {
         .failure()
         .stderr(predicate::str::contains("No .sddd file found"));
 }
+
+// =============================================================
+// Project Tests
+// =============================================================
+
+/// Create a project whose model spans two files.
+fn project_dir() -> tempfile::TempDir {
+    let temp_dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(temp_dir.path().join("model")).unwrap();
+    fs::write(
+        temp_dir.path().join("sketchddd.toml"),
+        r#"
+[model]
+sources = ["model/*.sddd"]
+
+[codegen]
+targets = ["rust", "typescript"]
+output = "generated"
+
+[viz]
+output = "diagrams"
+"#,
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("model/billing.sddd"),
+        "context Billing {\n  objects { Invoice }\n  aggregate Invoice { root: Invoice }\n}\n",
+    )
+    .unwrap();
+    // The map refers to a context declared in the other file
+    fs::write(
+        temp_dir.path().join("model/sales.sddd"),
+        r#"context Sales {
+  objects { Order, line_item }
+  aggregate Order {
+    root: Order
+    contains: [line_item]
+  }
+}
+
+map SalesToBilling: Sales -> Billing {
+  pattern: CustomerSupplier
+  mappings { Order -> Invoice }
+}
+"#,
+    )
+    .unwrap();
+    temp_dir
+}
+
+#[test]
+fn test_project_check() {
+    let temp_dir = project_dir();
+
+    // Run from a subdirectory, issues point at the files they are in
+    fs::create_dir(temp_dir.path().join("model/nested")).unwrap();
+    let mut cmd = sketchddd();
+    cmd.current_dir(temp_dir.path().join("model/nested"));
+    cmd.arg("check");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("../../model/sales.sddd:2:20: warning[W0030]"));
+
+    let mut cmd = sketchddd();
+    cmd.current_dir(temp_dir.path());
+    cmd.args(["check", "--format", "json"]);
+    let output = cmd.output().unwrap();
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["file"], "sketchddd.toml");
+    let naming = report["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|i| i["code"] == "W0030")
+        .unwrap();
+    assert_eq!(naming["file"], "model/sales.sddd");
+    assert_eq!(naming["line"], 2);
+
+    // Fixes are applied to the file they edit
+    let mut cmd = sketchddd();
+    cmd.current_dir(temp_dir.path());
+    cmd.args(["check", "--fix"]);
+    cmd.assert().success();
+    let sales = fs::read_to_string(temp_dir.path().join("model/sales.sddd")).unwrap();
+    assert!(sales.contains("objects { Order, LineItem }"));
+    assert!(fs::read_to_string(temp_dir.path().join("model/billing.sddd"))
+        .unwrap()
+        .starts_with("context Billing {"));
+}
+
+#[test]
+fn test_project_defaults() {
+    let temp_dir = project_dir();
+
+    let mut cmd = sketchddd();
+    cmd.current_dir(temp_dir.path());
+    cmd.arg("codegen");
+    cmd.assert().success();
+    assert!(temp_dir.path().join("generated/rust/sales.rs").exists());
+    assert!(temp_dir.path().join("generated/typescript/billing.ts").exists());
+
+    let mut cmd = sketchddd();
+    cmd.current_dir(temp_dir.path());
+    cmd.args(["viz", "--context-map"]);
+    cmd.assert().success();
+    let map = fs::read_to_string(temp_dir.path().join("diagrams/context_map.md")).unwrap();
+    assert!(map.contains("Sales"));
+    assert!(map.contains("Billing"));
+
+    // Options win over the project's defaults
+    let mut cmd = sketchddd();
+    cmd.current_dir(temp_dir.path());
+    cmd.args(["viz", "--format", "graphviz", "--output", "model.dot"]);
+    cmd.assert().success();
+    let dot = fs::read_to_string(temp_dir.path().join("model.dot")).unwrap();
+    assert!(dot.contains("digraph"));
+
+    let mut cmd = sketchddd();
+    cmd.current_dir(temp_dir.path());
+    cmd.args(["export", "-q"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("SalesToBilling"));
+}
+
+#[test]
+fn test_project_viz_theme() {
+    let temp_dir = project_dir();
+    let manifest = temp_dir.path().join("sketchddd.toml");
+    let mut config = fs::read_to_string(&manifest).unwrap();
+    config.push_str("direction = \"TB\"\nmultiplicities = true\n\n[viz.colors]\naggregate-root = \"#ffe6cc\"\n\n[viz.edges]\nupstream = \"dashed\"\n");
+    fs::write(&manifest, config).unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.current_dir(temp_dir.path());
+    cmd.args(["viz", "--format", "graphviz", "--context", "Billing", "--output", "billing.dot"]);
+    cmd.assert().success();
+    let dot = fs::read_to_string(temp_dir.path().join("billing.dot")).unwrap();
+    assert!(dot.contains("rankdir=TB;"));
+    assert!(dot.contains("Invoice [label=\"Invoice\" shape=box style=filled fillcolor=\"#ffe6cc\" penwidth=2];"));
+
+    let mut cmd = sketchddd();
+    cmd.current_dir(temp_dir.path());
+    cmd.args(["viz", "--format", "graphviz", "--context", "Sales", "--output", "sales.dot"]);
+    cmd.assert().success();
+    let dot = fs::read_to_string(temp_dir.path().join("sales.dot")).unwrap();
+    assert!(dot.contains("taillabel=\"*\" headlabel=\"1\""));
+
+    let mut cmd = sketchddd();
+    cmd.current_dir(temp_dir.path());
+    cmd.args(["viz", "--context-map", "--output", "map.md"]);
+    cmd.assert().success();
+    let map = fs::read_to_string(temp_dir.path().join("map.md")).unwrap();
+    assert!(map.contains("flowchart TB"));
+    assert!(map.contains("Sales -.->|\"CS (U → D)\"| Billing"));
+
+    // Unknown theme keys are rejected
+    fs::write(&manifest, "[model]\nsources = [\"model/*.sddd\"]\n\n[viz.colors]\nentities = \"red\"\n").unwrap();
+    let mut cmd = sketchddd();
+    cmd.current_dir(temp_dir.path());
+    cmd.arg("viz");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown field `entities`"));
+}
+
+#[test]
+fn test_viz_settings_apply_outside_projects() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    fs::write(
+        temp_dir.path().join("sketchddd.toml"),
+        "[viz]\nmultiplicities = true\n",
+    )
+    .unwrap();
+    let model = temp_dir.path().join("sales.sddd");
+    fs::write(
+        &model,
+        "context Sales {\n  entity Customer {}\n  entity Order {}\n  morphisms { placedBy: Order -> Customer }\n}\n",
+    )
+    .unwrap();
+
+    // The settings apply to a model file given on the command line
+    let mut cmd = sketchddd();
+    cmd.args(["viz", "-q", "--format", "mermaid", model.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Order \"*\" --> \"1\" Customer : placedBy"))
+        // An identity relates each element to itself alone
+        .stdout(predicate::str::contains("Customer \"1\" --> \"1\" Customer : id_Customer"));
+}
+
+#[test]
+fn test_project_without_model_files() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    fs::write(
+        temp_dir.path().join("sketchddd.toml"),
+        "[model]\nsources = [\"model/*.sddd\"]\n",
+    )
+    .unwrap();
+
+    let mut cmd = sketchddd();
+    cmd.current_dir(temp_dir.path());
+    cmd.arg("check");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("no model file matches [model] sources"));
+}
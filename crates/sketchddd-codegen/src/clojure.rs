@@ -8,12 +8,14 @@
 //! - clojure.spec for validation
 
 use crate::CodegenError;
+use serde::Deserialize;
 use sketchddd_core::sketch::{ColimitCocone, LimitCone, Morphism, ObjectId};
-use sketchddd_core::BoundedContext;
+use sketchddd_core::{BoundedContext, Collection};
 use std::collections::{HashMap, HashSet};
 
 /// Configuration options for Clojure code generation.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct ClojureConfig {
     /// Namespace (defaults to context name in kebab-case)
     pub namespace: Option<String>,
@@ -259,7 +261,8 @@ impl<'a> ClojureGenerator<'a> {
             if let Some(morphisms) = self.object_morphisms.get(&object_id) {
                 for morphism in morphisms {
                     let field_name = to_kebab_case(&morphism.name);
-                    self.output.push_str(&format!("    :{field_name} {field_name}\n"));
+                    let value = self.field_value(morphism);
+                    self.output.push_str(&format!("    :{field_name} {value}\n"));
                 }
             }
 
@@ -268,6 +271,8 @@ impl<'a> ClojureGenerator<'a> {
 
         // Spec definition
         if self.config.use_spec {
+            let specs = self.collection_specs(object_id);
+            self.output.push_str(&specs);
             self.output.push_str(&format!("(s/def ::{kebab_name}\n"));
             self.output.push_str(&format!("  (s/keys :req-un [::{kebab_name}-id"));
 
@@ -342,10 +347,7 @@ impl<'a> ClojureGenerator<'a> {
             if let Some(morphisms) = morphisms {
                 let assignments: Vec<String> = morphisms
                     .iter()
-                    .map(|m| {
-                        let field_name = to_kebab_case(&m.name);
-                        format!(":{} {}", field_name, field_name)
-                    })
+                    .map(|m| format!(":{} {}", to_kebab_case(&m.name), self.field_value(m)))
                     .collect();
                 self.output.push_str(&assignments.join(" "));
             }
@@ -355,6 +357,8 @@ impl<'a> ClojureGenerator<'a> {
 
         // Spec definition
         if self.config.use_spec {
+            let specs = self.collection_specs(object_id);
+            self.output.push_str(&specs);
             self.output.push_str(&format!("(s/def ::{kebab_name}\n"));
             self.output.push_str("  (s/keys :req-un [");
 
@@ -370,6 +374,48 @@ impl<'a> ClojureGenerator<'a> {
         }
     }
 
+    /// Get the specs of an object's to-many fields, as collections of
+    /// their target.
+    fn collection_specs(&self, object_id: ObjectId) -> String {
+        let Some(morphisms) = self.object_morphisms.get(&object_id) else {
+            return String::new();
+        };
+        morphisms
+            .iter()
+            .filter_map(|m| {
+                let kind = match self.context.collection_of(m.id)? {
+                    Collection::List => "",
+                    Collection::Set => " :kind set?",
+                };
+                let target = to_kebab_case(&self.object_names[&m.target]);
+                let element = if self.entity_ids.contains(&m.target) {
+                    format!("::{}-id", target)
+                } else if self.value_object_ids.contains(&m.target) {
+                    format!("::{}", target)
+                } else {
+                    "any?".to_string()
+                };
+                Some(format!(
+                    "(s/def ::{} (s/coll-of {}{}))\n\n",
+                    to_kebab_case(&m.name),
+                    element,
+                    kind
+                ))
+            })
+            .collect()
+    }
+
+    /// Get the value a constructor stores for a morphism's parameter, as a
+    /// vector or a set when the morphism is to-many.
+    fn field_value(&self, morphism: &Morphism) -> String {
+        let param = to_kebab_case(&morphism.name);
+        match self.context.collection_of(morphism.id) {
+            Some(Collection::List) => format!("(vec {})", param),
+            Some(Collection::Set) => format!("(set {})", param),
+            None => param,
+        }
+    }
+
     fn write_enums(&mut self) {
         let colimits = &self.context.sketch().colimits;
         if colimits.is_empty() {
@@ -659,6 +705,20 @@ mod tests {
         assert!(result.contains(":placed-by placed-by"));
     }
 
+    #[test]
+    fn test_generate_collection_fields() {
+        let context = crate::parse_context(crate::COLLECTION_FIELDS);
+
+        let result = generate(&context).unwrap();
+
+        assert!(result.contains(":items (vec items)"));
+        assert!(result.contains(":tags (set tags)"));
+        // Each to-many field is specified as a collection of its target
+        assert!(result.contains("(s/def ::items (s/coll-of ::line-item-id))"));
+        assert!(result.contains("(s/def ::tags (s/coll-of ::tag-id :kind set?))"));
+        assert!(result.contains("(s/def ::amounts (s/coll-of ::money))"));
+    }
+
     #[test]
     fn test_generate_value_object() {
         let mut context = BoundedContext::new("Commerce");
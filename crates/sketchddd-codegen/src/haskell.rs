@@ -9,12 +9,14 @@
 //! - QuickCheck Arbitrary instances (optional)
 
 use crate::CodegenError;
+use serde::Deserialize;
 use sketchddd_core::sketch::{ColimitCocone, LimitCone, Morphism, ObjectId};
-use sketchddd_core::BoundedContext;
+use sketchddd_core::{BoundedContext, Collection};
 use std::collections::{HashMap, HashSet};
 
 /// Configuration options for Haskell code generation.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct HaskellConfig {
     /// Module name (defaults to context name in PascalCase)
     pub module_name: Option<String>,
@@ -153,6 +155,15 @@ module {} where
         self.output.push_str("import qualified Data.UUID as UUID\n");
         self.output.push_str("import qualified Data.UUID.V4 as UUID\n");
 
+        let has_sets = self
+            .context
+            .graph()
+            .morphisms()
+            .any(|m| self.context.collection_of(m.id) == Some(Collection::Set));
+        if has_sets {
+            self.output.push_str("import Data.Set (Set)\n");
+        }
+
         if self.config.derive_generic {
             self.output.push_str("import GHC.Generics (Generic)\n");
         }
@@ -165,7 +176,7 @@ module {} where
             self.output.push_str("import Test.QuickCheck (Arbitrary(..))\n");
         }
 
-        self.output.push_str("\n");
+        self.output.push('\n');
     }
 
     fn write_entities(&mut self) {
@@ -203,7 +214,7 @@ newtype {name}Id = {name}Id {{ un{name}Id :: UUID }}
             self.output.push_str("  deriving newtype (ToJSON, FromJSON)\n");
         }
 
-        self.output.push_str("\n");
+        self.output.push('\n');
 
         // Smart constructor
         self.output.push_str(&format!(
@@ -243,7 +254,7 @@ data {name} = {name}
         if let Some(morphisms) = self.object_morphisms.get(&object_id) {
             for morphism in morphisms {
                 let field_name = format!("{}{}", field_prefix, capitalize_first(&morphism.name));
-                let field_type = self.haskell_type_for_target(morphism.target);
+                let field_type = self.haskell_type_for(morphism);
 
                 if let Some(desc) = &morphism.description {
                     self.output.push_str(&format!("    -- ^ {}\n", desc));
@@ -270,7 +281,7 @@ data {name} = {name}
             ));
         }
 
-        self.output.push_str("\n");
+        self.output.push('\n');
 
         // Smart constructor
         self.write_entity_constructor(name, object_id);
@@ -287,7 +298,7 @@ create{name} :: "#
         // Type signature parameters
         if let Some(morphisms) = self.object_morphisms.get(&object_id) {
             for morphism in morphisms {
-                let field_type = self.haskell_type_for_target(morphism.target);
+                let field_type = self.haskell_type_for(morphism);
                 self.output.push_str(&format!("{} -> ", field_type));
             }
         }
@@ -355,7 +366,7 @@ data {name} = {name}
                 .iter()
                 .map(|m| {
                     let field_name = format!("{}{}", field_prefix, capitalize_first(&m.name));
-                    let field_type = self.haskell_type_for_target(m.target);
+                    let field_type = self.haskell_type_for(m);
                     format!("{} :: {}{}", field_name, bang, field_type)
                 })
                 .collect();
@@ -380,7 +391,7 @@ data {name} = {name}
             ));
         }
 
-        self.output.push_str("\n");
+        self.output.push('\n');
     }
 
     fn write_enums(&mut self) {
@@ -457,7 +468,7 @@ data {name} = {name}
             ));
         }
 
-        self.output.push_str("\n");
+        self.output.push('\n');
 
         // Pattern matching helpers
         if !is_simple_enum {
@@ -528,7 +539,7 @@ data ValidationError = ValidationError
             self.output.push_str("\ninstance ToJSON ValidationError\ninstance FromJSON ValidationError\n");
         }
 
-        self.output.push_str(&format!(
+        self.output.push_str(
             r#"
 -- | Create a validation error.
 mkValidationError :: Text -> Text -> ValidationError
@@ -545,8 +556,8 @@ validationSuccess = Right
 validationFailure :: [ValidationError] -> ValidationResult a
 validationFailure = Left
 
-"#
-        ));
+"#,
+        );
     }
 
     fn write_aggregate_validation(&mut self, limit: &LimitCone) {
@@ -606,6 +617,17 @@ validate{}OrThrow entity =
         ));
     }
 
+    /// Get the type of a morphism's field: its target, or a collection of
+    /// its target when the morphism is to-many.
+    fn haskell_type_for(&self, morphism: &Morphism) -> String {
+        let element = self.haskell_type_for_target(morphism.target);
+        match self.context.collection_of(morphism.id) {
+            Some(Collection::List) => format!("[{}]", element),
+            Some(Collection::Set) => format!("(Set {})", element),
+            None => element,
+        }
+    }
+
     fn haskell_type_for_target(&self, target: ObjectId) -> String {
         let target_name = self
             .object_names
@@ -696,6 +718,18 @@ mod tests {
         assert!(result.contains("orderPlaced_by :: !CustomerId"));
     }
 
+    #[test]
+    fn test_generate_collection_fields() {
+        let context = crate::parse_context(crate::COLLECTION_FIELDS);
+
+        let result = generate(&context).unwrap();
+
+        assert!(result.contains("import Data.Set (Set)"));
+        assert!(result.contains("orderItems :: ![LineItemId]"));
+        assert!(result.contains("orderTags :: !(Set TagId)"));
+        assert!(result.contains("pricesAmounts :: ![Money]"));
+    }
+
     #[test]
     fn test_generate_value_object() {
         let mut context = BoundedContext::new("Commerce");
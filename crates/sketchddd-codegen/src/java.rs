@@ -3,17 +3,20 @@
 //! Generates idiomatic Java code from a bounded context:
 //! - Entities as records (Java 17+) or classes with Builder pattern
 //! - Value objects as immutable records
-//! - Aggregates with validation methods
+//! - Aggregates with validation methods checking their strict invariants
 //! - Enums for sum types
+//! - Derived morphisms as computed accessors
 //! - Optional Jakarta/Javax validation annotations
 
 use crate::CodegenError;
+use serde::Deserialize;
 use sketchddd_core::sketch::{ColimitCocone, LimitCone, Morphism, ObjectId};
-use sketchddd_core::BoundedContext;
+use sketchddd_core::{BinaryOp, BoundedContext, Collection, Enforcement, Expr, FieldConstraint};
 use std::collections::{HashMap, HashSet};
 
 /// Configuration options for Java code generation.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct JavaConfig {
     /// Package name (defaults to context name in lowercase)
     pub package_name: Option<String>,
@@ -55,7 +58,8 @@ pub fn generate_with_config(
     context: &BoundedContext,
     config: &JavaConfig,
 ) -> Result<String, CodegenError> {
-    let mut gen = JavaGenerator::new(context, config);
+    let context = crate::with_constrained_fields(context);
+    let mut gen = JavaGenerator::new(&context, config);
     gen.generate()
 }
 
@@ -85,7 +89,7 @@ impl<'a> JavaGenerator<'a> {
 
         let mut object_morphisms: HashMap<ObjectId, Vec<&Morphism>> = HashMap::new();
         for morphism in context.graph().morphisms() {
-            if !morphism.is_identity {
+            if !morphism.is_identity && !context.is_derived(morphism.id) {
                 object_morphisms
                     .entry(morphism.source)
                     .or_default()
@@ -145,6 +149,7 @@ package {};
         self.output.push_str("import java.util.UUID;\n");
         self.output.push_str("import java.util.Objects;\n");
         self.output.push_str("import java.util.List;\n");
+        self.output.push_str("import java.util.Set;\n");
         self.output.push_str("import java.util.ArrayList;\n");
 
         if self.config.use_jackson {
@@ -154,7 +159,11 @@ package {};
         }
 
         if self.config.use_jakarta_validation {
+            self.output.push_str("import jakarta.validation.constraints.DecimalMax;\n");
+            self.output.push_str("import jakarta.validation.constraints.DecimalMin;\n");
             self.output.push_str("import jakarta.validation.constraints.NotNull;\n");
+            self.output.push_str("import jakarta.validation.constraints.Pattern;\n");
+            self.output.push_str("import jakarta.validation.constraints.Size;\n");
             self.output.push_str("import jakarta.validation.Valid;\n");
         }
 
@@ -164,7 +173,7 @@ package {};
             self.output.push_str("import lombok.Value;\n");
         }
 
-        self.output.push_str("\n");
+        self.output.push('\n');
     }
 
     fn write_entities(&mut self) {
@@ -313,13 +322,14 @@ public final class {name}Id {{
     }
 
     fn write_entity_record(&mut self, name: &str, object_id: ObjectId, root_note: &str) {
+        let deprecated = self.deprecated_annotation(object_id);
         self.output.push_str(&format!(
             r#"/**
  * Entity: {name}{root_note}
  *
  * An entity has a unique identity that persists through state changes.
  */
-public record {name}(
+{deprecated}public record {name}(
     {name}Id id"#
         ));
 
@@ -327,8 +337,9 @@ public record {name}(
         if let Some(morphisms) = self.object_morphisms.get(&object_id) {
             for morphism in morphisms {
                 let field_name = to_camel_case(&morphism.name);
-                let field_type = self.java_type_for_target(morphism.target);
-                self.output.push_str(&format!(",\n    {} {}", field_type, field_name));
+                let field_type = self.java_type_for(morphism);
+                let annotations = self.field_annotations(morphism);
+                self.output.push_str(&format!(",\n    {}{} {}", annotations, field_type, field_name));
             }
         }
 
@@ -343,7 +354,7 @@ public record {name}(
         if let Some(morphisms) = self.object_morphisms.get(&object_id) {
             let params: Vec<String> = morphisms
                 .iter()
-                .map(|m| format!("{} {}", self.java_type_for_target(m.target), to_camel_case(&m.name)))
+                .map(|m| format!("{} {}", self.java_type_for(m), to_camel_case(&m.name)))
                 .collect();
             self.output.push_str(&params.join(", "));
         }
@@ -359,17 +370,20 @@ public record {name}(
             }
         }
 
-        self.output.push_str("\n        );\n    }\n}\n\n");
+        self.output.push_str("\n        );\n    }\n");
+        self.output.push_str(&self.derived_methods(object_id));
+        self.output.push_str("}\n\n");
     }
 
     fn write_entity_lombok(&mut self, name: &str, object_id: ObjectId, root_note: &str) {
+        let deprecated = self.deprecated_annotation(object_id);
         self.output.push_str(&format!(
             r#"/**
  * Entity: {name}{root_note}
  *
  * An entity has a unique identity that persists through state changes.
  */
-@Data
+{deprecated}@Data
 @Builder
 public class {name} {{
     private final {name}Id id;
@@ -379,8 +393,9 @@ public class {name} {{
         if let Some(morphisms) = self.object_morphisms.get(&object_id) {
             for morphism in morphisms {
                 let field_name = to_camel_case(&morphism.name);
-                let field_type = self.java_type_for_target(morphism.target);
-                self.output.push_str(&format!("    private {} {};\n", field_type, field_name));
+                let field_type = self.java_type_for(morphism);
+                let annotations = self.field_annotations(morphism);
+                self.output.push_str(&format!("    {}private {} {};\n", annotations, field_type, field_name));
             }
         }
 
@@ -392,7 +407,7 @@ public class {name} {{
         if let Some(morphisms) = self.object_morphisms.get(&object_id) {
             let params: Vec<String> = morphisms
                 .iter()
-                .map(|m| format!("{} {}", self.java_type_for_target(m.target), to_camel_case(&m.name)))
+                .map(|m| format!("{} {}", self.java_type_for(m), to_camel_case(&m.name)))
                 .collect();
             self.output.push_str(&params.join(", "));
         }
@@ -408,17 +423,20 @@ public class {name} {{
             }
         }
 
-        self.output.push_str("            .build();\n    }\n}\n\n");
+        self.output.push_str("            .build();\n    }\n");
+        self.output.push_str(&self.derived_methods(object_id));
+        self.output.push_str("}\n\n");
     }
 
     fn write_entity_pojo(&mut self, name: &str, object_id: ObjectId, root_note: &str) {
+        let deprecated = self.deprecated_annotation(object_id);
         self.output.push_str(&format!(
             r#"/**
  * Entity: {name}{root_note}
  *
  * An entity has a unique identity that persists through state changes.
  */
-public class {name} {{
+{deprecated}public class {name} {{
     private final {name}Id id;
 "#
         ));
@@ -426,8 +444,9 @@ public class {name} {{
         if let Some(morphisms) = self.object_morphisms.get(&object_id) {
             for morphism in morphisms {
                 let field_name = to_camel_case(&morphism.name);
-                let field_type = self.java_type_for_target(morphism.target);
-                self.output.push_str(&format!("    private {} {};\n", field_type, field_name));
+                let field_type = self.java_type_for(morphism);
+                let annotations = self.field_annotations(morphism);
+                self.output.push_str(&format!("    {}private {} {};\n", annotations, field_type, field_name));
             }
         }
 
@@ -437,7 +456,7 @@ public class {name} {{
         if let Some(morphisms) = self.object_morphisms.get(&object_id) {
             for morphism in morphisms {
                 let field_name = to_camel_case(&morphism.name);
-                let field_type = self.java_type_for_target(morphism.target);
+                let field_type = self.java_type_for(morphism);
                 self.output.push_str(&format!(", {} {}", field_type, field_name));
             }
         }
@@ -460,7 +479,7 @@ public class {name} {{
         if let Some(morphisms) = self.object_morphisms.get(&object_id) {
             for morphism in morphisms {
                 let field_name = to_camel_case(&morphism.name);
-                let field_type = self.java_type_for_target(morphism.target);
+                let field_type = self.java_type_for(morphism);
                 let getter_name = format!("get{}", capitalize_first(&field_name));
                 self.output.push_str(&format!(
                     "    public {} {}() {{ return {}; }}\n",
@@ -477,7 +496,7 @@ public class {name} {{
         if let Some(morphisms) = self.object_morphisms.get(&object_id) {
             let params: Vec<String> = morphisms
                 .iter()
-                .map(|m| format!("{} {}", self.java_type_for_target(m.target), to_camel_case(&m.name)))
+                .map(|m| format!("{} {}", self.java_type_for(m), to_camel_case(&m.name)))
                 .collect();
             self.output.push_str(&params.join(", "));
         }
@@ -492,7 +511,9 @@ public class {name} {{
             }
         }
 
-        self.output.push_str(");\n    }\n}\n\n");
+        self.output.push_str(");\n    }\n");
+        self.output.push_str(&self.derived_methods(object_id));
+        self.output.push_str("}\n\n");
     }
 
     fn write_value_objects(&mut self) {
@@ -513,6 +534,7 @@ public class {name} {{
 
     fn write_value_object(&mut self, name: &str, object_id: ObjectId) {
         if self.config.use_records {
+            let deprecated = self.deprecated_annotation(object_id);
             self.output.push_str(&format!(
                 r#"/**
  * Value Object: {name}
@@ -520,7 +542,7 @@ public class {name} {{
  * A value object is defined by its attributes, not identity.
  * Two value objects with the same attributes are considered equal.
  */
-public record {name}(
+{deprecated}public record {name}(
 "#
             ));
 
@@ -528,12 +550,24 @@ public record {name}(
             if let Some(morphisms) = morphisms {
                 let fields: Vec<String> = morphisms
                     .iter()
-                    .map(|m| format!("    {} {}", self.java_type_for_target(m.target), to_camel_case(&m.name)))
+                    .map(|m| {
+                        format!(
+                            "    {}{} {}",
+                            self.field_annotations(m),
+                            self.java_type_for(m),
+                            to_camel_case(&m.name)
+                        )
+                    })
                     .collect();
                 self.output.push_str(&fields.join(",\n"));
             }
 
-            self.output.push_str("\n) {}\n\n");
+            let methods = self.derived_methods(object_id);
+            if methods.is_empty() {
+                self.output.push_str("\n) {}\n\n");
+            } else {
+                self.output.push_str(&format!("\n) {{\n{}}}\n\n", methods.trim_start_matches('\n')));
+            }
         } else {
             self.write_value_object_class(name, object_id);
         }
@@ -541,6 +575,7 @@ public record {name}(
 
     fn write_value_object_class(&mut self, name: &str, object_id: ObjectId) {
         let lombok = if self.config.use_lombok { "@Value\n" } else { "" };
+        let deprecated = self.deprecated_annotation(object_id);
 
         self.output.push_str(&format!(
             r#"/**
@@ -549,7 +584,7 @@ public record {name}(
  * A value object is defined by its attributes, not identity.
  * Two value objects with the same attributes are considered equal.
  */
-{lombok}public final class {name} {{
+{deprecated}{lombok}public final class {name} {{
 "#
         ));
 
@@ -558,8 +593,9 @@ public record {name}(
         if let Some(morphisms) = morphisms {
             for morphism in morphisms {
                 let field_name = to_camel_case(&morphism.name);
-                let field_type = self.java_type_for_target(morphism.target);
-                self.output.push_str(&format!("    private final {} {};\n", field_type, field_name));
+                let field_type = self.java_type_for(morphism);
+                let annotations = self.field_annotations(morphism);
+                self.output.push_str(&format!("    {}private final {} {};\n", annotations, field_type, field_name));
             }
         }
 
@@ -570,7 +606,7 @@ public record {name}(
             if let Some(morphisms) = morphisms {
                 let params: Vec<String> = morphisms
                     .iter()
-                    .map(|m| format!("{} {}", self.java_type_for_target(m.target), to_camel_case(&m.name)))
+                    .map(|m| format!("{} {}", self.java_type_for(m), to_camel_case(&m.name)))
                     .collect();
                 self.output.push_str(&params.join(", "));
             }
@@ -590,7 +626,7 @@ public record {name}(
             if let Some(morphisms) = morphisms {
                 for morphism in morphisms {
                     let field_name = to_camel_case(&morphism.name);
-                    let field_type = self.java_type_for_target(morphism.target);
+                    let field_type = self.java_type_for(morphism);
                     let getter_name = format!("get{}", capitalize_first(&field_name));
                     self.output.push_str(&format!(
                         "\n    public {} {}() {{ return {}; }}\n",
@@ -635,6 +671,7 @@ public record {name}(
             self.output.push_str(");\n    }\n");
         }
 
+        self.output.push_str(&self.derived_methods(object_id));
         self.output.push_str("}\n\n");
     }
 
@@ -655,6 +692,7 @@ public record {name}(
 
     fn write_enum(&mut self, colimit: &ColimitCocone) {
         let is_simple_enum = colimit.injections.iter().all(|i| i.source == colimit.apex);
+        let deprecated = self.deprecated_annotation(colimit.apex);
 
         if is_simple_enum {
             self.output.push_str(&format!(
@@ -663,7 +701,7 @@ public record {name}(
  *
  * A simple enumeration of possible values.
  */
-public enum {} {{
+{deprecated}public enum {} {{
 "#,
                 colimit.name, colimit.name
             ));
@@ -693,6 +731,7 @@ public enum {} {{
             .iter()
             .map(|i| format!("{}.{}", colimit.name, i.name))
             .collect();
+        let deprecated = self.deprecated_annotation(colimit.apex);
 
         self.output.push_str(&format!(
             r#"/**
@@ -700,7 +739,7 @@ public enum {} {{
  *
  * A sealed hierarchy representing one of several possible variants.
  */
-public sealed interface {} permits {} {{
+{deprecated}public sealed interface {} permits {} {{
 "#,
             colimit.name, colimit.name, variant_names.join(", ")
         ));
@@ -722,13 +761,14 @@ public sealed interface {} permits {} {{
     }
 
     fn write_abstract_sum_type(&mut self, colimit: &ColimitCocone) {
+        let deprecated = self.deprecated_annotation(colimit.apex);
         self.output.push_str(&format!(
             r#"/**
  * Sum Type: {}
  *
  * An abstract base class representing one of several possible variants.
  */
-public abstract class {} {{
+{deprecated}public abstract class {} {{
     private {}() {{}}  // Prevent external subclassing
 
 "#,
@@ -888,6 +928,17 @@ public abstract class ValidationResult<T> {
             .cloned()
             .unwrap_or_else(|| "Unknown".to_string());
 
+        let checks = self.invariant_checks(root_id).unwrap_or_else(|| {
+            r#"        // TODO: Add invariant validation logic based on model equations
+        //
+        // Example invariant:
+        // if (!entity.getTotalPrice().equals(calculateTotal(entity.getItems()))) {
+        //     errors.add(new ValidationError("totalPrice", "totalPrice must equal sum of item prices"));
+        // }
+"#
+            .to_string()
+        });
+
         self.output.push_str(&format!(
             r#"/**
  * Validator for {} aggregate.
@@ -899,13 +950,7 @@ public class {}Validator {{
     public static ValidationResult<{}> validate({} entity) {{
         List<ValidationError> errors = new ArrayList<>();
 
-        // TODO: Add invariant validation logic based on model equations
-        //
-        // Example invariant:
-        // if (!entity.getTotalPrice().equals(calculateTotal(entity.getItems()))) {{
-        //     errors.add(new ValidationError("totalPrice", "totalPrice must equal sum of item prices"));
-        // }}
-
+{}
         if (errors.isEmpty()) {{
             return ValidationResult.success(entity);
         }}
@@ -926,11 +971,201 @@ public class {}Validator {{
 }}
 
 "#,
-            limit.name, root_name, root_name, root_name, root_name,
+            limit.name, root_name, root_name, root_name, root_name, checks,
             root_name, root_name, root_name, root_name, root_name, root_name
         ));
     }
 
+    /// Checks for the strict invariants of an aggregate, followed by its
+    /// eventual invariants as comments, or `None` if it declares none.
+    fn invariant_checks(&self, root_id: ObjectId) -> Option<String> {
+        let invariants = self.context.invariants_of(root_id);
+        if invariants.is_empty() {
+            return None;
+        }
+
+        let mut checks = String::new();
+        let (strict, eventual): (Vec<_>, Vec<_>) = invariants
+            .into_iter()
+            .partition(|inv| inv.enforcement == Enforcement::Strict);
+
+        for invariant in strict {
+            checks.push_str(&format!(
+                "        if (!({})) {{\n            errors.add(new ValidationError({:?}, \"strict invariant violated\"));\n        }}\n",
+                self.java_expr(&invariant.expression, "entity"),
+                invariant.expression.to_string()
+            ));
+        }
+
+        if !eventual.is_empty() {
+            checks.push_str("        // Eventual invariants are restored asynchronously and not checked here:\n");
+            for invariant in eventual {
+                checks.push_str(&format!("        // - {}\n", invariant.expression));
+            }
+        }
+
+        Some(checks)
+    }
+
+    /// Annotations for a field: `@Deprecated` for scheduled removals, plus
+    /// Bean Validation annotations for its literal constraints when Jakarta
+    /// validation is enabled.
+    ///
+    /// Each annotation is followed by a space so it can prefix the declaration.
+    fn field_annotations(&self, morphism: &Morphism) -> String {
+        let mut annotations = Vec::new();
+        if self.context.removal_of(morphism.source, Some(&morphism.name)).is_some() {
+            annotations.push("@Deprecated(forRemoval = true)".to_string());
+        }
+        if !self.config.use_jakarta_validation {
+            return annotations.iter().map(|a| format!("{} ", a)).collect();
+        }
+
+        let constraints = self.context.field_constraints(morphism.source, &morphism.name);
+        let mut size = Vec::new();
+
+        for constraint in constraints {
+            match constraint {
+                FieldConstraint::Min(v) => annotations.push(format!("@DecimalMin(\"{}\")", v)),
+                FieldConstraint::Max(v) => annotations.push(format!("@DecimalMax(\"{}\")", v)),
+                FieldConstraint::MinLength(n) => size.push(format!("min = {}", n)),
+                FieldConstraint::MaxLength(n) => size.push(format!("max = {}", n)),
+                FieldConstraint::Pattern(p) => annotations.push(format!(
+                    "@Pattern(regexp = \"{}\")",
+                    p.replace('\\', "\\\\").replace('"', "\\\"")
+                )),
+            }
+        }
+        if !size.is_empty() {
+            annotations.push(format!("@Size({})", size.join(", ")));
+        }
+
+        annotations.iter().map(|a| format!("{} ", a)).collect()
+    }
+
+    /// Accessor methods computing the derived morphisms out of an object.
+    fn derived_methods(&self, object_id: ObjectId) -> String {
+        let mut methods = String::new();
+
+        for (morphism, expression) in self.context.derivations_from(object_id) {
+            let name = to_camel_case(&morphism.name);
+            let accessor = if self.config.use_records {
+                name
+            } else {
+                format!("get{}", capitalize_first(&name))
+            };
+            let deprecated = if self.context.removal_of(object_id, Some(&morphism.name)).is_some() {
+                "    @Deprecated(forRemoval = true)\n"
+            } else {
+                ""
+            };
+
+            methods.push_str(&format!(
+                r#"
+    /**
+     * Derived: computed as {{@code {}}}.
+     */
+{}    public {} {}() {{
+        return {};
+    }}
+"#,
+                expression,
+                deprecated,
+                self.java_type_for(morphism),
+                accessor,
+                self.java_expr(expression, "this")
+            ));
+        }
+
+        methods
+    }
+
+    /// Render an expression as Java over `receiver`.
+    ///
+    /// Fields of `this` are read directly, those of any other receiver
+    /// through accessors. Aggregate functions take a path whose first step
+    /// is a list field, so `sum(items.price)` sums `price` over the elements
+    /// of `items`.
+    fn java_expr(&self, expr: &Expr, receiver: &str) -> String {
+        let operand = |expr: &Expr| match expr {
+            Expr::Binary { .. } => format!("({})", self.java_expr(expr, receiver)),
+            _ => self.java_expr(expr, receiver),
+        };
+        // Fields of other objects are read through their accessors
+        let accessors = |path: &[String]| -> String {
+            path.iter()
+                .map(|p| {
+                    let name = to_camel_case(p);
+                    if self.config.use_records {
+                        format!(".{}()", name)
+                    } else {
+                        format!(".get{}()", capitalize_first(&name))
+                    }
+                })
+                .collect()
+        };
+        let field = |step: &String| -> String {
+            if receiver == "this" {
+                format!("this.{}", to_camel_case(step))
+            } else {
+                format!("{}{}", receiver, accessors(std::slice::from_ref(step)))
+            }
+        };
+
+        match expr {
+            Expr::Number(n) => n.to_string(),
+            Expr::Text(s) => format!("{:?}", s),
+            Expr::Path(p) => format!("{}{}", field(&p[0]), accessors(&p[1..])),
+            Expr::Binary { op: BinaryOp::Eq, left, right } => {
+                format!("Objects.equals({}, {})", self.java_expr(left, receiver), self.java_expr(right, receiver))
+            }
+            Expr::Binary { op: BinaryOp::Ne, left, right } => {
+                format!("!Objects.equals({}, {})", self.java_expr(left, receiver), self.java_expr(right, receiver))
+            }
+            Expr::Binary { op, left, right } => format!("{} {} {}", operand(left), op, operand(right)),
+            Expr::Unary { op, operand: inner } => format!("{}{}", op, operand(inner)),
+            Expr::Call { function, args } => match (function.as_str(), args.as_slice()) {
+                ("count", [Expr::Path(p)]) => format!("{}.size()", field(&p[0])),
+                (function, [Expr::Path(p)]) => {
+                    let values = format!(
+                        "{}.stream().mapToDouble(item -> item{})",
+                        field(&p[0]),
+                        accessors(&p[1..])
+                    );
+                    match function {
+                        "sum" => format!("{}.sum()", values),
+                        _ => format!("{}.{}().orElse(0)", values, function),
+                    }
+                }
+                (function, args) => {
+                    let args: Vec<String> = args.iter().map(|a| self.java_expr(a, receiver)).collect();
+                    format!("{}({})", function, args.join(", "))
+                }
+            },
+            Expr::Index { target, index } => format!("{}.get({})", operand(target), self.java_expr(index, receiver)),
+        }
+    }
+
+    /// A `@Deprecated` line for a type scheduled for removal.
+    fn deprecated_annotation(&self, object_id: ObjectId) -> &'static str {
+        if self.context.removal_of(object_id, None).is_some() {
+            "@Deprecated(forRemoval = true)\n"
+        } else {
+            ""
+        }
+    }
+
+    /// Get the type of a morphism's field: its target, or a collection of
+    /// its target when the morphism is to-many.
+    fn java_type_for(&self, morphism: &Morphism) -> String {
+        let element = self.java_type_for_target(morphism.target);
+        match self.context.collection_of(morphism.id) {
+            Some(Collection::List) => format!("List<{}>", element),
+            Some(Collection::Set) => format!("Set<{}>", element),
+            None => element,
+        }
+    }
+
     fn java_type_for_target(&self, target: ObjectId) -> String {
         let target_name = self
             .object_names
@@ -988,6 +1223,7 @@ fn capitalize_first(s: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use sketchddd_core::ModelVersion;
 
     #[test]
     fn test_to_camel_case() {
@@ -1038,6 +1274,17 @@ mod tests {
         assert!(result.contains("CustomerId placedBy"));
     }
 
+    #[test]
+    fn test_generate_collection_fields() {
+        let context = crate::parse_context(crate::COLLECTION_FIELDS);
+
+        let result = generate(&context).unwrap();
+
+        assert!(result.contains("List<LineItemId> items,"));
+        assert!(result.contains("Set<TagId> tags"));
+        assert!(result.contains("List<Money> amounts"));
+    }
+
     #[test]
     fn test_generate_value_object() {
         let mut context = BoundedContext::new("Commerce");
@@ -1081,6 +1328,38 @@ mod tests {
         assert!(result.contains("public static ValidationResult<Order> validate(Order entity)"));
     }
 
+    #[test]
+    fn test_generate_aggregate_invariants() {
+        let mut context = BoundedContext::new("Commerce");
+        let order = context.add_entity("Order");
+        context.define_aggregate("OrderAggregate", order);
+        context.add_aggregate_invariant(
+            order,
+            Expr::Binary {
+                op: BinaryOp::Gt,
+                left: Box::new(Expr::Call {
+                    function: "count".into(),
+                    args: vec![Expr::Path(vec!["items".into()])],
+                }),
+                right: Box::new(Expr::Number(0.0)),
+            },
+            Enforcement::Strict,
+        );
+        context.add_aggregate_invariant(
+            order,
+            Expr::Path(vec!["customer".into(), "active".into()]),
+            Enforcement::Eventual,
+        );
+
+        let result = generate(&context).unwrap();
+
+        assert!(!result.contains("// TODO: Add invariant validation logic"));
+        assert!(result.contains(
+            "        if (!(entity.items().size() > 0)) {\n            errors.add(new ValidationError(\"count(items) > 0\", \"strict invariant violated\"));\n"
+        ));
+        assert!(result.contains("        // - customer.active\n"));
+    }
+
     #[test]
     fn test_generate_sealed_sum_type() {
         let mut context = BoundedContext::new("Payments");
@@ -1119,6 +1398,112 @@ mod tests {
         assert!(result.contains("public class Customer {"));
     }
 
+    #[test]
+    fn test_bean_validation_annotations() {
+        let mut context = BoundedContext::new("Billing");
+        let money = context.add_value_object("Money");
+        let decimal = context.sketch_mut().add_object("Decimal");
+        let string = context.sketch_mut().add_object("String");
+        context.sketch_mut().graph.add_morphism("amount", money, decimal);
+        context.sketch_mut().graph.add_morphism("currency", money, string);
+        context.constrain_field(money, "amount", FieldConstraint::Min(0.0));
+        context.constrain_field(money, "currency", FieldConstraint::Pattern("\\p{Lu}{3}".into()));
+        context.constrain_field(money, "currency", FieldConstraint::MaxLength(3));
+
+        let config = JavaConfig {
+            use_jakarta_validation: true,
+            ..Default::default()
+        };
+        let result = generate_with_config(&context, &config).unwrap();
+
+        assert!(result.contains("import jakarta.validation.constraints.DecimalMin;"));
+        assert!(result.contains("    @DecimalMin(\"0\") Decimal amount"));
+        assert!(result.contains("    @Pattern(regexp = \"\\\\p{Lu}{3}\") @Size(max = 3) String currency"));
+
+        // Without Jakarta validation, no annotations are emitted
+        let plain = generate(&context).unwrap();
+        assert!(plain.contains("    Decimal amount"));
+        assert!(!plain.contains("@DecimalMin"));
+    }
+
+    #[test]
+    fn test_bean_validation_of_declared_fields() {
+        let context = crate::parse_context(crate::CONSTRAINED_FIELDS);
+        let config = JavaConfig {
+            use_jakarta_validation: true,
+            ..Default::default()
+        };
+        let result = generate_with_config(&context, &config).unwrap();
+
+        assert!(result.contains("    @DecimalMin(\"0\") Decimal amount"));
+        assert!(result.contains("    @Pattern(regexp = \"[A-Z]{3}\") String currency"));
+        assert!(result.contains("@DecimalMax(\"1000\")"));
+    }
+
+    #[test]
+    fn test_deprecation_markers() {
+        let mut context = BoundedContext::new("Billing");
+        let invoice = context.add_entity("Invoice");
+        let status = context.add_enum("LegacyStatus", vec!["Open".into()]);
+        let string = context.sketch_mut().add_object("String");
+        context.sketch_mut().graph.add_morphism("legacy_code", invoice, string);
+        context.schedule_removal(status, None, ModelVersion::new(3, 0, 0));
+        context.schedule_removal(invoice, Some("legacy_code"), ModelVersion::new(2, 1, 0));
+
+        let result = generate(&context).unwrap();
+
+        assert!(result.contains(" */\n@Deprecated(forRemoval = true)\npublic enum LegacyStatus {"));
+        assert!(result.contains("@Deprecated(forRemoval = true) String legacyCode"));
+        assert!(!result.contains("@Deprecated(forRemoval = true)\npublic record Invoice("));
+    }
+
+    #[test]
+    fn test_derived_morphisms() {
+        let mut context = BoundedContext::new("Commerce");
+        let order = context.add_entity("Order");
+        let line_item = context.add_entity("LineItem");
+        let money = context.add_value_object("Money");
+        let decimal = context.sketch_mut().add_object("Decimal");
+        context.sketch_mut().graph.add_morphism("items", order, line_item);
+        context.sketch_mut().graph.add_morphism("unit_price", line_item, decimal);
+        context.sketch_mut().graph.add_morphism("amount", money, decimal);
+        let total = context.sketch_mut().graph.add_morphism("total", order, decimal);
+        let doubled = context.sketch_mut().graph.add_morphism("doubled", money, decimal);
+        context.derive_morphism(
+            total,
+            Expr::Call {
+                function: "sum".into(),
+                args: vec![Expr::Path(vec!["items".into(), "unit_price".into()])],
+            },
+        );
+        context.derive_morphism(
+            doubled,
+            Expr::Binary {
+                op: BinaryOp::Mul,
+                left: Box::new(Expr::Path(vec!["amount".into()])),
+                right: Box::new(Expr::Number(2.0)),
+            },
+        );
+
+        let result = generate(&context).unwrap();
+
+        assert!(!result.contains("Decimal total\n") && !result.contains("Decimal total,"));
+        assert!(result.contains("     * Derived: computed as {@code sum(items.unit_price)}.\n"));
+        assert!(result.contains(
+            "    public Decimal total() {\n        return this.items.stream().mapToDouble(item -> item.unitPrice()).sum();\n    }\n}"
+        ));
+        assert!(result.contains("    Decimal amount\n) {\n    /**"));
+        assert!(result.contains("        return this.amount * 2;\n"));
+
+        let config = JavaConfig {
+            use_records: false,
+            ..Default::default()
+        };
+        let pojo = generate_with_config(&context, &config).unwrap();
+        assert!(pojo.contains("item -> item.getUnitPrice()"));
+        assert!(pojo.contains("    public Decimal getDoubled() {\n"));
+    }
+
     #[test]
     fn test_entity_references_use_id_type() {
         let mut context = BoundedContext::new("Commerce");
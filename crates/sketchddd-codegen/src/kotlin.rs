@@ -3,17 +3,20 @@
 //! Generates idiomatic Kotlin code from a bounded context:
 //! - Entities as data classes with inline value class IDs
 //! - Value objects as immutable data classes
-//! - Aggregates with validation methods
+//! - Aggregates with validation methods checking their strict invariants
 //! - Sealed classes/interfaces for sum types
+//! - Derived morphisms as computed properties
 //! - kotlinx.serialization annotations
 
 use crate::CodegenError;
+use serde::Deserialize;
 use sketchddd_core::sketch::{ColimitCocone, LimitCone, Morphism, ObjectId};
-use sketchddd_core::BoundedContext;
+use sketchddd_core::{BoundedContext, Collection, Enforcement, Expr};
 use std::collections::{HashMap, HashSet};
 
 /// Configuration options for Kotlin code generation.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct KotlinConfig {
     /// Package name (defaults to context name in lowercase)
     pub package_name: Option<String>,
@@ -79,7 +82,7 @@ impl<'a> KotlinGenerator<'a> {
 
         let mut object_morphisms: HashMap<ObjectId, Vec<&Morphism>> = HashMap::new();
         for morphism in context.graph().morphisms() {
-            if !morphism.is_identity {
+            if !morphism.is_identity && !context.is_derived(morphism.id) {
                 object_morphisms
                     .entry(morphism.source)
                     .or_default()
@@ -153,7 +156,7 @@ package {}
             self.output.push_str("import arrow.core.right\n");
         }
 
-        self.output.push_str("\n");
+        self.output.push('\n');
     }
 
     fn write_entities(&mut self) {
@@ -240,6 +243,7 @@ value class {name}Id(val value: UUID) {{
         } else {
             ""
         };
+        let deprecated = self.deprecated_annotation(object_id);
 
         self.output.push_str(&format!(
             r#"/**
@@ -247,7 +251,7 @@ value class {name}Id(val value: UUID) {{
  *
  * An entity has a unique identity that persists through state changes.
  */
-{serializable}data class {name}(
+{deprecated}{serializable}data class {name}(
     /** Unique identifier */
     val id: {name}Id,
 "#
@@ -281,22 +285,28 @@ value class {name}Id(val value: UUID) {{
             self.output.push('\n');
         }
 
-        self.output.push_str(")");
+        self.output.push(')');
+
+        let derived = self.derived_properties(object_id);
 
         // Add companion object with factory
         if self.config.generate_factories {
             self.output.push_str(" {\n");
+            if !derived.is_empty() {
+                self.output.push_str(&derived);
+                self.output.push('\n');
+            }
             self.output.push_str("    companion object {\n");
             self.output.push_str(&format!(
                 "        /** Create a new {name} with a generated ID. */\n"
             ));
-            self.output.push_str(&format!("        fun create("));
+            self.output.push_str("        fun create(");
 
             // Parameters
             if let Some(morphisms) = self.object_morphisms.get(&object_id) {
                 let params: Vec<String> = morphisms
                     .iter()
-                    .map(|m| format!("{}: {}", to_camel_case(&m.name), self.kotlin_type_for_target(m.target)))
+                    .map(|m| format!("{}: {}", to_camel_case(&m.name), self.kotlin_type_for(m)))
                     .collect();
                 self.output.push_str(&params.join(", "));
             }
@@ -314,6 +324,8 @@ value class {name}Id(val value: UUID) {{
             self.output.push_str("        )\n");
             self.output.push_str("    }\n");
             self.output.push_str("}\n\n");
+        } else if !derived.is_empty() {
+            self.output.push_str(&format!(" {{\n{}}}\n\n", derived));
         } else {
             self.output.push_str("\n\n");
         }
@@ -343,6 +355,7 @@ value class {name}Id(val value: UUID) {{
         };
 
         let limit_cone = self.context.get_value_object_limit(object_id);
+        let deprecated = self.deprecated_annotation(object_id);
 
         self.output.push_str(&format!(
             r#"/**
@@ -351,7 +364,7 @@ value class {name}Id(val value: UUID) {{
  * A value object is defined by its attributes, not identity.
  * Two value objects with the same attributes are considered equal.
  */
-{serializable}data class {name}(
+{deprecated}{serializable}data class {name}(
 "#
         ));
 
@@ -377,7 +390,12 @@ value class {name}Id(val value: UUID) {{
             }
         }
 
-        self.output.push_str(")\n\n");
+        let derived = self.derived_properties(object_id);
+        if derived.is_empty() {
+            self.output.push_str(")\n\n");
+        } else {
+            self.output.push_str(&format!(") {{\n{}}}\n\n", derived));
+        }
     }
 
     fn write_enums(&mut self) {
@@ -404,6 +422,7 @@ value class {name}Id(val value: UUID) {{
 
         // Check if this is a simple enum or a sum type with payloads
         let is_simple_enum = colimit.injections.iter().all(|i| i.source == colimit.apex);
+        let deprecated = self.deprecated_annotation(colimit.apex);
 
         if is_simple_enum {
             // Generate Kotlin enum class
@@ -413,7 +432,7 @@ value class {name}Id(val value: UUID) {{
  *
  * A simple enumeration of possible values.
  */
-{}enum class {} {{
+{deprecated}{}enum class {} {{
 "#,
                 colimit.name, serializable, colimit.name
             ));
@@ -435,7 +454,7 @@ value class {name}Id(val value: UUID) {{
  *
  * A sealed hierarchy representing one of several possible variants.
  */
-{}sealed interface {} {{
+{deprecated}{}sealed interface {} {{
 "#,
                 colimit.name, serializable, colimit.name
             ));
@@ -562,6 +581,20 @@ fun validationFailure(invariant: String, message: String): ValidationResult<Noth
             .filter_map(|p| self.object_names.get(&p.target).cloned())
             .collect();
 
+        let checks = self.invariant_checks(root_id).unwrap_or_else(|| {
+            r#"    // TODO: Add invariant validation logic based on model equations
+    //
+    // Example invariant:
+    // if (totalPrice != items.sumOf { it.price }) {
+    //     errors.add(ValidationError(
+    //         invariant = "totalPrice",
+    //         message = "totalPrice must equal sum of item prices"
+    //     ))
+    // }
+"#
+            .to_string()
+        });
+
         self.output.push_str(&format!(
             r#"/**
  * Aggregate: {}
@@ -579,16 +612,7 @@ fun validationFailure(invariant: String, message: String): ValidationResult<Noth
 fun {}.validate(): ValidationResult<{}> {{
     val errors = mutableListOf<ValidationError>()
 
-    // TODO: Add invariant validation logic based on model equations
-    //
-    // Example invariant:
-    // if (totalPrice != items.sumOf {{ it.price }}) {{
-    //     errors.add(ValidationError(
-    //         invariant = "totalPrice",
-    //         message = "totalPrice must equal sum of item prices"
-    //     ))
-    // }}
-
+{}
     return if (errors.isEmpty()) {{
         validationSuccess(this)
     }} else {{
@@ -613,23 +637,102 @@ fun {}.validateOrThrow(): {} {{
             root_name,
             root_name,
             root_name,
+            checks,
             root_name,
             root_name
         ));
     }
 
+    /// Checks for the strict invariants of an aggregate, followed by its
+    /// eventual invariants as comments, or `None` if it declares none.
+    fn invariant_checks(&self, root_id: ObjectId) -> Option<String> {
+        let invariants = self.context.invariants_of(root_id);
+        if invariants.is_empty() {
+            return None;
+        }
+
+        let mut checks = String::new();
+        let (strict, eventual): (Vec<_>, Vec<_>) = invariants
+            .into_iter()
+            .partition(|inv| inv.enforcement == Enforcement::Strict);
+
+        for invariant in strict {
+            checks.push_str(&format!(
+                "    if (!({})) {{\n        errors.add(ValidationError(\n            invariant = {:?},\n            message = \"strict invariant violated\"\n        ))\n    }}\n",
+                kotlin_expr(&invariant.expression),
+                invariant.expression.to_string()
+            ));
+        }
+
+        if !eventual.is_empty() {
+            checks.push_str("    // Eventual invariants are restored asynchronously and not checked here:\n");
+            for invariant in eventual {
+                checks.push_str(&format!("    // - {}\n", invariant.expression));
+            }
+        }
+
+        Some(checks)
+    }
+
     fn format_field(&self, morphism: &Morphism) -> String {
         let field_name = to_camel_case(&morphism.name);
-        let field_type = self.kotlin_type_for_target(morphism.target);
+        let field_type = self.kotlin_type_for(morphism);
 
         let mut result = String::new();
         if let Some(desc) = &morphism.description {
             result.push_str(&format!("    /** {} */\n", desc));
         }
+        if let Some(removal) = self.context.removal_of(morphism.source, Some(&morphism.name)) {
+            result.push_str(&format!("    @Deprecated(\"{}\")\n", removal.note()));
+        }
         result.push_str(&format!("    val {}: {}", field_name, field_type));
         result
     }
 
+    /// Computed properties for the derived morphisms out of an object.
+    fn derived_properties(&self, object_id: ObjectId) -> String {
+        let properties: Vec<String> = self
+            .context
+            .derivations_from(object_id)
+            .into_iter()
+            .map(|(morphism, expression)| {
+                let deprecated = match self.context.removal_of(object_id, Some(&morphism.name)) {
+                    Some(removal) => format!("    @Deprecated(\"{}\")\n", removal.note()),
+                    None => String::new(),
+                };
+                format!(
+                    "    /** Derived: computed as `{}`. */\n{}    val {}: {}\n        get() = {}\n",
+                    expression,
+                    deprecated,
+                    to_camel_case(&morphism.name),
+                    self.kotlin_type_for(morphism),
+                    kotlin_expr(expression)
+                )
+            })
+            .collect();
+
+        properties.join("\n")
+    }
+
+    /// A `@Deprecated` line for a type scheduled for removal.
+    fn deprecated_annotation(&self, object_id: ObjectId) -> String {
+        match self.context.removal_of(object_id, None) {
+            Some(removal) => format!("@Deprecated(\"{}\")\n", removal.note()),
+            None => String::new(),
+        }
+    }
+
+    /// Get the type of a morphism's field: its target, or a collection of
+    /// its target when the morphism is to-many.
+    fn kotlin_type_for(&self, morphism: &Morphism) -> String {
+        let element = self.kotlin_type_for_target(morphism.target);
+        match self.context.collection_of(morphism.id) {
+            Some(Collection::List) => format!("List<{}>", element),
+            Some(Collection::Set) => format!("Set<{}>", element),
+            None => element,
+        }
+    }
+
     fn kotlin_type_for_target(&self, target: ObjectId) -> String {
         let target_name = self
             .object_names
@@ -653,6 +756,38 @@ fn to_package_name(s: &str) -> String {
         .to_lowercase()
 }
 
+/// Render the expression of a derived morphism as Kotlin inside its class.
+///
+/// Aggregate functions take a path whose first step is a list property, so
+/// `sum(items.price)` sums `price` over the elements of `items`.
+fn kotlin_expr(expr: &Expr) -> String {
+    let operand = |expr: &Expr| match expr {
+        Expr::Binary { .. } => format!("({})", kotlin_expr(expr)),
+        _ => kotlin_expr(expr),
+    };
+    let path = |path: &[String]| path.iter().map(|p| to_camel_case(p)).collect::<Vec<_>>().join(".");
+
+    match expr {
+        Expr::Number(n) => n.to_string(),
+        Expr::Text(s) => format!("{:?}", s),
+        Expr::Path(p) => path(p),
+        Expr::Binary { op, left, right } => format!("{} {} {}", operand(left), op, operand(right)),
+        Expr::Unary { op, operand: inner } => format!("{}{}", op, operand(inner)),
+        Expr::Call { function, args } => match (function.as_str(), args.as_slice()) {
+            ("count", [Expr::Path(p)]) => format!("{}.size", to_camel_case(&p[0])),
+            (function, [Expr::Path(p)]) if p.len() > 1 => {
+                format!("{}.{}Of {{ it.{} }}", to_camel_case(&p[0]), function, path(&p[1..]))
+            }
+            (function, [Expr::Path(p)]) => format!("{}.{}()", to_camel_case(&p[0]), function),
+            (function, args) => {
+                let args: Vec<String> = args.iter().map(kotlin_expr).collect();
+                format!("{}({})", function, args.join(", "))
+            }
+        },
+        Expr::Index { target, index } => format!("{}[{}]", operand(target), kotlin_expr(index)),
+    }
+}
+
 /// Convert PascalCase or snake_case to camelCase.
 fn to_camel_case(s: &str) -> String {
     let mut result = String::with_capacity(s.len());
@@ -678,6 +813,7 @@ fn to_camel_case(s: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use sketchddd_core::{BinaryOp, ModelVersion};
 
     #[test]
     fn test_to_camel_case() {
@@ -736,6 +872,17 @@ mod tests {
         assert!(result.contains("val placedBy: CustomerId"));
     }
 
+    #[test]
+    fn test_generate_collection_fields() {
+        let context = crate::parse_context(crate::COLLECTION_FIELDS);
+
+        let result = generate(&context).unwrap();
+
+        assert!(result.contains("val items: List<LineItemId>,"));
+        assert!(result.contains("val tags: Set<TagId>"));
+        assert!(result.contains("val amounts: List<Money>"));
+    }
+
     #[test]
     fn test_generate_value_object() {
         let mut context = BoundedContext::new("Commerce");
@@ -747,6 +894,59 @@ mod tests {
         assert!(result.contains("data class Money("));
     }
 
+    #[test]
+    fn test_generate_deprecation_markers() {
+        let mut context = BoundedContext::new("Billing");
+        let money = context.add_value_object("Money");
+        let string = context.sketch_mut().add_object("String");
+        context.sketch_mut().graph.add_morphism("legacy_code", money, string);
+        context.schedule_removal(money, None, ModelVersion::new(3, 0, 0));
+        context.schedule_removal(money, Some("legacy_code"), ModelVersion::new(2, 1, 0));
+
+        let result = generate(&context).unwrap();
+
+        assert!(result.contains(" */\n@Deprecated(\"Removed in 3.0\")\n@Serializable\ndata class Money("));
+        assert!(result.contains("    @Deprecated(\"Removed in 2.1\")\n    val legacyCode: String"));
+    }
+
+    #[test]
+    fn test_generate_derived_morphisms() {
+        let mut context = BoundedContext::new("Commerce");
+        let order = context.add_entity("Order");
+        let line_item = context.add_entity("LineItem");
+        let money = context.add_value_object("Money");
+        let decimal = context.sketch_mut().add_object("Decimal");
+        context.sketch_mut().graph.add_morphism("items", order, line_item);
+        context.sketch_mut().graph.add_morphism("unit_price", line_item, decimal);
+        context.sketch_mut().graph.add_morphism("amount", money, decimal);
+        let total = context.sketch_mut().graph.add_morphism("total", order, decimal);
+        let count = context.sketch_mut().graph.add_morphism("item_count", order, decimal);
+        let negated = context.sketch_mut().graph.add_morphism("negated", money, decimal);
+        let call = |path: Vec<String>, function: &str| Expr::Call {
+            function: function.into(),
+            args: vec![Expr::Path(path)],
+        };
+        context.derive_morphism(total, call(vec!["items".into(), "unit_price".into()], "sum"));
+        context.derive_morphism(count, call(vec!["items".into()], "count"));
+        context.derive_morphism(
+            negated,
+            Expr::Unary {
+                op: sketchddd_core::UnaryOp::Neg,
+                operand: Box::new(Expr::Path(vec!["amount".into()])),
+            },
+        );
+
+        let result = generate(&context).unwrap();
+
+        assert!(!result.contains("val total: Decimal,"));
+        assert!(result.contains(
+            ") {\n    /** Derived: computed as `sum(items.unit_price)`. */\n    val total: Decimal\n        get() = items.sumOf { it.unitPrice }\n"
+        ));
+        assert!(result.contains("        get() = items.size\n\n    companion object {"));
+        assert!(result.contains("    val amount: Decimal\n) {\n"));
+        assert!(result.contains("        get() = -amount\n}"));
+    }
+
     #[test]
     fn test_generate_simple_enum() {
         let mut context = BoundedContext::new("Commerce");
@@ -779,6 +979,38 @@ mod tests {
         assert!(result.contains("fun Order.validateOrThrow(): Order"));
     }
 
+    #[test]
+    fn test_generate_aggregate_invariants() {
+        let mut context = BoundedContext::new("Commerce");
+        let order = context.add_entity("Order");
+        context.define_aggregate("OrderAggregate", order);
+        context.add_aggregate_invariant(
+            order,
+            Expr::Binary {
+                op: BinaryOp::Gt,
+                left: Box::new(Expr::Call {
+                    function: "count".into(),
+                    args: vec![Expr::Path(vec!["items".into()])],
+                }),
+                right: Box::new(Expr::Number(0.0)),
+            },
+            Enforcement::Strict,
+        );
+        context.add_aggregate_invariant(
+            order,
+            Expr::Path(vec!["customer".into(), "active".into()]),
+            Enforcement::Eventual,
+        );
+
+        let result = generate(&context).unwrap();
+
+        assert!(!result.contains("// TODO: Add invariant validation logic"));
+        assert!(result.contains(
+            "    if (!(items.size > 0)) {\n        errors.add(ValidationError(\n            invariant = \"count(items) > 0\",\n"
+        ));
+        assert!(result.contains("    // - customer.active\n"));
+    }
+
     #[test]
     fn test_generate_sum_type() {
         let mut context = BoundedContext::new("Payments");
@@ -25,6 +25,10 @@
 //! let rust_code = generate(&context, Target::Rust).unwrap();
 //! println!("{}", rust_code);
 //! ```
+//!
+//! Each target also has a configuration, such as [`RustConfig`], taken by
+//! its module's `generate_with_config`. Configurations can be read from
+//! JSON or TOML, where options left out keep their defaults.
 
 pub mod clojure;
 pub mod haskell;
@@ -42,7 +46,8 @@ pub use python::PythonConfig;
 pub use rust::RustConfig;
 pub use typescript::TypeScriptConfig;
 
-use sketchddd_core::BoundedContext;
+use sketchddd_core::{BoundedContext, Workspace};
+use std::borrow::Cow;
 use thiserror::Error;
 
 /// Error during code generation.
@@ -59,7 +64,7 @@ pub enum CodegenError {
 }
 
 /// Target language for code generation.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Target {
     Rust,
     TypeScript,
@@ -87,6 +92,109 @@ impl std::str::FromStr for Target {
     }
 }
 
+impl Target {
+    /// Get the extension of the files generated code is written to.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Target::Rust => "rs",
+            Target::TypeScript => "ts",
+            Target::Kotlin => "kt",
+            Target::Python => "py",
+            Target::Java => "java",
+            Target::Clojure => "clj",
+            Target::Haskell => "hs",
+        }
+    }
+}
+
+/// Give each constrained field declared on an object a morphism to its
+/// declared type, so that generators render the field and its constraints
+/// like those of a morphism.
+///
+/// Fields of entities and value objects, such as `amount: Decimal [min: 0]`,
+/// have no morphisms of their own; their constraints record their types.
+pub(crate) fn with_constrained_fields(context: &BoundedContext) -> Cow<'_, BoundedContext> {
+    let missing: Vec<_> = context
+        .constrained_fields()
+        .iter()
+        .filter_map(|field| Some((field, field.type_name.as_deref()?)))
+        .filter(|(field, _)| {
+            !context
+                .graph()
+                .morphisms()
+                .any(|m| m.source == field.object && *m.name == *field.field)
+        })
+        .collect();
+    if missing.is_empty() {
+        return Cow::Borrowed(context);
+    }
+
+    let mut fielded = context.clone();
+    let graph = &mut fielded.sketch_mut().graph;
+    for (field, type_name) in missing {
+        let target = match graph.find_object_by_name(type_name) {
+            Some(object) => object.id,
+            None => graph.add_object(type_name),
+        };
+        graph.add_morphism(&field.field, field.object, target);
+    }
+    Cow::Owned(fielded)
+}
+
+/// Parse a source holding a single bounded context, for tests.
+#[cfg(test)]
+pub(crate) fn parse_context(source: &str) -> BoundedContext {
+    let file = sketchddd_parser::parse_file(source).unwrap();
+    sketchddd_parser::transform(&file).unwrap().contexts.remove(0)
+}
+
+/// Source declaring constraints on fields of a value object and an entity.
+#[cfg(test)]
+pub(crate) const CONSTRAINED_FIELDS: &str = r#"
+context Billing {
+    value Money {
+        amount: Decimal [min: 0]
+        currency: String [pattern: "[A-Z]{3}"]
+    }
+    entity Account {
+        balance: Decimal [max: 1000]
+    }
+}
+"#;
+
+/// Source declaring to-many morphisms, to a list or a set of entities or
+/// value objects.
+#[cfg(test)]
+pub(crate) const COLLECTION_FIELDS: &str = r#"
+context Commerce {
+    entity Order
+    entity LineItem
+    entity Tag
+    value Money {}
+    value Prices {}
+    morphisms {
+        items: Order -> List<LineItem>
+        tags: Order -> Set<Tag>
+        amounts: Prices -> List<Money>
+    }
+}
+"#;
+
+/// Generate code for every bounded context of a workspace.
+///
+/// Returns the name of each context with its generated code, in
+/// declaration order.
+pub fn generate_workspace(
+    workspace: &Workspace,
+    target: Target,
+) -> Result<Vec<(String, String)>, CodegenError> {
+    workspace
+        .contexts
+        .iter()
+        .map(|context| Ok((context.name().to_string(), generate(context, target)?)))
+        .collect()
+}
+
 /// Generate code from a bounded context.
 pub fn generate(context: &BoundedContext, target: Target) -> Result<String, CodegenError> {
     match target {
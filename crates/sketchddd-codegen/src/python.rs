@@ -3,17 +3,20 @@
 //! Generates idiomatic Python code from a bounded context:
 //! - Entities as dataclasses with UUID IDs
 //! - Value objects as frozen dataclasses
-//! - Aggregates with validation methods
+//! - Aggregates with validation functions checking their strict invariants
 //! - Enums using Python's enum module
+//! - Derived morphisms as computed properties
 //! - Pydantic models for validation (optional)
 
 use crate::CodegenError;
+use serde::Deserialize;
 use sketchddd_core::sketch::{ColimitCocone, LimitCone, Morphism, ObjectId};
-use sketchddd_core::BoundedContext;
+use sketchddd_core::{BoundedContext, Collection, Enforcement, Expr, FieldConstraint, UnaryOp};
 use std::collections::{HashMap, HashSet};
 
 /// Configuration options for Python code generation.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct PythonConfig {
     /// Whether to use Pydantic models instead of dataclasses
     pub use_pydantic: bool,
@@ -49,7 +52,8 @@ pub fn generate_with_config(
     context: &BoundedContext,
     config: &PythonConfig,
 ) -> Result<String, CodegenError> {
-    let mut gen = PythonGenerator::new(context, config);
+    let context = crate::with_constrained_fields(context);
+    let mut gen = PythonGenerator::new(&context, config);
     gen.generate()
 }
 
@@ -79,7 +83,7 @@ impl<'a> PythonGenerator<'a> {
 
         let mut object_morphisms: HashMap<ObjectId, Vec<&Morphism>> = HashMap::new();
         for morphism in context.graph().morphisms() {
-            if !morphism.is_identity {
+            if !morphism.is_identity && !context.is_derived(morphism.id) {
                 object_morphisms
                     .entry(morphism.source)
                     .or_default()
@@ -136,11 +140,21 @@ from __future__ import annotations
     }
 
     fn write_imports(&mut self) {
+        let uses_patterns = self
+            .context
+            .constrained_fields()
+            .iter()
+            .flat_map(|c| &c.constraints)
+            .any(|c| matches!(c, FieldConstraint::Pattern(_)));
+        if self.config.use_pydantic && uses_patterns {
+            self.output.push_str("import re\n");
+        }
+
         self.output.push_str("from dataclasses import dataclass, field\n");
         self.output.push_str("from enum import Enum, auto\n");
 
         if self.config.use_type_hints {
-            self.output.push_str("from typing import List, Optional, NewType, Union\n");
+            self.output.push_str("from typing import List, Optional, NewType, Set, Union\n");
         }
 
         self.output.push_str("from uuid import UUID, uuid4\n");
@@ -218,6 +232,7 @@ def create_{}_id() -> {}Id:
     }
 
     fn write_dataclass_entity(&mut self, name: &str, object_id: ObjectId, root_note: &str) {
+        let deprecated = self.deprecated_docstring(object_id);
         self.output.push_str(&format!(
             r#"@dataclass
 class {name}:
@@ -225,7 +240,7 @@ class {name}:
     Entity: {name}{root_note}
 
     An entity has a unique identity that persists through state changes.
-    """
+{deprecated}    """
 
     id: {name}Id = field(default_factory=create_{snake_name}_id)
 "#,
@@ -236,33 +251,36 @@ class {name}:
         if let Some(morphisms) = self.object_morphisms.get(&object_id) {
             for morphism in morphisms {
                 let field_name = to_snake_case(&morphism.name);
-                let field_type = self.python_type_for_target(morphism.target);
+                let field_type = self.python_type_for(morphism);
 
                 if let Some(desc) = &morphism.description {
                     self.output.push_str(&format!("    # {}\n", desc));
                 }
+                self.output.push_str(&self.deprecated_comment(morphism));
                 self.output.push_str(&format!("    {}: {}\n", field_name, field_type));
             }
         }
 
-        self.output.push_str("\n");
+        self.write_derived_properties(object_id);
+        self.output.push('\n');
 
         // Factory method
         if self.config.generate_factories {
             self.write_entity_factory(name, object_id);
         }
 
-        self.output.push_str("\n");
+        self.output.push('\n');
     }
 
     fn write_pydantic_entity(&mut self, name: &str, object_id: ObjectId, root_note: &str) {
+        let deprecated = self.deprecated_docstring(object_id);
         self.output.push_str(&format!(
             r#"class {name}(BaseModel):
     """
     Entity: {name}{root_note}
 
     An entity has a unique identity that persists through state changes.
-    """
+{deprecated}    """
 
     id: {name}Id = Field(default_factory=lambda: {name}Id(uuid4()))
 "#
@@ -272,9 +290,10 @@ class {name}:
         if let Some(morphisms) = self.object_morphisms.get(&object_id) {
             for morphism in morphisms {
                 let field_name = to_snake_case(&morphism.name);
-                let field_type = self.python_type_for_target(morphism.target);
+                let field_type = self.python_type_for(morphism);
                 let desc = morphism.description.as_deref().unwrap_or("");
 
+                self.output.push_str(&self.deprecated_comment(morphism));
                 self.output.push_str(&format!(
                     "    {}: {} = Field(..., description=\"{}\")\n",
                     field_name, field_type, desc
@@ -282,6 +301,9 @@ class {name}:
             }
         }
 
+        self.write_pydantic_validators(object_id);
+        self.write_derived_properties(object_id);
+
         self.output.push_str("\n    class Config:\n");
         self.output.push_str("        frozen = False  # Entities are mutable\n");
         self.output.push_str("\n\n");
@@ -296,7 +318,7 @@ class {name}:
         if let Some(morphisms) = self.object_morphisms.get(&object_id) {
             let params: Vec<String> = morphisms
                 .iter()
-                .map(|m| format!("{}: {}", to_snake_case(&m.name), self.python_type_for_target(m.target)))
+                .map(|m| format!("{}: {}", to_snake_case(&m.name), self.python_type_for(m)))
                 .collect();
             self.output.push_str(&params.join(", "));
         }
@@ -334,6 +356,7 @@ class {name}:
 
     fn write_value_object(&mut self, name: &str, object_id: ObjectId) {
         let limit_cone = self.context.get_value_object_limit(object_id);
+        let deprecated = self.deprecated_docstring(object_id);
 
         if self.config.use_pydantic {
             self.output.push_str(&format!(
@@ -343,7 +366,7 @@ class {name}:
 
     A value object is defined by its attributes, not identity.
     Two value objects with the same attributes are considered equal.
-    """
+{deprecated}    """
 
 "#
             ));
@@ -353,14 +376,15 @@ class {name}:
             if let Some(morphisms) = morphisms {
                 for morphism in morphisms {
                     let field_name = to_snake_case(&morphism.name);
-                    let field_type = self.python_type_for_target(morphism.target);
+                    let field_type = self.python_type_for(morphism);
+                    self.output.push_str(&self.deprecated_comment(morphism));
                     self.output.push_str(&format!("    {}: {}\n", field_name, field_type));
                 }
             } else if let Some(cone) = limit_cone {
                 for proj in &cone.projections {
                     if let Some(m) = self.context.graph().get_morphism(proj.morphism) {
                         let field_name = to_snake_case(&m.name);
-                        let field_type = self.python_type_for_target(m.target);
+                        let field_type = self.python_type_for(m);
                         self.output.push_str(&format!("    {}: {}\n", field_name, field_type));
                     }
                 }
@@ -368,6 +392,9 @@ class {name}:
                 self.output.push_str("    pass\n");
             }
 
+            self.write_pydantic_validators(object_id);
+            self.write_derived_properties(object_id);
+
             self.output.push_str("\n    class Config:\n");
             self.output.push_str("        frozen = True  # Value objects are immutable\n");
         } else {
@@ -379,7 +406,7 @@ class {name}:
 
     A value object is defined by its attributes, not identity.
     Two value objects with the same attributes are considered equal.
-    """
+{deprecated}    """
 
 "#
             ));
@@ -389,20 +416,23 @@ class {name}:
             if let Some(morphisms) = morphisms {
                 for morphism in morphisms {
                     let field_name = to_snake_case(&morphism.name);
-                    let field_type = self.python_type_for_target(morphism.target);
+                    let field_type = self.python_type_for(morphism);
+                    self.output.push_str(&self.deprecated_comment(morphism));
                     self.output.push_str(&format!("    {}: {}\n", field_name, field_type));
                 }
             } else if let Some(cone) = limit_cone {
                 for proj in &cone.projections {
                     if let Some(m) = self.context.graph().get_morphism(proj.morphism) {
                         let field_name = to_snake_case(&m.name);
-                        let field_type = self.python_type_for_target(m.target);
+                        let field_type = self.python_type_for(m);
                         self.output.push_str(&format!("    {}: {}\n", field_name, field_type));
                     }
                 }
             } else {
                 self.output.push_str("    pass\n");
             }
+
+            self.write_derived_properties(object_id);
         }
 
         self.output.push_str("\n\n");
@@ -426,6 +456,7 @@ class {name}:
     fn write_enum(&mut self, colimit: &ColimitCocone) {
         // Check if this is a simple enum or a sum type with payloads
         let is_simple_enum = colimit.injections.iter().all(|i| i.source == colimit.apex);
+        let deprecated = self.deprecated_docstring(colimit.apex);
 
         if is_simple_enum {
             self.output.push_str(&format!(
@@ -434,7 +465,7 @@ class {name}:
     Enumeration: {}
 
     A simple enumeration of possible values.
-    """
+{deprecated}    """
 
 "#,
                 colimit.name, colimit.name
@@ -597,6 +628,18 @@ class ValidationResult:
             .unwrap_or_else(|| "Unknown".to_string());
 
         let snake_name = to_snake_case(&root_name);
+        let checks = self.invariant_checks(root_id).unwrap_or_else(|| {
+            r#"    # TODO: Add invariant validation logic based on model equations
+    #
+    # Example invariant:
+    # if entity.total_price != sum(item.price for item in entity.items):
+    #     errors.append(ValidationError(
+    #         invariant="total_price",
+    #         message="total_price must equal sum of item prices"
+    #     ))
+"#
+            .to_string()
+        });
 
         self.output.push_str(&format!(
             r#"def validate_{snake_name}(entity: {root_name}) -> ValidationResult:
@@ -608,15 +651,7 @@ class ValidationResult:
     """
     errors: List[ValidationError] = []
 
-    # TODO: Add invariant validation logic based on model equations
-    #
-    # Example invariant:
-    # if entity.total_price != sum(item.price for item in entity.items):
-    #     errors.append(ValidationError(
-    #         invariant="total_price",
-    #         message="total_price must equal sum of item prices"
-    #     ))
-
+{checks}
     if errors:
         return ValidationResult.fail(errors)
 
@@ -636,6 +671,123 @@ def validate_{snake_name}_or_raise(entity: {root_name}) -> {root_name}:
         ));
     }
 
+    /// Checks for the strict invariants of an aggregate, followed by its
+    /// eventual invariants as comments, or `None` if it declares none.
+    fn invariant_checks(&self, root_id: ObjectId) -> Option<String> {
+        let invariants = self.context.invariants_of(root_id);
+        if invariants.is_empty() {
+            return None;
+        }
+
+        let mut checks = String::new();
+        let (strict, eventual): (Vec<_>, Vec<_>) = invariants
+            .into_iter()
+            .partition(|inv| inv.enforcement == Enforcement::Strict);
+
+        for invariant in strict {
+            checks.push_str(&format!(
+                "    if not ({}):\n        errors.append(ValidationError(\n            invariant={:?},\n            message=\"strict invariant violated\"\n        ))\n",
+                python_expr(&invariant.expression, "entity"),
+                invariant.expression.to_string()
+            ));
+        }
+
+        if !eventual.is_empty() {
+            checks.push_str("    # Eventual invariants are restored asynchronously and not checked here:\n");
+            for invariant in eventual {
+                checks.push_str(&format!("    # - {}\n", invariant.expression));
+            }
+        }
+
+        Some(checks)
+    }
+
+    /// Write a Pydantic validator for each constrained field of an object.
+    fn write_pydantic_validators(&mut self, object_id: ObjectId) {
+        let context = self.context;
+        let morphisms = self.object_morphisms.get(&object_id).cloned().unwrap_or_default();
+
+        for morphism in morphisms {
+            let constraints = context.field_constraints(object_id, &morphism.name);
+            if constraints.is_empty() {
+                continue;
+            }
+
+            let field_name = to_snake_case(&morphism.name);
+            self.output.push_str(&format!(
+                "\n    @validator(\"{field_name}\")\n    def check_{field_name}(cls, value):\n"
+            ));
+
+            for constraint in constraints {
+                let (condition, requirement) = match constraint {
+                    FieldConstraint::Min(v) => (format!("value < {}", v), format!(">= {}", v)),
+                    FieldConstraint::Max(v) => (format!("value > {}", v), format!("<= {}", v)),
+                    FieldConstraint::MinLength(n) => {
+                        (format!("len(value) < {}", n), format!("have at least {} characters", n))
+                    }
+                    FieldConstraint::MaxLength(n) => {
+                        (format!("len(value) > {}", n), format!("have at most {} characters", n))
+                    }
+                    FieldConstraint::Pattern(p) => (
+                        format!("not re.fullmatch({}, value)", python_string(p)),
+                        "match the required pattern".to_string(),
+                    ),
+                };
+                let verb = if constraint.is_numeric() { "be " } else { "" };
+                self.output.push_str(&format!(
+                    "        if {condition}:\n            raise ValueError(\"{field_name} must {verb}{requirement}\")\n"
+                ));
+            }
+
+            self.output.push_str("        return value\n");
+        }
+    }
+
+    /// Write a computed property for each derived morphism out of an object.
+    fn write_derived_properties(&mut self, object_id: ObjectId) {
+        for (morphism, expression) in self.context.derivations_from(object_id) {
+            let deprecated = match self.context.removal_of(object_id, Some(&morphism.name)) {
+                Some(removal) => format!(" Deprecated: {}.", removal.note()),
+                None => String::new(),
+            };
+            self.output.push_str(&format!(
+                "\n    @property\n    def {}(self) -> {}:\n        \"\"\"Derived: computed as `{}`.{}\"\"\"\n        return {}\n",
+                to_snake_case(&morphism.name),
+                self.python_type_for(morphism),
+                expression,
+                deprecated,
+                python_expr(expression, "self")
+            ));
+        }
+    }
+
+    /// A docstring paragraph for a type scheduled for removal.
+    fn deprecated_docstring(&self, object_id: ObjectId) -> String {
+        match self.context.removal_of(object_id, None) {
+            Some(removal) => format!("\n    Deprecated: {}.\n", removal.note()),
+            None => String::new(),
+        }
+    }
+
+    /// A comment line for a field scheduled for removal.
+    fn deprecated_comment(&self, morphism: &Morphism) -> String {
+        match self.context.removal_of(morphism.source, Some(&morphism.name)) {
+            Some(removal) => format!("    # Deprecated: {}\n", removal.note()),
+            None => String::new(),
+        }
+    }
+
+    /// Get the type of a morphism's field: its target, or a collection of
+    /// its target when the morphism is to-many.
+    fn python_type_for(&self, morphism: &Morphism) -> String {
+        let element = self.python_type_for_target(morphism.target);
+        match self.context.collection_of(morphism.id) {
+            Some(Collection::List) => format!("List[{}]", element),
+            Some(Collection::Set) => format!("Set[{}]", element),
+            None => element,
+        }
+    }
+
     fn python_type_for_target(&self, target: ObjectId) -> String {
         let target_name = self
             .object_names
@@ -651,6 +803,60 @@ def validate_{snake_name}_or_raise(entity: {root_name}) -> {root_name}:
     }
 }
 
+/// Quote a string as a Python string literal.
+fn python_string(s: &str) -> String {
+    let mut literal = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '\\' => literal.push_str("\\\\"),
+            '"' => literal.push_str("\\\""),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            c if c.is_control() => literal.push_str(&format!("\\u{:04x}", c as u32)),
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+/// Render an expression as Python over `receiver`.
+///
+/// Aggregate functions take a path whose first step is a list field, so
+/// `sum(items.price)` sums `price` over the elements of `receiver.items`.
+fn python_expr(expr: &Expr, receiver: &str) -> String {
+    let operand = |expr: &Expr| match expr {
+        Expr::Binary { .. } => format!("({})", python_expr(expr, receiver)),
+        _ => python_expr(expr, receiver),
+    };
+    let path = |path: &[String]| path.iter().map(|p| to_snake_case(p)).collect::<Vec<_>>().join(".");
+
+    match expr {
+        Expr::Number(n) => n.to_string(),
+        Expr::Text(s) => format!("{:?}", s),
+        Expr::Path(p) => format!("{}.{}", receiver, path(p)),
+        Expr::Binary { op, left, right } => format!("{} {} {}", operand(left), op, operand(right)),
+        Expr::Unary { op: UnaryOp::Not, operand: inner } => format!("not {}", operand(inner)),
+        Expr::Unary { op, operand: inner } => format!("{}{}", op, operand(inner)),
+        Expr::Call { function, args } => match (function.as_str(), args.as_slice()) {
+            ("count", [Expr::Path(p)]) => format!("len({}.{})", receiver, to_snake_case(&p[0])),
+            (function, [Expr::Path(p)]) if p.len() > 1 => format!(
+                "{}(item.{} for item in {}.{})",
+                function,
+                path(&p[1..]),
+                receiver,
+                to_snake_case(&p[0])
+            ),
+            (function, args) => {
+                let args: Vec<String> = args.iter().map(|a| python_expr(a, receiver)).collect();
+                format!("{}({})", function, args.join(", "))
+            }
+        },
+        Expr::Index { target, index } => format!("{}[{}]", operand(target), python_expr(index, receiver)),
+    }
+}
+
 /// Convert PascalCase to snake_case.
 fn to_snake_case(s: &str) -> String {
     let mut result = String::with_capacity(s.len() + 4);
@@ -675,6 +881,7 @@ fn to_screaming_snake_case(s: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use sketchddd_core::ModelVersion;
 
     #[test]
     fn test_to_snake_case() {
@@ -733,6 +940,17 @@ mod tests {
         assert!(result.contains("placed_by: CustomerId"));
     }
 
+    #[test]
+    fn test_generate_collection_fields() {
+        let context = crate::parse_context(crate::COLLECTION_FIELDS);
+
+        let result = generate(&context).unwrap();
+
+        assert!(result.contains("items: List[LineItemId]"));
+        assert!(result.contains("tags: Set[TagId]"));
+        assert!(result.contains("amounts: List[Money]"));
+    }
+
     #[test]
     fn test_generate_value_object() {
         let mut context = BoundedContext::new("Commerce");
@@ -777,6 +995,38 @@ mod tests {
         assert!(result.contains("def validate_order_or_raise(entity: Order) -> Order:"));
     }
 
+    #[test]
+    fn test_generate_aggregate_invariants() {
+        let mut context = BoundedContext::new("Commerce");
+        let order = context.add_entity("Order");
+        context.define_aggregate("OrderAggregate", order);
+        context.add_aggregate_invariant(
+            order,
+            Expr::Binary {
+                op: sketchddd_core::BinaryOp::Gt,
+                left: Box::new(Expr::Call {
+                    function: "count".into(),
+                    args: vec![Expr::Path(vec!["items".into()])],
+                }),
+                right: Box::new(Expr::Number(0.0)),
+            },
+            Enforcement::Strict,
+        );
+        context.add_aggregate_invariant(
+            order,
+            Expr::Path(vec!["customer".into(), "active".into()]),
+            Enforcement::Eventual,
+        );
+
+        let result = generate(&context).unwrap();
+
+        assert!(!result.contains("# TODO: Add invariant validation logic"));
+        assert!(result.contains(
+            "    if not (len(entity.items) > 0):\n        errors.append(ValidationError(\n            invariant=\"count(items) > 0\",\n"
+        ));
+        assert!(result.contains("    # - customer.active\n"));
+    }
+
     #[test]
     fn test_generate_sum_type() {
         let mut context = BoundedContext::new("Payments");
@@ -818,6 +1068,125 @@ mod tests {
         assert!(result.contains("class Config:"));
     }
 
+    #[test]
+    fn test_pydantic_field_validators() {
+        let mut context = BoundedContext::new("Billing");
+        let money = context.add_value_object("Money");
+        let decimal = context.sketch_mut().add_object("Decimal");
+        let string = context.sketch_mut().add_object("String");
+        context.sketch_mut().graph.add_morphism("amount", money, decimal);
+        context.sketch_mut().graph.add_morphism("currency", money, string);
+        context.constrain_field(money, "amount", FieldConstraint::Min(0.0));
+        context.constrain_field(money, "currency", FieldConstraint::Pattern("[A-Z]{3}".into()));
+
+        let config = PythonConfig {
+            use_pydantic: true,
+            ..Default::default()
+        };
+        let result = generate_with_config(&context, &config).unwrap();
+
+        assert!(result.contains("import re\n"));
+        assert!(result.contains("    @validator(\"amount\")\n    def check_amount(cls, value):"));
+        assert!(result.contains("        if value < 0:\n            raise ValueError(\"amount must be >= 0\")"));
+        assert!(result.contains("        if not re.fullmatch(\"[A-Z]{3}\", value):"));
+
+        // Dataclasses are left unchanged
+        let plain = generate(&context).unwrap();
+        assert!(!plain.contains("@validator"));
+    }
+
+    #[test]
+    fn test_pydantic_validators_of_declared_fields() {
+        let context = crate::parse_context(crate::CONSTRAINED_FIELDS);
+        let config = PythonConfig {
+            use_pydantic: true,
+            ..Default::default()
+        };
+        let result = generate_with_config(&context, &config).unwrap();
+
+        assert!(result.contains("    @validator(\"amount\")\n    def check_amount(cls, value):"));
+        assert!(result.contains("        if not re.fullmatch(\"[A-Z]{3}\", value):"));
+        assert!(result.contains("    @validator(\"balance\")\n    def check_balance(cls, value):"));
+    }
+
+    #[test]
+    fn test_pydantic_patterns_are_escaped() {
+        let mut context = BoundedContext::new("Billing");
+        let money = context.add_value_object("Money");
+        let string = context.sketch_mut().add_object("String");
+        context
+            .sketch_mut()
+            .graph
+            .add_morphism("code", money, string);
+        context
+            .sketch_mut()
+            .graph
+            .add_morphism("path", money, string);
+        context.constrain_field(
+            money,
+            "code",
+            FieldConstraint::Pattern(r#"\d+"[a-z]"#.into()),
+        );
+        context.constrain_field(money, "path", FieldConstraint::Pattern(r"C:\\".into()));
+
+        let config = PythonConfig {
+            use_pydantic: true,
+            ..Default::default()
+        };
+        let result = generate_with_config(&context, &config).unwrap();
+        assert!(result.contains(r#"        if not re.fullmatch("\\d+\"[a-z]", value):"#));
+        assert!(result.contains(r#"        if not re.fullmatch("C:\\\\", value):"#));
+    }
+
+    #[test]
+    fn test_deprecation_markers() {
+        let mut context = BoundedContext::new("Billing");
+        let invoice = context.add_entity("Invoice");
+        let string = context.sketch_mut().add_object("String");
+        context.sketch_mut().graph.add_morphism("legacyCode", invoice, string);
+        context.schedule_removal(invoice, None, ModelVersion::new(3, 0, 0));
+        context.schedule_removal(invoice, Some("legacyCode"), ModelVersion::new(2, 1, 0));
+
+        let result = generate(&context).unwrap();
+
+        assert!(result.contains("state changes.\n\n    Deprecated: Removed in 3.0.\n    \"\"\""));
+        assert!(result.contains("    # Deprecated: Removed in 2.1\n    legacy_code: String"));
+    }
+
+    #[test]
+    fn test_derived_morphisms() {
+        let mut context = BoundedContext::new("Commerce");
+        let order = context.add_entity("Order");
+        let line_item = context.add_entity("LineItem");
+        let decimal = context.sketch_mut().add_object("Decimal");
+        context.sketch_mut().graph.add_morphism("items", order, line_item);
+        context.sketch_mut().graph.add_morphism("unitPrice", line_item, decimal);
+        context.sketch_mut().graph.add_morphism("discount", order, decimal);
+        let total = context.sketch_mut().graph.add_morphism("total", order, decimal);
+        context.derive_morphism(
+            total,
+            Expr::Binary {
+                op: sketchddd_core::BinaryOp::Sub,
+                left: Box::new(Expr::Call {
+                    function: "sum".into(),
+                    args: vec![Expr::Path(vec!["items".into(), "unitPrice".into()])],
+                }),
+                right: Box::new(Expr::Path(vec!["discount".into()])),
+            },
+        );
+        context.schedule_removal(order, Some("total"), ModelVersion::new(3, 0, 0));
+
+        let result = generate(&context).unwrap();
+
+        assert!(!result.contains("    total: Decimal\n"));
+        assert!(result.contains(
+            "    @property\n    def total(self) -> Decimal:\n        \"\"\"Derived: computed as `sum(items.unitPrice) - discount`. Deprecated: Removed in 3.0.\"\"\"\n"
+        ));
+        assert!(result.contains(
+            "        return sum(item.unit_price for item in self.items) - self.discount\n"
+        ));
+    }
+
     #[test]
     fn test_entity_references_use_id_type() {
         let mut context = BoundedContext::new("Commerce");
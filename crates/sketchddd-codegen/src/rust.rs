@@ -3,17 +3,22 @@
 //! Generates idiomatic Rust code from a bounded context:
 //! - Entities as structs with newtype ID wrappers
 //! - Value objects as structs with structural equality
-//! - Aggregates with validation methods
+//! - Aggregates with validation methods checking their strict invariants
+//! - Field constraints as checking methods
+//! - Derived morphisms as computed getters
 //! - Enums for sum types
 //! - Morphisms as struct fields
+//! - Lifecycles as typestate modules
 
 use crate::CodegenError;
+use serde::Deserialize;
 use sketchddd_core::sketch::{ColimitCocone, LimitCone, Morphism, ObjectId};
-use sketchddd_core::BoundedContext;
+use sketchddd_core::{BoundedContext, Classification, Collection, Enforcement, Expr, FieldConstraint, Lifecycle};
 use std::collections::{HashMap, HashSet};
 
 /// Configuration options for Rust code generation.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct RustConfig {
     /// Derive macros to add to all structs
     pub derives: Vec<String>,
@@ -51,7 +56,8 @@ pub fn generate_with_config(
     context: &BoundedContext,
     config: &RustConfig,
 ) -> Result<String, CodegenError> {
-    let mut gen = RustGenerator::new(context, config);
+    let context = crate::with_constrained_fields(context);
+    let mut gen = RustGenerator::new(&context, config);
     gen.generate()
 }
 
@@ -82,10 +88,10 @@ impl<'a> RustGenerator<'a> {
             .map(|o| (o.id, o.name.clone()))
             .collect();
 
-        // Group morphisms by source object (excluding identity morphisms)
+        // Group stored morphisms by source object (excluding identity and derived morphisms)
         let mut object_morphisms: HashMap<ObjectId, Vec<&Morphism>> = HashMap::new();
         for morphism in context.graph().morphisms() {
-            if !morphism.is_identity {
+            if !morphism.is_identity && !context.is_derived(morphism.id) {
                 object_morphisms
                     .entry(morphism.source)
                     .or_default()
@@ -106,16 +112,43 @@ impl<'a> RustGenerator<'a> {
     }
 
     fn generate(&mut self) -> Result<String, CodegenError> {
+        self.check_patterns()?;
         self.write_header();
         self.write_imports();
         self.write_entities();
         self.write_value_objects();
         self.write_enums();
         self.write_aggregates();
+        self.write_field_constraints();
+        self.write_derived_morphisms();
+        self.write_lifecycles();
 
         Ok(std::mem::take(&mut self.output))
     }
 
+    /// Check that every field pattern compiles, so that the generated
+    /// code never fails to.
+    fn check_patterns(&self) -> Result<(), CodegenError> {
+        for field in self.context.constrained_fields() {
+            for constraint in &field.constraints {
+                if let FieldConstraint::Pattern(p) = constraint {
+                    regex::Regex::new(&anchored(p)).map_err(|e| {
+                        let object = self
+                            .object_names
+                            .get(&field.object)
+                            .cloned()
+                            .unwrap_or_default();
+                        CodegenError::InvalidModel(format!(
+                            "Invalid pattern on {}.{}: {}",
+                            object, field.field, e
+                        ))
+                    })?;
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn write_header(&mut self) {
         let module_name = self
             .config
@@ -146,7 +179,7 @@ impl<'a> RustGenerator<'a> {
             self.output.push_str("use uuid::Uuid;\n");
         }
 
-        self.output.push_str("\n");
+        self.output.push('\n');
     }
 
     fn write_entities(&mut self) {
@@ -211,12 +244,14 @@ impl std::fmt::Display for {name}Id {{
         } else {
             ""
         };
+        let deprecated = self.deprecated_attribute(object_id, None, "");
+        let metadata = self.metadata_doc(object_id);
 
         self.output.push_str(&format!(
             r#"/// Entity: {name}{root_note}
 ///
 /// An entity has a unique identity that persists through state changes.
-{derives}
+{metadata}{deprecated}{derives}
 pub struct {name} {{
     /// Unique identifier
     pub id: {name}Id,
@@ -258,7 +293,7 @@ pub struct {name} {{
         if let Some(morphisms) = morphisms {
             let params: Vec<String> = morphisms
                 .iter()
-                .map(|m| format!("{}: {}", to_snake_case(&m.name), self.rust_type_for_target(m.target)))
+                .map(|m| format!("{}: {}", to_snake_case(&m.name), self.rust_type_for(m)))
                 .collect();
             self.output.push_str(&params.join(", "));
         }
@@ -298,6 +333,15 @@ pub struct {name} {{
     fn write_value_object(&mut self, name: &str, object_id: ObjectId) {
         // Value objects need PartialEq, Eq for structural equality
         let mut derives: Vec<&str> = vec!["Debug", "Clone", "PartialEq", "Eq", "Hash", "Serialize", "Deserialize"];
+        // A HashSet field cannot be hashed
+        let has_set = self.object_morphisms.get(&object_id).is_some_and(|morphisms| {
+            morphisms
+                .iter()
+                .any(|m| self.context.collection_of(m.id) == Some(Collection::Set))
+        });
+        if has_set {
+            derives.retain(|&d| d != "Hash");
+        }
 
         // Remove duplicates with config derives
         let config_derives: HashSet<&str> = self.config.derives.iter().map(|s| s.as_str()).collect();
@@ -311,13 +355,15 @@ pub struct {name} {{
 
         // Check if this value object has a limit cone definition with projections
         let limit_cone = self.context.get_value_object_limit(object_id);
+        let deprecated = self.deprecated_attribute(object_id, None, "");
+        let metadata = self.metadata_doc(object_id);
 
         self.output.push_str(&format!(
             r#"/// Value Object: {name}
 ///
 /// A value object is defined by its attributes, not identity.
 /// Two value objects with the same attributes are considered equal.
-{derives_str}
+{metadata}{deprecated}{derives_str}
 pub struct {name} {{
 "#
         ));
@@ -357,7 +403,7 @@ pub struct {name} {{
         if let Some(morphisms) = morphisms {
             let params: Vec<String> = morphisms
                 .iter()
-                .map(|m| format!("{}: {}", to_snake_case(&m.name), self.rust_type_for_target(m.target)))
+                .map(|m| format!("{}: {}", to_snake_case(&m.name), self.rust_type_for(m)))
                 .collect();
             self.output.push_str(&params.join(", "));
         }
@@ -394,15 +440,16 @@ pub struct {name} {{
 
     fn write_enum(&mut self, colimit: &ColimitCocone) {
         let derives = self.format_derives(&["Debug", "Clone", "PartialEq", "Eq", "Hash", "Serialize", "Deserialize"]);
+        let deprecated = self.deprecated_attribute(colimit.apex, None, "");
 
         self.output.push_str(&format!(
             r#"/// Enumeration: {}
 ///
 /// A sum type representing one of several possible variants.
-{}
+{}{}
 pub enum {} {{
 "#,
-            colimit.name, derives, colimit.name
+            colimit.name, deprecated, derives, colimit.name
         ));
 
         // Check if this is a simple enum (all variants have same source as apex)
@@ -539,6 +586,20 @@ impl std::error::Error for ValidationError {}
             .filter_map(|p| self.object_names.get(&p.target).cloned())
             .collect();
 
+        let checks = self.invariant_checks(root_id).unwrap_or_else(|| {
+            r#"        // TODO: Add invariant validation logic based on model equations
+        //
+        // Example invariant:
+        // if self.total_price != self.items.iter().map(|i| i.price).sum() {
+        //     return Err(ValidationError::new(
+        //         "totalPrice",
+        //         "totalPrice must equal sum of item prices"
+        //     ));
+        // }
+"#
+            .to_string()
+        });
+
         self.output.push_str(&format!(
             r#"/// Aggregate: {}
 ///
@@ -550,16 +611,7 @@ impl {} {{
     /// Call this method after making changes to ensure the aggregate
     /// is in a valid state.
     pub fn validate(&self) -> Result<(), ValidationError> {{
-        // TODO: Add invariant validation logic based on model equations
-        //
-        // Example invariant:
-        // if self.total_price != self.items.iter().map(|i| i.price).sum() {{
-        //     return Err(ValidationError::new(
-        //         "totalPrice",
-        //         "totalPrice must equal sum of item prices"
-        //     ));
-        // }}
-        Ok(())
+{}        Ok(())
     }}
 
     /// Validate and return self, useful for builder patterns.
@@ -570,22 +622,416 @@ impl {} {{
 }}
 
 "#,
-            limit.name, root_name, member_names, root_name
+            limit.name, root_name, member_names, root_name, checks
         ));
     }
 
+    /// Checks for the strict invariants of an aggregate, followed by its
+    /// eventual invariants as comments, or `None` if it declares none.
+    fn invariant_checks(&self, root_id: ObjectId) -> Option<String> {
+        let invariants = self.context.invariants_of(root_id);
+        if invariants.is_empty() {
+            return None;
+        }
+
+        let mut checks = String::new();
+        let (strict, eventual): (Vec<_>, Vec<_>) = invariants
+            .into_iter()
+            .partition(|inv| inv.enforcement == Enforcement::Strict);
+
+        for invariant in strict {
+            checks.push_str(&format!(
+                "        if !({}) {{\n            return Err(ValidationError::new({:?}, \"strict invariant violated\"));\n        }}\n",
+                rust_expr(&invariant.expression),
+                invariant.expression.to_string()
+            ));
+        }
+
+        if !eventual.is_empty() {
+            checks.push_str(
+                "        // Eventual invariants are restored asynchronously and not checked here:\n",
+            );
+            for invariant in eventual {
+                checks.push_str(&format!("        // - {}\n", invariant.expression));
+            }
+        }
+
+        Some(checks)
+    }
+
+    fn write_field_constraints(&mut self) {
+        let context = self.context;
+
+        // Only entities and value objects are generated as structs
+        let constrained: Vec<(ObjectId, Vec<&Morphism>)> = context
+            .entities()
+            .iter()
+            .chain(context.value_objects())
+            .filter_map(|&id| {
+                let fields: Vec<&Morphism> = self
+                    .object_morphisms
+                    .get(&id)?
+                    .iter()
+                    .copied()
+                    .filter(|m| !context.field_constraints(id, &m.name).is_empty())
+                    .collect();
+                (!fields.is_empty()).then_some((id, fields))
+            })
+            .collect();
+
+        if constrained.is_empty() {
+            return;
+        }
+
+        self.output.push_str("// =============================================================\n");
+        self.output.push_str("// Field Constraints\n");
+        self.output.push_str("// =============================================================\n\n");
+
+        // The aggregate section already defines the error type
+        if !context.sketch().limits.iter().any(|l| l.is_aggregate) {
+            self.write_validation_error();
+        }
+
+        for (object_id, fields) in constrained {
+            let name = self.object_names.get(&object_id).cloned().unwrap_or_default();
+            self.output.push_str(&format!("impl {name} {{\n"));
+            self.output.push_str("    /// Check the literal constraints declared on the fields.\n");
+            self.output.push_str("    pub fn check_constraints(&self) -> Result<(), ValidationError> {\n");
+
+            for field in fields {
+                let field_name = to_snake_case(&field.name);
+                let mut patterns = 0;
+                for constraint in context.field_constraints(object_id, &field.name) {
+                    let mut pattern = String::new();
+                    if let FieldConstraint::Pattern(p) = constraint {
+                        // Compiled once, on the first check
+                        patterns += 1;
+                        pattern = format!("{}_PATTERN", field_name.to_uppercase());
+                        if patterns > 1 {
+                            pattern.push_str(&format!("_{}", patterns));
+                        }
+                        self.output.push_str(&format!(
+                            r#"        static {pattern}: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+        let {binding} = {pattern}.get_or_init(|| regex::Regex::new({literal:?}).expect("pattern is valid"));
+"#,
+                            binding = pattern.to_lowercase(),
+                            literal = anchored(p),
+                        ));
+                    }
+                    let (condition, requirement) =
+                        rust_constraint_check(&field_name, constraint, &pattern.to_lowercase());
+                    self.output.push_str(&format!(
+                        r#"        if {condition} {{
+            return Err(ValidationError::new("{field_name}", "{field_name} must {requirement}"));
+        }}
+"#
+                    ));
+                }
+            }
+
+            self.output.push_str("        Ok(())\n");
+            self.output.push_str("    }\n");
+            self.output.push_str("}\n\n");
+        }
+    }
+
+    fn write_derived_morphisms(&mut self) {
+        let context = self.context;
+
+        // Only entities and value objects are generated as structs
+        let derived: Vec<(ObjectId, Vec<(&Morphism, &Expr)>)> = context
+            .entities()
+            .iter()
+            .chain(context.value_objects())
+            .map(|&id| (id, context.derivations_from(id)))
+            .filter(|(_, derivations)| !derivations.is_empty())
+            .collect();
+
+        if derived.is_empty() {
+            return;
+        }
+
+        self.output.push_str("// =============================================================\n");
+        self.output.push_str("// Derived Morphisms\n");
+        self.output.push_str("// =============================================================\n\n");
+
+        for (object_id, derivations) in derived {
+            let name = self.object_names.get(&object_id).cloned().unwrap_or_default();
+            self.output.push_str(&format!("impl {name} {{\n"));
+
+            for (morphism, expression) in derivations {
+                let deprecated = self.deprecated_attribute(object_id, Some(&morphism.name), "    ");
+                self.output.push_str(&format!(
+                    r#"    /// Computed as `{}`.
+{}    pub fn {}(&self) -> {} {{
+        {}
+    }}
+"#,
+                    expression,
+                    deprecated,
+                    to_snake_case(&morphism.name),
+                    self.rust_type_for(morphism),
+                    rust_expr(expression)
+                ));
+            }
+
+            self.output.push_str("}\n\n");
+        }
+    }
+
+    fn write_lifecycles(&mut self) {
+        let lifecycles = self.context.lifecycles();
+        if lifecycles.is_empty() {
+            return;
+        }
+
+        self.output.push_str("// =============================================================\n");
+        self.output.push_str("// Lifecycles (Typestate)\n");
+        self.output.push_str("// =============================================================\n\n");
+
+        for lifecycle in lifecycles {
+            self.write_lifecycle(lifecycle);
+        }
+    }
+
+    fn write_lifecycle(&mut self, lifecycle: &Lifecycle) {
+        let Some(initial) = lifecycle.initial_state() else {
+            return;
+        };
+        let name = &lifecycle.name;
+        let module = format!("{}_lifecycle", to_snake_case(name));
+        let enum_name = self
+            .object_names
+            .get(&lifecycle.subject)
+            .cloned()
+            .unwrap_or_else(|| "Unknown".to_string());
+
+        // The wrapper carries the domain object when one with the same name is generated
+        let data_type = self
+            .context
+            .graph()
+            .find_object_by_name(name)
+            .filter(|o| self.entity_ids.contains(&o.id) || self.value_object_ids.contains(&o.id))
+            .map(|o| o.name.clone());
+
+        // Typestate markers can only carry the enum value for unit variants
+        let is_simple_enum = self
+            .context
+            .get_enum_colimit(lifecycle.subject)
+            .map(|c| c.injections.iter().all(|i| i.source == c.apex))
+            .unwrap_or(false);
+
+        self.output.push_str(&format!(
+            r#"/// Typestate encoding of the `{name}` lifecycle over `{enum_name}`.
+///
+/// Only declared transitions are available as methods, so invalid state
+/// changes are rejected at compile time.
+pub mod {module} {{
+    use std::marker::PhantomData;
+
+    /// A state of the `{name}` lifecycle.
+    pub trait State {{
+        /// Name of the corresponding `{enum_name}` variant.
+        const NAME: &'static str;
+"#
+        ));
+        if is_simple_enum {
+            self.output.push_str(&format!(
+                r#"
+        /// The corresponding `{enum_name}` value.
+        const STATUS: super::{enum_name};
+"#
+            ));
+        }
+        self.output.push_str("    }\n\n");
+
+        for state in lifecycle.states() {
+            self.output.push_str(&format!(
+                r#"    /// State marker: `{state}`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct {state};
+
+    impl State for {state} {{
+        const NAME: &'static str = "{state}";
+"#
+            ));
+            if is_simple_enum {
+                self.output.push_str(&format!(
+                    "        const STATUS: super::{enum_name} = super::{enum_name}::{state};\n"
+                ));
+            }
+            self.output.push_str("    }\n\n");
+        }
+
+        // Exhaustive match from enum values to states; variants handled by
+        // default, or not at all (W0052), have no state
+        if is_simple_enum {
+            self.output.push_str(&format!(
+                r#"    /// Get the state a `{enum_name}` value is in, or `None` for a
+    /// variant the lifecycle does not handle as a state.
+    pub fn state_of(status: &super::{enum_name}) -> Option<&'static str> {{
+        match status {{
+"#
+            ));
+            let states = lifecycle.states();
+            let variants = self
+                .context
+                .get_enum_colimit(lifecycle.subject)
+                .map(|c| c.variant_names().collect::<Vec<_>>())
+                .unwrap_or_default();
+            for variant in variants {
+                let arm = if states.contains(&variant) {
+                    format!("Some({variant}::NAME)")
+                } else {
+                    "None".to_string()
+                };
+                self.output.push_str(&format!(
+                    "            super::{enum_name}::{variant} => {arm},\n"
+                ));
+            }
+            self.output.push_str("        }\n    }\n\n");
+        }
+
+        // Wrapper type parameterized by state
+        self.output.push_str(&format!("    /// A `{name}` in lifecycle state `S`.\n"));
+        self.output.push_str("    #[derive(Debug, Clone)]\n");
+        self.output.push_str(&format!("    pub struct {name}<S: State> {{\n"));
+        if let Some(data) = &data_type {
+            self.output.push_str(&format!("        pub data: super::{data},\n"));
+        }
+        self.output.push_str("        state: PhantomData<S>,\n");
+        self.output.push_str("    }\n\n");
+
+        // Accessors available in every state
+        self.output.push_str(&format!(
+            r#"    impl<S: State> {name}<S> {{
+        /// Name of the current state.
+        pub fn state_name(&self) -> &'static str {{
+            S::NAME
+        }}
+"#
+        ));
+        if is_simple_enum {
+            self.output.push_str(&format!(
+                r#"
+        /// The current state as a `{enum_name}` value.
+        pub fn status(&self) -> super::{enum_name} {{
+            S::STATUS
+        }}
+"#
+            ));
+        }
+        self.output.push_str("    }\n\n");
+
+        // Constructor in the initial state, plus one method per transition
+        let (new_params, new_fields, move_fields) = match &data_type {
+            Some(data) => (
+                format!("data: super::{data}"),
+                "data, state: PhantomData",
+                "data: self.data, state: PhantomData",
+            ),
+            None => (String::new(), "state: PhantomData", "state: PhantomData"),
+        };
+
+        for state in lifecycle.states() {
+            let mut methods = Vec::new();
+            if state == initial {
+                methods.push(format!(
+                    r#"        /// Start a new `{name}` in its initial state.
+        pub fn new({new_params}) -> Self {{
+            Self {{ {new_fields} }}
+        }}
+"#
+                ));
+            }
+            for next in lifecycle.successors(state) {
+                let method = to_snake_case(next);
+                methods.push(format!(
+                    r#"        /// Transition `{state} -> {next}`.
+        pub fn to_{method}(self) -> {name}<{next}> {{
+            {name} {{ {move_fields} }}
+        }}
+"#
+                ));
+            }
+
+            if methods.is_empty() {
+                continue;
+            }
+            self.output.push_str(&format!("    impl {name}<{state}> {{\n"));
+            self.output.push_str(&methods.join("\n"));
+            self.output.push_str("    }\n\n");
+        }
+
+        // Drop the blank line before the module's closing brace
+        self.output.pop();
+        self.output.push_str("}\n\n");
+    }
+
     fn format_field_string(&self, morphism: &Morphism) -> String {
         let field_name = to_snake_case(&morphism.name);
-        let field_type = self.rust_type_for_target(morphism.target);
+        let field_type = self.rust_type_for(morphism);
 
         let mut result = String::new();
         if let Some(desc) = &morphism.description {
             result.push_str(&format!("    /// {}\n", desc));
         }
+        result.push_str(&self.deprecated_attribute(morphism.source, Some(&morphism.name), "    "));
         result.push_str(&format!("    pub {}: {},\n", field_name, field_type));
         result
     }
 
+    /// A `#[deprecated]` line for an object or field scheduled for removal.
+    fn deprecated_attribute(&self, object_id: ObjectId, field: Option<&str>, indent: &str) -> String {
+        match self.context.removal_of(object_id, field) {
+            Some(removal) => format!("{indent}#[deprecated(note = \"{}\")]\n", removal.note()),
+            None => String::new(),
+        }
+    }
+
+    /// Doc comment lines describing the classification, tags and metadata
+    /// of an object.
+    fn metadata_doc(&self, object_id: ObjectId) -> String {
+        let mut lines = Vec::new();
+        if let Some(classification) = self.context.classification_of(object_id) {
+            let kind = match classification {
+                Classification::Core => "core domain",
+                Classification::Supporting => "supporting subdomain",
+                Classification::Generic => "generic subdomain",
+            };
+            lines.push(format!("Classification: {}", kind));
+        }
+        if let Some(metadata) = self.context.object_metadata(object_id) {
+            if !metadata.tags.is_empty() {
+                let tags: Vec<&str> = metadata.tags.iter().map(|t| t.as_str()).collect();
+                lines.push(format!("Tags: {}", tags.join(", ")));
+            }
+            for (key, value) in &metadata.values {
+                lines.push(format!("{}: {}", key, value));
+            }
+        }
+
+        if lines.is_empty() {
+            return String::new();
+        }
+        let mut doc = String::from("///\n");
+        for line in lines {
+            doc.push_str(&format!("/// {}\n", line));
+        }
+        doc
+    }
+
+    /// Get the type of a morphism's field: its target, or a collection of
+    /// its target when the morphism is to-many.
+    fn rust_type_for(&self, morphism: &Morphism) -> String {
+        let element = self.rust_type_for_target(morphism.target);
+        match self.context.collection_of(morphism.id) {
+            Some(Collection::List) => format!("Vec<{}>", element),
+            Some(Collection::Set) => format!("std::collections::HashSet<{}>", element),
+            None => element,
+        }
+    }
+
     fn rust_type_for_target(&self, target: ObjectId) -> String {
         let target_name = self
             .object_names
@@ -610,6 +1056,88 @@ impl {} {{
     }
 }
 
+/// Build the violation check for a field constraint.
+///
+/// Returns the condition under which the constraint is violated and a
+/// description of the requirement for the error message. Numeric bounds are
+/// converted with `.into()` so they work with any numeric field type, and
+/// patterns are matched by the `regex::Regex` bound to `pattern`.
+fn rust_constraint_check(
+    field_name: &str,
+    constraint: &FieldConstraint,
+    pattern: &str,
+) -> (String, String) {
+    let literal = |v: f64| if v < 0.0 { format!("({})", v) } else { v.to_string() };
+
+    match constraint {
+        FieldConstraint::Min(v) => (
+            format!("self.{} < {}.into()", field_name, literal(*v)),
+            format!("be >= {}", v),
+        ),
+        FieldConstraint::Max(v) => (
+            format!("self.{} > {}.into()", field_name, literal(*v)),
+            format!("be <= {}", v),
+        ),
+        FieldConstraint::MinLength(n) => (
+            format!("self.{}.chars().count() < {}", field_name, n),
+            format!("have at least {} characters", n),
+        ),
+        FieldConstraint::MaxLength(n) => (
+            format!("self.{}.chars().count() > {}", field_name, n),
+            format!("have at most {} characters", n),
+        ),
+        FieldConstraint::Pattern(_) => (
+            format!("!{}.is_match(&self.{})", pattern, field_name),
+            "match the required pattern".to_string(),
+        ),
+    }
+}
+
+/// Anchor a pattern so that it matches whole values.
+fn anchored(pattern: &str) -> String {
+    format!("^(?:{})$", pattern)
+}
+
+/// Render the expression of a derived morphism as Rust code over `self`.
+///
+/// Aggregate functions take a path whose first step is a collection field,
+/// so `sum(items.price)` sums `price` over the elements of `self.items`.
+fn rust_expr(expr: &Expr) -> String {
+    let operand = |expr: &Expr| match expr {
+        Expr::Binary { .. } => format!("({})", rust_expr(expr)),
+        _ => rust_expr(expr),
+    };
+    let path = |path: &[String]| path.iter().map(|p| to_snake_case(p)).collect::<Vec<_>>().join(".");
+
+    match expr {
+        Expr::Number(n) => n.to_string(),
+        Expr::Text(s) => format!("{:?}.to_string()", s),
+        Expr::Path(p) => format!("self.{}.clone()", path(p)),
+        Expr::Binary { op, left, right } => format!("{} {} {}", operand(left), op, operand(right)),
+        Expr::Unary { op, operand: inner } => format!("{}{}", op, operand(inner)),
+        Expr::Call { function, args } => match (function.as_str(), args.as_slice()) {
+            ("count", [Expr::Path(p)]) => format!("self.{}.len()", to_snake_case(&p[0])),
+            (function, [Expr::Path(p)]) => {
+                let collection = to_snake_case(&p[0]);
+                let values = if p.len() > 1 {
+                    format!("self.{}.iter().map(|item| item.{}.clone())", collection, path(&p[1..]))
+                } else {
+                    format!("self.{}.iter().cloned()", collection)
+                };
+                match function {
+                    "sum" => format!("{}.sum()", values),
+                    _ => format!("{}.{}().unwrap_or_default()", values, function),
+                }
+            }
+            (function, args) => {
+                let args: Vec<String> = args.iter().map(rust_expr).collect();
+                format!("{}({})", function, args.join(", "))
+            }
+        },
+        Expr::Index { target, index } => format!("{}[{}]", operand(target), rust_expr(index)),
+    }
+}
+
 /// Convert PascalCase to snake_case.
 fn to_snake_case(s: &str) -> String {
     let mut result = String::with_capacity(s.len() + 4);
@@ -629,6 +1157,7 @@ fn to_snake_case(s: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use sketchddd_core::{BinaryOp, Collection, ModelVersion};
 
     #[test]
     fn test_to_snake_case() {
@@ -638,6 +1167,20 @@ mod tests {
         assert_eq!(to_snake_case("HTTPClient"), "h_t_t_p_client");
     }
 
+    #[test]
+    fn test_config_from_json() {
+        let config: RustConfig =
+            serde_json::from_str(r#"{ "use_builder_pattern": true, "module_name": "sales" }"#)
+                .unwrap();
+        assert!(config.use_builder_pattern);
+        assert_eq!(config.module_name.as_deref(), Some("sales"));
+        // Unset options keep their defaults
+        assert!(config.generate_validation);
+        assert_eq!(config.derives, RustConfig::default().derives);
+
+        assert!(serde_json::from_str::<RustConfig>(r#"{ "builder": true }"#).is_err());
+    }
+
     #[test]
     fn test_generate_empty_context() {
         let context = BoundedContext::new("Test");
@@ -682,6 +1225,38 @@ mod tests {
         assert!(result.contains("pub placed_by: CustomerId"));
     }
 
+    #[test]
+    fn test_generate_collection_fields() {
+        let context = crate::parse_context(crate::COLLECTION_FIELDS);
+
+        let result = generate(&context).unwrap();
+
+        assert!(result.contains("pub items: Vec<LineItemId>,"));
+        assert!(result.contains("pub tags: std::collections::HashSet<TagId>,"));
+        assert!(result.contains("pub amounts: Vec<Money>,"));
+    }
+
+    #[test]
+    fn test_generate_to_many_morphism() {
+        let mut context = BoundedContext::new("Commerce");
+        let order = context.add_entity("Order");
+        let line = context.add_entity("LineItem");
+        let items = context.sketch_mut().graph.add_morphism("items", order, line);
+        context.mark_many(items, Collection::List);
+        let tags = context.add_value_object("Tags");
+        let tag = context.add_entity("Tag");
+        let values = context.sketch_mut().graph.add_morphism("values", tags, tag);
+        context.mark_many(values, Collection::Set);
+
+        let result = generate(&context).unwrap();
+
+        assert!(result.contains("pub items: Vec<LineItemId>,"));
+        assert!(result.contains("pub fn new(items: Vec<LineItemId>) -> Self"));
+        assert!(result.contains("pub values: std::collections::HashSet<TagId>,"));
+        // A set cannot be hashed, so neither can the value object
+        assert!(result.contains("#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]\npub struct Tags"));
+    }
+
     #[test]
     fn test_generate_value_object() {
         let mut context = BoundedContext::new("Commerce");
@@ -746,6 +1321,39 @@ mod tests {
         assert!(result.contains("pub fn validate(&self) -> Result<(), ValidationError>"));
     }
 
+    #[test]
+    fn test_generate_aggregate_invariants() {
+        let mut context = BoundedContext::new("Commerce");
+        let order = context.add_entity("Order");
+        context.define_aggregate("OrderAggregate", order);
+        context.add_aggregate_invariant(
+            order,
+            Expr::Binary {
+                op: BinaryOp::Gt,
+                left: Box::new(Expr::Call {
+                    function: "count".into(),
+                    args: vec![Expr::Path(vec!["items".into()])],
+                }),
+                right: Box::new(Expr::Number(0.0)),
+            },
+            Enforcement::Strict,
+        );
+        context.add_aggregate_invariant(
+            order,
+            Expr::Path(vec!["customer".into(), "active".into()]),
+            Enforcement::Eventual,
+        );
+
+        let result = generate(&context).unwrap();
+
+        assert!(!result.contains("// TODO: Add invariant validation logic"));
+        assert!(result.contains(
+            "        if !(self.items.len() > 0) {\n            return Err(ValidationError::new(\"count(items) > 0\", \"strict invariant violated\"));\n"
+        ));
+        assert!(result.contains("        // - customer.active\n"));
+        assert!(!result.contains("self.customer.active"));
+    }
+
     #[test]
     fn test_generate_commerce_domain() {
         let mut context = BoundedContext::new("Commerce");
@@ -787,6 +1395,194 @@ mod tests {
         assert!(result.contains("pub fn validate(&self)"));
     }
 
+    #[test]
+    fn test_generate_lifecycle_typestate() {
+        let mut context = BoundedContext::new("Commerce");
+        context.add_entity("Order");
+        let status = context.add_enum(
+            "OrderStatus",
+            vec!["Pending".into(), "Confirmed".into(), "Shipped".into(), "Cancelled".into()],
+        );
+        let lifecycle = context.define_lifecycle("Order", status);
+        lifecycle.add_transition("Pending", "Confirmed");
+        lifecycle.add_transition("Confirmed", "Shipped");
+        lifecycle.add_transition("Pending", "Cancelled");
+
+        let result = generate(&context).unwrap();
+
+        assert!(result.contains("// Lifecycles (Typestate)"));
+        assert!(result.contains("pub mod order_lifecycle {"));
+        assert!(result.contains("pub struct Pending;"));
+        assert!(result.contains("const STATUS: super::OrderStatus = super::OrderStatus::Shipped;"));
+        assert!(result.contains("pub struct Order<S: State> {"));
+        assert!(result.contains("pub data: super::Order,"));
+        assert!(result.contains("impl Order<Pending> {"));
+        assert!(result.contains("pub fn new(data: super::Order) -> Self"));
+        assert!(result.contains("pub fn to_confirmed(self) -> Order<Confirmed>"));
+        assert!(result.contains("pub fn to_cancelled(self) -> Order<Cancelled>"));
+        // Terminal states have no transition impl
+        assert!(!result.contains("impl Order<Shipped>"));
+    }
+
+    #[test]
+    fn test_generate_lifecycle_state_of() {
+        let mut context = BoundedContext::new("Commerce");
+        let status = context.add_enum(
+            "OrderStatus",
+            vec!["Pending".into(), "Shipped".into(), "Imported".into()],
+        );
+        let lifecycle = context.define_lifecycle("Order", status);
+        lifecycle.add_transition("Pending", "Shipped");
+        lifecycle.add_default("Imported");
+
+        let result = generate(&context).unwrap();
+
+        assert!(result.contains("pub fn state_of(status: &super::OrderStatus) -> Option<&'static str> {"));
+        assert!(result.contains("super::OrderStatus::Pending => Some(Pending::NAME),"));
+        assert!(result.contains("super::OrderStatus::Imported => None,"));
+        assert!(!result.contains("pub struct Imported;"));
+    }
+
+    #[test]
+    fn test_generate_field_constraints() {
+        let mut context = BoundedContext::new("Billing");
+        let money = context.add_value_object("Money");
+        let decimal = context.sketch_mut().add_object("Decimal");
+        let string = context.sketch_mut().add_object("String");
+        context.sketch_mut().graph.add_morphism("amount", money, decimal);
+        context.sketch_mut().graph.add_morphism("currency", money, string);
+        context.constrain_field(money, "amount", FieldConstraint::Min(0.0));
+        context.constrain_field(money, "amount", FieldConstraint::Max(-0.5));
+        context.constrain_field(money, "currency", FieldConstraint::Pattern("[A-Z]{3}".into()));
+
+        let result = generate(&context).unwrap();
+
+        assert!(result.contains("// Field Constraints"));
+        // No aggregates, so the error type is emitted here
+        assert!(result.contains("pub struct ValidationError {"));
+        assert!(result.contains("pub fn check_constraints(&self) -> Result<(), ValidationError> {"));
+        assert!(result.contains("if self.amount < 0.into() {"));
+        assert!(result.contains("if self.amount > (-0.5).into() {"));
+        assert!(result.contains(
+            "        static CURRENCY_PATTERN: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();\n"
+        ));
+        assert!(result.contains(
+            r#"        let currency_pattern = CURRENCY_PATTERN.get_or_init(|| regex::Regex::new("^(?:[A-Z]{3})$").expect("pattern is valid"));"#
+        ));
+        assert!(result.contains("        if !currency_pattern.is_match(&self.currency) {"));
+        assert!(result.contains(r#"ValidationError::new("amount", "amount must be >= 0")"#));
+    }
+
+    #[test]
+    fn test_generate_constraints_of_declared_fields() {
+        let context = crate::parse_context(crate::CONSTRAINED_FIELDS);
+        let result = generate(&context).unwrap();
+
+        assert!(result.contains("pub amount: Decimal,"));
+        assert!(result.contains("if self.amount < 0.into() {"));
+        assert!(result.contains("if !currency_pattern.is_match(&self.currency) {"));
+        assert!(result.contains("pub balance: Decimal,"));
+        assert!(result.contains("if self.balance > 1000.into() {"));
+    }
+
+    #[test]
+    fn test_generate_pattern_literals() {
+        let mut context = BoundedContext::new("Billing");
+        let money = context.add_value_object("Money");
+        let string = context.sketch_mut().add_object("String");
+        context
+            .sketch_mut()
+            .graph
+            .add_morphism("code", money, string);
+        context.constrain_field(money, "code", FieldConstraint::Pattern(r#"\d+"#.into()));
+        context.constrain_field(money, "code", FieldConstraint::Pattern(r#"[^"]*\\"#.into()));
+
+        let result = generate(&context).unwrap();
+        assert!(result.contains(r#"regex::Regex::new("^(?:\\d+)$")"#));
+        assert!(result.contains("static CODE_PATTERN_2: std::sync::OnceLock<regex::Regex>"));
+        assert!(result.contains(r#"regex::Regex::new("^(?:[^\"]*\\\\)$")"#));
+        assert!(result.contains("if !code_pattern_2.is_match(&self.code) {"));
+
+        context.constrain_field(money, "code", FieldConstraint::Pattern("[A-Z".into()));
+        let error = generate(&context).unwrap_err().to_string();
+        assert!(
+            error.starts_with("Invalid model: Invalid pattern on Money.code:"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_generate_deprecation_markers() {
+        let mut context = BoundedContext::new("Billing");
+        let invoice = context.add_entity("Invoice");
+        let status = context.add_enum("LegacyStatus", vec!["Open".into()]);
+        let string = context.sketch_mut().add_object("String");
+        context.sketch_mut().graph.add_morphism("legacyCode", invoice, string);
+        context.schedule_removal(status, None, ModelVersion::new(3, 0, 0));
+        context.schedule_removal(invoice, Some("legacyCode"), ModelVersion::new(2, 1, 0));
+
+        let result = generate(&context).unwrap();
+
+        assert!(result.contains("#[deprecated(note = \"Removed in 3.0\")]\n#[derive("));
+        assert!(result.contains("    #[deprecated(note = \"Removed in 2.1\")]\n    pub legacy_code: String,"));
+        assert!(!result.contains("#[deprecated(note = \"Removed in 2.1\")]\n#[derive("));
+    }
+
+    #[test]
+    fn test_generate_metadata_docs() {
+        let mut context = BoundedContext::new("Billing");
+        let invoice = context.add_entity("Invoice");
+        context.add_value_object("Money");
+        context.metadata_mut().classification = Some(Classification::Supporting);
+        let metadata = context.object_metadata_mut(invoice);
+        metadata.classification = Some(Classification::Core);
+        metadata.add_tag("pii");
+        metadata.set("owner", sketchddd_core::MetadataValue::Text("billing".into()));
+
+        let result = generate(&context).unwrap();
+
+        assert!(result.contains(
+            "identity that persists through state changes.\n///\n/// Classification: core domain\n/// Tags: pii\n/// owner: billing\n#[derive("
+        ));
+        assert!(result.contains("considered equal.\n///\n/// Classification: supporting subdomain\n#[derive("));
+    }
+
+    #[test]
+    fn test_generate_derived_morphisms() {
+        let mut context = BoundedContext::new("Commerce");
+        let order = context.add_entity("Order");
+        let line_item = context.add_entity("LineItem");
+        let money = context.add_value_object("Money");
+        context.sketch_mut().graph.add_morphism("items", order, line_item);
+        context.sketch_mut().graph.add_morphism("unit_price", line_item, money);
+        context.sketch_mut().graph.add_morphism("discount", order, money);
+        let total = context.sketch_mut().graph.add_morphism("total", order, money);
+        context.derive_morphism(
+            total,
+            Expr::Binary {
+                op: BinaryOp::Sub,
+                left: Box::new(Expr::Call {
+                    function: "sum".into(),
+                    args: vec![Expr::Path(vec!["items".into(), "unit_price".into()])],
+                }),
+                right: Box::new(Expr::Path(vec!["discount".into()])),
+            },
+        );
+
+        let result = generate(&context).unwrap();
+
+        // Derived morphisms are computed, not stored
+        assert!(!result.contains("pub total: Money,"));
+        assert!(!result.contains("total: Money, "));
+        assert!(result.contains("pub discount: Money,"));
+        assert!(result.contains("    /// Computed as `sum(items.unit_price) - discount`.\n"));
+        assert!(result.contains("    pub fn total(&self) -> Money {\n"));
+        assert!(result.contains(
+            "self.items.iter().map(|item| item.unit_price.clone()).sum() - self.discount.clone()"
+        ));
+    }
+
     #[test]
     fn test_config_custom_derives() {
         let context = BoundedContext::new("Test");
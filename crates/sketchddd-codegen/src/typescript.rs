@@ -6,14 +6,17 @@
 //! - Aggregates with Zod schema validation
 //! - Enums and union types for sum types
 //! - Zod schemas for runtime validation
+//! - Derived morphisms as computing functions
 
 use crate::CodegenError;
+use serde::Deserialize;
 use sketchddd_core::sketch::{ColimitCocone, LimitCone, Morphism, ObjectId};
-use sketchddd_core::BoundedContext;
+use sketchddd_core::{BinaryOp, BoundedContext, Collection, Enforcement, Expr, FieldConstraint};
 use std::collections::{HashMap, HashSet};
 
 /// Configuration options for TypeScript code generation.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct TypeScriptConfig {
     /// Whether to generate Zod schemas for runtime validation
     pub generate_zod_schemas: bool,
@@ -49,7 +52,8 @@ pub fn generate_with_config(
     context: &BoundedContext,
     config: &TypeScriptConfig,
 ) -> Result<String, CodegenError> {
-    let mut gen = TypeScriptGenerator::new(context, config);
+    let context = crate::with_constrained_fields(context);
+    let mut gen = TypeScriptGenerator::new(&context, config);
     gen.generate()
 }
 
@@ -80,10 +84,10 @@ impl<'a> TypeScriptGenerator<'a> {
             .map(|o| (o.id, o.name.clone()))
             .collect();
 
-        // Group morphisms by source object (excluding identity morphisms)
+        // Group stored morphisms by source object (excluding identity and derived morphisms)
         let mut object_morphisms: HashMap<ObjectId, Vec<&Morphism>> = HashMap::new();
         for morphism in context.graph().morphisms() {
-            if !morphism.is_identity {
+            if !morphism.is_identity && !context.is_derived(morphism.id) {
                 object_morphisms
                     .entry(morphism.source)
                     .or_default()
@@ -118,6 +122,7 @@ impl<'a> TypeScriptGenerator<'a> {
         self.write_value_objects();
         self.write_enums();
         self.write_aggregates();
+        self.write_derived_morphisms();
 
         if use_namespace {
             self.output.push_str("}\n");
@@ -251,13 +256,14 @@ type Brand<T, B> = T & { readonly [__brand]: B };
         } else {
             ""
         };
+        let deprecated = self.deprecated_doc(object_id);
 
         self.output.push_str(&format!(
             r#"/**
  * Entity: {name}{root_note}
  *
  * An entity has a unique identity that persists through state changes.
- */
+{deprecated} */
 {export}interface {name} {{
   /** Unique identifier */
   readonly id: {name}Id;
@@ -324,7 +330,7 @@ type Brand<T, B> = T & { readonly [__brand]: B };
             .map(|morphisms| {
                 morphisms
                     .iter()
-                    .map(|m| format!("{}: {}", to_camel_case(&m.name), self.ts_type_for_target(m.target)))
+                    .map(|m| format!("{}: {}", to_camel_case(&m.name), self.ts_type_for(m)))
                     .collect()
             })
             .unwrap_or_default();
@@ -376,6 +382,7 @@ type Brand<T, B> = T & { readonly [__brand]: B };
 
         // Check if this value object has a limit cone definition with projections
         let limit_cone = self.context.get_value_object_limit(object_id);
+        let deprecated = self.deprecated_doc(object_id);
 
         self.output.push_str(&format!(
             r#"/**
@@ -384,7 +391,7 @@ type Brand<T, B> = T & { readonly [__brand]: B };
  * A value object is defined by its attributes, not identity.
  * Two value objects with the same attributes are considered equal.
  * All properties are readonly to enforce immutability.
- */
+{deprecated} */
 {export}interface {name} {{
 "#
         ));
@@ -460,6 +467,7 @@ type Brand<T, B> = T & { readonly [__brand]: B };
         // Check if this is a simple enum (all variants have same source as apex)
         // or a sum type with different variant types
         let is_simple_enum = colimit.injections.iter().all(|i| i.source == colimit.apex);
+        let deprecated = self.deprecated_doc(colimit.apex);
 
         if is_simple_enum {
             // Generate TypeScript enum for simple enums
@@ -468,10 +476,10 @@ type Brand<T, B> = T & { readonly [__brand]: B };
  * Enumeration: {}
  *
  * A simple enumeration of possible values.
- */
+{} */
 {}enum {} {{
 "#,
-                colimit.name, export, colimit.name
+                colimit.name, deprecated, export, colimit.name
             ));
 
             for injection in &colimit.injections {
@@ -499,9 +507,9 @@ type Brand<T, B> = T & { readonly [__brand]: B };
  * Sum Type: {}
  *
  * A discriminated union representing one of several possible variants.
- */
+{} */
 "#,
-                colimit.name
+                colimit.name, deprecated
             ));
 
             // Generate individual variant types
@@ -678,6 +686,20 @@ type Brand<T, B> = T & { readonly [__brand]: B };
             .filter_map(|p| self.object_names.get(&p.target).cloned())
             .collect();
 
+        let checks = self.invariant_checks(root_id).unwrap_or_else(|| {
+            r#"  // TODO: Add invariant validation logic based on model equations
+  //
+  // Example invariant:
+  // if (entity.totalPrice !== entity.items.reduce((sum, item) => sum + item.price, 0)) {
+  //   errors.push({
+  //     invariant: 'totalPrice',
+  //     message: 'totalPrice must equal sum of item prices',
+  //   });
+  // }
+"#
+            .to_string()
+        });
+
         self.output.push_str(&format!(
             r#"/**
  * Aggregate: {}
@@ -695,16 +717,7 @@ type Brand<T, B> = T & { readonly [__brand]: B };
 {export}function validate{}(entity: {}): ValidationResult<{}> {{
   const errors: ValidationError[] = [];
 
-  // TODO: Add invariant validation logic based on model equations
-  //
-  // Example invariant:
-  // if (entity.totalPrice !== entity.items.reduce((sum, item) => sum + item.price, 0)) {{
-  //   errors.push({{
-  //     invariant: 'totalPrice',
-  //     message: 'totalPrice must equal sum of item prices',
-  //   }});
-  // }}
-
+{}
   if (errors.length > 0) {{
     return validationFailure(errors);
   }}
@@ -719,7 +732,8 @@ type Brand<T, B> = T & { readonly [__brand]: B };
             root_name,
             root_name,
             root_name,
-            root_name
+            root_name,
+            checks
         ));
 
         // Generate Zod schema with refinement if configured
@@ -742,18 +756,111 @@ type Brand<T, B> = T & { readonly [__brand]: B };
         }
     }
 
+    /// Checks for the strict invariants of an aggregate, followed by its
+    /// eventual invariants as comments, or `None` if it declares none.
+    fn invariant_checks(&self, root_id: ObjectId) -> Option<String> {
+        let invariants = self.context.invariants_of(root_id);
+        if invariants.is_empty() {
+            return None;
+        }
+
+        let mut checks = String::new();
+        let (strict, eventual): (Vec<_>, Vec<_>) = invariants
+            .into_iter()
+            .partition(|inv| inv.enforcement == Enforcement::Strict);
+
+        for invariant in strict {
+            checks.push_str(&format!(
+                "  if (!({})) {{\n    errors.push({{\n      invariant: {:?},\n      message: 'strict invariant violated',\n    }});\n  }}\n",
+                ts_expr(&invariant.expression, "entity"),
+                invariant.expression.to_string()
+            ));
+        }
+
+        if !eventual.is_empty() {
+            checks.push_str("  // Eventual invariants are restored asynchronously and not checked here:\n");
+            for invariant in eventual {
+                checks.push_str(&format!("  // - {}\n", invariant.expression));
+            }
+        }
+
+        Some(checks)
+    }
+
+    fn write_derived_morphisms(&mut self) {
+        let context = self.context;
+        let export = if self.config.use_named_exports { "export " } else { "" };
+
+        // Only entities and value objects are generated as interfaces
+        let derivations: Vec<(&Morphism, &Expr)> = context
+            .entities()
+            .iter()
+            .chain(context.value_objects())
+            .flat_map(|&id| context.derivations_from(id))
+            .collect();
+
+        if derivations.is_empty() {
+            return;
+        }
+
+        self.output.push_str("// =============================================================\n");
+        self.output.push_str("// Derived Morphisms\n");
+        self.output.push_str("// =============================================================\n\n");
+
+        for (morphism, expression) in derivations {
+            let source = self.object_names.get(&morphism.source).cloned().unwrap_or_default();
+            let param = lower_first(&source);
+            let deprecated = match context.removal_of(morphism.source, Some(&morphism.name)) {
+                Some(removal) => format!(" *\n * @deprecated {}\n", removal.note()),
+                None => String::new(),
+            };
+
+            self.output.push_str(&format!(
+                r#"/**
+ * Derived: {source}.{name}
+ *
+ * Computed as `{expression}`.
+{deprecated} */
+{export}function {param}{function}({param}: {source}): {target} {{
+  return {body};
+}}
+
+"#,
+                name = morphism.name,
+                function = upper_first(&morphism.name),
+                target = self.ts_type_for(morphism),
+                body = ts_expr(expression, &param),
+            ));
+        }
+    }
+
     fn format_interface_field(&self, morphism: &Morphism) -> String {
         let field_name = to_camel_case(&morphism.name);
-        let field_type = self.ts_type_for_target(morphism.target);
+        let field_type = self.ts_type_for(morphism);
+
+        let removal = self.context.removal_of(morphism.source, Some(&morphism.name));
 
         let mut result = String::new();
-        if let Some(desc) = &morphism.description {
-            result.push_str(&format!("  /** {} */\n", desc));
+        match (&morphism.description, removal) {
+            (Some(desc), Some(removal)) => {
+                result.push_str(&format!("  /**\n   * {}\n   * @deprecated {}\n   */\n", desc, removal.note()));
+            }
+            (Some(desc), None) => result.push_str(&format!("  /** {} */\n", desc)),
+            (None, Some(removal)) => result.push_str(&format!("  /** @deprecated {} */\n", removal.note())),
+            (None, None) => {}
         }
         result.push_str(&format!("  readonly {}: {};\n", field_name, field_type));
         result
     }
 
+    /// Doc comment lines marking a type scheduled for removal as deprecated.
+    fn deprecated_doc(&self, object_id: ObjectId) -> String {
+        match self.context.removal_of(object_id, None) {
+            Some(removal) => format!(" *\n * @deprecated {}\n", removal.note()),
+            None => String::new(),
+        }
+    }
+
     fn format_schema_field(&self, morphism: &Morphism) -> String {
         let field_name = to_camel_case(&morphism.name);
         let target = morphism.target;
@@ -776,13 +883,34 @@ type Brand<T, B> = T & { readonly [__brand]: B };
                 .unwrap_or_else(|| "Unknown".to_string());
             format!("{}Schema", target_name)
         } else {
-            // Unknown type - use unknown schema
-            "z.unknown()".to_string()
+            let constraints = self.context.field_constraints(morphism.source, &morphism.name);
+            if constraints.is_empty() {
+                // Unknown type - use unknown schema
+                "z.unknown()".to_string()
+            } else {
+                zod_constrained_schema(constraints)
+            }
+        };
+        let schema = match self.context.collection_of(morphism.id) {
+            Some(Collection::List) => format!("z.array({})", schema),
+            Some(Collection::Set) => format!("z.set({})", schema),
+            None => schema,
         };
 
         format!("  {}: {},\n", field_name, schema)
     }
 
+    /// Get the type of a morphism's field: its target, or a collection of
+    /// its target when the morphism is to-many.
+    fn ts_type_for(&self, morphism: &Morphism) -> String {
+        let element = self.ts_type_for_target(morphism.target);
+        match self.context.collection_of(morphism.id) {
+            Some(Collection::List) => format!("{}[]", element),
+            Some(Collection::Set) => format!("Set<{}>", element),
+            None => element,
+        }
+    }
+
     fn ts_type_for_target(&self, target: ObjectId) -> String {
         let target_name = self
             .object_names
@@ -799,6 +927,91 @@ type Brand<T, B> = T & { readonly [__brand]: B };
     }
 }
 
+/// Build a Zod schema refined by literal field constraints.
+///
+/// Text constraints produce a `z.string()` schema, otherwise `z.number()`.
+/// Patterns are anchored so the whole value must match.
+fn zod_constrained_schema(constraints: &[FieldConstraint]) -> String {
+    let textual = constraints.iter().any(|c| c.is_textual());
+    let mut schema = if textual { "z.string()" } else { "z.number()" }.to_string();
+
+    for constraint in constraints {
+        match constraint {
+            FieldConstraint::Min(v) if !textual => schema.push_str(&format!(".min({})", v)),
+            FieldConstraint::Max(v) if !textual => schema.push_str(&format!(".max({})", v)),
+            FieldConstraint::MinLength(n) => schema.push_str(&format!(".min({})", n)),
+            FieldConstraint::MaxLength(n) => schema.push_str(&format!(".max({})", n)),
+            FieldConstraint::Pattern(p) => {
+                schema.push_str(&format!(".regex(/^(?:{})$/)", p.replace('/', "\\/")))
+            }
+            _ => {}
+        }
+    }
+
+    schema
+}
+
+/// Render the expression of a derived morphism as TypeScript over `param`.
+///
+/// Aggregate functions take a path whose first step is an array field, so
+/// `sum(items.price)` sums `price` over the elements of `param.items`.
+fn ts_expr(expr: &Expr, param: &str) -> String {
+    let operand = |expr: &Expr| match expr {
+        Expr::Binary { .. } => format!("({})", ts_expr(expr, param)),
+        _ => ts_expr(expr, param),
+    };
+    let path = |path: &[String]| path.iter().map(|p| to_camel_case(p)).collect::<Vec<_>>().join(".");
+
+    match expr {
+        Expr::Number(n) => n.to_string(),
+        Expr::Text(s) => format!("{:?}", s),
+        Expr::Path(p) => format!("{}.{}", param, path(p)),
+        Expr::Binary { op, left, right } => {
+            let op = match op {
+                BinaryOp::Eq => "===",
+                BinaryOp::Ne => "!==",
+                op => op.symbol(),
+            };
+            format!("{} {} {}", operand(left), op, operand(right))
+        }
+        Expr::Unary { op, operand: inner } => format!("{}{}", op, operand(inner)),
+        Expr::Call { function, args } => match (function.as_str(), args.as_slice()) {
+            ("count", [Expr::Path(p)]) => format!("{}.{}.length", param, to_camel_case(&p[0])),
+            (function, [Expr::Path(p)]) => {
+                let collection = format!("{}.{}", param, to_camel_case(&p[0]));
+                let item = if p.len() > 1 { format!("item.{}", path(&p[1..])) } else { "item".to_string() };
+                match function {
+                    "sum" => format!("{}.reduce((total, item) => total + {}, 0)", collection, item),
+                    _ => format!("Math.{}(...{}.map((item) => {}))", function, collection, item),
+                }
+            }
+            (function, args) => {
+                let args: Vec<String> = args.iter().map(|a| ts_expr(a, param)).collect();
+                format!("{}({})", function, args.join(", "))
+            }
+        },
+        Expr::Index { target, index } => format!("{}[{}]", operand(target), ts_expr(index, param)),
+    }
+}
+
+/// Lowercase the first character: `LineItem` becomes `lineItem`.
+fn lower_first(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Uppercase the first character: `unitPrice` becomes `UnitPrice`.
+fn upper_first(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
 /// Convert PascalCase or snake_case to camelCase.
 fn to_camel_case(s: &str) -> String {
     let mut result = String::with_capacity(s.len());
@@ -825,6 +1038,7 @@ fn to_camel_case(s: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use sketchddd_core::ModelVersion;
 
     #[test]
     fn test_to_camel_case() {
@@ -884,6 +1098,19 @@ mod tests {
         assert!(result.contains("readonly placedBy: CustomerId"));
     }
 
+    #[test]
+    fn test_generate_collection_fields() {
+        let context = crate::parse_context(crate::COLLECTION_FIELDS);
+
+        let result = generate(&context).unwrap();
+
+        assert!(result.contains("readonly items: LineItemId[];"));
+        assert!(result.contains("readonly tags: Set<TagId>;"));
+        assert!(result.contains("tags: z.set(TagIdSchema),"));
+        assert!(result.contains("readonly amounts: Money[];"));
+        assert!(result.contains("amounts: z.array(MoneySchema),"));
+    }
+
     #[test]
     fn test_generate_value_object() {
         let mut context = BoundedContext::new("Commerce");
@@ -901,6 +1128,84 @@ mod tests {
         assert!(result.contains("const MoneySchema = z.object({"));
     }
 
+    #[test]
+    fn test_generate_field_constraints() {
+        let mut context = BoundedContext::new("Billing");
+        let money = context.add_value_object("Money");
+        let decimal = context.sketch_mut().add_object("Decimal");
+        let string = context.sketch_mut().add_object("String");
+        context.sketch_mut().graph.add_morphism("amount", money, decimal);
+        context.sketch_mut().graph.add_morphism("currency", money, string);
+        context.constrain_field(money, "amount", FieldConstraint::Min(0.0));
+        context.constrain_field(money, "currency", FieldConstraint::Pattern("[A-Z]{3}".into()));
+        context.constrain_field(money, "currency", FieldConstraint::MaxLength(3));
+
+        let result = generate(&context).unwrap();
+        assert!(result.contains("amount: z.number().min(0),"));
+        assert!(result.contains("currency: z.string().regex(/^(?:[A-Z]{3})$/).max(3),"));
+    }
+
+    #[test]
+    fn test_generate_constraints_of_declared_fields() {
+        let context = crate::parse_context(crate::CONSTRAINED_FIELDS);
+        let result = generate(&context).unwrap();
+
+        assert!(result.contains("amount: z.number().min(0),"));
+        assert!(result.contains("currency: z.string().regex(/^(?:[A-Z]{3})$/),"));
+        assert!(result.contains("balance: z.number().max(1000),"));
+    }
+
+    #[test]
+    fn test_generate_deprecation_markers() {
+        let mut context = BoundedContext::new("Billing");
+        let invoice = context.add_entity("Invoice");
+        let string = context.sketch_mut().add_object("String");
+        context.sketch_mut().graph.add_morphism("legacy_code", invoice, string);
+        context.schedule_removal(invoice, None, ModelVersion::new(3, 0, 0));
+        context.schedule_removal(invoice, Some("legacy_code"), ModelVersion::new(2, 1, 0));
+
+        let result = generate(&context).unwrap();
+
+        assert!(result.contains(" *\n * @deprecated Removed in 3.0\n */\nexport interface Invoice {"));
+        assert!(result.contains("  /** @deprecated Removed in 2.1 */\n  readonly legacyCode: String;"));
+    }
+
+    #[test]
+    fn test_generate_derived_morphisms() {
+        let mut context = BoundedContext::new("Commerce");
+        let order = context.add_entity("Order");
+        let line_item = context.add_entity("LineItem");
+        let money = context.add_value_object("Money");
+        let count = context.sketch_mut().add_object("Count");
+        context.sketch_mut().graph.add_morphism("items", order, line_item);
+        context.sketch_mut().graph.add_morphism("unit_price", line_item, money);
+        let total = context.sketch_mut().graph.add_morphism("total", order, money);
+        let size = context.sketch_mut().graph.add_morphism("size", order, count);
+        context.derive_morphism(
+            total,
+            Expr::Call {
+                function: "sum".into(),
+                args: vec![Expr::Path(vec!["items".into(), "unit_price".into()])],
+            },
+        );
+        context.derive_morphism(
+            size,
+            Expr::Call {
+                function: "count".into(),
+                args: vec![Expr::Path(vec!["items".into()])],
+            },
+        );
+
+        let result = generate(&context).unwrap();
+
+        assert!(!result.contains("readonly total: Money;"));
+        assert!(result.contains(" * Computed as `sum(items.unit_price)`.\n"));
+        assert!(result.contains(
+            "export function orderTotal(order: Order): Money {\n  return order.items.reduce((total, item) => total + item.unitPrice, 0);\n}"
+        ));
+        assert!(result.contains("export function orderSize(order: Order): Count {\n  return order.items.length;\n}"));
+    }
+
     #[test]
     fn test_generate_simple_enum() {
         let mut context = BoundedContext::new("Commerce");
@@ -950,6 +1255,33 @@ mod tests {
         assert!(result.contains("const OrderAggregateSchema = OrderSchema.refine("));
     }
 
+    #[test]
+    fn test_generate_aggregate_invariants() {
+        let mut context = BoundedContext::new("Commerce");
+        let order = context.add_entity("Order");
+        context.define_aggregate("OrderAggregate", order);
+        context.add_aggregate_invariant(
+            order,
+            Expr::Binary {
+                op: BinaryOp::Ge,
+                left: Box::new(Expr::Path(vec!["total".into()])),
+                right: Box::new(Expr::Number(0.0)),
+            },
+            Enforcement::Strict,
+        );
+        context.add_aggregate_invariant(
+            order,
+            Expr::Path(vec!["customer".into(), "active".into()]),
+            Enforcement::Eventual,
+        );
+
+        let result = generate(&context).unwrap();
+
+        assert!(!result.contains("// TODO: Add invariant validation logic"));
+        assert!(result.contains("  if (!(entity.total >= 0)) {\n    errors.push({\n      invariant: \"total >= 0\",\n"));
+        assert!(result.contains("  // - customer.active\n"));
+    }
+
     #[test]
     fn test_generate_commerce_domain() {
         let mut context = BoundedContext::new("Commerce");
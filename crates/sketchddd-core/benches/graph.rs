@@ -0,0 +1,53 @@
+//! Benchmarks of graph operations on a large model.
+//!
+//! Run with `cargo bench -p sketchddd-core`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use sketchddd_core::sketch::{Graph, ObjectId};
+
+/// Number of objects in the benchmarked graph.
+const OBJECTS: usize = 10_000;
+
+/// Build a graph of `objects` entities, each with an identity and a few
+/// morphisms to its neighbours, reusing field names the way models do.
+fn large_graph(objects: usize) -> Graph {
+    let mut graph = Graph::new();
+    let ids: Vec<ObjectId> = (0..objects)
+        .map(|i| graph.add_object(format!("Object{}", i)))
+        .collect();
+    for (i, &id) in ids.iter().enumerate() {
+        graph.add_identity_morphism(id);
+        for (field, step) in [("parent", 1), ("owner", 7), ("related", 31)] {
+            graph.add_morphism(field, id, ids[(i + step) % objects]);
+        }
+    }
+    graph
+}
+
+fn bench_graph(c: &mut Criterion) {
+    c.bench_function("build 10k objects", |b| {
+        b.iter(|| large_graph(black_box(OBJECTS)))
+    });
+
+    let graph = large_graph(OBJECTS);
+    let ids: Vec<ObjectId> = graph.objects().map(|o| o.id).collect();
+
+    c.bench_function("get every object", |b| {
+        b.iter(|| {
+            ids.iter()
+                .filter_map(|&id| graph.get_object(id))
+                .map(|o| o.name.len())
+                .sum::<usize>()
+        })
+    });
+    c.bench_function("iterate morphisms", |b| {
+        b.iter(|| graph.morphisms().filter(|m| m.name == "owner").count())
+    });
+    c.bench_function("clone 10k objects", |b| b.iter(|| graph.clone()));
+    c.bench_function("serialize 10k objects", |b| {
+        b.iter(|| serde_json::to_vec(&graph).unwrap())
+    });
+}
+
+criterion_group!(benches, bench_graph);
+criterion_main!(benches);
@@ -0,0 +1,429 @@
+//! Graph algorithms over a sketch's objects and morphisms.
+//!
+//! These treat the graph of a context as a directed graph whose edges are
+//! its non-identity morphisms. Lint rules use them to find cycles,
+//! visualizations to cluster objects, and code generators to order the
+//! types they emit.
+
+use crate::context::BoundedContext;
+use crate::sketch::{Graph, MorphismId, ObjectId};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+
+/// Non-identity morphisms grouped by source, both in id order.
+fn adjacency(graph: &Graph) -> BTreeMap<ObjectId, Vec<(MorphismId, ObjectId)>> {
+    let mut adjacency: BTreeMap<ObjectId, Vec<(MorphismId, ObjectId)>> =
+        graph.objects().map(|o| (o.id, Vec::new())).collect();
+    for morphism in graph.morphisms().filter(|m| !m.is_identity) {
+        adjacency
+            .entry(morphism.source)
+            .or_default()
+            .push((morphism.id, morphism.target));
+    }
+    for edges in adjacency.values_mut() {
+        edges.sort();
+    }
+    adjacency
+}
+
+/// Get the objects reachable from `start` by following morphisms.
+///
+/// `start` itself is included, reached by the empty path.
+pub fn objects_reachable_from(graph: &Graph, start: ObjectId) -> BTreeSet<ObjectId> {
+    let adjacency = adjacency(graph);
+    let mut reached = BTreeSet::from([start]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(object) = queue.pop_front() {
+        for &(_, target) in adjacency.get(&object).into_iter().flatten() {
+            if reached.insert(target) {
+                queue.push_back(target);
+            }
+        }
+    }
+    reached
+}
+
+/// Get the strongly connected components of the graph.
+///
+/// Objects in the same component are reachable from each other. The
+/// components are in topological order: every morphism between two
+/// components goes from an earlier one to a later one. Objects within a
+/// component are sorted by id.
+pub fn strongly_connected_components(graph: &Graph) -> Vec<Vec<ObjectId>> {
+    let adjacency = adjacency(graph);
+    let nodes: Vec<ObjectId> = adjacency.keys().copied().collect();
+    let mut components = tarjan(&nodes, |object| {
+        adjacency
+            .get(&object)
+            .into_iter()
+            .flatten()
+            .map(|&(_, target)| target)
+            .collect()
+    });
+    // Tarjan's algorithm finds components that others point to first
+    components.reverse();
+    components
+}
+
+/// Find the cycles of morphisms in the graph.
+///
+/// Returns one closed path for every strongly connected component that
+/// contains a cycle, starting and ending at the component's smallest
+/// object. A morphism from an object to itself is a cycle of length one;
+/// identities are not.
+pub fn find_cycles(graph: &Graph) -> Vec<Vec<MorphismId>> {
+    let adjacency = adjacency(graph);
+    strongly_connected_components(graph)
+        .into_iter()
+        .filter_map(|component| {
+            let members: BTreeSet<ObjectId> = component.iter().copied().collect();
+            shortest_cycle(&adjacency, component[0], &members)
+        })
+        .collect()
+}
+
+/// Find a shortest closed path through `start` that stays within `members`.
+fn shortest_cycle(
+    adjacency: &BTreeMap<ObjectId, Vec<(MorphismId, ObjectId)>>,
+    start: ObjectId,
+    members: &BTreeSet<ObjectId>,
+) -> Option<Vec<MorphismId>> {
+    let mut parent: HashMap<ObjectId, (MorphismId, ObjectId)> = HashMap::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(object) = queue.pop_front() {
+        for &(morphism, target) in adjacency.get(&object).into_iter().flatten() {
+            if target == start {
+                let mut cycle = vec![morphism];
+                let mut current = object;
+                while current != start {
+                    let (morphism, previous) = parent[&current];
+                    cycle.push(morphism);
+                    current = previous;
+                }
+                cycle.reverse();
+                return Some(cycle);
+            }
+            if members.contains(&target) && !parent.contains_key(&target) {
+                parent.insert(target, (morphism, object));
+                queue.push_back(target);
+            }
+        }
+    }
+    None
+}
+
+/// Order the aggregates of a context by their dependencies.
+///
+/// An aggregate depends on another when a morphism leads from its
+/// boundary (its root and the targets of its projections) into the
+/// other's. The result groups aggregate roots so that each group depends
+/// only on itself and on earlier groups; aggregates that depend on each
+/// other share a group.
+pub fn aggregate_dependency_order(context: &BoundedContext) -> Vec<Vec<ObjectId>> {
+    let boundaries = aggregate_boundaries(context);
+    let mut owners: HashMap<ObjectId, Vec<ObjectId>> = HashMap::new();
+    for (&root, boundary) in &boundaries {
+        for &object in boundary {
+            owners.entry(object).or_default().push(root);
+        }
+    }
+
+    let adjacency = adjacency(context.graph());
+    let roots: Vec<ObjectId> = boundaries.keys().copied().collect();
+    // Dependencies are found before their dependents, which is the order wanted
+    tarjan(&roots, |root| {
+        let dependencies: BTreeSet<ObjectId> = boundaries[&root]
+            .iter()
+            .flat_map(|object| adjacency.get(object).into_iter().flatten())
+            .flat_map(|(_, target)| owners.get(target).into_iter().flatten())
+            .copied()
+            .filter(|&other| other != root)
+            .collect();
+        dependencies.into_iter().collect()
+    })
+}
+
+/// A stored morphism leading into an aggregate's internal entity from
+/// outside the aggregate.
+///
+/// Only the root of an aggregate may be referenced from outside. Holding a
+/// reference to an internal entity couples the holder to the aggregate
+/// transactionally: changing the entity through it bypasses the root that
+/// guards the aggregate's invariants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoundaryCrossing {
+    /// The crossing morphism
+    pub morphism: MorphismId,
+
+    /// Root of the aggregate the morphism leaves, or `None` if its source
+    /// belongs to no aggregate
+    pub from: Option<ObjectId>,
+
+    /// Root of the aggregate the morphism reaches into
+    pub into: ObjectId,
+
+    /// The internal entity reached
+    pub target: ObjectId,
+}
+
+/// Find the morphisms that cross aggregate boundaries into an aggregate's
+/// internal entities.
+///
+/// A morphism crosses when its target is a non-root entity of an aggregate
+/// its source is outside of, whether the source belongs to another
+/// aggregate or to none. Derived morphisms only read
+/// through the graph and are not reported; neither are references to
+/// another aggregate's root, which is how aggregates are meant to refer to
+/// each other. Crossings are in morphism id order.
+pub fn aggregate_boundary_crossings(context: &BoundedContext) -> Vec<BoundaryCrossing> {
+    let boundaries = aggregate_boundaries(context);
+    let owner = |object: ObjectId| {
+        boundaries
+            .iter()
+            .filter(move |(_, boundary)| boundary.contains(&object))
+            .map(|(&root, _)| root)
+    };
+
+    let mut morphisms: Vec<_> = context
+        .graph()
+        .morphisms()
+        .filter(|m| !m.is_identity && !context.is_derived(m.id))
+        .collect();
+    morphisms.sort_by_key(|m| m.id);
+
+    let mut crossings = Vec::new();
+    for morphism in morphisms {
+        let target = morphism.target;
+        if !context.is_entity(target) || boundaries.contains_key(&target) {
+            continue;
+        }
+        let mut sources: Vec<Option<ObjectId>> = owner(morphism.source).map(Some).collect();
+        if sources.is_empty() {
+            sources.push(None);
+        }
+        for from in sources {
+            for into in owner(target) {
+                if !boundaries[&into].contains(&morphism.source) {
+                    crossings.push(BoundaryCrossing {
+                        morphism: morphism.id,
+                        from,
+                        into,
+                        target,
+                    });
+                }
+            }
+        }
+    }
+    crossings
+}
+
+/// The objects of each aggregate, keyed by root: the root itself and the
+/// targets of its projections.
+fn aggregate_boundaries(context: &BoundedContext) -> BTreeMap<ObjectId, BTreeSet<ObjectId>> {
+    let mut boundaries: BTreeMap<ObjectId, BTreeSet<ObjectId>> = BTreeMap::new();
+    for &root in context.aggregate_roots() {
+        let boundary = boundaries.entry(root).or_default();
+        boundary.insert(root);
+        if let Some(aggregate) = context.get_aggregate(root) {
+            boundary.extend(aggregate.component_objects());
+        }
+    }
+    boundaries
+}
+
+/// Tarjan's strongly connected components algorithm.
+///
+/// Components are returned in reverse topological order: a component comes
+/// after every component reachable from it.
+fn tarjan<F>(nodes: &[ObjectId], successors: F) -> Vec<Vec<ObjectId>>
+where
+    F: Fn(ObjectId) -> Vec<ObjectId>,
+{
+    struct State<F> {
+        successors: F,
+        index: HashMap<ObjectId, usize>,
+        lowlink: HashMap<ObjectId, usize>,
+        stack: Vec<ObjectId>,
+        on_stack: BTreeSet<ObjectId>,
+        components: Vec<Vec<ObjectId>>,
+    }
+
+    fn visit<F: Fn(ObjectId) -> Vec<ObjectId>>(state: &mut State<F>, node: ObjectId) {
+        let index = state.index.len();
+        state.index.insert(node, index);
+        state.lowlink.insert(node, index);
+        state.stack.push(node);
+        state.on_stack.insert(node);
+
+        for next in (state.successors)(node) {
+            if !state.index.contains_key(&next) {
+                visit(state, next);
+                let low = state.lowlink[&node].min(state.lowlink[&next]);
+                state.lowlink.insert(node, low);
+            } else if state.on_stack.contains(&next) {
+                let low = state.lowlink[&node].min(state.index[&next]);
+                state.lowlink.insert(node, low);
+            }
+        }
+
+        if state.lowlink[&node] == state.index[&node] {
+            let mut component = Vec::new();
+            while let Some(member) = state.stack.pop() {
+                state.on_stack.remove(&member);
+                component.push(member);
+                if member == node {
+                    break;
+                }
+            }
+            component.sort();
+            state.components.push(component);
+        }
+    }
+
+    let mut state = State {
+        successors,
+        index: HashMap::new(),
+        lowlink: HashMap::new(),
+        stack: Vec::new(),
+        on_stack: BTreeSet::new(),
+        components: Vec::new(),
+    };
+    for &node in nodes {
+        if !state.index.contains_key(&node) {
+            visit(&mut state, node);
+        }
+    }
+    state.components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Order -> Customer -> Address, with Customer <-> Account in a cycle.
+    fn commerce() -> (Graph, [ObjectId; 4], [MorphismId; 3]) {
+        let mut graph = Graph::new();
+        let order = graph.add_object("Order");
+        let customer = graph.add_object("Customer");
+        let account = graph.add_object("Account");
+        let address = graph.add_object("Address");
+        graph.add_identity_morphism(order);
+        graph.add_morphism("placedBy", order, customer);
+        let account_of = graph.add_morphism("account", customer, account);
+        let holder = graph.add_morphism("holder", account, customer);
+        let address_of = graph.add_morphism("address", customer, address);
+        (
+            graph,
+            [order, customer, account, address],
+            [account_of, holder, address_of],
+        )
+    }
+
+    #[test]
+    fn test_objects_reachable_from() {
+        let (graph, [order, customer, account, address], _) = commerce();
+
+        assert_eq!(
+            objects_reachable_from(&graph, customer),
+            BTreeSet::from([customer, account, address])
+        );
+        assert_eq!(objects_reachable_from(&graph, order).len(), 4);
+        assert_eq!(
+            objects_reachable_from(&graph, address),
+            BTreeSet::from([address])
+        );
+        assert!(!objects_reachable_from(&graph, account).contains(&order));
+    }
+
+    #[test]
+    fn test_strongly_connected_components() {
+        let (graph, [order, customer, account, address], _) = commerce();
+
+        assert_eq!(
+            strongly_connected_components(&graph),
+            vec![vec![order], vec![customer, account], vec![address]]
+        );
+    }
+
+    #[test]
+    fn test_find_cycles() {
+        let (mut graph, [_, _, account, _], [account_of, holder, _]) = commerce();
+        assert_eq!(find_cycles(&graph), vec![vec![account_of, holder]]);
+
+        let parent = graph.add_morphism("parent", account, account);
+        let cycles = find_cycles(&graph);
+        assert_eq!(cycles.len(), 1);
+        assert!(cycles[0]
+            .iter()
+            .all(|m| [account_of, holder, parent].contains(m)));
+
+        let mut acyclic = Graph::new();
+        let customer = acyclic.add_object("Customer");
+        acyclic.add_identity_morphism(customer);
+        assert!(find_cycles(&acyclic).is_empty());
+    }
+
+    #[test]
+    fn test_aggregate_dependency_order() {
+        let mut ctx = BoundedContext::new("Commerce");
+        let order = ctx.add_entity("Order");
+        let line_item = ctx.add_entity("LineItem");
+        let product = ctx.add_entity("Product");
+        let customer = ctx.add_entity("Customer");
+        let wishlist = ctx.add_entity("Wishlist");
+        ctx.sketch_mut().add_morphism("product", line_item, product);
+        ctx.sketch_mut().add_morphism("placedBy", order, customer);
+        ctx.sketch_mut()
+            .add_morphism("wishlist", customer, wishlist);
+        ctx.sketch_mut().add_morphism("owner", wishlist, customer);
+
+        ctx.define_aggregate_with_members("Order", order, &[line_item]);
+        ctx.define_aggregate("Product", product);
+        ctx.define_aggregate("Customer", customer);
+        ctx.define_aggregate("Wishlist", wishlist);
+
+        let order_of = aggregate_dependency_order(&ctx);
+        let position = |root| order_of.iter().position(|g| g.contains(&root)).unwrap();
+        assert!(position(product) < position(order));
+        assert!(position(customer) < position(order));
+        assert_eq!(position(customer), position(wishlist));
+        assert_eq!(order_of.len(), 3);
+    }
+
+    #[test]
+    fn test_aggregate_boundary_crossings() {
+        let mut ctx = BoundedContext::new("Commerce");
+        let order = ctx.add_entity("Order");
+        let line_item = ctx.add_entity("LineItem");
+        let shipment = ctx.add_entity("Shipment");
+        let parcel = ctx.add_entity("Parcel");
+        let money = ctx.add_value_object("Money");
+        ctx.sketch_mut().add_morphism("price", line_item, money);
+        let contents = ctx.sketch_mut().add_morphism("contents", parcel, line_item);
+        ctx.sketch_mut().add_morphism("order", shipment, order);
+        let review = ctx.add_entity("Review");
+        let reviewed = ctx.sketch_mut().add_morphism("item", review, line_item);
+
+        ctx.define_aggregate_with_members("Order", order, &[line_item]);
+        ctx.define_aggregate_with_members("Shipment", shipment, &[parcel]);
+
+        // Referencing the Order root is fine; reaching its line items is
+        // not, from another aggregate or from outside any
+        assert_eq!(
+            aggregate_boundary_crossings(&ctx),
+            vec![
+                BoundaryCrossing {
+                    morphism: contents,
+                    from: Some(shipment),
+                    into: order,
+                    target: line_item,
+                },
+                BoundaryCrossing {
+                    morphism: reviewed,
+                    from: None,
+                    into: order,
+                    target: line_item,
+                },
+            ]
+        );
+    }
+}
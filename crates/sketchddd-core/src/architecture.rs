@@ -0,0 +1,456 @@
+//! Exports to architecture portals.
+//!
+//! Teams often keep a catalog of their systems in a portal such as
+//! [Structurizr](https://structurizr.com) or
+//! [Backstage](https://backstage.io). These exports describe the strategic
+//! design of a model in the terms of those tools, so the portal can show
+//! it next to everything else:
+//!
+//! | Model | Structurizr | Backstage |
+//! |-------|-------------|-----------|
+//! | Domain | Group | Domain |
+//! | Subdomain | Software system | System |
+//! | Bounded context | Container | Component |
+//! | Context map | Relationship | `dependsOn` |
+//!
+//! Structurizr has components only inside containers, so contexts become
+//! containers there. A context map makes the downstream context depend on
+//! the upstream one, and each side depend on the other for a symmetric
+//! pattern; maps of separate ways are left out. Owners, descriptions and
+//! lifecycles are taken from the `owner`, `description` and `lifecycle`
+//! metadata of contexts and subdomains.
+
+use crate::domain::Subdomain;
+use crate::mapping::RelationshipPattern;
+use crate::metadata::{Metadata, MetadataValue};
+use crate::naming::to_kebab_case;
+use crate::workspace::Workspace;
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Owner of Backstage entities whose metadata declares none, which
+/// Backstage requires.
+const UNKNOWN_OWNER: &str = "unknown";
+
+/// Get the text of a metadata value.
+fn text(metadata: &Metadata, key: &str) -> Option<String> {
+    metadata.get(key).map(MetadataValue::to_string)
+}
+
+/// Get the dependencies between contexts as pairs of the context that
+/// depends and the one it depends on, with the map they come from.
+fn dependencies(workspace: &Workspace) -> Vec<(&str, &str, &str, String)> {
+    let mut dependencies = Vec::new();
+    for map in &workspace.context_maps {
+        let pattern = map.pattern();
+        if pattern == RelationshipPattern::SeparateWays {
+            continue;
+        }
+        let (source, target) = (map.source_context(), map.target_context());
+        let name = format!("{:?}", pattern);
+        dependencies.push((target, source, map.name(), name.clone()));
+        if pattern.is_symmetric() {
+            dependencies.push((source, target, map.name(), name));
+        }
+    }
+    dependencies
+}
+
+/// Get the subdomain of a context, with the domain it is part of.
+fn subdomain_of<'a>(workspace: &'a Workspace, context: &str) -> Option<(&'a str, &'a Subdomain)> {
+    workspace.domains.iter().find_map(|domain| {
+        domain
+            .subdomain_of(context)
+            .map(|subdomain| (domain.name.as_str(), subdomain))
+    })
+}
+
+/// Get the Structurizr tags of an element: its kind, its classification
+/// and its own tags.
+fn structurizr_tags(kinds: &[&str], metadata: &Metadata) -> String {
+    let mut tags: Vec<String> = kinds.iter().map(|kind| kind.to_string()).collect();
+    if let Some(classification) = metadata.classification {
+        let classification = classification.to_string();
+        let mut chars = classification.chars();
+        tags.extend(
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect()),
+        );
+    }
+    tags.extend(metadata.tags.iter().cloned());
+    tags.join(",")
+}
+
+/// Start a Structurizr element with its id, name, tags, and the
+/// description and properties of its metadata.
+fn structurizr_element(
+    id: usize,
+    name: &str,
+    kinds: &[&str],
+    metadata: &Metadata,
+) -> Map<String, Value> {
+    let mut element = Map::new();
+    element.insert("id".into(), json!(id.to_string()));
+    element.insert("name".into(), json!(name));
+    element.insert("tags".into(), json!(structurizr_tags(kinds, metadata)));
+    if let Some(description) = text(metadata, "description") {
+        element.insert("description".into(), json!(description));
+    }
+    let properties: Map<String, Value> = metadata
+        .values
+        .iter()
+        .filter(|(key, _)| key.as_str() != "description")
+        .map(|(key, value)| (key.clone(), json!(value.to_string())))
+        .collect();
+    if !properties.is_empty() {
+        element.insert("properties".into(), Value::Object(properties));
+    }
+    element
+}
+
+/// Write a model as a Structurizr workspace, in its JSON format.
+///
+/// Contexts outside every subdomain go in a software system named after
+/// the model. Relationships between contexts of different subdomains are
+/// implied between the subdomains as well, as Structurizr itself does,
+/// and the workspace has a system landscape view of the subdomains.
+pub fn structurizr(workspace: &Workspace, name: &str) -> Value {
+    let mut ids = 0;
+    let mut next_id = || {
+        ids += 1;
+        ids
+    };
+
+    // Software systems, by name, with their group and metadata
+    let mut systems: Vec<(String, Option<&str>, Metadata)> = Vec::new();
+    let mut system_of = BTreeMap::new();
+    for context in &workspace.contexts {
+        let (group, system, metadata) = match subdomain_of(workspace, context.name()) {
+            Some((domain, subdomain)) => (
+                Some(domain),
+                subdomain.name.clone(),
+                subdomain.metadata.clone(),
+            ),
+            None => (None, name.to_string(), Metadata::default()),
+        };
+        let index = match systems.iter().position(|(s, _, _)| *s == system) {
+            Some(index) => index,
+            None => {
+                systems.push((system, group, metadata));
+                systems.len() - 1
+            }
+        };
+        system_of.insert(context.name(), index);
+    }
+
+    let system_ids: Vec<usize> = systems.iter().map(|_| next_id()).collect();
+    let mut container_ids = BTreeMap::new();
+    for context in &workspace.contexts {
+        container_ids.insert(context.name(), next_id());
+    }
+
+    // Relationships between containers, and those they imply between
+    // software systems, once per pair
+    let mut container_relationships: BTreeMap<&str, Vec<Value>> = BTreeMap::new();
+    let mut system_relationships: BTreeMap<usize, Vec<Value>> = BTreeMap::new();
+    let mut implied = BTreeSet::new();
+    let mut landscape_relationships = Vec::new();
+    for (from, to, map, pattern) in dependencies(workspace) {
+        let (Some(&source), Some(&destination)) = (container_ids.get(from), container_ids.get(to))
+        else {
+            continue;
+        };
+        container_relationships
+            .entry(from)
+            .or_default()
+            .push(json!({
+                "id": next_id().to_string(),
+                "sourceId": source.to_string(),
+                "destinationId": destination.to_string(),
+                "description": map,
+                "technology": pattern,
+                "tags": "Relationship",
+            }));
+
+        let (from_system, to_system) = (system_of[from], system_of[to]);
+        if from_system != to_system && implied.insert((from_system, to_system)) {
+            let id = next_id().to_string();
+            system_relationships
+                .entry(from_system)
+                .or_default()
+                .push(json!({
+                    "id": id,
+                    "sourceId": system_ids[from_system].to_string(),
+                    "destinationId": system_ids[to_system].to_string(),
+                    "description": map,
+                    "tags": "Relationship",
+                }));
+            landscape_relationships.push(json!({ "id": id }));
+        }
+    }
+
+    let software_systems: Vec<Value> = systems
+        .iter()
+        .enumerate()
+        .map(|(index, (system, group, metadata))| {
+            let mut element = structurizr_element(
+                system_ids[index],
+                system,
+                &["Element", "Software System"],
+                metadata,
+            );
+            if let Some(group) = group {
+                element.insert("group".into(), json!(group));
+            }
+            let containers: Vec<Value> = workspace
+                .contexts
+                .iter()
+                .filter(|context| system_of[context.name()] == index)
+                .map(|context| {
+                    let mut container = structurizr_element(
+                        container_ids[context.name()],
+                        context.name(),
+                        &["Element", "Container"],
+                        context.metadata(),
+                    );
+                    if let Some(relationships) = container_relationships.remove(context.name()) {
+                        container.insert("relationships".into(), json!(relationships));
+                    }
+                    Value::Object(container)
+                })
+                .collect();
+            element.insert("containers".into(), json!(containers));
+            if let Some(relationships) = system_relationships.remove(&index) {
+                element.insert("relationships".into(), json!(relationships));
+            }
+            Value::Object(element)
+        })
+        .collect();
+
+    let landscape_elements: Vec<Value> = system_ids
+        .iter()
+        .map(|id| json!({ "id": id.to_string() }))
+        .collect();
+
+    json!({
+        "name": name,
+        "model": { "softwareSystems": software_systems },
+        "views": {
+            "systemLandscapeViews": [{
+                "key": "Landscape",
+                "elements": landscape_elements,
+                "relationships": landscape_relationships,
+                "automaticLayout": {
+                    "implementation": "Graphviz",
+                    "rankDirection": "TopBottom",
+                },
+            }],
+        },
+    })
+}
+
+/// Start a Backstage entity of a kind, named after a model element.
+fn backstage_entity(kind: &str, name: &str, metadata: &Metadata) -> Value {
+    let mut entity_metadata = json!({
+        "name": to_kebab_case(name),
+        "title": name,
+    });
+    if let Some(description) = text(metadata, "description") {
+        entity_metadata["description"] = json!(description);
+    }
+    let tags: BTreeSet<String> = metadata
+        .classification
+        .map(|classification| classification.to_string())
+        .into_iter()
+        .chain(metadata.tags.iter().map(|tag| to_kebab_case(tag)))
+        .collect();
+    if !tags.is_empty() {
+        entity_metadata["tags"] = json!(tags);
+    }
+    json!({
+        "apiVersion": "backstage.io/v1alpha1",
+        "kind": kind,
+        "metadata": entity_metadata,
+        "spec": {
+            "owner": text(metadata, "owner").unwrap_or_else(|| UNKNOWN_OWNER.to_string()),
+        },
+    })
+}
+
+/// Write a model as Backstage catalog entities, one per document of a
+/// `catalog-info.yaml`.
+///
+/// Entities are named in kebab-case, as Backstage requires, and titled
+/// with the name in the model. Components are services; their lifecycle
+/// is `production` unless their metadata declares one.
+pub fn backstage(workspace: &Workspace) -> Vec<Value> {
+    let mut entities = Vec::new();
+    for domain in &workspace.domains {
+        entities.push(backstage_entity(
+            "Domain",
+            &domain.name,
+            &Metadata::default(),
+        ));
+        for subdomain in &domain.subdomains {
+            let mut system = backstage_entity("System", &subdomain.name, &subdomain.metadata);
+            system["spec"]["domain"] = json!(to_kebab_case(&domain.name));
+            entities.push(system);
+        }
+    }
+
+    let mut depends_on: BTreeMap<&str, BTreeSet<String>> = BTreeMap::new();
+    for (from, to, _, _) in dependencies(workspace) {
+        depends_on
+            .entry(from)
+            .or_default()
+            .insert(format!("component:{}", to_kebab_case(to)));
+    }
+
+    for context in &workspace.contexts {
+        let metadata = context.metadata();
+        let subdomain = subdomain_of(workspace, context.name()).map(|(_, s)| s);
+        let mut component = backstage_entity("Component", context.name(), metadata);
+        let spec = &mut component["spec"];
+        if metadata.get("owner").is_none() {
+            if let Some(owner) = subdomain.and_then(|s| text(&s.metadata, "owner")) {
+                spec["owner"] = json!(owner);
+            }
+        }
+        spec["type"] = json!("service");
+        spec["lifecycle"] =
+            json!(text(metadata, "lifecycle").unwrap_or_else(|| "production".to_string()));
+        if let Some(subdomain) = subdomain {
+            spec["system"] = json!(to_kebab_case(&subdomain.name));
+        }
+        if let Some(dependencies) = depends_on.get(context.name()) {
+            spec["dependsOn"] = json!(dependencies);
+        }
+        entities.push(component);
+    }
+    entities
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::BoundedContext;
+    use crate::domain::Domain;
+    use crate::mapping::NamedContextMap;
+    use crate::metadata::Classification;
+
+    fn model() -> Workspace {
+        let mut workspace = Workspace::new();
+        for name in ["Sales", "Billing", "Shipping"] {
+            let mut context = BoundedContext::new(name);
+            if name == "Billing" {
+                context
+                    .metadata_mut()
+                    .set("owner", MetadataValue::Text("payments-team".into()));
+            }
+            workspace.add_context(context);
+        }
+        workspace.add_context_map(NamedContextMap::new(
+            "SalesToBilling",
+            "Sales",
+            "Billing",
+            RelationshipPattern::CustomerSupplier,
+        ));
+        workspace.add_context_map(NamedContextMap::new(
+            "SalesAndShipping",
+            "Sales",
+            "Shipping",
+            RelationshipPattern::SeparateWays,
+        ));
+
+        let mut ordering = Subdomain::new("OrderManagement");
+        ordering.metadata.classification = Some(Classification::Core);
+        ordering
+            .metadata
+            .set("owner", MetadataValue::Text("orders-team".into()));
+        ordering.add_context("Sales");
+        let mut payments = Subdomain::new("Payments");
+        payments.add_context("Billing");
+        let mut domain = Domain::new("Commerce");
+        domain.add_subdomain(ordering);
+        domain.add_subdomain(payments);
+        workspace.domains.push(domain);
+        workspace
+    }
+
+    #[test]
+    fn test_structurizr() {
+        let value = structurizr(&model(), "commerce");
+        let systems = value["model"]["softwareSystems"].as_array().unwrap();
+        let names: Vec<&str> = systems
+            .iter()
+            .map(|s| s["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["OrderManagement", "Payments", "commerce"]);
+        assert_eq!(systems[0]["group"], "Commerce");
+        assert_eq!(systems[0]["tags"], "Element,Software System,Core");
+        assert_eq!(systems[0]["properties"]["owner"], "orders-team");
+        assert_eq!(systems[2]["containers"][0]["name"], "Shipping");
+
+        // Billing depends on Sales, its upstream; separate ways are left out
+        let billing = &systems[1]["containers"][0];
+        let relationships = billing["relationships"].as_array().unwrap();
+        assert_eq!(relationships.len(), 1);
+        assert_eq!(
+            relationships[0]["destinationId"],
+            systems[0]["containers"][0]["id"]
+        );
+        assert_eq!(relationships[0]["technology"], "CustomerSupplier");
+        assert!(systems[2]["containers"][0].get("relationships").is_none());
+
+        // ...which is implied between the subdomains, on the landscape
+        let implied = &systems[1]["relationships"][0];
+        assert_eq!(implied["destinationId"], systems[0]["id"]);
+        let landscape = &value["views"]["systemLandscapeViews"][0];
+        assert_eq!(landscape["elements"].as_array().unwrap().len(), 3);
+        assert_eq!(landscape["relationships"][0]["id"], implied["id"]);
+    }
+
+    #[test]
+    fn test_backstage() {
+        let entities = backstage(&model());
+        let kinds: Vec<(&str, &str)> = entities
+            .iter()
+            .map(|e| {
+                (
+                    e["kind"].as_str().unwrap(),
+                    e["metadata"]["name"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("Domain", "commerce"),
+                ("System", "order-management"),
+                ("System", "payments"),
+                ("Component", "sales"),
+                ("Component", "billing"),
+                ("Component", "shipping"),
+            ]
+        );
+
+        let system = &entities[1];
+        assert_eq!(system["metadata"]["title"], "OrderManagement");
+        assert_eq!(system["metadata"]["tags"], json!(["core"]));
+        assert_eq!(system["spec"]["domain"], "commerce");
+
+        // Sales is owned by its subdomain's team, Billing by its own
+        let sales = &entities[3];
+        assert_eq!(sales["spec"]["owner"], "orders-team");
+        assert_eq!(sales["spec"]["system"], "order-management");
+        assert_eq!(sales["spec"]["lifecycle"], "production");
+        assert!(sales["spec"].get("dependsOn").is_none());
+        let billing = &entities[4];
+        assert_eq!(billing["spec"]["owner"], "payments-team");
+        assert_eq!(billing["spec"]["dependsOn"], json!(["component:sales"]));
+        let shipping = &entities[5];
+        assert_eq!(shipping["spec"]["owner"], UNKNOWN_OWNER);
+        assert!(shipping["spec"].get("system").is_none());
+    }
+}
@@ -0,0 +1,431 @@
+//! Concurrent edits to a model by several collaborators.
+//!
+//! A [`Collaboration`] puts the edits of everyone editing a model in one
+//! order, the order a server receives them in, and numbers the revisions
+//! they make. Each [`Operation`] names the revision its author last saw, so
+//! the edits applied since are concurrent with it.
+//!
+//! Ids are never reused, so an edit made on an older revision still refers
+//! to the elements its author meant, and applies unchanged after the
+//! concurrent edits: transforming it against them leaves it as it is. The
+//! exceptions are conflicts, where the first edit to arrive wins and the
+//! later one is rejected:
+//!
+//! - an edit refers to an element a concurrent edit removed,
+//! - an edit adds or renames an element under a name already taken, by a
+//!   concurrent edit or not,
+//! - an edit renames an element a concurrent edit renamed.
+//!
+//! The author of a rejected edit sees the edits that won, and can make it
+//! again on the model as they left it.
+
+use crate::edit::{EditCommand, EditError, Edited, ModelEditSession};
+use crate::workspace::Workspace;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Number of applied edits kept to check concurrent edits against. Edits
+/// made on revisions before those are rejected.
+const HISTORY: usize = 1000;
+
+/// An edit to a context of the model.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Operation {
+    /// Name of the context edited
+    pub context: String,
+
+    /// The change to the context
+    pub command: EditCommand,
+}
+
+/// An edit as applied, making a revision of the model.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Applied {
+    /// The revision the edit made
+    pub revision: u64,
+
+    /// The edit
+    pub operation: Operation,
+
+    /// The element the edit created or changed
+    pub edited: Edited,
+}
+
+/// Why an edit was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CollabError {
+    /// The edit was made on a revision older than the history kept
+    Stale(u64),
+
+    /// The edit was made on a revision the model has not reached
+    UnknownRevision(u64),
+
+    /// A concurrent edit, making the revision, renamed the same element
+    RenamedConcurrently(u64),
+
+    /// Another element of the context already has the name the edit adds
+    /// or renames an element to
+    NameTaken(String),
+
+    /// The edit does not apply to the model as it is
+    Edit(EditError),
+}
+
+impl fmt::Display for CollabError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CollabError::Stale(base) => {
+                write!(f, "Revision {} is too old; reload the model", base)
+            }
+            CollabError::UnknownRevision(base) => {
+                write!(f, "Revision {} does not exist", base)
+            }
+            CollabError::RenamedConcurrently(revision) => write!(
+                f,
+                "The element was renamed concurrently, in revision {}",
+                revision
+            ),
+            CollabError::NameTaken(name) => write!(f, "'{}' already exists", name),
+            CollabError::Edit(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for CollabError {}
+
+impl From<EditError> for CollabError {
+    fn from(e: EditError) -> Self {
+        CollabError::Edit(e)
+    }
+}
+
+/// A model edited by several collaborators at once.
+#[derive(Debug, Clone)]
+pub struct Collaboration {
+    session: ModelEditSession,
+
+    /// The current revision
+    revision: u64,
+
+    /// The most recent edits, the last one making the current revision
+    history: VecDeque<Operation>,
+}
+
+impl Collaboration {
+    /// Start editing a model, as of a revision. A model loaded again starts
+    /// after the revisions of the one it replaces, so that edits made on
+    /// those are rejected as stale.
+    pub fn new(workspace: Workspace, revision: u64) -> Self {
+        Self {
+            session: ModelEditSession::new(workspace),
+            revision,
+            history: VecDeque::new(),
+        }
+    }
+
+    /// Get the current revision.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Get the model in its current revision.
+    pub fn workspace(&self) -> &Workspace {
+        self.session.workspace()
+    }
+
+    /// Apply an edit made on the `base` revision, making the next revision.
+    ///
+    /// Nothing changes if the edit is rejected.
+    pub fn apply(&mut self, base: u64, operation: Operation) -> Result<Applied, CollabError> {
+        if base > self.revision {
+            return Err(CollabError::UnknownRevision(base));
+        }
+        let concurrent = (self.revision - base) as usize;
+        if concurrent > self.history.len() {
+            return Err(CollabError::Stale(base));
+        }
+        let first = self.history.len() - concurrent;
+        for (revision, other) in (base + 1..).zip(self.history.iter().skip(first)) {
+            if other.context == operation.context
+                && renamed(&other.command).is_some()
+                && renamed(&other.command) == renamed(&operation.command)
+            {
+                return Err(CollabError::RenamedConcurrently(revision));
+            }
+        }
+        self.check_name(&operation)?;
+
+        let edited = self
+            .session
+            .apply(&operation.context, operation.command.clone())?;
+        // Collaborators cannot undo each other's edits
+        self.session.clear_history();
+        self.history.push_back(operation.clone());
+        if self.history.len() > HISTORY {
+            self.history.pop_front();
+        }
+        self.revision += 1;
+        Ok(Applied {
+            revision: self.revision,
+            operation,
+            edited,
+        })
+    }
+
+    /// Check that an edit adding or renaming an element does not reuse a
+    /// name.
+    fn check_name(&self, operation: &Operation) -> Result<(), CollabError> {
+        let Some(context) = self
+            .workspace()
+            .contexts
+            .iter()
+            .find(|c| c.name() == operation.context)
+        else {
+            // Applying reports the missing context
+            return Ok(());
+        };
+        let graph = context.graph();
+        let taken = match &operation.command {
+            EditCommand::AddObject { name }
+            | EditCommand::AddEntity { name }
+            | EditCommand::AddValueObject { name } => graph.find_object_by_name(name).map(|_| name),
+            EditCommand::AddMorphism { name, source, .. } => graph
+                .outgoing_morphisms(*source)
+                .any(|m| &m.name == name)
+                .then_some(name),
+            EditCommand::RenameObject { object, name } => graph
+                .find_object_by_name(name)
+                .is_some_and(|o| o.id != *object)
+                .then_some(name),
+            EditCommand::RenameMorphism { morphism, name } => graph
+                .get_morphism(*morphism)
+                .is_some_and(|renamed| {
+                    graph
+                        .outgoing_morphisms(renamed.source)
+                        .any(|m| m.id != *morphism && &m.name == name)
+                })
+                .then_some(name),
+            EditCommand::RenameAggregate { root, name } => context
+                .sketch()
+                .limits
+                .iter()
+                .any(|l| l.is_aggregate && l.root != Some(*root) && &l.name == name)
+                .then_some(name),
+            _ => None,
+        };
+        match taken {
+            Some(name) => Err(CollabError::NameTaken(name.clone())),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Get the element a command renames.
+fn renamed(command: &EditCommand) -> Option<Edited> {
+    match command {
+        EditCommand::RenameObject { object, .. } => Some(Edited::Object(*object)),
+        EditCommand::RenameMorphism { morphism, .. } => Some(Edited::Morphism(*morphism)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::BoundedContext;
+
+    fn collaboration() -> Collaboration {
+        let mut workspace = Workspace::new();
+        let mut sales = BoundedContext::new("Sales");
+        sales.add_entity("Order");
+        sales.add_entity("Customer");
+        workspace.contexts.push(sales);
+        Collaboration::new(workspace, 0)
+    }
+
+    fn edit(command: EditCommand) -> Operation {
+        Operation {
+            context: "Sales".into(),
+            command,
+        }
+    }
+
+    fn object(collaboration: &Collaboration, name: &str) -> crate::sketch::ObjectId {
+        collaboration.workspace().contexts[0]
+            .graph()
+            .find_object_by_name(name)
+            .unwrap()
+            .id
+    }
+
+    #[test]
+    fn test_concurrent_edits_apply_in_order() {
+        let mut collaboration = collaboration();
+        let order = object(&collaboration, "Order");
+        let customer = object(&collaboration, "Customer");
+
+        // Both made on revision 0
+        let added = collaboration
+            .apply(
+                0,
+                edit(EditCommand::AddMorphism {
+                    name: "placedBy".into(),
+                    source: order,
+                    target: customer,
+                }),
+            )
+            .unwrap();
+        assert_eq!(added.revision, 1);
+        let renamed = collaboration
+            .apply(
+                0,
+                edit(EditCommand::RenameObject {
+                    object: customer,
+                    name: "Buyer".into(),
+                }),
+            )
+            .unwrap();
+        assert_eq!(renamed.revision, 2);
+        assert_eq!(renamed.edited, Edited::Object(customer));
+
+        let graph = collaboration.workspace().contexts[0].graph();
+        let placed_by = graph.find_morphism_by_name("placedBy").unwrap();
+        assert_eq!(graph.get_object(placed_by.target).unwrap().name, "Buyer");
+        assert_eq!(collaboration.revision(), 2);
+    }
+
+    #[test]
+    fn test_conflicting_edits_are_rejected() {
+        let mut collaboration = collaboration();
+        let order = object(&collaboration, "Order");
+        let customer = object(&collaboration, "Customer");
+
+        collaboration
+            .apply(
+                0,
+                edit(EditCommand::AddEntity {
+                    name: "Invoice".into(),
+                }),
+            )
+            .unwrap();
+        assert_eq!(
+            collaboration.apply(
+                0,
+                edit(EditCommand::AddValueObject {
+                    name: "Invoice".into()
+                })
+            ),
+            Err(CollabError::NameTaken("Invoice".into()))
+        );
+
+        collaboration
+            .apply(
+                1,
+                edit(EditCommand::RenameObject {
+                    object: order,
+                    name: "Purchase".into(),
+                }),
+            )
+            .unwrap();
+        assert_eq!(
+            collaboration.apply(
+                1,
+                edit(EditCommand::RenameObject {
+                    object: order,
+                    name: "Sale".into(),
+                }),
+            ),
+            Err(CollabError::RenamedConcurrently(2))
+        );
+        // Renaming it again after seeing the rename is not a conflict
+        collaboration
+            .apply(
+                2,
+                edit(EditCommand::RenameObject {
+                    object: order,
+                    name: "Sale".into(),
+                }),
+            )
+            .unwrap();
+
+        collaboration
+            .apply(3, edit(EditCommand::RemoveObject { object: customer }))
+            .unwrap();
+        assert_eq!(
+            collaboration.apply(
+                3,
+                edit(EditCommand::RenameObject {
+                    object: customer,
+                    name: "Buyer".into(),
+                }),
+            ),
+            Err(CollabError::Edit(EditError::UnknownObject(customer)))
+        );
+
+        assert_eq!(
+            collaboration.apply(
+                9,
+                edit(EditCommand::AddEntity {
+                    name: "Refund".into()
+                })
+            ),
+            Err(CollabError::UnknownRevision(9))
+        );
+        assert_eq!(collaboration.revision(), 4);
+    }
+
+    #[test]
+    fn test_renames_to_taken_names_are_rejected() {
+        let mut collaboration = collaboration();
+        let order = object(&collaboration, "Order");
+        let customer = object(&collaboration, "Customer");
+        let add = |name: &str, target| {
+            edit(EditCommand::AddMorphism {
+                name: name.into(),
+                source: order,
+                target,
+            })
+        };
+        collaboration.apply(0, add("placedBy", customer)).unwrap();
+        collaboration.apply(1, add("billedTo", customer)).unwrap();
+
+        assert_eq!(
+            collaboration.apply(
+                2,
+                edit(EditCommand::RenameObject {
+                    object: order,
+                    name: "Customer".into(),
+                }),
+            ),
+            Err(CollabError::NameTaken("Customer".into()))
+        );
+        let billed_to = collaboration.workspace().contexts[0]
+            .graph()
+            .find_morphism_by_name("billedTo")
+            .unwrap()
+            .id;
+        assert_eq!(
+            collaboration.apply(
+                2,
+                edit(EditCommand::RenameMorphism {
+                    morphism: billed_to,
+                    name: "placedBy".into(),
+                }),
+            ),
+            Err(CollabError::NameTaken("placedBy".into()))
+        );
+        assert_eq!(collaboration.revision(), 2);
+
+        // An element may keep its own name
+        collaboration
+            .apply(
+                2,
+                edit(EditCommand::RenameObject {
+                    object: order,
+                    name: "Order".into(),
+                }),
+            )
+            .unwrap();
+    }
+}
@@ -0,0 +1,151 @@
+//! Literal constraints on the fields of domain objects.
+//!
+//! A field constraint restricts the values a field may take, such as a lower
+//! bound on an amount or a pattern for a currency code. Constraints are
+//! attached to an object and a field name; code generators apply them to the
+//! field with that name wherever the object is rendered.
+//!
+//! Categorically, a constraint picks out a subobject of the field's target:
+//! the field morphism must factor through the subset of values satisfying
+//! the predicate.
+
+use crate::sketch::ObjectId;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A single constraint on the values of a field.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FieldConstraint {
+    /// Inclusive numeric lower bound
+    Min(f64),
+
+    /// Inclusive numeric upper bound
+    Max(f64),
+
+    /// Minimum length of a text value
+    MinLength(usize),
+
+    /// Maximum length of a text value
+    MaxLength(usize),
+
+    /// Regular expression the whole text value must match
+    Pattern(String),
+}
+
+impl FieldConstraint {
+    /// The keyword used for this constraint in the DSL.
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            FieldConstraint::Min(_) => "min",
+            FieldConstraint::Max(_) => "max",
+            FieldConstraint::MinLength(_) => "minLength",
+            FieldConstraint::MaxLength(_) => "maxLength",
+            FieldConstraint::Pattern(_) => "pattern",
+        }
+    }
+
+    /// All constraint keywords understood by the DSL.
+    pub fn keywords() -> &'static [&'static str] {
+        &["min", "max", "minLength", "maxLength", "pattern"]
+    }
+
+    /// Check if this constraint applies to numeric values.
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, FieldConstraint::Min(_) | FieldConstraint::Max(_))
+    }
+
+    /// Check if this constraint applies to text values.
+    pub fn is_textual(&self) -> bool {
+        !self.is_numeric()
+    }
+}
+
+impl fmt::Display for FieldConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldConstraint::Min(v) | FieldConstraint::Max(v) => {
+                write!(f, "{}: {}", self.keyword(), v)
+            }
+            FieldConstraint::MinLength(n) | FieldConstraint::MaxLength(n) => {
+                write!(f, "{}: {}", self.keyword(), n)
+            }
+            FieldConstraint::Pattern(p) => write!(f, "{}: {:?}", self.keyword(), p),
+        }
+    }
+}
+
+/// The constraints declared on one field of an object.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConstrainedField {
+    /// The object owning the field
+    pub object: ObjectId,
+
+    /// Name of the field
+    pub field: String,
+
+    /// Declared type of a field declared on the object rather than as a
+    /// morphism out of it, such as `Decimal` in `amount: Decimal [min: 0]`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub type_name: Option<String>,
+
+    /// Constraints in declaration order
+    pub constraints: Vec<FieldConstraint>,
+}
+
+impl ConstrainedField {
+    /// Create an entry for a field without any constraints yet.
+    pub fn new(object: ObjectId, field: impl Into<String>) -> Self {
+        Self {
+            object,
+            field: field.into(),
+            type_name: None,
+            constraints: Vec::new(),
+        }
+    }
+
+    /// Get the numeric bounds as `(min, max)`.
+    pub fn bounds(&self) -> (Option<f64>, Option<f64>) {
+        let mut min = None;
+        let mut max = None;
+        for constraint in &self.constraints {
+            match constraint {
+                FieldConstraint::Min(v) => min = Some(*v),
+                FieldConstraint::Max(v) => max = Some(*v),
+                _ => {}
+            }
+        }
+        (min, max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constraint_kinds() {
+        assert!(FieldConstraint::Min(0.0).is_numeric());
+        assert!(FieldConstraint::Pattern("[A-Z]{3}".into()).is_textual());
+        assert!(FieldConstraint::MaxLength(10).is_textual());
+        assert_eq!(FieldConstraint::MinLength(1).keyword(), "minLength");
+        assert!(FieldConstraint::keywords().contains(&"pattern"));
+    }
+
+    #[test]
+    fn test_constraint_display() {
+        assert_eq!(FieldConstraint::Min(0.0).to_string(), "min: 0");
+        assert_eq!(FieldConstraint::Max(99.5).to_string(), "max: 99.5");
+        assert_eq!(
+            FieldConstraint::Pattern("[A-Z]{3}".into()).to_string(),
+            "pattern: \"[A-Z]{3}\""
+        );
+    }
+
+    #[test]
+    fn test_constrained_field_bounds() {
+        let mut field = ConstrainedField::new(ObjectId(0), "amount");
+        field.constraints.push(FieldConstraint::Min(0.0));
+        field.constraints.push(FieldConstraint::Max(100.0));
+        assert_eq!(field.bounds(), (Some(0.0), Some(100.0)));
+    }
+}
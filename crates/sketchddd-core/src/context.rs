@@ -1,8 +1,22 @@
 //! Bounded Context as a DDD-specific wrapper around Sketch.
 
-use crate::sketch::{ColimitCocone, Graph, LimitCone, MorphismId, ObjectId, PathEquation, Sketch};
+use crate::constraint::{ConstrainedField, FieldConstraint};
+use crate::event_storming::{Actor, Hotspot, Policy};
+use crate::expr::{DerivedMorphism, Expr};
+use crate::lifecycle::Lifecycle;
+use crate::metadata::{Classification, Metadata};
+use crate::process::ProcessManager;
+use crate::refactor::{AggregateError, RenameError};
+use crate::sketch::{
+    ColimitCocone, Graph, LimitCone, Morphism, MorphismId, ObjectId, PathEquation, RemovalPolicy,
+    Sketch,
+};
+use crate::suppression::Suppression;
+use crate::validation::{validate_aggregate_boundaries, ValidationResult};
+use crate::version::{ModelVersion, Removal};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 
 /// A bounded context in Domain-Driven Design terms.
 ///
@@ -28,6 +42,75 @@ pub struct BoundedContext {
 
     /// Invariants (equalizers) in this context
     invariants: Vec<Invariant>,
+
+    /// Process managers (sagas) coordinating events and commands
+    #[serde(default)]
+    processes: Vec<ProcessManager>,
+
+    /// Actors issuing commands, captured from event storming
+    #[serde(default)]
+    actors: Vec<Actor>,
+
+    /// Policies reacting to events with commands
+    #[serde(default)]
+    policies: Vec<Policy>,
+
+    /// Open questions and risks
+    #[serde(default)]
+    hotspots: Vec<Hotspot>,
+
+    /// Lifecycle state machines attached to enumerations
+    #[serde(default)]
+    lifecycles: Vec<Lifecycle>,
+
+    /// Objects included from shared kernels (object -> kernel name)
+    #[serde(default)]
+    shared_objects: HashMap<ObjectId, String>,
+
+    /// Literal constraints on object fields
+    #[serde(default)]
+    field_constraints: Vec<ConstrainedField>,
+
+    /// Declared version of this context's model
+    #[serde(default)]
+    version: Option<ModelVersion>,
+
+    /// Objects and fields scheduled for removal
+    #[serde(default)]
+    removals: Vec<Removal>,
+
+    /// Morphisms computed from expressions instead of stored
+    #[serde(default)]
+    derived_morphisms: Vec<DerivedMorphism>,
+
+    /// Morphisms whose target may be absent, e.g. `Customer?`
+    #[serde(default)]
+    optional_morphisms: Vec<MorphismId>,
+
+    /// Morphisms to a collection of their target, e.g. `List<LineItem>`,
+    /// with the kind of collection
+    #[serde(default)]
+    many_morphisms: Vec<(MorphismId, Collection)>,
+
+    /// Business rules declared on aggregates
+    #[serde(default)]
+    aggregate_invariants: Vec<AggregateInvariant>,
+
+    /// Tags and metadata of the context itself
+    #[serde(default)]
+    metadata: Metadata,
+
+    /// Tags and metadata of objects
+    #[serde(default)]
+    object_metadata: BTreeMap<ObjectId, Metadata>,
+
+    /// Tags and metadata of aggregates, by root
+    #[serde(default)]
+    aggregate_metadata: BTreeMap<ObjectId, Metadata>,
+
+    /// Lint rules the context's elements are allowed to break
+    #[serde(default)]
+    suppressions: Vec<Suppression>,
 }
 
 /// An invariant expressed as an equalizer.
@@ -56,6 +139,65 @@ pub struct Invariant {
     pub description: Option<String>,
 }
 
+/// How an aggregate invariant is enforced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Enforcement {
+    /// Checked transactionally: the rule holds after every change
+    #[default]
+    Strict,
+
+    /// Eventually consistent: the rule may be violated temporarily and is
+    /// restored asynchronously, e.g. by events or process managers
+    Eventual,
+}
+
+impl fmt::Display for Enforcement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Enforcement::Strict => "strict",
+            Enforcement::Eventual => "eventual",
+        })
+    }
+}
+
+/// The kind of collection a to-many morphism takes an element of its
+/// source to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Collection {
+    /// An ordered collection, `List<T>`
+    List,
+
+    /// A collection of distinct elements, `Set<T>`
+    Set,
+}
+
+impl Collection {
+    /// Get the name of the collection's type in the DSL.
+    pub fn name(self) -> &'static str {
+        match self {
+            Collection::List => "List",
+            Collection::Set => "Set",
+        }
+    }
+}
+
+/// A business rule declared on an aggregate.
+///
+/// Strict invariants must hold within the aggregate's transactional
+/// boundary; eventual invariants may span aggregates.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AggregateInvariant {
+    /// The root of the aggregate declaring the rule
+    pub root: ObjectId,
+
+    /// The rule, as an expression over paths from the root
+    pub expression: Expr,
+
+    /// How the rule is enforced
+    #[serde(default)]
+    pub enforcement: Enforcement,
+}
+
 impl BoundedContext {
     /// Create a new bounded context with the given name.
     pub fn new(name: impl Into<String>) -> Self {
@@ -66,6 +208,23 @@ impl BoundedContext {
             value_objects: Vec::new(),
             aggregate_roots: Vec::new(),
             invariants: Vec::new(),
+            processes: Vec::new(),
+            actors: Vec::new(),
+            policies: Vec::new(),
+            hotspots: Vec::new(),
+            lifecycles: Vec::new(),
+            shared_objects: HashMap::new(),
+            field_constraints: Vec::new(),
+            version: None,
+            removals: Vec::new(),
+            derived_morphisms: Vec::new(),
+            optional_morphisms: Vec::new(),
+            many_morphisms: Vec::new(),
+            aggregate_invariants: Vec::new(),
+            metadata: Metadata::default(),
+            object_metadata: BTreeMap::new(),
+            aggregate_metadata: BTreeMap::new(),
+            suppressions: Vec::new(),
         }
     }
 
@@ -133,18 +292,24 @@ impl BoundedContext {
         name: impl Into<String>,
         component_types: &[ObjectId],
     ) -> ObjectId {
-        let name_str = name.into();
-        let id = self.sketch.add_object(&name_str);
-        let mut limit = LimitCone::value_object(&name_str, id);
-
-        // Add projections to component types
-        for (i, &component) in component_types.iter().enumerate() {
-            let proj_name = format!("proj_{}", i);
-            let morphism = self.sketch.graph.add_morphism(&proj_name, id, component);
-            limit.add_projection(morphism, component);
-        }
+        let fields = component_types
+            .iter()
+            .enumerate()
+            .map(|(i, &component)| (format!("proj_{}", i), component))
+            .collect();
+        self.add_value_object_with_fields(name, fields)
+    }
 
-        self.sketch.add_limit(limit);
+    /// Add a value object with named fields.
+    ///
+    /// Like [`BoundedContext::add_value_object_with_components`], but each
+    /// projection is named after its field.
+    pub fn add_value_object_with_fields(
+        &mut self,
+        name: impl Into<String>,
+        fields: Vec<(String, ObjectId)>,
+    ) -> ObjectId {
+        let id = self.sketch.add_product(name, fields);
         self.value_objects.push(id);
         id
     }
@@ -223,6 +388,33 @@ impl BoundedContext {
         self.aggregate_roots.contains(&id)
     }
 
+    /// Declare a business rule on the aggregate rooted at `root`.
+    pub fn add_aggregate_invariant(
+        &mut self,
+        root: ObjectId,
+        expression: Expr,
+        enforcement: Enforcement,
+    ) {
+        self.aggregate_invariants.push(AggregateInvariant {
+            root,
+            expression,
+            enforcement,
+        });
+    }
+
+    /// Get all aggregate invariants in this context.
+    pub fn aggregate_invariants(&self) -> &[AggregateInvariant] {
+        &self.aggregate_invariants
+    }
+
+    /// Get the invariants declared on the aggregate rooted at `root`.
+    pub fn invariants_of(&self, root: ObjectId) -> Vec<&AggregateInvariant> {
+        self.aggregate_invariants
+            .iter()
+            .filter(|inv| inv.root == root)
+            .collect()
+    }
+
     /// Add an enumeration to this context.
     ///
     /// An enumeration is represented as a colimit (coproduct/sum type) where
@@ -296,6 +488,10 @@ impl BoundedContext {
             equalizer,
             source,
         );
+        // Register the equalizer as a limit, with `incl.f = incl.g`
+        let mut limit = LimitCone::equalizer(&name_str, equalizer, f, g);
+        limit.add_projection(inclusion, source);
+        self.sketch.add_commuting_limit(limit);
 
         let invariant = Invariant {
             name: name_str,
@@ -315,6 +511,393 @@ impl BoundedContext {
         &self.invariants
     }
 
+    /// Define a process manager (saga) reacting to events with commands.
+    ///
+    /// Each step is an `(event, command)` pair. The process is kept as a
+    /// diagram over existing objects and does not add morphisms to the graph.
+    pub fn define_process(
+        &mut self,
+        name: impl Into<String>,
+        steps: &[(ObjectId, ObjectId)],
+    ) -> &mut ProcessManager {
+        let mut process = ProcessManager::new(name);
+        for &(event, command) in steps {
+            process.add_step(event, command);
+        }
+        self.processes.push(process);
+        self.processes.last_mut().unwrap()
+    }
+
+    /// Get a process manager by name.
+    pub fn get_process(&self, name: &str) -> Option<&ProcessManager> {
+        self.processes.iter().find(|p| p.name == name)
+    }
+
+    /// Get all process managers in this context.
+    pub fn processes(&self) -> &[ProcessManager] {
+        &self.processes
+    }
+
+    /// Define an actor issuing the given command objects.
+    pub fn define_actor(&mut self, name: impl Into<String>, commands: &[ObjectId]) -> &mut Actor {
+        let mut actor = Actor::new(name);
+        for &command in commands {
+            actor.add_command(command);
+        }
+        self.actors.push(actor);
+        self.actors.last_mut().unwrap()
+    }
+
+    /// Get an actor by name.
+    pub fn get_actor(&self, name: &str) -> Option<&Actor> {
+        self.actors.iter().find(|a| a.name == name)
+    }
+
+    /// Get all actors in this context.
+    pub fn actors(&self) -> &[Actor] {
+        &self.actors
+    }
+
+    /// Define a policy reacting to an event object with command objects.
+    ///
+    /// Like a process, a policy refers to existing objects and does not add
+    /// morphisms to the graph.
+    pub fn define_policy(
+        &mut self,
+        name: impl Into<String>,
+        event: ObjectId,
+        commands: &[ObjectId],
+    ) -> &mut Policy {
+        let mut policy = Policy::new(name, event);
+        for &command in commands {
+            policy.add_command(command);
+        }
+        self.policies.push(policy);
+        self.policies.last_mut().unwrap()
+    }
+
+    /// Get a policy by name.
+    pub fn get_policy(&self, name: &str) -> Option<&Policy> {
+        self.policies.iter().find(|p| p.name == name)
+    }
+
+    /// Get all policies in this context.
+    pub fn policies(&self) -> &[Policy] {
+        &self.policies
+    }
+
+    /// Mark an open question or risk.
+    pub fn add_hotspot(&mut self, hotspot: Hotspot) {
+        self.hotspots.push(hotspot);
+    }
+
+    /// Get all hotspots in this context.
+    pub fn hotspots(&self) -> &[Hotspot] {
+        &self.hotspots
+    }
+
+    /// Define a lifecycle state machine over an enumeration.
+    ///
+    /// The enum's variants are the states; transitions are added on the
+    /// returned lifecycle. The first transition's source is the initial state.
+    pub fn define_lifecycle(&mut self, name: impl Into<String>, subject: ObjectId) -> &mut Lifecycle {
+        self.lifecycles.push(Lifecycle::new(name, subject));
+        self.lifecycles.last_mut().unwrap()
+    }
+
+    /// Get the lifecycle attached to an enumeration.
+    pub fn get_lifecycle(&self, subject: ObjectId) -> Option<&Lifecycle> {
+        self.lifecycles.iter().find(|l| l.subject == subject)
+    }
+
+    /// Get all lifecycles in this context.
+    pub fn lifecycles(&self) -> &[Lifecycle] {
+        &self.lifecycles
+    }
+
+    /// Include a single object from a shared kernel into this context.
+    ///
+    /// The object keeps its kind: entities stay entities, value objects stay
+    /// value objects and enumerations keep their variants. Returns the ID of
+    /// the new object, or `None` if the kernel has no such object.
+    pub fn include_shared_object(
+        &mut self,
+        kernel: &BoundedContext,
+        object: ObjectId,
+    ) -> Option<ObjectId> {
+        let name = kernel.graph().get_object(object)?.name.clone();
+
+        let id = if kernel.is_entity(object) {
+            self.add_entity(&name)
+        } else if kernel.is_value_object(object) {
+            self.add_value_object(&name)
+        } else if let Some(colimit) = kernel.get_enum_colimit(object) {
+            let variants = colimit.variant_names().map(String::from).collect();
+            self.add_enum(&name, variants)
+        } else {
+            self.sketch.add_object(&name)
+        };
+
+        self.shared_objects.insert(id, kernel.name().to_string());
+        Some(id)
+    }
+
+    /// Include every object of a shared kernel into this context.
+    ///
+    /// Objects whose name already exists in this context are skipped, so
+    /// local declarations take precedence. Returns the IDs of included objects.
+    pub fn include_shared_kernel(&mut self, kernel: &BoundedContext) -> Vec<ObjectId> {
+        let mut kernel_objects: Vec<_> = kernel.graph().objects().collect();
+        kernel_objects.sort_by_key(|o| o.id.0);
+
+        let mut included = Vec::new();
+        for object in kernel_objects {
+            if self.graph().find_object_by_name(&object.name).is_some() {
+                continue;
+            }
+            if let Some(id) = self.include_shared_object(kernel, object.id) {
+                included.push(id);
+            }
+        }
+        included
+    }
+
+    /// Mark a local object as belonging to a shared kernel.
+    ///
+    /// Used for the context a kernel takes the object from, so that it
+    /// shares the kernel with every context that includes it.
+    pub fn mark_shared_object(&mut self, id: ObjectId, kernel: impl Into<String>) {
+        self.shared_objects.insert(id, kernel.into());
+    }
+
+    /// Get the shared kernel an object was included from, if any.
+    pub fn shared_kernel_of(&self, id: ObjectId) -> Option<&str> {
+        self.shared_objects.get(&id).map(String::as_str)
+    }
+
+    /// Get the names of all shared kernels this context includes, sorted.
+    pub fn shared_kernels(&self) -> Vec<&str> {
+        let mut kernels: Vec<&str> = self.shared_objects.values().map(String::as_str).collect();
+        kernels.sort_unstable();
+        kernels.dedup();
+        kernels
+    }
+
+    /// Get the shared kernels this context has in common with another.
+    pub fn common_shared_kernels<'a>(&'a self, other: &BoundedContext) -> Vec<&'a str> {
+        let theirs = other.shared_kernels();
+        self.shared_kernels()
+            .into_iter()
+            .filter(|k| theirs.contains(k))
+            .collect()
+    }
+
+    /// Constrain the values of a field of an object.
+    ///
+    /// The field is identified by name, so the constraint applies whether
+    /// the field is declared on the object or as a morphism out of it.
+    pub fn constrain_field(
+        &mut self,
+        object: ObjectId,
+        field: impl Into<String>,
+        constraint: FieldConstraint,
+    ) {
+        let field = field.into();
+        let index = match self
+            .field_constraints
+            .iter()
+            .position(|c| c.object == object && c.field == field)
+        {
+            Some(index) => index,
+            None => {
+                self.field_constraints.push(ConstrainedField::new(object, field));
+                self.field_constraints.len() - 1
+            }
+        };
+        self.field_constraints[index].constraints.push(constraint);
+    }
+
+    /// Record the declared type of a constrained field declared on an object
+    /// rather than as a morphism out of it, so that code generators can
+    /// render the field.
+    pub fn set_field_type(&mut self, object: ObjectId, field: &str, type_name: impl Into<String>) {
+        if let Some(constrained) = self
+            .field_constraints
+            .iter_mut()
+            .find(|c| c.object == object && c.field == field)
+        {
+            constrained.type_name = Some(type_name.into());
+        }
+    }
+
+    /// Get the constraints on a field of an object.
+    pub fn field_constraints(&self, object: ObjectId, field: &str) -> &[FieldConstraint] {
+        self.field_constraints
+            .iter()
+            .find(|c| c.object == object && c.field == field)
+            .map(|c| c.constraints.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Get all constrained fields in this context.
+    pub fn constrained_fields(&self) -> &[ConstrainedField] {
+        &self.field_constraints
+    }
+
+    /// Set the declared version of this context's model.
+    pub fn set_version(&mut self, version: ModelVersion) {
+        self.version = Some(version);
+    }
+
+    /// Get the declared version of this context's model.
+    pub fn version(&self) -> Option<ModelVersion> {
+        self.version
+    }
+
+    /// Get the tags and metadata of the context itself.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Get a mutable reference to the tags and metadata of the context.
+    pub fn metadata_mut(&mut self) -> &mut Metadata {
+        &mut self.metadata
+    }
+
+    /// Get the tags and metadata of an object, if any were declared.
+    pub fn object_metadata(&self, object: ObjectId) -> Option<&Metadata> {
+        self.object_metadata.get(&object)
+    }
+
+    /// Get a mutable reference to the tags and metadata of an object.
+    pub fn object_metadata_mut(&mut self, object: ObjectId) -> &mut Metadata {
+        self.object_metadata.entry(object).or_default()
+    }
+
+    /// Get the tags and metadata of the aggregate rooted at `root`, if any
+    /// were declared.
+    pub fn aggregate_metadata(&self, root: ObjectId) -> Option<&Metadata> {
+        self.aggregate_metadata.get(&root)
+    }
+
+    /// Get a mutable reference to the tags and metadata of the aggregate
+    /// rooted at `root`.
+    pub fn aggregate_metadata_mut(&mut self, root: ObjectId) -> &mut Metadata {
+        self.aggregate_metadata.entry(root).or_default()
+    }
+
+    /// Allow an element of the context, or the whole context when `element`
+    /// is `None`, to break a lint rule.
+    pub fn suppress(&mut self, code: impl Into<String>, element: Option<&str>) {
+        self.suppressions
+            .push(Suppression::new(code, element.map(str::to_string)));
+    }
+
+    /// Get the lint rules the context's elements are allowed to break.
+    pub fn suppressions(&self) -> &[Suppression] {
+        &self.suppressions
+    }
+
+    /// Get the classification that applies to an object.
+    ///
+    /// An object's own classification wins over that of the aggregate it
+    /// is the root of, which wins over the context's.
+    pub fn classification_of(&self, object: ObjectId) -> Option<Classification> {
+        self.object_metadata(object)
+            .and_then(|m| m.classification)
+            .or_else(|| self.aggregate_metadata(object).and_then(|m| m.classification))
+            .or(self.metadata.classification)
+    }
+
+    /// Schedule an object, or one of its fields, for removal in a version.
+    pub fn schedule_removal(&mut self, object: ObjectId, field: Option<&str>, removed_in: ModelVersion) {
+        self.removals.push(Removal {
+            object,
+            field: field.map(String::from),
+            removed_in,
+        });
+    }
+
+    /// Get the scheduled removal of an object (`field = None`) or field.
+    pub fn removal_of(&self, object: ObjectId, field: Option<&str>) -> Option<&Removal> {
+        self.removals
+            .iter()
+            .find(|r| r.object == object && r.field.as_deref() == field)
+    }
+
+    /// Get all scheduled removals in this context.
+    pub fn removals(&self) -> &[Removal] {
+        &self.removals
+    }
+
+    /// Define a morphism as computed from an expression over paths.
+    pub fn derive_morphism(&mut self, morphism: MorphismId, expression: Expr) {
+        self.derived_morphisms.push(DerivedMorphism { morphism, expression });
+    }
+
+    /// Get the expression computing a derived morphism.
+    pub fn derivation_of(&self, morphism: MorphismId) -> Option<&Expr> {
+        self.derived_morphisms
+            .iter()
+            .find(|d| d.morphism == morphism)
+            .map(|d| &d.expression)
+    }
+
+    /// Check if a morphism is derived rather than stored.
+    pub fn is_derived(&self, morphism: MorphismId) -> bool {
+        self.derivation_of(morphism).is_some()
+    }
+
+    /// Get all derived morphisms in this context.
+    pub fn derived_morphisms(&self) -> &[DerivedMorphism] {
+        &self.derived_morphisms
+    }
+
+    /// Get the derived morphisms out of an object, with their expressions.
+    pub fn derivations_from(&self, source: ObjectId) -> Vec<(&Morphism, &Expr)> {
+        self.derived_morphisms
+            .iter()
+            .filter_map(|d| {
+                let morphism = self.sketch.graph.get_morphism(d.morphism)?;
+                (morphism.source == source).then_some((morphism, &d.expression))
+            })
+            .collect()
+    }
+
+    /// Mark a morphism as optional: an element of its source may have no
+    /// value for it.
+    pub fn mark_optional(&mut self, morphism: MorphismId) {
+        if !self.optional_morphisms.contains(&morphism) {
+            self.optional_morphisms.push(morphism);
+        }
+    }
+
+    /// Check if a morphism is optional.
+    pub fn is_optional(&self, morphism: MorphismId) -> bool {
+        self.optional_morphisms.contains(&morphism)
+    }
+
+    /// Mark a morphism as to-many: it takes an element of its source to a
+    /// collection of elements of its target.
+    pub fn mark_many(&mut self, morphism: MorphismId, collection: Collection) {
+        self.many_morphisms.retain(|&(id, _)| id != morphism);
+        self.many_morphisms.push((morphism, collection));
+    }
+
+    /// Get the kind of collection a to-many morphism targets, or `None` if
+    /// the morphism is to-one.
+    pub fn collection_of(&self, morphism: MorphismId) -> Option<Collection> {
+        self.many_morphisms
+            .iter()
+            .find(|&&(id, _)| id == morphism)
+            .map(|&(_, collection)| collection)
+    }
+
+    /// Check if a morphism is to-many.
+    pub fn is_many(&self, morphism: MorphismId) -> bool {
+        self.collection_of(morphism).is_some()
+    }
+
     /// Add a business rule (path equation) - deprecated, use add_path_equation.
     #[deprecated(since = "0.1.0", note = "Use add_path_equation instead")]
     pub fn add_invariant(&mut self, name: impl Into<String>, equation: PathEquation) {
@@ -335,12 +918,446 @@ impl BoundedContext {
     pub fn aggregate_roots(&self) -> &[ObjectId] {
         &self.aggregate_roots
     }
+
+    /// Move a member from one aggregate to another.
+    ///
+    /// The member's projection from the old root is dropped, together with
+    /// its morphism if the aggregate created it, and a projection from the
+    /// new root is added, reusing a morphism from the new root to the
+    /// member if there is one. Returns the aggregate boundary validation of
+    /// the result, which reports invariants the move leaves reaching
+    /// outside their aggregate.
+    pub fn move_to_aggregate(
+        &mut self,
+        member: ObjectId,
+        from: ObjectId,
+        to: ObjectId,
+    ) -> Result<ValidationResult, AggregateError> {
+        let from_index = self.aggregate_index(from)?;
+        let to_index = self.aggregate_index(to)?;
+        self.check_aggregate_members(from_index, &[member])?;
+
+        self.remove_aggregate_member(from_index, member);
+        self.add_aggregate_member(to_index, member);
+        Ok(validate_aggregate_boundaries(self))
+    }
+
+    /// Split members off an aggregate into a new aggregate.
+    ///
+    /// `new_root` and `members` must be members of the aggregate rooted at
+    /// `root`. They leave it, and `new_root` becomes the root of a new
+    /// aggregate, named after it, containing `members`.
+    pub fn split_aggregate(
+        &mut self,
+        root: ObjectId,
+        new_root: ObjectId,
+        members: &[ObjectId],
+    ) -> Result<ValidationResult, AggregateError> {
+        let index = self.aggregate_index(root)?;
+        self.check_aggregate_members(index, &[new_root])?;
+        self.check_aggregate_members(index, members)?;
+
+        self.remove_aggregate_member(index, new_root);
+        for &member in members {
+            self.remove_aggregate_member(index, member);
+        }
+
+        let name = self
+            .sketch
+            .graph
+            .get_object(new_root)
+            .map(|o| o.name.clone())
+            .unwrap_or_default();
+        self.define_aggregate(name, new_root);
+        let new_index = self.sketch.limits.len() - 1;
+        for &member in members {
+            self.add_aggregate_member(new_index, member);
+        }
+        Ok(validate_aggregate_boundaries(self))
+    }
+
+    /// Merge the aggregate rooted at `other` into the one rooted at `into`.
+    ///
+    /// The other root and its members become members of `into`, and the
+    /// other aggregate's invariants are re-rooted by prefixing their paths
+    /// with the projection from `into` to the other root.
+    pub fn merge_aggregates(
+        &mut self,
+        into: ObjectId,
+        other: ObjectId,
+    ) -> Result<ValidationResult, AggregateError> {
+        if into == other {
+            return Err(AggregateError::SameAggregate(into));
+        }
+        self.aggregate_index(into)?;
+        let other_index = self.aggregate_index(other)?;
+
+        let members: Vec<ObjectId> = self.sketch.limits[other_index].component_objects().collect();
+        for &member in &members {
+            self.remove_aggregate_member(other_index, member);
+        }
+        self.sketch.limits.remove(other_index);
+        self.aggregate_roots.retain(|&root| root != other);
+
+        let into_index = self.aggregate_index(into)?;
+        let projection = self.add_aggregate_member(into_index, other);
+        for member in members {
+            self.add_aggregate_member(into_index, member);
+        }
+
+        let prefix = self.sketch.graph.get_morphism(projection).map(|m| m.name.clone());
+        for invariant in self.aggregate_invariants.iter_mut().filter(|i| i.root == other) {
+            invariant.root = into;
+            if let Some(prefix) = &prefix {
+                for path in invariant.expression.paths_mut() {
+                    path.insert(0, prefix.clone());
+                }
+            }
+        }
+        Ok(validate_aggregate_boundaries(self))
+    }
+
+    fn aggregate_index(&self, root: ObjectId) -> Result<usize, AggregateError> {
+        self.sketch
+            .limits
+            .iter()
+            .position(|l| l.is_aggregate && l.root == Some(root))
+            .ok_or(AggregateError::NotAnAggregate(root))
+    }
+
+    fn check_aggregate_members(
+        &self,
+        index: usize,
+        members: &[ObjectId],
+    ) -> Result<(), AggregateError> {
+        let limit = &self.sketch.limits[index];
+        match members.iter().find(|&&m| !limit.component_objects().any(|o| o == m)) {
+            Some(&object) => Err(AggregateError::NotAMember {
+                aggregate: limit.name.clone(),
+                object,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Drop an aggregate's projections to a member, and the morphisms the
+    /// aggregate created for them.
+    fn remove_aggregate_member(&mut self, index: usize, member: ObjectId) {
+        let limit = &mut self.sketch.limits[index];
+        let member_name = self.sketch.graph.get_object(member).map(|o| o.name.as_str());
+        let generated = format!("{}_{}", limit.name, member_name.unwrap_or_default());
+        let removed: Vec<MorphismId> = limit
+            .projections
+            .iter()
+            .filter(|p| p.target == member)
+            .map(|p| p.morphism)
+            .collect();
+        limit.projections.retain(|p| p.target != member);
+
+        let mut pruned = false;
+        for morphism in removed {
+            if self.sketch.graph.get_morphism(morphism).is_some_and(|m| m.name == generated) {
+                self.sketch.graph.remove_morphism(morphism);
+                pruned = true;
+            }
+        }
+        if pruned {
+            self.prune();
+        }
+    }
+
+    /// Add a projection from an aggregate's root to a member, and return
+    /// its morphism.
+    fn add_aggregate_member(&mut self, index: usize, member: ObjectId) -> MorphismId {
+        let limit = &self.sketch.limits[index];
+        if let Some(p) = limit.projections.iter().find(|p| p.target == member) {
+            return p.morphism;
+        }
+        let root = limit.apex;
+        let existing = self
+            .sketch
+            .graph
+            .outgoing_morphisms(root)
+            .filter(|m| !m.is_identity && m.target == member && !self.is_derived(m.id))
+            .map(|m| m.id)
+            .min();
+        let morphism = existing.unwrap_or_else(|| {
+            let member_name = self.sketch.graph.get_object(member).map(|o| o.name.clone());
+            let name = format!("{}_{}", limit.name, member_name.unwrap_or_default());
+            self.sketch.graph.add_morphism(name, root, member)
+        });
+        self.sketch.limits[index].add_projection(morphism, member);
+        morphism
+    }
+
+    /// Remove an object and everything that depends on it.
+    ///
+    /// Morphisms into or out of the object are removed with it, and so is
+    /// every equation, cone, invariant, process step, policy, lifecycle, constraint
+    /// and registry entry that refers to the object or those morphisms.
+    /// Returns `false` if the object does not exist.
+    pub fn remove_object(&mut self, object: ObjectId) -> bool {
+        if self.sketch.remove_object(object, RemovalPolicy::Cascade).is_err() {
+            return false;
+        }
+        self.prune();
+        true
+    }
+
+    /// Remove a morphism and everything that depends on it.
+    ///
+    /// Like [`BoundedContext::remove_object`], every equation, cone,
+    /// invariant and registry entry that refers to the morphism goes with
+    /// it. Returns `false` if the morphism does not exist.
+    pub fn remove_morphism(&mut self, morphism: MorphismId) -> bool {
+        if self
+            .sketch
+            .remove_morphism(morphism, RemovalPolicy::Cascade)
+            .is_err()
+        {
+            return false;
+        }
+        self.prune();
+        true
+    }
+
+    /// Drop every reference to objects and morphisms no longer in the graph.
+    fn prune(&mut self) {
+        self.sketch.prune();
+        let graph = &self.sketch.graph;
+        let object = |id| graph.get_object(id).is_some();
+        let morphism = |id| graph.get_morphism(id).is_some();
+
+        self.entities.retain(|&id| object(id));
+        self.entity_identities.retain(|&id, &mut identity| object(id) && morphism(identity));
+        self.value_objects.retain(|&id| object(id));
+        self.aggregate_roots.retain(|&id| object(id));
+        self.invariants.retain(|inv| {
+            object(inv.equalizer)
+                && [inv.inclusion, inv.morphism_f, inv.morphism_g].into_iter().all(morphism)
+        });
+        for process in self.processes.iter_mut() {
+            process.steps.retain(|step| object(step.event) && object(step.command));
+        }
+        for actor in self.actors.iter_mut() {
+            actor.commands.retain(|&id| object(id));
+        }
+        self.policies.retain(|policy| object(policy.event));
+        for policy in self.policies.iter_mut() {
+            policy.commands.retain(|&id| object(id));
+        }
+        self.hotspots.retain(|hotspot| hotspot.target.is_none_or(object));
+        self.lifecycles.retain(|lifecycle| object(lifecycle.subject));
+        self.shared_objects.retain(|&id, _| object(id));
+        self.field_constraints.retain(|field| object(field.object));
+        self.removals.retain(|removal| object(removal.object));
+        self.derived_morphisms.retain(|derived| morphism(derived.morphism));
+        self.optional_morphisms.retain(|&id| morphism(id));
+        self.many_morphisms.retain(|&(id, _)| morphism(id));
+        self.aggregate_invariants.retain(|invariant| object(invariant.root));
+        self.object_metadata.retain(|&id, _| object(id));
+        let roots = &self.aggregate_roots;
+        self.aggregate_metadata.retain(|id, _| roots.contains(id));
+    }
+
+    /// Rename an object.
+    ///
+    /// Equations, cones, invariants and the entity, value object and
+    /// aggregate registries refer to the object by id and stay as they are.
+    /// The object's identity morphism and the cones and lifecycles named
+    /// after it are renamed along with it. Nothing changes if the name is
+    /// empty or taken by another object.
+    pub fn rename_object(
+        &mut self,
+        object: ObjectId,
+        new_name: impl Into<String>,
+    ) -> Result<(), RenameError> {
+        let new_name = new_name.into();
+        let old_name = match self.sketch.graph.get_object(object) {
+            Some(o) => o.name.clone(),
+            None => return Err(RenameError::UnknownObject(object)),
+        };
+        if new_name.trim().is_empty() {
+            return Err(RenameError::EmptyName);
+        }
+        if self
+            .sketch
+            .graph
+            .find_object_by_name(&new_name)
+            .is_some_and(|o| o.id != object)
+        {
+            return Err(RenameError::NameTaken(new_name));
+        }
+
+        let identity_name = format!("id_{}", old_name);
+        let identities: Vec<MorphismId> = self
+            .sketch
+            .graph
+            .morphisms()
+            .filter(|m| m.is_identity && m.source == object && m.name == identity_name)
+            .map(|m| m.id)
+            .collect();
+        for identity in identities {
+            if let Some(mut m) = self.sketch.graph.get_morphism_mut(identity) {
+                m.set_name(format!("id_{}", new_name));
+            }
+        }
+
+        for limit in self.sketch.limits.iter_mut().filter(|l| l.apex == object) {
+            if limit.name == old_name {
+                limit.name = new_name.clone();
+            }
+        }
+        for colimit in self.sketch.colimits.iter_mut().filter(|c| c.apex == object) {
+            if colimit.name == old_name {
+                colimit.name = new_name.clone();
+            }
+        }
+        for lifecycle in self.lifecycles.iter_mut() {
+            if lifecycle.name == old_name {
+                lifecycle.name = new_name.clone();
+            }
+        }
+
+        if let Some(mut o) = self.sketch.graph.get_object_mut(object) {
+            o.set_name(new_name);
+        }
+        Ok(())
+    }
+
+    /// Rename a morphism.
+    ///
+    /// Paths in derived morphism expressions and aggregate invariants that
+    /// go through the morphism are rewritten, as are the field constraints
+    /// and removals naming it as a field of its source. Nothing changes if
+    /// the name is empty or taken by another morphism out of the same
+    /// source.
+    pub fn rename_morphism(
+        &mut self,
+        morphism: MorphismId,
+        new_name: impl Into<String>,
+    ) -> Result<(), RenameError> {
+        let new_name = new_name.into();
+        let (old_name, source) = match self.sketch.graph.get_morphism(morphism) {
+            Some(m) => (m.name.clone(), m.source),
+            None => return Err(RenameError::UnknownMorphism(morphism)),
+        };
+        if new_name.trim().is_empty() {
+            return Err(RenameError::EmptyName);
+        }
+        if self
+            .sketch
+            .graph
+            .outgoing_morphisms(source)
+            .any(|m| m.id != morphism && m.name == new_name)
+        {
+            return Err(RenameError::NameTaken(new_name));
+        }
+
+        // Paths are resolved by name, so rewrite them before the graph changes
+        let graph = &self.sketch.graph;
+        for derived in self.derived_morphisms.iter_mut() {
+            if let Some(start) = graph.get_morphism(derived.morphism).map(|m| m.source) {
+                rename_in_paths(graph, &mut derived.expression, start, morphism, &new_name);
+            }
+        }
+        for invariant in self.aggregate_invariants.iter_mut() {
+            let start = invariant.root;
+            rename_in_paths(graph, &mut invariant.expression, start, morphism, &new_name);
+        }
+
+        for field in self.field_constraints.iter_mut() {
+            if field.object == source && field.field == old_name {
+                field.field = new_name.clone();
+            }
+        }
+        for removal in self.removals.iter_mut() {
+            if removal.object == source && removal.field.as_deref() == Some(&old_name) {
+                removal.field = Some(new_name.clone());
+            }
+        }
+
+        if let Some(mut m) = self.sketch.graph.get_morphism_mut(morphism) {
+            m.set_name(new_name);
+        }
+        Ok(())
+    }
+
+    /// Rename the aggregate rooted at `root`.
+    ///
+    /// The root object keeps its name. Projections the aggregate created
+    /// for its members, named `{aggregate}_{member}`, are renamed with it.
+    pub fn rename_aggregate(
+        &mut self,
+        root: ObjectId,
+        new_name: impl Into<String>,
+    ) -> Result<(), RenameError> {
+        let new_name = new_name.into();
+        let index = self
+            .aggregate_index(root)
+            .map_err(|_| RenameError::NotAnAggregate(root))?;
+        if new_name.trim().is_empty() {
+            return Err(RenameError::EmptyName);
+        }
+        if self
+            .sketch
+            .limits
+            .iter()
+            .enumerate()
+            .any(|(i, l)| i != index && l.is_aggregate && l.name == new_name)
+        {
+            return Err(RenameError::NameTaken(new_name));
+        }
+
+        let old_name = std::mem::replace(&mut self.sketch.limits[index].name, new_name.clone());
+        let generated: Vec<(MorphismId, String)> = self.sketch.limits[index]
+            .projections
+            .iter()
+            .filter_map(|p| {
+                let member = self.sketch.graph.get_object(p.target)?;
+                let morphism = self.sketch.graph.get_morphism(p.morphism)?;
+                (morphism.name == format!("{}_{}", old_name, member.name))
+                    .then(|| (p.morphism, format!("{}_{}", new_name, member.name)))
+            })
+            .collect();
+        for (morphism, name) in generated {
+            // A clash only leaves the generated name as it was
+            let _ = self.rename_morphism(morphism, name);
+        }
+        Ok(())
+    }
+}
+
+/// Rename the steps of an expression's paths from `start` that go through
+/// `renamed`.
+fn rename_in_paths(
+    graph: &Graph,
+    expression: &mut Expr,
+    start: ObjectId,
+    renamed: MorphismId,
+    new_name: &str,
+) {
+    for path in expression.paths_mut() {
+        let mut current = start;
+        for step in path.iter_mut() {
+            let Some(next) = graph
+                .outgoing_morphisms(current)
+                .find(|m| !m.is_identity && &m.name == step)
+            else {
+                break;
+            };
+            if next.id == renamed {
+                *step = new_name.to_string();
+            }
+            current = next.target;
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::sketch::Path;
+    use crate::sketch::{LimitShape, Path};
 
     #[test]
     fn test_create_context() {
@@ -532,6 +1549,23 @@ mod tests {
         assert_eq!(inv.morphism_g, g);
     }
 
+    #[test]
+    fn test_equalizer_invariant_registers_limit() {
+        let mut ctx = BoundedContext::new("Commerce");
+        let order = ctx.add_entity("Order");
+        let money = ctx.add_value_object("Money");
+        let f = ctx.sketch_mut().add_morphism("computedTotal", order, money);
+        let g = ctx.sketch_mut().add_morphism("storedTotal", order, money);
+
+        let equalizer = ctx.add_equalizer_invariant("TotalConsistency", order, f, g, None);
+
+        let limit = ctx.sketch().limits.iter().find(|l| l.apex == equalizer).unwrap();
+        assert_eq!(limit.shape, LimitShape::Equalizer { f, g });
+        assert_eq!(limit.projections[0].morphism, ctx.invariants()[0].inclusion);
+        assert_eq!(ctx.sketch().equations.len(), 1);
+        assert!(crate::validation::validate_context(&ctx).is_ok());
+    }
+
     #[test]
     fn test_add_path_equation() {
         let mut ctx = BoundedContext::new("Commerce");
@@ -547,6 +1581,204 @@ mod tests {
         assert_eq!(ctx.sketch().equations.len(), 1);
     }
 
+    #[test]
+    fn test_aggregate_invariants() {
+        let mut ctx = BoundedContext::new("Commerce");
+        let order = ctx.add_entity("Order");
+        let customer = ctx.add_entity("Customer");
+        ctx.define_aggregate("OrderAggregate", order);
+
+        ctx.add_aggregate_invariant(order, Expr::Path(vec!["paid".into()]), Enforcement::Strict);
+        ctx.add_aggregate_invariant(
+            order,
+            Expr::Path(vec!["customer".into(), "active".into()]),
+            Enforcement::Eventual,
+        );
+
+        let invariants = ctx.invariants_of(order);
+        assert_eq!(invariants.len(), 2);
+        assert_eq!(invariants[0].enforcement, Enforcement::Strict);
+        assert_eq!(invariants[1].enforcement.to_string(), "eventual");
+        assert!(ctx.invariants_of(customer).is_empty());
+        assert_eq!(ctx.aggregate_invariants().len(), 2);
+    }
+
+    // ========== Process Tests ==========
+
+    #[test]
+    fn test_define_process() {
+        let mut ctx = BoundedContext::new("Fulfillment");
+        let placed = ctx.sketch_mut().add_object("OrderPlaced");
+        let reserve = ctx.sketch_mut().add_object("ReserveStock");
+        let reserved = ctx.sketch_mut().add_object("StockReserved");
+        let ship = ctx.sketch_mut().add_object("Ship");
+        let morphisms_before = ctx.graph().morphisms().count();
+
+        ctx.define_process("FulfillmentSaga", &[(placed, reserve), (reserved, ship)]);
+
+        assert_eq!(ctx.processes().len(), 1);
+        let process = ctx.get_process("FulfillmentSaga").unwrap();
+        assert_eq!(process.steps.len(), 2);
+        assert_eq!(process.commands_for(reserved), vec![ship]);
+        // Processes are diagrams, not new structure in the graph
+        assert_eq!(ctx.graph().morphisms().count(), morphisms_before);
+        assert!(ctx.get_process("Unknown").is_none());
+    }
+
+    #[test]
+    fn test_event_storming_elements() {
+        let mut ctx = BoundedContext::new("Fulfillment");
+        let place = ctx.sketch_mut().add_object("PlaceOrder");
+        let placed = ctx.sketch_mut().add_object("OrderPlaced");
+        let reserve = ctx.sketch_mut().add_object("ReserveStock");
+
+        ctx.define_actor("Customer", &[place]);
+        ctx.define_policy("ReserveOnPlacement", placed, &[reserve]);
+        ctx.add_hotspot(Hotspot::on(reserve, "What if stock runs out?"));
+        ctx.add_hotspot(Hotspot::new("Who pays for returns?"));
+
+        assert_eq!(ctx.get_actor("Customer").unwrap().commands, vec![place]);
+        assert_eq!(ctx.get_policy("ReserveOnPlacement").unwrap().event, placed);
+        assert_eq!(ctx.hotspots().len(), 2);
+
+        // Removing an object drops what refers to it
+        assert!(ctx.remove_object(reserve));
+        assert!(ctx.get_policy("ReserveOnPlacement").unwrap().commands.is_empty());
+        assert_eq!(ctx.hotspots(), [Hotspot::new("Who pays for returns?")]);
+
+        assert!(ctx.remove_object(placed));
+        assert!(ctx.policies().is_empty());
+        assert_eq!(ctx.actors().len(), 1);
+    }
+
+    // ========== Lifecycle Tests ==========
+
+    #[test]
+    fn test_define_lifecycle() {
+        let mut ctx = BoundedContext::new("Commerce");
+        let status = ctx.add_enum(
+            "OrderStatus",
+            vec!["Pending".into(), "Confirmed".into(), "Shipped".into()],
+        );
+
+        let lifecycle = ctx.define_lifecycle("Order", status);
+        lifecycle.add_transition("Pending", "Confirmed");
+        lifecycle.add_transition("Confirmed", "Shipped");
+
+        assert_eq!(ctx.lifecycles().len(), 1);
+        let lifecycle = ctx.get_lifecycle(status).unwrap();
+        assert_eq!(lifecycle.name, "Order");
+        assert_eq!(lifecycle.initial_state(), Some("Pending"));
+        assert_eq!(lifecycle.reachable_states().len(), 3);
+    }
+
+    // ========== Field Constraint Tests ==========
+
+    #[test]
+    fn test_constrain_field() {
+        let mut ctx = BoundedContext::new("Billing");
+        let money = ctx.add_value_object("Money");
+
+        ctx.constrain_field(money, "amount", FieldConstraint::Min(0.0));
+        ctx.constrain_field(money, "currency", FieldConstraint::Pattern("[A-Z]{3}".into()));
+        ctx.constrain_field(money, "amount", FieldConstraint::Max(1e6));
+
+        assert_eq!(
+            ctx.field_constraints(money, "amount"),
+            &[FieldConstraint::Min(0.0), FieldConstraint::Max(1e6)]
+        );
+        assert_eq!(ctx.constrained_fields().len(), 2);
+        assert!(ctx.field_constraints(money, "unknown").is_empty());
+    }
+
+    // ========== Versioning Tests ==========
+
+    #[test]
+    fn test_version_and_removals() {
+        let mut ctx = BoundedContext::new("Billing");
+        assert_eq!(ctx.version(), None);
+
+        ctx.set_version(ModelVersion::new(2, 0, 0));
+        let invoice = ctx.add_entity("Invoice");
+        ctx.schedule_removal(invoice, None, ModelVersion::new(3, 0, 0));
+        ctx.schedule_removal(invoice, Some("legacyCode"), ModelVersion::new(2, 5, 0));
+
+        assert_eq!(ctx.version(), Some(ModelVersion::new(2, 0, 0)));
+        assert_eq!(ctx.removal_of(invoice, None).unwrap().note(), "Removed in 3.0");
+        assert_eq!(
+            ctx.removal_of(invoice, Some("legacyCode")).unwrap().removed_in,
+            ModelVersion::new(2, 5, 0)
+        );
+        assert!(ctx.removal_of(invoice, Some("number")).is_none());
+        assert_eq!(ctx.removals().len(), 2);
+    }
+
+    // ========== Derived Morphism Tests ==========
+
+    #[test]
+    fn test_derived_morphisms() {
+        let mut ctx = BoundedContext::new("Commerce");
+        let order = ctx.add_entity("Order");
+        let money = ctx.add_value_object("Money");
+        let placed_at = ctx.sketch_mut().graph.add_morphism("placedAt", order, money);
+        let total = ctx.sketch_mut().graph.add_morphism("total", order, money);
+
+        let expression = Expr::Call {
+            function: "sum".into(),
+            args: vec![Expr::Path(vec!["items".into(), "price".into()])],
+        };
+        ctx.derive_morphism(total, expression.clone());
+
+        assert!(ctx.is_derived(total));
+        assert!(!ctx.is_derived(placed_at));
+        assert_eq!(ctx.derivation_of(total), Some(&expression));
+        assert_eq!(ctx.derived_morphisms().len(), 1);
+        assert_eq!(ctx.derivations_from(order)[0].0.name, "total");
+        assert!(ctx.derivations_from(money).is_empty());
+    }
+
+    // ========== Shared Kernel Tests ==========
+
+    #[test]
+    fn test_include_shared_kernel() {
+        let mut kernel = BoundedContext::new("Identity");
+        kernel.sketch_mut().add_object("UserId");
+        kernel.add_entity("Tenant");
+        kernel.add_enum("Role", vec!["Admin".into(), "Member".into()]);
+
+        let mut ctx = BoundedContext::new("Billing");
+        ctx.sketch_mut().add_object("UserId"); // Local declaration wins
+
+        let included = ctx.include_shared_kernel(&kernel);
+        assert_eq!(included.len(), 2);
+
+        let tenant = ctx.graph().find_object_by_name("Tenant").unwrap().id;
+        assert!(ctx.is_entity(tenant));
+        assert_eq!(ctx.shared_kernel_of(tenant), Some("Identity"));
+
+        let role = ctx.graph().find_object_by_name("Role").unwrap().id;
+        assert_eq!(ctx.get_enum_colimit(role).unwrap().injections.len(), 2);
+
+        let user_id = ctx.graph().find_object_by_name("UserId").unwrap().id;
+        assert_eq!(ctx.shared_kernel_of(user_id), None);
+        assert_eq!(ctx.shared_kernels(), vec!["Identity"]);
+    }
+
+    #[test]
+    fn test_common_shared_kernels() {
+        let mut kernel = BoundedContext::new("Identity");
+        kernel.sketch_mut().add_object("UserId");
+
+        let mut billing = BoundedContext::new("Billing");
+        let mut shipping = BoundedContext::new("Shipping");
+        let other = BoundedContext::new("Other");
+        billing.include_shared_kernel(&kernel);
+        shipping.include_shared_kernel(&kernel);
+
+        assert_eq!(billing.common_shared_kernels(&shipping), vec!["Identity"]);
+        assert!(billing.common_shared_kernels(&other).is_empty());
+    }
+
     // ========== Integration Tests ==========
 
     #[test]
@@ -600,4 +1832,143 @@ mod tests {
             assert!(ctx.get_value_object_limit(*vo).is_some());
         }
     }
+
+    // ========== Refactoring Tests ==========
+
+    #[test]
+    fn test_rename_object() {
+        let mut ctx = BoundedContext::new("Commerce");
+        let customer = ctx.add_entity("Customer");
+        let status = ctx.add_enum("Status", vec!["Active".into()]);
+        ctx.define_aggregate("Customer", customer);
+
+        ctx.rename_object(customer, "Client").unwrap();
+
+        assert_eq!(ctx.graph().get_object(customer).unwrap().name, "Client");
+        let identity = ctx.get_entity_identity(customer).unwrap();
+        assert_eq!(ctx.graph().get_morphism(identity).unwrap().name, "id_Client");
+        assert_eq!(ctx.get_aggregate(customer).unwrap().name, "Client");
+        assert!(ctx.is_entity(customer) && ctx.is_aggregate_root(customer));
+
+        assert_eq!(
+            ctx.rename_object(customer, "Status"),
+            Err(RenameError::NameTaken("Status".into()))
+        );
+        assert_eq!(ctx.rename_object(status, " "), Err(RenameError::EmptyName));
+        assert_eq!(
+            ctx.rename_object(ObjectId(99), "Ghost"),
+            Err(RenameError::UnknownObject(ObjectId(99)))
+        );
+    }
+
+    #[test]
+    fn test_rename_morphism_updates_references() {
+        let mut ctx = BoundedContext::new("Commerce");
+        let order = ctx.add_entity("Order");
+        let customer = ctx.add_entity("Customer");
+        let flag = ctx.sketch_mut().add_object("Boolean");
+        let text = ctx.sketch_mut().add_object("String");
+        let placed_by = ctx.sketch_mut().add_morphism("placedBy", order, customer);
+        ctx.sketch_mut().add_morphism("active", customer, flag);
+        let name = ctx.sketch_mut().add_morphism("name", customer, text);
+        let buyer_active = ctx.sketch_mut().add_morphism("buyerActive", order, flag);
+        ctx.derive_morphism(
+            buyer_active,
+            Expr::Path(vec!["placedBy".into(), "active".into()]),
+        );
+        ctx.add_aggregate_invariant(
+            order,
+            Expr::Path(vec!["placedBy".into(), "active".into()]),
+            Enforcement::Strict,
+        );
+        ctx.constrain_field(order, "placedBy", FieldConstraint::MinLength(1));
+
+        ctx.rename_morphism(placed_by, "buyer").unwrap();
+
+        let renamed = Expr::Path(vec!["buyer".into(), "active".into()]);
+        assert_eq!(ctx.derivation_of(buyer_active), Some(&renamed));
+        assert_eq!(ctx.aggregate_invariants()[0].expression, renamed);
+        assert_eq!(ctx.field_constraints(order, "buyer").len(), 1);
+
+        assert_eq!(
+            ctx.rename_morphism(name, "active"),
+            Err(RenameError::NameTaken("active".into()))
+        );
+    }
+
+    #[test]
+    fn test_move_and_split_aggregates() {
+        let mut ctx = BoundedContext::new("Commerce");
+        let order = ctx.add_entity("Order");
+        let line_item = ctx.add_entity("LineItem");
+        let payment = ctx.add_entity("Payment");
+        let cart = ctx.add_entity("Cart");
+        ctx.define_aggregate_with_members("Order", order, &[line_item, payment]);
+        ctx.define_aggregate("Cart", cart);
+
+        let result = ctx.move_to_aggregate(line_item, order, cart).unwrap();
+        assert!(result.is_ok());
+        let members: Vec<_> = ctx.get_aggregate(cart).unwrap().component_objects().collect();
+        assert_eq!(members, vec![line_item]);
+        assert!(ctx.graph().find_morphism_by_name("Order_LineItem").is_none());
+        assert!(ctx.graph().find_morphism_by_name("Cart_LineItem").is_some());
+
+        ctx.split_aggregate(order, payment, &[]).unwrap();
+        assert!(ctx.is_aggregate_root(payment));
+        assert_eq!(ctx.get_aggregate(order).unwrap().projections.len(), 0);
+        assert_eq!(ctx.get_aggregate(payment).unwrap().name, "Payment");
+
+        assert_eq!(
+            ctx.move_to_aggregate(line_item, order, cart).unwrap_err(),
+            AggregateError::NotAMember {
+                aggregate: "Order".into(),
+                object: line_item
+            }
+        );
+        assert_eq!(
+            ctx.split_aggregate(line_item, order, &[]).unwrap_err(),
+            AggregateError::NotAnAggregate(line_item)
+        );
+    }
+
+    #[test]
+    fn test_merge_aggregates() {
+        let mut ctx = BoundedContext::new("Commerce");
+        let order = ctx.add_entity("Order");
+        let shipment = ctx.add_entity("Shipment");
+        let parcel = ctx.add_entity("Parcel");
+        let flag = ctx.sketch_mut().add_object("Boolean");
+        ctx.sketch_mut().add_morphism("delivered", shipment, flag);
+        ctx.define_aggregate("Order", order);
+        ctx.define_aggregate_with_members("Shipment", shipment, &[parcel]);
+        ctx.add_aggregate_invariant(
+            shipment,
+            Expr::Path(vec!["delivered".into()]),
+            Enforcement::Strict,
+        );
+
+        // A strict invariant of Order reaching Shipment is flagged before the merge
+        ctx.sketch_mut().add_morphism("shipment", order, shipment);
+        ctx.add_aggregate_invariant(
+            order,
+            Expr::Path(vec!["shipment".into(), "delivered".into()]),
+            Enforcement::Strict,
+        );
+        let result = validate_aggregate_boundaries(&ctx);
+        assert!(result.warnings().any(|w| w.code == "W0002"));
+
+        let result = ctx.merge_aggregates(order, shipment).unwrap();
+        assert!(!result.warnings().any(|w| w.code == "W0002"));
+        assert!(!ctx.is_aggregate_root(shipment));
+        assert!(ctx.get_aggregate(shipment).is_none());
+
+        let members: Vec<_> = ctx.get_aggregate(order).unwrap().component_objects().collect();
+        assert_eq!(members, vec![shipment, parcel]);
+        let rerooted = &ctx.invariants_of(order)[0].expression;
+        assert_eq!(rerooted, &Expr::Path(vec!["shipment".into(), "delivered".into()]));
+        assert_eq!(
+            ctx.merge_aggregates(order, order).unwrap_err(),
+            AggregateError::SameAggregate(order)
+        );
+    }
 }
@@ -0,0 +1,230 @@
+//! How well the context maps cover the concepts contexts share.
+//!
+//! Two contexts that both model a `Customer` integrate somewhere, whether or
+//! not the model says so. [`Workspace::context_map_coverage`] finds the
+//! public concepts each pair of contexts shares by name and checks that a
+//! context map between them maps each one. The opt-in lint rule W0090, see
+//! [`ContextMapCoverage`](crate::lint::ContextMapCoverage), reports the
+//! gaps.
+//!
+//! A concept counts as mapped when a map between the pair maps it, when
+//! both contexts take it from the same shared kernel, or when the pair goes
+//! separate ways. Conformist and shared kernel maps must map every object
+//! anyway (E0069), so their gaps are errors already.
+
+use crate::context::BoundedContext;
+use crate::mapping::{NamedContextMap, RelationshipPattern};
+use crate::workspace::Workspace;
+use std::collections::BTreeSet;
+
+/// The concepts a pair of contexts shares and how the maps cover them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedConcepts {
+    /// The context declared first
+    pub first: String,
+
+    /// The context declared second
+    pub second: String,
+
+    /// Names of the context maps between the two, in declaration order
+    pub maps: Vec<String>,
+
+    /// Concepts both contexts declare, in name order
+    pub concepts: Vec<String>,
+
+    /// Shared concepts no map, shared kernel or separate ways accounts for
+    pub unmapped: Vec<String>,
+}
+
+/// The coverage of shared concepts by context maps across a model.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MapCoverage {
+    /// Pairs of contexts sharing at least one concept, in declaration order
+    pub pairs: Vec<SharedConcepts>,
+}
+
+impl MapCoverage {
+    /// Get the number of shared concepts, counted once per pair.
+    pub fn concepts(&self) -> usize {
+        self.pairs.iter().map(|p| p.concepts.len()).sum()
+    }
+
+    /// Get the number of shared concepts the maps account for.
+    pub fn mapped(&self) -> usize {
+        self.pairs
+            .iter()
+            .map(|p| p.concepts.len() - p.unmapped.len())
+            .sum()
+    }
+
+    /// Get the percentage of shared concepts the maps account for, 100
+    /// when contexts share nothing.
+    pub fn percent(&self) -> f64 {
+        match self.concepts() {
+            0 => 100.0,
+            concepts => self.mapped() as f64 * 100.0 / concepts as f64,
+        }
+    }
+}
+
+/// Get the names of a context's public objects: entities, value objects,
+/// enums and aggregate roots, leaving out the members internal to an
+/// aggregate.
+fn public_concepts(context: &BoundedContext) -> BTreeSet<&str> {
+    let graph = context.graph();
+    let internal: BTreeSet<_> = context
+        .aggregate_roots()
+        .iter()
+        .filter_map(|&root| context.get_aggregate(root))
+        .flat_map(|aggregate| aggregate.component_objects())
+        .filter(|&object| !context.is_aggregate_root(object) && context.is_entity(object))
+        .collect();
+    graph
+        .objects()
+        .filter(|o| !internal.contains(&o.id))
+        .filter(|o| {
+            context.is_entity(o.id)
+                || context.is_value_object(o.id)
+                || context.is_aggregate_root(o.id)
+                || context.get_enum_colimit(o.id).is_some()
+        })
+        .map(|o| o.name.as_str())
+        .collect()
+}
+
+/// Check if both contexts take a concept from the same shared kernel.
+fn from_same_kernel(first: &BoundedContext, second: &BoundedContext, concept: &str) -> bool {
+    let kernel = |context: &BoundedContext| {
+        context
+            .graph()
+            .find_object_by_name(concept)
+            .and_then(|o| context.shared_kernel_of(o.id))
+            .map(str::to_string)
+    };
+    kernel(first).is_some_and(|k| kernel(second) == Some(k))
+}
+
+/// Check if a map maps a concept, on either side.
+fn maps_concept(map: &NamedContextMap, concept: &str) -> bool {
+    map.object_mappings()
+        .iter()
+        .any(|m| m.source == concept || m.target == concept)
+}
+
+impl Workspace {
+    /// Find the concepts pairs of contexts share and how the context maps
+    /// cover them.
+    pub fn context_map_coverage(&self) -> MapCoverage {
+        let mut coverage = MapCoverage::default();
+        for (i, first) in self.contexts.iter().enumerate() {
+            let first_concepts = public_concepts(first);
+            for second in &self.contexts[i + 1..] {
+                let concepts: Vec<&str> = public_concepts(second)
+                    .intersection(&first_concepts)
+                    .copied()
+                    .collect();
+                if concepts.is_empty() {
+                    continue;
+                }
+                let maps: Vec<&NamedContextMap> = self
+                    .context_maps
+                    .iter()
+                    .filter(|m| {
+                        let ends = (m.source_context(), m.target_context());
+                        ends == (first.name(), second.name())
+                            || ends == (second.name(), first.name())
+                    })
+                    .collect();
+                let separate = maps
+                    .iter()
+                    .any(|m| m.pattern() == RelationshipPattern::SeparateWays);
+                let unmapped = concepts
+                    .iter()
+                    .filter(|&&concept| {
+                        !separate
+                            && !from_same_kernel(first, second, concept)
+                            && !maps.iter().any(|m| maps_concept(m, concept))
+                    })
+                    .map(|c| c.to_string())
+                    .collect();
+                coverage.pairs.push(SharedConcepts {
+                    first: first.name().to_string(),
+                    second: second.name().to_string(),
+                    maps: maps.iter().map(|m| m.name().to_string()).collect(),
+                    concepts: concepts.iter().map(|c| c.to_string()).collect(),
+                    unmapped,
+                });
+            }
+        }
+        coverage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapping::NamedObjectMapping;
+
+    fn context(name: &str, entities: &[&str]) -> BoundedContext {
+        let mut context = BoundedContext::new(name);
+        for entity in entities {
+            context.add_entity(*entity);
+        }
+        context
+    }
+
+    #[test]
+    fn test_context_map_coverage() {
+        let mut workspace = Workspace::new();
+        workspace.add_context(context("Sales", &["Customer", "Order", "Quote"]));
+        workspace.add_context(context("Billing", &["Customer", "Order", "Invoice"]));
+        workspace.add_context(context("Shipping", &["Order", "Parcel"]));
+        workspace.add_context(context("Marketing", &["Campaign"]));
+
+        let mut map = NamedContextMap::new(
+            "SalesToBilling",
+            "Sales",
+            "Billing",
+            RelationshipPattern::CustomerSupplier,
+        );
+        map.add_object_mapping(NamedObjectMapping {
+            source: "Order".into(),
+            target: "Order".into(),
+            description: None,
+        });
+        workspace.add_context_map(map);
+
+        let coverage = workspace.context_map_coverage();
+        assert_eq!(coverage.pairs.len(), 3);
+        let sales_billing = &coverage.pairs[0];
+        assert_eq!(sales_billing.maps, vec!["SalesToBilling"]);
+        assert_eq!(sales_billing.concepts, vec!["Customer", "Order"]);
+        assert_eq!(sales_billing.unmapped, vec!["Customer"]);
+        assert!(coverage.pairs[1].maps.is_empty());
+        assert_eq!(coverage.mapped(), 1);
+        assert_eq!(coverage.concepts(), 4);
+        assert_eq!(coverage.percent(), 25.0);
+
+        workspace.add_context_map(NamedContextMap::new(
+            "ShippingAndBilling",
+            "Shipping",
+            "Billing",
+            RelationshipPattern::SeparateWays,
+        ));
+        assert_eq!(workspace.context_map_coverage().percent(), 50.0);
+        assert_eq!(Workspace::new().context_map_coverage().percent(), 100.0);
+    }
+
+    #[test]
+    fn test_internal_members_are_not_shared() {
+        let mut sales = context("Sales", &["Order"]);
+        let order = sales.graph().find_object_by_name("Order").unwrap().id;
+        let line = sales.add_entity("LineItem");
+        sales.define_aggregate_with_members("Order", order, &[line]);
+
+        let mut workspace = Workspace::new();
+        workspace.add_context(sales);
+        workspace.add_context(context("Billing", &["LineItem"]));
+        assert!(workspace.context_map_coverage().pairs.is_empty());
+    }
+}
@@ -329,7 +329,12 @@ pub fn suggest_similar_with_threshold<'a>(
 
 /// Generate a "did you mean?" help message.
 pub fn did_you_mean(name: &str, candidates: &[&str]) -> Option<String> {
-    suggest_similar(name, candidates).map(|suggestion| format!("did you mean `{}`?", suggestion))
+    suggest_similar(name, candidates).map(did_you_mean_correction)
+}
+
+/// Generate a "did you mean?" help message for a known correction.
+pub fn did_you_mean_correction(correction: &str) -> String {
+    format!("did you mean `{}`?", correction)
 }
 
 /// Generate a list of available options as a note.
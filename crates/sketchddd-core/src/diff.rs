@@ -0,0 +1,671 @@
+//! Structural differences between two versions of a domain model.
+//!
+//! A diff compares bounded contexts by name and reports the objects,
+//! morphisms and enum variants that were added, removed or changed. Each
+//! change is classified by its [`Compatibility`]: removing or retyping
+//! something that consumers may depend on is breaking, as is adding a
+//! required morphism to an existing object; other additions are compatible;
+//! changes to descriptions and versions are cosmetic.
+//!
+//! When both versions of a context declare a [`ModelVersion`], the diff also
+//! checks that breaking changes come with a major version bump.
+//!
+//! [`ModelVersion`]: crate::version::ModelVersion
+
+use crate::context::BoundedContext;
+use crate::sketch::ObjectId;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// What happened to a model element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChangeKind {
+    /// The element exists only in the new model
+    Added,
+    /// The element exists only in the old model
+    Removed,
+    /// The element exists in both models but differs
+    Changed,
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChangeKind::Added => write!(f, "added"),
+            ChangeKind::Removed => write!(f, "removed"),
+            ChangeKind::Changed => write!(f, "changed"),
+        }
+    }
+}
+
+/// Whether a change can break consumers of the model.
+///
+/// Variants are ordered by severity, from cosmetic to breaking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Compatibility {
+    /// Only documentation changed; the structure is the same
+    Cosmetic,
+    /// Existing consumers keep working
+    Compatible,
+    /// Existing consumers may stop working
+    Breaking,
+}
+
+impl fmt::Display for Compatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Compatibility::Cosmetic => write!(f, "cosmetic"),
+            Compatibility::Compatible => write!(f, "compatible"),
+            Compatibility::Breaking => write!(f, "breaking"),
+        }
+    }
+}
+
+impl FromStr for Compatibility {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "cosmetic" => Ok(Compatibility::Cosmetic),
+            "compatible" => Ok(Compatibility::Compatible),
+            "breaking" => Ok(Compatibility::Breaking),
+            _ => Err(format!(
+                "Unknown severity '{}': expected breaking, compatible or cosmetic",
+                s
+            )),
+        }
+    }
+}
+
+/// A single difference between two models.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelChange {
+    /// Name of the bounded context the change belongs to
+    pub context: String,
+
+    /// What happened to the element
+    pub kind: ChangeKind,
+
+    /// Human-readable description, e.g. `entity 'Invoice'`
+    pub element: String,
+
+    /// Additional detail, e.g. the old and new target of a morphism
+    pub detail: Option<String>,
+
+    /// Whether the change is breaking
+    pub compatibility: Compatibility,
+}
+
+impl ModelChange {
+    fn new(
+        context: &str,
+        kind: ChangeKind,
+        element: impl Into<String>,
+        compatibility: Compatibility,
+    ) -> Self {
+        Self {
+            context: context.to_string(),
+            kind,
+            element: element.into(),
+            detail: None,
+            compatibility,
+        }
+    }
+
+    fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Check if this change is breaking.
+    pub fn is_breaking(&self) -> bool {
+        self.compatibility == Compatibility::Breaking
+    }
+}
+
+impl fmt::Display for ModelChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} {}", self.context, self.element, self.kind)?;
+        if let Some(detail) = &self.detail {
+            write!(f, " ({})", detail)?;
+        }
+        Ok(())
+    }
+}
+
+/// The differences between two versions of a model.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelDiff {
+    /// All changes, grouped by context in model order
+    pub changes: Vec<ModelChange>,
+
+    /// Problems with declared versions, e.g. a missing major bump
+    pub version_warnings: Vec<String>,
+}
+
+impl ModelDiff {
+    /// Check if the models are structurally identical.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Get the breaking changes.
+    pub fn breaking_changes(&self) -> impl Iterator<Item = &ModelChange> {
+        self.changes.iter().filter(|c| c.is_breaking())
+    }
+
+    /// Check if any change is breaking.
+    pub fn has_breaking_changes(&self) -> bool {
+        self.breaking_changes().next().is_some()
+    }
+
+    /// Get the most severe classification among the changes, or `None` if
+    /// there are no changes.
+    pub fn max_severity(&self) -> Option<Compatibility> {
+        self.changes.iter().map(|c| c.compatibility).max()
+    }
+
+    /// Group the changes by context, in the order the contexts first
+    /// appear, keeping the order of the changes.
+    pub fn by_context(&self) -> Vec<(&str, Vec<&ModelChange>)> {
+        let mut groups: Vec<(&str, Vec<&ModelChange>)> = Vec::new();
+        for change in &self.changes {
+            match groups.iter_mut().find(|(name, _)| *name == change.context) {
+                Some((_, changes)) => changes.push(change),
+                None => groups.push((&change.context, vec![change])),
+            }
+        }
+        groups
+    }
+
+    /// Order the changes of each context as removals, then other changes,
+    /// then additions, keeping the contexts in the order they first appear.
+    fn sort(&mut self) {
+        let mut contexts: Vec<String> = Vec::new();
+        for change in &self.changes {
+            if !contexts.contains(&change.context) {
+                contexts.push(change.context.clone());
+            }
+        }
+        self.changes.sort_by_key(|c| {
+            let context = contexts.iter().position(|name| *name == c.context);
+            let kind = match c.kind {
+                ChangeKind::Removed => 0,
+                ChangeKind::Changed => 1,
+                ChangeKind::Added => 2,
+            };
+            (context, kind)
+        });
+    }
+}
+
+/// Compare two models context by context.
+///
+/// Contexts are matched by name; a context present on one side only is
+/// reported as a single added or removed change.
+pub fn diff_models(old: &[BoundedContext], new: &[BoundedContext]) -> ModelDiff {
+    let mut diff = ModelDiff::default();
+
+    for old_ctx in old {
+        match new.iter().find(|c| c.name() == old_ctx.name()) {
+            Some(new_ctx) => {
+                let context_diff = diff_contexts(old_ctx, new_ctx);
+                diff.changes.extend(context_diff.changes);
+                diff.version_warnings.extend(context_diff.version_warnings);
+            }
+            None => diff.changes.push(ModelChange::new(
+                old_ctx.name(),
+                ChangeKind::Removed,
+                format!("context '{}'", old_ctx.name()),
+                Compatibility::Breaking,
+            )),
+        }
+    }
+
+    for new_ctx in new {
+        if !old.iter().any(|c| c.name() == new_ctx.name()) {
+            diff.changes.push(ModelChange::new(
+                new_ctx.name(),
+                ChangeKind::Added,
+                format!("context '{}'", new_ctx.name()),
+                Compatibility::Compatible,
+            ));
+        }
+    }
+
+    diff.sort();
+    diff
+}
+
+/// Compare two versions of the same bounded context.
+pub fn diff_contexts(old: &BoundedContext, new: &BoundedContext) -> ModelDiff {
+    let mut diff = ModelDiff::default();
+    let name = new.name();
+
+    diff_objects(old, new, &mut diff);
+    diff_morphisms(old, new, &mut diff);
+    diff_variants(old, new, &mut diff);
+
+    match (old.version(), new.version()) {
+        (Some(old_version), Some(new_version)) => {
+            if old_version != new_version {
+                diff.changes.push(
+                    ModelChange::new(name, ChangeKind::Changed, "version", Compatibility::Cosmetic)
+                        .with_detail(format!("{} -> {}", old_version, new_version)),
+                );
+            }
+            if diff.has_breaking_changes() && !old_version.is_major_bump(&new_version) {
+                diff.version_warnings.push(format!(
+                    "Context '{}' has breaking changes but its version goes from {} to {}; expected a major version bump",
+                    name, old_version, new_version
+                ));
+            }
+        }
+        (None, Some(new_version)) => diff.changes.push(
+            ModelChange::new(name, ChangeKind::Added, "version", Compatibility::Cosmetic)
+                .with_detail(new_version.to_string()),
+        ),
+        _ => {}
+    }
+
+    diff
+}
+
+fn diff_objects(old: &BoundedContext, new: &BoundedContext, diff: &mut ModelDiff) {
+    let name = new.name();
+
+    // An object missing by name but present by stable id was renamed
+    let renamed = |old_obj: &crate::sketch::Object| {
+        new.graph()
+            .object_by_uid(old_obj.uid())
+            .filter(|o| old.graph().find_object_by_name(&o.name).is_none())
+    };
+
+    for old_obj in old.graph().objects() {
+        let old_kind = object_kind(old, old_obj.id);
+        match new.graph().find_object_by_name(&old_obj.name) {
+            None if renamed(old_obj).is_some() => {
+                let new_name = renamed(old_obj).map(|o| o.name.as_str()).unwrap_or_default();
+                diff.changes.push(
+                    ModelChange::new(
+                        name,
+                        ChangeKind::Changed,
+                        format!("{} '{}'", old_kind, old_obj.name),
+                        Compatibility::Breaking,
+                    )
+                    .with_detail(format!("renamed to {}", new_name)),
+                );
+            }
+            None => {
+                let mut change = ModelChange::new(
+                    name,
+                    ChangeKind::Removed,
+                    format!("{} '{}'", old_kind, old_obj.name),
+                    Compatibility::Breaking,
+                );
+                if let Some(removal) = old.removal_of(old_obj.id, None) {
+                    change = change.with_detail(format!("scheduled for removal in {}", removal.removed_in));
+                }
+                diff.changes.push(change);
+            }
+            Some(new_obj) => {
+                let new_kind = object_kind(new, new_obj.id);
+                if old_kind != new_kind {
+                    diff.changes.push(
+                        ModelChange::new(
+                            name,
+                            ChangeKind::Changed,
+                            format!("'{}'", old_obj.name),
+                            Compatibility::Breaking,
+                        )
+                        .with_detail(format!("{} -> {}", old_kind, new_kind)),
+                    );
+                } else if old_obj.description != new_obj.description {
+                    diff.changes.push(
+                        ModelChange::new(
+                            name,
+                            ChangeKind::Changed,
+                            format!("{} '{}'", old_kind, old_obj.name),
+                            Compatibility::Cosmetic,
+                        )
+                        .with_detail("description"),
+                    );
+                }
+            }
+        }
+    }
+
+    for new_obj in new.graph().objects() {
+        let was_renamed = old
+            .graph()
+            .object_by_uid(new_obj.uid())
+            .is_some_and(|o| new.graph().find_object_by_name(&o.name).is_none());
+        if old.graph().find_object_by_name(&new_obj.name).is_none() && !was_renamed {
+            diff.changes.push(ModelChange::new(
+                name,
+                ChangeKind::Added,
+                format!("{} '{}'", object_kind(new, new_obj.id), new_obj.name),
+                Compatibility::Compatible,
+            ));
+        }
+    }
+}
+
+fn diff_morphisms(old: &BoundedContext, new: &BoundedContext, diff: &mut ModelDiff) {
+    let name = new.name();
+    let object_name = |ctx: &BoundedContext, id: ObjectId| {
+        ctx.graph()
+            .get_object(id)
+            .map(|o| o.name.clone())
+            .unwrap_or_default()
+    };
+    let object_uid = |ctx: &BoundedContext, id: ObjectId| {
+        ctx.graph().get_object(id).map(|o| o.uid()).unwrap_or_default()
+    };
+    // Morphisms are identified by their name and source object, which is
+    // matched by stable id so that renaming it does not count
+    let key = |ctx: &BoundedContext, m: &crate::sketch::Morphism| {
+        (object_uid(ctx, m.source), m.name.clone())
+    };
+
+    let old_morphisms: Vec<_> = old.graph().morphisms().filter(|m| !m.is_identity).collect();
+    let new_morphisms: Vec<_> = new.graph().morphisms().filter(|m| !m.is_identity).collect();
+
+    for old_m in &old_morphisms {
+        let element = format!("morphism '{}.{}'", object_name(old, old_m.source), old_m.name);
+        match new_morphisms.iter().find(|m| key(new, m) == key(old, old_m)) {
+            None => {
+                let mut change =
+                    ModelChange::new(name, ChangeKind::Removed, element, Compatibility::Breaking);
+                if let Some(removal) = old.removal_of(old_m.source, Some(&old_m.name)) {
+                    change = change.with_detail(format!("scheduled for removal in {}", removal.removed_in));
+                }
+                diff.changes.push(change);
+            }
+            Some(new_m) => {
+                let old_target = object_name(old, old_m.target);
+                let new_target = object_name(new, new_m.target);
+                let was_optional = old.is_optional(old_m.id);
+                let is_optional = new.is_optional(new_m.id);
+                if object_uid(old, old_m.target) != object_uid(new, new_m.target) {
+                    diff.changes.push(
+                        ModelChange::new(name, ChangeKind::Changed, element, Compatibility::Breaking)
+                            .with_detail(format!("target {} -> {}", old_target, new_target)),
+                    );
+                } else if was_optional != is_optional {
+                    // Requiring a value breaks producers; relaxing it does not
+                    let (compatibility, detail) = if was_optional {
+                        (Compatibility::Breaking, "optional -> required")
+                    } else {
+                        (Compatibility::Compatible, "required -> optional")
+                    };
+                    diff.changes.push(
+                        ModelChange::new(name, ChangeKind::Changed, element, compatibility)
+                            .with_detail(detail),
+                    );
+                } else if old_m.description != new_m.description {
+                    diff.changes.push(
+                        ModelChange::new(name, ChangeKind::Changed, element, Compatibility::Cosmetic)
+                            .with_detail("description"),
+                    );
+                }
+            }
+        }
+    }
+
+    for new_m in &new_morphisms {
+        if !old_morphisms.iter().any(|m| key(old, m) == key(new, new_m)) {
+            let source = object_name(new, new_m.source);
+            let element = format!("morphism '{}.{}'", source, new_m.name);
+            // Existing elements of the source have no value for a new
+            // required morphism, so only optional ones can be added freely
+            let change = if new.is_optional(new_m.id) {
+                ModelChange::new(name, ChangeKind::Added, element, Compatibility::Compatible)
+                    .with_detail("optional")
+            } else if old.graph().object_by_uid(object_uid(new, new_m.source)).is_some() {
+                ModelChange::new(name, ChangeKind::Added, element, Compatibility::Breaking)
+                    .with_detail("required")
+            } else {
+                ModelChange::new(name, ChangeKind::Added, element, Compatibility::Compatible)
+            };
+            diff.changes.push(change);
+        }
+    }
+}
+
+fn diff_variants(old: &BoundedContext, new: &BoundedContext, diff: &mut ModelDiff) {
+    let name = new.name();
+
+    for old_colimit in &old.sketch().colimits {
+        let Some(new_colimit) = new.sketch().colimits.iter().find(|c| c.name == old_colimit.name) else {
+            continue;
+        };
+        let old_variants: Vec<&str> = old_colimit.variant_names().collect();
+        let new_variants: Vec<&str> = new_colimit.variant_names().collect();
+
+        for variant in &old_variants {
+            if !new_variants.contains(variant) {
+                diff.changes.push(ModelChange::new(
+                    name,
+                    ChangeKind::Removed,
+                    format!("variant '{}.{}'", old_colimit.name, variant),
+                    Compatibility::Breaking,
+                ));
+            }
+        }
+        for variant in &new_variants {
+            if !old_variants.contains(variant) {
+                diff.changes.push(ModelChange::new(
+                    name,
+                    ChangeKind::Added,
+                    format!("variant '{}.{}'", new_colimit.name, variant),
+                    Compatibility::Compatible,
+                ));
+            }
+        }
+    }
+}
+
+/// Describe the kind of an object for change descriptions.
+fn object_kind(ctx: &BoundedContext, id: ObjectId) -> &'static str {
+    if ctx.is_entity(id) {
+        "entity"
+    } else if ctx.is_value_object(id) {
+        "value object"
+    } else if ctx.get_enum_colimit(id).is_some() {
+        "enum"
+    } else {
+        "object"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::version::ModelVersion;
+
+    fn billing_v1() -> BoundedContext {
+        let mut ctx = BoundedContext::new("Billing");
+        let invoice = ctx.add_entity("Invoice");
+        let money = ctx.add_value_object("Money");
+        ctx.add_enum("Status", vec!["Draft".into(), "Paid".into()]);
+        ctx.sketch_mut().graph.add_morphism("total", invoice, money);
+        ctx.set_version(ModelVersion::new(1, 0, 0));
+        ctx
+    }
+
+    #[test]
+    fn test_identical_models() {
+        let diff = diff_contexts(&billing_v1(), &billing_v1());
+        assert!(diff.is_empty());
+        assert!(diff.version_warnings.is_empty());
+    }
+
+    #[test]
+    fn test_additions_are_compatible() {
+        let mut new = billing_v1();
+        let invoice = new.graph().find_object_by_name("Invoice").unwrap().id;
+        let customer = new.add_entity("Customer");
+        let customer_of = new.sketch_mut().graph.add_morphism("customer", invoice, customer);
+        new.mark_optional(customer_of);
+        new.set_version(ModelVersion::new(1, 1, 0));
+
+        let diff = diff_contexts(&billing_v1(), &new);
+        assert!(!diff.has_breaking_changes());
+        let elements: Vec<_> = diff.changes.iter().map(|c| c.element.as_str()).collect();
+        assert_eq!(elements, vec!["entity 'Customer'", "morphism 'Invoice.customer'", "version"]);
+        assert!(diff.version_warnings.is_empty());
+    }
+
+    #[test]
+    fn test_removals_are_breaking() {
+        let mut old = billing_v1();
+        let money = old.graph().find_object_by_name("Money").unwrap().id;
+        old.schedule_removal(money, None, ModelVersion::new(2, 0, 0));
+
+        let mut new = BoundedContext::new("Billing");
+        new.add_entity("Invoice");
+        new.add_enum("Status", vec!["Draft".into()]);
+        new.set_version(ModelVersion::new(1, 1, 0));
+
+        let diff = diff_contexts(&old, &new);
+        let breaking: Vec<String> = diff.breaking_changes().map(|c| c.to_string()).collect();
+        assert_eq!(
+            breaking,
+            vec![
+                "Billing: value object 'Money' removed (scheduled for removal in 2.0)",
+                "Billing: morphism 'Invoice.total' removed",
+                "Billing: variant 'Status.Paid' removed",
+            ]
+        );
+        assert_eq!(diff.version_warnings.len(), 1);
+        assert!(diff.version_warnings[0].contains("expected a major version bump"));
+    }
+
+    #[test]
+    fn test_kind_and_target_changes_are_breaking() {
+        let mut new = BoundedContext::new("Billing");
+        let invoice = new.add_value_object("Invoice");
+        new.add_value_object("Money");
+        let amount = new.sketch_mut().add_object("Amount");
+        new.add_enum("Status", vec!["Draft".into(), "Paid".into()]);
+        new.sketch_mut().graph.add_morphism("total", invoice, amount);
+        new.set_version(ModelVersion::new(2, 0, 0));
+
+        let diff = diff_contexts(&billing_v1(), &new);
+        let changed: Vec<String> = diff
+            .changes
+            .iter()
+            .filter(|c| c.kind == ChangeKind::Changed && c.is_breaking())
+            .map(|c| c.to_string())
+            .collect();
+        assert_eq!(
+            changed,
+            vec![
+                "Billing: 'Invoice' changed (entity -> value object)",
+                "Billing: morphism 'Invoice.total' changed (target Money -> Amount)",
+            ]
+        );
+        // Major bump, so no version warning
+        assert!(diff.version_warnings.is_empty());
+    }
+
+    #[test]
+    fn test_diff_models_matches_contexts_by_name() {
+        let old = vec![billing_v1(), BoundedContext::new("Legacy")];
+        let new = vec![billing_v1(), BoundedContext::new("Shipping")];
+
+        let diff = diff_models(&old, &new);
+        assert_eq!(diff.changes.len(), 2);
+        assert_eq!(diff.changes[0].element, "context 'Legacy'");
+        assert!(diff.changes[0].is_breaking());
+        assert_eq!(diff.changes[1].element, "context 'Shipping'");
+        assert_eq!(diff.changes[1].kind, ChangeKind::Added);
+    }
+
+    #[test]
+    fn test_changes_by_context() {
+        let mut new = billing_v1();
+        let money = new.graph().find_object_by_name("Money").unwrap().id;
+        new.rename_object(money, "Amount").unwrap();
+        new.add_entity("Customer");
+        let old = vec![billing_v1(), BoundedContext::new("Legacy")];
+        let new = vec![BoundedContext::new("Shipping"), new];
+
+        let diff = diff_models(&old, &new);
+        let groups: Vec<(&str, Vec<&str>)> = diff
+            .by_context()
+            .into_iter()
+            .map(|(context, changes)| {
+                (context, changes.iter().map(|c| c.element.as_str()).collect())
+            })
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                ("Billing", vec!["value object 'Money'", "entity 'Customer'"]),
+                ("Legacy", vec!["context 'Legacy'"]),
+                ("Shipping", vec!["context 'Shipping'"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_renames_are_matched_by_stable_id() {
+        let mut new = billing_v1();
+        let invoice = new.graph().find_object_by_name("Invoice").unwrap().id;
+        new.rename_object(invoice, "Bill").unwrap();
+
+        let diff = diff_contexts(&billing_v1(), &new);
+        let changes: Vec<String> = diff.changes.iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            changes,
+            vec!["Billing: entity 'Invoice' changed (renamed to Bill)"]
+        );
+        assert!(diff.has_breaking_changes());
+    }
+
+    #[test]
+    fn test_max_severity() {
+        assert_eq!(diff_contexts(&billing_v1(), &billing_v1()).max_severity(), None);
+
+        let mut documented = billing_v1();
+        let money = documented.graph().find_object_by_name("Money").unwrap().id;
+        documented
+            .sketch_mut()
+            .graph
+            .get_object_mut(money)
+            .unwrap()
+            .set_description(Some("An amount in a currency".into()));
+        documented.set_version(ModelVersion::new(1, 0, 1));
+        let diff = diff_contexts(&billing_v1(), &documented);
+        assert_eq!(diff.max_severity(), Some(Compatibility::Cosmetic));
+        assert_eq!(diff.changes[0].to_string(), "Billing: value object 'Money' changed (description)");
+
+        // A required morphism on an existing object breaks existing data
+        let mut required = billing_v1();
+        let invoice = required.graph().find_object_by_name("Invoice").unwrap().id;
+        let note = required.sketch_mut().add_object("Note");
+        required.sketch_mut().add_morphism("note", invoice, note);
+        let diff = diff_contexts(&billing_v1(), &required);
+        assert_eq!(diff.max_severity(), Some(Compatibility::Breaking));
+        assert_eq!(
+            diff.breaking_changes().map(|c| c.to_string()).collect::<Vec<_>>(),
+            vec!["Billing: morphism 'Invoice.note' added (required)"]
+        );
+
+        // Relaxing it afterwards is compatible
+        let mut optional = required.clone();
+        let note_of = optional.graph().morphisms().find(|m| m.name == "note").unwrap().id;
+        optional.mark_optional(note_of);
+        let diff = diff_contexts(&required, &optional);
+        assert_eq!(diff.max_severity(), Some(Compatibility::Compatible));
+        assert_eq!(diff.changes[0].detail.as_deref(), Some("required -> optional"));
+        assert_eq!(diff_contexts(&optional, &required).max_severity(), Some(Compatibility::Breaking));
+
+        assert!(Compatibility::Cosmetic < Compatibility::Compatible);
+        assert_eq!("Breaking".parse(), Ok(Compatibility::Breaking));
+        assert!("fatal".parse::<Compatibility>().is_err());
+    }
+}
@@ -0,0 +1,100 @@
+//! Domains and subdomains grouping bounded contexts.
+//!
+//! A domain is the problem space a model addresses. It is split into
+//! subdomains, each classified as core, supporting or generic, and each
+//! solved by one or more bounded contexts. Contexts are referenced by name,
+//! like the contexts of a [`NamedContextMap`](crate::NamedContextMap).
+
+use crate::metadata::{Classification, Metadata};
+use serde::{Deserialize, Serialize};
+
+/// A subdomain: part of the problem space, solved by some bounded contexts.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Subdomain {
+    /// Name of the subdomain
+    pub name: String,
+
+    /// Classification, tags and metadata
+    #[serde(default)]
+    pub metadata: Metadata,
+
+    /// Names of the bounded contexts in the subdomain
+    pub contexts: Vec<String>,
+}
+
+impl Subdomain {
+    /// Create a new subdomain without contexts.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Get the classification of the subdomain, if declared.
+    pub fn classification(&self) -> Option<Classification> {
+        self.metadata.classification
+    }
+
+    /// Add a bounded context to the subdomain.
+    pub fn add_context(&mut self, context: impl Into<String>) {
+        self.contexts.push(context.into());
+    }
+
+    /// Check if the subdomain contains a bounded context.
+    pub fn contains(&self, context: &str) -> bool {
+        self.contexts.iter().any(|c| c == context)
+    }
+}
+
+/// A domain, split into subdomains.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Domain {
+    /// Name of the domain
+    pub name: String,
+
+    /// Subdomains, in declaration order
+    pub subdomains: Vec<Subdomain>,
+}
+
+impl Domain {
+    /// Create a new domain without subdomains.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            subdomains: Vec::new(),
+        }
+    }
+
+    /// Add a subdomain.
+    pub fn add_subdomain(&mut self, subdomain: Subdomain) {
+        self.subdomains.push(subdomain);
+    }
+
+    /// Get the first subdomain containing a bounded context.
+    pub fn subdomain_of(&self, context: &str) -> Option<&Subdomain> {
+        self.subdomains.iter().find(|s| s.contains(context))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subdomain_of() {
+        let mut ordering = Subdomain::new("Ordering");
+        ordering.metadata.classification = Some(Classification::Core);
+        ordering.add_context("Sales");
+        ordering.add_context("Checkout");
+
+        let mut domain = Domain::new("Commerce");
+        domain.add_subdomain(ordering);
+        domain.add_subdomain(Subdomain::new("Identity"));
+
+        let subdomain = domain.subdomain_of("Checkout").unwrap();
+        assert_eq!(subdomain.name, "Ordering");
+        assert_eq!(subdomain.classification(), Some(Classification::Core));
+        assert!(domain.subdomain_of("Auth").is_none());
+    }
+}
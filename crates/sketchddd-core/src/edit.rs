@@ -0,0 +1,680 @@
+//! Reversible edits to a bounded context.
+//!
+//! An [`EditSession`] owns a context and changes it only through
+//! [`EditCommand`]s, keeping undo and redo stacks so that interactive
+//! editors such as the visual builder can step back and forth through a
+//! user's changes without reloading the model.
+//!
+//! Most commands are undone by another command: renaming back, or removing
+//! what was added. Removals cascade through the cones, equations and
+//! registries that refer to the removed element, so undoing one restores a
+//! copy of the context taken before the removal. Ids are never reused, so
+//! commands further up the redo stack still refer to the right elements.
+//!
+//! A [`ModelEditSession`] does the same for a whole workspace, with one
+//! history across its contexts. Renames there also rename the element in
+//! the context maps mapping it.
+
+use crate::context::BoundedContext;
+use crate::mapping::NamedContextMap;
+use crate::refactor::{rename_morphism_mappings, rename_object_mappings, RenameError};
+use crate::sketch::{MorphismId, ObjectId};
+use crate::workspace::Workspace;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A single change to a bounded context.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EditCommand {
+    /// Add a plain object
+    AddObject { name: String },
+
+    /// Add an entity with its identity morphism
+    AddEntity { name: String },
+
+    /// Add a value object
+    AddValueObject { name: String },
+
+    /// Add a morphism between existing objects
+    AddMorphism {
+        name: String,
+        source: ObjectId,
+        target: ObjectId,
+    },
+
+    /// Remove an object and everything that depends on it
+    RemoveObject { object: ObjectId },
+
+    /// Remove a morphism and everything that depends on it
+    RemoveMorphism { morphism: MorphismId },
+
+    /// Rename an object
+    RenameObject { object: ObjectId, name: String },
+
+    /// Rename a morphism
+    RenameMorphism { morphism: MorphismId, name: String },
+
+    /// Rename the aggregate rooted at an object
+    RenameAggregate { root: ObjectId, name: String },
+}
+
+/// The element an applied command created or changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Edited {
+    Object(ObjectId),
+    Morphism(MorphismId),
+}
+
+/// Errors from applying an edit command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EditError {
+    /// No context of the workspace has the name
+    UnknownContext(String),
+
+    /// The object does not exist in the context
+    UnknownObject(ObjectId),
+
+    /// The morphism does not exist in the context
+    UnknownMorphism(MorphismId),
+
+    /// A rename was rejected
+    Rename(RenameError),
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EditError::UnknownContext(name) => write!(f, "Context '{}' does not exist", name),
+            EditError::UnknownObject(id) => write!(f, "Object {:?} does not exist", id),
+            EditError::UnknownMorphism(id) => write!(f, "Morphism {:?} does not exist", id),
+            EditError::Rename(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for EditError {}
+
+impl From<RenameError> for EditError {
+    fn from(e: RenameError) -> Self {
+        EditError::Rename(e)
+    }
+}
+
+/// How to take back one applied command.
+#[derive(Debug, Clone)]
+enum Inverse {
+    /// Apply another command
+    Command(EditCommand),
+
+    /// Put back the context as it was
+    Restore(Box<BoundedContext>),
+}
+
+/// A bounded context under edit, with undo and redo history.
+#[derive(Debug, Clone)]
+pub struct EditSession {
+    context: BoundedContext,
+
+    /// Inverses of each applied step, a batch being one step
+    undo_stack: Vec<Vec<Inverse>>,
+
+    /// Inverses of each undone step, most recently undone last
+    redo_stack: Vec<Vec<Inverse>>,
+}
+
+impl EditSession {
+    /// Start editing a context with empty history.
+    pub fn new(context: BoundedContext) -> Self {
+        Self {
+            context,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+        }
+    }
+
+    /// Get the context in its current state.
+    pub fn context(&self) -> &BoundedContext {
+        &self.context
+    }
+
+    /// Stop editing and take the context.
+    pub fn into_context(self) -> BoundedContext {
+        self.context
+    }
+
+    /// Apply a command as one undoable step.
+    ///
+    /// Applying a command discards anything that could be redone. Nothing
+    /// changes if the command fails.
+    pub fn apply(&mut self, command: EditCommand) -> Result<Edited, EditError> {
+        let (edited, inverse) = execute(&mut self.context, &mut [], &command)?;
+        self.undo_stack.push(vec![inverse]);
+        self.redo_stack.clear();
+        Ok(edited)
+    }
+
+    /// Apply several commands as one undoable step.
+    ///
+    /// The batch is a transaction: if any command fails, the ones before it
+    /// are taken back and the error is returned.
+    pub fn apply_batch(
+        &mut self,
+        commands: impl IntoIterator<Item = EditCommand>,
+    ) -> Result<Vec<Edited>, EditError> {
+        let mut edited = Vec::new();
+        let mut inverses = Vec::new();
+        for command in commands {
+            match execute(&mut self.context, &mut [], &command) {
+                Ok((element, inverse)) => {
+                    edited.push(element);
+                    inverses.push(inverse);
+                }
+                Err(e) => {
+                    revert_all(&mut self.context, &mut [], inverses);
+                    return Err(e);
+                }
+            }
+        }
+        if !inverses.is_empty() {
+            self.undo_stack.push(inverses);
+            self.redo_stack.clear();
+        }
+        Ok(edited)
+    }
+
+    /// Check if there is a step to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    /// Check if there is a step to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Take back the most recent step. Returns `false` if there is none.
+    pub fn undo(&mut self) -> bool {
+        match self.undo_stack.pop() {
+            Some(step) => {
+                let redo = revert_all(&mut self.context, &mut [], step);
+                self.redo_stack.push(redo);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Apply the most recently undone step again. Returns `false` if there
+    /// is none.
+    pub fn redo(&mut self) -> bool {
+        match self.redo_stack.pop() {
+            Some(step) => {
+                let undo = revert_all(&mut self.context, &mut [], step);
+                self.undo_stack.push(undo);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// A step of a model's history: the inverses of the commands applied to
+/// one of its contexts.
+#[derive(Debug, Clone)]
+struct ModelStep {
+    context: usize,
+    inverses: Vec<Inverse>,
+}
+
+/// A workspace under edit, with one undo and redo history across its
+/// contexts.
+#[derive(Debug, Clone)]
+pub struct ModelEditSession {
+    workspace: Workspace,
+    undo_stack: Vec<ModelStep>,
+    redo_stack: Vec<ModelStep>,
+}
+
+impl ModelEditSession {
+    /// Start editing a workspace with empty history.
+    pub fn new(workspace: Workspace) -> Self {
+        Self {
+            workspace,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+        }
+    }
+
+    /// Get the workspace in its current state.
+    pub fn workspace(&self) -> &Workspace {
+        &self.workspace
+    }
+
+    /// Stop editing and take the workspace.
+    pub fn into_workspace(self) -> Workspace {
+        self.workspace
+    }
+
+    /// Apply a command to a context as one undoable step.
+    ///
+    /// Applying a command discards anything that could be redone. Nothing
+    /// changes if the command fails.
+    pub fn apply(&mut self, context: &str, command: EditCommand) -> Result<Edited, EditError> {
+        let index = self
+            .workspace
+            .contexts
+            .iter()
+            .position(|c| c.name() == context)
+            .ok_or_else(|| EditError::UnknownContext(context.to_string()))?;
+        let Workspace {
+            contexts,
+            context_maps,
+            ..
+        } = &mut self.workspace;
+        let (edited, inverse) = execute(&mut contexts[index], context_maps, &command)?;
+        self.undo_stack.push(ModelStep {
+            context: index,
+            inverses: vec![inverse],
+        });
+        self.redo_stack.clear();
+        Ok(edited)
+    }
+
+    /// Check if there is a step to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    /// Check if there is a step to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Take back the most recent step. Returns the name of the context it
+    /// changed, or `None` if there is no step.
+    pub fn undo(&mut self) -> Option<&str> {
+        let step = self.undo_stack.pop()?;
+        let redo = self.revert(step);
+        let context = redo.context;
+        self.redo_stack.push(redo);
+        Some(self.workspace.contexts[context].name())
+    }
+
+    /// Apply the most recently undone step again. Returns the name of the
+    /// context it changed, or `None` if there is no step.
+    pub fn redo(&mut self) -> Option<&str> {
+        let step = self.redo_stack.pop()?;
+        let undo = self.revert(step);
+        let context = undo.context;
+        self.undo_stack.push(undo);
+        Some(self.workspace.contexts[context].name())
+    }
+
+    /// Forget every step, so that nothing can be undone or redone.
+    pub fn clear_history(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+
+    /// Take back a step and return how to take that back in turn.
+    fn revert(&mut self, step: ModelStep) -> ModelStep {
+        let Workspace {
+            contexts,
+            context_maps,
+            ..
+        } = &mut self.workspace;
+        ModelStep {
+            context: step.context,
+            inverses: revert_all(&mut contexts[step.context], context_maps, step.inverses),
+        }
+    }
+}
+
+/// Apply a command and work out how to take it back, renaming elements in
+/// the context maps too.
+fn execute(
+    ctx: &mut BoundedContext,
+    maps: &mut [NamedContextMap],
+    command: &EditCommand,
+) -> Result<(Edited, Inverse), EditError> {
+    let object_exists = |ctx: &BoundedContext, id| {
+        ctx.graph()
+            .get_object(id)
+            .map(|_| ())
+            .ok_or(EditError::UnknownObject(id))
+    };
+
+    match command {
+        EditCommand::AddObject { name } => {
+            let object = ctx.sketch_mut().add_object(name);
+            Ok((
+                Edited::Object(object),
+                Inverse::Command(EditCommand::RemoveObject { object }),
+            ))
+        }
+        EditCommand::AddEntity { name } => {
+            let object = ctx.add_entity(name);
+            Ok((
+                Edited::Object(object),
+                Inverse::Command(EditCommand::RemoveObject { object }),
+            ))
+        }
+        EditCommand::AddValueObject { name } => {
+            let object = ctx.add_value_object(name);
+            Ok((
+                Edited::Object(object),
+                Inverse::Command(EditCommand::RemoveObject { object }),
+            ))
+        }
+        EditCommand::AddMorphism {
+            name,
+            source,
+            target,
+        } => {
+            object_exists(ctx, *source)?;
+            object_exists(ctx, *target)?;
+            let morphism = ctx.sketch_mut().add_morphism(name, *source, *target);
+            Ok((
+                Edited::Morphism(morphism),
+                Inverse::Command(EditCommand::RemoveMorphism { morphism }),
+            ))
+        }
+        EditCommand::RemoveObject { object } => {
+            let before = ctx.clone();
+            if !ctx.remove_object(*object) {
+                return Err(EditError::UnknownObject(*object));
+            }
+            Ok((Edited::Object(*object), Inverse::Restore(Box::new(before))))
+        }
+        EditCommand::RemoveMorphism { morphism } => {
+            let before = ctx.clone();
+            if !ctx.remove_morphism(*morphism) {
+                return Err(EditError::UnknownMorphism(*morphism));
+            }
+            Ok((
+                Edited::Morphism(*morphism),
+                Inverse::Restore(Box::new(before)),
+            ))
+        }
+        EditCommand::RenameObject { object, name } => {
+            let old_name = ctx
+                .graph()
+                .get_object(*object)
+                .map(|o| o.name.clone())
+                .ok_or(EditError::UnknownObject(*object))?;
+            ctx.rename_object(*object, name.clone())?;
+            rename_object_mappings(maps, ctx.name(), &old_name, name);
+            let inverse = EditCommand::RenameObject {
+                object: *object,
+                name: old_name,
+            };
+            Ok((Edited::Object(*object), Inverse::Command(inverse)))
+        }
+        EditCommand::RenameMorphism { morphism, name } => {
+            let old_name = ctx
+                .graph()
+                .get_morphism(*morphism)
+                .map(|m| m.name.clone())
+                .ok_or(EditError::UnknownMorphism(*morphism))?;
+            ctx.rename_morphism(*morphism, name.clone())?;
+            rename_morphism_mappings(maps, ctx.name(), &old_name, name);
+            let inverse = EditCommand::RenameMorphism {
+                morphism: *morphism,
+                name: old_name,
+            };
+            Ok((Edited::Morphism(*morphism), Inverse::Command(inverse)))
+        }
+        EditCommand::RenameAggregate { root, name } => {
+            let old_name = ctx
+                .get_aggregate(*root)
+                .map(|l| l.name.clone())
+                .ok_or(RenameError::NotAnAggregate(*root))?;
+            ctx.rename_aggregate(*root, name.clone())?;
+            let inverse = EditCommand::RenameAggregate {
+                root: *root,
+                name: old_name,
+            };
+            Ok((Edited::Object(*root), Inverse::Command(inverse)))
+        }
+    }
+}
+
+/// Take back a step, last command first, and return how to take that back
+/// in turn.
+fn revert_all(
+    ctx: &mut BoundedContext,
+    maps: &mut [NamedContextMap],
+    step: Vec<Inverse>,
+) -> Vec<Inverse> {
+    let mut inverses: Vec<Inverse> = step
+        .into_iter()
+        .rev()
+        .filter_map(|inverse| match inverse {
+            Inverse::Command(command) => {
+                // The context is exactly as the command left it, so taking
+                // it back cannot fail
+                execute(ctx, maps, &command)
+                    .ok()
+                    .map(|(_, inverse)| inverse)
+            }
+            Inverse::Restore(before) => {
+                let after = std::mem::replace(ctx, *before);
+                Some(Inverse::Restore(Box::new(after)))
+            }
+        })
+        .collect();
+    inverses.reverse();
+    inverses
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapping::{NamedObjectMapping, RelationshipPattern};
+    use crate::sketch::{Path, PathEquation};
+
+    #[test]
+    fn test_undo_and_redo() {
+        let mut session = EditSession::new(BoundedContext::new("Commerce"));
+        let Edited::Object(order) = session
+            .apply(EditCommand::AddEntity {
+                name: "Order".into(),
+            })
+            .unwrap()
+        else {
+            panic!("expected an object");
+        };
+        session
+            .apply(EditCommand::RenameObject {
+                object: order,
+                name: "PurchaseOrder".into(),
+            })
+            .unwrap();
+        assert!(session.context().is_entity(order));
+
+        assert!(session.undo());
+        assert_eq!(
+            session.context().graph().get_object(order).unwrap().name,
+            "Order"
+        );
+        assert!(session.undo());
+        assert!(session.context().graph().get_object(order).is_none());
+        assert!(!session.undo());
+
+        // Redo brings back the same ids, so later steps still apply
+        assert!(session.redo());
+        assert!(session.context().is_entity(order));
+        assert!(session.redo());
+        assert_eq!(
+            session.context().graph().get_object(order).unwrap().name,
+            "PurchaseOrder"
+        );
+        assert!(!session.can_redo());
+
+        // A new command discards the redo stack
+        session.undo();
+        session
+            .apply(EditCommand::AddObject {
+                name: "Customer".into(),
+            })
+            .unwrap();
+        assert!(!session.can_redo());
+    }
+
+    #[test]
+    fn test_undo_removal_restores_dependents() {
+        let mut ctx = BoundedContext::new("Commerce");
+        let order = ctx.add_entity("Order");
+        let customer = ctx.add_entity("Customer");
+        let placed_by = ctx.sketch_mut().add_morphism("placedBy", order, customer);
+        let buyer = ctx.sketch_mut().add_morphism("buyer", order, customer);
+        ctx.add_path_equation(
+            "sameCustomer",
+            PathEquation::new(
+                "sameCustomer",
+                Path::new(order, customer, vec![placed_by]),
+                Path::new(order, customer, vec![buyer]),
+            ),
+        );
+        ctx.define_aggregate("Ordering", order);
+
+        let mut session = EditSession::new(ctx);
+        session
+            .apply(EditCommand::RemoveMorphism { morphism: buyer })
+            .unwrap();
+        session
+            .apply(EditCommand::RenameAggregate {
+                root: order,
+                name: "Orders".into(),
+            })
+            .unwrap();
+        assert!(session.context().sketch().equations.is_empty());
+        assert_eq!(
+            session.context().get_aggregate(order).unwrap().name,
+            "Orders"
+        );
+
+        session.undo();
+        session.undo();
+        assert_eq!(
+            session.context().get_aggregate(order).unwrap().name,
+            "Ordering"
+        );
+        assert_eq!(session.context().sketch().equations.len(), 1);
+        assert!(session.context().graph().get_morphism(buyer).is_some());
+    }
+
+    #[test]
+    fn test_batch_is_one_step_and_atomic() {
+        let mut ctx = BoundedContext::new("Commerce");
+        let order = ctx.add_entity("Order");
+        let mut session = EditSession::new(ctx);
+
+        let edited = session
+            .apply_batch([
+                EditCommand::AddEntity {
+                    name: "Customer".into(),
+                },
+                EditCommand::AddObject {
+                    name: "Money".into(),
+                },
+            ])
+            .unwrap();
+        let Edited::Object(customer) = edited[0] else {
+            panic!("expected an object");
+        };
+        assert_eq!(session.context().graph().objects().count(), 3);
+
+        let result = session.apply_batch([
+            EditCommand::AddMorphism {
+                name: "placedBy".into(),
+                source: order,
+                target: customer,
+            },
+            EditCommand::RenameObject {
+                object: order,
+                name: "Customer".into(),
+            },
+        ]);
+        assert_eq!(
+            result,
+            Err(EditError::Rename(RenameError::NameTaken("Customer".into())))
+        );
+        assert!(session
+            .context()
+            .graph()
+            .find_morphism_by_name("placedBy")
+            .is_none());
+
+        // The failed batch left no step behind; the first batch is undone whole
+        assert!(session.undo());
+        assert_eq!(session.context().graph().objects().count(), 1);
+        assert!(!session.can_undo());
+    }
+
+    #[test]
+    fn test_model_session_renames_in_context_maps() {
+        let mut sales = BoundedContext::new("Sales");
+        let customer = sales.add_entity("Customer");
+        let mut billing = BoundedContext::new("Billing");
+        billing.add_entity("Customer");
+        let mut map = NamedContextMap::new(
+            "SalesToBilling",
+            "Sales",
+            "Billing",
+            RelationshipPattern::CustomerSupplier,
+        );
+        map.add_object_mapping(NamedObjectMapping {
+            source: "Customer".into(),
+            target: "Customer".into(),
+            description: None,
+        });
+        let mut workspace = Workspace::new();
+        workspace.add_context(sales);
+        workspace.add_context(billing);
+        workspace.add_context_map(map);
+
+        let mut session = ModelEditSession::new(workspace);
+        session
+            .apply(
+                "Sales",
+                EditCommand::RenameObject {
+                    object: customer,
+                    name: "Buyer".into(),
+                },
+            )
+            .unwrap();
+        session
+            .apply(
+                "Billing",
+                EditCommand::AddEntity {
+                    name: "Invoice".into(),
+                },
+            )
+            .unwrap();
+        let mapping = |session: &ModelEditSession| {
+            session.workspace().context_maps[0].object_mappings[0]
+                .source
+                .clone()
+        };
+        assert_eq!(mapping(&session), "Buyer");
+
+        // One history across contexts, the map following the rename
+        assert_eq!(session.undo(), Some("Billing"));
+        assert_eq!(session.undo(), Some("Sales"));
+        assert_eq!(mapping(&session), "Customer");
+        assert_eq!(session.undo(), None);
+        assert_eq!(session.redo(), Some("Sales"));
+        assert_eq!(mapping(&session), "Buyer");
+
+        assert_eq!(
+            session.apply(
+                "Shipping",
+                EditCommand::AddObject {
+                    name: "Parcel".into()
+                }
+            ),
+            Err(EditError::UnknownContext("Shipping".into()))
+        );
+    }
+}
@@ -0,0 +1,139 @@
+//! Event storming elements: actors, policies and hotspots.
+//!
+//! An event storming session produces more than events and commands. Actors
+//! issue commands, policies react to events ("whenever X happens, do Y") and
+//! hotspots mark questions or disagreements still open. Like processes, these
+//! reference existing objects of the context and never add morphisms to the
+//! graph, so capturing them does not change the structure of the model.
+
+use crate::sketch::ObjectId;
+use serde::{Deserialize, Serialize};
+
+/// A person, role or system issuing commands.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Actor {
+    /// Name of the actor
+    pub name: String,
+
+    /// Command objects the actor issues, in declaration order
+    pub commands: Vec<ObjectId>,
+}
+
+impl Actor {
+    /// Create a new actor issuing no commands.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            commands: Vec::new(),
+        }
+    }
+
+    /// Add a command issued by the actor.
+    pub fn add_command(&mut self, command: ObjectId) {
+        if !self.commands.contains(&command) {
+            self.commands.push(command);
+        }
+    }
+}
+
+/// A reaction to an event: whenever `event` happens, issue `commands`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Policy {
+    /// Name of the policy
+    pub name: String,
+
+    /// The event object the policy reacts to
+    pub event: ObjectId,
+
+    /// Command objects issued in response, in declaration order
+    pub commands: Vec<ObjectId>,
+}
+
+impl Policy {
+    /// Create a new policy reacting to an event without issuing commands.
+    pub fn new(name: impl Into<String>, event: ObjectId) -> Self {
+        Self {
+            name: name.into(),
+            event,
+            commands: Vec::new(),
+        }
+    }
+
+    /// Add a command issued in response to the event.
+    pub fn add_command(&mut self, command: ObjectId) {
+        if !self.commands.contains(&command) {
+            self.commands.push(command);
+        }
+    }
+
+    /// Get all objects the policy refers to, the event first.
+    pub fn objects(&self) -> Vec<ObjectId> {
+        std::iter::once(self.event)
+            .chain(self.commands.iter().copied())
+            .collect()
+    }
+}
+
+/// An open question, risk or disagreement, optionally about an object.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hotspot {
+    /// What is unresolved
+    pub note: String,
+
+    /// The object the hotspot is about, if any
+    pub target: Option<ObjectId>,
+}
+
+impl Hotspot {
+    /// Create a hotspot about the context as a whole.
+    pub fn new(note: impl Into<String>) -> Self {
+        Self {
+            note: note.into(),
+            target: None,
+        }
+    }
+
+    /// Create a hotspot about an object.
+    pub fn on(target: ObjectId, note: impl Into<String>) -> Self {
+        Self {
+            note: note.into(),
+            target: Some(target),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sketch::Graph;
+
+    #[test]
+    fn test_policy_objects() {
+        let mut graph = Graph::new();
+        let placed = graph.add_object("OrderPlaced");
+        let reserve = graph.add_object("ReserveStock");
+        let notify = graph.add_object("NotifyWarehouse");
+
+        let mut policy = Policy::new("ReserveOnPlacement", placed);
+        policy.add_command(reserve);
+        policy.add_command(notify);
+        policy.add_command(reserve);
+
+        assert_eq!(policy.commands, vec![reserve, notify]);
+        assert_eq!(policy.objects(), vec![placed, reserve, notify]);
+    }
+
+    #[test]
+    fn test_actor_and_hotspot() {
+        let mut graph = Graph::new();
+        let place = graph.add_object("PlaceOrder");
+
+        let mut customer = Actor::new("Customer");
+        customer.add_command(place);
+        customer.add_command(place);
+        assert_eq!(customer.commands, vec![place]);
+
+        assert_eq!(Hotspot::new("Who pays for returns?").target, None);
+        assert_eq!(Hotspot::on(place, "Can guests order?").target, Some(place));
+    }
+}
@@ -0,0 +1,243 @@
+//! Expressions over paths, used to define computed (derived) morphisms.
+//!
+//! A derived morphism such as `total: Order -> Money = sum(items.price)` is
+//! not stored on its source object; its value is computed from other
+//! morphisms. Categorically, it is a morphism whose definition factors
+//! through the paths its expression navigates. Code generators render the
+//! expression as a computed getter, and validation checks that the paths
+//! resolve and that the expression's type matches the declared target.
+
+use crate::sketch::MorphismId;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// An expression over the paths of a bounded context.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Expr {
+    /// A numeric literal
+    Number(f64),
+
+    /// A string literal
+    Text(String),
+
+    /// A path of morphism names starting at the source object, e.g. `items.price`
+    Path(Vec<String>),
+
+    /// A binary operation
+    Binary {
+        op: BinaryOp,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+
+    /// A unary operation
+    Unary { op: UnaryOp, operand: Box<Expr> },
+
+    /// A function call such as `sum(items.price)`
+    Call { function: String, args: Vec<Expr> },
+
+    /// An index into a collection, e.g. `items[0]`
+    Index { target: Box<Expr>, index: Box<Expr> },
+}
+
+impl Expr {
+    /// Functions that may be used in expressions.
+    ///
+    /// Each function takes a single path into a collection: `sum`, `min` and
+    /// `max` aggregate the values at the end of the path, and `count` counts
+    /// the elements of the collection.
+    pub fn functions() -> &'static [&'static str] {
+        &["sum", "count", "min", "max"]
+    }
+
+    /// Collect every path used in the expression.
+    pub fn paths(&self) -> Vec<&[String]> {
+        let mut paths = Vec::new();
+        self.collect_paths(&mut paths);
+        paths
+    }
+
+    /// Collect every path used in the expression, for rewriting.
+    pub fn paths_mut(&mut self) -> Vec<&mut Vec<String>> {
+        let mut paths = Vec::new();
+        self.collect_paths_mut(&mut paths);
+        paths
+    }
+
+    fn collect_paths_mut<'a>(&'a mut self, paths: &mut Vec<&'a mut Vec<String>>) {
+        match self {
+            Expr::Number(_) | Expr::Text(_) => {}
+            Expr::Path(path) => paths.push(path),
+            Expr::Binary { left, right, .. } => {
+                left.collect_paths_mut(paths);
+                right.collect_paths_mut(paths);
+            }
+            Expr::Unary { operand, .. } => operand.collect_paths_mut(paths),
+            Expr::Call { args, .. } => args.iter_mut().for_each(|arg| arg.collect_paths_mut(paths)),
+            Expr::Index { target, index } => {
+                target.collect_paths_mut(paths);
+                index.collect_paths_mut(paths);
+            }
+        }
+    }
+
+    fn collect_paths<'a>(&'a self, paths: &mut Vec<&'a [String]>) {
+        match self {
+            Expr::Number(_) | Expr::Text(_) => {}
+            Expr::Path(path) => paths.push(path),
+            Expr::Binary { left, right, .. } => {
+                left.collect_paths(paths);
+                right.collect_paths(paths);
+            }
+            Expr::Unary { operand, .. } => operand.collect_paths(paths),
+            Expr::Call { args, .. } => args.iter().for_each(|arg| arg.collect_paths(paths)),
+            Expr::Index { target, index } => {
+                target.collect_paths(paths);
+                index.collect_paths(paths);
+            }
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Nested operations are parenthesized so grouping is never ambiguous
+        let operand = |expr: &Expr| match expr {
+            Expr::Binary { .. } => format!("({})", expr),
+            _ => expr.to_string(),
+        };
+
+        match self {
+            Expr::Number(n) => write!(f, "{}", n),
+            Expr::Text(s) => write!(f, "{:?}", s),
+            Expr::Path(path) => write!(f, "{}", path.join(".")),
+            Expr::Binary { op, left, right } => {
+                write!(f, "{} {} {}", operand(left), op, operand(right))
+            }
+            Expr::Unary { op, operand: inner } => write!(f, "{}{}", op, operand(inner)),
+            Expr::Call { function, args } => {
+                let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+                write!(f, "{}({})", function, args.join(", "))
+            }
+            Expr::Index { target, index } => write!(f, "{}[{}]", operand(target), index),
+        }
+    }
+}
+
+/// Binary operators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl BinaryOp {
+    /// The operator symbol, e.g. `+` or `==`.
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Mod => "%",
+            BinaryOp::Eq => "==",
+            BinaryOp::Ne => "!=",
+            BinaryOp::Lt => "<",
+            BinaryOp::Le => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::Ge => ">=",
+        }
+    }
+
+    /// Check if this operator compares its operands.
+    pub fn is_comparison(&self) -> bool {
+        !matches!(
+            self,
+            BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod
+        )
+    }
+}
+
+impl fmt::Display for BinaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+/// Unary operators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UnaryOp {
+    Not,
+    Neg,
+}
+
+impl fmt::Display for UnaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            UnaryOp::Not => "!",
+            UnaryOp::Neg => "-",
+        })
+    }
+}
+
+/// A morphism whose value is computed from an expression.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DerivedMorphism {
+    /// The computed morphism
+    pub morphism: MorphismId,
+
+    /// The expression computing its value from the source object
+    pub expression: Expr,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(p: &str) -> Expr {
+        Expr::Path(p.split('.').map(String::from).collect())
+    }
+
+    #[test]
+    fn test_expr_paths() {
+        let expr = Expr::Binary {
+            op: BinaryOp::Sub,
+            left: Box::new(Expr::Call {
+                function: "sum".into(),
+                args: vec![path("items.price")],
+            }),
+            right: Box::new(path("discount")),
+        };
+
+        let paths: Vec<String> = expr.paths().iter().map(|p| p.join(".")).collect();
+        assert_eq!(paths, vec!["items.price", "discount"]);
+    }
+
+    #[test]
+    fn test_expr_display() {
+        let expr = Expr::Binary {
+            op: BinaryOp::Mul,
+            left: Box::new(path("subtotal")),
+            right: Box::new(Expr::Number(1.2)),
+        };
+        assert_eq!(expr.to_string(), "subtotal * 1.2");
+
+        let nested = Expr::Binary {
+            op: BinaryOp::Sub,
+            left: Box::new(path("total")),
+            right: Box::new(expr),
+        };
+        assert_eq!(nested.to_string(), "total - (subtotal * 1.2)");
+        assert!(BinaryOp::Ge.is_comparison());
+        assert!(!BinaryOp::Add.is_comparison());
+    }
+}
@@ -0,0 +1,151 @@
+//! Machine-applicable fixes for validation issues.
+//!
+//! Some issues have a single obvious repair: declare the object a morphism
+//! refers to, remove a duplicate declaration, rename an element to the name
+//! a rule suggests. A [`Fix`] spells the repair out as edits to the source
+//! text, so that `sketchddd check --fix` and editors can apply it without
+//! understanding the issue. The model itself does not know where its
+//! elements are declared, so fixes are attached by the parser, which does.
+
+use serde::{Deserialize, Serialize};
+
+/// A replacement of a byte range of source text.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextEdit {
+    /// Byte offset where the replaced text starts
+    pub start: usize,
+
+    /// Byte offset just past the replaced text
+    pub end: usize,
+
+    /// Text to put in place of the range
+    pub new_text: String,
+}
+
+impl TextEdit {
+    /// Replace the text between `start` and `end`.
+    pub fn replace(start: usize, end: usize, new_text: impl Into<String>) -> Self {
+        Self {
+            start,
+            end,
+            new_text: new_text.into(),
+        }
+    }
+
+    /// Insert text at an offset.
+    pub fn insert(offset: usize, new_text: impl Into<String>) -> Self {
+        Self::replace(offset, offset, new_text)
+    }
+
+    /// Delete the text between `start` and `end`.
+    pub fn delete(start: usize, end: usize) -> Self {
+        Self::replace(start, end, "")
+    }
+
+    /// Check if two edits touch the same text.
+    ///
+    /// Insertions at the same offset do not overlap; an insertion strictly
+    /// inside a replaced range does.
+    pub fn overlaps(&self, other: &TextEdit) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+}
+
+/// A repair for an issue, as a set of edits applied together.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fix {
+    /// What the fix does, such as "Declare object 'Customer'"
+    pub title: String,
+
+    /// The edits making up the fix, which never overlap each other
+    pub edits: Vec<TextEdit>,
+}
+
+impl Fix {
+    /// Create a fix without edits.
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            edits: Vec::new(),
+        }
+    }
+
+    /// Add an edit to the fix.
+    pub fn with_edit(mut self, edit: TextEdit) -> Self {
+        self.edits.push(edit);
+        self
+    }
+
+    /// Apply the fix to a source text.
+    pub fn apply(&self, source: &str) -> String {
+        apply_fixes(source, [self]).0
+    }
+}
+
+/// Apply fixes to a source text, returning the new text and the number of
+/// fixes applied.
+///
+/// Fixes are taken in order. A fix with an edit overlapping an edit of an
+/// earlier fix is skipped as a whole, so that each fix is either applied
+/// completely or not at all; running the check again after applying will
+/// offer it afresh against the new text.
+pub fn apply_fixes<'a>(source: &str, fixes: impl IntoIterator<Item = &'a Fix>) -> (String, usize) {
+    let mut accepted: Vec<&TextEdit> = Vec::new();
+    let mut applied = 0;
+    for fix in fixes {
+        let in_bounds = fix
+            .edits
+            .iter()
+            .all(|e| e.start <= e.end && e.end <= source.len());
+        let clashes = fix
+            .edits
+            .iter()
+            .any(|e| accepted.iter().any(|a| a.overlaps(e)));
+        if in_bounds && !clashes {
+            accepted.extend(&fix.edits);
+            applied += 1;
+        }
+    }
+
+    // Stable, so insertions at one offset keep the order of their fixes
+    accepted.sort_by_key(|e| (e.start, e.end));
+    let mut output = String::with_capacity(source.len());
+    let mut position = 0;
+    for edit in accepted {
+        output.push_str(&source[position..edit.start]);
+        output.push_str(&edit.new_text);
+        position = edit.end;
+    }
+    output.push_str(&source[position..]);
+    (output, applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_fix() {
+        let source = "objects { Order, Order }";
+        let fix = Fix::new("Remove duplicate 'Order'").with_edit(TextEdit::delete(15, 22));
+        assert_eq!(fix.apply(source), "objects { Order }");
+    }
+
+    #[test]
+    fn test_apply_fixes_skips_overlapping() {
+        let source = "line_item -> order";
+        let rename = Fix::new("Rename")
+            .with_edit(TextEdit::replace(0, 9, "LineItem"))
+            .with_edit(TextEdit::replace(13, 18, "Order"));
+        let clash = Fix::new("Rename again").with_edit(TextEdit::replace(13, 18, "Purchase"));
+        let first = Fix::new("Insert").with_edit(TextEdit::insert(0, "a "));
+        let second = Fix::new("Insert more").with_edit(TextEdit::insert(0, "b "));
+
+        let (fixed, applied) = apply_fixes(source, [&rename, &clash, &first, &second]);
+        assert_eq!(fixed, "a b LineItem -> Order");
+        assert_eq!(applied, 3);
+
+        let inside = Fix::new("Insert inside").with_edit(TextEdit::insert(3, "x"));
+        assert_eq!(apply_fixes(source, [&rename, &inside]).1, 1);
+    }
+}
@@ -0,0 +1,645 @@
+//! Re-validating only what an edit affects.
+//!
+//! The visual builder and watch mode validate the model after every change,
+//! and most changes touch a single element. A [`ValidationCache`] keeps the
+//! issues of each context and context map from the last run, and is told
+//! which elements were edited since. The next run re-validates the contexts
+//! holding those elements and the maps touching those contexts, and reuses
+//! the issues of everything else.
+//!
+//! A context is the unit of re-validation: its checks look across the whole
+//! context, so an edited object can change issues about any other element
+//! of it. Checks spanning the model, such as unused elements (W0080) and
+//! duplicate names, run on every call.
+//!
+//! The cache trusts what it is told. A context changed without marking it
+//! dirty keeps its old issues until it is marked, so callers that cannot
+//! track their edits should mark whole contexts with
+//! [`ValidationCache::mark_context_dirty`].
+//!
+//! Callers that load the model again instead of editing it, such as watch
+//! mode and editors, cannot say what changed. They take the
+//! [`Fingerprints`] of the model they load, and
+//! [`ValidationCache::mark_changed`] marks the contexts and maps whose
+//! fingerprint differs from the last one it saw. Diagrams, generated files
+//! and other outputs derived from a context are kept in a [`Memo`] with the
+//! fingerprint of the context, and derived again when it changes.
+
+use crate::context::BoundedContext;
+use crate::edit::{EditCommand, EditError, Edited, ModelEditSession};
+use crate::lint::{LintConfig, Registry};
+use crate::sketch::{MorphismId, ObjectId};
+use crate::validation::{
+    context_lookup, validate_context_in_model, validate_context_map, validate_context_names,
+    validate_domains, validate_each, validate_map_names, validate_unreferenced, ValidationError,
+    ValidationResult,
+};
+use crate::workspace::Workspace;
+use serde::Serialize;
+use std::collections::hash_map::{DefaultHasher, Entry};
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+/// A digest of the content of a model element, which changes when the
+/// element does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub struct Fingerprint(u64);
+
+impl Fingerprint {
+    /// Take the fingerprint of a value, as it serializes.
+    pub fn of<T: Serialize + ?Sized>(value: &T) -> Self {
+        // JSON objects are sorted by key, so maps holding the same entries
+        // in another order agree
+        let value =
+            serde_json::to_value(value).expect("a model element serializes to a JSON value");
+        let mut hasher = DefaultHasher::new();
+        value.to_string().hash(&mut hasher);
+        Fingerprint(hasher.finish())
+    }
+}
+
+/// The fingerprints of the contexts and context maps of a model, in the
+/// order the model declares them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Fingerprints {
+    /// Fingerprint of each context
+    pub contexts: Vec<Fingerprint>,
+
+    /// Fingerprint of each context map
+    pub maps: Vec<Fingerprint>,
+}
+
+impl Fingerprints {
+    /// Take the fingerprints of a model.
+    pub fn of(workspace: &Workspace) -> Self {
+        Self {
+            contexts: workspace.contexts.iter().map(Fingerprint::of).collect(),
+            maps: workspace.context_maps.iter().map(Fingerprint::of).collect(),
+        }
+    }
+}
+
+/// An element of a context that was edited.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Element {
+    /// An object
+    Object(ObjectId),
+
+    /// A morphism
+    Morphism(MorphismId),
+
+    /// A path equation, by name
+    Equation(String),
+}
+
+impl From<Edited> for Element {
+    fn from(edited: Edited) -> Self {
+        match edited {
+            Edited::Object(id) => Element::Object(id),
+            Edited::Morphism(id) => Element::Morphism(id),
+        }
+    }
+}
+
+impl Element {
+    /// Get the element's name in a context, if it still exists.
+    fn name<'c>(&'c self, context: &'c BoundedContext) -> Option<&'c str> {
+        let graph = context.graph();
+        match self {
+            Element::Object(id) => graph.get_object(*id).map(|o| o.name.as_str()),
+            Element::Morphism(id) => graph.get_morphism(*id).map(|m| m.name.as_str()),
+            Element::Equation(name) => Some(name),
+        }
+    }
+}
+
+/// The issues of a model's contexts and maps from the last validation,
+/// with the elements edited since.
+#[derive(Debug, Clone, Default)]
+pub struct ValidationCache {
+    /// Issues of each context, prefixed with its name
+    contexts: HashMap<String, ValidationResult>,
+
+    /// Issues of each context map
+    maps: HashMap<String, ValidationResult>,
+
+    /// Edited elements of each dirty context
+    dirty: HashMap<String, Vec<Element>>,
+
+    /// Context maps edited since the last validation
+    dirty_maps: HashSet<String>,
+
+    /// Fingerprint of each context, as [`mark_changed`](Self::mark_changed)
+    /// last saw it
+    fingerprints: HashMap<String, Fingerprint>,
+
+    /// Fingerprint of each context map, likewise
+    map_fingerprints: HashMap<String, Fingerprint>,
+}
+
+impl ValidationCache {
+    /// Create an empty cache; the first validation checks everything.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that an element of a context was edited.
+    pub fn mark_dirty(&mut self, context: &str, element: impl Into<Element>) {
+        let elements = self.dirty.entry(context.to_string()).or_default();
+        let element = element.into();
+        if !elements.contains(&element) {
+            elements.push(element);
+        }
+    }
+
+    /// Record that a context changed as a whole, such as after reloading it.
+    pub fn mark_context_dirty(&mut self, context: &str) {
+        self.dirty.entry(context.to_string()).or_default();
+    }
+
+    /// Record that a context map was edited.
+    pub fn mark_map_dirty(&mut self, map: &str) {
+        self.dirty_maps.insert(map.to_string());
+    }
+
+    /// Mark the contexts and maps of a model whose fingerprints differ from
+    /// the ones last given, for callers that cannot track their edits.
+    ///
+    /// The fingerprints are those of the model, as [`Fingerprints::of`]
+    /// takes them.
+    pub fn mark_changed(&mut self, workspace: &Workspace, fingerprints: &Fingerprints) {
+        let contexts = workspace.contexts.iter().map(|c| c.name());
+        let mut seen = HashMap::new();
+        for (name, &fingerprint) in contexts.zip(&fingerprints.contexts) {
+            if self.fingerprints.get(name) != Some(&fingerprint) {
+                self.mark_context_dirty(name);
+            }
+            seen.insert(name.to_string(), fingerprint);
+        }
+        self.fingerprints = seen;
+
+        let maps = workspace.context_maps.iter().map(|m| m.name());
+        let mut seen = HashMap::new();
+        for (name, &fingerprint) in maps.zip(&fingerprints.maps) {
+            if self.map_fingerprints.get(name) != Some(&fingerprint) {
+                self.mark_map_dirty(name);
+            }
+            seen.insert(name.to_string(), fingerprint);
+        }
+        self.map_fingerprints = seen;
+    }
+
+    /// Get the elements of a context edited since the last validation.
+    pub fn dirty_elements(&self, context: &str) -> &[Element] {
+        self.dirty
+            .get(context)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Check if nothing was edited since the last validation.
+    pub fn is_clean(&self) -> bool {
+        self.dirty.is_empty() && self.dirty_maps.is_empty()
+    }
+
+    /// Validate a workspace, re-validating only the dirty contexts, new
+    /// ones, and the maps touching them.
+    ///
+    /// The result is the same as [`Workspace::validate`] would give, in the
+    /// same order, provided every edit was marked.
+    pub fn validate(&mut self, workspace: &Workspace) -> ValidationResult {
+        let contexts = &workspace.contexts;
+        let maps = &workspace.context_maps;
+        let duplicated = duplicated_names(contexts.iter().map(|c| c.name()));
+        let duplicated_maps = duplicated_names(maps.iter().map(|m| m.name()));
+
+        // Contexts that were removed change the maps pointing at them too
+        let present: HashSet<&str> = contexts.iter().map(|c| c.name()).collect();
+        let mut stale: HashSet<String> = self
+            .contexts
+            .keys()
+            .filter(|name| !present.contains(name.as_str()))
+            .cloned()
+            .collect();
+        self.contexts
+            .retain(|name, _| present.contains(name.as_str()));
+
+        let stale_contexts: Vec<&BoundedContext> = contexts
+            .iter()
+            .filter(|c| {
+                duplicated.contains(c.name())
+                    || self.dirty.contains_key(c.name())
+                    || !self.contexts.contains_key(c.name())
+            })
+            .collect();
+        stale.extend(stale_contexts.iter().map(|c| c.name().to_string()));
+        let fresh = validate_each(&stale_contexts, |c| validate_context_in_model(c));
+
+        let mut result = ValidationResult::new();
+        validate_context_names(contexts, &mut result);
+        let mut fresh = stale_contexts.iter().zip(fresh);
+        for context in contexts {
+            let issues = match self.contexts.get(context.name()) {
+                Some(cached) if !stale.contains(context.name()) => cached.issues.clone(),
+                _ => {
+                    let (_, context_result) = fresh.next().expect("stale context was validated");
+                    let issues = context_result.issues.clone();
+                    if !duplicated.contains(context.name()) {
+                        self.contexts
+                            .insert(context.name().to_string(), context_result);
+                    }
+                    issues
+                }
+            };
+            for issue in issues {
+                result.add(issue);
+            }
+        }
+
+        validate_map_names(maps, &mut result);
+        let lookup = context_lookup(contexts);
+        let present_maps: HashSet<&str> = maps.iter().map(|m| m.name()).collect();
+        self.maps
+            .retain(|name, _| present_maps.contains(name.as_str()));
+        for map in maps {
+            let reuse = !duplicated_maps.contains(map.name())
+                && !self.dirty_maps.contains(map.name())
+                && !stale.contains(map.source_context())
+                && !stale.contains(map.target_context());
+            let map_result = match self.maps.get(map.name()) {
+                Some(cached) if reuse => cached.clone(),
+                _ => {
+                    let map_result = validate_context_map(map, &lookup);
+                    if !duplicated_maps.contains(map.name()) {
+                        self.maps.insert(map.name().to_string(), map_result.clone());
+                    }
+                    map_result
+                }
+            };
+            for issue in map_result.issues {
+                result.add(issue);
+            }
+        }
+
+        validate_unreferenced(contexts, maps, &mut result);
+        for issue in validate_domains(&workspace.domains, contexts).issues {
+            result.add(issue);
+        }
+
+        self.dirty.clear();
+        self.dirty_maps.clear();
+        result
+    }
+
+    /// Validate a workspace as [`validate`](Self::validate) does, then run
+    /// the lint rules of `registry` as [`Workspace::lint`] does.
+    ///
+    /// The rules look across the model, so they all run on every call.
+    pub fn lint(
+        &mut self,
+        workspace: &Workspace,
+        registry: &Registry,
+        config: &LintConfig,
+    ) -> ValidationResult {
+        let result = self.validate(workspace);
+        workspace.lint_validated(result, registry, config)
+    }
+
+    /// Validate the next context the next [`validate`](Self::validate)
+    /// would re-validate, returning its name, or `None` when there is none.
+    ///
+    /// A long validation can be spread over several calls this way, such
+    /// as between the frames of a browser, and stopped between any two of
+    /// them: once this returns `None`, [`validate`](Self::validate) only
+    /// runs the checks spanning the model.
+    pub fn validate_next<'w>(&mut self, workspace: &'w Workspace) -> Option<&'w str> {
+        let contexts = &workspace.contexts;
+        let duplicated = duplicated_names(contexts.iter().map(|c| c.name()));
+        let context = contexts.iter().find(|c| {
+            !duplicated.contains(c.name())
+                && (self.dirty.contains_key(c.name()) || !self.contexts.contains_key(c.name()))
+        })?;
+
+        self.contexts.insert(
+            context.name().to_string(),
+            validate_context_in_model(context),
+        );
+        self.dirty.remove(context.name());
+        // The maps touching the context still need re-validating
+        for map in &workspace.context_maps {
+            if map.source_context() == context.name() || map.target_context() == context.name() {
+                self.dirty_maps.insert(map.name().to_string());
+            }
+        }
+        Some(context.name())
+    }
+
+    /// Get the cached issues of a context that are about an element.
+    ///
+    /// Issues do not point at model elements, so an issue is taken to be
+    /// about an element when its message quotes the element's name.
+    pub fn issues_about(
+        &self,
+        context: &BoundedContext,
+        element: &Element,
+    ) -> Vec<&ValidationError> {
+        let (Some(name), Some(cached)) = (element.name(context), self.contexts.get(context.name()))
+        else {
+            return Vec::new();
+        };
+        let quoted = format!("'{}'", name);
+        cached
+            .issues
+            .iter()
+            .filter(|issue| issue.message.contains(&quoted))
+            .collect()
+    }
+}
+
+/// A model under edit with the cache validating it, which marks what every
+/// edit, undo and redo changed.
+#[derive(Debug, Clone)]
+pub struct ValidatedSession {
+    session: ModelEditSession,
+    cache: ValidationCache,
+}
+
+impl ValidatedSession {
+    /// Start editing a workspace; the first validation checks everything.
+    pub fn new(workspace: Workspace) -> Self {
+        Self {
+            session: ModelEditSession::new(workspace),
+            cache: ValidationCache::new(),
+        }
+    }
+
+    /// Get the edit session, with the model and its history.
+    pub fn session(&self) -> &ModelEditSession {
+        &self.session
+    }
+
+    /// Get the model being edited.
+    pub fn workspace(&self) -> &Workspace {
+        self.session.workspace()
+    }
+
+    /// Apply a command to a context, marking the element it edited.
+    pub fn apply(&mut self, context: &str, command: EditCommand) -> Result<Edited, EditError> {
+        let edited = self.session.apply(context, command)?;
+        self.cache.mark_dirty(context, edited);
+        Ok(edited)
+    }
+
+    /// Take back the most recent edit, marking the context it changed.
+    /// Returns `false` if there is none.
+    pub fn undo(&mut self) -> bool {
+        match self.session.undo() {
+            Some(context) => {
+                self.cache.mark_context_dirty(context);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Apply the most recently undone edit again, marking the context it
+    /// changed. Returns `false` if there is none.
+    pub fn redo(&mut self) -> bool {
+        match self.session.redo() {
+            Some(context) => {
+                self.cache.mark_context_dirty(context);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Validate the model, re-checking only what changed.
+    pub fn validate(&mut self) -> ValidationResult {
+        self.cache.validate(self.session.workspace())
+    }
+}
+
+/// Outputs derived from the elements of a model, such as diagrams and
+/// generated files, each kept with the fingerprint of what it was derived
+/// from so that it is derived again only when that changes.
+#[derive(Debug, Clone)]
+pub struct Memo<K, V> {
+    entries: HashMap<K, (Fingerprint, V)>,
+}
+
+impl<K, V> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+}
+
+impl<K: Eq + Hash, V> Memo<K, V> {
+    /// Create an empty memo.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the output kept under `key`, deriving it again with `derive`
+    /// unless it was derived from an input with the same fingerprint.
+    pub fn get_or_derive(&mut self, key: K, input: Fingerprint, derive: impl FnOnce() -> V) -> &V {
+        let entry = match self.entries.entry(key) {
+            Entry::Occupied(entry) => {
+                let entry = entry.into_mut();
+                if entry.0 != input {
+                    *entry = (input, derive());
+                }
+                entry
+            }
+            Entry::Vacant(entry) => entry.insert((input, derive())),
+        };
+        &entry.1
+    }
+
+    /// Forget the outputs whose keys `keep` rejects, such as those of
+    /// removed contexts.
+    pub fn retain(&mut self, mut keep: impl FnMut(&K) -> bool) {
+        self.entries.retain(|key, _| keep(key));
+    }
+
+    /// Get the number of outputs kept.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if no output is kept.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Get the names that occur more than once.
+fn duplicated_names<'n>(names: impl Iterator<Item = &'n str>) -> HashSet<&'n str> {
+    let mut seen = HashSet::new();
+    names.filter(|name| !seen.insert(*name)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapping::{NamedContextMap, RelationshipPattern};
+
+    fn messages(result: &ValidationResult) -> Vec<String> {
+        result.issues.iter().map(|i| i.message.clone()).collect()
+    }
+
+    fn workspace() -> Workspace {
+        let mut workspace = Workspace::new();
+        for name in ["Sales", "Billing"] {
+            let mut context = BoundedContext::new(name);
+            context.add_entity("Order");
+            workspace.add_context(context);
+        }
+        workspace.add_context_map(NamedContextMap::new(
+            "SalesToBilling",
+            "Sales",
+            "Billing",
+            RelationshipPattern::Partnership,
+        ));
+        workspace
+    }
+
+    #[test]
+    fn test_cache_matches_full_validation() {
+        let mut workspace = workspace();
+        let mut cache = ValidationCache::new();
+        assert_eq!(
+            messages(&cache.validate(&workspace)),
+            messages(&workspace.validate())
+        );
+        assert!(cache.is_clean());
+
+        let duplicate = workspace.contexts[1].add_entity("Order");
+        cache.mark_dirty("Billing", Edited::Object(duplicate));
+        assert_eq!(
+            cache.dirty_elements("Billing"),
+            &[Element::Object(duplicate)]
+        );
+        let result = cache.validate(&workspace);
+        assert_eq!(messages(&result), messages(&workspace.validate()));
+        assert!(result.errors().any(|e| e.code == "E0020"));
+
+        workspace.contexts.remove(1);
+        let result = cache.validate(&workspace);
+        assert_eq!(messages(&result), messages(&workspace.validate()));
+    }
+
+    #[test]
+    fn test_validate_next_spreads_validation() {
+        let mut workspace = workspace();
+        workspace.contexts[1].add_entity("Order");
+        let mut cache = ValidationCache::new();
+
+        assert_eq!(cache.validate_next(&workspace), Some("Sales"));
+        assert_eq!(cache.validate_next(&workspace), Some("Billing"));
+        assert_eq!(cache.validate_next(&workspace), None);
+        assert_eq!(
+            messages(&cache.validate(&workspace)),
+            messages(&workspace.validate())
+        );
+
+        cache.mark_context_dirty("Billing");
+        assert_eq!(cache.validate_next(&workspace), Some("Billing"));
+        assert_eq!(cache.validate_next(&workspace), None);
+        assert_eq!(
+            messages(&cache.validate(&workspace)),
+            messages(&workspace.validate())
+        );
+    }
+
+    #[test]
+    fn test_unmarked_contexts_are_reused() {
+        let mut workspace = workspace();
+        let mut cache = ValidationCache::new();
+        cache.validate(&workspace);
+
+        let order = workspace.contexts[0].add_entity("Order");
+        assert!(cache.validate(&workspace).is_ok());
+
+        cache.mark_context_dirty("Sales");
+        let result = cache.validate(&workspace);
+        assert!(!result.is_ok());
+        let about = cache.issues_about(&workspace.contexts[0], &Element::Object(order));
+        assert_eq!(about.len(), 1);
+        assert_eq!(about[0].message, "[Sales] Duplicate object name: 'Order'");
+    }
+
+    #[test]
+    fn test_changed_contexts_are_found_by_fingerprint() {
+        let mut cache = ValidationCache::new();
+        let workspace = workspace();
+        cache.mark_changed(&workspace, &Fingerprints::of(&workspace));
+        cache.validate(&workspace);
+
+        // Loading the same model again changes nothing
+        let mut reloaded = self::workspace();
+        assert_eq!(Fingerprints::of(&reloaded), Fingerprints::of(&workspace));
+        cache.mark_changed(&reloaded, &Fingerprints::of(&reloaded));
+        assert!(cache.is_clean());
+
+        reloaded.contexts[1].add_entity("Order");
+        cache.mark_changed(&reloaded, &Fingerprints::of(&reloaded));
+        assert!(cache.dirty_elements("Sales").is_empty());
+        assert!(!cache.is_clean());
+        assert_eq!(
+            messages(&cache.validate(&reloaded)),
+            messages(&reloaded.validate())
+        );
+    }
+
+    #[test]
+    fn test_session_revalidates_undone_edits() {
+        let mut session = ValidatedSession::new(workspace());
+        assert!(session.validate().is_ok());
+
+        let edited = session.apply(
+            "Sales",
+            EditCommand::AddEntity {
+                name: "Order".to_string(),
+            },
+        );
+        assert!(edited.is_ok());
+        let result = session.validate();
+        assert!(result.errors().any(|e| e.code == "E0020"));
+        assert_eq!(messages(&result), messages(&session.workspace().validate()));
+
+        assert!(session.undo());
+        assert!(session.validate().is_ok());
+
+        assert!(session.redo());
+        assert!(session.validate().errors().any(|e| e.code == "E0020"));
+        assert!(session.undo());
+        assert!(!session.undo());
+        assert!(session.validate().is_ok());
+    }
+
+    #[test]
+    fn test_memo_derives_again_when_input_changes() {
+        let mut workspace = workspace();
+        let mut memo = Memo::new();
+        let mut derived = 0;
+        let mut diagram = |memo: &mut Memo<String, usize>, context: &BoundedContext| {
+            *memo.get_or_derive(context.name().to_string(), Fingerprint::of(context), || {
+                derived += 1;
+                context.graph().objects().count()
+            })
+        };
+
+        assert_eq!(diagram(&mut memo, &workspace.contexts[0]), 1);
+        assert_eq!(diagram(&mut memo, &workspace.contexts[0]), 1);
+        workspace.contexts[0].add_value_object("Money");
+        assert_eq!(diagram(&mut memo, &workspace.contexts[0]), 2);
+        assert_eq!(diagram(&mut memo, &workspace.contexts[1]), 1);
+        assert_eq!(derived, 3);
+
+        memo.retain(|name| name == "Sales");
+        assert_eq!(memo.len(), 1);
+    }
+}
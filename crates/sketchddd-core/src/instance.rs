@@ -0,0 +1,411 @@
+//! Instances: example data for a sketch.
+//!
+//! An instance populates a sketch with data. Each object is interpreted
+//! as a set of rows, identified by name (`"order1"`, `"alice"`), and each
+//! morphism as a function from the rows of its source to the rows of its
+//! target. Categorically, an instance is a functor from the sketch to
+//! `Set`, and validation checks that it respects the sketch's equations
+//! and limits.
+//!
+//! Example data is usually partial, so an instance may leave parts of
+//! the sketch unpopulated:
+//!
+//! - an object without rows is *open*: any value is accepted for it, so
+//!   primitive types such as `String` need not list their values;
+//! - a morphism without values is skipped, but a morphism with values
+//!   must map every row of its source.
+
+use crate::sketch::{Graph, MorphismId, ObjectId, Sketch};
+use crate::validation::{ValidationError, ValidationResult};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Example data for a sketch, mapping objects to rows and morphisms to
+/// functions between rows.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Instance {
+    rows: HashMap<ObjectId, BTreeSet<String>>,
+    functions: HashMap<MorphismId, BTreeMap<String, String>>,
+}
+
+impl Instance {
+    /// Create an empty instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a row to an object.
+    pub fn add_row(&mut self, object: ObjectId, row: impl Into<String>) {
+        self.rows.entry(object).or_default().insert(row.into());
+    }
+
+    /// Set the value of a morphism at a row of its source.
+    pub fn set_value(
+        &mut self,
+        morphism: MorphismId,
+        row: impl Into<String>,
+        value: impl Into<String>,
+    ) {
+        self.functions
+            .entry(morphism)
+            .or_default()
+            .insert(row.into(), value.into());
+    }
+
+    /// Get the rows of an object, in order.
+    pub fn rows(&self, object: ObjectId) -> impl Iterator<Item = &str> {
+        self.rows
+            .get(&object)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+    }
+
+    /// Check if an object has any rows.
+    pub fn is_populated(&self, object: ObjectId) -> bool {
+        self.rows.get(&object).is_some_and(|rows| !rows.is_empty())
+    }
+
+    /// Get the value of a morphism at a row.
+    ///
+    /// Identity morphisms map every row to itself.
+    pub fn apply<'a>(
+        &'a self,
+        graph: &Graph,
+        morphism: MorphismId,
+        row: &'a str,
+    ) -> Option<&'a str> {
+        if graph.get_morphism(morphism)?.is_identity {
+            return Some(row);
+        }
+        self.functions.get(&morphism)?.get(row).map(String::as_str)
+    }
+
+    /// Follow a path of morphisms from a row.
+    ///
+    /// Returns `None` if some morphism along the path has no value.
+    pub fn follow<'a>(
+        &'a self,
+        graph: &Graph,
+        path: &[MorphismId],
+        row: &'a str,
+    ) -> Option<&'a str> {
+        path.iter().try_fold(row, |current, &morphism| {
+            self.apply(graph, morphism, current)
+        })
+    }
+
+    /// Check that the instance is well-typed and satisfies the equations
+    /// and limits of a sketch.
+    pub fn validate(&self, sketch: &Sketch) -> ValidationResult {
+        let mut result = ValidationResult::new();
+        let graph = &sketch.graph;
+
+        self.validate_rows(graph, &mut result);
+        self.validate_functions(graph, &mut result);
+        if !result.is_ok() {
+            // Equations and limits are only meaningful for a well-typed instance
+            return result;
+        }
+        self.validate_equations(sketch, &mut result);
+        self.validate_limits(sketch, &mut result);
+        self.validate_colimits(sketch, &mut result);
+
+        result
+    }
+
+    /// E0130: rows and values must belong to objects and morphisms of the sketch.
+    fn validate_rows(&self, graph: &Graph, result: &mut ValidationResult) {
+        for object in self.rows.keys() {
+            if graph.get_object(*object).is_none() {
+                result.add(ValidationError::error(
+                    "E0130",
+                    format!(
+                        "Instance has rows for non-existent object (id: {:?})",
+                        object
+                    ),
+                ));
+            }
+        }
+        for morphism in self.functions.keys() {
+            if graph.get_morphism(*morphism).is_none() {
+                result.add(ValidationError::error(
+                    "E0130",
+                    format!(
+                        "Instance has values for non-existent morphism (id: {:?})",
+                        morphism
+                    ),
+                ));
+            }
+        }
+    }
+
+    /// E0131-E0132: populated morphisms must be total functions between rows.
+    fn validate_functions(&self, graph: &Graph, result: &mut ValidationResult) {
+        let mut functions: Vec<_> = self.functions.iter().collect();
+        functions.sort_by_key(|(id, _)| **id);
+
+        for (&id, function) in functions {
+            let Some(morphism) = graph.get_morphism(id) else {
+                continue;
+            };
+            let source = object_name(graph, morphism.source);
+            let target = object_name(graph, morphism.target);
+
+            for row in self.rows(morphism.source) {
+                if !function.contains_key(row) {
+                    result.add(ValidationError::error(
+                        "E0131",
+                        format!(
+                            "Morphism '{}' has no value for row '{}' of '{}'",
+                            morphism.name, row, source
+                        ),
+                    ));
+                }
+            }
+
+            for (row, value) in function {
+                if self.is_populated(morphism.source) && !self.has_row(morphism.source, row) {
+                    result.add(ValidationError::error(
+                        "E0132",
+                        format!(
+                            "Morphism '{}' maps '{}', which is not a row of '{}'",
+                            morphism.name, row, source
+                        ),
+                    ));
+                }
+                if self.is_populated(morphism.target) && !self.has_row(morphism.target, value) {
+                    let rows: Vec<&str> = self.rows(morphism.target).collect();
+                    let mut error = ValidationError::error(
+                        "E0132",
+                        format!(
+                            "Morphism '{}' maps '{}' to '{}', which is not a row of '{}'",
+                            morphism.name, row, value, target
+                        ),
+                    );
+                    if let Some(suggestion) = crate::did_you_mean(value, &rows) {
+                        error = error.with_suggestion(suggestion);
+                    }
+                    result.add(error);
+                }
+            }
+        }
+    }
+
+    /// E0133: both sides of every equation must agree on every row where
+    /// both are defined.
+    fn validate_equations(&self, sketch: &Sketch, result: &mut ValidationResult) {
+        let graph = &sketch.graph;
+
+        for equation in sketch.equations.iter().filter(|eq| eq.is_well_formed()) {
+            for row in self.rows(equation.lhs.source) {
+                let lhs = self.follow(graph, &equation.lhs.morphisms, row);
+                let rhs = self.follow(graph, &equation.rhs.morphisms, row);
+                if let (Some(lhs), Some(rhs)) = (lhs, rhs) {
+                    if lhs != rhs {
+                        result.add(ValidationError::error(
+                            "E0133",
+                            format!(
+                                "Row '{}' of '{}' violates equation '{}': one side gives '{}', the other '{}'",
+                                row,
+                                object_name(graph, equation.lhs.source),
+                                equation.name,
+                                lhs,
+                                rhs
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    /// E0134: rows of a value object, or any other limit, are determined by
+    /// their components.
+    ///
+    /// Example data need not contain every combination of components, so
+    /// only the uniqueness half of the limit's universal property is checked.
+    fn validate_limits(&self, sketch: &Sketch, result: &mut ValidationResult) {
+        let graph = &sketch.graph;
+
+        for limit in sketch.limits.iter().filter(|l| !l.is_aggregate) {
+            let mut seen: HashMap<Vec<&str>, &str> = HashMap::new();
+            for row in self.rows(limit.apex) {
+                let components: Option<Vec<&str>> = limit
+                    .projections
+                    .iter()
+                    .map(|p| self.apply(graph, p.morphism, row))
+                    .collect();
+                let Some(components) = components else {
+                    continue;
+                };
+                if let Some(existing) = seen.insert(components, row) {
+                    result.add(
+                        ValidationError::error(
+                            "E0134",
+                            format!(
+                                "Rows '{}' and '{}' of {} '{}' have the same components",
+                                existing,
+                                row,
+                                if limit.is_product() { "value object" } else { "limit" },
+                                limit.name
+                            ),
+                        )
+                        .with_suggestion(
+                            "Value objects are equal when their components are; merge the rows",
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    /// E0135: rows of an enumeration are its variants.
+    fn validate_colimits(&self, sketch: &Sketch, result: &mut ValidationResult) {
+        for colimit in &sketch.colimits {
+            // Only enumerations, whose variants are all units, name their rows
+            if colimit.injections.iter().any(|i| i.source != colimit.apex) {
+                continue;
+            }
+            let variants: Vec<&str> = colimit.variant_names().collect();
+            for row in self.rows(colimit.apex) {
+                if !variants.contains(&row) {
+                    let mut error = ValidationError::error(
+                        "E0135",
+                        format!(
+                            "Row '{}' of enum '{}' is not one of its variants",
+                            row, colimit.name
+                        ),
+                    );
+                    if let Some(suggestion) = crate::did_you_mean(row, &variants) {
+                        error = error.with_suggestion(suggestion);
+                    }
+                    result.add(error);
+                }
+            }
+        }
+    }
+
+    fn has_row(&self, object: ObjectId, row: &str) -> bool {
+        self.rows
+            .get(&object)
+            .is_some_and(|rows| rows.contains(row))
+    }
+}
+
+fn object_name(graph: &Graph, id: ObjectId) -> &str {
+    graph.get_object(id).map_or("?", |o| o.name.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sketch::{ColimitCocone, LimitCone, Path, PathEquation};
+
+    struct Commerce {
+        sketch: Sketch,
+        order: ObjectId,
+        customer: ObjectId,
+        placed_by: MorphismId,
+        billed_to: MorphismId,
+    }
+
+    fn commerce() -> Commerce {
+        let mut sketch = Sketch::new("Commerce");
+        let order = sketch.add_object("Order");
+        let customer = sketch.add_object("Customer");
+        let placed_by = sketch.add_morphism("placedBy", order, customer);
+        let billed_to = sketch.add_morphism("billedTo", order, customer);
+        sketch.add_equation(PathEquation::new(
+            "billed_to_buyer",
+            Path::new(order, customer, vec![billed_to]),
+            Path::new(order, customer, vec![placed_by]),
+        ));
+        Commerce {
+            sketch,
+            order,
+            customer,
+            placed_by,
+            billed_to,
+        }
+    }
+
+    fn codes(result: &ValidationResult) -> Vec<&str> {
+        result.issues.iter().map(|e| e.code.as_str()).collect()
+    }
+
+    #[test]
+    fn test_valid_instance() {
+        let c = commerce();
+        let mut instance = Instance::new();
+        instance.add_row(c.order, "order1");
+        instance.add_row(c.customer, "alice");
+        instance.set_value(c.placed_by, "order1", "alice");
+        instance.set_value(c.billed_to, "order1", "alice");
+
+        assert!(!instance.validate(&c.sketch).has_issues());
+        assert_eq!(
+            instance.follow(&c.sketch.graph, &[c.placed_by], "order1"),
+            Some("alice")
+        );
+    }
+
+    #[test]
+    fn test_ill_typed_instance() {
+        let c = commerce();
+        let mut instance = Instance::new();
+        instance.add_row(c.order, "order1");
+        instance.add_row(c.order, "order2");
+        instance.add_row(c.customer, "alice");
+        instance.set_value(c.placed_by, "order1", "alicee");
+
+        let result = instance.validate(&c.sketch);
+        assert_eq!(codes(&result), vec!["E0131", "E0132"]);
+        assert!(result.issues[1]
+            .suggestion
+            .as_deref()
+            .is_some_and(|s| s.contains("alice")));
+    }
+
+    #[test]
+    fn test_equation_violation() {
+        let c = commerce();
+        let mut instance = Instance::new();
+        instance.add_row(c.order, "order1");
+        instance.add_row(c.customer, "alice");
+        instance.add_row(c.customer, "bob");
+        instance.set_value(c.placed_by, "order1", "alice");
+        instance.set_value(c.billed_to, "order1", "bob");
+
+        let result = instance.validate(&c.sketch);
+        assert_eq!(codes(&result), vec!["E0133"]);
+        assert!(result.issues[0].message.contains("'billed_to_buyer'"));
+    }
+
+    #[test]
+    fn test_limit_and_colimit_violations() {
+        let mut sketch = Sketch::new("Pricing");
+        let money = sketch.add_object("Money");
+        let currency = sketch.add_object("Currency");
+        let currency_of = sketch.add_morphism("currency", money, currency);
+        let mut limit = LimitCone::value_object("Money", money);
+        limit.add_projection(currency_of, currency);
+        sketch.add_limit(limit);
+        sketch.add_colimit(ColimitCocone::enumeration(
+            "Currency",
+            currency,
+            vec!["USD".into(), "EUR".into()],
+        ));
+
+        let mut instance = Instance::new();
+        instance.add_row(money, "ten");
+        instance.add_row(money, "also_ten");
+        instance.add_row(currency, "USD");
+        instance.add_row(currency, "GBP");
+        instance.set_value(currency_of, "ten", "USD");
+        instance.set_value(currency_of, "also_ten", "USD");
+
+        let result = instance.validate(&sketch);
+        assert_eq!(codes(&result), vec!["E0134", "E0135"]);
+    }
+}
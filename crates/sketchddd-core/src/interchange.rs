@@ -0,0 +1,343 @@
+//! A versioned interchange format for whole models.
+//!
+//! `sketchddd export` writes a model as a [`ModelDocument`], in JSON or
+//! YAML, for tools that would rather not parse the DSL, and `sketchddd
+//! import` reads one back. A document records the version of the format
+//! it was written in, see [`MODEL_FORMAT_VERSION`]. Documents written in an
+//! older version are migrated when read; documents written by a newer
+//! SketchDDD are refused rather than misread.
+//!
+//! The format is independent of the serialization: documents are converted
+//! through [`serde_json::Value`], which YAML maps onto as well.
+//!
+//! Large models are slow to write and read as text, so the same format has
+//! a binary serialization, [`Workspace::to_bytes`], for caches and for
+//! clients such as the visual builder. It starts with [`BINARY_MAGIC`] and
+//! the version, followed by the model as MessagePack. A model of the
+//! current version is read straight from the bytes; an older one goes
+//! through a [`serde_json::Value`] to be migrated like any document.
+
+use crate::workspace::Workspace;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+/// Version of the [`ModelDocument`] format.
+pub const MODEL_FORMAT_VERSION: u32 = 1;
+
+/// First bytes of a model in the binary format.
+pub const BINARY_MAGIC: &[u8; 4] = b"SDDM";
+
+/// Upgrades from each version of the format to the next: the one at index
+/// `i` turns a document of version `i + 1` into one of version `i + 2`.
+const MIGRATIONS: &[fn(&mut Value)] = &[];
+
+/// A model in the interchange format.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelDocument {
+    /// Version of the format, see [`MODEL_FORMAT_VERSION`]
+    pub version: u32,
+
+    /// The model
+    pub model: Workspace,
+}
+
+/// Error reading a [`ModelDocument`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatError {
+    /// The document does not say which version of the format it uses
+    MissingVersion,
+
+    /// The document uses a version of the format newer than this one
+    UnsupportedVersion(u64),
+
+    /// The document does not describe a model
+    Invalid(String),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::MissingVersion => {
+                write!(
+                    f,
+                    "not a SketchDDD model: the document has no format version"
+                )
+            }
+            FormatError::UnsupportedVersion(version) => write!(
+                f,
+                "model format version {} is newer than the supported version {}; upgrade SketchDDD",
+                version, MODEL_FORMAT_VERSION
+            ),
+            FormatError::Invalid(message) => write!(f, "invalid model: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+impl ModelDocument {
+    /// Wrap a model in a document of the current version.
+    pub fn new(model: Workspace) -> Self {
+        Self {
+            version: MODEL_FORMAT_VERSION,
+            model,
+        }
+    }
+
+    /// Convert the document to a value, ready to be written as JSON or YAML.
+    pub fn to_value(&self) -> Value {
+        serde_json::to_value(self).expect("a model serializes to a JSON value")
+    }
+
+    /// Read a document of any supported version, migrating it to the
+    /// current one.
+    pub fn from_value(mut value: Value) -> Result<Self, FormatError> {
+        let version = match value.get("version").and_then(Value::as_u64) {
+            Some(version) if version >= 1 => version,
+            _ => return Err(FormatError::MissingVersion),
+        };
+        if version > u64::from(MODEL_FORMAT_VERSION) {
+            return Err(FormatError::UnsupportedVersion(version));
+        }
+
+        for migrate in &MIGRATIONS[(version - 1) as usize..] {
+            migrate(&mut value);
+        }
+        value["version"] = MODEL_FORMAT_VERSION.into();
+        serde_json::from_value(value).map_err(|e| FormatError::Invalid(e.to_string()))
+    }
+}
+
+impl Workspace {
+    /// Write the model in the binary format: [`BINARY_MAGIC`], the version
+    /// of the format as a little-endian `u32`, then the model.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = BINARY_MAGIC.to_vec();
+        bytes.extend(MODEL_FORMAT_VERSION.to_le_bytes());
+        // Fields are written by name, so that older models can be migrated
+        rmp_serde::encode::write_named(&mut bytes, self)
+            .expect("a model serializes to MessagePack");
+        bytes
+    }
+
+    /// Read a model in the binary format of any supported version,
+    /// migrating it to the current one.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FormatError> {
+        let header = BINARY_MAGIC.len() + 4;
+        if bytes.len() < header || !bytes.starts_with(BINARY_MAGIC) {
+            return Err(FormatError::MissingVersion);
+        }
+        let version = u32::from_le_bytes(bytes[BINARY_MAGIC.len()..header].try_into().unwrap());
+        let payload = &bytes[header..];
+        if version == MODEL_FORMAT_VERSION {
+            return rmp_serde::from_slice(payload).map_err(|e| FormatError::Invalid(e.to_string()));
+        }
+
+        let model = decode_value(payload)?;
+        ModelDocument::from_value(serde_json::json!({ "version": version, "model": model }))
+            .map(|document| document.model)
+    }
+}
+
+/// Decode a MessagePack value as the value a JSON document would give:
+/// map keys, such as the ids keying the objects of a graph, become strings.
+fn decode_value(mut payload: &[u8]) -> Result<Value, FormatError> {
+    fn convert(value: rmpv::Value) -> Value {
+        match value {
+            rmpv::Value::Nil | rmpv::Value::Ext(..) => Value::Null,
+            rmpv::Value::Boolean(b) => Value::Bool(b),
+            rmpv::Value::Integer(i) => match i.as_u64() {
+                Some(u) => u.into(),
+                None => i.as_i64().map_or(Value::Null, Value::from),
+            },
+            rmpv::Value::F32(f) => f64::from(f).into(),
+            rmpv::Value::F64(f) => f.into(),
+            rmpv::Value::String(s) => Value::String(s.into_str().unwrap_or_default()),
+            rmpv::Value::Binary(bytes) => bytes.into(),
+            rmpv::Value::Array(items) => items.into_iter().map(convert).collect(),
+            rmpv::Value::Map(entries) => Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| {
+                        let key = match convert(key) {
+                            Value::String(key) => key,
+                            key => key.to_string(),
+                        };
+                        (key, convert(value))
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    rmpv::decode::read_value(&mut payload)
+        .map(convert)
+        .map_err(|e| FormatError::Invalid(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::BoundedContext;
+    use crate::mapping::{NamedContextMap, RelationshipPattern};
+    use serde_json::json;
+
+    fn model() -> Workspace {
+        let mut workspace = Workspace::new();
+        for name in ["Sales", "Billing"] {
+            let mut context = BoundedContext::new(name);
+            let order = context.add_entity("Order");
+            context.define_aggregate_with_members("Order", order, &[]);
+            workspace.add_context(context);
+        }
+        workspace.add_context_map(NamedContextMap::new(
+            "SalesToBilling",
+            "Sales",
+            "Billing",
+            RelationshipPattern::CustomerSupplier,
+        ));
+        workspace
+    }
+
+    #[test]
+    fn test_model_document_round_trip() {
+        let value = ModelDocument::new(model()).to_value();
+        assert_eq!(value["version"], MODEL_FORMAT_VERSION);
+
+        let document = ModelDocument::from_value(value).unwrap();
+        let sales = document.model.context("Sales").unwrap();
+        let order = sales.graph().find_object_by_name("Order").unwrap().id;
+        assert!(sales.is_entity(order));
+        assert!(sales.is_aggregate_root(order));
+        assert_eq!(
+            document.model.context_maps[0].pattern(),
+            RelationshipPattern::CustomerSupplier
+        );
+        assert!(document.model.validate().is_ok());
+    }
+
+    #[test]
+    fn test_model_document_versions() {
+        let mut value = ModelDocument::new(model()).to_value();
+        value["version"] = json!(MODEL_FORMAT_VERSION + 1);
+        assert_eq!(
+            ModelDocument::from_value(value).unwrap_err(),
+            FormatError::UnsupportedVersion(u64::from(MODEL_FORMAT_VERSION) + 1)
+        );
+
+        let summary = json!({ "contexts": [{ "name": "Sales" }] });
+        assert_eq!(
+            ModelDocument::from_value(summary).unwrap_err(),
+            FormatError::MissingVersion
+        );
+        let broken = json!({ "version": 1, "model": { "contexts": 3 } });
+        assert!(matches!(
+            ModelDocument::from_value(broken),
+            Err(FormatError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let bytes = model().to_bytes();
+        assert!(bytes.starts_with(BINARY_MAGIC));
+        assert!(bytes.len() < serde_json::to_vec(&model()).unwrap().len());
+
+        let workspace = Workspace::from_bytes(&bytes).unwrap();
+        let billing = workspace.context("Billing").unwrap();
+        let order = billing.graph().find_object_by_name("Order").unwrap().id;
+        assert!(billing.is_aggregate_root(order));
+        assert_eq!(workspace.context_maps[0].name(), "SalesToBilling");
+
+        // Older models are migrated through the value a document would give
+        let value = decode_value(&bytes[BINARY_MAGIC.len() + 4..]).unwrap();
+        assert_eq!(value, serde_json::to_value(model()).unwrap());
+    }
+
+    #[test]
+    fn test_binary_versions() {
+        let mut bytes = model().to_bytes();
+        bytes[BINARY_MAGIC.len()..BINARY_MAGIC.len() + 4]
+            .copy_from_slice(&(MODEL_FORMAT_VERSION + 1).to_le_bytes());
+        assert_eq!(
+            Workspace::from_bytes(&bytes).unwrap_err(),
+            FormatError::UnsupportedVersion(u64::from(MODEL_FORMAT_VERSION) + 1)
+        );
+
+        assert_eq!(
+            Workspace::from_bytes(b"{\"version\": 1}").unwrap_err(),
+            FormatError::MissingVersion
+        );
+        let truncated = &model().to_bytes()[..20];
+        assert!(matches!(
+            Workspace::from_bytes(truncated),
+            Err(FormatError::Invalid(_))
+        ));
+    }
+
+    /// Get the value under `key` in a MessagePack map.
+    fn entry<'a>(map: &'a mut rmpv::Value, key: &str) -> &'a mut rmpv::Value {
+        let rmpv::Value::Map(entries) = map else {
+            panic!("not a map");
+        };
+        let (_, value) = entries.iter_mut().find(|(k, _)| k.as_str() == Some(key)).unwrap();
+        value
+    }
+
+    #[test]
+    fn test_binary_rejects_corrupted_graphs() {
+        // Read a model after editing the graph of its first context
+        let corrupted = |edit: &dyn Fn(&mut rmpv::Value)| {
+            let bytes = model().to_bytes();
+            let header = BINARY_MAGIC.len() + 4;
+            let mut value = rmpv::decode::read_value(&mut &bytes[header..]).unwrap();
+            let rmpv::Value::Array(contexts) = entry(&mut value, "contexts") else {
+                panic!("contexts are not an array");
+            };
+            edit(entry(entry(&mut contexts[0], "sketch"), "graph"));
+            let mut bytes = bytes[..header].to_vec();
+            rmpv::encode::write_value(&mut bytes, &value).unwrap();
+            Workspace::from_bytes(&bytes)
+        };
+        let error = |edit: &dyn Fn(&mut rmpv::Value)| match corrupted(edit) {
+            Err(FormatError::Invalid(message)) => message,
+            other => panic!("expected an invalid model, got {:?}", other.map(|_| ())),
+        };
+        let elements = |graph: &mut rmpv::Value, kind: &str| -> Vec<(rmpv::Value, rmpv::Value)> {
+            match entry(graph, kind) {
+                rmpv::Value::Map(entries) => entries.clone(),
+                _ => panic!("{} are not a map", kind),
+            }
+        };
+        assert!(corrupted(&|_| {}).is_ok());
+
+        // An object given twice under its id
+        let message = error(&|graph| {
+            let mut objects = elements(graph, "objects");
+            objects.push(objects[0].clone());
+            *entry(graph, "objects") = rmpv::Value::Map(objects);
+        });
+        assert!(message.contains("ObjectId(0) is given twice"), "{}", message);
+
+        // Two objects sharing a stable id
+        let message = error(&|graph| {
+            let mut objects = elements(graph, "objects");
+            let mut copy = objects[0].1.clone();
+            *entry(&mut copy, "id") = 1.into();
+            *entry(&mut copy, "name") = "Copy".into();
+            objects.push((1.into(), copy));
+            *entry(graph, "objects") = rmpv::Value::Map(objects);
+            *entry(graph, "next_object_id") = 2.into();
+        });
+        assert!(message.contains("ObjectId(0) and ObjectId(1) share the stable id"), "{}", message);
+
+        // A morphism to an object that is not in the graph
+        let message = error(&|graph| {
+            let mut morphisms = elements(graph, "morphisms");
+            *entry(&mut morphisms[0].1, "target") = 7.into();
+            *entry(graph, "morphisms") = rmpv::Value::Map(morphisms);
+        });
+        assert!(message.contains("MorphismId(0) leads to the missing ObjectId(7)"), "{}", message);
+    }
+}
@@ -22,24 +22,102 @@
 //! | Aggregate | Limit cone with root |
 //! | Invariant | Equalizer |
 //! | Context Map | Sketch morphism |
+//! | Entity Lifecycle | Transition graph over enum variants |
+//! | Process Manager | Diagram over events and commands |
+//! | Field Constraint | Subobject of the field's target |
+//! | Derived Morphism | Morphism defined by a path expression |
+//! | Example Data | Instance (functor to Set) |
 
+pub mod analysis;
+pub mod architecture;
+pub mod collab;
+pub mod constraint;
 pub mod context;
+pub mod coverage;
 pub mod diagnostics;
+pub mod diff;
+pub mod domain;
+pub mod edit;
+pub mod event_storming;
+pub mod expr;
+pub mod incremental;
+pub mod interchange;
+pub mod fix;
+pub mod instance;
+pub mod lifecycle;
+pub mod lint;
 pub mod mapping;
+pub mod metadata;
+pub mod migration;
+pub mod models;
+pub mod naming;
+pub mod policy;
+pub mod process;
+pub mod query;
+pub mod refactor;
+pub mod report;
+#[cfg(feature = "scripting")]
+pub mod script;
 pub mod sketch;
+pub mod stats;
+pub mod suppression;
 pub mod validation;
+pub mod version;
+pub mod workspace;
 
-pub use context::{BoundedContext, Invariant};
+pub use analysis::{
+    aggregate_boundary_crossings, aggregate_dependency_order, find_cycles, objects_reachable_from,
+    strongly_connected_components, BoundaryCrossing,
+};
+pub use constraint::{ConstrainedField, FieldConstraint};
+pub use collab::{Applied, CollabError, Collaboration, Operation};
+pub use context::{AggregateInvariant, BoundedContext, Collection, Enforcement, Invariant};
+pub use coverage::{MapCoverage, SharedConcepts};
+pub use diff::{diff_contexts, diff_models, ChangeKind, Compatibility, ModelChange, ModelDiff};
+pub use domain::{Domain, Subdomain};
+pub use edit::{EditCommand, EditError, EditSession, Edited, ModelEditSession};
+pub use event_storming::{Actor, Hotspot, Policy};
+pub use expr::{BinaryOp, DerivedMorphism, Expr, UnaryOp};
+pub use fix::{apply_fixes, Fix, TextEdit};
 pub use diagnostics::{
-    available_options, did_you_mean, group_errors, suggest_similar, DiagnosticRenderer,
-    GroupedErrors, LocatedError, SourceSpan,
+    available_options, did_you_mean, did_you_mean_correction, group_errors, suggest_similar,
+    DiagnosticRenderer, GroupedErrors, LocatedError, SourceSpan,
+};
+pub use incremental::{
+    Element, Fingerprint, Fingerprints, Memo, ValidatedSession, ValidationCache,
+};
+pub use interchange::{FormatError, ModelDocument, BINARY_MAGIC, MODEL_FORMAT_VERSION};
+pub use instance::Instance;
+pub use lifecycle::{Lifecycle, Transition};
+pub use lint::{
+    AggregateSuffix, ContextMapCoverage, GodObject, LargeContext, LintConfig, LintConfigError,
+    ParallelMorphisms, Registry, Rule, RuleLevel,
 };
 pub use mapping::{
-    check_functorial_consistency, ContextMap, FunctorCheckResult, FunctorError, MorphismMapping,
+    check_functor_laws, check_functorial_consistency, map_path, ContextMap, FunctorCheckResult,
+    FunctorError, MorphismMapping,
     NamedContextMap, NamedMorphismMapping, NamedObjectMapping, ObjectMapping, RelationshipPattern,
 };
+pub use metadata::{Classification, Metadata, MetadataValue};
+pub use migration::{Migration, MigrationError};
+pub use models::{Constraint, FiniteModel, ModelFinder, ModelSearch};
+pub use policy::SeverityPolicy;
+pub use process::{ProcessManager, ProcessStep};
+pub use query::{AggregateDependencies, Query, Usage};
+pub use refactor::{
+    extract_context, rename_morphism_in_model, rename_object_in_model, AggregateError,
+    ContextExtraction, RenameError,
+};
+pub use report::{JsonIssue, JsonReport, JsonSummary, JSON_REPORT_VERSION};
+#[cfg(feature = "scripting")]
+pub use script::{model_view, ScriptError, ScriptRule};
 pub use sketch::Sketch;
+pub use stats::{AggregateSize, ContextStats, Counts, Coupling, ModelStats};
+pub use suppression::{Baseline, BaselineIssue, Suppression};
 pub use validation::{
-    validate_context, validate_context_map, validate_model, validate_sketch, Severity,
+    validate_aggregate_boundaries, validate_context, validate_context_map, validate_domains, validate_model,
+    validate_sketch, Severity,
     SourceLocation, ValidationError, ValidationResult,
 };
+pub use version::{ModelVersion, Removal};
+pub use workspace::Workspace;
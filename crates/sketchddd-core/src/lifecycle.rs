@@ -0,0 +1,238 @@
+//! Entity lifecycles as state machines over enumerations.
+//!
+//! A lifecycle attaches a transition graph to an enumeration: the variants
+//! of the enum are the states, and each declared transition is an edge
+//! between two of them. The first transition's source is the initial state.
+//!
+//! Categorically, the enum is a colimit (its variants are the injections)
+//! and the lifecycle is a small graph whose vertices are those injections.
+//! A state is reachable when a path leads to it from the initial state.
+//!
+//! Every variant should be handled: either a state some transition
+//! mentions, or declared as handled by default, outside the state machine.
+
+use crate::sketch::ObjectId;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// A transition between two states of a lifecycle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transition {
+    /// Source state (enum variant name)
+    pub from: String,
+
+    /// Target state (enum variant name)
+    pub to: String,
+}
+
+/// A lifecycle state machine attached to an enumeration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lifecycle {
+    /// Name of the lifecycle (usually the entity it describes)
+    pub name: String,
+
+    /// The enumeration whose variants are the states
+    pub subject: ObjectId,
+
+    /// Transitions in declaration order
+    pub transitions: Vec<Transition>,
+
+    /// Variants handled by default rather than as states
+    #[serde(default)]
+    pub defaults: Vec<String>,
+}
+
+impl Lifecycle {
+    /// Create a new lifecycle over the given enumeration.
+    pub fn new(name: impl Into<String>, subject: ObjectId) -> Self {
+        Self {
+            name: name.into(),
+            subject,
+            transitions: Vec::new(),
+            defaults: Vec::new(),
+        }
+    }
+
+    /// Add a transition from one state to another.
+    ///
+    /// Duplicate transitions are ignored.
+    pub fn add_transition(&mut self, from: impl Into<String>, to: impl Into<String>) {
+        let transition = Transition {
+            from: from.into(),
+            to: to.into(),
+        };
+        if !self.transitions.contains(&transition) {
+            self.transitions.push(transition);
+        }
+    }
+
+    /// Declare a variant as handled by default, outside the state machine.
+    pub fn add_default(&mut self, variant: impl Into<String>) {
+        let variant = variant.into();
+        if !self.defaults.contains(&variant) {
+            self.defaults.push(variant);
+        }
+    }
+
+    /// Get the given variants that are neither states nor handled by
+    /// default.
+    pub fn unhandled_variants<'s>(&self, all_variants: &'s [String]) -> Vec<&'s str> {
+        let states = self.states();
+        all_variants
+            .iter()
+            .map(String::as_str)
+            .filter(|v| !states.contains(v) && !self.defaults.iter().any(|d| d == v))
+            .collect()
+    }
+
+    /// Get the initial state: the source of the first declared transition.
+    pub fn initial_state(&self) -> Option<&str> {
+        self.transitions.first().map(|t| t.from.as_str())
+    }
+
+    /// Get all states mentioned by transitions, in order of first appearance.
+    pub fn states(&self) -> Vec<&str> {
+        let mut states: Vec<&str> = Vec::new();
+        for transition in &self.transitions {
+            for state in [transition.from.as_str(), transition.to.as_str()] {
+                if !states.contains(&state) {
+                    states.push(state);
+                }
+            }
+        }
+        states
+    }
+
+    /// Get the states directly reachable from the given state.
+    pub fn successors(&self, state: &str) -> Vec<&str> {
+        self.transitions
+            .iter()
+            .filter(|t| t.from == state)
+            .map(|t| t.to.as_str())
+            .collect()
+    }
+
+    /// Check if a direct transition between two states is declared.
+    pub fn can_transition(&self, from: &str, to: &str) -> bool {
+        self.transitions.iter().any(|t| t.from == from && t.to == to)
+    }
+
+    /// Get all states reachable from the initial state (including itself).
+    pub fn reachable_states(&self) -> Vec<&str> {
+        let mut reachable: Vec<&str> = Vec::new();
+        let mut queue: VecDeque<&str> = VecDeque::new();
+
+        if let Some(initial) = self.initial_state() {
+            reachable.push(initial);
+            queue.push_back(initial);
+        }
+
+        while let Some(state) = queue.pop_front() {
+            for next in self.successors(state) {
+                if !reachable.contains(&next) {
+                    reachable.push(next);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        reachable
+    }
+
+    /// Get the given states that cannot be reached from the initial state.
+    ///
+    /// Pass the full list of enum variants to also catch states that no
+    /// transition mentions at all.
+    pub fn unreachable_states<'s>(&self, all_states: &'s [String]) -> Vec<&'s str> {
+        let reachable = self.reachable_states();
+        all_states
+            .iter()
+            .map(String::as_str)
+            .filter(|s| !reachable.contains(s))
+            .collect()
+    }
+
+    /// Get states with no outgoing transitions.
+    pub fn terminal_states(&self) -> Vec<&str> {
+        self.states()
+            .into_iter()
+            .filter(|s| self.successors(s).is_empty())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sketch::Graph;
+
+    fn order_lifecycle() -> Lifecycle {
+        let mut graph = Graph::new();
+        let status = graph.add_object("OrderStatus");
+        let mut lifecycle = Lifecycle::new("Order", status);
+        lifecycle.add_transition("Pending", "Confirmed");
+        lifecycle.add_transition("Confirmed", "Shipped");
+        lifecycle.add_transition("Pending", "Cancelled");
+        lifecycle
+    }
+
+    #[test]
+    fn test_lifecycle_states() {
+        let lifecycle = order_lifecycle();
+        assert_eq!(lifecycle.initial_state(), Some("Pending"));
+        assert_eq!(
+            lifecycle.states(),
+            vec!["Pending", "Confirmed", "Shipped", "Cancelled"]
+        );
+        assert_eq!(lifecycle.successors("Pending"), vec!["Confirmed", "Cancelled"]);
+        assert_eq!(lifecycle.terminal_states(), vec!["Shipped", "Cancelled"]);
+    }
+
+    #[test]
+    fn test_lifecycle_transitions() {
+        let mut lifecycle = order_lifecycle();
+        assert!(lifecycle.can_transition("Pending", "Confirmed"));
+        assert!(!lifecycle.can_transition("Shipped", "Pending"));
+
+        lifecycle.add_transition("Pending", "Confirmed");
+        assert_eq!(lifecycle.transitions.len(), 3);
+    }
+
+    #[test]
+    fn test_unreachable_states() {
+        let mut lifecycle = order_lifecycle();
+        lifecycle.add_transition("Refunded", "Closed");
+
+        let variants: Vec<String> = [
+            "Pending", "Confirmed", "Shipped", "Cancelled", "Refunded", "Closed", "Lost",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        assert_eq!(
+            lifecycle.unreachable_states(&variants),
+            vec!["Refunded", "Closed", "Lost"]
+        );
+    }
+
+    #[test]
+    fn test_unhandled_variants() {
+        let mut lifecycle = order_lifecycle();
+        lifecycle.add_default("Archived");
+
+        let variants: Vec<String> = ["Pending", "Shipped", "Cancelled", "Archived", "Lost"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        assert_eq!(lifecycle.unhandled_variants(&variants), vec!["Lost"]);
+    }
+
+    #[test]
+    fn test_empty_lifecycle() {
+        let lifecycle = Lifecycle::new("Empty", ObjectId(0));
+        assert_eq!(lifecycle.initial_state(), None);
+        assert!(lifecycle.reachable_states().is_empty());
+    }
+}
@@ -0,0 +1,1234 @@
+//! Lint rules: warnings about a model that a project can tune.
+//!
+//! Errors make a model invalid and always apply. Warnings flag designs that
+//! are legal but often unintended, and what counts as unintended differs
+//! between projects. Each warning is therefore produced by a [`Rule`],
+//! identified by its warning code. A [`Registry`] holds the rules to run and
+//! a [`LintConfig`] turns rules off or changes their severity, typically
+//! from the `[lint]` section of a project's `sketchddd.toml`:
+//!
+//! ```toml
+//! [lint]
+//! W0001 = "off"
+//! W0003 = "error"
+//! ```
+//!
+//! Besides the warnings of validation, the registry holds naming convention
+//! rules: PascalCase objects (W0030), camelCase morphisms (W0031), singular
+//! entity names (W0032) and aggregate names ending in a project's chosen
+//! suffix (W0033, see [`AggregateSuffix`]). Design heuristics flag anemic
+//! entities (W0040), objects with too many morphisms (W0041, see
+//! [`GodObject`]), oversized contexts (W0042, see [`LargeContext`]) and, as
+//! hints, parallel relationships (W0043, see [`ParallelMorphisms`]).
+//!
+//! Some rules are opt-in and only run when the configuration gives them a
+//! level, such as the integration gaps between contexts of W0090 (see
+//! [`ContextMapCoverage`]).
+
+use crate::context::BoundedContext;
+use crate::diagnostics::did_you_mean_correction;
+use crate::naming;
+use crate::sketch::{MorphismId, ObjectId};
+use crate::validation::{self, Severity, ValidationError, ValidationResult};
+use crate::workspace::Workspace;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A check producing warnings about a model.
+pub trait Rule {
+    /// The code of the issues the rule reports, such as `W0001`.
+    fn id(&self) -> &str;
+
+    /// A short description of what the rule looks for.
+    fn description(&self) -> &str;
+
+    /// Documentation of the rule beyond its description: why it matters and
+    /// how to address its issues, shown by `sketchddd lint --explain`.
+    fn explanation(&self) -> Option<&str> {
+        None
+    }
+
+    /// The severity of the rule's issues unless configured otherwise.
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    /// Whether the rule runs without being configured. Opt-in rules return
+    /// `false` and only run when the configuration gives them a level.
+    fn enabled_by_default(&self) -> bool {
+        true
+    }
+
+    /// Check a model, returning the issues found.
+    fn check(&self, workspace: &Workspace) -> Vec<ValidationError>;
+}
+
+/// A built-in rule, keeping the issues of one code from a validation pass.
+struct BuiltinRule {
+    id: &'static str,
+    description: &'static str,
+    explanation: &'static str,
+    check: fn(&Workspace) -> ValidationResult,
+}
+
+impl Rule for BuiltinRule {
+    fn id(&self) -> &str {
+        self.id
+    }
+
+    fn description(&self) -> &str {
+        self.description
+    }
+
+    fn explanation(&self) -> Option<&str> {
+        Some(self.explanation)
+    }
+
+    fn check(&self, workspace: &Workspace) -> Vec<ValidationError> {
+        (self.check)(workspace)
+            .issues
+            .into_iter()
+            .filter(|issue| issue.code == self.id)
+            .collect()
+    }
+}
+
+/// Run a context-level validation pass over every context.
+fn each_context(
+    workspace: &Workspace,
+    check: fn(&BoundedContext, &mut ValidationResult),
+) -> ValidationResult {
+    let mut result = ValidationResult::new();
+    for context in &workspace.contexts {
+        check(context, &mut result);
+    }
+    result
+}
+
+/// Run the model-level check for unreferenced elements.
+fn unreferenced(workspace: &Workspace) -> ValidationResult {
+    let mut result = ValidationResult::new();
+    validation::validate_unreferenced(&workspace.contexts, &workspace.context_maps, &mut result);
+    result
+}
+
+/// The rules that ship with SketchDDD, in code order.
+fn builtin_rules() -> Vec<BuiltinRule> {
+    vec![
+        BuiltinRule {
+            id: "W0001",
+            description: "Aggregate with more than five members",
+            explanation: include_str!("../rules/W0001.md"),
+            check: |w| {
+                let mut result = ValidationResult::new();
+                for context in &w.contexts {
+                    result
+                        .issues
+                        .extend(validation::validate_sketch(context.sketch()).issues);
+                }
+                result
+            },
+        },
+        BuiltinRule {
+            id: "W0002",
+            description: "Strict invariant or equation reaching outside its aggregate",
+            explanation: include_str!("../rules/W0002.md"),
+            check: |w| each_context(w, validation::validate_aggregate_invariants),
+        },
+        BuiltinRule {
+            id: "W0003",
+            description: "Morphism into an aggregate's internal entity from outside it",
+            explanation: include_str!("../rules/W0003.md"),
+            check: |w| each_context(w, validation::validate_boundary_crossings),
+        },
+        BuiltinRule {
+            id: "W0010",
+            description: "Value object without a limit cone",
+            explanation: include_str!("../rules/W0010.md"),
+            check: |w| each_context(w, validation::validate_value_objects),
+        },
+        BuiltinRule {
+            id: "W0020",
+            description: "Process without steps",
+            explanation: include_str!("../rules/W0020.md"),
+            check: |w| each_context(w, validation::validate_processes),
+        },
+        BuiltinRule {
+            id: "W0021",
+            description: "Policy issuing no commands",
+            explanation: include_str!("../rules/W0021.md"),
+            check: |w| each_context(w, validation::validate_event_storming),
+        },
+        BuiltinRule {
+            id: "W0022",
+            description: "Policy reacting to a command instead of an event",
+            explanation: include_str!("../rules/W0022.md"),
+            check: |w| each_context(w, validation::validate_event_storming),
+        },
+        BuiltinRule {
+            id: "W0030",
+            description: "Object name not in PascalCase",
+            explanation: include_str!("../rules/W0030.md"),
+            check: |w| each_context(w, check_object_names),
+        },
+        BuiltinRule {
+            id: "W0031",
+            description: "Morphism name not in camelCase",
+            explanation: include_str!("../rules/W0031.md"),
+            check: |w| each_context(w, check_morphism_names),
+        },
+        BuiltinRule {
+            id: "W0032",
+            description: "Entity with a plural name",
+            explanation: include_str!("../rules/W0032.md"),
+            check: |w| each_context(w, check_entity_names),
+        },
+        BuiltinRule {
+            id: "W0040",
+            description: "Entity with nothing but primitive fields",
+            explanation: include_str!("../rules/W0040.md"),
+            check: |w| each_context(w, check_anemic_entities),
+        },
+        BuiltinRule {
+            id: "W0050",
+            description: "Lifecycle state unreachable from the initial state",
+            explanation: include_str!("../rules/W0050.md"),
+            check: |w| each_context(w, validation::validate_lifecycles),
+        },
+        BuiltinRule {
+            id: "W0051",
+            description: "Lifecycle without transitions",
+            explanation: include_str!("../rules/W0051.md"),
+            check: |w| each_context(w, validation::validate_lifecycles),
+        },
+        BuiltinRule {
+            id: "W0052",
+            description: "Enum variant not handled by its lifecycle",
+            explanation: include_str!("../rules/W0052.md"),
+            check: |w| each_context(w, validation::validate_lifecycles),
+        },
+        BuiltinRule {
+            id: "W0060",
+            description: "Element still present after its removal version",
+            explanation: include_str!("../rules/W0060.md"),
+            check: |w| each_context(w, validation::validate_removals),
+        },
+        BuiltinRule {
+            id: "W0070",
+            description: "Core concept in a context that is not core",
+            explanation: include_str!("../rules/W0070.md"),
+            check: |w| each_context(w, validation::validate_classifications),
+        },
+        BuiltinRule {
+            id: "W0071",
+            description: "Context classification disagreeing with its subdomain",
+            explanation: include_str!("../rules/W0071.md"),
+            check: |w| validation::validate_domains(&w.domains, &w.contexts),
+        },
+        BuiltinRule {
+            id: "W0080",
+            description: "Object nothing refers to",
+            explanation: include_str!("../rules/W0080.md"),
+            check: unreferenced,
+        },
+        BuiltinRule {
+            id: "W0081",
+            description: "Morphism not used in any equation or aggregate",
+            explanation: include_str!("../rules/W0081.md"),
+            check: unreferenced,
+        },
+        BuiltinRule {
+            id: "W0082",
+            description: "Context outside every context map",
+            explanation: include_str!("../rules/W0082.md"),
+            check: unreferenced,
+        },
+    ]
+}
+
+/// Get the morphisms SketchDDD names itself: identities, aggregate
+/// projections named `{aggregate}_{member}` and invariant inclusions.
+pub(crate) fn generated_morphisms(context: &BoundedContext) -> Vec<MorphismId> {
+    let graph = context.graph();
+    let mut generated: Vec<MorphismId> = graph
+        .morphisms()
+        .filter(|m| m.is_identity)
+        .map(|m| m.id)
+        .collect();
+    for limit in context.sketch().limits.iter().filter(|l| l.is_aggregate) {
+        for projection in &limit.projections {
+            let (Some(morphism), Some(member)) = (
+                graph.get_morphism(projection.morphism),
+                graph.get_object(projection.target),
+            ) else {
+                continue;
+            };
+            if morphism.name == format!("{}_{}", limit.name, member.name) {
+                generated.push(morphism.id);
+            }
+        }
+    }
+    generated.extend(context.invariants().iter().map(|i| i.inclusion));
+    generated
+}
+
+fn check_object_names(context: &BoundedContext, result: &mut ValidationResult) {
+    let equalizers: Vec<_> = context.invariants().iter().map(|i| i.equalizer).collect();
+    for object in context.graph().objects() {
+        if equalizers.contains(&object.id) || naming::is_pascal_case(&object.name) {
+            continue;
+        }
+        result.add(
+            ValidationError::warning(
+                "W0030",
+                format!("Object '{}' is not named in PascalCase", object.name),
+            )
+            .with_suggestion(did_you_mean_correction(&naming::to_pascal_case(
+                &object.name,
+            ))),
+        );
+    }
+}
+
+fn check_morphism_names(context: &BoundedContext, result: &mut ValidationResult) {
+    let generated = generated_morphisms(context);
+    for morphism in context.graph().morphisms() {
+        if generated.contains(&morphism.id) || naming::is_camel_case(&morphism.name) {
+            continue;
+        }
+        result.add(
+            ValidationError::warning(
+                "W0031",
+                format!("Morphism '{}' is not named in camelCase", morphism.name),
+            )
+            .with_suggestion(did_you_mean_correction(&naming::to_camel_case(
+                &morphism.name,
+            ))),
+        );
+    }
+}
+
+fn check_entity_names(context: &BoundedContext, result: &mut ValidationResult) {
+    for &entity in context.entities() {
+        let Some(object) = context.graph().get_object(entity) else {
+            continue;
+        };
+        if let Some(singular) = naming::singularize(&object.name) {
+            result.add(
+                ValidationError::warning(
+                    "W0032",
+                    format!("Entity '{}' has a plural name", object.name),
+                )
+                .with_suggestion(did_you_mean_correction(&singular)),
+            );
+        }
+    }
+}
+
+/// Requires aggregate names to end in a suffix, such as `Aggregate`.
+///
+/// Projects differ on whether aggregates carry a suffix at all, so the
+/// built-in rule has none and reports nothing. Register the rule with the
+/// project's suffix to enable it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AggregateSuffix {
+    suffix: String,
+}
+
+impl AggregateSuffix {
+    /// Create the rule requiring `suffix`.
+    pub fn new(suffix: impl Into<String>) -> Self {
+        Self {
+            suffix: suffix.into(),
+        }
+    }
+
+    /// Get the required suffix.
+    pub fn suffix(&self) -> &str {
+        &self.suffix
+    }
+}
+
+impl Rule for AggregateSuffix {
+    fn id(&self) -> &str {
+        "W0033"
+    }
+
+    fn description(&self) -> &str {
+        "Aggregate name without the configured suffix"
+    }
+
+    fn explanation(&self) -> Option<&str> {
+        Some(include_str!("../rules/W0033.md"))
+    }
+
+    fn check(&self, workspace: &Workspace) -> Vec<ValidationError> {
+        if self.suffix.is_empty() {
+            return Vec::new();
+        }
+        workspace
+            .contexts
+            .iter()
+            .flat_map(|c| c.sketch().limits.iter().filter(|l| l.is_aggregate))
+            .filter(|aggregate| !aggregate.name.ends_with(&self.suffix))
+            .map(|aggregate| {
+                ValidationError::warning(
+                    "W0033",
+                    format!(
+                        "Aggregate '{}' does not end in '{}'",
+                        aggregate.name, self.suffix
+                    ),
+                )
+                .with_suggestion(did_you_mean_correction(&format!(
+                    "{}{}",
+                    aggregate.name, self.suffix
+                )))
+            })
+            .collect()
+    }
+}
+
+/// Check if an object is a primitive: a plain object, not an entity, value
+/// object, enum or aggregate, with no morphisms of its own.
+fn is_primitive(context: &BoundedContext, object: ObjectId) -> bool {
+    !context.is_entity(object)
+        && !context.is_value_object(object)
+        && !context.is_aggregate_root(object)
+        && context.get_enum_colimit(object).is_none()
+        && !context
+            .graph()
+            .morphisms()
+            .any(|m| !m.is_identity && m.source == object)
+}
+
+fn check_anemic_entities(context: &BoundedContext, result: &mut ValidationResult) {
+    let graph = context.graph();
+    for &entity in context.entities() {
+        let Some(object) = graph.get_object(entity) else {
+            continue;
+        };
+        let outgoing: Vec<_> = graph
+            .morphisms()
+            .filter(|m| !m.is_identity && m.source == entity)
+            .collect();
+        let has_behaviour = !context.invariants_of(entity).is_empty()
+            || context.get_lifecycle(entity).is_some()
+            || outgoing.iter().any(|m| context.is_derived(m.id));
+        // Entities without fields are unfinished rather than anemic
+        if outgoing.is_empty()
+            || has_behaviour
+            || outgoing.iter().any(|m| !is_primitive(context, m.target))
+        {
+            continue;
+        }
+        result.add(
+            ValidationError::warning(
+                "W0040",
+                format!(
+                    "Entity '{}' has nothing but primitive fields, which may make it anemic",
+                    object.name
+                ),
+            )
+            .with_suggestion(format!(
+                "Give '{}' value objects, relationships, invariants or a lifecycle",
+                object.name
+            )),
+        );
+    }
+}
+
+/// Flags objects with more morphisms than a threshold.
+///
+/// An object most of the model leads to or from tends to gather every
+/// concern of the context. Morphisms are counted in both directions;
+/// identities and aggregate projections are not counted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GodObject {
+    max_morphisms: usize,
+}
+
+impl GodObject {
+    /// The threshold of the built-in rule.
+    pub const DEFAULT_MAX_MORPHISMS: usize = 12;
+
+    /// Create the rule flagging objects with more than `max_morphisms`.
+    pub fn new(max_morphisms: usize) -> Self {
+        Self { max_morphisms }
+    }
+
+    /// Get the most morphisms an object may have.
+    pub fn max_morphisms(&self) -> usize {
+        self.max_morphisms
+    }
+}
+
+impl Default for GodObject {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_MAX_MORPHISMS)
+    }
+}
+
+impl Rule for GodObject {
+    fn id(&self) -> &str {
+        "W0041"
+    }
+
+    fn description(&self) -> &str {
+        "Object with too many morphisms"
+    }
+
+    fn explanation(&self) -> Option<&str> {
+        Some(include_str!("../rules/W0041.md"))
+    }
+
+    fn check(&self, workspace: &Workspace) -> Vec<ValidationError> {
+        let mut issues = Vec::new();
+        for context in &workspace.contexts {
+            let graph = context.graph();
+            let generated = generated_morphisms(context);
+            let mut counts: BTreeMap<ObjectId, usize> = BTreeMap::new();
+            for morphism in graph.morphisms().filter(|m| !generated.contains(&m.id)) {
+                *counts.entry(morphism.source).or_default() += 1;
+                if morphism.target != morphism.source {
+                    *counts.entry(morphism.target).or_default() += 1;
+                }
+            }
+            for (object, count) in counts {
+                if count <= self.max_morphisms {
+                    continue;
+                }
+                let Some(object) = graph.get_object(object) else {
+                    continue;
+                };
+                issues.push(
+                    ValidationError::warning(
+                        "W0041",
+                        format!(
+                            "Object '{}' has {} morphisms, more than {}",
+                            object.name, count, self.max_morphisms
+                        ),
+                    )
+                    .with_suggestion(format!(
+                        "Consider splitting '{}' into smaller concepts",
+                        object.name
+                    )),
+                );
+            }
+        }
+        issues
+    }
+}
+
+/// Flags contexts with more objects than a threshold.
+///
+/// A context too large for one team to hold in mind is often several
+/// contexts sharing a model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LargeContext {
+    max_objects: usize,
+}
+
+impl LargeContext {
+    /// The threshold of the built-in rule.
+    pub const DEFAULT_MAX_OBJECTS: usize = 40;
+
+    /// Create the rule flagging contexts with more than `max_objects`.
+    pub fn new(max_objects: usize) -> Self {
+        Self { max_objects }
+    }
+
+    /// Get the most objects a context may have.
+    pub fn max_objects(&self) -> usize {
+        self.max_objects
+    }
+}
+
+impl Default for LargeContext {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_MAX_OBJECTS)
+    }
+}
+
+impl Rule for LargeContext {
+    fn id(&self) -> &str {
+        "W0042"
+    }
+
+    fn description(&self) -> &str {
+        "Context with too many objects"
+    }
+
+    fn explanation(&self) -> Option<&str> {
+        Some(include_str!("../rules/W0042.md"))
+    }
+
+    fn check(&self, workspace: &Workspace) -> Vec<ValidationError> {
+        workspace
+            .contexts
+            .iter()
+            .filter(|c| c.graph().objects().count() > self.max_objects)
+            .map(|context| {
+                ValidationError::warning(
+                    "W0042",
+                    format!(
+                        "Context '{}' has {} objects, more than {}",
+                        context.name(),
+                        context.graph().objects().count(),
+                        self.max_objects
+                    ),
+                )
+                .with_suggestion(format!(
+                    "Consider splitting '{}' into smaller bounded contexts",
+                    context.name()
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Hints at relationships declared twice under different names.
+///
+/// Two morphisms leading from one entity to another, such as `buyer` and
+/// `seller` from `Order` to `Customer`, are fine when the model says how
+/// they differ. When no equation mentions either, they are often the same
+/// relationship named twice. Only morphisms into entities are considered;
+/// parallel attributes such as `subtotal` and `total` are common.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParallelMorphisms;
+
+impl Rule for ParallelMorphisms {
+    fn id(&self) -> &str {
+        "W0043"
+    }
+
+    fn description(&self) -> &str {
+        "Parallel relationships without an equation telling them apart"
+    }
+
+    fn explanation(&self) -> Option<&str> {
+        Some(include_str!("../rules/W0043.md"))
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Hint
+    }
+
+    fn check(&self, workspace: &Workspace) -> Vec<ValidationError> {
+        let mut issues = Vec::new();
+        for context in &workspace.contexts {
+            let graph = context.graph();
+            let generated = generated_morphisms(context);
+            let in_equation: Vec<MorphismId> = context
+                .sketch()
+                .equations
+                .iter()
+                .flat_map(|e| e.lhs.morphisms.iter().chain(&e.rhs.morphisms))
+                .copied()
+                .collect();
+
+            let mut parallel: BTreeMap<(ObjectId, ObjectId), Vec<&str>> = BTreeMap::new();
+            let morphisms = graph.morphisms().filter(|m| {
+                !generated.contains(&m.id)
+                    && !context.is_derived(m.id)
+                    && !in_equation.contains(&m.id)
+                    && context.is_entity(m.target)
+            });
+            for morphism in morphisms {
+                parallel
+                    .entry((morphism.source, morphism.target))
+                    .or_default()
+                    .push(&morphism.name);
+            }
+
+            for ((source, target), mut names) in parallel {
+                // One name declared twice is a duplicate, reported as E0021
+                names.sort_unstable();
+                names.dedup();
+                if names.len() < 2 {
+                    continue;
+                }
+                let (Some(source), Some(target)) =
+                    (graph.get_object(source), graph.get_object(target))
+                else {
+                    continue;
+                };
+                let names: Vec<String> = names.iter().map(|n| format!("'{}'", n)).collect();
+                issues.push(
+                    ValidationError::warning(
+                        "W0043",
+                        format!(
+                            "Morphisms {} lead from '{}' to '{}' with no equation telling them apart",
+                            names.join(", "),
+                            source.name,
+                            target.name
+                        ),
+                    )
+                    .with_suggestion(
+                        "Keep one if they are the same relationship, or state how they relate with an equation",
+                    ),
+                );
+            }
+        }
+        issues
+    }
+}
+
+/// Flags concepts two contexts share that no context map accounts for.
+///
+/// Contexts declaring a public object of the same name integrate on it, so
+/// a context map between them should map it, see
+/// [`Workspace::context_map_coverage`]. Models often leave integration
+/// implicit while they are young, so the rule is opt-in:
+///
+/// ```toml
+/// [lint]
+/// W0090 = "warning"
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ContextMapCoverage;
+
+impl Rule for ContextMapCoverage {
+    fn id(&self) -> &str {
+        "W0090"
+    }
+
+    fn description(&self) -> &str {
+        "Concept shared by two contexts that no context map covers"
+    }
+
+    fn explanation(&self) -> Option<&str> {
+        Some(include_str!("../rules/W0090.md"))
+    }
+
+    fn enabled_by_default(&self) -> bool {
+        false
+    }
+
+    fn check(&self, workspace: &Workspace) -> Vec<ValidationError> {
+        let quoted = |names: &[String]| {
+            names
+                .iter()
+                .map(|n| format!("'{}'", n))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let mut issues = Vec::new();
+        for pair in workspace.context_map_coverage().pairs {
+            let Some(map) = pair.maps.first() else {
+                issues.push(
+                    ValidationError::warning(
+                        "W0090",
+                        format!(
+                            "Contexts '{}' and '{}' share {} but no context map connects them",
+                            pair.first,
+                            pair.second,
+                            quoted(&pair.concepts)
+                        ),
+                    )
+                    .with_suggestion(format!(
+                        "Declare a context map between '{}' and '{}', with the SeparateWays pattern if they do not integrate",
+                        pair.first, pair.second
+                    )),
+                );
+                continue;
+            };
+            for concept in &pair.unmapped {
+                issues.push(
+                    ValidationError::warning(
+                        "W0090",
+                        format!(
+                            "Context map '{}' does not map '{}', which '{}' and '{}' share",
+                            map, concept, pair.first, pair.second
+                        ),
+                    )
+                    .with_suggestion(format!(
+                        "Map '{}' in '{}', or rename it in one of the contexts if they mean different things",
+                        concept, map
+                    )),
+                );
+            }
+        }
+        issues
+    }
+}
+
+/// The rules to run when linting.
+pub struct Registry {
+    rules: Vec<Box<dyn Rule>>,
+}
+
+impl Registry {
+    /// Create a registry without any rules.
+    pub fn new() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// Create a registry holding the built-in rules.
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        for rule in builtin_rules() {
+            registry.register(rule);
+        }
+        registry.register(AggregateSuffix::default());
+        registry.register(GodObject::default());
+        registry.register(LargeContext::default());
+        registry.register(ParallelMorphisms);
+        registry.register(ContextMapCoverage);
+        registry
+    }
+
+    /// Add a rule, replacing any rule with the same id.
+    pub fn register(&mut self, rule: impl Rule + 'static) {
+        self.rules.retain(|r| r.id() != rule.id());
+        let index = self.rules.partition_point(|r| r.id() < rule.id());
+        self.rules.insert(index, Box::new(rule));
+    }
+
+    /// Get a rule by id.
+    pub fn get(&self, id: &str) -> Option<&dyn Rule> {
+        self.rules.iter().find(|r| r.id() == id).map(|r| r.as_ref())
+    }
+
+    /// Iterate over the rules, in order of their ids.
+    pub fn rules(&self) -> impl Iterator<Item = &dyn Rule> {
+        self.rules.iter().map(|r| r.as_ref())
+    }
+
+    /// Run every enabled rule, with the severity the configuration gives it.
+    pub fn check(&self, workspace: &Workspace, config: &LintConfig) -> Vec<ValidationError> {
+        let mut issues = Vec::new();
+        for rule in self.rules() {
+            let Some(severity) = config.severity(rule) else {
+                continue;
+            };
+            issues.extend(rule.check(workspace).into_iter().map(|mut issue| {
+                issue.severity = severity;
+                issue
+            }));
+        }
+        issues
+    }
+
+    /// Get the ids a configuration mentions that no rule has.
+    pub fn unknown_rules<'a>(&self, config: &'a LintConfig) -> Vec<&'a str> {
+        config
+            .levels
+            .keys()
+            .map(String::as_str)
+            .filter(|id| self.get(id).is_none())
+            .collect()
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl fmt::Debug for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.rules.iter().map(|r| r.id()))
+            .finish()
+    }
+}
+
+/// How a configured rule should run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleLevel {
+    /// Do not run the rule
+    Off,
+    /// Run the rule, reporting its issues with this severity
+    On(Severity),
+}
+
+impl std::str::FromStr for RuleLevel {
+    type Err = LintConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" | "allow" => Ok(RuleLevel::Off),
+            "hint" => Ok(RuleLevel::On(Severity::Hint)),
+            "warn" | "warning" => Ok(RuleLevel::On(Severity::Warning)),
+            "error" | "deny" => Ok(RuleLevel::On(Severity::Error)),
+            _ => Err(LintConfigError::InvalidLevel(s.to_string())),
+        }
+    }
+}
+
+/// Error in a lint configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LintConfigError {
+    /// A level other than `off`, `hint`, `warning` or `error`
+    InvalidLevel(String),
+}
+
+impl fmt::Display for LintConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintConfigError::InvalidLevel(level) => write!(
+                f,
+                "invalid lint level '{}', expected \"off\", \"hint\", \"warning\" or \"error\"",
+                level
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LintConfigError {}
+
+/// Per-project overrides of the rules' defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LintConfig {
+    levels: BTreeMap<String, RuleLevel>,
+}
+
+impl LintConfig {
+    /// Create a configuration running every rule with its default severity.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how a rule runs.
+    pub fn set(&mut self, id: impl Into<String>, level: RuleLevel) {
+        self.levels.insert(id.into(), level);
+    }
+
+    /// Turn a rule off.
+    pub fn disable(&mut self, id: impl Into<String>) {
+        self.set(id, RuleLevel::Off);
+    }
+
+    /// Get the configured level of a rule, if overridden.
+    pub fn level(&self, id: &str) -> Option<RuleLevel> {
+        self.levels.get(id).copied()
+    }
+
+    /// Get the severity a rule runs with, or `None` if it is off.
+    ///
+    /// Rules that are not enabled by default are off unless configured.
+    pub fn severity(&self, rule: &dyn Rule) -> Option<Severity> {
+        match self.level(rule.id()) {
+            Some(RuleLevel::Off) => None,
+            Some(RuleLevel::On(severity)) => Some(severity),
+            None => rule.enabled_by_default().then(|| rule.default_severity()),
+        }
+    }
+}
+
+impl Workspace {
+    /// Validate the model, running the lint rules of `registry` as
+    /// configured instead of reporting their warnings unconditionally.
+    ///
+    /// Issues whose code belongs to a registered rule are left to the rule;
+    /// every other error and warning of [`Workspace::validate`] is kept.
+    /// Issues of a rule that a context suppresses, with `@allow`, are
+    /// dropped.
+    pub fn lint(&self, registry: &Registry, config: &LintConfig) -> ValidationResult {
+        self.lint_validated(self.validate(), registry, config)
+    }
+
+    /// Run the lint rules on the result of validating the model.
+    pub(crate) fn lint_validated(
+        &self,
+        mut result: ValidationResult,
+        registry: &Registry,
+        config: &LintConfig,
+    ) -> ValidationResult {
+        result
+            .issues
+            .retain(|issue| registry.get(&issue.code).is_none());
+        result
+            .issues
+            .extend(registry.check(self, config).into_iter().filter(|issue| {
+                !self.contexts.iter().any(|context| {
+                    context
+                        .suppressions()
+                        .iter()
+                        .any(|s| s.covers(context, issue))
+                })
+            }));
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A workspace with one oversized aggregate and one empty process.
+    fn model() -> Workspace {
+        let mut sales = BoundedContext::new("Sales");
+        let order = sales.add_entity("Order");
+        let members: Vec<_> = (0..6)
+            .map(|i| sales.add_entity(format!("Part{}", i)))
+            .collect();
+        sales.define_aggregate_with_members("Order", order, &members);
+        sales.define_process("Fulfillment", &[]);
+
+        let mut workspace = Workspace::new();
+        workspace.add_context(sales);
+        workspace
+    }
+
+    fn codes(result: &ValidationResult) -> Vec<(&str, Severity)> {
+        result
+            .issues
+            .iter()
+            .map(|i| (i.code.as_str(), i.severity))
+            .collect()
+    }
+
+    #[test]
+    fn test_lint_defaults_match_validate() {
+        let workspace = model();
+        let linted = workspace.lint(&Registry::builtin(), &LintConfig::new());
+        let validated = workspace.validate();
+        let mut expected = codes(&validated);
+        let mut actual = codes(&linted);
+        expected.sort_by_key(|(code, _)| *code);
+        actual.sort_by_key(|(code, _)| *code);
+        assert_eq!(actual, expected);
+        assert!(actual.contains(&("W0001", Severity::Warning)));
+        assert!(actual.contains(&("W0020", Severity::Warning)));
+    }
+
+    #[test]
+    fn test_lint_config_overrides() {
+        let mut config = LintConfig::new();
+        config.disable("W0001");
+        config.set("W0020", "error".parse().unwrap());
+
+        let result = model().lint(&Registry::builtin(), &config);
+        assert_eq!(codes(&result), vec![("W0020", Severity::Error)]);
+        assert!(!result.is_ok());
+
+        assert_eq!(
+            "loud".parse::<RuleLevel>(),
+            Err(LintConfigError::InvalidLevel("loud".into()))
+        );
+    }
+
+    #[test]
+    fn test_naming_rules() {
+        let mut sales = BoundedContext::new("Sales");
+        let orders = sales.add_entity("Orders");
+        let line_item = sales.add_value_object("line_item");
+        sales
+            .sketch_mut()
+            .add_morphism("LineItems", orders, line_item);
+        sales.define_aggregate_with_members("Order", orders, &[line_item]);
+        let mut workspace = Workspace::new();
+        workspace.add_context(sales);
+
+        let mut registry = Registry::builtin();
+        let issues = registry.check(&workspace, &LintConfig::new());
+        let naming: Vec<_> = issues
+            .iter()
+            .filter(|i| i.code.starts_with("W003"))
+            .map(|i| (i.code.as_str(), i.suggestion.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            naming,
+            vec![
+                ("W0030", "did you mean `LineItem`?"),
+                ("W0031", "did you mean `lineItems`?"),
+                ("W0032", "did you mean `Order`?"),
+            ]
+        );
+
+        registry.register(AggregateSuffix::new("Aggregate"));
+        let issues = registry.check(&workspace, &LintConfig::new());
+        let suffix: Vec<_> = issues.iter().filter(|i| i.code == "W0033").collect();
+        assert_eq!(suffix.len(), 1);
+        assert_eq!(
+            suffix[0].message,
+            "Aggregate 'Order' does not end in 'Aggregate'"
+        );
+        assert_eq!(
+            suffix[0].suggestion.as_deref(),
+            Some("did you mean `OrderAggregate`?")
+        );
+    }
+
+    #[test]
+    fn test_design_heuristics() {
+        let mut sales = BoundedContext::new("Sales");
+        let customer = sales.add_entity("Customer");
+        let order = sales.add_entity("Order");
+        let text = sales.sketch_mut().add_object("String");
+        sales.sketch_mut().add_morphism("name", customer, text);
+        sales.sketch_mut().add_morphism("placedBy", order, customer);
+        for i in 0..3 {
+            sales
+                .sketch_mut()
+                .add_morphism(format!("note{}", i), order, text);
+        }
+        let mut workspace = Workspace::new();
+        workspace.add_context(sales);
+
+        // Customer only has a primitive field; Order refers to Customer
+        let issues = Registry::builtin().check(&workspace, &LintConfig::new());
+        let anemic: Vec<_> = issues
+            .iter()
+            .filter(|i| i.code == "W0040")
+            .map(|i| i.message.as_str())
+            .collect();
+        assert_eq!(
+            anemic,
+            vec!["Entity 'Customer' has nothing but primitive fields, which may make it anemic"]
+        );
+        assert!(!issues
+            .iter()
+            .any(|i| i.code == "W0041" || i.code == "W0042"));
+
+        let mut registry = Registry::builtin();
+        registry.register(GodObject::new(3));
+        registry.register(LargeContext::new(2));
+        let issues = registry.check(&workspace, &LintConfig::new());
+        let messages: Vec<_> = issues
+            .iter()
+            .filter(|i| i.code == "W0041" || i.code == "W0042")
+            .map(|i| i.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Object 'Order' has 4 morphisms, more than 3",
+                "Object 'String' has 4 morphisms, more than 3",
+                "Context 'Sales' has 3 objects, more than 2",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parallel_morphisms_hint() {
+        let mut sales = BoundedContext::new("Sales");
+        let order = sales.add_entity("Order");
+        let customer = sales.add_entity("Customer");
+        let money = sales.add_value_object("Money");
+        for name in ["buyer", "seller", "buyer"] {
+            sales.sketch_mut().add_morphism(name, order, customer);
+        }
+        sales.sketch_mut().add_morphism("subtotal", order, money);
+        sales.sketch_mut().add_morphism("total", order, money);
+        let mut workspace = Workspace::new();
+        workspace.add_context(sales);
+
+        let result = workspace.lint(&Registry::builtin(), &LintConfig::new());
+        let hints: Vec<_> = result.issues.iter().filter(|i| i.code == "W0043").collect();
+        assert_eq!(hints.len(), 1);
+        assert_eq!(hints[0].severity, Severity::Hint);
+        assert_eq!(
+            hints[0].message,
+            "Morphisms 'buyer', 'seller' lead from 'Order' to 'Customer' with no equation telling them apart"
+        );
+        assert!(result.errors().any(|e| e.code == "E0021"));
+    }
+
+    #[test]
+    fn test_context_map_coverage_is_opt_in() {
+        let mut workspace = Workspace::new();
+        for name in ["Sales", "Billing"] {
+            let mut context = BoundedContext::new(name);
+            context.add_entity("Customer");
+            workspace.add_context(context);
+        }
+        let registry = Registry::builtin();
+        let gaps = |workspace: &Workspace, config: &LintConfig| -> Vec<String> {
+            registry
+                .check(workspace, config)
+                .into_iter()
+                .filter(|i| i.code == "W0090")
+                .map(|i| i.message)
+                .collect()
+        };
+        assert!(gaps(&workspace, &LintConfig::new()).is_empty());
+
+        let mut config = LintConfig::new();
+        config.set("W0090", RuleLevel::On(Severity::Warning));
+        assert_eq!(
+            gaps(&workspace, &config),
+            vec![
+                "Contexts 'Sales' and 'Billing' share 'Customer' but no context map connects them"
+            ]
+        );
+
+        workspace.add_context_map(crate::mapping::NamedContextMap::new(
+            "SalesToBilling",
+            "Sales",
+            "Billing",
+            crate::mapping::RelationshipPattern::CustomerSupplier,
+        ));
+        assert_eq!(
+            gaps(&workspace, &config),
+            vec!["Context map 'SalesToBilling' does not map 'Customer', which 'Sales' and 'Billing' share"]
+        );
+    }
+
+    #[test]
+    fn test_builtin_rules_are_explained() {
+        for rule in Registry::builtin().rules() {
+            let explanation = rule.explanation().unwrap_or_default();
+            assert!(!explanation.trim().is_empty(), "{}", rule.id());
+        }
+    }
+
+    #[test]
+    fn test_rules_are_listed_by_id() {
+        let registry = Registry::builtin();
+        let ids: Vec<&str> = registry.rules().map(|r| r.id()).collect();
+        let mut sorted = ids.clone();
+        sorted.sort();
+        assert_eq!(ids, sorted);
+    }
+
+    #[test]
+    fn test_custom_rule() {
+        struct NoProcesses;
+
+        impl Rule for NoProcesses {
+            fn id(&self) -> &str {
+                "X0001"
+            }
+
+            fn description(&self) -> &str {
+                "Processes are not used in this project"
+            }
+
+            fn default_severity(&self) -> Severity {
+                Severity::Error
+            }
+
+            fn check(&self, workspace: &Workspace) -> Vec<ValidationError> {
+                workspace
+                    .contexts
+                    .iter()
+                    .flat_map(|c| c.processes())
+                    .map(|p| {
+                        ValidationError::warning(
+                            "X0001",
+                            format!("Process '{}' is not allowed", p.name),
+                        )
+                    })
+                    .collect()
+            }
+        }
+
+        let mut registry = Registry::builtin();
+        registry.register(NoProcesses);
+        let mut config = LintConfig::new();
+        config.disable("W9999");
+
+        let issues = registry.check(&model(), &config);
+        let custom: Vec<_> = issues.iter().filter(|i| i.code == "X0001").collect();
+        assert_eq!(custom.len(), 1);
+        assert_eq!(custom[0].severity, Severity::Error);
+        assert_eq!(registry.unknown_rules(&config), vec!["W9999"]);
+        assert_eq!(
+            registry.get("W0003").unwrap().description(),
+            "Morphism into an aggregate's internal entity from outside it"
+        );
+    }
+}
@@ -40,6 +40,42 @@ pub enum RelationshipPattern {
     SharedKernel,
 }
 
+impl RelationshipPattern {
+    /// Get the abbreviation used on context map diagrams, such as `ACL`.
+    pub fn abbreviation(&self) -> &'static str {
+        match self {
+            RelationshipPattern::Partnership => "P",
+            RelationshipPattern::CustomerSupplier => "CS",
+            RelationshipPattern::Conformist => "CF",
+            RelationshipPattern::AntiCorruptionLayer => "ACL",
+            RelationshipPattern::SeparateWays => "SW",
+            RelationshipPattern::PublishedLanguage => "PL",
+            RelationshipPattern::OpenHostService => "OHS",
+            RelationshipPattern::SharedKernel => "SK",
+        }
+    }
+
+    /// Check if the source context of a map with this pattern is upstream
+    /// (provider) of its target.
+    pub fn source_is_upstream(&self) -> bool {
+        matches!(
+            self,
+            RelationshipPattern::CustomerSupplier
+                | RelationshipPattern::Conformist
+                | RelationshipPattern::AntiCorruptionLayer
+                | RelationshipPattern::OpenHostService
+        )
+    }
+
+    /// Check if this is a symmetric relationship.
+    pub fn is_symmetric(&self) -> bool {
+        matches!(
+            self,
+            RelationshipPattern::Partnership | RelationshipPattern::SharedKernel
+        )
+    }
+}
+
 /// A mapping of a single object from source to target context.
 ///
 /// In categorical terms, this represents the object part of a functor:
@@ -182,21 +218,12 @@ impl ContextMap {
 
     /// Check if the source context is upstream (provider).
     pub fn source_is_upstream(&self) -> bool {
-        matches!(
-            self.pattern,
-            RelationshipPattern::CustomerSupplier
-                | RelationshipPattern::Conformist
-                | RelationshipPattern::AntiCorruptionLayer
-                | RelationshipPattern::OpenHostService
-        )
+        self.pattern.source_is_upstream()
     }
 
     /// Check if this is a symmetric relationship.
     pub fn is_symmetric(&self) -> bool {
-        matches!(
-            self.pattern,
-            RelationshipPattern::Partnership | RelationshipPattern::SharedKernel
-        )
+        self.pattern.is_symmetric()
     }
 
     /// Add a mapping between morphisms.
@@ -238,6 +265,18 @@ impl ContextMap {
             .map(|m| m.target)
     }
 
+    /// Check if this relationship must map the whole source context.
+    ///
+    /// A Conformist downstream adopts the upstream model as is, and a
+    /// Shared Kernel is the same model in both contexts, so neither may
+    /// leave an object or morphism behind: the map must be a total functor.
+    pub fn requires_total_functor(&self) -> bool {
+        matches!(
+            self.pattern,
+            RelationshipPattern::Conformist | RelationshipPattern::SharedKernel
+        )
+    }
+
     /// Check if this relationship requires translation (ACL).
     pub fn requires_translation(&self) -> bool {
         matches!(self.pattern, RelationshipPattern::AntiCorruptionLayer)
@@ -346,6 +385,51 @@ impl NamedContextMap {
         self.morphism_mappings.push(mapping);
     }
 
+    /// Check if this relationship must map the whole source context.
+    ///
+    /// See [`ContextMap::requires_total_functor`].
+    pub fn requires_total_functor(&self) -> bool {
+        matches!(
+            self.pattern,
+            RelationshipPattern::Conformist | RelationshipPattern::SharedKernel
+        )
+    }
+
+    /// Resolve the names of the mappings against the graphs of the source
+    /// and target contexts.
+    ///
+    /// Mappings whose names do not resolve are dropped; validation reports
+    /// them separately.
+    pub fn resolve(&self, source: &Graph, target: &Graph) -> ContextMap {
+        let mut map = ContextMap::new(
+            &self.name,
+            &self.source_context,
+            &self.target_context,
+            self.pattern,
+        );
+
+        for mapping in &self.object_mappings {
+            let objects = (
+                source.find_object_by_name(&mapping.source),
+                target.find_object_by_name(&mapping.target),
+            );
+            if let (Some(s), Some(t)) = objects {
+                map.map_object(s.id, t.id);
+            }
+        }
+        for mapping in &self.morphism_mappings {
+            let morphisms = (
+                source.find_morphism_by_name(&mapping.source),
+                target.find_morphism_by_name(&mapping.target),
+            );
+            if let (Some(s), Some(t)) = morphisms {
+                map.map_morphism(s.id, t.id);
+            }
+        }
+
+        map
+    }
+
     /// Get the context map name.
     pub fn name(&self) -> &str {
         &self.name
@@ -411,6 +495,16 @@ pub enum FunctorError {
         source_identity: MorphismId,
         target_morphism: MorphismId,
     },
+
+    /// The two sides of a source equation map to paths that are not
+    /// equal in the target, so composition is not preserved.
+    CompositionNotPreserved { equation: String },
+
+    /// An object is not mapped by a map that must be total.
+    UnmappedObject { object: ObjectId },
+
+    /// A morphism is not mapped by a map that must be total.
+    UnmappedMorphism { morphism: MorphismId },
 }
 
 impl std::fmt::Display for FunctorError {
@@ -462,6 +556,19 @@ impl std::fmt::Display for FunctorError {
                     source_identity, target_morphism
                 )
             }
+            FunctorError::CompositionNotPreserved { equation } => {
+                write!(
+                    f,
+                    "Equation '{}' maps to paths that are not equal in the target",
+                    equation
+                )
+            }
+            FunctorError::UnmappedObject { object } => {
+                write!(f, "Object {:?} is not mapped", object)
+            }
+            FunctorError::UnmappedMorphism { morphism } => {
+                write!(f, "Morphism {:?} is not mapped", morphism)
+            }
         }
     }
 }
@@ -496,7 +603,7 @@ impl FunctorCheckResult {
     }
 }
 
-use crate::sketch::Graph;
+use crate::sketch::{Graph, Path, RewriteSystem, Sketch};
 
 /// Check functorial consistency of a context map against source and target graphs.
 ///
@@ -588,6 +695,86 @@ pub fn check_functorial_consistency(
     }
 }
 
+/// Check every functor law of a context map against source and target
+/// sketches.
+///
+/// In addition to [`check_functorial_consistency`], this verifies:
+/// 4. Composition preservation: F(g ∘ f) = F(g) ∘ F(f). Paths compose
+///    freely, so this amounts to every equation of the source holding in
+///    the target once both of its sides are mapped, which is decided with
+///    the target's equations.
+/// 5. Totality, for maps that [require it](ContextMap::requires_total_functor):
+///    every object and non-identity morphism of the source is mapped.
+pub fn check_functor_laws(
+    context_map: &ContextMap,
+    source: &Sketch,
+    target: &Sketch,
+) -> FunctorCheckResult {
+    let mut errors =
+        check_functorial_consistency(context_map, &source.graph, &target.graph).errors;
+
+    let rewriting = RewriteSystem::new(&target.graph, &target.equations);
+    for equation in &source.equations {
+        let lhs = map_path(context_map, &source.graph, &equation.lhs);
+        let rhs = map_path(context_map, &source.graph, &equation.rhs);
+        if let (Some(lhs), Some(rhs)) = (lhs, rhs) {
+            if !rewriting.equal(&lhs, &rhs) {
+                errors.push(FunctorError::CompositionNotPreserved {
+                    equation: equation.name.clone(),
+                });
+            }
+        }
+    }
+
+    if context_map.requires_total_functor() {
+        let mut objects: Vec<ObjectId> = source.graph.objects().map(|o| o.id).collect();
+        objects.sort();
+        for object in objects {
+            if context_map.get_object_mapping(object).is_none() {
+                errors.push(FunctorError::UnmappedObject { object });
+            }
+        }
+
+        let mut morphisms: Vec<MorphismId> = source
+            .graph
+            .morphisms()
+            .filter(|m| !m.is_identity)
+            .map(|m| m.id)
+            .collect();
+        morphisms.sort();
+        for morphism in morphisms {
+            if context_map.get_morphism_mapping(morphism).is_none() {
+                errors.push(FunctorError::UnmappedMorphism { morphism });
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        FunctorCheckResult::valid()
+    } else {
+        FunctorCheckResult::invalid(errors)
+    }
+}
+
+/// Map a path of the source context to the target context.
+///
+/// Identity morphisms map to the empty path. Returns `None` if an object
+/// or morphism of the path is not mapped.
+pub fn map_path(context_map: &ContextMap, source_graph: &Graph, path: &Path) -> Option<Path> {
+    let morphisms = path
+        .morphisms
+        .iter()
+        .filter(|&&m| source_graph.get_morphism(m).is_some_and(|m| !m.is_identity))
+        .map(|&m| context_map.get_morphism_mapping(m))
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(Path::new(
+        context_map.get_object_mapping(path.source)?,
+        context_map.get_object_mapping(path.target)?,
+        morphisms,
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -735,6 +922,17 @@ mod tests {
         assert_eq!(map.directionality(), "bidirectional (shared)");
     }
 
+    #[test]
+    fn test_pattern_abbreviations() {
+        assert_eq!(RelationshipPattern::CustomerSupplier.abbreviation(), "CS");
+        assert_eq!(
+            RelationshipPattern::AntiCorruptionLayer.abbreviation(),
+            "ACL"
+        );
+        assert_eq!(RelationshipPattern::OpenHostService.abbreviation(), "OHS");
+        assert_eq!(RelationshipPattern::SharedKernel.abbreviation(), "SK");
+    }
+
     // =============================================================
     // Tests for Object and Morphism Mappings
     // =============================================================
@@ -972,6 +1170,59 @@ mod tests {
         assert!(result.is_valid);
     }
 
+    #[test]
+    fn test_check_functor_laws() {
+        use crate::sketch::{Path, PathEquation, Sketch};
+
+        // Source: f.g = h; target: f'.g' and h' with no equation
+        let mut source = Sketch::new("Source");
+        let a = source.add_object("A");
+        let b = source.add_object("B");
+        let c = source.add_object("C");
+        let f = source.add_morphism("f", a, b);
+        let g = source.add_morphism("g", b, c);
+        let h = source.add_morphism("h", a, c);
+        source.add_equation(PathEquation::new(
+            "commutes",
+            Path::new(a, c, vec![f, g]),
+            Path::new(a, c, vec![h]),
+        ));
+
+        let mut target = Sketch::new("Target");
+        let fa = target.add_object("FA");
+        let fb = target.add_object("FB");
+        let fc = target.add_object("FC");
+        let ff = target.add_morphism("Ff", fa, fb);
+        let fg = target.add_morphism("Fg", fb, fc);
+        let fh = target.add_morphism("Fh", fa, fc);
+
+        let mut map = ContextMap::new("Laws", "Source", "Target", RelationshipPattern::Conformist);
+        map.map_object(a, fa);
+        map.map_object(b, fb);
+        map.map_object(c, fc);
+        map.map_morphism(f, ff);
+        map.map_morphism(g, fg);
+
+        let result = check_functor_laws(&map, &source, &target);
+        assert_eq!(result.errors, vec![FunctorError::UnmappedMorphism { morphism: h }]);
+
+        map.map_morphism(h, fh);
+        let result = check_functor_laws(&map, &source, &target);
+        assert_eq!(
+            result.errors,
+            vec![FunctorError::CompositionNotPreserved {
+                equation: "commutes".into()
+            }]
+        );
+
+        target.add_equation(PathEquation::new(
+            "commutes",
+            Path::new(fa, fc, vec![ff, fg]),
+            Path::new(fa, fc, vec![fh]),
+        ));
+        assert!(check_functor_laws(&map, &source, &target).is_valid);
+    }
+
     // =============================================================
     // Tests for Context Map Creation
     // =============================================================
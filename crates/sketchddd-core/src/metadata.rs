@@ -0,0 +1,140 @@
+//! Tags and metadata attached to model elements.
+//!
+//! Contexts, objects and aggregates can carry a strategic [`Classification`]
+//! (is this part of the core domain?), free-form tags and arbitrary
+//! key-value metadata. None of it changes the categorical meaning of the
+//! model; it is there for diagrams, lint rules and generated documentation.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::str::FromStr;
+
+/// The strategic classification of part of a domain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Classification {
+    /// What sets the business apart; where modeling effort pays off most
+    Core,
+    /// Needed by the core domain but not a differentiator
+    Supporting,
+    /// A solved problem, often bought or taken off the shelf
+    Generic,
+}
+
+impl Classification {
+    /// Get all classifications, from most to least strategic.
+    pub fn all() -> [Classification; 3] {
+        [
+            Classification::Core,
+            Classification::Supporting,
+            Classification::Generic,
+        ]
+    }
+}
+
+impl fmt::Display for Classification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Classification::Core => "core",
+            Classification::Supporting => "supporting",
+            Classification::Generic => "generic",
+        })
+    }
+}
+
+impl FromStr for Classification {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Classification::all()
+            .into_iter()
+            .find(|c| c.to_string() == s.trim().to_lowercase())
+            .ok_or_else(|| {
+                format!(
+                    "Unknown classification '{}': expected core, supporting or generic",
+                    s
+                )
+            })
+    }
+}
+
+/// A metadata value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MetadataValue {
+    Text(String),
+    Number(f64),
+}
+
+impl fmt::Display for MetadataValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetadataValue::Text(s) => write!(f, "{}", s),
+            MetadataValue::Number(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+/// Tags and metadata of one model element.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Metadata {
+    /// Strategic classification, if declared
+    #[serde(default)]
+    pub classification: Option<Classification>,
+
+    /// Free-form tags, e.g. `pii`
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+
+    /// Arbitrary key-value metadata, e.g. `owner: "payments-team"`
+    #[serde(default)]
+    pub values: BTreeMap<String, MetadataValue>,
+}
+
+impl Metadata {
+    /// Check if nothing is declared.
+    pub fn is_empty(&self) -> bool {
+        self.classification.is_none() && self.tags.is_empty() && self.values.is_empty()
+    }
+
+    /// Add a tag.
+    pub fn add_tag(&mut self, tag: impl Into<String>) {
+        self.tags.insert(tag.into());
+    }
+
+    /// Check if the element has a tag.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.contains(tag)
+    }
+
+    /// Set a metadata value, replacing any previous one.
+    pub fn set(&mut self, key: impl Into<String>, value: MetadataValue) {
+        self.values.insert(key.into(), value);
+    }
+
+    /// Get a metadata value.
+    pub fn get(&self, key: &str) -> Option<&MetadataValue> {
+        self.values.get(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata() {
+        let mut metadata = Metadata::default();
+        assert!(metadata.is_empty());
+
+        metadata.classification = Some("Core".parse().unwrap());
+        metadata.add_tag("pii");
+        metadata.set("owner", MetadataValue::Text("payments".into()));
+        metadata.set("owner", MetadataValue::Text("billing".into()));
+
+        assert_eq!(metadata.classification, Some(Classification::Core));
+        assert!(metadata.has_tag("pii"));
+        assert_eq!(metadata.get("owner").unwrap().to_string(), "billing");
+        assert!(!metadata.is_empty());
+        assert!("critical".parse::<Classification>().is_err());
+    }
+}
@@ -0,0 +1,626 @@
+//! Functorial data migration along context maps.
+//!
+//! A context map `F: C → D` is a functor between the sketches of two
+//! bounded contexts, and it moves instances between them:
+//!
+//! - **Δ (pullback)** turns an instance of `D` into one of `C` by reading
+//!   each object `c` as the rows of `F(c)`;
+//! - **Σ (left pushforward)** turns an instance of `C` into one of `D` by
+//!   taking, for each object `d`, the union of the rows of every object
+//!   mapped to `d`, merging rows that the equations of `D` identify and
+//!   filling in values that `D` requires but `C` does not provide;
+//! - **Π (right pushforward)** turns an instance of `C` into one of `D` by
+//!   taking, for each object `d`, the consistent tuples of rows of the
+//!   objects mapped to `d`.
+//!
+//! Σ and Π are left and right adjoint to Δ. Migrations assume the map
+//! passes [`check_functorial_consistency`](crate::check_functorial_consistency).
+
+use crate::instance::Instance;
+use crate::mapping::ContextMap;
+use crate::sketch::{Graph, MorphismId, ObjectId, Sketch};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Maximum number of rounds of merging and row invention in Σ.
+const MAX_CHASE_ROUNDS: usize = 16;
+
+/// Errors that can occur during data migration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrationError {
+    /// Σ kept inventing rows, e.g. because a morphism of the target
+    /// context forms a cycle that the source instance does not close.
+    ChaseDidNotTerminate { rounds: usize },
+}
+
+impl std::fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MigrationError::ChaseDidNotTerminate { rounds } => {
+                write!(
+                    f,
+                    "Pushforward did not stabilize after {} rounds of inventing rows",
+                    rounds
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+/// Data migration along a context map from `source` to `target`.
+#[derive(Debug, Clone, Copy)]
+pub struct Migration<'a> {
+    map: &'a ContextMap,
+    source: &'a Sketch,
+    target: &'a Sketch,
+}
+
+impl<'a> Migration<'a> {
+    /// Create a migration along a map between two sketches.
+    pub fn new(map: &'a ContextMap, source: &'a Sketch, target: &'a Sketch) -> Self {
+        Self {
+            map,
+            source,
+            target,
+        }
+    }
+
+    /// Pull an instance of the target context back to the source context (Δ).
+    ///
+    /// Objects and morphisms that are not mapped are left unpopulated.
+    pub fn delta(&self, instance: &Instance) -> Instance {
+        let mut result = Instance::new();
+
+        for mapping in self.map.object_mappings() {
+            for row in instance.rows(mapping.target) {
+                result.add_row(mapping.source, row);
+            }
+        }
+
+        for morphism in self.source.graph.morphisms().filter(|m| !m.is_identity) {
+            let Some(target) = self.map.get_morphism_mapping(morphism.id) else {
+                continue;
+            };
+            let Some(d) = self.map.get_object_mapping(morphism.source) else {
+                continue;
+            };
+            for row in instance.rows(d) {
+                if let Some(value) = instance.apply(&self.target.graph, target, row) {
+                    result.set_value(morphism.id, row, value);
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Push an instance of the source context forward to the target
+    /// context (Σ).
+    ///
+    /// Rows of different source objects mapped to the same object are
+    /// identified when they have the same name. The result is then chased
+    /// until it stabilizes:
+    ///
+    /// - rows are merged until every morphism is a function and both sides
+    ///   of every equation of the target agree;
+    /// - a missing last step of one side of an equation is set to the
+    ///   value of the other side;
+    /// - a row named after its definition, such as `placedBy(order1)`, is
+    ///   invented wherever a populated morphism lacks a value.
+    pub fn sigma(&self, instance: &Instance) -> Result<Instance, MigrationError> {
+        let graph = &self.target.graph;
+        let mut chase = Chase::default();
+
+        for mapping in self.map.object_mappings() {
+            for row in instance.rows(mapping.source) {
+                chase.add_row(mapping.target, row.to_string());
+            }
+        }
+
+        for morphism in self.source.graph.morphisms().filter(|m| !m.is_identity) {
+            let Some(target) = self
+                .map
+                .get_morphism_mapping(morphism.id)
+                .and_then(|id| graph.get_morphism(id))
+            else {
+                continue;
+            };
+            for row in instance.rows(morphism.source) {
+                let Some(value) = instance.apply(&self.source.graph, morphism.id, row) else {
+                    continue;
+                };
+                if target.is_identity {
+                    // The morphism collapses: its rows and values are the same rows
+                    chase.union(target.source, row, value);
+                } else {
+                    chase.add_row(target.target, value.to_string());
+                    chase.add_value(target.id, row.to_string(), value.to_string());
+                }
+            }
+        }
+
+        for _ in 0..MAX_CHASE_ROUNDS {
+            if !chase.step(self.target) {
+                return Ok(chase.into_instance(graph));
+            }
+        }
+        Err(MigrationError::ChaseDidNotTerminate {
+            rounds: MAX_CHASE_ROUNDS,
+        })
+    }
+
+    /// Push an instance of the source context forward to the target
+    /// context (Π).
+    ///
+    /// A row of an object `d` is a tuple with one row for every source
+    /// object mapped to `d`, consistent along the source morphisms that
+    /// map to the identity of `d`. Tuples of one row keep that row's name;
+    /// longer tuples are named like `(order1, alice)`. The limit is taken
+    /// over the objects mapped to `d` only, so objects that nothing maps
+    /// to are left unpopulated.
+    pub fn pi(&self, instance: &Instance) -> Instance {
+        let source_graph = &self.source.graph;
+        let target_graph = &self.target.graph;
+        let mut result = Instance::new();
+
+        let mut fibers: BTreeMap<ObjectId, Vec<ObjectId>> = BTreeMap::new();
+        for mapping in self.map.object_mappings() {
+            fibers
+                .entry(mapping.target)
+                .or_default()
+                .push(mapping.source);
+        }
+        for fiber in fibers.values_mut() {
+            fiber.sort();
+            fiber.dedup();
+        }
+
+        // Rows of each target object, as tuples indexed like its fiber
+        let mut tuples: HashMap<ObjectId, Vec<Vec<&str>>> = HashMap::new();
+        for (&d, fiber) in &fibers {
+            let collapsed: Vec<_> = source_graph
+                .morphisms()
+                .filter(|m| {
+                    !m.is_identity && fiber.contains(&m.source) && fiber.contains(&m.target)
+                })
+                .filter(|m| {
+                    self.map
+                        .get_morphism_mapping(m.id)
+                        .and_then(|id| target_graph.get_morphism(id))
+                        .is_some_and(|t| t.is_identity)
+                })
+                .collect();
+
+            let consistent: Vec<Vec<&str>> =
+                product(fiber.iter().map(|&c| instance.rows(c).collect()))
+                    .into_iter()
+                    .filter(|tuple| {
+                        collapsed.iter().all(|m| {
+                            let at = |object| fiber.iter().position(|&c| c == object).unwrap_or(0);
+                            instance.apply(source_graph, m.id, tuple[at(m.source)])
+                                == Some(tuple[at(m.target)])
+                        })
+                    })
+                    .collect();
+
+            for tuple in &consistent {
+                result.add_row(d, tuple_name(tuple));
+            }
+            tuples.insert(d, consistent);
+        }
+
+        for g in target_graph.morphisms().filter(|m| !m.is_identity) {
+            let (Some(from), Some(to)) = (fibers.get(&g.source), fibers.get(&g.target)) else {
+                continue;
+            };
+            // For each component of the target tuple, a morphism computing it
+            let components: Option<Vec<(usize, MorphismId)>> = to
+                .iter()
+                .map(|&c2| {
+                    source_graph.incoming_morphisms(c2).find_map(|f| {
+                        let position = from.iter().position(|&c| c == f.source)?;
+                        (self.map.get_morphism_mapping(f.id) == Some(g.id))
+                            .then_some((position, f.id))
+                    })
+                })
+                .collect();
+            let Some(components) = components else {
+                continue;
+            };
+
+            for tuple in &tuples[&g.source] {
+                let image: Option<Vec<&str>> = components
+                    .iter()
+                    .map(|&(position, f)| instance.apply(source_graph, f, tuple[position]))
+                    .collect();
+                if let Some(image) = image.filter(|image| tuples[&g.target].contains(image)) {
+                    result.set_value(g.id, tuple_name(tuple), tuple_name(&image));
+                }
+            }
+        }
+
+        result
+    }
+}
+
+/// Name a tuple of rows.
+fn tuple_name(tuple: &[&str]) -> String {
+    match tuple {
+        [row] => row.to_string(),
+        _ => format!("({})", tuple.join(", ")),
+    }
+}
+
+/// All tuples choosing one element from each list.
+fn product<'a>(lists: impl Iterator<Item = Vec<&'a str>>) -> Vec<Vec<&'a str>> {
+    lists.fold(vec![Vec::new()], |tuples, list| {
+        tuples
+            .iter()
+            .flat_map(|tuple| {
+                list.iter().map(move |row| {
+                    let mut extended = tuple.clone();
+                    extended.push(row);
+                    extended
+                })
+            })
+            .collect()
+    })
+}
+
+/// The working state of Σ: rows and values up to a union-find over rows.
+#[derive(Debug, Default)]
+struct Chase {
+    rows: HashMap<ObjectId, BTreeSet<String>>,
+    values: HashMap<MorphismId, BTreeMap<String, String>>,
+    parents: HashMap<ObjectId, HashMap<String, String>>,
+}
+
+impl Chase {
+    fn add_row(&mut self, object: ObjectId, row: String) {
+        self.rows.entry(object).or_default().insert(row);
+    }
+
+    fn add_value(&mut self, morphism: MorphismId, row: String, value: String) {
+        self.values.entry(morphism).or_default().insert(row, value);
+    }
+
+    fn find(&self, object: ObjectId, row: &str) -> String {
+        let mut current = row;
+        while let Some(parent) = self.parents.get(&object).and_then(|p| p.get(current)) {
+            current = parent;
+        }
+        current.to_string()
+    }
+
+    /// Merge two rows of an object, preferring original rows over invented
+    /// ones as the representative. Returns whether they were distinct.
+    fn union(&mut self, object: ObjectId, a: &str, b: &str) -> bool {
+        let (a, b) = (self.find(object, a), self.find(object, b));
+        if a == b {
+            return false;
+        }
+        let key = |row: &String| (row.contains('('), row.clone());
+        let (root, child) = if key(&a) <= key(&b) { (a, b) } else { (b, a) };
+        self.add_row(object, root.clone());
+        self.parents.entry(object).or_default().insert(child, root);
+        true
+    }
+
+    /// Run one round: rewrite rows to their representatives, merge rows
+    /// forced together by functionality and by the equations, then invent
+    /// missing values. Returns whether anything changed.
+    fn step(&mut self, sketch: &Sketch) -> bool {
+        let graph = &sketch.graph;
+        let mut changed = false;
+
+        let rows = std::mem::take(&mut self.rows);
+        for (object, set) in rows {
+            for row in set {
+                let row = self.find(object, &row);
+                self.add_row(object, row);
+            }
+        }
+
+        let values = std::mem::take(&mut self.values);
+        let mut morphisms: Vec<_> = values.into_iter().collect();
+        morphisms.sort_by_key(|(id, _)| *id);
+        for (id, function) in morphisms {
+            let Some(morphism) = graph.get_morphism(id) else {
+                continue;
+            };
+            for (row, value) in function {
+                let row = self.find(morphism.source, &row);
+                let value = self.find(morphism.target, &value);
+                let existing = self.values.get(&id).and_then(|f| f.get(&row)).cloned();
+                match existing {
+                    Some(existing) if existing != value => {
+                        changed |= self.union(morphism.target, &existing, &value);
+                    }
+                    _ => self.add_value(id, row, value),
+                }
+            }
+        }
+
+        for equation in sketch.equations.iter().filter(|eq| eq.is_well_formed()) {
+            let rows: Vec<String> = self
+                .rows
+                .get(&equation.lhs.source)
+                .into_iter()
+                .flatten()
+                .cloned()
+                .collect();
+            let (lhs, rhs) = (&equation.lhs.morphisms, &equation.rhs.morphisms);
+            for row in rows {
+                match (self.follow(graph, lhs, &row), self.follow(graph, rhs, &row)) {
+                    (Some(l), Some(r)) => changed |= self.union(equation.lhs.target, &l, &r),
+                    (Some(value), None) => changed |= self.complete(graph, rhs, &row, value),
+                    (None, Some(value)) => changed |= self.complete(graph, lhs, &row, value),
+                    (None, None) => {}
+                }
+            }
+        }
+
+        if changed {
+            return true;
+        }
+
+        let mut populated: Vec<MorphismId> = self.values.keys().copied().collect();
+        populated.sort();
+        for id in populated {
+            let Some(morphism) = graph.get_morphism(id) else {
+                continue;
+            };
+            let rows: Vec<String> = self
+                .rows
+                .get(&morphism.source)
+                .into_iter()
+                .flatten()
+                .filter(|row| !self.values[&id].contains_key(*row))
+                .cloned()
+                .collect();
+            for row in rows {
+                let invented = format!("{}({})", morphism.name, row);
+                self.add_row(morphism.target, invented.clone());
+                self.add_value(id, row, invented);
+                changed = true;
+            }
+        }
+
+        changed
+    }
+
+    /// Make a path whose last step is missing end at `value`, as an
+    /// equation requires. Returns whether a value was added.
+    fn complete(&mut self, graph: &Graph, path: &[MorphismId], row: &str, value: String) -> bool {
+        let Some((&last, init)) = path.split_last() else {
+            return false;
+        };
+        let Some(morphism) = graph.get_morphism(last).filter(|m| !m.is_identity) else {
+            return false;
+        };
+        let Some(at) = self.follow(graph, init, row) else {
+            return false;
+        };
+        self.add_row(morphism.target, value.clone());
+        self.add_value(last, at, value);
+        true
+    }
+
+    fn follow(&self, graph: &Graph, path: &[MorphismId], row: &str) -> Option<String> {
+        path.iter().try_fold(row.to_string(), |current, id| {
+            if graph.get_morphism(*id)?.is_identity {
+                return Some(current);
+            }
+            self.values.get(id)?.get(&current).cloned()
+        })
+    }
+
+    fn into_instance(self, graph: &Graph) -> Instance {
+        let mut instance = Instance::new();
+        for (object, rows) in &self.rows {
+            for row in rows {
+                if self.find(*object, row) == *row {
+                    instance.add_row(*object, row.clone());
+                }
+            }
+        }
+        for (id, function) in &self.values {
+            if graph.get_morphism(*id).is_some() {
+                for (row, value) in function {
+                    instance.set_value(*id, row.clone(), value.clone());
+                }
+            }
+        }
+        instance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapping::RelationshipPattern;
+    use crate::sketch::{Path, PathEquation};
+
+    /// Sales has orders placed by customers; Billing has invoices billed
+    /// to accounts, with every account owned by a customer profile.
+    struct Fixture {
+        sales: Sketch,
+        billing: Sketch,
+        map: ContextMap,
+        order: ObjectId,
+        customer: ObjectId,
+        placed_by: MorphismId,
+        invoice: ObjectId,
+        account: ObjectId,
+        billed_to: MorphismId,
+    }
+
+    fn fixture() -> Fixture {
+        let mut sales = Sketch::new("Sales");
+        let order = sales.add_object("Order");
+        let customer = sales.add_object("Customer");
+        let placed_by = sales.add_morphism("placedBy", order, customer);
+
+        let mut billing = Sketch::new("Billing");
+        let invoice = billing.add_object("Invoice");
+        let account = billing.add_object("Account");
+        let billed_to = billing.add_morphism("billedTo", invoice, account);
+
+        let mut map = ContextMap::new(
+            "SalesToBilling",
+            "Sales",
+            "Billing",
+            RelationshipPattern::CustomerSupplier,
+        );
+        map.map_object(order, invoice);
+        map.map_object(customer, account);
+        map.map_morphism(placed_by, billed_to);
+
+        Fixture {
+            sales,
+            billing,
+            map,
+            order,
+            customer,
+            placed_by,
+            invoice,
+            account,
+            billed_to,
+        }
+    }
+
+    fn rows(instance: &Instance, object: ObjectId) -> Vec<&str> {
+        instance.rows(object).collect()
+    }
+
+    #[test]
+    fn test_delta() {
+        let f = fixture();
+        let mut billing = Instance::new();
+        billing.add_row(f.invoice, "inv1");
+        billing.add_row(f.account, "acme");
+        billing.set_value(f.billed_to, "inv1", "acme");
+
+        let sales = Migration::new(&f.map, &f.sales, &f.billing).delta(&billing);
+        assert_eq!(rows(&sales, f.order), vec!["inv1"]);
+        assert_eq!(
+            sales.apply(&f.sales.graph, f.placed_by, "inv1"),
+            Some("acme")
+        );
+        assert!(!sales.validate(&f.sales).has_issues());
+    }
+
+    #[test]
+    fn test_sigma_and_pi_along_isomorphism() {
+        let f = fixture();
+        let mut sales = Instance::new();
+        sales.add_row(f.order, "order1");
+        sales.add_row(f.customer, "alice");
+        sales.set_value(f.placed_by, "order1", "alice");
+
+        let migration = Migration::new(&f.map, &f.sales, &f.billing);
+        let sigma = migration.sigma(&sales).unwrap();
+        let pi = migration.pi(&sales);
+
+        for billing in [&sigma, &pi] {
+            assert_eq!(rows(billing, f.invoice), vec!["order1"]);
+            assert_eq!(
+                billing.apply(&f.billing.graph, f.billed_to, "order1"),
+                Some("alice")
+            );
+        }
+        assert_eq!(migration.delta(&sigma), sales);
+    }
+
+    #[test]
+    fn test_sigma_completes_equations() {
+        let mut f = fixture();
+        let profile = f.billing.add_object("Profile");
+        let owner = f.billing.add_morphism("owner", f.account, profile);
+        let payer = f.billing.add_morphism("payer", f.invoice, profile);
+        // Invoices are paid by the owner of the account they are billed to
+        f.billing.add_equation(PathEquation::new(
+            "payer_owns_account",
+            Path::new(f.invoice, profile, vec![f.billed_to, owner]),
+            Path::new(f.invoice, profile, vec![payer]),
+        ));
+
+        // Sales tracks who paid; both orders were paid by bob
+        let person = f.sales.add_object("Person");
+        let paid_by = f.sales.add_morphism("paidBy", f.order, person);
+        f.map.map_object(person, profile);
+        f.map.map_morphism(paid_by, payer);
+
+        let mut sales = Instance::new();
+        sales.add_row(f.order, "order1");
+        sales.add_row(f.customer, "alice");
+        sales.add_row(person, "bob");
+        sales.set_value(f.placed_by, "order1", "alice");
+        sales.set_value(paid_by, "order1", "bob");
+
+        let billing = Migration::new(&f.map, &f.sales, &f.billing)
+            .sigma(&sales)
+            .unwrap();
+
+        // The equation requires the owner of alice's account to be bob
+        assert_eq!(rows(&billing, profile), vec!["bob"]);
+        assert_eq!(billing.apply(&f.billing.graph, owner, "alice"), Some("bob"));
+        assert!(!billing.validate(&f.billing).has_issues());
+    }
+
+    #[test]
+    fn test_sigma_cycle_does_not_terminate() {
+        let mut f = fixture();
+        let parent = f.billing.add_morphism("parent", f.account, f.account);
+
+        let mut sales = Instance::new();
+        sales.add_row(f.customer, "alice");
+        let referral = f.sales.add_morphism("referredBy", f.customer, f.customer);
+        sales.set_value(referral, "alice", "alice2");
+        sales.add_row(f.customer, "alice2");
+        f.map.map_morphism(referral, parent);
+
+        let result = Migration::new(&f.map, &f.sales, &f.billing).sigma(&sales);
+        assert_eq!(
+            result,
+            Err(MigrationError::ChaseDidNotTerminate {
+                rounds: MAX_CHASE_ROUNDS
+            })
+        );
+    }
+
+    #[test]
+    fn test_pi_tuples_collapsed_objects() {
+        // Orders and their customers both map to Invoice; Π pairs each
+        // order with the customer who placed it
+        let mut f = fixture();
+        let mut billing = Sketch::new("Billing");
+        let invoice = billing.add_object("Invoice");
+        let id_invoice = billing.graph.add_identity_morphism(invoice);
+        f.billing = billing;
+        f.map = ContextMap::new(
+            "Collapse",
+            "Sales",
+            "Billing",
+            RelationshipPattern::Conformist,
+        );
+        f.map.map_object(f.order, invoice);
+        f.map.map_object(f.customer, invoice);
+        f.map.map_morphism(f.placed_by, id_invoice);
+
+        let mut sales = Instance::new();
+        sales.add_row(f.order, "order1");
+        sales.add_row(f.order, "order2");
+        sales.add_row(f.customer, "alice");
+        sales.add_row(f.customer, "bob");
+        sales.set_value(f.placed_by, "order1", "alice");
+        sales.set_value(f.placed_by, "order2", "alice");
+
+        let pi = Migration::new(&f.map, &f.sales, &f.billing).pi(&sales);
+        assert_eq!(
+            rows(&pi, invoice),
+            vec!["(order1, alice)", "(order2, alice)"]
+        );
+    }
+}
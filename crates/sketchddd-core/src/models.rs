@@ -0,0 +1,619 @@
+//! Finite model finding for sketches.
+//!
+//! A model of a sketch interprets every object as a set and every morphism
+//! as a function, such that:
+//!
+//! - identity morphisms are identity functions,
+//! - both sides of every path equation compute the same function,
+//! - every value object is the product of its components, i.e. an element
+//!   is determined by, and exists for every choice of, its component
+//!   values,
+//! - every pullback or equalizer holds exactly one element for each choice
+//!   of components on which its diagram commutes, and
+//! - every enum or sum type is the disjoint union of its variants, with
+//!   one element per unit variant.
+//!
+//! Aggregate cones group entities for consistency but do not constrain
+//! their sets, so they impose nothing here.
+//!
+//! [`ModelFinder`] searches for a model whose sets are small, which proves
+//! the domain rules consistent. When there is none within the size bound,
+//! it reports a minimal combination of constraints that cannot be satisfied
+//! together.
+
+use crate::sketch::{LimitCone, LimitShape, MorphismId, ObjectId, Path, Sketch};
+use std::collections::{HashMap, HashSet};
+
+/// A set-valued model of a sketch with finite sets.
+///
+/// The elements of an object of size `n` are `0..n`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FiniteModel {
+    sizes: HashMap<ObjectId, usize>,
+    functions: HashMap<MorphismId, Vec<usize>>,
+}
+
+impl FiniteModel {
+    /// Get the number of elements of an object's set.
+    pub fn size(&self, object: ObjectId) -> Option<usize> {
+        self.sizes.get(&object).copied()
+    }
+
+    /// Get the function interpreting a morphism, as the image of each
+    /// element of its source.
+    pub fn function(&self, morphism: MorphismId) -> Option<&[usize]> {
+        self.functions.get(&morphism).map(Vec::as_slice)
+    }
+
+    /// Apply a morphism's function to an element of its source.
+    pub fn apply(&self, morphism: MorphismId, element: usize) -> Option<usize> {
+        self.function(morphism)?.get(element).copied()
+    }
+}
+
+/// A constraint of a sketch that a model must satisfy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Constraint {
+    /// A path equation, by index into the sketch's equations
+    Equation(usize),
+
+    /// A value object's product cone, or a pullback or equalizer, by index
+    /// into the sketch's limits
+    Limit(usize),
+
+    /// A sum type's cocone, by index into the sketch's colimits
+    Colimit(usize),
+}
+
+impl Constraint {
+    /// Describe the constraint using the names from the sketch.
+    pub fn describe(&self, sketch: &Sketch) -> String {
+        match *self {
+            Constraint::Equation(i) => format!("equation '{}'", sketch.equations[i].name),
+            Constraint::Limit(i) if sketch.limits[i].is_product() => {
+                format!("value object '{}'", sketch.limits[i].name)
+            }
+            Constraint::Limit(i) => format!("limit '{}'", sketch.limits[i].name),
+            Constraint::Colimit(i) => format!("enum '{}'", sketch.colimits[i].name),
+        }
+    }
+}
+
+/// The outcome of a model search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModelSearch {
+    /// A model was found
+    Found(FiniteModel),
+
+    /// No model exists within the size bound. The constraints cannot be
+    /// satisfied together, and dropping any one of them makes a model
+    /// possible.
+    NoModel { conflict: Vec<Constraint> },
+
+    /// The search budget ran out before the search could finish
+    GaveUp,
+}
+
+/// Default largest set size to try.
+const DEFAULT_MAX_SIZE: usize = 3;
+
+/// Default number of search steps before giving up.
+const DEFAULT_MAX_STEPS: usize = 200_000;
+
+/// Searches for small finite models of a sketch.
+#[derive(Debug, Clone)]
+pub struct ModelFinder<'a> {
+    sketch: &'a Sketch,
+    min_size: usize,
+    max_size: usize,
+    max_steps: usize,
+}
+
+impl<'a> ModelFinder<'a> {
+    /// Create a finder for a sketch.
+    ///
+    /// By default every set has between 1 and 3 elements, since a model in
+    /// which some object is empty rarely reflects the domain.
+    pub fn new(sketch: &'a Sketch) -> Self {
+        Self {
+            sketch,
+            min_size: 1,
+            max_size: DEFAULT_MAX_SIZE,
+            max_steps: DEFAULT_MAX_STEPS,
+        }
+    }
+
+    /// Set the smallest set size to try; 0 allows empty sets.
+    pub fn with_min_size(mut self, min_size: usize) -> Self {
+        self.min_size = min_size;
+        self
+    }
+
+    /// Set the largest set size to try.
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Set the number of search steps before giving up.
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Search for a model satisfying every constraint of the sketch.
+    pub fn find(&self) -> ModelSearch {
+        let all = self.constraints();
+        match self.search(&all) {
+            Outcome::Found(model) => ModelSearch::Found(model),
+            Outcome::GaveUp => ModelSearch::GaveUp,
+            Outcome::NoModel => ModelSearch::NoModel {
+                conflict: self.shrink_conflict(all),
+            },
+        }
+    }
+
+    /// All constraints of the sketch that involve only known objects.
+    fn constraints(&self) -> Vec<Constraint> {
+        let graph = &self.sketch.graph;
+        let known = |id: ObjectId| graph.get_object(id).is_some();
+        let path_valid = |path: &Path| match graph.compose(&path.morphisms) {
+            Ok(c) => c.source == path.source && c.target == path.target,
+            Err(_) => path.is_identity() && known(path.source),
+        };
+
+        let equations =
+            self.sketch.equations.iter().enumerate().filter(|(_, eq)| {
+                eq.is_well_formed() && path_valid(&eq.lhs) && path_valid(&eq.rhs)
+            });
+        let limits = self.sketch.limits.iter().enumerate().filter(|(_, limit)| {
+            !limit.is_aggregate
+                && known(limit.apex)
+                && limit.projections.iter().all(|p| {
+                    graph
+                        .get_morphism(p.morphism)
+                        .is_some_and(|m| m.source == limit.apex && m.target == p.target)
+                })
+                && (limit.is_product() || !limit.commuting_equations(graph).is_empty())
+                && limit.diagram_morphisms().iter().all(|&m| {
+                    graph
+                        .get_morphism(m)
+                        .is_some_and(|m| known(m.source) && known(m.target))
+                })
+        });
+        let colimits = self
+            .sketch
+            .colimits
+            .iter()
+            .enumerate()
+            .filter(|(_, colimit)| {
+                known(colimit.apex) && colimit.injections.iter().all(|i| known(i.source))
+            });
+
+        equations
+            .map(|(i, _)| Constraint::Equation(i))
+            .chain(limits.map(|(i, _)| Constraint::Limit(i)))
+            .chain(colimits.map(|(i, _)| Constraint::Colimit(i)))
+            .collect()
+    }
+
+    /// Shrink an unsatisfiable set of constraints to a minimal one by
+    /// dropping each constraint whose removal keeps it unsatisfiable.
+    fn shrink_conflict(&self, mut conflict: Vec<Constraint>) -> Vec<Constraint> {
+        let mut i = 0;
+        while i < conflict.len() {
+            let mut without = conflict.clone();
+            without.remove(i);
+            match self.search(&without) {
+                Outcome::NoModel => conflict = without,
+                Outcome::Found(_) | Outcome::GaveUp => i += 1,
+            }
+        }
+        conflict
+    }
+
+    fn search(&self, constraints: &[Constraint]) -> Outcome {
+        let mut search = Search::new(self, constraints);
+        match search.assign_sizes(0) {
+            Some(model) => Outcome::Found(model),
+            None if search.steps > self.max_steps => Outcome::GaveUp,
+            None => Outcome::NoModel,
+        }
+    }
+}
+
+enum Outcome {
+    Found(FiniteModel),
+    NoModel,
+    GaveUp,
+}
+
+/// The state of one backtracking search: first over the size of every
+/// object, then over the value of every morphism at every element.
+struct Search<'a> {
+    finder: &'a ModelFinder<'a>,
+    constraints: &'a [Constraint],
+    objects: Vec<ObjectId>,
+    /// Non-identity morphisms, in the order their functions are chosen
+    morphisms: Vec<MorphismId>,
+    identities: HashSet<MorphismId>,
+    /// Constraints to check once the morphism at each index is chosen
+    checks: Vec<Vec<Constraint>>,
+    sizes: HashMap<ObjectId, usize>,
+    functions: HashMap<MorphismId, Vec<usize>>,
+    steps: usize,
+}
+
+impl<'a> Search<'a> {
+    fn new(finder: &'a ModelFinder<'a>, constraints: &'a [Constraint]) -> Self {
+        let sketch = finder.sketch;
+        let graph = &sketch.graph;
+
+        let mut objects: Vec<ObjectId> = graph.objects().map(|o| o.id).collect();
+        objects.sort();
+
+        let mut morphisms: Vec<MorphismId> = graph
+            .morphisms()
+            .filter(|m| !m.is_identity && graph.get_object(m.source).is_some())
+            .filter(|m| graph.get_object(m.target).is_some())
+            .map(|m| m.id)
+            .collect();
+        morphisms.sort();
+        let identities = graph
+            .morphisms()
+            .filter(|m| m.is_identity)
+            .map(|m| m.id)
+            .collect();
+
+        let mut checks = vec![Vec::new(); morphisms.len() + 1];
+        for &constraint in constraints {
+            let used: Vec<MorphismId> = match constraint {
+                Constraint::Equation(i) => {
+                    let eq = &sketch.equations[i];
+                    eq.lhs
+                        .morphisms
+                        .iter()
+                        .chain(&eq.rhs.morphisms)
+                        .copied()
+                        .collect()
+                }
+                Constraint::Limit(i) => {
+                    let limit = &sketch.limits[i];
+                    let projections = limit.projections.iter().map(|p| p.morphism);
+                    projections.chain(limit.diagram_morphisms()).collect()
+                }
+                Constraint::Colimit(_) => continue,
+            };
+            let last = used
+                .iter()
+                .filter_map(|m| morphisms.iter().position(|x| x == m))
+                .max()
+                .map_or(0, |i| i + 1);
+            checks[last].push(constraint);
+        }
+
+        Self {
+            finder,
+            constraints,
+            objects,
+            morphisms,
+            identities,
+            checks,
+            sizes: HashMap::new(),
+            functions: HashMap::new(),
+            steps: 0,
+        }
+    }
+
+    fn out_of_steps(&mut self) -> bool {
+        self.steps += 1;
+        self.steps > self.finder.max_steps
+    }
+
+    fn assign_sizes(&mut self, index: usize) -> Option<FiniteModel> {
+        let Some(&object) = self.objects.get(index) else {
+            return self.assign_functions(0, 0);
+        };
+
+        for size in self.finder.min_size..=self.finder.max_size {
+            if self.out_of_steps() {
+                return None;
+            }
+            self.sizes.insert(object, size);
+            if self.sizes_consistent() {
+                if let Some(model) = self.assign_sizes(index + 1) {
+                    return Some(model);
+                }
+            }
+        }
+        self.sizes.remove(&object);
+        None
+    }
+
+    /// Check the size constraints of limits and colimits whose objects
+    /// all have a size.
+    fn sizes_consistent(&self) -> bool {
+        let sketch = self.finder.sketch;
+        self.constraints.iter().all(|constraint| match *constraint {
+            Constraint::Equation(_) => true,
+            // The size of a pullback or equalizer depends on its functions
+            Constraint::Limit(i) if !sketch.limits[i].is_product() => true,
+            Constraint::Limit(i) => {
+                let limit = &sketch.limits[i];
+                let components: Option<Vec<usize>> = limit
+                    .projections
+                    .iter()
+                    .map(|p| self.sizes.get(&p.target).copied())
+                    .collect();
+                match (self.sizes.get(&limit.apex), components) {
+                    (Some(&apex), Some(components)) => apex == components.iter().product::<usize>(),
+                    _ => true,
+                }
+            }
+            Constraint::Colimit(i) => {
+                let colimit = &sketch.colimits[i];
+                let variants: Option<Vec<usize>> = colimit
+                    .injections
+                    .iter()
+                    .map(|inj| {
+                        // Variants of an enumeration are units sourced at the apex
+                        if inj.source == colimit.apex {
+                            Some(1)
+                        } else {
+                            self.sizes.get(&inj.source).copied()
+                        }
+                    })
+                    .collect();
+                match (self.sizes.get(&colimit.apex), variants) {
+                    (Some(&apex), Some(variants)) => apex == variants.iter().sum::<usize>(),
+                    _ => true,
+                }
+            }
+        })
+    }
+
+    /// Choose the image of `element` under the morphism at `index`, then
+    /// everything after it.
+    fn assign_functions(&mut self, index: usize, element: usize) -> Option<FiniteModel> {
+        let graph = &self.finder.sketch.graph;
+
+        if element == 0 && !self.checks_hold(index) {
+            return None;
+        }
+        let Some(&morphism) = self.morphisms.get(index) else {
+            return Some(FiniteModel {
+                sizes: self.sizes.clone(),
+                functions: self.functions.clone(),
+            });
+        };
+
+        let m = graph.get_morphism(morphism)?;
+        let (source, target) = (self.sizes[&m.source], self.sizes[&m.target]);
+        if element == source {
+            return self.assign_functions(index + 1, 0);
+        }
+
+        for value in 0..target {
+            if self.out_of_steps() {
+                return None;
+            }
+            let function = self.functions.entry(morphism).or_default();
+            function.truncate(element);
+            function.push(value);
+            if let Some(model) = self.assign_functions(index, element + 1) {
+                return Some(model);
+            }
+        }
+        None
+    }
+
+    /// Check the constraints that become decidable once every morphism
+    /// before `index` has a function.
+    fn checks_hold(&self, index: usize) -> bool {
+        let sketch = self.finder.sketch;
+        self.checks[index]
+            .iter()
+            .all(|constraint| match *constraint {
+                Constraint::Equation(i) => {
+                    let eq = &sketch.equations[i];
+                    (0..self.sizes[&eq.lhs.source])
+                        .all(|x| self.eval(&eq.lhs.morphisms, x) == self.eval(&eq.rhs.morphisms, x))
+                }
+                Constraint::Limit(i) => {
+                    let limit = &sketch.limits[i];
+                    let mut tuples = HashSet::new();
+                    let injective = (0..self.sizes[&limit.apex]).all(|x| {
+                        let tuple: Vec<usize> = limit
+                            .projections
+                            .iter()
+                            .map(|p| self.eval(&[p.morphism], x))
+                            .collect();
+                        tuples.insert(tuple)
+                    });
+                    injective && self.limit_complete(limit, &tuples)
+                }
+                Constraint::Colimit(_) => true,
+            })
+    }
+
+    /// Check that the tuples of a pullback or equalizer are exactly the
+    /// elements on which its diagram commutes.
+    ///
+    /// A cone without the projections of its shape, as one read from a
+    /// malformed file may be, is never satisfied.
+    fn limit_complete(&self, limit: &LimitCone, tuples: &HashSet<Vec<usize>>) -> bool {
+        let commutes: Vec<Vec<usize>> = match (limit.shape, limit.projections.as_slice()) {
+            (LimitShape::Product, _) => return true,
+            (LimitShape::Pullback { f, g }, [p, q]) => {
+                let (a, b) = (self.sizes[&p.target], self.sizes[&q.target]);
+                (0..a)
+                    .flat_map(|x| (0..b).map(move |y| vec![x, y]))
+                    .filter(|t| self.eval(&[f], t[0]) == self.eval(&[g], t[1]))
+                    .collect()
+            }
+            (LimitShape::Equalizer { f, g }, [p]) => (0..self.sizes[&p.target])
+                .filter(|&x| self.eval(&[f], x) == self.eval(&[g], x))
+                .map(|x| vec![x])
+                .collect(),
+            _ => return false,
+        };
+        commutes.len() == tuples.len() && commutes.iter().all(|t| tuples.contains(t))
+    }
+
+    /// Follow a path of morphisms from an element.
+    fn eval(&self, path: &[MorphismId], element: usize) -> usize {
+        path.iter().fold(element, |x, m| {
+            if self.identities.contains(m) {
+                x
+            } else {
+                self.functions[m][x]
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sketch::{ColimitCocone, LimitCone, PathEquation};
+
+    #[test]
+    fn test_find_model() {
+        let mut sketch = Sketch::new("Commerce");
+        let order = sketch.add_object("Order");
+        let customer = sketch.add_object("Customer");
+        let status = sketch.add_object("OrderStatus");
+        let placed_by = sketch.add_morphism("placedBy", order, customer);
+        let state = sketch.add_morphism("status", order, status);
+        sketch.add_colimit(ColimitCocone::enumeration(
+            "OrderStatus",
+            status,
+            vec!["Pending".into(), "Shipped".into()],
+        ));
+
+        let ModelSearch::Found(model) = ModelFinder::new(&sketch).find() else {
+            panic!("expected a model");
+        };
+        assert_eq!(model.size(status), Some(2));
+        assert!(model.apply(placed_by, 0).unwrap() < model.size(customer).unwrap());
+        assert!(model.apply(state, 0).unwrap() < 2);
+    }
+
+    #[test]
+    fn test_model_satisfies_equations_and_products() {
+        let mut sketch = Sketch::new("Pricing");
+        let line = sketch.add_object("LineItem");
+        let money = sketch.add_object("Money");
+        let amount = sketch.add_object("Decimal");
+        let currency = sketch.add_object("Currency");
+        let price = sketch.add_morphism("price", line, money);
+        let total = sketch.add_morphism("total", line, money);
+        let amount_of = sketch.add_morphism("amount", money, amount);
+        let currency_of = sketch.add_morphism("currency", money, currency);
+
+        let mut limit = LimitCone::value_object("Money", money);
+        limit.add_projection(amount_of, amount);
+        limit.add_projection(currency_of, currency);
+        sketch.add_limit(limit);
+        sketch.add_colimit(ColimitCocone::enumeration(
+            "Currency",
+            currency,
+            vec!["USD".into(), "EUR".into()],
+        ));
+        sketch.add_equation(PathEquation::new(
+            "total_is_price",
+            Path::new(line, money, vec![total]),
+            Path::new(line, money, vec![price]),
+        ));
+
+        let ModelSearch::Found(model) = ModelFinder::new(&sketch).find() else {
+            panic!("expected a model");
+        };
+        let money_size = model.size(money).unwrap();
+        assert_eq!(
+            money_size,
+            model.size(amount).unwrap() * model.size(currency).unwrap()
+        );
+        assert_eq!(model.function(total), model.function(price));
+    }
+
+    #[test]
+    fn test_model_equalizer_holds_agreeing_elements() {
+        let mut sketch = Sketch::new("Billing");
+        let order = sketch.add_object("Order");
+        let money = sketch.add_object("Money");
+        let total = sketch.add_morphism("total", order, money);
+        let paid = sketch.add_morphism("paid", order, money);
+        let settled = sketch.add_equalizer("SettledOrder", total, paid).unwrap();
+
+        let ModelSearch::Found(model) = ModelFinder::new(&sketch).with_min_size(2).find() else {
+            panic!("expected a model");
+        };
+        let agreeing = (0..model.size(order).unwrap())
+            .filter(|&x| model.apply(total, x) == model.apply(paid, x))
+            .count();
+        assert_eq!(model.size(settled), Some(agreeing));
+    }
+
+    #[test]
+    fn test_malformed_cone_is_unsatisfied() {
+        let mut sketch = Sketch::new("Billing");
+        let order = sketch.add_object("Order");
+        let money = sketch.add_object("Money");
+        let settled = sketch.add_object("SettledOrder");
+        let total = sketch.add_morphism("total", order, money);
+        let paid = sketch.add_morphism("paid", order, money);
+
+        // Cones without their projections, as read from a malformed file
+        let finder = ModelFinder::new(&sketch);
+        let search = Search::new(&finder, &[]);
+        let equalizer = LimitCone::equalizer("SettledOrder", settled, total, paid);
+        let pullback = LimitCone::pullback("SettledOrder", settled, total, paid);
+        assert!(!search.limit_complete(&equalizer, &HashSet::new()));
+        assert!(!search.limit_complete(&pullback, &HashSet::new()));
+    }
+
+    #[test]
+    fn test_report_conflict() {
+        // A two-variant enum cannot be a retract of a singleton
+        let mut sketch = Sketch::new("Conflict");
+        let status = sketch.add_object("Status");
+        let unit = sketch.add_object("Unit");
+        let other = sketch.add_object("Other");
+        let forget = sketch.add_morphism("forget", status, unit);
+        let pick = sketch.add_morphism("pick", unit, status);
+        sketch.add_morphism("describe", status, other);
+
+        sketch.add_colimit(ColimitCocone::enumeration(
+            "Status",
+            status,
+            vec!["Open".into(), "Closed".into()],
+        ));
+        sketch.add_colimit(ColimitCocone::enumeration("Other", other, vec!["X".into()]));
+        sketch.add_limit(LimitCone::value_object("Unit", unit));
+        sketch.add_equation(PathEquation::new(
+            "retract",
+            Path::new(status, status, vec![forget, pick]),
+            Path::identity(status),
+        ));
+
+        let ModelSearch::NoModel { conflict } = ModelFinder::new(&sketch).find() else {
+            panic!("expected no model");
+        };
+        let described: Vec<String> = conflict.iter().map(|c| c.describe(&sketch)).collect();
+        assert_eq!(
+            described,
+            vec!["equation 'retract'", "value object 'Unit'", "enum 'Status'"]
+        );
+    }
+
+    #[test]
+    fn test_give_up_when_out_of_steps() {
+        let mut sketch = Sketch::new("Big");
+        let a = sketch.add_object("A");
+        sketch.add_morphism("f", a, a);
+
+        let search = ModelFinder::new(&sketch).with_max_steps(0).find();
+        assert_eq!(search, ModelSearch::GaveUp);
+    }
+}
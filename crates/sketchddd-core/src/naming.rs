@@ -0,0 +1,195 @@
+//! Naming conventions for model elements.
+//!
+//! Objects are named in PascalCase (`LineItem`), morphisms in camelCase
+//! (`placedBy`) and entities in the singular (`Order`, not `Orders`). These
+//! helpers check a name against a convention and compute the corrected name
+//! the naming lint rules suggest.
+
+/// Split a name into words at separators and case changes.
+///
+/// `order_line`, `order-line` and `orderLine` all give `order` and `line`.
+/// A run of capitals is kept as one word, so `HTTPServer` gives `HTTP` and
+/// `Server`.
+fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    for part in name.split(|c: char| !c.is_alphanumeric()) {
+        let chars: Vec<char> = part.chars().collect();
+        let mut word = String::new();
+        for (i, &c) in chars.iter().enumerate() {
+            let boundary = i > 0
+                && c.is_uppercase()
+                && (chars[i - 1].is_lowercase()
+                    || chars[i - 1].is_ascii_digit()
+                    || chars.get(i + 1).is_some_and(|n| n.is_lowercase()));
+            if boundary && !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            word.push(c);
+        }
+        if !word.is_empty() {
+            words.push(word);
+        }
+    }
+    words
+}
+
+/// Capitalize a word, lowering the rest of it if it is all capitals.
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    let Some(first) = chars.next() else {
+        return String::new();
+    };
+    let rest: String = chars.collect();
+    let rest = if rest.chars().any(char::is_lowercase) {
+        rest
+    } else {
+        rest.to_lowercase()
+    };
+    first.to_uppercase().chain(rest.chars()).collect()
+}
+
+/// Check if a name is in PascalCase.
+pub fn is_pascal_case(name: &str) -> bool {
+    name.starts_with(char::is_uppercase) && name.chars().all(char::is_alphanumeric)
+}
+
+/// Check if a name is in camelCase.
+pub fn is_camel_case(name: &str) -> bool {
+    name.starts_with(char::is_lowercase) && name.chars().all(char::is_alphanumeric)
+}
+
+/// Convert a name to PascalCase.
+pub fn to_pascal_case(name: &str) -> String {
+    words(name).iter().map(|w| capitalize(w)).collect()
+}
+
+/// Convert a name to camelCase.
+pub fn to_camel_case(name: &str) -> String {
+    let words = words(name);
+    let Some((first, rest)) = words.split_first() else {
+        return String::new();
+    };
+    std::iter::once(first.to_lowercase())
+        .chain(rest.iter().map(|w| capitalize(w)))
+        .collect()
+}
+
+/// Convert a name to kebab-case, as used by tools outside the model.
+pub fn to_kebab_case(name: &str) -> String {
+    words(name)
+        .iter()
+        .map(|w| w.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Words that end like plurals but are singular or have no singular.
+const SINGULAR_WORDS: [&str; 10] = [
+    "alias", "bias", "canvas", "chassis", "data", "gas", "lens", "news", "series", "species",
+];
+
+/// Plurals that do not follow the regular rules, with their singular.
+const IRREGULAR_PLURALS: [(&str, &str); 6] = [
+    ("children", "child"),
+    ("feet", "foot"),
+    ("men", "man"),
+    ("people", "person"),
+    ("teeth", "tooth"),
+    ("women", "woman"),
+];
+
+/// Get the singular of a name whose last word is an English plural.
+///
+/// Returns `None` when the name does not look plural. The check is a
+/// heuristic over common English endings: `Categories` gives `Category`,
+/// `Addresses` gives `Address` and `OrderLines` gives `OrderLine`, while
+/// `Status`, `Address` and `Analysis` are left alone.
+pub fn singularize(name: &str) -> Option<String> {
+    let start = name
+        .char_indices()
+        .rev()
+        .find(|&(i, c)| (i > 0 && c.is_uppercase()) || !c.is_alphanumeric())
+        .map(|(i, c)| {
+            if c.is_alphanumeric() {
+                i
+            } else {
+                i + c.len_utf8()
+            }
+        })
+        .unwrap_or(0);
+    let (head, last) = name.split_at(start);
+    let lower = last.to_lowercase();
+
+    if SINGULAR_WORDS.contains(&lower.as_str()) {
+        return None;
+    }
+    let singular = if let Some((_, singular)) = IRREGULAR_PLURALS
+        .iter()
+        .find(|(plural, _)| *plural == lower)
+    {
+        let mut singular = singular.to_string();
+        if last.starts_with(char::is_uppercase) {
+            singular = capitalize(&singular);
+        }
+        singular
+    } else if lower.len() > 3 && lower.ends_with("ies") {
+        format!("{}y", &last[..last.len() - 3])
+    } else if ["sses", "xes", "zes", "ches", "shes"]
+        .iter()
+        .any(|suffix| lower.ends_with(suffix))
+    {
+        last[..last.len() - 2].to_string()
+    } else if lower.len() > 1
+        && lower.ends_with('s')
+        && !["ss", "us", "is"]
+            .iter()
+            .any(|suffix| lower.ends_with(suffix))
+    {
+        last[..last.len() - 1].to_string()
+    } else {
+        return None;
+    };
+    Some(format!("{}{}", head, singular))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_case_conversions() {
+        assert!(is_pascal_case("LineItem"));
+        assert!(!is_pascal_case("lineItem"));
+        assert!(!is_pascal_case("Line_Item"));
+        assert!(is_camel_case("placedBy"));
+        assert!(!is_camel_case("PlacedBy"));
+
+        assert_eq!(to_pascal_case("line_item"), "LineItem");
+        assert_eq!(to_pascal_case("lineItem"), "LineItem");
+        assert_eq!(to_pascal_case("LINE_ITEM"), "LineItem");
+        assert_eq!(to_pascal_case("HTTP_server"), "HttpServer");
+        assert_eq!(to_camel_case("PlacedBy"), "placedBy");
+        assert_eq!(to_camel_case("placed_by"), "placedBy");
+        assert_eq!(to_camel_case("URL"), "url");
+        assert_eq!(to_camel_case("shipping-address2"), "shippingAddress2");
+        assert_eq!(to_kebab_case("OrderManagement"), "order-management");
+        assert_eq!(to_kebab_case("HTTP_server"), "http-server");
+    }
+
+    #[test]
+    fn test_singularize() {
+        assert_eq!(singularize("Orders").as_deref(), Some("Order"));
+        assert_eq!(singularize("OrderLines").as_deref(), Some("OrderLine"));
+        assert_eq!(singularize("Categories").as_deref(), Some("Category"));
+        assert_eq!(singularize("Addresses").as_deref(), Some("Address"));
+        assert_eq!(singularize("Batches").as_deref(), Some("Batch"));
+        assert_eq!(singularize("SalesPeople").as_deref(), Some("SalesPerson"));
+        assert_eq!(singularize("line_items").as_deref(), Some("line_item"));
+
+        assert_eq!(singularize("Order"), None);
+        assert_eq!(singularize("Address"), None);
+        assert_eq!(singularize("OrderStatus"), None);
+        assert_eq!(singularize("Analysis"), None);
+        assert_eq!(singularize("News"), None);
+    }
+}
@@ -0,0 +1,156 @@
+//! Changing the severity of issues for one run.
+//!
+//! A project's `[lint]` configuration says how its warnings should be
+//! reported in general. A [`SeverityPolicy`] adjusts a validation result
+//! for a particular run instead, such as a CI job that fails on any
+//! warning, or a migration that reports an error as a warning while the
+//! model is being repaired. The CLI builds one from `--deny`, `--warn` and
+//! `--allow`; the WASM bindings accept the same policy as JSON.
+
+use crate::validation::{Severity, ValidationResult};
+use serde::{Deserialize, Serialize};
+
+/// Severity changes applied to a validation result.
+///
+/// Codes listed explicitly take precedence over `deny_warnings`, and
+/// `allow` over `deny` and `warn`. Hints are only changed when listed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeverityPolicy {
+    /// Report every warning as an error
+    #[serde(default)]
+    pub deny_warnings: bool,
+
+    /// Codes to report as errors
+    #[serde(default)]
+    pub deny: Vec<String>,
+
+    /// Codes to report as warnings, including errors
+    #[serde(default)]
+    pub warn: Vec<String>,
+
+    /// Codes not to report
+    #[serde(default)]
+    pub allow: Vec<String>,
+}
+
+impl SeverityPolicy {
+    /// Create a policy leaving every issue as it is.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Report every warning as an error.
+    pub fn deny_warnings(mut self) -> Self {
+        self.deny_warnings = true;
+        self
+    }
+
+    /// Report the issues of a code as errors.
+    pub fn deny(mut self, code: impl Into<String>) -> Self {
+        self.deny.push(code.into());
+        self
+    }
+
+    /// Report the issues of a code as warnings.
+    pub fn warn(mut self, code: impl Into<String>) -> Self {
+        self.warn.push(code.into());
+        self
+    }
+
+    /// Leave out the issues of a code.
+    pub fn allow(mut self, code: impl Into<String>) -> Self {
+        self.allow.push(code.into());
+        self
+    }
+
+    /// Get the severity an issue is reported with, or `None` if it is
+    /// left out.
+    pub fn severity(&self, code: &str, severity: Severity) -> Option<Severity> {
+        let listed = |codes: &[String]| codes.iter().any(|c| c == code);
+        if listed(&self.allow) {
+            None
+        } else if listed(&self.deny) {
+            Some(Severity::Error)
+        } else if listed(&self.warn) {
+            Some(Severity::Warning)
+        } else if self.deny_warnings && severity == Severity::Warning {
+            Some(Severity::Error)
+        } else {
+            Some(severity)
+        }
+    }
+}
+
+impl ValidationResult {
+    /// Apply a severity policy to the issues.
+    pub fn with_policy(mut self, policy: &SeverityPolicy) -> Self {
+        self.issues
+            .retain_mut(|issue| match policy.severity(&issue.code, issue.severity) {
+                Some(severity) => {
+                    issue.severity = severity;
+                    true
+                }
+                None => false,
+            });
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validation::ValidationError;
+
+    fn result() -> ValidationResult {
+        let mut result = ValidationResult::new();
+        result.add(ValidationError::error(
+            "E0020",
+            "Duplicate object name: 'Order'",
+        ));
+        result.add(ValidationError::warning(
+            "W0001",
+            "Aggregate 'Order' is large",
+        ));
+        result.add(ValidationError::warning(
+            "W0080",
+            "Object 'Note' is not used",
+        ));
+        result
+    }
+
+    fn severities(result: &ValidationResult) -> Vec<(&str, Severity)> {
+        result
+            .issues
+            .iter()
+            .map(|i| (i.code.as_str(), i.severity))
+            .collect()
+    }
+
+    #[test]
+    fn test_deny_warnings() {
+        let result = result().with_policy(&SeverityPolicy::new().deny_warnings().warn("W0080"));
+        assert_eq!(
+            severities(&result),
+            vec![
+                ("E0020", Severity::Error),
+                ("W0001", Severity::Error),
+                ("W0080", Severity::Warning),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_warn_and_allow_codes() {
+        let policy = SeverityPolicy::new().warn("E0020").allow("W0001");
+        let result = result().with_policy(&policy);
+        assert!(result.is_ok());
+        assert_eq!(
+            severities(&result),
+            vec![("E0020", Severity::Warning), ("W0080", Severity::Warning)]
+        );
+
+        let policy: SeverityPolicy =
+            serde_json::from_str(r#"{ "deny_warnings": true, "allow": ["W0080"] }"#).unwrap();
+        assert_eq!(policy, SeverityPolicy::new().deny_warnings().allow("W0080"));
+    }
+}
@@ -0,0 +1,138 @@
+//! Process managers (sagas) coordinating work across aggregates.
+//!
+//! A process manager reacts to domain events by issuing commands, which is
+//! how long-running workflows span several aggregates without breaking
+//! their consistency boundaries.
+//!
+//! Categorically, a process is a diagram in the context's sketch: each step
+//! `on E -> C` is an arrow of the indexing shape mapped onto the event object
+//! `E` and the command object `C`. The diagram does not add morphisms to the
+//! underlying graph, so it never changes the structure of the domain model.
+
+use crate::sketch::ObjectId;
+use serde::{Deserialize, Serialize};
+
+/// A single reaction of a process manager: on `event`, dispatch `command`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessStep {
+    /// The event object that triggers this step
+    pub event: ObjectId,
+
+    /// The command object dispatched in response
+    pub command: ObjectId,
+}
+
+/// A process manager (saga) modeled as a diagram over events and commands.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessManager {
+    /// Name of the process
+    pub name: String,
+
+    /// Steps in declaration order
+    pub steps: Vec<ProcessStep>,
+}
+
+impl ProcessManager {
+    /// Create a new process manager without any steps.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            steps: Vec::new(),
+        }
+    }
+
+    /// Add a step reacting to `event` by dispatching `command`.
+    pub fn add_step(&mut self, event: ObjectId, command: ObjectId) {
+        self.steps.push(ProcessStep { event, command });
+    }
+
+    /// Get the distinct events handled by this process, in declaration order.
+    pub fn events(&self) -> Vec<ObjectId> {
+        let mut events = Vec::new();
+        for step in &self.steps {
+            if !events.contains(&step.event) {
+                events.push(step.event);
+            }
+        }
+        events
+    }
+
+    /// Get the distinct commands dispatched by this process, in declaration order.
+    pub fn commands(&self) -> Vec<ObjectId> {
+        let mut commands = Vec::new();
+        for step in &self.steps {
+            if !commands.contains(&step.command) {
+                commands.push(step.command);
+            }
+        }
+        commands
+    }
+
+    /// Get all objects taking part in the diagram, in order of first appearance.
+    pub fn objects(&self) -> Vec<ObjectId> {
+        let mut objects = Vec::new();
+        for step in &self.steps {
+            for id in [step.event, step.command] {
+                if !objects.contains(&id) {
+                    objects.push(id);
+                }
+            }
+        }
+        objects
+    }
+
+    /// Get the commands dispatched in response to the given event.
+    pub fn commands_for(&self, event: ObjectId) -> Vec<ObjectId> {
+        self.steps
+            .iter()
+            .filter(|s| s.event == event)
+            .map(|s| s.command)
+            .collect()
+    }
+
+    /// Check if this process has no steps.
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sketch::Graph;
+
+    #[test]
+    fn test_process_steps() {
+        let mut graph = Graph::new();
+        let placed = graph.add_object("OrderPlaced");
+        let reserve = graph.add_object("ReserveStock");
+        let reserved = graph.add_object("StockReserved");
+        let ship = graph.add_object("Ship");
+
+        let mut process = ProcessManager::new("FulfillmentSaga");
+        process.add_step(placed, reserve);
+        process.add_step(reserved, ship);
+
+        assert_eq!(process.steps.len(), 2);
+        assert_eq!(process.events(), vec![placed, reserved]);
+        assert_eq!(process.commands(), vec![reserve, ship]);
+        assert_eq!(process.objects(), vec![placed, reserve, reserved, ship]);
+        assert!(!process.is_empty());
+    }
+
+    #[test]
+    fn test_commands_for_event() {
+        let mut graph = Graph::new();
+        let placed = graph.add_object("OrderPlaced");
+        let reserve = graph.add_object("ReserveStock");
+        let notify = graph.add_object("NotifyCustomer");
+
+        let mut process = ProcessManager::new("FulfillmentSaga");
+        process.add_step(placed, reserve);
+        process.add_step(placed, notify);
+
+        assert_eq!(process.events(), vec![placed]);
+        assert_eq!(process.commands_for(placed), vec![reserve, notify]);
+        assert!(process.commands_for(reserve).is_empty());
+    }
+}
@@ -0,0 +1,442 @@
+//! Queries over a whole model.
+//!
+//! Questions such as "where is this object used?" or "how does an order
+//! reach its customer?" span the graph of a
+//! context, its aggregates and the context maps of the workspace. A
+//! [`Query`], obtained from [`Workspace::query`], answers them by name, so
+//! that editors, lint rules and the command line can share one
+//! implementation.
+
+use crate::context::BoundedContext;
+use crate::mapping::RelationshipPattern;
+use crate::sketch::{LimitCone, Morphism, MorphismId, ObjectId};
+use crate::workspace::Workspace;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+/// A place where an object is referenced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Usage {
+    /// A morphism of the context from or to the object
+    Morphism { morphism: MorphismId, name: String },
+
+    /// The object is the root of an aggregate
+    AggregateRoot { aggregate: String },
+
+    /// The object is a member of an aggregate
+    AggregateMember { aggregate: String },
+
+    /// A context map maps the object to or from an object of another context
+    ContextMap { map: String, counterpart: String },
+}
+
+/// The value objects an aggregate depends on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AggregateDependencies {
+    /// Name of the context
+    pub context: String,
+
+    /// Name of the aggregate
+    pub aggregate: String,
+
+    /// Names of the value objects used by the aggregate, sorted
+    pub value_objects: Vec<String>,
+}
+
+/// Read-only queries over a workspace.
+#[derive(Debug, Clone, Copy)]
+pub struct Query<'a> {
+    workspace: &'a Workspace,
+}
+
+impl<'a> Query<'a> {
+    /// Create a query over a workspace.
+    pub fn new(workspace: &'a Workspace) -> Self {
+        Self { workspace }
+    }
+
+    /// Get the morphisms of a context leaving an object, in the order they
+    /// were declared.
+    ///
+    /// Identity morphisms and the projections of aggregates are left out.
+    /// Returns nothing if the context or object is unknown.
+    pub fn morphisms_from(&self, context: &str, object: &str) -> Vec<&'a Morphism> {
+        self.morphisms_of(context, object, |m, id| m.source == id)
+    }
+
+    /// Get the morphisms of a context arriving at an object, in the order
+    /// they were declared.
+    ///
+    /// Identity morphisms and the projections of aggregates are left out.
+    /// Returns nothing if the context or object is unknown.
+    pub fn morphisms_to(&self, context: &str, object: &str) -> Vec<&'a Morphism> {
+        self.morphisms_of(context, object, |m, id| m.target == id)
+    }
+
+    /// The morphisms of a context touching an object, but identities and
+    /// projections.
+    fn morphisms_of(
+        &self,
+        context: &str,
+        object: &str,
+        touches: impl Fn(&Morphism, ObjectId) -> bool,
+    ) -> Vec<&'a Morphism> {
+        let Some(ctx) = self.workspace.context(context) else {
+            return Vec::new();
+        };
+        let Some(id) = ctx.graph().find_object_by_name(object).map(|o| o.id) else {
+            return Vec::new();
+        };
+
+        let projections: BTreeSet<MorphismId> = aggregates(ctx)
+            .flat_map(|cone| cone.projections.iter().map(|p| p.morphism))
+            .collect();
+        let mut morphisms: Vec<_> = ctx
+            .graph()
+            .morphisms()
+            .filter(|m| !m.is_identity && !projections.contains(&m.id))
+            .filter(|m| touches(m, id))
+            .collect();
+        morphisms.sort_by_key(|m| m.id);
+        morphisms
+    }
+
+    /// Find a shortest chain of morphisms of a context leading from one
+    /// object to another.
+    ///
+    /// Every morphism but identities is followed, including the projections
+    /// from aggregate roots to their members. Among chains of the same
+    /// length, the one of the earliest declared morphisms is chosen; an
+    /// object reaches itself by the empty chain. Returns `None` if the
+    /// context or an object is unknown, or no chain exists.
+    pub fn path(&self, context: &str, source: &str, target: &str) -> Option<Vec<&'a Morphism>> {
+        let graph = self.workspace.context(context)?.graph();
+        let source = graph.find_object_by_name(source)?.id;
+        let target = graph.find_object_by_name(target)?.id;
+
+        let mut outgoing: HashMap<ObjectId, Vec<&'a Morphism>> = HashMap::new();
+        for morphism in graph.morphisms().filter(|m| !m.is_identity) {
+            outgoing.entry(morphism.source).or_default().push(morphism);
+        }
+        for morphisms in outgoing.values_mut() {
+            morphisms.sort_by_key(|m| m.id);
+        }
+
+        // Breadth-first search, remembering the morphism each object was
+        // first reached by
+        let mut reached_by: HashMap<ObjectId, Option<&'a Morphism>> = HashMap::new();
+        reached_by.insert(source, None);
+        let mut queue = VecDeque::from([source]);
+        while let Some(object) = queue.pop_front() {
+            if object == target {
+                break;
+            }
+            for &morphism in outgoing.get(&object).into_iter().flatten() {
+                if let Entry::Vacant(entry) = reached_by.entry(morphism.target) {
+                    entry.insert(Some(morphism));
+                    queue.push_back(morphism.target);
+                }
+            }
+        }
+
+        let mut path = Vec::new();
+        let mut object = target;
+        while let Some(morphism) = *reached_by.get(&object)? {
+            path.push(morphism);
+            object = morphism.source;
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Find where an object of a context is referenced: by morphisms, by
+    /// aggregates, and by the object mappings of context maps.
+    ///
+    /// Identity morphisms and the projections of aggregates are not listed
+    /// as morphisms. Returns nothing if the context or object is unknown.
+    pub fn usages(&self, context: &str, object: &str) -> Vec<Usage> {
+        let Some(ctx) = self.workspace.context(context) else {
+            return Vec::new();
+        };
+        let Some(id) = ctx.graph().find_object_by_name(object).map(|o| o.id) else {
+            return Vec::new();
+        };
+
+        let projections: BTreeSet<MorphismId> = aggregates(ctx)
+            .flat_map(|cone| cone.projections.iter().map(|p| p.morphism))
+            .collect();
+        let mut morphisms: Vec<_> = ctx
+            .graph()
+            .morphisms()
+            .filter(|m| !m.is_identity && !projections.contains(&m.id))
+            .filter(|m| m.source == id || m.target == id)
+            .collect();
+        morphisms.sort_by_key(|m| m.id);
+
+        let mut usages: Vec<Usage> = morphisms
+            .into_iter()
+            .map(|m| Usage::Morphism {
+                morphism: m.id,
+                name: m.name.clone(),
+            })
+            .collect();
+
+        for cone in aggregates(ctx) {
+            if cone.root == Some(id) {
+                usages.push(Usage::AggregateRoot {
+                    aggregate: cone.name.clone(),
+                });
+            } else if cone.projections.iter().any(|p| p.target == id) {
+                usages.push(Usage::AggregateMember {
+                    aggregate: cone.name.clone(),
+                });
+            }
+        }
+
+        for map in &self.workspace.context_maps {
+            for mapping in &map.object_mappings {
+                let counterpart = if map.source_context == context && mapping.source == object {
+                    &mapping.target
+                } else if map.target_context == context && mapping.target == object {
+                    &mapping.source
+                } else {
+                    continue;
+                };
+                usages.push(Usage::ContextMap {
+                    map: map.name.clone(),
+                    counterpart: counterpart.clone(),
+                });
+            }
+        }
+
+        usages
+    }
+
+    /// List, for every aggregate of the workspace, the value objects it
+    /// depends on.
+    ///
+    /// An aggregate depends on the value objects its root and members point
+    /// at, and on the value objects those are made of in turn.
+    pub fn aggregate_dependencies(&self) -> Vec<AggregateDependencies> {
+        let mut dependencies = Vec::new();
+
+        for ctx in &self.workspace.contexts {
+            for cone in aggregates(ctx) {
+                let mut queue: Vec<ObjectId> = cone
+                    .root
+                    .into_iter()
+                    .chain(cone.projections.iter().map(|p| p.target))
+                    .collect();
+                let mut seen: BTreeSet<ObjectId> = queue.iter().copied().collect();
+                let mut value_objects = BTreeSet::new();
+
+                while let Some(object) = queue.pop() {
+                    for morphism in ctx.graph().outgoing_morphisms(object) {
+                        let target = morphism.target;
+                        if ctx.is_value_object(target) && seen.insert(target) {
+                            if let Some(vo) = ctx.graph().get_object(target) {
+                                value_objects.insert(vo.name.clone());
+                            }
+                            queue.push(target);
+                        }
+                    }
+                }
+
+                dependencies.push(AggregateDependencies {
+                    context: ctx.name().to_string(),
+                    aggregate: cone.name.clone(),
+                    value_objects: value_objects.into_iter().collect(),
+                });
+            }
+        }
+
+        dependencies
+    }
+
+    /// Get the names of the aggregates of a context depending on a value
+    /// object.
+    pub fn aggregates_using(&self, context: &str, value_object: &str) -> Vec<String> {
+        self.aggregate_dependencies()
+            .into_iter()
+            .filter(|d| d.context == context && d.value_objects.iter().any(|v| v == value_object))
+            .map(|d| d.aggregate)
+            .collect()
+    }
+
+    /// Get the contexts consuming a context: the downstream side of every
+    /// context map it is upstream of, except `SeparateWays`.
+    pub fn consumers_of(&self, context: &str) -> Vec<&'a str> {
+        let mut consumers: Vec<&'a str> = Vec::new();
+        for map in &self.workspace.context_maps {
+            if map.source_context == context
+                && map.pattern != RelationshipPattern::SeparateWays
+                && !consumers.contains(&map.target_context.as_str())
+            {
+                consumers.push(&map.target_context);
+            }
+        }
+        consumers
+    }
+}
+
+impl Workspace {
+    /// Query the workspace.
+    pub fn query(&self) -> Query<'_> {
+        Query::new(self)
+    }
+}
+
+/// The aggregate cones of a context.
+fn aggregates(ctx: &BoundedContext) -> impl Iterator<Item = &LimitCone> {
+    ctx.sketch().limits.iter().filter(|l| l.is_aggregate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapping::{NamedContextMap, NamedObjectMapping};
+
+    fn commerce() -> Workspace {
+        let mut sales = BoundedContext::new("Sales");
+        let order = sales.add_entity("Order");
+        let line_item = sales.add_entity("LineItem");
+        let customer = sales.add_entity("Customer");
+        let money = sales.add_value_object("Money");
+        let currency = sales.add_value_object("Currency");
+        let address = sales.add_value_object("Address");
+        sales.sketch_mut().add_morphism("placedBy", order, customer);
+        sales.sketch_mut().add_morphism("price", line_item, money);
+        sales.sketch_mut().add_morphism("currency", money, currency);
+        sales
+            .sketch_mut()
+            .add_morphism("address", customer, address);
+        sales.define_aggregate_with_members("Order", order, &[line_item]);
+
+        let mut workspace = Workspace::new();
+        workspace.add_context(sales);
+        workspace.add_context(BoundedContext::new("Shipping"));
+        workspace.add_context(BoundedContext::new("Archive"));
+
+        let mut shipping = NamedContextMap::new(
+            "SalesToShipping",
+            "Sales",
+            "Shipping",
+            RelationshipPattern::CustomerSupplier,
+        );
+        shipping.add_object_mapping(NamedObjectMapping {
+            source: "Order".into(),
+            target: "Shipment".into(),
+            description: None,
+        });
+        workspace.add_context_map(shipping);
+        workspace.add_context_map(NamedContextMap::new(
+            "SalesToArchive",
+            "Sales",
+            "Archive",
+            RelationshipPattern::SeparateWays,
+        ));
+        workspace
+    }
+
+    #[test]
+    fn test_usages() {
+        let workspace = commerce();
+        let query = workspace.query();
+
+        let usages = query.usages("Sales", "Order");
+        assert!(matches!(&usages[0], Usage::Morphism { name, .. } if name == "placedBy"));
+        assert_eq!(
+            usages[1..],
+            [
+                Usage::AggregateRoot {
+                    aggregate: "Order".into()
+                },
+                Usage::ContextMap {
+                    map: "SalesToShipping".into(),
+                    counterpart: "Shipment".into()
+                },
+            ]
+        );
+
+        let usages = query.usages("Sales", "LineItem");
+        assert_eq!(usages.len(), 2);
+        assert_eq!(
+            usages[1],
+            Usage::AggregateMember {
+                aggregate: "Order".into()
+            }
+        );
+
+        assert!(query.usages("Sales", "Invoice").is_empty());
+        assert!(query.usages("Billing", "Order").is_empty());
+    }
+
+    #[test]
+    fn test_morphisms_from_and_to() {
+        let workspace = commerce();
+        let query = workspace.query();
+
+        let names = |morphisms: Vec<&Morphism>| -> Vec<String> {
+            morphisms.into_iter().map(|m| m.name.clone()).collect()
+        };
+        // The projection to LineItem belongs to the aggregate
+        assert_eq!(names(query.morphisms_from("Sales", "Order")), ["placedBy"]);
+        assert_eq!(names(query.morphisms_to("Sales", "Money")), ["price"]);
+        assert_eq!(names(query.morphisms_from("Sales", "Money")), ["currency"]);
+        assert!(query.morphisms_to("Sales", "Order").is_empty());
+        assert!(query.morphisms_from("Sales", "Invoice").is_empty());
+        assert!(query.morphisms_from("Billing", "Order").is_empty());
+    }
+
+    #[test]
+    fn test_path() {
+        let workspace = commerce();
+        let query = workspace.query();
+
+        let names = |path: Option<Vec<&Morphism>>| -> Option<Vec<String>> {
+            path.map(|p| p.into_iter().map(|m| m.name.clone()).collect())
+        };
+        assert_eq!(
+            names(query.path("Sales", "Order", "Address")),
+            Some(vec!["placedBy".into(), "address".into()])
+        );
+        assert_eq!(
+            names(query.path("Sales", "Order", "Currency")),
+            Some(vec![
+                "Order_LineItem".into(),
+                "price".into(),
+                "currency".into()
+            ])
+        );
+        assert_eq!(names(query.path("Sales", "Order", "Order")), Some(vec![]));
+        assert_eq!(names(query.path("Sales", "Customer", "Order")), None);
+        assert_eq!(names(query.path("Sales", "Order", "Invoice")), None);
+    }
+
+    #[test]
+    fn test_aggregate_dependencies() {
+        let workspace = commerce();
+        let query = workspace.query();
+
+        // Address belongs to Customer, which is outside the aggregate
+        assert_eq!(
+            query.aggregate_dependencies(),
+            vec![AggregateDependencies {
+                context: "Sales".into(),
+                aggregate: "Order".into(),
+                value_objects: vec!["Currency".into(), "Money".into()],
+            }]
+        );
+        assert_eq!(query.aggregates_using("Sales", "Currency"), vec!["Order"]);
+        assert!(query.aggregates_using("Sales", "Address").is_empty());
+    }
+
+    #[test]
+    fn test_consumers_of() {
+        let workspace = commerce();
+        let query = workspace.query();
+
+        assert_eq!(query.consumers_of("Sales"), vec!["Shipping"]);
+        assert!(query.consumers_of("Shipping").is_empty());
+    }
+}
@@ -0,0 +1,363 @@
+//! Refactoring operations over bounded contexts and context maps.
+//!
+//! Objects and morphisms are referenced by id inside a context, so most
+//! structure survives a refactoring untouched. What these operations keep
+//! consistent are the places that refer to elements by name: expressions,
+//! field constraints, cones named after their apex, and context maps.
+
+use crate::context::BoundedContext;
+use crate::mapping::{ContextMap, NamedContextMap, RelationshipPattern};
+use crate::sketch::{MorphismId, ObjectId};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// Errors from renaming an element of a model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenameError {
+    /// The object does not exist in the context
+    UnknownObject(ObjectId),
+
+    /// The morphism does not exist in the context
+    UnknownMorphism(MorphismId),
+
+    /// No context or element has the given name
+    NotFound(String),
+
+    /// The new name is empty
+    EmptyName,
+
+    /// Another element already uses the new name
+    NameTaken(String),
+
+    /// The object is not the root of an aggregate
+    NotAnAggregate(ObjectId),
+}
+
+impl fmt::Display for RenameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenameError::UnknownObject(id) => write!(f, "Object {:?} does not exist", id),
+            RenameError::UnknownMorphism(id) => write!(f, "Morphism {:?} does not exist", id),
+            RenameError::NotFound(name) => write!(f, "'{}' not found", name),
+            RenameError::EmptyName => write!(f, "The new name is empty"),
+            RenameError::NameTaken(name) => write!(f, "The name '{}' is already taken", name),
+            RenameError::NotAnAggregate(id) => {
+                write!(f, "Object {:?} is not the root of an aggregate", id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RenameError {}
+
+/// Errors from restructuring the aggregates of a context.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AggregateError {
+    /// The object is not the root of an aggregate
+    NotAnAggregate(ObjectId),
+
+    /// The object is not a member of the aggregate
+    NotAMember { aggregate: String, object: ObjectId },
+
+    /// An aggregate cannot be merged with itself
+    SameAggregate(ObjectId),
+}
+
+impl fmt::Display for AggregateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AggregateError::NotAnAggregate(id) => {
+                write!(f, "Object {:?} is not the root of an aggregate", id)
+            }
+            AggregateError::NotAMember { aggregate, object } => {
+                write!(f, "Object {:?} is not a member of aggregate '{}'", object, aggregate)
+            }
+            AggregateError::SameAggregate(id) => {
+                write!(f, "Aggregate {:?} cannot be merged with itself", id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AggregateError {}
+
+/// Rename an object of a context and every context map mapping it.
+///
+/// Maps out of the context are updated on their source side, maps into it
+/// on their target side.
+pub fn rename_object_in_model(
+    contexts: &mut [BoundedContext],
+    context_maps: &mut [NamedContextMap],
+    context: &str,
+    old_name: &str,
+    new_name: &str,
+) -> Result<(), RenameError> {
+    let ctx = contexts
+        .iter_mut()
+        .find(|c| c.name() == context)
+        .ok_or_else(|| RenameError::NotFound(context.to_string()))?;
+    let object = ctx
+        .graph()
+        .find_object_by_name(old_name)
+        .ok_or_else(|| RenameError::NotFound(format!("{}.{}", context, old_name)))?
+        .id;
+    ctx.rename_object(object, new_name)?;
+    rename_object_mappings(context_maps, context, old_name, new_name);
+    Ok(())
+}
+
+/// Rename an object of a context in the context maps mapping it.
+pub(crate) fn rename_object_mappings(
+    context_maps: &mut [NamedContextMap],
+    context: &str,
+    old_name: &str,
+    new_name: &str,
+) {
+    for map in context_maps.iter_mut() {
+        for mapping in &mut map.object_mappings {
+            if map.source_context == context && mapping.source == old_name {
+                mapping.source = new_name.to_string();
+            }
+            if map.target_context == context && mapping.target == old_name {
+                mapping.target = new_name.to_string();
+            }
+        }
+    }
+}
+
+/// Rename a morphism of a context and every context map mapping it.
+///
+/// The morphism is looked up by name, so the name must identify a single
+/// morphism of the context.
+pub fn rename_morphism_in_model(
+    contexts: &mut [BoundedContext],
+    context_maps: &mut [NamedContextMap],
+    context: &str,
+    old_name: &str,
+    new_name: &str,
+) -> Result<(), RenameError> {
+    let ctx = contexts
+        .iter_mut()
+        .find(|c| c.name() == context)
+        .ok_or_else(|| RenameError::NotFound(context.to_string()))?;
+    let candidates: Vec<MorphismId> = ctx
+        .graph()
+        .morphisms()
+        .filter(|m| !m.is_identity && m.name == old_name)
+        .map(|m| m.id)
+        .collect();
+    let [morphism] = candidates[..] else {
+        return Err(RenameError::NotFound(format!("{}.{}", context, old_name)));
+    };
+    ctx.rename_morphism(morphism, new_name)?;
+    rename_morphism_mappings(context_maps, context, old_name, new_name);
+    Ok(())
+}
+
+/// Rename a morphism of a context in the context maps mapping it.
+pub(crate) fn rename_morphism_mappings(
+    context_maps: &mut [NamedContextMap],
+    context: &str,
+    old_name: &str,
+    new_name: &str,
+) {
+    for map in context_maps.iter_mut() {
+        for mapping in &mut map.morphism_mappings {
+            if map.source_context == context && mapping.source == old_name {
+                mapping.source = new_name.to_string();
+            }
+            if map.target_context == context && mapping.target == old_name {
+                mapping.target = new_name.to_string();
+            }
+        }
+    }
+}
+
+/// The result of extracting objects into a new bounded context.
+#[derive(Debug, Clone)]
+pub struct ContextExtraction {
+    /// The original context without the extracted objects
+    pub remaining: BoundedContext,
+
+    /// The new context holding the extracted objects
+    pub extracted: BoundedContext,
+
+    /// Map from the remaining context to the extracted one
+    pub map: ContextMap,
+
+    /// Morphisms of the original context between an extracted and a
+    /// remaining object, in id order
+    pub cut: Vec<MorphismId>,
+}
+
+/// Move a set of objects out of a context into a new one.
+///
+/// Morphisms, cones, equations and the rest of the context's structure go
+/// with the objects they are about, keeping their ids. A morphism cut by
+/// the split stays in the context of its source, pointing at a plain copy
+/// of its target there; the returned map relates each copy to the object
+/// it stands for, so that both contexts still describe the relationship.
+/// Structure spanning both sides, such as an equation using a cut
+/// morphism, is dropped.
+pub fn extract_context(
+    ctx: &BoundedContext,
+    objects: &[ObjectId],
+    new_name: impl Into<String>,
+) -> ContextExtraction {
+    let moved: BTreeSet<ObjectId> = objects.iter().copied().collect();
+    let mut cut: Vec<(MorphismId, ObjectId, ObjectId)> = ctx
+        .graph()
+        .morphisms()
+        .filter(|m| moved.contains(&m.source) != moved.contains(&m.target))
+        .map(|m| (m.id, m.source, m.target))
+        .collect();
+    cut.sort();
+
+    let mut remaining = ctx.clone();
+    let mut extracted = ctx.clone();
+    extracted.sketch_mut().name = new_name.into();
+
+    // Point cut morphisms at copies of their targets on the source's side
+    let mut copies: [BTreeMap<ObjectId, ObjectId>; 2] = Default::default();
+    for &(morphism, source, target) in &cut {
+        let (side, copies) = if moved.contains(&source) {
+            (&mut extracted, &mut copies[1])
+        } else {
+            (&mut remaining, &mut copies[0])
+        };
+        let copy = *copies.entry(target).or_insert_with(|| {
+            let name = ctx.graph().get_object(target).map(|o| o.name.clone());
+            side.sketch_mut().add_object(name.unwrap_or_default())
+        });
+        if let Some(mut m) = side.sketch_mut().graph.get_morphism_mut(morphism) {
+            m.set_target(copy);
+        }
+    }
+
+    for object in ctx.graph().objects().map(|o| o.id) {
+        if moved.contains(&object) {
+            remaining.remove_object(object);
+        } else {
+            extracted.remove_object(object);
+        }
+    }
+
+    let mut map = ContextMap::new(
+        format!("{}To{}", remaining.name(), extracted.name()),
+        remaining.name(),
+        extracted.name(),
+        RelationshipPattern::CustomerSupplier,
+    );
+    for (&original, &copy) in &copies[0] {
+        map.map_object(copy, original);
+    }
+    for (&original, &copy) in &copies[1] {
+        map.map_object(original, copy);
+    }
+
+    ContextExtraction {
+        remaining,
+        extracted,
+        map,
+        cut: cut.into_iter().map(|(morphism, _, _)| morphism).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapping::{NamedMorphismMapping, NamedObjectMapping, RelationshipPattern};
+
+    #[test]
+    fn test_rename_in_model_updates_context_maps() {
+        let mut sales = BoundedContext::new("Sales");
+        let order = sales.add_entity("Order");
+        let customer = sales.add_entity("Customer");
+        sales.sketch_mut().add_morphism("placedBy", order, customer);
+
+        let mut billing = BoundedContext::new("Billing");
+        let invoice = billing.add_entity("Invoice");
+        let account = billing.add_entity("Customer");
+        billing
+            .sketch_mut()
+            .add_morphism("placedBy", invoice, account);
+
+        let mut map = NamedContextMap::new(
+            "SalesToBilling",
+            "Sales",
+            "Billing",
+            RelationshipPattern::CustomerSupplier,
+        );
+        map.add_object_mapping(NamedObjectMapping {
+            source: "Customer".into(),
+            target: "Customer".into(),
+            description: None,
+        });
+        map.add_morphism_mapping(NamedMorphismMapping {
+            source: "placedBy".into(),
+            target: "placedBy".into(),
+            description: None,
+        });
+
+        let mut contexts = vec![sales, billing];
+        let mut maps = vec![map];
+        rename_object_in_model(&mut contexts, &mut maps, "Billing", "Customer", "Account").unwrap();
+        rename_morphism_in_model(&mut contexts, &mut maps, "Sales", "placedBy", "orderedBy")
+            .unwrap();
+
+        let mapping = &maps[0].object_mappings[0];
+        assert_eq!(
+            (mapping.source.as_str(), mapping.target.as_str()),
+            ("Customer", "Account")
+        );
+        let mapping = &maps[0].morphism_mappings[0];
+        assert_eq!(
+            (mapping.source.as_str(), mapping.target.as_str()),
+            ("orderedBy", "placedBy")
+        );
+        assert!(contexts[1].graph().find_object_by_name("Account").is_some());
+
+        assert_eq!(
+            rename_object_in_model(&mut contexts, &mut maps, "Shipping", "Customer", "Client"),
+            Err(RenameError::NotFound("Shipping".into()))
+        );
+    }
+
+    #[test]
+    fn test_extract_context() {
+        let mut ctx = BoundedContext::new("Commerce");
+        let order = ctx.add_entity("Order");
+        let customer = ctx.add_entity("Customer");
+        let shipment = ctx.add_entity("Shipment");
+        let address = ctx.add_value_object("Address");
+        let placed_by = ctx.sketch_mut().add_morphism("placedBy", order, customer);
+        let order_of = ctx.sketch_mut().add_morphism("order", shipment, order);
+        let destination = ctx
+            .sketch_mut()
+            .add_morphism("destination", shipment, address);
+        ctx.define_aggregate("Shipment", shipment);
+
+        let split = extract_context(&ctx, &[shipment, address], "Shipping");
+
+        assert_eq!(split.cut, vec![order_of]);
+        assert_eq!(split.remaining.name(), "Commerce");
+        assert_eq!(split.extracted.name(), "Shipping");
+
+        let remaining = split.remaining.graph();
+        assert!(remaining.get_object(shipment).is_none());
+        assert!(remaining.get_morphism(placed_by).is_some());
+        assert!(remaining.get_morphism(order_of).is_none());
+
+        let extracted = split.extracted.graph();
+        assert!(extracted.get_object(order).is_none());
+        assert!(extracted.get_morphism(destination).is_some());
+        assert!(split.extracted.is_aggregate_root(shipment));
+        assert_eq!(split.extracted.value_objects(), &[address]);
+
+        // The cut morphism now points at a copy of Order in Shipping
+        let copy = extracted.get_morphism(order_of).unwrap().target;
+        assert_eq!(extracted.get_object(copy).unwrap().name, "Order");
+        assert_eq!(split.map.get_object_mapping(order), Some(copy));
+        assert!(crate::validation::validate_context(&split.extracted).is_ok());
+    }
+}
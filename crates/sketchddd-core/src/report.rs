@@ -0,0 +1,330 @@
+//! Machine-readable reports of validation results.
+//!
+//! `sketchddd check` prints issues for people by default. For tools it can
+//! print the [`JsonIssue`]s of a [`JsonReport`], whose shape is versioned
+//! and only changes with [`JSON_REPORT_VERSION`], as the diagnostics of its
+//! JSON output, or a SARIF 2.1.0 log, the format GitHub code scanning and
+//! most CI dashboards ingest.
+
+use crate::fix::Fix;
+use crate::lint::Registry;
+use crate::validation::{Severity, ValidationError, ValidationResult};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+
+/// Version of the [`JsonReport`] format.
+pub const JSON_REPORT_VERSION: u32 = 1;
+
+/// A validation result in the stable JSON format.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonReport {
+    /// Version of the format, see [`JSON_REPORT_VERSION`]
+    pub version: u32,
+
+    /// Model file that was checked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+
+    /// Number of issues of each severity
+    pub summary: JsonSummary,
+
+    /// Percentage of shared concepts the context maps cover, when the
+    /// coverage rule W0090 is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_map_coverage: Option<f64>,
+
+    /// The issues, in the order they were found
+    pub issues: Vec<JsonIssue>,
+}
+
+/// Number of issues of each severity in a [`JsonReport`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JsonSummary {
+    /// Number of errors
+    pub errors: usize,
+
+    /// Number of warnings
+    pub warnings: usize,
+
+    /// Number of hints
+    pub hints: usize,
+}
+
+/// An issue in a [`JsonReport`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonIssue {
+    /// Error or warning code, such as `W0001`
+    pub code: String,
+
+    /// `error`, `warning` or `hint`
+    pub severity: String,
+
+    /// Human-readable message
+    pub message: String,
+
+    /// File of the issue, when the model spans several files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+
+    /// Line of the issue, 1-indexed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+
+    /// Column of the issue, 1-indexed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<u32>,
+
+    /// Suggested fix, in words
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+
+    /// Machine-applicable fix, as edits to byte ranges of the file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fix: Option<Fix>,
+}
+
+/// Get the name of a severity in reports.
+fn severity_name(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Hint => "hint",
+    }
+}
+
+impl ValidationResult {
+    /// Convert the result to the stable JSON format, for issues found in
+    /// `file`.
+    pub fn to_json_report(&self, file: Option<&str>) -> JsonReport {
+        let count = |severity| {
+            self.issues
+                .iter()
+                .filter(|i| i.severity == severity)
+                .count()
+        };
+        JsonReport {
+            version: JSON_REPORT_VERSION,
+            file: file.map(str::to_string),
+            summary: JsonSummary {
+                errors: count(Severity::Error),
+                warnings: count(Severity::Warning),
+                hints: count(Severity::Hint),
+            },
+            context_map_coverage: None,
+            issues: self
+                .issues
+                .iter()
+                .map(|issue| JsonIssue {
+                    code: issue.code.clone(),
+                    severity: severity_name(issue.severity).to_string(),
+                    message: issue.message.clone(),
+                    file: issue.location.file.clone(),
+                    line: issue.location.line,
+                    column: issue.location.column,
+                    suggestion: issue.suggestion.clone(),
+                    fix: issue.fix.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Convert the result to a SARIF 2.1.0 log, for issues found in `file`
+    /// unless their location names another.
+    ///
+    /// Rules are described from `registry`; codes it does not hold, such as
+    /// errors, are listed by id only. Fixes become SARIF fixes replacing
+    /// byte ranges of the file.
+    pub fn to_sarif(&self, file: &str, registry: &Registry) -> Value {
+        let codes: BTreeSet<&str> = self.issues.iter().map(|i| i.code.as_str()).collect();
+        let rules: Vec<Value> = codes
+            .iter()
+            .map(|code| match registry.get(code) {
+                Some(rule) => json!({
+                    "id": code,
+                    "shortDescription": { "text": rule.description() },
+                }),
+                None => json!({ "id": code }),
+            })
+            .collect();
+        let results: Vec<Value> = self
+            .issues
+            .iter()
+            .map(|issue| sarif_result(issue, file, &codes))
+            .collect();
+
+        json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "sketchddd",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                        "rules": rules,
+                    }
+                },
+                "results": results,
+            }]
+        })
+    }
+}
+
+/// Convert an issue to a SARIF result.
+fn sarif_result(issue: &ValidationError, file: &str, codes: &BTreeSet<&str>) -> Value {
+    let level = match issue.severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Hint => "note",
+    };
+    let mut message = issue.message.clone();
+    if let Some(suggestion) = &issue.suggestion {
+        message.push_str(&format!(" ({})", suggestion));
+    }
+
+    let uri = issue.location.file.as_deref().unwrap_or(file);
+    let mut location = json!({ "artifactLocation": { "uri": uri } });
+    if let Some(line) = issue.location.line {
+        location["region"] = json!({ "startLine": line });
+        if let Some(column) = issue.location.column {
+            location["region"]["startColumn"] = json!(column);
+        }
+    }
+
+    let mut result = json!({
+        "ruleId": issue.code,
+        "ruleIndex": codes.iter().position(|c| *c == issue.code),
+        "level": level,
+        "message": { "text": message },
+        "locations": [{ "physicalLocation": location }],
+    });
+    if let Some(fix) = &issue.fix {
+        let replacements: Vec<Value> = fix
+            .edits
+            .iter()
+            .map(|edit| {
+                json!({
+                    "deletedRegion": {
+                        "byteOffset": edit.start,
+                        "byteLength": edit.end - edit.start,
+                    },
+                    "insertedContent": { "text": edit.new_text },
+                })
+            })
+            .collect();
+        result["fixes"] = json!([{
+            "description": { "text": fix.title },
+            "artifactChanges": [{
+                "artifactLocation": { "uri": uri },
+                "replacements": replacements,
+            }],
+        }]);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fix::TextEdit;
+    use crate::validation::SourceLocation;
+
+    fn result() -> ValidationResult {
+        let mut result = ValidationResult::new();
+        result.add(
+            ValidationError::error("E0020", "[Sales] Duplicate object name: 'Order'")
+                .with_location(SourceLocation {
+                    file: None,
+                    line: Some(2),
+                    column: Some(18),
+                })
+                .with_fix(Fix::new("Remove duplicate").with_edit(TextEdit::delete(30, 37))),
+        );
+        result.add(
+            ValidationError::warning("W0030", "Object 'line_item' is not named in PascalCase")
+                .with_suggestion("did you mean `LineItem`?"),
+        );
+        result
+    }
+
+    #[test]
+    fn test_json_report() {
+        let report = result().to_json_report(Some("sales.sddd"));
+        assert_eq!(report.version, JSON_REPORT_VERSION);
+        assert_eq!(
+            report.summary,
+            JsonSummary {
+                errors: 1,
+                warnings: 1,
+                hints: 0
+            }
+        );
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["file"], "sales.sddd");
+        assert_eq!(json["issues"][0]["severity"], "error");
+        assert_eq!(json["issues"][0]["line"], 2);
+        assert_eq!(json["issues"][0]["fix"]["edits"][0]["start"], 30);
+        assert_eq!(json["issues"][1]["suggestion"], "did you mean `LineItem`?");
+        assert!(json.get("context_map_coverage").is_none());
+        assert!(json["issues"][1].get("line").is_none());
+        assert!(json["issues"][0].get("file").is_none());
+    }
+
+    #[test]
+    fn test_reports_name_issue_files() {
+        let mut result = result();
+        result.issues[0].location.file = Some("model/sales.sddd".to_string());
+
+        let json = serde_json::to_value(result.to_json_report(Some("sketchddd.toml"))).unwrap();
+        assert_eq!(json["file"], "sketchddd.toml");
+        assert_eq!(json["issues"][0]["file"], "model/sales.sddd");
+
+        let sarif = result.to_sarif("sketchddd.toml", &Registry::builtin());
+        let results = &sarif["runs"][0]["results"];
+        let uri =
+            |i: usize| &results[i]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"];
+        assert_eq!(uri(0), "model/sales.sddd");
+        assert_eq!(uri(1), "sketchddd.toml");
+        assert_eq!(
+            results[0]["fixes"][0]["artifactChanges"][0]["artifactLocation"]["uri"],
+            "model/sales.sddd"
+        );
+    }
+
+    #[test]
+    fn test_sarif() {
+        let sarif = result().to_sarif("sales.sddd", &Registry::builtin());
+        assert_eq!(sarif["version"], "2.1.0");
+
+        let run = &sarif["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "sketchddd");
+        let rules = &run["tool"]["driver"]["rules"];
+        assert_eq!(rules[0]["id"], "E0020");
+        assert!(rules[0].get("shortDescription").is_none());
+        assert_eq!(
+            rules[1]["shortDescription"]["text"],
+            "Object name not in PascalCase"
+        );
+
+        let duplicate = &run["results"][0];
+        assert_eq!(duplicate["level"], "error");
+        assert_eq!(duplicate["ruleIndex"], 0);
+        let location = &duplicate["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "sales.sddd");
+        assert_eq!(location["region"]["startLine"], 2);
+        let replacement = &duplicate["fixes"][0]["artifactChanges"][0]["replacements"][0];
+        assert_eq!(replacement["deletedRegion"]["byteLength"], 7);
+
+        let naming = &run["results"][1];
+        assert_eq!(naming["level"], "warning");
+        assert!(naming["locations"][0]["physicalLocation"]
+            .get("region")
+            .is_none());
+        assert_eq!(
+            naming["message"]["text"],
+            "Object 'line_item' is not named in PascalCase (did you mean `LineItem`?)"
+        );
+    }
+}
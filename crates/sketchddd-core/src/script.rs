@@ -0,0 +1,403 @@
+//! Project-specific lint rules written as scripts.
+//!
+//! Organizations have policies no built-in rule knows about, such as "every
+//! aggregate has an owner". A [`ScriptRule`] is a lint rule whose check is a
+//! [Rhai](https://rhai.rs) script defining a `check` function. The function
+//! receives a read-only view of the model, see [`model_view`], and returns
+//! an array of issues, each either a message or a map with a `message` and
+//! an optional `suggestion`:
+//!
+//! ```rhai
+//! fn check(model) {
+//!     let issues = [];
+//!     for context in model.contexts {
+//!         for aggregate in context.aggregates {
+//!             if !("owner" in aggregate.meta) {
+//!                 issues.push(`Aggregate '${aggregate.name}' has no owner`);
+//!             }
+//!         }
+//!     }
+//!     issues
+//! }
+//! ```
+//!
+//! Scripts run sandboxed: they cannot touch files or the network, a script
+//! running too long or building too large a string, array or map is
+//! stopped, and what it prints is discarded rather than mixed into the
+//! output of the lint run. A script that fails reports the failure as an
+//! error of its rule rather than aborting the lint run.
+//!
+//! Requires the `scripting` feature.
+
+use crate::context::BoundedContext;
+use crate::lint::Rule;
+use crate::metadata::Metadata;
+use crate::sketch::ObjectId;
+use crate::validation::{Severity, ValidationError};
+use crate::workspace::Workspace;
+use rhai::{Dynamic, Engine, Scope, AST};
+use serde_json::{json, Value};
+use std::fmt;
+
+/// Operations a script may run per check before it is stopped.
+const MAX_OPERATIONS: u64 = 10_000_000;
+
+/// Largest string a script may build, in bytes.
+const MAX_STRING_SIZE: usize = 16 << 20;
+
+/// Most elements an array of a script may hold, counting those of arrays
+/// nested in it, the model's included.
+const MAX_ARRAY_SIZE: usize = 1_000_000;
+
+/// Most entries a map of a script may hold, counting those of maps nested
+/// in it, the model's included.
+const MAX_MAP_SIZE: usize = 1_000_000;
+
+/// A lint rule checked by a Rhai script.
+pub struct ScriptRule {
+    id: String,
+    description: String,
+    severity: Severity,
+    engine: Engine,
+    ast: AST,
+}
+
+/// Error compiling a rule script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptError {
+    /// Id of the rule
+    pub rule: String,
+
+    /// What is wrong with the script
+    pub message: String,
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rule '{}': {}", self.rule, self.message)
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+impl ScriptRule {
+    /// Compile a rule from its script.
+    ///
+    /// The script must define a `check` function taking the model.
+    pub fn new(
+        id: impl Into<String>,
+        description: impl Into<String>,
+        source: &str,
+    ) -> Result<Self, ScriptError> {
+        let id = id.into();
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.set_max_expr_depths(64, 64);
+        engine.set_max_string_size(MAX_STRING_SIZE);
+        engine.set_max_array_size(MAX_ARRAY_SIZE);
+        engine.set_max_map_size(MAX_MAP_SIZE);
+        engine.on_print(|_| {});
+        engine.on_debug(|_, _, _| {});
+        let error = |message: String| ScriptError {
+            rule: id.clone(),
+            message,
+        };
+        let ast = engine.compile(source).map_err(|e| error(e.to_string()))?;
+        if !ast
+            .iter_functions()
+            .any(|f| f.name == "check" && f.params.len() == 1)
+        {
+            return Err(error(
+                "script does not define 'fn check(model)'".to_string(),
+            ));
+        }
+        Ok(Self {
+            id,
+            description: description.into(),
+            severity: Severity::Warning,
+            engine,
+            ast,
+        })
+    }
+
+    /// Set the severity of the rule's issues unless configured otherwise.
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    /// Run the script, converting what it returns to issues.
+    fn run(&self, workspace: &Workspace) -> Result<Vec<ValidationError>, String> {
+        let model = rhai::serde::to_dynamic(model_view(workspace)).map_err(|e| e.to_string())?;
+        let returned: Dynamic = self
+            .engine
+            .call_fn(&mut Scope::new(), &self.ast, "check", (model,))
+            .map_err(|e| e.to_string())?;
+        let items = returned
+            .into_array()
+            .map_err(|t| format!("'check' returned {} instead of an array", t))?;
+        items.into_iter().map(|item| self.issue(item)).collect()
+    }
+
+    /// Convert an item returned by the script to an issue.
+    fn issue(&self, item: Dynamic) -> Result<ValidationError, String> {
+        let type_name = item.type_name();
+        if item.is_string() {
+            return Ok(ValidationError::warning(&self.id, item.to_string()));
+        }
+        let map = item
+            .try_cast::<rhai::Map>()
+            .ok_or_else(|| format!("issue is {} instead of a string or a map", type_name))?;
+        let message = map
+            .get("message")
+            .filter(|m| m.is_string())
+            .ok_or("issue map has no 'message' string")?;
+        let mut issue = ValidationError::warning(&self.id, message.to_string());
+        if let Some(suggestion) = map.get("suggestion").filter(|s| s.is_string()) {
+            issue = issue.with_suggestion(suggestion.to_string());
+        }
+        Ok(issue)
+    }
+}
+
+impl Rule for ScriptRule {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn default_severity(&self) -> Severity {
+        self.severity
+    }
+
+    fn check(&self, workspace: &Workspace) -> Vec<ValidationError> {
+        self.run(workspace).unwrap_or_else(|message| {
+            vec![ValidationError::error(
+                &self.id,
+                format!("Rule script '{}' failed: {}", self.id, message),
+            )]
+        })
+    }
+}
+
+impl fmt::Debug for ScriptRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScriptRule")
+            .field("id", &self.id)
+            .field("description", &self.description)
+            .field("severity", &self.severity)
+            .finish()
+    }
+}
+
+/// Build the view of a model that rule scripts receive.
+///
+/// ```text
+/// contexts: [{ name, classification, tags, meta,
+///              objects: [{ name, kind, classification, tags, meta }],
+///              morphisms: [{ name, source, target }],
+///              aggregates: [{ name, root, members, classification, tags, meta }] }]
+/// context_maps: [{ name, source, target, pattern }]
+/// ```
+///
+/// Elements refer to each other by name. An object's `kind` is `entity`,
+/// `value`, `enum` or `object`; `classification` is `core`, `supporting`,
+/// `generic` or unit when not declared.
+pub fn model_view(workspace: &Workspace) -> Value {
+    json!({
+        "contexts": workspace.contexts.iter().map(context_view).collect::<Vec<_>>(),
+        "context_maps": workspace
+            .context_maps
+            .iter()
+            .map(|map| json!({
+                "name": map.name(),
+                "source": map.source_context(),
+                "target": map.target_context(),
+                "pattern": map.pattern(),
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// Build the view of a context.
+fn context_view(context: &BoundedContext) -> Value {
+    let graph = context.graph();
+    let name = |id: ObjectId| graph.get_object(id).map(|o| o.name.as_str());
+    let objects: Vec<Value> = graph
+        .objects()
+        .map(|object| {
+            let kind = if context.is_entity(object.id) {
+                "entity"
+            } else if context.is_value_object(object.id) {
+                "value"
+            } else if context.get_enum_colimit(object.id).is_some() {
+                "enum"
+            } else {
+                "object"
+            };
+            with_metadata(
+                json!({ "name": object.name, "kind": kind }),
+                context.object_metadata(object.id),
+            )
+        })
+        .collect();
+    let morphisms: Vec<Value> = graph
+        .morphisms()
+        .map(|m| json!({ "name": m.name, "source": name(m.source), "target": name(m.target) }))
+        .collect();
+    let aggregates: Vec<Value> = context
+        .aggregate_roots()
+        .iter()
+        .filter_map(|&root| context.get_aggregate(root).map(|a| (root, a)))
+        .map(|(root, aggregate)| {
+            let members: Vec<_> = aggregate
+                .component_objects()
+                .filter(|&id| id != root)
+                .filter_map(name)
+                .collect();
+            with_metadata(
+                json!({ "name": aggregate.name, "root": name(root), "members": members }),
+                context.aggregate_metadata(root),
+            )
+        })
+        .collect();
+    with_metadata(
+        json!({
+            "name": context.name(),
+            "objects": objects,
+            "morphisms": morphisms,
+            "aggregates": aggregates,
+        }),
+        Some(context.metadata()),
+    )
+}
+
+/// Add the `classification`, `tags` and `meta` of an element to its view.
+fn with_metadata(mut view: Value, metadata: Option<&Metadata>) -> Value {
+    let metadata = metadata.cloned().unwrap_or_default();
+    view["classification"] = json!(metadata.classification.map(|c| c.to_string()));
+    view["tags"] = json!(metadata.tags);
+    view["meta"] = metadata
+        .values
+        .iter()
+        .map(|(key, value)| (key.clone(), json!(value.to_string())))
+        .collect::<serde_json::Map<_, _>>()
+        .into();
+    view
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lint::{LintConfig, Registry};
+    use crate::metadata::MetadataValue;
+
+    const OWNER_RULE: &str = r#"
+        fn check(model) {
+            let issues = [];
+            for context in model.contexts {
+                for aggregate in context.aggregates {
+                    if !("owner" in aggregate.meta) {
+                        issues.push(#{
+                            message: `Aggregate '${aggregate.name}' has no owner`,
+                            suggestion: "Add @meta(owner: \"team\")",
+                        });
+                    }
+                }
+            }
+            issues
+        }
+    "#;
+
+    fn model() -> Workspace {
+        let mut sales = BoundedContext::new("Sales");
+        for name in ["Order", "Quote"] {
+            let root = sales.add_entity(name);
+            sales.define_aggregate_with_members(name, root, &[]);
+        }
+        let order = sales.graph().find_object_by_name("Order").unwrap().id;
+        sales
+            .aggregate_metadata_mut(order)
+            .set("owner", MetadataValue::Text("sales-team".into()));
+
+        let mut workspace = Workspace::new();
+        workspace.add_context(sales);
+        workspace
+    }
+
+    #[test]
+    fn test_script_rule() {
+        let rule = ScriptRule::new("X0001", "Aggregate without owner", OWNER_RULE).unwrap();
+        let mut registry = Registry::builtin();
+        registry.register(rule);
+
+        let result = model().lint(&registry, &LintConfig::new());
+        let issues: Vec<_> = result.issues.iter().filter(|i| i.code == "X0001").collect();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].message, "Aggregate 'Quote' has no owner");
+        assert_eq!(issues[0].severity, Severity::Warning);
+        assert_eq!(
+            issues[0].suggestion.as_deref(),
+            Some("Add @meta(owner: \"team\")")
+        );
+
+        let view = model_view(&model());
+        assert_eq!(view["contexts"][0]["objects"][0]["kind"], "entity");
+        assert_eq!(view["contexts"][0]["aggregates"][0]["root"], "Order");
+    }
+
+    #[test]
+    fn test_script_errors() {
+        let error = ScriptRule::new("X0002", "Broken", "fn check(model) { let }").unwrap_err();
+        assert_eq!(error.rule, "X0002");
+        assert!(ScriptRule::new("X0002", "No check", "fn verify(model) { [] }").is_err());
+
+        let runaway = ScriptRule::new("X0003", "Runaway", "fn check(model) { loop {} }").unwrap();
+        let issues = runaway.check(&model());
+        assert_eq!(issues[0].severity, Severity::Error);
+        assert!(issues[0].message.starts_with("Rule script 'X0003' failed:"));
+
+        let sandboxed = ScriptRule::new("X0004", "Files", r#"fn check(model) { open("x") }"#);
+        assert!(sandboxed.unwrap().check(&model())[0]
+            .message
+            .contains("Function not found: open"));
+    }
+
+    #[test]
+    fn test_script_size_limits() {
+        let scripts = [
+            r#"fn check(model) { let s = "x"; loop { s += s; } }"#,
+            "fn check(model) { let a = [0]; loop { a += a; } }",
+            "fn check(model) { let m = #{}; loop { m = #{ left: m, right: m }; } }",
+        ];
+        for script in scripts {
+            let rule = ScriptRule::new("X0005", "Growing", script).unwrap();
+            let issues = rule.check(&model());
+            assert_eq!(issues.len(), 1, "{}", script);
+            assert_eq!(issues[0].severity, Severity::Error);
+            assert!(
+                issues[0].message.contains("too large"),
+                "{}",
+                issues[0].message
+            );
+        }
+    }
+
+    #[test]
+    fn test_script_output_is_discarded() {
+        let source = r#"
+            fn check(model) {
+                print("checking");
+                debug(model.contexts.len());
+                ["Printed and checked"]
+            }
+        "#;
+        let rule = ScriptRule::new("X0006", "Chatty", source).unwrap();
+        let issues = rule.check(&model());
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].message, "Printed and checked");
+    }
+}
@@ -1,6 +1,6 @@
 //! Colimit cocones for sum types and enumerations.
 
-use super::ObjectId;
+use super::{MorphismId, ObjectId};
 use serde::{Deserialize, Serialize};
 
 /// An injection from a variant into the colimit.
@@ -11,6 +11,10 @@ pub struct Injection {
 
     /// The source object (variant type)
     pub source: ObjectId,
+
+    /// The morphism representing this injection, if the variant has one
+    #[serde(default)]
+    pub morphism: Option<MorphismId>,
 }
 
 /// A colimit cocone representing a sum type or enumeration.
@@ -46,6 +50,21 @@ impl ColimitCocone {
         self.injections.push(Injection {
             name: name.into(),
             source,
+            morphism: None,
+        });
+    }
+
+    /// Add a variant whose injection is a morphism of the graph.
+    pub fn add_injection(
+        &mut self,
+        name: impl Into<String>,
+        morphism: MorphismId,
+        source: ObjectId,
+    ) {
+        self.injections.push(Injection {
+            name: name.into(),
+            source,
+            morphism: Some(morphism),
         });
     }
 
@@ -61,6 +80,7 @@ impl ColimitCocone {
             cocone.injections.push(Injection {
                 name: variant,
                 source: apex,
+                morphism: None,
             });
         }
         cocone
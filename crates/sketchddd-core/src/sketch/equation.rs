@@ -65,6 +65,11 @@ pub struct PathEquation {
 
     /// Right-hand side path
     pub rhs: Path,
+
+    /// Whether the rule may be violated temporarily and restored
+    /// asynchronously, so that it may span aggregates
+    #[serde(default)]
+    pub eventual: bool,
 }
 
 impl PathEquation {
@@ -74,9 +79,16 @@ impl PathEquation {
             name: name.into(),
             lhs,
             rhs,
+            eventual: false,
         }
     }
 
+    /// Mark the equation as eventually consistent.
+    pub fn eventual(mut self) -> Self {
+        self.eventual = true;
+        self
+    }
+
     /// Check if the equation is well-formed (same source and target).
     pub fn is_well_formed(&self) -> bool {
         self.lhs.source == self.rhs.source && self.lhs.target == self.rhs.target
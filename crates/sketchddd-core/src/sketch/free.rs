@@ -0,0 +1,160 @@
+//! The path category presented by a sketch, up to a bound.
+//!
+//! The morphisms of the category presented by a sketch are the paths of its
+//! graph, with paths identified when the equations make them equal. There
+//! are infinitely many paths as soon as the graph has a cycle, so
+//! [`FreeCategory`] materializes only the paths of at most a given length,
+//! keeping one representative per equivalence class. That is enough to ask
+//! which ways there are to get from `Order` to `Money`, or whether two
+//! navigations the model allows have been stated equal.
+
+use super::{MorphismId, ObjectId, Path, RewriteSystem, Sketch};
+use std::collections::BTreeMap;
+
+/// The morphisms of a sketch's path category of at most `max_len` steps.
+#[derive(Debug, Clone)]
+pub struct FreeCategory {
+    max_len: usize,
+    exact: bool,
+    homs: BTreeMap<(ObjectId, ObjectId), Vec<Path>>,
+}
+
+impl FreeCategory {
+    /// Materialize every path of at most `max_len` morphisms, quotiented by
+    /// the equations of the sketch.
+    ///
+    /// Identity morphisms of the graph are left out, since they equal the
+    /// empty path. Within a hom-set, the representative of a class is its
+    /// shortest path, ties broken by morphism ids.
+    pub fn new(sketch: &Sketch, max_len: usize) -> Self {
+        let graph = &sketch.graph;
+        let rewrite = RewriteSystem::new(graph, &sketch.equations);
+
+        let mut outgoing: BTreeMap<ObjectId, Vec<(MorphismId, ObjectId)>> = BTreeMap::new();
+        for morphism in graph.morphisms().filter(|m| !m.is_identity) {
+            outgoing
+                .entry(morphism.source)
+                .or_default()
+                .push((morphism.id, morphism.target));
+        }
+        for edges in outgoing.values_mut() {
+            edges.sort();
+        }
+
+        // Breadth-first, so paths arrive in shortlex order per hom-set
+        let mut objects: Vec<ObjectId> = graph.objects().map(|o| o.id).collect();
+        objects.sort();
+        let mut frontier: Vec<Path> = objects.into_iter().map(Path::identity).collect();
+        let mut homs: BTreeMap<(ObjectId, ObjectId), Vec<Path>> = BTreeMap::new();
+
+        for length in 0..=max_len {
+            let mut next = Vec::new();
+            for path in frontier {
+                if length < max_len {
+                    for &(morphism, target) in outgoing.get(&path.target).into_iter().flatten() {
+                        let mut morphisms = path.morphisms.clone();
+                        morphisms.push(morphism);
+                        next.push(Path::new(path.source, target, morphisms));
+                    }
+                }
+
+                let hom = homs.entry((path.source, path.target)).or_default();
+                if !hom.iter().any(|p| rewrite.equal(p, &path)) {
+                    hom.push(path);
+                }
+            }
+            frontier = next;
+        }
+
+        Self {
+            max_len,
+            exact: rewrite.is_complete(),
+            homs,
+        }
+    }
+
+    /// Get the bound on path length.
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    /// Check if paths were identified by an exact decision procedure.
+    ///
+    /// When completion of the equations fails, two paths are only
+    /// identified if a bounded search connects them, so a hom-set may list
+    /// equal paths separately.
+    pub fn is_exact(&self) -> bool {
+        self.exact
+    }
+
+    /// Get the morphisms from `source` to `target`, one path per class.
+    pub fn hom(&self, source: ObjectId, target: ObjectId) -> &[Path] {
+        self.homs
+            .get(&(source, target))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Iterate over every materialized morphism, identities included.
+    pub fn morphisms(&self) -> impl Iterator<Item = &Path> {
+        self.homs.values().flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sketch::PathEquation;
+
+    #[test]
+    fn test_hom_sets() {
+        let mut sketch = Sketch::new("Commerce");
+        let order = sketch.add_object("Order");
+        let line_item = sketch.add_object("LineItem");
+        let money = sketch.add_object("Money");
+        let first_item = sketch.add_morphism("firstItem", order, line_item);
+        let price = sketch.add_morphism("price", line_item, money);
+        let total = sketch.add_morphism("total", order, money);
+        sketch.graph.add_identity_morphism(order);
+
+        let free = sketch.free_category(3);
+        assert_eq!(free.hom(order, order), [Path::identity(order)]);
+        let hom: Vec<_> = free
+            .hom(order, money)
+            .iter()
+            .map(|p| p.morphisms.clone())
+            .collect();
+        assert_eq!(hom, vec![vec![total], vec![first_item, price]]);
+        assert!(free.hom(money, order).is_empty());
+        assert_eq!(free.morphisms().count(), 3 + 3 + 1);
+
+        // Stating the two paths equal leaves a single morphism
+        sketch.add_equation(PathEquation::new(
+            "totalIsFirstPrice",
+            Path::new(order, money, vec![first_item, price]),
+            Path::new(order, money, vec![total]),
+        ));
+        let free = sketch.free_category(3);
+        assert!(free.is_exact());
+        assert_eq!(
+            free.hom(order, money),
+            [Path::new(order, money, vec![total])]
+        );
+    }
+
+    #[test]
+    fn test_cycles_are_bounded() {
+        let mut sketch = Sketch::new("Org");
+        let employee = sketch.add_object("Employee");
+        let manager = sketch.add_morphism("manager", employee, employee);
+
+        let free = sketch.free_category(3);
+        assert_eq!(free.max_len(), 3);
+        let lengths: Vec<_> = free.hom(employee, employee).iter().map(Path::len).collect();
+        assert_eq!(lengths, vec![0, 1, 2, 3]);
+        assert_eq!(
+            free.hom(employee, employee)[2].morphisms,
+            vec![manager, manager]
+        );
+    }
+}
@@ -1,22 +1,84 @@
 //! Graph structures for representing objects and morphisms.
 
-use serde::{Deserialize, Serialize};
+use super::Path;
+use serde::de::{self, MapAccess, Visitor};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Borrow;
 use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::ops::Deref;
 
 /// Unique identifier for an object in the graph.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct ObjectId(pub(crate) u32);
 
 /// Unique identifier for a morphism in the graph.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct MorphismId(pub(crate) u32);
 
+/// An identifier for an object or morphism that is stable across parses.
+///
+/// [`ObjectId`]s and [`MorphismId`]s are counters that depend on the order
+/// elements were added. A stable id is instead derived from the element's
+/// content when it is added: an object's name, or a morphism's source and
+/// name. Parsing the same model twice gives the same stable ids, and an
+/// element keeps its stable id when renamed. Serialized as a hex string.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct StableId(u64);
+
+impl StableId {
+    /// Derive an id from content with 64-bit FNV-1a, which unlike the
+    /// standard library's hashers is fixed across platforms and releases.
+    pub fn from_content(content: &str) -> Self {
+        let hash = content.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+        Self(hash)
+    }
+
+    /// Check if the id was never assigned, as in data serialized before
+    /// stable ids existed.
+    pub fn is_unassigned(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl fmt::Display for StableId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl From<StableId> for String {
+    fn from(id: StableId) -> Self {
+        id.to_string()
+    }
+}
+
+impl TryFrom<String> for StableId {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        u64::from_str_radix(&s, 16)
+            .map(StableId)
+            .map_err(|_| format!("Invalid stable id '{}'", s))
+    }
+}
+
 /// An object (node) in the graph, representing a domain concept.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Object {
     /// Unique identifier
     pub id: ObjectId,
 
+    /// Identifier that is stable across parses
+    #[serde(default)]
+    pub(crate) uid: StableId,
+
     /// Name of the object (e.g., "Customer", "Order")
     pub name: String,
 
@@ -30,6 +92,10 @@ pub struct Morphism {
     /// Unique identifier
     pub id: MorphismId,
 
+    /// Identifier that is stable across parses
+    #[serde(default)]
+    pub(crate) uid: StableId,
+
     /// Name of the morphism (e.g., "placedBy", "items")
     pub name: String,
 
@@ -46,15 +112,372 @@ pub struct Morphism {
     pub is_identity: bool,
 }
 
+/// The composite of a sequence of composable morphisms.
+///
+/// Produced by [`Graph::compose`]. The composite exists in every category
+/// presented by the graph, but only becomes an edge of the graph once it
+/// is materialized with [`Graph::add_composite`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComposedMorphism {
+    /// Source of the first morphism
+    pub source: ObjectId,
+
+    /// Target of the last morphism
+    pub target: ObjectId,
+
+    /// The composed morphisms, in the order they are followed
+    pub morphisms: Vec<MorphismId>,
+
+    /// Dotted name of the composite, e.g. `customer.address`, skipping
+    /// identity morphisms
+    pub name: String,
+}
+
+impl ComposedMorphism {
+    /// Convert the composite into a path, e.g. for use in an equation.
+    pub fn to_path(&self) -> Path {
+        Path::new(self.source, self.target, self.morphisms.clone())
+    }
+}
+
+/// Errors that can occur when composing morphisms.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ComposeError {
+    /// The path contains no morphisms, so its endpoints are unknown.
+    EmptyPath,
+
+    /// A morphism in the path does not exist in the graph.
+    UnknownMorphism { position: usize, morphism: MorphismId },
+
+    /// A morphism does not start where the previous one ends.
+    NotComposable {
+        position: usize,
+        previous: String,
+        next: String,
+    },
+}
+
+impl fmt::Display for ComposeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComposeError::EmptyPath => write!(f, "Cannot compose an empty path"),
+            ComposeError::UnknownMorphism { position, morphism } => {
+                write!(
+                    f,
+                    "Morphism {:?} at position {} does not exist",
+                    morphism, position
+                )
+            }
+            ComposeError::NotComposable {
+                position,
+                previous,
+                next,
+            } => {
+                write!(
+                    f,
+                    "Morphism '{}' at position {} does not start where '{}' ends",
+                    next, position, previous
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ComposeError {}
+
+impl Object {
+    /// Get the identifier that is stable across parses.
+    pub fn uid(&self) -> StableId {
+        self.uid
+    }
+}
+
+impl Morphism {
+    /// Get the identifier that is stable across parses.
+    pub fn uid(&self) -> StableId {
+        self.uid
+    }
+}
+
 /// A directed graph of objects and morphisms.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+///
+/// Objects and morphisms are stored in arenas indexed by their ids: ids
+/// are handed out in order and never reused, so the slot of an element is
+/// its id, and the slot of a removed element stays empty.
+///
+/// Lookups by name and by the ends of morphisms go through indices, which
+/// every change to the graph keeps up to date, including changes made
+/// through the setters of [`ObjectMut`] and [`MorphismMut`].
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(try_from = "GraphData")]
 pub struct Graph {
+    /// Objects by id, `None` where one was removed
+    objects: Vec<Option<Object>>,
+
+    /// Morphisms by id, `None` where one was removed
+    morphisms: Vec<Option<Morphism>>,
+
+    /// Index from stable ids to objects
+    object_uids: HashMap<StableId, ObjectId>,
+
+    /// Index from stable ids to morphisms
+    morphism_uids: HashMap<StableId, MorphismId>,
+
+    /// Indices by name and by end
+    indices: Indices,
+}
+
+/// Indices of the elements of a graph, each list of ids in id order.
+#[derive(Debug, Clone, Default)]
+struct Indices {
+    /// Objects by name
+    objects: HashMap<String, Vec<ObjectId>>,
+
+    /// Morphisms by name
+    morphisms: HashMap<String, Vec<MorphismId>>,
+
+    /// Morphisms by source
+    outgoing: HashMap<ObjectId, Vec<MorphismId>>,
+
+    /// Morphisms by target
+    incoming: HashMap<ObjectId, Vec<MorphismId>>,
+}
+
+impl Indices {
+    fn add_object(&mut self, object: &Object) {
+        insert(&mut self.objects, object.name.clone(), object.id);
+    }
+
+    fn remove_object(&mut self, object: &Object) {
+        remove(&mut self.objects, object.name.as_str(), object.id);
+    }
+
+    fn add_morphism(&mut self, morphism: &Morphism) {
+        insert(&mut self.morphisms, morphism.name.clone(), morphism.id);
+        insert(&mut self.outgoing, morphism.source, morphism.id);
+        insert(&mut self.incoming, morphism.target, morphism.id);
+    }
+
+    fn remove_morphism(&mut self, morphism: &Morphism) {
+        remove(&mut self.morphisms, morphism.name.as_str(), morphism.id);
+        remove(&mut self.outgoing, &morphism.source, morphism.id);
+        remove(&mut self.incoming, &morphism.target, morphism.id);
+    }
+}
+
+/// Add an id to the list of a key, keeping the list in order.
+fn insert<K: Hash + Eq, Id: Ord>(index: &mut HashMap<K, Vec<Id>>, key: K, id: Id) {
+    let ids = index.entry(key).or_default();
+    if let Err(at) = ids.binary_search(&id) {
+        ids.insert(at, id);
+    }
+}
+
+/// Remove an id from the list of a key, and the key with its last id.
+fn remove<K, Q, Id>(index: &mut HashMap<K, Vec<Id>>, key: &Q, id: Id)
+where
+    K: Hash + Eq + Borrow<Q>,
+    Q: Hash + Eq + ?Sized,
+    Id: Ord,
+{
+    if let Some(ids) = index.get_mut(key) {
+        if let Ok(at) = ids.binary_search(&id) {
+            ids.remove(at);
+        }
+        if ids.is_empty() {
+            index.remove(key);
+        }
+    }
+}
+
+/// The serialized form of a [`Graph`], without its indices.
+#[derive(Deserialize)]
+struct GraphData {
+    #[serde(deserialize_with = "unique_keys")]
     objects: HashMap<ObjectId, Object>,
+    #[serde(deserialize_with = "unique_keys")]
     morphisms: HashMap<MorphismId, Morphism>,
     next_object_id: u32,
     next_morphism_id: u32,
 }
 
+/// Deserialize a map, failing on a key given twice instead of keeping the
+/// last element under it.
+fn unique_keys<'de, D, K, V>(deserializer: D) -> Result<HashMap<K, V>, D::Error>
+where
+    D: Deserializer<'de>,
+    K: Deserialize<'de> + Eq + Hash + fmt::Debug,
+    V: Deserialize<'de>,
+{
+    struct UniqueKeys<K, V>(PhantomData<(K, V)>);
+
+    impl<'de, K, V> Visitor<'de> for UniqueKeys<K, V>
+    where
+        K: Deserialize<'de> + Eq + Hash + fmt::Debug,
+        V: Deserialize<'de>,
+    {
+        type Value = HashMap<K, V>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a map of elements by id")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut elements = HashMap::new();
+            while let Some((key, value)) = map.next_entry()? {
+                if elements.contains_key(&key) {
+                    return Err(de::Error::custom(format!("{:?} is given twice", key)));
+                }
+                elements.insert(key, value);
+            }
+            Ok(elements)
+        }
+    }
+
+    deserializer.deserialize_map(UniqueKeys(PhantomData))
+}
+
+/// Most empty slots a deserialized arena may have, left by removed
+/// elements. Bounds the memory a graph read from untrusted data can claim.
+const MAX_VACANT_SLOTS: usize = 1 << 16;
+
+/// Put elements in the slots of their ids, leaving room for `next` ids.
+/// Every id must be below `next`, match the id its element carries, and
+/// leave at most [`MAX_VACANT_SLOTS`] slots empty.
+fn arena<Id, T>(
+    kind: &str,
+    elements: HashMap<Id, T>,
+    next: u32,
+    id_of: impl Fn(&T) -> Id,
+) -> Result<Vec<Option<T>>, String>
+where
+    Id: Into<usize> + Copy + PartialEq + fmt::Debug,
+{
+    let next = next as usize;
+    if next < elements.len() || next - elements.len() > MAX_VACANT_SLOTS {
+        return Err(format!(
+            "Invalid next {} id {} for {} {}s",
+            kind,
+            next,
+            elements.len(),
+            kind
+        ));
+    }
+    let mut slots: Vec<Option<T>> = Vec::new();
+    slots.resize_with(next, || None);
+    for (id, element) in elements {
+        if id_of(&element) != id {
+            return Err(format!("{:?} is stored under {:?}", id_of(&element), id));
+        }
+        let slot = id.into();
+        if slot >= next {
+            return Err(format!(
+                "{:?} is not below the next {} id {}",
+                id, kind, next
+            ));
+        }
+        slots[slot] = Some(element);
+    }
+    Ok(slots)
+}
+
+impl From<ObjectId> for usize {
+    fn from(id: ObjectId) -> usize {
+        id.0 as usize
+    }
+}
+
+impl From<MorphismId> for usize {
+    fn from(id: MorphismId) -> usize {
+        id.0 as usize
+    }
+}
+
+impl TryFrom<GraphData> for Graph {
+    type Error = String;
+
+    /// Rebuild the indices, deriving stable ids for elements serialized
+    /// before they existed. Elements are visited in id order, so the
+    /// derived ids match those a fresh parse would assign.
+    ///
+    /// Fails if two elements share a stable id, or a morphism's source or
+    /// target is missing.
+    fn try_from(data: GraphData) -> Result<Self, Self::Error> {
+        let mut graph = Graph {
+            objects: arena("object", data.objects, data.next_object_id, |o| o.id)?,
+            morphisms: arena("morphism", data.morphisms, data.next_morphism_id, |m| m.id)?,
+            object_uids: HashMap::new(),
+            morphism_uids: HashMap::new(),
+            indices: Indices::default(),
+        };
+
+        for slot in 0..graph.objects.len() {
+            let Some(object) = &graph.objects[slot] else {
+                continue;
+            };
+            let mut uid = object.uid;
+            if uid.is_unassigned() {
+                uid = graph.object_uid(&object.name);
+            }
+            let object = graph.objects[slot].as_mut().expect("slot is filled");
+            object.uid = uid;
+            if let Some(other) = graph.object_uids.insert(uid, object.id) {
+                return Err(format!("{:?} and {:?} share the stable id {}", other, object.id, uid));
+            }
+            graph.indices.add_object(object);
+        }
+
+        for slot in 0..graph.morphisms.len() {
+            let Some(morphism) = &graph.morphisms[slot] else {
+                continue;
+            };
+            for end in [morphism.source, morphism.target] {
+                if graph.get_object(end).is_none() {
+                    return Err(format!("{:?} leads to the missing {:?}", morphism.id, end));
+                }
+            }
+            let mut uid = morphism.uid;
+            if uid.is_unassigned() {
+                uid = graph.morphism_uid(morphism.source, &morphism.name);
+            }
+            let morphism = graph.morphisms[slot].as_mut().expect("slot is filled");
+            morphism.uid = uid;
+            if let Some(other) = graph.morphism_uids.insert(uid, morphism.id) {
+                return Err(format!("{:?} and {:?} share the stable id {}", other, morphism.id, uid));
+            }
+            graph.indices.add_morphism(morphism);
+        }
+        Ok(graph)
+    }
+}
+
+/// Serializes elements as a map from their ids, in id order.
+struct Elements<'a, T>(&'a [Option<T>]);
+
+impl Serialize for Elements<'_, Object> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().flatten().map(|o| (o.id, o)))
+    }
+}
+
+impl Serialize for Elements<'_, Morphism> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().flatten().map(|m| (m.id, m)))
+    }
+}
+
+impl Serialize for Graph {
+    /// Serialize as [`GraphData`], the form graphs had before arenas.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut graph = serializer.serialize_struct("Graph", 4)?;
+        graph.serialize_field("objects", &Elements(&self.objects))?;
+        graph.serialize_field("morphisms", &Elements(&self.morphisms))?;
+        graph.serialize_field("next_object_id", &(self.objects.len() as u32))?;
+        graph.serialize_field("next_morphism_id", &(self.morphisms.len() as u32))?;
+        graph.end()
+    }
+}
+
 impl Graph {
     /// Create a new empty graph.
     pub fn new() -> Self {
@@ -63,16 +486,19 @@ impl Graph {
 
     /// Add an object to the graph.
     pub fn add_object(&mut self, name: impl Into<String>) -> ObjectId {
-        let id = ObjectId(self.next_object_id);
-        self.next_object_id += 1;
-
+        let id = ObjectId(self.objects.len() as u32);
+        let name = name.into();
+        let uid = self.object_uid(&name);
         let object = Object {
             id,
-            name: name.into(),
+            uid,
+            name,
             description: None,
         };
 
-        self.objects.insert(id, object);
+        self.indices.add_object(&object);
+        self.objects.push(Some(object));
+        self.object_uids.insert(uid, id);
         id
     }
 
@@ -83,19 +509,22 @@ impl Graph {
         source: ObjectId,
         target: ObjectId,
     ) -> MorphismId {
-        let id = MorphismId(self.next_morphism_id);
-        self.next_morphism_id += 1;
-
+        let id = MorphismId(self.morphisms.len() as u32);
+        let name = name.into();
+        let uid = self.morphism_uid(source, &name);
         let morphism = Morphism {
             id,
-            name: name.into(),
+            uid,
+            name,
             source,
             target,
             description: None,
             is_identity: false,
         };
 
-        self.morphisms.insert(id, morphism);
+        self.indices.add_morphism(&morphism);
+        self.morphisms.push(Some(morphism));
+        self.morphism_uids.insert(uid, id);
         id
     }
 
@@ -104,17 +533,17 @@ impl Graph {
     /// In category theory, every object has an identity morphism.
     /// For entities in DDD, this represents the concept of identity.
     pub fn add_identity_morphism(&mut self, object: ObjectId) -> MorphismId {
-        let id = MorphismId(self.next_morphism_id);
-        self.next_morphism_id += 1;
-
+        let id = MorphismId(self.morphisms.len() as u32);
         let name = if let Some(obj) = self.get_object(object) {
             format!("id_{}", obj.name)
         } else {
             format!("id_{}", object.0)
         };
 
+        let uid = self.morphism_uid(object, &name);
         let morphism = Morphism {
             id,
+            uid,
             name,
             source: object,
             target: object,
@@ -122,56 +551,390 @@ impl Graph {
             is_identity: true,
         };
 
-        self.morphisms.insert(id, morphism);
+        self.indices.add_morphism(&morphism);
+        self.morphisms.push(Some(morphism));
+        self.morphism_uids.insert(uid, id);
         id
     }
 
     /// Get the identity morphism for an object, if it exists.
     pub fn get_identity_morphism(&self, object: ObjectId) -> Option<&Morphism> {
-        self.morphisms
-            .values()
-            .find(|m| m.is_identity && m.source == object && m.target == object)
+        self.outgoing_morphisms(object)
+            .find(|m| m.is_identity && m.target == object)
+    }
+
+    /// Remove an object together with every morphism into or out of it.
+    ///
+    /// Ids of the remaining objects and morphisms do not change.
+    pub fn remove_object(&mut self, id: ObjectId) -> Option<Object> {
+        let object = self.objects.get_mut(usize::from(id))?.take()?;
+        self.object_uids.remove(&object.uid);
+        self.indices.remove_object(&object);
+        let touching: Vec<MorphismId> = [&self.indices.outgoing, &self.indices.incoming]
+            .into_iter()
+            .filter_map(|index| index.get(&id))
+            .flatten()
+            .copied()
+            .collect();
+        for morphism in touching {
+            self.remove_morphism(morphism);
+        }
+        Some(object)
+    }
+
+    /// Remove a morphism.
+    pub fn remove_morphism(&mut self, id: MorphismId) -> Option<Morphism> {
+        let morphism = self.morphisms.get_mut(usize::from(id))?.take()?;
+        self.morphism_uids.remove(&morphism.uid);
+        self.indices.remove_morphism(&morphism);
+        Some(morphism)
+    }
+
+    /// Get an object by its stable id.
+    pub fn object_by_uid(&self, uid: StableId) -> Option<&Object> {
+        self.object_uids
+            .get(&uid)
+            .and_then(|&id| self.get_object(id))
+    }
+
+    /// Get a morphism by its stable id.
+    pub fn morphism_by_uid(&self, uid: StableId) -> Option<&Morphism> {
+        self.morphism_uids
+            .get(&uid)
+            .and_then(|&id| self.get_morphism(id))
+    }
+
+    /// Derive an unused stable id for an object named `name`.
+    fn object_uid(&self, name: &str) -> StableId {
+        unused_uid(&format!("object:{}", name), |uid| {
+            self.object_uids.contains_key(&uid)
+        })
+    }
+
+    /// Derive an unused stable id for a morphism out of `source`.
+    fn morphism_uid(&self, source: ObjectId, name: &str) -> StableId {
+        let source = self.get_object(source).map_or("", |o| o.name.as_str());
+        unused_uid(&format!("morphism:{}.{}", source, name), |uid| {
+            self.morphism_uids.contains_key(&uid)
+        })
     }
 
     /// Get an object by its ID.
     pub fn get_object(&self, id: ObjectId) -> Option<&Object> {
-        self.objects.get(&id)
+        self.objects.get(usize::from(id))?.as_ref()
     }
 
     /// Get a morphism by its ID.
     pub fn get_morphism(&self, id: MorphismId) -> Option<&Morphism> {
-        self.morphisms.get(&id)
+        self.morphisms.get(usize::from(id))?.as_ref()
+    }
+
+    /// Get a mutable reference to an object by ID, changed through the
+    /// setters of [`ObjectMut`].
+    pub fn get_object_mut(&mut self, id: ObjectId) -> Option<ObjectMut<'_>> {
+        self.get_object(id)?;
+        Some(ObjectMut {
+            graph: self,
+            object: id,
+        })
+    }
+
+    /// Get a mutable reference to a morphism by ID, changed through the
+    /// setters of [`MorphismMut`].
+    pub fn get_morphism_mut(&mut self, id: MorphismId) -> Option<MorphismMut<'_>> {
+        self.get_morphism(id)?;
+        Some(MorphismMut {
+            graph: self,
+            morphism: id,
+        })
     }
 
-    /// Get all objects.
+    /// Get all objects, in id order.
     pub fn objects(&self) -> impl Iterator<Item = &Object> {
-        self.objects.values()
+        self.objects.iter().flatten()
     }
 
-    /// Get all morphisms.
+    /// Get all morphisms, in id order.
     pub fn morphisms(&self) -> impl Iterator<Item = &Morphism> {
-        self.morphisms.values()
+        self.morphisms.iter().flatten()
     }
 
-    /// Find an object by name.
+    /// Find an object by name, the first added if several have it.
     pub fn find_object_by_name(&self, name: &str) -> Option<&Object> {
-        self.objects.values().find(|o| o.name == name)
+        let &id = self.indices.objects.get(name)?.first()?;
+        self.get_object(id)
     }
 
-    /// Find a morphism by name.
+    /// Find a morphism by name, the first added if several have it.
     pub fn find_morphism_by_name(&self, name: &str) -> Option<&Morphism> {
-        self.morphisms.values().find(|m| m.name == name)
+        let &id = self.indices.morphisms.get(name)?.first()?;
+        self.get_morphism(id)
     }
 
-    /// Get all morphisms originating from an object.
+    /// Get all morphisms originating from an object, in id order.
     pub fn outgoing_morphisms(&self, source: ObjectId) -> impl Iterator<Item = &Morphism> {
-        self.morphisms.values().filter(move |m| m.source == source)
+        self.listed(self.indices.outgoing.get(&source))
     }
 
-    /// Get all morphisms targeting an object.
+    /// Get all morphisms targeting an object, in id order.
     pub fn incoming_morphisms(&self, target: ObjectId) -> impl Iterator<Item = &Morphism> {
-        self.morphisms.values().filter(move |m| m.target == target)
+        self.listed(self.indices.incoming.get(&target))
     }
+
+    /// Get the morphisms of a list of an index.
+    fn listed<'g>(
+        &'g self,
+        ids: Option<&'g Vec<MorphismId>>,
+    ) -> impl Iterator<Item = &'g Morphism> {
+        ids.into_iter()
+            .flatten()
+            .filter_map(|&id| self.get_morphism(id))
+    }
+
+    /// Compose a sequence of morphisms, given in the order they are followed.
+    ///
+    /// Fails if the path is empty, references an unknown morphism, or if a
+    /// morphism does not start at the target of the one before it.
+    pub fn compose(&self, path: &[MorphismId]) -> Result<ComposedMorphism, ComposeError> {
+        let mut steps = path.iter().enumerate().map(|(position, &id)| {
+            self.get_morphism(id)
+                .ok_or(ComposeError::UnknownMorphism { position, morphism: id })
+        });
+
+        let first = steps.next().ok_or(ComposeError::EmptyPath)??;
+        let mut last = first;
+        let mut names: Vec<&str> = Vec::new();
+        if !first.is_identity {
+            names.push(&first.name);
+        }
+
+        for (position, step) in (1..).zip(steps) {
+            let next = step?;
+            if next.source != last.target {
+                return Err(ComposeError::NotComposable {
+                    position,
+                    previous: last.name.to_string(),
+                    next: next.name.to_string(),
+                });
+            }
+            if !next.is_identity {
+                names.push(&next.name);
+            }
+            last = next;
+        }
+
+        Ok(ComposedMorphism {
+            source: first.source,
+            target: last.target,
+            morphisms: path.to_vec(),
+            name: names.join("."),
+        })
+    }
+
+    /// Enumerate the paths from `source` to `target` of at most `max_len`
+    /// morphisms.
+    ///
+    /// Identity morphisms are skipped, and a path never passes through the
+    /// same object twice, so cycles are not unrolled; a path may only return
+    /// to `source` by ending there. When `source` and `target` coincide the
+    /// identity path comes first. Paths are ordered by length, then by the
+    /// ids of their morphisms.
+    pub fn paths_between(&self, source: ObjectId, target: ObjectId, max_len: usize) -> Vec<Path> {
+        let mut paths = Vec::new();
+        if self.get_object(source).is_none() || self.get_object(target).is_none() {
+            return paths;
+        }
+        if source == target {
+            paths.push(Path::identity(source));
+        }
+        if max_len == 0 {
+            return paths;
+        }
+
+        // Morphisms come in id order, so each list of outgoing ones is sorted
+        let mut adjacency: HashMap<ObjectId, Vec<&Morphism>> = HashMap::new();
+        for morphism in self.morphisms().filter(|m| !m.is_identity) {
+            adjacency.entry(morphism.source).or_default().push(morphism);
+        }
+
+        // Depth-first search; `visited` holds the objects on the current path
+        let mut found = Vec::new();
+        let mut morphisms = Vec::new();
+        let mut visited = vec![source];
+        let mut stack = vec![(source, 0)];
+        while let Some((object, next)) = stack.pop() {
+            let outgoing = adjacency.get(&object).map(Vec::as_slice).unwrap_or_default();
+            let Some(morphism) = outgoing.get(next) else {
+                // Every morphism out of this object is explored; backtrack
+                morphisms.pop();
+                visited.pop();
+                continue;
+            };
+            stack.push((object, next + 1));
+
+            if morphism.target == target {
+                let mut path = morphisms.clone();
+                path.push(morphism.id);
+                found.push(path);
+            }
+            if morphisms.len() + 1 < max_len && !visited.contains(&morphism.target) {
+                morphisms.push(morphism.id);
+                visited.push(morphism.target);
+                stack.push((morphism.target, 0));
+            }
+        }
+
+        found.sort_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+        paths.extend(
+            found
+                .into_iter()
+                .map(|morphisms| Path::new(source, target, morphisms)),
+        );
+        paths
+    }
+
+    /// Materialize a composite as a named morphism of the graph.
+    pub fn add_composite(
+        &mut self,
+        name: impl Into<String>,
+        composite: &ComposedMorphism,
+    ) -> MorphismId {
+        let id = self.add_morphism(name, composite.source, composite.target);
+        if let Some(mut morphism) = self.get_morphism_mut(id) {
+            morphism.set_description(Some(format!("Composite of {}", composite.name)));
+        }
+        id
+    }
+}
+
+/// A mutable reference to an object of a graph.
+///
+/// The object reads through the reference, and changes go through its
+/// setters, which keep the graph's indices up to date. The id of the
+/// object cannot change:
+///
+/// ```compile_fail
+/// # use sketchddd_core::sketch::Graph;
+/// let mut graph = Graph::new();
+/// let order = graph.add_object("Order");
+/// let customer = graph.add_object("Customer");
+/// graph.get_object_mut(order).unwrap().id = customer;
+/// ```
+pub struct ObjectMut<'g> {
+    graph: &'g mut Graph,
+    object: ObjectId,
+}
+
+impl ObjectMut<'_> {
+    fn object_mut(&mut self) -> &mut Object {
+        self.graph.objects[usize::from(self.object)]
+            .as_mut()
+            .expect("object exists")
+    }
+
+    /// Rename the object.
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        let name = name.into();
+        let indices = &mut self.graph.indices;
+        let object = self.graph.objects[usize::from(self.object)]
+            .as_mut()
+            .expect("object exists");
+        if object.name != name {
+            indices.remove_object(object);
+            object.name = name;
+            indices.add_object(object);
+        }
+    }
+
+    /// Set the description of the object.
+    pub fn set_description(&mut self, description: Option<String>) {
+        self.object_mut().description = description;
+    }
+}
+
+impl Deref for ObjectMut<'_> {
+    type Target = Object;
+
+    fn deref(&self) -> &Object {
+        self.graph.get_object(self.object).expect("object exists")
+    }
+}
+
+/// A mutable reference to a morphism of a graph.
+///
+/// The morphism reads through the reference, and changes go through its
+/// setters, which keep the graph's indices up to date. The id of the
+/// morphism cannot change:
+///
+/// ```compile_fail
+/// # use sketchddd_core::sketch::Graph;
+/// let mut graph = Graph::new();
+/// let order = graph.add_object("Order");
+/// let placed_by = graph.add_morphism("placedBy", order, order);
+/// let ships_to = graph.add_morphism("shipsTo", order, order);
+/// graph.get_morphism_mut(placed_by).unwrap().id = ships_to;
+/// ```
+pub struct MorphismMut<'g> {
+    graph: &'g mut Graph,
+    morphism: MorphismId,
+}
+
+impl MorphismMut<'_> {
+    /// Change the morphism, reindexing it around the change.
+    fn update(&mut self, change: impl FnOnce(&mut Morphism)) {
+        let indices = &mut self.graph.indices;
+        let morphism = self.graph.morphisms[usize::from(self.morphism)]
+            .as_mut()
+            .expect("morphism exists");
+        indices.remove_morphism(morphism);
+        change(morphism);
+        indices.add_morphism(morphism);
+    }
+
+    /// Rename the morphism.
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        let name = name.into();
+        self.update(|morphism| morphism.name = name);
+    }
+
+    /// Set the object the morphism starts at.
+    pub fn set_source(&mut self, source: ObjectId) {
+        self.update(|morphism| morphism.source = source);
+    }
+
+    /// Set the object the morphism ends at.
+    pub fn set_target(&mut self, target: ObjectId) {
+        self.update(|morphism| morphism.target = target);
+    }
+
+    /// Set the description of the morphism.
+    pub fn set_description(&mut self, description: Option<String>) {
+        self.graph.morphisms[usize::from(self.morphism)]
+            .as_mut()
+            .expect("morphism exists")
+            .description = description;
+    }
+}
+
+impl Deref for MorphismMut<'_> {
+    type Target = Morphism;
+
+    fn deref(&self) -> &Morphism {
+        self.graph
+            .get_morphism(self.morphism)
+            .expect("morphism exists")
+    }
+}
+
+/// Derive a stable id from `content`, adding a counter while it is taken.
+fn unused_uid(content: &str, taken: impl Fn(StableId) -> bool) -> StableId {
+    let mut uid = StableId::from_content(content);
+    let mut n = 1;
+    while uid.is_unassigned() || taken(uid) {
+        uid = StableId::from_content(&format!("{}#{}", content, n));
+        n += 1;
+    }
+    uid
 }
 
 #[cfg(test)]
@@ -218,4 +981,251 @@ mod tests {
         assert!(graph.find_object_by_name("Customer").is_some());
         assert!(graph.find_object_by_name("NotFound").is_none());
     }
+
+    #[test]
+    fn test_compose() {
+        let mut graph = Graph::new();
+        let order = graph.add_object("Order");
+        let customer = graph.add_object("Customer");
+        let address = graph.add_object("Address");
+        let placed_by = graph.add_morphism("placedBy", order, customer);
+        let lives_at = graph.add_morphism("livesAt", customer, address);
+        let id_order = graph.add_identity_morphism(order);
+
+        let composite = graph.compose(&[id_order, placed_by, lives_at]).unwrap();
+        assert_eq!(composite.source, order);
+        assert_eq!(composite.target, address);
+        assert_eq!(composite.name, "placedBy.livesAt");
+        assert_eq!(composite.to_path().len(), 3);
+
+        let shipping = graph.add_composite("shippingAddress", &composite);
+        let m = graph.get_morphism(shipping).unwrap();
+        assert_eq!((m.source, m.target), (order, address));
+        assert_eq!(m.description.as_deref(), Some("Composite of placedBy.livesAt"));
+    }
+
+    #[test]
+    fn test_paths_between() {
+        let mut graph = Graph::new();
+        let order = graph.add_object("Order");
+        let customer = graph.add_object("Customer");
+        let address = graph.add_object("Address");
+        let placed_by = graph.add_morphism("placedBy", order, customer);
+        let lives_at = graph.add_morphism("livesAt", customer, address);
+        let ships_to = graph.add_morphism("shipsTo", order, address);
+        let last_order = graph.add_morphism("lastOrder", customer, order);
+        graph.add_identity_morphism(order);
+
+        let paths = graph.paths_between(order, address, 3);
+        let morphisms: Vec<_> = paths.iter().map(|p| p.morphisms.clone()).collect();
+        assert_eq!(morphisms, vec![vec![ships_to], vec![placed_by, lives_at]]);
+        assert!(paths.iter().all(|p| p.source == order && p.target == address));
+
+        // The cycle through Customer is not unrolled
+        let morphisms: Vec<_> = graph
+            .paths_between(order, order, 5)
+            .into_iter()
+            .map(|p| p.morphisms)
+            .collect();
+        assert_eq!(morphisms, vec![vec![], vec![placed_by, last_order]]);
+
+        assert_eq!(graph.paths_between(order, address, 1).len(), 1);
+        assert!(graph.paths_between(order, address, 0).is_empty());
+        assert!(graph.paths_between(address, order, 5).is_empty());
+        assert!(graph.paths_between(order, ObjectId(99), 5).is_empty());
+    }
+
+    #[test]
+    fn test_compose_errors() {
+        let mut graph = Graph::new();
+        let order = graph.add_object("Order");
+        let customer = graph.add_object("Customer");
+        let placed_by = graph.add_morphism("placedBy", order, customer);
+
+        assert_eq!(graph.compose(&[]), Err(ComposeError::EmptyPath));
+        assert_eq!(
+            graph.compose(&[placed_by, placed_by]),
+            Err(ComposeError::NotComposable {
+                position: 1,
+                previous: "placedBy".into(),
+                next: "placedBy".into(),
+            })
+        );
+        assert_eq!(
+            graph.compose(&[placed_by, MorphismId(99)]),
+            Err(ComposeError::UnknownMorphism {
+                position: 1,
+                morphism: MorphismId(99),
+            })
+        );
+    }
+
+    #[test]
+    fn test_stable_ids() {
+        let build = || {
+            let mut graph = Graph::new();
+            let order = graph.add_object("Order");
+            let customer = graph.add_object("Customer");
+            graph.add_morphism("placedBy", order, customer);
+            graph
+        };
+        let (first, second) = (build(), build());
+        let uid = |g: &Graph, name| g.find_object_by_name(name).unwrap().uid();
+        assert_eq!(uid(&first, "Order"), uid(&second, "Order"));
+        assert_ne!(uid(&first, "Order"), uid(&first, "Customer"));
+        let placed_by = first.find_morphism_by_name("placedBy").unwrap();
+        assert_eq!(first.morphism_by_uid(placed_by.uid()).unwrap().id, placed_by.id);
+
+        // Renaming keeps the stable id; duplicate names get distinct ones
+        let mut graph = build();
+        let order = graph.find_object_by_name("Order").unwrap().id;
+        graph.get_object_mut(order).unwrap().set_name("Purchase");
+        assert_eq!(graph.object_by_uid(uid(&first, "Order")).unwrap().id, order);
+        let duplicate = graph.add_object("Customer");
+        assert_ne!(graph.get_object(duplicate).unwrap().uid(), uid(&first, "Customer"));
+
+        graph.remove_object(order);
+        assert!(graph.object_by_uid(uid(&first, "Order")).is_none());
+        assert!(graph.morphism_by_uid(placed_by.uid()).is_none());
+
+        assert_eq!(
+            StableId::try_from(uid(&first, "Order").to_string()),
+            Ok(uid(&first, "Order"))
+        );
+    }
+
+    #[test]
+    fn test_stable_ids_survive_serialization() {
+        let mut graph = Graph::new();
+        let order = graph.add_object("Order");
+        let customer = graph.add_object("Customer");
+        let placed_by = graph.add_morphism("placedBy", order, customer);
+        graph.get_object_mut(order).unwrap().set_name("Purchase");
+        let order_uid = graph.get_object(order).unwrap().uid();
+
+        let json = serde_json::to_string(&graph).unwrap();
+        let restored: Graph = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.object_by_uid(order_uid).unwrap().name, "Purchase");
+
+        // Graphs saved without stable ids get the ids a fresh parse assigns
+        let legacy = json.replace(&format!("\"uid\":\"{}\",", order_uid), "");
+        assert_ne!(legacy, json);
+        let migrated: Graph = serde_json::from_str(&legacy).unwrap();
+        let uid = migrated.get_object(order).unwrap().uid();
+        assert_eq!(uid, StableId::from_content("object:Purchase"));
+        assert_eq!(migrated.object_by_uid(uid).unwrap().id, order);
+        assert_eq!(
+            migrated.morphism_by_uid(graph.get_morphism(placed_by).unwrap().uid()).unwrap().id,
+            placed_by
+        );
+    }
+
+    #[test]
+    fn test_deserialization_rejects_invalid_ids() {
+        let mut graph = Graph::new();
+        let order = graph.add_object("Order");
+        graph.add_object("Customer");
+        graph.remove_object(order);
+        let json = serde_json::to_string(&graph).unwrap();
+        let restored: Graph = serde_json::from_str(&json).unwrap();
+        assert!(restored.get_object(order).is_none());
+        assert_eq!(restored.objects().count(), 1);
+
+        let huge = json.replace("\"next_object_id\":2", "\"next_object_id\":4000000000");
+        assert_ne!(huge, json);
+        let error = serde_json::from_str::<Graph>(&huge).unwrap_err();
+        assert!(error.to_string().contains("Invalid next object id"));
+
+        let beyond = json.replace("\"1\":{\"id\":1", "\"7\":{\"id\":7");
+        assert_ne!(beyond, json);
+        let error = serde_json::from_str::<Graph>(&beyond).unwrap_err();
+        assert!(error.to_string().contains("is not below"));
+
+        let mismatched = json.replace("\"1\":{\"id\":1", "\"0\":{\"id\":1");
+        assert_ne!(mismatched, json);
+        let error = serde_json::from_str::<Graph>(&mismatched).unwrap_err();
+        assert!(error.to_string().contains("is stored under"));
+    }
+
+    #[test]
+    fn test_mutable_references_keep_ids() {
+        let mut graph = Graph::new();
+        let order = graph.add_object("Order");
+        let customer = graph.add_object("Customer");
+        let placed_by = graph.add_morphism("placedBy", order, customer);
+        let order_uid = graph.get_object(order).unwrap().uid();
+
+        // Only the setters change an element; its ids stay as they were
+        let mut object = graph.get_object_mut(order).unwrap();
+        object.set_name("Purchase");
+        object.set_description(Some("A purchase".into()));
+        assert_eq!((object.id, object.uid()), (order, order_uid));
+        let mut morphism = graph.get_morphism_mut(placed_by).unwrap();
+        morphism.set_source(customer);
+        morphism.set_target(order);
+        assert_eq!(morphism.id, placed_by);
+
+        assert_eq!(graph.object_by_uid(order_uid).unwrap().name, "Purchase");
+        assert_eq!(graph.find_object_by_name("Purchase").unwrap().id, order);
+        let ids = |morphisms: Vec<&Morphism>| morphisms.iter().map(|m| m.id).collect::<Vec<_>>();
+        assert_eq!(ids(graph.outgoing_morphisms(customer).collect()), [placed_by]);
+        assert_eq!(ids(graph.incoming_morphisms(order).collect()), [placed_by]);
+        assert_eq!(graph.outgoing_morphisms(order).count(), 0);
+        assert!(graph.get_object_mut(ObjectId(99)).is_none());
+    }
+
+    #[test]
+    fn test_indices_follow_changes() {
+        let mut graph = Graph::new();
+        let order = graph.add_object("Order");
+        let customer = graph.add_object("Customer");
+        let address = graph.add_object("Address");
+        let placed_by = graph.add_morphism("placedBy", order, customer);
+        let lives_at = graph.add_morphism("livesAt", customer, address);
+        let identity = graph.add_identity_morphism(order);
+
+        let ids = |morphisms: Vec<&Morphism>| morphisms.iter().map(|m| m.id).collect::<Vec<_>>();
+        assert_eq!(
+            ids(graph.outgoing_morphisms(order).collect()),
+            [placed_by, identity]
+        );
+        assert_eq!(graph.get_identity_morphism(order).unwrap().id, identity);
+
+        graph.get_object_mut(order).unwrap().set_name("Purchase");
+        assert!(graph.find_object_by_name("Order").is_none());
+        assert_eq!(graph.find_object_by_name("Purchase").unwrap().id, order);
+        assert!(!graph.indices.objects.contains_key("Order"));
+
+        let mut morphism = graph.get_morphism_mut(placed_by).unwrap();
+        morphism.set_name("shipsTo");
+        morphism.set_target(address);
+        assert_eq!(morphism.id, placed_by);
+        assert!(graph.find_morphism_by_name("placedBy").is_none());
+        assert_eq!(
+            ids(graph.incoming_morphisms(address).collect()),
+            [placed_by, lives_at]
+        );
+        assert_eq!(graph.incoming_morphisms(customer).count(), 0);
+
+        // Duplicate names find the first added
+        let second = graph.add_object("Purchase");
+        assert_eq!(graph.find_object_by_name("Purchase").unwrap().id, order);
+        graph.remove_object(order);
+        assert_eq!(graph.find_object_by_name("Purchase").unwrap().id, second);
+        assert_eq!(ids(graph.incoming_morphisms(address).collect()), [lives_at]);
+        assert!(graph.find_morphism_by_name("shipsTo").is_none());
+        assert!(!graph.indices.morphisms.contains_key("shipsTo"));
+        assert!(graph.indices.objects.contains_key("Purchase"));
+
+        let json = serde_json::to_string(&graph).unwrap();
+        let restored: Graph = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            ids(restored.outgoing_morphisms(customer).collect()),
+            [lives_at]
+        );
+        assert_eq!(
+            restored.find_morphism_by_name("livesAt").unwrap().id,
+            lives_at
+        );
+    }
 }
@@ -1,6 +1,6 @@
-//! Limit cones for aggregates and value objects.
+//! Limit cones for aggregates, value objects, pullbacks and equalizers.
 
-use super::{MorphismId, ObjectId};
+use super::{Graph, MorphismId, ObjectId, Path, PathEquation};
 use serde::{Deserialize, Serialize};
 
 /// A projection from the apex of a limit cone to a component.
@@ -13,6 +13,22 @@ pub struct Projection {
     pub target: ObjectId,
 }
 
+/// The diagram a limit cone is taken over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LimitShape {
+    /// A discrete diagram: the apex is the product of the projection targets.
+    #[default]
+    Product,
+
+    /// A cospan `f: A -> C`, `g: B -> C`: the apex is the pullback, with
+    /// projections to `A` and then `B`.
+    Pullback { f: MorphismId, g: MorphismId },
+
+    /// A parallel pair `f, g: A -> B`: the apex is the equalizer, with a
+    /// single projection (the inclusion) into `A`.
+    Equalizer { f: MorphismId, g: MorphismId },
+}
+
 /// A limit cone representing an aggregate or value object.
 ///
 /// In category theory, a limit is a universal construction that
@@ -37,6 +53,10 @@ pub struct LimitCone {
 
     /// For aggregates: the designated root entity
     pub root: Option<ObjectId>,
+
+    /// The diagram the cone is over
+    #[serde(default)]
+    pub shape: LimitShape,
 }
 
 impl LimitCone {
@@ -48,6 +68,7 @@ impl LimitCone {
             projections: Vec::new(),
             is_aggregate: true,
             root: Some(root),
+            shape: LimitShape::Product,
         }
     }
 
@@ -59,6 +80,33 @@ impl LimitCone {
             projections: Vec::new(),
             is_aggregate: false,
             root: None,
+            shape: LimitShape::Product,
+        }
+    }
+
+    /// Create a new pullback cone of `f` and `g`.
+    ///
+    /// The projections to the sources of `f` and `g` are added in that order.
+    pub fn pullback(name: impl Into<String>, apex: ObjectId, f: MorphismId, g: MorphismId) -> Self {
+        Self {
+            shape: LimitShape::Pullback { f, g },
+            ..Self::value_object(name, apex)
+        }
+    }
+
+    /// Create a new equalizer cone of `f` and `g`.
+    ///
+    /// The inclusion into the common source of `f` and `g` is added as the
+    /// only projection.
+    pub fn equalizer(
+        name: impl Into<String>,
+        apex: ObjectId,
+        f: MorphismId,
+        g: MorphismId,
+    ) -> Self {
+        Self {
+            shape: LimitShape::Equalizer { f, g },
+            ..Self::value_object(name, apex)
         }
     }
 
@@ -71,6 +119,52 @@ impl LimitCone {
     pub fn component_objects(&self) -> impl Iterator<Item = ObjectId> + '_ {
         self.projections.iter().map(|p| p.target)
     }
+
+    /// Check whether the cone is over a discrete diagram.
+    pub fn is_product(&self) -> bool {
+        self.shape == LimitShape::Product
+    }
+
+    /// Get the morphisms of the diagram the cone is over.
+    pub fn diagram_morphisms(&self) -> Vec<MorphismId> {
+        match self.shape {
+            LimitShape::Product => Vec::new(),
+            LimitShape::Pullback { f, g } | LimitShape::Equalizer { f, g } => vec![f, g],
+        }
+    }
+
+    /// Get the equations the cone must satisfy to commute with its diagram.
+    ///
+    /// For a pullback these say `proj1.f = proj2.g`, for an equalizer
+    /// `incl.f = incl.g`. Products have none, and neither does a cone whose
+    /// projections do not fit its diagram.
+    pub fn commuting_equations(&self, graph: &Graph) -> Vec<PathEquation> {
+        let morphism = |id| graph.get_morphism(id);
+        let sides = match (self.shape, self.projections.as_slice()) {
+            (LimitShape::Pullback { f, g }, [p1, p2]) => Some((p1, f, p2, g)),
+            (LimitShape::Equalizer { f, g }, [incl]) => Some((incl, f, incl, g)),
+            _ => None,
+        };
+        let Some((p1, f, p2, g)) = sides else {
+            return Vec::new();
+        };
+        let (Some(f_morphism), Some(g_morphism)) = (morphism(f), morphism(g)) else {
+            return Vec::new();
+        };
+        if p1.target != f_morphism.source
+            || p2.target != g_morphism.source
+            || f_morphism.target != g_morphism.target
+        {
+            return Vec::new();
+        }
+
+        let target = f_morphism.target;
+        vec![PathEquation::new(
+            format!("{}_commutes", self.name),
+            Path::new(self.apex, target, vec![p1.morphism, f]),
+            Path::new(self.apex, target, vec![p2.morphism, g]),
+        )]
+    }
 }
 
 #[cfg(test)]
@@ -109,4 +203,29 @@ mod tests {
         assert_eq!(vo.projections.len(), 2);
         assert_eq!(vo.component_objects().count(), 2);
     }
+
+    #[test]
+    fn test_pullback_commuting_equations() {
+        let mut graph = Graph::new();
+        let shipment = graph.add_object("Shipment");
+        let order = graph.add_object("Order");
+        let customer = graph.add_object("Customer");
+        let pair = graph.add_object("ShippedOrder");
+        let ordered_by = graph.add_morphism("orderedBy", order, customer);
+        let recipient = graph.add_morphism("recipient", shipment, customer);
+        let p1 = graph.add_morphism("order", pair, order);
+        let p2 = graph.add_morphism("shipment", pair, shipment);
+
+        let mut pullback = LimitCone::pullback("ShippedOrder", pair, ordered_by, recipient);
+        assert!(!pullback.is_product());
+        assert!(pullback.commuting_equations(&graph).is_empty());
+
+        pullback.add_projection(p1, order);
+        pullback.add_projection(p2, shipment);
+        let equations = pullback.commuting_equations(&graph);
+        assert_eq!(equations.len(), 1);
+        assert_eq!(equations[0].lhs.morphisms, vec![p1, ordered_by]);
+        assert_eq!(equations[0].rhs.morphisms, vec![p2, recipient]);
+        assert!(equations[0].is_well_formed());
+    }
 }
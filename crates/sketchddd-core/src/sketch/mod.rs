@@ -7,11 +7,17 @@ mod graph;
 mod equation;
 mod limit;
 mod colimit;
+mod rewrite;
+mod free;
+mod removal;
 
-pub use graph::{Graph, Object, Morphism, ObjectId, MorphismId};
+pub use graph::{ComposeError, ComposedMorphism, Graph, Object, Morphism, ObjectId, MorphismId, ObjectMut, MorphismMut, StableId};
 pub use equation::{PathEquation, Path};
-pub use limit::{LimitCone, Projection};
+pub use limit::{LimitCone, LimitShape, Projection};
 pub use colimit::{ColimitCocone, Injection};
+pub use rewrite::RewriteSystem;
+pub use free::FreeCategory;
+pub use removal::{DanglingReferences, RemovalError, RemovalPolicy};
 
 use serde::{Deserialize, Serialize};
 
@@ -78,6 +84,127 @@ impl Sketch {
     pub fn add_colimit(&mut self, colimit: ColimitCocone) {
         self.colimits.push(colimit);
     }
+
+    /// Add a product of named components and return its apex.
+    ///
+    /// Creates the apex object, one projection morphism per component
+    /// named after it, and the limit cone over them. The components form a
+    /// discrete diagram, so the cone has no commuting conditions: its
+    /// universal property is stated by registering it as a limit.
+    pub fn add_product(
+        &mut self,
+        name: impl Into<String>,
+        components: Vec<(String, ObjectId)>,
+    ) -> ObjectId {
+        let name = name.into();
+        let apex = self.add_object(&name);
+        let mut limit = LimitCone::value_object(name, apex);
+        for (component_name, component) in components {
+            let projection = self.add_morphism(component_name, apex, component);
+            limit.add_projection(projection, component);
+        }
+        self.add_limit(limit);
+        apex
+    }
+
+    /// Add a coproduct of named variants and return its apex.
+    ///
+    /// Creates the apex object, one injection morphism per variant named
+    /// after it, and the colimit cocone over them. As with
+    /// [`Sketch::add_product`], the universal property is stated by the
+    /// cocone itself.
+    pub fn add_coproduct(
+        &mut self,
+        name: impl Into<String>,
+        variants: Vec<(String, ObjectId)>,
+    ) -> ObjectId {
+        let name = name.into();
+        let apex = self.add_object(&name);
+        let mut colimit = ColimitCocone::new(name, apex);
+        for (variant_name, variant) in variants {
+            let injection = self.add_morphism(&variant_name, variant, apex);
+            colimit.add_injection(variant_name, injection, variant);
+        }
+        self.add_colimit(colimit);
+        apex
+    }
+
+    /// Add the pullback of `f: A -> C` and `g: B -> C` and return its apex.
+    ///
+    /// Creates the apex, projections `proj1_{name}: P -> A` and
+    /// `proj2_{name}: P -> B`, the limit cone, and the equation making the
+    /// square commute. Returns `None` if `f` and `g` do not share a target.
+    pub fn add_pullback(
+        &mut self,
+        name: impl Into<String>,
+        f: MorphismId,
+        g: MorphismId,
+    ) -> Option<ObjectId> {
+        let (a, b) = match (self.graph.get_morphism(f), self.graph.get_morphism(g)) {
+            (Some(f), Some(g)) if f.target == g.target => (f.source, g.source),
+            _ => return None,
+        };
+        let name = name.into();
+        let apex = self.add_object(&name);
+        let mut limit = LimitCone::pullback(&name, apex, f, g);
+        let p1 = self.add_morphism(format!("proj1_{}", name), apex, a);
+        let p2 = self.add_morphism(format!("proj2_{}", name), apex, b);
+        limit.add_projection(p1, a);
+        limit.add_projection(p2, b);
+        self.add_commuting_limit(limit);
+        Some(apex)
+    }
+
+    /// Add the equalizer of parallel `f, g: A -> B` and return its apex.
+    ///
+    /// Creates the apex, the inclusion `incl_{name}: E -> A`, the limit
+    /// cone, and the equation `incl.f = incl.g`. Returns `None` if `f` and
+    /// `g` are not parallel.
+    pub fn add_equalizer(
+        &mut self,
+        name: impl Into<String>,
+        f: MorphismId,
+        g: MorphismId,
+    ) -> Option<ObjectId> {
+        let a = match (self.graph.get_morphism(f), self.graph.get_morphism(g)) {
+            (Some(f), Some(g)) if f.source == g.source && f.target == g.target => f.source,
+            _ => return None,
+        };
+        let name = name.into();
+        let apex = self.add_object(&name);
+        let mut limit = LimitCone::equalizer(&name, apex, f, g);
+        let inclusion = self.add_morphism(format!("incl_{}", name), apex, a);
+        limit.add_projection(inclusion, a);
+        self.add_commuting_limit(limit);
+        Some(apex)
+    }
+
+    /// Add a limit cone together with the equations it must satisfy.
+    ///
+    /// See [`LimitCone::commuting_equations`].
+    pub fn add_commuting_limit(&mut self, limit: LimitCone) {
+        for equation in limit.commuting_equations(&self.graph) {
+            self.add_equation(equation);
+        }
+        self.add_limit(limit);
+    }
+
+    /// Decide whether two paths are equal under the sketch's equations.
+    ///
+    /// See [`RewriteSystem`] for how equality is decided. When many paths
+    /// are compared against the same equations, build the rewrite system
+    /// once instead.
+    pub fn paths_equal(&self, p1: &Path, p2: &Path) -> bool {
+        RewriteSystem::new(&self.graph, &self.equations).equal(p1, p2)
+    }
+
+    /// Materialize the path category of the sketch up to paths of
+    /// `max_len` morphisms, quotiented by its equations.
+    ///
+    /// Hom-sets can then be queried with [`FreeCategory::hom`].
+    pub fn free_category(&self, max_len: usize) -> FreeCategory {
+        FreeCategory::new(self, max_len)
+    }
 }
 
 #[cfg(test)]
@@ -102,4 +229,75 @@ mod tests {
         assert_eq!(sketch.graph.objects().count(), 2);
         assert_eq!(sketch.graph.morphisms().count(), 1);
     }
+
+    #[test]
+    fn test_add_product_and_coproduct() {
+        let mut sketch = Sketch::new("Commerce");
+        let decimal = sketch.add_object("Decimal");
+        let currency = sketch.add_object("Currency");
+        let card = sketch.add_object("Card");
+        let cash = sketch.add_object("Cash");
+
+        let money = sketch.add_product(
+            "Money",
+            vec![("amount".into(), decimal), ("currency".into(), currency)],
+        );
+        let payment = sketch.add_coproduct(
+            "Payment",
+            vec![("Card".into(), card), ("Cash".into(), cash)],
+        );
+
+        let limit = &sketch.limits[0];
+        assert_eq!(limit.apex, money);
+        let projections: Vec<_> = limit
+            .projections
+            .iter()
+            .map(|p| {
+                let m = sketch.graph.get_morphism(p.morphism).unwrap();
+                (m.name.as_str(), m.source, m.target)
+            })
+            .collect();
+        assert_eq!(
+            projections,
+            vec![("amount", money, decimal), ("currency", money, currency)]
+        );
+
+        let colimit = &sketch.colimits[0];
+        assert_eq!(colimit.apex, payment);
+        let injection = sketch
+            .graph
+            .get_morphism(colimit.injections[1].morphism.unwrap())
+            .unwrap();
+        assert_eq!(
+            (injection.name.as_str(), injection.source, injection.target),
+            ("Cash", cash, payment)
+        );
+
+        assert!(crate::validation::validate_sketch(&sketch).is_ok());
+    }
+
+    #[test]
+    fn test_add_pullback_and_equalizer() {
+        let mut sketch = Sketch::new("Commerce");
+        let order = sketch.add_object("Order");
+        let shipment = sketch.add_object("Shipment");
+        let customer = sketch.add_object("Customer");
+        let money = sketch.add_object("Money");
+        let ordered_by = sketch.add_morphism("orderedBy", order, customer);
+        let recipient = sketch.add_morphism("recipient", shipment, customer);
+        let total = sketch.add_morphism("total", order, money);
+        let paid = sketch.add_morphism("paid", order, money);
+
+        let shipped = sketch.add_pullback("ShippedOrder", ordered_by, recipient).unwrap();
+        let settled = sketch.add_equalizer("SettledOrder", total, paid).unwrap();
+        assert!(sketch.add_pullback("Mismatched", ordered_by, total).is_none());
+        assert!(sketch.add_equalizer("Mismatched", ordered_by, recipient).is_none());
+
+        assert_eq!(sketch.limits[0].apex, shipped);
+        assert_eq!(sketch.limits[0].shape, LimitShape::Pullback { f: ordered_by, g: recipient });
+        assert_eq!(sketch.limits[1].apex, settled);
+        assert_eq!(sketch.limits[1].component_objects().collect::<Vec<_>>(), vec![order]);
+        assert_eq!(sketch.equations.len(), 2);
+        assert!(crate::validation::validate_sketch(&sketch).is_ok());
+    }
 }
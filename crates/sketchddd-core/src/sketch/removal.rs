@@ -0,0 +1,411 @@
+//! Removing objects and morphisms without leaving dangling references.
+
+use super::{MorphismId, ObjectId, Path, Sketch};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// What to do with structure that refers to a removed element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum RemovalPolicy {
+    /// Remove the dependent structure too
+    #[default]
+    Cascade,
+
+    /// Refuse to remove an element that anything else refers to
+    Restrict,
+}
+
+/// The structure of a sketch that refers to an element being removed.
+///
+/// With [`RemovalPolicy::Cascade`] this is what was removed along with the
+/// element; with [`RemovalPolicy::Restrict`] it is why the removal failed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DanglingReferences {
+    /// Morphisms into or out of a removed object, other than its identity
+    pub morphisms: Vec<MorphismId>,
+
+    /// Names of equations with a path through a removed element
+    pub equations: Vec<String>,
+
+    /// Names of limit cones that no longer make sense without the element
+    pub limits: Vec<String>,
+
+    /// Projections dropped from limit cones that remain, by cone name
+    pub projections: Vec<(String, MorphismId)>,
+
+    /// Names of colimit cocones whose apex is removed
+    pub colimits: Vec<String>,
+
+    /// Injections dropped from colimit cocones that remain, by cocone and
+    /// variant name
+    pub injections: Vec<(String, String)>,
+}
+
+impl DanglingReferences {
+    /// Check if nothing refers to the removed element.
+    pub fn is_empty(&self) -> bool {
+        self.morphisms.is_empty()
+            && self.equations.is_empty()
+            && self.limits.is_empty()
+            && self.projections.is_empty()
+            && self.colimits.is_empty()
+            && self.injections.is_empty()
+    }
+}
+
+impl fmt::Display for DanglingReferences {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if !self.morphisms.is_empty() {
+            parts.push(format!("{} morphism(s)", self.morphisms.len()));
+        }
+        parts.extend(self.equations.iter().map(|e| format!("equation '{}'", e)));
+        parts.extend(self.limits.iter().map(|l| format!("limit '{}'", l)));
+        parts.extend(
+            self.projections
+                .iter()
+                .map(|(limit, _)| format!("a projection of '{}'", limit)),
+        );
+        parts.extend(self.colimits.iter().map(|c| format!("colimit '{}'", c)));
+        parts.extend(
+            self.injections
+                .iter()
+                .map(|(colimit, variant)| format!("variant '{}.{}'", colimit, variant)),
+        );
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/// Errors from removing an element of a sketch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemovalError {
+    /// The object does not exist
+    UnknownObject(ObjectId),
+
+    /// The morphism does not exist
+    UnknownMorphism(MorphismId),
+
+    /// Other structure refers to the element
+    DanglingReferences(Box<DanglingReferences>),
+}
+
+impl fmt::Display for RemovalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemovalError::UnknownObject(id) => write!(f, "Object {:?} does not exist", id),
+            RemovalError::UnknownMorphism(id) => write!(f, "Morphism {:?} does not exist", id),
+            RemovalError::DanglingReferences(refs) => {
+                write!(f, "Still referred to by {}", refs)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RemovalError {}
+
+impl Sketch {
+    /// Get the structure that would dangle if the object were removed.
+    ///
+    /// The object's identity morphisms go with it and are not reported
+    /// themselves, but equations through them are.
+    pub fn references_to_object(&self, object: ObjectId) -> DanglingReferences {
+        let gone: BTreeSet<MorphismId> = self
+            .graph
+            .morphisms()
+            .filter(|m| m.source == object || m.target == object)
+            .map(|m| m.id)
+            .collect();
+        let mut refs = self.references(&BTreeSet::from([object]), &gone);
+        refs.morphisms = self
+            .graph
+            .morphisms()
+            .filter(|m| gone.contains(&m.id) && !(m.is_identity && m.source == object))
+            .map(|m| m.id)
+            .collect();
+        refs.morphisms.sort();
+        refs
+    }
+
+    /// Get the structure that would dangle if the morphism were removed.
+    pub fn references_to_morphism(&self, morphism: MorphismId) -> DanglingReferences {
+        self.references(&BTreeSet::new(), &BTreeSet::from([morphism]))
+    }
+
+    /// Remove an object, together with its morphisms and whatever refers to
+    /// them if the policy allows.
+    ///
+    /// Returns the structure removed with the object.
+    pub fn remove_object(
+        &mut self,
+        object: ObjectId,
+        policy: RemovalPolicy,
+    ) -> Result<DanglingReferences, RemovalError> {
+        if self.graph.get_object(object).is_none() {
+            return Err(RemovalError::UnknownObject(object));
+        }
+        let refs = self.references_to_object(object);
+        if policy == RemovalPolicy::Restrict && !refs.is_empty() {
+            return Err(RemovalError::DanglingReferences(Box::new(refs)));
+        }
+        self.graph.remove_object(object);
+        self.prune();
+        Ok(refs)
+    }
+
+    /// Remove a morphism, together with whatever refers to it if the policy
+    /// allows.
+    ///
+    /// Returns the structure removed with the morphism.
+    pub fn remove_morphism(
+        &mut self,
+        morphism: MorphismId,
+        policy: RemovalPolicy,
+    ) -> Result<DanglingReferences, RemovalError> {
+        if self.graph.get_morphism(morphism).is_none() {
+            return Err(RemovalError::UnknownMorphism(morphism));
+        }
+        let refs = self.references_to_morphism(morphism);
+        if policy == RemovalPolicy::Restrict && !refs.is_empty() {
+            return Err(RemovalError::DanglingReferences(Box::new(refs)));
+        }
+        self.graph.remove_morphism(morphism);
+        self.prune();
+        Ok(refs)
+    }
+
+    /// Drop every equation, cone, projection and injection that refers to
+    /// objects or morphisms no longer in the graph.
+    pub(crate) fn prune(&mut self) {
+        let graph = &self.graph;
+        let dangling = self.dangling(
+            |id| graph.get_object(id).is_none(),
+            |id| graph.get_morphism(id).is_none(),
+        );
+
+        // Positions are ascending, so remove from the back
+        for &i in dangling.equations.iter().rev() {
+            self.equations.remove(i);
+        }
+        for &(i, morphism) in &dangling.projections {
+            self.limits[i]
+                .projections
+                .retain(|p| p.morphism != morphism);
+        }
+        for &i in dangling.limits.iter().rev() {
+            self.limits.remove(i);
+        }
+        for &(i, j) in dangling.injections.iter().rev() {
+            self.colimits[i].injections.remove(j);
+        }
+        for &i in dangling.colimits.iter().rev() {
+            self.colimits.remove(i);
+        }
+    }
+
+    /// Find the equations and cones referring to any of the given objects
+    /// or morphisms.
+    fn references(
+        &self,
+        objects: &BTreeSet<ObjectId>,
+        morphisms: &BTreeSet<MorphismId>,
+    ) -> DanglingReferences {
+        let dangling = self.dangling(|id| objects.contains(&id), |id| morphisms.contains(&id));
+        DanglingReferences {
+            morphisms: Vec::new(),
+            equations: dangling
+                .equations
+                .iter()
+                .map(|&i| self.equations[i].name.clone())
+                .collect(),
+            limits: dangling
+                .limits
+                .iter()
+                .map(|&i| self.limits[i].name.clone())
+                .collect(),
+            projections: dangling
+                .projections
+                .iter()
+                .map(|&(i, morphism)| (self.limits[i].name.clone(), morphism))
+                .collect(),
+            colimits: dangling
+                .colimits
+                .iter()
+                .map(|&i| self.colimits[i].name.clone())
+                .collect(),
+            injections: dangling
+                .injections
+                .iter()
+                .map(|&(i, j)| {
+                    let colimit = &self.colimits[i];
+                    (colimit.name.clone(), colimit.injections[j].name.clone())
+                })
+                .collect(),
+        }
+    }
+
+    /// Find, by position, the structure referring to gone elements.
+    fn dangling(
+        &self,
+        object_gone: impl Fn(ObjectId) -> bool,
+        morphism_gone: impl Fn(MorphismId) -> bool,
+    ) -> Dangling {
+        let touches = |p: &Path| {
+            object_gone(p.source)
+                || object_gone(p.target)
+                || p.morphisms.iter().any(|&m| morphism_gone(m))
+        };
+        let mut dangling = Dangling::default();
+
+        for (i, eq) in self.equations.iter().enumerate() {
+            if touches(&eq.lhs) || touches(&eq.rhs) {
+                dangling.equations.push(i);
+            }
+        }
+
+        for (i, limit) in self.limits.iter().enumerate() {
+            let dropped: Vec<MorphismId> = limit
+                .projections
+                .iter()
+                .map(|p| p.morphism)
+                .filter(|&m| morphism_gone(m))
+                .collect();
+            // Only a product survives losing a projection
+            if object_gone(limit.apex)
+                || limit.diagram_morphisms().into_iter().any(&morphism_gone)
+                || (!limit.is_product() && !dropped.is_empty())
+            {
+                dangling.limits.push(i);
+            } else {
+                dangling
+                    .projections
+                    .extend(dropped.into_iter().map(|m| (i, m)));
+            }
+        }
+
+        for (i, colimit) in self.colimits.iter().enumerate() {
+            if object_gone(colimit.apex) {
+                dangling.colimits.push(i);
+                continue;
+            }
+            for (j, injection) in colimit.injections.iter().enumerate() {
+                if object_gone(injection.source) || injection.morphism.is_some_and(&morphism_gone) {
+                    dangling.injections.push((i, j));
+                }
+            }
+        }
+        dangling
+    }
+}
+
+/// Positions of the equations, cones, projections and injections that
+/// refer to gone elements.
+#[derive(Default)]
+struct Dangling {
+    equations: Vec<usize>,
+    limits: Vec<usize>,
+    projections: Vec<(usize, MorphismId)>,
+    colimits: Vec<usize>,
+    injections: Vec<(usize, usize)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sketch::PathEquation;
+
+    /// Order -> Customer twice, equated, with Money as a product of Order.
+    fn commerce() -> (Sketch, [ObjectId; 3], [MorphismId; 2]) {
+        let mut sketch = Sketch::new("Commerce");
+        let order = sketch.add_object("Order");
+        let customer = sketch.add_object("Customer");
+        let decimal = sketch.add_object("Decimal");
+        sketch.graph.add_identity_morphism(order);
+        let placed_by = sketch.add_morphism("placedBy", order, customer);
+        let buyer = sketch.add_morphism("buyer", order, customer);
+        sketch.add_equation(PathEquation::new(
+            "sameCustomer",
+            Path::new(order, customer, vec![placed_by]),
+            Path::new(order, customer, vec![buyer]),
+        ));
+        sketch.add_product(
+            "Money",
+            vec![("amount".into(), decimal), ("buyer".into(), customer)],
+        );
+        sketch.add_coproduct("Party", vec![("Customer".into(), customer)]);
+        (sketch, [order, customer, decimal], [placed_by, buyer])
+    }
+
+    #[test]
+    fn test_restrict_reports_dangling_references() {
+        let (mut sketch, [order, customer, _], [placed_by, buyer]) = commerce();
+
+        let refs = sketch.references_to_morphism(buyer);
+        assert_eq!(refs.equations, vec!["sameCustomer"]);
+        assert!(refs.limits.is_empty());
+
+        let err = sketch
+            .remove_object(customer, RemovalPolicy::Restrict)
+            .unwrap_err();
+        let RemovalError::DanglingReferences(refs) = &err else {
+            panic!("expected dangling references");
+        };
+        assert_eq!(refs.morphisms.len(), 4);
+        assert!(refs.morphisms.contains(&placed_by));
+        assert_eq!(refs.equations, vec!["sameCustomer"]);
+        assert_eq!(refs.projections.len(), 1);
+        assert_eq!(
+            refs.injections,
+            vec![("Party".to_string(), "Customer".to_string())]
+        );
+        assert_eq!(
+            err.to_string(),
+            "Still referred to by 4 morphism(s), equation 'sameCustomer', \
+             a projection of 'Money', variant 'Party.Customer'"
+        );
+        assert!(sketch.graph.get_object(customer).is_some());
+
+        // The identity goes with the object without being reported
+        let mut lone = Sketch::new("Lone");
+        let note = lone.add_object("Note");
+        lone.graph.add_identity_morphism(note);
+        assert!(lone.remove_object(note, RemovalPolicy::Restrict).is_ok());
+
+        assert_eq!(
+            sketch
+                .remove_object(order, RemovalPolicy::Restrict)
+                .map(|_| ()),
+            Err(RemovalError::DanglingReferences(Box::new(
+                sketch.references_to_object(order)
+            )))
+        );
+    }
+
+    #[test]
+    fn test_cascade_removes_dependents() {
+        let (mut sketch, [order, customer, decimal], [_, buyer]) = commerce();
+
+        let removed = sketch
+            .remove_morphism(buyer, RemovalPolicy::Cascade)
+            .unwrap();
+        assert_eq!(removed.equations, vec!["sameCustomer"]);
+        assert!(sketch.equations.is_empty());
+
+        sketch
+            .remove_object(customer, RemovalPolicy::Cascade)
+            .unwrap();
+        assert_eq!(sketch.graph.morphisms().count(), 2);
+        let money = &sketch.limits[0];
+        assert_eq!(money.component_objects().collect::<Vec<_>>(), vec![decimal]);
+        assert!(sketch.colimits[0].injections.is_empty());
+
+        let party = sketch.colimits[0].apex;
+        sketch.remove_object(party, RemovalPolicy::Cascade).unwrap();
+        assert!(sketch.colimits.is_empty());
+        assert!(sketch.graph.get_object(order).is_some());
+        assert_eq!(
+            sketch.remove_object(party, RemovalPolicy::Cascade),
+            Err(RemovalError::UnknownObject(party))
+        );
+    }
+}
@@ -0,0 +1,326 @@
+//! Deciding path equality modulo the equations of a sketch.
+//!
+//! Two paths are equal in the category presented by a sketch if one can be
+//! turned into the other by repeatedly replacing a subpath that matches one
+//! side of an equation with the other side. This is the word problem for
+//! the equations, which is undecidable in general, so we proceed in two
+//! stages:
+//!
+//! 1. Orient every equation into a rewrite rule that shortens paths (under
+//!    the shortlex order) and run Knuth–Bendix completion. If completion
+//!    finishes within its budget, every path has a unique normal form and
+//!    two paths are equal exactly when their normal forms are.
+//! 2. Otherwise, fall back to a bounded breadth-first search that applies
+//!    the rules in both directions. Finding a connection proves equality;
+//!    exhausting the budget without one is reported as "not equal".
+//!
+//! Identity morphisms are dropped from paths before rewriting, since
+//! composing with an identity changes nothing.
+
+use super::{Graph, MorphismId, Path, PathEquation};
+use std::cmp::Ordering;
+use std::collections::{HashSet, VecDeque};
+
+/// Maximum number of rules completion may generate before giving up.
+const MAX_RULES: usize = 256;
+
+/// Maximum number of paths the fallback search may visit.
+const MAX_SEARCH_STATES: usize = 10_000;
+
+/// A path with identities removed, as a word over morphisms.
+type Word = Vec<MorphismId>;
+
+/// A rewrite rule replacing `lhs` by the smaller word `rhs`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    lhs: Word,
+    rhs: Word,
+}
+
+/// A rewriting system derived from a set of path equations.
+#[derive(Debug, Clone)]
+pub struct RewriteSystem {
+    rules: Vec<Rule>,
+    identities: HashSet<MorphismId>,
+    complete: bool,
+}
+
+impl RewriteSystem {
+    /// Build and complete the rewriting system for a set of equations.
+    pub fn new(graph: &Graph, equations: &[PathEquation]) -> Self {
+        let identities = graph
+            .morphisms()
+            .filter(|m| m.is_identity)
+            .map(|m| m.id)
+            .collect();
+
+        let mut system = Self {
+            rules: Vec::new(),
+            identities,
+            complete: false,
+        };
+
+        for equation in equations.iter().filter(|eq| eq.is_well_formed()) {
+            let lhs = system.word(&equation.lhs);
+            let rhs = system.word(&equation.rhs);
+            system.add_rule(lhs, rhs);
+        }
+        system.complete = system.complete_rules();
+        system
+    }
+
+    /// Check if completion succeeded, making [`RewriteSystem::equal`] an
+    /// exact decision procedure.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Reduce a path to its normal form under the rules.
+    pub fn normal_form(&self, path: &Path) -> Vec<MorphismId> {
+        self.reduce(self.word(path))
+    }
+
+    /// Decide whether two paths are equal modulo the equations.
+    pub fn equal(&self, p1: &Path, p2: &Path) -> bool {
+        if p1.source != p2.source || p1.target != p2.target {
+            return false;
+        }
+
+        let w1 = self.normal_form(p1);
+        let w2 = self.normal_form(p2);
+        if w1 == w2 {
+            return true;
+        }
+        !self.complete && self.connected(w1, w2)
+    }
+
+    fn word(&self, path: &Path) -> Word {
+        path.morphisms
+            .iter()
+            .copied()
+            .filter(|m| !self.identities.contains(m))
+            .collect()
+    }
+
+    /// Orient a pair of words into a rule, unless they reduce to the same
+    /// word already.
+    fn add_rule(&mut self, a: Word, b: Word) {
+        let a = self.reduce(a);
+        let b = self.reduce(b);
+        let rule = match shortlex(&a, &b) {
+            Ordering::Equal => return,
+            Ordering::Greater => Rule { lhs: a, rhs: b },
+            Ordering::Less => Rule { lhs: b, rhs: a },
+        };
+        self.rules.push(rule);
+    }
+
+    /// Rewrite a word with the rules until none applies.
+    ///
+    /// Terminates because every rule makes the word smaller in the
+    /// shortlex order, which is well-founded.
+    fn reduce(&self, mut word: Word) -> Word {
+        'rewrite: loop {
+            for rule in &self.rules {
+                if let Some(at) = find(&word, &rule.lhs) {
+                    word.splice(at..at + rule.lhs.len(), rule.rhs.iter().copied());
+                    continue 'rewrite;
+                }
+            }
+            return word;
+        }
+    }
+
+    /// Run Knuth–Bendix completion, adding a rule for every critical pair
+    /// whose reducts differ. Returns whether the rules became confluent
+    /// within the budget.
+    fn complete_rules(&mut self) -> bool {
+        let mut checked = 0;
+
+        while checked < self.rules.len() {
+            if self.rules.len() > MAX_RULES {
+                return false;
+            }
+
+            let rule = self.rules[checked].clone();
+            for other in 0..=checked {
+                let other = self.rules[other].clone();
+                for (a, b) in critical_pairs(&rule, &other)
+                    .into_iter()
+                    .chain(critical_pairs(&other, &rule))
+                {
+                    self.add_rule(a, b);
+                }
+            }
+            checked += 1;
+        }
+
+        true
+    }
+
+    /// Search for a chain of rewrites, in either direction, from one word
+    /// to another.
+    fn connected(&self, from: Word, to: Word) -> bool {
+        let longest_rule = self.rules.iter().map(|r| r.lhs.len()).max().unwrap_or(0);
+        let max_len = from.len().max(to.len()) + longest_rule;
+
+        let mut seen = HashSet::from([from.clone()]);
+        let mut queue = VecDeque::from([from]);
+
+        while let Some(word) = queue.pop_front() {
+            if word == to {
+                return true;
+            }
+
+            for rule in &self.rules {
+                for (pattern, replacement) in [(&rule.lhs, &rule.rhs), (&rule.rhs, &rule.lhs)] {
+                    for at in occurrences(&word, pattern) {
+                        let mut next = word.clone();
+                        next.splice(at..at + pattern.len(), replacement.iter().copied());
+                        if next.len() <= max_len && seen.insert(next.clone()) {
+                            if seen.len() > MAX_SEARCH_STATES {
+                                return false;
+                            }
+                            queue.push_back(next);
+                        }
+                    }
+                }
+            }
+        }
+
+        false
+    }
+}
+
+/// Compare words by length, then lexicographically.
+fn shortlex(a: &[MorphismId], b: &[MorphismId]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+/// Find the first occurrence of a non-empty pattern in a word.
+fn find(word: &[MorphismId], pattern: &[MorphismId]) -> Option<usize> {
+    occurrences(word, pattern).next()
+}
+
+/// Find every position where a pattern occurs in a word.
+///
+/// An empty pattern is treated as occurring nowhere, so rules with an
+/// empty side are only ever applied from their non-empty side.
+fn occurrences<'a>(
+    word: &'a [MorphismId],
+    pattern: &'a [MorphismId],
+) -> impl Iterator<Item = usize> + 'a {
+    let positions = if pattern.is_empty() || pattern.len() > word.len() {
+        0
+    } else {
+        word.len() - pattern.len() + 1
+    };
+    (0..positions).filter(move |&i| &word[i..i + pattern.len()] == pattern)
+}
+
+/// Compute the critical pairs where the left-hand side of `r2` overlaps
+/// the end of, or lies inside, the left-hand side of `r1`.
+fn critical_pairs(r1: &Rule, r2: &Rule) -> Vec<(Word, Word)> {
+    let (l1, l2) = (&r1.lhs, &r2.lhs);
+    let mut pairs = Vec::new();
+
+    // A suffix of l1 is a proper prefix of l2: l1 = xy, l2 = yz
+    for k in 1..l1.len().min(l2.len()) {
+        if l1[l1.len() - k..] == l2[..k] {
+            let x = &l1[..l1.len() - k];
+            let z = &l2[k..];
+            pairs.push((
+                [&r1.rhs[..], z].concat(),
+                [x, &r2.rhs[..]].concat(),
+            ));
+        }
+    }
+
+    // l2 occurs inside l1: l1 = x l2 z
+    if r1 != r2 {
+        for at in occurrences(l1, l2) {
+            let x = &l1[..at];
+            let z = &l1[at + l2.len()..];
+            pairs.push((r1.rhs.clone(), [x, &r2.rhs[..], z].concat()));
+        }
+    }
+
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sketch::Sketch;
+
+    #[test]
+    fn test_paths_equal_by_rewriting() {
+        let mut sketch = Sketch::new("Commerce");
+        let order = sketch.add_object("Order");
+        let customer = sketch.add_object("Customer");
+        let address = sketch.add_object("Address");
+        let placed_by = sketch.add_morphism("placedBy", order, customer);
+        let lives_at = sketch.add_morphism("livesAt", customer, address);
+        let ships_to = sketch.add_morphism("shipsTo", order, address);
+        let id_order = sketch.graph.add_identity_morphism(order);
+
+        let lhs = Path::new(order, address, vec![ships_to]);
+        let rhs = Path::new(order, address, vec![placed_by, lives_at]);
+        assert!(!sketch.paths_equal(&lhs, &rhs));
+
+        sketch.add_equation(PathEquation::new("shipping", lhs.clone(), rhs.clone()));
+        assert!(sketch.paths_equal(&lhs, &rhs));
+        assert!(sketch.paths_equal(
+            &Path::new(order, address, vec![id_order, placed_by, lives_at]),
+            &lhs
+        ));
+        assert!(!sketch.paths_equal(&Path::identity(order), &lhs));
+    }
+
+    #[test]
+    fn test_completion_finds_derived_equalities() {
+        // f.g = h and g.k = m imply f.m = h.k
+        let mut sketch = Sketch::new("Chain");
+        let a = sketch.add_object("A");
+        let b = sketch.add_object("B");
+        let c = sketch.add_object("C");
+        let d = sketch.add_object("D");
+        let f = sketch.add_morphism("f", a, b);
+        let g = sketch.add_morphism("g", b, c);
+        let h = sketch.add_morphism("h", a, c);
+        let k = sketch.add_morphism("k", c, d);
+        let m = sketch.add_morphism("m", b, d);
+
+        sketch.add_equation(PathEquation::new(
+            "fg",
+            Path::new(a, c, vec![f, g]),
+            Path::new(a, c, vec![h]),
+        ));
+        sketch.add_equation(PathEquation::new(
+            "gk",
+            Path::new(b, d, vec![g, k]),
+            Path::new(b, d, vec![m]),
+        ));
+
+        let system = RewriteSystem::new(&sketch.graph, &sketch.equations);
+        assert!(system.is_complete());
+        assert!(system.equal(&Path::new(a, d, vec![f, m]), &Path::new(a, d, vec![h, k])));
+        assert!(!system.equal(&Path::new(a, d, vec![f, m]), &Path::new(a, d, vec![f, g, m])));
+    }
+
+    #[test]
+    fn test_idempotent_morphism() {
+        // n.n = n makes every non-empty power of n equal
+        let mut sketch = Sketch::new("Loop");
+        let a = sketch.add_object("A");
+        let n = sketch.add_morphism("normalize", a, a);
+        sketch.add_equation(PathEquation::new(
+            "idempotent",
+            Path::new(a, a, vec![n, n]),
+            Path::new(a, a, vec![n]),
+        ));
+
+        assert!(sketch.paths_equal(&Path::new(a, a, vec![n, n, n]), &Path::new(a, a, vec![n])));
+        assert!(!sketch.paths_equal(&Path::new(a, a, vec![n]), &Path::identity(a)));
+    }
+}
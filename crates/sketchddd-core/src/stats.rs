@@ -0,0 +1,309 @@
+//! Size and coupling metrics of a model.
+//!
+//! [`Workspace::stats`] counts what each context declares, measures its
+//! aggregates and finds which contexts the context maps couple, for
+//! `sketchddd stats` and dashboards tracking a model as it grows.
+//!
+//! Morphisms are counted as declared: identities, the projections
+//! SketchDDD adds to aggregates and the inclusions of invariants are left
+//! out, like in the design limits of the lint rules. The size of an
+//! aggregate is the number of objects in it, its root included.
+
+use crate::context::BoundedContext;
+use crate::lint::generated_morphisms;
+use crate::workspace::Workspace;
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+/// Counts of what a context, or a whole model, declares.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Counts {
+    /// Entities, aggregate roots included
+    pub entities: usize,
+
+    /// Value objects
+    pub value_objects: usize,
+
+    /// Enums and sum types
+    pub enums: usize,
+
+    /// Aggregates
+    pub aggregates: usize,
+
+    /// Declared morphisms
+    pub morphisms: usize,
+
+    /// Path equations and invariants
+    pub equations: usize,
+
+    /// Mean number of objects in an aggregate, 0 without aggregates
+    pub average_aggregate_size: f64,
+}
+
+/// Metrics of a bounded context.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ContextStats {
+    /// Name of the context
+    pub name: String,
+
+    /// What the context declares
+    #[serde(flatten)]
+    pub counts: Counts,
+
+    /// Names of the other contexts a context map relates it to, in name
+    /// order
+    pub coupled_with: Vec<String>,
+}
+
+/// The size of an aggregate.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AggregateSize {
+    /// Context the aggregate is in
+    pub context: String,
+
+    /// Name of the aggregate's root
+    pub root: String,
+
+    /// Number of objects in the aggregate, its root included
+    pub size: usize,
+}
+
+/// The context maps from one context to another.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Coupling {
+    /// The source (upstream) context
+    pub source: String,
+
+    /// The target (downstream) context
+    pub target: String,
+
+    /// Names of the maps, in declaration order
+    pub maps: Vec<String>,
+
+    /// Object mappings across the maps
+    pub mapped_objects: usize,
+}
+
+/// Metrics of a whole model.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ModelStats {
+    /// What the model declares, summed over its contexts
+    pub totals: Counts,
+
+    /// Number of context maps
+    pub context_maps: usize,
+
+    /// Metrics of each context, in declaration order
+    pub contexts: Vec<ContextStats>,
+
+    /// Pairs of contexts related by context maps, in the order of their
+    /// first map
+    pub couplings: Vec<Coupling>,
+
+    /// Every aggregate, largest first, then by context and root
+    pub aggregates: Vec<AggregateSize>,
+}
+
+impl ModelStats {
+    /// Get the `n` largest aggregates.
+    pub fn largest_aggregates(&self, n: usize) -> &[AggregateSize] {
+        &self.aggregates[..n.min(self.aggregates.len())]
+    }
+}
+
+/// Get the mean size of aggregates, 0 when there are none.
+fn average_size(sizes: &[AggregateSize]) -> f64 {
+    match sizes.len() {
+        0 => 0.0,
+        count => sizes.iter().map(|a| a.size).sum::<usize>() as f64 / count as f64,
+    }
+}
+
+/// Measure the aggregates of a context, in declaration order.
+fn aggregate_sizes(context: &BoundedContext) -> Vec<AggregateSize> {
+    let graph = context.graph();
+    context
+        .aggregate_roots()
+        .iter()
+        .filter_map(|&root| {
+            let aggregate = context.get_aggregate(root)?;
+            let mut objects: BTreeSet<_> = aggregate.component_objects().collect();
+            objects.insert(root);
+            Some(AggregateSize {
+                context: context.name().to_string(),
+                root: graph.get_object(root)?.name.clone(),
+                size: objects.len(),
+            })
+        })
+        .collect()
+}
+
+/// Count what a context declares, given its aggregates.
+fn counts(context: &BoundedContext, aggregates: &[AggregateSize]) -> Counts {
+    let generated = generated_morphisms(context);
+    Counts {
+        entities: context.entities().len(),
+        value_objects: context.value_objects().len(),
+        enums: context.sketch().colimits.len(),
+        aggregates: aggregates.len(),
+        morphisms: context
+            .graph()
+            .morphisms()
+            .filter(|m| !generated.contains(&m.id))
+            .count(),
+        equations: context.sketch().equations.len(),
+        average_aggregate_size: average_size(aggregates),
+    }
+}
+
+impl Workspace {
+    /// Measure the model: what each context declares, how large its
+    /// aggregates are and which contexts the context maps couple.
+    pub fn stats(&self) -> ModelStats {
+        let mut stats = ModelStats {
+            context_maps: self.context_maps.len(),
+            ..ModelStats::default()
+        };
+
+        for map in &self.context_maps {
+            let (source, target) = (map.source_context(), map.target_context());
+            let coupling = match stats
+                .couplings
+                .iter_mut()
+                .find(|c| c.source == source && c.target == target)
+            {
+                Some(coupling) => coupling,
+                None => {
+                    stats.couplings.push(Coupling {
+                        source: source.to_string(),
+                        target: target.to_string(),
+                        maps: Vec::new(),
+                        mapped_objects: 0,
+                    });
+                    stats.couplings.last_mut().expect("a coupling was added")
+                }
+            };
+            coupling.maps.push(map.name().to_string());
+            coupling.mapped_objects += map.object_mappings().len();
+        }
+
+        for context in &self.contexts {
+            let aggregates = aggregate_sizes(context);
+            let counts = counts(context, &aggregates);
+            let totals = &mut stats.totals;
+            totals.entities += counts.entities;
+            totals.value_objects += counts.value_objects;
+            totals.enums += counts.enums;
+            totals.aggregates += counts.aggregates;
+            totals.morphisms += counts.morphisms;
+            totals.equations += counts.equations;
+
+            let coupled_with: BTreeSet<&str> = stats
+                .couplings
+                .iter()
+                .filter_map(|c| match context.name() {
+                    name if name == c.source && name != c.target => Some(c.target.as_str()),
+                    name if name == c.target && name != c.source => Some(c.source.as_str()),
+                    _ => None,
+                })
+                .collect();
+            stats.contexts.push(ContextStats {
+                name: context.name().to_string(),
+                counts,
+                coupled_with: coupled_with.into_iter().map(str::to_string).collect(),
+            });
+            stats.aggregates.extend(aggregates);
+        }
+
+        stats.totals.average_aggregate_size = average_size(&stats.aggregates);
+        stats.aggregates.sort_by(|a, b| {
+            b.size
+                .cmp(&a.size)
+                .then_with(|| (&a.context, &a.root).cmp(&(&b.context, &b.root)))
+        });
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapping::{NamedContextMap, NamedObjectMapping, RelationshipPattern};
+
+    fn sales() -> BoundedContext {
+        let mut context = BoundedContext::new("Sales");
+        let order = context.add_entity("Order");
+        let line_item = context.add_entity("LineItem");
+        let customer = context.add_entity("Customer");
+        let money = context.add_value_object("Money");
+        context.add_enum("OrderStatus", vec!["Draft".into(), "Placed".into()]);
+        context
+            .sketch_mut()
+            .graph
+            .add_morphism("placedBy", order, customer);
+        context
+            .sketch_mut()
+            .graph
+            .add_morphism("total", order, money);
+        context.define_aggregate_with_members("Order", order, &[line_item, money]);
+        context.define_aggregate_with_members("Customer", customer, &[]);
+        context
+    }
+
+    #[test]
+    fn test_context_stats() {
+        let mut workspace = Workspace::new();
+        workspace.add_context(sales());
+        let stats = workspace.stats();
+
+        let sales = &stats.contexts[0];
+        assert_eq!(sales.name, "Sales");
+        assert_eq!(sales.counts.entities, 3);
+        assert_eq!(sales.counts.value_objects, 1);
+        assert_eq!(sales.counts.enums, 1);
+        assert_eq!(sales.counts.aggregates, 2);
+        assert_eq!(sales.counts.morphisms, 2);
+        assert_eq!(sales.counts.average_aggregate_size, 2.0);
+        assert_eq!(stats.totals, sales.counts);
+
+        let largest = stats.largest_aggregates(1);
+        assert_eq!(largest.len(), 1);
+        assert_eq!((largest[0].root.as_str(), largest[0].size), ("Order", 3));
+        assert_eq!(stats.largest_aggregates(5).len(), 2);
+    }
+
+    #[test]
+    fn test_coupling_stats() {
+        let mut workspace = Workspace::new();
+        for name in ["Sales", "Billing", "Shipping"] {
+            workspace.add_context(BoundedContext::new(name));
+        }
+        let mut map = NamedContextMap::new(
+            "SalesToBilling",
+            "Sales",
+            "Billing",
+            RelationshipPattern::CustomerSupplier,
+        );
+        map.add_object_mapping(NamedObjectMapping {
+            source: "Order".into(),
+            target: "Invoice".into(),
+            description: None,
+        });
+        workspace.add_context_map(map);
+        workspace.add_context_map(NamedContextMap::new(
+            "SalesToShipping",
+            "Sales",
+            "Shipping",
+            RelationshipPattern::Conformist,
+        ));
+
+        let stats = workspace.stats();
+        assert_eq!(stats.context_maps, 2);
+        assert_eq!(stats.couplings.len(), 2);
+        assert_eq!(stats.couplings[0].maps, vec!["SalesToBilling"]);
+        assert_eq!(stats.couplings[0].mapped_objects, 1);
+        assert_eq!(stats.contexts[0].coupled_with, vec!["Billing", "Shipping"]);
+        assert_eq!(stats.contexts[1].coupled_with, vec!["Sales"]);
+        assert_eq!(stats.totals.average_aggregate_size, 0.0);
+    }
+}
@@ -0,0 +1,206 @@
+//! Accepting known issues, so validation can be adopted incrementally.
+//!
+//! A model rarely passes every lint rule on the day the rules are turned on.
+//! Two mechanisms let a team accept the issues it has while still hearing
+//! about new ones:
+//!
+//! - a [`Suppression`], written `@allow(W0001)` on a declaration, accepts the
+//!   issues of a rule about that declaration, or about anything in the
+//!   context when written on the context itself;
+//! - a [`Baseline`], kept in `sketchddd-baseline.json`, records the issues a
+//!   model had when it was taken, and only issues beyond those are reported.
+//!
+//! Both apply to lint rules only: errors make a model invalid and cannot be
+//! accepted.
+
+use crate::context::BoundedContext;
+use crate::validation::{Severity, ValidationError, ValidationResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Permission for an element to break a lint rule.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Suppression {
+    /// Code of the rule, such as `W0001`
+    pub code: String,
+
+    /// Name of the element, or `None` for the whole context
+    pub element: Option<String>,
+}
+
+impl Suppression {
+    /// Create a suppression of a rule for an element.
+    pub fn new(code: impl Into<String>, element: Option<String>) -> Self {
+        Self {
+            code: code.into(),
+            element,
+        }
+    }
+
+    /// Check if the suppression, declared in `context`, covers an issue.
+    ///
+    /// Issues do not point at model elements, so an issue is taken to be
+    /// about an element when its message quotes the element's name.
+    pub fn covers(&self, context: &BoundedContext, issue: &ValidationError) -> bool {
+        issue.code == self.code
+            && concerns(context, &issue.message)
+            && self
+                .element
+                .as_ref()
+                .is_none_or(|element| mentions(&issue.message, element))
+    }
+}
+
+/// Check if a message quotes a name, as in `Aggregate 'Order' ...`.
+fn mentions(message: &str, name: &str) -> bool {
+    message.contains(&format!("'{}'", name))
+}
+
+/// Check if an issue message is about a context.
+///
+/// Model validation prefixes the issues within a context with `[Name] `.
+/// Other issues are about the context when they quote its name or the name
+/// of one of its objects or morphisms.
+fn concerns(context: &BoundedContext, message: &str) -> bool {
+    if let Some((prefix, _)) = message
+        .strip_prefix('[')
+        .and_then(|rest| rest.split_once("] "))
+    {
+        return prefix == context.name();
+    }
+    let graph = context.graph();
+    mentions(message, context.name())
+        || graph.objects().any(|o| mentions(message, &o.name))
+        || graph.morphisms().any(|m| mentions(message, &m.name))
+}
+
+/// An issue recorded in a baseline.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct BaselineIssue {
+    /// Model file the issue was found in, if the baseline covers several
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+
+    /// Code of the issue
+    pub code: String,
+
+    /// Message of the issue
+    pub message: String,
+}
+
+/// The issues a model had when the baseline was taken.
+///
+/// Issues are recorded by code and message rather than by location, so
+/// that editing other parts of the model does not make them new again.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Baseline {
+    /// The recorded issues
+    pub issues: Vec<BaselineIssue>,
+}
+
+impl Baseline {
+    /// Create an empty baseline.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the current issues of a file, replacing those recorded for it
+    /// before.
+    ///
+    /// Errors are not recorded, since they cannot be accepted.
+    pub fn record(&mut self, file: Option<&str>, issues: &[ValidationError]) {
+        self.issues.retain(|issue| issue.file.as_deref() != file);
+        self.issues.extend(
+            issues
+                .iter()
+                .filter(|issue| issue.severity != Severity::Error)
+                .map(|issue| BaselineIssue {
+                    file: file.map(str::to_string),
+                    code: issue.code.clone(),
+                    message: issue.message.clone(),
+                }),
+        );
+        self.issues.sort();
+    }
+
+    /// Remove the issues of a file that the baseline records, keeping the
+    /// new ones.
+    ///
+    /// Each recorded issue accounts for one reported issue, so an issue
+    /// reported more often than it was recorded is new.
+    pub fn remove_known(&self, file: Option<&str>, result: &mut ValidationResult) {
+        let mut known: HashMap<(String, String), usize> = HashMap::new();
+        for issue in self.issues.iter().filter(|i| i.file.as_deref() == file) {
+            *known
+                .entry((issue.code.clone(), issue.message.clone()))
+                .or_default() += 1;
+        }
+        result.issues.retain(|issue| {
+            if issue.severity == Severity::Error {
+                return true;
+            }
+            match known.get_mut(&(issue.code.clone(), issue.message.clone())) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    false
+                }
+                _ => true,
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suppression_covers() {
+        let mut sales = BoundedContext::new("Sales");
+        let order = sales.add_entity("Order");
+        let customer = sales.add_entity("Customer");
+        sales.sketch_mut().add_morphism("placedBy", order, customer);
+
+        let issue = |message: &str| ValidationError::warning("W0041", message);
+        let for_order = Suppression::new("W0041", Some("Order".to_string()));
+        assert!(for_order.covers(
+            &sales,
+            &issue("Object 'Order' has 4 morphisms, more than 3")
+        ));
+        assert!(!for_order.covers(&sales, &issue("Object 'Customer' has 4 morphisms")));
+        assert!(!for_order.covers(&sales, &issue("[Billing] Object 'Order' has 4 morphisms")));
+
+        let for_context = Suppression::new("W0041", None);
+        assert!(for_context.covers(&sales, &issue("Object 'Customer' has 4 morphisms")));
+        assert!(!for_context.covers(&sales, &issue("Object 'Invoice' has 4 morphisms")));
+        assert!(!for_context.covers(
+            &sales,
+            &ValidationError::warning("W0001", "[Sales] Aggregate 'Order' is large")
+        ));
+    }
+
+    #[test]
+    fn test_baseline_reports_new_issues() {
+        let large = ValidationError::warning("W0001", "[Sales] Aggregate 'Order' is large");
+        let unused = ValidationError::warning("W0080", "[Sales] Object 'Note' is not used");
+        let invalid = ValidationError::error("E0020", "[Sales] Duplicate object name: 'Order'");
+
+        let mut baseline = Baseline::new();
+        baseline.record(Some("sales.sddd"), &[large.clone(), invalid.clone()]);
+        assert_eq!(baseline.issues.len(), 1);
+
+        let mut result = ValidationResult::new();
+        for issue in [&large, &large, &unused, &invalid] {
+            result.add(issue.clone());
+        }
+        baseline.remove_known(Some("sales.sddd"), &mut result);
+        let codes: Vec<_> = result.issues.iter().map(|i| i.code.as_str()).collect();
+        assert_eq!(codes, vec!["W0001", "W0080", "E0020"]);
+
+        // Recorded for another file, nothing is known
+        let mut result = ValidationResult::new();
+        result.add(large);
+        baseline.remove_known(Some("billing.sddd"), &mut result);
+        assert_eq!(result.issues.len(), 1);
+    }
+}
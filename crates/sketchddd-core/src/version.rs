@@ -0,0 +1,114 @@
+//! Model versions and scheduled removals for schema evolution.
+//!
+//! A bounded context may declare the version of its model, and individual
+//! objects or fields may announce the version in which they will be removed.
+//! Code generators turn announced removals into deprecation markers, and
+//! model diffs compare versions to decide whether a breaking change was
+//! released with the required major version bump.
+
+use crate::sketch::ObjectId;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// A semantic model version such as `2`, `2.1` or `2.1.3`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct ModelVersion {
+    /// Major version, bumped for breaking changes
+    pub major: u32,
+
+    /// Minor version, bumped for compatible additions
+    pub minor: u32,
+
+    /// Patch version
+    pub patch: u32,
+}
+
+impl ModelVersion {
+    /// Create a new version.
+    pub fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self { major, minor, patch }
+    }
+
+    /// Check if going from `self` to `next` is a major version bump.
+    pub fn is_major_bump(&self, next: &ModelVersion) -> bool {
+        next.major > self.major
+    }
+}
+
+impl fmt::Display for ModelVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.patch != 0 {
+            write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+        } else {
+            write!(f, "{}.{}", self.major, self.minor)
+        }
+    }
+}
+
+impl FromStr for ModelVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.trim().split('.').collect();
+        if parts.is_empty() || parts.len() > 3 {
+            return Err(format!("Invalid version '{}'", s));
+        }
+
+        let mut numbers = [0u32; 3];
+        for (i, part) in parts.iter().enumerate() {
+            numbers[i] = part
+                .parse()
+                .map_err(|_| format!("Invalid version '{}': expected numbers like 2 or 2.1", s))?;
+        }
+
+        Ok(Self::new(numbers[0], numbers[1], numbers[2]))
+    }
+}
+
+/// A scheduled removal of an object or one of its fields.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Removal {
+    /// The object being removed, or owning the removed field
+    pub object: ObjectId,
+
+    /// The field being removed, if only a field is affected
+    pub field: Option<String>,
+
+    /// The version in which the element will be removed
+    pub removed_in: ModelVersion,
+}
+
+impl Removal {
+    /// A short deprecation note, e.g. `Removed in 3.0`.
+    pub fn note(&self) -> String {
+        format!("Removed in {}", self.removed_in)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_versions() {
+        assert_eq!("2".parse::<ModelVersion>().unwrap(), ModelVersion::new(2, 0, 0));
+        assert_eq!("3.1".parse::<ModelVersion>().unwrap(), ModelVersion::new(3, 1, 0));
+        assert_eq!("1.2.3".parse::<ModelVersion>().unwrap(), ModelVersion::new(1, 2, 3));
+        assert!("v2".parse::<ModelVersion>().is_err());
+        assert!("1.2.3.4".parse::<ModelVersion>().is_err());
+    }
+
+    #[test]
+    fn test_version_ordering_and_display() {
+        let v2 = ModelVersion::new(2, 0, 0);
+        let v21 = ModelVersion::new(2, 1, 0);
+        let v3 = ModelVersion::new(3, 0, 0);
+
+        assert!(v2 < v21 && v21 < v3);
+        assert!(v2.is_major_bump(&v3));
+        assert!(!v2.is_major_bump(&v21));
+        assert_eq!(v3.to_string(), "3.0");
+        assert_eq!(ModelVersion::new(1, 2, 3).to_string(), "1.2.3");
+    }
+}
@@ -0,0 +1,186 @@
+//! The whole model: every bounded context and how they relate.
+//!
+//! A [`Workspace`] is what a SketchDDD file (or a set of files) describes
+//! as a whole: its bounded contexts, the context maps between them, shared
+//! kernels, the domains grouping the contexts, and a glossary of the
+//! ubiquitous language. Tools that work on a model rather than on a single
+//! context, such as `sketchddd check` or a context map diagram, take one.
+
+use crate::context::BoundedContext;
+use crate::domain::{Domain, Subdomain};
+use crate::mapping::NamedContextMap;
+use crate::validation::{validate_domains, validate_model, ValidationResult};
+use crate::version::ModelVersion;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A complete model.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Workspace {
+    /// Bounded contexts, in declaration order
+    pub contexts: Vec<BoundedContext>,
+
+    /// Context maps between the contexts
+    pub context_maps: Vec<NamedContextMap>,
+
+    /// Shared kernels, as contexts holding only the shared objects
+    #[serde(default)]
+    pub shared_kernels: Vec<BoundedContext>,
+
+    /// Domains grouping the contexts into subdomains
+    #[serde(default)]
+    pub domains: Vec<Domain>,
+
+    /// Terms of the ubiquitous language and their definitions
+    #[serde(default)]
+    pub glossary: BTreeMap<String, String>,
+
+    /// Version of the model as a whole, if declared
+    #[serde(default)]
+    pub version: Option<ModelVersion>,
+}
+
+impl Workspace {
+    /// Create an empty workspace.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a bounded context.
+    pub fn add_context(&mut self, context: BoundedContext) {
+        self.contexts.push(context);
+    }
+
+    /// Add a context map.
+    pub fn add_context_map(&mut self, map: NamedContextMap) {
+        self.context_maps.push(map);
+    }
+
+    /// Get a bounded context by name.
+    pub fn context(&self, name: &str) -> Option<&BoundedContext> {
+        self.contexts.iter().find(|c| c.name() == name)
+    }
+
+    /// Get a mutable reference to a bounded context by name.
+    pub fn context_mut(&mut self, name: &str) -> Option<&mut BoundedContext> {
+        self.contexts.iter_mut().find(|c| c.name() == name)
+    }
+
+    /// Get a context map by name.
+    pub fn context_map(&self, name: &str) -> Option<&NamedContextMap> {
+        self.context_maps.iter().find(|m| m.name() == name)
+    }
+
+    /// Get a shared kernel by name.
+    pub fn shared_kernel(&self, name: &str) -> Option<&BoundedContext> {
+        self.shared_kernels.iter().find(|k| k.name() == name)
+    }
+
+    /// Get the subdomain a bounded context belongs to, if any.
+    pub fn subdomain_of(&self, context: &str) -> Option<&Subdomain> {
+        self.domains.iter().find_map(|d| d.subdomain_of(context))
+    }
+
+    /// Define a term of the ubiquitous language, replacing any previous
+    /// definition.
+    pub fn define_term(&mut self, term: impl Into<String>, definition: impl Into<String>) {
+        self.glossary.insert(term.into(), definition.into());
+    }
+
+    /// Get the definition of a term.
+    pub fn definition(&self, term: &str) -> Option<&str> {
+        self.glossary.get(term).map(|d| d.as_str())
+    }
+
+    /// Validate the whole model: every context, the context maps between
+    /// them and the subdomains grouping them.
+    pub fn validate(&self) -> ValidationResult {
+        let mut result = validate_model(&self.contexts, &self.context_maps);
+        for issue in validate_domains(&self.domains, &self.contexts).issues {
+            result.add(issue);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapping::RelationshipPattern;
+
+    fn commerce() -> Workspace {
+        let mut workspace = Workspace::new();
+        let mut sales = BoundedContext::new("Sales");
+        sales.add_entity("Order");
+        workspace.add_context(sales);
+        workspace.add_context(BoundedContext::new("Shipping"));
+        workspace.add_context_map(NamedContextMap::new(
+            "SalesToShipping",
+            "Sales",
+            "Shipping",
+            RelationshipPattern::CustomerSupplier,
+        ));
+        workspace.define_term("Order", "A customer's request to buy products");
+        workspace
+    }
+
+    #[test]
+    fn test_lookups() {
+        let mut workspace = commerce();
+
+        assert!(workspace.context("Sales").is_some());
+        assert!(workspace.context("Billing").is_none());
+        assert_eq!(
+            workspace
+                .context_map("SalesToShipping")
+                .unwrap()
+                .target_context(),
+            "Shipping"
+        );
+        assert_eq!(
+            workspace.definition("Order"),
+            Some("A customer's request to buy products")
+        );
+
+        workspace
+            .context_mut("Shipping")
+            .unwrap()
+            .add_entity("Shipment");
+        assert_eq!(workspace.context("Shipping").unwrap().entities().len(), 1);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut workspace = commerce();
+        assert!(workspace.validate().is_ok());
+
+        let mut ordering = Subdomain::new("Ordering");
+        ordering.add_context("Billing");
+        let mut domain = Domain::new("Commerce");
+        domain.add_subdomain(ordering);
+        workspace.domains.push(domain);
+
+        let result = workspace.validate();
+        assert!(result.errors().any(|e| e.code == "E0072"));
+    }
+
+    #[test]
+    fn test_serialization_roundtrip() {
+        let mut workspace = commerce();
+        workspace.version = Some(ModelVersion::new(2, 0, 0));
+
+        let json = serde_json::to_string(&workspace).unwrap();
+        let restored: Workspace = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.contexts.len(), 2);
+        assert_eq!(restored.context_maps[0].name(), "SalesToShipping");
+        assert_eq!(restored.glossary, workspace.glossary);
+        assert_eq!(restored.version, Some(ModelVersion::new(2, 0, 0)));
+        assert!(restored
+            .context("Sales")
+            .unwrap()
+            .graph()
+            .find_object_by_name("Order")
+            .is_some());
+    }
+}
@@ -1,11 +1,14 @@
 //! LSP Backend implementation
 
 use dashmap::DashMap;
+use sketchddd_core::{Fingerprint, Memo};
+use std::sync::Mutex;
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer};
 
 use crate::capabilities::server_capabilities;
+use crate::code_actions::provide_code_actions;
 use crate::completion::provide_completions;
 use crate::diagnostics::publish_diagnostics;
 use crate::document::Document;
@@ -18,6 +21,8 @@ pub struct SketchDDDBackend {
     client: Client,
     /// Open documents indexed by URI
     documents: DashMap<Url, Document>,
+    /// Diagnostics of each open document, kept until its text changes
+    diagnostics: Mutex<Memo<Url, Vec<Diagnostic>>>,
 }
 
 impl SketchDDDBackend {
@@ -26,6 +31,7 @@ impl SketchDDDBackend {
         Self {
             client,
             documents: DashMap::new(),
+            diagnostics: Mutex::default(),
         }
     }
 
@@ -35,10 +41,17 @@ impl SketchDDDBackend {
         self.documents.get(uri)
     }
 
-    /// Update a document and publish diagnostics
+    /// Update a document and publish diagnostics. Text that did not change,
+    /// as when the document is saved, is not checked again.
     async fn update_document(&self, uri: Url, text: String, version: i32) {
+        let fingerprint = Fingerprint::of(&text);
         let document = Document::new(uri.clone(), text, version);
-        let diagnostics = publish_diagnostics(&document);
+        let diagnostics = self
+            .diagnostics
+            .lock()
+            .expect("diagnostics lock poisoned")
+            .get_or_derive(uri.clone(), fingerprint, || publish_diagnostics(&document))
+            .clone();
         self.documents.insert(uri.clone(), document);
         self.client
             .publish_diagnostics(uri, diagnostics, Some(version))
@@ -101,8 +114,14 @@ impl LanguageServer for SketchDDDBackend {
 
         // Re-validate on save if we have the text
         if let Some(text) = params.text {
-            if let Some(doc) = self.documents.get(&params.text_document.uri) {
-                self.update_document(params.text_document.uri.clone(), text, doc.version).await;
+            // Not holding the document while it is replaced
+            let version = self
+                .documents
+                .get(&params.text_document.uri)
+                .map(|doc| doc.version);
+            if let Some(version) = version {
+                self.update_document(params.text_document.uri, text, version)
+                    .await;
             }
         }
     }
@@ -110,6 +129,10 @@ impl LanguageServer for SketchDDDBackend {
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
         tracing::debug!("Document closed: {}", params.text_document.uri);
         self.documents.remove(&params.text_document.uri);
+        self.diagnostics
+            .lock()
+            .expect("diagnostics lock poisoned")
+            .retain(|uri| uri != &params.text_document.uri);
         // Clear diagnostics
         self.client
             .publish_diagnostics(params.text_document.uri, vec![], None)
@@ -199,4 +222,16 @@ impl LanguageServer for SketchDDDBackend {
         }
         Ok(None)
     }
+
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let uri = &params.text_document.uri;
+
+        if let Some(doc) = self.documents.get(uri) {
+            let actions = provide_code_actions(&doc, params.range);
+            if !actions.is_empty() {
+                return Ok(Some(actions));
+            }
+        }
+        Ok(None)
+    }
 }
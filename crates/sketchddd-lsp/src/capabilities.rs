@@ -45,6 +45,12 @@ pub fn server_capabilities() -> ServerCapabilities {
         // Formatting
         document_formatting_provider: Some(OneOf::Left(true)),
 
+        // Quick fixes for validation issues
+        code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+            code_action_kinds: Some(vec![CodeActionKind::QUICKFIX]),
+            ..Default::default()
+        })),
+
         // Semantic tokens for enhanced highlighting
         semantic_tokens_provider: Some(
             SemanticTokensServerCapabilities::SemanticTokensOptions(SemanticTokensOptions {
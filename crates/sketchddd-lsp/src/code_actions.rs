@@ -0,0 +1,83 @@
+//! Quick fixes for validation issues
+
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::*;
+
+use sketchddd_core::{Fix, LintConfig, Registry};
+use sketchddd_parser::{attach_fixes, parse_file, transform};
+
+use crate::document::Document;
+
+/// Provide the quick fixes for the issues on the lines of a range
+pub fn provide_code_actions(document: &Document, range: Range) -> Vec<CodeActionOrCommand> {
+    let text = document.text();
+    let Ok(file) = parse_file(&text) else {
+        return Vec::new();
+    };
+    let Ok(result) = transform(&file) else {
+        return Vec::new();
+    };
+
+    // Fixes with the 1-indexed line of the issue they address
+    let mut fixes: Vec<(Option<u32>, Fix)> = result
+        .warnings
+        .iter()
+        .filter_map(|w| Some((w.line, w.fix.clone()?)))
+        .collect();
+    let mut issues = result
+        .into_workspace()
+        .lint(&Registry::builtin(), &LintConfig::new())
+        .issues;
+    attach_fixes(&text, &file, &mut issues);
+    fixes.extend(
+        issues
+            .into_iter()
+            .filter_map(|i| Some((i.location.line, i.fix?))),
+    );
+
+    fixes
+        .into_iter()
+        .filter(|(line, _)| {
+            line.is_some_and(|line| {
+                let line = line.saturating_sub(1);
+                range.start.line <= line && line <= range.end.line
+            })
+        })
+        .map(|(_, fix)| CodeActionOrCommand::CodeAction(code_action(document, fix)))
+        .collect()
+}
+
+/// Convert a fix into a code action editing the document
+fn code_action(document: &Document, fix: Fix) -> CodeAction {
+    let edits = fix
+        .edits
+        .iter()
+        .map(|edit| TextEdit {
+            range: Range {
+                start: position(document, edit.start),
+                end: position(document, edit.end),
+            },
+            new_text: edit.new_text.clone(),
+        })
+        .collect();
+
+    CodeAction {
+        title: fix.title,
+        kind: Some(CodeActionKind::QUICKFIX),
+        edit: Some(WorkspaceEdit {
+            changes: Some(HashMap::from([(document.uri.clone(), edits)])),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Convert a byte offset into a position in the document
+fn position(document: &Document, offset: usize) -> Position {
+    let content = &document.content;
+    let char_idx = content.byte_to_char(offset.min(content.len_bytes()));
+    let line = content.char_to_line(char_idx);
+    let character = char_idx - content.line_to_char(line);
+    Position::new(line as u32, character as u32)
+}
@@ -146,8 +146,8 @@ fn add_semantic_diagnostics(text: &str, diagnostics: &mut Vec<Diagnostic>) {
 
         // Extract type name from entity/value/enum definitions
         for keyword in &["entity", "value", "enum", "aggregate"] {
-            if trimmed.starts_with(keyword) {
-                let rest = trimmed[keyword.len()..].trim_start();
+            if let Some(rest) = trimmed.strip_prefix(keyword) {
+                let rest = rest.trim_start();
                 let name: String = rest.chars().take_while(|c| c.is_alphanumeric()).collect();
 
                 if !name.is_empty() {
@@ -73,7 +73,7 @@ fn keyword_hover(word: &str) -> Option<Hover> {
         ),
         "invariant" => (
             "invariant",
-            "Defines an **Invariant** - a business rule that must always hold.\n\nInvariants are commutative diagrams ensuring consistency across the aggregate.",
+            "Defines an **Invariant** - a business rule that must always hold.\n\nInvariants are commutative diagrams ensuring consistency across the aggregate. Use `invariant strict:` (the default) for rules enforced within the aggregate's transaction, and `invariant eventual:` for rules restored asynchronously, which may span aggregates.",
         ),
         "pattern" => (
             "pattern",
@@ -7,12 +7,14 @@
 //! - Code completion
 //! - Document symbols
 //! - Formatting
+//! - Quick fixes for validation issues
 
 use tower_lsp::{LspService, Server};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 mod backend;
 mod capabilities;
+mod code_actions;
 mod completion;
 mod diagnostics;
 mod document;
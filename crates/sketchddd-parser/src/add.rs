@@ -0,0 +1,283 @@
+//! Adding declarations to a parsed context.
+//!
+//! Tools such as `sketchddd add` grow a model one declaration at a time:
+//! they parse a file, add a declaration to one of its contexts and print
+//! the file back with [`PrettyPrint`](crate::PrettyPrint). Comments are
+//! attached to the declarations of the AST as trivia, so they survive the
+//! round trip.
+
+use pest::Parser;
+
+use crate::ast::{
+    split_qualified_name, AggregateDecl, ContextDecl, EntityDecl, EnumDecl, FieldDecl,
+    MorphismDecl, Trivia, TriviaItem, ValueObjectDecl,
+};
+use crate::error::ParseError;
+use crate::grammar::{Rule, SketchDDDParser};
+
+/// A declaration that can be added to a context.
+#[derive(Debug, Clone)]
+pub enum Declaration {
+    /// `entity Customer { ... }`
+    Entity(EntityDecl),
+
+    /// `value Money { ... }`
+    Value(ValueObjectDecl),
+
+    /// `enum OrderStatus = ...`
+    Enum(EnumDecl),
+
+    /// `aggregate Order { ... }`
+    Aggregate(AggregateDecl),
+
+    /// A morphism of the `morphisms` block
+    Morphism(MorphismDecl),
+}
+
+impl Declaration {
+    /// Get the name of the declared element.
+    pub fn name(&self) -> &str {
+        match self {
+            Declaration::Entity(entity) => &entity.name,
+            Declaration::Value(value) => &value.name,
+            Declaration::Enum(enum_decl) => &enum_decl.name,
+            Declaration::Aggregate(aggregate) => &aggregate.name,
+            Declaration::Morphism(morphism) => &morphism.name,
+        }
+    }
+
+    /// Get the kind of the declared element, such as `value object`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Declaration::Entity(_) => "entity",
+            Declaration::Value(_) => "value object",
+            Declaration::Enum(_) => "enum",
+            Declaration::Aggregate(_) => "aggregate",
+            Declaration::Morphism(_) => "morphism",
+        }
+    }
+
+    /// Get the trivia of a declaration printed as a block of its own, which
+    /// morphisms are not.
+    fn block_trivia(&mut self) -> Option<&mut Trivia> {
+        match self {
+            Declaration::Entity(entity) => Some(&mut entity.trivia),
+            Declaration::Value(value) => Some(&mut value.trivia),
+            Declaration::Enum(enum_decl) => Some(&mut enum_decl.trivia),
+            Declaration::Aggregate(aggregate) => Some(&mut aggregate.trivia),
+            Declaration::Morphism(_) => None,
+        }
+    }
+
+    /// Get the names the declaration introduces, which must be identifiers.
+    fn identifiers(&self) -> Vec<&str> {
+        let mut names = vec![self.name()];
+        match self {
+            Declaration::Entity(entity) => names.extend(field_names(&entity.fields)),
+            Declaration::Value(value) => names.extend(field_names(&value.fields)),
+            Declaration::Enum(enum_decl) => {
+                names.extend(enum_decl.variants.iter().map(|v| v.name.as_str()))
+            }
+            Declaration::Aggregate(aggregate) => {
+                names.extend(aggregate.root.as_deref());
+                names.extend(aggregate.contains.iter().map(String::as_str));
+            }
+            Declaration::Morphism(_) => {}
+        }
+        names
+    }
+}
+
+/// Get the names of fields.
+fn field_names(fields: &[FieldDecl]) -> impl Iterator<Item = &str> {
+    fields.iter().map(|f| f.name.as_str())
+}
+
+/// Check whether a name is an identifier of the language.
+pub fn is_identifier(name: &str) -> bool {
+    SketchDDDParser::parse(Rule::identifier, name)
+        .is_ok_and(|mut pairs| pairs.next().is_some_and(|p| p.as_str() == name))
+}
+
+impl ContextDecl {
+    /// Check whether the context declares a type of a name: an object, an
+    /// entity, a value object or an enum.
+    pub fn declares_type(&self, name: &str) -> bool {
+        self.objects
+            .iter()
+            .any(|o| split_qualified_name(&o.name).1 == name)
+            || self.entities.iter().any(|e| e.name == name)
+            || self.value_objects.iter().any(|v| v.name == name)
+            || self.enums.iter().any(|e| e.name == name)
+    }
+
+    /// Check whether the context declares nothing.
+    fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+            && self.entities.is_empty()
+            && self.morphisms.is_empty()
+            && self.aggregates.is_empty()
+            && self.value_objects.is_empty()
+            && self.enums.is_empty()
+            && self.uses.is_empty()
+    }
+
+    /// Add a declaration after the others of its kind, or at the end of the
+    /// context if there are none.
+    ///
+    /// Fails if a name the declaration introduces is not an identifier, or
+    /// if the context already declares a type of the name of a new type, an
+    /// aggregate of the name of a new aggregate, or a morphism of the same
+    /// name leaving the same type.
+    pub fn add_declaration(&mut self, declaration: Declaration) -> Result<(), ParseError> {
+        if let Some(invalid) = declaration
+            .identifiers()
+            .into_iter()
+            .find(|name| !is_identifier(name))
+        {
+            return Err(ParseError::new(format!(
+                "'{}' is not a valid name",
+                invalid
+            )));
+        }
+
+        let name = declaration.name();
+        let duplicate = match &declaration {
+            Declaration::Entity(_) | Declaration::Value(_) | Declaration::Enum(_) => {
+                self.declares_type(name)
+            }
+            Declaration::Aggregate(_) => self.aggregates.iter().any(|a| a.name == name),
+            Declaration::Morphism(morphism) => self.morphisms.iter().any(|m| {
+                m.name == morphism.name && m.source.base_name() == morphism.source.base_name()
+            }),
+        };
+        if duplicate {
+            return Err(ParseError::new(format!(
+                "Context '{}' already declares '{}'",
+                self.name, name
+            )));
+        }
+
+        // Space blocks like the last declaration of their kind, or set the
+        // first of their kind apart from what comes before them
+        let previous = match &declaration {
+            Declaration::Entity(_) => self.entities.last().map(|e| &e.trivia),
+            Declaration::Value(_) => self.value_objects.last().map(|v| &v.trivia),
+            Declaration::Enum(_) => self.enums.last().map(|e| &e.trivia),
+            Declaration::Aggregate(_) => self.aggregates.last().map(|a| &a.trivia),
+            Declaration::Morphism(_) => None,
+        };
+        let spaced = previous.map_or(!self.is_empty(), |trivia| {
+            trivia.leading.contains(&TriviaItem::BlankLine)
+        });
+        let mut declaration = declaration;
+        if spaced {
+            if let Some(trivia) = declaration.block_trivia() {
+                if trivia.leading.is_empty() {
+                    trivia.leading.push(TriviaItem::BlankLine);
+                }
+            }
+        }
+
+        match declaration {
+            Declaration::Entity(entity) => self.entities.push(entity),
+            Declaration::Value(value) => self.value_objects.push(value),
+            Declaration::Enum(enum_decl) => self.enums.push(enum_decl),
+            Declaration::Aggregate(aggregate) => self.aggregates.push(aggregate),
+            Declaration::Morphism(morphism) => self.morphisms.push(morphism),
+        }
+        Ok(())
+    }
+}
+
+/// Build a field from a `name: Type` pair, as given on the command line.
+pub fn parse_field(source: &str) -> Result<FieldDecl, ParseError> {
+    let (name, type_expr) = source.split_once(':').ok_or_else(|| {
+        ParseError::new(format!(
+            "Field '{}' has no type; expected name: Type",
+            source
+        ))
+    })?;
+    Ok(FieldDecl::new(name.trim(), crate::parse_type(type_expr)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_context, parse_file, PrettyPrint, TypeExpr, VariantDecl};
+
+    #[test]
+    fn test_add_declarations() {
+        let mut ctx = parse_context(
+            r#"
+            context Sales {
+                objects { Customer }
+                entity Order
+            }
+            "#,
+        )
+        .unwrap();
+
+        let mut money = ValueObjectDecl::new("Money");
+        money.fields.push(parse_field("amount: Decimal").unwrap());
+        ctx.add_declaration(Declaration::Value(money)).unwrap();
+        ctx.add_declaration(Declaration::Morphism(MorphismDecl::new(
+            "placedBy",
+            TypeExpr::simple("Order"),
+            TypeExpr::simple("Customer"),
+        )))
+        .unwrap();
+        let mut status = EnumDecl::new("OrderStatus");
+        status.variants.push(VariantDecl::new("Pending"));
+        ctx.add_declaration(Declaration::Enum(status)).unwrap();
+
+        assert!(ctx.declares_type("Money"));
+        assert!(ctx.declares_type("OrderStatus"));
+        let printed = ctx.pretty_print();
+        assert!(printed.contains("placedBy: Order -> Customer"));
+        assert!(printed.contains("amount: Decimal"));
+        assert!(parse_file(&printed).is_ok());
+    }
+
+    #[test]
+    fn test_add_rejects_duplicates_and_invalid_names() {
+        let mut ctx = parse_context(
+            r#"
+            context Sales {
+                objects { Customer }
+                morphisms {
+                    placedBy: Order -> Customer
+                }
+            }
+            "#,
+        )
+        .unwrap();
+
+        let error = ctx
+            .add_declaration(Declaration::Entity(EntityDecl::new("Customer")))
+            .unwrap_err();
+        assert_eq!(error.message, "Context 'Sales' already declares 'Customer'");
+        assert!(ctx
+            .add_declaration(Declaration::Morphism(MorphismDecl::new(
+                "placedBy",
+                TypeExpr::simple("Order"),
+                TypeExpr::simple("Account"),
+            )))
+            .is_err());
+        // The same name may leave another type
+        assert!(ctx
+            .add_declaration(Declaration::Morphism(MorphismDecl::new(
+                "placedBy",
+                TypeExpr::simple("Invoice"),
+                TypeExpr::simple("Customer"),
+            )))
+            .is_ok());
+
+        let error = ctx
+            .add_declaration(Declaration::Entity(EntityDecl::new("Line Item")))
+            .unwrap_err();
+        assert_eq!(error.message, "'Line Item' is not a valid name");
+        assert!(parse_field("amount").is_err());
+        assert!(parse_field("amount: List<").is_err());
+    }
+}
@@ -0,0 +1,989 @@
+//! Completion and hover for editors.
+//!
+//! The editor of the visual builder asks what could be typed at the cursor
+//! and what the word under it is. [`complete`] and [`hover`] answer from
+//! the parsed file: the names declared in the context around the cursor,
+//! and the declaration a name refers to. Which blocks the cursor is in, and
+//! what comes before it on its line, is read from the text itself.
+//!
+//! A file being typed often does not parse. Both then retry with the line
+//! under the cursor blanked out, usually the line being typed, and fall
+//! back to keywords and built-in types when even that fails.
+//!
+//! Offsets are byte offsets into the source.
+
+use std::collections::HashSet;
+
+use crate::add::Declaration;
+use crate::ast::{ContextDecl, File, Trivia, TriviaItem};
+use crate::parse_file;
+use crate::pretty::PrettyPrint;
+
+/// What a completion item is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    Keyword,
+    Type,
+    Context,
+    Entity,
+    ValueObject,
+    Enum,
+    Variant,
+    Object,
+    Morphism,
+    Pattern,
+}
+
+impl CompletionKind {
+    /// Get the kind's name, such as `value_object`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CompletionKind::Keyword => "keyword",
+            CompletionKind::Type => "type",
+            CompletionKind::Context => "context",
+            CompletionKind::Entity => "entity",
+            CompletionKind::ValueObject => "value_object",
+            CompletionKind::Enum => "enum",
+            CompletionKind::Variant => "variant",
+            CompletionKind::Object => "object",
+            CompletionKind::Morphism => "morphism",
+            CompletionKind::Pattern => "pattern",
+        }
+    }
+}
+
+/// An item offered for completion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    /// The text to insert
+    pub label: String,
+    pub kind: CompletionKind,
+    /// A short description, such as the signature of a morphism
+    pub detail: Option<String>,
+}
+
+/// The items that could complete the word at an offset.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Completions {
+    /// Start of the part of the word typed so far
+    pub start: usize,
+    /// The cursor, where the typed part ends
+    pub end: usize,
+    /// Items starting with the typed part, ignoring case
+    pub items: Vec<Completion>,
+}
+
+/// What the word at an offset is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hover {
+    /// Start of the word
+    pub start: usize,
+    /// Just past the end of the word
+    pub end: usize,
+    /// Markdown describing the word, with the declaration it refers to
+    pub contents: String,
+}
+
+/// Keywords, with what they declare.
+const KEYWORDS: &[(&str, &str)] = &[
+    (
+        "context",
+        "Declares a bounded context: a boundary within which a model applies.",
+    ),
+    (
+        "entity",
+        "Declares an entity: an object with identity that persists over time.",
+    ),
+    (
+        "value",
+        "Declares a value object: an immutable object defined by its attributes.",
+    ),
+    (
+        "enum",
+        "Declares an enumeration: a sum type of named variants.",
+    ),
+    ("objects", "Declares plain objects."),
+    (
+        "morphisms",
+        "Declares morphisms, the relationships between objects: `name: Source -> Target`.",
+    ),
+    (
+        "derive",
+        "Declares a morphism computed from an expression over paths.",
+    ),
+    (
+        "aggregate",
+        "Declares an aggregate: objects kept consistent as one unit, reached through its root.",
+    ),
+    ("root", "Names the entity at the root of an aggregate."),
+    ("contains", "Lists the objects an aggregate contains."),
+    (
+        "invariant",
+        "States a rule an aggregate keeps. Rules marked `eventual` may be restored asynchronously.",
+    ),
+    ("equation", "States that two paths of morphisms agree."),
+    (
+        "lifecycle",
+        "Declares the states of an object as transitions between the variants of an enum.",
+    ),
+    (
+        "default",
+        "Lists the variants a lifecycle handles outside its state machine.",
+    ),
+    (
+        "process",
+        "Declares a process manager reacting to events across aggregates.",
+    ),
+    ("actor", "Declares an actor and the commands it issues."),
+    (
+        "policy",
+        "Declares the commands issued whenever an event happens.",
+    ),
+    ("hotspot", "Records an open question about the model."),
+    ("use", "Stamps a template into a context."),
+    (
+        "template",
+        "Declares a context body taking object names, stamped into contexts with `use`.",
+    ),
+    (
+        "shared",
+        "Declares a shared kernel: objects shared by several contexts.",
+    ),
+    ("domain", "Groups contexts into subdomains."),
+    ("subdomain", "Declares a subdomain and the contexts in it."),
+    ("contexts", "Lists the contexts of a subdomain."),
+    (
+        "map",
+        "Declares a context map: how one context translates into another.",
+    ),
+    ("pattern", "Names the integration pattern of a context map."),
+    (
+        "mappings",
+        "Maps objects of the source context to objects of the target context.",
+    ),
+    (
+        "morphism_mappings",
+        "Maps morphisms of the source context to morphisms of the target context.",
+    ),
+];
+
+const TOP_LEVEL: &[&str] = &["context", "map", "shared", "template", "domain"];
+
+const CONTEXT_MEMBERS: &[&str] = &[
+    "entity",
+    "value",
+    "enum",
+    "objects",
+    "morphisms",
+    "aggregate",
+    "derive",
+    "equation",
+    "lifecycle",
+    "process",
+    "actor",
+    "policy",
+    "hotspot",
+    "use",
+];
+
+const KERNEL_MEMBERS: &[&str] = &["objects", "entity", "value", "enum"];
+
+const AGGREGATE_MEMBERS: &[&str] = &["root", "contains", "invariant"];
+
+const MAP_MEMBERS: &[&str] = &["pattern", "mappings", "morphism_mappings"];
+
+/// Built-in types, with what they hold.
+const BUILTIN_TYPES: &[(&str, &str)] = &[
+    ("String", "UTF-8 text"),
+    ("Int", "64-bit integer"),
+    ("Float", "64-bit floating point"),
+    ("Bool", "Boolean (true/false)"),
+    ("UUID", "Universally unique identifier"),
+    ("DateTime", "Date and time with timezone"),
+    ("Date", "Calendar date"),
+    ("Decimal", "Arbitrary precision decimal"),
+    ("Email", "Email address"),
+    ("List", "Ordered collection: `List<T>`"),
+    ("Map", "Key-value mapping: `Map<K, V>`"),
+    ("Set", "Collection of unique elements: `Set<T>`"),
+];
+
+/// Integration patterns of context maps, with what they mean.
+const PATTERNS: &[(&str, &str)] = &[
+    (
+        "Partnership",
+        "The contexts depend on each other and plan changes together.",
+    ),
+    (
+        "CustomerSupplier",
+        "The upstream supplier serves the needs of the downstream customer.",
+    ),
+    (
+        "Conformist",
+        "The downstream context adopts the upstream model as it is.",
+    ),
+    (
+        "AntiCorruptionLayer",
+        "The downstream context translates the upstream model to protect its own.",
+    ),
+    ("ACL", "Short for `AntiCorruptionLayer`."),
+    ("SeparateWays", "The contexts do not integrate."),
+    (
+        "PublishedLanguage",
+        "The contexts exchange a documented shared language.",
+    ),
+    (
+        "OpenHostService",
+        "The upstream context offers a protocol open to any consumer.",
+    ),
+    ("OHS", "Short for `OpenHostService`."),
+    (
+        "SharedKernel",
+        "The contexts share part of their model and change it together.",
+    ),
+];
+
+/// Get the items that could complete the word before an offset.
+pub fn complete(source: &str, offset: usize) -> Completions {
+    let end = char_boundary(source, offset);
+    let start = word_start(source, end);
+    let file = parse_around(source, end);
+    let blocks = open_blocks(&source[..start]);
+    let line = &source[line_start(source, start)..start];
+
+    let mut items = Vec::new();
+    candidates(
+        &mut items,
+        file.as_ref(),
+        &blocks,
+        line,
+        qualifier(source, start),
+    );
+
+    let typed = source[start..end].to_lowercase();
+    let mut seen = HashSet::new();
+    items.retain(|item| {
+        item.label.to_lowercase().starts_with(&typed) && seen.insert(item.label.clone())
+    });
+    Completions { start, end, items }
+}
+
+/// Describe the word at an offset, if it is a keyword, a built-in type, an
+/// integration pattern or a declared name.
+pub fn hover(source: &str, offset: usize) -> Option<Hover> {
+    let offset = char_boundary(source, offset);
+    let start = word_start(source, offset);
+    let end = word_end(source, offset);
+    if start == end {
+        return None;
+    }
+    let file = parse_around(source, offset);
+    let blocks = open_blocks(&source[..start]);
+    let line = &source[line_start(source, start)..start];
+    let contents = describe(
+        file.as_ref(),
+        &blocks,
+        line,
+        qualifier(source, start),
+        &source[start..end],
+    )?;
+    Some(Hover {
+        start,
+        end,
+        contents,
+    })
+}
+
+// =============================================================
+// Completion
+// =============================================================
+
+/// Collect what could be typed where a line leaves off inside some blocks.
+fn candidates(
+    items: &mut Vec<Completion>,
+    file: Option<&File>,
+    blocks: &[Block<'_>],
+    line: &str,
+    qualifier: Option<&str>,
+) {
+    let context_named = |name: Option<&str>| find_context(file?, name?);
+    let context = blocks
+        .first()
+        .filter(|b| matches!(b.keyword(), "context" | "template"))
+        .and_then(|b| context_named(b.name()));
+    let words = words(line);
+    let first = words.first().copied();
+    let has_colon = line.contains(':');
+
+    if let Some(qualifier) = qualifier {
+        match context_named(Some(qualifier)) {
+            Some(ctx) => {
+                push_objects(items, ctx);
+                push_morphisms(items, ctx);
+            }
+            // A path through morphisms, such as `items.price`
+            None => context
+                .into_iter()
+                .for_each(|ctx| push_morphisms(items, ctx)),
+        }
+        return;
+    }
+
+    let Some(block) = blocks.last() else {
+        if words.is_empty() {
+            push_keywords(items, TOP_LEVEL);
+        }
+        return;
+    };
+    match block.keyword() {
+        "context" | "template" => match first {
+            None => push_keywords(items, CONTEXT_MEMBERS),
+            Some("equation") if has_colon => context
+                .into_iter()
+                .for_each(|ctx| push_morphisms(items, ctx)),
+            Some("derive") if line.contains('=') => context
+                .into_iter()
+                .for_each(|ctx| push_morphisms(items, ctx)),
+            Some("lifecycle") if has_colon => context.into_iter().for_each(|ctx| {
+                for enum_decl in &ctx.enums {
+                    items.push(item(&enum_decl.name, CompletionKind::Enum, Some("enum")));
+                }
+            }),
+            Some("lifecycle" | "hotspot") if words.len() == 1 => {
+                context.into_iter().for_each(|ctx| push_objects(items, ctx))
+            }
+            Some("use") if words.len() == 1 => {
+                for template in file.iter().flat_map(|f| &f.templates) {
+                    items.push(item(
+                        &template.name,
+                        CompletionKind::Keyword,
+                        Some("template"),
+                    ));
+                }
+            }
+            Some("use") => context.into_iter().for_each(|ctx| push_objects(items, ctx)),
+            _ if expects_type(line) => push_types(items, context),
+            _ => {}
+        },
+        "entity" | "value" | "morphisms" if expects_type(line) => push_types(items, context),
+        "shared" if words.is_empty() => push_keywords(items, KERNEL_MEMBERS),
+        "shared" if expects_type(line) => push_types(items, None),
+        "aggregate" => match first {
+            None => push_keywords(items, AGGREGATE_MEMBERS),
+            Some("root") if has_colon => context.into_iter().for_each(|ctx| {
+                for entity in &ctx.entities {
+                    items.push(item(&entity.name, CompletionKind::Entity, Some("entity")));
+                }
+            }),
+            Some("contains") if has_colon => {
+                context.into_iter().for_each(|ctx| push_objects(items, ctx))
+            }
+            Some("invariant") if has_colon => context
+                .into_iter()
+                .for_each(|ctx| push_morphisms(items, ctx)),
+            _ => {}
+        },
+        "map" => match first {
+            None => push_keywords(items, MAP_MEMBERS),
+            Some("pattern") if has_colon => push_table(items, PATTERNS, CompletionKind::Pattern),
+            _ => {}
+        },
+        "mappings" | "morphism_mappings" => {
+            // The header of the map is `map Name: Source -> Target`
+            let side = if line.contains("->") { 3 } else { 2 };
+            let map = blocks.len().checked_sub(2).map(|i| &blocks[i]);
+            if let Some(ctx) = context_named(map.and_then(|m| m.words.get(side).copied())) {
+                match block.keyword() {
+                    "mappings" => push_objects(items, ctx),
+                    _ => push_morphisms(items, ctx),
+                }
+            }
+        }
+        "lifecycle" => {
+            if first.is_none() {
+                push_keywords(items, &["default"]);
+            }
+            // Without `lifecycle Name: Enum`, the enum is `Name`, `NameStatus` or `NameState`
+            let names = match (block.words.get(1), block.words.get(2)) {
+                (_, Some(enum_name)) => vec![enum_name.to_string()],
+                (Some(name), None) => vec![
+                    name.to_string(),
+                    format!("{}Status", name),
+                    format!("{}State", name),
+                ],
+                (None, None) => Vec::new(),
+            };
+            let enum_decl = context.and_then(|ctx| {
+                names
+                    .iter()
+                    .find_map(|name| ctx.enums.iter().find(|e| &e.name == name))
+            });
+            for variant in enum_decl.iter().flat_map(|e| &e.variants) {
+                items.push(item(&variant.name, CompletionKind::Variant, None));
+            }
+        }
+        "domain" if words.is_empty() => push_keywords(items, &["subdomain"]),
+        "subdomain" if words.is_empty() => push_keywords(items, &["contexts"]),
+        "contexts" => {
+            for ctx in file.iter().flat_map(|f| &f.contexts) {
+                items.push(item(&ctx.name, CompletionKind::Context, Some("context")));
+            }
+        }
+        _ => {}
+    }
+}
+
+fn item(label: &str, kind: CompletionKind, detail: Option<&str>) -> Completion {
+    Completion {
+        label: label.to_string(),
+        kind,
+        detail: detail.map(str::to_string),
+    }
+}
+
+fn push_keywords(items: &mut Vec<Completion>, keywords: &[&str]) {
+    for keyword in keywords {
+        items.push(item(keyword, CompletionKind::Keyword, None));
+    }
+}
+
+fn push_table(items: &mut Vec<Completion>, table: &[(&str, &str)], kind: CompletionKind) {
+    for (name, detail) in table {
+        items.push(item(name, kind, Some(detail)));
+    }
+}
+
+/// Offer the objects of a context, then the built-in types.
+fn push_types(items: &mut Vec<Completion>, context: Option<&ContextDecl>) {
+    if let Some(ctx) = context {
+        push_objects(items, ctx);
+    }
+    push_table(items, BUILTIN_TYPES, CompletionKind::Type);
+}
+
+fn push_objects(items: &mut Vec<Completion>, ctx: &ContextDecl) {
+    for entity in &ctx.entities {
+        items.push(item(&entity.name, CompletionKind::Entity, Some("entity")));
+    }
+    for value in &ctx.value_objects {
+        items.push(item(
+            &value.name,
+            CompletionKind::ValueObject,
+            Some("value object"),
+        ));
+    }
+    for enum_decl in &ctx.enums {
+        items.push(item(&enum_decl.name, CompletionKind::Enum, Some("enum")));
+    }
+    for object in &ctx.objects {
+        items.push(item(&object.name, CompletionKind::Object, Some("object")));
+    }
+}
+
+/// Offer the morphisms of a context, with their signatures.
+fn push_morphisms(items: &mut Vec<Completion>, ctx: &ContextDecl) {
+    for morphism in &ctx.morphisms {
+        let signature = format!("{} -> {}", morphism.source, morphism.target);
+        items.push(item(
+            &morphism.name,
+            CompletionKind::Morphism,
+            Some(&signature),
+        ));
+    }
+    for derivation in &ctx.derivations {
+        let signature = format!("{} -> {}", derivation.source, derivation.target);
+        items.push(item(
+            &derivation.name,
+            CompletionKind::Morphism,
+            Some(&signature),
+        ));
+    }
+}
+
+/// Check if a line leaves off where a type goes: after `:`, `->`, `<`, `,`
+/// or `(`, outside the brackets of constraints and annotations.
+fn expects_type(line: &str) -> bool {
+    let in_brackets = line.matches('[').count() > line.matches(']').count();
+    let line = line.trim_end();
+    !in_brackets && [":", "->", "<", ",", "("].iter().any(|p| line.ends_with(p))
+}
+
+// =============================================================
+// Hover
+// =============================================================
+
+/// Describe a word found inside some blocks, after the start of a line.
+fn describe(
+    file: Option<&File>,
+    blocks: &[Block<'_>],
+    line: &str,
+    qualifier: Option<&str>,
+    word: &str,
+) -> Option<String> {
+    let context_named = |name: Option<&str>| find_context(file?, name?);
+
+    if let Some(ctx) = context_named(qualifier) {
+        return describe_element(ctx, word);
+    }
+    // Keywords start their line, so that a field named `value` is not one
+    if words(line).is_empty() {
+        if let Some(doc) = lookup(KEYWORDS, word) {
+            return Some(format!("**{}**\n\n{}", word, doc));
+        }
+    }
+    if blocks.last().map(Block::keyword) == Some("map") {
+        if let Some(doc) = lookup(PATTERNS, word) {
+            return Some(format!("**pattern** `{}`\n\n{}", word, doc));
+        }
+    }
+
+    // The context around the word comes first, then every other one
+    let around = match blocks.last().map(Block::keyword) {
+        Some("mappings" | "morphism_mappings") => {
+            let side = if line.contains("->") { 3 } else { 2 };
+            let map = blocks.len().checked_sub(2).map(|i| &blocks[i]);
+            context_named(map.and_then(|m| m.words.get(side).copied()))
+        }
+        _ => blocks
+            .first()
+            .filter(|b| matches!(b.keyword(), "context" | "template"))
+            .and_then(|b| context_named(b.name())),
+    };
+    let contexts = file.iter().flat_map(|f| &f.contexts);
+    around
+        .and_then(|ctx| describe_element(ctx, word))
+        .or_else(|| contexts.clone().find_map(|ctx| describe_element(ctx, word)))
+        .or_else(|| {
+            contexts
+                .clone()
+                .find(|c| c.name == word)
+                .map(describe_context)
+        })
+        .or_else(|| {
+            let map = file?.context_maps.iter().find(|m| m.name == word)?;
+            let mut contents = format!(
+                "**map** `{}`: `{}` -> `{}`\n",
+                map.name, map.source_context, map.target_context
+            );
+            push_doc(&mut contents, &map.trivia);
+            if let Some(pattern) = &map.pattern {
+                contents.push_str(&format!("\nPattern: `{}`\n", pattern));
+            }
+            Some(contents)
+        })
+        .or_else(|| {
+            lookup(BUILTIN_TYPES, word)
+                .map(|doc| format!("**built-in type** `{}`\n\n{}", word, doc))
+        })
+}
+
+/// Describe an element of a context by name, with its declaration.
+fn describe_element(ctx: &ContextDecl, name: &str) -> Option<String> {
+    let describe = |kind: &str, trivia: &Trivia, code: String| {
+        let mut contents = format!("**{}** `{}` in context `{}`\n", kind, name, ctx.name);
+        push_doc(&mut contents, trivia);
+        contents.push_str(&format!("\n```sketchddd\n{}\n```", code.trim_end()));
+        contents
+    };
+
+    if let Some(entity) = ctx.entities.iter().find(|e| e.name == name) {
+        let code = Declaration::Entity(entity.clone()).pretty_print();
+        return Some(describe("entity", &entity.trivia, code));
+    }
+    if let Some(value) = ctx.value_objects.iter().find(|v| v.name == name) {
+        let code = Declaration::Value(value.clone()).pretty_print();
+        return Some(describe("value object", &value.trivia, code));
+    }
+    if let Some(enum_decl) = ctx.enums.iter().find(|e| e.name == name) {
+        let code = Declaration::Enum(enum_decl.clone()).pretty_print();
+        return Some(describe("enum", &enum_decl.trivia, code));
+    }
+    if let Some(morphism) = ctx.morphisms.iter().find(|m| m.name == name) {
+        let code = Declaration::Morphism(morphism.clone()).pretty_print();
+        return Some(describe("morphism", &morphism.trivia, code));
+    }
+    if let Some(derivation) = ctx.derivations.iter().find(|d| d.name == name) {
+        let code = format!(
+            "derive {}: {} -> {} = {}",
+            derivation.name, derivation.source, derivation.target, derivation.expression
+        );
+        return Some(describe("derived morphism", &derivation.trivia, code));
+    }
+    if let Some(object) = ctx.objects.iter().find(|o| o.name == name) {
+        let code = format!("objects {{ {} }}", object.name);
+        return Some(describe("object", &object.trivia, code));
+    }
+    if let Some(aggregate) = ctx.aggregates.iter().find(|a| a.name == name) {
+        let code = Declaration::Aggregate(aggregate.clone()).pretty_print();
+        return Some(describe("aggregate", &aggregate.trivia, code));
+    }
+    let enum_decl = ctx
+        .enums
+        .iter()
+        .find(|e| e.variants.iter().any(|v| v.name == name))?;
+    let code = Declaration::Enum(enum_decl.clone()).pretty_print();
+    Some(describe(
+        &format!("variant of `{}`", enum_decl.name),
+        &Trivia::default(),
+        code,
+    ))
+}
+
+/// Describe a context by what it declares.
+fn describe_context(ctx: &ContextDecl) -> String {
+    let count =
+        |n: usize, one: &str, many: &str| format!("{} {}", n, if n == 1 { one } else { many });
+    let mut contents = format!("**context** `{}`\n", ctx.name);
+    push_doc(&mut contents, &ctx.trivia);
+    contents.push_str(&format!(
+        "\n{}, {}, {}, {}",
+        count(ctx.entities.len(), "entity", "entities"),
+        count(ctx.value_objects.len(), "value object", "value objects"),
+        count(ctx.enums.len(), "enum", "enums"),
+        count(
+            ctx.morphisms.len() + ctx.derivations.len(),
+            "morphism",
+            "morphisms"
+        ),
+    ));
+    contents
+}
+
+/// Add the comment right before a declaration, without its markers, as a
+/// paragraph.
+fn push_doc(contents: &mut String, trivia: &Trivia) {
+    let comments: Vec<&str> = trivia
+        .leading
+        .iter()
+        .rev()
+        .map_while(|item| match item {
+            TriviaItem::Comment(comment) => Some(comment.as_str()),
+            TriviaItem::BlankLine => None,
+        })
+        .collect();
+    let lines: Vec<&str> = comments
+        .iter()
+        .rev()
+        .flat_map(|comment| match comment.strip_prefix("/*") {
+            Some(body) => body
+                .strip_suffix("*/")
+                .unwrap_or(body)
+                .lines()
+                .map(|line| line.trim().trim_start_matches('*').trim())
+                .filter(|line| !line.is_empty())
+                .collect(),
+            None => vec![comment.trim_start_matches('/').trim()],
+        })
+        .collect();
+    if !lines.is_empty() {
+        contents.push_str(&format!("\n{}\n", lines.join("\n")));
+    }
+}
+
+fn lookup<'t>(table: &[(&str, &'t str)], name: &str) -> Option<&'t str> {
+    table.iter().find(|(n, _)| *n == name).map(|(_, doc)| *doc)
+}
+
+// =============================================================
+// Source text
+// =============================================================
+
+/// A block open at the cursor, as the words of its header, such as
+/// `map SalesToBilling: Sales -> Billing`.
+struct Block<'s> {
+    words: Vec<&'s str>,
+}
+
+impl Block<'_> {
+    fn keyword(&self) -> &str {
+        self.words.first().copied().unwrap_or_default()
+    }
+
+    /// Get the declared name; a shared kernel is `shared kernel Name`.
+    fn name(&self) -> Option<&str> {
+        let index = if self.keyword() == "shared" { 2 } else { 1 };
+        self.words.get(index).copied()
+    }
+}
+
+/// Get the blocks open at the end of a text, outermost first, skipping
+/// braces in comments and strings.
+fn open_blocks(text: &str) -> Vec<Block<'_>> {
+    let mut blocks = Vec::new();
+    let mut header = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '{' => {
+                blocks.push(Block {
+                    words: words(&text[header..i]),
+                });
+                header = i + 1;
+            }
+            '}' => {
+                blocks.pop();
+                header = i + 1;
+            }
+            '\n' => header = i + 1,
+            '"' => {
+                while let Some((_, c)) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '/' if chars.next_if(|&(_, c)| c == '/').is_some() => {
+                while chars.next_if(|&(_, c)| c != '\n').is_some() {}
+            }
+            '/' if chars.next_if(|&(_, c)| c == '*').is_some() => {
+                let mut star = false;
+                for (_, c) in chars.by_ref() {
+                    if star && c == '/' {
+                        break;
+                    }
+                    star = c == '*';
+                }
+            }
+            _ => {}
+        }
+    }
+    blocks
+}
+
+/// Get the identifiers of a text, leaving out decorators and anything in
+/// parentheses.
+fn words(text: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut depth = 0u32;
+    let mut decorator = false;
+    let mut start = None;
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        if is_word(c) {
+            start.get_or_insert(i);
+            continue;
+        }
+        if let Some(start) = start.take() {
+            if !decorator && depth == 0 {
+                words.push(&text[start..i]);
+            }
+            decorator = false;
+        }
+        match c {
+            '@' => decorator = true,
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    words
+}
+
+fn is_word(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Move an offset back onto a character boundary within the source.
+fn char_boundary(source: &str, offset: usize) -> usize {
+    let mut offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+fn word_start(source: &str, offset: usize) -> usize {
+    source[..offset]
+        .char_indices()
+        .rev()
+        .find(|&(_, c)| !is_word(c))
+        .map_or(0, |(i, c)| i + c.len_utf8())
+}
+
+fn word_end(source: &str, offset: usize) -> usize {
+    source[offset..]
+        .find(|c: char| !is_word(c))
+        .map_or(source.len(), |i| offset + i)
+}
+
+fn line_start(source: &str, offset: usize) -> usize {
+    source[..offset].rfind('\n').map_or(0, |i| i + 1)
+}
+
+/// Get the name qualifying the word starting at an offset, such as `Sales`
+/// in `Sales.Order`.
+fn qualifier(source: &str, start: usize) -> Option<&str> {
+    let dot = start
+        .checked_sub(1)
+        .filter(|&i| source[i..].starts_with('.'))?;
+    let qualifier = &source[word_start(source, dot)..dot];
+    (!qualifier.is_empty()).then_some(qualifier)
+}
+
+/// Find the context, or the body of the template, with a name.
+fn find_context<'f>(file: &'f File, name: &str) -> Option<&'f ContextDecl> {
+    file.contexts.iter().find(|c| c.name == name).or_else(|| {
+        file.templates
+            .iter()
+            .find(|t| t.name == name)
+            .map(|t| &t.body)
+    })
+}
+
+/// Parse the source, or else the source with the line at an offset blanked
+/// out, keeping the offsets of everything else.
+fn parse_around(source: &str, offset: usize) -> Option<File> {
+    parse_file(source).ok().or_else(|| {
+        let start = line_start(source, offset);
+        let end = source[offset..]
+            .find('\n')
+            .map_or(source.len(), |i| offset + i);
+        let blanked = format!(
+            "{}{}{}",
+            &source[..start],
+            " ".repeat(end - start),
+            &source[end..]
+        );
+        parse_file(&blanked).ok()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"context Sales {
+  // Someone who buys
+  entity Customer {
+    id: UUID
+  }
+
+  entity Order {
+    id: UUID
+    status: OrderStatus
+  }
+
+  value Money {
+    amount: Decimal
+  }
+
+  enum OrderStatus = Pending | Shipped
+
+  morphisms {
+    placedBy: Order -> Customer
+  }
+
+  aggregate Ordering {
+    root: Order
+  }
+}
+
+context Billing {
+  entity Invoice
+}
+
+map SalesToBilling: Sales -> Billing {
+  pattern: CustomerSupplier
+  mappings {
+    Order -> Invoice
+  }
+}
+"#;
+
+    /// Complete at the `$` of a source.
+    fn labels(source: &str) -> Vec<String> {
+        let offset = source.find('$').unwrap();
+        let source = source.replacen('$', "", 1);
+        complete(&source, offset)
+            .items
+            .into_iter()
+            .map(|item| item.label)
+            .collect()
+    }
+
+    #[test]
+    fn test_complete_types_of_the_context() {
+        let offered = labels(&SOURCE.replace("amount: Decimal", "amount: $"));
+        assert_eq!(&offered[..4], ["Customer", "Order", "Money", "OrderStatus"]);
+        assert!(offered.contains(&"Decimal".to_string()));
+        assert!(!offered.contains(&"Invoice".to_string()));
+
+        // Only names starting with the typed part, ignoring case
+        let source = SOURCE.replace("amount: Decimal", "amount: de$");
+        assert_eq!(labels(&source), ["Decimal"]);
+    }
+
+    #[test]
+    fn test_complete_while_the_line_does_not_parse() {
+        let source = SOURCE.replace(
+            "    placedBy: Order -> Customer\n",
+            "    placedBy: Order -> Customer\n    paidWith: Order -> List<M$\n",
+        );
+        assert_eq!(labels(&source), ["Money", "Map"]);
+
+        let items = complete(&source.replace('$', ""), source.find('$').unwrap()).items;
+        assert_eq!(items[0].kind, CompletionKind::ValueObject);
+    }
+
+    #[test]
+    fn test_complete_keywords_and_names_by_position() {
+        let source = SOURCE.replace("  morphisms {", "  $\n  morphisms {");
+        assert_eq!(labels(&source), CONTEXT_MEMBERS);
+        assert_eq!(labels(&format!("{}\nm$", SOURCE)), ["map"]);
+        assert_eq!(
+            labels(&SOURCE.replace("root: Order", "root: $")),
+            ["Customer", "Order"]
+        );
+        assert_eq!(
+            labels(&SOURCE.replace("pattern: CustomerSupplier", "pattern: Con$")),
+            ["Conformist"]
+        );
+
+        // Mappings offer the source context before `->` and the target after it
+        assert_eq!(
+            labels(&SOURCE.replace("Order -> Invoice", "Order -> $")),
+            ["Invoice"]
+        );
+        assert_eq!(
+            labels(&SOURCE.replace("Order -> Invoice", "Sales.pl$")),
+            ["placedBy"]
+        );
+    }
+
+    #[test]
+    fn test_hover_describes_declarations() {
+        let offset = SOURCE.find("Order -> Customer").unwrap() + "Order -> ".len() + 2;
+        let customer = hover(SOURCE, offset).unwrap();
+        assert_eq!(&SOURCE[customer.start..customer.end], "Customer");
+        assert_eq!(
+            customer.contents,
+            "**entity** `Customer` in context `Sales`\n\nSomeone who buys\n\n```sketchddd\nentity Customer {\n  id: UUID\n}\n```"
+        );
+
+        let offset = SOURCE.find("placedBy").unwrap();
+        assert!(hover(SOURCE, offset)
+            .unwrap()
+            .contents
+            .contains("placedBy: Order -> Customer"));
+        let offset = SOURCE.find("Shipped").unwrap();
+        assert!(hover(SOURCE, offset)
+            .unwrap()
+            .contents
+            .starts_with("**variant of `OrderStatus`** `Shipped`"));
+    }
+
+    #[test]
+    fn test_hover_keywords_types_and_patterns() {
+        let hover_at = |text: &str| hover(SOURCE, SOURCE.find(text).unwrap()).unwrap().contents;
+        assert!(hover_at("aggregate").starts_with("**aggregate**\n\n"));
+        assert!(hover_at("UUID").starts_with("**built-in type** `UUID`"));
+        assert!(hover_at("CustomerSupplier").starts_with("**pattern** `CustomerSupplier`"));
+        assert!(
+            hover_at("Billing {").starts_with("**context** `Billing`\n\n1 entity, 0 value objects")
+        );
+        assert_eq!(hover(SOURCE, SOURCE.find("{\n").unwrap()), None);
+    }
+}
@@ -5,6 +5,7 @@
 //! representation before conversion to the semantic model.
 
 use serde::{Deserialize, Serialize};
+use sketchddd_core::Collection;
 
 // =============================================================
 // Source Location
@@ -35,6 +36,40 @@ impl Span {
     }
 }
 
+// =============================================================
+// Trivia
+// =============================================================
+
+/// A comment or blank line that carries no meaning for the model.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TriviaItem {
+    /// A `//` or `/* */` comment, including its markers
+    Comment(String),
+    /// One or more consecutive blank lines
+    BlankLine,
+}
+
+/// Comments and blank lines attached to a declaration.
+///
+/// Trivia is recorded so that tools rewriting a file, such as the formatter,
+/// can emit comments back next to the declarations they belong to.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Trivia {
+    /// Comments and blank lines on the lines before the declaration
+    pub leading: Vec<TriviaItem>,
+    /// A comment on the same line as the start of the declaration
+    pub trailing: Option<String>,
+    /// Comments before the closing brace of a block declaration
+    pub dangling: Vec<TriviaItem>,
+}
+
+impl Trivia {
+    /// Check if no trivia is attached.
+    pub fn is_empty(&self) -> bool {
+        self.leading.is_empty() && self.trailing.is_none() && self.dangling.is_empty()
+    }
+}
+
 // =============================================================
 // File (Top Level)
 // =============================================================
@@ -46,6 +81,17 @@ pub struct File {
     pub contexts: Vec<ContextDecl>,
     /// Context map declarations in the file
     pub context_maps: Vec<ContextMapDecl>,
+    /// Shared kernel declarations in the file
+    pub shared_kernels: Vec<SharedKernelDecl>,
+    /// Template declarations in the file
+    #[serde(default)]
+    pub templates: Vec<TemplateDecl>,
+    /// Domain declarations in the file
+    #[serde(default)]
+    pub domains: Vec<DomainDecl>,
+    /// Comments and blank lines after the last declaration
+    #[serde(default)]
+    pub trailing_trivia: Vec<TriviaItem>,
 }
 
 // =============================================================
@@ -53,7 +99,7 @@ pub struct File {
 // =============================================================
 
 /// A context declaration representing a bounded context.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ContextDecl {
     /// Name of the bounded context
     pub name: String,
@@ -63,6 +109,9 @@ pub struct ContextDecl {
     pub entities: Vec<EntityDecl>,
     /// Morphisms (relationships) declared in the context
     pub morphisms: Vec<MorphismDecl>,
+    /// Morphisms computed from expressions
+    #[serde(default)]
+    pub derivations: Vec<DerivationDecl>,
     /// Aggregate definitions
     pub aggregates: Vec<AggregateDecl>,
     /// Value object definitions
@@ -71,26 +120,316 @@ pub struct ContextDecl {
     pub enums: Vec<EnumDecl>,
     /// Path equation definitions
     pub equations: Vec<EquationDecl>,
+    /// Process manager (saga) definitions
+    pub processes: Vec<ProcessDecl>,
+    /// Actors issuing commands
+    #[serde(default)]
+    pub actors: Vec<ActorDecl>,
+    /// Policies reacting to events with commands
+    #[serde(default)]
+    pub policies: Vec<PolicyDecl>,
+    /// Open questions and risks
+    #[serde(default)]
+    pub hotspots: Vec<HotspotDecl>,
+    /// Lifecycle (state machine) definitions
+    pub lifecycles: Vec<LifecycleDecl>,
+    /// Template instantiations such as `use CrudAggregate<Product>`
+    #[serde(default)]
+    pub uses: Vec<UseDecl>,
+    /// Decorators such as `@version(2)`
+    #[serde(default)]
+    pub decorators: Vec<DecoratorDecl>,
+    /// Declarations of the body in source order; declarations missing from
+    /// it, such as added ones, are printed after the others of their kind
+    #[serde(default)]
+    pub order: Vec<BodyItem>,
+    /// Comments and blank lines around the declaration
+    #[serde(default)]
+    pub trivia: Trivia,
+    /// Source location
+    pub span: Span,
+}
+
+/// Kind of a declaration in the body of a context.
+///
+/// Variants are listed in the order in which declarations of a context
+/// built without a source order are printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BodyItemKind {
+    /// `use CrudAggregate<Product>`
+    Use,
+    /// `objects { ... }`
+    Objects,
+    /// `entity Customer { ... }`
+    Entity,
+    /// `morphisms { ... }`
+    Morphisms,
+    /// `derive total: Order -> Money = ...`
+    Derivation,
+    /// `aggregate Order { ... }`
+    Aggregate,
+    /// `value Money { ... }`
+    ValueObject,
+    /// `enum OrderStatus = ...`
+    Enum,
+    /// `equation ...`
+    Equation,
+    /// `process FulfillmentSaga { ... }`
+    Process,
+    /// `actor Customer { ... }`
+    Actor,
+    /// `policy ReserveOnPlacement { ... }`
+    Policy,
+    /// `hotspot Order "..."`
+    Hotspot,
+    /// `lifecycle Order { ... }`
+    Lifecycle,
+}
+
+impl BodyItemKind {
+    /// Every kind, in print order.
+    pub const ALL: [BodyItemKind; 14] = [
+        BodyItemKind::Use,
+        BodyItemKind::Objects,
+        BodyItemKind::Entity,
+        BodyItemKind::Morphisms,
+        BodyItemKind::Derivation,
+        BodyItemKind::Aggregate,
+        BodyItemKind::ValueObject,
+        BodyItemKind::Enum,
+        BodyItemKind::Equation,
+        BodyItemKind::Process,
+        BodyItemKind::Actor,
+        BodyItemKind::Policy,
+        BodyItemKind::Hotspot,
+        BodyItemKind::Lifecycle,
+    ];
+
+    /// Check if declarations of this kind are elements of a block, such as
+    /// `objects { ... }`, rather than declarations of their own.
+    pub fn is_block(self) -> bool {
+        matches!(self, BodyItemKind::Objects | BodyItemKind::Morphisms)
+    }
+}
+
+/// A declaration of a context body, as it appears in the source.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BodyItem {
+    /// Kind of the declaration
+    pub kind: BodyItemKind,
+    /// Index of the declaration among those of its kind; for an `objects`
+    /// or `morphisms` block, index of its first element
+    pub index: usize,
+    /// Comments and blank lines around an `objects` or `morphisms` block
+    #[serde(default)]
+    pub trivia: Trivia,
     /// Source location
     pub span: Span,
 }
 
-impl Default for ContextDecl {
-    fn default() -> Self {
+impl BodyItem {
+    /// Create an item without trivia or source location.
+    pub fn new(kind: BodyItemKind, index: usize) -> Self {
         Self {
-            name: String::new(),
-            objects: Vec::new(),
-            entities: Vec::new(),
-            morphisms: Vec::new(),
-            aggregates: Vec::new(),
-            value_objects: Vec::new(),
-            enums: Vec::new(),
-            equations: Vec::new(),
+            kind,
+            index,
+            trivia: Trivia::default(),
             span: Span::default(),
         }
     }
 }
 
+impl ContextDecl {
+    /// Get the number of declarations of a kind, or of elements of blocks
+    /// of that kind.
+    pub fn count(&self, kind: BodyItemKind) -> usize {
+        match kind {
+            BodyItemKind::Use => self.uses.len(),
+            BodyItemKind::Objects => self.objects.len(),
+            BodyItemKind::Entity => self.entities.len(),
+            BodyItemKind::Morphisms => self.morphisms.len(),
+            BodyItemKind::Derivation => self.derivations.len(),
+            BodyItemKind::Aggregate => self.aggregates.len(),
+            BodyItemKind::ValueObject => self.value_objects.len(),
+            BodyItemKind::Enum => self.enums.len(),
+            BodyItemKind::Equation => self.equations.len(),
+            BodyItemKind::Process => self.processes.len(),
+            BodyItemKind::Actor => self.actors.len(),
+            BodyItemKind::Policy => self.policies.len(),
+            BodyItemKind::Hotspot => self.hotspots.len(),
+            BodyItemKind::Lifecycle => self.lifecycles.len(),
+        }
+    }
+
+    /// Get the declarations of the body in print order.
+    ///
+    /// This is the source order, with declarations missing from it placed
+    /// after the last declaration of their kind, or at the end if there is
+    /// none. Elements of `objects` and `morphisms` blocks missing from it
+    /// belong to the last block of their kind.
+    pub fn layout(&self) -> Vec<BodyItem> {
+        let mut layout: Vec<BodyItem> = Vec::new();
+        for item in &self.order {
+            let count = self.count(item.kind);
+            let known = layout
+                .iter()
+                .any(|other| other.kind == item.kind && other.index == item.index);
+            if item.index < count && !known {
+                layout.push(item.clone());
+            }
+        }
+
+        for kind in BodyItemKind::ALL {
+            let count = self.count(kind);
+            if kind.is_block() {
+                let first = layout
+                    .iter_mut()
+                    .filter(|item| item.kind == kind)
+                    .min_by_key(|item| item.index);
+                match first {
+                    Some(first) => first.index = 0,
+                    None if count > 0 => layout.push(BodyItem::new(kind, 0)),
+                    None => {}
+                }
+                continue;
+            }
+            for index in 0..count {
+                if layout.iter().any(|item| item.kind == kind && item.index == index) {
+                    continue;
+                }
+                let at = layout
+                    .iter()
+                    .rposition(|item| item.kind == kind)
+                    .map_or(layout.len(), |last| last + 1);
+                layout.insert(at, BodyItem::new(kind, index));
+            }
+        }
+        layout
+    }
+}
+
+// =============================================================
+// Shared Kernel Declaration
+// =============================================================
+
+/// A shared kernel: objects available to every context that references them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SharedKernelDecl {
+    /// Name of the shared kernel
+    pub name: String,
+    /// Objects declared in the kernel, possibly qualified (`Auth.UserId`)
+    pub objects: Vec<ObjectDecl>,
+    /// Entities declared in the kernel
+    pub entities: Vec<EntityDecl>,
+    /// Value object definitions
+    pub value_objects: Vec<ValueObjectDecl>,
+    /// Enum/sum type definitions
+    pub enums: Vec<EnumDecl>,
+    /// Comments and blank lines around the declaration
+    #[serde(default)]
+    pub trivia: Trivia,
+    /// Source location
+    pub span: Span,
+}
+
+impl SharedKernelDecl {
+    /// Get the names of all types declared in the kernel.
+    pub fn type_names(&self) -> impl Iterator<Item = &str> {
+        self.objects
+            .iter()
+            .map(|o| split_qualified_name(&o.name).1)
+            .chain(self.entities.iter().map(|e| e.name.as_str()))
+            .chain(self.value_objects.iter().map(|v| v.name.as_str()))
+            .chain(self.enums.iter().map(|e| e.name.as_str()))
+    }
+}
+
+// =============================================================
+// Domain Declaration
+// =============================================================
+
+/// A domain grouping bounded contexts into subdomains.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DomainDecl {
+    /// Name of the domain
+    pub name: String,
+    /// Subdomains of the domain
+    pub subdomains: Vec<SubdomainDecl>,
+    /// Comments and blank lines around the declaration
+    #[serde(default)]
+    pub trivia: Trivia,
+    /// Source location
+    pub span: Span,
+}
+
+/// A subdomain: `@core subdomain Ordering { contexts { Sales } }`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SubdomainDecl {
+    /// Name of the subdomain
+    pub name: String,
+    /// Names of the contexts in the subdomain
+    pub contexts: Vec<String>,
+    /// Decorators such as `@core`
+    pub decorators: Vec<DecoratorDecl>,
+    /// Comments and blank lines around the declaration
+    #[serde(default)]
+    pub trivia: Trivia,
+    /// Source location
+    pub span: Span,
+}
+
+// =============================================================
+// Template Declaration
+// =============================================================
+
+/// A template: a context body parameterized by type names, such as
+/// `template CrudAggregate<T> { ... }`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TemplateDecl {
+    /// Name of the template
+    pub name: String,
+    /// Type parameters
+    pub params: Vec<String>,
+    /// Declarations stamped out by each instantiation
+    pub body: ContextDecl,
+    /// Comments and blank lines around the declaration
+    #[serde(default)]
+    pub trivia: Trivia,
+    /// Source location
+    pub span: Span,
+}
+
+/// A template instantiation: `use CrudAggregate<Product>`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UseDecl {
+    /// Name of the template
+    pub template: String,
+    /// Type arguments, one per template parameter
+    pub args: Vec<String>,
+    /// Comments and blank lines around the declaration
+    #[serde(default)]
+    pub trivia: Trivia,
+    /// Source location
+    pub span: Span,
+}
+
+/// Split a possibly qualified name such as `Billing.Invoice` into its
+/// context qualifier and local name.
+///
+/// ```
+/// use sketchddd_parser::split_qualified_name;
+///
+/// assert_eq!(split_qualified_name("Billing.Invoice"), (Some("Billing"), "Invoice"));
+/// assert_eq!(split_qualified_name("Invoice"), (None, "Invoice"));
+/// ```
+pub fn split_qualified_name(name: &str) -> (Option<&str>, &str) {
+    match name.split_once('.') {
+        Some((context, local)) => (Some(context), local),
+        None => (None, name),
+    }
+}
+
 // =============================================================
 // Object Declaration
 // =============================================================
@@ -100,6 +439,9 @@ impl Default for ContextDecl {
 pub struct ObjectDecl {
     /// Name of the object
     pub name: String,
+    /// Comments and blank lines around the declaration
+    #[serde(default)]
+    pub trivia: Trivia,
     /// Source location
     pub span: Span,
 }
@@ -109,6 +451,7 @@ impl ObjectDecl {
     pub fn new(name: impl Into<String>) -> Self {
         Self {
             name: name.into(),
+            trivia: Trivia::default(),
             span: Span::default(),
         }
     }
@@ -125,6 +468,12 @@ pub struct EntityDecl {
     pub name: String,
     /// Fields of the entity
     pub fields: Vec<FieldDecl>,
+    /// Decorators such as `@removed_in("3.0")`
+    #[serde(default)]
+    pub decorators: Vec<DecoratorDecl>,
+    /// Comments and blank lines around the declaration
+    #[serde(default)]
+    pub trivia: Trivia,
     /// Source location
     pub span: Span,
 }
@@ -135,6 +484,8 @@ impl EntityDecl {
         Self {
             name: name.into(),
             fields: Vec::new(),
+            decorators: Vec::new(),
+            trivia: Trivia::default(),
             span: Span::default(),
         }
     }
@@ -155,6 +506,15 @@ pub struct MorphismDecl {
     pub target: TypeExpr,
     /// Optional annotations
     pub annotations: Vec<Annotation>,
+    /// Literal constraints on the morphism's values
+    #[serde(default)]
+    pub constraints: Vec<ConstraintDecl>,
+    /// Decorators such as `@removed_in("3.0")`
+    #[serde(default)]
+    pub decorators: Vec<DecoratorDecl>,
+    /// Comments and blank lines around the declaration
+    #[serde(default)]
+    pub trivia: Trivia,
     /// Source location
     pub span: Span,
 }
@@ -167,11 +527,33 @@ impl MorphismDecl {
             source,
             target,
             annotations: Vec::new(),
+            constraints: Vec::new(),
+            decorators: Vec::new(),
+            trivia: Trivia::default(),
             span: Span::default(),
         }
     }
 }
 
+/// A derived morphism whose value is computed from an expression:
+/// `derive total: Order -> Money = sum(items.price)`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DerivationDecl {
+    /// Name of the morphism
+    pub name: String,
+    /// Source type
+    pub source: TypeExpr,
+    /// Target type
+    pub target: TypeExpr,
+    /// Expression computing the value, navigating paths from the source
+    pub expression: Expr,
+    /// Comments and blank lines around the declaration
+    #[serde(default)]
+    pub trivia: Trivia,
+    /// Source location
+    pub span: Span,
+}
+
 /// An annotation on a morphism.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Annotation {
@@ -234,6 +616,37 @@ impl TypeExpr {
             TypeExpr::Optional(inner) => inner.base_name(),
         }
     }
+
+    /// Get the element type of a collection type, `T` of `List<T>` or
+    /// `Set<T>`, optional or not.
+    pub fn collection_element(&self) -> Option<&TypeExpr> {
+        match self {
+            TypeExpr::Generic { name, args } if matches!(name.as_str(), "List" | "Set") => {
+                match args.as_slice() {
+                    [element] => Some(element),
+                    _ => None,
+                }
+            }
+            TypeExpr::Optional(inner) => inner.collection_element(),
+            _ => None,
+        }
+    }
+
+    /// Get the kind of a collection type, optional or not.
+    pub fn collection(&self) -> Option<Collection> {
+        self.collection_element()?;
+        match self.base_name() {
+            "Set" => Some(Collection::Set),
+            _ => Some(Collection::List),
+        }
+    }
+
+    /// Get the name of the object a morphism to this type targets: the
+    /// element type of a collection, or else the base type.
+    pub fn target_name(&self) -> &str {
+        self.collection_element()
+            .map_or_else(|| self.base_name(), TypeExpr::base_name)
+    }
 }
 
 // =============================================================
@@ -251,6 +664,12 @@ pub struct AggregateDecl {
     pub contains: Vec<String>,
     /// Invariants
     pub invariants: Vec<InvariantDecl>,
+    /// Decorators such as `@core`
+    #[serde(default)]
+    pub decorators: Vec<DecoratorDecl>,
+    /// Comments and blank lines around the declaration
+    #[serde(default)]
+    pub trivia: Trivia,
     /// Source location
     pub span: Span,
 }
@@ -263,6 +682,8 @@ impl AggregateDecl {
             root: None,
             contains: Vec::new(),
             invariants: Vec::new(),
+            decorators: Vec::new(),
+            trivia: Trivia::default(),
             span: Span::default(),
         }
     }
@@ -273,10 +694,22 @@ impl AggregateDecl {
 pub struct InvariantDecl {
     /// The invariant expression as a string
     pub expression: Expr,
+    /// Enforcement mode, if given (`invariant strict:` or `invariant eventual:`)
+    #[serde(default)]
+    pub mode: Option<InvariantMode>,
     /// Source location
     pub span: Span,
 }
 
+/// How an invariant is enforced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InvariantMode {
+    /// Checked transactionally within the aggregate
+    Strict,
+    /// Restored asynchronously; may span aggregates
+    Eventual,
+}
+
 // =============================================================
 // Value Object Declaration
 // =============================================================
@@ -288,6 +721,12 @@ pub struct ValueObjectDecl {
     pub name: String,
     /// Fields of the value object
     pub fields: Vec<FieldDecl>,
+    /// Decorators such as `@removed_in("3.0")`
+    #[serde(default)]
+    pub decorators: Vec<DecoratorDecl>,
+    /// Comments and blank lines around the declaration
+    #[serde(default)]
+    pub trivia: Trivia,
     /// Source location
     pub span: Span,
 }
@@ -298,6 +737,8 @@ impl ValueObjectDecl {
         Self {
             name: name.into(),
             fields: Vec::new(),
+            decorators: Vec::new(),
+            trivia: Trivia::default(),
             span: Span::default(),
         }
     }
@@ -310,6 +751,15 @@ pub struct FieldDecl {
     pub name: String,
     /// Field type
     pub type_expr: TypeExpr,
+    /// Literal constraints on the field's values
+    #[serde(default)]
+    pub constraints: Vec<ConstraintDecl>,
+    /// Decorators such as `@removed_in("3.0")`
+    #[serde(default)]
+    pub decorators: Vec<DecoratorDecl>,
+    /// Comments and blank lines around the declaration
+    #[serde(default)]
+    pub trivia: Trivia,
     /// Source location
     pub span: Span,
 }
@@ -320,11 +770,60 @@ impl FieldDecl {
         Self {
             name: name.into(),
             type_expr,
+            constraints: Vec::new(),
+            decorators: Vec::new(),
+            trivia: Trivia::default(),
             span: Span::default(),
         }
     }
 }
 
+/// A literal constraint such as `min: 0` or `pattern: "[A-Z]{3}"`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConstraintDecl {
+    /// Constraint keyword
+    pub name: String,
+    /// Literal value
+    pub value: ConstraintValue,
+    /// Source location
+    pub span: Span,
+}
+
+/// The literal value of a constraint.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ConstraintValue {
+    /// Numeric literal
+    Number(f64),
+    /// String literal (without quotes)
+    Text(String),
+}
+
+impl std::fmt::Display for ConstraintValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConstraintValue::Number(n) => write!(f, "{}", n),
+            ConstraintValue::Text(s) => f.write_str(&crate::pretty::quote(s)),
+        }
+    }
+}
+
+/// A decorator such as `@version(2)` or `@removed_in("3.0")`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecoratorDecl {
+    /// Decorator name, without the `@`
+    pub name: String,
+    /// Key naming the argument, as in `@meta(owner: "billing")`
+    #[serde(default)]
+    pub key: Option<String>,
+    /// Optional literal argument
+    pub argument: Option<ConstraintValue>,
+    /// Bare names given as arguments, as in `@allow(W0001, W0003)`
+    #[serde(default)]
+    pub names: Vec<String>,
+    /// Source location
+    pub span: Span,
+}
+
 // =============================================================
 // Enum Declaration
 // =============================================================
@@ -336,6 +835,12 @@ pub struct EnumDecl {
     pub name: String,
     /// Variants of the enum
     pub variants: Vec<VariantDecl>,
+    /// Decorators such as `@removed_in("3.0")`
+    #[serde(default)]
+    pub decorators: Vec<DecoratorDecl>,
+    /// Comments and blank lines around the declaration
+    #[serde(default)]
+    pub trivia: Trivia,
     /// Source location
     pub span: Span,
 }
@@ -346,6 +851,8 @@ impl EnumDecl {
         Self {
             name: name.into(),
             variants: Vec::new(),
+            decorators: Vec::new(),
+            trivia: Trivia::default(),
             span: Span::default(),
         }
     }
@@ -391,10 +898,16 @@ impl VariantDecl {
 pub struct EquationDecl {
     /// Optional name for the equation
     pub name: Option<String>,
+    /// Enforcement mode, if given (`equation eventual:`)
+    #[serde(default)]
+    pub mode: Option<InvariantMode>,
     /// Left-hand side path
     pub lhs: Path,
     /// Right-hand side path
     pub rhs: Path,
+    /// Comments and blank lines around the declaration
+    #[serde(default)]
+    pub trivia: Trivia,
     /// Source location
     pub span: Span,
 }
@@ -420,6 +933,130 @@ impl Path {
     }
 }
 
+// =============================================================
+// Process Declaration
+// =============================================================
+
+/// A process manager (saga) declaration coordinating aggregates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessDecl {
+    /// Name of the process
+    pub name: String,
+    /// Steps reacting to events with commands
+    pub steps: Vec<ProcessStepDecl>,
+    /// Comments and blank lines around the declaration
+    #[serde(default)]
+    pub trivia: Trivia,
+    /// Source location
+    pub span: Span,
+}
+
+/// A single `on Event -> Command` step of a process.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessStepDecl {
+    /// Event that triggers the step
+    pub event: String,
+    /// Command dispatched in response
+    pub command: String,
+    /// Whether the step is followed by a `;`
+    #[serde(default)]
+    pub terminated: bool,
+    /// Source location
+    pub span: Span,
+}
+
+// =============================================================
+// Event Storming Declarations
+// =============================================================
+
+/// An actor declaration: `actor Customer { PlaceOrder }`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActorDecl {
+    /// Name of the actor
+    pub name: String,
+    /// Commands the actor issues
+    pub commands: Vec<String>,
+    /// Comments and blank lines around the declaration
+    #[serde(default)]
+    pub trivia: Trivia,
+    /// Source location
+    pub span: Span,
+}
+
+/// A policy declaration: `policy Name { on Event -> Command, ... }`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyDecl {
+    /// Name of the policy
+    pub name: String,
+    /// Event the policy reacts to
+    pub event: String,
+    /// Commands issued in response
+    pub commands: Vec<String>,
+    /// Comments and blank lines around the declaration
+    #[serde(default)]
+    pub trivia: Trivia,
+    /// Source location
+    pub span: Span,
+}
+
+/// A hotspot declaration: `hotspot Order "Can guests order?"`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotspotDecl {
+    /// Object the hotspot is about, if any
+    pub target: Option<String>,
+    /// What is unresolved
+    pub note: String,
+    /// Comments and blank lines around the declaration
+    #[serde(default)]
+    pub trivia: Trivia,
+    /// Source location
+    pub span: Span,
+}
+
+// =============================================================
+// Lifecycle Declaration
+// =============================================================
+
+/// A lifecycle declaration: a state machine over an enum's variants.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LifecycleDecl {
+    /// Name of the lifecycle (usually the entity it describes)
+    pub name: String,
+    /// Explicit enum providing the states (`lifecycle Order: OrderStatus`)
+    pub enum_name: Option<String>,
+    /// Transition chains such as `Pending -> Confirmed -> Shipped`
+    pub chains: Vec<TransitionChainDecl>,
+    /// Variants handled by default rather than as states
+    #[serde(default)]
+    pub defaults: Vec<String>,
+    /// Comments and blank lines around the declaration
+    #[serde(default)]
+    pub trivia: Trivia,
+    /// Source location
+    pub span: Span,
+}
+
+/// A chain of transitions through consecutive states.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransitionChainDecl {
+    /// States in order; each adjacent pair is a transition
+    pub states: Vec<String>,
+    /// Whether the chain is followed by a `;`
+    #[serde(default)]
+    pub terminated: bool,
+    /// Source location
+    pub span: Span,
+}
+
+impl TransitionChainDecl {
+    /// Get the individual `(from, to)` transitions of this chain.
+    pub fn transitions(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.states
+            .windows(2)
+            .map(|pair| (pair[0].as_str(), pair[1].as_str()))
+    }
+}
+
 // =============================================================
 // Context Map Declaration
 // =============================================================
@@ -439,6 +1076,18 @@ pub struct ContextMapDecl {
     pub object_mappings: Vec<ObjectMappingDecl>,
     /// Morphism mappings
     pub morphism_mappings: Vec<MorphismMappingDecl>,
+    /// The `pattern:` line
+    #[serde(default)]
+    pub pattern_clause: MapClause,
+    /// The `mappings` block
+    #[serde(default)]
+    pub mappings_clause: MapClause,
+    /// The `morphism_mappings` block
+    #[serde(default)]
+    pub morphism_mappings_clause: MapClause,
+    /// Comments and blank lines around the declaration
+    #[serde(default)]
+    pub trivia: Trivia,
     /// Source location
     pub span: Span,
 }
@@ -457,11 +1106,26 @@ impl ContextMapDecl {
             pattern: None,
             object_mappings: Vec::new(),
             morphism_mappings: Vec::new(),
+            pattern_clause: MapClause::default(),
+            mappings_clause: MapClause::default(),
+            morphism_mappings_clause: MapClause::default(),
+            trivia: Trivia::default(),
             span: Span::default(),
         }
     }
 }
 
+/// The `pattern:` line or a mappings block of a context map, which holds
+/// the comments around it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MapClause {
+    /// Comments and blank lines around the clause
+    #[serde(default)]
+    pub trivia: Trivia,
+    /// Source location, unless the clause was not parsed from a source
+    pub span: Option<Span>,
+}
+
 /// An object mapping in a context map.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ObjectMappingDecl {
@@ -471,6 +1135,9 @@ pub struct ObjectMappingDecl {
     pub target: String,
     /// Optional description
     pub description: Option<String>,
+    /// Comments and blank lines around the declaration
+    #[serde(default)]
+    pub trivia: Trivia,
     /// Source location
     pub span: Span,
 }
@@ -484,6 +1151,9 @@ pub struct MorphismMappingDecl {
     pub target: String,
     /// Optional description
     pub description: Option<String>,
+    /// Comments and blank lines around the declaration
+    #[serde(default)]
+    pub trivia: Trivia,
     /// Source location
     pub span: Span,
 }
@@ -0,0 +1,371 @@
+//! Locating fixes for validation issues in the source.
+//!
+//! Validation works on the semantic model, which does not know where its
+//! elements are declared, so issues come out of it without fixes. Given the
+//! source and the AST it was parsed into, [`attach_fixes`] finds the
+//! declarations an issue is about and attaches a [`Fix`] to the issues with
+//! an obvious repair:
+//!
+//! - `E0020` (duplicate object): remove the later declaration, or the plain
+//!   `objects` entry when the name is also declared as an entity, value
+//!   object or enum;
+//! - `E0021` (duplicate morphism): remove the later declaration;
+//! - `W0030`, `W0031` and `W0032` (naming conventions): rename every use of
+//!   the name to the one the rule suggests;
+//! - `W0033` (aggregate suffix): rename the aggregate.
+//!
+//! Implicitly declared objects are fixed during transformation, see
+//! [`TransformWarning::fix`](crate::TransformWarning::fix).
+
+use sketchddd_core::{Fix, SourceLocation, TextEdit, ValidationError};
+
+use crate::ast::{ContextDecl, File, Span};
+
+/// Attach fixes to the issues found in a model parsed from `source`.
+///
+/// Issues that already have a fix, or that have no obvious repair, are left
+/// alone. Issues without a location get the location of the fix.
+pub fn attach_fixes(source: &str, file: &File, issues: &mut [ValidationError]) {
+    for issue in issues.iter_mut().filter(|i| i.fix.is_none()) {
+        let (context, message) = split_context(&issue.message);
+        let contexts: Vec<&ContextDecl> = file
+            .contexts
+            .iter()
+            .filter(|c| context.is_none_or(|name| c.name == name))
+            .collect();
+
+        let fix = match issue.code.as_str() {
+            "E0020" => quoted(message).and_then(|name| remove_duplicate(source, &contexts, name)),
+            "E0021" => {
+                quoted(message).and_then(|name| remove_duplicate_morphism(source, &contexts, name))
+            }
+            "W0030" | "W0031" | "W0032" => quoted(message).and_then(|name| {
+                let new_name = suggested(issue.suggestion.as_deref()?)?;
+                rename_everywhere(source, name, new_name)
+            }),
+            "W0033" => quoted(message).and_then(|name| {
+                let new_name = suggested(issue.suggestion.as_deref()?)?;
+                rename_aggregate(source, &contexts, name, new_name)
+            }),
+            _ => None,
+        };
+
+        if let Some(fix) = fix {
+            if issue.location.line.is_none() {
+                if let Some(edit) = fix.edits.first() {
+                    let (line, column) = line_col(source, edit.start);
+                    issue.location = SourceLocation {
+                        line: Some(line),
+                        column: Some(column),
+                        ..issue.location.clone()
+                    };
+                }
+            }
+            issue.fix = Some(fix);
+        }
+    }
+}
+
+/// Split the `[Context] ` prefix model validation puts on context issues.
+fn split_context(message: &str) -> (Option<&str>, &str) {
+    message
+        .strip_prefix('[')
+        .and_then(|rest| rest.split_once("] "))
+        .map_or((None, message), |(context, rest)| (Some(context), rest))
+}
+
+/// Get the first name quoted in a message, as in `Object 'Order' ...`.
+fn quoted(message: &str) -> Option<&str> {
+    let (_, rest) = message.split_once('\'')?;
+    rest.split_once('\'').map(|(name, _)| name)
+}
+
+/// Get the name suggested by a `did you mean `X`?` suggestion.
+fn suggested(suggestion: &str) -> Option<&str> {
+    let (_, rest) = suggestion.split_once('`')?;
+    rest.split_once('`').map(|(name, _)| name)
+}
+
+/// Remove the later of two declarations of an object.
+fn remove_duplicate(source: &str, contexts: &[&ContextDecl], name: &str) -> Option<Fix> {
+    let title = format!("Remove duplicate declaration of '{}'", name);
+    for decl in contexts {
+        let plain: Vec<&Span> = decl
+            .objects
+            .iter()
+            .filter(|o| o.name == name && within(&o.span, &decl.span))
+            .map(|o| &o.span)
+            .collect();
+        let blocks: Vec<&Span> = decl
+            .entities
+            .iter()
+            .filter(|e| e.name == name)
+            .map(|e| &e.span)
+            .chain(
+                decl.value_objects
+                    .iter()
+                    .filter(|v| v.name == name)
+                    .map(|v| &v.span),
+            )
+            .chain(
+                decl.enums
+                    .iter()
+                    .filter(|e| e.name == name)
+                    .map(|e| &e.span),
+            )
+            .filter(|span| within(span, &decl.span))
+            .collect();
+
+        // A plain entry is the redundant one; otherwise keep the first block
+        let edit = match (plain.last(), blocks.len()) {
+            (Some(span), _) if plain.len() > 1 || !blocks.is_empty() => {
+                remove_list_item(source, span)
+            }
+            (_, count) if count > 1 => {
+                let span = blocks.iter().max_by_key(|s| s.start)?;
+                remove_lines(source, span)
+            }
+            _ => continue,
+        };
+        return Some(Fix::new(title).with_edit(edit));
+    }
+    None
+}
+
+/// Remove the later of two declarations of a morphism between the same
+/// objects.
+fn remove_duplicate_morphism(source: &str, contexts: &[&ContextDecl], name: &str) -> Option<Fix> {
+    for decl in contexts {
+        let morphisms: Vec<_> = decl
+            .morphisms
+            .iter()
+            .filter(|m| m.name == name && within(&m.span, &decl.span))
+            .collect();
+        let duplicate = morphisms.iter().enumerate().find_map(|(i, later)| {
+            morphisms[..i]
+                .iter()
+                .any(|m| m.source == later.source && m.target == later.target)
+                .then_some(later)
+        });
+        if let Some(duplicate) = duplicate {
+            // The span of a morphism includes the whitespace after it
+            let span = &duplicate.span;
+            let text = source[span.start..span.end].trim_end();
+            let span = Span {
+                end: span.start + text.len(),
+                ..span.clone()
+            };
+            return Some(
+                Fix::new(format!("Remove duplicate morphism '{}'", name))
+                    .with_edit(remove_lines(source, &span)),
+            );
+        }
+    }
+    None
+}
+
+/// Check if a span lies within another, which is not the case for
+/// declarations coming from an instantiated template.
+fn within(span: &Span, outer: &Span) -> bool {
+    outer.start <= span.start && span.end <= outer.end
+}
+
+/// Remove an item of a comma-separated list, along with one comma.
+fn remove_list_item(source: &str, span: &Span) -> TextEdit {
+    let before = source[..span.start].trim_end();
+    if before.ends_with(',') {
+        return TextEdit::delete(before.len() - 1, span.end);
+    }
+    let after = &source[span.end..];
+    let trimmed = after.trim_start();
+    if let Some(rest) = trimmed.strip_prefix(',') {
+        let end = source.len() - rest.trim_start().len();
+        return TextEdit::delete(span.start, end);
+    }
+    TextEdit::delete(span.start, span.end)
+}
+
+/// Remove a block declaration, with its line when nothing else is on it.
+fn remove_lines(source: &str, span: &Span) -> TextEdit {
+    let line_start = source[..span.start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[span.end..]
+        .find('\n')
+        .map_or(source.len(), |i| span.end + i + 1);
+    let alone = source[line_start..span.start].trim().is_empty()
+        && source[span.end..line_end].trim().is_empty();
+    if alone {
+        TextEdit::delete(line_start, line_end)
+    } else {
+        TextEdit::delete(span.start, span.end)
+    }
+}
+
+/// Rename every occurrence of an identifier in the source.
+fn rename_everywhere(source: &str, name: &str, new_name: &str) -> Option<Fix> {
+    let edits: Vec<TextEdit> = identifiers(source)
+        .filter(|&(_, identifier)| identifier == name)
+        .map(|(start, _)| TextEdit::replace(start, start + name.len(), new_name))
+        .collect();
+    if edits.is_empty() {
+        return None;
+    }
+    Some(Fix {
+        title: format!("Rename '{}' to '{}'", name, new_name),
+        edits,
+    })
+}
+
+/// Rename an aggregate where it is declared.
+///
+/// An aggregate without a `root` is rooted at the object of its own name, so
+/// renaming it would change its root; those are left without a fix.
+fn rename_aggregate(
+    source: &str,
+    contexts: &[&ContextDecl],
+    name: &str,
+    new_name: &str,
+) -> Option<Fix> {
+    let span = contexts
+        .iter()
+        .flat_map(|c| &c.aggregates)
+        .find(|a| a.name == name && a.root.is_some())
+        .map(|a| &a.span)?;
+    let (start, _) = identifiers(&source[span.start..span.end])
+        .skip_while(|&(_, identifier)| identifier != "aggregate")
+        .nth(1)
+        .filter(|&(_, identifier)| identifier == name)?;
+    let start = span.start + start;
+    Some(
+        Fix::new(format!("Rename aggregate '{}' to '{}'", name, new_name))
+            .with_edit(TextEdit::replace(start, start + name.len(), new_name)),
+    )
+}
+
+/// Iterate over the identifiers of a source text with their offsets,
+/// skipping comments and string literals.
+fn identifiers(source: &str) -> impl Iterator<Item = (usize, &str)> {
+    let bytes = source.as_bytes();
+    let mut i = 0;
+    std::iter::from_fn(move || {
+        while i < bytes.len() {
+            match bytes[i] {
+                b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                    i = source[i..].find('\n').map_or(bytes.len(), |n| i + n);
+                }
+                b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                    i = source[i + 2..]
+                        .find("*/")
+                        .map_or(bytes.len(), |n| i + 2 + n + 2);
+                }
+                b'"' => {
+                    i += 1;
+                    while i < bytes.len() && bytes[i] != b'"' && bytes[i] != b'\n' {
+                        i += if bytes[i] == b'\\' { 2 } else { 1 };
+                    }
+                    i += 1;
+                }
+                c if c.is_ascii_alphabetic() || c == b'_' => {
+                    let start = i;
+                    while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_')
+                    {
+                        i += 1;
+                    }
+                    return Some((start, &source[start..i]));
+                }
+                c if c.is_ascii_digit() => {
+                    // Skip numbers whole, so `2x` does not yield `x`
+                    while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_')
+                    {
+                        i += 1;
+                    }
+                }
+                _ => i += 1,
+            }
+        }
+        None
+    })
+}
+
+/// Get the 1-indexed line and column of a byte offset.
+fn line_col(source: &str, offset: usize) -> (u32, u32) {
+    let before = &source[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rfind('\n').map_or(offset, |i| offset - i - 1) + 1;
+    (line as u32, column as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_file, transform};
+    use sketchddd_core::{apply_fixes, LintConfig, Registry};
+
+    fn fixed(source: &str) -> String {
+        let file = parse_file(source).unwrap();
+        let result = transform(&file).unwrap();
+        let mut fixes: Vec<Fix> = result
+            .warnings
+            .iter()
+            .filter_map(|w| w.fix.clone())
+            .collect();
+        let mut issues = result
+            .into_workspace()
+            .lint(&Registry::builtin(), &LintConfig::new())
+            .issues;
+        attach_fixes(source, &file, &mut issues);
+        fixes.extend(issues.into_iter().filter_map(|i| i.fix));
+        apply_fixes(source, &fixes).0
+    }
+
+    #[test]
+    fn test_declare_implicit_object() {
+        let source = "context Sales {\n  objects { Order }\n  morphisms {\n    placedBy: Order -> Customer\n  }\n}\n";
+        assert!(fixed(source).contains("objects { Order, Customer }"));
+
+        let source = "context Sales {\n  entity Order\n  morphisms {\n    placedBy: Order -> Customer\n  }\n}\n";
+        assert!(fixed(source).ends_with("  }\n  objects { Customer }\n}\n"));
+    }
+
+    #[test]
+    fn test_remove_duplicate() {
+        let source = "context Sales {\n  objects { Order, Customer, Order }\n}\n";
+        assert!(fixed(source).contains("objects { Order, Customer }"));
+
+        let source = "context Sales {\n  objects { Order, Customer }\n  entity Order\n}\n";
+        assert!(fixed(source).contains("objects { Customer }"));
+    }
+
+    #[test]
+    fn test_remove_duplicate_morphism() {
+        let source = "context Sales {\n  objects { Order, Customer }\n  morphisms {\n    placedBy: Order -> Customer\n    placedBy: Order -> Customer\n  }\n}\n";
+        assert_eq!(
+            fixed(source),
+            "context Sales {\n  objects { Order, Customer }\n  morphisms {\n    placedBy: Order -> Customer\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_rename_to_suggestion() {
+        let source = "context Sales {\n  objects { line_item, Money }\n  morphisms {\n    // line_item has a price\n    Price: line_item -> Money\n  }\n}\n";
+        let fixed = fixed(source);
+        assert!(fixed.contains("objects { LineItem, Money }"));
+        assert!(fixed.contains("price: LineItem -> Money"));
+        assert!(fixed.contains("// line_item has a price"));
+    }
+
+    #[test]
+    fn test_fixes_are_located() {
+        let source = "context Sales {\n  objects { Order, Order }\n}\n";
+        let file = parse_file(source).unwrap();
+        let mut issues = transform(&file)
+            .unwrap()
+            .into_workspace()
+            .lint(&Registry::builtin(), &LintConfig::new())
+            .issues;
+        attach_fixes(source, &file, &mut issues);
+        let duplicate = issues.iter().find(|i| i.code == "E0020").unwrap();
+        assert!(duplicate.fix.is_some());
+        assert_eq!(duplicate.location.line, Some(2));
+        assert_eq!(duplicate.location.column, Some(18));
+    }
+}
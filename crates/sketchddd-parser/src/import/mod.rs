@@ -0,0 +1,193 @@
+//! Drafting bounded contexts from the schemas of other tools.
+//!
+//! Few models start from nothing: a database schema or an API description
+//! usually captures much of the domain already. An importer reads such a
+//! schema and drafts a context declaration from it, which
+//! [`PrettyPrint`](crate::PrettyPrint) writes as source for the team to
+//! refine. The draft keeps what the schema states and guesses the rest,
+//! such as which columns make up a value object; each guess, and whatever
+//! could not be imported, is reported as an [`ImportWarning`].
+//!
+//! Importers build the AST rather than the semantic model, because the
+//! model does not keep the fields of entities.
+
+#[cfg(feature = "rust")]
+pub mod rust;
+pub mod schema;
+#[cfg(feature = "sql")]
+pub mod sql;
+
+use std::fmt;
+
+#[cfg(any(feature = "sql", feature = "rust"))]
+use sketchddd_core::naming::to_camel_case;
+use sketchddd_core::naming::to_pascal_case;
+
+use crate::ast::{ContextDecl, EnumDecl, File, TypeExpr, ValueObjectDecl, VariantDecl};
+
+/// A context drafted from a schema.
+#[derive(Debug, Clone)]
+pub struct Imported {
+    /// The drafted context
+    pub context: ContextDecl,
+    /// What the importer guessed or left out, in the order of the schema
+    pub warnings: Vec<ImportWarning>,
+}
+
+impl Imported {
+    /// Get a file declaring the drafted context.
+    pub fn into_file(self) -> File {
+        File {
+            contexts: vec![self.context],
+            ..File::default()
+        }
+    }
+}
+
+/// Something an importer guessed or could not import.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportWarning {
+    /// Warning message
+    pub message: String,
+    /// Line of the schema the warning is about, if known
+    pub line: Option<u32>,
+}
+
+impl ImportWarning {
+    /// Create a new warning with the given message.
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            line: None,
+        }
+    }
+
+    /// Set the line of the schema the warning is about.
+    pub fn with_line(mut self, line: u32) -> Self {
+        self.line = Some(line);
+        self
+    }
+}
+
+impl fmt::Display for ImportWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// A context being drafted, with the warnings about it.
+struct Draft {
+    context: ContextDecl,
+    warnings: Vec<ImportWarning>,
+}
+
+impl Draft {
+    fn new(context: &str) -> Self {
+        Self {
+            context: ContextDecl {
+                name: context.to_string(),
+                ..ContextDecl::default()
+            },
+            warnings: Vec::new(),
+        }
+    }
+
+    fn warn(&mut self, message: String, line: Option<u32>) {
+        let warning = ImportWarning::new(message);
+        self.warnings.push(match line {
+            Some(line) => warning.with_line(line),
+            None => warning,
+        });
+    }
+
+    /// Declare an enum with a variant per label, returning its name.
+    fn declare_enum(&mut self, name: String, labels: &[String], line: Option<u32>) -> String {
+        let mut enum_decl = EnumDecl::new(&name);
+        for label in labels {
+            let variant = to_pascal_case(label);
+            if !variant.starts_with(|c: char| c.is_alphabetic()) {
+                self.warn(
+                    format!(
+                        "Left out '{}' of enum '{}', which is not a name",
+                        label, name
+                    ),
+                    line,
+                );
+            } else if !enum_decl.variants.iter().any(|v| v.name == variant) {
+                enum_decl.variants.push(VariantDecl::new(variant));
+            }
+        }
+        self.context.enums.push(enum_decl);
+        name
+    }
+
+    /// Declare a value object, returning its name, which is prefixed with
+    /// its owner's if another value object has the name already.
+    fn declare_value(&mut self, mut value: ValueObjectDecl, owner: &str) -> String {
+        let same_fields = |other: &ValueObjectDecl| {
+            other.fields.len() == value.fields.len()
+                && other
+                    .fields
+                    .iter()
+                    .zip(&value.fields)
+                    .all(|(a, b)| a.name == b.name)
+        };
+        match self
+            .context
+            .value_objects
+            .iter()
+            .find(|other| other.name == value.name)
+        {
+            Some(other) if same_fields(other) => return value.name,
+            Some(_) => value.name = format!("{}{}", owner, value.name),
+            None => {}
+        }
+        let name = value.name.clone();
+        self.context.value_objects.push(value);
+        name
+    }
+
+    fn finish(self) -> Imported {
+        Imported {
+            context: self.context,
+            warnings: self.warnings,
+        }
+    }
+}
+
+/// Name a type declared for a member of `owner`, such as the enum of a
+/// column, after the member, prefixed with the owner unless it already
+/// starts with it: `status` of `Order` gives `OrderStatus`.
+fn member_type_name(owner: &str, member: &str) -> String {
+    let name = to_pascal_case(member);
+    if name.starts_with(owner) {
+        name
+    } else {
+        format!("{}{}", owner, name)
+    }
+}
+
+/// Name the morphism of a reference, such as a foreign key, after what
+/// holds it without `_id`, or after the entity it references when nothing
+/// else is left.
+#[cfg(any(feature = "sql", feature = "rust"))]
+fn reference_name(member: &str, target: &str) -> String {
+    let name = to_camel_case(member);
+    match name.strip_suffix("Id") {
+        Some(stripped) if !stripped.is_empty() => stripped.to_string(),
+        _ if name == "id" => to_camel_case(target),
+        _ => name,
+    }
+}
+
+/// Make a type optional if what it types may be missing.
+fn optional_if(type_expr: TypeExpr, optional: bool) -> TypeExpr {
+    if optional {
+        TypeExpr::optional(type_expr)
+    } else {
+        type_expr
+    }
+}
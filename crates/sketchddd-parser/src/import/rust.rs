@@ -0,0 +1,759 @@
+//! Drafting a context from Rust source.
+//!
+//! [`import`] reads the files of a crate for the structs and enums of its
+//! domain: the ones marked `#[domain(...)]`, and the public ones of the
+//! modules it is given.
+//!
+//! - `#[domain(entity)]`, `#[domain(value)]` and `#[domain(enum)]` mark
+//!   entities, value objects and enums, and `#[domain(aggregate)]` marks an
+//!   entity that is the root of an aggregate. A bare `#[domain]`, like the
+//!   items of the modules, is an entity if it has an `id` field, a value
+//!   object if it is another struct, and an enum if it is an enum. Crates
+//!   without a `domain` attribute macro may write the attribute as
+//!   `#[cfg_attr(any(), domain(...))]`, which the compiler ignores.
+//! - Fields become fields in camelCase, optional if they are `Option`s.
+//! - A field of an entity holding another entity, its identifier (as
+//!   `CustomerId` is the identifier of `Customer`), or a collection of
+//!   them, becomes a morphism instead of a field.
+//! - Variants of enums holding data become variants with a payload.
+//!
+//! Identifiers such as `CustomerId` are not imported from modules: their
+//! uses become references to their entities, and the `id` fields of the
+//! entities hold the types they wrap.
+
+use std::collections::HashMap;
+use std::path::{Component, Path};
+
+use sketchddd_core::naming::to_camel_case;
+use syn::ext::IdentExt;
+use syn::punctuated::Punctuated;
+use syn::{
+    Attribute, Fields, GenericArgument, Item, ItemEnum, ItemStruct, Meta, PathArguments, Token,
+    Type, Visibility,
+};
+
+use super::{reference_name, Draft, Imported};
+use crate::ast::{
+    AggregateDecl, EntityDecl, EnumDecl, FieldDecl, MorphismDecl, TypeExpr, ValueObjectDecl,
+    VariantDecl,
+};
+use crate::error::ParseError;
+
+/// Rust types with a counterpart, by the last segment of their path.
+const SCALAR_TYPES: &[(&str, &str)] = &[
+    ("String", "String"),
+    ("str", "String"),
+    ("char", "String"),
+    ("i8", "Int"),
+    ("i16", "Int"),
+    ("i32", "Int"),
+    ("i64", "Int"),
+    ("i128", "Int"),
+    ("isize", "Int"),
+    ("u8", "Int"),
+    ("u16", "Int"),
+    ("u32", "Int"),
+    ("u64", "Int"),
+    ("u128", "Int"),
+    ("usize", "Int"),
+    ("f32", "Float"),
+    ("f64", "Float"),
+    ("bool", "Bool"),
+    ("Uuid", "UUID"),
+    ("Decimal", "Decimal"),
+    ("BigDecimal", "Decimal"),
+    ("NaiveDate", "Date"),
+    ("Date", "Date"),
+    ("DateTime", "DateTime"),
+    ("NaiveDateTime", "DateTime"),
+    ("OffsetDateTime", "DateTime"),
+    ("PrimitiveDateTime", "DateTime"),
+    ("SystemTime", "DateTime"),
+];
+
+/// A file of a crate to draft a context from.
+#[derive(Debug, Clone)]
+pub struct RustFile {
+    /// Path of the module the file holds, such as `domain::order`, which is
+    /// empty for the root of the crate
+    pub module: String,
+    /// Path of the file, for warnings
+    pub path: String,
+    /// Source of the file
+    pub source: String,
+}
+
+/// Get the module a file of the `src` directory of a crate holds, from its
+/// path in the directory: `domain/order.rs` holds `domain::order`.
+pub fn module_of(path: &Path) -> String {
+    let mut parts: Vec<String> = path
+        .with_extension("")
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect();
+    match parts.last().map(String::as_str) {
+        Some("mod") => {
+            parts.pop();
+        }
+        Some("lib" | "main") if parts.len() == 1 => {
+            parts.pop();
+        }
+        _ => {}
+    }
+    parts.join("::")
+}
+
+/// Draft a context named `context` from the files of a crate, with the
+/// public structs and enums of `modules` as well as the marked ones.
+///
+/// Fails if the crate has no structs or enums to import. A file that
+/// cannot be parsed is skipped with a warning.
+pub fn import(
+    files: &[RustFile],
+    context: &str,
+    modules: &[String],
+) -> Result<Imported, ParseError> {
+    let modules: Vec<&str> = modules
+        .iter()
+        .map(|module| match module.as_str() {
+            "crate" => "",
+            module => module.strip_prefix("crate::").unwrap_or(module),
+        })
+        .collect();
+
+    let mut draft = Draft::new(context);
+    let mut found = Collected::default();
+    for file in files {
+        match syn::parse_file(&file.source) {
+            Ok(parsed) => collect(parsed.items, &file.module, &modules, &mut found, &mut draft),
+            Err(e) => draft.warn(
+                format!("Skipped {}, which does not parse: {}", file.path, e),
+                None,
+            ),
+        }
+    }
+    if found.items.is_empty() {
+        return Err(ParseError::new(
+            "The crate has no structs or enums marked #[domain(...)] or in the modules given",
+        ));
+    }
+
+    let mut importer = Importer {
+        draft,
+        kinds: found
+            .items
+            .iter()
+            .map(|item| (item.name.clone(), item.kind))
+            .collect(),
+        identifiers: found.identifiers,
+    };
+    for item in &found.items {
+        importer.add_item(item);
+    }
+    Ok(importer.draft.finish())
+}
+
+/// What a struct or enum becomes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Entity,
+    /// An entity that is the root of an aggregate
+    Aggregate,
+    Value,
+    Enum,
+}
+
+impl Kind {
+    fn is_entity(self) -> bool {
+        matches!(self, Kind::Entity | Kind::Aggregate)
+    }
+}
+
+/// A struct or enum to import.
+struct DomainItem {
+    name: String,
+    kind: Kind,
+    definition: Definition,
+}
+
+enum Definition {
+    Struct(ItemStruct),
+    Enum(ItemEnum),
+}
+
+/// What was found in the files of a crate.
+#[derive(Default)]
+struct Collected {
+    items: Vec<DomainItem>,
+    /// Identifiers of entities, with the types they wrap
+    identifiers: HashMap<String, Type>,
+}
+
+/// Collect the structs and enums to import from the items of `module`,
+/// including the ones of its inline modules.
+fn collect(
+    items: Vec<Item>,
+    module: &str,
+    modules: &[&str],
+    found: &mut Collected,
+    draft: &mut Draft,
+) {
+    let listed = modules.iter().any(|listed| {
+        listed.is_empty()
+            || module == *listed
+            || module
+                .strip_prefix(listed)
+                .is_some_and(|rest| rest.starts_with("::"))
+    });
+    for item in items {
+        let (name, attrs, public, definition) = match item {
+            Item::Struct(item) => (
+                item.ident.unraw().to_string(),
+                item.attrs.clone(),
+                matches!(item.vis, Visibility::Public(_)),
+                Definition::Struct(item),
+            ),
+            Item::Enum(item) => (
+                item.ident.unraw().to_string(),
+                item.attrs.clone(),
+                matches!(item.vis, Visibility::Public(_)),
+                Definition::Enum(item),
+            ),
+            Item::Mod(item) => {
+                if let Some((_, items)) = item.content {
+                    let name = item.ident.unraw().to_string();
+                    let inner = if module.is_empty() {
+                        name
+                    } else {
+                        format!("{}::{}", module, name)
+                    };
+                    collect(items, &inner, modules, found, draft);
+                }
+                continue;
+            }
+            _ => continue,
+        };
+
+        let wrapped = identifier(&definition, &name).cloned();
+        if let Some(wrapped) = &wrapped {
+            found.identifiers.insert(name.clone(), wrapped.clone());
+        }
+        let marked = match domain_kind(&attrs, &name, draft) {
+            Some(kind) => kind,
+            None if listed && public && wrapped.is_none() => None,
+            None => continue,
+        };
+        let is_enum = matches!(definition, Definition::Enum(_));
+        let kind = match marked {
+            Some(kind) if (kind == Kind::Enum) != is_enum => {
+                draft.warn(
+                    format!(
+                        "'{}' is marked as {}, but is {}; drafted from what it is",
+                        name,
+                        if is_enum { "a struct" } else { "an enum" },
+                        if is_enum { "an enum" } else { "a struct" }
+                    ),
+                    None,
+                );
+                inferred_kind(&definition)
+            }
+            Some(kind) => kind,
+            None => inferred_kind(&definition),
+        };
+        found.items.push(DomainItem {
+            name,
+            kind,
+            definition,
+        });
+    }
+}
+
+/// Find the `#[domain(...)]` attribute of an item, which may be written
+/// `#[cfg_attr(any(), domain(...))]` for the compiler to ignore it.
+fn domain_attribute(attrs: &[Attribute]) -> Option<Meta> {
+    attrs.iter().find_map(|attr| {
+        if attr.path().is_ident("domain") {
+            return Some(attr.meta.clone());
+        }
+        if !attr.path().is_ident("cfg_attr") {
+            return None;
+        }
+        let metas = attr
+            .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+            .ok()?;
+        metas
+            .into_iter()
+            .skip(1)
+            .find(|meta| meta.path().is_ident("domain"))
+    })
+}
+
+/// Read the kind a `#[domain(...)]` attribute marks an item as: `None` if
+/// it is not marked, and `Some(None)` if the kind is left to be inferred.
+fn domain_kind(attrs: &[Attribute], name: &str, draft: &mut Draft) -> Option<Option<Kind>> {
+    let Meta::List(list) = domain_attribute(attrs)? else {
+        return Some(None);
+    };
+    let mut kind = None;
+    let parsed = list.parse_nested_meta(|meta| {
+        let Some(ident) = meta.path.get_ident() else {
+            return Err(meta.error("expected a kind"));
+        };
+        kind = Some(match ident.to_string().as_str() {
+            "entity" => Kind::Entity,
+            "aggregate" => Kind::Aggregate,
+            "value" | "value_object" => Kind::Value,
+            "enum" => Kind::Enum,
+            other => return Err(meta.error(format!("unknown kind '{}'", other))),
+        });
+        Ok(())
+    });
+    if let Err(e) = parsed {
+        draft.warn(
+            format!(
+                "The #[domain(...)] attribute of '{}' is not understood ({}); drafted from what it is",
+                name, e
+            ),
+            None,
+        );
+        return Some(None);
+    }
+    Some(kind)
+}
+
+/// Decide what an item that is not marked with a kind becomes.
+fn inferred_kind(definition: &Definition) -> Kind {
+    match definition {
+        Definition::Enum(_) => Kind::Enum,
+        Definition::Struct(item) => {
+            let has_id = item.fields.iter().any(|field| {
+                field
+                    .ident
+                    .as_ref()
+                    .is_some_and(|ident| ident.unraw() == "id")
+            });
+            if has_id {
+                Kind::Entity
+            } else {
+                Kind::Value
+            }
+        }
+    }
+}
+
+/// Get the type an identifier of an entity, a struct such as
+/// `struct CustomerId(Uuid)`, wraps.
+fn identifier<'i>(definition: &'i Definition, name: &str) -> Option<&'i Type> {
+    match definition {
+        Definition::Struct(ItemStruct {
+            fields: Fields::Unnamed(fields),
+            ..
+        }) if name.len() > 2 && name.ends_with("Id") && fields.unnamed.len() == 1 => {
+            fields.unnamed.first().map(|field| &field.ty)
+        }
+        _ => None,
+    }
+}
+
+/// Get the types between the angle brackets of the last segment of a path.
+fn type_arguments(arguments: &PathArguments) -> Vec<&Type> {
+    match arguments {
+        PathArguments::AngleBracketed(arguments) => arguments
+            .args
+            .iter()
+            .filter_map(|argument| match argument {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The type of a field.
+struct Typed {
+    type_expr: TypeExpr,
+    /// The entity it holds, which makes it a morphism for an entity
+    entity: Option<String>,
+}
+
+impl Typed {
+    fn simple(name: impl Into<String>) -> Self {
+        Self {
+            type_expr: TypeExpr::simple(name),
+            entity: None,
+        }
+    }
+
+    fn map(self, wrap: impl FnOnce(TypeExpr) -> TypeExpr) -> Self {
+        Self {
+            type_expr: wrap(self.type_expr),
+            entity: self.entity,
+        }
+    }
+}
+
+/// The context drafted so far, with the items being imported.
+struct Importer {
+    draft: Draft,
+    kinds: HashMap<String, Kind>,
+    identifiers: HashMap<String, Type>,
+}
+
+impl Importer {
+    fn add_item(&mut self, item: &DomainItem) {
+        let name = &item.name;
+        match (&item.definition, item.kind) {
+            (Definition::Enum(definition), _) => {
+                let mut enum_decl = EnumDecl::new(name);
+                for variant in &definition.variants {
+                    let variant_name = variant.ident.unraw().to_string();
+                    if let Fields::Named(_) = variant.fields {
+                        self.draft.warn(
+                            format!(
+                                "Variant '{}::{}' has named fields; drafted with their types only",
+                                name, variant_name
+                            ),
+                            None,
+                        );
+                    }
+                    let owner = format!("{}::{}", name, variant_name);
+                    let payload: Vec<TypeExpr> = variant
+                        .fields
+                        .iter()
+                        .map(|field| self.type_of(&owner, &field.ty).type_expr)
+                        .collect();
+                    enum_decl
+                        .variants
+                        .push(VariantDecl::with_payload(variant_name, payload));
+                }
+                self.draft.context.enums.push(enum_decl);
+            }
+            (Definition::Struct(definition), kind) if kind.is_entity() => {
+                let mut entity = EntityDecl::new(name);
+                entity.fields = self.fields(name, definition, true);
+                self.draft.context.entities.push(entity);
+                if kind == Kind::Aggregate {
+                    let mut aggregate = AggregateDecl::new(name);
+                    aggregate.root = Some(name.clone());
+                    self.draft.context.aggregates.push(aggregate);
+                }
+            }
+            (Definition::Struct(definition), _) => {
+                let mut value = ValueObjectDecl::new(name);
+                value.fields = self.fields(name, definition, false);
+                self.draft.context.value_objects.push(value);
+            }
+        }
+    }
+
+    /// Get the fields of a struct, declaring the morphisms of an entity.
+    fn fields(&mut self, owner: &str, definition: &ItemStruct, entity: bool) -> Vec<FieldDecl> {
+        let count = definition.fields.len();
+        let mut fields = Vec::new();
+        for (i, field) in definition.fields.iter().enumerate() {
+            let field_name = match &field.ident {
+                Some(ident) => ident.unraw().to_string(),
+                None if count == 1 => "value".to_string(),
+                None => format!("value{}", i),
+            };
+            let path = format!("{}.{}", owner, field_name);
+            let typed = if field_name == "id" {
+                self.identity_type(&path, &field.ty)
+            } else {
+                self.type_of(&path, &field.ty)
+            };
+            match typed.entity {
+                Some(target) if entity => {
+                    self.draft.context.morphisms.push(MorphismDecl::new(
+                        reference_name(&field_name, &target),
+                        TypeExpr::simple(owner),
+                        typed.type_expr,
+                    ));
+                }
+                Some(target) => fields.push(FieldDecl::new(
+                    reference_name(&field_name, &target),
+                    typed.type_expr,
+                )),
+                None => fields.push(FieldDecl::new(to_camel_case(&field_name), typed.type_expr)),
+            }
+        }
+        fields
+    }
+
+    /// Get the type of the `id` field of a struct, which is the type its
+    /// identifier wraps rather than a reference.
+    fn identity_type(&mut self, owner: &str, ty: &Type) -> Typed {
+        let wrapped = match ty {
+            Type::Path(path) => path
+                .path
+                .segments
+                .last()
+                .and_then(|segment| self.identifiers.get(&segment.ident.unraw().to_string()))
+                .cloned(),
+            _ => None,
+        };
+        let typed = self.type_of(owner, wrapped.as_ref().unwrap_or(ty));
+        Typed {
+            entity: None,
+            ..typed
+        }
+    }
+
+    /// Get the type of a field, named `owner` in warnings.
+    fn type_of(&mut self, owner: &str, ty: &Type) -> Typed {
+        match ty {
+            Type::Reference(reference) => self.type_of(owner, &reference.elem),
+            Type::Paren(inner) => self.type_of(owner, &inner.elem),
+            Type::Group(inner) => self.type_of(owner, &inner.elem),
+            Type::Array(array) => self
+                .type_of(owner, &array.elem)
+                .map(|element| TypeExpr::generic("List", element)),
+            Type::Slice(slice) => self
+                .type_of(owner, &slice.elem)
+                .map(|element| TypeExpr::generic("List", element)),
+            Type::Path(path) if path.qself.is_none() => {
+                let Some(segment) = path.path.segments.last() else {
+                    return Typed::simple("String");
+                };
+                let name = segment.ident.unraw().to_string();
+                let arguments = type_arguments(&segment.arguments);
+                self.path_type(owner, &name, &arguments)
+            }
+            _ => {
+                self.draft.warn(
+                    format!(
+                        "'{}' has a type with no counterpart; drafted as String",
+                        owner
+                    ),
+                    None,
+                );
+                Typed::simple("String")
+            }
+        }
+    }
+
+    fn path_type(&mut self, owner: &str, name: &str, arguments: &[&Type]) -> Typed {
+        match (name, arguments) {
+            ("Option", [inner]) => self.type_of(owner, inner).map(TypeExpr::optional),
+            ("Box" | "Rc" | "Arc" | "Cow", [inner]) => self.type_of(owner, inner),
+            ("Vec" | "VecDeque" | "LinkedList", [element]) => self
+                .type_of(owner, element)
+                .map(|element| TypeExpr::generic("List", element)),
+            ("HashSet" | "BTreeSet" | "IndexSet", [element]) => self
+                .type_of(owner, element)
+                .map(|element| TypeExpr::generic("Set", element)),
+            ("HashMap" | "BTreeMap" | "IndexMap", [key, value]) => {
+                let key = self.type_of(owner, key).type_expr;
+                let value = self.type_of(owner, value);
+                Typed {
+                    type_expr: TypeExpr::generic_multi("Map", vec![key, value.type_expr]),
+                    entity: value.entity,
+                }
+            }
+            _ => {
+                if let Some(&kind) = self.kinds.get(name) {
+                    return Typed {
+                        type_expr: TypeExpr::simple(name),
+                        entity: kind.is_entity().then(|| name.to_string()),
+                    };
+                }
+                if let Some(entity) = name
+                    .strip_suffix("Id")
+                    .filter(|entity| self.kinds.get(*entity).is_some_and(|kind| kind.is_entity()))
+                {
+                    return Typed {
+                        type_expr: TypeExpr::simple(entity),
+                        entity: Some(entity.to_string()),
+                    };
+                }
+                if let Some((_, scalar)) = SCALAR_TYPES.iter().find(|(rust, _)| *rust == name) {
+                    return Typed::simple(*scalar);
+                }
+                self.draft.warn(
+                    format!(
+                        "'{}' has the type '{}', which is not imported; kept as the object '{}'",
+                        owner, name, name
+                    ),
+                    None,
+                );
+                Typed::simple(name)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PrettyPrint;
+
+    fn file(module: &str, source: &str) -> RustFile {
+        RustFile {
+            module: module.to_string(),
+            path: format!("src/{}.rs", module.replace("::", "/")),
+            source: source.to_string(),
+        }
+    }
+
+    fn draft(files: &[RustFile], modules: &[&str]) -> (String, Vec<String>) {
+        let modules: Vec<String> = modules.iter().map(|m| m.to_string()).collect();
+        let imported = import(files, "Sales", &modules).unwrap();
+        let warnings = imported.warnings.iter().map(|w| w.to_string()).collect();
+        (imported.into_file().pretty_print(), warnings)
+    }
+
+    #[test]
+    fn test_marked_items_are_imported() {
+        let (source, warnings) = draft(
+            &[file(
+                "order",
+                r#"
+                use uuid::Uuid;
+
+                pub struct CustomerId(Uuid);
+
+                #[domain(entity)]
+                pub struct Customer {
+                    id: CustomerId,
+                    email: String,
+                }
+
+                #[domain(aggregate)]
+                pub struct Order {
+                    id: Uuid,
+                    customer_id: CustomerId,
+                    lines: Vec<OrderLine>,
+                    status: OrderStatus,
+                    total: Money,
+                    note: Option<String>,
+                    placed_at: chrono::DateTime<chrono::Utc>,
+                }
+
+                #[domain(entity)]
+                pub struct OrderLine {
+                    id: u64,
+                    quantity: u32,
+                }
+
+                #[cfg_attr(any(), domain(value))]
+                pub struct Money {
+                    amount: rust_decimal::Decimal,
+                    currency: String,
+                }
+
+                #[domain]
+                pub enum OrderStatus {
+                    Pending,
+                    Cancelled(String),
+                }
+
+                struct Helper;
+                "#,
+            )],
+            &[],
+        );
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert!(source.contains("  entity Customer {\n    id: UUID\n    email: String\n  }"));
+        assert!(source.contains(
+            "  entity Order {\n    id: UUID\n    status: OrderStatus\n    total: Money\n    note: String?\n    placedAt: DateTime\n  }"
+        ));
+        assert!(source
+            .contains("    customer: Order -> Customer\n    lines: Order -> List<OrderLine>\n"));
+        assert!(source.contains("  aggregate Order {\n    root: Order\n  }"));
+        assert!(source.contains("  value Money {\n    amount: Decimal\n    currency: String\n  }"));
+        assert!(source.contains("  enum OrderStatus = Pending | Cancelled(String)"));
+        assert!(!source.contains("Helper"));
+    }
+
+    #[test]
+    fn test_public_items_of_modules_are_imported() {
+        let files = [
+            file(
+                "domain",
+                r#"
+                pub struct InvoiceId(u64);
+
+                pub struct Invoice {
+                    pub id: InvoiceId,
+                    pub lines: Vec<InvoiceLine>,
+                }
+
+                pub struct InvoiceLine {
+                    pub description: String,
+                }
+
+                struct Cache;
+
+                pub mod tax {
+                    pub enum Rate { Standard, Reduced }
+                }
+                "#,
+            ),
+            file("infra", "pub struct Database { pub url: String }"),
+        ];
+        let (source, warnings) = draft(&files, &["crate::domain"]);
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert!(
+            source.contains("  entity Invoice {\n    id: Int\n    lines: List<InvoiceLine>\n  }")
+        );
+        assert!(source.contains("  value InvoiceLine {\n    description: String\n  }"));
+        assert!(source.contains("  enum Rate = Standard | Reduced"));
+        for name in ["InvoiceId", "Cache", "Database"] {
+            assert!(!source.contains(name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_what_cannot_be_imported_is_warned_about() {
+        let (_, warnings) = draft(
+            &[
+                file(
+                    "",
+                    r#"
+                    #[domain(entity)]
+                    pub struct Shipment {
+                        id: u64,
+                        carrier: Carrier,
+                        route: (f64, f64),
+                    }
+
+                    #[domain(enum)]
+                    pub struct Parcel;
+
+                    #[domain(aggregate_root)]
+                    pub enum Event { Shipped { at: u64 } }
+                    "#,
+                ),
+                file("broken", "pub struct {"),
+            ],
+            &[],
+        );
+        assert_eq!(warnings.len(), 6, "{:?}", warnings);
+        assert!(warnings[0].contains("'Parcel' is marked as an enum, but is a struct"));
+        assert!(warnings[1].contains("The #[domain(...)] attribute of 'Event' is not understood"));
+        assert!(warnings[2].starts_with("Skipped src/broken.rs, which does not parse"));
+        assert!(warnings[3].contains(
+            "'Shipment.carrier' has the type 'Carrier', which is not imported; kept as the object 'Carrier'"
+        ));
+        assert!(warnings[4].contains("'Shipment.route' has a type with no counterpart"));
+        assert!(warnings[5].contains("Variant 'Event::Shipped' has named fields"));
+    }
+
+    #[test]
+    fn test_crate_without_domain_items_is_an_error() {
+        let files = [file("", "pub struct Config { pub port: u16 }")];
+        assert!(import(&files, "Sales", &[]).is_err());
+    }
+
+    #[test]
+    fn test_module_of() {
+        assert_eq!(module_of(Path::new("lib.rs")), "");
+        assert_eq!(module_of(Path::new("domain/mod.rs")), "domain");
+        assert_eq!(module_of(Path::new("domain/order.rs")), "domain::order");
+        assert_eq!(module_of(Path::new("domain/lib.rs")), "domain::lib");
+    }
+}
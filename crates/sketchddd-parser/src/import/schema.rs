@@ -0,0 +1,774 @@
+//! Drafting a context from JSON Schema and OpenAPI documents.
+//!
+//! [`import`] reads the schemas of a document: the component schemas of an
+//! OpenAPI description, the definitions of a Swagger one, or the `$defs`
+//! and root of a JSON Schema.
+//!
+//! - An object schema with an `id` property becomes an entity, and other
+//!   object schemas become value objects. Properties become fields in
+//!   camelCase, optional unless they are required.
+//! - A property of an entity referencing another entity, or holding an
+//!   array of them, becomes a morphism instead of a field.
+//! - A schema or property listing its values with `enum` becomes an enum.
+//!   One that is `oneOf` or `anyOf` several schemas becomes an enum with a
+//!   variant holding each.
+//! - A property holding an object that is not a schema of the document
+//!   becomes a value object named after the property.
+//!
+//! Documents are read without the order of their keys, so schemas and
+//! their properties are drafted in alphabetical order, with `id` first.
+//!
+//! What the importer had to decide, such as whether an object is an entity,
+//! is warned about.
+
+use std::collections::{HashMap, HashSet};
+
+use serde_json::Value;
+use sketchddd_core::naming::{to_camel_case, to_pascal_case};
+
+use super::{member_type_name, optional_if, Draft, Imported};
+use crate::ast::{
+    EntityDecl, EnumDecl, FieldDecl, MorphismDecl, TypeExpr, ValueObjectDecl, VariantDecl,
+};
+use crate::error::ParseError;
+use crate::pretty::format_type_expr;
+
+/// Where documents keep their named schemas, as JSON pointers.
+const SCHEMA_PATHS: &[&[&str]] = &[&["components", "schemas"], &["definitions"], &["$defs"]];
+
+/// The name of the root schema among the named ones.
+const ROOT: &str = "#";
+
+/// Check whether a document is an OpenAPI or Swagger description or a JSON
+/// Schema, rather than an export of a model.
+pub fn is_schema(document: &Value) -> bool {
+    ["openapi", "swagger", "$schema"]
+        .iter()
+        .any(|key| document.get(key).is_some())
+}
+
+/// Draft a context named `context` from a JSON Schema or OpenAPI document.
+///
+/// Fails if the document has no schemas.
+pub fn import(document: &Value, context: &str) -> Result<Imported, ParseError> {
+    let mut importer = Importer::new(context);
+
+    for path in SCHEMA_PATHS {
+        let schemas = path
+            .iter()
+            .try_fold(document, |value, key| value.get(key))
+            .and_then(Value::as_object);
+        for (raw, schema) in schemas.into_iter().flatten() {
+            importer
+                .schemas
+                .push((raw.as_str(), to_pascal_case(raw), schema));
+        }
+    }
+    let api = ["openapi", "swagger"]
+        .iter()
+        .any(|key| document.get(key).is_some());
+    let has_root = ["properties", "enum", "oneOf", "anyOf", "allOf"]
+        .iter()
+        .any(|key| document.get(key).is_some());
+    if !api && has_root {
+        let name = match document.get("title").and_then(Value::as_str) {
+            Some(title) => to_pascal_case(title),
+            None => {
+                importer.draft.warn(
+                    format!("The root schema has no title; named it '{}'", context),
+                    None,
+                );
+                context.to_string()
+            }
+        };
+        importer.schemas.push((ROOT, name, document));
+    }
+    if importer.schemas.is_empty() {
+        return Err(ParseError::new("The document has no schemas"));
+    }
+
+    let schemas = importer.schemas.clone();
+    for &(raw, ref name, schema) in &schemas {
+        let kind = importer.kind(schema);
+        importer.kinds.insert(raw, (name.clone(), kind));
+    }
+    for (raw, name, schema) in schemas {
+        importer.add_schema(raw, &name, schema);
+    }
+
+    Ok(importer.draft.finish())
+}
+
+/// What a named schema becomes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Entity,
+    Value,
+    Enum,
+    /// An enum with a variant holding each schema it may be
+    Union,
+    /// Another name for a type, such as a string with a format
+    Alias,
+}
+
+/// The type of a property.
+struct Typed {
+    type_expr: TypeExpr,
+    /// Whether the property may be null
+    nullable: bool,
+    /// Whether it holds entities, so it is a morphism for an entity
+    entity: bool,
+}
+
+impl Typed {
+    fn new(type_expr: TypeExpr) -> Self {
+        Self {
+            type_expr,
+            nullable: false,
+            entity: false,
+        }
+    }
+
+    fn simple(name: impl Into<String>) -> Self {
+        Self::new(TypeExpr::simple(name))
+    }
+}
+
+/// The properties of an object schema, including the ones it has from the
+/// schemas it is `allOf`.
+#[derive(Default)]
+struct Properties<'d> {
+    properties: Vec<(&'d str, &'d Value)>,
+    required: Vec<&'d str>,
+    /// Named schemas whose properties it has
+    bases: Vec<String>,
+}
+
+/// The context drafted so far, with what is known of the document.
+struct Importer<'d> {
+    draft: Draft,
+    /// Named schemas, by the name the document gives them, with the name
+    /// they are declared with
+    schemas: Vec<(&'d str, String, &'d Value)>,
+    kinds: HashMap<&'d str, (String, Kind)>,
+    /// Aliases being resolved, to stop at ones that refer to themselves
+    resolving: HashSet<String>,
+}
+
+impl<'d> Importer<'d> {
+    fn new(context: &str) -> Self {
+        Self {
+            draft: Draft::new(context),
+            schemas: Vec::new(),
+            kinds: HashMap::new(),
+            resolving: HashSet::new(),
+        }
+    }
+
+    /// Get the named schema a `$ref` points at.
+    fn schema(&self, reference: &str) -> Option<(&'d str, &'d Value)> {
+        let raw = if reference == ROOT {
+            ROOT.to_string()
+        } else {
+            let (_, name) = reference.rsplit_once('/')?;
+            name.replace("~1", "/").replace("~0", "~")
+        };
+        self.schemas
+            .iter()
+            .find(|(name, _, _)| *name == raw)
+            .map(|&(name, _, schema)| (name, schema))
+    }
+
+    /// Decide what a schema becomes.
+    fn kind(&self, schema: &'d Value) -> Kind {
+        if schema.get("enum").is_some() {
+            Kind::Enum
+        } else if union_options(schema).is_some_and(|options| options.len() > 1) {
+            Kind::Union
+        } else if is_object(schema) {
+            let properties = self.properties(schema, &mut Vec::new());
+            if properties
+                .properties
+                .iter()
+                .any(|(name, _)| name.eq_ignore_ascii_case("id"))
+            {
+                Kind::Entity
+            } else {
+                Kind::Value
+            }
+        } else {
+            Kind::Alias
+        }
+    }
+
+    /// Collect the properties of an object schema, following `allOf`.
+    fn properties(&self, schema: &'d Value, visited: &mut Vec<&'d str>) -> Properties<'d> {
+        let mut properties = Properties::default();
+        for part in schema
+            .get("allOf")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let inherited = match part.get("$ref").and_then(Value::as_str) {
+                Some(reference) => match self.schema(reference) {
+                    Some((raw, base)) if !visited.contains(&raw) => {
+                        visited.push(raw);
+                        let mut inherited = self.properties(base, visited);
+                        if let Some((_, name, _)) = self.schemas.iter().find(|(r, _, _)| *r == raw)
+                        {
+                            inherited.bases.insert(0, name.clone());
+                        }
+                        inherited
+                    }
+                    _ => continue,
+                },
+                None => self.properties(part, visited),
+            };
+            properties.properties.extend(inherited.properties);
+            properties.required.extend(inherited.required);
+            properties.bases.extend(inherited.bases);
+        }
+        if let Some(own) = schema.get("properties").and_then(Value::as_object) {
+            properties
+                .properties
+                .extend(own.iter().map(|(name, schema)| (name.as_str(), schema)));
+        }
+        properties.required.extend(
+            schema
+                .get("required")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str),
+        );
+        properties
+    }
+
+    fn add_schema(&mut self, raw: &'d str, name: &str, schema: &'d Value) {
+        match self.kinds[raw].1 {
+            Kind::Entity => {
+                let mut entity = EntityDecl::new(name);
+                entity.fields = self.members(name, schema, true);
+                self.draft.context.entities.push(entity);
+            }
+            Kind::Value => {
+                let mut value = ValueObjectDecl::new(name);
+                value.fields = self.members(name, schema, false);
+                if value.fields.is_empty() {
+                    self.draft.warn(
+                        format!(
+                            "Schema '{}' has no properties; declare the fields of its value object",
+                            name
+                        ),
+                        None,
+                    );
+                } else {
+                    self.draft.warn(
+                        format!(
+                            "Schema '{}' has no id property, so it became a value object",
+                            name
+                        ),
+                        None,
+                    );
+                }
+                self.draft.context.value_objects.push(value);
+            }
+            Kind::Enum => {
+                let labels = enum_labels(schema);
+                self.draft.declare_enum(name.to_string(), &labels, None);
+            }
+            Kind::Union => {
+                let options = union_options(schema).unwrap_or_default();
+                self.declare_union(name.to_string(), &options);
+            }
+            Kind::Alias => {
+                let typed = self.type_of(name, name, schema);
+                self.draft.warn(
+                    format!(
+                        "Schema '{}' is not an object; its uses became {}",
+                        name,
+                        format_type_expr(&typed.type_expr)
+                    ),
+                    None,
+                );
+            }
+        }
+    }
+
+    /// Get the fields of an object schema, declaring the morphisms of an
+    /// entity.
+    fn members(&mut self, owner: &str, schema: &'d Value, entity: bool) -> Vec<FieldDecl> {
+        let properties = self.properties(schema, &mut Vec::new());
+        if !properties.bases.is_empty() {
+            self.draft.warn(
+                format!(
+                    "Schema '{}' is all of {}; drafted with their properties copied",
+                    owner,
+                    properties.bases.join(", ")
+                ),
+                None,
+            );
+        }
+
+        let mut members = properties.properties;
+        members.sort_by_key(|(name, _)| !name.eq_ignore_ascii_case("id"));
+
+        let mut fields: Vec<FieldDecl> = Vec::new();
+        for (name, property) in members {
+            let field_name = to_camel_case(name);
+            let typed = self.type_of(owner, name, property);
+            let optional = typed.nullable || !properties.required.contains(&name);
+            let type_expr = optional_if(typed.type_expr, optional);
+            if entity && typed.entity {
+                self.draft.context.morphisms.push(MorphismDecl::new(
+                    field_name,
+                    TypeExpr::simple(owner),
+                    type_expr,
+                ));
+            } else if let Some(field) = fields.iter_mut().find(|f| f.name == field_name) {
+                // A schema may narrow a property it has from another
+                field.type_expr = type_expr;
+            } else {
+                fields.push(FieldDecl::new(field_name, type_expr));
+            }
+        }
+        fields
+    }
+
+    /// Get the type of a property of `owner`, declaring the enums and value
+    /// objects it holds.
+    fn type_of(&mut self, owner: &str, property: &str, schema: &'d Value) -> Typed {
+        let nullable = schema.get("nullable").and_then(Value::as_bool) == Some(true);
+        let mut typed = self.non_null_type(owner, property, schema);
+        typed.nullable |= nullable;
+        typed
+    }
+
+    fn non_null_type(&mut self, owner: &str, property: &str, schema: &'d Value) -> Typed {
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            return self.reference(owner, property, reference);
+        }
+        if let Some([part]) = schema
+            .get("allOf")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+        {
+            return self.type_of(owner, property, part);
+        }
+        if schema.get("enum").is_some() {
+            let name = self.draft.declare_enum(
+                member_type_name(owner, property),
+                &enum_labels(schema),
+                None,
+            );
+            return Typed::simple(name);
+        }
+        if let Some(options) = union_options(schema) {
+            let nullable = options.len() < union_count(schema);
+            let mut typed = match options.as_slice() {
+                [single] => self.type_of(owner, property, single),
+                _ => Typed::simple(self.declare_union(member_type_name(owner, property), &options)),
+            };
+            typed.nullable |= nullable;
+            return typed;
+        }
+
+        let (types, nullable) = types(schema);
+        let mut typed = match types.as_slice() {
+            ["string"] => Typed::simple(match schema.get("format").and_then(Value::as_str) {
+                Some("uuid") => "UUID",
+                Some("date") => "Date",
+                Some("date-time") => "DateTime",
+                Some("email") => "Email",
+                _ => "String",
+            }),
+            ["integer"] => Typed::simple("Int"),
+            ["number"] => Typed::simple(match schema.get("format").and_then(Value::as_str) {
+                Some("decimal") => "Decimal",
+                _ => "Float",
+            }),
+            ["boolean"] => Typed::simple("Bool"),
+            ["array"] => {
+                let collection = if schema.get("uniqueItems").and_then(Value::as_bool) == Some(true)
+                {
+                    "Set"
+                } else {
+                    "List"
+                };
+                match schema.get("items") {
+                    Some(items) => {
+                        let element = self.type_of(owner, property, items);
+                        Typed {
+                            type_expr: TypeExpr::generic(collection, element.type_expr),
+                            nullable: false,
+                            entity: element.entity,
+                        }
+                    }
+                    None => {
+                        self.draft.warn(
+                            format!(
+                                "Property '{}.{}' is an array of unknown items; drafted as {}<String>",
+                                owner, property, collection
+                            ),
+                            None,
+                        );
+                        Typed::new(TypeExpr::generic(collection, TypeExpr::simple("String")))
+                    }
+                }
+            }
+            [] | ["object"] if is_object(schema) => self.object(owner, property, schema),
+            [] => {
+                self.draft.warn(
+                    format!(
+                        "Property '{}.{}' has no type; drafted as String",
+                        owner, property
+                    ),
+                    None,
+                );
+                Typed::simple("String")
+            }
+            _ => {
+                self.draft.warn(
+                    format!(
+                        "Property '{}.{}' may be a {}; drafted as String",
+                        owner,
+                        property,
+                        types.join(" or ")
+                    ),
+                    None,
+                );
+                Typed::simple("String")
+            }
+        };
+        typed.nullable |= nullable;
+        typed
+    }
+
+    /// Get the type of a property holding an object that is not a named
+    /// schema.
+    fn object(&mut self, owner: &str, property: &str, schema: &'d Value) -> Typed {
+        let has_properties = schema.get("properties").is_some() || schema.get("allOf").is_some();
+        if let (false, Some(values)) = (has_properties, schema.get("additionalProperties")) {
+            if values.is_object() {
+                let value = self.type_of(owner, property, values);
+                return Typed::new(TypeExpr::generic_multi(
+                    "Map",
+                    vec![TypeExpr::simple("String"), value.type_expr],
+                ));
+            }
+        }
+
+        let name = to_pascal_case(property);
+        let mut value = ValueObjectDecl::new(&name);
+        value.fields = self.members(&name, schema, false);
+        let empty = value.fields.is_empty();
+        let name = self.draft.declare_value(value, owner);
+        if empty {
+            self.draft.warn(
+                format!(
+                    "Property '{}.{}' holds any object, so it became the value object '{}'; declare its fields",
+                    owner, property, name
+                ),
+                None,
+            );
+        }
+        Typed::simple(name)
+    }
+
+    /// Get the type a `$ref` refers to.
+    fn reference(&mut self, owner: &str, property: &str, reference: &str) -> Typed {
+        let Some((raw, schema)) = self.schema(reference) else {
+            // Name it after the last part of the reference, without the
+            // extension of a file
+            let last = reference.rsplit('/').next().unwrap_or(reference);
+            let name = to_pascal_case(last.split('.').next().unwrap_or(last));
+            self.draft.warn(
+                format!(
+                    "Property '{}.{}' refers to '{}', which is not a schema of the document; kept as the object '{}'",
+                    owner, property, reference, name
+                ),
+                None,
+            );
+            return Typed::simple(name);
+        };
+        let (name, kind) = self.kinds[raw].clone();
+        match kind {
+            Kind::Entity => Typed {
+                entity: true,
+                ..Typed::simple(name)
+            },
+            Kind::Alias if self.resolving.insert(name.clone()) => {
+                let typed = self.type_of(&name, &name, schema);
+                self.resolving.remove(&name);
+                typed
+            }
+            _ => Typed::simple(name),
+        }
+    }
+
+    /// Declare an enum with a variant holding each schema a value may be,
+    /// returning its name.
+    fn declare_union(&mut self, name: String, options: &[&'d Value]) -> String {
+        let mut enum_decl = EnumDecl::new(&name);
+        for option in options {
+            let Some(reference) = option.get("$ref").and_then(Value::as_str) else {
+                self.draft.warn(
+                    format!(
+                        "Left out a schema of '{}' that is not a schema of the document",
+                        name
+                    ),
+                    None,
+                );
+                continue;
+            };
+            let typed = self.reference(&name, &name, reference);
+            let variant = typed.type_expr.base_name().to_string();
+            if !enum_decl.variants.iter().any(|v| v.name == variant) {
+                enum_decl
+                    .variants
+                    .push(VariantDecl::with_payload(variant, vec![typed.type_expr]));
+            }
+        }
+        self.draft.warn(
+            format!(
+                "'{}' is one of several schemas; drafted as an enum with a variant holding each",
+                name
+            ),
+            None,
+        );
+        self.draft.context.enums.push(enum_decl);
+        name
+    }
+}
+
+/// Check whether a schema describes an object.
+fn is_object(schema: &Value) -> bool {
+    types(schema).0 == ["object"]
+        || schema.get("properties").is_some()
+        || schema.get("allOf").is_some()
+}
+
+/// Get the types a schema allows other than null, and whether it allows
+/// null.
+fn types(schema: &Value) -> (Vec<&str>, bool) {
+    let types: Vec<&str> = match schema.get("type") {
+        Some(Value::String(name)) => vec![name.as_str()],
+        Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    let nullable = types.contains(&"null");
+    (
+        types.into_iter().filter(|t| *t != "null").collect(),
+        nullable,
+    )
+}
+
+/// Get the schemas a schema is `oneOf` or `anyOf`, leaving out null.
+fn union_options(schema: &Value) -> Option<Vec<&Value>> {
+    let options = schema
+        .get("oneOf")
+        .or_else(|| schema.get("anyOf"))?
+        .as_array()?;
+    Some(
+        options
+            .iter()
+            .filter(|option| option.get("type").and_then(Value::as_str) != Some("null"))
+            .collect(),
+    )
+}
+
+/// Count the schemas a schema is `oneOf` or `anyOf`, including null.
+fn union_count(schema: &Value) -> usize {
+    schema
+        .get("oneOf")
+        .or_else(|| schema.get("anyOf"))
+        .and_then(Value::as_array)
+        .map_or(0, Vec::len)
+}
+
+/// Get the values of an enum schema as labels.
+fn enum_labels(schema: &Value) -> Vec<String> {
+    schema
+        .get("enum")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|value| !value.is_null())
+        .map(|value| match value {
+            Value::String(label) => label.clone(),
+            other => other.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PrettyPrint;
+    use serde_json::json;
+
+    fn draft(document: Value) -> (String, Vec<String>) {
+        let imported = import(&document, "Sales").unwrap();
+        let warnings = imported.warnings.iter().map(|w| w.to_string()).collect();
+        (imported.into_file().pretty_print(), warnings)
+    }
+
+    #[test]
+    fn test_openapi_schemas_become_entities_values_and_morphisms() {
+        let (source, warnings) = draft(json!({
+            "openapi": "3.0.3",
+            "components": {
+                "schemas": {
+                    "Address": {
+                        "type": "object",
+                        "required": ["street"],
+                        "properties": {
+                            "street": { "type": "string" },
+                            "city": { "type": "string" }
+                        }
+                    },
+                    "Customer": {
+                        "type": "object",
+                        "required": ["id", "email"],
+                        "properties": {
+                            "id": { "type": "string", "format": "uuid" },
+                            "email": { "type": "string", "format": "email" },
+                            "address": { "$ref": "#/components/schemas/Address" }
+                        }
+                    },
+                    "Order": {
+                        "type": "object",
+                        "required": ["id", "customer", "lines", "status"],
+                        "properties": {
+                            "id": { "type": "integer" },
+                            "customer": { "$ref": "#/components/schemas/Customer" },
+                            "lines": {
+                                "type": "array",
+                                "items": { "$ref": "#/components/schemas/OrderLine" }
+                            },
+                            "notes": { "type": "string", "nullable": true },
+                            "placedAt": { "type": "string", "format": "date-time" },
+                            "status": { "type": "string", "enum": ["pending", "shipped"] },
+                            "tags": {
+                                "type": "array",
+                                "uniqueItems": true,
+                                "items": { "type": "string" }
+                            },
+                            "total": { "type": "number", "format": "decimal" }
+                        }
+                    },
+                    "OrderLine": {
+                        "type": "object",
+                        "required": ["id"],
+                        "properties": {
+                            "id": { "type": "integer" },
+                            "quantity": { "type": "integer" }
+                        }
+                    }
+                }
+            }
+        }));
+        assert!(source.contains(
+            "  entity Customer {\n    id: UUID\n    address: Address?\n    email: Email\n  }"
+        ));
+        assert!(source.contains(
+            "  entity Order {\n    id: Int\n    notes: String?\n    placedAt: DateTime?\n    status: OrderStatus\n    tags: Set<String>?\n    total: Decimal?\n  }"
+        ));
+        assert!(source.contains("    customer: Order -> Customer\n"));
+        assert!(source.contains("    lines: Order -> List<OrderLine>\n"));
+        assert!(source.contains("  value Address {\n    city: String?\n    street: String\n  }"));
+        assert!(source.contains("  enum OrderStatus = Pending | Shipped"));
+        assert_eq!(
+            warnings,
+            ["Schema 'Address' has no id property, so it became a value object"]
+        );
+    }
+
+    #[test]
+    fn test_json_schema_compositions() {
+        let (source, warnings) = draft(json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "payment request",
+            "type": "object",
+            "required": ["id", "method"],
+            "properties": {
+                "id": { "type": "string" },
+                "method": { "$ref": "#/$defs/method" },
+                "labels": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" }
+                },
+                "payer": {
+                    "type": "object",
+                    "properties": { "name": { "type": "string" } }
+                },
+                "receipt": { "$ref": "https://example.com/receipt.json" }
+            },
+            "$defs": {
+                "card": {
+                    "allOf": [{ "$ref": "#/$defs/instrument" }],
+                    "properties": { "last4": { "type": "string" } }
+                },
+                "instrument": {
+                    "type": "object",
+                    "properties": { "holder": { "type": "string" } }
+                },
+                "method": {
+                    "oneOf": [{ "$ref": "#/$defs/card" }, { "$ref": "#/$defs/wire" }]
+                },
+                "wire": {
+                    "type": "object",
+                    "properties": { "iban": { "type": ["string", "null"] } }
+                }
+            }
+        }));
+        assert!(source.contains("  enum Method = Card(Card) | Wire(Wire)"));
+        assert!(source.contains("  value Card {\n    holder: String?\n    last4: String?\n  }"));
+        assert!(source.contains(
+            "  entity PaymentRequest {\n    id: String\n    labels: Map<String, String>?\n    method: Method\n    payer: Payer?\n    receipt: Receipt?\n  }"
+        ));
+        assert!(source.contains("  value Payer {\n    name: String?\n  }"));
+        assert!(warnings.contains(
+            &"Schema 'Card' is all of Instrument; drafted with their properties copied".to_string()
+        ));
+        assert!(warnings.contains(
+            &"'Method' is one of several schemas; drafted as an enum with a variant holding each"
+                .to_string()
+        ));
+        assert!(warnings.iter().any(|w| w.contains(
+            "refers to 'https://example.com/receipt.json', which is not a schema of the document"
+        )));
+    }
+
+    #[test]
+    fn test_aliases_are_resolved() {
+        let (source, warnings) = draft(json!({
+            "swagger": "2.0",
+            "definitions": {
+                "Account": {
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "string" },
+                        "contact": { "$ref": "#/definitions/EmailAddress" }
+                    }
+                },
+                "EmailAddress": { "type": "string", "format": "email" }
+            }
+        }));
+        assert!(source.contains("    contact: Email?\n"));
+        assert!(!source.contains("EmailAddress"));
+        assert_eq!(
+            warnings,
+            ["Schema 'EmailAddress' is not an object; its uses became Email"]
+        );
+    }
+
+    #[test]
+    fn test_documents_without_schemas_are_an_error() {
+        let document = json!({ "openapi": "3.1.0", "paths": {} });
+        assert!(is_schema(&document));
+        assert!(import(&document, "Sales").is_err());
+        assert!(!is_schema(&json!({ "version": 1, "model": {} })));
+    }
+}
@@ -0,0 +1,746 @@
+//! Drafting a context from an SQL schema.
+//!
+//! [`import`] reads the `CREATE TABLE` and `CREATE TYPE` statements of a
+//! schema and skips the others, such as indexes and grants:
+//!
+//! - A table becomes an entity named in the singular, so `order_lines`
+//!   gives `OrderLine`, and its columns become fields in camelCase. Columns
+//!   that may be null give optional fields.
+//! - A foreign key becomes a morphism to the entity of the table it
+//!   references instead of a field, named after its column without `_id`.
+//!   A table holding two foreign keys and nothing else joins the tables
+//!   they reference, and becomes a morphism to a set.
+//! - Enum types, MySQL `ENUM(...)` columns and columns checked against a
+//!   list of strings, as in `CHECK (status IN ('open', 'closed'))`, become
+//!   enums. Composite types become value objects.
+//! - JSON columns, and columns sharing a prefix such as `price_amount` and
+//!   `price_currency`, become value objects. These are guesses, and each
+//!   is warned about.
+
+use std::collections::HashMap;
+
+use sketchddd_core::naming::{singularize, to_camel_case, to_pascal_case};
+use sqlparser::ast::{
+    ArrayElemTypeDef, ColumnDef, ColumnOption, CreateTable, DataType, EnumMember, Expr, Ident,
+    ObjectName, Statement, TableConstraint, UserDefinedTypeCompositeAttributeDef,
+    UserDefinedTypeRepresentation, Value,
+};
+use sqlparser::dialect::GenericDialect;
+use sqlparser::keywords::Keyword;
+use sqlparser::parser::Parser;
+use sqlparser::tokenizer::{Token, TokenWithSpan, Tokenizer};
+
+use super::{member_type_name, optional_if, reference_name, Draft, Imported};
+use crate::ast::{EntityDecl, FieldDecl, MorphismDecl, TypeExpr, ValueObjectDecl};
+use crate::error::ParseError;
+
+/// Prefixes that columns share without making up a value, as `created_at`
+/// and `created_by` do.
+const NOT_VALUE_PREFIXES: &[&str] = &["created", "updated", "deleted", "modified", "is", "has"];
+
+/// Types that SQL dialects define beyond the standard, with their
+/// counterpart.
+const DIALECT_TYPES: &[(&str, &str)] = &[
+    ("serial", "Int"),
+    ("smallserial", "Int"),
+    ("bigserial", "Int"),
+    ("citext", "String"),
+    ("money", "Decimal"),
+];
+
+/// Draft a context named `context` from an SQL schema.
+///
+/// Fails if the schema cannot be tokenized or has no tables. A statement
+/// that cannot be parsed is skipped with a warning.
+pub fn import(sql: &str, context: &str) -> Result<Imported, ParseError> {
+    let dialect = GenericDialect {};
+    let tokens = Tokenizer::new(&dialect, sql)
+        .tokenize_with_location()
+        .map_err(|e| ParseError::new(format!("Invalid SQL: {}", e)))?;
+
+    let mut importer = Importer::new(context);
+    let mut tables = Vec::new();
+    for statement in statements(tokens) {
+        let line = statement.first().and_then(|t| line(t.span.start.line));
+        if let Some((name, labels)) = enum_type(&statement) {
+            importer.add_enum_type(&name, &labels, line);
+            continue;
+        }
+        match Parser::new(&dialect)
+            .with_tokens_with_locations(statement)
+            .parse_statement()
+        {
+            Ok(Statement::CreateTable(table)) => tables.push(table),
+            Ok(Statement::CreateType {
+                name,
+                representation: UserDefinedTypeRepresentation::Composite { attributes },
+            }) => importer.add_composite_type(&name, &attributes, line),
+            Ok(_) => {}
+            Err(e) => importer.draft.warn(
+                format!("Skipped a statement that does not parse: {}", e),
+                line,
+            ),
+        }
+    }
+
+    if tables.is_empty() {
+        return Err(ParseError::new("The schema has no CREATE TABLE statements"));
+    }
+    for table in &tables {
+        let name = unqualified(&table.name);
+        let pascal = to_pascal_case(name);
+        let entity = singularize(&pascal).unwrap_or(pascal);
+        importer.entities.insert(name.to_lowercase(), entity);
+    }
+    for table in &tables {
+        importer.add_table(table);
+    }
+
+    Ok(importer.draft.finish())
+}
+
+/// Split tokens into statements at semicolons, leaving out empty ones.
+fn statements(tokens: Vec<TokenWithSpan>) -> Vec<Vec<TokenWithSpan>> {
+    let mut statements = vec![Vec::new()];
+    for token in tokens {
+        match token.token {
+            Token::SemiColon => statements.push(Vec::new()),
+            Token::Whitespace(_) => {}
+            _ => statements.last_mut().expect("a statement").push(token),
+        }
+    }
+    statements.retain(|statement| !statement.is_empty());
+    statements
+}
+
+/// Read `CREATE TYPE name AS ENUM ('label', ...)`, which the SQL parser
+/// does not support, into the type's name and labels.
+fn enum_type(statement: &[TokenWithSpan]) -> Option<(String, Vec<String>)> {
+    let token = |i: usize| statement.get(i).map(|t| &t.token);
+    let keyword = |i: usize, keyword: Keyword| matches!(token(i), Some(Token::Word(w)) if w.keyword == keyword);
+    if !keyword(0, Keyword::CREATE) || !keyword(1, Keyword::TYPE) {
+        return None;
+    }
+
+    // The name may be qualified by its schema
+    let mut i = 2;
+    let mut name = None;
+    while let Some(Token::Word(word)) = token(i) {
+        name = Some(word.value.clone());
+        i += 1;
+        if token(i) != Some(&Token::Period) {
+            break;
+        }
+        i += 1;
+    }
+    if !keyword(i, Keyword::AS)
+        || !keyword(i + 1, Keyword::ENUM)
+        || token(i + 2) != Some(&Token::LParen)
+    {
+        return None;
+    }
+    let labels = statement[i + 3..]
+        .iter()
+        .take_while(|t| t.token != Token::RParen)
+        .filter_map(|t| match &t.token {
+            Token::SingleQuotedString(label) => Some(label.clone()),
+            _ => None,
+        })
+        .collect();
+    Some((name?, labels))
+}
+
+/// Get the name of a table or type without its schema.
+fn unqualified(name: &ObjectName) -> &str {
+    name.0
+        .last()
+        .map(|ident| ident.value.as_str())
+        .unwrap_or_default()
+}
+
+/// Get a line of the schema, which is 0 where the parser does not know it.
+fn line(line: u64) -> Option<u32> {
+    (line > 0).then_some(line as u32)
+}
+
+/// Get the line an identifier is on.
+fn line_of(ident: &Ident) -> Option<u32> {
+    line(ident.span.start.line)
+}
+
+/// Get the type standard SQL types map to, if they have one.
+fn scalar_type(data_type: &DataType) -> Option<&'static str> {
+    use DataType::*;
+    let name = match data_type {
+        Character(_)
+        | Char(_)
+        | CharacterVarying(_)
+        | CharVarying(_)
+        | Varchar(_)
+        | Nvarchar(_)
+        | CharacterLargeObject(_)
+        | CharLargeObject(_)
+        | Clob(_)
+        | Text
+        | TinyText
+        | MediumText
+        | LongText
+        | String(_)
+        | FixedString(_) => "String",
+        Uuid => "UUID",
+        TinyInt(_) | UnsignedTinyInt(_) | Int2(_) | UnsignedInt2(_) | SmallInt(_)
+        | UnsignedSmallInt(_) | MediumInt(_) | UnsignedMediumInt(_) | Int(_) | Int4(_)
+        | Int8(_) | Int16 | Int32 | Int64 | Int128 | Int256 | Integer(_) | UnsignedInt(_)
+        | UnsignedInt4(_) | UnsignedInteger(_) | UInt8 | UInt16 | UInt32 | UInt64 | UInt128
+        | UInt256 | BigInt(_) | UnsignedBigInt(_) | UnsignedInt8(_) => "Int",
+        Float(_) | Float4 | Float32 | Float64 | Real | Float8 | Double | DoublePrecision => "Float",
+        Numeric(_) | Decimal(_) | BigNumeric(_) | BigDecimal(_) | Dec(_) => "Decimal",
+        Bool | Boolean => "Bool",
+        Date | Date32 => "Date",
+        Datetime(_) | Datetime64(..) | Timestamp(..) => "DateTime",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Get the column and strings of a check such as `status IN ('a', 'b')`.
+fn checked_labels(expr: &Expr) -> Option<(String, Vec<String>)> {
+    match expr {
+        Expr::Nested(inner) => checked_labels(inner),
+        Expr::InList {
+            expr,
+            list,
+            negated: false,
+        } => {
+            let column = match expr.as_ref() {
+                Expr::Identifier(ident) => ident,
+                Expr::CompoundIdentifier(idents) => idents.last()?,
+                _ => return None,
+            };
+            let labels = list
+                .iter()
+                .map(|item| match item {
+                    Expr::Value(Value::SingleQuotedString(label)) => Some(label.clone()),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()?;
+            Some((column.value.to_lowercase(), labels))
+        }
+        _ => None,
+    }
+}
+
+/// A column of a table being imported, for naming what it becomes.
+struct Column<'t> {
+    table: &'t str,
+    entity: &'t str,
+    name: &'t str,
+    line: Option<u32>,
+}
+
+/// The context drafted so far, with what is known of the schema.
+struct Importer {
+    draft: Draft,
+    /// Enums and value objects declared for the types of the schema, by
+    /// lowercased type name
+    types: HashMap<String, String>,
+    /// Entities of the tables, by lowercased table name
+    entities: HashMap<String, String>,
+}
+
+impl Importer {
+    fn new(context: &str) -> Self {
+        Self {
+            draft: Draft::new(context),
+            types: HashMap::new(),
+            entities: HashMap::new(),
+        }
+    }
+
+    fn add_enum_type(&mut self, name: &str, labels: &[String], line: Option<u32>) {
+        let declared = self.draft.declare_enum(to_pascal_case(name), labels, line);
+        self.types.insert(name.to_lowercase(), declared);
+    }
+
+    fn add_composite_type(
+        &mut self,
+        name: &ObjectName,
+        attributes: &[UserDefinedTypeCompositeAttributeDef],
+        line: Option<u32>,
+    ) {
+        let type_name = unqualified(name);
+        let value_name = to_pascal_case(type_name);
+        let mut value = ValueObjectDecl::new(&value_name);
+        for attribute in attributes {
+            let column = Column {
+                table: type_name,
+                entity: &value_name,
+                name: &attribute.name.value,
+                line: line_of(&attribute.name).or(line),
+            };
+            let type_expr = self.column_type(&attribute.data_type, &column);
+            value.fields.push(FieldDecl::new(
+                to_camel_case(&attribute.name.value),
+                type_expr,
+            ));
+        }
+        self.draft.context.value_objects.push(value);
+        self.types.insert(type_name.to_lowercase(), value_name);
+    }
+
+    /// Get the type of a column.
+    fn column_type(&mut self, data_type: &DataType, column: &Column) -> TypeExpr {
+        match data_type {
+            DataType::Enum(members, _) => {
+                let labels: Vec<String> = members
+                    .iter()
+                    .map(|member| match member {
+                        EnumMember::Name(label) | EnumMember::NamedValue(label, _) => label.clone(),
+                    })
+                    .collect();
+                TypeExpr::simple(self.draft.declare_enum(
+                    member_type_name(column.entity, column.name),
+                    &labels,
+                    column.line,
+                ))
+            }
+            DataType::Custom(name, _) => {
+                let type_name = unqualified(name);
+                if let Some(declared) = self.types.get(&type_name.to_lowercase()) {
+                    return TypeExpr::simple(declared.clone());
+                }
+                if let Some((_, scalar)) = DIALECT_TYPES
+                    .iter()
+                    .find(|(sql, _)| sql.eq_ignore_ascii_case(type_name))
+                {
+                    return TypeExpr::simple(*scalar);
+                }
+                let object = to_pascal_case(type_name);
+                self.draft.warn(
+                    format!(
+                        "Column '{}.{}' has the unknown type '{}'; kept as the object '{}'",
+                        column.table, column.name, name, object
+                    ),
+                    column.line,
+                );
+                TypeExpr::simple(object)
+            }
+            DataType::Array(
+                ArrayElemTypeDef::AngleBracket(element)
+                | ArrayElemTypeDef::SquareBracket(element, _)
+                | ArrayElemTypeDef::Parenthesis(element),
+            ) => TypeExpr::generic("List", self.column_type(element, column)),
+            DataType::JSON | DataType::JSONB => {
+                let name = self.draft.declare_value(
+                    ValueObjectDecl::new(to_pascal_case(column.name)),
+                    column.entity,
+                );
+                self.draft.warn(
+                    format!(
+                        "JSON column '{}.{}' became the value object '{}'; declare its fields",
+                        column.table, column.name, name
+                    ),
+                    column.line,
+                );
+                TypeExpr::simple(name)
+            }
+            other => match scalar_type(other) {
+                Some(scalar) => TypeExpr::simple(scalar),
+                None => {
+                    self.draft.warn(
+                        format!(
+                            "Column '{}.{}' has the type {}, which has no counterpart; imported as String",
+                            column.table, column.name, other
+                        ),
+                        column.line,
+                    );
+                    TypeExpr::simple("String")
+                }
+            },
+        }
+    }
+
+    fn add_table(&mut self, table: &CreateTable) {
+        let table_name = unqualified(&table.name);
+        let entity = self.entities[&table_name.to_lowercase()].clone();
+        let table_line = table.name.0.last().and_then(line_of);
+
+        let mut primary = Vec::new();
+        let mut foreign: HashMap<String, &ObjectName> = HashMap::new();
+        let mut checks: HashMap<String, Vec<String>> = HashMap::new();
+        for column in &table.columns {
+            let key = column.name.value.to_lowercase();
+            for option in &column.options {
+                match &option.option {
+                    ColumnOption::Unique {
+                        is_primary: true, ..
+                    } => primary.push(key.clone()),
+                    ColumnOption::ForeignKey { foreign_table, .. } => {
+                        foreign.insert(key.clone(), foreign_table);
+                    }
+                    ColumnOption::Check(expr) => checks.extend(checked_labels(expr)),
+                    _ => {}
+                }
+            }
+        }
+        for constraint in &table.constraints {
+            match constraint {
+                TableConstraint::PrimaryKey { columns, .. } => {
+                    primary.extend(columns.iter().map(|c| c.value.to_lowercase()))
+                }
+                TableConstraint::ForeignKey {
+                    columns,
+                    foreign_table,
+                    ..
+                } => match columns.as_slice() {
+                    [column] => {
+                        foreign.insert(column.value.to_lowercase(), foreign_table);
+                    }
+                    _ => {
+                        let names: Vec<&str> = columns.iter().map(|c| c.value.as_str()).collect();
+                        self.draft.warn(
+                            format!(
+                                "The foreign key ({}) of '{}' spans several columns; kept them as fields",
+                                names.join(", "),
+                                table_name
+                            ),
+                            columns.first().and_then(line_of).or(table_line),
+                        );
+                    }
+                },
+                TableConstraint::Check { expr, .. } => checks.extend(checked_labels(expr)),
+                _ => {}
+            }
+        }
+
+        // Resolve foreign keys to the entities of their tables
+        let mut references: HashMap<String, String> = HashMap::new();
+        for column in &table.columns {
+            let key = column.name.value.to_lowercase();
+            let Some(foreign_table) = foreign.get(&key) else {
+                continue;
+            };
+            match self.entities.get(&unqualified(foreign_table).to_lowercase()) {
+                Some(target) => {
+                    references.insert(key, target.clone());
+                }
+                None => self.draft.warn(
+                    format!(
+                        "Foreign key '{}.{}' references '{}', which is not in the schema; kept as a field",
+                        table_name, column.name.value, foreign_table
+                    ),
+                    line_of(&column.name),
+                ),
+            }
+        }
+
+        if let [first, second] = table.columns.as_slice() {
+            let first_key = first.name.value.to_lowercase();
+            let second_key = second.name.value.to_lowercase();
+            if let (Some(source), Some(target)) =
+                (references.get(&first_key), references.get(&second_key))
+            {
+                let name = to_camel_case(unqualified(foreign[&second_key]));
+                self.draft.warn(
+                    format!(
+                        "Join table '{}' became the morphism '{}: {} -> Set<{}>'",
+                        table_name, name, source, target
+                    ),
+                    table_line,
+                );
+                self.draft.context.morphisms.push(MorphismDecl::new(
+                    name,
+                    TypeExpr::simple(source.clone()),
+                    TypeExpr::generic("Set", TypeExpr::simple(target.clone())),
+                ));
+                return;
+            }
+        }
+
+        let required = |column: &ColumnDef| {
+            primary.contains(&column.name.value.to_lowercase())
+                || column
+                    .options
+                    .iter()
+                    .any(|option| matches!(option.option, ColumnOption::NotNull))
+        };
+        let groups = self.value_groups(table, &primary, &references, &checks);
+
+        let mut entity_decl = EntityDecl::new(&entity);
+        for (i, column_def) in table.columns.iter().enumerate() {
+            let key = column_def.name.value.to_lowercase();
+            let column = Column {
+                table: table_name,
+                entity: &entity,
+                name: &column_def.name.value,
+                line: line_of(&column_def.name).or(table_line),
+            };
+            let optional = !required(column_def);
+
+            if let Some(target) = references.get(&key) {
+                self.draft.context.morphisms.push(MorphismDecl::new(
+                    reference_name(column.name, target),
+                    TypeExpr::simple(&entity),
+                    optional_if(TypeExpr::simple(target.clone()), optional),
+                ));
+                continue;
+            }
+
+            if let Some((prefix, members)) = groups.iter().find(|(_, members)| members.contains(&i))
+            {
+                if members[0] == i {
+                    let field = self.embedded_value(table, &entity, prefix, members, &required);
+                    entity_decl.fields.push(field);
+                }
+                continue;
+            }
+
+            let type_expr = match checks.get(&key) {
+                Some(labels) => TypeExpr::simple(self.draft.declare_enum(
+                    member_type_name(column.entity, column.name),
+                    labels,
+                    column.line,
+                )),
+                None => self.column_type(&column_def.data_type, &column),
+            };
+            entity_decl.fields.push(FieldDecl::new(
+                to_camel_case(column.name),
+                optional_if(type_expr, optional),
+            ));
+        }
+        self.draft.context.entities.push(entity_decl);
+    }
+
+    /// Group the columns of a table that share a prefix, such as
+    /// `price_amount` and `price_currency`, by prefix, in the order of the
+    /// table.
+    fn value_groups(
+        &self,
+        table: &CreateTable,
+        primary: &[String],
+        references: &HashMap<String, String>,
+        checks: &HashMap<String, Vec<String>>,
+    ) -> Vec<(String, Vec<usize>)> {
+        let mut groups: Vec<(String, Vec<usize>)> = Vec::new();
+        for (i, column) in table.columns.iter().enumerate() {
+            let key = column.name.value.to_lowercase();
+            if primary.contains(&key) || references.contains_key(&key) || checks.contains_key(&key)
+            {
+                continue;
+            }
+            let Some((prefix, _)) = key.rsplit_once('_') else {
+                continue;
+            };
+            match groups.iter_mut().find(|(p, _)| p == prefix) {
+                Some((_, members)) => members.push(i),
+                None => groups.push((prefix.to_string(), vec![i])),
+            }
+        }
+        groups.retain(|(prefix, members)| {
+            let name = to_pascal_case(prefix);
+            members.len() > 1
+                && !prefix.is_empty()
+                && !NOT_VALUE_PREFIXES.contains(&prefix.as_str())
+                && !self.entities.values().any(|entity| *entity == name)
+        });
+        groups
+    }
+
+    /// Declare the value object of columns sharing a prefix, returning the
+    /// field holding it.
+    fn embedded_value(
+        &mut self,
+        table: &CreateTable,
+        entity: &str,
+        prefix: &str,
+        members: &[usize],
+        required: &dyn Fn(&ColumnDef) -> bool,
+    ) -> FieldDecl {
+        let table_name = unqualified(&table.name);
+        let columns: Vec<&ColumnDef> = members.iter().map(|&i| &table.columns[i]).collect();
+        // A value that may be missing as a whole is optional, not its parts
+        let optional = !columns.iter().any(|c| required(c));
+
+        let mut value = ValueObjectDecl::new(to_pascal_case(prefix));
+        for column_def in &columns {
+            let column = Column {
+                table: table_name,
+                entity,
+                name: &column_def.name.value,
+                line: line_of(&column_def.name),
+            };
+            let part = column.name[prefix.len() + 1..].to_string();
+            let type_expr = self.column_type(&column_def.data_type, &column);
+            value.fields.push(FieldDecl::new(
+                to_camel_case(&part),
+                optional_if(type_expr, !optional && !required(column_def)),
+            ));
+        }
+        let name = self.draft.declare_value(value, entity);
+
+        let names: Vec<&str> = columns.iter().map(|c| c.name.value.as_str()).collect();
+        self.draft.warn(
+            format!(
+                "Columns {} of '{}' became the value object '{}'",
+                names.join(", "),
+                table_name,
+                name
+            ),
+            columns.first().and_then(|c| line_of(&c.name)),
+        );
+        FieldDecl::new(
+            to_camel_case(prefix),
+            optional_if(TypeExpr::simple(name), optional),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PrettyPrint;
+    use pretty_assertions::assert_eq;
+
+    fn draft(sql: &str) -> (String, Vec<String>) {
+        let imported = import(sql, "Sales").unwrap();
+        let warnings = imported.warnings.iter().map(|w| w.to_string()).collect();
+        (imported.into_file().pretty_print(), warnings)
+    }
+
+    #[test]
+    fn test_tables_become_entities_and_foreign_keys_morphisms() {
+        let (source, warnings) = draft(
+            "CREATE TABLE customers (
+                id UUID PRIMARY KEY,
+                email VARCHAR(255) NOT NULL,
+                nickname TEXT
+            );
+            CREATE TABLE orders (
+                id BIGINT PRIMARY KEY,
+                customer_id UUID NOT NULL REFERENCES customers(id),
+                referrer_id UUID,
+                placed_at TIMESTAMP NOT NULL,
+                FOREIGN KEY (referrer_id) REFERENCES customers (id)
+            );
+            CREATE INDEX orders_customer ON orders (customer_id);",
+        );
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(
+            source,
+            "context Sales {
+  entity Customer {
+    id: UUID
+    email: String
+    nickname: String?
+  }
+  entity Order {
+    id: Int
+    placedAt: DateTime
+  }
+  morphisms {
+    customer: Order -> Customer
+    referrer: Order -> Customer?
+  }
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_enums_from_types_and_checks() {
+        let (source, _) = draft(
+            "CREATE TYPE order_status AS ENUM ('pending', 'in_progress', 'shipped');
+            CREATE TABLE orders (
+                id SERIAL PRIMARY KEY,
+                status order_status NOT NULL,
+                channel TEXT NOT NULL CHECK (channel IN ('web', 'store')),
+                priority ENUM('low', 'high') NOT NULL
+            );",
+        );
+        assert!(source.contains("enum OrderStatus = Pending | InProgress | Shipped"));
+        assert!(source.contains("enum OrderChannel = Web | Store"));
+        assert!(source.contains("enum OrderPriority = Low | High"));
+        assert!(
+            source.contains("    id: Int\n    status: OrderStatus\n    channel: OrderChannel\n")
+        );
+    }
+
+    #[test]
+    fn test_value_objects_are_guessed_with_warnings() {
+        let (source, warnings) = draft(
+            "CREATE TABLE orders (
+                id UUID PRIMARY KEY,
+                total_amount NUMERIC(10, 2) NOT NULL,
+                total_currency CHAR(3) NOT NULL,
+                created_at TIMESTAMP NOT NULL,
+                created_by TEXT NOT NULL,
+                metadata JSONB
+            );",
+        );
+        assert!(source.contains("    total: Total\n"));
+        assert!(source.contains("  value Total {\n    amount: Decimal\n    currency: String\n  }"));
+        assert!(source.contains("    createdAt: DateTime\n    createdBy: String\n"));
+        assert!(source.contains("    metadata: Metadata?\n"));
+        assert_eq!(
+            warnings,
+            [
+                "line 3: Columns total_amount, total_currency of 'orders' became the value object 'Total'",
+                "line 7: JSON column 'orders.metadata' became the value object 'Metadata'; declare its fields",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_join_tables_become_morphisms() {
+        let (source, warnings) = draft(
+            "CREATE TABLE posts (id INT PRIMARY KEY);
+            CREATE TABLE tags (id INT PRIMARY KEY);
+            CREATE TABLE post_tags (
+                post_id INT REFERENCES posts(id),
+                tag_id INT REFERENCES tags(id),
+                PRIMARY KEY (post_id, tag_id)
+            );",
+        );
+        assert!(source.contains("    tags: Post -> Set<Tag>\n"));
+        assert!(!source.contains("entity PostTag"));
+        assert_eq!(
+            warnings,
+            ["line 3: Join table 'post_tags' became the morphism 'tags: Post -> Set<Tag>'"]
+        );
+    }
+
+    #[test]
+    fn test_what_cannot_be_imported_is_warned_about() {
+        let imported = import(
+            "CREATE TABLE orders (
+                id INT PRIMARY KEY,
+                warehouse_id INT REFERENCES warehouses(id),
+                shipped_after INTERVAL
+            );
+            ALTER TABLE orders FROBNICATE;",
+            "Sales",
+        )
+        .unwrap();
+        let messages: Vec<&str> = imported
+            .warnings
+            .iter()
+            .map(|w| w.message.as_str())
+            .collect();
+        assert_eq!(messages.len(), 3, "{:?}", messages);
+        assert!(messages[0].starts_with("Skipped a statement that does not parse"));
+        assert!(messages[1].contains("'warehouses', which is not in the schema"));
+        assert!(messages[2].contains("INTERVAL, which has no counterpart"));
+        let fields: Vec<&str> = imported.context.entities[0]
+            .fields
+            .iter()
+            .map(|f| f.name.as_str())
+            .collect();
+        assert_eq!(fields, ["id", "warehouseId", "shippedAfter"]);
+    }
+
+    #[test]
+    fn test_schema_without_tables_is_an_error() {
+        assert!(import("CREATE INDEX i ON t (c);", "Sales").is_err());
+        assert!(import("CREATE TABLE 'oops", "Sales").is_err());
+    }
+}
@@ -4,8 +4,13 @@
 //!
 //! This crate provides:
 //! - A pest-based parser that produces an AST
-//! - AST → Semantic Model transformation
+//! - AST → Semantic Model transformation, and back
 //! - Pretty-printing for debugging
+//! - Completion and hover for editors
+//! - Drafting contexts from JSON Schema and OpenAPI documents, from SQL
+//!   schemas with the `sql` feature, and from Rust source with the `rust`
+//!   feature
+//! - Three-way merges of models, for a git merge driver
 //!
 //! ## Example
 //!
@@ -32,6 +37,16 @@
 //!   }
 //!
 //!   enum OrderStatus = Pending | Confirmed | Shipped | Cancelled
+//!
+//!   lifecycle Order: OrderStatus {
+//!     Pending -> Confirmed -> Shipped;
+//!     Pending -> Cancelled
+//!   }
+//!
+//!   process FulfillmentSaga {
+//!     on OrderPlaced -> ReserveStock;
+//!     on StockReserved -> Ship
+//!   }
 //! }
 //! ```
 //!
@@ -59,19 +74,35 @@
 //! }
 //! ```
 
+pub mod add;
+pub mod assist;
 pub mod ast;
 pub mod error;
+pub mod fix;
 pub mod grammar;
+pub mod import;
+pub mod merge;
+pub mod migrate;
 pub mod pretty;
+pub mod template;
 pub mod transform;
+pub mod trivia;
+pub mod unparse;
 
+pub use add::Declaration;
 pub use ast::*;
 pub use error::ParseError;
+pub use fix::attach_fixes;
 pub use grammar::Rule;
+pub use migrate::{migrate_source, Migrated, SourceChange};
 pub use pretty::PrettyPrint;
+pub use template::expand_templates;
 pub use transform::{transform, TransformResult, TransformWarning};
+pub use trivia::attach_trivia;
+pub use unparse::unparse;
 
 use grammar::SketchDDDParser;
+use pest::error::LineColLocation;
 use pest::Parser;
 
 /// Create a Span from a pest Pair.
@@ -84,7 +115,10 @@ fn span_from_pest<R: pest::RuleType>(pair: &pest::iterators::Pair<'_, R>) -> Spa
 /// Parse a SketchDDD source file into a File AST.
 pub fn parse_file(source: &str) -> Result<File, ParseError> {
     let pairs = SketchDDDParser::parse(Rule::file, source).map_err(|e| {
-        ParseError::new(format!("Parse error: {}", e))
+        let (line, column) = match e.line_col {
+            LineColLocation::Pos(start) | LineColLocation::Span(start, _) => start,
+        };
+        ParseError::new(format!("Parse error: {}", e)).with_location(line as u32, column as u32)
     })?;
 
     let mut file = File::default();
@@ -100,6 +134,15 @@ pub fn parse_file(source: &str) -> Result<File, ParseError> {
                     Rule::map_decl => {
                         file.context_maps.push(parse_map_decl(inner)?);
                     }
+                    Rule::shared_kernel_decl => {
+                        file.shared_kernels.push(parse_shared_kernel_decl(inner)?);
+                    }
+                    Rule::template_decl => {
+                        file.templates.push(parse_template_decl(inner)?);
+                    }
+                    Rule::domain_decl => {
+                        file.domains.push(parse_domain_decl(inner)?);
+                    }
                     Rule::EOI => {}
                     _ => {}
                 }
@@ -107,9 +150,18 @@ pub fn parse_file(source: &str) -> Result<File, ParseError> {
         }
     }
 
+    attach_trivia(&mut file, source);
+
     Ok(file)
 }
 
+/// Parse and transform a SketchDDD source file into a [`Workspace`].
+///
+/// Transform warnings are dropped; use [`transform`] to keep them.
+pub fn parse_workspace(source: &str) -> Result<sketchddd_core::Workspace, ParseError> {
+    Ok(transform(&parse_file(source)?)?.into_workspace())
+}
+
 /// Parse a SketchDDD source file into a list of context declarations.
 /// This is a convenience function for backward compatibility.
 pub fn parse(source: &str) -> Result<Vec<ContextDecl>, ParseError> {
@@ -126,14 +178,27 @@ pub fn parse_context(source: &str) -> Result<ContextDecl, ParseError> {
         .ok_or_else(|| ParseError::new("No context found in source"))
 }
 
+/// Parse a type expression such as `List<LineItem>` or `Customer?`.
+pub fn parse_type(source: &str) -> Result<TypeExpr, ParseError> {
+    let source = source.trim();
+    let pair = SketchDDDParser::parse(Rule::type_expr, source)
+        .map_err(|e| ParseError::new(format!("Invalid type '{}': {}", source, e)))?
+        .next()
+        .filter(|pair| pair.as_str().trim_end() == source)
+        .ok_or_else(|| ParseError::new(format!("Invalid type '{}'", source)))?;
+    parse_type_expr(pair)
+}
+
 // =============================================================
 // Context Parsing
 // =============================================================
 
 fn parse_context_decl(pair: pest::iterators::Pair<'_, Rule>) -> Result<ContextDecl, ParseError> {
     let span = span_from_pest(&pair);
-    let mut context = ContextDecl::default();
-    context.span = span;
+    let mut context = ContextDecl {
+        span,
+        ..Default::default()
+    };
 
     for inner in pair.into_inner() {
         match inner.as_rule() {
@@ -143,6 +208,9 @@ fn parse_context_decl(pair: pest::iterators::Pair<'_, Rule>) -> Result<ContextDe
             Rule::context_body => {
                 parse_context_body(inner, &mut context)?;
             }
+            Rule::decorator => {
+                context.decorators.push(parse_decorator(inner)?);
+            }
             _ => {}
         }
     }
@@ -155,8 +223,33 @@ fn parse_context_body(
     context: &mut ContextDecl,
 ) -> Result<(), ParseError> {
     for inner in pair.into_inner() {
+        let kind = match inner.as_rule() {
+            Rule::objects_block | Rule::kernel_objects_block => BodyItemKind::Objects,
+            Rule::entity_block => BodyItemKind::Entity,
+            Rule::morphisms_block => BodyItemKind::Morphisms,
+            Rule::derive_block => BodyItemKind::Derivation,
+            Rule::aggregate_block => BodyItemKind::Aggregate,
+            Rule::value_block => BodyItemKind::ValueObject,
+            Rule::enum_block => BodyItemKind::Enum,
+            Rule::equation_block => BodyItemKind::Equation,
+            Rule::process_block => BodyItemKind::Process,
+            Rule::actor_block => BodyItemKind::Actor,
+            Rule::policy_block => BodyItemKind::Policy,
+            Rule::hotspot_decl => BodyItemKind::Hotspot,
+            Rule::lifecycle_block => BodyItemKind::Lifecycle,
+            Rule::use_decl => BodyItemKind::Use,
+            _ => continue,
+        };
+        // Record where the declaration appears, for printing it back there
+        context.order.push(BodyItem {
+            kind,
+            index: context.count(kind),
+            trivia: Trivia::default(),
+            span: span_from_pest(&inner),
+        });
+
         match inner.as_rule() {
-            Rule::objects_block => {
+            Rule::objects_block | Rule::kernel_objects_block => {
                 parse_objects_block(inner, context)?;
             }
             Rule::entity_block => {
@@ -165,6 +258,9 @@ fn parse_context_body(
             Rule::morphisms_block => {
                 parse_morphisms_block(inner, context)?;
             }
+            Rule::derive_block => {
+                context.derivations.push(parse_derive_block(inner)?);
+            }
             Rule::aggregate_block => {
                 context.aggregates.push(parse_aggregate_block(inner)?);
             }
@@ -177,12 +273,174 @@ fn parse_context_body(
             Rule::equation_block => {
                 context.equations.push(parse_equation_block(inner)?);
             }
+            Rule::process_block => {
+                context.processes.push(parse_process_block(inner)?);
+            }
+            Rule::actor_block => {
+                context.actors.push(parse_actor_block(inner));
+            }
+            Rule::policy_block => {
+                context.policies.push(parse_policy_block(inner)?);
+            }
+            Rule::hotspot_decl => {
+                context.hotspots.push(parse_hotspot_decl(inner));
+            }
+            Rule::lifecycle_block => {
+                context.lifecycles.push(parse_lifecycle_block(inner)?);
+            }
+            Rule::use_decl => {
+                context.uses.push(parse_use_decl(inner)?);
+            }
             _ => {}
         }
     }
     Ok(())
 }
 
+// =============================================================
+// Template Parsing
+// =============================================================
+
+fn parse_template_decl(pair: pest::iterators::Pair<'_, Rule>) -> Result<TemplateDecl, ParseError> {
+    let span = span_from_pest(&pair);
+    let mut template = TemplateDecl {
+        span,
+        ..Default::default()
+    };
+
+    for inner in pair.into_inner() {
+        match inner.as_rule() {
+            Rule::identifier => {
+                template.name = inner.as_str().to_string();
+            }
+            Rule::identifier_list => {
+                template.params = parse_identifier_list(inner);
+            }
+            Rule::context_body => {
+                parse_context_body(inner, &mut template.body)?;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(template)
+}
+
+fn parse_use_decl(pair: pest::iterators::Pair<'_, Rule>) -> Result<UseDecl, ParseError> {
+    let span = span_from_pest(&pair);
+    let mut template = String::new();
+    let mut args = Vec::new();
+
+    for inner in pair.into_inner() {
+        match inner.as_rule() {
+            Rule::identifier => template = inner.as_str().to_string(),
+            Rule::identifier_list => args = parse_identifier_list(inner),
+            _ => {}
+        }
+    }
+
+    Ok(UseDecl {
+        template,
+        args,
+        trivia: Trivia::default(),
+        span,
+    })
+}
+
+fn parse_identifier_list(pair: pest::iterators::Pair<'_, Rule>) -> Vec<String> {
+    pair.into_inner()
+        .filter(|ident| ident.as_rule() == Rule::identifier)
+        .map(|ident| ident.as_str().to_string())
+        .collect()
+}
+
+// =============================================================
+// Shared Kernel Parsing
+// =============================================================
+
+fn parse_shared_kernel_decl(
+    pair: pest::iterators::Pair<'_, Rule>,
+) -> Result<SharedKernelDecl, ParseError> {
+    let span = span_from_pest(&pair);
+    let mut kernel = SharedKernelDecl {
+        span,
+        ..Default::default()
+    };
+
+    for inner in pair.into_inner() {
+        match inner.as_rule() {
+            Rule::identifier => {
+                kernel.name = inner.as_str().to_string();
+            }
+            Rule::shared_kernel_body => {
+                // The kernel body is a subset of a context body
+                let mut body = ContextDecl::default();
+                parse_context_body(inner, &mut body)?;
+                kernel.objects = body.objects;
+                kernel.entities = body.entities;
+                kernel.value_objects = body.value_objects;
+                kernel.enums = body.enums;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(kernel)
+}
+
+// =============================================================
+// Domain Parsing
+// =============================================================
+
+fn parse_domain_decl(pair: pest::iterators::Pair<'_, Rule>) -> Result<DomainDecl, ParseError> {
+    let span = span_from_pest(&pair);
+    let mut domain = DomainDecl {
+        span,
+        ..Default::default()
+    };
+
+    for inner in pair.into_inner() {
+        match inner.as_rule() {
+            Rule::identifier => {
+                domain.name = inner.as_str().to_string();
+            }
+            Rule::subdomain_decl => {
+                domain.subdomains.push(parse_subdomain_decl(inner)?);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(domain)
+}
+
+fn parse_subdomain_decl(pair: pest::iterators::Pair<'_, Rule>) -> Result<SubdomainDecl, ParseError> {
+    let span = span_from_pest(&pair);
+    let mut subdomain = SubdomainDecl {
+        span,
+        ..Default::default()
+    };
+
+    for inner in pair.into_inner() {
+        match inner.as_rule() {
+            Rule::decorator => {
+                subdomain.decorators.push(parse_decorator(inner)?);
+            }
+            Rule::identifier => {
+                subdomain.name = inner.as_str().to_string();
+            }
+            Rule::subdomain_contexts => {
+                for list in inner.into_inner() {
+                    subdomain.contexts.extend(parse_identifier_list(list));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(subdomain)
+}
+
 // =============================================================
 // Objects Parsing
 // =============================================================
@@ -192,15 +450,27 @@ fn parse_objects_block(
     context: &mut ContextDecl,
 ) -> Result<(), ParseError> {
     for inner in pair.into_inner() {
-        if inner.as_rule() == Rule::identifier_list {
-            for ident in inner.into_inner() {
-                if ident.as_rule() == Rule::identifier {
-                    context.objects.push(ObjectDecl {
-                        name: ident.as_str().to_string(),
-                        span: span_from_pest(&ident),
-                    });
+        match inner.as_rule() {
+            Rule::identifier_list => {
+                for ident in inner.into_inner() {
+                    if ident.as_rule() == Rule::identifier {
+                        context.objects.push(ObjectDecl {
+                            name: ident.as_str().to_string(),
+                            trivia: Trivia::default(),
+                            span: span_from_pest(&ident),
+                        });
+                    }
                 }
             }
+            // Kernel objects, possibly qualified by their context
+            Rule::qualified_name => {
+                context.objects.push(ObjectDecl {
+                    name: inner.as_str().to_string(),
+                    trivia: Trivia::default(),
+                    span: span_from_pest(&inner),
+                });
+            }
+            _ => {}
         }
     }
     Ok(())
@@ -215,6 +485,8 @@ fn parse_entity_block(pair: pest::iterators::Pair<'_, Rule>) -> Result<EntityDec
     let mut entity = EntityDecl {
         name: String::new(),
         fields: Vec::new(),
+        decorators: Vec::new(),
+        trivia: Trivia::default(),
         span,
     };
 
@@ -230,6 +502,9 @@ fn parse_entity_block(pair: pest::iterators::Pair<'_, Rule>) -> Result<EntityDec
                     }
                 }
             }
+            Rule::decorator => {
+                entity.decorators.push(parse_decorator(inner)?);
+            }
             _ => {}
         }
     }
@@ -259,6 +534,8 @@ fn parse_morphism_decl(pair: pest::iterators::Pair<'_, Rule>) -> Result<Morphism
     let mut source = TypeExpr::Simple(String::new());
     let mut target = TypeExpr::Simple(String::new());
     let mut annotations = Vec::new();
+    let mut constraints = Vec::new();
+    let mut decorators = Vec::new();
     let mut type_count = 0;
 
     for inner in pair.into_inner() {
@@ -278,6 +555,22 @@ fn parse_morphism_decl(pair: pest::iterators::Pair<'_, Rule>) -> Result<Morphism
             Rule::morphism_annotations => {
                 annotations = parse_annotations(inner)?;
             }
+            Rule::field_constraints => {
+                constraints = parse_field_constraints(inner)?;
+            }
+            Rule::decorator => {
+                decorators.push(parse_decorator(inner)?);
+            }
+            Rule::legacy_cardinality => {
+                let (line, column) = inner.line_col();
+                return Err(ParseError::new(format!(
+                    "Cardinality '{}' after the target of morphism '{}' is no longer supported; \
+                     run `sketchddd migrate <file>` to rewrite it",
+                    inner.as_str(),
+                    name
+                ))
+                .with_location(line as u32, column as u32));
+            }
             _ => {}
         }
     }
@@ -287,6 +580,41 @@ fn parse_morphism_decl(pair: pest::iterators::Pair<'_, Rule>) -> Result<Morphism
         source,
         target,
         annotations,
+        constraints,
+        decorators,
+        trivia: Trivia::default(),
+        span,
+    })
+}
+
+fn parse_derive_block(pair: pest::iterators::Pair<'_, Rule>) -> Result<DerivationDecl, ParseError> {
+    let span = span_from_pest(&pair);
+    let mut name = String::new();
+    let mut types = Vec::new();
+    let mut expression = Expr::Number(0.0);
+
+    for inner in pair.into_inner() {
+        match inner.as_rule() {
+            Rule::identifier => {
+                name = inner.as_str().to_string();
+            }
+            Rule::type_expr => {
+                types.push(parse_type_expr(inner)?);
+            }
+            Rule::expression => {
+                expression = parse_expression(inner)?;
+            }
+            _ => {}
+        }
+    }
+
+    let mut types = types.into_iter();
+    Ok(DerivationDecl {
+        name,
+        source: types.next().unwrap_or_else(|| TypeExpr::Simple(String::new())),
+        target: types.next().unwrap_or_else(|| TypeExpr::Simple(String::new())),
+        expression,
+        trivia: Trivia::default(),
         span,
     })
 }
@@ -334,10 +662,9 @@ fn parse_annotations(
 // =============================================================
 
 fn parse_type_expr(pair: pest::iterators::Pair<'_, Rule>) -> Result<TypeExpr, ParseError> {
-    let inner = pair.into_inner();
-
-    // Check for optional marker at the end
-    let type_parts: Vec<_> = inner.collect();
+    // The "?" marker is a literal in the grammar, so it has no pair of its own
+    let is_optional = pair.as_str().trim_end().ends_with('?');
+    let type_parts: Vec<_> = pair.into_inner().collect();
 
     if type_parts.is_empty() {
         return Err(ParseError::new("Expected type expression"));
@@ -349,9 +676,7 @@ fn parse_type_expr(pair: pest::iterators::Pair<'_, Rule>) -> Result<TypeExpr, Pa
         _ => TypeExpr::Simple(type_parts[0].as_str().to_string()),
     };
 
-    // The grammar now includes "?" inline, so check the original string
-    let pair_str = type_parts.last().map(|p| p.as_str()).unwrap_or("");
-    if pair_str == "?" {
+    if is_optional {
         Ok(TypeExpr::Optional(Box::new(base_type)))
     } else {
         Ok(base_type)
@@ -400,11 +725,16 @@ fn parse_aggregate_block(
         root: None,
         contains: Vec::new(),
         invariants: Vec::new(),
+        decorators: Vec::new(),
+        trivia: Trivia::default(),
         span,
     };
 
     for inner in pair.into_inner() {
         match inner.as_rule() {
+            Rule::decorator => {
+                aggregate.decorators.push(parse_decorator(inner)?);
+            }
             Rule::identifier => {
                 aggregate.name = inner.as_str().to_string();
             }
@@ -455,13 +785,24 @@ fn parse_invariant_clause(
     pair: pest::iterators::Pair<'_, Rule>,
 ) -> Result<InvariantDecl, ParseError> {
     let span = span_from_pest(&pair);
+    let mut mode = None;
 
     for inner in pair.into_inner() {
-        if inner.as_rule() == Rule::expression {
-            return Ok(InvariantDecl {
-                expression: parse_expression(inner)?,
-                span,
-            });
+        match inner.as_rule() {
+            Rule::invariant_mode => {
+                mode = Some(match inner.as_str() {
+                    "eventual" => InvariantMode::Eventual,
+                    _ => InvariantMode::Strict,
+                });
+            }
+            Rule::expression => {
+                return Ok(InvariantDecl {
+                    expression: parse_expression(inner)?,
+                    mode,
+                    span,
+                });
+            }
+            _ => {}
         }
     }
 
@@ -477,6 +818,8 @@ fn parse_value_block(pair: pest::iterators::Pair<'_, Rule>) -> Result<ValueObjec
     let mut value_object = ValueObjectDecl {
         name: String::new(),
         fields: Vec::new(),
+        decorators: Vec::new(),
+        trivia: Trivia::default(),
         span,
     };
 
@@ -488,6 +831,9 @@ fn parse_value_block(pair: pest::iterators::Pair<'_, Rule>) -> Result<ValueObjec
             Rule::field_decl => {
                 value_object.fields.push(parse_field_decl(inner)?);
             }
+            Rule::decorator => {
+                value_object.decorators.push(parse_decorator(inner)?);
+            }
             _ => {}
         }
     }
@@ -499,6 +845,8 @@ fn parse_field_decl(pair: pest::iterators::Pair<'_, Rule>) -> Result<FieldDecl,
     let span = span_from_pest(&pair);
     let mut name = String::new();
     let mut type_expr = TypeExpr::Simple(String::new());
+    let mut constraints = Vec::new();
+    let mut decorators = Vec::new();
 
     for inner in pair.into_inner() {
         match inner.as_rule() {
@@ -508,6 +856,12 @@ fn parse_field_decl(pair: pest::iterators::Pair<'_, Rule>) -> Result<FieldDecl,
             Rule::type_expr | Rule::simple_type | Rule::generic_type => {
                 type_expr = parse_type_expr(inner)?;
             }
+            Rule::field_constraints => {
+                constraints = parse_field_constraints(inner)?;
+            }
+            Rule::decorator => {
+                decorators.push(parse_decorator(inner)?);
+            }
             _ => {}
         }
     }
@@ -515,6 +869,113 @@ fn parse_field_decl(pair: pest::iterators::Pair<'_, Rule>) -> Result<FieldDecl,
     Ok(FieldDecl {
         name,
         type_expr,
+        constraints,
+        decorators,
+        trivia: Trivia::default(),
+        span,
+    })
+}
+
+fn parse_field_constraints(
+    pair: pest::iterators::Pair<'_, Rule>,
+) -> Result<Vec<ConstraintDecl>, ParseError> {
+    let mut constraints = Vec::new();
+
+    for inner in pair.into_inner() {
+        if inner.as_rule() != Rule::field_constraint {
+            continue;
+        }
+
+        let span = span_from_pest(&inner);
+        let mut name = String::new();
+        let mut value = ConstraintValue::Number(0.0);
+
+        for part in inner.into_inner() {
+            match part.as_rule() {
+                Rule::identifier => {
+                    name = part.as_str().to_string();
+                }
+                Rule::string_literal | Rule::number => {
+                    value = parse_literal_value(part, &span)?;
+                }
+                _ => {}
+            }
+        }
+
+        constraints.push(ConstraintDecl { name, value, span });
+    }
+
+    Ok(constraints)
+}
+
+/// The text of a string literal, without its quotes and with `\"` and
+/// `\\` unescaped. Other backslashes, as in patterns, are kept.
+fn string_value(literal: &str) -> String {
+    let mut text = String::new();
+    let mut chars = literal[1..literal.len() - 1].chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some(&next @ ('"' | '\\'))) => {
+                text.push(next);
+                chars.next();
+            }
+            _ => text.push(c),
+        }
+    }
+    text
+}
+
+fn parse_literal_value(
+    pair: pest::iterators::Pair<'_, Rule>,
+    span: &Span,
+) -> Result<ConstraintValue, ParseError> {
+    if pair.as_rule() == Rule::string_literal {
+        // Remove quotes from string
+        let s = pair.as_str();
+        return Ok(ConstraintValue::Text(string_value(s)));
+    }
+
+    let n = pair.as_str().parse().map_err(|_| {
+        ParseError::new(format!("Invalid number '{}'", pair.as_str()))
+            .with_location(span.line, span.column)
+    })?;
+    Ok(ConstraintValue::Number(n))
+}
+
+// =============================================================
+// Decorator Parsing
+// =============================================================
+
+fn parse_decorator(pair: pest::iterators::Pair<'_, Rule>) -> Result<DecoratorDecl, ParseError> {
+    let span = span_from_pest(&pair);
+    let mut name = String::new();
+    let mut key = None;
+    let mut argument = None;
+    let mut names = Vec::new();
+
+    for inner in pair.into_inner() {
+        match inner.as_rule() {
+            Rule::identifier => {
+                name = inner.as_str().to_string();
+            }
+            Rule::decorator_key => {
+                key = Some(inner.as_str().to_string());
+            }
+            Rule::string_literal | Rule::number => {
+                argument = Some(parse_literal_value(inner, &span)?);
+            }
+            Rule::decorator_names => {
+                names = inner.into_inner().map(|n| n.as_str().to_string()).collect();
+            }
+            _ => {}
+        }
+    }
+
+    Ok(DecoratorDecl {
+        name,
+        key,
+        argument,
+        names,
         span,
     })
 }
@@ -528,6 +989,8 @@ fn parse_enum_block(pair: pest::iterators::Pair<'_, Rule>) -> Result<EnumDecl, P
     let mut enum_decl = EnumDecl {
         name: String::new(),
         variants: Vec::new(),
+        decorators: Vec::new(),
+        trivia: Trivia::default(),
         span,
     };
 
@@ -543,6 +1006,9 @@ fn parse_enum_block(pair: pest::iterators::Pair<'_, Rule>) -> Result<EnumDecl, P
                     }
                 }
             }
+            Rule::decorator => {
+                enum_decl.decorators.push(parse_decorator(inner)?);
+            }
             _ => {}
         }
     }
@@ -592,6 +1058,7 @@ fn parse_variant(pair: pest::iterators::Pair<'_, Rule>) -> Result<VariantDecl, P
 fn parse_equation_block(pair: pest::iterators::Pair<'_, Rule>) -> Result<EquationDecl, ParseError> {
     let span = span_from_pest(&pair);
     let mut name = None;
+    let mut mode = None;
     let mut lhs = Path::new(Vec::new());
     let mut rhs = Path::new(Vec::new());
     let mut path_count = 0;
@@ -601,6 +1068,12 @@ fn parse_equation_block(pair: pest::iterators::Pair<'_, Rule>) -> Result<Equatio
             Rule::identifier => {
                 name = Some(inner.as_str().to_string());
             }
+            Rule::invariant_mode => {
+                mode = Some(match inner.as_str() {
+                    "eventual" => InvariantMode::Eventual,
+                    _ => InvariantMode::Strict,
+                });
+            }
             Rule::path => {
                 let path = parse_path(inner)?;
                 if path_count == 0 {
@@ -616,8 +1089,10 @@ fn parse_equation_block(pair: pest::iterators::Pair<'_, Rule>) -> Result<Equatio
 
     Ok(EquationDecl {
         name,
+        mode,
         lhs,
         rhs,
+        trivia: Trivia::default(),
         span,
     })
 }
@@ -635,79 +1110,262 @@ fn parse_path(pair: pest::iterators::Pair<'_, Rule>) -> Result<Path, ParseError>
 }
 
 // =============================================================
-// Context Map Parsing
+// Process Parsing
 // =============================================================
 
-fn parse_map_decl(pair: pest::iterators::Pair<'_, Rule>) -> Result<ContextMapDecl, ParseError> {
+fn parse_process_block(pair: pest::iterators::Pair<'_, Rule>) -> Result<ProcessDecl, ParseError> {
     let span = span_from_pest(&pair);
-    let mut name = String::new();
-    let mut source_context = String::new();
-    let mut target_context = String::new();
-    let mut pattern = None;
-    let mut object_mappings = Vec::new();
-    let mut morphism_mappings = Vec::new();
+    let block = pair.as_span();
+    let mut process = ProcessDecl {
+        name: String::new(),
+        steps: Vec::new(),
+        trivia: Trivia::default(),
+        span,
+    };
+
+    for inner in pair.into_inner() {
+        match inner.as_rule() {
+            Rule::identifier => {
+                process.name = inner.as_str().to_string();
+            }
+            Rule::process_step => {
+                let terminated = followed_by_semicolon(&block, &inner);
+                process.steps.push(ProcessStepDecl {
+                    terminated,
+                    ..parse_process_step(inner)?
+                });
+            }
+            _ => {}
+        }
+    }
+
+    Ok(process)
+}
+
+fn parse_process_step(
+    pair: pest::iterators::Pair<'_, Rule>,
+) -> Result<ProcessStepDecl, ParseError> {
+    let span = span_from_pest(&pair);
+    let mut identifiers = pair
+        .into_inner()
+        .filter(|p| p.as_rule() == Rule::identifier)
+        .map(|p| p.as_str().to_string());
+
+    let event = identifiers
+        .next()
+        .ok_or_else(|| ParseError::new("Process step is missing its event"))?;
+    let command = identifiers
+        .next()
+        .ok_or_else(|| ParseError::new("Process step is missing its command"))?;
+
+    Ok(ProcessStepDecl {
+        event,
+        command,
+        terminated: false,
+        span,
+    })
+}
+
+/// Check if a `;` follows an element of a block, such as a process step.
+fn followed_by_semicolon(
+    block: &pest::Span<'_>,
+    element: &pest::iterators::Pair<'_, Rule>,
+) -> bool {
+    let rest = &block.as_str()[element.as_span().end() - block.start()..];
+    rest.trim_start().starts_with(';')
+}
+
+// =============================================================
+// Event Storming Parsing
+// =============================================================
+
+fn parse_actor_block(pair: pest::iterators::Pair<'_, Rule>) -> ActorDecl {
+    let span = span_from_pest(&pair);
+    let mut actor = ActorDecl {
+        name: String::new(),
+        commands: Vec::new(),
+        trivia: Trivia::default(),
+        span,
+    };
+
+    for inner in pair.into_inner() {
+        match inner.as_rule() {
+            Rule::identifier => actor.name = inner.as_str().to_string(),
+            Rule::identifier_list => actor.commands = parse_identifier_list(inner),
+            _ => {}
+        }
+    }
+
+    actor
+}
+
+fn parse_policy_block(pair: pest::iterators::Pair<'_, Rule>) -> Result<PolicyDecl, ParseError> {
+    let span = span_from_pest(&pair);
+    let mut identifiers = Vec::new();
+    let mut commands = Vec::new();
+
+    for inner in pair.into_inner() {
+        match inner.as_rule() {
+            Rule::identifier => identifiers.push(inner.as_str().to_string()),
+            Rule::identifier_list => commands = parse_identifier_list(inner),
+            _ => {}
+        }
+    }
+
+    let mut identifiers = identifiers.into_iter();
+    let name = identifiers
+        .next()
+        .ok_or_else(|| ParseError::new("Policy is missing its name"))?;
+    let event = identifiers
+        .next()
+        .ok_or_else(|| ParseError::new("Policy is missing its event"))?;
+
+    Ok(PolicyDecl {
+        name,
+        event,
+        commands,
+        trivia: Trivia::default(),
+        span,
+    })
+}
+
+fn parse_hotspot_decl(pair: pest::iterators::Pair<'_, Rule>) -> HotspotDecl {
+    let span = span_from_pest(&pair);
+    let mut hotspot = HotspotDecl {
+        target: None,
+        note: String::new(),
+        trivia: Trivia::default(),
+        span,
+    };
+
+    for inner in pair.into_inner() {
+        match inner.as_rule() {
+            Rule::identifier => hotspot.target = Some(inner.as_str().to_string()),
+            Rule::string_literal => {
+                let s = inner.as_str();
+                hotspot.note = string_value(s);
+            }
+            _ => {}
+        }
+    }
+
+    hotspot
+}
+
+// =============================================================
+// Lifecycle Parsing
+// =============================================================
+
+fn parse_lifecycle_block(
+    pair: pest::iterators::Pair<'_, Rule>,
+) -> Result<LifecycleDecl, ParseError> {
+    let span = span_from_pest(&pair);
+    let block = pair.as_span();
+    let mut lifecycle = LifecycleDecl {
+        name: String::new(),
+        enum_name: None,
+        chains: Vec::new(),
+        defaults: Vec::new(),
+        trivia: Trivia::default(),
+        span,
+    };
     let mut ident_count = 0;
 
     for inner in pair.into_inner() {
         match inner.as_rule() {
             Rule::identifier => {
                 match ident_count {
-                    0 => name = inner.as_str().to_string(),
-                    1 => source_context = inner.as_str().to_string(),
-                    2 => target_context = inner.as_str().to_string(),
+                    0 => lifecycle.name = inner.as_str().to_string(),
+                    1 => lifecycle.enum_name = Some(inner.as_str().to_string()),
                     _ => {}
                 }
                 ident_count += 1;
             }
-            Rule::map_body => {
-                parse_map_body(
-                    inner,
-                    &mut pattern,
-                    &mut object_mappings,
-                    &mut morphism_mappings,
-                )?;
+            Rule::transition_chain => {
+                let span = span_from_pest(&inner);
+                let terminated = followed_by_semicolon(&block, &inner);
+                let states = inner
+                    .into_inner()
+                    .filter(|p| p.as_rule() == Rule::identifier)
+                    .map(|p| p.as_str().to_string())
+                    .collect();
+                lifecycle.chains.push(TransitionChainDecl {
+                    states,
+                    terminated,
+                    span,
+                });
+            }
+            Rule::lifecycle_default => {
+                lifecycle.defaults.extend(
+                    inner
+                        .into_inner()
+                        .flat_map(|list| list.into_inner())
+                        .map(|p| p.as_str().to_string()),
+                );
             }
             _ => {}
         }
     }
 
-    Ok(ContextMapDecl {
-        name,
-        source_context,
-        target_context,
-        pattern,
-        object_mappings,
-        morphism_mappings,
-        span,
-    })
+    Ok(lifecycle)
+}
+
+// =============================================================
+// Context Map Parsing
+// =============================================================
+
+fn parse_map_decl(pair: pest::iterators::Pair<'_, Rule>) -> Result<ContextMapDecl, ParseError> {
+    let mut map = ContextMapDecl::new("", "", "");
+    map.span = span_from_pest(&pair);
+    let mut ident_count = 0;
+
+    for inner in pair.into_inner() {
+        match inner.as_rule() {
+            Rule::identifier => {
+                match ident_count {
+                    0 => map.name = inner.as_str().to_string(),
+                    1 => map.source_context = inner.as_str().to_string(),
+                    2 => map.target_context = inner.as_str().to_string(),
+                    _ => {}
+                }
+                ident_count += 1;
+            }
+            Rule::map_body => parse_map_body(inner, &mut map)?,
+            _ => {}
+        }
+    }
+
+    Ok(map)
 }
 
 fn parse_map_body(
     pair: pest::iterators::Pair<'_, Rule>,
-    pattern: &mut Option<String>,
-    object_mappings: &mut Vec<ObjectMappingDecl>,
-    morphism_mappings: &mut Vec<MorphismMappingDecl>,
+    map: &mut ContextMapDecl,
 ) -> Result<(), ParseError> {
     for inner in pair.into_inner() {
+        let span = span_from_pest(&inner);
         match inner.as_rule() {
             Rule::pattern_clause => {
+                map.pattern_clause.span.get_or_insert(span);
                 for pat in inner.into_inner() {
                     if pat.as_rule() == Rule::map_pattern {
-                        *pattern = Some(normalize_pattern(pat.as_str()));
+                        map.pattern = Some(normalize_pattern(pat.as_str()));
                     }
                 }
             }
             Rule::mappings_block => {
+                map.mappings_clause.span.get_or_insert(span);
                 for mapping in inner.into_inner() {
                     if mapping.as_rule() == Rule::object_mapping_decl {
-                        object_mappings.push(parse_object_mapping(mapping)?);
+                        map.object_mappings.push(parse_object_mapping(mapping)?);
                     }
                 }
             }
             Rule::morphism_mappings_block => {
+                map.morphism_mappings_clause.span.get_or_insert(span);
                 for mapping in inner.into_inner() {
                     if mapping.as_rule() == Rule::morphism_mapping_decl {
-                        morphism_mappings.push(parse_morphism_mapping(mapping)?);
+                        map.morphism_mappings.push(parse_morphism_mapping(mapping)?);
                     }
                 }
             }
@@ -736,7 +1394,7 @@ fn parse_object_mapping(
 
     for inner in pair.into_inner() {
         match inner.as_rule() {
-            Rule::identifier => {
+            Rule::qualified_name => {
                 if ident_count == 0 {
                     source = inner.as_str().to_string();
                 } else {
@@ -748,7 +1406,7 @@ fn parse_object_mapping(
                 for desc in inner.into_inner() {
                     if desc.as_rule() == Rule::string_literal {
                         let s = desc.as_str();
-                        description = Some(s[1..s.len() - 1].to_string());
+                        description = Some(string_value(s));
                     }
                 }
             }
@@ -760,6 +1418,7 @@ fn parse_object_mapping(
         source,
         target,
         description,
+        trivia: Trivia::default(),
         span,
     })
 }
@@ -775,7 +1434,7 @@ fn parse_morphism_mapping(
 
     for inner in pair.into_inner() {
         match inner.as_rule() {
-            Rule::identifier => {
+            Rule::qualified_name => {
                 if ident_count == 0 {
                     source = inner.as_str().to_string();
                 } else {
@@ -787,7 +1446,7 @@ fn parse_morphism_mapping(
                 for desc in inner.into_inner() {
                     if desc.as_rule() == Rule::string_literal {
                         let s = desc.as_str();
-                        description = Some(s[1..s.len() - 1].to_string());
+                        description = Some(string_value(s));
                     }
                 }
             }
@@ -799,6 +1458,7 @@ fn parse_morphism_mapping(
         source,
         target,
         description,
+        trivia: Trivia::default(),
         span,
     })
 }
@@ -972,7 +1632,7 @@ fn parse_primary_expr(pair: pest::iterators::Pair<'_, Rule>) -> Result<Expr, Par
             }
             Rule::string_literal => {
                 let s = p.as_str();
-                Ok(Expr::String(s[1..s.len() - 1].to_string()))
+                Ok(Expr::String(string_value(s)))
             }
             Rule::expression => parse_expression(p),
             Rule::identifier => Ok(Expr::Path(Path::single(p.as_str()))),
@@ -1117,6 +1777,54 @@ mod tests {
         assert_eq!(contexts[0].aggregates[0].contains, vec!["LineItem", "Payment"]);
     }
 
+    #[test]
+    fn test_parse_invariant_modes() {
+        let source = r#"
+            context Commerce {
+                aggregate Order {
+                    invariant: total >= 0
+                    invariant strict: count(items) > 0
+                    invariant eventual: customer.active == 1
+                }
+            }
+        "#;
+        let contexts = parse(source).unwrap();
+        let modes: Vec<_> = contexts[0].aggregates[0]
+            .invariants
+            .iter()
+            .map(|inv| inv.mode)
+            .collect();
+        assert_eq!(
+            modes,
+            vec![None, Some(InvariantMode::Strict), Some(InvariantMode::Eventual)]
+        );
+    }
+
+    #[test]
+    fn test_parse_equation_modes() {
+        let source = r#"
+            context Commerce {
+                equation total: items.price = total
+                equation eventual withinLimit: placedBy.limit = total
+                equation eventualTotal: items.price = total
+            }
+        "#;
+        let contexts = parse(source).unwrap();
+        let equations: Vec<_> = contexts[0]
+            .equations
+            .iter()
+            .map(|eq| (eq.name.as_deref(), eq.mode))
+            .collect();
+        assert_eq!(
+            equations,
+            vec![
+                (Some("total"), None),
+                (Some("withinLimit"), Some(InvariantMode::Eventual)),
+                (Some("eventualTotal"), None),
+            ]
+        );
+    }
+
     #[test]
     fn test_parse_value_object() {
         let source = r#"
@@ -1265,6 +1973,330 @@ mod tests {
         assert_eq!(contexts[0].entities[0].fields.len(), 3);
     }
 
+    #[test]
+    fn test_parse_process_block() {
+        let source = r#"
+            context Fulfillment {
+                process FulfillmentSaga { on OrderPlaced -> ReserveStock; on StockReserved -> Ship }
+            }
+        "#;
+        let result = parse(source);
+        assert!(result.is_ok());
+        let contexts = result.unwrap();
+        assert_eq!(contexts[0].processes.len(), 1);
+        let process = &contexts[0].processes[0];
+        assert_eq!(process.name, "FulfillmentSaga");
+        assert_eq!(process.steps.len(), 2);
+        assert_eq!(process.steps[0].event, "OrderPlaced");
+        assert_eq!(process.steps[0].command, "ReserveStock");
+        assert_eq!(process.steps[1].event, "StockReserved");
+        assert_eq!(process.steps[1].command, "Ship");
+    }
+
+    #[test]
+    fn test_parse_process_block_multiline() {
+        let source = r#"
+            context Fulfillment {
+                process FulfillmentSaga {
+                    on OrderPlaced -> ReserveStock
+                    on StockReserved -> Ship;
+                }
+            }
+        "#;
+        let result = parse(source);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].processes[0].steps.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_event_storming() {
+        let source = r#"
+            context Fulfillment {
+                actor Customer { PlaceOrder, CancelOrder }
+                policy ReserveOnPlacement { on OrderPlaced -> ReserveStock, NotifyWarehouse }
+                hotspot "Who pays for returns?"
+                hotspot ReserveStock "What if stock runs out?"
+            }
+        "#;
+        let contexts = parse(source).unwrap();
+        let ctx = &contexts[0];
+
+        assert_eq!(ctx.actors[0].name, "Customer");
+        assert_eq!(ctx.actors[0].commands, vec!["PlaceOrder", "CancelOrder"]);
+        assert_eq!(ctx.policies[0].name, "ReserveOnPlacement");
+        assert_eq!(ctx.policies[0].event, "OrderPlaced");
+        assert_eq!(ctx.policies[0].commands, vec!["ReserveStock", "NotifyWarehouse"]);
+        assert_eq!(ctx.hotspots[0].target, None);
+        assert_eq!(ctx.hotspots[0].note, "Who pays for returns?");
+        assert_eq!(ctx.hotspots[1].target.as_deref(), Some("ReserveStock"));
+    }
+
+    #[test]
+    fn test_parse_lifecycle_block() {
+        let source = r#"
+            context Commerce {
+                enum OrderStatus = Pending | Confirmed | Shipped | Cancelled
+                lifecycle Order { Pending -> Confirmed -> Shipped; Pending -> Cancelled }
+            }
+        "#;
+        let result = parse(source);
+        assert!(result.is_ok());
+        let contexts = result.unwrap();
+        assert_eq!(contexts[0].lifecycles.len(), 1);
+        let lifecycle = &contexts[0].lifecycles[0];
+        assert_eq!(lifecycle.name, "Order");
+        assert_eq!(lifecycle.enum_name, None);
+        assert_eq!(lifecycle.chains.len(), 2);
+        assert_eq!(lifecycle.chains[0].states, vec!["Pending", "Confirmed", "Shipped"]);
+        let transitions: Vec<_> = lifecycle.chains[0].transitions().collect();
+        assert_eq!(transitions, vec![("Pending", "Confirmed"), ("Confirmed", "Shipped")]);
+    }
+
+    #[test]
+    fn test_parse_lifecycle_with_enum() {
+        let source = r#"
+            context Commerce {
+                lifecycle Order: OrderStatus {
+                    Pending -> Confirmed
+                    Confirmed -> Shipped
+                }
+            }
+        "#;
+        let result = parse(source);
+        assert!(result.is_ok());
+        let lifecycle = &result.unwrap()[0].lifecycles[0];
+        assert_eq!(lifecycle.enum_name, Some("OrderStatus".to_string()));
+        assert_eq!(lifecycle.chains.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_lifecycle_defaults() {
+        let source = r#"
+            context Commerce {
+                lifecycle Order {
+                    Pending -> Confirmed
+                    default: Imported, Archived
+                    defaulted -> Pending
+                }
+            }
+        "#;
+        let lifecycle = &parse(source).unwrap()[0].lifecycles[0];
+        assert_eq!(lifecycle.defaults, vec!["Imported", "Archived"]);
+        assert_eq!(lifecycle.chains.len(), 2);
+        assert_eq!(lifecycle.chains[1].states, vec!["defaulted", "Pending"]);
+    }
+
+    #[test]
+    fn test_parse_field_constraints() {
+        let source = r#"
+            context Billing {
+                value Money {
+                    amount: Decimal [min: 0]
+                    currency: String [pattern: "[A-Z]{3}"]
+                }
+                morphisms {
+                    total: Invoice -> Money [description="Invoice total"] [max: -1.5]
+                }
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let ctx = &file.contexts[0];
+
+        let fields = &ctx.value_objects[0].fields;
+        assert_eq!(fields[0].constraints[0].name, "min");
+        assert_eq!(fields[0].constraints[0].value, ConstraintValue::Number(0.0));
+        assert_eq!(
+            fields[1].constraints[0].value,
+            ConstraintValue::Text("[A-Z]{3}".to_string())
+        );
+
+        let morph = &ctx.morphisms[0];
+        assert_eq!(morph.annotations.len(), 1);
+        assert_eq!(morph.constraints[0].value, ConstraintValue::Number(-1.5));
+    }
+
+    #[test]
+    fn test_parse_derive_block() {
+        let source = r#"
+            context Commerce {
+                morphisms {
+                    items: Order -> List<LineItem>
+                }
+                derive total: Order -> Money = sum(items.price) - discount
+                entity Order
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let ctx = &file.contexts[0];
+
+        assert_eq!(ctx.derivations.len(), 1);
+        assert_eq!(ctx.entities.len(), 1);
+        let derivation = &ctx.derivations[0];
+        assert_eq!(derivation.name, "total");
+        assert_eq!(derivation.source, TypeExpr::simple("Order"));
+        assert_eq!(derivation.target, TypeExpr::simple("Money"));
+        match &derivation.expression {
+            Expr::BinaryOp { left, op, right } => {
+                assert_eq!(*op, BinaryOperator::Sub);
+                assert_eq!(
+                    **left,
+                    Expr::FunctionCall {
+                        name: "sum".to_string(),
+                        args: vec![Expr::Path(Path::new(vec!["items".into(), "price".into()]))],
+                    }
+                );
+                assert_eq!(**right, Expr::Path(Path::single("discount")));
+            }
+            other => panic!("expected a binary expression, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_decorators() {
+        let source = r#"
+            @version(2)
+            context Billing {
+                @removed_in("3.0")
+                enum LegacyStatus = Open | Closed
+                entity Invoice {
+                    @removed_in("3.0")
+                    legacyCode: String
+                }
+                morphisms {
+                    @deprecated
+                    total: Invoice -> Money
+                }
+                @meta(owner: "billing")
+                aggregate Invoice { }
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let ctx = &file.contexts[0];
+
+        assert_eq!(ctx.decorators[0].name, "version");
+        assert_eq!(ctx.decorators[0].argument, Some(ConstraintValue::Number(2.0)));
+        assert_eq!(
+            ctx.enums[0].decorators[0].argument,
+            Some(ConstraintValue::Text("3.0".to_string()))
+        );
+        assert_eq!(ctx.entities[0].fields[0].decorators[0].name, "removed_in");
+        assert_eq!(ctx.morphisms[0].decorators[0].name, "deprecated");
+        assert_eq!(ctx.morphisms[0].decorators[0].argument, None);
+        assert_eq!(ctx.morphisms[0].name, "total");
+        let meta = &ctx.aggregates[0].decorators[0];
+        assert_eq!(meta.key.as_deref(), Some("owner"));
+        assert_eq!(meta.argument, Some(ConstraintValue::Text("billing".to_string())));
+    }
+
+    #[test]
+    fn test_parse_workspace() {
+        let source = r#"
+            context Sales { entity Order }
+            context Shipping { entity Shipment }
+            map SalesToShipping: Sales -> Shipping {
+                pattern: CustomerSupplier
+                mappings { Order -> Shipment }
+            }
+        "#;
+        let workspace = parse_workspace(source).unwrap();
+
+        assert_eq!(workspace.contexts.len(), 2);
+        assert!(workspace.context("Shipping").is_some());
+        assert!(workspace.context_map("SalesToShipping").is_some());
+        assert!(workspace.validate().is_ok());
+    }
+
+    #[test]
+    fn test_parse_qualified_names() {
+        let source = r#"
+            shared kernel Identity {
+                objects { Auth.UserId, TenantId }
+            }
+
+            map AuthToBilling: Auth -> Billing {
+                mappings {
+                    Auth.UserId -> Billing.Payer
+                    Session -> Billing.Account
+                }
+                morphism_mappings {
+                    Auth.owner -> payer
+                }
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+
+        let kernel = &file.shared_kernels[0];
+        assert_eq!(kernel.objects[0].name, "Auth.UserId");
+        assert_eq!(kernel.type_names().collect::<Vec<_>>(), vec!["UserId", "TenantId"]);
+
+        let map = &file.context_maps[0];
+        assert_eq!(map.object_mappings[0].source, "Auth.UserId");
+        assert_eq!(map.object_mappings[0].target, "Billing.Payer");
+        assert_eq!(map.object_mappings[1].source, "Session");
+        assert_eq!(map.morphism_mappings[0].source, "Auth.owner");
+
+        // Qualified names are not allowed in a context's own objects
+        assert!(parse_file("context C { objects { Auth.UserId } }").is_err());
+    }
+
+    #[test]
+    fn test_parse_shared_kernel() {
+        let source = r#"
+            shared kernel Identity {
+                objects { UserId, TenantId }
+                enum Role = Admin | Member
+            }
+
+            context Billing {
+                objects { Invoice }
+            }
+        "#;
+        let result = parse_file(source);
+        assert!(result.is_ok());
+        let file = result.unwrap();
+        assert_eq!(file.shared_kernels.len(), 1);
+        assert_eq!(file.contexts.len(), 1);
+        let kernel = &file.shared_kernels[0];
+        assert_eq!(kernel.name, "Identity");
+        assert_eq!(kernel.objects.len(), 2);
+        assert_eq!(kernel.enums.len(), 1);
+        let names: Vec<_> = kernel.type_names().collect();
+        assert_eq!(names, vec!["UserId", "TenantId", "Role"]);
+    }
+
+    #[test]
+    fn test_parse_shared_kernel_rejects_morphisms() {
+        let source = r#"
+            shared kernel Identity {
+                morphisms { owner: TenantId -> UserId }
+            }
+        "#;
+        assert!(parse_file(source).is_err());
+    }
+
+    #[test]
+    fn test_parse_error_location() {
+        let source = "context Orders {\n  entity Order\n    id: UUID\n  }\n}\n";
+        let error = parse_file(source).unwrap_err();
+        assert_eq!((error.line, error.column), (Some(3), Some(5)));
+    }
+
+    #[test]
+    fn test_parse_old_cardinality_points_at_migrate() {
+        let source = "context Blog {\n  morphisms {\n    comments: Post -> Comment @many\n    author: Post -> Author [lazy] @one\n  }\n}\n";
+        let error = parse_file(source).unwrap_err();
+        assert_eq!((error.line, error.column), (Some(3), Some(31)));
+        assert!(error.message.contains("'@many'"));
+        assert!(error.message.contains("run `sketchddd migrate <file>`"));
+
+        let error = parse_file(&source.replace(" @many", "")).unwrap_err();
+        assert_eq!((error.line, error.column), (Some(4), Some(35)));
+
+        // Decorators on the next morphism are still decorators
+        let source = "context Blog {\n  morphisms {\n    comments: Post -> Comment\n    @oneway author: Post -> Author\n  }\n}\n";
+        assert!(parse_file(source).is_ok());
+    }
+
     #[test]
     fn test_parse_multiple_contexts() {
         let source = r#"
@@ -0,0 +1,634 @@
+//! Three-way merges of models, for a git merge driver.
+//!
+//! A line-based merge conflicts whenever both sides touch nearby lines,
+//! though adding an entity on one branch and a morphism on the other never
+//! really conflicts. [`merge`] merges the parsed files instead, declaration
+//! by declaration: contexts and domains are merged by what they declare,
+//! other declarations, such as an entity or a context map, as a whole. A
+//! declaration changed on one side only takes that side's version, as does
+//! one added or removed; only a declaration changed differently on both
+//! sides, or changed on one and removed on the other, conflicts.
+//!
+//! The merged file is printed like the formatter prints it, with each
+//! conflicting declaration between the usual conflict markers:
+//!
+//! ```text
+//! <<<<<<< ours
+//!   enum Status = Open | Closed
+//! =======
+//!   enum Status = Open | Archived
+//! >>>>>>> theirs
+//! ```
+//!
+//! Objects are merged as a set, and the declarations of a list keep the
+//! order of ours, followed by those only theirs add.
+
+use std::fmt;
+
+use crate::ast::*;
+use crate::pretty::{PrettyConfig, PrettyPrint, PrettyPrintIndented};
+
+/// Marker opening our side of a conflict.
+const OURS: &str = "<<<<<<< ours\n";
+/// Marker between the sides of a conflict.
+const SEPARATOR: &str = "=======\n";
+/// Marker closing their side of a conflict.
+const THEIRS: &str = ">>>>>>> theirs\n";
+
+/// Comment marking a declaration in conflict until the merged file is
+/// printed, followed by the index of its markers.
+const SENTINEL: &str = "//\u{0}conflict ";
+
+/// A declaration changed differently on both sides of a merge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    /// Kind of the declaration, such as `entity`
+    pub kind: &'static str,
+    /// Name of the declaration
+    pub name: String,
+    /// Context or domain declaring it, if not the file
+    pub scope: Option<String>,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.kind, self.name)?;
+        if let Some(scope) = &self.scope {
+            write!(f, " in {}", scope)?;
+        }
+        Ok(())
+    }
+}
+
+/// The result of a merge.
+#[derive(Debug, Clone)]
+pub struct Merged {
+    /// Source of the merged file, with conflict markers around conflicts
+    pub source: String,
+    /// The conflicts, in the order of the merged file
+    pub conflicts: Vec<Conflict>,
+}
+
+/// Merge the changes from `base` to `ours` and from `base` to `theirs`.
+pub fn merge(base: &File, ours: &File, theirs: &File) -> Merged {
+    let mut merger = Merger::default();
+    let merged = File {
+        shared_kernels: merger.merge_list(
+            None,
+            &base.shared_kernels,
+            &ours.shared_kernels,
+            &theirs.shared_kernels,
+            as_a_whole,
+        ),
+        templates: merger.merge_list(
+            None,
+            &base.templates,
+            &ours.templates,
+            &theirs.templates,
+            as_a_whole,
+        ),
+        contexts: merger.merge_list(
+            None,
+            &base.contexts,
+            &ours.contexts,
+            &theirs.contexts,
+            merge_context,
+        ),
+        context_maps: merger.merge_list(
+            None,
+            &base.context_maps,
+            &ours.context_maps,
+            &theirs.context_maps,
+            as_a_whole,
+        ),
+        domains: merger.merge_list(
+            None,
+            &base.domains,
+            &ours.domains,
+            &theirs.domains,
+            merge_domain,
+        ),
+        // Comments at the end of the file are not worth a conflict
+        trailing_trivia: match resolve(
+            Some(&base.trailing_trivia),
+            Some(&ours.trailing_trivia),
+            Some(&theirs.trailing_trivia),
+        ) {
+            Side::Theirs => theirs.trailing_trivia.clone(),
+            Side::Ours | Side::Conflict => ours.trailing_trivia.clone(),
+        },
+    };
+    Merged {
+        source: merger.print(&merged),
+        conflicts: merger.conflicts,
+    }
+}
+
+/// Which side of a merge to take.
+enum Side {
+    Ours,
+    Theirs,
+    Conflict,
+}
+
+/// Decide which side of a merge to take, from what each side holds.
+fn resolve<T: PartialEq>(base: Option<T>, ours: Option<T>, theirs: Option<T>) -> Side {
+    if ours == theirs || theirs == base {
+        Side::Ours
+    } else if ours == base {
+        Side::Theirs
+    } else {
+        Side::Conflict
+    }
+}
+
+/// A declaration merged as part of a list of declarations of its kind.
+trait Element: Clone {
+    /// Kind of the declaration, as named in conflicts
+    const KIND: &'static str;
+
+    /// Identify the declaration among those of its kind.
+    fn key(&self) -> String;
+
+    /// Get what the declaration says, to compare it with other versions of
+    /// it; declarations saying the same are equal whatever their spans.
+    fn text(&self) -> String;
+
+    fn trivia_mut(&mut self) -> &mut Trivia;
+}
+
+/// Print a declaration of a context's body on its own.
+fn indented(element: &impl PrettyPrintIndented) -> String {
+    element.pretty_print_indented("", &PrettyConfig::default())
+}
+
+/// Print a declaration of a file on its own.
+fn top_level(element: &impl PrettyPrint) -> String {
+    element.pretty_print()
+}
+
+/// Print a subdomain on its own, which is only printed within its domain.
+fn subdomain(subdomain: &SubdomainDecl) -> String {
+    let domain = DomainDecl {
+        subdomains: vec![subdomain.clone()],
+        ..DomainDecl::default()
+    };
+    domain.pretty_print()
+}
+
+/// Implement [`Element`] for a declaration known by its name.
+macro_rules! named_element {
+    ($type:ty, $kind:literal, $print:ident) => {
+        impl Element for $type {
+            const KIND: &'static str = $kind;
+
+            fn key(&self) -> String {
+                self.name.clone()
+            }
+
+            fn text(&self) -> String {
+                format!("{}{:?}", $print(self), self.trivia)
+            }
+
+            fn trivia_mut(&mut self) -> &mut Trivia {
+                &mut self.trivia
+            }
+        }
+    };
+}
+
+named_element!(EntityDecl, "entity", indented);
+named_element!(MorphismDecl, "morphism", indented);
+named_element!(DerivationDecl, "derived morphism", indented);
+named_element!(AggregateDecl, "aggregate", indented);
+named_element!(ValueObjectDecl, "value object", indented);
+named_element!(EnumDecl, "enum", indented);
+named_element!(ProcessDecl, "process", indented);
+named_element!(ActorDecl, "actor", indented);
+named_element!(PolicyDecl, "policy", indented);
+named_element!(LifecycleDecl, "lifecycle", indented);
+named_element!(ContextDecl, "context", top_level);
+named_element!(ContextMapDecl, "context map", top_level);
+named_element!(SharedKernelDecl, "shared kernel", top_level);
+named_element!(TemplateDecl, "template", top_level);
+named_element!(DomainDecl, "domain", top_level);
+named_element!(SubdomainDecl, "subdomain", subdomain);
+
+impl Element for EquationDecl {
+    const KIND: &'static str = "equation";
+
+    fn key(&self) -> String {
+        // An unnamed equation is known by what it equates
+        match &self.name {
+            Some(name) => name.clone(),
+            None => indented(self),
+        }
+    }
+
+    fn text(&self) -> String {
+        format!("{}{:?}", indented(self), self.trivia)
+    }
+
+    fn trivia_mut(&mut self) -> &mut Trivia {
+        &mut self.trivia
+    }
+}
+
+impl Element for HotspotDecl {
+    const KIND: &'static str = "hotspot";
+
+    fn key(&self) -> String {
+        indented(self)
+    }
+
+    fn text(&self) -> String {
+        format!("{}{:?}", indented(self), self.trivia)
+    }
+
+    fn trivia_mut(&mut self) -> &mut Trivia {
+        &mut self.trivia
+    }
+}
+
+impl Element for UseDecl {
+    const KIND: &'static str = "use";
+
+    fn key(&self) -> String {
+        format!("{}<{}>", self.template, self.args.join(", "))
+    }
+
+    fn text(&self) -> String {
+        format!("{}{:?}", self.key(), self.trivia)
+    }
+
+    fn trivia_mut(&mut self) -> &mut Trivia {
+        &mut self.trivia
+    }
+}
+
+/// Leave declarations changed on both sides to conflict as a whole.
+fn as_a_whole<T>(_: &mut Merger, _: Option<&T>, _: &T, _: &T) -> Option<T> {
+    None
+}
+
+/// Merge the declarations of two contexts changed on both sides.
+fn merge_context(
+    merger: &mut Merger,
+    base: Option<&ContextDecl>,
+    ours: &ContextDecl,
+    theirs: &ContextDecl,
+) -> Option<ContextDecl> {
+    // What the context declares of itself, rather than in its body
+    let header = |context: &ContextDecl| {
+        let bare = ContextDecl {
+            name: context.name.clone(),
+            decorators: context.decorators.clone(),
+            ..ContextDecl::default()
+        };
+        format!("{}{:?}", bare.pretty_print(), context.trivia)
+    };
+    let mut merged = match resolve(base.map(header), Some(header(ours)), Some(header(theirs))) {
+        Side::Ours => ours.clone(),
+        Side::Theirs => theirs.clone(),
+        Side::Conflict => return None,
+    };
+
+    let empty = ContextDecl::default();
+    let base = base.unwrap_or(&empty);
+    let scope = Some(ours.name.as_str());
+    merged.objects = merge_objects(&base.objects, &ours.objects, &theirs.objects);
+    merged.uses = merger.merge_list(scope, &base.uses, &ours.uses, &theirs.uses, as_a_whole);
+    merged.entities = merger.merge_list(
+        scope,
+        &base.entities,
+        &ours.entities,
+        &theirs.entities,
+        as_a_whole,
+    );
+    merged.morphisms = merger.merge_list(
+        scope,
+        &base.morphisms,
+        &ours.morphisms,
+        &theirs.morphisms,
+        as_a_whole,
+    );
+    merged.derivations = merger.merge_list(
+        scope,
+        &base.derivations,
+        &ours.derivations,
+        &theirs.derivations,
+        as_a_whole,
+    );
+    merged.aggregates = merger.merge_list(
+        scope,
+        &base.aggregates,
+        &ours.aggregates,
+        &theirs.aggregates,
+        as_a_whole,
+    );
+    merged.value_objects = merger.merge_list(
+        scope,
+        &base.value_objects,
+        &ours.value_objects,
+        &theirs.value_objects,
+        as_a_whole,
+    );
+    merged.enums = merger.merge_list(scope, &base.enums, &ours.enums, &theirs.enums, as_a_whole);
+    merged.equations = merger.merge_list(
+        scope,
+        &base.equations,
+        &ours.equations,
+        &theirs.equations,
+        as_a_whole,
+    );
+    merged.processes = merger.merge_list(
+        scope,
+        &base.processes,
+        &ours.processes,
+        &theirs.processes,
+        as_a_whole,
+    );
+    merged.actors = merger.merge_list(
+        scope,
+        &base.actors,
+        &ours.actors,
+        &theirs.actors,
+        as_a_whole,
+    );
+    merged.policies = merger.merge_list(
+        scope,
+        &base.policies,
+        &ours.policies,
+        &theirs.policies,
+        as_a_whole,
+    );
+    merged.hotspots = merger.merge_list(
+        scope,
+        &base.hotspots,
+        &ours.hotspots,
+        &theirs.hotspots,
+        as_a_whole,
+    );
+    merged.lifecycles = merger.merge_list(
+        scope,
+        &base.lifecycles,
+        &ours.lifecycles,
+        &theirs.lifecycles,
+        as_a_whole,
+    );
+    Some(merged)
+}
+
+/// Merge the subdomains of two domains changed on both sides.
+fn merge_domain(
+    merger: &mut Merger,
+    base: Option<&DomainDecl>,
+    ours: &DomainDecl,
+    theirs: &DomainDecl,
+) -> Option<DomainDecl> {
+    let mut merged = match resolve(
+        base.map(|domain| &domain.trivia),
+        Some(&ours.trivia),
+        Some(&theirs.trivia),
+    ) {
+        Side::Ours => ours.clone(),
+        Side::Theirs => theirs.clone(),
+        Side::Conflict => return None,
+    };
+    let empty = Vec::new();
+    merged.subdomains = merger.merge_list(
+        Some(&ours.name),
+        base.map_or(&empty, |domain| &domain.subdomains),
+        &ours.subdomains,
+        &theirs.subdomains,
+        as_a_whole,
+    );
+    Some(merged)
+}
+
+/// Merge the objects of a context as a set.
+fn merge_objects(
+    base: &[ObjectDecl],
+    ours: &[ObjectDecl],
+    theirs: &[ObjectDecl],
+) -> Vec<ObjectDecl> {
+    let has = |objects: &[ObjectDecl], name: &str| objects.iter().any(|o| o.name == name);
+    let kept = ours
+        .iter()
+        .filter(|object| has(theirs, &object.name) || !has(base, &object.name));
+    let added = theirs
+        .iter()
+        .filter(|object| !has(base, &object.name) && !has(ours, &object.name));
+    kept.chain(added).cloned().collect()
+}
+
+/// Merges declarations, keeping the conflicts.
+#[derive(Default)]
+struct Merger {
+    conflicts: Vec<Conflict>,
+    /// Text before and after each declaration in conflict
+    markers: Vec<(String, String)>,
+}
+
+impl Merger {
+    /// Merge lists of declarations of one kind, trying `nested` on
+    /// declarations changed on both sides before they conflict.
+    fn merge_list<T: Element>(
+        &mut self,
+        scope: Option<&str>,
+        base: &[T],
+        ours: &[T],
+        theirs: &[T],
+        nested: impl Fn(&mut Self, Option<&T>, &T, &T) -> Option<T>,
+    ) -> Vec<T> {
+        let find = |elements: &[T], key: &str| elements.iter().find(|e| e.key() == key).cloned();
+        let mut keys: Vec<String> = ours.iter().map(T::key).collect();
+        for key in theirs.iter().map(T::key) {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+
+        let mut merged = Vec::new();
+        for key in keys {
+            let (base, ours, theirs) = (find(base, &key), find(ours, &key), find(theirs, &key));
+            let text = |element: &Option<T>| element.as_ref().map(T::text);
+            match resolve(text(&base), text(&ours), text(&theirs)) {
+                Side::Ours => merged.extend(ours),
+                Side::Theirs => merged.extend(theirs),
+                Side::Conflict => {
+                    if let (Some(o), Some(t)) = (&ours, &theirs) {
+                        if let Some(element) = nested(self, base.as_ref(), o, t) {
+                            merged.push(element);
+                            continue;
+                        }
+                    }
+                    self.conflicts.push(Conflict {
+                        kind: T::KIND,
+                        name: key,
+                        scope: scope.map(str::to_string),
+                    });
+                    merged.extend(self.mark(ours, theirs));
+                }
+            }
+        }
+        merged
+    }
+
+    /// Mark the sides of a conflict, to be printed between markers.
+    fn mark<T: Element>(&mut self, ours: Option<T>, theirs: Option<T>) -> Vec<T> {
+        let sides = match (ours, theirs) {
+            (Some(ours), Some(theirs)) => vec![
+                (ours, OURS.to_string(), String::new()),
+                (theirs, SEPARATOR.to_string(), THEIRS.to_string()),
+            ],
+            (Some(ours), None) => {
+                vec![(ours, OURS.to_string(), format!("{}{}", SEPARATOR, THEIRS))]
+            }
+            (None, Some(theirs)) => {
+                vec![(theirs, format!("{}{}", OURS, SEPARATOR), THEIRS.to_string())]
+            }
+            (None, None) => Vec::new(),
+        };
+        sides
+            .into_iter()
+            .enumerate()
+            .map(|(side, (mut element, before, after))| {
+                let sentinel = format!("{}{}", SENTINEL, self.markers.len());
+                self.markers.push((before, after));
+                // Blank lines before the conflict go before its markers,
+                // and none between its sides
+                let leading = &mut element.trivia_mut().leading;
+                let blank = leading
+                    .iter()
+                    .take_while(|item| **item == TriviaItem::BlankLine)
+                    .count();
+                if side > 0 {
+                    leading.drain(..blank);
+                }
+                let at = if side == 0 { blank } else { 0 };
+                leading.insert(at, TriviaItem::Comment(sentinel));
+                element
+            })
+            .collect()
+    }
+
+    /// Print the merged file, replacing the sentinels of conflicting
+    /// declarations with conflict markers.
+    fn print(&self, file: &File) -> String {
+        let printed = file.pretty_print();
+        let mut output = String::new();
+        let mut lines = printed.split_inclusive('\n');
+        while let Some(line) = lines.next() {
+            let marked = line
+                .trim()
+                .strip_prefix(SENTINEL)
+                .and_then(|i| i.parse::<usize>().ok());
+            let Some((before, after)) = marked.map(|index| &self.markers[index]) else {
+                output.push_str(line);
+                continue;
+            };
+
+            // The declaration follows its comments and decorators, and ends
+            // with the line closing its braces
+            output.push_str(before);
+            let (mut started, mut in_comment, mut depth) = (false, false, 0);
+            for line in lines.by_ref() {
+                output.push_str(line);
+                let trimmed = line.trim();
+                if in_comment || trimmed.starts_with("/*") {
+                    in_comment = !trimmed.contains("*/");
+                    continue;
+                }
+                if !started
+                    && (trimmed.is_empty() || trimmed.starts_with("//") || trimmed.starts_with('@'))
+                {
+                    continue;
+                }
+                started = true;
+                depth += brace_depth(line);
+                if depth <= 0 {
+                    break;
+                }
+            }
+            output.push_str(after);
+        }
+        output
+    }
+}
+
+/// Count the braces a line opens, less those it closes, outside strings
+/// and comments.
+fn brace_depth(line: &str) -> i32 {
+    let (mut depth, mut in_string, mut escaped) = (0, false, false);
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '/' if !in_string && chars.peek() == Some(&'/') => break,
+            '{' if !in_string => depth += 1,
+            '}' if !in_string => depth -= 1,
+            _ => {}
+        }
+    }
+    depth
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_file;
+
+    fn merge_sources(base: &str, ours: &str, theirs: &str) -> Merged {
+        merge(
+            &parse_file(base).unwrap(),
+            &parse_file(ours).unwrap(),
+            &parse_file(theirs).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_merge_changes_to_different_declarations() {
+        let base = "context Sales {\n  objects { Money }\n  entity Order\n  enum Status = Open | Closed\n}\n";
+        let ours = "context Sales {\n  objects { Money, Currency }\n  // Who orders\n  entity Customer\n  entity Order\n  enum Status = Open | Closed\n}\n";
+        let theirs = "context Sales {\n  entity Order {\n    total: Decimal\n  }\n  morphisms {\n    placedBy: Order -> Customer\n  }\n  enum Status = Open | Closed | Archived\n}\n";
+
+        let merged = merge_sources(base, ours, theirs);
+        assert!(merged.conflicts.is_empty());
+        assert_eq!(
+            merged.source,
+            "context Sales {\n  objects { Currency }\n  // Who orders\n  entity Customer\n  entity Order {\n    total: Decimal\n  }\n  enum Status = Open | Closed | Archived\n  morphisms {\n    placedBy: Order -> Customer\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_conflicting_changes() {
+        let base = "context Sales {\n  entity Order\n  enum Status = Open | Closed\n}\n\nmap SalesToBilling: Sales -> Billing {\n  pattern: Conformist\n}\n";
+        let ours = "context Sales {\n  entity Order\n  enum Status = Open | Shipped\n}\n\nmap SalesToBilling: Sales -> Billing {\n  pattern: CustomerSupplier\n}\n";
+        let theirs = "context Sales {\n  @core\n  entity Order {\n    total: Decimal\n  }\n  enum Status = Open | Archived\n}\n";
+
+        let merged = merge_sources(base, ours, theirs);
+        let conflicts: Vec<String> = merged.conflicts.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            conflicts,
+            ["enum Status in Sales", "context map SalesToBilling"]
+        );
+        assert_eq!(
+            merged.source,
+            "context Sales {\n  @core\n  entity Order {\n    total: Decimal\n  }\n<<<<<<< ours\n  enum Status = Open | Shipped\n=======\n  enum Status = Open | Archived\n>>>>>>> theirs\n}\n\n<<<<<<< ours\nmap SalesToBilling: Sales -> Billing {\n  pattern: CustomerSupplier\n}\n=======\n>>>>>>> theirs\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_removed_declarations() {
+        let base = "context Sales {\n  entity Order\n  entity Invoice\n}\n\ncontext Billing { }\n";
+        let ours = "context Sales {\n  entity Order\n}\n\ncontext Billing { }\n";
+        let theirs = "context Sales {\n  entity Order\n  entity Invoice\n}\n";
+
+        let merged = merge_sources(base, ours, theirs);
+        assert!(merged.conflicts.is_empty());
+        assert_eq!(merged.source, "context Sales {\n  entity Order\n}\n");
+    }
+}
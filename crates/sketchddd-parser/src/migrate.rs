@@ -0,0 +1,228 @@
+//! Upgrades of source written in older versions of the language.
+//!
+//! Syntax the grammar no longer accepts is rewritten into its current
+//! form, line by line, so that the rest of a file, comments included, is
+//! left as it was. [`migrate_source`] reports each change it makes, for
+//! `sketchddd migrate` to show before writing a file back.
+//!
+//! Rewrites:
+//!
+//! - cardinality markers after a morphism's target, which became part of
+//!   its type: `items: Order -> LineItem @many` is now
+//!   `items: Order -> List<LineItem>`, `@optional` makes the target
+//!   optional and `@one`, the default, is dropped.
+
+use serde::Serialize;
+
+/// A change made to a line of source.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SourceChange {
+    /// Line changed (1-indexed)
+    pub line: u32,
+
+    /// What was rewritten
+    pub description: String,
+
+    /// The line before the change
+    pub before: String,
+
+    /// The line after the change
+    pub after: String,
+}
+
+/// A migrated source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migrated {
+    /// The source in the current syntax
+    pub source: String,
+
+    /// Changes made, in the order of the lines
+    pub changes: Vec<SourceChange>,
+}
+
+impl Migrated {
+    /// Check whether the source was already in the current syntax.
+    pub fn is_unchanged(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// Rewrite the syntax of older versions of the language in a source.
+pub fn migrate_source(source: &str) -> Migrated {
+    let mut migrated = String::with_capacity(source.len());
+    let mut changes = Vec::new();
+
+    for (index, line) in source.split_inclusive('\n').enumerate() {
+        let (text, ending) = split_line_ending(line);
+        match migrate_cardinality(text) {
+            Some((after, description)) => {
+                migrated.push_str(&after);
+                changes.push(SourceChange {
+                    line: index as u32 + 1,
+                    description,
+                    before: text.trim().to_string(),
+                    after: after.trim().to_string(),
+                });
+            }
+            None => migrated.push_str(text),
+        }
+        migrated.push_str(ending);
+    }
+
+    Migrated {
+        source: migrated,
+        changes,
+    }
+}
+
+/// Split the line ending off a line.
+fn split_line_ending(line: &str) -> (&str, &str) {
+    let text = line.trim_end_matches(['\n', '\r']);
+    (text, &line[text.len()..])
+}
+
+/// Rewrite the cardinality markers after the target of a morphism on a
+/// line, returning the line and a description of the change.
+fn migrate_cardinality(line: &str) -> Option<(String, String)> {
+    let (code, comment) = split_comment(line);
+    let mut rest = code.trim_end();
+    let spacing = &code[rest.len()..];
+
+    // Markers are read from the end of the line
+    let mut markers = Vec::new();
+    while let Some((before, marker)) = rest.rsplit_once('@') {
+        if !matches!(marker, "one" | "many" | "optional") || !before.ends_with(char::is_whitespace)
+        {
+            break;
+        }
+        markers.push(marker);
+        rest = before.trim_end();
+    }
+    if markers.is_empty() {
+        return None;
+    }
+    markers.reverse();
+
+    // The target runs from the arrow to the annotations, if any
+    let arrow = rest.rfind("->")?;
+    if !rest[..arrow].contains(':') {
+        return None;
+    }
+    let target_start = arrow + 2;
+    let target_end = rest[target_start..]
+        .find('[')
+        .map_or(rest.len(), |i| target_start + i);
+    let target = rest[target_start..target_end].trim();
+    if target.is_empty() {
+        return None;
+    }
+
+    let mut migrated = target.to_string();
+    if markers.contains(&"many") && !migrated.starts_with("List<") {
+        migrated = format!("List<{}>", migrated);
+    }
+    if markers.contains(&"optional") && !migrated.ends_with('?') {
+        migrated.push('?');
+    }
+
+    let written: Vec<String> = markers.iter().map(|m| format!("@{}", m)).collect();
+    let description = if migrated == target {
+        format!(
+            "removed {}, which is implied by the target type",
+            written.join(" ")
+        )
+    } else {
+        format!(
+            "replaced {} by the target type {}",
+            written.join(" "),
+            migrated
+        )
+    };
+
+    let after_target = rest[target_end..].trim_end();
+    let mut line = format!("{} {}", rest[..target_start].trim_end(), migrated);
+    if !after_target.is_empty() {
+        line.push(' ');
+        line.push_str(after_target.trim_start());
+    }
+    line.push_str(spacing);
+    line.push_str(comment);
+    Some((line, description))
+}
+
+/// Split a line into its code and its `//` comment, if any, outside of
+/// string literals.
+fn split_comment(line: &str) -> (&str, &str) {
+    let mut in_string = false;
+    let mut previous = '\0';
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' if previous != '\\' => in_string = !in_string,
+            '/' if !in_string && previous == '/' => return line.split_at(i - 1),
+            _ => {}
+        }
+        previous = c;
+    }
+    (line, "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_file;
+
+    #[test]
+    fn test_migrate_cardinality_markers() {
+        let source = "context Blog {\n  morphisms {\n    // Post relationships\n    author: Post -> Author @one\n    comments: Post -> Comment @many  // newest first\n    editor: Post -> Author @optional\n    tags: Post -> Tag [ordered] @many @optional\n    reviewer: Post -> Author? @one\n  }\n}\n";
+        let migrated = migrate_source(source);
+
+        assert_eq!(
+            migrated.source,
+            "context Blog {\n  morphisms {\n    // Post relationships\n    author: Post -> Author\n    comments: Post -> List<Comment>  // newest first\n    editor: Post -> Author?\n    tags: Post -> List<Tag>? [ordered]\n    reviewer: Post -> Author?\n  }\n}\n"
+        );
+        assert!(parse_file(&migrated.source).is_ok());
+
+        let lines: Vec<u32> = migrated.changes.iter().map(|c| c.line).collect();
+        assert_eq!(lines, [4, 5, 6, 7, 8]);
+        assert_eq!(
+            migrated.changes[1].before,
+            "comments: Post -> Comment @many  // newest first"
+        );
+        assert_eq!(
+            migrated.changes[1].description,
+            "replaced @many by the target type List<Comment>"
+        );
+        assert_eq!(
+            migrated.changes[0].description,
+            "removed @one, which is implied by the target type"
+        );
+    }
+
+    #[test]
+    fn test_migrated_cardinality_keeps_targets() {
+        let source = "context Commerce {\n  entity Order\n  entity LineItem\n  morphisms {\n    items: Order -> LineItem @many\n    gifts: Order -> LineItem @many @optional\n  }\n}\n";
+        let migrated = migrate_source(source);
+        let result = crate::transform(&parse_file(&migrated.source).unwrap()).unwrap();
+        assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+
+        let commerce = &result.contexts[0];
+        let graph = commerce.graph();
+        assert!(graph.find_object_by_name("List").is_none());
+        let line_item = graph.find_object_by_name("LineItem").unwrap().id;
+        for name in ["items", "gifts"] {
+            let morphism = graph.find_morphism_by_name(name).unwrap();
+            assert_eq!(morphism.target, line_item);
+            assert!(commerce.is_many(morphism.id));
+        }
+        let gifts = graph.find_morphism_by_name("gifts").unwrap();
+        assert!(commerce.is_optional(gifts.id));
+    }
+
+    #[test]
+    fn test_migrate_leaves_current_syntax() {
+        let source = "context Blog {\n  @core\n  aggregate Post {\n    root: Post\n  }\n  morphisms {\n    @removed_in(\"3.0\") author: Post -> Author // by @one author\n    note: Post -> Text [doc = \"a @many\"]\n  }\n}\r\n";
+        let migrated = migrate_source(source);
+        assert!(migrated.is_unchanged());
+        assert_eq!(migrated.source, source);
+    }
+}
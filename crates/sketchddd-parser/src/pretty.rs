@@ -2,9 +2,15 @@
 //!
 //! This module provides human-readable representations of AST nodes
 //! for debugging and error reporting purposes.
+//!
+//! Comments and blank lines recorded as [`Trivia`] are printed next to the
+//! declarations they belong to, so a parsed file keeps its comments when it
+//! is printed back.
 
 use std::fmt::{self, Display, Write};
+use std::ops::Range;
 
+use crate::add::Declaration;
 use crate::ast::*;
 
 /// Pretty-print configuration.
@@ -64,16 +70,46 @@ impl PrettyPrint for File {
     fn pretty_print_with_config(&self, config: &PrettyConfig) -> String {
         let mut output = String::new();
 
+        for kernel in &self.shared_kernels {
+            let rendered = kernel.pretty_print_with_config(config);
+            push_with_trivia(&mut output, "", config, &kernel.trivia, &rendered);
+            output.push('\n');
+        }
+
+        for template in &self.templates {
+            let rendered = template.pretty_print_with_config(config);
+            push_with_trivia(&mut output, "", config, &template.trivia, &rendered);
+            output.push('\n');
+        }
+
         for ctx in &self.contexts {
-            output.push_str(&ctx.pretty_print_with_config(config));
+            let rendered = ctx.pretty_print_with_config(config);
+            push_with_trivia(&mut output, "", config, &ctx.trivia, &rendered);
             output.push('\n');
         }
 
         for map in &self.context_maps {
-            output.push_str(&map.pretty_print_with_config(config));
+            let rendered = map.pretty_print_with_config(config);
+            push_with_trivia(&mut output, "", config, &map.trivia, &rendered);
             output.push('\n');
         }
 
+        for domain in &self.domains {
+            let rendered = domain.pretty_print_with_config(config);
+            push_with_trivia(&mut output, "", config, &domain.trivia, &rendered);
+            output.push('\n');
+        }
+
+        // Comments after the last declaration keep their own blank lines
+        if !self.trailing_trivia.is_empty() && output.ends_with("\n\n") {
+            output.pop();
+        }
+        write_trivia_items(&mut output, "", &self.trailing_trivia);
+
+        // End the file with a single newline
+        if output.ends_with("\n\n") {
+            output.pop();
+        }
         output
     }
 }
@@ -81,50 +117,160 @@ impl PrettyPrint for File {
 impl PrettyPrint for ContextDecl {
     fn pretty_print_with_config(&self, config: &PrettyConfig) -> String {
         let mut output = String::new();
-        let indent = &config.indent;
 
+        write_decorators(&mut output, "", &self.decorators);
         writeln!(output, "context {} {{", self.name).unwrap();
+        write_context_body(&mut output, self, config);
+        writeln!(output, "}}").unwrap();
+        output
+    }
+}
 
-        // Objects
-        if !self.objects.is_empty() {
-            write!(output, "{}objects {{ ", indent).unwrap();
-            let names: Vec<_> = self.objects.iter().map(|o| o.name.as_str()).collect();
-            write!(output, "{}", names.join(", ")).unwrap();
-            writeln!(output, " }}").unwrap();
-        }
+impl PrettyPrint for TemplateDecl {
+    fn pretty_print_with_config(&self, config: &PrettyConfig) -> String {
+        let mut output = String::new();
 
-        // Entities
-        for entity in &self.entities {
-            output.push_str(&entity.pretty_print_indented(indent, config));
-        }
+        writeln!(output, "template {}<{}> {{", self.name, self.params.join(", ")).unwrap();
+        write_context_body(&mut output, &self.body, config);
+        writeln!(output, "}}").unwrap();
+        output
+    }
+}
 
-        // Morphisms
-        if !self.morphisms.is_empty() {
-            writeln!(output, "{}morphisms {{", indent).unwrap();
-            for morph in &self.morphisms {
-                output.push_str(&morph.pretty_print_indented(&format!("{}{}", indent, indent), config));
+/// Write the declarations of a context or template body, in source order.
+fn write_context_body(output: &mut String, body: &ContextDecl, config: &PrettyConfig) {
+    let indent = &config.indent;
+    let layout = body.layout();
+
+    for item in &layout {
+        let (rendered, trivia) = match item.kind {
+            BodyItemKind::Use => {
+                let use_decl = &body.uses[item.index];
+                let rendered = format!(
+                    "{}use {}<{}>\n",
+                    indent,
+                    use_decl.template,
+                    use_decl.args.join(", ")
+                );
+                (rendered, use_decl.trivia.clone())
             }
-            writeln!(output, "{}}}", indent).unwrap();
+            BodyItemKind::Objects => {
+                let objects = &body.objects[block_elements(&layout, item, body.objects.len())];
+                if objects.is_empty() {
+                    continue;
+                }
+                let names: Vec<_> = objects.iter().map(|o| o.name.as_str()).collect();
+                let rendered = format!("{}objects {{ {} }}\n", indent, names.join(", "));
+                let elements = objects_trivia(objects);
+                let trivia = Trivia {
+                    leading: [item.trivia.leading.clone(), elements.leading].concat(),
+                    trailing: item.trivia.trailing.clone().or(elements.trailing),
+                    dangling: item.trivia.dangling.clone(),
+                };
+                (rendered, trivia)
+            }
+            BodyItemKind::Entity => {
+                let entity = &body.entities[item.index];
+                (entity.pretty_print_indented(indent, config), entity.trivia.clone())
+            }
+            BodyItemKind::Morphisms => {
+                let range = block_elements(&layout, item, body.morphisms.len());
+                let morphisms = &body.morphisms[range];
+                if morphisms.is_empty() {
+                    continue;
+                }
+                let morph_indent = format!("{}{}", indent, indent);
+                let mut rendered = format!("{}morphisms {{\n", indent);
+                for morph in morphisms {
+                    let line = morph.pretty_print_indented(&morph_indent, config);
+                    push_with_trivia(&mut rendered, &morph_indent, config, &morph.trivia, &line);
+                }
+                writeln!(rendered, "{}}}", indent).unwrap();
+                (rendered, item.trivia.clone())
+            }
+            BodyItemKind::Derivation => {
+                let derivation = &body.derivations[item.index];
+                (derivation.pretty_print_indented(indent, config), derivation.trivia.clone())
+            }
+            BodyItemKind::Aggregate => {
+                let agg = &body.aggregates[item.index];
+                (agg.pretty_print_indented(indent, config), agg.trivia.clone())
+            }
+            BodyItemKind::ValueObject => {
+                let vo = &body.value_objects[item.index];
+                (vo.pretty_print_indented(indent, config), vo.trivia.clone())
+            }
+            BodyItemKind::Enum => {
+                let enum_decl = &body.enums[item.index];
+                (enum_decl.pretty_print_indented(indent, config), enum_decl.trivia.clone())
+            }
+            BodyItemKind::Equation => {
+                let eq = &body.equations[item.index];
+                (eq.pretty_print_indented(indent, config), eq.trivia.clone())
+            }
+            BodyItemKind::Process => {
+                let process = &body.processes[item.index];
+                (process.pretty_print_indented(indent, config), process.trivia.clone())
+            }
+            BodyItemKind::Actor => {
+                let actor = &body.actors[item.index];
+                (actor.pretty_print_indented(indent, config), actor.trivia.clone())
+            }
+            BodyItemKind::Policy => {
+                let policy = &body.policies[item.index];
+                (policy.pretty_print_indented(indent, config), policy.trivia.clone())
+            }
+            BodyItemKind::Hotspot => {
+                let hotspot = &body.hotspots[item.index];
+                (hotspot.pretty_print_indented(indent, config), hotspot.trivia.clone())
+            }
+            BodyItemKind::Lifecycle => {
+                let lifecycle = &body.lifecycles[item.index];
+                (lifecycle.pretty_print_indented(indent, config), lifecycle.trivia.clone())
+            }
+        };
+        push_with_trivia(output, indent, config, &trivia, &rendered);
+    }
+}
+
+/// Get the range of the elements of an `objects` or `morphisms` block,
+/// which ends where the next block of its kind starts.
+fn block_elements(layout: &[BodyItem], block: &BodyItem, count: usize) -> Range<usize> {
+    let end = layout
+        .iter()
+        .filter(|item| item.kind == block.kind && item.index > block.index)
+        .map(|item| item.index)
+        .min()
+        .unwrap_or(count);
+    block.index..end
+}
+
+impl PrettyPrint for SharedKernelDecl {
+    fn pretty_print_with_config(&self, config: &PrettyConfig) -> String {
+        let mut output = String::new();
+        let indent = &config.indent;
+
+        writeln!(output, "shared kernel {} {{", self.name).unwrap();
+
+        if !self.objects.is_empty() {
+            let names: Vec<_> = self.objects.iter().map(|o| o.name.as_str()).collect();
+            let rendered = format!("{}objects {{ {} }}\n", indent, names.join(", "));
+            push_with_trivia(&mut output, indent, config, &objects_trivia(&self.objects), &rendered);
         }
 
-        // Aggregates
-        for agg in &self.aggregates {
-            output.push_str(&agg.pretty_print_indented(indent, config));
+        for entity in &self.entities {
+            let rendered = entity.pretty_print_indented(indent, config);
+            push_with_trivia(&mut output, indent, config, &entity.trivia, &rendered);
         }
 
-        // Value objects
         for vo in &self.value_objects {
-            output.push_str(&vo.pretty_print_indented(indent, config));
+            let rendered = vo.pretty_print_indented(indent, config);
+            push_with_trivia(&mut output, indent, config, &vo.trivia, &rendered);
         }
 
-        // Enums
         for enum_decl in &self.enums {
-            output.push_str(&enum_decl.pretty_print_indented(indent, config));
-        }
-
-        // Equations
-        for eq in &self.equations {
-            output.push_str(&eq.pretty_print_indented(indent, config));
+            let rendered = enum_decl.pretty_print_indented(indent, config);
+            push_with_trivia(&mut output, indent, config, &enum_decl.trivia, &rendered);
         }
 
         writeln!(output, "}}").unwrap();
@@ -145,41 +291,45 @@ impl PrettyPrint for ContextMapDecl {
         .unwrap();
 
         if let Some(pattern) = &self.pattern {
-            writeln!(output, "{}pattern: {}", indent, pattern).unwrap();
+            let rendered = format!("{}pattern: {}\n", indent, pattern);
+            push_with_trivia(&mut output, indent, config, &self.pattern_clause.trivia, &rendered);
         }
 
-        if !self.object_mappings.is_empty() {
-            writeln!(output, "{}mappings {{", indent).unwrap();
+        // A block is kept while it holds comments, even without mappings
+        if !self.object_mappings.is_empty() || !self.mappings_clause.trivia.is_empty() {
+            let mut block = format!("{}mappings {{\n", indent);
             for mapping in &self.object_mappings {
-                write!(
-                    output,
-                    "{}{}{} -> {}",
-                    indent, indent, mapping.source, mapping.target
-                )
-                .unwrap();
+                let mapping_indent = format!("{}{}", indent, indent);
+                let mut rendered = format!("{}{} -> {}", mapping_indent, mapping.source, mapping.target);
                 if let Some(desc) = &mapping.description {
-                    write!(output, ": \"{}\"", desc).unwrap();
+                    write!(rendered, ": {}", quote(desc)).unwrap();
                 }
-                writeln!(output).unwrap();
+                rendered.push('\n');
+                push_with_trivia(&mut block, &mapping_indent, config, &mapping.trivia, &rendered);
             }
-            writeln!(output, "{}}}", indent).unwrap();
+            writeln!(block, "{}}}", indent).unwrap();
+            push_with_trivia(&mut output, indent, config, &self.mappings_clause.trivia, &block);
         }
 
-        if !self.morphism_mappings.is_empty() {
-            writeln!(output, "{}morphism_mappings {{", indent).unwrap();
+        if !self.morphism_mappings.is_empty() || !self.morphism_mappings_clause.trivia.is_empty() {
+            let mut block = format!("{}morphism_mappings {{\n", indent);
             for mapping in &self.morphism_mappings {
-                write!(
-                    output,
-                    "{}{}{} -> {}",
-                    indent, indent, mapping.source, mapping.target
-                )
-                .unwrap();
+                let mapping_indent = format!("{}{}", indent, indent);
+                let mut rendered = format!("{}{} -> {}", mapping_indent, mapping.source, mapping.target);
                 if let Some(desc) = &mapping.description {
-                    write!(output, ": \"{}\"", desc).unwrap();
+                    write!(rendered, ": {}", quote(desc)).unwrap();
                 }
-                writeln!(output).unwrap();
+                rendered.push('\n');
+                push_with_trivia(&mut block, &mapping_indent, config, &mapping.trivia, &rendered);
             }
-            writeln!(output, "{}}}", indent).unwrap();
+            writeln!(block, "{}}}", indent).unwrap();
+            push_with_trivia(
+                &mut output,
+                indent,
+                config,
+                &self.morphism_mappings_clause.trivia,
+                &block,
+            );
         }
 
         writeln!(output, "}}").unwrap();
@@ -187,8 +337,20 @@ impl PrettyPrint for ContextMapDecl {
     }
 }
 
+impl PrettyPrint for Declaration {
+    fn pretty_print_with_config(&self, config: &PrettyConfig) -> String {
+        match self {
+            Declaration::Entity(entity) => entity.pretty_print_indented("", config),
+            Declaration::Value(value) => value.pretty_print_indented("", config),
+            Declaration::Enum(enum_decl) => enum_decl.pretty_print_indented("", config),
+            Declaration::Aggregate(aggregate) => aggregate.pretty_print_indented("", config),
+            Declaration::Morphism(morphism) => morphism.pretty_print_indented("", config),
+        }
+    }
+}
+
 // Helper trait for indented pretty-printing
-trait PrettyPrintIndented {
+pub(crate) trait PrettyPrintIndented {
     fn pretty_print_indented(&self, indent: &str, config: &PrettyConfig) -> String;
 }
 
@@ -197,19 +359,14 @@ impl PrettyPrintIndented for EntityDecl {
         let mut output = String::new();
         let inner_indent = format!("{}{}", indent, config.indent);
 
+        write_decorators(&mut output, indent, &self.decorators);
         if self.fields.is_empty() {
             writeln!(output, "{}entity {}", indent, self.name).unwrap();
         } else {
             writeln!(output, "{}entity {} {{", indent, self.name).unwrap();
             for field in &self.fields {
-                writeln!(
-                    output,
-                    "{}{}: {}",
-                    inner_indent,
-                    field.name,
-                    format_type_expr(&field.type_expr)
-                )
-                .unwrap();
+                let rendered = format_field(field, &inner_indent);
+                push_with_trivia(&mut output, &inner_indent, config, &field.trivia, &rendered);
             }
             writeln!(output, "{}}}", indent).unwrap();
         }
@@ -222,6 +379,7 @@ impl PrettyPrintIndented for MorphismDecl {
     fn pretty_print_indented(&self, indent: &str, _config: &PrettyConfig) -> String {
         let mut output = String::new();
 
+        write_decorators(&mut output, indent, &self.decorators);
         write!(
             output,
             "{}{}: {} -> {}",
@@ -249,6 +407,8 @@ impl PrettyPrintIndented for MorphismDecl {
             write!(output, "]").unwrap();
         }
 
+        write!(output, "{}", format_constraints(&self.constraints)).unwrap();
+
         writeln!(output).unwrap();
         output
     }
@@ -259,6 +419,7 @@ impl PrettyPrintIndented for AggregateDecl {
         let mut output = String::new();
         let inner_indent = format!("{}{}", indent, config.indent);
 
+        write_decorators(&mut output, indent, &self.decorators);
         writeln!(output, "{}aggregate {} {{", indent, self.name).unwrap();
 
         if let Some(root) = &self.root {
@@ -276,10 +437,16 @@ impl PrettyPrintIndented for AggregateDecl {
         }
 
         for inv in &self.invariants {
+            let mode = match inv.mode {
+                Some(InvariantMode::Strict) => " strict",
+                Some(InvariantMode::Eventual) => " eventual",
+                None => "",
+            };
             writeln!(
                 output,
-                "{}invariant: {}",
+                "{}invariant{}: {}",
                 inner_indent,
+                mode,
                 format_expr(&inv.expression)
             )
             .unwrap();
@@ -295,16 +462,11 @@ impl PrettyPrintIndented for ValueObjectDecl {
         let mut output = String::new();
         let inner_indent = format!("{}{}", indent, config.indent);
 
+        write_decorators(&mut output, indent, &self.decorators);
         writeln!(output, "{}value {} {{", indent, self.name).unwrap();
         for field in &self.fields {
-            writeln!(
-                output,
-                "{}{}: {}",
-                inner_indent,
-                field.name,
-                format_type_expr(&field.type_expr)
-            )
-            .unwrap();
+            let rendered = format_field(field, &inner_indent);
+            push_with_trivia(&mut output, &inner_indent, config, &field.trivia, &rendered);
         }
         writeln!(output, "{}}}", indent).unwrap();
         output
@@ -315,6 +477,7 @@ impl PrettyPrintIndented for EnumDecl {
     fn pretty_print_indented(&self, indent: &str, _config: &PrettyConfig) -> String {
         let mut output = String::new();
 
+        write_decorators(&mut output, indent, &self.decorators);
         write!(output, "{}enum {} = ", indent, self.name).unwrap();
 
         let variants: Vec<_> = self
@@ -335,17 +498,35 @@ impl PrettyPrintIndented for EnumDecl {
     }
 }
 
+impl PrettyPrintIndented for DerivationDecl {
+    fn pretty_print_indented(&self, indent: &str, _config: &PrettyConfig) -> String {
+        format!(
+            "{}derive {}: {} -> {} = {}\n",
+            indent,
+            self.name,
+            format_type_expr(&self.source),
+            format_type_expr(&self.target),
+            format_expr(&self.expression)
+        )
+    }
+}
+
 impl PrettyPrintIndented for EquationDecl {
     fn pretty_print_indented(&self, indent: &str, _config: &PrettyConfig) -> String {
         let mut output = String::new();
 
-        write!(output, "{}equation ", indent).unwrap();
+        let mode = match self.mode {
+            Some(InvariantMode::Strict) => " strict",
+            Some(InvariantMode::Eventual) => " eventual",
+            None => "",
+        };
+        write!(output, "{}equation{}", indent, mode).unwrap();
         if let Some(name) = &self.name {
-            write!(output, "{}: ", name).unwrap();
+            write!(output, " {}", name).unwrap();
         }
         writeln!(
             output,
-            "{} = {}",
+            ": {} = {}",
             format_path(&self.lhs),
             format_path(&self.rhs)
         )
@@ -355,6 +536,215 @@ impl PrettyPrintIndented for EquationDecl {
     }
 }
 
+impl PrettyPrintIndented for ProcessDecl {
+    fn pretty_print_indented(&self, indent: &str, _config: &PrettyConfig) -> String {
+        let mut output = String::new();
+
+        writeln!(output, "{}process {} {{", indent, self.name).unwrap();
+        for step in &self.steps {
+            writeln!(
+                output,
+                "{}{}on {} -> {}{}",
+                indent,
+                indent,
+                step.event,
+                step.command,
+                if step.terminated { ";" } else { "" }
+            )
+            .unwrap();
+        }
+        writeln!(output, "{}}}", indent).unwrap();
+
+        output
+    }
+}
+
+impl PrettyPrintIndented for ActorDecl {
+    fn pretty_print_indented(&self, indent: &str, _config: &PrettyConfig) -> String {
+        format!(
+            "{}actor {} {{ {} }}\n",
+            indent,
+            self.name,
+            self.commands.join(", ")
+        )
+    }
+}
+
+impl PrettyPrintIndented for PolicyDecl {
+    fn pretty_print_indented(&self, indent: &str, _config: &PrettyConfig) -> String {
+        format!(
+            "{}policy {} {{ on {} -> {} }}\n",
+            indent,
+            self.name,
+            self.event,
+            self.commands.join(", ")
+        )
+    }
+}
+
+impl PrettyPrintIndented for HotspotDecl {
+    fn pretty_print_indented(&self, indent: &str, _config: &PrettyConfig) -> String {
+        match &self.target {
+            Some(target) => format!("{}hotspot {} {}\n", indent, target, quote(&self.note)),
+            None => format!("{}hotspot {}\n", indent, quote(&self.note)),
+        }
+    }
+}
+
+impl PrettyPrintIndented for LifecycleDecl {
+    fn pretty_print_indented(&self, indent: &str, _config: &PrettyConfig) -> String {
+        let mut output = String::new();
+
+        write!(output, "{}lifecycle {}", indent, self.name).unwrap();
+        if let Some(enum_name) = &self.enum_name {
+            write!(output, ": {}", enum_name).unwrap();
+        }
+        writeln!(output, " {{").unwrap();
+        for chain in &self.chains {
+            let separator = if chain.terminated { ";" } else { "" };
+            writeln!(
+                output,
+                "{}{}{}{}",
+                indent,
+                indent,
+                chain.states.join(" -> "),
+                separator
+            )
+            .unwrap();
+        }
+        if !self.defaults.is_empty() {
+            let defaults = self.defaults.join(", ");
+            writeln!(output, "{}{}default: {}", indent, indent, defaults).unwrap();
+        }
+        writeln!(output, "{}}}", indent).unwrap();
+
+        output
+    }
+}
+
+/// Format a field declaration, preceded by its decorators.
+fn format_field(field: &FieldDecl, indent: &str) -> String {
+    let mut output = String::new();
+    write_decorators(&mut output, indent, &field.decorators);
+    writeln!(
+        output,
+        "{}{}: {}{}",
+        indent,
+        field.name,
+        format_type_expr(&field.type_expr),
+        format_constraints(&field.constraints)
+    )
+    .unwrap();
+    output
+}
+
+/// Append a rendered declaration to `output`, surrounded by its trivia.
+///
+/// Leading comments and blank lines go before the declaration, the trailing
+/// comment at the end of its first line (after any decorators), and dangling
+/// comments before its closing brace.
+fn push_with_trivia(
+    output: &mut String,
+    indent: &str,
+    config: &PrettyConfig,
+    trivia: &Trivia,
+    rendered: &str,
+) {
+    write_trivia_items(output, indent, &trivia.leading);
+
+    let mut rendered = rendered.to_string();
+    if let Some(comment) = &trivia.trailing {
+        let mut offset = 0;
+        for line in rendered.split_inclusive('\n') {
+            if !line.trim_start().starts_with('@') {
+                offset += line.trim_end_matches('\n').len();
+                break;
+            }
+            offset += line.len();
+        }
+        rendered.insert_str(offset, &format!(" {}", comment));
+    }
+
+    if !trivia.dangling.is_empty() && rendered.ends_with("}\n") {
+        let closing_line = rendered[..rendered.len() - 1].rfind('\n').map_or(0, |i| i + 1);
+        let mut dangling = String::new();
+        write_trivia_items(&mut dangling, &format!("{}{}", indent, config.indent), &trivia.dangling);
+        rendered.insert_str(closing_line, &dangling);
+    }
+
+    output.push_str(&rendered);
+}
+
+/// Write comments and blank lines, never doubling a blank line or starting
+/// a block with one.
+fn write_trivia_items(output: &mut String, indent: &str, items: &[TriviaItem]) {
+    for item in items {
+        match item {
+            TriviaItem::BlankLine => {
+                if !output.is_empty() && !output.ends_with("\n\n") && !output.ends_with("{\n") {
+                    output.push('\n');
+                }
+            }
+            TriviaItem::Comment(text) => writeln!(output, "{}{}", indent, text).unwrap(),
+        }
+    }
+}
+
+/// Merge the trivia of an objects list, which is printed on a single line.
+fn objects_trivia(objects: &[ObjectDecl]) -> Trivia {
+    Trivia {
+        leading: objects.iter().flat_map(|o| o.trivia.leading.iter().cloned()).collect(),
+        trailing: objects.iter().find_map(|o| o.trivia.trailing.clone()),
+        dangling: Vec::new(),
+    }
+}
+
+/// Quote a string as a literal, escaping its quotes and the backslashes
+/// that would otherwise read as an escape.
+pub(crate) fn quote(text: &str) -> String {
+    let mut quoted = String::from('"');
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' if matches!(chars.peek(), None | Some('"' | '\\')) => quoted.push_str("\\\\"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Format field constraints as a trailing ` [min: 0, pattern: "..."]` list.
+fn format_constraints(constraints: &[ConstraintDecl]) -> String {
+    if constraints.is_empty() {
+        return String::new();
+    }
+    let items: Vec<_> = constraints
+        .iter()
+        .map(|c| format!("{}: {}", c.name, c.value))
+        .collect();
+    format!(" [{}]", items.join(", "))
+}
+
+/// Write each decorator on its own line, e.g. `@removed_in("3.0")`.
+fn write_decorators(output: &mut String, indent: &str, decorators: &[DecoratorDecl]) {
+    for decorator in decorators {
+        if !decorator.names.is_empty() {
+            let names = decorator.names.join(", ");
+            writeln!(output, "{}@{}({})", indent, decorator.name, names).unwrap();
+            continue;
+        }
+        match (&decorator.key, &decorator.argument) {
+            (Some(key), Some(arg)) => {
+                writeln!(output, "{}@{}({}: {})", indent, decorator.name, key, arg).unwrap()
+            }
+            (None, Some(arg)) => writeln!(output, "{}@{}({})", indent, decorator.name, arg).unwrap(),
+            (_, None) => writeln!(output, "{}@{}", indent, decorator.name).unwrap(),
+        }
+    }
+}
+
 /// Format a type expression to a string.
 pub fn format_type_expr(type_expr: &TypeExpr) -> String {
     match type_expr {
@@ -382,7 +772,7 @@ pub fn format_expr(expr: &Expr) -> String {
                 format!("{}", n)
             }
         }
-        Expr::String(s) => format!("\"{}\"", s),
+        Expr::String(s) => quote(s),
         Expr::Path(path) => format_path(path),
         Expr::BinaryOp { left, op, right } => {
             let op_str = match op {
@@ -398,7 +788,19 @@ pub fn format_expr(expr: &Expr) -> String {
                 BinaryOperator::Gt => ">",
                 BinaryOperator::Ge => ">=",
             };
-            format!("{} {} {}", format_expr(left), op_str, format_expr(right))
+            // Parenthesize operands that bind more loosely than `op`
+            let operand = |expr: &Expr, min_precedence: u8| match expr {
+                Expr::BinaryOp { op, .. } if precedence(*op) < min_precedence => {
+                    format!("({})", format_expr(expr))
+                }
+                _ => format_expr(expr),
+            };
+            format!(
+                "{} {} {}",
+                operand(left, precedence(*op)),
+                op_str,
+                operand(right, precedence(*op) + 1)
+            )
         }
         Expr::UnaryOp { op, operand } => {
             let op_str = match op {
@@ -417,12 +819,47 @@ pub fn format_expr(expr: &Expr) -> String {
     }
 }
 
+/// Binding strength of a binary operator; higher binds tighter.
+fn precedence(op: BinaryOperator) -> u8 {
+    match op {
+        BinaryOperator::Mul | BinaryOperator::Div | BinaryOperator::Mod => 3,
+        BinaryOperator::Add | BinaryOperator::Sub => 2,
+        _ => 1,
+    }
+}
+
 impl Display for File {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.pretty_print())
     }
 }
 
+impl PrettyPrint for DomainDecl {
+    fn pretty_print_with_config(&self, config: &PrettyConfig) -> String {
+        let mut output = String::new();
+        let indent = &config.indent;
+        let inner_indent = format!("{}{}", indent, indent);
+
+        writeln!(output, "domain {} {{", self.name).unwrap();
+
+        for subdomain in &self.subdomains {
+            let mut rendered = String::new();
+            write_decorators(&mut rendered, indent, &subdomain.decorators);
+            if subdomain.contexts.is_empty() {
+                writeln!(rendered, "{}subdomain {} {{ }}", indent, subdomain.name).unwrap();
+            } else {
+                writeln!(rendered, "{}subdomain {} {{", indent, subdomain.name).unwrap();
+                writeln!(rendered, "{}contexts {{ {} }}", inner_indent, subdomain.contexts.join(", ")).unwrap();
+                writeln!(rendered, "{}}}", indent).unwrap();
+            }
+            push_with_trivia(&mut output, indent, config, &subdomain.trivia, &rendered);
+        }
+
+        writeln!(output, "}}").unwrap();
+        output
+    }
+}
+
 impl Display for ContextDecl {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.pretty_print())
@@ -435,6 +872,12 @@ impl Display for ContextMapDecl {
     }
 }
 
+impl Display for SharedKernelDecl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.pretty_print())
+    }
+}
+
 impl Display for TypeExpr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", format_type_expr(self))
@@ -520,6 +963,358 @@ mod tests {
         assert!(output.contains("enum OrderStatus = Pending | Confirmed | Shipped"));
     }
 
+    #[test]
+    fn test_pretty_print_process() {
+        let source = r#"
+            context Fulfillment {
+                process FulfillmentSaga { on OrderPlaced -> ReserveStock; on StockReserved -> Ship }
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let output = file.pretty_print();
+
+        assert!(output.contains("process FulfillmentSaga {"));
+        assert!(output.contains("on OrderPlaced -> ReserveStock"));
+        assert!(output.contains("on StockReserved -> Ship"));
+        // Output must parse back to the same process
+        let reparsed = parse_file(&output).unwrap();
+        assert_eq!(reparsed.contexts[0].processes[0].steps.len(), 2);
+    }
+
+    #[test]
+    fn test_pretty_print_event_storming() {
+        let source = r#"
+            context Fulfillment {
+                actor Customer { PlaceOrder, CancelOrder }
+                policy ReserveOnPlacement { on OrderPlaced -> ReserveStock }
+                hotspot ReserveStock "What if stock runs out?"
+                hotspot "Who pays for returns?"
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let output = file.pretty_print();
+
+        assert!(output.contains("  actor Customer { PlaceOrder, CancelOrder }\n"));
+        assert!(output.contains("  policy ReserveOnPlacement { on OrderPlaced -> ReserveStock }\n"));
+        assert!(output.contains("  hotspot ReserveStock \"What if stock runs out?\"\n"));
+        assert!(output.contains("  hotspot \"Who pays for returns?\"\n"));
+        let reparsed = parse_file(&output).unwrap();
+        assert_eq!(reparsed.contexts[0].hotspots.len(), 2);
+    }
+
+    #[test]
+    fn test_pretty_print_lifecycle() {
+        let source = r#"
+            context Commerce {
+                lifecycle Order: OrderStatus { Pending -> Confirmed -> Shipped; Pending -> Cancelled; default: Imported }
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let output = file.pretty_print();
+
+        assert!(output.contains("lifecycle Order: OrderStatus {"));
+        assert!(output.contains("Pending -> Confirmed -> Shipped"));
+        assert!(output.contains("Pending -> Cancelled"));
+        assert!(output.contains("default: Imported\n"));
+        let reparsed = parse_file(&output).unwrap();
+        let lifecycle = &reparsed.contexts[0].lifecycles[0];
+        assert_eq!(lifecycle.defaults, vec!["Imported"]);
+    }
+
+    #[test]
+    fn test_pretty_print_derive_block() {
+        let source = r#"
+            context Commerce {
+                derive total: Order -> Money = (subtotal - discount) * (1 + taxRate)
+                derive size: Order -> Count = count(items)
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let output = file.pretty_print();
+
+        assert!(output.contains("  derive total: Order -> Money = (subtotal - discount) * (1 + taxRate)\n"));
+        assert!(output.contains("  derive size: Order -> Count = count(items)\n"));
+
+        let reparsed = parse_file(&output).unwrap();
+        assert_eq!(reparsed.pretty_print(), output);
+    }
+
+    #[test]
+    fn test_pretty_print_invariant_modes() {
+        let source = r#"
+            context Commerce {
+                aggregate Order {
+                    invariant: total >= 0
+                    invariant eventual: customer.active == 1
+                }
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let output = file.pretty_print();
+
+        assert!(output.contains("    invariant: total >= 0\n"));
+        assert!(output.contains("    invariant eventual: customer.active = 1\n"));
+    }
+
+    #[test]
+    fn test_pretty_print_template() {
+        let source = r#"// Stamped out per entity
+template Crud<T> {
+  morphisms {
+    // audit trail
+    createdAt: T -> Timestamp
+  }
+}
+
+context Catalog {
+  use Crud<Product> // adds createdAt
+  entity Product
+}
+"#;
+        let file = parse_file(source).unwrap();
+        assert_eq!(file.pretty_print().trim_end(), source.trim_end());
+    }
+
+    #[test]
+    fn test_pretty_print_domain() {
+        let source = r#"context Sales {
+}
+
+// Strategic design
+domain Commerce {
+  @core
+  subdomain Ordering { // where the money is
+    contexts { Sales, Checkout }
+  }
+  subdomain Identity { }
+}
+"#;
+        let file = parse_file(source).unwrap();
+        let subdomains = &file.domains[0].subdomains;
+        assert_eq!(subdomains[0].contexts, vec!["Sales", "Checkout"]);
+        assert_eq!(subdomains[0].decorators[0].name, "core");
+        assert_eq!(file.pretty_print().trim_end(), source.trim_end());
+    }
+
+    #[test]
+    fn test_pretty_print_shared_kernel() {
+        let source = r#"
+            shared kernel Identity {
+                objects { UserId, TenantId }
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let output = file.pretty_print();
+
+        assert!(output.contains("shared kernel Identity {"));
+        assert!(output.contains("objects { UserId, TenantId }"));
+    }
+
+    #[test]
+    fn test_pretty_print_field_constraints() {
+        let source = r#"
+            context Billing {
+                value Money {
+                    amount: Decimal [min: 0, max: 100.5]
+                    currency: String [pattern: "[A-Z]{3}"]
+                }
+                morphisms {
+                    total: Invoice -> Decimal [min: 0]
+                }
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let output = file.pretty_print();
+
+        assert!(output.contains("amount: Decimal [min: 0, max: 100.5]"));
+        assert!(output.contains("currency: String [pattern: \"[A-Z]{3}\"]"));
+        assert!(output.contains("total: Invoice -> Decimal [min: 0]"));
+
+        // Output parses back to the same constraints
+        let reparsed = parse_file(&output).unwrap();
+        let values = |f: &File| -> Vec<ConstraintValue> {
+            f.contexts[0].value_objects[0].fields[0]
+                .constraints
+                .iter()
+                .map(|c| c.value.clone())
+                .collect()
+        };
+        assert_eq!(values(&reparsed), values(&file));
+    }
+
+    #[test]
+    fn test_pretty_print_decorators() {
+        let source = r#"
+            @version(2)
+            context Billing {
+                @removed_in("3.0")
+                entity LegacyInvoice
+                value Money {
+                    @removed_in("3.0")
+                    currencyName: String
+                }
+                @core
+                @meta(owner: "billing")
+                aggregate LegacyInvoice { }
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let output = file.pretty_print();
+
+        assert!(output.starts_with("@version(2)\ncontext Billing {"));
+        assert!(output.contains("  @core\n  @meta(owner: \"billing\")\n  aggregate LegacyInvoice {"));
+        assert!(output.contains("  @removed_in(\"3.0\")\n  entity LegacyInvoice"));
+        assert!(output.contains("    @removed_in(\"3.0\")\n    currencyName: String"));
+
+        // Output parses back to the same decorators
+        let reparsed = parse_file(&output).unwrap();
+        let decorators = |f: &File| -> Vec<(String, Option<String>, Option<ConstraintValue>)> {
+            let ctx = &f.contexts[0];
+            ctx.decorators
+                .iter()
+                .chain(&ctx.entities[0].decorators)
+                .chain(&ctx.value_objects[0].fields[0].decorators)
+                .chain(&ctx.aggregates[0].decorators)
+                .map(|d| (d.name.clone(), d.key.clone(), d.argument.clone()))
+                .collect()
+        };
+        assert_eq!(decorators(&reparsed), decorators(&file));
+    }
+
+    #[test]
+    fn test_pretty_print_preserves_comments() {
+        let source = r#"// Sales model
+
+/// Core context
+context Commerce { // core domain
+  objects { Customer, Order }
+
+  // People who buy things
+  entity Customer {
+    name: String // display name
+
+    email: String
+    /* more fields later */
+  }
+
+  morphisms {
+    placedBy: Order -> Customer // who placed it
+  }
+}
+
+map CommerceToShipping: Commerce -> Shipping {
+  mappings {
+    // the main mapping
+    Order -> Shipment
+  }
+}
+// end
+"#;
+        let file = parse_file(source).unwrap();
+        let output = file.pretty_print();
+
+        assert_eq!(output, source);
+
+        // Printing is stable across round trips
+        let reparsed = parse_file(&output).unwrap();
+        assert_eq!(reparsed.pretty_print(), output);
+    }
+
+    #[test]
+    fn test_pretty_print_preserves_map_comments() {
+        let source = r#"map CommerceToShipping: Commerce -> Shipping { // shipping
+  // they set the terms
+  pattern: Conformist // for now
+  mappings { // objects
+    Order -> Shipment: "the \"main\" one"
+    Customer -> Recipient: "under C:\\"
+    // more to come
+  }
+  morphism_mappings {
+    placedBy -> sentTo: "matches [a-z]\d+"
+  }
+}
+"#;
+        let file = parse_file(source).unwrap();
+        let map = &file.context_maps[0];
+        assert_eq!(map.pattern_clause.trivia.trailing.as_deref(), Some("// for now"));
+        assert!(map.object_mappings[0].trivia.is_empty());
+        assert_eq!(
+            map.object_mappings[0].description.as_deref(),
+            Some("the \"main\" one")
+        );
+        assert_eq!(map.object_mappings[1].description.as_deref(), Some("under C:\\"));
+
+        let output = file.pretty_print();
+        assert_eq!(output, source);
+        assert_eq!(parse_file(&output).unwrap().pretty_print(), output);
+    }
+
+    #[test]
+    fn test_pretty_print_preserves_comments_at_end_of_file() {
+        for source in [
+            "context Sales {\n  entity Order\n}\n// end\n",
+            "context Sales {\n  entity Order\n}\n\n// end\n\n// really\n",
+            "context Sales {\n  entity Order\n}\n\n/* end */\n\n\n",
+        ] {
+            let output = parse_file(source).unwrap().pretty_print();
+            assert_eq!(output, source.trim_end_matches('\n').to_string() + "\n");
+            assert_eq!(parse_file(&output).unwrap().pretty_print(), output);
+        }
+    }
+
+    #[test]
+    fn test_pretty_print_escapes_descriptions() {
+        let mut map = ContextMapDecl::new("SalesToBilling", "Sales", "Billing");
+        map.object_mappings.push(ObjectMappingDecl {
+            source: "Order".into(),
+            target: "Invoice".into(),
+            description: Some(r#"a "paid" order, in C:\"#.into()),
+            trivia: Trivia::default(),
+            span: Span::default(),
+        });
+        let file = File {
+            context_maps: vec![map],
+            ..File::default()
+        };
+
+        let output = file.pretty_print();
+        assert!(output.contains(r#"Order -> Invoice: "a \"paid\" order, in C:\\""#));
+        let reparsed = parse_file(&output).unwrap();
+        assert_eq!(
+            reparsed.context_maps[0].object_mappings[0].description,
+            file.context_maps[0].object_mappings[0].description
+        );
+    }
+
+    #[test]
+    fn test_pretty_print_preserves_source_order() {
+        let source = r#"context Commerce {
+  value Money {
+    amount: Decimal
+  }
+
+  objects { Customer }
+
+  entity Customer {
+    name: String
+  }
+
+  // Orders come later
+  objects { Order }
+
+  process FulfillmentSaga {
+    on OrderPlaced -> ReserveStock;
+    on StockReserved -> Ship
+  }
+}
+"#;
+        let file = parse_file(source).unwrap();
+        let output = file.pretty_print();
+
+        assert_eq!(output, source);
+    }
+
     #[test]
     fn test_pretty_print_context_map() {
         let source = r#"
@@ -0,0 +1,318 @@
+//! Template expansion.
+//!
+//! A template is a context body parameterized by type names:
+//!
+//! ```text
+//! template Audited<T> {
+//!   morphisms {
+//!     createdAt: T -> Timestamp
+//!   }
+//! }
+//! ```
+//!
+//! Each `use Audited<Product>` in a context stamps out a copy of the
+//! template's declarations with every occurrence of a parameter as a type
+//! name replaced by the corresponding argument. Expansion happens on the
+//! AST, before transformation, so instantiated declarations behave exactly
+//! as if they had been written in the context by hand.
+
+use std::collections::{HashMap, HashSet};
+
+use sketchddd_core::did_you_mean;
+
+use crate::ast::{ContextDecl, File, TemplateDecl, TypeExpr, UseDecl};
+use crate::error::ParseError;
+
+/// Expand every template instantiation in the file.
+///
+/// Returns a copy of the file whose contexts contain the instantiated
+/// declarations in place of their `use` declarations.
+pub fn expand_templates(file: &File) -> Result<File, ParseError> {
+    let templates: HashMap<&str, &TemplateDecl> =
+        file.templates.iter().map(|t| (t.name.as_str(), t)).collect();
+
+    let mut expanded = file.clone();
+    for ctx in &mut expanded.contexts {
+        expand_context(ctx, &templates, &mut Vec::new())?;
+    }
+
+    Ok(expanded)
+}
+
+/// Replace the `use` declarations of a context body with the declarations
+/// they instantiate, expanding nested instantiations.
+///
+/// `stack` holds the templates currently being instantiated, to reject
+/// templates that instantiate themselves.
+fn expand_context<'a>(
+    ctx: &mut ContextDecl,
+    templates: &HashMap<&str, &'a TemplateDecl>,
+    stack: &mut Vec<&'a str>,
+) -> Result<(), ParseError> {
+    for use_decl in std::mem::take(&mut ctx.uses) {
+        let template = lookup(templates, &use_decl)?;
+        if stack.contains(&template.name.as_str()) {
+            return Err(ParseError::new(format!(
+                "Template '{}' instantiates itself",
+                template.name
+            ))
+            .with_location(use_decl.span.line, use_decl.span.column));
+        }
+
+        let mut body = instantiate(template, &use_decl)?;
+        stack.push(&template.name);
+        expand_context(&mut body, templates, stack)?;
+        stack.pop();
+
+        merge(ctx, body);
+    }
+
+    Ok(())
+}
+
+/// Find the template a `use` declaration refers to.
+fn lookup<'a>(
+    templates: &HashMap<&str, &'a TemplateDecl>,
+    use_decl: &UseDecl,
+) -> Result<&'a TemplateDecl, ParseError> {
+    templates.get(use_decl.template.as_str()).copied().ok_or_else(|| {
+        let mut names: Vec<&str> = templates.keys().copied().collect();
+        names.sort_unstable();
+        let mut message = format!("Unknown template '{}'", use_decl.template);
+        if let Some(help) = did_you_mean(&use_decl.template, &names) {
+            message.push_str(&format!("; {}", help));
+        }
+        ParseError::new(message).with_location(use_decl.span.line, use_decl.span.column)
+    })
+}
+
+/// Copy a template's body with its parameters bound to the arguments of
+/// a `use` declaration.
+fn instantiate(template: &TemplateDecl, use_decl: &UseDecl) -> Result<ContextDecl, ParseError> {
+    if template.params.len() != use_decl.args.len() {
+        return Err(ParseError::new(format!(
+            "Template '{}' expects {} type argument(s) but {} were given",
+            template.name,
+            template.params.len(),
+            use_decl.args.len()
+        ))
+        .with_location(use_decl.span.line, use_decl.span.column));
+    }
+
+    let bindings: HashMap<&str, &str> = template
+        .params
+        .iter()
+        .map(String::as_str)
+        .zip(use_decl.args.iter().map(String::as_str))
+        .collect();
+
+    let mut body = template.body.clone();
+    substitute(&mut body, &bindings);
+    Ok(body)
+}
+
+/// Replace template parameters used as type names throughout a body.
+fn substitute(body: &mut ContextDecl, bindings: &HashMap<&str, &str>) {
+    let rename = |name: &mut String| {
+        if let Some(arg) = bindings.get(name.as_str()) {
+            *name = arg.to_string();
+        }
+    };
+
+    for obj in &mut body.objects {
+        rename(&mut obj.name);
+    }
+    for entity in &mut body.entities {
+        rename(&mut entity.name);
+        for field in &mut entity.fields {
+            substitute_type(&mut field.type_expr, &rename);
+        }
+    }
+    for morph in &mut body.morphisms {
+        substitute_type(&mut morph.source, &rename);
+        substitute_type(&mut morph.target, &rename);
+    }
+    for derivation in &mut body.derivations {
+        substitute_type(&mut derivation.source, &rename);
+        substitute_type(&mut derivation.target, &rename);
+    }
+    for agg in &mut body.aggregates {
+        rename(&mut agg.name);
+        if let Some(root) = &mut agg.root {
+            rename(root);
+        }
+        agg.contains.iter_mut().for_each(rename);
+    }
+    for vo in &mut body.value_objects {
+        rename(&mut vo.name);
+        for field in &mut vo.fields {
+            substitute_type(&mut field.type_expr, &rename);
+        }
+    }
+    for enum_decl in &mut body.enums {
+        rename(&mut enum_decl.name);
+        for variant in &mut enum_decl.variants {
+            for payload in &mut variant.payload {
+                substitute_type(payload, &rename);
+            }
+        }
+    }
+    for eq in &mut body.equations {
+        eq.lhs.components.iter_mut().for_each(rename);
+        eq.rhs.components.iter_mut().for_each(rename);
+    }
+    for process in &mut body.processes {
+        for step in &mut process.steps {
+            rename(&mut step.event);
+            rename(&mut step.command);
+        }
+    }
+    for actor in &mut body.actors {
+        actor.commands.iter_mut().for_each(rename);
+    }
+    for policy in &mut body.policies {
+        rename(&mut policy.event);
+        policy.commands.iter_mut().for_each(rename);
+    }
+    for hotspot in &mut body.hotspots {
+        if let Some(target) = &mut hotspot.target {
+            rename(target);
+        }
+    }
+    for lifecycle in &mut body.lifecycles {
+        rename(&mut lifecycle.name);
+        if let Some(enum_name) = &mut lifecycle.enum_name {
+            rename(enum_name);
+        }
+    }
+    for use_decl in &mut body.uses {
+        use_decl.args.iter_mut().for_each(rename);
+    }
+}
+
+fn substitute_type(type_expr: &mut TypeExpr, rename: &impl Fn(&mut String)) {
+    match type_expr {
+        TypeExpr::Simple(name) => rename(name),
+        TypeExpr::Generic { name, args } => {
+            rename(name);
+            for arg in args {
+                substitute_type(arg, rename);
+            }
+        }
+        TypeExpr::Optional(inner) => substitute_type(inner, rename),
+    }
+}
+
+/// Append the declarations of an instantiated body to a context.
+///
+/// Types the context already declares, by hand or through an earlier
+/// instantiation, are shared rather than declared twice.
+fn merge(ctx: &mut ContextDecl, body: ContextDecl) {
+    let declared: HashSet<String> = ctx
+        .objects
+        .iter()
+        .map(|o| o.name.clone())
+        .chain(ctx.entities.iter().map(|e| e.name.clone()))
+        .chain(ctx.value_objects.iter().map(|v| v.name.clone()))
+        .chain(ctx.enums.iter().map(|e| e.name.clone()))
+        .collect();
+    let is_new = |name: &String| !declared.contains(name);
+
+    ctx.objects.extend(body.objects.into_iter().filter(|o| is_new(&o.name)));
+    ctx.entities.extend(body.entities.into_iter().filter(|e| is_new(&e.name)));
+    ctx.value_objects.extend(body.value_objects.into_iter().filter(|v| is_new(&v.name)));
+    ctx.enums.extend(body.enums.into_iter().filter(|e| is_new(&e.name)));
+    ctx.morphisms.extend(body.morphisms);
+    ctx.derivations.extend(body.derivations);
+    ctx.aggregates.extend(body.aggregates);
+    ctx.equations.extend(body.equations);
+    ctx.processes.extend(body.processes);
+    ctx.actors.extend(body.actors);
+    ctx.policies.extend(body.policies);
+    ctx.hotspots.extend(body.hotspots);
+    ctx.lifecycles.extend(body.lifecycles);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_file;
+
+    #[test]
+    fn test_expand_templates() {
+        let source = r#"
+            template CrudAggregate<T> {
+                objects { Timestamp }
+                entity T
+                morphisms {
+                    createdAt: T -> Timestamp
+                    history: T -> List<T>
+                }
+                aggregate T {
+                    root: T
+                }
+            }
+
+            context Catalog {
+                entity Product
+                entity Supplier
+                use CrudAggregate<Product>
+                use CrudAggregate<Supplier>
+            }
+        "#;
+        let file = expand_templates(&parse_file(source).unwrap()).unwrap();
+        let ctx = &file.contexts[0];
+
+        assert!(ctx.uses.is_empty());
+        assert_eq!(ctx.objects.len(), 1);
+        assert_eq!(ctx.entities.len(), 2);
+        assert_eq!(ctx.morphisms.len(), 4);
+        assert_eq!(ctx.morphisms[0].source, TypeExpr::simple("Product"));
+        assert_eq!(
+            ctx.morphisms[1].target,
+            TypeExpr::generic("List", TypeExpr::simple("Product"))
+        );
+        assert_eq!(ctx.morphisms[2].source, TypeExpr::simple("Supplier"));
+        let roots: Vec<_> = ctx.aggregates.iter().map(|a| a.root.as_deref()).collect();
+        assert_eq!(roots, vec![Some("Product"), Some("Supplier")]);
+    }
+
+    #[test]
+    fn test_expand_nested_templates() {
+        let source = r#"
+            template Audited<T> {
+                morphisms {
+                    createdAt: T -> Timestamp
+                }
+            }
+            template Crud<T> {
+                use Audited<T>
+                aggregate T { root: T }
+            }
+            context Catalog {
+                entity Product
+                use Crud<Product>
+            }
+        "#;
+        let file = expand_templates(&parse_file(source).unwrap()).unwrap();
+        let ctx = &file.contexts[0];
+
+        assert_eq!(ctx.aggregates[0].name, "Product");
+        assert_eq!(ctx.morphisms[0].source, TypeExpr::simple("Product"));
+    }
+
+    #[test]
+    fn test_expand_template_errors() {
+        let unknown = parse_file("context A { use Crud<Product> }").unwrap();
+        let err = expand_templates(&unknown).unwrap_err();
+        assert!(err.message.contains("Unknown template 'Crud'"));
+
+        let arity = parse_file("template Crud<T> { } context A { use Crud<A, B> }").unwrap();
+        let err = expand_templates(&arity).unwrap_err();
+        assert!(err.message.contains("expects 1 type argument(s) but 2 were given"));
+
+        let cyclic = parse_file("template Loop<T> { use Loop<T> } context A { use Loop<A> }").unwrap();
+        let err = expand_templates(&cyclic).unwrap_err();
+        assert!(err.message.contains("Template 'Loop' instantiates itself"));
+    }
+}
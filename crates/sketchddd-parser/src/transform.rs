@@ -4,17 +4,20 @@
 //! `sketchddd-core`. The transformation validates references and constructs
 //! the categorical representation of the domain model.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use sketchddd_core::{
-    BoundedContext, NamedContextMap, NamedMorphismMapping, NamedObjectMapping, RelationshipPattern,
+    did_you_mean, BoundedContext, Domain, Enforcement, FieldConstraint, Fix, Hotspot, Metadata, MetadataValue, ModelVersion,
+    NamedContextMap, NamedMorphismMapping, NamedObjectMapping, RelationshipPattern, Subdomain, TextEdit, Workspace,
 };
 
 use crate::ast::{
-    AggregateDecl, ContextDecl, ContextMapDecl, EnumDecl, EquationDecl, File,
-    MorphismDecl, ValueObjectDecl,
+    split_qualified_name, ActorDecl, AggregateDecl, BinaryOperator, ConstraintDecl, ConstraintValue, ContextDecl,
+    ContextMapDecl, DecoratorDecl, DerivationDecl, DomainDecl, EnumDecl, EquationDecl, File, InvariantMode, LifecycleDecl, MorphismDecl, ObjectDecl,
+    HotspotDecl, PolicyDecl, ProcessDecl, SharedKernelDecl, Span, Trivia, TypeExpr, UnaryOperator, ValueObjectDecl,
 };
 use crate::error::ParseError;
+use crate::template::expand_templates;
 
 /// Result of transforming an AST to a semantic model.
 #[derive(Debug)]
@@ -23,10 +26,28 @@ pub struct TransformResult {
     pub contexts: Vec<BoundedContext>,
     /// Context maps between contexts (using named mappings)
     pub context_maps: Vec<NamedContextMap>,
+    /// Shared kernels, as contexts holding only the shared objects
+    pub shared_kernels: Vec<BoundedContext>,
+    /// Domains grouping the contexts into subdomains
+    pub domains: Vec<Domain>,
     /// Warnings encountered during transformation
     pub warnings: Vec<TransformWarning>,
 }
 
+impl TransformResult {
+    /// Collect the transformed model into a [`Workspace`], dropping the
+    /// warnings.
+    pub fn into_workspace(self) -> Workspace {
+        Workspace {
+            contexts: self.contexts,
+            context_maps: self.context_maps,
+            shared_kernels: self.shared_kernels,
+            domains: self.domains,
+            ..Default::default()
+        }
+    }
+}
+
 /// A warning encountered during transformation.
 #[derive(Debug, Clone)]
 pub struct TransformWarning {
@@ -36,6 +57,8 @@ pub struct TransformWarning {
     pub line: Option<u32>,
     /// Column number where the warning occurred
     pub column: Option<u32>,
+    /// Edits to the source that address the warning
+    pub fix: Option<Fix>,
 }
 
 impl TransformWarning {
@@ -45,6 +68,7 @@ impl TransformWarning {
             message: message.into(),
             line: None,
             column: None,
+            fix: None,
         }
     }
 
@@ -54,37 +78,207 @@ impl TransformWarning {
         self.column = Some(column);
         self
     }
+
+    /// Add a fix to the warning.
+    pub fn with_fix(mut self, fix: Fix) -> Self {
+        self.fix = Some(fix);
+        self
+    }
 }
 
 /// Transform an AST File into a semantic model.
+///
+/// Template instantiations are expanded first, see [`expand_templates`].
 pub fn transform(file: &File) -> Result<TransformResult, ParseError> {
+    let file = &expand_templates(file)?;
     let mut result = TransformResult {
         contexts: Vec::new(),
         context_maps: Vec::new(),
+        shared_kernels: Vec::new(),
+        domains: Vec::new(),
         warnings: Vec::new(),
     };
 
+    let symbols = SymbolTable::new(file);
+
+    // Shared kernels come first so contexts can include their objects
+    for kernel_decl in &file.shared_kernels {
+        let kernel = transform_shared_kernel(kernel_decl, &symbols, &mut result.warnings)?;
+        result.shared_kernels.push(kernel);
+    }
+
     // First pass: transform all contexts
     let mut context_lookup: HashMap<String, usize> = HashMap::new();
 
     for context_decl in &file.contexts {
-        let ctx = transform_context(context_decl, &mut result.warnings)?;
+        let mut ctx = transform_context(context_decl, &result.shared_kernels, &mut result.warnings)?;
+
+        // Objects a kernel takes from this context are shared by it too
+        for kernel_decl in &file.shared_kernels {
+            for obj in &kernel_decl.objects {
+                if let (Some(qualifier), name) = split_qualified_name(&obj.name) {
+                    if qualifier != context_decl.name {
+                        continue;
+                    }
+                    if let Some(id) = ctx.graph().find_object_by_name(name).map(|o| o.id) {
+                        ctx.mark_shared_object(id, &kernel_decl.name);
+                    }
+                }
+            }
+        }
+
         context_lookup.insert(ctx.name().to_string(), result.contexts.len());
         result.contexts.push(ctx);
     }
 
     // Second pass: transform context maps
     for map_decl in &file.context_maps {
-        let ctx_map = transform_context_map(map_decl, &context_lookup, &mut result.warnings)?;
+        let ctx_map = transform_context_map(
+            map_decl,
+            &symbols,
+            &result.contexts,
+            &context_lookup,
+            &mut result.warnings,
+        )?;
         result.context_maps.push(ctx_map);
     }
 
+    // Third pass: group contexts into subdomains
+    for domain_decl in &file.domains {
+        result.domains.push(transform_domain(domain_decl)?);
+    }
+
+    // Contexts without a classification of their own take their subdomain's
+    for ctx in &mut result.contexts {
+        let inherited = result
+            .domains
+            .iter()
+            .find_map(|d| d.subdomain_of(ctx.name()))
+            .and_then(|s| s.classification());
+        if ctx.metadata().classification.is_none() {
+            ctx.metadata_mut().classification = inherited;
+        }
+    }
+
     Ok(result)
 }
 
+/// Transform a domain declaration.
+///
+/// Subdomains may refer to contexts declared anywhere in the model, so
+/// unknown contexts are left to [`sketchddd_core::validate_domains`].
+fn transform_domain(decl: &DomainDecl) -> Result<Domain, ParseError> {
+    let mut domain = Domain::new(&decl.name);
+    for subdomain_decl in &decl.subdomains {
+        let mut subdomain = Subdomain::new(&subdomain_decl.name);
+        for decorator in &subdomain_decl.decorators {
+            if !METADATA_DECORATORS.contains(&decorator.name.as_str()) {
+                return Err(misplaced_decorator(decorator, "a subdomain"));
+            }
+            apply_metadata(&mut subdomain.metadata, decorator)?;
+        }
+        for context in &subdomain_decl.contexts {
+            subdomain.add_context(context);
+        }
+        domain.add_subdomain(subdomain);
+    }
+    Ok(domain)
+}
+
+/// Global symbol table: every context declared in the file, by name.
+///
+/// Used to resolve qualified names such as `Billing.Invoice`, which may
+/// refer to any context regardless of declaration order.
+struct SymbolTable<'a> {
+    contexts: HashMap<&'a str, &'a ContextDecl>,
+}
+
+impl<'a> SymbolTable<'a> {
+    fn new(file: &'a File) -> Self {
+        Self {
+            contexts: file.contexts.iter().map(|c| (c.name.as_str(), c)).collect(),
+        }
+    }
+
+    /// Look up a context, failing with a suggestion if it does not exist.
+    fn context(&self, name: &str, line: u32, column: u32) -> Result<&'a ContextDecl, ParseError> {
+        self.contexts.get(name).copied().ok_or_else(|| {
+            let mut names: Vec<&str> = self.contexts.keys().copied().collect();
+            names.sort_unstable();
+            let mut message = format!("Unknown context '{}'", name);
+            if let Some(help) = did_you_mean(name, &names) {
+                message.push_str(&format!("; {}", help));
+            }
+            ParseError::new(message).with_location(line, column)
+        })
+    }
+}
+
+/// Transform a shared kernel declaration into a context holding its objects.
+///
+/// Qualified objects such as `Auth.UserId` are copied from the named
+/// context, keeping their kind (entity, value object, enum or plain object).
+fn transform_shared_kernel(
+    decl: &SharedKernelDecl,
+    symbols: &SymbolTable<'_>,
+    warnings: &mut Vec<TransformWarning>,
+) -> Result<BoundedContext, ParseError> {
+    let mut context_decl = ContextDecl {
+        name: decl.name.clone(),
+        entities: decl.entities.clone(),
+        value_objects: decl.value_objects.clone(),
+        enums: decl.enums.clone(),
+        span: decl.span.clone(),
+        ..Default::default()
+    };
+
+    for obj in &decl.objects {
+        let (qualifier, name) = split_qualified_name(&obj.name);
+        let Some(qualifier) = qualifier else {
+            context_decl.objects.push(obj.clone());
+            continue;
+        };
+
+        let source = symbols.context(qualifier, obj.span.line, obj.span.column)?;
+        if let Some(entity) = source.entities.iter().find(|e| e.name == name) {
+            context_decl.entities.push(entity.clone());
+        } else if let Some(vo) = source.value_objects.iter().find(|v| v.name == name) {
+            context_decl.value_objects.push(vo.clone());
+        } else if let Some(enum_decl) = source.enums.iter().find(|e| e.name == name) {
+            context_decl.enums.push(enum_decl.clone());
+        } else if source.objects.iter().any(|o| o.name == name) {
+            context_decl.objects.push(ObjectDecl {
+                name: name.to_string(),
+                trivia: Trivia::default(),
+                span: obj.span.clone(),
+            });
+        } else {
+            let declared: Vec<&str> = source
+                .objects
+                .iter()
+                .map(|o| o.name.as_str())
+                .chain(source.entities.iter().map(|e| e.name.as_str()))
+                .chain(source.value_objects.iter().map(|v| v.name.as_str()))
+                .chain(source.enums.iter().map(|e| e.name.as_str()))
+                .collect();
+            let mut message = format!(
+                "Object '{}' is not declared in context '{}'",
+                name, qualifier
+            );
+            if let Some(help) = did_you_mean(name, &declared) {
+                message.push_str(&format!("; {}", help));
+            }
+            return Err(ParseError::new(message).with_location(obj.span.line, obj.span.column));
+        }
+    }
+
+    transform_context(&context_decl, &[], warnings)
+}
+
 /// Transform a single context declaration into a BoundedContext.
 fn transform_context(
     decl: &ContextDecl,
+    shared_kernels: &[BoundedContext],
     warnings: &mut Vec<TransformWarning>,
 ) -> Result<BoundedContext, ParseError> {
     let mut ctx = BoundedContext::new(&decl.name);
@@ -92,6 +286,9 @@ fn transform_context(
     // Track object names to IDs for morphism resolution
     let mut object_lookup: HashMap<String, sketchddd_core::sketch::ObjectId> = HashMap::new();
 
+    // 0. Include every shared kernel this context references
+    include_shared_kernels(&mut ctx, decl, shared_kernels, &mut object_lookup);
+
     // 1. Add all declared objects first
     for obj in &decl.objects {
         let id = ctx.sketch_mut().add_object(&obj.name);
@@ -117,9 +314,12 @@ fn transform_context(
         object_lookup.insert(enum_decl.name.clone(), id);
     }
 
-    // 5. Add morphisms
+    // 5. Add morphisms, including derived ones
     for morph in &decl.morphisms {
-        transform_morphism(&mut ctx, morph, &mut object_lookup, warnings)?;
+        transform_morphism(&mut ctx, decl, morph, &mut object_lookup, warnings)?;
+    }
+    for derivation in &decl.derivations {
+        transform_derivation(&mut ctx, decl, derivation, &mut object_lookup, warnings);
     }
 
     // 6. Define aggregates
@@ -132,9 +332,151 @@ fn transform_context(
         transform_equation(&mut ctx, eq, &object_lookup, warnings)?;
     }
 
+    // 8. Define processes (sagas) over events and commands
+    for process in &decl.processes {
+        transform_process(&mut ctx, decl, process, &mut object_lookup, warnings);
+    }
+
+    // 8b. Capture actors, policies and hotspots from event storming
+    transform_event_storming(&mut ctx, decl, &object_lookup)?;
+
+    // 9. Attach lifecycles (state machines) to enums
+    for lifecycle in &decl.lifecycles {
+        transform_lifecycle(&mut ctx, lifecycle)?;
+    }
+
+    // 10. Attach literal constraints to fields and morphisms, recording the
+    // types of fields, which have no morphisms of their own
+    let constrained_fields = decl
+        .entities
+        .iter()
+        .flat_map(|e| e.fields.iter().map(move |f| (e.name.as_str(), f)))
+        .chain(decl.value_objects.iter().flat_map(|v| v.fields.iter().map(move |f| (v.name.as_str(), f))))
+        .map(|(owner, f)| (owner, f.name.as_str(), &f.constraints, Some(&f.type_expr)))
+        .chain(
+            decl.morphisms
+                .iter()
+                .map(|m| (m.source.base_name(), m.name.as_str(), &m.constraints, None)),
+        );
+    for (owner, field, constraints, type_expr) in constrained_fields {
+        if let Some(&id) = object_lookup.get(owner) {
+            for constraint in constraints {
+                ctx.constrain_field(id, field, transform_constraint(constraint)?);
+            }
+            if let Some(type_expr) = type_expr.filter(|_| !constraints.is_empty()) {
+                ctx.set_field_type(id, field, type_expr.target_name());
+            }
+        }
+    }
+
+    // 11. Apply versioning and metadata decorators
+    transform_versioning(&mut ctx, decl, &object_lookup)?;
+    transform_metadata(&mut ctx, decl, &object_lookup)?;
+    transform_suppressions(&mut ctx, decl)?;
+
     Ok(ctx)
 }
 
+/// Include the objects of each shared kernel referenced by the context.
+///
+/// A kernel is referenced when the context uses one of its type names
+/// without declaring it locally. Local declarations take precedence.
+fn include_shared_kernels(
+    ctx: &mut BoundedContext,
+    decl: &ContextDecl,
+    shared_kernels: &[BoundedContext],
+    object_lookup: &mut HashMap<String, sketchddd_core::sketch::ObjectId>,
+) {
+    let local_names: HashSet<&str> = decl
+        .objects
+        .iter()
+        .map(|o| o.name.as_str())
+        .chain(decl.entities.iter().map(|e| e.name.as_str()))
+        .chain(decl.value_objects.iter().map(|v| v.name.as_str()))
+        .chain(decl.enums.iter().map(|e| e.name.as_str()))
+        .collect();
+    let referenced = referenced_names(decl);
+
+    for kernel in shared_kernels {
+        let mut kernel_objects: Vec<_> = kernel.graph().objects().collect();
+        kernel_objects.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let is_referenced = kernel_objects
+            .iter()
+            .any(|o| referenced.contains(o.name.as_str()) && !local_names.contains(o.name.as_str()));
+        if !is_referenced {
+            continue;
+        }
+
+        for object in kernel_objects {
+            if local_names.contains(object.name.as_str()) || object_lookup.contains_key(&object.name) {
+                continue;
+            }
+            if let Some(id) = ctx.include_shared_object(kernel, object.id) {
+                object_lookup.insert(object.name.clone(), id);
+            }
+        }
+    }
+}
+
+/// Collect every type name a context declaration refers to.
+fn referenced_names(decl: &ContextDecl) -> HashSet<&str> {
+    fn collect<'a>(type_expr: &'a TypeExpr, names: &mut HashSet<&'a str>) {
+        match type_expr {
+            TypeExpr::Simple(name) => {
+                names.insert(name);
+            }
+            TypeExpr::Generic { name, args } => {
+                names.insert(name);
+                for arg in args {
+                    collect(arg, names);
+                }
+            }
+            TypeExpr::Optional(inner) => collect(inner, names),
+        }
+    }
+
+    let mut names = HashSet::new();
+    for (source, target) in decl
+        .morphisms
+        .iter()
+        .map(|m| (&m.source, &m.target))
+        .chain(decl.derivations.iter().map(|d| (&d.source, &d.target)))
+    {
+        collect(source, &mut names);
+        collect(target, &mut names);
+    }
+    for field in decl
+        .entities
+        .iter()
+        .flat_map(|e| &e.fields)
+        .chain(decl.value_objects.iter().flat_map(|v| &v.fields))
+    {
+        collect(&field.type_expr, &mut names);
+    }
+    for variant in decl.enums.iter().flat_map(|e| &e.variants) {
+        for payload in &variant.payload {
+            collect(payload, &mut names);
+        }
+    }
+    for agg in &decl.aggregates {
+        names.extend(agg.root.as_deref());
+        names.extend(agg.contains.iter().map(String::as_str));
+    }
+    for step in decl.processes.iter().flat_map(|p| &p.steps) {
+        names.insert(step.event.as_str());
+        names.insert(step.command.as_str());
+    }
+    names.extend(decl.actors.iter().flat_map(|a| &a.commands).map(String::as_str));
+    for policy in &decl.policies {
+        names.insert(policy.event.as_str());
+        names.extend(policy.commands.iter().map(String::as_str));
+    }
+    names.extend(decl.hotspots.iter().filter_map(|h| h.target.as_deref()));
+    names.extend(decl.lifecycles.iter().filter_map(|l| l.enum_name.as_deref()));
+    names
+}
+
 /// Transform a value object declaration.
 fn transform_value_object(
     ctx: &mut BoundedContext,
@@ -143,12 +485,12 @@ fn transform_value_object(
     warnings: &mut Vec<TransformWarning>,
 ) -> Result<sketchddd_core::sketch::ObjectId, ParseError> {
     // Get component types from fields
-    let mut component_ids = Vec::new();
+    let mut components = Vec::new();
 
     for field in &vo.fields {
         let type_name = field.type_expr.base_name();
         if let Some(&id) = object_lookup.get(type_name) {
-            component_ids.push(id);
+            components.push((field.name.clone(), id));
         } else {
             // Type not found - add as a new object
             warnings.push(
@@ -161,11 +503,11 @@ fn transform_value_object(
         }
     }
 
-    if component_ids.is_empty() {
+    if components.is_empty() {
         // Simple value object without explicit components
         Ok(ctx.add_value_object(&vo.name))
     } else {
-        Ok(ctx.add_value_object_with_components(&vo.name, &component_ids))
+        Ok(ctx.add_value_object_with_fields(&vo.name, components))
     }
 }
 
@@ -181,30 +523,122 @@ fn transform_enum(
 /// Transform a morphism declaration.
 fn transform_morphism(
     ctx: &mut BoundedContext,
+    decl: &ContextDecl,
     morph: &MorphismDecl,
     object_lookup: &mut HashMap<String, sketchddd_core::sketch::ObjectId>,
     warnings: &mut Vec<TransformWarning>,
 ) -> Result<sketchddd_core::sketch::MorphismId, ParseError> {
     // Resolve or create source type
     let source_name = morph.source.base_name();
-    let source_id = resolve_or_create_object(ctx, source_name, object_lookup, warnings, &morph.span);
+    let source_id = resolve_or_create_object(ctx, decl, source_name, object_lookup, warnings, &morph.span);
 
     // Resolve or create target type
-    let target_name = morph.target.base_name();
-    let target_id = resolve_or_create_object(ctx, target_name, object_lookup, warnings, &morph.span);
+    let target_name = morph.target.target_name();
+    let target_id = resolve_or_create_object(ctx, decl, target_name, object_lookup, warnings, &morph.span);
 
     // Add the morphism
     let morph_id = ctx
         .sketch_mut()
         .graph
         .add_morphism(&morph.name, source_id, target_id);
+    if matches!(morph.target, TypeExpr::Optional(_)) {
+        ctx.mark_optional(morph_id);
+    }
+    if let Some(collection) = morph.target.collection() {
+        ctx.mark_many(morph_id, collection);
+    }
 
     Ok(morph_id)
 }
 
+/// Transform a derived morphism: add it to the graph and record the
+/// expression computing it.
+fn transform_derivation(
+    ctx: &mut BoundedContext,
+    decl: &ContextDecl,
+    derivation: &DerivationDecl,
+    object_lookup: &mut HashMap<String, sketchddd_core::sketch::ObjectId>,
+    warnings: &mut Vec<TransformWarning>,
+) {
+    let source_id = resolve_or_create_object(
+        ctx,
+        decl,
+        derivation.source.base_name(),
+        object_lookup,
+        warnings,
+        &derivation.span,
+    );
+    let target_id = resolve_or_create_object(
+        ctx,
+        decl,
+        derivation.target.target_name(),
+        object_lookup,
+        warnings,
+        &derivation.span,
+    );
+
+    let morph_id = ctx
+        .sketch_mut()
+        .graph
+        .add_morphism(&derivation.name, source_id, target_id);
+    if let Some(collection) = derivation.target.collection() {
+        ctx.mark_many(morph_id, collection);
+    }
+    ctx.derive_morphism(morph_id, transform_expr(&derivation.expression));
+}
+
+/// Convert an AST expression to the core expression model.
+fn transform_expr(expr: &crate::ast::Expr) -> sketchddd_core::Expr {
+    use crate::ast::Expr;
+    use sketchddd_core::{BinaryOp, Expr as CoreExpr, UnaryOp};
+
+    let boxed = |expr: &Expr| Box::new(transform_expr(expr));
+    match expr {
+        Expr::Number(n) => CoreExpr::Number(*n),
+        Expr::String(s) => CoreExpr::Text(s.clone()),
+        Expr::Path(path) => CoreExpr::Path(path.components.clone()),
+        Expr::BinaryOp { left, op, right } => CoreExpr::Binary {
+            op: match op {
+                BinaryOperator::Add => BinaryOp::Add,
+                BinaryOperator::Sub => BinaryOp::Sub,
+                BinaryOperator::Mul => BinaryOp::Mul,
+                BinaryOperator::Div => BinaryOp::Div,
+                BinaryOperator::Mod => BinaryOp::Mod,
+                BinaryOperator::Eq => BinaryOp::Eq,
+                BinaryOperator::Ne => BinaryOp::Ne,
+                BinaryOperator::Lt => BinaryOp::Lt,
+                BinaryOperator::Le => BinaryOp::Le,
+                BinaryOperator::Gt => BinaryOp::Gt,
+                BinaryOperator::Ge => BinaryOp::Ge,
+            },
+            left: boxed(left),
+            right: boxed(right),
+        },
+        Expr::UnaryOp { op, operand } => CoreExpr::Unary {
+            op: match op {
+                UnaryOperator::Not => UnaryOp::Not,
+                UnaryOperator::Neg => UnaryOp::Neg,
+            },
+            operand: boxed(operand),
+        },
+        Expr::FunctionCall { name, args } => CoreExpr::Call {
+            function: name.clone(),
+            args: args.iter().map(transform_expr).collect(),
+        },
+        Expr::Index { expr, index } => CoreExpr::Index {
+            target: boxed(expr),
+            index: boxed(index),
+        },
+    }
+}
+
 /// Resolve an object by name or create it if it doesn't exist.
+///
+/// The warning about an implicitly added object carries a fix declaring it
+/// in the context.
 fn resolve_or_create_object(
     ctx: &mut BoundedContext,
+    decl: &ContextDecl,
     name: &str,
     object_lookup: &mut HashMap<String, sketchddd_core::sketch::ObjectId>,
     warnings: &mut Vec<TransformWarning>,
@@ -218,7 +652,8 @@ fn resolve_or_create_object(
                 "Object '{}' referenced but not declared, adding implicitly",
                 name
             ))
-            .with_location(span.line, span.column),
+            .with_location(span.line, span.column)
+            .with_fix(declare_object_fix(decl, name)),
         );
         let id = ctx.sketch_mut().add_object(name);
         object_lookup.insert(name.to_string(), id);
@@ -226,6 +661,32 @@ fn resolve_or_create_object(
     }
 }
 
+/// Build the fix declaring an object in a context.
+///
+/// The name is appended to the context's last `objects` list, or a new
+/// `objects` block is opened before the closing brace when the context has
+/// none of its own. Objects that come from an instantiated template are not
+/// written in the context and do not count.
+fn declare_object_fix(decl: &ContextDecl, name: &str) -> Fix {
+    let title = format!("Declare object '{}'", name);
+    let last_object = decl
+        .objects
+        .iter()
+        .filter(|o| decl.span.start <= o.span.start && o.span.end <= decl.span.end)
+        .max_by_key(|o| o.span.end);
+    let edit = match last_object {
+        Some(object) => TextEdit::insert(object.span.end, format!(", {}", name)),
+        None => {
+            let indent = " ".repeat(decl.span.column.saturating_sub(1) as usize);
+            TextEdit::insert(
+                decl.span.end.saturating_sub(1),
+                format!("  objects {{ {} }}\n{}", name, indent),
+            )
+        }
+    };
+    Fix::new(title).with_edit(edit)
+}
+
 /// Transform an aggregate declaration.
 fn transform_aggregate(
     ctx: &mut BoundedContext,
@@ -261,6 +722,14 @@ fn transform_aggregate(
 
     ctx.define_aggregate_with_members(&agg.name, *root_id, &member_ids);
 
+    for invariant in &agg.invariants {
+        let enforcement = match invariant.mode {
+            Some(InvariantMode::Eventual) => Enforcement::Eventual,
+            Some(InvariantMode::Strict) | None => Enforcement::Strict,
+        };
+        ctx.add_aggregate_invariant(*root_id, transform_expr(&invariant.expression), enforcement);
+    }
+
     Ok(())
 }
 
@@ -276,17 +745,387 @@ fn transform_equation(
     let rhs = transform_path(&eq.rhs, object_lookup, warnings)?;
 
     // Create path equation
-    let equation = sketchddd_core::sketch::PathEquation::new(
+    let mut equation = sketchddd_core::sketch::PathEquation::new(
         eq.name.as_deref().unwrap_or(""),
         lhs,
         rhs,
     );
+    if eq.mode == Some(InvariantMode::Eventual) {
+        equation = equation.eventual();
+    }
 
     ctx.add_path_equation(eq.name.as_deref().unwrap_or("anonymous"), equation);
 
     Ok(())
 }
 
+/// Transform a process declaration into a process manager diagram.
+fn transform_process(
+    ctx: &mut BoundedContext,
+    decl: &ContextDecl,
+    process: &ProcessDecl,
+    object_lookup: &mut HashMap<String, sketchddd_core::sketch::ObjectId>,
+    warnings: &mut Vec<TransformWarning>,
+) {
+    let mut steps = Vec::new();
+    for step in &process.steps {
+        let event = resolve_or_create_object(ctx, decl, &step.event, object_lookup, warnings, &step.span);
+        let command =
+            resolve_or_create_object(ctx, decl, &step.command, object_lookup, warnings, &step.span);
+        steps.push((event, command));
+    }
+
+    ctx.define_process(&process.name, &steps);
+}
+
+/// Transform the actors, policies and hotspots of a context.
+///
+/// Unlike process steps, these must refer to declared objects: an event
+/// storming element about an unknown event or command is a typo rather than
+/// a new concept.
+fn transform_event_storming(
+    ctx: &mut BoundedContext,
+    decl: &ContextDecl,
+    object_lookup: &HashMap<String, sketchddd_core::sketch::ObjectId>,
+) -> Result<(), ParseError> {
+    let resolve = |name: &str, what: &str, span: &Span| {
+        object_lookup.get(name).copied().ok_or_else(|| {
+            let mut names: Vec<&str> = object_lookup.keys().map(String::as_str).collect();
+            names.sort_unstable();
+            let mut message = format!("{} refers to unknown object '{}'", what, name);
+            if let Some(help) = did_you_mean(name, &names) {
+                message.push_str(&format!("; {}", help));
+            }
+            ParseError::new(message).with_location(span.line, span.column)
+        })
+    };
+
+    for actor in &decl.actors {
+        let ActorDecl { name, commands, span, .. } = actor;
+        let what = format!("Actor '{}'", name);
+        let commands = commands
+            .iter()
+            .map(|c| resolve(c, &what, span))
+            .collect::<Result<Vec<_>, _>>()?;
+        ctx.define_actor(name, &commands);
+    }
+
+    for policy in &decl.policies {
+        let PolicyDecl { name, event, commands, span, .. } = policy;
+        let what = format!("Policy '{}'", name);
+        let event = resolve(event, &what, span)?;
+        let commands = commands
+            .iter()
+            .map(|c| resolve(c, &what, span))
+            .collect::<Result<Vec<_>, _>>()?;
+        ctx.define_policy(name, event, &commands);
+    }
+
+    for hotspot in &decl.hotspots {
+        let HotspotDecl { target, note, span, .. } = hotspot;
+        let hotspot = match target {
+            Some(target) => Hotspot::on(resolve(target, &format!("Hotspot '{}'", note), span)?, note),
+            None => Hotspot::new(note),
+        };
+        ctx.add_hotspot(hotspot);
+    }
+
+    Ok(())
+}
+
+/// Transform a lifecycle declaration, attaching it to its enum.
+///
+/// Without an explicit `lifecycle Name: Enum`, the enum is looked up as
+/// `Name`, `NameStatus` or `NameState`, in that order.
+fn transform_lifecycle(ctx: &mut BoundedContext, lifecycle: &LifecycleDecl) -> Result<(), ParseError> {
+    let candidates = match &lifecycle.enum_name {
+        Some(enum_name) => vec![enum_name.clone()],
+        None => vec![
+            lifecycle.name.clone(),
+            format!("{}Status", lifecycle.name),
+            format!("{}State", lifecycle.name),
+        ],
+    };
+
+    let subject = candidates
+        .iter()
+        .find_map(|name| {
+            ctx.sketch()
+                .colimits
+                .iter()
+                .find(|c| &c.name == name)
+                .map(|c| c.apex)
+        })
+        .ok_or_else(|| {
+            let message = match &lifecycle.enum_name {
+                Some(enum_name) => format!(
+                    "Enum '{}' for lifecycle '{}' not found in context",
+                    enum_name, lifecycle.name
+                ),
+                None => format!(
+                    "No enum found for lifecycle '{}'; declare enum '{}Status' or use 'lifecycle {}: <Enum>'",
+                    lifecycle.name, lifecycle.name, lifecycle.name
+                ),
+            };
+            ParseError::new(message).with_location(lifecycle.span.line, lifecycle.span.column)
+        })?;
+
+    let machine = ctx.define_lifecycle(&lifecycle.name, subject);
+    for chain in &lifecycle.chains {
+        for (from, to) in chain.transitions() {
+            machine.add_transition(from, to);
+        }
+    }
+    for variant in &lifecycle.defaults {
+        machine.add_default(variant);
+    }
+
+    Ok(())
+}
+
+/// Transform a literal constraint, checking its keyword and value type.
+fn transform_constraint(constraint: &ConstraintDecl) -> Result<FieldConstraint, ParseError> {
+    let error = |message: String| {
+        ParseError::new(message).with_location(constraint.span.line, constraint.span.column)
+    };
+    let length = |n: f64| {
+        if n >= 0.0 && n.fract() == 0.0 {
+            Ok(n as usize)
+        } else {
+            Err(error(format!(
+                "Constraint '{}' must be a non-negative integer, found {}",
+                constraint.name, n
+            )))
+        }
+    };
+
+    match (constraint.name.as_str(), &constraint.value) {
+        ("min", ConstraintValue::Number(n)) => Ok(FieldConstraint::Min(*n)),
+        ("max", ConstraintValue::Number(n)) => Ok(FieldConstraint::Max(*n)),
+        ("minLength", ConstraintValue::Number(n)) => Ok(FieldConstraint::MinLength(length(*n)?)),
+        ("maxLength", ConstraintValue::Number(n)) => Ok(FieldConstraint::MaxLength(length(*n)?)),
+        ("pattern", ConstraintValue::Text(p)) => Ok(FieldConstraint::Pattern(p.clone())),
+        ("pattern", ConstraintValue::Number(_)) => Err(error(format!(
+            "Constraint '{}' expects a string literal",
+            constraint.name
+        ))),
+        (name, ConstraintValue::Text(_)) if FieldConstraint::keywords().contains(&name) => {
+            Err(error(format!("Constraint '{}' expects a number", name)))
+        }
+        (name, _) => Err(error(format!(
+            "Unknown constraint '{}'; expected one of: {}",
+            name,
+            FieldConstraint::keywords().join(", ")
+        ))),
+    }
+}
+
+/// Decorators understood by the DSL.
+const DECORATORS: &[&str] = &[
+    "version", "removed_in", "core", "supporting", "generic", "tag", "meta", "allow",
+];
+
+/// Decorators attaching tags and metadata rather than versioning information.
+const METADATA_DECORATORS: &[&str] = &["core", "supporting", "generic", "tag", "meta"];
+
+/// Apply `@version` and `@removed_in` decorators to the context.
+///
+/// `@version` is only allowed on the context itself; `@removed_in` is
+/// allowed on entities, value objects, enums, fields and morphisms.
+fn transform_versioning(
+    ctx: &mut BoundedContext,
+    decl: &ContextDecl,
+    object_lookup: &HashMap<String, sketchddd_core::sketch::ObjectId>,
+) -> Result<(), ParseError> {
+    for decorator in &decl.decorators {
+        match decorator.name.as_str() {
+            "version" => ctx.set_version(decorator_version(decorator)?),
+            "allow" => {}
+            name if METADATA_DECORATORS.contains(&name) => {}
+            _ => return Err(misplaced_decorator(decorator, "a context")),
+        }
+    }
+
+    let objects = decl
+        .entities
+        .iter()
+        .map(|e| (e.name.as_str(), &e.decorators))
+        .chain(decl.value_objects.iter().map(|v| (v.name.as_str(), &v.decorators)))
+        .chain(decl.enums.iter().map(|e| (e.name.as_str(), &e.decorators)));
+    let fields = decl
+        .entities
+        .iter()
+        .flat_map(|e| e.fields.iter().map(move |f| (e.name.as_str(), Some(f.name.as_str()), &f.decorators)))
+        .chain(decl.value_objects.iter().flat_map(|v| {
+            v.fields.iter().map(move |f| (v.name.as_str(), Some(f.name.as_str()), &f.decorators))
+        }))
+        .chain(
+            decl.morphisms
+                .iter()
+                .map(|m| (m.source.base_name(), Some(m.name.as_str()), &m.decorators)),
+        );
+
+    for (owner, field, decorators) in objects.map(|(o, d)| (o, None, d)).chain(fields) {
+        let Some(&id) = object_lookup.get(owner) else {
+            continue;
+        };
+        for decorator in decorators.iter() {
+            match decorator.name.as_str() {
+                "removed_in" => ctx.schedule_removal(id, field, decorator_version(decorator)?),
+                "allow" => {}
+                name if field.is_none() && METADATA_DECORATORS.contains(&name) => {}
+                _ => {
+                    let target = if field.is_some() { "a field" } else { "a declaration" };
+                    return Err(misplaced_decorator(decorator, target));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Apply `@core`, `@supporting`, `@generic`, `@tag` and `@meta` decorators.
+///
+/// They are allowed on the context, on entities, value objects and enums,
+/// and on aggregates.
+fn transform_metadata(
+    ctx: &mut BoundedContext,
+    decl: &ContextDecl,
+    object_lookup: &HashMap<String, sketchddd_core::sketch::ObjectId>,
+) -> Result<(), ParseError> {
+    for decorator in &decl.decorators {
+        apply_metadata(ctx.metadata_mut(), decorator)?;
+    }
+
+    let objects = decl
+        .entities
+        .iter()
+        .map(|e| (e.name.as_str(), &e.decorators))
+        .chain(decl.value_objects.iter().map(|v| (v.name.as_str(), &v.decorators)))
+        .chain(decl.enums.iter().map(|e| (e.name.as_str(), &e.decorators)));
+    for (name, decorators) in objects {
+        let Some(&id) = object_lookup.get(name) else {
+            continue;
+        };
+        for decorator in decorators.iter() {
+            apply_metadata(ctx.object_metadata_mut(id), decorator)?;
+        }
+    }
+
+    for agg in &decl.aggregates {
+        let root_name = agg.root.as_ref().unwrap_or(&agg.name);
+        let Some(&root) = object_lookup.get(root_name) else {
+            continue;
+        };
+        for decorator in &agg.decorators {
+            if decorator.name == "allow" {
+                continue;
+            }
+            if !METADATA_DECORATORS.contains(&decorator.name.as_str()) {
+                return Err(misplaced_decorator(decorator, "an aggregate"));
+            }
+            apply_metadata(ctx.aggregate_metadata_mut(root), decorator)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Record the lint rules `@allow` decorators let elements break.
+///
+/// `@allow` on the context covers all of it; on an entity, value object,
+/// enum, aggregate, field or morphism it covers issues about that element.
+fn transform_suppressions(ctx: &mut BoundedContext, decl: &ContextDecl) -> Result<(), ParseError> {
+    let elements = std::iter::once((None, &decl.decorators))
+        .chain(decl.entities.iter().flat_map(|e| {
+            std::iter::once((Some(e.name.as_str()), &e.decorators))
+                .chain(e.fields.iter().map(|f| (Some(f.name.as_str()), &f.decorators)))
+        }))
+        .chain(decl.value_objects.iter().flat_map(|v| {
+            std::iter::once((Some(v.name.as_str()), &v.decorators))
+                .chain(v.fields.iter().map(|f| (Some(f.name.as_str()), &f.decorators)))
+        }))
+        .chain(decl.enums.iter().map(|e| (Some(e.name.as_str()), &e.decorators)))
+        .chain(decl.aggregates.iter().map(|a| (Some(a.name.as_str()), &a.decorators)))
+        .chain(decl.morphisms.iter().map(|m| (Some(m.name.as_str()), &m.decorators)));
+
+    for (element, decorators) in elements {
+        for decorator in decorators.iter().filter(|d| d.name == "allow") {
+            if decorator.names.is_empty() || decorator.argument.is_some() {
+                return Err(ParseError::new(
+                    "Decorator '@allow' expects rule codes such as W0001",
+                )
+                .with_location(decorator.span.line, decorator.span.column));
+            }
+            for code in &decorator.names {
+                ctx.suppress(code, element);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Apply a metadata decorator, ignoring any other decorator.
+fn apply_metadata(metadata: &mut Metadata, decorator: &DecoratorDecl) -> Result<(), ParseError> {
+    let error = |message: &str| {
+        ParseError::new(format!("Decorator '@{}' {}", decorator.name, message))
+            .with_location(decorator.span.line, decorator.span.column)
+    };
+
+    match (decorator.name.as_str(), &decorator.key, &decorator.argument) {
+        (name @ ("core" | "supporting" | "generic"), None, None) => {
+            metadata.classification = name.parse().ok();
+        }
+        ("core" | "supporting" | "generic", _, _) => return Err(error("takes no argument")),
+        ("tag", None, Some(ConstraintValue::Text(tag))) => metadata.add_tag(tag.clone()),
+        ("tag", _, _) => return Err(error("expects a tag such as \"pii\"")),
+        ("meta", Some(key), Some(ConstraintValue::Text(text))) => {
+            metadata.set(key.clone(), MetadataValue::Text(text.clone()))
+        }
+        ("meta", Some(key), Some(ConstraintValue::Number(n))) => {
+            metadata.set(key.clone(), MetadataValue::Number(*n))
+        }
+        ("meta", _, _) => return Err(error("expects a key and a value such as owner: \"billing\"")),
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Read the version argument of a decorator, e.g. `@version(2)`.
+fn decorator_version(decorator: &DecoratorDecl) -> Result<ModelVersion, ParseError> {
+    let text = match &decorator.argument {
+        Some(ConstraintValue::Number(n)) => n.to_string(),
+        Some(ConstraintValue::Text(s)) => s.clone(),
+        None => {
+            return Err(ParseError::new(format!(
+                "Decorator '@{}' expects a version such as 2 or \"2.1\"",
+                decorator.name
+            ))
+            .with_location(decorator.span.line, decorator.span.column))
+        }
+    };
+
+    text.parse().map_err(|e: String| {
+        ParseError::new(format!("Decorator '@{}': {}", decorator.name, e))
+            .with_location(decorator.span.line, decorator.span.column)
+    })
+}
+
+/// Report a decorator that is unknown or not allowed on `target`.
+fn misplaced_decorator(decorator: &DecoratorDecl, target: &str) -> ParseError {
+    let message = if DECORATORS.contains(&decorator.name.as_str()) {
+        format!("Decorator '@{}' is not allowed on {}", decorator.name, target)
+    } else {
+        let mut message = format!("Unknown decorator '@{}'", decorator.name);
+        if let Some(help) = did_you_mean(&decorator.name, DECORATORS) {
+            message.push_str(&format!("; {}", help));
+        }
+        message
+    };
+    ParseError::new(message).with_location(decorator.span.line, decorator.span.column)
+}
+
 /// Transform an AST path to a semantic model path.
 fn transform_path(
     path: &crate::ast::Path,
@@ -309,8 +1148,14 @@ fn transform_path(
 }
 
 /// Transform a context map declaration.
+///
+/// Maps without an explicit pattern default to `SharedKernel` when both
+/// contexts include a common shared kernel, and to `Partnership` otherwise.
+/// Mapped names may be qualified with the context on their side of the map.
 fn transform_context_map(
     map_decl: &ContextMapDecl,
+    symbols: &SymbolTable<'_>,
+    contexts: &[BoundedContext],
     context_lookup: &HashMap<String, usize>,
     warnings: &mut Vec<TransformWarning>,
 ) -> Result<NamedContextMap, ParseError> {
@@ -342,7 +1187,16 @@ fn transform_context_map(
         .as_ref()
         .map(|p| parse_relationship_pattern(p))
         .transpose()?
-        .unwrap_or(RelationshipPattern::Partnership);
+        .unwrap_or_else(|| {
+            let source = context_lookup.get(&map_decl.source_context).map(|&i| &contexts[i]);
+            let target = context_lookup.get(&map_decl.target_context).map(|&i| &contexts[i]);
+            match (source, target) {
+                (Some(s), Some(t)) if !s.common_shared_kernels(t).is_empty() => {
+                    RelationshipPattern::SharedKernel
+                }
+                _ => RelationshipPattern::Partnership,
+            }
+        });
 
     // Create the context map with named mappings
     let mut ctx_map = NamedContextMap::new(
@@ -352,11 +1206,38 @@ fn transform_context_map(
         pattern,
     );
 
+    // Resolve names qualified with a context, e.g. `Billing.Invoice`
+    let resolve = |name: &str, side_context: &str, span: &Span| -> Result<String, ParseError> {
+        let (qualifier, local) = split_qualified_name(name);
+        let Some(qualifier) = qualifier else {
+            return Ok(name.to_string());
+        };
+
+        symbols.context(qualifier, span.line, span.column)?;
+        if qualifier != side_context {
+            let message = if qualifier == map_decl.source_context
+                || qualifier == map_decl.target_context
+            {
+                format!(
+                    "'{}' is on the wrong side of map '{}'; expected a name from '{}'",
+                    name, map_decl.name, side_context
+                )
+            } else {
+                format!(
+                    "Context '{}' is not imported by map '{}'; it maps '{}' to '{}'",
+                    qualifier, map_decl.name, map_decl.source_context, map_decl.target_context
+                )
+            };
+            return Err(ParseError::new(message).with_location(span.line, span.column));
+        }
+        Ok(local.to_string())
+    };
+
     // Add object mappings
     for obj_map in &map_decl.object_mappings {
         ctx_map.add_object_mapping(NamedObjectMapping {
-            source: obj_map.source.clone(),
-            target: obj_map.target.clone(),
+            source: resolve(&obj_map.source, &map_decl.source_context, &obj_map.span)?,
+            target: resolve(&obj_map.target, &map_decl.target_context, &obj_map.span)?,
             description: obj_map.description.clone(),
         });
     }
@@ -364,8 +1245,8 @@ fn transform_context_map(
     // Add morphism mappings
     for morph_map in &map_decl.morphism_mappings {
         ctx_map.add_morphism_mapping(NamedMorphismMapping {
-            source: morph_map.source.clone(),
-            target: morph_map.target.clone(),
+            source: resolve(&morph_map.source, &map_decl.source_context, &morph_map.span)?,
+            target: resolve(&morph_map.target, &map_decl.target_context, &morph_map.span)?,
             description: morph_map.description.clone(),
         });
     }
@@ -395,6 +1276,7 @@ fn parse_relationship_pattern(pattern: &str) -> Result<RelationshipPattern, Pars
 mod tests {
     use super::*;
     use crate::parse_file;
+    use sketchddd_core::{Classification, Collection, LintConfig, Registry};
 
     #[test]
     fn test_transform_empty_context() {
@@ -519,36 +1401,153 @@ mod tests {
     }
 
     #[test]
-    fn test_transform_context_map() {
+    fn test_transform_optional_morphism() {
         let source = r#"
-            context Commerce {
-                objects { Order, Customer }
-            }
-
-            context Shipping {
-                objects { Shipment, Recipient }
-            }
-
-            map CommerceToShipping: Commerce -> Shipping {
-                pattern: CustomerSupplier
-                mappings {
-                    Order -> Shipment
-                    Customer -> Recipient
+            context Sales {
+                morphisms {
+                    placedBy: Order -> Customer
+                    referredBy: Order -> Customer?
                 }
             }
         "#;
         let file = parse_file(source).unwrap();
         let result = transform(&file).unwrap();
+        let ctx = &result.contexts[0];
 
-        assert_eq!(result.contexts.len(), 2);
-        assert_eq!(result.context_maps.len(), 1);
-
-        let map = &result.context_maps[0];
-        assert_eq!(map.name(), "CommerceToShipping");
-        assert_eq!(map.source_context(), "Commerce");
-        assert_eq!(map.target_context(), "Shipping");
-        assert_eq!(map.pattern(), RelationshipPattern::CustomerSupplier);
-        assert_eq!(map.object_mappings().len(), 2);
+        let optional: Vec<&str> = ctx
+            .graph()
+            .morphisms()
+            .filter(|m| ctx.is_optional(m.id))
+            .map(|m| m.name.as_str())
+            .collect();
+        assert_eq!(optional, vec!["referredBy"]);
+    }
+
+    #[test]
+    fn test_transform_to_many_morphism() {
+        let source = r#"
+            context Sales {
+                morphisms {
+                    items: Order -> List<LineItem>
+                    tags: Order -> Set<Tag>
+                }
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let result = transform(&file).unwrap();
+        let ctx = &result.contexts[0];
+
+        // A collection targets its element, without an object of its own
+        assert!(ctx.graph().find_object_by_name("List").is_none());
+        assert!(ctx.graph().find_object_by_name("Set").is_none());
+        let targets: Vec<(&str, &str, Option<Collection>)> = ctx
+            .graph()
+            .morphisms()
+            .filter(|m| !m.is_identity)
+            .map(|m| {
+                let target = ctx.graph().get_object(m.target).unwrap();
+                (m.name.as_str(), target.name.as_str(), ctx.collection_of(m.id))
+            })
+            .collect();
+        assert_eq!(
+            targets,
+            vec![
+                ("items", "LineItem", Some(Collection::List)),
+                ("tags", "Tag", Some(Collection::Set)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_transform_template_instantiation() {
+        let source = r#"
+            template CrudAggregate<T> {
+                objects { Timestamp }
+                morphisms {
+                    createdAt: T -> Timestamp
+                }
+                aggregate T {
+                    root: T
+                }
+            }
+
+            context Catalog {
+                entity Product
+                use CrudAggregate<Product>
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let result = transform(&file).unwrap();
+        let ctx = &result.contexts[0];
+
+        let product = ctx.graph().find_object_by_name("Product").unwrap().id;
+        assert!(ctx.is_aggregate_root(product));
+        assert!(ctx
+            .graph()
+            .outgoing_morphisms(product)
+            .any(|m| m.name == "createdAt"));
+        assert!(ctx.graph().find_object_by_name("T").is_none());
+    }
+
+    #[test]
+    fn test_transform_aggregate_invariants() {
+        let source = r#"
+            context Commerce {
+                entity Order
+                entity Customer
+                morphisms {
+                    customer: Order -> Customer
+                }
+                aggregate OrderAggregate {
+                    root: Order
+                    invariant: count(lines) > 0
+                    invariant eventual: customer.active == 1
+                }
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let result = transform(&file).unwrap();
+        let ctx = &result.contexts[0];
+        let order = ctx.graph().find_object_by_name("Order").unwrap().id;
+
+        let invariants = ctx.invariants_of(order);
+        assert_eq!(invariants.len(), 2);
+        assert_eq!(invariants[0].enforcement, Enforcement::Strict);
+        assert_eq!(invariants[0].expression.to_string(), "count(lines) > 0");
+        assert_eq!(invariants[1].enforcement, Enforcement::Eventual);
+    }
+
+    #[test]
+    fn test_transform_context_map() {
+        let source = r#"
+            context Commerce {
+                objects { Order, Customer }
+            }
+
+            context Shipping {
+                objects { Shipment, Recipient }
+            }
+
+            map CommerceToShipping: Commerce -> Shipping {
+                pattern: CustomerSupplier
+                mappings {
+                    Order -> Shipment
+                    Customer -> Recipient
+                }
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let result = transform(&file).unwrap();
+
+        assert_eq!(result.contexts.len(), 2);
+        assert_eq!(result.context_maps.len(), 1);
+
+        let map = &result.context_maps[0];
+        assert_eq!(map.name(), "CommerceToShipping");
+        assert_eq!(map.source_context(), "Commerce");
+        assert_eq!(map.target_context(), "Shipping");
+        assert_eq!(map.pattern(), RelationshipPattern::CustomerSupplier);
+        assert_eq!(map.object_mappings().len(), 2);
     }
 
     #[test]
@@ -617,6 +1616,544 @@ mod tests {
         assert!(result.warnings.iter().any(|w| w.message.contains("Customer")));
     }
 
+    #[test]
+    fn test_transform_process() {
+        let source = r#"
+            context Fulfillment {
+                objects { OrderPlaced, ReserveStock, StockReserved }
+                process FulfillmentSaga {
+                    on OrderPlaced -> ReserveStock
+                    on StockReserved -> Ship
+                }
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let result = transform(&file).unwrap();
+        let ctx = &result.contexts[0];
+
+        let process = ctx.get_process("FulfillmentSaga").unwrap();
+        assert_eq!(process.steps.len(), 2);
+        let placed = ctx.graph().find_object_by_name("OrderPlaced").unwrap().id;
+        let reserve = ctx.graph().find_object_by_name("ReserveStock").unwrap().id;
+        assert_eq!(process.commands_for(placed), vec![reserve]);
+
+        // Undeclared command is added implicitly with a warning
+        assert!(ctx.graph().find_object_by_name("Ship").is_some());
+        assert_eq!(result.warnings.len(), 1);
+        assert!(result.warnings[0].message.contains("Ship"));
+    }
+
+    #[test]
+    fn test_transform_event_storming() {
+        let source = r#"
+            context Fulfillment {
+                objects { PlaceOrder, OrderPlaced, ReserveStock }
+                actor Customer { PlaceOrder }
+                policy ReserveOnPlacement { on OrderPlaced -> ReserveStock }
+                hotspot ReserveStock "What if stock runs out?"
+                hotspot "Who pays for returns?"
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let result = transform(&file).unwrap();
+        let ctx = &result.contexts[0];
+        let id = |name| ctx.graph().find_object_by_name(name).unwrap().id;
+
+        assert_eq!(ctx.get_actor("Customer").unwrap().commands, vec![id("PlaceOrder")]);
+        let policy = ctx.get_policy("ReserveOnPlacement").unwrap();
+        assert_eq!(policy.event, id("OrderPlaced"));
+        assert_eq!(policy.commands, vec![id("ReserveStock")]);
+        assert_eq!(ctx.hotspots()[0].target, Some(id("ReserveStock")));
+        assert_eq!(ctx.hotspots()[1].target, None);
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_transform_policy_unknown_event() {
+        let source = r#"
+            context Fulfillment {
+                objects { OrderPlaced, ReserveStock }
+                policy ReserveOnPlacement { on OrderPlcaed -> ReserveStock }
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let err = transform(&file).unwrap_err();
+        assert!(err.message.contains("Policy 'ReserveOnPlacement' refers to unknown object 'OrderPlcaed'"));
+        assert!(err.message.contains("OrderPlaced"));
+    }
+
+    #[test]
+    fn test_transform_derived_morphism() {
+        let source = r#"
+            context Commerce {
+                entity Order
+                entity LineItem
+                value Money { amount: Decimal }
+                morphisms {
+                    items: Order -> LineItem
+                    price: LineItem -> Money
+                }
+                derive total: Order -> Money = sum(items.price)
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let result = transform(&file).unwrap();
+        let ctx = &result.contexts[0];
+
+        let total = ctx.graph().find_morphism_by_name("total").unwrap();
+        assert!(ctx.is_derived(total.id));
+        assert_eq!(ctx.derivation_of(total.id).unwrap().to_string(), "sum(items.price)");
+        assert!(!ctx.is_derived(ctx.graph().find_morphism_by_name("items").unwrap().id));
+        assert!(sketchddd_core::validate_context(ctx).is_ok());
+    }
+
+    #[test]
+    fn test_transform_lifecycle() {
+        let source = r#"
+            context Commerce {
+                enum OrderStatus = Pending | Confirmed | Shipped | Cancelled
+                lifecycle Order { Pending -> Confirmed -> Shipped; Pending -> Cancelled }
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let result = transform(&file).unwrap();
+        let ctx = &result.contexts[0];
+
+        let status = ctx.graph().find_object_by_name("OrderStatus").unwrap().id;
+        let lifecycle = ctx.get_lifecycle(status).unwrap();
+        assert_eq!(lifecycle.name, "Order");
+        assert_eq!(lifecycle.transitions.len(), 3);
+        assert_eq!(lifecycle.initial_state(), Some("Pending"));
+        assert!(lifecycle.can_transition("Confirmed", "Shipped"));
+    }
+
+    #[test]
+    fn test_transform_lifecycle_explicit_enum() {
+        let source = r#"
+            context Commerce {
+                enum Phase = Draft | Published
+                lifecycle Article: Phase { Draft -> Published; default: Legacy }
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let result = transform(&file).unwrap();
+        assert_eq!(result.contexts[0].lifecycles().len(), 1);
+        assert_eq!(result.contexts[0].lifecycles()[0].defaults, vec!["Legacy"]);
+    }
+
+    #[test]
+    fn test_transform_lifecycle_without_enum_error() {
+        let source = r#"
+            context Commerce {
+                lifecycle Order { Pending -> Confirmed }
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let err = transform(&file).unwrap_err();
+        assert!(err.to_string().contains("No enum found for lifecycle 'Order'"));
+    }
+
+    #[test]
+    fn test_transform_shared_kernel() {
+        let source = r#"
+            shared kernel Identity {
+                objects { UserId, TenantId }
+            }
+
+            context Billing {
+                objects { Invoice }
+                morphisms {
+                    owner: Invoice -> UserId
+                }
+            }
+
+            context Shipping {
+                objects { Shipment }
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let result = transform(&file).unwrap();
+
+        assert_eq!(result.shared_kernels.len(), 1);
+        // Referenced kernel objects are not implicit, so no warnings
+        assert!(result.warnings.is_empty());
+
+        let billing = &result.contexts[0];
+        let tenant = billing.graph().find_object_by_name("TenantId").unwrap();
+        assert_eq!(billing.shared_kernel_of(tenant.id), Some("Identity"));
+
+        // Shipping never references the kernel
+        let shipping = &result.contexts[1];
+        assert!(shipping.graph().find_object_by_name("UserId").is_none());
+    }
+
+    #[test]
+    fn test_transform_shared_kernel_local_declaration_wins() {
+        let source = r#"
+            shared kernel Identity {
+                entity UserId
+                objects { TenantId }
+            }
+
+            context Billing {
+                objects { UserId, Invoice }
+                morphisms {
+                    tenant: Invoice -> TenantId
+                }
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let result = transform(&file).unwrap();
+        let billing = &result.contexts[0];
+
+        let user = billing.graph().find_object_by_name("UserId").unwrap();
+        assert_eq!(billing.shared_kernel_of(user.id), None);
+        assert!(!billing.is_entity(user.id));
+        assert_eq!(billing.graph().objects().filter(|o| o.name == "UserId").count(), 1);
+    }
+
+    #[test]
+    fn test_transform_shared_kernel_map_pattern() {
+        let source = r#"
+            shared kernel Identity {
+                objects { UserId }
+            }
+
+            context Billing {
+                morphisms { payer: Invoice -> UserId }
+            }
+
+            context Shipping {
+                morphisms { recipient: Shipment -> UserId }
+            }
+
+            map BillingToShipping: Billing -> Shipping {
+                mappings { Invoice -> Shipment }
+            }
+
+            map ExplicitMap: Billing -> Shipping {
+                pattern: CustomerSupplier
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let result = transform(&file).unwrap();
+
+        assert_eq!(result.context_maps[0].pattern(), RelationshipPattern::SharedKernel);
+        assert_eq!(result.context_maps[1].pattern(), RelationshipPattern::CustomerSupplier);
+
+        // The shared UserId does not count as unmapped
+        let validation = sketchddd_core::validate_model(&result.contexts, &result.context_maps[..1]);
+        assert!(!validation.issues.iter().any(|i| i.code == "W0130"));
+    }
+
+    #[test]
+    fn test_transform_field_constraints() {
+        let source = r#"
+            context Billing {
+                value Money {
+                    amount: Decimal [min: 0, max: 1000000]
+                    currency: String [pattern: "[A-Z]{3}"]
+                }
+                entity Invoice {
+                    number: String [minLength: 3, maxLength: 20]
+                }
+                morphisms {
+                    total: Invoice -> Decimal [min: 0]
+                }
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let result = transform(&file).unwrap();
+        let ctx = &result.contexts[0];
+
+        let money = ctx.graph().find_object_by_name("Money").unwrap().id;
+        assert_eq!(
+            ctx.field_constraints(money, "amount"),
+            &[FieldConstraint::Min(0.0), FieldConstraint::Max(1_000_000.0)]
+        );
+        assert_eq!(
+            ctx.field_constraints(money, "currency"),
+            &[FieldConstraint::Pattern("[A-Z]{3}".into())]
+        );
+
+        let invoice = ctx.graph().find_object_by_name("Invoice").unwrap().id;
+        assert_eq!(
+            ctx.field_constraints(invoice, "number"),
+            &[FieldConstraint::MinLength(3), FieldConstraint::MaxLength(20)]
+        );
+        assert_eq!(ctx.field_constraints(invoice, "total"), &[FieldConstraint::Min(0.0)]);
+
+        // Fields record their declared types; morphisms carry their own
+        let type_of = |field: &str| {
+            let constrained = ctx.constrained_fields().iter().find(|c| c.field == field).unwrap();
+            constrained.type_name.as_deref()
+        };
+        assert_eq!(type_of("amount"), Some("Decimal"));
+        assert_eq!(type_of("number"), Some("String"));
+        assert_eq!(type_of("total"), None);
+    }
+
+    #[test]
+    fn test_transform_invalid_field_constraints() {
+        for (constraint, expected) in [
+            ("[minimum: 0]", "Unknown constraint 'minimum'"),
+            ("[min: \"zero\"]", "expects a number"),
+            ("[pattern: 3]", "expects a string literal"),
+            ("[maxLength: 2.5]", "non-negative integer"),
+        ] {
+            let source = format!("context C {{ value V {{ x: String {} }} }}", constraint);
+            let file = parse_file(&source).unwrap();
+            let err = transform(&file).unwrap_err();
+            assert!(err.message.contains(expected), "{}: {}", constraint, err.message);
+        }
+    }
+
+    #[test]
+    fn test_transform_shared_kernel_qualified_objects() {
+        let source = r#"
+            context Auth {
+                entity User
+                enum Role = Admin | Member
+            }
+
+            shared kernel Identity {
+                objects { Auth.User, Auth.Role, TenantId }
+            }
+
+            context Billing {
+                morphisms { payer: Invoice -> User }
+            }
+
+            map AuthToBilling: Auth -> Billing {
+                mappings { Auth.User -> Billing.User }
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let result = transform(&file).unwrap();
+
+        let kernel = &result.shared_kernels[0];
+        let user = kernel.graph().find_object_by_name("User").unwrap();
+        assert!(kernel.is_entity(user.id));
+        let role = kernel.graph().find_object_by_name("Role").unwrap();
+        assert!(kernel.get_enum_colimit(role.id).is_some());
+
+        // Billing includes the kernel; Auth is marked as its source
+        let billing = &result.contexts[1];
+        let included = billing.graph().find_object_by_name("User").unwrap();
+        assert!(billing.is_entity(included.id));
+        assert_eq!(result.contexts[0].shared_kernels(), vec!["Identity"]);
+
+        let map = &result.context_maps[0];
+        assert_eq!(map.pattern(), RelationshipPattern::SharedKernel);
+        assert_eq!(map.object_mappings()[0].source, "User");
+        assert_eq!(map.object_mappings()[0].target, "User");
+    }
+
+    #[test]
+    fn test_transform_qualified_name_errors() {
+        let cases = [
+            (
+                "shared kernel K { objects { Ath.User } }",
+                "Unknown context 'Ath'; did you mean `Auth`?",
+            ),
+            (
+                "shared kernel K { objects { Auth.Usr } }",
+                "Object 'Usr' is not declared in context 'Auth'; did you mean `User`?",
+            ),
+            (
+                "map M: Auth -> Billing { mappings { Shipping.Parcel -> Invoice } }",
+                "Context 'Shipping' is not imported by map 'M'",
+            ),
+            (
+                "map M: Auth -> Billing { mappings { Billing.Invoice -> User } }",
+                "'Billing.Invoice' is on the wrong side of map 'M'; expected a name from 'Auth'",
+            ),
+        ];
+
+        for (decl, expected) in cases {
+            let source = format!(
+                "context Auth {{ entity User }} context Billing {{ objects {{ Invoice }} }} context Shipping {{ }} {}",
+                decl
+            );
+            let file = parse_file(&source).unwrap();
+            let err = transform(&file).unwrap_err();
+            assert!(err.message.contains(expected), "{}: {}", decl, err.message);
+        }
+    }
+
+    #[test]
+    fn test_transform_versioning_decorators() {
+        let source = r#"
+            @version("2.1")
+            context Billing {
+                @removed_in("3.0")
+                entity LegacyInvoice
+
+                value Money {
+                    amount: Decimal
+                    @removed_in(3)
+                    currencyName: String
+                }
+
+                morphisms {
+                    @removed_in("3.0")
+                    legacy: Money -> LegacyInvoice
+                }
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let result = transform(&file).unwrap();
+        let ctx = &result.contexts[0];
+
+        assert_eq!(ctx.version(), Some(ModelVersion::new(2, 1, 0)));
+        let legacy = ctx.graph().find_object_by_name("LegacyInvoice").unwrap().id;
+        let money = ctx.graph().find_object_by_name("Money").unwrap().id;
+        assert_eq!(
+            ctx.removal_of(legacy, None).unwrap().removed_in,
+            ModelVersion::new(3, 0, 0)
+        );
+        assert!(ctx.removal_of(money, Some("currencyName")).is_some());
+        assert!(ctx.removal_of(money, Some("legacy")).is_some());
+        assert!(ctx.removal_of(money, Some("amount")).is_none());
+        assert_eq!(ctx.removals().len(), 3);
+    }
+
+    #[test]
+    fn test_transform_invalid_decorators() {
+        let cases = [
+            ("@removed_in(3) context C { }", "Decorator '@removed_in' is not allowed on a context"),
+            ("context C { @version(2) entity E }", "Decorator '@version' is not allowed on a declaration"),
+            ("@verison(2) context C { }", "Unknown decorator '@verison'; did you mean `version`?"),
+            ("@version context C { }", "Decorator '@version' expects a version"),
+            ("@version(\"two\") context C { }", "Invalid version 'two'"),
+            ("@core(1) context C { }", "Decorator '@core' takes no argument"),
+            ("@meta(\"billing\") context C { }", "Decorator '@meta' expects a key and a value"),
+            ("context C { value V { @tag(\"pii\") x: Int } }", "Decorator '@tag' is not allowed on a field"),
+            ("context C { entity E\n @version(2) aggregate E { } }", "Decorator '@version' is not allowed on an aggregate"),
+            ("@allow context C { }", "Decorator '@allow' expects rule codes such as W0001"),
+            ("@allow(\"W0001\") context C { }", "Decorator '@allow' expects rule codes"),
+        ];
+
+        for (source, expected) in cases {
+            let file = parse_file(source).unwrap();
+            let err = transform(&file).unwrap_err();
+            assert!(err.message.contains(expected), "{}: {}", source, err.message);
+        }
+    }
+
+    #[test]
+    fn test_transform_metadata_decorators() {
+        let source = r#"
+            @supporting
+            @meta(owner: "billing-team")
+            context Billing {
+                @tag("pii")
+                entity Customer
+
+                entity Invoice
+                entity Ledger
+
+                @core
+                @meta(priority: 1)
+                aggregate Invoice { }
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let result = transform(&file).unwrap();
+        let ctx = &result.contexts[0];
+
+        assert_eq!(ctx.metadata().classification, Some(Classification::Supporting));
+        assert_eq!(
+            ctx.metadata().get("owner"),
+            Some(&MetadataValue::Text("billing-team".into()))
+        );
+
+        let customer = ctx.graph().find_object_by_name("Customer").unwrap().id;
+        let invoice = ctx.graph().find_object_by_name("Invoice").unwrap().id;
+        let ledger = ctx.graph().find_object_by_name("Ledger").unwrap().id;
+        assert!(ctx.object_metadata(customer).unwrap().has_tag("pii"));
+        assert_eq!(
+            ctx.aggregate_metadata(invoice).unwrap().get("priority"),
+            Some(&MetadataValue::Number(1.0))
+        );
+        assert_eq!(ctx.classification_of(invoice), Some(Classification::Core));
+        assert_eq!(ctx.classification_of(ledger), Some(Classification::Supporting));
+    }
+
+    #[test]
+    fn test_transform_allow_decorators() {
+        let source = r#"
+            @allow(W0080)
+            context Sales {
+                objects { Note }
+                @allow(W0032, W0040)
+                entity Orders {
+                    total: Int
+                }
+                entity Customers {
+                    name: String
+                }
+                morphisms {
+                    @allow(W0031)
+                    PlacedBy: Orders -> Customers
+                }
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let workspace = transform(&file).unwrap().into_workspace();
+        assert_eq!(workspace.contexts[0].suppressions().len(), 4);
+
+        let result = workspace.lint(&Registry::builtin(), &LintConfig::new());
+        let mut issues: Vec<_> = result
+            .issues
+            .iter()
+            .filter(|i| ["W0031", "W0032", "W0080"].contains(&i.code.as_str()))
+            .map(|i| i.message.as_str())
+            .collect();
+        issues.sort();
+        assert_eq!(issues, vec!["Entity 'Customers' has a plural name"]);
+    }
+
+    #[test]
+    fn test_transform_domain() {
+        let source = r#"
+            context Sales { }
+            @generic
+            context Auth { }
+
+            domain Commerce {
+                @core
+                subdomain Ordering {
+                    contexts { Sales }
+                }
+                @supporting
+                @tag("platform")
+                subdomain Identity {
+                    contexts { Auth }
+                }
+            }
+        "#;
+        let file = parse_file(source).unwrap();
+        let result = transform(&file).unwrap();
+
+        let domain = &result.domains[0];
+        assert_eq!(domain.name, "Commerce");
+        assert_eq!(domain.subdomain_of("Sales").unwrap().name, "Ordering");
+        assert!(domain.subdomains[1].metadata.has_tag("platform"));
+
+        // Sales inherits its subdomain's classification; Auth keeps its own
+        assert_eq!(result.contexts[0].metadata().classification, Some(Classification::Core));
+        assert_eq!(result.contexts[1].metadata().classification, Some(Classification::Generic));
+
+        let validation = sketchddd_core::validate_domains(&result.domains, &result.contexts);
+        assert_eq!(validation.issues.len(), 1);
+        assert_eq!(validation.issues[0].code, "W0071");
+
+        let file = parse_file("domain D { @version(2) subdomain S { } }").unwrap();
+        let err = transform(&file).unwrap_err();
+        assert!(err.message.contains("Decorator '@version' is not allowed on a subdomain"));
+    }
+
     #[test]
     fn test_transform_all_relationship_patterns() {
         let patterns = [
@@ -0,0 +1,395 @@
+//! Comment and blank-line tracking for round-trip tooling.
+//!
+//! The grammar skips comments, so they never reach the AST through pest.
+//! After parsing, [`attach_trivia`] scans the source for comments and blank
+//! lines and attaches each one to the declaration it belongs to:
+//!
+//! - a comment after code on the same line as a declaration's start is that
+//!   declaration's *trailing* comment;
+//! - other comments and blank lines are *leading* trivia of the next
+//!   declaration in the same block;
+//! - comments with no following declaration in their block are *dangling*
+//!   trivia of the block, or trailing trivia of the file at the top level,
+//!   where the blank lines between them are kept too.
+
+use crate::ast::{ContextDecl, File, Span, Trivia, TriviaItem};
+
+/// A comment or blank line found in the source, with its byte offset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RawTrivia {
+    offset: usize,
+    item: TriviaItem,
+    /// Whether code precedes the comment on its line
+    after_code: bool,
+}
+
+/// A declaration that can hold trivia.
+struct Slot<'a> {
+    start: usize,
+    /// End offset, for declarations that are closed by a `}`
+    block_end: Option<usize>,
+    trivia: &'a mut Trivia,
+}
+
+/// Attach the comments and blank lines of `source` to the declarations of
+/// `file`, which must have been parsed from `source`.
+pub fn attach_trivia(file: &mut File, source: &str) {
+    let raw = scan_trivia(source);
+    if raw.is_empty() {
+        return;
+    }
+
+    let mut file_trailing = Vec::new();
+    let mut slots = collect_slots(file, source);
+    slots.sort_by_key(|slot| slot.start);
+
+    for trivia in raw {
+        let container = slots
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| {
+                s.block_end
+                    .is_some_and(|end| s.start < trivia.offset && trivia.offset < end)
+            })
+            .max_by_key(|(_, s)| s.start)
+            .map(|(i, s)| (i, s.block_end.unwrap_or_default()));
+
+        if let TriviaItem::Comment(text) = &trivia.item {
+            if trivia.after_code {
+                let owner = slots
+                    .iter_mut()
+                    .rev()
+                    .find(|s| s.start < trivia.offset)
+                    .filter(|s| starts_on_line(source, s.start, trivia.offset));
+                if let Some(owner) = owner {
+                    if owner.trivia.trailing.is_none() {
+                        owner.trivia.trailing = Some(text.clone());
+                        continue;
+                    }
+                }
+            }
+        }
+
+        let next = slots.iter().position(|s| s.start > trivia.offset);
+        match (next, container) {
+            (Some(next), Some((_, end))) if slots[next].start < end => {
+                slots[next].trivia.leading.push(trivia.item);
+            }
+            (Some(next), None) => slots[next].trivia.leading.push(trivia.item),
+            (_, Some((container, _))) => {
+                if trivia.item != TriviaItem::BlankLine {
+                    slots[container].trivia.dangling.push(trivia.item);
+                }
+            }
+            (None, None) => file_trailing.push(trivia.item),
+        }
+    }
+
+    while file_trailing.last() == Some(&TriviaItem::BlankLine) {
+        file_trailing.pop();
+    }
+    file.trailing_trivia = file_trailing;
+}
+
+/// Check if the declaration starting at `start` has its first line, after
+/// any decorators, on the same line as `offset`.
+fn starts_on_line(source: &str, start: usize, offset: usize) -> bool {
+    let mut header = &source[start..offset];
+    while header.starts_with('@') {
+        match header.find('\n') {
+            Some(newline) => header = header[newline + 1..].trim_start(),
+            None => break,
+        }
+    }
+    !header.contains('\n')
+}
+
+/// Collect every declaration of the file that can hold trivia.
+fn collect_slots<'a>(file: &'a mut File, source: &str) -> Vec<Slot<'a>> {
+    let mut slots = Vec::new();
+
+    for ctx in &mut file.contexts {
+        push_body_slots(&mut slots, source, ctx, true);
+    }
+
+    for template in &mut file.templates {
+        push_slot(&mut slots, source, &template.span, &mut template.trivia);
+        push_body_slots(&mut slots, source, &mut template.body, false);
+    }
+
+    for kernel in &mut file.shared_kernels {
+        push_slot(&mut slots, source, &kernel.span, &mut kernel.trivia);
+        for obj in &mut kernel.objects {
+            push_slot(&mut slots, source, &obj.span, &mut obj.trivia);
+        }
+        for entity in &mut kernel.entities {
+            push_slot(&mut slots, source, &entity.span, &mut entity.trivia);
+            for field in &mut entity.fields {
+                push_slot(&mut slots, source, &field.span, &mut field.trivia);
+            }
+        }
+        for vo in &mut kernel.value_objects {
+            push_slot(&mut slots, source, &vo.span, &mut vo.trivia);
+            for field in &mut vo.fields {
+                push_slot(&mut slots, source, &field.span, &mut field.trivia);
+            }
+        }
+        for enum_decl in &mut kernel.enums {
+            push_slot(&mut slots, source, &enum_decl.span, &mut enum_decl.trivia);
+        }
+    }
+
+    for domain in &mut file.domains {
+        push_slot(&mut slots, source, &domain.span, &mut domain.trivia);
+        for subdomain in &mut domain.subdomains {
+            push_slot(&mut slots, source, &subdomain.span, &mut subdomain.trivia);
+        }
+    }
+
+    for map in &mut file.context_maps {
+        push_slot(&mut slots, source, &map.span, &mut map.trivia);
+        for clause in [
+            &mut map.pattern_clause,
+            &mut map.mappings_clause,
+            &mut map.morphism_mappings_clause,
+        ] {
+            if let Some(span) = &clause.span {
+                push_slot(&mut slots, source, span, &mut clause.trivia);
+            }
+        }
+        for mapping in &mut map.object_mappings {
+            push_slot(&mut slots, source, &mapping.span, &mut mapping.trivia);
+        }
+        for mapping in &mut map.morphism_mappings {
+            push_slot(&mut slots, source, &mapping.span, &mut mapping.trivia);
+        }
+    }
+
+    slots
+}
+
+/// Collect the declarations of a context or template body, and the
+/// context itself if `with_context` is set.
+fn push_body_slots<'a>(
+    slots: &mut Vec<Slot<'a>>,
+    source: &str,
+    body: &'a mut ContextDecl,
+    with_context: bool,
+) {
+    if with_context {
+        push_slot(slots, source, &body.span, &mut body.trivia);
+    }
+    for use_decl in &mut body.uses {
+        push_slot(slots, source, &use_decl.span, &mut use_decl.trivia);
+    }
+    // `objects` and `morphisms` blocks hold the comments before them
+    for block in body.order.iter_mut().filter(|item| item.kind.is_block()) {
+        push_slot(slots, source, &block.span, &mut block.trivia);
+    }
+    for obj in &mut body.objects {
+        push_slot(slots, source, &obj.span, &mut obj.trivia);
+    }
+    for entity in &mut body.entities {
+        push_slot(slots, source, &entity.span, &mut entity.trivia);
+        for field in &mut entity.fields {
+            push_slot(slots, source, &field.span, &mut field.trivia);
+        }
+    }
+    for morph in &mut body.morphisms {
+        push_slot(slots, source, &morph.span, &mut morph.trivia);
+    }
+    for derivation in &mut body.derivations {
+        push_slot(slots, source, &derivation.span, &mut derivation.trivia);
+    }
+    for agg in &mut body.aggregates {
+        push_slot(slots, source, &agg.span, &mut agg.trivia);
+    }
+    for vo in &mut body.value_objects {
+        push_slot(slots, source, &vo.span, &mut vo.trivia);
+        for field in &mut vo.fields {
+            push_slot(slots, source, &field.span, &mut field.trivia);
+        }
+    }
+    for enum_decl in &mut body.enums {
+        push_slot(slots, source, &enum_decl.span, &mut enum_decl.trivia);
+    }
+    for eq in &mut body.equations {
+        push_slot(slots, source, &eq.span, &mut eq.trivia);
+    }
+    for process in &mut body.processes {
+        push_slot(slots, source, &process.span, &mut process.trivia);
+    }
+    for actor in &mut body.actors {
+        push_slot(slots, source, &actor.span, &mut actor.trivia);
+    }
+    for policy in &mut body.policies {
+        push_slot(slots, source, &policy.span, &mut policy.trivia);
+    }
+    for hotspot in &mut body.hotspots {
+        push_slot(slots, source, &hotspot.span, &mut hotspot.trivia);
+    }
+    for lifecycle in &mut body.lifecycles {
+        push_slot(slots, source, &lifecycle.span, &mut lifecycle.trivia);
+    }
+}
+
+fn push_slot<'a>(slots: &mut Vec<Slot<'a>>, source: &str, span: &Span, trivia: &'a mut Trivia) {
+    let is_block = source.as_bytes().get(span.end.wrapping_sub(1)) == Some(&b'}');
+    slots.push(Slot {
+        start: span.start,
+        block_end: is_block.then_some(span.end),
+        trivia,
+    });
+}
+
+/// Find all comments and blank lines in the source, skipping string literals.
+///
+/// Consecutive blank lines are reported once.
+fn scan_trivia(source: &str) -> Vec<RawTrivia> {
+    let bytes = source.as_bytes();
+    let mut result: Vec<RawTrivia> = Vec::new();
+    let mut line_has_code = false;
+    let mut line_has_comment = false;
+    let mut previous_blank = false;
+    let mut line_start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\n' => {
+                let is_blank = !line_has_code && !line_has_comment;
+                if is_blank && !previous_blank && line_start > 0 {
+                    result.push(RawTrivia {
+                        offset: line_start,
+                        item: TriviaItem::BlankLine,
+                        after_code: false,
+                    });
+                }
+                previous_blank = is_blank;
+                line_has_code = false;
+                line_has_comment = false;
+                line_start = i + 1;
+                i += 1;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                let end = source[i..].find('\n').map_or(bytes.len(), |n| i + n);
+                result.push(RawTrivia {
+                    offset: i,
+                    item: TriviaItem::Comment(source[i..end].trim_end().to_string()),
+                    after_code: line_has_code,
+                });
+                line_has_comment = true;
+                i = end;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                let end = source[i + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |n| i + 2 + n + 2);
+                result.push(RawTrivia {
+                    offset: i,
+                    item: TriviaItem::Comment(source[i..end].to_string()),
+                    after_code: line_has_code,
+                });
+                line_has_comment = true;
+                if let Some(last_newline) = source[i..end].rfind('\n') {
+                    line_start = i + last_newline + 1;
+                    line_has_code = false;
+                }
+                i = end;
+            }
+            b'"' => {
+                line_has_code = true;
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' && bytes[i] != b'\n' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                i += 1;
+            }
+            c => {
+                if !c.is_ascii_whitespace() {
+                    line_has_code = true;
+                }
+                i += 1;
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_file;
+
+    #[test]
+    fn test_attach_trivia() {
+        let source = r#"// Sales model
+
+/// Core context
+@version(2)
+context Commerce { // core domain
+  // People who buy things
+  entity Customer {
+    name: String // display name
+
+    email: String
+    // more fields later
+  }
+}
+// end
+"#;
+        let file = parse_file(source).unwrap();
+        let ctx = &file.contexts[0];
+        let customer = &ctx.entities[0];
+
+        assert_eq!(
+            ctx.trivia.leading,
+            vec![
+                TriviaItem::Comment("// Sales model".into()),
+                TriviaItem::BlankLine,
+                TriviaItem::Comment("/// Core context".into()),
+            ]
+        );
+        assert_eq!(ctx.trivia.trailing.as_deref(), Some("// core domain"));
+        assert_eq!(
+            customer.trivia.leading,
+            vec![TriviaItem::Comment("// People who buy things".into())]
+        );
+        assert_eq!(
+            customer.fields[0].trivia.trailing.as_deref(),
+            Some("// display name")
+        );
+        assert_eq!(
+            customer.fields[1].trivia.leading,
+            vec![TriviaItem::BlankLine]
+        );
+        assert_eq!(
+            customer.trivia.dangling,
+            vec![TriviaItem::Comment("// more fields later".into())]
+        );
+        assert_eq!(
+            file.trailing_trivia,
+            vec![TriviaItem::Comment("// end".into())]
+        );
+    }
+
+    #[test]
+    fn test_scan_trivia() {
+        let source = "// top\ncontext A { // open\n\n\n  x: \"//not\" /* b */\n}\n";
+        let items: Vec<_> = scan_trivia(source)
+            .into_iter()
+            .map(|t| (t.item, t.after_code))
+            .collect();
+
+        assert_eq!(
+            items,
+            vec![
+                (TriviaItem::Comment("// top".into()), false),
+                (TriviaItem::Comment("// open".into()), true),
+                (TriviaItem::BlankLine, false),
+                (TriviaItem::Comment("/* b */".into()), true),
+            ]
+        );
+    }
+}
@@ -0,0 +1,701 @@
+//! Semantic Model to AST transformation.
+//!
+//! The inverse of [`transform`](crate::transform::transform): turns a
+//! [`Workspace`] back into a [`File`] that the pretty-printer writes as
+//! source, so that a model imported from another format can be edited as
+//! a `.sddd` file again.
+//!
+//! The model keeps less than the source does, so the file is the plainest
+//! one describing the model rather than the file it was parsed from:
+//! comments, templates, entity fields and the type arguments of fields are
+//! gone, objects that were added implicitly are declared, and lifecycles
+//! name their enum. Transforming the file gives back an equivalent model.
+
+use std::collections::{HashMap, HashSet};
+
+use sketchddd_core::sketch::{MorphismId, Object, ObjectId};
+use sketchddd_core::{
+    BoundedContext, Domain, Enforcement, FieldConstraint, Lifecycle, Metadata, MetadataValue,
+    NamedContextMap, Workspace,
+};
+
+use crate::ast::{
+    ActorDecl, AggregateDecl, BinaryOperator, ConstraintDecl, ConstraintValue, ContextDecl,
+    ContextMapDecl, DecoratorDecl, DerivationDecl, DomainDecl, EntityDecl, EnumDecl, EquationDecl,
+    Expr, FieldDecl, File, HotspotDecl, InvariantDecl, InvariantMode, LifecycleDecl, MorphismDecl,
+    MorphismMappingDecl, ObjectDecl, ObjectMappingDecl, Path, PolicyDecl, ProcessDecl,
+    ProcessStepDecl, SharedKernelDecl, Span, SubdomainDecl, TransitionChainDecl, Trivia, TypeExpr,
+    UnaryOperator, ValueObjectDecl, VariantDecl,
+};
+
+/// Transform a semantic model into an AST File.
+pub fn unparse(workspace: &Workspace) -> File {
+    File {
+        shared_kernels: workspace
+            .shared_kernels
+            .iter()
+            .map(unparse_shared_kernel)
+            .collect(),
+        contexts: workspace
+            .contexts
+            .iter()
+            .map(|context| unparse_context(context, workspace))
+            .collect(),
+        context_maps: workspace
+            .context_maps
+            .iter()
+            .map(unparse_context_map)
+            .collect(),
+        domains: workspace.domains.iter().map(unparse_domain).collect(),
+        ..Default::default()
+    }
+}
+
+/// Transform a bounded context into a context declaration.
+///
+/// Objects included from a shared kernel of the model are left out: the
+/// context picks them up again by referring to them.
+fn unparse_context(context: &BoundedContext, workspace: &Workspace) -> ContextDecl {
+    let graph = context.graph();
+    let name = |id: ObjectId| object_name(context, id);
+    let mut allows = Allows::new(context);
+
+    let mut decl = ContextDecl {
+        name: context.name().to_string(),
+        ..Default::default()
+    };
+    if let Some(version) = context.version() {
+        decl.decorators.push(decorator(
+            "version",
+            Some(ConstraintValue::Text(version.to_string())),
+        ));
+    }
+    decl.decorators
+        .extend(metadata_decorators(context.metadata()));
+    decl.decorators.extend(allows.take(None));
+
+    let from_kernel = |object: &Object| {
+        context
+            .shared_kernel_of(object.id)
+            .and_then(|kernel| workspace.shared_kernel(kernel))
+            .is_some_and(|kernel| kernel.graph().find_object_by_name(&object.name).is_some())
+    };
+    for object in objects(context).into_iter().filter(|o| !from_kernel(o)) {
+        declare_object(context, object, &mut allows, &mut decl);
+    }
+
+    // Morphisms standing for structure, such as identities and the fields
+    // of value objects, are implied by the declarations above
+    let structural: HashSet<MorphismId> = context
+        .sketch()
+        .limits
+        .iter()
+        .flat_map(|limit| limit.projections.iter().map(|p| p.morphism))
+        .chain(
+            context
+                .sketch()
+                .colimits
+                .iter()
+                .flat_map(|colimit| colimit.injections.iter().filter_map(|i| i.morphism)),
+        )
+        .collect();
+    let mut morphisms: Vec<_> = graph.morphisms().collect();
+    morphisms.sort_by_key(|m| m.id);
+    for morphism in morphisms {
+        if morphism.is_identity || structural.contains(&morphism.id) {
+            continue;
+        }
+        let source = name(morphism.source);
+        let mut target = TypeExpr::simple(name(morphism.target));
+        if let Some(collection) = context.collection_of(morphism.id) {
+            target = TypeExpr::generic(collection.name(), target);
+        }
+        if context.is_optional(morphism.id) {
+            target = TypeExpr::optional(target);
+        }
+        match context.derivation_of(morphism.id) {
+            Some(expression) => decl.derivations.push(DerivationDecl {
+                name: morphism.name.clone(),
+                source: TypeExpr::simple(source),
+                target,
+                expression: unparse_expr(expression),
+                trivia: Trivia::default(),
+                span: Span::default(),
+            }),
+            None => {
+                let mut decl_morphism =
+                    MorphismDecl::new(&morphism.name, TypeExpr::simple(source), target);
+                decl_morphism.constraints =
+                    constraints(context.field_constraints(morphism.source, &morphism.name));
+                decl_morphism.decorators = removal(context, morphism.source, Some(&morphism.name));
+                decl_morphism
+                    .decorators
+                    .extend(allows.take(Some(&morphism.name)));
+                decl.morphisms.push(decl_morphism);
+            }
+        }
+    }
+
+    for &root in context.aggregate_roots() {
+        let Some(limit) = context.get_aggregate(root) else {
+            continue;
+        };
+        let mut aggregate = AggregateDecl::new(&limit.name);
+        let root_name = name(root);
+        if root_name != limit.name {
+            aggregate.root = Some(root_name);
+        }
+        aggregate.contains = limit.projections.iter().map(|p| name(p.target)).collect();
+        aggregate.invariants = context
+            .invariants_of(root)
+            .into_iter()
+            .map(|invariant| InvariantDecl {
+                expression: unparse_expr(&invariant.expression),
+                mode: match invariant.enforcement {
+                    Enforcement::Eventual => Some(InvariantMode::Eventual),
+                    Enforcement::Strict => None,
+                },
+                span: Span::default(),
+            })
+            .collect();
+        if let Some(metadata) = context.aggregate_metadata(root) {
+            aggregate.decorators = metadata_decorators(metadata);
+        }
+        aggregate.decorators.extend(allows.take(Some(&limit.name)));
+        decl.aggregates.push(aggregate);
+    }
+
+    for equation in &context.sketch().equations {
+        let path = |path: &sketchddd_core::sketch::Path| {
+            let morphisms = path
+                .morphisms
+                .iter()
+                .filter_map(|&m| graph.get_morphism(m))
+                .map(|m| m.name.clone());
+            Path::new(
+                std::iter::once(name(path.source))
+                    .chain(morphisms)
+                    .collect(),
+            )
+        };
+        decl.equations.push(EquationDecl {
+            name: Some(equation.name.clone()).filter(|n| !n.is_empty() && n != "anonymous"),
+            mode: equation.eventual.then_some(InvariantMode::Eventual),
+            lhs: path(&equation.lhs),
+            rhs: path(&equation.rhs),
+            trivia: Trivia::default(),
+            span: Span::default(),
+        });
+    }
+
+    for process in context.processes() {
+        decl.processes.push(ProcessDecl {
+            name: process.name.clone(),
+            steps: process
+                .steps
+                .iter()
+                .map(|step| ProcessStepDecl {
+                    event: name(step.event),
+                    command: name(step.command),
+                    terminated: false,
+                    span: Span::default(),
+                })
+                .collect(),
+            trivia: Trivia::default(),
+            span: Span::default(),
+        });
+    }
+
+    for actor in context.actors() {
+        decl.actors.push(ActorDecl {
+            name: actor.name.clone(),
+            commands: actor.commands.iter().map(|&c| name(c)).collect(),
+            trivia: Trivia::default(),
+            span: Span::default(),
+        });
+    }
+    for policy in context.policies() {
+        decl.policies.push(PolicyDecl {
+            name: policy.name.clone(),
+            event: name(policy.event),
+            commands: policy.commands.iter().map(|&c| name(c)).collect(),
+            trivia: Trivia::default(),
+            span: Span::default(),
+        });
+    }
+    for hotspot in context.hotspots() {
+        decl.hotspots.push(HotspotDecl {
+            target: hotspot.target.map(name),
+            note: hotspot.note.clone(),
+            trivia: Trivia::default(),
+            span: Span::default(),
+        });
+    }
+
+    for lifecycle in context.lifecycles() {
+        decl.lifecycles
+            .push(unparse_lifecycle(lifecycle, name(lifecycle.subject)));
+    }
+
+    decl
+}
+
+/// Transform a shared kernel into a shared kernel declaration holding its
+/// objects.
+fn unparse_shared_kernel(kernel: &BoundedContext) -> SharedKernelDecl {
+    let mut allows = Allows::new(kernel);
+    let mut decl = ContextDecl::default();
+    for object in objects(kernel) {
+        declare_object(kernel, object, &mut allows, &mut decl);
+    }
+    SharedKernelDecl {
+        name: kernel.name().to_string(),
+        objects: decl.objects,
+        entities: decl.entities,
+        value_objects: decl.value_objects,
+        enums: decl.enums,
+        trivia: Trivia::default(),
+        span: Span::default(),
+    }
+}
+
+/// Get the objects of a context in the order they were added.
+fn objects(context: &BoundedContext) -> Vec<&Object> {
+    let mut objects: Vec<_> = context.graph().objects().collect();
+    objects.sort_by_key(|o| o.id);
+    objects
+}
+
+/// Get the name of an object, empty if it does not exist.
+fn object_name(context: &BoundedContext, id: ObjectId) -> String {
+    context
+        .graph()
+        .get_object(id)
+        .map(|o| o.name.clone())
+        .unwrap_or_default()
+}
+
+/// Declare an object as an entity, value object, enum or plain object,
+/// according to its kind.
+fn declare_object(
+    context: &BoundedContext,
+    object: &Object,
+    allows: &mut Allows,
+    decl: &mut ContextDecl,
+) {
+    let mut decorators = removal(context, object.id, None);
+    if let Some(metadata) = context.object_metadata(object.id) {
+        decorators.extend(metadata_decorators(metadata));
+    }
+    decorators.extend(allows.take(Some(&object.name)));
+
+    if context.is_entity(object.id) {
+        let mut entity = EntityDecl::new(&object.name);
+        entity.decorators = decorators;
+        decl.entities.push(entity);
+    } else if context.is_value_object(object.id) {
+        let mut value_object = ValueObjectDecl::new(&object.name);
+        value_object.decorators = decorators;
+        let projections = context
+            .get_value_object_limit(object.id)
+            .map(|limit| limit.projections.as_slice())
+            .unwrap_or_default();
+        for projection in projections {
+            let Some(morphism) = context.graph().get_morphism(projection.morphism) else {
+                continue;
+            };
+            let type_name = object_name(context, projection.target);
+            let mut field = FieldDecl::new(&morphism.name, TypeExpr::simple(type_name));
+            field.constraints = constraints(context.field_constraints(object.id, &morphism.name));
+            field.decorators = removal(context, object.id, Some(&morphism.name));
+            field.decorators.extend(allows.take(Some(&morphism.name)));
+            value_object.fields.push(field);
+        }
+        decl.value_objects.push(value_object);
+    } else if let Some(colimit) = context.get_enum_colimit(object.id) {
+        let mut enum_decl = EnumDecl::new(&object.name);
+        enum_decl.variants = colimit.variant_names().map(VariantDecl::new).collect();
+        enum_decl.decorators = decorators;
+        decl.enums.push(enum_decl);
+    } else {
+        decl.objects.push(ObjectDecl::new(&object.name));
+    }
+}
+
+/// Transform a lifecycle, joining transitions that continue one another
+/// into chains.
+fn unparse_lifecycle(lifecycle: &Lifecycle, enum_name: String) -> LifecycleDecl {
+    let mut chains: Vec<Vec<String>> = Vec::new();
+    for transition in &lifecycle.transitions {
+        match chains.last_mut() {
+            Some(chain) if chain.last() == Some(&transition.from) => {
+                chain.push(transition.to.clone())
+            }
+            _ => chains.push(vec![transition.from.clone(), transition.to.clone()]),
+        }
+    }
+    LifecycleDecl {
+        name: lifecycle.name.clone(),
+        enum_name: Some(enum_name),
+        chains: chains
+            .into_iter()
+            .map(|states| TransitionChainDecl {
+                states,
+                terminated: false,
+                span: Span::default(),
+            })
+            .collect(),
+        defaults: lifecycle.defaults.clone(),
+        trivia: Trivia::default(),
+        span: Span::default(),
+    }
+}
+
+/// Transform a context map declaration.
+fn unparse_context_map(map: &NamedContextMap) -> ContextMapDecl {
+    let mut decl = ContextMapDecl::new(map.name(), map.source_context(), map.target_context());
+    decl.pattern = Some(format!("{:?}", map.pattern()));
+    decl.object_mappings = map
+        .object_mappings
+        .iter()
+        .map(|mapping| ObjectMappingDecl {
+            source: mapping.source.clone(),
+            target: mapping.target.clone(),
+            description: mapping.description.clone(),
+            trivia: Trivia::default(),
+            span: Span::default(),
+        })
+        .collect();
+    decl.morphism_mappings = map
+        .morphism_mappings
+        .iter()
+        .map(|mapping| MorphismMappingDecl {
+            source: mapping.source.clone(),
+            target: mapping.target.clone(),
+            description: mapping.description.clone(),
+            trivia: Trivia::default(),
+            span: Span::default(),
+        })
+        .collect();
+    decl
+}
+
+/// Transform a domain declaration.
+fn unparse_domain(domain: &Domain) -> DomainDecl {
+    DomainDecl {
+        name: domain.name.clone(),
+        subdomains: domain
+            .subdomains
+            .iter()
+            .map(|subdomain| SubdomainDecl {
+                name: subdomain.name.clone(),
+                contexts: subdomain.contexts.clone(),
+                decorators: metadata_decorators(&subdomain.metadata),
+                trivia: Trivia::default(),
+                span: Span::default(),
+            })
+            .collect(),
+        trivia: Trivia::default(),
+        span: Span::default(),
+    }
+}
+
+/// Convert a core expression to an AST expression.
+fn unparse_expr(expr: &sketchddd_core::Expr) -> Expr {
+    use sketchddd_core::{BinaryOp, Expr as CoreExpr, UnaryOp};
+
+    let boxed = |expr: &CoreExpr| Box::new(unparse_expr(expr));
+    match expr {
+        CoreExpr::Number(n) => Expr::Number(*n),
+        CoreExpr::Text(s) => Expr::String(s.clone()),
+        CoreExpr::Path(components) => Expr::Path(Path::new(components.clone())),
+        CoreExpr::Binary { op, left, right } => Expr::BinaryOp {
+            left: boxed(left),
+            op: match op {
+                BinaryOp::Add => BinaryOperator::Add,
+                BinaryOp::Sub => BinaryOperator::Sub,
+                BinaryOp::Mul => BinaryOperator::Mul,
+                BinaryOp::Div => BinaryOperator::Div,
+                BinaryOp::Mod => BinaryOperator::Mod,
+                BinaryOp::Eq => BinaryOperator::Eq,
+                BinaryOp::Ne => BinaryOperator::Ne,
+                BinaryOp::Lt => BinaryOperator::Lt,
+                BinaryOp::Le => BinaryOperator::Le,
+                BinaryOp::Gt => BinaryOperator::Gt,
+                BinaryOp::Ge => BinaryOperator::Ge,
+            },
+            right: boxed(right),
+        },
+        CoreExpr::Unary { op, operand } => Expr::UnaryOp {
+            op: match op {
+                UnaryOp::Not => UnaryOperator::Not,
+                UnaryOp::Neg => UnaryOperator::Neg,
+            },
+            operand: boxed(operand),
+        },
+        CoreExpr::Call { function, args } => Expr::FunctionCall {
+            name: function.clone(),
+            args: args.iter().map(unparse_expr).collect(),
+        },
+        CoreExpr::Index { target, index } => Expr::Index {
+            expr: boxed(target),
+            index: boxed(index),
+        },
+    }
+}
+
+/// Convert field constraints to their literal form, e.g. `min: 0`.
+fn constraints(constraints: &[FieldConstraint]) -> Vec<ConstraintDecl> {
+    constraints
+        .iter()
+        .map(|constraint| ConstraintDecl {
+            name: constraint.keyword().to_string(),
+            value: match constraint {
+                FieldConstraint::Min(n) | FieldConstraint::Max(n) => ConstraintValue::Number(*n),
+                FieldConstraint::MinLength(n) | FieldConstraint::MaxLength(n) => {
+                    ConstraintValue::Number(*n as f64)
+                }
+                FieldConstraint::Pattern(p) => ConstraintValue::Text(p.clone()),
+            },
+            span: Span::default(),
+        })
+        .collect()
+}
+
+/// Build the `@removed_in` decorator of an object or field, if scheduled.
+fn removal(context: &BoundedContext, object: ObjectId, field: Option<&str>) -> Vec<DecoratorDecl> {
+    context
+        .removal_of(object, field)
+        .map(|removal| {
+            let version = ConstraintValue::Text(removal.removed_in.to_string());
+            decorator("removed_in", Some(version))
+        })
+        .into_iter()
+        .collect()
+}
+
+/// Build the `@core`, `@tag` and `@meta` decorators of some metadata.
+fn metadata_decorators(metadata: &Metadata) -> Vec<DecoratorDecl> {
+    let mut decorators = Vec::new();
+    if let Some(classification) = metadata.classification {
+        decorators.push(decorator(&classification.to_string(), None));
+    }
+    for tag in &metadata.tags {
+        decorators.push(decorator("tag", Some(ConstraintValue::Text(tag.clone()))));
+    }
+    for (key, value) in &metadata.values {
+        let argument = match value {
+            MetadataValue::Text(text) => ConstraintValue::Text(text.clone()),
+            MetadataValue::Number(n) => ConstraintValue::Number(*n),
+        };
+        let mut meta = decorator("meta", Some(argument));
+        meta.key = Some(key.clone());
+        decorators.push(meta);
+    }
+    decorators
+}
+
+/// Build a decorator with an optional argument.
+fn decorator(name: &str, argument: Option<ConstraintValue>) -> DecoratorDecl {
+    DecoratorDecl {
+        name: name.to_string(),
+        key: None,
+        argument,
+        names: Vec::new(),
+        span: Span::default(),
+    }
+}
+
+/// The rule codes each element of a context is allowed to break, handed
+/// out as `@allow` decorators.
+///
+/// Suppressions refer to elements by name, so each is written once, on the
+/// first declaration of that name. Suppressions about elements the model
+/// does not declare, such as entity fields, are dropped.
+struct Allows {
+    codes: HashMap<Option<String>, Vec<String>>,
+}
+
+impl Allows {
+    fn new(context: &BoundedContext) -> Self {
+        let mut codes: HashMap<Option<String>, Vec<String>> = HashMap::new();
+        for suppression in context.suppressions() {
+            codes
+                .entry(suppression.element.clone())
+                .or_default()
+                .push(suppression.code.clone());
+        }
+        Self { codes }
+    }
+
+    /// Take the `@allow` decorator of an element, or of the whole context.
+    fn take(&mut self, element: Option<&str>) -> Option<DecoratorDecl> {
+        let names = self.codes.remove(&element.map(str::to_string))?;
+        Some(DecoratorDecl {
+            names,
+            ..decorator("allow", None)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pretty::PrettyPrint;
+    use crate::{parse_file, parse_workspace};
+
+    const SOURCE: &str = r#"
+        shared kernel Identity {
+            value UserId {
+                value: String
+            }
+        }
+
+        @version("2.1")
+        @core
+        context Sales {
+            objects { Note, Decimal }
+            @allow(W0001)
+            entity Order
+            entity Customer
+            entity LineItem
+            morphisms {
+                placedBy: Order -> Customer
+                items: Order -> List<LineItem>
+                watchers: Order -> Set<Customer>
+                owner: Order -> UserId
+                note: Order -> Note?
+                @removed_in("3.0")
+                discount: Order -> Money [min: 0]
+            }
+            derive totalPrice: Order -> Money = sum(lines.price) * 2
+            aggregate Order {
+                contains: [Customer]
+                invariant eventual: count(lines) > 0
+            }
+            @tag("pii")
+            value Money {
+                amount: Decimal [min: 0]
+            }
+            enum OrderStatus = Pending | Confirmed | Shipped | Cancelled | Archived
+            equation eventual paid: Order = Order
+            process Fulfillment {
+                on Order -> Customer
+            }
+            actor Clerk { Order }
+            policy Notify { on Order -> Customer }
+            hotspot Order "Who pays for returns?"
+            lifecycle Order: OrderStatus {
+                Pending -> Confirmed -> Shipped
+                Pending -> Cancelled
+                default: Archived
+            }
+        }
+
+        context Billing {
+            entity Invoice
+        }
+
+        map SalesToBilling: Sales -> Billing {
+            pattern: CustomerSupplier
+            mappings {
+                Order -> Invoice: "Each order is billed"
+            }
+        }
+
+        domain Commerce {
+            @supporting
+            subdomain Invoicing {
+                contexts { Billing }
+            }
+        }
+    "#;
+
+    #[test]
+    fn test_unparse_round_trip() {
+        let workspace = parse_workspace(SOURCE).unwrap();
+        let printed = unparse(&workspace).pretty_print();
+        let reparsed = parse_workspace(&printed).unwrap();
+        assert_eq!(unparse(&reparsed).pretty_print(), printed);
+
+        let sales = reparsed.context("Sales").unwrap();
+        let original = workspace.context("Sales").unwrap();
+        assert_eq!(sales.entities().len(), original.entities().len());
+        assert_eq!(sales.value_objects().len(), original.value_objects().len());
+        assert_eq!(
+            sales.graph().morphisms().count(),
+            original.graph().morphisms().count()
+        );
+        assert_eq!(sales.version(), original.version());
+        assert_eq!(sales.metadata(), original.metadata());
+        assert_eq!(sales.suppressions(), original.suppressions());
+        assert_eq!(
+            sales.lifecycles()[0].transitions,
+            original.lifecycles()[0].transitions
+        );
+        assert_eq!(
+            reparsed.context_maps[0].object_mappings,
+            workspace.context_maps[0].object_mappings
+        );
+        assert_eq!(
+            reparsed.domains[0].subdomains[0].metadata,
+            workspace.domains[0].subdomains[0].metadata
+        );
+    }
+
+    #[test]
+    fn test_unparse_declarations() {
+        let file = unparse(&parse_workspace(SOURCE).unwrap());
+        let sales = &file.contexts[0];
+        assert!(sales.objects.iter().all(|o| o.name != "UserId"));
+        assert_eq!(file.shared_kernels[0].value_objects[0].name, "UserId");
+
+        let note = sales.morphisms.iter().find(|m| m.name == "note").unwrap();
+        assert_eq!(note.target, TypeExpr::optional(TypeExpr::simple("Note")));
+        let items = sales.morphisms.iter().find(|m| m.name == "items").unwrap();
+        assert_eq!(
+            items.target,
+            TypeExpr::generic("List", TypeExpr::simple("LineItem"))
+        );
+        let watchers = sales
+            .morphisms
+            .iter()
+            .find(|m| m.name == "watchers")
+            .unwrap();
+        assert_eq!(
+            watchers.target,
+            TypeExpr::generic("Set", TypeExpr::simple("Customer"))
+        );
+        let discount = sales
+            .morphisms
+            .iter()
+            .find(|m| m.name == "discount")
+            .unwrap();
+        assert_eq!(discount.decorators[0].name, "removed_in");
+        assert_eq!(discount.constraints[0].value, ConstraintValue::Number(0.0));
+        assert_eq!(sales.derivations[0].name, "totalPrice");
+        assert!(sales.aggregates[0].root.is_none());
+        let amount = &sales.value_objects[0].fields[0];
+        assert_eq!(amount.type_expr, TypeExpr::simple("Decimal"));
+        assert_eq!(amount.constraints[0].name, "min");
+        assert_eq!(sales.aggregates[0].contains, vec!["Customer"]);
+        assert_eq!(sales.equations[0].name.as_deref(), Some("paid"));
+
+        let chains: Vec<_> = sales.lifecycles[0]
+            .chains
+            .iter()
+            .map(|c| c.states.join(" -> "))
+            .collect();
+        assert_eq!(
+            chains,
+            vec!["Pending -> Confirmed -> Shipped", "Pending -> Cancelled"]
+        );
+        assert_eq!(
+            file.context_maps[0].pattern.as_deref(),
+            Some("CustomerSupplier")
+        );
+
+        let printed = file.pretty_print();
+        assert!(parse_file(&printed).is_ok(), "{}", printed);
+    }
+}
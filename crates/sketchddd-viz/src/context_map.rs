@@ -0,0 +1,374 @@
+//! Context map diagram generation.
+//!
+//! Renders the bounded contexts of a model and the context maps between
+//! them. Contexts are drawn inside the subdomain they belong to, and
+//! subdomains inside their domain; contexts outside any subdomain are drawn
+//! on their own.
+//!
+//! Each context map is an edge labelled with the abbreviation of its
+//! relationship pattern, such as `ACL`. Edges point from the upstream to the
+//! downstream context, marked `U` and `D` where the pattern makes the source
+//! upstream; symmetric patterns point both ways, and separate ways neither.
+//! A theme can style the edges of each of these kinds.
+
+use std::collections::HashSet;
+
+use crate::graphviz::{font_attributes, identifier, quote};
+use crate::mermaid::{font_directive, label, plain, Ids};
+use crate::theme::{Direction, LineStyle, Theme};
+use crate::{classification_color, drawio, excalidraw, Format, VizError};
+use sketchddd_core::{BoundedContext, Domain, NamedContextMap, RelationshipPattern, Workspace};
+
+/// Generate a context map diagram of a workspace in the given format.
+pub fn generate(workspace: &Workspace, format: Format) -> Result<String, VizError> {
+    generate_with_theme(workspace, format, &Theme::default())
+}
+
+/// Generate a context map diagram of a workspace, styled by a theme.
+///
+/// Contexts are filled by classification only; the theme's colours of
+/// kinds of objects do not apply to them.
+pub fn generate_with_theme(
+    workspace: &Workspace,
+    format: Format,
+    theme: &Theme,
+) -> Result<String, VizError> {
+    let layout = Layout::new(&workspace.contexts, &workspace.domains);
+    Ok(match format {
+        Format::Mermaid => mermaid(&layout, &workspace.context_maps, theme),
+        Format::Graphviz => graphviz(&layout, &workspace.context_maps, theme),
+        Format::Excalidraw => return excalidraw::generate_context_map(workspace, theme),
+        Format::Drawio => return drawio::generate_context_map(workspace, theme),
+        Format::Html => {
+            return Err(VizError::UnsupportedFormat(
+                "context maps are drawn in Graphviz, Mermaid, Excalidraw or draw.io".to_string(),
+            ))
+        }
+    })
+}
+
+/// Contexts grouped by domain and subdomain.
+pub(crate) struct Layout<'a> {
+    contexts: &'a [BoundedContext],
+    /// Per domain, its subdomains with the contexts drawn in them
+    pub domains: Vec<(&'a Domain, Vec<Vec<&'a BoundedContext>>)>,
+    /// Contexts outside any subdomain
+    pub ungrouped: Vec<&'a BoundedContext>,
+}
+
+impl<'a> Layout<'a> {
+    /// Place each context in the first subdomain listing it.
+    pub(crate) fn new(contexts: &'a [BoundedContext], domains: &'a [Domain]) -> Self {
+        let mut placed = HashSet::new();
+        let domains = domains
+            .iter()
+            .map(|domain| {
+                let groups = domain
+                    .subdomains
+                    .iter()
+                    .map(|subdomain| {
+                        contexts
+                            .iter()
+                            .filter(|c| subdomain.contains(c.name()) && placed.insert(c.name()))
+                            .collect()
+                    })
+                    .collect();
+                (domain, groups)
+            })
+            .collect();
+        let ungrouped = contexts
+            .iter()
+            .filter(|c| !placed.contains(c.name()))
+            .collect();
+
+        Self {
+            contexts,
+            domains,
+            ungrouped,
+        }
+    }
+}
+
+/// Label a subdomain with its classification, e.g. `Ordering (core)`.
+pub(crate) fn subdomain_label(subdomain: &sketchddd_core::Subdomain) -> String {
+    match subdomain.classification() {
+        Some(classification) => format!("{} ({})", subdomain.name, classification),
+        None => subdomain.name.clone(),
+    }
+}
+
+/// How the edge of a context map points.
+#[derive(Clone, Copy)]
+pub(crate) enum Arrow {
+    /// From the upstream source to the downstream target
+    Upstream,
+    /// From the source to the target, neither of them upstream
+    Forward,
+    /// Both ways, between partners
+    Both,
+    /// Neither way, between contexts that do not integrate
+    None,
+}
+
+pub(crate) fn arrow(pattern: RelationshipPattern) -> Arrow {
+    if pattern.source_is_upstream() {
+        Arrow::Upstream
+    } else if pattern.is_symmetric() {
+        Arrow::Both
+    } else if pattern == RelationshipPattern::SeparateWays {
+        Arrow::None
+    } else {
+        Arrow::Forward
+    }
+}
+
+/// Get the line style the theme gives the edges of an arrow.
+pub(crate) fn line_style(arrow: Arrow, theme: &Theme) -> Option<LineStyle> {
+    match arrow {
+        Arrow::Upstream | Arrow::Forward => theme.edges.upstream,
+        Arrow::Both => theme.edges.symmetric,
+        Arrow::None => theme.edges.separate_ways,
+    }
+}
+
+fn mermaid(layout: &Layout<'_>, context_maps: &[NamedContextMap], theme: &Theme) -> String {
+    let mut output = String::new();
+
+    output.push_str("```mermaid\n");
+    output.push_str(&font_directive(theme));
+    output.push_str(&format!(
+        "flowchart {}\n",
+        theme.direction.map_or("LR", Direction::as_str)
+    ));
+
+    let ids = Ids::new(layout.contexts.iter().map(BoundedContext::name));
+    for (domain, groups) in &layout.domains {
+        output.push_str(&format!(
+            "    subgraph domain_{}[\"{}\"]\n",
+            plain(&domain.name),
+            label(&domain.name)
+        ));
+        for (subdomain, contexts) in domain.subdomains.iter().zip(groups) {
+            output.push_str(&format!(
+                "        subgraph subdomain_{}[\"{}\"]\n",
+                plain(&subdomain.name),
+                label(&subdomain_label(subdomain))
+            ));
+            for context in contexts {
+                output.push_str(&format!("            {}\n", ids.declare(context.name())));
+            }
+            output.push_str("        end\n");
+        }
+        output.push_str("    end\n");
+    }
+
+    for context in &layout.ungrouped {
+        output.push_str(&format!("    {}\n", ids.declare(context.name())));
+    }
+
+    for context in layout.contexts {
+        if let Some(classification) = context.metadata().classification {
+            output.push_str(&format!(
+                "    style {} fill:{}\n",
+                ids.get(context.name()),
+                classification_color(classification)
+            ));
+        }
+    }
+
+    for map in context_maps {
+        let pattern = map.pattern.abbreviation();
+        let arrow = arrow(map.pattern);
+        let label = match arrow {
+            Arrow::Upstream => format!("\"{} (U → D)\"", pattern),
+            _ => pattern.to_string(),
+        };
+        // Flowcharts draw dashed lines dotted
+        let link = match (arrow, line_style(arrow, theme)) {
+            (Arrow::Both, Some(LineStyle::Dashed | LineStyle::Dotted)) => "<-.->",
+            (Arrow::Both, Some(LineStyle::Bold)) => "<==>",
+            (Arrow::Both, _) => "<-->",
+            (Arrow::None, Some(LineStyle::Dashed | LineStyle::Dotted)) => "-.-",
+            (Arrow::None, Some(LineStyle::Bold)) => "===",
+            (Arrow::None, _) => "---",
+            (_, Some(LineStyle::Dashed | LineStyle::Dotted)) => "-.->",
+            (_, Some(LineStyle::Bold)) => "==>",
+            (_, _) => "-->",
+        };
+        output.push_str(&format!(
+            "    {} {}|{}| {}\n",
+            ids.get(&map.source_context),
+            link,
+            label,
+            ids.get(&map.target_context)
+        ));
+    }
+
+    output.push_str("```\n");
+    output
+}
+
+fn graphviz(layout: &Layout<'_>, context_maps: &[NamedContextMap], theme: &Theme) -> String {
+    let mut output = String::new();
+
+    output.push_str("digraph ContextMap {\n");
+    output.push_str(&format!(
+        "  rankdir={};\n",
+        theme.direction.map_or("LR", Direction::as_str)
+    ));
+    output.push_str("  node [shape=box];\n");
+    output.push_str(&font_attributes(theme));
+    output.push('\n');
+
+    let node = |context: &BoundedContext| match context.metadata().classification {
+        Some(classification) => format!(
+            "{} [style=filled fillcolor=\"{}\"];",
+            identifier(context.name()),
+            classification_color(classification)
+        ),
+        None => format!("{};", identifier(context.name())),
+    };
+
+    for (domain, groups) in &layout.domains {
+        output.push_str(&format!(
+            "  subgraph {} {{\n",
+            identifier(&format!("cluster_{}", domain.name))
+        ));
+        output.push_str(&format!("    label={};\n", quote(&domain.name)));
+        for (subdomain, contexts) in domain.subdomains.iter().zip(groups) {
+            output.push_str(&format!(
+                "    subgraph {} {{\n",
+                identifier(&format!("cluster_{}_{}", domain.name, subdomain.name))
+            ));
+            output.push_str(&format!(
+                "      label={};\n",
+                quote(&subdomain_label(subdomain))
+            ));
+            for context in contexts {
+                output.push_str(&format!("      {}\n", node(context)));
+            }
+            output.push_str("    }\n");
+        }
+        output.push_str("  }\n");
+    }
+
+    for context in &layout.ungrouped {
+        output.push_str(&format!("  {}\n", node(context)));
+    }
+
+    output.push('\n');
+
+    for map in context_maps {
+        let arrow = arrow(map.pattern);
+        let mut attributes = match arrow {
+            Arrow::Upstream => " taillabel=\"U\" headlabel=\"D\"",
+            Arrow::Forward => "",
+            Arrow::Both => " dir=both",
+            Arrow::None => " dir=none",
+        }
+        .to_string();
+        // Separate ways are dashed unless the theme says otherwise
+        let style = match arrow {
+            Arrow::None => line_style(arrow, theme).or(Some(LineStyle::Dashed)),
+            _ => line_style(arrow, theme),
+        };
+        if let Some(style) = style {
+            attributes.push_str(&format!(" style={}", style.as_str()));
+        }
+        output.push_str(&format!(
+            "  {} -> {} [label=\"{}\"{}];\n",
+            identifier(&map.source_context),
+            identifier(&map.target_context),
+            map.pattern.abbreviation(),
+            attributes
+        ));
+    }
+
+    output.push_str("}\n");
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::EdgeStyles;
+    use sketchddd_core::{Classification, RelationshipPattern, Subdomain};
+
+    fn model() -> Workspace {
+        let mut workspace = Workspace::new();
+        let mut sales = BoundedContext::new("Sales");
+        sales.metadata_mut().classification = Some(Classification::Core);
+        workspace.add_context(sales);
+        workspace.add_context(BoundedContext::new("Shipping"));
+        workspace.add_context_map(NamedContextMap::new(
+            "SalesToShipping",
+            "Sales",
+            "Shipping",
+            RelationshipPattern::CustomerSupplier,
+        ));
+        workspace.add_context(BoundedContext::new("Billing"));
+        workspace.add_context_map(NamedContextMap::new(
+            "SalesAndBilling",
+            "Sales",
+            "Billing",
+            RelationshipPattern::SharedKernel,
+        ));
+
+        let mut ordering = Subdomain::new("Ordering");
+        ordering.metadata.classification = Some(Classification::Core);
+        ordering.add_context("Sales");
+        let mut domain = Domain::new("Commerce");
+        domain.add_subdomain(ordering);
+        workspace.domains.push(domain);
+
+        workspace
+    }
+
+    #[test]
+    fn test_generate_mermaid_context_map() {
+        let result = generate(&model(), Format::Mermaid).unwrap();
+
+        assert!(result.contains(
+            "    subgraph domain_Commerce[\"Commerce\"]\n        subgraph subdomain_Ordering[\"Ordering (core)\"]\n            Sales\n        end\n    end\n    Shipping\n"
+        ));
+        assert!(result.contains("    style Sales fill:#f9d67a\n"));
+        assert!(result.contains("    Sales -->|\"CS (U → D)\"| Shipping\n"));
+        assert!(result.contains("    Sales <-->|SK| Billing\n"));
+    }
+
+    #[test]
+    fn test_generate_graphviz_context_map() {
+        let result = generate(&model(), Format::Graphviz).unwrap();
+
+        assert!(result.contains("  subgraph cluster_Commerce {\n    label=\"Commerce\";\n"));
+        assert!(result.contains(
+            "      label=\"Ordering (core)\";\n      Sales [style=filled fillcolor=\"#f9d67a\"];\n"
+        ));
+        assert!(result.contains("  Shipping;\n"));
+        assert!(result
+            .contains("  Sales -> Shipping [label=\"CS\" taillabel=\"U\" headlabel=\"D\"];\n"));
+        assert!(result.contains("  Sales -> Billing [label=\"SK\" dir=both];\n"));
+    }
+
+    #[test]
+    fn test_generate_context_map_with_theme() {
+        let theme = Theme {
+            direction: Some(Direction::TopToBottom),
+            edges: EdgeStyles {
+                upstream: Some(LineStyle::Bold),
+                symmetric: Some(LineStyle::Dashed),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let result = generate_with_theme(&model(), Format::Mermaid, &theme).unwrap();
+        assert!(result.contains("flowchart TB\n"));
+        assert!(result.contains("    Sales ==>|\"CS (U → D)\"| Shipping\n"));
+        assert!(result.contains("    Sales <-.->|SK| Billing\n"));
+
+        let result = generate_with_theme(&model(), Format::Graphviz, &theme).unwrap();
+        assert!(result.contains("  rankdir=TB;\n"));
+        assert!(result.contains("  Sales -> Billing [label=\"SK\" dir=both style=dashed];\n"));
+    }
+}
@@ -0,0 +1,309 @@
+//! Laid-out drawings for whiteboard tools.
+//!
+//! Excalidraw and draw.io files hold shapes at fixed positions rather than
+//! a graph for the tool to lay out, so diagrams exported to them are laid
+//! out here. A drawing places boxes in groups, such as the objects of an
+//! aggregate or the contexts of a subdomain, and the groups and the boxes
+//! outside any of them in rows, left to right. Links join boxes and are
+//! left for the tool to route.
+
+use crate::context_map::{arrow, line_style, subdomain_label, Arrow, Layout};
+use crate::theme::{LineStyle, Theme};
+use crate::{aggregate_clusters, classification_color, fill_color, morphism_style};
+use sketchddd_core::{BoundedContext, Workspace};
+use std::collections::HashMap;
+
+/// Height of a box.
+const BOX_HEIGHT: f64 = 50.0;
+
+/// Space between boxes, and between groups.
+const GAP: f64 = 60.0;
+
+/// Space between a group's border and its boxes.
+const PADDING: f64 = 20.0;
+
+/// Height of a group's title.
+const TITLE_HEIGHT: f64 = 30.0;
+
+/// Width past which rows of groups and boxes wrap.
+const ROW_WIDTH: f64 = 1200.0;
+
+/// A box of a drawing, such as an object or a context.
+pub(crate) struct Shape {
+    pub label: String,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    /// Fill colour, if any
+    pub fill: Option<String>,
+    /// Whether the box is outlined in bold, as aggregate roots are
+    pub bold: bool,
+    /// Index of the group the box is in, if any
+    pub group: Option<usize>,
+}
+
+/// A group of boxes, such as an aggregate or a subdomain.
+pub(crate) struct Group {
+    pub label: String,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// A link between two boxes, by their index.
+pub(crate) struct Link {
+    pub from: usize,
+    pub to: usize,
+    pub label: String,
+    pub style: Option<LineStyle>,
+    /// Whether the link has an arrowhead at its start
+    pub start_arrow: bool,
+    /// Whether the link has an arrowhead at its end
+    pub end_arrow: bool,
+}
+
+/// A laid-out diagram.
+pub(crate) struct Drawing {
+    pub name: String,
+    pub shapes: Vec<Shape>,
+    pub groups: Vec<Group>,
+    pub links: Vec<Link>,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Drawing {
+    /// Lay out the class diagram of a context: its objects, grouped by
+    /// aggregate, and its morphisms.
+    pub fn of_context(context: &BoundedContext, theme: &Theme) -> Self {
+        let graph = context.graph();
+        let mut drawing = Drawing::new(context.name());
+        let mut shapes = HashMap::new();
+        let mut add = |drawing: &mut Drawing, id, group| {
+            let Some(object) = graph.get_object(id) else {
+                return;
+            };
+            let index = drawing.add_shape(
+                &object.name,
+                fill_color(context, id, theme),
+                context.is_aggregate_root(id),
+                group,
+            );
+            shapes.insert(id, index);
+        };
+
+        let (clusters, mut ungrouped) = aggregate_clusters(context);
+        for cluster in &clusters {
+            let group = drawing.add_group(cluster.name);
+            for &id in std::iter::once(&cluster.root).chain(&cluster.members) {
+                add(&mut drawing, id, Some(group));
+            }
+        }
+        ungrouped.sort();
+        for id in ungrouped {
+            add(&mut drawing, id, None);
+        }
+
+        let mut morphisms: Vec<_> = graph.morphisms().filter(|m| !m.is_identity).collect();
+        morphisms.sort_by_key(|m| m.id);
+        for morphism in morphisms {
+            if let (Some(&from), Some(&to)) =
+                (shapes.get(&morphism.source), shapes.get(&morphism.target))
+            {
+                drawing.links.push(Link {
+                    from,
+                    to,
+                    label: morphism.name.clone(),
+                    style: morphism_style(context, morphism.id, theme),
+                    start_arrow: false,
+                    end_arrow: true,
+                });
+            }
+        }
+
+        drawing.place();
+        drawing
+    }
+
+    /// Lay out the context map of a workspace: its contexts, grouped by
+    /// subdomain, and the maps between them.
+    pub fn of_workspace(workspace: &Workspace, theme: &Theme) -> Self {
+        let layout = Layout::new(&workspace.contexts, &workspace.domains);
+        let mut drawing = Drawing::new("ContextMap");
+        let mut shapes = HashMap::new();
+        let mut add = |drawing: &mut Drawing, context: &BoundedContext, group| {
+            let fill = context
+                .metadata()
+                .classification
+                .map(|classification| classification_color(classification).to_string());
+            shapes.insert(
+                context.name().to_string(),
+                drawing.add_shape(context.name(), fill, false, group),
+            );
+        };
+
+        for (domain, groups) in &layout.domains {
+            for (subdomain, contexts) in domain.subdomains.iter().zip(groups) {
+                let label = format!("{} / {}", domain.name, subdomain_label(subdomain));
+                let group = drawing.add_group(&label);
+                for context in contexts {
+                    add(&mut drawing, context, Some(group));
+                }
+            }
+        }
+        for context in &layout.ungrouped {
+            add(&mut drawing, context, None);
+        }
+
+        for map in &workspace.context_maps {
+            let (Some(&from), Some(&to)) = (
+                shapes.get(&map.source_context),
+                shapes.get(&map.target_context),
+            ) else {
+                continue;
+            };
+            let pattern = map.pattern.abbreviation();
+            let arrow = arrow(map.pattern);
+            // Separate ways are dashed unless the theme says otherwise
+            let style = match arrow {
+                Arrow::None => line_style(arrow, theme).or(Some(LineStyle::Dashed)),
+                _ => line_style(arrow, theme),
+            };
+            drawing.links.push(Link {
+                from,
+                to,
+                label: match arrow {
+                    Arrow::Upstream => format!("{} (U → D)", pattern),
+                    _ => pattern.to_string(),
+                },
+                style,
+                start_arrow: matches!(arrow, Arrow::Both),
+                end_arrow: !matches!(arrow, Arrow::None),
+            });
+        }
+
+        drawing.place();
+        drawing
+    }
+
+    fn new(name: &str) -> Self {
+        Drawing {
+            name: name.to_string(),
+            shapes: Vec::new(),
+            groups: Vec::new(),
+            links: Vec::new(),
+            width: 0.0,
+            height: 0.0,
+        }
+    }
+
+    fn add_group(&mut self, label: &str) -> usize {
+        self.groups.push(Group {
+            label: label.to_string(),
+            x: 0.0,
+            y: 0.0,
+            width: 0.0,
+            height: 0.0,
+        });
+        self.groups.len() - 1
+    }
+
+    fn add_shape(
+        &mut self,
+        label: &str,
+        fill: Option<String>,
+        bold: bool,
+        group: Option<usize>,
+    ) -> usize {
+        self.shapes.push(Shape {
+            label: label.to_string(),
+            x: 0.0,
+            y: 0.0,
+            width: (label.chars().count() as f64 * 9.0 + 40.0).max(120.0),
+            height: BOX_HEIGHT,
+            fill,
+            bold,
+            group,
+        });
+        self.shapes.len() - 1
+    }
+
+    /// Place the groups, with their boxes side by side, and the boxes
+    /// outside any group in rows.
+    fn place(&mut self) {
+        // Each group, and each box outside any group, is a block of the rows
+        let mut blocks: Vec<(Option<usize>, Vec<usize>)> = (0..self.groups.len())
+            .map(|g| (Some(g), Vec::new()))
+            .collect();
+        for (i, shape) in self.shapes.iter().enumerate() {
+            match shape.group {
+                Some(group) => blocks[group].1.push(i),
+                None => blocks.push((None, vec![i])),
+            }
+        }
+
+        let (mut x, mut y, mut row_height) = (0.0_f64, 0.0_f64, 0.0_f64);
+        for (group, shapes) in blocks {
+            let inset = if group.is_some() { PADDING } else { 0.0 };
+            let title = if group.is_some() { TITLE_HEIGHT } else { 0.0 };
+            let content: f64 = shapes.iter().map(|&i| self.shapes[i].width).sum::<f64>()
+                + GAP * shapes.len().saturating_sub(1) as f64;
+            let width = content + 2.0 * inset;
+            let height = BOX_HEIGHT + 2.0 * inset + title;
+            if x > 0.0 && x + width > ROW_WIDTH {
+                x = 0.0;
+                y += row_height + GAP;
+                row_height = 0.0;
+            }
+
+            let mut left = x + inset;
+            for &i in &shapes {
+                let shape = &mut self.shapes[i];
+                shape.x = left;
+                shape.y = y + inset + title;
+                left += shape.width + GAP;
+            }
+            if let Some(group) = group {
+                let group = &mut self.groups[group];
+                (group.x, group.y, group.width, group.height) = (x, y, width, height);
+            }
+
+            x += width + GAP;
+            row_height = row_height.max(height);
+            self.width = self.width.max(x - GAP);
+        }
+        self.height = y + row_height;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_of_context_groups_aggregates() {
+        let mut context = BoundedContext::new("Sales");
+        let order = context.add_entity("Order");
+        let line = context.add_entity("LineItem");
+        context.add_value_object("Money");
+        context.define_aggregate_with_members("Order", order, &[line]);
+
+        let drawing = Drawing::of_context(&context, &Theme::default());
+        assert_eq!(drawing.groups.len(), 1);
+        let group = &drawing.groups[0];
+        let labels: Vec<&str> = drawing.shapes.iter().map(|s| s.label.as_str()).collect();
+        assert_eq!(labels, ["Order", "LineItem", "Money"]);
+        assert!(drawing.shapes[0].bold);
+
+        // Boxes of the aggregate lie inside it, the others outside
+        for shape in &drawing.shapes[..2] {
+            assert!(shape.x >= group.x && shape.x + shape.width <= group.x + group.width);
+            assert!(shape.y >= group.y && shape.y + shape.height <= group.y + group.height);
+        }
+        assert!(drawing.shapes[2].x > group.x + group.width);
+        assert_eq!(drawing.links.len(), 1);
+        assert_eq!(drawing.width, drawing.shapes[2].x + drawing.shapes[2].width);
+    }
+}
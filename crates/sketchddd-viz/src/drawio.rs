@@ -0,0 +1,186 @@
+//! draw.io export.
+//!
+//! Writes a laid-out drawing as a `.drawio` file: groups are containers
+//! titled with their name, boxes are cells inside them, and links are edges
+//! between the boxes, routed by draw.io. Themes set colours, line styles
+//! and the font.
+
+use crate::drawing::Drawing;
+use crate::html::escape;
+use crate::theme::{LineStyle, Theme};
+use crate::VizError;
+use sketchddd_core::{BoundedContext, Workspace};
+use std::fmt::Write;
+
+/// Generate a draw.io diagram of a bounded context.
+pub fn generate(context: &BoundedContext) -> Result<String, VizError> {
+    generate_with_theme(context, &Theme::default())
+}
+
+/// Generate a draw.io diagram of a bounded context, styled by a theme.
+pub fn generate_with_theme(context: &BoundedContext, theme: &Theme) -> Result<String, VizError> {
+    Ok(write(&Drawing::of_context(context, theme), theme))
+}
+
+/// Generate a draw.io diagram of the context map of a workspace, styled by
+/// a theme.
+pub fn generate_context_map(workspace: &Workspace, theme: &Theme) -> Result<String, VizError> {
+    Ok(write(&Drawing::of_workspace(workspace, theme), theme))
+}
+
+/// Write a drawing as a draw.io file.
+fn write(drawing: &Drawing, theme: &Theme) -> String {
+    let font = theme
+        .font
+        .as_ref()
+        .map(|font| format!("fontFamily={};", font))
+        .unwrap_or_default();
+
+    let mut output = String::new();
+    output.push_str("<mxfile host=\"SketchDDD\">\n");
+    let _ = writeln!(
+        output,
+        "  <diagram id=\"{0}\" name=\"{0}\">",
+        escape(&drawing.name)
+    );
+    let _ = writeln!(
+        output,
+        "    <mxGraphModel grid=\"1\" gridSize=\"10\" guides=\"1\" tooltips=\"1\" connect=\"1\" arrows=\"1\" fold=\"1\" page=\"1\" pageScale=\"1\" pageWidth=\"{}\" pageHeight=\"{}\" math=\"0\" shadow=\"0\">",
+        drawing.width.ceil(),
+        drawing.height.ceil()
+    );
+    output.push_str("      <root>\n");
+    output.push_str("        <mxCell id=\"0\"/>\n");
+    output.push_str("        <mxCell id=\"1\" parent=\"0\"/>\n");
+
+    for (i, group) in drawing.groups.iter().enumerate() {
+        let _ = writeln!(
+            output,
+            "        <mxCell id=\"group-{}\" value=\"{}\" style=\"swimlane;rounded=1;startSize=30;fillColor=none;dashed=1;{}\" vertex=\"1\" parent=\"1\">",
+            i,
+            escape(&group.label),
+            font
+        );
+        geometry(&mut output, group.x, group.y, group.width, group.height);
+        output.push_str("        </mxCell>\n");
+    }
+
+    // Boxes in a group are placed relative to it
+    for (i, shape) in drawing.shapes.iter().enumerate() {
+        let (parent, x, y) = match shape.group {
+            Some(group) => {
+                let group_box = &drawing.groups[group];
+                (
+                    format!("group-{}", group),
+                    shape.x - group_box.x,
+                    shape.y - group_box.y,
+                )
+            }
+            None => ("1".to_string(), shape.x, shape.y),
+        };
+        let _ = writeln!(
+            output,
+            "        <mxCell id=\"shape-{}\" value=\"{}\" style=\"rounded=1;whiteSpace=wrap;html=1;fillColor={};strokeWidth={};{}\" vertex=\"1\" parent=\"{}\">",
+            i,
+            escape(&shape.label),
+            shape.fill.as_deref().unwrap_or("#ffffff"),
+            if shape.bold { 3 } else { 1 },
+            font,
+            parent
+        );
+        geometry(&mut output, x, y, shape.width, shape.height);
+        output.push_str("        </mxCell>\n");
+    }
+
+    for (i, link) in drawing.links.iter().enumerate() {
+        let line = match link.style {
+            Some(LineStyle::Dashed) => "dashed=1;",
+            Some(LineStyle::Dotted) => "dashed=1;dashPattern=1 4;",
+            Some(LineStyle::Bold) => "strokeWidth=3;",
+            _ => "",
+        };
+        let head = |shown: bool| if shown { "classic" } else { "none" };
+        let _ = writeln!(
+            output,
+            "        <mxCell id=\"link-{}\" value=\"{}\" style=\"edgeStyle=orthogonalEdgeStyle;rounded=1;html=1;startArrow={};endArrow={};{}{}\" edge=\"1\" parent=\"1\" source=\"shape-{}\" target=\"shape-{}\">",
+            i,
+            escape(&link.label),
+            head(link.start_arrow),
+            head(link.end_arrow),
+            line,
+            font,
+            link.from,
+            link.to
+        );
+        output.push_str("          <mxGeometry relative=\"1\" as=\"geometry\"/>\n");
+        output.push_str("        </mxCell>\n");
+    }
+
+    output.push_str("      </root>\n");
+    output.push_str("    </mxGraphModel>\n");
+    output.push_str("  </diagram>\n");
+    output.push_str("</mxfile>\n");
+    output
+}
+
+/// Write the geometry of a cell.
+fn geometry(output: &mut String, x: f64, y: f64, width: f64, height: f64) {
+    let _ = writeln!(
+        output,
+        "          <mxGeometry x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" as=\"geometry\"/>",
+        x, y, width, height
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sketchddd_core::{NamedContextMap, RelationshipPattern};
+
+    #[test]
+    fn test_generate_nests_aggregate_members() {
+        let mut context = BoundedContext::new("Sales");
+        let order = context.add_entity("Order");
+        let customer = context.add_entity("Customer");
+        context.define_aggregate_with_members("Order", order, &[]);
+        context
+            .sketch_mut()
+            .graph
+            .add_morphism("placedBy", order, customer);
+        let theme = Theme {
+            font: Some("Inter".to_string()),
+            ..Default::default()
+        };
+
+        let result = generate_with_theme(&context, &theme).unwrap();
+        assert!(result
+            .starts_with("<mxfile host=\"SketchDDD\">\n  <diagram id=\"Sales\" name=\"Sales\">\n"));
+        assert!(result.contains(
+            "        <mxCell id=\"group-0\" value=\"Order\" style=\"swimlane;rounded=1;startSize=30;fillColor=none;dashed=1;fontFamily=Inter;\" vertex=\"1\" parent=\"1\">\n          <mxGeometry x=\"0\" y=\"0\" "
+        ));
+        assert!(result.contains(
+            "        <mxCell id=\"shape-0\" value=\"Order\" style=\"rounded=1;whiteSpace=wrap;html=1;fillColor=#ffffff;strokeWidth=3;fontFamily=Inter;\" vertex=\"1\" parent=\"group-0\">\n          <mxGeometry x=\"20\" y=\"50\" "
+        ));
+        assert!(result.contains("parent=\"1\" source=\"shape-0\" target=\"shape-1\">"));
+        assert!(result.contains("value=\"placedBy\""));
+    }
+
+    #[test]
+    fn test_generate_context_map() {
+        let mut workspace = Workspace::new();
+        workspace.add_context(BoundedContext::new("Sales"));
+        workspace.add_context(BoundedContext::new("Shipping"));
+        workspace.add_context_map(NamedContextMap::new(
+            "SalesToShipping",
+            "Sales",
+            "Shipping",
+            RelationshipPattern::SeparateWays,
+        ));
+
+        let result = generate_context_map(&workspace, &Theme::default()).unwrap();
+        assert!(result.contains("<diagram id=\"ContextMap\" name=\"ContextMap\">"));
+        assert!(result.contains(
+            "value=\"SW\" style=\"edgeStyle=orthogonalEdgeStyle;rounded=1;html=1;startArrow=none;endArrow=none;dashed=1;\""
+        ));
+    }
+}
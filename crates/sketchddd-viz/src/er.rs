@@ -0,0 +1,130 @@
+//! Mermaid entity-relationship diagram generation.
+//!
+//! Entities are drawn as ER entities. Morphisms to value objects and enums
+//! become their attributes, and morphisms between entities become
+//! relationships: many sources relate to exactly one target, or to at most
+//! one when the morphism is optional, and a to-many morphism relates one
+//! source to many targets.
+
+use crate::{multiplicities, Multiplicity, VizError};
+use sketchddd_core::sketch::ObjectId;
+use sketchddd_core::BoundedContext;
+
+/// Generate a Mermaid ER diagram from a bounded context.
+pub fn generate(context: &BoundedContext) -> Result<String, VizError> {
+    let mut output = String::new();
+
+    output.push_str("```mermaid\n");
+    output.push_str("erDiagram\n");
+    output.push_str(&format!("    %% {}\n", context.name()));
+
+    let graph = context.graph();
+    // Objects and morphisms in the order they were declared
+    let mut objects: Vec<_> = graph.objects().collect();
+    objects.sort_by_key(|o| o.id);
+    let mut morphisms: Vec<_> = graph.morphisms().filter(|m| !m.is_identity).collect();
+    morphisms.sort_by_key(|m| m.id);
+
+    // Add entities, with the morphisms to values as their attributes
+    for object in objects
+        .into_iter()
+        .filter(|o| !is_attribute_type(context, o.id))
+    {
+        let attributes: Vec<String> = morphisms
+            .iter()
+            .filter(|m| m.source == object.id && is_attribute_type(context, m.target))
+            .filter_map(|m| {
+                let target = graph.get_object(m.target)?;
+                let mut attribute = format!("{} {}", target.name, m.name);
+                if context.is_optional(m.id) {
+                    attribute.push_str(" \"optional\"");
+                }
+                Some(attribute)
+            })
+            .collect();
+
+        if attributes.is_empty() {
+            output.push_str(&format!("    {}\n", object.name));
+        } else {
+            output.push_str(&format!("    {} {{\n", object.name));
+            for attribute in attributes {
+                output.push_str(&format!("        {}\n", attribute));
+            }
+            output.push_str("    }\n");
+        }
+    }
+
+    // Add the morphisms between entities as relationships
+    for morphism in &morphisms {
+        if is_attribute_type(context, morphism.target) {
+            continue;
+        }
+        if let (Some(source), Some(target)) = (
+            graph.get_object(morphism.source),
+            graph.get_object(morphism.target),
+        ) {
+            let (tail, head) = multiplicities(context, morphism.id);
+            let tail = match tail {
+                Multiplicity::One => "||",
+                Multiplicity::AtMostOne => "|o",
+                Multiplicity::Many => "}o",
+            };
+            let head = match head {
+                Multiplicity::One => "||",
+                Multiplicity::AtMostOne => "o|",
+                Multiplicity::Many => "o{",
+            };
+            output.push_str(&format!(
+                "    {} {}--{} {} : {}\n",
+                source.name, tail, head, target.name, morphism.name
+            ));
+        }
+    }
+
+    output.push_str("```\n");
+
+    Ok(output)
+}
+
+/// Check whether an object is drawn as an attribute rather than an entity.
+fn is_attribute_type(context: &BoundedContext, id: ObjectId) -> bool {
+    context.is_value_object(id) || context.get_enum_colimit(id).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sketchddd_core::Collection;
+
+    #[test]
+    fn test_generate_er_diagram() {
+        let mut context = BoundedContext::new("Sales");
+        let customer = context.add_entity("Customer");
+        let order = context.add_entity("Order");
+        let money = context.add_value_object("Money");
+        let status = context.add_enum("OrderStatus", vec!["Pending".into()]);
+        let line = context.add_entity("LineItem");
+        let graph = &mut context.sketch_mut().graph;
+        graph.add_morphism("placedBy", order, customer);
+        let items = graph.add_morphism("items", order, line);
+        let referrer = graph.add_morphism("referredBy", customer, customer);
+        graph.add_morphism("total", order, money);
+        let discount = graph.add_morphism("discount", order, money);
+        graph.add_morphism("status", order, status);
+        context.mark_optional(referrer);
+        context.mark_optional(discount);
+        context.mark_many(items, Collection::List);
+
+        let result = generate(&context).unwrap();
+        assert!(result.starts_with("```mermaid\nerDiagram\n    %% Sales\n"));
+        assert!(result.contains("\n    Customer\n"));
+        assert!(result.contains(
+            "    Order {\n        Money total\n        Money discount \"optional\"\n        OrderStatus status\n    }\n"
+        ));
+        assert!(result.contains("    Order }o--|| Customer : placedBy\n"));
+        assert!(result.contains("    Customer }o--o| Customer : referredBy\n"));
+        assert!(result.contains("    Order ||--o{ LineItem : items\n"));
+        assert!(!result.contains("    Money {"));
+        assert!(!result.contains(": total"));
+    }
+}
@@ -0,0 +1,619 @@
+//! Event storming board generation.
+//!
+//! Renders the behaviour of a bounded context the way an event storming
+//! session lays it out on a wall: actors issue commands, handled by
+//! aggregates that emit events, policies react to events by issuing further
+//! commands, and hotspots flag open questions. Process steps are drawn as
+//! arrows from their event to their command. Each kind of element gets the
+//! sticky-note colour it has on the board: orange events, blue commands,
+//! yellow aggregates and purple policies.
+//!
+//! An aggregate takes part when a morphism links one of its objects to a
+//! command or an event. Boards are drawn in Mermaid, or as SVG with a lane
+//! per kind of sticky note, on its own or in a self-contained HTML page.
+
+use sketchddd_core::sketch::ObjectId;
+use sketchddd_core::BoundedContext;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::html::{border_point, document, escape, ARROW_MARKER};
+use crate::VizError;
+
+/// Kind of sticky note, in board order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Actor,
+    Command,
+    Aggregate,
+    Event,
+    Policy,
+    Hotspot,
+}
+
+impl Kind {
+    const ALL: [Kind; 6] = [
+        Kind::Actor,
+        Kind::Command,
+        Kind::Aggregate,
+        Kind::Event,
+        Kind::Policy,
+        Kind::Hotspot,
+    ];
+
+    /// Get the name of the kind's Mermaid class.
+    fn class(self) -> &'static str {
+        match self {
+            Kind::Actor => "actor",
+            Kind::Command => "command",
+            Kind::Aggregate => "aggregate",
+            Kind::Event => "event",
+            Kind::Policy => "policy",
+            Kind::Hotspot => "hotspot",
+        }
+    }
+
+    /// Get the sticky-note colour of the kind.
+    fn color(self) -> &'static str {
+        match self {
+            Kind::Actor => "#ffe066",
+            Kind::Command => "#74c0fc",
+            Kind::Aggregate => "#fff3bf",
+            Kind::Event => "#ffa94d",
+            Kind::Policy => "#d0bfff",
+            Kind::Hotspot => "#f783ac",
+        }
+    }
+
+    /// Get the title of the kind's lane on an SVG board.
+    fn lane(self) -> &'static str {
+        match self {
+            Kind::Actor => "Actors",
+            Kind::Command => "Commands",
+            Kind::Aggregate => "Aggregates",
+            Kind::Event => "Events",
+            Kind::Policy => "Policies",
+            Kind::Hotspot => "Hotspots",
+        }
+    }
+}
+
+/// A sticky note on the board.
+struct Note {
+    kind: Kind,
+    /// Identifier of the note's Mermaid node
+    key: String,
+    label: String,
+}
+
+/// An arrow between two notes, by their index.
+struct Arrow {
+    from: usize,
+    to: usize,
+    label: Option<String>,
+    /// Whether the arrow is a dashed line without a head, tying a hotspot
+    /// to what it is about
+    dashed: bool,
+}
+
+/// The notes of a context's board and the arrows between them.
+struct Board {
+    notes: Vec<Note>,
+    arrows: Vec<Arrow>,
+}
+
+impl Board {
+    /// Lay out the board of a context, if it declares any actors, policies,
+    /// hotspots or processes.
+    fn new(context: &BoundedContext) -> Result<Option<Board>, VizError> {
+        if context.actors().is_empty()
+            && context.policies().is_empty()
+            && context.hotspots().is_empty()
+            && context.processes().is_empty()
+        {
+            return Ok(None);
+        }
+
+        let steps: Vec<_> = context
+            .processes()
+            .iter()
+            .flat_map(|p| p.steps.iter().map(move |s| (p.name.as_str(), s)))
+            .collect();
+
+        // Anything reacted to is an event; anything else issued is a command
+        let mut events = Vec::new();
+        for id in context
+            .policies()
+            .iter()
+            .map(|p| p.event)
+            .chain(steps.iter().map(|(_, s)| s.event))
+        {
+            push_unique(&mut events, id);
+        }
+        let mut commands = Vec::new();
+        for id in context
+            .actors()
+            .iter()
+            .flat_map(|a| a.commands.iter().copied())
+            .chain(
+                context
+                    .policies()
+                    .iter()
+                    .flat_map(|p| p.commands.iter().copied()),
+            )
+            .chain(steps.iter().map(|(_, s)| s.command))
+        {
+            if !events.contains(&id) {
+                push_unique(&mut commands, id);
+            }
+        }
+
+        let name = |id: ObjectId| {
+            context
+                .graph()
+                .get_object(id)
+                .map(|o| o.name.clone())
+                .ok_or_else(|| {
+                    VizError::InvalidModel(format!(
+                        "Event storming board references unknown object {:?}",
+                        id
+                    ))
+                })
+        };
+
+        let mut notes = Vec::new();
+        let mut arrows = Vec::new();
+        let mut objects = HashMap::new();
+        let mut aggregates = HashMap::new();
+        let note = |notes: &mut Vec<Note>, kind: Kind, key: String, label: String| {
+            notes.push(Note { kind, key, label });
+            notes.len() - 1
+        };
+
+        let actors: Vec<usize> = context
+            .actors()
+            .iter()
+            .map(|actor| {
+                let key = format!("actor_{}", actor.name);
+                note(&mut notes, Kind::Actor, key, actor.name.clone())
+            })
+            .collect();
+        for &id in &commands {
+            let command = name(id)?;
+            objects.insert(
+                id,
+                note(&mut notes, Kind::Command, command.clone(), command),
+            );
+        }
+
+        // Aggregates handling the commands and emitting the events
+        let mut handled = Vec::new();
+        for &root in context.aggregate_roots() {
+            let Some(aggregate) = context.get_aggregate(root) else {
+                continue;
+            };
+            let members: Vec<ObjectId> = std::iter::once(root)
+                .chain(aggregate.component_objects())
+                .collect();
+            let linked = |id: ObjectId| {
+                context.graph().morphisms().any(|m| {
+                    !m.is_identity
+                        && ((m.source == id && members.contains(&m.target))
+                            || (m.target == id && members.contains(&m.source)))
+                })
+            };
+            let handles: Vec<ObjectId> = commands.iter().copied().filter(|&c| linked(c)).collect();
+            let emits: Vec<ObjectId> = events.iter().copied().filter(|&e| linked(e)).collect();
+            if handles.is_empty() && emits.is_empty() {
+                continue;
+            }
+            let key = format!("aggregate_{}", aggregate.name);
+            let index = note(&mut notes, Kind::Aggregate, key, aggregate.name.clone());
+            for &member in &members {
+                aggregates.entry(member).or_insert(index);
+            }
+            handled.push((index, handles, emits));
+        }
+
+        for &id in &events {
+            let event = name(id)?;
+            objects.insert(id, note(&mut notes, Kind::Event, event.clone(), event));
+        }
+        let policies: Vec<usize> = context
+            .policies()
+            .iter()
+            .map(|policy| {
+                let key = format!("policy_{}", policy.name);
+                note(&mut notes, Kind::Policy, key, policy.name.clone())
+            })
+            .collect();
+
+        // Hotspots about objects off the board name them instead
+        let mut hotspots = Vec::new();
+        for (i, hotspot) in context.hotspots().iter().enumerate() {
+            let target = hotspot
+                .target
+                .map(|id| match objects.get(&id).or(aggregates.get(&id)) {
+                    Some(&index) => Ok((Some(index), hotspot.note.clone())),
+                    None => Ok((None, format!("{}: {}", name(id)?, hotspot.note))),
+                })
+                .transpose()?;
+            let (about, label) = target.unwrap_or((None, hotspot.note.clone()));
+            let key = format!("hotspot_{}", i + 1);
+            hotspots.push((note(&mut notes, Kind::Hotspot, key, label), about));
+        }
+
+        let arrow = |from: usize, to: usize| Arrow {
+            from,
+            to,
+            label: None,
+            dashed: false,
+        };
+        for (actor, &index) in context.actors().iter().zip(&actors) {
+            for id in &actor.commands {
+                arrows.push(arrow(index, objects[id]));
+            }
+        }
+        for (policy, &index) in context.policies().iter().zip(&policies) {
+            arrows.push(arrow(objects[&policy.event], index));
+            for id in &policy.commands {
+                arrows.push(arrow(index, objects[id]));
+            }
+        }
+        for (process, step) in &steps {
+            arrows.push(Arrow {
+                label: Some(process.to_string()),
+                ..arrow(objects[&step.event], objects[&step.command])
+            });
+        }
+        for (index, handles, emits) in handled {
+            for id in handles {
+                arrows.push(arrow(objects[&id], index));
+            }
+            for id in emits {
+                arrows.push(arrow(index, objects[&id]));
+            }
+        }
+        for (index, about) in hotspots {
+            if let Some(about) = about {
+                arrows.push(Arrow {
+                    dashed: true,
+                    ..arrow(index, about)
+                });
+            }
+        }
+
+        Ok(Some(Board { notes, arrows }))
+    }
+
+    /// Draw the board as a Mermaid flowchart.
+    fn mermaid(&self, context: &BoundedContext) -> String {
+        let mut output = String::new();
+        output.push_str("```mermaid\n");
+        output.push_str("flowchart LR\n");
+        output.push_str(&format!("    %% {}\n", context.name()));
+        for kind in Kind::ALL {
+            output.push_str(&format!(
+                "    classDef {} fill:{}\n",
+                kind.class(),
+                kind.color()
+            ));
+        }
+
+        output.push('\n');
+
+        for note in &self.notes {
+            let (open, close) = match note.kind {
+                Kind::Actor => ("([", "])"),
+                Kind::Policy => ("{{", "}}"),
+                Kind::Hotspot => (">", "]"),
+                _ => ("[", "]"),
+            };
+            output.push_str(&format!(
+                "    {}{}\"{}\"{}:::{}\n",
+                note.key,
+                open,
+                note.label.replace('"', "'"),
+                close,
+                note.kind.class()
+            ));
+        }
+
+        output.push('\n');
+
+        for arrow in &self.arrows {
+            let link = match (&arrow.label, arrow.dashed) {
+                (_, true) => "-.-".to_string(),
+                (Some(label), false) => format!("-->|{}|", label),
+                (None, false) => "-->".to_string(),
+            };
+            output.push_str(&format!(
+                "    {} {} {}\n",
+                self.notes[arrow.from].key, link, self.notes[arrow.to].key
+            ));
+        }
+
+        output.push_str("```\n");
+        output
+    }
+
+    /// Draw the board as SVG, with a lane per kind of note.
+    fn svg(&self) -> String {
+        const PADDING: f64 = 12.0;
+        const LINE_HEIGHT: f64 = 16.0;
+        const GAP: f64 = 40.0;
+        const LANE_TITLE_WIDTH: f64 = 100.0;
+
+        let lines: Vec<Vec<String>> = self
+            .notes
+            .iter()
+            .map(|note| match note.kind {
+                Kind::Hotspot => wrap(&note.label, 22),
+                _ => vec![note.label.clone()],
+            })
+            .collect();
+        let size = |i: usize| {
+            let chars = lines[i]
+                .iter()
+                .map(|l| l.chars().count())
+                .max()
+                .unwrap_or(0);
+            (
+                (chars as f64 * 7.5 + 2.0 * PADDING).max(110.0),
+                (lines[i].len() as f64 * LINE_HEIGHT + 2.0 * PADDING).max(60.0),
+            )
+        };
+
+        // Place the notes of each kind side by side in its lane
+        let mut centers = vec![(0.0, 0.0); self.notes.len()];
+        let mut lanes = Vec::new();
+        let (mut width, mut top) = (0.0_f64, 0.0);
+        for kind in Kind::ALL {
+            let notes: Vec<usize> = (0..self.notes.len())
+                .filter(|&i| self.notes[i].kind == kind)
+                .collect();
+            if notes.is_empty() {
+                continue;
+            }
+            let height = notes.iter().map(|&i| size(i).1).fold(0.0, f64::max) + GAP;
+            let mut x = LANE_TITLE_WIDTH + GAP;
+            for &i in &notes {
+                let (note_width, _) = size(i);
+                centers[i] = (x + note_width / 2.0, top + height / 2.0);
+                x += note_width + GAP;
+            }
+            width = width.max(x);
+            lanes.push((kind, top, height));
+            top += height;
+        }
+
+        let mut output = String::new();
+        let _ = writeln!(
+            output,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\" font-family=\"sans-serif\" font-size=\"13\">",
+            width, top
+        );
+        output.push_str(ARROW_MARKER);
+
+        for (i, (kind, lane_top, height)) in lanes.iter().enumerate() {
+            if i > 0 {
+                let _ = writeln!(
+                    output,
+                    "<line x1=\"0\" y1=\"{0}\" x2=\"{1}\" y2=\"{0}\" stroke=\"#dee2e6\"/>",
+                    lane_top, width
+                );
+            }
+            let _ = writeln!(
+                output,
+                "<text x=\"{}\" y=\"{}\" dominant-baseline=\"central\" font-weight=\"bold\" fill=\"#868e96\">{}</text>",
+                PADDING,
+                lane_top + height / 2.0,
+                kind.lane()
+            );
+        }
+
+        // Arrows go under the notes
+        for arrow in &self.arrows {
+            let from = border_point(centers[arrow.from], size(arrow.from), centers[arrow.to]);
+            let to = border_point(centers[arrow.to], size(arrow.to), centers[arrow.from]);
+            let style = if arrow.dashed {
+                " stroke-dasharray=\"6 4\""
+            } else {
+                " marker-end=\"url(#arrow)\""
+            };
+            let _ = writeln!(
+                output,
+                "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"#495057\"{}/>",
+                from.0, from.1, to.0, to.1, style
+            );
+            if let Some(label) = &arrow.label {
+                let _ = writeln!(
+                    output,
+                    "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"11\" fill=\"#495057\">{}</text>",
+                    (from.0 + to.0) / 2.0,
+                    (from.1 + to.1) / 2.0 - 4.0,
+                    escape(label)
+                );
+            }
+        }
+
+        for (i, note) in self.notes.iter().enumerate() {
+            let (x, y) = centers[i];
+            let (note_width, note_height) = size(i);
+            let _ = writeln!(
+                output,
+                "<g class=\"{}\">\n<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" stroke=\"#adb5bd\"/>",
+                note.kind.class(),
+                x - note_width / 2.0,
+                y - note_height / 2.0,
+                note_width,
+                note_height,
+                note.kind.color()
+            );
+            let first = y - (lines[i].len() as f64 - 1.0) * LINE_HEIGHT / 2.0;
+            let _ = write!(
+                output,
+                "<text x=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\">",
+                x
+            );
+            for (n, line) in lines[i].iter().enumerate() {
+                let _ = write!(
+                    output,
+                    "<tspan x=\"{}\" y=\"{}\">{}</tspan>",
+                    x,
+                    first + n as f64 * LINE_HEIGHT,
+                    escape(line)
+                );
+            }
+            output.push_str("</text>\n</g>\n");
+        }
+
+        output.push_str("</svg>\n");
+        output
+    }
+}
+
+/// Generate a Mermaid event storming board for a bounded context.
+///
+/// Returns an empty string when the context declares no actors, policies,
+/// hotspots or processes.
+pub fn generate(context: &BoundedContext) -> Result<String, VizError> {
+    Ok(Board::new(context)?
+        .map(|board| board.mermaid(context))
+        .unwrap_or_default())
+}
+
+/// Generate an SVG event storming board for a bounded context.
+///
+/// Returns an empty string when the context declares no actors, policies,
+/// hotspots or processes.
+pub fn generate_svg(context: &BoundedContext) -> Result<String, VizError> {
+    Ok(Board::new(context)?
+        .map(|board| board.svg())
+        .unwrap_or_default())
+}
+
+/// Generate a self-contained HTML page holding the SVG event storming board
+/// of a bounded context.
+///
+/// Returns an empty string when the context declares no actors, policies,
+/// hotspots or processes.
+pub fn generate_html(context: &BoundedContext) -> Result<String, VizError> {
+    let svg = generate_svg(context)?;
+    if svg.is_empty() {
+        return Ok(svg);
+    }
+    let title = format!("{} event storming", context.name());
+    Ok(document(&title, "body { margin: 0; }\n", &svg))
+}
+
+fn push_unique(ids: &mut Vec<ObjectId>, id: ObjectId) {
+    if !ids.contains(&id) {
+        ids.push(id);
+    }
+}
+
+/// Break text into lines of at most `width` characters, between words.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.chars().count() + 1 + word.chars().count() <= width => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sketchddd_core::Hotspot;
+
+    fn fulfillment_context() -> BoundedContext {
+        let mut ctx = BoundedContext::new("Fulfillment");
+        let place = ctx.sketch_mut().add_object("PlaceOrder");
+        let placed = ctx.sketch_mut().add_object("OrderPlaced");
+        let reserve = ctx.sketch_mut().add_object("ReserveStock");
+        let reserved = ctx.sketch_mut().add_object("StockReserved");
+        let ship = ctx.sketch_mut().add_object("Ship");
+        ctx.define_actor("Customer", &[place]);
+        ctx.define_policy("ReserveOnPlacement", placed, &[reserve]);
+        ctx.define_process("FulfillmentSaga", &[(reserved, ship)]);
+        ctx.add_hotspot(Hotspot::on(reserve, "What if stock runs out?"));
+        ctx
+    }
+
+    #[test]
+    fn test_generate_board() {
+        let result = generate(&fulfillment_context()).unwrap();
+
+        assert!(
+            result.contains("flowchart LR\n    %% Fulfillment\n    classDef actor fill:#ffe066\n")
+        );
+        assert!(result.contains("    actor_Customer([\"Customer\"]):::actor\n"));
+        assert!(result.contains("    PlaceOrder[\"PlaceOrder\"]:::command\n"));
+        assert!(result.contains("    OrderPlaced[\"OrderPlaced\"]:::event\n"));
+        assert!(result.contains("    StockReserved[\"StockReserved\"]:::event\n"));
+        assert!(
+            result.contains("    policy_ReserveOnPlacement{{\"ReserveOnPlacement\"}}:::policy\n")
+        );
+        assert!(result.contains("    hotspot_1>\"What if stock runs out?\"]:::hotspot\n"));
+
+        assert!(result.contains("    actor_Customer --> PlaceOrder\n"));
+        assert!(result.contains("    OrderPlaced --> policy_ReserveOnPlacement\n"));
+        assert!(result.contains("    policy_ReserveOnPlacement --> ReserveStock\n"));
+        assert!(result.contains("    StockReserved -->|FulfillmentSaga| Ship\n"));
+        assert!(result.contains("    hotspot_1 -.- ReserveStock\n"));
+    }
+
+    #[test]
+    fn test_generate_board_with_aggregates() {
+        let mut ctx = fulfillment_context();
+        let order = ctx.add_entity("Order");
+        ctx.define_aggregate_with_members("Order", order, &[]);
+        let objects: Vec<_> = ["PlaceOrder", "OrderPlaced"]
+            .iter()
+            .map(|name| ctx.graph().find_object_by_name(name).unwrap().id)
+            .collect();
+        let graph = &mut ctx.sketch_mut().graph;
+        graph.add_morphism("order", objects[0], order);
+        graph.add_morphism("order", objects[1], order);
+
+        let result = generate(&ctx).unwrap();
+        assert!(result.contains("    classDef aggregate fill:#fff3bf\n"));
+        assert!(result.contains("    aggregate_Order[\"Order\"]:::aggregate\n"));
+        assert!(result.contains("    PlaceOrder --> aggregate_Order\n"));
+        assert!(result.contains("    aggregate_Order --> OrderPlaced\n"));
+    }
+
+    #[test]
+    fn test_generate_svg_board() {
+        let svg = generate_svg(&fulfillment_context()).unwrap();
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        for lane in ["Actors", "Commands", "Events", "Policies", "Hotspots"] {
+            assert!(svg.contains(&format!(">{}</text>", lane)));
+        }
+        assert!(!svg.contains(">Aggregates</text>"));
+        assert!(svg.contains("fill=\"#ffa94d\""));
+        assert!(svg.contains(">What if stock runs</tspan>"));
+        assert!(svg.contains(">out?</tspan>"));
+        assert!(svg.contains("stroke-dasharray=\"6 4\""));
+        assert!(svg.contains(">FulfillmentSaga</text>"));
+
+        let html = generate_html(&fulfillment_context()).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Fulfillment event storming</title>"));
+        assert!(html.contains(&svg));
+    }
+
+    #[test]
+    fn test_generate_without_event_storming() {
+        let ctx = BoundedContext::new("Empty");
+        assert!(generate(&ctx).unwrap().is_empty());
+        assert!(generate_html(&ctx).unwrap().is_empty());
+    }
+}
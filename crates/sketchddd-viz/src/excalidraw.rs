@@ -0,0 +1,287 @@
+//! Excalidraw export.
+//!
+//! Writes a laid-out drawing as an `.excalidraw` file: boxes are rectangles
+//! with their label bound to them, groups are frames holding their boxes,
+//! and links are arrows bound to the boxes they join, so that the arrows
+//! follow the boxes when they are moved. Themes set colours and line styles;
+//! labels keep Excalidraw's hand-drawn font.
+
+use crate::drawing::{Drawing, Shape};
+use crate::html::border_point;
+use crate::theme::{LineStyle, Theme};
+use crate::VizError;
+use serde_json::{json, Map, Value};
+use sketchddd_core::{BoundedContext, Workspace};
+
+/// Size of labels.
+const FONT_SIZE: f64 = 20.0;
+
+/// Generate an Excalidraw drawing of a bounded context.
+pub fn generate(context: &BoundedContext) -> Result<String, VizError> {
+    generate_with_theme(context, &Theme::default())
+}
+
+/// Generate an Excalidraw drawing of a bounded context, styled by a theme.
+pub fn generate_with_theme(context: &BoundedContext, theme: &Theme) -> Result<String, VizError> {
+    write(&Drawing::of_context(context, theme))
+}
+
+/// Generate an Excalidraw drawing of the context map of a workspace,
+/// styled by a theme.
+pub fn generate_context_map(workspace: &Workspace, theme: &Theme) -> Result<String, VizError> {
+    write(&Drawing::of_workspace(workspace, theme))
+}
+
+/// Write a drawing as an Excalidraw file.
+fn write(drawing: &Drawing) -> Result<String, VizError> {
+    let mut elements = Vec::new();
+    let mut seed = 0;
+    let mut element = |id: String, kind: &str, x: f64, y: f64, width: f64, height: f64| {
+        seed += 1;
+        let mut element = Map::new();
+        for (key, value) in [
+            ("id", json!(id)),
+            ("type", json!(kind)),
+            ("x", json!(x)),
+            ("y", json!(y)),
+            ("width", json!(width)),
+            ("height", json!(height)),
+            ("angle", json!(0)),
+            ("strokeColor", json!("#1e1e1e")),
+            ("backgroundColor", json!("transparent")),
+            ("fillStyle", json!("solid")),
+            ("strokeWidth", json!(2)),
+            ("strokeStyle", json!("solid")),
+            ("roughness", json!(1)),
+            ("opacity", json!(100)),
+            ("groupIds", json!([])),
+            ("frameId", Value::Null),
+            ("roundness", Value::Null),
+            ("seed", json!(seed)),
+            ("version", json!(1)),
+            ("versionNonce", json!(seed)),
+            ("isDeleted", json!(false)),
+            ("boundElements", json!([])),
+            ("updated", json!(1)),
+            ("link", Value::Null),
+            ("locked", json!(false)),
+        ] {
+            element.insert(key.to_string(), value);
+        }
+        element
+    };
+    let label =
+        |element: &mut Map<String, Value>, container: &str, text: &str, center: (f64, f64)| {
+            let width = text.chars().count() as f64 * FONT_SIZE * 0.55;
+            let height = FONT_SIZE * 1.25;
+            element.insert("x".to_string(), json!(center.0 - width / 2.0));
+            element.insert("y".to_string(), json!(center.1 - height / 2.0));
+            element.insert("width".to_string(), json!(width));
+            element.insert("height".to_string(), json!(height));
+            for (key, value) in [
+                ("text", json!(text)),
+                ("originalText", json!(text)),
+                ("fontSize", json!(FONT_SIZE)),
+                ("fontFamily", json!(1)),
+                ("textAlign", json!("center")),
+                ("verticalAlign", json!("middle")),
+                ("containerId", json!(container)),
+                ("lineHeight", json!(1.25)),
+                ("autoResize", json!(true)),
+            ] {
+                element.insert(key.to_string(), value);
+            }
+        };
+    let center = |shape: &Shape| (shape.x + shape.width / 2.0, shape.y + shape.height / 2.0);
+    let frame_id = |shape: &Shape| match shape.group {
+        Some(group) => json!(format!("group-{}", group)),
+        None => Value::Null,
+    };
+
+    // Which text and arrows are bound to each box
+    let mut bound: Vec<Vec<Value>> = drawing
+        .shapes
+        .iter()
+        .enumerate()
+        .map(|(i, _)| vec![json!({ "id": format!("shape-{}-label", i), "type": "text" })])
+        .collect();
+    for (i, link) in drawing.links.iter().enumerate() {
+        let arrow = json!({ "id": format!("link-{}", i), "type": "arrow" });
+        bound[link.from].push(arrow.clone());
+        if link.to != link.from {
+            bound[link.to].push(arrow);
+        }
+    }
+
+    for (i, shape) in drawing.shapes.iter().enumerate() {
+        let id = format!("shape-{}", i);
+        let mut rectangle = element(
+            id.clone(),
+            "rectangle",
+            shape.x,
+            shape.y,
+            shape.width,
+            shape.height,
+        );
+        if let Some(fill) = &shape.fill {
+            rectangle.insert("backgroundColor".to_string(), json!(fill));
+        }
+        if shape.bold {
+            rectangle.insert("strokeWidth".to_string(), json!(4));
+        }
+        rectangle.insert("roundness".to_string(), json!({ "type": 3 }));
+        rectangle.insert("frameId".to_string(), frame_id(shape));
+        rectangle.insert(
+            "boundElements".to_string(),
+            Value::Array(std::mem::take(&mut bound[i])),
+        );
+        elements.push(Value::Object(rectangle));
+
+        let mut text = element(format!("{}-label", id), "text", 0.0, 0.0, 0.0, 0.0);
+        label(&mut text, &id, &shape.label, center(shape));
+        text.insert("frameId".to_string(), frame_id(shape));
+        elements.push(Value::Object(text));
+    }
+
+    for (i, link) in drawing.links.iter().enumerate() {
+        let (from, to) = (&drawing.shapes[link.from], &drawing.shapes[link.to]);
+        let (start, points, middle) = if link.from == link.to {
+            // A loop over the top of the box
+            let start = (from.x + from.width * 0.3, from.y);
+            let span = from.width * 0.4;
+            let middle = (start.0 + span / 2.0, start.1 - 40.0 - FONT_SIZE);
+            (start, json!([[0, 0], [span / 2.0, -40], [span, 0]]), middle)
+        } else {
+            let start = border_point(center(from), (from.width, from.height), center(to));
+            let end = border_point(center(to), (to.width, to.height), center(from));
+            let middle = ((start.0 + end.0) / 2.0, (start.1 + end.1) / 2.0);
+            (
+                start,
+                json!([[0, 0], [end.0 - start.0, end.1 - start.1]]),
+                middle,
+            )
+        };
+        let (width, height) = extent(&points);
+
+        let id = format!("link-{}", i);
+        let mut arrow = element(id.clone(), "arrow", start.0, start.1, width, height);
+        let (style, stroke_width) = match link.style {
+            Some(LineStyle::Dashed) => ("dashed", 2),
+            Some(LineStyle::Dotted) => ("dotted", 2),
+            Some(LineStyle::Bold) => ("solid", 4),
+            _ => ("solid", 2),
+        };
+        let binding =
+            |shape: usize| json!({ "elementId": format!("shape-{}", shape), "focus": 0, "gap": 1 });
+        let arrowhead = |shown: bool| if shown { json!("arrow") } else { Value::Null };
+        for (key, value) in [
+            ("strokeStyle", json!(style)),
+            ("strokeWidth", json!(stroke_width)),
+            ("roundness", json!({ "type": 2 })),
+            ("points", points),
+            ("lastCommittedPoint", Value::Null),
+            ("startBinding", binding(link.from)),
+            ("endBinding", binding(link.to)),
+            ("startArrowhead", arrowhead(link.start_arrow)),
+            ("endArrowhead", arrowhead(link.end_arrow)),
+            ("elbowed", json!(false)),
+            (
+                "boundElements",
+                json!([{ "id": format!("{}-label", id), "type": "text" }]),
+            ),
+        ] {
+            arrow.insert(key.to_string(), value);
+        }
+        elements.push(Value::Object(arrow));
+
+        let mut text = element(format!("{}-label", id), "text", 0.0, 0.0, 0.0, 0.0);
+        label(&mut text, &id, &link.label, middle);
+        elements.push(Value::Object(text));
+    }
+
+    // Frames follow the boxes they hold
+    for (i, group) in drawing.groups.iter().enumerate() {
+        let mut frame = element(
+            format!("group-{}", i),
+            "frame",
+            group.x,
+            group.y,
+            group.width,
+            group.height,
+        );
+        frame.insert("name".to_string(), json!(group.label));
+        elements.push(Value::Object(frame));
+    }
+
+    let file = json!({
+        "type": "excalidraw",
+        "version": 2,
+        "source": env!("CARGO_PKG_REPOSITORY"),
+        "elements": elements,
+        "appState": { "viewBackgroundColor": "#ffffff", "gridSize": null },
+        "files": {},
+    });
+    serde_json::to_string_pretty(&file)
+        .map(|json| json + "\n")
+        .map_err(|e| VizError::InvalidModel(e.to_string()))
+}
+
+/// Get the width and height spanned by the points of an arrow.
+fn extent(points: &Value) -> (f64, f64) {
+    let coordinates = |axis: usize| {
+        points
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(move |point| point[axis].as_f64())
+    };
+    let span = |axis: usize| {
+        let (min, max) =
+            coordinates(axis).fold((0.0_f64, 0.0_f64), |(min, max), c| (min.min(c), max.max(c)));
+        max - min
+    };
+    (span(0), span(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_binds_labels_and_arrows() {
+        let mut context = BoundedContext::new("Sales");
+        let order = context.add_entity("Order");
+        let customer = context.add_entity("Customer");
+        context.define_aggregate_with_members("Order", order, &[]);
+        context
+            .sketch_mut()
+            .graph
+            .add_morphism("placedBy", order, customer);
+
+        let file: Value = serde_json::from_str(&generate(&context).unwrap()).unwrap();
+        assert_eq!(file["type"], "excalidraw");
+        let elements = file["elements"].as_array().unwrap();
+        let find = |id: &str| elements.iter().find(|e| e["id"] == id).unwrap();
+
+        let order = find("shape-0");
+        assert_eq!(order["type"], "rectangle");
+        assert_eq!(order["strokeWidth"], 4);
+        assert_eq!(order["frameId"], "group-0");
+        assert_eq!(find("shape-0-label")["text"], "Order");
+        assert_eq!(find("shape-0-label")["containerId"], "shape-0");
+
+        let arrow = find("link-0");
+        assert_eq!(arrow["startBinding"]["elementId"], "shape-0");
+        assert_eq!(arrow["endBinding"]["elementId"], "shape-1");
+        assert_eq!(arrow["endArrowhead"], "arrow");
+        assert_eq!(find("link-0-label")["text"], "placedBy");
+        assert!(order["boundElements"]
+            .as_array()
+            .unwrap()
+            .contains(&json!({ "id": "link-0", "type": "arrow" })));
+
+        let frame = find("group-0");
+        assert_eq!(frame["type"], "frame");
+        assert_eq!(frame["name"], "Order");
+    }
+}
@@ -1,32 +1,57 @@
 //! Graphviz DOT format generation.
+//!
+//! Names are written as they are where DOT allows it, and quoted otherwise,
+//! such as names with spaces, quotes or non-ASCII letters and names that
+//! are DOT keywords, like `Node` or `Graph`.
 
+use crate::theme::{Direction, Theme};
+use crate::{aggregate_clusters, fill_color, morphism_style, multiplicities, VizError};
+use sketchddd_core::sketch::ObjectId;
 use sketchddd_core::BoundedContext;
-use crate::VizError;
 
 /// Generate Graphviz DOT from a bounded context.
 pub fn generate(context: &BoundedContext) -> Result<String, VizError> {
+    generate_with_theme(context, &Theme::default())
+}
+
+/// Generate Graphviz DOT from a bounded context, styled by a theme.
+pub fn generate_with_theme(context: &BoundedContext, theme: &Theme) -> Result<String, VizError> {
     let mut output = String::new();
 
-    output.push_str(&format!("digraph {} {{\n", context.name()));
-    output.push_str("  rankdir=LR;\n");
-    output.push_str("  node [shape=box];\n\n");
-
-    // Add objects as nodes
-    for object in context.graph().objects() {
-        let shape = if context.is_entity(object.id) {
-            "box"
-        } else if context.is_value_object(object.id) {
-            "ellipse"
-        } else {
-            "box"
-        };
+    output.push_str(&format!("digraph {} {{\n", identifier(context.name())));
+    output.push_str(&format!(
+        "  rankdir={};\n",
+        theme.direction.map_or("LR", Direction::as_str)
+    ));
+    output.push_str("  node [shape=box];\n");
+    output.push_str(&font_attributes(theme));
+    output.push('\n');
+
+    // Add aggregates as clusters around their objects
+    let (clusters, ungrouped) = aggregate_clusters(context);
+    for cluster in &clusters {
         output.push_str(&format!(
-            "  {} [label=\"{}\" shape={}];\n",
-            object.name, object.name, shape
+            "  subgraph {} {{\n",
+            identifier(&format!("cluster_{}", cluster.name))
         ));
+        output.push_str(&format!("    label={};\n", quote(cluster.name)));
+        output.push_str("    style=rounded;\n");
+        for &id in std::iter::once(&cluster.root).chain(&cluster.members) {
+            if let Some(node) = node(context, id, theme) {
+                output.push_str(&format!("    {}\n", node));
+            }
+        }
+        output.push_str("  }\n");
     }
 
-    output.push_str("\n");
+    // Add the other objects as nodes
+    for id in ungrouped {
+        if let Some(node) = node(context, id, theme) {
+            output.push_str(&format!("  {}\n", node));
+        }
+    }
+
+    output.push('\n');
 
     // Add morphisms as edges
     for morphism in context.graph().morphisms() {
@@ -34,9 +59,23 @@ pub fn generate(context: &BoundedContext) -> Result<String, VizError> {
             context.graph().get_object(morphism.source),
             context.graph().get_object(morphism.target),
         ) {
+            let mut style = morphism_style(context, morphism.id, theme)
+                .map(|style| format!(" style={}", style.as_str()))
+                .unwrap_or_default();
+            if theme.multiplicities {
+                let (tail, head) = multiplicities(context, morphism.id);
+                style.push_str(&format!(
+                    " taillabel={} headlabel={}",
+                    quote(tail.label()),
+                    quote(head.label())
+                ));
+            }
             output.push_str(&format!(
-                "  {} -> {} [label=\"{}\"];\n",
-                source.name, target.name, morphism.name
+                "  {} -> {} [label={}{}];\n",
+                identifier(&source.name),
+                identifier(&target.name),
+                quote(&morphism.name),
+                style
             ));
         }
     }
@@ -46,9 +85,84 @@ pub fn generate(context: &BoundedContext) -> Result<String, VizError> {
     Ok(output)
 }
 
+/// Declare the node of an object, with aggregate roots in bold.
+fn node(context: &BoundedContext, id: ObjectId, theme: &Theme) -> Option<String> {
+    let object = context.graph().get_object(id)?;
+    let shape = if context.is_entity(id) {
+        "box"
+    } else if context.is_value_object(id) {
+        "ellipse"
+    } else {
+        "box"
+    };
+    let fill = fill_color(context, id, theme)
+        .map(|color| format!(" style=filled fillcolor={}", quote(&color)))
+        .unwrap_or_default();
+    let border = if context.is_aggregate_root(id) {
+        " penwidth=2"
+    } else {
+        ""
+    };
+    Some(format!(
+        "{} [label={} shape={}{}{}];",
+        identifier(&object.name),
+        quote(&object.name),
+        shape,
+        fill,
+        border
+    ))
+}
+
+/// Declare the font of a theme for the graph, its nodes and its edges.
+pub(crate) fn font_attributes(theme: &Theme) -> String {
+    match &theme.font {
+        Some(font) => format!(
+            "  fontname={0};\n  node [fontname={0}];\n  edge [fontname={0}];\n",
+            quote(font)
+        ),
+        None => String::new(),
+    }
+}
+
+/// Write a name as a DOT identifier, quoting it unless it is a plain name
+/// other than a keyword.
+pub(crate) fn identifier(name: &str) -> String {
+    const KEYWORDS: [&str; 6] = ["node", "edge", "graph", "digraph", "subgraph", "strict"];
+    let mut chars = name.chars();
+    let plain = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !KEYWORDS.iter().any(|k| k.eq_ignore_ascii_case(name));
+    if plain {
+        name.to_string()
+    } else {
+        quote(name)
+    }
+}
+
+/// Write text as a quoted DOT string.
+pub(crate) fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => {}
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::theme::{EdgeStyles, ElementColors, LineStyle};
+    use sketchddd_core::Collection;
 
     #[test]
     fn test_generate_empty_context() {
@@ -56,4 +170,133 @@ mod tests {
         let result = generate(&context).unwrap();
         assert!(result.contains("digraph Test"));
     }
+
+    #[test]
+    fn test_generate_colors_classified_objects() {
+        let mut context = BoundedContext::new("Billing");
+        let invoice = context.add_entity("Invoice");
+        context.add_entity("Ledger");
+        context.object_metadata_mut(invoice).classification =
+            Some(sketchddd_core::Classification::Core);
+
+        let result = generate(&context).unwrap();
+        assert!(result
+            .contains("Invoice [label=\"Invoice\" shape=box style=filled fillcolor=\"#f9d67a\"];"));
+        assert!(result.contains("Ledger [label=\"Ledger\" shape=box];"));
+    }
+
+    #[test]
+    fn test_generate_clusters_aggregates() {
+        let mut context = BoundedContext::new("Sales");
+        let order = context.add_entity("Order");
+        let line = context.add_entity("LineItem");
+        context.add_value_object("Money");
+        context.define_aggregate_with_members("OrderAggregate", order, &[line]);
+
+        let result = generate(&context).unwrap();
+        assert!(result.contains(
+            "  subgraph cluster_OrderAggregate {\n    label=\"OrderAggregate\";\n    style=rounded;\n    Order [label=\"Order\" shape=box penwidth=2];\n    LineItem [label=\"LineItem\" shape=box];\n  }\n"
+        ));
+        assert!(result.contains("\n  Money [label=\"Money\" shape=ellipse];\n"));
+    }
+
+    #[test]
+    fn test_generate_with_theme() {
+        let mut context = BoundedContext::new("Sales");
+        let order = context.add_entity("Order");
+        let line = context.add_entity("LineItem");
+        let money = context.add_value_object("Money");
+        context
+            .sketch_mut()
+            .graph
+            .add_morphism("total", order, money);
+        context.define_aggregate_with_members("OrderAggregate", order, &[line]);
+        context.object_metadata_mut(line).classification =
+            Some(sketchddd_core::Classification::Core);
+
+        let theme = Theme {
+            direction: Some(Direction::TopToBottom),
+            font: Some("Helvetica".to_string()),
+            colors: ElementColors {
+                entity: Some("#dae8fc".to_string()),
+                value_object: Some("#d5e8d4".to_string()),
+                ..Default::default()
+            },
+            edges: EdgeStyles {
+                containment: Some(LineStyle::Bold),
+                ..Default::default()
+            },
+            multiplicities: false,
+        };
+
+        let result = generate_with_theme(&context, &theme).unwrap();
+        assert!(result.contains("  rankdir=TB;\n  node [shape=box];\n  fontname=\"Helvetica\";\n"));
+        assert!(result.contains(
+            "Order [label=\"Order\" shape=box style=filled fillcolor=\"#dae8fc\" penwidth=2];"
+        ));
+        // Classification wins over the colour of the kind
+        assert!(result.contains(
+            "LineItem [label=\"LineItem\" shape=box style=filled fillcolor=\"#f9d67a\"];"
+        ));
+        assert!(result
+            .contains("Money [label=\"Money\" shape=ellipse style=filled fillcolor=\"#d5e8d4\"];"));
+        assert!(result
+            .contains("  Order -> LineItem [label=\"OrderAggregate_LineItem\" style=bold];\n"));
+        assert!(result.contains("  Order -> Money [label=\"total\"];\n"));
+    }
+
+    #[test]
+    fn test_generate_quotes_names() {
+        let mut context = BoundedContext::new("Sales Ops");
+        let line = context.add_entity("Order Line");
+        let node = context.add_entity("Node");
+        let note = context.add_value_object("Note \"draft\"");
+        let graph = &mut context.sketch_mut().graph;
+        graph.add_morphism("next", line, node);
+        graph.add_morphism("note", node, note);
+
+        let result = generate(&context).unwrap();
+        assert!(result.starts_with("digraph \"Sales Ops\" {\n"));
+        assert!(result.contains("  \"Order Line\" [label=\"Order Line\" shape=box];\n"));
+        assert!(result.contains("  \"Node\" [label=\"Node\" shape=box];\n"));
+        assert!(result
+            .contains("  \"Note \\\"draft\\\"\" [label=\"Note \\\"draft\\\"\" shape=ellipse];\n"));
+        assert!(result.contains("  \"Order Line\" -> \"Node\" [label=\"next\"];\n"));
+    }
+
+    #[test]
+    fn test_generate_multiplicities() {
+        let mut context = BoundedContext::new("Sales");
+        let order = context.add_entity("Order");
+        let customer = context.add_entity("Customer");
+        let graph = &mut context.sketch_mut().graph;
+        graph.add_morphism("placedBy", order, customer);
+        let referrer = graph.add_morphism("referredBy", customer, customer);
+        context.mark_optional(referrer);
+        let line = context.add_entity("LineItem");
+        let items = context
+            .sketch_mut()
+            .graph
+            .add_morphism("items", order, line);
+        context.mark_many(items, Collection::List);
+        context.sketch_mut().graph.add_identity_morphism(customer);
+
+        let theme = Theme {
+            multiplicities: true,
+            ..Default::default()
+        };
+        let result = generate_with_theme(&context, &theme).unwrap();
+        assert!(result.contains(
+            "  Customer -> Customer [label=\"id_Customer\" taillabel=\"1\" headlabel=\"1\"];\n"
+        ));
+        assert!(result.contains(
+            "  Order -> Customer [label=\"placedBy\" taillabel=\"*\" headlabel=\"1\"];\n"
+        ));
+        assert!(result.contains(
+            "  Customer -> Customer [label=\"referredBy\" taillabel=\"*\" headlabel=\"0..1\"];\n"
+        ));
+        assert!(result
+            .contains("  Order -> LineItem [label=\"items\" taillabel=\"1\" headlabel=\"*\"];\n"));
+        assert!(!generate(&context).unwrap().contains("headlabel"));
+    }
 }
@@ -0,0 +1,281 @@
+//! Interactive HTML diagram generation.
+//!
+//! A context is drawn as an SVG diagram inside a self-contained HTML page,
+//! with its styles and script inline so that the file can be opened or
+//! shared on its own. Objects are placed in layers following their
+//! morphisms, by the [`crate::layout`] module.
+//!
+//! Each object links to its section of the documentation generated by
+//! `sketchddd docs`, as `contexts/<Context>.html#<Object>`, so the links
+//! resolve when the page sits at the root of the documentation site. Its
+//! kind, description and invariants are embedded as `data-` attributes and
+//! shown when hovering over it. A description is the object's own, or its
+//! `@meta(description: "...")`.
+
+use crate::layout::layout;
+use crate::theme::{LineStyle, Theme};
+use crate::{fill_color, morphism_style, VizError};
+use sketchddd_core::sketch::ObjectId;
+use sketchddd_core::BoundedContext;
+use std::fmt::Write;
+
+const STYLE: &str = "body { margin: 0; font-family: var(--font); }
+svg a { cursor: pointer; }
+svg a:hover rect { stroke: #1f6feb; }
+#details { position: fixed; display: none; max-width: 320px; padding: 8px 10px;
+  background: #fff; border: 1px solid #888; border-radius: 4px;
+  box-shadow: 0 2px 6px rgba(0, 0, 0, 0.2); font-size: 13px; }
+#details ul { margin: 4px 0 0; padding-left: 18px; }
+";
+
+/// Marker drawn at the head of arrows, as `url(#arrow)`.
+pub(crate) const ARROW_MARKER: &str = "<defs><marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" markerWidth=\"8\" markerHeight=\"8\" orient=\"auto-start-reverse\"><path d=\"M0,0 L10,5 L0,10 z\"/></marker></defs>\n";
+
+const SCRIPT: &str = r#"const details = document.getElementById("details");
+for (const node of document.querySelectorAll("a[data-name]")) {
+  node.addEventListener("mouseenter", () => {
+    const data = node.dataset;
+    let html = "<strong>" + escapeHtml(data.name) + "</strong> <em>" + escapeHtml(data.kind) + "</em>";
+    if (data.description) html += "<p>" + escapeHtml(data.description) + "</p>";
+    const invariants = data.invariants ? data.invariants.split("\n") : [];
+    if (invariants.length) {
+      html += "<ul>" + invariants.map((i) => "<li>" + escapeHtml(i) + "</li>").join("") + "</ul>";
+    }
+    details.innerHTML = html;
+    details.style.display = "block";
+  });
+  node.addEventListener("mousemove", (event) => {
+    details.style.left = event.clientX + 12 + "px";
+    details.style.top = event.clientY + 12 + "px";
+  });
+  node.addEventListener("mouseleave", () => { details.style.display = "none"; });
+}
+function escapeHtml(text) {
+  return text.replace(/[&<>"]/g, (c) => ({ "&": "&amp;", "<": "&lt;", ">": "&gt;", '"': "&quot;" })[c]);
+}
+"#;
+
+/// Generate an interactive HTML page from a bounded context.
+pub fn generate(context: &BoundedContext) -> Result<String, VizError> {
+    generate_with_theme(context, &Theme::default())
+}
+
+/// Generate an interactive HTML page from a bounded context, styled by a
+/// theme.
+pub fn generate_with_theme(context: &BoundedContext, theme: &Theme) -> Result<String, VizError> {
+    let diagram = layout(context, theme);
+
+    let mut output = String::new();
+    let _ = writeln!(
+        output,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\" font-size=\"14\">",
+        diagram.width, diagram.height
+    );
+    output.push_str(ARROW_MARKER);
+
+    // Add morphisms as arrows, under the objects
+    for edge in &diagram.edges {
+        let stroke = match morphism_style(context, edge.morphism, theme) {
+            Some(LineStyle::Dashed) => " stroke-dasharray=\"6 4\"",
+            Some(LineStyle::Dotted) => " stroke-dasharray=\"2 3\"",
+            Some(LineStyle::Bold) => " stroke-width=\"2.5\"",
+            _ => "",
+        };
+        let path: Vec<String> = edge
+            .points
+            .iter()
+            .map(|point| format!("{},{}", point.x, point.y))
+            .collect();
+        let _ = writeln!(
+            output,
+            "<path d=\"M{}\" fill=\"none\" stroke=\"#555\"{} marker-end=\"url(#arrow)\"/>",
+            path.join(" L"),
+            stroke
+        );
+        let _ = writeln!(
+            output,
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"12\" fill=\"#333\">{}</text>",
+            edge.label.x,
+            edge.label.y - 4.0,
+            escape(&edge.name)
+        );
+    }
+
+    // Add objects as links to their documentation
+    let graph = context.graph();
+    for node in &diagram.nodes {
+        let id = node.object;
+        let name = escape(&node.name);
+        let description = graph
+            .get_object(id)
+            .and_then(|o| o.description.clone())
+            .or_else(|| {
+                context
+                    .object_metadata(id)
+                    .and_then(|m| m.get("description"))
+                    .map(|d| d.to_string())
+            });
+        let invariants = invariants_of(context, id);
+
+        let _ = write!(
+            output,
+            "<a href=\"contexts/{}.html#{}\" data-name=\"{}\" data-kind=\"{}\"",
+            escape(context.name()),
+            name,
+            name,
+            escape(&node.kind)
+        );
+        if let Some(description) = &description {
+            let _ = write!(output, " data-description=\"{}\"", escape(description));
+        }
+        if !invariants.is_empty() {
+            let _ = write!(
+                output,
+                " data-invariants=\"{}\"",
+                escape(&invariants.join("\n"))
+            );
+        }
+        output.push_str(">\n");
+        let _ = writeln!(
+            output,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"4\" fill=\"{}\" stroke=\"#333\" stroke-width=\"{}\"/>",
+            node.x,
+            node.y,
+            node.width,
+            node.height,
+            escape(&fill_color(context, id, theme).unwrap_or_else(|| "#ffffff".to_string())),
+            if context.is_aggregate_root(id) { 3 } else { 1 }
+        );
+        let _ = writeln!(
+            output,
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>",
+            node.center().x,
+            node.center().y,
+            name
+        );
+        output.push_str("</a>\n");
+    }
+
+    output.push_str("</svg>\n<div id=\"details\"></div>\n");
+    let _ = writeln!(output, "<script>\n{}</script>", SCRIPT);
+
+    let font = theme.font.as_deref().unwrap_or("sans-serif");
+    let style = format!(":root {{ --font: {}; }}\n{}", escape(font), STYLE);
+    Ok(document(context.name(), &style, &output))
+}
+
+/// Wrap the body of a page in a self-contained HTML document.
+pub(crate) fn document(title: &str, style: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape(title),
+        style,
+        body
+    )
+}
+
+/// Get the point where the line from the center of a box of a size towards
+/// another point leaves the box.
+pub(crate) fn border_point(
+    (x, y): (f64, f64),
+    (width, height): (f64, f64),
+    (to_x, to_y): (f64, f64),
+) -> (f64, f64) {
+    let (dx, dy) = (to_x - x, to_y - y);
+    let scale_x = if dx == 0.0 {
+        f64::INFINITY
+    } else {
+        (width / 2.0) / dx.abs()
+    };
+    let scale_y = if dy == 0.0 {
+        f64::INFINITY
+    } else {
+        (height / 2.0) / dy.abs()
+    };
+    let scale = scale_x.min(scale_y).min(1.0);
+    (x + dx * scale, y + dy * scale)
+}
+
+/// Describe the invariants constraining an object: the rules of the
+/// aggregate it is the root of, and the equations on it.
+fn invariants_of(context: &BoundedContext, id: ObjectId) -> Vec<String> {
+    let graph = context.graph();
+    let rules = context
+        .invariants_of(id)
+        .into_iter()
+        .map(|invariant| invariant.expression.to_string());
+    let equations = context
+        .invariants()
+        .iter()
+        .filter(|invariant| {
+            graph
+                .get_morphism(invariant.inclusion)
+                .is_some_and(|inclusion| inclusion.target == id)
+        })
+        .map(|invariant| {
+            invariant
+                .description
+                .clone()
+                .unwrap_or_else(|| invariant.name.clone())
+        });
+    rules.chain(equations).collect()
+}
+
+/// Escape text for HTML.
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\n' => escaped.push_str("&#10;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sketchddd_core::{BinaryOp, Enforcement, Expr, MetadataValue};
+
+    #[test]
+    fn test_generate_links_objects_to_docs() {
+        let mut context = BoundedContext::new("Sales");
+        let order = context.add_entity("Order");
+        let customer = context.add_entity("Customer");
+        context.define_aggregate_with_members("Order", order, &[]);
+        context
+            .sketch_mut()
+            .graph
+            .add_morphism("placedBy", order, customer);
+        context.object_metadata_mut(order).set(
+            "description",
+            MetadataValue::Text("A customer's \"order\"".to_string()),
+        );
+        context.add_aggregate_invariant(
+            order,
+            Expr::Binary {
+                op: BinaryOp::Ge,
+                left: Box::new(Expr::Path(vec!["total".to_string()])),
+                right: Box::new(Expr::Number(0.0)),
+            },
+            Enforcement::Strict,
+        );
+
+        let result = generate(&context).unwrap();
+        assert!(result.starts_with("<!DOCTYPE html>\n"));
+        assert!(!result.contains("<script src") && !result.contains("<link"));
+        assert!(result.contains(
+            "<a href=\"contexts/Sales.html#Order\" data-name=\"Order\" data-kind=\"aggregate root\" data-description=\"A customer's &quot;order&quot;\" data-invariants=\"total &gt;= 0\">"
+        ));
+        assert!(result.contains(
+            "<a href=\"contexts/Sales.html#Customer\" data-name=\"Customer\" data-kind=\"entity\">"
+        ));
+        assert!(result.contains(">placedBy</text>"));
+        assert!(result.contains("stroke-width=\"3\"/>"));
+    }
+}
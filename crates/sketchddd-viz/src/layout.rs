@@ -0,0 +1,587 @@
+//! Layered diagram layout.
+//!
+//! Places the objects of a context in layers, in the manner of Sugiyama, so
+//! that morphisms point from one layer to the next: cycles are broken by
+//! reversing the morphisms that close them, each object is layered after
+//! the objects pointing to it, morphisms spanning several layers bend
+//! through the layers in between, and the objects of each layer are
+//! ordered to reduce crossings, then moved towards their neighbours.
+//!
+//! The result, a [`LayoutedDiagram`], holds the box of each object and the
+//! route of each morphism, and serializes to JSON for front ends that draw
+//! diagrams themselves, such as the HTML export and the visual builder.
+//! Layers run left to right, or top to bottom with the theme's direction.
+
+use crate::html::border_point;
+use crate::theme::{Direction, Theme};
+use crate::{aggregate_clusters, VizError};
+use serde::Serialize;
+use sketchddd_core::sketch::{MorphismId, ObjectId};
+use sketchddd_core::BoundedContext;
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+/// Height of an object's box.
+const NODE_HEIGHT: f64 = 40.0;
+
+/// Space between layers.
+const LAYER_GAP: f64 = 80.0;
+
+/// Space between the boxes of a layer.
+const NODE_GAP: f64 = 40.0;
+
+/// Space around the diagram, leaving room for loops over the top boxes.
+const MARGIN: f64 = 40.0;
+
+/// Height of a loop over a box.
+const LOOP_HEIGHT: f64 = 30.0;
+
+/// Passes over the layers when ordering and placing boxes.
+const SWEEPS: usize = 4;
+
+/// A point of a diagram.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// An object placed in a diagram.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+pub struct LayoutNode {
+    pub name: String,
+    /// Kind of the object, such as `entity` or `aggregate root`
+    pub kind: String,
+    /// Left of the object's box
+    pub x: f64,
+    /// Top of the object's box
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    #[serde(skip)]
+    pub(crate) object: ObjectId,
+}
+
+impl LayoutNode {
+    /// Get the center of the object's box.
+    pub fn center(&self) -> Point {
+        Point {
+            x: self.x + self.width / 2.0,
+            y: self.y + self.height / 2.0,
+        }
+    }
+}
+
+/// A morphism routed between two objects.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+pub struct LayoutEdge {
+    pub name: String,
+    pub source: String,
+    pub target: String,
+    /// Route of the morphism, from the border of its source's box to that
+    /// of its target's
+    pub points: Vec<Point>,
+    /// Where the morphism's name is placed
+    pub label: Point,
+    #[serde(skip)]
+    pub(crate) morphism: MorphismId,
+}
+
+/// A laid-out class diagram of a context.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+pub struct LayoutedDiagram {
+    pub name: String,
+    pub direction: Direction,
+    pub width: f64,
+    pub height: f64,
+    pub nodes: Vec<LayoutNode>,
+    pub edges: Vec<LayoutEdge>,
+}
+
+/// Generate the layout of a context's class diagram as JSON.
+pub fn generate(context: &BoundedContext) -> Result<String, VizError> {
+    generate_with_theme(context, &Theme::default())
+}
+
+/// Generate the layout of a context's class diagram as JSON, in the
+/// direction of a theme.
+pub fn generate_with_theme(context: &BoundedContext, theme: &Theme) -> Result<String, VizError> {
+    serde_json::to_string_pretty(&layout(context, theme))
+        .map(|json| json + "\n")
+        .map_err(|e| VizError::InvalidModel(e.to_string()))
+}
+
+/// Lay out the class diagram of a context.
+pub fn layout(context: &BoundedContext, theme: &Theme) -> LayoutedDiagram {
+    let graph = context.graph();
+    let direction = theme.direction.unwrap_or(Direction::LeftToRight);
+
+    // Objects constrained by invariants are drawn, not their equalizers.
+    // Aggregates come first, their members after their root, which sets
+    // the order the boxes of each layer start in.
+    let equalizers: HashSet<ObjectId> = context.invariants().iter().map(|i| i.equalizer).collect();
+    let (clusters, mut ungrouped) = aggregate_clusters(context);
+    ungrouped.sort();
+    let objects: Vec<_> = clusters
+        .iter()
+        .flat_map(|cluster| std::iter::once(cluster.root).chain(cluster.members.iter().copied()))
+        .chain(ungrouped)
+        .filter(|id| !equalizers.contains(id))
+        .filter_map(|id| graph.get_object(id))
+        .collect();
+    let index: HashMap<ObjectId, usize> = objects
+        .iter()
+        .enumerate()
+        .map(|(i, object)| (object.id, i))
+        .collect();
+    let sizes: Vec<(f64, f64)> = objects
+        .iter()
+        .map(|object| {
+            let width = (object.name.chars().count() as f64 * 8.0 + 32.0).max(100.0);
+            (width, NODE_HEIGHT)
+        })
+        .collect();
+
+    let mut morphisms: Vec<_> = graph
+        .morphisms()
+        .filter(|m| !m.is_identity)
+        .filter(|m| index.contains_key(&m.source) && index.contains_key(&m.target))
+        .collect();
+    morphisms.sort_by_key(|m| m.id);
+    let links: Vec<(usize, usize)> = morphisms
+        .iter()
+        .map(|m| (index[&m.source], index[&m.target]))
+        .collect();
+
+    let (centers, bends) = place(&sizes, &links, direction);
+    let nodes: Vec<LayoutNode> = objects
+        .iter()
+        .zip(&sizes)
+        .zip(&centers)
+        .map(|((object, &(width, height)), center)| LayoutNode {
+            name: object.name.clone(),
+            kind: kind(context, object.id).to_string(),
+            x: center.x - width / 2.0,
+            y: center.y - height / 2.0,
+            width,
+            height,
+            object: object.id,
+        })
+        .collect();
+
+    let edges: Vec<LayoutEdge> = morphisms
+        .iter()
+        .zip(&links)
+        .zip(bends)
+        .map(|((morphism, &(from, to)), bends)| {
+            let (source, target) = (&nodes[from], &nodes[to]);
+            let points = if from == to {
+                // A loop over the top of the box
+                let (left, right) = (
+                    source.x + source.width / 4.0,
+                    source.x + source.width * 3.0 / 4.0,
+                );
+                let top = source.y - LOOP_HEIGHT;
+                vec![
+                    Point {
+                        x: left,
+                        y: source.y,
+                    },
+                    Point { x: left, y: top },
+                    Point { x: right, y: top },
+                    Point {
+                        x: right,
+                        y: source.y,
+                    },
+                ]
+            } else {
+                let toward = bends.first().copied().unwrap_or(target.center());
+                let back = bends.last().copied().unwrap_or(source.center());
+                let mut points = vec![leave(source, toward)];
+                points.extend(bends);
+                points.push(leave(target, back));
+                points
+            };
+            LayoutEdge {
+                name: morphism.name.clone(),
+                source: source.name.clone(),
+                target: target.name.clone(),
+                label: middle(&points),
+                points,
+                morphism: morphism.id,
+            }
+        })
+        .collect();
+
+    // The diagram spans its boxes and routes, with a margin around them
+    let (mut width, mut height) = (0.0_f64, 0.0_f64);
+    for node in &nodes {
+        width = width.max(node.x + node.width);
+        height = height.max(node.y + node.height);
+    }
+    for point in edges.iter().flat_map(|edge| &edge.points) {
+        width = width.max(point.x);
+        height = height.max(point.y);
+    }
+
+    LayoutedDiagram {
+        name: context.name().to_string(),
+        direction,
+        width: width + MARGIN,
+        height: height + MARGIN,
+        nodes,
+        edges,
+    }
+}
+
+/// Place boxes of the given widths and heights in layers, so that links
+/// point from one layer to a later one where they can. Returns the center
+/// of each box and the bends of each link.
+fn place(
+    sizes: &[(f64, f64)],
+    links: &[(usize, usize)],
+    direction: Direction,
+) -> (Vec<Point>, Vec<Vec<Point>>) {
+    let count = sizes.len();
+
+    // Break cycles by reversing the links that close them
+    let reversed = back_links(count, links);
+    let forward: Vec<(usize, usize)> = links
+        .iter()
+        .zip(&reversed)
+        .map(|(&(from, to), &reversed)| if reversed { (to, from) } else { (from, to) })
+        .collect();
+
+    // Split links spanning several layers by dummy boxes, one per layer
+    // in between
+    let mut layer_of = layers(count, &forward);
+    let chains: Vec<Vec<usize>> = forward
+        .iter()
+        .map(|&(from, to)| {
+            let mut chain = vec![from];
+            if from != to {
+                for layer in layer_of[from] + 1..layer_of[to] {
+                    layer_of.push(layer);
+                    chain.push(layer_of.len() - 1);
+                }
+            }
+            chain.push(to);
+            chain
+        })
+        .collect();
+    let total = layer_of.len();
+
+    let mut layers: Vec<Vec<usize>> = vec![Vec::new(); layer_of.iter().max().map_or(0, |l| l + 1)];
+    for (v, &layer) in layer_of.iter().enumerate() {
+        layers[layer].push(v);
+    }
+    let (mut before, mut after) = (vec![Vec::new(); total], vec![Vec::new(); total]);
+    for chain in &chains {
+        for pair in chain.windows(2).filter(|pair| pair[0] != pair[1]) {
+            before[pair[1]].push(pair[0]);
+            after[pair[0]].push(pair[1]);
+        }
+    }
+
+    // Sizes along and across the layers; dummy boxes have none
+    let size = |v: usize| {
+        let (width, height) = sizes.get(v).copied().unwrap_or((0.0, 0.0));
+        match direction {
+            Direction::LeftToRight => (width, height),
+            Direction::TopToBottom => (height, width),
+        }
+    };
+
+    // Order each layer by the mean position of its neighbours in the
+    // layer before it, or after it on the way back
+    let mut position = vec![0.0; total];
+    let number = |layer: &[usize], position: &mut [f64]| {
+        for (i, &v) in layer.iter().enumerate() {
+            position[v] = i as f64;
+        }
+    };
+    for layer in &layers {
+        number(layer, &mut position);
+    }
+    for sweep in 0..SWEEPS {
+        let (order, neighbours): (Vec<usize>, _) = if sweep % 2 == 0 {
+            ((1..layers.len()).collect(), &before)
+        } else {
+            ((0..layers.len().saturating_sub(1)).rev().collect(), &after)
+        };
+        for l in order {
+            let barycenter = |v: usize| mean(&neighbours[v], &position).unwrap_or(position[v]);
+            let mut keyed: Vec<(f64, usize)> =
+                layers[l].iter().map(|&v| (barycenter(v), v)).collect();
+            keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+            layers[l] = keyed.into_iter().map(|(_, v)| v).collect();
+            number(&layers[l], &mut position);
+        }
+    }
+
+    // Pack each layer, then move its boxes towards their neighbours, as far
+    // as the boxes before them in the layer allow
+    let mut across = vec![0.0; total];
+    let pack = |layer: &[usize], across: &mut [f64], wanted: &dyn Fn(usize) -> f64| {
+        let mut end = f64::NEG_INFINITY;
+        for &v in layer {
+            let half = size(v).1 / 2.0;
+            across[v] = wanted(v).max(end + NODE_GAP + half);
+            end = across[v] + half;
+        }
+    };
+    for layer in &layers {
+        pack(layer, &mut across, &|_| 0.0);
+    }
+    for sweep in 0..SWEEPS {
+        let (order, neighbours): (Vec<usize>, _) = if sweep % 2 == 0 {
+            ((1..layers.len()).collect(), &before)
+        } else {
+            ((0..layers.len().saturating_sub(1)).rev().collect(), &after)
+        };
+        for l in order {
+            let current = across.clone();
+            pack(&layers[l], &mut across, &|v| {
+                mean(&neighbours[v], &current).unwrap_or(current[v])
+            });
+        }
+    }
+    let start = (0..total)
+        .map(|v| across[v] - size(v).1 / 2.0)
+        .fold(f64::INFINITY, f64::min);
+
+    // Space the layers by their deepest box
+    let mut along = Vec::with_capacity(layers.len());
+    let mut offset = 0.0;
+    for layer in &layers {
+        let depth = layer.iter().map(|&v| size(v).0).fold(0.0, f64::max);
+        along.push(offset + depth / 2.0);
+        offset += depth + LAYER_GAP;
+    }
+
+    let point = |v: usize| {
+        let (along, across) = (along[layer_of[v]] + MARGIN, across[v] - start + MARGIN);
+        match direction {
+            Direction::LeftToRight => Point {
+                x: along,
+                y: across,
+            },
+            Direction::TopToBottom => Point {
+                x: across,
+                y: along,
+            },
+        }
+    };
+    let centers = (0..count).map(point).collect();
+    let bends = chains
+        .iter()
+        .zip(&reversed)
+        .map(|(chain, &reversed)| {
+            let mut bends: Vec<Point> = chain[1..chain.len() - 1]
+                .iter()
+                .map(|&v| point(v))
+                .collect();
+            if reversed {
+                bends.reverse();
+            }
+            bends
+        })
+        .collect();
+    (centers, bends)
+}
+
+/// Find the links closing cycles, by a depth-first search from each box in
+/// turn. Reversing them leaves no cycles but loops.
+fn back_links(count: usize, links: &[(usize, usize)]) -> Vec<bool> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Visit {
+        New,
+        Open,
+        Done,
+    }
+
+    let mut outgoing = vec![Vec::new(); count];
+    for (i, &(from, _)) in links.iter().enumerate() {
+        outgoing[from].push(i);
+    }
+    let mut visits = vec![Visit::New; count];
+    let mut reversed = vec![false; links.len()];
+    for start in 0..count {
+        if visits[start] != Visit::New {
+            continue;
+        }
+        visits[start] = Visit::Open;
+        let mut stack = vec![(start, 0)];
+        while let Some((v, next)) = stack.last_mut() {
+            let v = *v;
+            let Some(&link) = outgoing[v].get(*next) else {
+                visits[v] = Visit::Done;
+                stack.pop();
+                continue;
+            };
+            *next += 1;
+            let to = links[link].1;
+            match visits[to] {
+                Visit::Open => reversed[link] = to != v,
+                Visit::New => {
+                    visits[to] = Visit::Open;
+                    stack.push((to, 0));
+                }
+                Visit::Done => {}
+            }
+        }
+    }
+    reversed
+}
+
+/// Layer each box one past the last of the boxes linking to it, given
+/// links without cycles.
+fn layers(count: usize, links: &[(usize, usize)]) -> Vec<usize> {
+    let mut incoming = vec![0; count];
+    for &(_, to) in links.iter().filter(|(from, to)| from != to) {
+        incoming[to] += 1;
+    }
+    let mut layer = vec![0; count];
+    let mut ready: Vec<usize> = (0..count).filter(|&v| incoming[v] == 0).collect();
+    while let Some(v) = ready.pop() {
+        for &(_, to) in links.iter().filter(|&&(from, to)| from == v && to != v) {
+            layer[to] = layer[to].max(layer[v] + 1);
+            incoming[to] -= 1;
+            if incoming[to] == 0 {
+                ready.push(to);
+            }
+        }
+    }
+    layer
+}
+
+/// Get the mean of the values of some boxes, if there are any.
+fn mean(boxes: &[usize], values: &[f64]) -> Option<f64> {
+    if boxes.is_empty() {
+        return None;
+    }
+    Some(boxes.iter().map(|&v| values[v]).sum::<f64>() / boxes.len() as f64)
+}
+
+/// Get the point where a route towards a point leaves a box.
+fn leave(node: &LayoutNode, toward: Point) -> Point {
+    let center = node.center();
+    let (x, y) = border_point(
+        (center.x, center.y),
+        (node.width, node.height),
+        (toward.x, toward.y),
+    );
+    Point { x, y }
+}
+
+/// Get the middle of a route: its middle point, or the middle of its middle
+/// segment.
+fn middle(points: &[Point]) -> Point {
+    let half = points.len() / 2;
+    if points.len() % 2 == 1 {
+        points[half]
+    } else {
+        let (a, b) = (points[half - 1], points[half]);
+        Point {
+            x: (a.x + b.x) / 2.0,
+            y: (a.y + b.y) / 2.0,
+        }
+    }
+}
+
+/// Describe the kind of an object.
+fn kind(context: &BoundedContext, id: ObjectId) -> &'static str {
+    if context.is_aggregate_root(id) {
+        "aggregate root"
+    } else if context.is_entity(id) {
+        "entity"
+    } else if context.is_value_object(id) {
+        "value object"
+    } else if context.get_enum_colimit(id).is_some() {
+        "enum"
+    } else {
+        "object"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_layers_morphisms() {
+        let mut context = BoundedContext::new("Sales");
+        let customer = context.add_entity("Customer");
+        let order = context.add_entity("Order");
+        let line = context.add_entity("LineItem");
+        let graph = &mut context.sketch_mut().graph;
+        graph.add_morphism("placedBy", order, customer);
+        graph.add_morphism("lines", order, line);
+        graph.add_morphism("order", line, order);
+        graph.add_morphism("referredBy", customer, customer);
+
+        let diagram = layout(&context, &Theme::default());
+        let node = |name: &str| diagram.nodes.iter().find(|n| n.name == name).unwrap();
+        let (customer, order, line) = (node("Customer"), node("Order"), node("LineItem"));
+        assert_eq!(order.kind, "entity");
+
+        // Customer follows Order, in the layer after it, and so does the
+        // line item, its link back to Order reversed
+        assert!(customer.x > order.x + order.width);
+        assert_eq!(customer.x, line.x);
+        assert!(customer.y + customer.height <= line.y || line.y + line.height <= customer.y);
+
+        let edge = |name: &str| diagram.edges.iter().find(|e| e.name == name).unwrap();
+        let placed_by = edge("placedBy");
+        assert_eq!(
+            (placed_by.source.as_str(), placed_by.target.as_str()),
+            ("Order", "Customer")
+        );
+        assert_eq!(placed_by.points.first().unwrap().x, order.x + order.width);
+        assert_eq!(placed_by.points.last().unwrap().x, customer.x);
+        assert_eq!(
+            edge("order").points.last().unwrap().x,
+            order.x + order.width
+        );
+        assert_eq!(edge("referredBy").points.len(), 4);
+        assert!(diagram.width >= customer.x + customer.width);
+    }
+
+    #[test]
+    fn test_layout_bends_long_morphisms_top_to_bottom() {
+        let mut context = BoundedContext::new("Sales");
+        let a = context.add_entity("A");
+        let b = context.add_entity("B");
+        let c = context.add_entity("C");
+        let graph = &mut context.sketch_mut().graph;
+        graph.add_morphism("ab", a, b);
+        graph.add_morphism("bc", b, c);
+        graph.add_morphism("ac", a, c);
+        let theme = Theme {
+            direction: Some(Direction::TopToBottom),
+            ..Default::default()
+        };
+
+        let diagram = layout(&context, &theme);
+        let node = |name: &str| diagram.nodes.iter().find(|n| n.name == name).unwrap();
+        assert!(node("A").y < node("B").y && node("B").y < node("C").y);
+        let ac = diagram.edges.iter().find(|e| e.name == "ac").unwrap();
+        assert_eq!(ac.points.len(), 3);
+        assert_eq!(ac.points[1].y, node("B").center().y);
+
+        let json: serde_json::Value =
+            serde_json::from_str(&generate_with_theme(&context, &theme).unwrap()).unwrap();
+        assert_eq!(json["direction"], "TB");
+        assert_eq!(json["nodes"][0]["name"], "A");
+        assert_eq!(
+            json["edges"][0]["points"][0]["y"],
+            node("A").y + NODE_HEIGHT
+        );
+        assert!(json["nodes"][0].get("object").is_none());
+    }
+}
@@ -0,0 +1,487 @@
+//! Legends and banners of diagrams.
+//!
+//! A legend explains the shapes, colours and line styles a diagram uses,
+//! listing only those it draws. A banner names the model the diagram was
+//! drawn from, its version and when the diagram was generated. Both are
+//! added to Graphviz and Mermaid class diagrams and context maps, so that
+//! diagrams shared outside the team explain themselves.
+//!
+//! In Graphviz the legend is a cluster of sample nodes and edges, and the
+//! banner the graph's label. In Mermaid the legend of a class diagram is a
+//! note and that of a context map a subgraph, and the banner the diagram's
+//! title.
+
+use crate::context_map::{arrow, line_style, Arrow};
+use crate::graphviz::quote;
+use crate::mermaid::label;
+use crate::theme::{LineStyle, Theme};
+use crate::{
+    classification_color, fill_color, morphism_style, multiplicities, Format, Multiplicity,
+    VizError,
+};
+use sketchddd_core::{BoundedContext, Classification, Workspace};
+
+/// What a diagram's banner says about the model it was drawn from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Banner {
+    /// Name of the model
+    pub model: Option<String>,
+
+    /// Version of the model
+    pub version: Option<String>,
+
+    /// When the diagram was generated, such as `2024-05-01 12:00 UTC`
+    pub generated: Option<String>,
+}
+
+impl Banner {
+    /// Write the banner on a line, such as `Commerce v1.2 · generated
+    /// 2024-05-01 12:00 UTC`, if it says anything.
+    fn text(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if let Some(model) = &self.model {
+            parts.push(model.clone());
+        }
+        if let Some(version) = &self.version {
+            parts.push(format!("v{}", version));
+        }
+        let mut text = parts.join(" ");
+        if let Some(generated) = &self.generated {
+            if !text.is_empty() {
+                text.push_str(" · ");
+            }
+            text.push_str(&format!("generated {}", generated));
+        }
+        (!text.is_empty()).then_some(text)
+    }
+}
+
+/// How a legend shows a meaning.
+#[derive(Debug, Clone, PartialEq)]
+enum Mark {
+    /// A node of a Graphviz shape
+    Shape(&'static str),
+    /// A bold border
+    Border,
+    /// A fill colour
+    Fill(String),
+    /// A line style
+    Line(LineStyle),
+    /// A frame around nodes
+    Frame,
+    /// A label on edges
+    Label(String),
+}
+
+/// A mark of a legend and what it means.
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    mark: Mark,
+    meaning: String,
+}
+
+impl Entry {
+    fn new(mark: Mark, meaning: impl Into<String>) -> Self {
+        Entry {
+            mark,
+            meaning: meaning.into(),
+        }
+    }
+}
+
+/// Add a legend and banner to a class diagram of a context, generated in a
+/// format with a theme.
+pub fn class_diagram(
+    diagram: &str,
+    format: Format,
+    context: &BoundedContext,
+    theme: &Theme,
+    banner: &Banner,
+) -> Result<String, VizError> {
+    let entries = class_entries(context, theme);
+    match format {
+        Format::Graphviz => Ok(graphviz(diagram, &entries, banner)),
+        Format::Mermaid => Ok(mermaid_note(diagram, &entries, banner)),
+        _ => Err(VizError::UnsupportedFormat(
+            "legends are drawn in Graphviz or Mermaid".to_string(),
+        )),
+    }
+}
+
+/// Add a legend and banner to a context map of a workspace, generated in a
+/// format with a theme.
+pub fn context_map(
+    diagram: &str,
+    format: Format,
+    workspace: &Workspace,
+    theme: &Theme,
+    banner: &Banner,
+) -> Result<String, VizError> {
+    let entries = context_map_entries(workspace, theme);
+    match format {
+        Format::Graphviz => Ok(graphviz(diagram, &entries, banner)),
+        Format::Mermaid => Ok(mermaid_subgraph(diagram, &entries, banner)),
+        _ => Err(VizError::UnsupportedFormat(
+            "legends are drawn in Graphviz or Mermaid".to_string(),
+        )),
+    }
+}
+
+/// List the marks a class diagram of a context draws.
+fn class_entries(context: &BoundedContext, theme: &Theme) -> Vec<Entry> {
+    let graph = context.graph();
+    let mut objects: Vec<_> = graph.objects().map(|o| o.id).collect();
+    objects.sort();
+    let mut entries = Vec::new();
+    let mut add = |entry: Entry| {
+        if !entries.contains(&entry) {
+            entries.push(entry);
+        }
+    };
+
+    // Shapes of the kinds of objects, as Graphviz draws them
+    for &id in &objects {
+        if context.is_entity(id) {
+            add(Entry::new(Mark::Shape("box"), "entity"));
+        } else if context.is_value_object(id) {
+            add(Entry::new(Mark::Shape("ellipse"), "value object"));
+        }
+    }
+    if !context.aggregate_roots().is_empty() {
+        add(Entry::new(Mark::Border, "aggregate root"));
+        add(Entry::new(Mark::Frame, "aggregate"));
+    }
+
+    // Fills, by classification or else by kind
+    for &id in &objects {
+        let Some(color) = fill_color(context, id, theme) else {
+            continue;
+        };
+        let meaning = if let Some(classification) = context.classification_of(id) {
+            format!("{} subdomain", classification)
+        } else if context.is_aggregate_root(id) && theme.colors.aggregate_root.is_some() {
+            "aggregate root".to_string()
+        } else if context.is_entity(id) {
+            "entity".to_string()
+        } else if context.is_value_object(id) {
+            "value object".to_string()
+        } else {
+            "enum".to_string()
+        };
+        add(Entry::new(Mark::Fill(color), meaning));
+    }
+
+    // Line styles the theme gives morphisms
+    let mut morphisms: Vec<_> = graph.morphisms().filter(|m| !m.is_identity).collect();
+    morphisms.sort_by_key(|m| m.id);
+    for morphism in &morphisms {
+        if let Some(style) = morphism_style(context, morphism.id, theme) {
+            let meaning = if Some(style) == theme.edges.containment
+                && theme.edges.containment != theme.edges.morphism
+            {
+                "root to member"
+            } else {
+                "morphism"
+            };
+            add(Entry::new(Mark::Line(style), meaning));
+        }
+    }
+
+    // Multiplicities written on the ends of morphisms
+    if theme.multiplicities {
+        for morphism in &morphisms {
+            let (tail, head) = multiplicities(context, morphism.id);
+            for end in [tail, head] {
+                let meaning = match end {
+                    Multiplicity::Many => "any number",
+                    Multiplicity::AtMostOne => "at most one",
+                    Multiplicity::One => "exactly one",
+                };
+                add(Entry::new(Mark::Label(end.label().to_string()), meaning));
+            }
+        }
+    }
+    entries
+}
+
+/// List the marks a context map of a workspace draws.
+fn context_map_entries(workspace: &Workspace, theme: &Theme) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    let mut add = |entry: Entry| {
+        if !entries.contains(&entry) {
+            entries.push(entry);
+        }
+    };
+
+    if workspace.domains.iter().any(|d| !d.subdomains.is_empty()) {
+        add(Entry::new(Mark::Frame, "subdomain"));
+    }
+    for context in &workspace.contexts {
+        if let Some(classification) = context.metadata().classification {
+            add(classification_entry(classification));
+        }
+    }
+    for map in &workspace.context_maps {
+        let arrow = arrow(map.pattern);
+        if let Arrow::Upstream = arrow {
+            add(Entry::new(
+                Mark::Label("U → D".to_string()),
+                "upstream to downstream",
+            ));
+        }
+        add(Entry::new(
+            Mark::Label(map.pattern.abbreviation().to_string()),
+            format!("{:?}", map.pattern),
+        ));
+        if let Some(style) = line_style(arrow, theme) {
+            let meaning = match arrow {
+                Arrow::Upstream | Arrow::Forward => "upstream to downstream",
+                Arrow::Both => "symmetric",
+                Arrow::None => "separate ways",
+            };
+            add(Entry::new(Mark::Line(style), meaning));
+        }
+    }
+    entries
+}
+
+fn classification_entry(classification: Classification) -> Entry {
+    Entry::new(
+        Mark::Fill(classification_color(classification).to_string()),
+        format!("{} subdomain", classification),
+    )
+}
+
+/// Add a legend cluster and a banner label to a Graphviz diagram.
+fn graphviz(diagram: &str, entries: &[Entry], banner: &Banner) -> String {
+    let mut output = diagram.trim_end().trim_end_matches('}').to_string();
+
+    if let Some(text) = banner.text() {
+        // The banner follows the graph's opening line
+        let start = output.find('\n').map_or(output.len(), |i| i + 1);
+        output.insert_str(
+            start,
+            &format!("  label={};\n  labelloc=t;\n", quote(&text)),
+        );
+    }
+
+    if !entries.is_empty() {
+        output.push_str("\n  subgraph cluster_legend {\n");
+        output.push_str("    label=\"Legend\";\n");
+        output.push_str("    style=dashed;\n");
+        for (i, entry) in entries.iter().enumerate() {
+            let node = format!("_legend_{}", i);
+            let meaning = quote(&entry.meaning);
+            let line = match &entry.mark {
+                Mark::Shape(shape) => format!("{} [label={} shape={}];", node, meaning, shape),
+                Mark::Border => format!("{} [label={} penwidth=2];", node, meaning),
+                Mark::Fill(color) => format!(
+                    "{} [label={} style=filled fillcolor={}];",
+                    node,
+                    meaning,
+                    quote(color)
+                ),
+                Mark::Frame => format!("{} [label={} style=\"rounded,dashed\"];", node, meaning),
+                Mark::Line(style) => format!(
+                    "{0}_from [shape=point];\n    {0}_to [shape=point];\n    {0}_from -> {0}_to [label={1} style={2}];",
+                    node,
+                    meaning,
+                    style.as_str()
+                ),
+                Mark::Label(text) => format!(
+                    "{} [label={} shape=plaintext];",
+                    node,
+                    quote(&format!("{}: {}", text, entry.meaning))
+                ),
+            };
+            output.push_str(&format!("    {}\n", line));
+        }
+        output.push_str("  }\n");
+    }
+
+    output.push_str("}\n");
+    output
+}
+
+/// Add a banner title to a Mermaid diagram.
+fn mermaid_title(diagram: &str, banner: &Banner) -> String {
+    let body = diagram.strip_prefix("```mermaid\n").unwrap_or(diagram);
+    match banner.text() {
+        Some(text) => format!(
+            "```mermaid\n---\ntitle: \"{}\"\n---\n{}",
+            text.replace('\\', "\\\\").replace('"', "\\\""),
+            body
+        ),
+        None => format!("```mermaid\n{}", body),
+    }
+}
+
+/// Insert lines before the end of a Mermaid diagram.
+fn mermaid_append(diagram: &str, lines: &str) -> String {
+    let body = diagram.trim_end().trim_end_matches("```");
+    format!("{}{}```\n", body, lines)
+}
+
+/// Add a legend note and a banner to a Mermaid class diagram. Class
+/// diagrams name the kinds of objects and draw no bold lines, so those are
+/// left out of the note.
+fn mermaid_note(diagram: &str, entries: &[Entry], banner: &Banner) -> String {
+    let lines: Vec<String> = entries
+        .iter()
+        .filter_map(|entry| {
+            let mark = match &entry.mark {
+                Mark::Shape(_) => return None,
+                Mark::Border => "thick border".to_string(),
+                Mark::Fill(color) => format!("{} fill", color),
+                Mark::Line(LineStyle::Dashed | LineStyle::Dotted) => "dashed arrow".to_string(),
+                Mark::Line(_) => return None,
+                Mark::Frame => "namespace".to_string(),
+                Mark::Label(text) => text.clone(),
+            };
+            Some(format!("{}: {}", mark, entry.meaning))
+        })
+        .collect();
+
+    let diagram = mermaid_title(diagram, banner);
+    if lines.is_empty() {
+        return diagram;
+    }
+    let note = format!(
+        "\n    note \"{}\"\n",
+        label(&format!("Legend<br>{}", lines.join("<br>")))
+    );
+    mermaid_append(&diagram, &note)
+}
+
+/// Add a legend subgraph and a banner to a Mermaid flowchart.
+fn mermaid_subgraph(diagram: &str, entries: &[Entry], banner: &Banner) -> String {
+    let diagram = mermaid_title(diagram, banner);
+    if entries.is_empty() {
+        return diagram;
+    }
+
+    let mut lines = String::from("    subgraph legend[\"Legend\"]\n");
+    let mut styles = String::new();
+    for (i, entry) in entries.iter().enumerate() {
+        let node = format!("legend_{}", i);
+        let text = match &entry.mark {
+            Mark::Label(text) => format!("{}: {}", text, entry.meaning),
+            Mark::Line(style) => format!("{} line: {}", style.as_str(), entry.meaning),
+            Mark::Frame => format!("box around: {}", entry.meaning),
+            _ => entry.meaning.clone(),
+        };
+        lines.push_str(&format!("        {}[\"{}\"]\n", node, label(&text)));
+        if let Mark::Fill(color) = &entry.mark {
+            styles.push_str(&format!("    style {} fill:{}\n", node, color));
+        }
+    }
+    lines.push_str("    end\n");
+    lines.push_str(&styles);
+    mermaid_append(&diagram, &lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::EdgeStyles;
+    use sketchddd_core::{NamedContextMap, RelationshipPattern};
+
+    fn banner() -> Banner {
+        Banner {
+            model: Some("Commerce".to_string()),
+            version: Some("1.2".to_string()),
+            generated: Some("2024-05-01 12:00 UTC".to_string()),
+        }
+    }
+
+    fn sales() -> BoundedContext {
+        let mut context = BoundedContext::new("Sales");
+        let order = context.add_entity("Order");
+        let line = context.add_entity("LineItem");
+        context.add_value_object("Money");
+        context.define_aggregate_with_members("Order", order, &[line]);
+        context.object_metadata_mut(line).classification = Some(Classification::Core);
+        context
+    }
+
+    #[test]
+    fn test_graphviz_class_diagram_legend() {
+        let context = sales();
+        let theme = Theme {
+            edges: EdgeStyles {
+                containment: Some(LineStyle::Bold),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let diagram = crate::graphviz::generate_with_theme(&context, &theme).unwrap();
+
+        let result =
+            class_diagram(&diagram, Format::Graphviz, &context, &theme, &banner()).unwrap();
+        assert!(result.starts_with(
+            "digraph Sales {\n  label=\"Commerce v1.2 · generated 2024-05-01 12:00 UTC\";\n  labelloc=t;\n  rankdir=LR;\n"
+        ));
+        assert!(result.ends_with(
+            "  subgraph cluster_legend {\n    label=\"Legend\";\n    style=dashed;\n    _legend_0 [label=\"entity\" shape=box];\n    _legend_1 [label=\"value object\" shape=ellipse];\n    _legend_2 [label=\"aggregate root\" penwidth=2];\n    _legend_3 [label=\"aggregate\" style=\"rounded,dashed\"];\n    _legend_4 [label=\"core subdomain\" style=filled fillcolor=\"#f9d67a\"];\n    _legend_5_from [shape=point];\n    _legend_5_to [shape=point];\n    _legend_5_from -> _legend_5_to [label=\"root to member\" style=bold];\n  }\n}\n"
+        ));
+    }
+
+    #[test]
+    fn test_mermaid_class_diagram_legend() {
+        let context = sales();
+        let diagram = crate::mermaid::generate(&context).unwrap();
+
+        let banner = Banner {
+            model: Some("Commerce".to_string()),
+            ..Default::default()
+        };
+        let result = class_diagram(
+            &diagram,
+            Format::Mermaid,
+            &context,
+            &Theme::default(),
+            &banner,
+        )
+        .unwrap();
+        assert!(result.starts_with("```mermaid\n---\ntitle: \"Commerce\"\n---\nclassDiagram\n"));
+        assert!(result.ends_with(
+            "\n    note \"Legend<br>thick border: aggregate root<br>namespace: aggregate<br>#f9d67a fill: core subdomain\"\n```\n"
+        ));
+    }
+
+    #[test]
+    fn test_mermaid_context_map_legend() {
+        let mut workspace = Workspace::new();
+        let mut sales = BoundedContext::new("Sales");
+        sales.metadata_mut().classification = Some(Classification::Supporting);
+        workspace.add_context(sales);
+        workspace.add_context(BoundedContext::new("Shipping"));
+        workspace.add_context_map(NamedContextMap::new(
+            "SalesToShipping",
+            "Sales",
+            "Shipping",
+            RelationshipPattern::CustomerSupplier,
+        ));
+        let diagram = crate::context_map::generate(&workspace, Format::Mermaid).unwrap();
+
+        let result = context_map(
+            &diagram,
+            Format::Mermaid,
+            &workspace,
+            &Theme::default(),
+            &Banner::default(),
+        )
+        .unwrap();
+        assert!(result.starts_with("```mermaid\nflowchart LR\n"));
+        assert!(result.ends_with(
+            "    subgraph legend[\"Legend\"]\n        legend_0[\"supporting subdomain\"]\n        legend_1[\"U → D: upstream to downstream\"]\n        legend_2[\"CS: CustomerSupplier\"]\n    end\n    style legend_0 fill:#a8d5ba\n```\n"
+        ));
+    }
+
+    #[test]
+    fn test_legend_unsupported_format() {
+        let context = sales();
+        let result = class_diagram("", Format::Html, &context, &Theme::default(), &banner());
+        assert!(matches!(result, Err(VizError::UnsupportedFormat(_))));
+    }
+}
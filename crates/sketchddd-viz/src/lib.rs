@@ -6,11 +6,55 @@
 //!
 //! - **Graphviz DOT**: For rendering with Graphviz
 //! - **Mermaid**: For rendering in Markdown/GitHub
+//! - **HTML**: A self-contained interactive page, see the [`html`] module
+//! - **Excalidraw** and **draw.io**: Pre-laid-out drawings to refine in
+//!   those whiteboard tools, see the [`excalidraw`] and [`drawio`] modules
+//!
+//! Process managers (sagas) can additionally be rendered as Mermaid sequence
+//! or state diagrams via the [`process`] module, and entity lifecycles as
+//! Mermaid state diagrams via the [`lifecycle`] module. The [`er`] module
+//! draws a context as a Mermaid entity-relationship diagram, and
+//! [`generate_diagram`] picks between the class, ER and state diagrams and
+//! the event storming board of a context by [`Diagram`].
+//! [`generate_context_map`] draws the big picture of a whole model: its
+//! contexts, grouped by subdomain, and the context maps between them,
+//! labelled with their relationship patterns.
+//! The [`event_storming`] module lays out the actors, commands, aggregates,
+//! events, policies and hotspots of a context as an event storming board,
+//! in Mermaid or as an SVG or HTML page.
+//! The [`layout`] module places the objects and routes the morphisms of a
+//! class diagram in layers, for front ends that draw diagrams themselves.
+//! The [`legend`] module adds a legend and a banner naming the model to
+//! Graphviz and Mermaid class diagrams and context maps.
+//!
+//! Objects classified as core, supporting or generic subdomain (see
+//! [`sketchddd_core::Classification`]) are filled with a matching colour.
+//! Aggregates are drawn as boundaries enclosing their members, with their
+//! root outlined in bold.
+//!
+//! A [`theme::Theme`] sets the colours of each kind of object, the line
+//! styles of edges, the font and the direction of a diagram, for the
+//! `_with_theme` variants of the generators.
 
+pub mod context_map;
+mod drawing;
+pub mod drawio;
+pub mod er;
+pub mod event_storming;
+pub mod excalidraw;
 pub mod graphviz;
+pub mod html;
+pub mod layout;
+pub mod legend;
+pub mod lifecycle;
 pub mod mermaid;
+pub mod process;
+pub mod theme;
 
-use sketchddd_core::BoundedContext;
+use sketchddd_core::sketch::{MorphismId, ObjectId};
+use sketchddd_core::{BoundedContext, Classification, Workspace};
+use std::collections::HashSet;
+use theme::{LineStyle, Theme};
 use thiserror::Error;
 
 /// Error during visualization generation.
@@ -24,10 +68,21 @@ pub enum VizError {
 }
 
 /// Output format for visualization.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Format {
     Graphviz,
     Mermaid,
+    Html,
+    Excalidraw,
+    Drawio,
+}
+
+impl Format {
+    /// Check whether the format is a whole document, which holds a single
+    /// diagram rather than being concatenated with others.
+    pub fn is_document(self) -> bool {
+        matches!(self, Format::Html | Format::Excalidraw | Format::Drawio)
+    }
 }
 
 impl std::str::FromStr for Format {
@@ -37,15 +92,645 @@ impl std::str::FromStr for Format {
         match s.to_lowercase().as_str() {
             "graphviz" | "dot" => Ok(Format::Graphviz),
             "mermaid" | "md" => Ok(Format::Mermaid),
+            "html" => Ok(Format::Html),
+            "excalidraw" => Ok(Format::Excalidraw),
+            "drawio" | "draw.io" => Ok(Format::Drawio),
             _ => Err(VizError::UnsupportedFormat(s.to_string())),
         }
     }
 }
 
+/// Kind of diagram drawn of a bounded context.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Diagram {
+    /// Objects as classes and morphisms as relationships
+    #[default]
+    Class,
+    /// Entities, their attributes and the cardinality of relationships
+    Er,
+    /// The state diagrams of the context's lifecycles
+    State,
+    /// The context's event storming board
+    Board,
+}
+
+impl std::str::FromStr for Diagram {
+    type Err = VizError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "class" => Ok(Diagram::Class),
+            "er" => Ok(Diagram::Er),
+            "state" => Ok(Diagram::State),
+            "board" => Ok(Diagram::Board),
+            _ => Err(VizError::UnsupportedFormat(s.to_string())),
+        }
+    }
+}
+
+/// Get the fill colour of objects with a classification.
+pub(crate) fn classification_color(classification: Classification) -> &'static str {
+    match classification {
+        Classification::Core => "#f9d67a",
+        Classification::Supporting => "#a8d5ba",
+        Classification::Generic => "#d3d3d3",
+    }
+}
+
+/// Get the fill colour of an object: the colour of its classification, else
+/// the colour the theme gives its kind.
+pub(crate) fn fill_color(context: &BoundedContext, id: ObjectId, theme: &Theme) -> Option<String> {
+    if let Some(classification) = context.classification_of(id) {
+        return Some(classification_color(classification).to_string());
+    }
+    let colors = &theme.colors;
+    let color = if context.is_aggregate_root(id) {
+        colors.aggregate_root.as_ref().or(colors.entity.as_ref())
+    } else if context.is_entity(id) {
+        colors.entity.as_ref()
+    } else if context.is_value_object(id) {
+        colors.value_object.as_ref()
+    } else if context.get_enum_colimit(id).is_some() {
+        colors.enum_type.as_ref()
+    } else {
+        None
+    };
+    color.cloned()
+}
+
+/// Get the line style the theme gives a morphism: its containment style if
+/// it leads from the root of an aggregate to a member, else its morphism
+/// style.
+pub(crate) fn morphism_style(
+    context: &BoundedContext,
+    morphism: MorphismId,
+    theme: &Theme,
+) -> Option<LineStyle> {
+    let contains = context
+        .aggregate_roots()
+        .iter()
+        .filter_map(|&root| context.get_aggregate(root))
+        .any(|aggregate| aggregate.projections.iter().any(|p| p.morphism == morphism));
+    if contains {
+        theme.edges.containment
+    } else {
+        theme.edges.morphism
+    }
+}
+
+/// How many elements of an object an end of a morphism relates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Multiplicity {
+    One,
+    AtMostOne,
+    Many,
+}
+
+impl Multiplicity {
+    /// The UML label of the multiplicity.
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Multiplicity::One => "1",
+            Multiplicity::AtMostOne => "0..1",
+            Multiplicity::Many => "*",
+        }
+    }
+}
+
+/// Get the multiplicities of the source and target ends of a morphism.
+/// Any number of sources share a target, and each source has exactly one
+/// target, or at most one when the morphism is optional. A to-many
+/// morphism, to a `List<T>`, instead gives each source any number of
+/// targets, each belonging to one source. An identity relates each
+/// element to itself alone.
+pub(crate) fn multiplicities(
+    context: &BoundedContext,
+    morphism: MorphismId,
+) -> (Multiplicity, Multiplicity) {
+    let identity = context
+        .graph()
+        .get_morphism(morphism)
+        .is_some_and(|m| m.is_identity);
+    if identity {
+        (Multiplicity::One, Multiplicity::One)
+    } else if context.is_many(morphism) {
+        (Multiplicity::One, Multiplicity::Many)
+    } else if context.is_optional(morphism) {
+        (Multiplicity::Many, Multiplicity::AtMostOne)
+    } else {
+        (Multiplicity::Many, Multiplicity::One)
+    }
+}
+
+/// An aggregate, drawn as a boundary around its root and members.
+pub(crate) struct Cluster<'a> {
+    /// Name of the aggregate
+    pub name: &'a str,
+    /// The aggregate's root
+    pub root: ObjectId,
+    /// The other objects in the aggregate
+    pub members: Vec<ObjectId>,
+}
+
+/// Group the objects of a context into the first aggregate enclosing them,
+/// returning the aggregates and the objects outside any of them.
+pub(crate) fn aggregate_clusters(context: &BoundedContext) -> (Vec<Cluster<'_>>, Vec<ObjectId>) {
+    let mut placed = HashSet::new();
+    let clusters: Vec<Cluster<'_>> = context
+        .aggregate_roots()
+        .iter()
+        .filter_map(|&root| {
+            let aggregate = context.get_aggregate(root)?;
+            if !placed.insert(root) {
+                return None;
+            }
+            let members = aggregate
+                .component_objects()
+                .filter(|&member| placed.insert(member))
+                .collect();
+            Some(Cluster {
+                name: &aggregate.name,
+                root,
+                members,
+            })
+        })
+        .collect();
+    let ungrouped = context
+        .graph()
+        .objects()
+        .map(|object| object.id)
+        .filter(|id| !placed.contains(id))
+        .collect();
+    (clusters, ungrouped)
+}
+
 /// Generate visualization from a bounded context.
 pub fn generate(context: &BoundedContext, format: Format) -> Result<String, VizError> {
+    generate_with_theme(context, format, &Theme::default())
+}
+
+/// Generate visualization from a bounded context, styled by a theme.
+pub fn generate_with_theme(
+    context: &BoundedContext,
+    format: Format,
+    theme: &Theme,
+) -> Result<String, VizError> {
     match format {
-        Format::Graphviz => graphviz::generate(context),
-        Format::Mermaid => mermaid::generate(context),
+        Format::Graphviz => graphviz::generate_with_theme(context, theme),
+        Format::Mermaid => mermaid::generate_with_theme(context, theme),
+        Format::Html => html::generate_with_theme(context, theme),
+        Format::Excalidraw => excalidraw::generate_with_theme(context, theme),
+        Format::Drawio => drawio::generate_with_theme(context, theme),
+    }
+}
+
+/// Generate a kind of diagram from a bounded context, styled by a theme.
+///
+/// ER and state diagrams are only drawn in Mermaid, and event storming
+/// boards in Mermaid or HTML; none of them are themed.
+pub fn generate_diagram(
+    context: &BoundedContext,
+    format: Format,
+    diagram: Diagram,
+    theme: &Theme,
+) -> Result<String, VizError> {
+    match (diagram, format) {
+        (Diagram::Class, _) => generate_with_theme(context, format, theme),
+        (Diagram::Er, Format::Mermaid) => er::generate(context),
+        (Diagram::State, Format::Mermaid) => lifecycle::generate(context),
+        (Diagram::Board, Format::Mermaid) => event_storming::generate(context),
+        (Diagram::Board, Format::Html) => event_storming::generate_html(context),
+        (Diagram::Er, _) => Err(VizError::UnsupportedFormat(
+            "ER diagrams are only drawn in Mermaid".to_string(),
+        )),
+        (Diagram::State, _) => Err(VizError::UnsupportedFormat(
+            "state diagrams are only drawn in Mermaid".to_string(),
+        )),
+        (Diagram::Board, _) => Err(VizError::UnsupportedFormat(
+            "event storming boards are drawn in Mermaid or HTML".to_string(),
+        )),
+    }
+}
+
+/// Generate a context map diagram of a whole model.
+pub fn generate_context_map(workspace: &Workspace, format: Format) -> Result<String, VizError> {
+    context_map::generate(workspace, format)
+}
+
+/// Generate a context map diagram of a whole model, styled by a theme.
+pub fn generate_context_map_with_theme(
+    workspace: &Workspace,
+    format: Format,
+    theme: &Theme,
+) -> Result<String, VizError> {
+    context_map::generate_with_theme(workspace, format, theme)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use sketchddd_core::{Domain, NamedContextMap, RelationshipPattern, Subdomain};
+    use std::collections::HashSet;
+
+    /// Names of every kind: plain, keywords of DOT and Mermaid, and names
+    /// with spaces, quotes, backslashes, line breaks or non-ASCII letters.
+    fn name() -> impl Strategy<Value = String> {
+        prop_oneof![
+            "[A-Za-z_][A-Za-z0-9_]{0,6}",
+            prop::sample::select(vec![
+                "node",
+                "Edge",
+                "GRAPH",
+                "subgraph",
+                "strict",
+                "end",
+                "class",
+                "style",
+                "namespace",
+                "direction",
+                "flowchart",
+            ])
+            .prop_map(String::from),
+            "\\PC{1,10}",
+            "[a-z \"\\\\\n#;{}\\[\\]<>|:-]{1,10}",
+        ]
+    }
+
+    fn context() -> impl Strategy<Value = BoundedContext> {
+        (
+            name(),
+            prop::collection::vec((name(), any::<bool>()), 1..6),
+            prop::collection::vec(
+                (
+                    name(),
+                    any::<prop::sample::Index>(),
+                    any::<prop::sample::Index>(),
+                ),
+                0..6,
+            ),
+            any::<bool>(),
+        )
+            .prop_map(|(name, objects, morphisms, aggregate)| {
+                let mut context = BoundedContext::new(name);
+                let ids: Vec<_> = objects
+                    .into_iter()
+                    .map(|(name, entity)| {
+                        if entity {
+                            context.add_entity(name)
+                        } else {
+                            context.add_value_object(name)
+                        }
+                    })
+                    .collect();
+                for (name, source, target) in morphisms {
+                    context.sketch_mut().graph.add_morphism(
+                        name,
+                        *source.get(&ids),
+                        *target.get(&ids),
+                    );
+                }
+                if aggregate {
+                    let name = context.graph().get_object(ids[0]).unwrap().name.clone();
+                    context.define_aggregate_with_members(&name, ids[0], &ids[1..]);
+                }
+                context
+            })
+    }
+
+    fn workspace() -> impl Strategy<Value = Workspace> {
+        (
+            prop::collection::vec(name(), 1..5),
+            prop::collection::vec(
+                (any::<prop::sample::Index>(), any::<prop::sample::Index>()),
+                0..4,
+            ),
+            name(),
+            name(),
+        )
+            .prop_map(|(names, maps, domain, subdomain)| {
+                let mut workspace = Workspace::new();
+                for name in &names {
+                    workspace.add_context(BoundedContext::new(name.clone()));
+                }
+                for (i, (source, target)) in maps.into_iter().enumerate() {
+                    let pattern = [
+                        RelationshipPattern::CustomerSupplier,
+                        RelationshipPattern::SharedKernel,
+                        RelationshipPattern::SeparateWays,
+                    ][i % 3];
+                    workspace.add_context_map(NamedContextMap::new(
+                        format!("Map{}", i),
+                        source.get(&names).clone(),
+                        target.get(&names).clone(),
+                        pattern,
+                    ));
+                }
+                let mut subdomain = Subdomain::new(subdomain);
+                subdomain.add_context(names[0].clone());
+                let mut domain = Domain::new(domain);
+                domain.add_subdomain(subdomain);
+                workspace.domains.push(domain);
+                workspace
+            })
+    }
+
+    /// A token of DOT.
+    #[derive(Debug, PartialEq)]
+    enum Token {
+        Word(String),
+        Quoted,
+        Arrow,
+        Symbol(char),
+    }
+
+    /// Check that text is a DOT graph of statements, attributes, edges and
+    /// subgraphs, as Graphviz reads them.
+    fn check_dot(dot: &str) -> Result<(), String> {
+        let mut tokens = Vec::new();
+        let mut chars = dot.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                c if c.is_whitespace() => {}
+                '"' => {
+                    loop {
+                        match chars.next() {
+                            Some('\\') => {
+                                chars.next();
+                            }
+                            Some('"') => break,
+                            Some('\n') | None => return Err("unterminated string".into()),
+                            Some(_) => {}
+                        }
+                    }
+                    tokens.push(Token::Quoted);
+                }
+                '-' if chars.peek() == Some(&'>') => {
+                    chars.next();
+                    tokens.push(Token::Arrow);
+                }
+                '{' | '}' | '[' | ']' | ';' | ',' | '=' => tokens.push(Token::Symbol(c)),
+                c if c.is_ascii_alphanumeric() || c == '_' => {
+                    let mut word = c.to_string();
+                    while let Some(&c) = chars
+                        .peek()
+                        .filter(|c| c.is_ascii_alphanumeric() || **c == '_' || **c == '.')
+                    {
+                        word.push(c);
+                        chars.next();
+                    }
+                    if word.starts_with(|c: char| c.is_ascii_digit())
+                        && word.parse::<f64>().is_err()
+                    {
+                        return Err(format!("invalid number {}", word));
+                    }
+                    tokens.push(Token::Word(word));
+                }
+                c => return Err(format!("unexpected {:?}", c)),
+            }
+        }
+
+        let keyword = |token: Option<&Token>, keyword: &str| matches!(token, Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword));
+        let is_id = |token: Option<&Token>| match token {
+            Some(Token::Quoted) => true,
+            Some(Token::Word(word)) => !["node", "edge", "graph", "digraph", "subgraph", "strict"]
+                .iter()
+                .any(|k| word.eq_ignore_ascii_case(k)),
+            _ => false,
+        };
+        let mut at = 0;
+        let expect = |at: &mut usize, symbol: char| {
+            if tokens.get(*at) == Some(&Token::Symbol(symbol)) {
+                *at += 1;
+                Ok(())
+            } else {
+                Err(format!("expected {:?} at token {}", symbol, at))
+            }
+        };
+        let attributes = |at: &mut usize| -> Result<(), String> {
+            expect(at, '[')?;
+            while tokens.get(*at) != Some(&Token::Symbol(']')) {
+                if !is_id(tokens.get(*at)) {
+                    return Err(format!("expected an attribute at token {}", at));
+                }
+                *at += 1;
+                expect(at, '=')?;
+                if !is_id(tokens.get(*at)) {
+                    return Err(format!("expected a value at token {}", at));
+                }
+                *at += 1;
+                if matches!(tokens.get(*at), Some(Token::Symbol(',' | ';'))) {
+                    *at += 1;
+                }
+            }
+            expect(at, ']')
+        };
+
+        if !keyword(tokens.first(), "digraph") {
+            return Err("expected digraph".into());
+        }
+        at += 1;
+        if is_id(tokens.get(at)) {
+            at += 1;
+        }
+        expect(&mut at, '{')?;
+        let mut depth = 1;
+        while depth > 0 {
+            let token = tokens.get(at);
+            if token == Some(&Token::Symbol('}')) {
+                depth -= 1;
+                at += 1;
+            } else if token == Some(&Token::Symbol(';')) {
+                at += 1;
+            } else if ["node", "edge", "graph"].iter().any(|k| keyword(token, k)) {
+                at += 1;
+                attributes(&mut at)?;
+            } else if keyword(token, "subgraph") {
+                at += 1;
+                if is_id(tokens.get(at)) {
+                    at += 1;
+                }
+                expect(&mut at, '{')?;
+                depth += 1;
+            } else if is_id(token) {
+                at += 1;
+                if tokens.get(at) == Some(&Token::Symbol('=')) {
+                    at += 1;
+                    if !is_id(tokens.get(at)) {
+                        return Err(format!("expected a value at token {}", at));
+                    }
+                    at += 1;
+                    continue;
+                }
+                while tokens.get(at) == Some(&Token::Arrow) {
+                    at += 1;
+                    if !is_id(tokens.get(at)) {
+                        return Err(format!("expected a node at token {}", at));
+                    }
+                    at += 1;
+                }
+                if tokens.get(at) == Some(&Token::Symbol('[')) {
+                    attributes(&mut at)?;
+                }
+            } else {
+                return Err(format!("unexpected {:?} at token {}", token, at));
+            }
+        }
+        match tokens.get(at) {
+            None => Ok(()),
+            Some(token) => Err(format!("unexpected {:?} after the graph", token)),
+        }
+    }
+
+    /// Check that text is a Mermaid identifier.
+    fn check_id(id: &str) -> Result<(), String> {
+        let mut chars = id.chars();
+        let plain = chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+        let reserved = [
+            "end",
+            "class",
+            "style",
+            "namespace",
+            "direction",
+            "subgraph",
+            "flowchart",
+        ];
+        if plain && !reserved.contains(&id) {
+            Ok(())
+        } else {
+            Err(format!("invalid identifier {:?}", id))
+        }
+    }
+
+    /// Check a Mermaid node declaration, `id` or `id["label"]`, returning
+    /// its identifier.
+    fn check_node(node: &str) -> Result<&str, String> {
+        match node.split_once("[\"") {
+            Some((id, label)) => {
+                let label = label
+                    .strip_suffix("\"]")
+                    .ok_or_else(|| format!("unterminated label in {:?}", node))?;
+                if label.contains('"') {
+                    return Err(format!("quote in label {:?}", label));
+                }
+                check_id(id).map(|()| id)
+            }
+            None => check_id(node).map(|()| node),
+        }
+    }
+
+    /// Check that text is a Mermaid class diagram whose relationships and
+    /// styles refer to declared classes.
+    fn check_class_diagram(mermaid: &str) -> Result<(), String> {
+        let lines: Vec<&str> = mermaid.lines().collect();
+        if lines.first() != Some(&"```mermaid") || lines.last() != Some(&"```") {
+            return Err("expected a mermaid block".into());
+        }
+        let mut classes = HashSet::new();
+        for line in &lines[1..lines.len() - 1] {
+            let line = line.trim();
+            if line.is_empty()
+                || line.starts_with("%%")
+                || line == "classDiagram"
+                || line == "}"
+                || line.starts_with("direction ")
+                || (line.starts_with("<<") && line.ends_with(">>"))
+            {
+                continue;
+            }
+            if let Some(namespace) = line.strip_prefix("namespace ") {
+                check_id(namespace.strip_suffix(" {").ok_or("expected {")?)?;
+            } else if let Some(class) = line.strip_prefix("class ") {
+                classes.insert(check_node(class.strip_suffix(" {").unwrap_or(class))?);
+            } else if let Some(style) = line.strip_prefix("style ") {
+                let (id, _) = style.split_once(' ').ok_or("expected a style")?;
+                if !classes.contains(id) {
+                    return Err(format!("style of undeclared {:?}", id));
+                }
+            } else {
+                let relationship = line
+                    .split_once(" : ")
+                    .map_or(line, |(relationship, _)| relationship);
+                let parts: Vec<&str> = relationship.split(' ').collect();
+                match parts[..] {
+                    [source, "-->" | "..>", target]
+                        if classes.contains(source) && classes.contains(target) => {}
+                    _ => return Err(format!("invalid relationship {:?}", line)),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that text is a Mermaid flowchart whose links and styles refer
+    /// to declared nodes.
+    fn check_flowchart(mermaid: &str) -> Result<(), String> {
+        let lines: Vec<&str> = mermaid.lines().collect();
+        if lines.first() != Some(&"```mermaid") || lines.last() != Some(&"```") {
+            return Err("expected a mermaid block".into());
+        }
+        let mut nodes = HashSet::new();
+        let mut depth = 0;
+        for line in &lines[1..lines.len() - 1] {
+            let line = line.trim();
+            if line.starts_with("flowchart ") || line.starts_with("%%") {
+                continue;
+            }
+            if let Some(subgraph) = line.strip_prefix("subgraph ") {
+                check_node(subgraph)?;
+                depth += 1;
+            } else if line == "end" {
+                depth -= 1;
+            } else if let Some(style) = line.strip_prefix("style ") {
+                let (id, _) = style.split_once(' ').ok_or("expected a style")?;
+                if !nodes.contains(id) {
+                    return Err(format!("style of undeclared {:?}", id));
+                }
+            } else if let Ok(node) = check_node(line) {
+                nodes.insert(node);
+            } else {
+                let parts: Vec<&str> = line.splitn(3, '|').collect();
+                let [link, label, target] = parts[..] else {
+                    return Err(format!("invalid link {:?}", line));
+                };
+                let (source, _) = link.split_once(' ').ok_or("expected a link")?;
+                let label = label
+                    .strip_prefix('"')
+                    .and_then(|l| l.strip_suffix('"'))
+                    .unwrap_or(label);
+                if !nodes.contains(source) || !nodes.contains(target.trim()) || label.contains('"')
+                {
+                    return Err(format!("invalid link {:?}", line));
+                }
+            }
+        }
+        if depth == 0 {
+            Ok(())
+        } else {
+            Err("unbalanced subgraphs".into())
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_class_diagrams_are_valid(context in context()) {
+            let dot = generate(&context, Format::Graphviz).unwrap();
+            prop_assert!(check_dot(&dot).is_ok(), "{:?} in\n{}", check_dot(&dot), dot);
+            let mermaid = generate(&context, Format::Mermaid).unwrap();
+            let checked = check_class_diagram(&mermaid);
+            prop_assert!(checked.is_ok(), "{:?} in\n{}", checked, mermaid);
+        }
+
+        #[test]
+        fn test_context_maps_are_valid(workspace in workspace()) {
+            let dot = generate_context_map(&workspace, Format::Graphviz).unwrap();
+            prop_assert!(check_dot(&dot).is_ok(), "{:?} in\n{}", check_dot(&dot), dot);
+            let mermaid = generate_context_map(&workspace, Format::Mermaid).unwrap();
+            let checked = check_flowchart(&mermaid);
+            prop_assert!(checked.is_ok(), "{:?} in\n{}", checked, mermaid);
+        }
     }
 }
@@ -0,0 +1,91 @@
+//! Lifecycle state diagram generation.
+//!
+//! Each lifecycle is rendered as a Mermaid `stateDiagram-v2`, with an entry
+//! arrow into the initial state and exit arrows from terminal states.
+
+use sketchddd_core::{BoundedContext, Lifecycle};
+use crate::VizError;
+
+/// Generate one Mermaid state diagram per lifecycle in the bounded context.
+///
+/// Returns an empty string when the context declares no lifecycles.
+pub fn generate(context: &BoundedContext) -> Result<String, VizError> {
+    let mut output = String::new();
+
+    for (i, lifecycle) in context.lifecycles().iter().enumerate() {
+        if i > 0 {
+            output.push('\n');
+        }
+        output.push_str(&state_diagram(context, lifecycle));
+    }
+
+    Ok(output)
+}
+
+/// Render a single lifecycle as a state diagram.
+fn state_diagram(context: &BoundedContext, lifecycle: &Lifecycle) -> String {
+    let mut output = String::new();
+
+    let enum_name = context
+        .graph()
+        .get_object(lifecycle.subject)
+        .map(|o| o.name.as_str())
+        .unwrap_or("?");
+
+    output.push_str("```mermaid\n");
+    output.push_str("stateDiagram-v2\n");
+    output.push_str(&format!(
+        "    %% {} / {} ({})\n",
+        context.name(),
+        lifecycle.name,
+        enum_name
+    ));
+
+    if let Some(initial) = lifecycle.initial_state() {
+        output.push_str(&format!("    [*] --> {}\n", initial));
+    }
+
+    for transition in &lifecycle.transitions {
+        output.push_str(&format!("    {} --> {}\n", transition.from, transition.to));
+    }
+
+    for terminal in lifecycle.terminal_states() {
+        output.push_str(&format!("    {} --> [*]\n", terminal));
+    }
+
+    output.push_str("```\n");
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_lifecycle_state_diagram() {
+        let mut ctx = BoundedContext::new("Commerce");
+        let status = ctx.add_enum(
+            "OrderStatus",
+            vec!["Pending".into(), "Confirmed".into(), "Shipped".into(), "Cancelled".into()],
+        );
+        let lifecycle = ctx.define_lifecycle("Order", status);
+        lifecycle.add_transition("Pending", "Confirmed");
+        lifecycle.add_transition("Confirmed", "Shipped");
+        lifecycle.add_transition("Pending", "Cancelled");
+
+        let result = generate(&ctx).unwrap();
+        assert!(result.contains("stateDiagram-v2"));
+        assert!(result.contains("%% Commerce / Order (OrderStatus)"));
+        assert!(result.contains("[*] --> Pending"));
+        assert!(result.contains("Confirmed --> Shipped"));
+        assert!(result.contains("Shipped --> [*]"));
+        assert!(result.contains("Cancelled --> [*]"));
+    }
+
+    #[test]
+    fn test_generate_without_lifecycles() {
+        let ctx = BoundedContext::new("Empty");
+        assert!(generate(&ctx).unwrap().is_empty());
+    }
+}
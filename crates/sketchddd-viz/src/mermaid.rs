@@ -1,36 +1,72 @@
 //! Mermaid diagram format generation.
+//!
+//! Mermaid identifiers are plain names, so each object is identified by its
+//! name where that is a plain name, and otherwise by a name made from it,
+//! such as `Order_Line` for `Order Line`, with the object's name as its
+//! label.
 
+use crate::theme::{LineStyle, Theme};
+use crate::{aggregate_clusters, fill_color, morphism_style, multiplicities, VizError};
+use sketchddd_core::sketch::ObjectId;
 use sketchddd_core::BoundedContext;
-use crate::VizError;
+use std::collections::{HashMap, HashSet};
 
 /// Generate Mermaid diagram from a bounded context.
 pub fn generate(context: &BoundedContext) -> Result<String, VizError> {
+    generate_with_theme(context, &Theme::default())
+}
+
+/// Generate Mermaid diagram from a bounded context, styled by a theme.
+pub fn generate_with_theme(context: &BoundedContext, theme: &Theme) -> Result<String, VizError> {
     let mut output = String::new();
 
     output.push_str("```mermaid\n");
+    output.push_str(&font_directive(theme));
     output.push_str("classDiagram\n");
-    output.push_str(&format!("    %% {}\n\n", context.name()));
+    if let Some(direction) = theme.direction {
+        output.push_str(&format!("    direction {}\n", direction.as_str()));
+    }
+    output.push_str(&format!("    %% {}\n\n", line(context.name())));
 
-    // Add objects as classes
-    for object in context.graph().objects() {
-        let stereotype = if context.is_entity(object.id) {
-            "<<Entity>>"
-        } else if context.is_value_object(object.id) {
-            "<<ValueObject>>"
-        } else {
-            ""
-        };
+    let ids = Ids::new(context.graph().objects().map(|o| o.name.as_str()));
 
-        if !stereotype.is_empty() {
-            output.push_str(&format!("    class {} {{\n", object.name));
-            output.push_str(&format!("        {}\n", stereotype));
-            output.push_str("    }\n");
-        } else {
-            output.push_str(&format!("    class {}\n", object.name));
+    // Add aggregates as namespaces around their objects
+    let (clusters, ungrouped) = aggregate_clusters(context);
+    for cluster in &clusters {
+        output.push_str(&format!(
+            "    namespace {} {{\n",
+            plain(&namespace(cluster.name))
+        ));
+        for &id in std::iter::once(&cluster.root).chain(&cluster.members) {
+            class(&mut output, context, &ids, id, "        ");
         }
+        output.push_str("    }\n");
     }
 
-    output.push_str("\n");
+    // Add the other objects as classes
+    for id in ungrouped {
+        class(&mut output, context, &ids, id, "    ");
+    }
+
+    // Fill classified objects and outline aggregate roots
+    for object in context.graph().objects() {
+        let mut style = Vec::new();
+        if let Some(color) = fill_color(context, object.id, theme) {
+            style.push(format!("fill:{}", color));
+        }
+        if context.is_aggregate_root(object.id) {
+            style.push("stroke-width:3px".to_string());
+        }
+        if !style.is_empty() {
+            output.push_str(&format!(
+                "    style {} {}\n",
+                ids.get(&object.name),
+                style.join(",")
+            ));
+        }
+    }
+
+    output.push('\n');
 
     // Add morphisms as relationships
     for morphism in context.graph().morphisms() {
@@ -38,10 +74,28 @@ pub fn generate(context: &BoundedContext) -> Result<String, VizError> {
             context.graph().get_object(morphism.source),
             context.graph().get_object(morphism.target),
         ) {
+            // Class diagrams only have solid and dashed lines
+            let arrow = match morphism_style(context, morphism.id, theme) {
+                Some(LineStyle::Dashed | LineStyle::Dotted) => "..>",
+                _ => "-->",
+            };
+            let arrow = if theme.multiplicities {
+                let (tail, head) = multiplicities(context, morphism.id);
+                format!("\"{}\" {} \"{}\"", tail.label(), arrow, head.label())
+            } else {
+                arrow.to_string()
+            };
             output.push_str(&format!(
-                "    {} --> {} : {}\n",
-                source.name, target.name, morphism.name
+                "    {} {} {}",
+                ids.get(&source.name),
+                arrow,
+                ids.get(&target.name)
             ));
+            let name = line(&morphism.name);
+            if !name.is_empty() {
+                output.push_str(&format!(" : {}", name));
+            }
+            output.push('\n');
         }
     }
 
@@ -50,9 +104,150 @@ pub fn generate(context: &BoundedContext) -> Result<String, VizError> {
     Ok(output)
 }
 
+/// Set the font of a theme, in an init directive preceding the diagram.
+pub(crate) fn font_directive(theme: &Theme) -> String {
+    match &theme.font {
+        Some(font) => format!(
+            "%%{{init: {{'themeVariables': {{'fontFamily': '{}'}}}}}}%%\n",
+            font
+        ),
+        None => String::new(),
+    }
+}
+
+/// Name the namespace of an aggregate apart from its root's class.
+fn namespace(aggregate: &str) -> String {
+    if aggregate.ends_with("Aggregate") {
+        aggregate.to_string()
+    } else {
+        format!("{}Aggregate", aggregate)
+    }
+}
+
+/// Declare the class of an object, with its stereotype.
+fn class(output: &mut String, context: &BoundedContext, ids: &Ids, id: ObjectId, indent: &str) {
+    let Some(object) = context.graph().get_object(id) else {
+        return;
+    };
+    let name = ids.declare(&object.name);
+    let stereotype = if context.is_aggregate_root(id) {
+        "<<AggregateRoot>>"
+    } else if context.is_entity(id) {
+        "<<Entity>>"
+    } else if context.is_value_object(id) {
+        "<<ValueObject>>"
+    } else {
+        ""
+    };
+
+    if !stereotype.is_empty() {
+        output.push_str(&format!("{}class {} {{\n", indent, name));
+        output.push_str(&format!("{}    {}\n", indent, stereotype));
+        output.push_str(&format!("{}}}\n", indent));
+    } else {
+        output.push_str(&format!("{}class {}\n", indent, name));
+    }
+}
+
+/// Identifiers of the nodes of a Mermaid diagram, one per name.
+pub(crate) struct Ids {
+    ids: HashMap<String, String>,
+}
+
+impl Ids {
+    /// Identify each of some names: plain names by themselves, and other
+    /// names by a plain name made from them that no other name has.
+    pub fn new<'a>(names: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut names: Vec<&str> = names.into_iter().collect();
+        names.sort_unstable();
+        names.dedup();
+
+        let mut ids = HashMap::new();
+        let mut taken = HashSet::new();
+        for &name in names.iter().filter(|&&name| plain(name) == name) {
+            ids.insert(name.to_string(), name.to_string());
+            taken.insert(name.to_string());
+        }
+        for &name in names.iter().filter(|&&name| plain(name) != name) {
+            let base = plain(name);
+            let mut id = base.clone();
+            let mut n = 1;
+            while !taken.insert(id.clone()) {
+                n += 1;
+                id = format!("{}_{}", base, n);
+            }
+            ids.insert(name.to_string(), id);
+        }
+        Ids { ids }
+    }
+
+    /// Get the identifier of a name.
+    pub fn get(&self, name: &str) -> String {
+        self.ids.get(name).cloned().unwrap_or_else(|| plain(name))
+    }
+
+    /// Declare the node of a name: its identifier, followed by the name as
+    /// its label where they differ.
+    pub fn declare(&self, name: &str) -> String {
+        let id = self.get(name);
+        if id == name {
+            id
+        } else {
+            format!("{}[\"{}\"]", id, label(name))
+        }
+    }
+}
+
+/// Make a plain name from a name: letters, digits and underscores, not
+/// starting with a digit nor being a word Mermaid reserves.
+pub(crate) fn plain(name: &str) -> String {
+    const RESERVED: [&str; 12] = [
+        "end",
+        "graph",
+        "flowchart",
+        "subgraph",
+        "class",
+        "classDef",
+        "classDiagram",
+        "click",
+        "direction",
+        "namespace",
+        "style",
+        "linkStyle",
+    ];
+    let mut plain: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if plain.is_empty() || plain.starts_with(|c: char| c.is_ascii_digit()) {
+        plain.insert(0, '_');
+    }
+    if RESERVED.contains(&plain.as_str()) {
+        plain.push('_');
+    }
+    plain
+}
+
+/// Write text as the label of a node, inside double quotes.
+pub(crate) fn label(text: &str) -> String {
+    line(text).replace('"', "#quot;")
+}
+
+/// Write text on a single line, as comments and relationship labels are,
+/// without surrounding whitespace.
+pub(crate) fn line(text: &str) -> String {
+    text.split(['\r', '\n'])
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::theme::{Direction, EdgeStyles, ElementColors};
+    use sketchddd_core::Collection;
 
     #[test]
     fn test_generate_empty_context() {
@@ -60,4 +255,110 @@ mod tests {
         let result = generate(&context).unwrap();
         assert!(result.contains("classDiagram"));
     }
+
+    #[test]
+    fn test_generate_colors_classified_objects() {
+        let mut context = BoundedContext::new("Billing");
+        context.add_entity("Invoice");
+        context.metadata_mut().classification = Some(sketchddd_core::Classification::Generic);
+
+        let result = generate(&context).unwrap();
+        assert!(result.contains("    style Invoice fill:#d3d3d3\n"));
+    }
+
+    #[test]
+    fn test_generate_aggregate_namespaces() {
+        let mut context = BoundedContext::new("Sales");
+        let order = context.add_entity("Order");
+        let line = context.add_entity("LineItem");
+        context.add_value_object("Money");
+        context.define_aggregate_with_members("Order", order, &[line]);
+
+        let result = generate(&context).unwrap();
+        assert!(result.contains(
+            "    namespace OrderAggregate {\n        class Order {\n            <<AggregateRoot>>\n        }\n        class LineItem {\n            <<Entity>>\n        }\n    }\n"
+        ));
+        assert!(result.contains("\n    class Money {\n        <<ValueObject>>\n    }\n"));
+        assert!(result.contains("    style Order stroke-width:3px\n"));
+    }
+
+    #[test]
+    fn test_generate_with_theme() {
+        let mut context = BoundedContext::new("Sales");
+        let order = context.add_entity("Order");
+        let status = context.add_enum("OrderStatus", vec!["Pending".into()]);
+        context
+            .sketch_mut()
+            .graph
+            .add_morphism("status", order, status);
+
+        let theme = Theme {
+            direction: Some(Direction::LeftToRight),
+            font: Some("Inter".to_string()),
+            colors: ElementColors {
+                enum_type: Some("#e1d5e7".to_string()),
+                ..Default::default()
+            },
+            edges: EdgeStyles {
+                morphism: Some(LineStyle::Dotted),
+                ..Default::default()
+            },
+            multiplicities: false,
+        };
+
+        let result = generate_with_theme(&context, &theme).unwrap();
+        assert!(result.starts_with(
+            "```mermaid\n%%{init: {'themeVariables': {'fontFamily': 'Inter'}}}%%\nclassDiagram\n    direction LR\n"
+        ));
+        assert!(result.contains("    style OrderStatus fill:#e1d5e7\n"));
+        assert!(result.contains("    Order ..> OrderStatus : status\n"));
+    }
+
+    #[test]
+    fn test_generate_labels_names() {
+        let mut context = BoundedContext::new("Sales");
+        let spaced = context.add_entity("Order Line");
+        let plain = context.add_entity("Order_Line");
+        let end = context.add_value_object("end");
+        let quoted = context.add_value_object("Note \"draft\"");
+        let graph = &mut context.sketch_mut().graph;
+        graph.add_morphism("sibling", spaced, plain);
+        graph.add_morphism("last\nstep", spaced, end);
+        graph.add_morphism("note", plain, quoted);
+
+        let result = generate(&context).unwrap();
+        assert!(result.contains("    class Order_Line {\n"));
+        assert!(result.contains("    class Order_Line_2[\"Order Line\"] {\n"));
+        assert!(result.contains("    class end_[\"end\"] {\n"));
+        assert!(result.contains("    class Note__draft_[\"Note #quot;draft#quot;\"] {\n"));
+        assert!(result.contains("    Order_Line_2 --> Order_Line : sibling\n"));
+        assert!(result.contains("    Order_Line_2 --> end_ : last step\n"));
+        assert!(result.contains("    Order_Line --> Note__draft_ : note\n"));
+    }
+
+    #[test]
+    fn test_generate_multiplicities() {
+        let mut context = BoundedContext::new("Sales");
+        let order = context.add_entity("Order");
+        let customer = context.add_entity("Customer");
+        let graph = &mut context.sketch_mut().graph;
+        graph.add_morphism("placedBy", order, customer);
+        let referrer = graph.add_morphism("referredBy", customer, customer);
+        context.mark_optional(referrer);
+        let line = context.add_entity("LineItem");
+        let items = context
+            .sketch_mut()
+            .graph
+            .add_morphism("items", order, line);
+        context.mark_many(items, Collection::List);
+
+        let theme = Theme {
+            multiplicities: true,
+            ..Default::default()
+        };
+        let result = generate_with_theme(&context, &theme).unwrap();
+        assert!(result.contains("    Order \"*\" --> \"1\" Customer : placedBy\n"));
+        assert!(result.contains("    Customer \"*\" --> \"0..1\" Customer : referredBy\n"));
+        assert!(result.contains("    Order \"1\" --> \"*\" LineItem : items\n"));
+    }
 }
@@ -0,0 +1,165 @@
+//! Process manager (saga) diagram generation.
+//!
+//! Processes are rendered with Mermaid, either as a sequence diagram showing
+//! the process reacting to events and dispatching commands, or as a state
+//! diagram showing the flow from each event to its command.
+
+use sketchddd_core::{BoundedContext, ProcessManager};
+use crate::VizError;
+
+/// How to render a process manager.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessView {
+    /// Mermaid `sequenceDiagram`
+    Sequence,
+    /// Mermaid `stateDiagram-v2`
+    State,
+}
+
+impl std::str::FromStr for ProcessView {
+    type Err = VizError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "sequence" | "seq" => Ok(ProcessView::Sequence),
+            "state" => Ok(ProcessView::State),
+            _ => Err(VizError::UnsupportedFormat(s.to_string())),
+        }
+    }
+}
+
+/// Generate one Mermaid diagram per process in the bounded context.
+///
+/// Returns an empty string when the context declares no processes.
+pub fn generate(context: &BoundedContext, view: ProcessView) -> Result<String, VizError> {
+    let mut output = String::new();
+
+    for (i, process) in context.processes().iter().enumerate() {
+        if i > 0 {
+            output.push('\n');
+        }
+        let diagram = match view {
+            ProcessView::Sequence => sequence_diagram(context, process)?,
+            ProcessView::State => state_diagram(context, process)?,
+        };
+        output.push_str(&diagram);
+    }
+
+    Ok(output)
+}
+
+/// Render a process as a sequence diagram.
+fn sequence_diagram(context: &BoundedContext, process: &ProcessManager) -> Result<String, VizError> {
+    let mut output = String::new();
+
+    output.push_str("```mermaid\n");
+    output.push_str("sequenceDiagram\n");
+    output.push_str(&format!("    %% {} / {}\n", context.name(), process.name));
+
+    output.push_str(&format!("    participant {}\n", process.name));
+    for id in process.objects() {
+        output.push_str(&format!("    participant {}\n", object_name(context, process, id)?));
+    }
+
+    output.push('\n');
+
+    for step in &process.steps {
+        let event = object_name(context, process, step.event)?;
+        let command = object_name(context, process, step.command)?;
+        output.push_str(&format!("    {}-){} : on {}\n", event, process.name, event));
+        output.push_str(&format!(
+            "    {}->>{} : dispatch {}\n",
+            process.name, command, command
+        ));
+    }
+
+    output.push_str("```\n");
+
+    Ok(output)
+}
+
+/// Render a process as a state diagram.
+fn state_diagram(context: &BoundedContext, process: &ProcessManager) -> Result<String, VizError> {
+    let mut output = String::new();
+
+    output.push_str("```mermaid\n");
+    output.push_str("stateDiagram-v2\n");
+    output.push_str(&format!("    %% {} / {}\n", context.name(), process.name));
+
+    if let Some(first) = process.steps.first() {
+        output.push_str(&format!(
+            "    [*] --> {}\n",
+            object_name(context, process, first.event)?
+        ));
+    }
+
+    for step in &process.steps {
+        output.push_str(&format!(
+            "    {} --> {}\n",
+            object_name(context, process, step.event)?,
+            object_name(context, process, step.command)?
+        ));
+    }
+
+    output.push_str("```\n");
+
+    Ok(output)
+}
+
+/// Look up the name of an object referenced by a process.
+fn object_name<'a>(
+    context: &'a BoundedContext,
+    process: &ProcessManager,
+    id: sketchddd_core::sketch::ObjectId,
+) -> Result<&'a str, VizError> {
+    context
+        .graph()
+        .get_object(id)
+        .map(|o| o.name.as_str())
+        .ok_or_else(|| {
+            VizError::InvalidModel(format!(
+                "Process '{}' references unknown object {:?}",
+                process.name, id
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fulfillment_context() -> BoundedContext {
+        let mut ctx = BoundedContext::new("Fulfillment");
+        let placed = ctx.sketch_mut().add_object("OrderPlaced");
+        let reserve = ctx.sketch_mut().add_object("ReserveStock");
+        let reserved = ctx.sketch_mut().add_object("StockReserved");
+        let ship = ctx.sketch_mut().add_object("Ship");
+        ctx.define_process("FulfillmentSaga", &[(placed, reserve), (reserved, ship)]);
+        ctx
+    }
+
+    #[test]
+    fn test_generate_sequence_diagram() {
+        let ctx = fulfillment_context();
+        let result = generate(&ctx, ProcessView::Sequence).unwrap();
+        assert!(result.contains("sequenceDiagram"));
+        assert!(result.contains("participant FulfillmentSaga"));
+        assert!(result.contains("OrderPlaced-)FulfillmentSaga : on OrderPlaced"));
+        assert!(result.contains("FulfillmentSaga->>Ship : dispatch Ship"));
+    }
+
+    #[test]
+    fn test_generate_state_diagram() {
+        let ctx = fulfillment_context();
+        let result = generate(&ctx, ProcessView::State).unwrap();
+        assert!(result.contains("stateDiagram-v2"));
+        assert!(result.contains("[*] --> OrderPlaced"));
+        assert!(result.contains("StockReserved --> Ship"));
+    }
+
+    #[test]
+    fn test_generate_without_processes() {
+        let ctx = BoundedContext::new("Empty");
+        assert!(generate(&ctx, ProcessView::Sequence).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,158 @@
+//! Themes styling diagrams.
+//!
+//! A theme sets the fill colour of each kind of object, the line style of
+//! each kind of edge, the font of labels and the direction diagrams are
+//! laid out in. What it leaves unset keeps the default look, and colours
+//! given by classification win over colours by kind. Projects set their
+//! theme in the `[viz]` section of `sketchddd.toml`:
+//!
+//! ```toml
+//! [viz]
+//! direction = "TB"
+//! font = "Helvetica"
+//!
+//! [viz.colors]
+//! entity = "#dae8fc"
+//! value-object = "#d5e8d4"
+//! aggregate-root = "#ffe6cc"
+//! enum = "#e1d5e7"
+//!
+//! [viz.edges]
+//! containment = "bold"
+//! upstream = "dashed"
+//! ```
+//!
+//! Mermaid class diagrams only have solid and dashed lines, so they draw
+//! bold lines solid and dotted lines dashed. With `multiplicities = true`,
+//! class diagrams write UML multiplicities on the ends of morphisms.
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
+/// The styling of diagrams.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Theme {
+    /// Direction diagrams are laid out in, else each diagram's own
+    pub direction: Option<Direction>,
+
+    /// Font of labels
+    pub font: Option<String>,
+
+    /// Fill colours by kind of object
+    pub colors: ElementColors,
+
+    /// Line styles by kind of edge
+    pub edges: EdgeStyles,
+
+    /// Whether class diagrams write multiplicities on the ends of morphisms
+    pub multiplicities: bool,
+}
+
+/// The direction a diagram is laid out in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(Tsify))]
+pub enum Direction {
+    /// From left to right
+    #[serde(rename = "LR")]
+    LeftToRight,
+
+    /// From top to bottom
+    #[serde(rename = "TB")]
+    TopToBottom,
+}
+
+impl Direction {
+    /// Get the direction as Graphviz and Mermaid write it.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Direction::LeftToRight => "LR",
+            Direction::TopToBottom => "TB",
+        }
+    }
+}
+
+/// Fill colours by kind of object, as CSS colours such as `#dae8fc`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ElementColors {
+    /// Entities other than aggregate roots
+    pub entity: Option<String>,
+
+    /// Value objects
+    pub value_object: Option<String>,
+
+    /// Roots of aggregates
+    pub aggregate_root: Option<String>,
+
+    /// Enums
+    #[serde(rename = "enum")]
+    pub enum_type: Option<String>,
+}
+
+/// The style of a line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineStyle {
+    Solid,
+    Dashed,
+    Dotted,
+    Bold,
+}
+
+impl LineStyle {
+    /// Get the style as a Graphviz `style` attribute.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineStyle::Solid => "solid",
+            LineStyle::Dashed => "dashed",
+            LineStyle::Dotted => "dotted",
+            LineStyle::Bold => "bold",
+        }
+    }
+}
+
+/// Line styles by kind of edge.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct EdgeStyles {
+    /// Morphisms between objects
+    pub morphism: Option<LineStyle>,
+
+    /// Morphisms from the root of an aggregate to its members
+    pub containment: Option<LineStyle>,
+
+    /// Context maps from an upstream context to a downstream one
+    pub upstream: Option<LineStyle>,
+
+    /// Context maps between partners, or sharing a kernel
+    pub symmetric: Option<LineStyle>,
+
+    /// Context maps between contexts going separate ways
+    pub separate_ways: Option<LineStyle>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme_from_json() {
+        let theme: Theme = serde_json::from_str(
+            r##"{
+                "direction": "TB",
+                "colors": { "aggregate-root": "#ffe6cc", "enum": "#e1d5e7" },
+                "edges": { "separate-ways": "dotted" }
+            }"##,
+        )
+        .unwrap();
+
+        assert_eq!(theme.direction, Some(Direction::TopToBottom));
+        assert_eq!(theme.colors.aggregate_root.as_deref(), Some("#ffe6cc"));
+        assert_eq!(theme.colors.enum_type.as_deref(), Some("#e1d5e7"));
+        assert_eq!(theme.edges.separate_ways, Some(LineStyle::Dotted));
+        assert_eq!(theme.font, None);
+        assert!(serde_json::from_str::<Theme>(r#"{ "colour": {} }"#).is_err());
+    }
+}
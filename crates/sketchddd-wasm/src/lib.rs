@@ -7,7 +7,19 @@
 //! - Transforming AST to semantic models
 //! - Validating bounded contexts and context maps
 //! - Generating code in multiple languages
-//! - Generating visualizations (Mermaid, Graphviz)
+//! - Generating visualizations (Mermaid, Graphviz, HTML, Excalidraw,
+//!   draw.io), optionally themed
+//! - Laying out diagrams for the builder to draw
+//! - Comparing two versions of a model, for reviewing changes
+//! - Completion and hover for the browser editor
+//! - Writing a context edited visually back as source
+//! - Editing a model held in memory, with undo and redo, through a
+//!   [`ModelSession`]
+//! - Validating large models in steps that can be cancelled, through an
+//!   [`Analysis`], so a worker stays responsive
+//!
+//! The result types derive [`Tsify`], so the package's `.d.ts` declares
+//! them, and each function is declared to return its result type.
 //!
 //! ## Usage from JavaScript
 //!
@@ -41,8 +53,18 @@
 //! ```
 
 use serde::{Deserialize, Serialize};
-use sketchddd_core::{BoundedContext, Severity};
-use sketchddd_parser::{parse_file, transform, PrettyPrint};
+use sketchddd_codegen::Target;
+use sketchddd_core::{
+    BoundedContext, EditCommand, EditError, Fingerprint, Fix, Memo, Severity, SeverityPolicy,
+    ValidatedSession, ValidationCache, ValidationError, ValidationResult, Workspace,
+};
+use sketchddd_parser::assist;
+use sketchddd_parser::{
+    attach_fixes, parse_file, transform, unparse, File, ParseError, PrettyPrint, TransformWarning,
+};
+use sketchddd_viz::layout::LayoutedDiagram;
+use sketchddd_viz::theme::Theme;
+use tsify::Tsify;
 use wasm_bindgen::prelude::*;
 
 /// Initialize the WASM module.
@@ -58,15 +80,19 @@ pub fn init() {
 // =============================================================
 
 /// Result type for parsing operations
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Tsify)]
 pub struct ParseResult {
     pub success: bool,
+    #[tsify(optional)]
     pub data: Option<ParsedModel>,
+    #[tsify(optional)]
     pub error: Option<String>,
+    /// The error that stopped parsing, or the warnings of the model
+    pub diagnostics: Vec<JsValidationIssue>,
 }
 
 /// Parsed model data
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Tsify)]
 pub struct ParsedModel {
     pub contexts: Vec<ContextInfo>,
     pub context_maps: Vec<ContextMapInfo>,
@@ -74,7 +100,7 @@ pub struct ParsedModel {
 }
 
 /// Information about a bounded context
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Tsify)]
 pub struct ContextInfo {
     pub name: String,
     pub entities: Vec<EntityInfo>,
@@ -86,21 +112,21 @@ pub struct ContextInfo {
 }
 
 /// Entity information
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Tsify)]
 pub struct EntityInfo {
     pub name: String,
     pub fields: Vec<FieldInfo>,
 }
 
 /// Value object information
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Tsify)]
 pub struct ValueObjectInfo {
     pub name: String,
     pub fields: Vec<FieldInfo>,
 }
 
 /// Field information
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Tsify)]
 pub struct FieldInfo {
     pub name: String,
     pub type_name: String,
@@ -108,29 +134,30 @@ pub struct FieldInfo {
 }
 
 /// Aggregate information
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Tsify)]
 pub struct AggregateInfo {
     pub name: String,
+    #[tsify(optional)]
     pub root: Option<String>,
     pub contains: Vec<String>,
 }
 
 /// Enum information
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Tsify)]
 pub struct EnumInfo {
     pub name: String,
     pub variants: Vec<VariantInfo>,
 }
 
 /// Variant information
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Tsify)]
 pub struct VariantInfo {
     pub name: String,
     pub has_payload: bool,
 }
 
 /// Morphism information
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Tsify)]
 pub struct MorphismInfo {
     pub name: String,
     pub source: String,
@@ -138,32 +165,35 @@ pub struct MorphismInfo {
 }
 
 /// Context map information
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Tsify)]
 pub struct ContextMapInfo {
     pub name: String,
     pub source_context: String,
     pub target_context: String,
+    #[tsify(optional)]
     pub pattern: Option<String>,
     pub mappings: Vec<MappingInfo>,
 }
 
 /// Mapping information
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Tsify)]
 pub struct MappingInfo {
     pub source: String,
     pub target: String,
 }
 
 /// Warning information
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Tsify)]
 pub struct WarningInfo {
     pub message: String,
+    #[tsify(optional)]
     pub line: Option<u32>,
+    #[tsify(optional)]
     pub column: Option<u32>,
 }
 
 /// Validation result for JS
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Tsify)]
 pub struct JsValidationResult {
     pub valid: bool,
     pub error_count: usize,
@@ -171,34 +201,178 @@ pub struct JsValidationResult {
     pub issues: Vec<JsValidationIssue>,
 }
 
-/// Validation issue for JS
-#[derive(Serialize, Deserialize)]
+/// Validation issue for JS, a diagnostic for the editor to underline
+#[derive(Serialize, Deserialize, Tsify)]
 pub struct JsValidationIssue {
     pub severity: String,
     pub code: String,
     pub message: String,
+    #[tsify(optional)]
     pub context: Option<String>,
+    #[tsify(optional)]
     pub line: Option<u32>,
+    #[tsify(optional)]
     pub column: Option<u32>,
+    /// The source the issue is about, when it has a location
+    #[tsify(optional)]
+    pub span: Option<JsSpan>,
+    #[tsify(optional)]
     pub suggestion: Option<String>,
+    /// Edits that repair the issue, for a quick fix
+    #[tsify(optional)]
+    pub fix: Option<JsFix>,
+}
+
+/// A range of source, from 1-indexed lines and columns counted in
+/// characters, to just past its end
+#[derive(Debug, PartialEq, Serialize, Deserialize, Tsify)]
+pub struct JsSpan {
+    pub start_line: u32,
+    pub start_column: u32,
+    pub end_line: u32,
+    pub end_column: u32,
+}
+
+/// A quick fix
+#[derive(Serialize, Deserialize, Tsify)]
+pub struct JsFix {
+    pub title: String,
+    pub edits: Vec<JsTextEdit>,
+}
+
+/// An edit replacing a range of source
+#[derive(Serialize, Deserialize, Tsify)]
+pub struct JsTextEdit {
+    pub span: JsSpan,
+    pub new_text: String,
+}
+
+/// Result of an edit to a model session
+#[derive(Serialize, Deserialize, Tsify)]
+pub struct EditResult {
+    pub success: bool,
+    #[tsify(optional)]
+    pub error: Option<String>,
 }
 
 /// Code generation result
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Tsify)]
 pub struct CodegenResult {
     pub success: bool,
+    #[tsify(optional)]
     pub code: Option<String>,
+    #[tsify(optional)]
     pub error: Option<String>,
 }
 
+/// Generated files result, with the files a context is generated to
+#[derive(Serialize, Tsify)]
+pub struct CodeFilesResult {
+    pub success: bool,
+    #[tsify(optional)]
+    pub files: Option<Vec<GeneratedFile>>,
+    #[tsify(optional)]
+    pub error: Option<String>,
+}
+
+/// A generated source file
+#[derive(Serialize, Tsify)]
+pub struct GeneratedFile {
+    /// Name of the file, such as `sales.rs`
+    pub path: String,
+    pub content: String,
+}
+
 /// Visualization result
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Tsify)]
 pub struct VizResult {
     pub success: bool,
+    #[tsify(optional)]
     pub output: Option<String>,
+    #[tsify(optional)]
     pub error: Option<String>,
 }
 
+/// Diagram layout result, with a laid-out class diagram per context
+#[derive(Serialize, Tsify)]
+pub struct LayoutResult {
+    pub success: bool,
+    #[tsify(optional)]
+    pub diagrams: Option<Vec<LayoutedDiagram>>,
+    #[tsify(optional)]
+    pub error: Option<String>,
+}
+
+/// Model diff result
+#[derive(Serialize, Deserialize, Tsify)]
+pub struct DiffResult {
+    pub success: bool,
+    #[tsify(optional)]
+    pub diff: Option<DiffInfo>,
+    #[tsify(optional)]
+    pub error: Option<String>,
+}
+
+/// The differences between two versions of a model
+#[derive(Serialize, Deserialize, Tsify)]
+pub struct DiffInfo {
+    pub changes: Vec<ChangeInfo>,
+    pub version_warnings: Vec<String>,
+    pub has_breaking_changes: bool,
+    /// The most severe compatibility among the changes, if there are any
+    #[tsify(optional)]
+    pub max_severity: Option<String>,
+}
+
+/// A single difference between two models
+#[derive(Serialize, Deserialize, Tsify)]
+pub struct ChangeInfo {
+    pub context: String,
+    /// `added`, `removed` or `changed`
+    pub kind: String,
+    /// The element changed, such as `entity 'Invoice'`
+    pub element: String,
+    #[tsify(optional)]
+    pub detail: Option<String>,
+    /// `breaking`, `compatible` or `cosmetic`
+    pub compatibility: String,
+}
+
+/// Completion result
+#[derive(Serialize, Deserialize, Tsify)]
+pub struct CompletionResult {
+    pub items: Vec<CompletionItem>,
+    /// The part of the word typed so far, which an item replaces
+    pub span: JsSpan,
+}
+
+/// An item offered for completion
+#[derive(Serialize, Deserialize, Tsify)]
+pub struct CompletionItem {
+    pub label: String,
+    pub kind: String,
+    #[tsify(optional)]
+    pub detail: Option<String>,
+}
+
+/// Hover result
+#[derive(Serialize, Deserialize, Tsify)]
+pub struct HoverResult {
+    /// Markdown describing the word
+    pub contents: String,
+    pub span: JsSpan,
+}
+
+/// Names the result types are documented by, for TypeScript
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TYPE_ALIASES: &str = r#"
+export type ValidationResult = JsValidationResult;
+export type Diagnostic = JsValidationIssue;
+export type Span = JsSpan;
+export type Fix = JsFix;
+export type TextEdit = JsTextEdit;
+"#;
+
 // =============================================================
 // Core Functions
 // =============================================================
@@ -210,35 +384,46 @@ pub fn version() -> String {
 }
 
 /// Parse a SketchDDD source file and return structured data.
-#[wasm_bindgen]
+#[wasm_bindgen(unchecked_return_type = "ParseResult")]
 pub fn parse(source: &str) -> JsValue {
     match parse_and_transform(source) {
-        Ok(model) => {
+        Ok((model, diagnostics)) => {
             let result = ParseResult {
                 success: true,
                 data: Some(model),
                 error: None,
+                diagnostics,
             };
             serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
         }
-        Err(e) => {
+        Err(diagnostic) => {
             let result = ParseResult {
                 success: false,
                 data: None,
-                error: Some(e),
+                error: Some(diagnostic.message.clone()),
+                diagnostics: vec![*diagnostic],
             };
             serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
         }
     }
 }
 
-/// Parse and transform source into a model
-fn parse_and_transform(source: &str) -> Result<ParsedModel, String> {
+/// Parse and transform source into a model, with the diagnostics of its
+/// warnings, or the diagnostic of the error that stopped it
+fn parse_and_transform(
+    source: &str,
+) -> Result<(ParsedModel, Vec<JsValidationIssue>), Box<JsValidationIssue>> {
     // Parse to AST
-    let ast = parse_file(source).map_err(|e| e.to_string())?;
+    let ast = parse_file(source).map_err(|e| Box::new(parse_error(source, "E0100", &e)))?;
 
     // Transform to semantic model
-    let transform_result = transform(&ast).map_err(|e| e.to_string())?;
+    let transform_result =
+        transform(&ast).map_err(|e| Box::new(parse_error(source, "PARSE_ERROR", &e)))?;
+    let diagnostics = transform_result
+        .warnings
+        .iter()
+        .map(|warning| transform_warning(source, warning))
+        .collect();
 
     // Convert to JS-friendly format
     let warnings: Vec<WarningInfo> = transform_result
@@ -282,7 +467,7 @@ fn parse_and_transform(source: &str) -> Result<ParsedModel, String> {
         warnings,
     };
 
-    Ok(model)
+    Ok((model, diagnostics))
 }
 
 /// Convert a BoundedContext to ContextInfo
@@ -382,7 +567,7 @@ fn context_to_info(ctx: &BoundedContext) -> ContextInfo {
 }
 
 /// Validate a parsed model and return validation issues.
-#[wasm_bindgen]
+#[wasm_bindgen(unchecked_return_type = "ValidationResult")]
 pub fn validate(model_json: &str) -> JsValue {
     let result: Result<JsValidationResult, String> = (|| {
         let contexts: Vec<BoundedContext> =
@@ -390,22 +575,11 @@ pub fn validate(model_json: &str) -> JsValue {
 
         let validation = sketchddd_core::validate_model(&contexts, &[]);
 
+        // Without a source, issues have no spans or fixes
         let issues: Vec<JsValidationIssue> = validation
             .issues
             .iter()
-            .map(|issue| JsValidationIssue {
-                severity: match issue.severity {
-                    Severity::Error => "error".to_string(),
-                    Severity::Warning => "warning".to_string(),
-                    Severity::Hint => "hint".to_string(),
-                },
-                code: issue.code.clone(),
-                message: issue.message.clone(),
-                context: issue.location.file.clone(),
-                line: issue.location.line,
-                column: issue.location.column,
-                suggestion: issue.suggestion.clone(),
-            })
+            .map(|issue| validation_issue(None, issue))
             .collect();
 
         Ok(JsValidationResult {
@@ -418,111 +592,239 @@ pub fn validate(model_json: &str) -> JsValue {
 
     match result {
         Ok(r) => serde_wasm_bindgen::to_value(&r).unwrap_or(JsValue::NULL),
-        Err(e) => {
-            let error_result = JsValidationResult {
-                valid: false,
-                error_count: 1,
-                warning_count: 0,
-                issues: vec![JsValidationIssue {
-                    severity: "error".to_string(),
-                    code: "PARSE_ERROR".to_string(),
-                    message: e,
-                    context: None,
-                    line: None,
-                    column: None,
-                    suggestion: None,
-                }],
-            };
-            serde_wasm_bindgen::to_value(&error_result).unwrap_or(JsValue::NULL)
-        }
+        Err(e) => validation_failure(error_issue("PARSE_ERROR", e)),
     }
 }
 
 /// Validate source directly without pre-parsing.
-#[wasm_bindgen]
+#[wasm_bindgen(unchecked_return_type = "ValidationResult")]
 pub fn validate_source(source: &str) -> JsValue {
-    let result: Result<JsValidationResult, String> = (|| {
+    validate_source_with(source, &SeverityPolicy::new())
+}
+
+/// Validate source, changing the severity of issues with a policy.
+///
+/// The policy is JSON such as `{ "deny_warnings": true, "warn": ["E0020"],
+/// "allow": ["W0001"] }`, the same policy `sketchddd check` builds from
+/// `--deny`, `--warn` and `--allow`.
+#[wasm_bindgen(unchecked_return_type = "ValidationResult")]
+pub fn validate_source_with_policy(source: &str, policy_json: &str) -> JsValue {
+    match serde_json::from_str::<SeverityPolicy>(policy_json) {
+        Ok(policy) => validate_source_with(source, &policy),
+        Err(e) => validation_failure(error_issue(
+            "PARSE_ERROR",
+            format!("Invalid policy JSON: {}", e),
+        )),
+    }
+}
+
+/// Validate source with a severity policy.
+fn validate_source_with(source: &str, policy: &SeverityPolicy) -> JsValue {
+    let result: Result<JsValidationResult, Box<JsValidationIssue>> = (|| {
         // Parse
-        let ast = parse_file(source).map_err(|e| e.to_string())?;
+        let ast = parse_file(source).map_err(|e| Box::new(parse_error(source, "E0100", &e)))?;
 
         // Transform
-        let transform_result = transform(&ast).map_err(|e| e.to_string())?;
+        let transform_result =
+            transform(&ast).map_err(|e| Box::new(parse_error(source, "PARSE_ERROR", &e)))?;
 
         // Validate
-        let validation = sketchddd_core::validate_model(
-            &transform_result.contexts,
-            &transform_result.context_maps,
-        );
-
-        let mut issues: Vec<JsValidationIssue> = Vec::new();
-
-        // Add transform warnings
-        for warning in &transform_result.warnings {
-            issues.push(JsValidationIssue {
-                severity: "warning".to_string(),
-                code: "TRANSFORM_WARNING".to_string(),
-                message: warning.message.clone(),
-                context: None,
-                line: warning.line,
-                column: warning.column,
-                suggestion: None,
-            });
-        }
+        let warnings = transform_result.warnings.clone();
+        let validation = transform_result
+            .into_workspace()
+            .validate()
+            .with_policy(policy);
 
-        // Add validation issues
-        for issue in &validation.issues {
-            issues.push(JsValidationIssue {
-                severity: match issue.severity {
-                    Severity::Error => "error".to_string(),
-                    Severity::Warning => "warning".to_string(),
-                    Severity::Hint => "hint".to_string(),
-                },
-                code: issue.code.clone(),
-                message: issue.message.clone(),
-                context: issue.location.file.clone(),
-                line: issue.location.line,
-                column: issue.location.column,
-                suggestion: issue.suggestion.clone(),
-            });
-        }
-
-        let error_count = issues.iter().filter(|i| i.severity == "error").count();
-        let warning_count = issues.iter().filter(|i| i.severity == "warning").count();
-
-        Ok(JsValidationResult {
-            valid: error_count == 0,
-            error_count,
-            warning_count,
-            issues,
-        })
+        Ok(source_validation(source, &ast, &warnings, validation))
     })();
 
     match result {
         Ok(r) => serde_wasm_bindgen::to_value(&r).unwrap_or(JsValue::NULL),
-        Err(e) => {
-            let error_result = JsValidationResult {
-                valid: false,
-                error_count: 1,
-                warning_count: 0,
-                issues: vec![JsValidationIssue {
-                    severity: "error".to_string(),
-                    code: "PARSE_ERROR".to_string(),
-                    message: e,
-                    context: None,
-                    line: None,
-                    column: None,
-                    suggestion: None,
-                }],
-            };
-            serde_wasm_bindgen::to_value(&error_result).unwrap_or(JsValue::NULL)
-        }
+        Err(e) => validation_failure(*e),
+    }
+}
+
+/// The validation result of source, with the warnings of its transform and
+/// fixes for the issues.
+fn source_validation(
+    source: &str,
+    ast: &File,
+    warnings: &[TransformWarning],
+    mut validation: ValidationResult,
+) -> JsValidationResult {
+    attach_fixes(source, ast, &mut validation.issues);
+
+    let mut issues: Vec<JsValidationIssue> = Vec::new();
+
+    // Add transform warnings
+    for warning in warnings {
+        issues.push(transform_warning(source, warning));
+    }
+
+    // Add validation issues
+    for issue in &validation.issues {
+        issues.push(validation_issue(Some(source), issue));
+    }
+
+    let error_count = issues.iter().filter(|i| i.severity == "error").count();
+    let warning_count = issues.iter().filter(|i| i.severity == "warning").count();
+
+    JsValidationResult {
+        valid: error_count == 0,
+        error_count,
+        warning_count,
+        issues,
+    }
+}
+
+/// A validation result holding a single error that stopped validation.
+fn validation_failure(issue: JsValidationIssue) -> JsValue {
+    serde_wasm_bindgen::to_value(&failed_validation(issue)).unwrap_or(JsValue::NULL)
+}
+
+/// The result of a validation stopped by an error.
+fn failed_validation(issue: JsValidationIssue) -> JsValidationResult {
+    JsValidationResult {
+        valid: false,
+        error_count: 1,
+        warning_count: 0,
+        issues: vec![issue],
+    }
+}
+
+// =============================================================
+// Diagnostics
+// =============================================================
+
+/// A diagnostic to throw to JavaScript.
+fn thrown(issue: JsValidationIssue) -> JsValue {
+    serde_wasm_bindgen::to_value(&issue).unwrap_or(JsValue::NULL)
+}
+
+/// An error without a location.
+fn error_issue(code: &str, message: String) -> JsValidationIssue {
+    JsValidationIssue {
+        severity: "error".to_string(),
+        code: code.to_string(),
+        message,
+        context: None,
+        line: None,
+        column: None,
+        span: None,
+        suggestion: None,
+        fix: None,
+    }
+}
+
+/// The diagnostic of an error parsing or transforming a source.
+fn parse_error(source: &str, code: &str, error: &ParseError) -> JsValidationIssue {
+    JsValidationIssue {
+        line: error.line,
+        column: error.column,
+        span: span_at(source, error.line, error.column),
+        ..error_issue(code, error.message.clone())
+    }
+}
+
+/// The diagnostic of a warning transforming a source.
+fn transform_warning(source: &str, warning: &TransformWarning) -> JsValidationIssue {
+    JsValidationIssue {
+        severity: "warning".to_string(),
+        line: warning.line,
+        column: warning.column,
+        span: span_at(source, warning.line, warning.column),
+        fix: warning.fix.as_ref().map(|fix| js_fix(source, fix)),
+        ..error_issue("TRANSFORM_WARNING", warning.message.clone())
+    }
+}
+
+/// The diagnostic of a validation issue, with its span and fix when the
+/// source it was found in is known.
+fn validation_issue(source: Option<&str>, issue: &ValidationError) -> JsValidationIssue {
+    let location = &issue.location;
+    JsValidationIssue {
+        severity: match issue.severity {
+            Severity::Error => "error".to_string(),
+            Severity::Warning => "warning".to_string(),
+            Severity::Hint => "hint".to_string(),
+        },
+        code: issue.code.clone(),
+        message: issue.message.clone(),
+        context: location.file.clone(),
+        line: location.line,
+        column: location.column,
+        span: source.and_then(|source| span_at(source, location.line, location.column)),
+        suggestion: issue.suggestion.clone(),
+        fix: source.and_then(|source| Some(js_fix(source, issue.fix.as_ref()?))),
     }
 }
 
+/// A quick fix, with its edits located in the source.
+fn js_fix(source: &str, fix: &Fix) -> JsFix {
+    JsFix {
+        title: fix.title.clone(),
+        edits: fix
+            .edits
+            .iter()
+            .map(|edit| JsTextEdit {
+                span: span_of(source, edit.start, edit.end),
+                new_text: edit.new_text.clone(),
+            })
+            .collect(),
+    }
+}
+
+/// Get the span of the word at a 1-indexed line and column, or of the
+/// character there if it starts no word.
+fn span_at(source: &str, line: Option<u32>, column: Option<u32>) -> Option<JsSpan> {
+    let (line, column) = (line?, column.unwrap_or(1));
+    let start = source
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1) as usize)
+        .map(str::len)
+        .sum::<usize>();
+    let text = source.get(start..)?.split('\n').next()?;
+    let start = start
+        + text
+            .char_indices()
+            .nth(column.saturating_sub(1) as usize)
+            .map_or(text.len(), |(i, _)| i);
+
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let rest = &source[start..];
+    let end = match rest.chars().next() {
+        Some(c) if is_word(c) => start + rest.find(|c: char| !is_word(c)).unwrap_or(rest.len()),
+        Some(c) if c != '\n' => start + c.len_utf8(),
+        _ => start,
+    };
+    Some(span_of(source, start, end))
+}
+
+/// Get the span between two byte offsets of a source.
+fn span_of(source: &str, start: usize, end: usize) -> JsSpan {
+    let (start_line, start_column) = position(source, start);
+    let (end_line, end_column) = position(source, end);
+    JsSpan {
+        start_line,
+        start_column,
+        end_line,
+        end_column,
+    }
+}
+
+/// Get the 1-indexed line and column, in characters, of a byte offset.
+fn position(source: &str, offset: usize) -> (u32, u32) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let column = before[line_start..].chars().count() + 1;
+    (line as u32, column as u32)
+}
+
 /// Generate code from a SketchDDD source.
 ///
 /// Supported targets: rust, typescript, kotlin, python, java, clojure, haskell
-#[wasm_bindgen]
+#[wasm_bindgen(unchecked_return_type = "CodegenResult")]
 pub fn generate_code(source: &str, target: &str) -> JsValue {
     let result: Result<CodegenResult, String> = (|| {
         // Parse and transform
@@ -570,36 +872,143 @@ pub fn generate_code(source: &str, target: &str) -> JsValue {
     }
 }
 
+/// Generate the source files of a bounded context, as the builder edits it.
+///
+/// The context is JSON as `create_context` returns it. The configuration is
+/// JSON with the options of the target's generator, such as
+/// `{ "use_builder_pattern": true }` for Rust or `{ "use_pydantic": true }`
+/// for Python; options left out, or all of them with `{}`, keep their
+/// defaults.
+#[wasm_bindgen(unchecked_return_type = "CodeFilesResult")]
+pub fn generate_code_files(context_json: &str, target: &str, config_json: &str) -> JsValue {
+    let result: Result<Vec<GeneratedFile>, String> = (|| {
+        let context: BoundedContext = serde_json::from_str(context_json)
+            .map_err(|e| format!("Invalid context JSON: {}", e))?;
+        let target_enum: sketchddd_codegen::Target = target.parse().map_err(|_| {
+            format!(
+                "Unknown target: {}. Supported: rust, typescript, kotlin, python, java, clojure, haskell",
+                target
+            )
+        })?;
+
+        let content = generate_with_config(&context, target_enum, config_json)?;
+        Ok(vec![GeneratedFile {
+            path: format!(
+                "{}.{}",
+                to_snake_case(context.name()),
+                target_enum.extension()
+            ),
+            content,
+        }])
+    })();
+
+    let result = match result {
+        Ok(files) => CodeFilesResult {
+            success: true,
+            files: Some(files),
+            error: None,
+        },
+        Err(e) => CodeFilesResult {
+            success: false,
+            files: None,
+            error: Some(e),
+        },
+    };
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Generate the code of a context with the configuration of its target,
+/// given as JSON.
+fn generate_with_config(
+    context: &BoundedContext,
+    target: sketchddd_codegen::Target,
+    config_json: &str,
+) -> Result<String, String> {
+    use sketchddd_codegen::{clojure, haskell, java, kotlin, python, rust, typescript, Target};
+
+    fn config<T: serde::de::DeserializeOwned>(json: &str) -> Result<T, String> {
+        serde_json::from_str(json).map_err(|e| format!("Invalid config JSON: {}", e))
+    }
+
+    let code = match target {
+        Target::Rust => rust::generate_with_config(context, &config(config_json)?),
+        Target::TypeScript => typescript::generate_with_config(context, &config(config_json)?),
+        Target::Kotlin => kotlin::generate_with_config(context, &config(config_json)?),
+        Target::Python => python::generate_with_config(context, &config(config_json)?),
+        Target::Java => java::generate_with_config(context, &config(config_json)?),
+        Target::Clojure => clojure::generate_with_config(context, &config(config_json)?),
+        Target::Haskell => haskell::generate_with_config(context, &config(config_json)?),
+    };
+    code.map_err(|e| e.to_string())
+}
+
+/// Convert PascalCase to snake_case.
+fn to_snake_case(s: &str) -> String {
+    let mut result = String::with_capacity(s.len() + 4);
+    for (i, c) in s.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                result.push('_');
+            }
+            result.push(c.to_ascii_lowercase());
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
 /// Generate visualization from a SketchDDD source.
 ///
-/// Supported formats: mermaid, graphviz (or dot)
-#[wasm_bindgen]
+/// Supported formats: mermaid, graphviz (or dot), html, excalidraw, drawio
+#[wasm_bindgen(unchecked_return_type = "VizResult")]
 pub fn generate_viz(source: &str, format: &str) -> JsValue {
+    generate_viz_with(source, format, &Theme::default())
+}
+
+/// Generate visualization from a SketchDDD source, styled by a theme.
+///
+/// The theme is JSON such as `{ "direction": "TB", "font": "Inter",
+/// "colors": { "aggregate-root": "#ffe6cc" }, "edges": { "containment":
+/// "bold" } }`, with the keys of the `[viz]` section of `sketchddd.toml`.
+#[wasm_bindgen(unchecked_return_type = "VizResult")]
+pub fn generate_viz_with_theme(source: &str, format: &str, theme_json: &str) -> JsValue {
+    match serde_json::from_str::<Theme>(theme_json) {
+        Ok(theme) => generate_viz_with(source, format, &theme),
+        Err(e) => viz_failure(format!("Invalid theme JSON: {}", e)),
+    }
+}
+
+/// Generate visualization with a theme.
+fn generate_viz_with(source: &str, format: &str, theme: &Theme) -> JsValue {
     let result: Result<VizResult, String> = (|| {
         // Parse and transform
         let ast = parse_file(source).map_err(|e| e.to_string())?;
         let transform_result = transform(&ast).map_err(|e| e.to_string())?;
 
+        let viz_format: sketchddd_viz::Format = format.parse().map_err(|_| {
+            format!(
+                "Unknown format: {}. Supported: mermaid, graphviz, html, excalidraw, drawio",
+                format
+            )
+        })?;
+        // Documents such as HTML pages hold a single context
+        if viz_format.is_document() && transform_result.contexts.len() > 1 {
+            return Err(format!(
+                "{} contexts cannot share one {} document",
+                transform_result.contexts.len(),
+                format
+            ));
+        }
+
         // Generate visualization for all contexts
         let mut all_output = String::new();
         for (i, context) in transform_result.contexts.iter().enumerate() {
             if i > 0 {
                 all_output.push_str("\n\n");
             }
-            let viz = match format.to_lowercase().as_str() {
-                "mermaid" | "md" => {
-                    sketchddd_viz::mermaid::generate(context).map_err(|e| e.to_string())?
-                }
-                "graphviz" | "dot" => {
-                    sketchddd_viz::graphviz::generate(context).map_err(|e| e.to_string())?
-                }
-                _ => {
-                    return Err(format!(
-                        "Unknown format: {}. Supported: mermaid, graphviz",
-                        format
-                    ))
-                }
-            };
+            let viz = sketchddd_viz::generate_with_theme(context, viz_format, theme)
+                .map_err(|e| e.to_string())?;
             all_output.push_str(&viz);
         }
 
@@ -612,17 +1021,184 @@ pub fn generate_viz(source: &str, format: &str) -> JsValue {
 
     match result {
         Ok(r) => serde_wasm_bindgen::to_value(&r).unwrap_or(JsValue::NULL),
-        Err(e) => {
-            let error_result = VizResult {
-                success: false,
-                output: None,
-                error: Some(e),
+        Err(e) => viz_failure(e),
+    }
+}
+
+/// Options of the diagram of a context
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct VizOptions {
+    /// Kind of diagram: class, er, state or board
+    diagram: Option<String>,
+    /// Styling of the diagram
+    theme: Theme,
+}
+
+/// Generate a diagram of a bounded context, as the builder edits it.
+///
+/// The context is JSON as `create_context` returns it. Besides the formats
+/// of `generate_viz`, `layout` gives the laid-out class diagram as JSON, as
+/// `layout_viz` does. The options are JSON such as `{ "diagram": "er",
+/// "theme": { "direction": "TB" } }`; the diagram is a class diagram and the
+/// theme the default one unless given.
+#[wasm_bindgen(unchecked_return_type = "VizResult")]
+pub fn generate_context_viz(context_json: &str, format: &str, options_json: &str) -> JsValue {
+    let result: Result<String, String> = (|| {
+        let context: BoundedContext = serde_json::from_str(context_json)
+            .map_err(|e| format!("Invalid context JSON: {}", e))?;
+        let options: VizOptions = serde_json::from_str(options_json)
+            .map_err(|e| format!("Invalid options JSON: {}", e))?;
+        let diagram: sketchddd_viz::Diagram = match &options.diagram {
+            Some(diagram) => diagram.parse().map_err(|_| {
+                format!(
+                    "Unknown diagram: {}. Supported: class, er, state, board",
+                    diagram
+                )
+            })?,
+            None => sketchddd_viz::Diagram::Class,
+        };
+
+        // Layouts are of class diagrams
+        if format == "layout" {
+            if diagram != sketchddd_viz::Diagram::Class {
+                return Err("only class diagrams are laid out".to_string());
+            }
+            return sketchddd_viz::layout::generate_with_theme(&context, &options.theme)
+                .map_err(|e| e.to_string());
+        }
+        let viz_format: sketchddd_viz::Format = format.parse().map_err(|_| {
+            format!(
+                "Unknown format: {}. Supported: mermaid, graphviz, html, excalidraw, drawio, layout",
+                format
+            )
+        })?;
+        sketchddd_viz::generate_diagram(&context, viz_format, diagram, &options.theme)
+            .map_err(|e| e.to_string())
+    })();
+
+    match result {
+        Ok(output) => {
+            let result = VizResult {
+                success: true,
+                output: Some(output),
+                error: None,
             };
-            serde_wasm_bindgen::to_value(&error_result).unwrap_or(JsValue::NULL)
+            serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
         }
+        Err(e) => viz_failure(e),
+    }
+}
+
+/// A visualization result holding the error that stopped it.
+fn viz_failure(message: String) -> JsValue {
+    let error_result = VizResult {
+        success: false,
+        output: None,
+        error: Some(message),
+    };
+    serde_wasm_bindgen::to_value(&error_result).unwrap_or(JsValue::NULL)
+}
+
+/// Lay out the class diagram of each context of a SketchDDD source, with
+/// the position and size of each object and the route of each morphism.
+#[wasm_bindgen(unchecked_return_type = "LayoutResult")]
+pub fn layout_viz(source: &str) -> JsValue {
+    layout_viz_with(source, &Theme::default())
+}
+
+/// Lay out the class diagram of each context of a SketchDDD source, in the
+/// direction of a theme given as JSON, as for `generate_viz_with_theme`.
+#[wasm_bindgen(unchecked_return_type = "LayoutResult")]
+pub fn layout_viz_with_theme(source: &str, theme_json: &str) -> JsValue {
+    match serde_json::from_str::<Theme>(theme_json) {
+        Ok(theme) => layout_viz_with(source, &theme),
+        Err(e) => layout_failure(format!("Invalid theme JSON: {}", e)),
     }
 }
 
+/// Lay out diagrams with a theme.
+fn layout_viz_with(source: &str, theme: &Theme) -> JsValue {
+    let result: Result<Vec<LayoutedDiagram>, String> = (|| {
+        let ast = parse_file(source).map_err(|e| e.to_string())?;
+        let transform_result = transform(&ast).map_err(|e| e.to_string())?;
+        Ok(transform_result
+            .contexts
+            .iter()
+            .map(|context| sketchddd_viz::layout::layout(context, theme))
+            .collect())
+    })();
+
+    match result {
+        Ok(diagrams) => {
+            let result = LayoutResult {
+                success: true,
+                diagrams: Some(diagrams),
+                error: None,
+            };
+            serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+        }
+        Err(e) => layout_failure(e),
+    }
+}
+
+/// A layout result holding the error that stopped it.
+fn layout_failure(message: String) -> JsValue {
+    let error_result = LayoutResult {
+        success: false,
+        diagrams: None,
+        error: Some(message),
+    };
+    serde_wasm_bindgen::to_value(&error_result).unwrap_or(JsValue::NULL)
+}
+
+/// Compare two versions of a model, for reviewing changes before saving
+/// them or when pulling someone else's.
+///
+/// Both models are JSON arrays of bounded contexts, as `validate` takes
+/// them. Contexts are matched by name.
+#[wasm_bindgen(unchecked_return_type = "DiffResult")]
+pub fn diff_models(old_json: &str, new_json: &str) -> JsValue {
+    let result: Result<DiffInfo, String> = (|| {
+        let old: Vec<BoundedContext> =
+            serde_json::from_str(old_json).map_err(|e| format!("Invalid old model JSON: {}", e))?;
+        let new: Vec<BoundedContext> =
+            serde_json::from_str(new_json).map_err(|e| format!("Invalid new model JSON: {}", e))?;
+
+        let diff = sketchddd_core::diff_models(&old, &new);
+        Ok(DiffInfo {
+            changes: diff
+                .changes
+                .iter()
+                .map(|change| ChangeInfo {
+                    context: change.context.clone(),
+                    kind: change.kind.to_string(),
+                    element: change.element.clone(),
+                    detail: change.detail.clone(),
+                    compatibility: change.compatibility.to_string(),
+                })
+                .collect(),
+            has_breaking_changes: diff.has_breaking_changes(),
+            max_severity: diff.max_severity().map(|c| c.to_string()),
+            version_warnings: diff.version_warnings,
+        })
+    })();
+
+    let result = match result {
+        Ok(diff) => DiffResult {
+            success: true,
+            diff: Some(diff),
+            error: None,
+        },
+        Err(e) => DiffResult {
+            success: false,
+            diff: None,
+            error: Some(e),
+        },
+    };
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
 /// Create a new bounded context.
 #[wasm_bindgen]
 pub fn create_context(name: &str) -> JsValue {
@@ -631,7 +1207,7 @@ pub fn create_context(name: &str) -> JsValue {
 }
 
 /// Get list of supported code generation targets.
-#[wasm_bindgen]
+#[wasm_bindgen(unchecked_return_type = "string[]")]
 pub fn supported_targets() -> JsValue {
     let targets = vec![
         "rust",
@@ -646,23 +1222,456 @@ pub fn supported_targets() -> JsValue {
 }
 
 /// Get list of supported visualization formats.
-#[wasm_bindgen]
+#[wasm_bindgen(unchecked_return_type = "string[]")]
 pub fn supported_viz_formats() -> JsValue {
-    let formats = vec!["mermaid", "graphviz"];
+    let formats = vec!["mermaid", "graphviz", "html", "excalidraw", "drawio"];
     serde_wasm_bindgen::to_value(&formats).unwrap_or(JsValue::NULL)
 }
 
-/// Format source code (pretty print).
+/// Complete the word before an offset of the source, counted in UTF-16
+/// code units as JavaScript strings are.
+#[wasm_bindgen(unchecked_return_type = "CompletionResult")]
+pub fn complete(source: &str, offset: usize) -> JsValue {
+    let completions = assist::complete(source, byte_offset(source, offset));
+    let result = CompletionResult {
+        items: completions
+            .items
+            .into_iter()
+            .map(|item| CompletionItem {
+                label: item.label,
+                kind: item.kind.as_str().to_string(),
+                detail: item.detail,
+            })
+            .collect(),
+        span: span_of(source, completions.start, completions.end),
+    };
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Describe the word at an offset of the source, counted in UTF-16 code
+/// units. Returns `null` if there is nothing to say.
+#[wasm_bindgen(unchecked_return_type = "HoverResult | null")]
+pub fn hover(source: &str, offset: usize) -> JsValue {
+    match assist::hover(source, byte_offset(source, offset)) {
+        Some(hover) => {
+            let result = HoverResult {
+                contents: hover.contents,
+                span: span_of(source, hover.start, hover.end),
+            };
+            serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+        }
+        None => JsValue::NULL,
+    }
+}
+
+/// Get the byte offset of an offset in UTF-16 code units.
+fn byte_offset(source: &str, offset: usize) -> usize {
+    let mut units = 0;
+    for (i, c) in source.char_indices() {
+        if units >= offset {
+            return i;
+        }
+        units += c.len_utf16();
+    }
+    source.len()
+}
+
+/// Format source code with the printer the CLI rewrites files with,
+/// keeping comments. Throws the diagnostic of the error if the source does
+/// not parse.
 #[wasm_bindgen]
-pub fn format_source(source: &str) -> JsValue {
-    let result: Result<String, String> = (|| {
-        let ast = parse_file(source).map_err(|e| e.to_string())?;
-        Ok(ast.pretty_print())
-    })();
+pub fn format_source(source: &str) -> Result<String, JsValue> {
+    let ast = parse_file(source).map_err(|e| thrown(parse_error(source, "E0100", &e)))?;
+    Ok(ast.pretty_print())
+}
 
-    match result {
-        Ok(formatted) => JsValue::from_str(&formatted),
-        Err(e) => JsValue::from_str(&format!("Error: {}", e)),
+/// Write a context edited visually as canonical source, so the builder can
+/// switch between the diagram and the code.
+///
+/// The context is JSON as `create_context` returns it. Throws a diagnostic
+/// if it is not a context.
+#[wasm_bindgen]
+pub fn to_source(context_json: &str) -> Result<String, JsValue> {
+    let context: BoundedContext = serde_json::from_str(context_json).map_err(|e| {
+        thrown(error_issue(
+            "PARSE_ERROR",
+            format!("Invalid context JSON: {}", e),
+        ))
+    })?;
+    let mut workspace = Workspace::new();
+    workspace.add_context(context);
+    Ok(unparse(&workspace).pretty_print())
+}
+
+// =============================================================
+// Model Session
+// =============================================================
+
+/// A model held in memory while the builder edits it.
+///
+/// Edits change the model in place instead of round-tripping it through
+/// JSON, and validation only re-checks the contexts edited since the last
+/// run, as diagrams and generated code are only made again for contexts
+/// edited since. All edits share one undo and redo history.
+#[wasm_bindgen]
+pub struct ModelSession {
+    session: ValidatedSession,
+    diagrams: Memo<(String, sketchddd_viz::Format), Result<String, String>>,
+    code: Memo<(String, Target), Result<String, String>>,
+}
+
+#[wasm_bindgen]
+impl ModelSession {
+    /// Start a session on a SketchDDD source. Throws the diagnostic of the
+    /// error if the source does not parse.
+    #[wasm_bindgen(constructor)]
+    pub fn new(source: &str) -> Result<ModelSession, JsValue> {
+        let ast = parse_file(source).map_err(|e| thrown(parse_error(source, "E0100", &e)))?;
+        let transform_result =
+            transform(&ast).map_err(|e| thrown(parse_error(source, "PARSE_ERROR", &e)))?;
+        Ok(ModelSession {
+            session: ValidatedSession::new(transform_result.into_workspace()),
+            diagrams: Memo::new(),
+            code: Memo::new(),
+        })
+    }
+
+    /// Add an entity to a context.
+    #[wasm_bindgen(js_name = addEntity, unchecked_return_type = "EditResult")]
+    pub fn add_entity(&mut self, context: &str, name: &str) -> JsValue {
+        self.edit(context, |_| {
+            Ok(EditCommand::AddEntity {
+                name: name.to_string(),
+            })
+        })
+    }
+
+    /// Add a value object to a context.
+    #[wasm_bindgen(js_name = addValueObject, unchecked_return_type = "EditResult")]
+    pub fn add_value_object(&mut self, context: &str, name: &str) -> JsValue {
+        self.edit(context, |_| {
+            Ok(EditCommand::AddValueObject {
+                name: name.to_string(),
+            })
+        })
+    }
+
+    /// Add a morphism between two objects of a context, given by name.
+    #[wasm_bindgen(js_name = addMorphism, unchecked_return_type = "EditResult")]
+    pub fn add_morphism(
+        &mut self,
+        context: &str,
+        name: &str,
+        source: &str,
+        target: &str,
+    ) -> JsValue {
+        self.edit(context, |ctx| {
+            let object = |name: &str| {
+                ctx.graph()
+                    .find_object_by_name(name)
+                    .map(|o| o.id)
+                    .ok_or_else(|| format!("'{}.{}' not found", ctx.name(), name))
+            };
+            Ok(EditCommand::AddMorphism {
+                name: name.to_string(),
+                source: object(source)?,
+                target: object(target)?,
+            })
+        })
+    }
+
+    /// Rename the object of a context with a name, or else its morphism
+    /// with that name. Context maps follow the rename.
+    #[wasm_bindgen(unchecked_return_type = "EditResult")]
+    pub fn rename(&mut self, context: &str, old_name: &str, new_name: &str) -> JsValue {
+        self.edit(context, |ctx| {
+            let graph = ctx.graph();
+            if let Some(object) = graph.find_object_by_name(old_name) {
+                return Ok(EditCommand::RenameObject {
+                    object: object.id,
+                    name: new_name.to_string(),
+                });
+            }
+            let morphisms: Vec<_> = graph
+                .morphisms()
+                .filter(|m| !m.is_identity && m.name == old_name)
+                .collect();
+            match morphisms[..] {
+                [morphism] => Ok(EditCommand::RenameMorphism {
+                    morphism: morphism.id,
+                    name: new_name.to_string(),
+                }),
+                _ => Err(format!("'{}.{}' not found", ctx.name(), old_name)),
+            }
+        })
+    }
+
+    /// Take back the most recent edit. Returns `false` if there is none.
+    pub fn undo(&mut self) -> bool {
+        self.session.undo()
+    }
+
+    /// Apply the most recently undone edit again. Returns `false` if there
+    /// is none.
+    pub fn redo(&mut self) -> bool {
+        self.session.redo()
+    }
+
+    /// Check if there is an edit to undo.
+    #[wasm_bindgen(js_name = canUndo)]
+    pub fn can_undo(&self) -> bool {
+        self.session.session().can_undo()
+    }
+
+    /// Check if there is an edit to redo.
+    #[wasm_bindgen(js_name = canRedo)]
+    pub fn can_redo(&self) -> bool {
+        self.session.session().can_redo()
+    }
+
+    /// Validate the model, re-checking only what was edited.
+    #[wasm_bindgen(unchecked_return_type = "ValidationResult")]
+    pub fn validate(&mut self) -> JsValue {
+        let validation = self.session.validate();
+        let issues: Vec<JsValidationIssue> = validation
+            .issues
+            .iter()
+            .map(|issue| validation_issue(None, issue))
+            .collect();
+        let result = JsValidationResult {
+            valid: validation.is_ok(),
+            error_count: validation.error_count(),
+            warning_count: validation.warning_count(),
+            issues,
+        };
+        serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+    }
+
+    /// Draw a context of the model, in a format `generate_viz` supports.
+    #[wasm_bindgen(unchecked_return_type = "VizResult")]
+    pub fn visualize(&mut self, context: &str, format: &str) -> JsValue {
+        let diagram = (|| {
+            let format: sketchddd_viz::Format = format.parse().map_err(|_| {
+                format!(
+                    "Unknown format: {}. Supported: mermaid, graphviz, html, excalidraw, drawio",
+                    format
+                )
+            })?;
+            let ctx = self
+                .session
+                .workspace()
+                .context(context)
+                .ok_or_else(|| EditError::UnknownContext(context.to_string()).to_string())?;
+            let key = (context.to_string(), format);
+            self.diagrams
+                .get_or_derive(key, Fingerprint::of(ctx), || {
+                    sketchddd_viz::generate(ctx, format).map_err(|e| e.to_string())
+                })
+                .clone()
+        })();
+        match diagram {
+            Ok(output) => {
+                let result = VizResult {
+                    success: true,
+                    output: Some(output),
+                    error: None,
+                };
+                serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+            }
+            Err(e) => viz_failure(e),
+        }
+    }
+
+    /// Generate the code of a context of the model, for a target
+    /// `generate_code` supports.
+    #[wasm_bindgen(js_name = generateCode, unchecked_return_type = "CodegenResult")]
+    pub fn generate_code(&mut self, context: &str, target: &str) -> JsValue {
+        let code = (|| {
+            let target: Target = target.parse().map_err(|_| {
+                format!(
+                    "Unknown target: {}. Supported: rust, typescript, kotlin, python, java, clojure, haskell",
+                    target
+                )
+            })?;
+            let ctx = self
+                .session
+                .workspace()
+                .context(context)
+                .ok_or_else(|| EditError::UnknownContext(context.to_string()).to_string())?;
+            let key = (context.to_string(), target);
+            self.code
+                .get_or_derive(key, Fingerprint::of(ctx), || {
+                    sketchddd_codegen::generate(ctx, target).map_err(|e| e.to_string())
+                })
+                .clone()
+        })();
+        let result = CodegenResult {
+            success: code.is_ok(),
+            error: code.as_ref().err().cloned(),
+            code: code.ok(),
+        };
+        serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+    }
+
+    /// Print the model as SketchDDD source.
+    #[wasm_bindgen(js_name = toSource)]
+    pub fn to_source(&self) -> String {
+        unparse(self.session.workspace()).pretty_print()
+    }
+}
+
+impl ModelSession {
+    /// Apply the command built from a context to it, marking what it
+    /// edited for validation.
+    fn edit(
+        &mut self,
+        context: &str,
+        command: impl FnOnce(&BoundedContext) -> Result<EditCommand, String>,
+    ) -> JsValue {
+        let command = self
+            .session
+            .workspace()
+            .context(context)
+            .ok_or_else(|| EditError::UnknownContext(context.to_string()).to_string())
+            .and_then(command);
+        let edited = command.and_then(|command| {
+            self.session
+                .apply(context, command)
+                .map_err(|e| e.to_string())
+        });
+        let result = EditResult {
+            success: edited.is_ok(),
+            error: edited.err(),
+        };
+        serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+    }
+}
+
+// =============================================================
+// Chunked Analysis
+// =============================================================
+
+/// A validation of source run in steps, so that a large model does not
+/// block the thread analysing it.
+///
+/// Each poll does one step: parsing, transforming, validating one context,
+/// and last the checks spanning the model. A worker polls until the
+/// analysis is done, yielding between polls to receive messages, and
+/// cancels an analysis that newer source supersedes.
+#[wasm_bindgen]
+pub struct Analysis {
+    source: String,
+    policy: SeverityPolicy,
+    stage: Stage,
+}
+
+/// How far an analysis has got.
+enum Stage {
+    Parse,
+    Transform(File),
+    Validate(Box<Validating>),
+    Done(JsValidationResult),
+    Cancelled,
+}
+
+/// A model being validated a context at a time.
+struct Validating {
+    ast: File,
+    warnings: Vec<TransformWarning>,
+    workspace: Workspace,
+    cache: ValidationCache,
+}
+
+#[wasm_bindgen]
+impl Analysis {
+    /// Start analysing source; nothing is done until it is polled.
+    #[wasm_bindgen(constructor)]
+    pub fn new(source: &str) -> Analysis {
+        Analysis {
+            source: source.to_string(),
+            policy: SeverityPolicy::new(),
+            stage: Stage::Parse,
+        }
+    }
+
+    /// Start analysing source with a severity policy, given as JSON as
+    /// `validate_source_with_policy` takes it. Throws a diagnostic if the
+    /// policy is not valid.
+    #[wasm_bindgen(js_name = withPolicy)]
+    pub fn with_policy(source: &str, policy_json: &str) -> Result<Analysis, JsValue> {
+        let policy = serde_json::from_str(policy_json).map_err(|e| {
+            thrown(error_issue(
+                "PARSE_ERROR",
+                format!("Invalid policy JSON: {}", e),
+            ))
+        })?;
+        Ok(Analysis {
+            policy,
+            ..Analysis::new(source)
+        })
+    }
+
+    /// Do the next step, returning whether the analysis is over.
+    pub fn poll(&mut self) -> bool {
+        let source = &self.source;
+        self.stage = match std::mem::replace(&mut self.stage, Stage::Cancelled) {
+            Stage::Parse => match parse_file(source) {
+                Ok(ast) => Stage::Transform(ast),
+                Err(e) => Stage::Done(failed_validation(parse_error(source, "E0100", &e))),
+            },
+            Stage::Transform(ast) => match transform(&ast) {
+                Ok(result) => Stage::Validate(Box::new(Validating {
+                    warnings: result.warnings.clone(),
+                    workspace: result.into_workspace(),
+                    ast,
+                    cache: ValidationCache::new(),
+                })),
+                Err(e) => Stage::Done(failed_validation(parse_error(source, "PARSE_ERROR", &e))),
+            },
+            Stage::Validate(mut validating) => {
+                let Validating {
+                    ast,
+                    warnings,
+                    workspace,
+                    cache,
+                } = &mut *validating;
+                if cache.validate_next(workspace).is_some() {
+                    Stage::Validate(validating)
+                } else {
+                    let validation = cache.validate(workspace).with_policy(&self.policy);
+                    Stage::Done(source_validation(source, ast, warnings, validation))
+                }
+            }
+            over => over,
+        };
+        self.is_done()
+    }
+
+    /// Stop the analysis; polling it does nothing after.
+    pub fn cancel(&mut self) {
+        self.stage = Stage::Cancelled;
+    }
+
+    /// Check if the analysis is over, because it finished or was cancelled.
+    #[wasm_bindgen(js_name = isDone)]
+    pub fn is_done(&self) -> bool {
+        matches!(self.stage, Stage::Done(_) | Stage::Cancelled)
+    }
+
+    /// Check if the analysis was cancelled.
+    #[wasm_bindgen(js_name = isCancelled)]
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self.stage, Stage::Cancelled)
+    }
+
+    /// Get the validation result, as `validate_source` returns it, or
+    /// `null` if the analysis is not done or was cancelled.
+    #[wasm_bindgen(unchecked_return_type = "ValidationResult | null")]
+    pub fn result(&self) -> JsValue {
+        match &self.stage {
+            Stage::Done(result) => serde_wasm_bindgen::to_value(result).unwrap_or(JsValue::NULL),
+            _ => JsValue::NULL,
+        }
     }
 }
 
@@ -709,6 +1718,47 @@ mod tests {
         assert!(!targets.is_null());
     }
 
+    #[wasm_bindgen_test]
+    fn test_diff_models() {
+        let old = serde_json::to_string(&vec![BoundedContext::new("Sales")]).unwrap();
+        let result = diff_models(&old, "[]");
+        assert!(!result.is_null());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_to_source() {
+        let context = serde_json::to_string(&BoundedContext::new("Sales")).unwrap();
+        let source = to_source(&context).unwrap();
+        assert!(source.contains("context Sales"));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_analysis_in_steps() {
+        let mut analysis = Analysis::new("context Sales { entity Order }");
+        while !analysis.poll() {}
+        assert!(!analysis.is_cancelled());
+        assert!(!analysis.result().is_null());
+
+        let mut analysis = Analysis::new("context Sales { entity Order }");
+        analysis.poll();
+        analysis.cancel();
+        assert!(analysis.poll());
+        assert!(analysis.result().is_null());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_session_diagrams_follow_edits() {
+        let mut session = ModelSession::new("context Sales { entity Order }").unwrap();
+        assert!(!session.visualize("Sales", "mermaid").is_null());
+        assert!(!session.generate_code("Sales", "rust").is_null());
+        assert_eq!(session.diagrams.len(), 1);
+
+        session.add_entity("Sales", "Customer");
+        session.visualize("Sales", "mermaid");
+        assert_eq!(session.diagrams.len(), 1);
+        assert_eq!(session.code.len(), 1);
+    }
+
     #[wasm_bindgen_test]
     fn test_supported_viz_formats() {
         let formats = supported_viz_formats();
@@ -0,0 +1,85 @@
+//! Synthetic models for the benchmarks.
+//!
+//! A generated model has the shape of a real one at any size: each context
+//! has entities with fields and morphisms to their neighbours, value
+//! objects, an enum and aggregates, and a context map links each context
+//! to the next. Models of the same size are always the same.
+
+use std::fmt::Write;
+
+/// Number of entities in an aggregate.
+const AGGREGATE_SIZE: usize = 4;
+
+/// The sizes benchmarked, as contexts by objects per context.
+pub const SIZES: [(usize, usize); 3] = [(1, 100), (10, 100), (10, 1000)];
+
+/// Get the source of a model of `contexts` contexts of `objects` objects
+/// each.
+pub fn model_source(contexts: usize, objects: usize) -> String {
+    let mut source = String::new();
+    for c in 0..contexts {
+        context_source(&mut source, c, objects);
+    }
+    for c in 1..contexts {
+        writeln!(
+            source,
+            "map Context{}To{}: Context{} -> Context{} {{",
+            c - 1,
+            c,
+            c - 1,
+            c
+        )
+        .unwrap();
+        source.push_str("  pattern: CustomerSupplier\n");
+        source.push_str("  mappings {\n    Entity0 -> Entity0\n  }\n}\n\n");
+    }
+    source
+}
+
+/// Append the source of a context. Every fourth object is a value object,
+/// the others entities.
+fn context_source(source: &mut String, context: usize, objects: usize) {
+    let entities: Vec<usize> = (0..objects).filter(|i| i % 4 != 3).collect();
+    let values: Vec<usize> = (0..objects).filter(|i| i % 4 == 3).collect();
+
+    writeln!(source, "context Context{} {{", context).unwrap();
+    for &i in &entities {
+        writeln!(source, "  entity Entity{} {{", i).unwrap();
+        source.push_str("    id: UUID\n    name: String\n    status: Status\n");
+        if let Some(value) = values.get(i % values.len().max(1)) {
+            writeln!(source, "    amount: Value{}", value).unwrap();
+        }
+        source.push_str("  }\n");
+    }
+    for &i in &values {
+        writeln!(source, "  value Value{} {{", i).unwrap();
+        source.push_str("    amount: Decimal\n    currency: String\n  }\n");
+    }
+    source.push_str("  enum Status = Draft | Active | Archived\n");
+
+    source.push_str("  morphisms {\n");
+    for (n, &i) in entities.iter().enumerate() {
+        if let Some(&next) = entities.get(n + 1) {
+            writeln!(source, "    next: Entity{} -> Entity{}", i, next).unwrap();
+        }
+        if let Some(&related) = entities
+            .get((n * 7 + 3) % entities.len())
+            .filter(|&&j| j != i)
+        {
+            writeln!(source, "    related: Entity{} -> Entity{}", i, related).unwrap();
+        }
+    }
+    source.push_str("  }\n");
+
+    for group in entities
+        .chunks(AGGREGATE_SIZE)
+        .filter(|group| group.len() > 1)
+    {
+        let members: Vec<String> = group[1..].iter().map(|i| format!("Entity{}", i)).collect();
+        writeln!(source, "  aggregate Aggregate{} {{", group[0]).unwrap();
+        writeln!(source, "    root: Entity{}", group[0]).unwrap();
+        writeln!(source, "    contains: [{}]", members.join(", ")).unwrap();
+        source.push_str("  }\n");
+    }
+    source.push_str("}\n\n");
+}
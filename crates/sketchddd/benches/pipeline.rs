@@ -0,0 +1,66 @@
+//! Benchmarks of each stage of the pipeline on synthetic models: parsing,
+//! transforming, validating, generating code and drawing diagrams.
+//!
+//! Run with `cargo bench -p sketchddd`. Throughput is reported in objects.
+
+mod fixtures;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use sketchddd::codegen::Target;
+use sketchddd::core::Workspace;
+use sketchddd::parser::{parse_file, transform};
+use sketchddd::viz::Format;
+
+/// Load a model from its source.
+fn load(source: &str) -> Workspace {
+    let ast = parse_file(source).expect("fixture parses");
+    transform(&ast)
+        .expect("fixture transforms")
+        .into_workspace()
+}
+
+fn bench_pipeline(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pipeline");
+    group.sample_size(10);
+    for (contexts, objects) in fixtures::SIZES {
+        let size = format!("{}x{}", contexts, objects);
+        let source = fixtures::model_source(contexts, objects);
+        let ast = parse_file(&source).expect("fixture parses");
+        let workspace = load(&source);
+        group.throughput(Throughput::Elements((contexts * objects) as u64));
+
+        group.bench_with_input(BenchmarkId::new("parse", &size), &source, |b, source| {
+            b.iter(|| parse_file(source).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("transform", &size), &ast, |b, ast| {
+            b.iter(|| transform(ast).unwrap())
+        });
+        group.bench_with_input(
+            BenchmarkId::new("validate", &size),
+            &workspace,
+            |b, workspace| b.iter(|| workspace.validate()),
+        );
+        for target in [Target::Rust, Target::TypeScript] {
+            let id = BenchmarkId::new(format!("codegen/{:?}", target).to_lowercase(), &size);
+            group.bench_with_input(id, &workspace, |b, workspace| {
+                b.iter(|| sketchddd::codegen::generate_workspace(workspace, target).unwrap())
+            });
+        }
+        for format in [Format::Mermaid, Format::Graphviz] {
+            let id = BenchmarkId::new(format!("viz/{:?}", format).to_lowercase(), &size);
+            group.bench_with_input(id, &workspace, |b, workspace| {
+                b.iter(|| {
+                    workspace
+                        .contexts
+                        .iter()
+                        .map(|context| sketchddd::viz::generate(context, format).unwrap())
+                        .collect::<Vec<_>>()
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_pipeline);
+criterion_main!(benches);
@@ -80,7 +80,8 @@ export function generateViz(source: string, format: VizFormat): VizResult {
 }
 
 /**
- * Format/pretty-print SketchDDD source
+ * Format/pretty-print SketchDDD source, keeping comments. Throws the
+ * diagnostic of the syntax error if the source does not parse.
  */
 export function formatSource(source: string): string {
   if (!wasmModule) throw new Error('WASM module not initialized. Call initWasm() first.');